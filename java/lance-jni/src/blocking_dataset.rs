@@ -974,7 +974,8 @@ fn inner_create_index<'local>(
         | IndexType::NGram
         | IndexType::ZoneMap
         | IndexType::BloomFilter
-        | IndexType::RTree => {
+        | IndexType::RTree
+        | IndexType::SparseVector => {
             // For scalar indices, create a scalar IndexParams
             let (index_type_str, params_opt) = get_scalar_index_params(env, params_jobj)?;
             let scalar_params = lance_index::scalar::ScalarIndexParams {
@@ -3085,6 +3086,10 @@ fn inner_cleanup_with_policy<'local>(
         error_if_tagged_old_versions,
         clean_referenced_branches,
         delete_rate_limit,
+        delete_concurrency: None,
+        keep_last_n_versions: None,
+        dry_run: false,
+        delete_foreign_files: false,
     };
 
     let stats = {
@@ -3204,6 +3209,7 @@ fn inner_describe_indices<'local>(
             has_name: has_name.as_deref(),
             must_support_fts,
             must_support_exact_equality,
+            must_support_sparse_search: false,
         })
     })?;
 
@@ -3381,6 +3387,7 @@ fn inner_get_zonemap_stats<'local>(
                     has_name: None,
                     must_support_fts: false,
                     must_support_exact_equality: false,
+                    must_support_sparse_search: false,
                 }))
                 .await
                 .map_err(Error::from)?;
@@ -453,6 +453,7 @@ impl FromJObjectWithEnv<CompactionMetrics> for JObject<'_> {
             fragments_added,
             files_removed,
             files_added,
+            ..Default::default()
         })
     }
 }
@@ -382,6 +382,8 @@ pub fn get_vector_index_params(
                         m,
                         ef_construction,
                         prefetch_distance,
+                        m_max: None,
+                        m_max0: None,
                     })
                 },
             )?;
@@ -430,6 +432,7 @@ pub fn get_vector_index_params(
                         max_iters,
                         kmeans_redos,
                         codebook,
+                        codebook_file: None,
                         sample_rate,
                     })
                 },
@@ -77,6 +77,7 @@ fn build_pq_params_from_java(
         max_iters,
         kmeans_redos,
         codebook: None,
+        codebook_file: None,
         sample_rate,
     })
 }
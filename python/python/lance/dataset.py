@@ -3846,6 +3846,12 @@ class LanceDataset(pa.dataset.Dataset):
                 Int, the number of edges per node in the graph.
             ef_construction
                 Int, the number of nodes to examine during the construction.
+            m_max
+                Int, the maximum number of neighbors kept per node on the levels
+                above 0. Defaults to ``m``.
+            m_max0
+                Int, the maximum number of neighbors kept per node on level 0.
+                Defaults to ``2 * m``.
 
         Examples
         --------
@@ -94,6 +94,7 @@ class CleanupStats:
     transaction_files_removed: int
     index_files_removed: int
     deletion_files_removed: int
+    failed_paths: List[str]
 
 class LanceFileWriter:
     def __init__(
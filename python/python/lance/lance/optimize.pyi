@@ -23,6 +23,8 @@ class CompactionMetrics:
     fragments_added: int
     files_removed: int
     files_added: int
+    peak_memory_bytes: int
+    temp_bytes: int
 
 class RewriteResult:
     read_version: int
@@ -241,10 +241,13 @@ fn writer_config_from_kwargs(
                     "num_edges" => build = build.num_edges(value as usize),
                     "ef_construction" => build = build.ef_construction(value as usize),
                     "max_level" => build = build.max_level(value as u16),
+                    "m_max" => build = build.m_max(value as usize),
+                    "m_max0" => build = build.m_max0(value as usize),
                     other => {
                         return Err(PyValueError::new_err(format!(
                             "unknown HNSW build param '{}' for index '{}'; \
-                             expected one of 'num_edges', 'ef_construction', 'max_level'",
+                             expected one of 'num_edges', 'ef_construction', 'max_level', \
+                             'm_max', 'm_max0'",
                             other, index_name
                         )));
                     }
@@ -1957,6 +1960,11 @@ impl Dataset {
             transaction_files_removed: cleanup_stats.transaction_files_removed,
             index_files_removed: cleanup_stats.index_files_removed,
             deletion_files_removed: cleanup_stats.deletion_files_removed,
+            failed_paths: cleanup_stats
+                .failed_paths
+                .iter()
+                .map(|path| path.to_string())
+                .collect(),
         })
     }
 
@@ -4339,6 +4347,14 @@ fn prepare_vector_index_params(
             hnsw_params.ef_construction = ef_c.extract()?;
         }
 
+        if let Some(m_max) = kwargs.get_item("m_max")? {
+            hnsw_params.m_max = m_max.extract()?;
+        }
+
+        if let Some(m_max0) = kwargs.get_item("m_max0")? {
+            hnsw_params.m_max0 = m_max0.extract()?;
+        }
+
         // Parse PQ/RQ params
         if let Some(n) = kwargs.get_item("num_bits")? {
             let num_bits: u8 = n.extract()?;
@@ -23,6 +23,7 @@ pub struct CleanupStats {
     pub transaction_files_removed: u64,
     pub index_files_removed: u64,
     pub deletion_files_removed: u64,
+    pub failed_paths: Vec<String>,
 }
 
 #[pymethods]
@@ -114,6 +114,13 @@ pub struct PyCompactionMetrics {
     /// number of fragments.
     #[pyo3(get)]
     pub files_added: usize,
+    /// int : The approximate peak memory, in bytes, held by decoded batches and
+    /// read buffers at any one time.
+    #[pyo3(get)]
+    pub peak_memory_bytes: u64,
+    /// int : The number of bytes written to temporary storage.
+    #[pyo3(get)]
+    pub temp_bytes: u64,
 }
 
 #[pymethods]
@@ -133,6 +140,8 @@ impl From<CompactionMetrics> for PyCompactionMetrics {
             fragments_added: metrics.fragments_added,
             files_removed: metrics.files_removed,
             files_added: metrics.files_added,
+            peak_memory_bytes: metrics.peak_memory_bytes,
+            temp_bytes: metrics.temp_bytes,
         }
     }
 }
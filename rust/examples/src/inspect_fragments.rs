@@ -0,0 +1,81 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Print the physical layout of the fragments of a dataset.
+//!
+//! run with `cargo run --example inspect_fragments -- <uri>`
+#![allow(clippy::print_stdout)]
+use clap::Parser;
+use lance::Dataset;
+
+#[derive(Parser, Debug)]
+#[command(version, about, long_about = None)]
+struct Args {
+    /// Dataset URI
+    uri: String,
+
+    /// Version of the dataset to inspect, the latest by default
+    #[arg(long)]
+    version: Option<u64>,
+
+    /// Read the metadata of every data file for row and page counts
+    #[arg(long, default_value = "false")]
+    pages: bool,
+
+    /// Print the layout as JSON
+    #[arg(long, default_value = "false")]
+    json: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    let mut dataset = Dataset::open(&args.uri).await?;
+    if let Some(version) = args.version {
+        dataset = dataset.checkout_version(version).await?;
+    }
+    let layouts = dataset.describe_fragments(args.pages).await?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&layouts)?);
+        return Ok(());
+    }
+
+    println!(
+        "{} version {}: {} fragments",
+        args.uri,
+        dataset.version().version,
+        layouts.len()
+    );
+    for layout in &layouts {
+        let physical_rows = layout
+            .physical_rows
+            .map_or_else(|| "?".to_string(), |rows| rows.to_string());
+        println!("fragment {} ({} rows)", layout.id, physical_rows);
+        for file in &layout.files {
+            let size = file
+                .size_bytes
+                .map_or_else(|| "?".to_string(), |size| size.to_string());
+            println!(
+                "  {} (v{}, {} bytes)",
+                file.path, file.data_storage_version, size
+            );
+            for column in &file.columns {
+                let name = column.field_path.as_deref().unwrap_or("<dropped>");
+                match column.num_pages {
+                    Some(pages) => {
+                        println!("    {} (field {}): {} pages", name, column.field_id, pages)
+                    }
+                    None => println!("    {} (field {})", name, column.field_id),
+                }
+            }
+        }
+        if let Some(deletion_file) = &layout.deletion_file {
+            let deleted = deletion_file
+                .num_deleted_rows
+                .map_or_else(|| "?".to_string(), |rows| rows.to_string());
+            println!("  {} ({} deleted rows)", deletion_file.path, deleted);
+        }
+    }
+    Ok(())
+}
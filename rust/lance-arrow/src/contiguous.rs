@@ -0,0 +1,246 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Compact vector columns into contiguous, aligned buffers
+//!
+//! Consumers that hand vector data to a device (e.g. a GPU copy) want the values of
+//! a `FixedSizeList` column to be a single buffer that starts at the first value of
+//! the first row and is aligned to [`VECTOR_BUFFER_ALIGNMENT`].  Slicing, filtering
+//! and concatenating batches can all leave the values as a window into a larger
+//! allocation, so this module checks for that and copies when needed.
+
+use std::ptr::NonNull;
+use std::sync::Arc;
+
+use arrow_array::{Array, ArrayRef, FixedSizeListArray, RecordBatch, cast::AsArray, make_array};
+use arrow_buffer::Buffer;
+use arrow_data::ArrayDataBuilder;
+use arrow_schema::{ArrowError, DataType};
+
+use crate::Result;
+
+/// The alignment, in bytes, of the values buffer of a contiguous vector column
+pub const VECTOR_BUFFER_ALIGNMENT: usize = 64;
+
+#[repr(C, align(64))]
+#[derive(Clone, Copy)]
+struct AlignedChunk([u8; VECTOR_BUFFER_ALIGNMENT]);
+
+/// Copy `bytes` into a new buffer aligned to [`VECTOR_BUFFER_ALIGNMENT`]
+///
+/// The alignment of buffers allocated by arrow depends on the target, some targets
+/// only align to 32 bytes, so the allocation is made here.
+fn copy_aligned(bytes: &[u8]) -> Buffer {
+    let num_chunks = bytes.len().div_ceil(VECTOR_BUFFER_ALIGNMENT).max(1);
+    let mut chunks = vec![AlignedChunk([0; VECTOR_BUFFER_ALIGNMENT]); num_chunks];
+    // SAFETY: the chunks are plain bytes and at least `bytes.len()` long
+    let dest =
+        unsafe { std::slice::from_raw_parts_mut(chunks.as_mut_ptr() as *mut u8, bytes.len()) };
+    dest.copy_from_slice(bytes);
+    let ptr = NonNull::new(chunks.as_mut_ptr() as *mut u8).expect("should be a valid pointer");
+    // SAFETY: the pointer stays valid for `bytes.len()` bytes while `chunks` is alive,
+    // and moving the vec does not move its allocation
+    unsafe { Buffer::from_custom_allocation(ptr, bytes.len(), Arc::new(chunks)) }
+}
+
+/// The width of the values of `array`, if it is a vector column this module handles
+fn value_width(array: &FixedSizeListArray) -> Option<usize> {
+    array.value_type().primitive_width()
+}
+
+/// Whether the values of `array` are a single buffer with no offset, holding exactly
+/// the values of the array and aligned to [`VECTOR_BUFFER_ALIGNMENT`]
+///
+/// Always false if the values are not of a fixed-width primitive type.
+pub fn is_contiguous(array: &FixedSizeListArray) -> bool {
+    let Some(width) = value_width(array) else {
+        return false;
+    };
+    let values = array.values().to_data();
+    let [buffer] = values.buffers() else {
+        return false;
+    };
+    values.offset() == 0
+        && buffer.ptr_offset() == 0
+        && buffer.len() == array.len() * array.value_length() as usize * width
+        && buffer.as_ptr().align_offset(VECTOR_BUFFER_ALIGNMENT) == 0
+}
+
+/// Make the values of `array` contiguous, see [`is_contiguous`]
+///
+/// The values are copied only if they are not contiguous already.  The validity of
+/// the lists and of the values is kept as is.  Errors if the values are not of a
+/// fixed-width primitive type.
+pub fn make_contiguous(array: &FixedSizeListArray) -> Result<FixedSizeListArray> {
+    let Some(width) = value_width(array) else {
+        return Err(ArrowError::InvalidArgumentError(format!(
+            "Cannot make a vector column with values of type {} contiguous",
+            array.value_type()
+        )));
+    };
+    if is_contiguous(array) {
+        return Ok(array.clone());
+    }
+
+    let values = array.values().to_data();
+    let num_values = array.len() * array.value_length() as usize;
+    let start = values.offset() * width;
+    let bytes = &values.buffers()[0].as_slice()[start..start + num_values * width];
+    let new_values = ArrayDataBuilder::new(values.data_type().clone())
+        .len(num_values)
+        .nulls(values.nulls().cloned())
+        .add_buffer(copy_aligned(bytes))
+        .build()?;
+
+    let DataType::FixedSizeList(field, size) = array.data_type() else {
+        unreachable!()
+    };
+    FixedSizeListArray::try_new(
+        field.clone(),
+        *size,
+        make_array(new_values),
+        array.nulls().cloned(),
+    )
+}
+
+/// Make every top-level `FixedSizeList` column of `batch` with fixed-width primitive
+/// values contiguous, see [`make_contiguous`]
+///
+/// Other columns, including vectors nested in structs or lists, are left alone.
+pub fn make_vectors_contiguous(batch: &RecordBatch) -> Result<RecordBatch> {
+    let mut changed = false;
+    let columns = batch
+        .columns()
+        .iter()
+        .map(|column| match column.as_fixed_size_list_opt() {
+            Some(array) if value_width(array).is_some() && !is_contiguous(array) => {
+                changed = true;
+                Ok(Arc::new(make_contiguous(array)?) as ArrayRef)
+            }
+            _ => Ok(column.clone()),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if !changed {
+        return Ok(batch.clone());
+    }
+    RecordBatch::try_new(batch.schema(), columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::{Float32Array, Int32Array, types::Float32Type};
+    use arrow_schema::{Field, Schema};
+    use arrow_select::{concat::concat_batches, filter::filter_record_batch};
+
+    use super::*;
+    use crate::FixedSizeListArrayExt;
+
+    fn assert_contiguous(array: &FixedSizeListArray) {
+        assert!(is_contiguous(array));
+        let values = array.values().to_data();
+        assert_eq!(values.buffers().len(), 1);
+        assert_eq!(values.offset(), 0);
+        assert_eq!(values.buffers()[0].ptr_offset(), 0);
+        assert_eq!(
+            values.buffers()[0].as_ptr() as usize % VECTOR_BUFFER_ALIGNMENT,
+            0
+        );
+    }
+
+    fn vectors(num_rows: usize, dim: i32) -> FixedSizeListArray {
+        let values = Float32Array::from_iter_values((0..num_rows * dim as usize).map(|v| v as f32));
+        FixedSizeListArray::try_new_from_values(values, dim).unwrap()
+    }
+
+    #[test]
+    fn test_slice_made_contiguous() {
+        let array = vectors(100, 8);
+        let sliced = array.slice(3, 10);
+        assert!(!is_contiguous(&sliced));
+
+        let compacted = make_contiguous(&sliced).unwrap();
+        assert_contiguous(&compacted);
+        assert_eq!(compacted, sliced);
+        assert_eq!(compacted.values().len(), 80);
+
+        // Already contiguous arrays are not copied
+        let again = make_contiguous(&compacted).unwrap();
+        assert_eq!(
+            again.values().to_data().buffers()[0].as_ptr(),
+            compacted.values().to_data().buffers()[0].as_ptr()
+        );
+    }
+
+    #[test]
+    fn test_nulls_kept() {
+        let array = FixedSizeListArray::from_iter_primitive::<Float32Type, _, _>(
+            (0..20).map(|i| {
+                if i % 3 == 0 {
+                    None
+                } else {
+                    Some(vec![Some(i as f32), None, Some(-(i as f32))])
+                }
+            }),
+            3,
+        );
+        let sliced = array.slice(5, 11);
+        let compacted = make_contiguous(&sliced).unwrap();
+        assert_contiguous(&compacted);
+        assert_eq!(compacted, sliced);
+        assert_eq!(compacted.null_count(), sliced.null_count());
+        assert_eq!(
+            compacted.values().null_count(),
+            sliced.values().null_count()
+        );
+    }
+
+    #[test]
+    fn test_batch_after_filter_and_concat() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new(
+                "vec",
+                DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float32, true)), 4),
+                false,
+            ),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from_iter_values(0..50)),
+                Arc::new(vectors(50, 4)),
+            ],
+        )
+        .unwrap();
+
+        let predicate = arrow_array::BooleanArray::from_iter((0..50).map(|i| Some(i % 2 == 0)));
+        let filtered = filter_record_batch(&batch.slice(10, 30), &predicate.slice(0, 30)).unwrap();
+        let concatenated =
+            concat_batches(&schema, [&batch.slice(1, 5), &batch.slice(40, 7)]).unwrap();
+
+        for input in [batch.slice(7, 13), filtered, concatenated] {
+            let output = make_vectors_contiguous(&input).unwrap();
+            assert_eq!(output, input);
+            assert_contiguous(output.column(1).as_fixed_size_list());
+            // Non-vector columns are passed through
+            assert_eq!(
+                output.column(0).to_data().buffers()[0].as_ptr(),
+                input.column(0).to_data().buffers()[0].as_ptr()
+            );
+        }
+    }
+
+    #[test]
+    fn test_empty_and_unsupported() {
+        let empty = vectors(10, 4).slice(10, 0);
+        assert_contiguous(&make_contiguous(&empty).unwrap());
+
+        let strings = FixedSizeListArray::try_new_from_values(
+            arrow_array::StringArray::from(vec!["a", "b"]),
+            2,
+        )
+        .unwrap();
+        assert!(!is_contiguous(&strings));
+        assert!(make_contiguous(&strings).is_err());
+    }
+}
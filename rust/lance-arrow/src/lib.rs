@@ -22,6 +22,7 @@ use arrow_schema::{ArrowError, DataType, Field, Fields, IntervalUnit, Schema, So
 use arrow_select::{interleave::interleave, take::take};
 use rand::prelude::*;
 
+pub mod contiguous;
 pub mod deepcopy;
 pub mod schema;
 pub use schema::*;
@@ -35,6 +36,7 @@ pub mod json;
 pub mod list;
 pub mod memory;
 pub mod scalar;
+pub mod sparse;
 pub mod stream;
 pub mod r#struct;
 
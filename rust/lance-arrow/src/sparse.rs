@@ -0,0 +1,266 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Sparse vector support for Apache Arrow.
+//!
+//! A sparse vector, such as the output of a SPLADE model, is stored as the
+//! dimensions that have a value and those values:
+//! `Struct<indices: List<Int32>, values: List<Float32>>`, where both lists of
+//! a row have the same length.  The field is tagged with the
+//! [`SPARSE_VECTOR_EXT_NAME`] extension name.
+
+use std::sync::Arc;
+
+use arrow_array::builder::{Float32Builder, Int32Builder, ListBuilder};
+use arrow_array::cast::AsArray;
+use arrow_array::types::{Float32Type, Int32Type};
+use arrow_array::{Array, ArrayRef, ListArray, StructArray};
+use arrow_buffer::NullBuffer;
+use arrow_schema::{ArrowError, DataType, Field as ArrowField, Fields};
+
+use crate::ARROW_EXT_NAME_KEY;
+
+/// Arrow extension type name for sparse vectors
+pub const SPARSE_VECTOR_EXT_NAME: &str = "lance.sparse_vector";
+
+/// Name of the child field holding the dimensions of a sparse vector
+pub const SPARSE_INDICES_FIELD: &str = "indices";
+
+/// Name of the child field holding the values of a sparse vector
+pub const SPARSE_VALUES_FIELD: &str = "values";
+
+fn sparse_vector_fields() -> Fields {
+    Fields::from(vec![
+        ArrowField::new(
+            SPARSE_INDICES_FIELD,
+            DataType::List(Arc::new(ArrowField::new("item", DataType::Int32, true))),
+            true,
+        ),
+        ArrowField::new(
+            SPARSE_VALUES_FIELD,
+            DataType::List(Arc::new(ArrowField::new("item", DataType::Float32, true))),
+            true,
+        ),
+    ])
+}
+
+/// The storage type of sparse vectors
+pub fn sparse_vector_data_type() -> DataType {
+    DataType::Struct(sparse_vector_fields())
+}
+
+/// Check if a data type can store sparse vectors
+///
+/// The names of the list items and the nullability of the children are ignored.
+pub fn is_sparse_vector_data_type(data_type: &DataType) -> bool {
+    let DataType::Struct(fields) = data_type else {
+        return false;
+    };
+    let list_of = |field: &ArrowField, name: &str, item: &DataType| {
+        field.name() == name
+            && matches!(field.data_type(), DataType::List(inner) if inner.data_type() == item)
+    };
+    fields.len() == 2
+        && list_of(&fields[0], SPARSE_INDICES_FIELD, &DataType::Int32)
+        && list_of(&fields[1], SPARSE_VALUES_FIELD, &DataType::Float32)
+}
+
+/// Check if a field is a sparse vector extension field
+pub fn is_sparse_vector_field(field: &ArrowField) -> bool {
+    is_sparse_vector_data_type(field.data_type())
+        && field
+            .metadata()
+            .get(ARROW_EXT_NAME_KEY)
+            .map(|name| name == SPARSE_VECTOR_EXT_NAME)
+            .unwrap_or_default()
+}
+
+/// Create a sparse vector field with the appropriate extension metadata
+pub fn sparse_vector_field(name: &str, nullable: bool) -> ArrowField {
+    let mut field = ArrowField::new(name, sparse_vector_data_type(), nullable);
+    let mut metadata = std::collections::HashMap::new();
+    metadata.insert(
+        ARROW_EXT_NAME_KEY.to_string(),
+        SPARSE_VECTOR_EXT_NAME.to_string(),
+    );
+    field.set_metadata(metadata);
+    field
+}
+
+/// A specialized array for sparse vectors
+#[derive(Debug, Clone)]
+pub struct SparseVectorArray {
+    inner: StructArray,
+}
+
+impl SparseVectorArray {
+    /// Wrap a struct array of sparse vectors
+    ///
+    /// Returns an error if the array has the wrong type or if the two lists of
+    /// a row have different lengths.
+    pub fn try_new(inner: StructArray) -> Result<Self, ArrowError> {
+        if !is_sparse_vector_data_type(inner.data_type()) {
+            return Err(ArrowError::InvalidArgumentError(format!(
+                "Expected a sparse vector array of type {}, got {}",
+                sparse_vector_data_type(),
+                inner.data_type()
+            )));
+        }
+        let array = Self { inner };
+        let (indices, values) = (array.indices(), array.values());
+        for i in 0..array.len() {
+            if array.is_valid(i) && indices.value_length(i) != values.value_length(i) {
+                return Err(ArrowError::InvalidArgumentError(format!(
+                    "Sparse vector {} has {} indices but {} values",
+                    i,
+                    indices.value_length(i),
+                    values.value_length(i)
+                )));
+            }
+        }
+        Ok(array)
+    }
+
+    /// Create a sparse vector array from `(indices, values)` pairs
+    pub fn try_from_iter<I>(iter: I) -> Result<Self, ArrowError>
+    where
+        I: IntoIterator<Item = Option<(Vec<i32>, Vec<f32>)>>,
+    {
+        let mut indices = ListBuilder::new(Int32Builder::new());
+        let mut values = ListBuilder::new(Float32Builder::new());
+        let mut validity = Vec::new();
+        for vector in iter {
+            match vector {
+                Some((vector_indices, vector_values)) => {
+                    indices.append_value(vector_indices.into_iter().map(Some));
+                    values.append_value(vector_values.into_iter().map(Some));
+                    validity.push(true);
+                }
+                None => {
+                    indices.append_null();
+                    values.append_null();
+                    validity.push(false);
+                }
+            }
+        }
+        let nulls = NullBuffer::from(validity);
+        let inner = StructArray::try_new(
+            sparse_vector_fields(),
+            vec![
+                Arc::new(indices.finish()) as ArrayRef,
+                Arc::new(values.finish()) as ArrayRef,
+            ],
+            (nulls.null_count() > 0).then_some(nulls),
+        )?;
+        Self::try_new(inner)
+    }
+
+    fn indices(&self) -> &ListArray {
+        self.inner.column(0).as_list::<i32>()
+    }
+
+    fn values(&self) -> &ListArray {
+        self.inner.column(1).as_list::<i32>()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Whether the vector at `i` is set, a vector with no entries is valid
+    pub fn is_valid(&self, i: usize) -> bool {
+        self.inner.is_valid(i) && self.indices().is_valid(i) && self.values().is_valid(i)
+    }
+
+    /// The dimensions and values of the vector at `i`, or `None` if it is null
+    pub fn value(&self, i: usize) -> Option<(&[i32], &[f32])> {
+        if !self.is_valid(i) {
+            return None;
+        }
+        let (indices, values) = (self.indices(), self.values());
+        let indices_range =
+            indices.value_offsets()[i] as usize..indices.value_offsets()[i + 1] as usize;
+        let values_range =
+            values.value_offsets()[i] as usize..values.value_offsets()[i + 1] as usize;
+        Some((
+            &indices.values().as_primitive::<Int32Type>().values()[indices_range],
+            &values.values().as_primitive::<Float32Type>().values()[values_range],
+        ))
+    }
+
+    /// Iterate over the vectors, `None` for null ones
+    pub fn iter(&self) -> impl Iterator<Item = Option<(&[i32], &[f32])>> + '_ {
+        (0..self.len()).map(|i| self.value(i))
+    }
+
+    pub fn into_inner(self) -> StructArray {
+        self.inner
+    }
+}
+
+impl TryFrom<&dyn Array> for SparseVectorArray {
+    type Error = ArrowError;
+
+    fn try_from(array: &dyn Array) -> Result<Self, ArrowError> {
+        let inner = array.as_struct_opt().ok_or_else(|| {
+            ArrowError::InvalidArgumentError(format!(
+                "Expected a sparse vector array, got {}",
+                array.data_type()
+            ))
+        })?;
+        Self::try_new(inner.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparse_vector_array() {
+        let array = SparseVectorArray::try_from_iter(vec![
+            Some((vec![3, 17], vec![0.5, 1.5])),
+            None,
+            Some((vec![], vec![])),
+        ])
+        .unwrap();
+        assert_eq!(array.len(), 3);
+        assert_eq!(array.value(0), Some((&[3, 17][..], &[0.5, 1.5][..])));
+        assert_eq!(array.value(1), None);
+        assert_eq!(array.value(2), Some((&[][..], &[][..])));
+
+        // Slices keep their own offsets
+        let sliced = array.clone().into_inner().slice(2, 1);
+        let sliced = SparseVectorArray::try_from(&sliced as &dyn Array).unwrap();
+        assert_eq!(
+            sliced.iter().collect::<Vec<_>>(),
+            vec![Some((&[][..], &[][..]))]
+        );
+
+        let field = sparse_vector_field("embedding", true);
+        assert!(is_sparse_vector_field(&field));
+        assert_eq!(field.data_type(), array.into_inner().data_type());
+        let untagged = ArrowField::new("embedding", sparse_vector_data_type(), true);
+        assert!(!is_sparse_vector_field(&untagged));
+    }
+
+    #[test]
+    fn test_sparse_vector_array_validation() {
+        let err = SparseVectorArray::try_from_iter(vec![Some((vec![1, 2], vec![1.0]))]);
+        assert!(
+            err.unwrap_err()
+                .to_string()
+                .contains("2 indices but 1 values")
+        );
+
+        let other = StructArray::from(vec![(
+            Arc::new(ArrowField::new("indices", DataType::Int32, false)),
+            Arc::new(arrow_array::Int32Array::from(vec![1])) as ArrayRef,
+        )]);
+        assert!(SparseVectorArray::try_new(other).is_err());
+    }
+}
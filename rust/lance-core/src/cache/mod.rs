@@ -62,6 +62,7 @@ use std::sync::{
 use futures::{Future, FutureExt};
 
 use crate::Result;
+use crate::utils::metrics::{self, Counter};
 
 pub use deepsize::{Context, DeepSizeOf};
 
@@ -141,6 +142,34 @@ fn cache_entry_size<T: DeepSizeOf + ?Sized>(value: &T) -> usize {
     value.deep_size_of() + std::mem::size_of::<std::sync::atomic::AtomicUsize>() * 2
 }
 
+/// Hit and miss counters exported through [`crate::utils::metrics`].
+///
+/// Unregistered, and therefore free, unless the cache was named with
+/// [`LanceCache::with_metrics`].
+#[derive(Clone, Debug, Default)]
+struct CacheMetrics {
+    hits: Counter,
+    misses: Counter,
+}
+
+impl CacheMetrics {
+    fn new(cache: &str) -> Self {
+        let labels = [("cache", cache)];
+        Self {
+            hits: metrics::counter(
+                "lance_cache_hits_total",
+                "Lookups that found an entry in the cache.",
+                &labels,
+            ),
+            misses: metrics::counter(
+                "lance_cache_misses_total",
+                "Lookups that did not find an entry in the cache.",
+                &labels,
+            ),
+        }
+    }
+}
+
 /// Build an [`InternalCacheKey`] from a cache's prefix, a user key string,
 /// and a type name.
 fn build_key(prefix: &Arc<str>, key: &str, type_name: &'static str) -> InternalCacheKey {
@@ -161,6 +190,7 @@ pub struct LanceCache {
     prefix: Arc<str>,
     hits: Arc<AtomicU64>,
     misses: Arc<AtomicU64>,
+    metrics: CacheMetrics,
 }
 
 impl std::fmt::Debug for LanceCache {
@@ -184,6 +214,7 @@ impl LanceCache {
             prefix: Arc::from(""),
             hits: Arc::new(AtomicU64::new(0)),
             misses: Arc::new(AtomicU64::new(0)),
+            metrics: CacheMetrics::default(),
         }
     }
 
@@ -194,6 +225,7 @@ impl LanceCache {
             prefix: Arc::from(""),
             hits: Arc::new(AtomicU64::new(0)),
             misses: Arc::new(AtomicU64::new(0)),
+            metrics: CacheMetrics::default(),
         }
     }
 
@@ -203,6 +235,7 @@ impl LanceCache {
             prefix: Arc::from(""),
             hits: Arc::new(AtomicU64::new(0)),
             misses: Arc::new(AtomicU64::new(0)),
+            metrics: CacheMetrics::default(),
         }
     }
 
@@ -214,9 +247,17 @@ impl LanceCache {
             prefix: Arc::from(prefix),
             hits: Arc::new(AtomicU64::new(0)),
             misses: Arc::new(AtomicU64::new(0)),
+            metrics: CacheMetrics::default(),
         }
     }
 
+    /// Export the hits and misses of this cache, and of caches derived from it
+    /// with [`Self::with_key_prefix`], as metrics labeled with `cache`.
+    pub fn with_metrics(mut self, cache: &str) -> Self {
+        self.metrics = CacheMetrics::new(cache);
+        self
+    }
+
     /// Appends a prefix to the cache key.
     pub fn with_key_prefix(&self, prefix: &str) -> Self {
         Self {
@@ -224,6 +265,7 @@ impl LanceCache {
             prefix: Arc::from(format!("{}{}/", self.prefix, prefix)),
             hits: self.hits.clone(),
             misses: self.misses.clone(),
+            metrics: self.metrics.clone(),
         }
     }
 
@@ -269,25 +311,35 @@ impl LanceCache {
         if let Some(entry) = self.cache.get(&cache_key, codec).await {
             match entry.downcast::<T>() {
                 Ok(val) => {
-                    self.hits.fetch_add(1, Ordering::Relaxed);
+                    self.record_hit();
                     Some(val)
                 }
                 Err(_) => {
                     // Type mismatch: the backend returned a different concrete
                     // type than expected (e.g. a disk cache may store
                     // intermediate state). Treat as a miss.
-                    self.misses.fetch_add(1, Ordering::Relaxed);
+                    self.record_miss();
                     None
                 }
             }
         } else {
-            self.misses.fetch_add(1, Ordering::Relaxed);
+            self.record_miss();
             None
         }
     }
 
     // -- Stats / clear --------------------------------------------------------
 
+    fn record_hit(&self) {
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        self.metrics.hits.inc();
+    }
+
+    fn record_miss(&self) {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        self.metrics.misses.inc();
+    }
+
     pub async fn stats(&self) -> CacheStats {
         CacheStats {
             hits: self.hits.load(Ordering::Relaxed),
@@ -351,9 +403,9 @@ impl LanceCache {
             .await?;
 
         if was_cached {
-            self.hits.fetch_add(1, Ordering::Relaxed);
+            self.record_hit();
         } else {
-            self.misses.fetch_add(1, Ordering::Relaxed);
+            self.record_miss();
         }
 
         Ok(entry.downcast::<K::ValueType>().unwrap())
@@ -394,6 +446,7 @@ pub struct WeakLanceCache {
     prefix: Arc<str>,
     hits: Arc<AtomicU64>,
     misses: Arc<AtomicU64>,
+    metrics: CacheMetrics,
 }
 
 impl WeakLanceCache {
@@ -403,6 +456,7 @@ impl WeakLanceCache {
             prefix: cache.prefix.clone(),
             hits: cache.hits.clone(),
             misses: cache.misses.clone(),
+            metrics: cache.metrics.clone(),
         }
     }
 
@@ -412,6 +466,7 @@ impl WeakLanceCache {
             prefix: Arc::from(format!("{}{}/", self.prefix, prefix)),
             hits: self.hits.clone(),
             misses: self.misses.clone(),
+            metrics: self.metrics.clone(),
         }
     }
 
@@ -420,6 +475,16 @@ impl WeakLanceCache {
         &self.prefix
     }
 
+    fn record_hit(&self) {
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        self.metrics.hits.inc();
+    }
+
+    fn record_miss(&self) {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        self.metrics.misses.inc();
+    }
+
     pub async fn get_with_key<K>(&self, cache_key: &K) -> Option<Arc<K::ValueType>>
     where
         K: CacheKey,
@@ -428,10 +493,10 @@ impl WeakLanceCache {
         let cache = self.inner.upgrade()?;
         let key = build_key(&self.prefix, &cache_key.key(), K::type_name());
         if let Some(entry) = cache.get(&key, K::codec()).await {
-            self.hits.fetch_add(1, Ordering::Relaxed);
+            self.record_hit();
             Some(entry.downcast::<K::ValueType>().unwrap())
         } else {
-            self.misses.fetch_add(1, Ordering::Relaxed);
+            self.record_miss();
             None
         }
     }
@@ -476,9 +541,9 @@ impl WeakLanceCache {
             });
             let (entry, was_cached) = cache.get_or_insert(&key, typed_loader, K::codec()).await?;
             if was_cached {
-                self.hits.fetch_add(1, Ordering::Relaxed);
+                self.record_hit();
             } else {
-                self.misses.fetch_add(1, Ordering::Relaxed);
+                self.record_miss();
             }
             Ok(entry.downcast::<K::ValueType>().unwrap())
         } else {
@@ -13,14 +13,16 @@ use deepsize::DeepSizeOf;
 use lance_arrow::bfloat16::{BFLOAT16_EXT_NAME, is_bfloat16_field};
 use lance_arrow::{ARROW_EXT_META_KEY, ARROW_EXT_NAME_KEY};
 
+mod diff;
 mod field;
 mod schema;
 
 use crate::{Error, Result};
+pub use diff::{SchemaDiff, SchemaDifference};
 pub use field::{
     BlobVersion, Encoding, Field, LANCE_UNENFORCED_CLUSTERING_KEY_POSITION,
     LANCE_UNENFORCED_PRIMARY_KEY, LANCE_UNENFORCED_PRIMARY_KEY_POSITION, NullabilityComparison,
-    OnTypeMismatch, SchemaCompareOptions,
+    OnMetadataConflict, OnTypeMismatch, SchemaCompareOptions,
 };
 pub use schema::{
     BlobHandling, FieldRef, OnMissing, Projectable, Projection, Schema,
@@ -380,15 +382,13 @@ impl TryFrom<&LogicalType> for DataType {
                     }
                 }
                 "timestamp" => {
-                    if splits.len() != 3 {
+                    if splits.len() < 3 {
                         Err(Error::schema(format!("Unsupported timestamp type: {}", lt)))
                     } else {
                         let timeunit = parse_timeunit(splits[1])?;
-                        let tz: Option<Arc<str>> = if splits[2] == "-" {
-                            None
-                        } else {
-                            Some(splits[2].into())
-                        };
+                        // Offset time zones like "+08:00" contain the separator
+                        let tz = splits[2..].join(":");
+                        let tz: Option<Arc<str>> = if tz == "-" { None } else { Some(tz.into()) };
                         Ok(Timestamp(timeunit, tz))
                     }
                 }
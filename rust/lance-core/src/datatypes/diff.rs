@@ -0,0 +1,272 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Structured differences between two schemas, see [`Schema::diff`].
+
+use std::fmt::{self, Display, Formatter};
+
+use arrow_schema::DataType;
+
+use super::field::Field;
+use super::schema::{Schema, format_field_path};
+
+/// One difference found by [`Schema::diff`].
+///
+/// `path` holds the names from the top-level field down to the field that
+/// differs, list items included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaDifference {
+    /// A field that only the schema being tested has.
+    NewField {
+        path: Vec<String>,
+        data_type: DataType,
+        nullable: bool,
+    },
+    /// A field that only the expected schema has.
+    MissingField { path: Vec<String>, nullable: bool },
+    /// A field with a different type. Children of nested fields are compared
+    /// one by one, so this is only reported for them if the kind differs,
+    /// e.g. a struct and a list.
+    TypeMismatch {
+        path: Vec<String>,
+        expected: DataType,
+        actual: DataType,
+    },
+    /// A field with a different nullability.
+    NullabilityMismatch {
+        path: Vec<String>,
+        expected: bool,
+        actual: bool,
+    },
+}
+
+impl SchemaDifference {
+    pub fn path(&self) -> &[String] {
+        match self {
+            Self::NewField { path, .. }
+            | Self::MissingField { path, .. }
+            | Self::TypeMismatch { path, .. }
+            | Self::NullabilityMismatch { path, .. } => path,
+        }
+    }
+
+    /// The path as it is written in a projection, e.g. `a.b`.
+    pub fn path_string(&self) -> String {
+        format_field_path(&self.path().iter().map(String::as_str).collect::<Vec<_>>())
+    }
+}
+
+fn nullability(nullable: bool) -> &'static str {
+    if nullable { "nullable" } else { "non-nullable" }
+}
+
+impl Display for SchemaDifference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let path = self.path_string();
+        match self {
+            Self::NewField {
+                data_type,
+                nullable,
+                ..
+            } => write!(
+                f,
+                "unexpected field {} ({}, {})",
+                path,
+                data_type,
+                nullability(*nullable)
+            ),
+            Self::MissingField { nullable, .. } => {
+                write!(f, "missing field {} ({})", path, nullability(*nullable))
+            }
+            Self::TypeMismatch {
+                expected, actual, ..
+            } => write!(
+                f,
+                "{} should have type {} but was {}",
+                path, expected, actual
+            ),
+            Self::NullabilityMismatch {
+                expected, actual, ..
+            } => write!(
+                f,
+                "{} should be {} but was {}",
+                path,
+                nullability(*expected),
+                nullability(*actual)
+            ),
+        }
+    }
+}
+
+/// All differences between two schemas, see [`Schema::diff`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaDiff {
+    pub differences: Vec<SchemaDifference>,
+}
+
+impl SchemaDiff {
+    pub fn is_empty(&self) -> bool {
+        self.differences.is_empty()
+    }
+}
+
+impl Display for SchemaDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, difference) in self.differences.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", difference)?;
+        }
+        Ok(())
+    }
+}
+
+impl Schema {
+    /// Every difference between this schema and `expected`, with the path of
+    /// the field it was found at.
+    ///
+    /// Fields are matched by name, so their order does not matter. Field ids,
+    /// metadata and dictionaries are not compared.
+    pub fn diff(&self, expected: &Self) -> SchemaDiff {
+        let mut diff = SchemaDiff::default();
+        diff_fields(
+            &self.fields,
+            &expected.fields,
+            &mut Vec::new(),
+            &mut diff.differences,
+        );
+        diff
+    }
+}
+
+fn diff_fields(
+    fields: &[Field],
+    expected: &[Field],
+    path: &mut Vec<String>,
+    differences: &mut Vec<SchemaDifference>,
+) {
+    for expected_field in expected {
+        path.push(expected_field.name.clone());
+        match fields.iter().find(|f| f.name == expected_field.name) {
+            Some(field) => diff_field(field, expected_field, path, differences),
+            None => differences.push(SchemaDifference::MissingField {
+                path: path.clone(),
+                nullable: expected_field.nullable,
+            }),
+        }
+        path.pop();
+    }
+    for field in fields {
+        if !expected.iter().any(|f| f.name == field.name) {
+            path.push(field.name.clone());
+            differences.push(SchemaDifference::NewField {
+                path: path.clone(),
+                data_type: field.data_type(),
+                nullable: field.nullable,
+            });
+            path.pop();
+        }
+    }
+}
+
+fn diff_field(
+    field: &Field,
+    expected: &Field,
+    path: &mut Vec<String>,
+    differences: &mut Vec<SchemaDifference>,
+) {
+    if field.logical_type != expected.logical_type {
+        differences.push(SchemaDifference::TypeMismatch {
+            path: path.clone(),
+            expected: expected.data_type(),
+            actual: field.data_type(),
+        });
+    } else {
+        diff_fields(&field.children, &expected.children, path, differences);
+    }
+    if field.nullable != expected.nullable {
+        differences.push(SchemaDifference::NullabilityMismatch {
+            path: path.clone(),
+            expected: expected.nullable,
+            actual: field.nullable,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_schema::{Field as ArrowField, Fields, Schema as ArrowSchema};
+
+    use super::*;
+
+    fn schema(fields: Vec<ArrowField>) -> Schema {
+        Schema::try_from(&ArrowSchema::new(fields)).unwrap()
+    }
+
+    #[test]
+    fn test_diff_nested() {
+        let point = |x: DataType, y_nullable: bool| {
+            DataType::Struct(Fields::from(vec![
+                ArrowField::new("x", x, true),
+                ArrowField::new("y", DataType::Float32, y_nullable),
+            ]))
+        };
+        let expected = schema(vec![
+            ArrowField::new("id", DataType::Int64, false),
+            ArrowField::new("point", point(DataType::Float32, false), true),
+            ArrowField::new("tags", DataType::Utf8, true),
+        ]);
+        // Nested field names may contain dots, which are quoted in the message
+        let data_point = DataType::Struct(Fields::from(vec![
+            ArrowField::new("x", DataType::Float64, true),
+            ArrowField::new("y", DataType::Float32, true),
+            ArrowField::new("z.v2", DataType::Float32, true),
+        ]));
+        let data = schema(vec![
+            ArrowField::new("point", data_point, true),
+            ArrowField::new("id", DataType::Int32, false),
+        ]);
+
+        let diff = data.diff(&expected);
+        assert_eq!(
+            diff.differences,
+            vec![
+                SchemaDifference::TypeMismatch {
+                    path: vec!["id".into()],
+                    expected: DataType::Int64,
+                    actual: DataType::Int32,
+                },
+                SchemaDifference::TypeMismatch {
+                    path: vec!["point".into(), "x".into()],
+                    expected: DataType::Float32,
+                    actual: DataType::Float64,
+                },
+                SchemaDifference::NullabilityMismatch {
+                    path: vec!["point".into(), "y".into()],
+                    expected: false,
+                    actual: true,
+                },
+                SchemaDifference::NewField {
+                    path: vec!["point".into(), "z.v2".into()],
+                    data_type: DataType::Float32,
+                    nullable: true,
+                },
+                SchemaDifference::MissingField {
+                    path: vec!["tags".into()],
+                    nullable: true,
+                },
+            ]
+        );
+        assert_eq!(
+            diff.to_string(),
+            "id should have type Int64 but was Int32; \
+             point.x should have type Float32 but was Float64; \
+             point.y should be non-nullable but was nullable; \
+             unexpected field point.`z.v2` (Float32, nullable); \
+             missing field tags (nullable)"
+        );
+
+        assert!(expected.diff(&expected).is_empty());
+    }
+}
@@ -128,6 +128,36 @@ pub enum OnTypeMismatch {
     Error,
 }
 
+/// What to do on a merge operation if both sides carry a metadata key with
+/// different values
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, DeepSizeOf)]
+pub enum OnMetadataConflict {
+    /// Keep the value already present on the existing schema or field.
+    #[default]
+    KeepExisting,
+    /// Replace the existing value with the incoming one.
+    Overwrite,
+}
+
+/// Merge `incoming` metadata into `existing`. Keys missing from `existing` are
+/// always added; `on_conflict` decides the keys present on both sides.
+pub(super) fn merge_metadata(
+    existing: &mut HashMap<String, String>,
+    incoming: &HashMap<String, String>,
+    on_conflict: OnMetadataConflict,
+) {
+    for (key, value) in incoming {
+        match on_conflict {
+            OnMetadataConflict::KeepExisting => {
+                existing.entry(key.clone()).or_insert_with(|| value.clone());
+            }
+            OnMetadataConflict::Overwrite => {
+                existing.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
 /// Lance Schema Field
 ///
 #[derive(Debug, Clone, PartialEq, DeepSizeOf)]
@@ -920,12 +950,16 @@ impl Field {
     }
 
     /// Merge the children of other field into this one.
-    pub(super) fn merge(&mut self, other: &Self) -> Result<()> {
+    pub(super) fn merge(
+        &mut self,
+        other: &Self,
+        on_metadata_conflict: OnMetadataConflict,
+    ) -> Result<()> {
         match (self.data_type(), other.data_type()) {
             (DataType::Struct(_), DataType::Struct(_)) => {
                 for other_child in other.children.as_slice() {
                     if let Some(field) = self.child_mut(&other_child.name) {
-                        field.merge(other_child)?;
+                        field.merge(other_child, on_metadata_conflict)?;
                     } else {
                         self.children.push(other_child.clone());
                     }
@@ -933,7 +967,7 @@ impl Field {
             }
             (DataType::List(_), DataType::List(_))
             | (DataType::LargeList(_), DataType::LargeList(_)) => {
-                self.children[0].merge(&other.children[0])?;
+                self.children[0].merge(&other.children[0], on_metadata_conflict)?;
             }
             (
                 DataType::FixedSizeList(_, self_list_size),
@@ -955,6 +989,7 @@ impl Field {
                 }
             }
         }
+        merge_metadata(&mut self.metadata, &other.metadata, on_metadata_conflict);
         Ok(())
     }
 
@@ -1277,6 +1312,10 @@ mod tests {
                 "timestamp:s:America/New_York",
                 DataType::Timestamp(TimeUnit::Second, Some("America/New_York".into())),
             ),
+            (
+                "timestamp:ms:+08:00",
+                DataType::Timestamp(TimeUnit::Millisecond, Some("+08:00".into())),
+            ),
             ("time32:s", DataType::Time32(TimeUnit::Second)),
             ("time32:ms", DataType::Time32(TimeUnit::Millisecond)),
             ("time64:us", DataType::Time64(TimeUnit::Microsecond)),
@@ -14,7 +14,9 @@ use arrow_schema::{Field as ArrowField, Schema as ArrowSchema};
 use deepsize::DeepSizeOf;
 use lance_arrow::*;
 
-use super::field::{Field, OnTypeMismatch, SchemaCompareOptions};
+use super::field::{
+    Field, OnMetadataConflict, OnTypeMismatch, SchemaCompareOptions, merge_metadata,
+};
 use crate::{
     Error, ROW_ADDR, ROW_ADDR_FIELD, ROW_CREATED_AT_VERSION, ROW_CREATED_AT_VERSION_FIELD, ROW_ID,
     ROW_ID_FIELD, ROW_LAST_UPDATED_AT_VERSION, ROW_LAST_UPDATED_AT_VERSION_FIELD, ROW_OFFSET,
@@ -247,7 +249,7 @@ impl Schema {
                 let split_refs: Vec<&str> = split[1..].iter().map(|s| s.as_str()).collect();
                 let projected_field = field.project(&split_refs)?;
                 if let Some(candidate_field) = candidates.iter_mut().find(|f| f.name == first) {
-                    candidate_field.merge(&projected_field)?;
+                    candidate_field.merge(&projected_field, OnMetadataConflict::KeepExisting)?;
                 } else {
                     candidates.push(projected_field)
                 }
@@ -700,7 +702,23 @@ impl Schema {
     ///
     /// After merging, the field IDs from `other` schema will be reassigned,
     /// following the fields in `self`.
+    ///
+    /// Schema and field metadata from both sides is kept. When a key is present
+    /// on both sides, the value from `self` wins; use [`Self::merge_with`] to
+    /// take the value from `other` instead.
     pub fn merge<S: TryInto<Self, Error = Error>>(&self, other: S) -> Result<Self> {
+        self.merge_with(other, OnMetadataConflict::default())
+    }
+
+    /// Merge this schema from the other schema, resolving metadata keys present
+    /// on both sides according to `on_metadata_conflict`.
+    ///
+    /// See [`Self::merge`].
+    pub fn merge_with<S: TryInto<Self, Error = Error>>(
+        &self,
+        other: S,
+        on_metadata_conflict: OnMetadataConflict,
+    ) -> Result<Self> {
         let mut other: Self = other.try_into()?;
         other.reset_id();
 
@@ -708,7 +726,7 @@ impl Schema {
         for mut field in self.fields.iter().cloned() {
             if let Some(other_field) = other.field(&field.name) {
                 // if both are struct types, then merge the fields
-                field.merge(other_field)?;
+                field.merge(other_field, on_metadata_conflict)?;
             }
             merged_fields.push(field);
         }
@@ -720,12 +738,8 @@ impl Schema {
                 merged_fields.push(field.clone());
             }
         }
-        let metadata = self
-            .metadata
-            .iter()
-            .chain(other.metadata.iter())
-            .map(|(k, v)| (k.clone(), v.clone()))
-            .collect();
+        let mut metadata = self.metadata.clone();
+        merge_metadata(&mut metadata, &other.metadata, on_metadata_conflict);
         let schema = Self {
             fields: merged_fields,
             metadata,
@@ -2212,6 +2226,73 @@ mod tests {
         assert_eq!(field.id, 7);
     }
 
+    #[test]
+    fn test_merge_metadata_conflicts() {
+        let existing = Schema::try_from(
+            &ArrowSchema::new(vec![ArrowField::new(
+                "s",
+                DataType::Struct(ArrowFields::from(vec![
+                    ArrowField::new("x", DataType::Int32, true).with_metadata(HashMap::from([(
+                        "note".to_string(),
+                        "existing".to_string(),
+                    )])),
+                ])),
+                true,
+            )])
+            .with_metadata(HashMap::from([
+                ("owner".to_string(), "existing".to_string()),
+                ("kept".to_string(), "yes".to_string()),
+            ])),
+        )
+        .unwrap();
+        let incoming = ArrowSchema::new(vec![ArrowField::new(
+            "s",
+            DataType::Struct(ArrowFields::from(vec![
+                ArrowField::new("x", DataType::Int32, true).with_metadata(HashMap::from([
+                    ("note".to_string(), "incoming".to_string()),
+                    ("unit".to_string(), "m".to_string()),
+                ])),
+                ArrowField::new("y", DataType::Int32, true).with_metadata(HashMap::from([(
+                    "ARROW:extension:name".to_string(),
+                    "my.ext".to_string(),
+                )])),
+            ])),
+            true,
+        )])
+        .with_metadata(HashMap::from([
+            ("owner".to_string(), "incoming".to_string()),
+            ("added".to_string(), "yes".to_string()),
+        ]));
+
+        let merged = existing.merge(&incoming).unwrap();
+        assert_eq!(
+            merged.metadata,
+            HashMap::from([
+                ("owner".to_string(), "existing".to_string()),
+                ("kept".to_string(), "yes".to_string()),
+                ("added".to_string(), "yes".to_string()),
+            ])
+        );
+        assert_eq!(
+            merged.field("s.x").unwrap().metadata,
+            HashMap::from([
+                ("note".to_string(), "existing".to_string()),
+                ("unit".to_string(), "m".to_string()),
+            ])
+        );
+        assert_eq!(
+            merged.field("s.y").unwrap().extension_name(),
+            Some("my.ext")
+        );
+
+        let merged = existing
+            .merge_with(&incoming, OnMetadataConflict::Overwrite)
+            .unwrap();
+        assert_eq!(merged.metadata["owner"], "incoming");
+        assert_eq!(merged.metadata["kept"], "yes");
+        assert_eq!(merged.field("s.x").unwrap().metadata["note"], "incoming");
+    }
+
     #[test]
     fn test_merge_nested_field() {
         let arrow_schema1 = ArrowSchema::new(vec![ArrowField::new(
@@ -93,6 +93,13 @@ pub enum Error {
         #[snafu(implicit)]
         location: Location,
     },
+    /// A write was attempted on a dataset or store opened read-only.
+    #[snafu(display("Read-only: {message}, {location}"))]
+    ReadOnly {
+        message: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
     #[snafu(display("Commit conflict for version {version}: {source}, {location}"))]
     CommitConflict {
         version: u64,
@@ -125,6 +132,12 @@ pub enum Error {
         #[snafu(implicit)]
         location: Location,
     },
+    #[snafu(display("Operation was cancelled: {message}, {location}"))]
+    Cancelled {
+        message: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
     #[snafu(display(
         "Encountered internal error. Please file a bug report at https://github.com/lance-format/lance/issues. {message}, {location}"
     ))]
@@ -163,6 +176,13 @@ pub enum Error {
         #[snafu(implicit)]
         location: Location,
     },
+    #[snafu(display("Precondition failed for {path}: {source}, {location}"))]
+    PreconditionFailed {
+        path: String,
+        source: BoxedError,
+        #[snafu(implicit)]
+        location: Location,
+    },
     #[snafu(display("LanceError(IO): {source}, {location}"))]
     IO {
         source: BoxedError,
@@ -295,6 +315,11 @@ impl Error {
         NotFoundSnafu { uri: uri.into() }.build()
     }
 
+    #[track_caller]
+    pub fn precondition_failed(path: impl Into<String>, source: BoxedError) -> Self {
+        PreconditionFailedSnafu { path: path.into() }.into_error(source)
+    }
+
     #[track_caller]
     pub fn wrapped(error: BoxedError) -> Self {
         WrappedSnafu { error }.build()
@@ -318,6 +343,14 @@ impl Error {
         NotSupportedSnafu.into_error(source)
     }
 
+    #[track_caller]
+    pub fn read_only(message: impl Into<String>) -> Self {
+        ReadOnlySnafu {
+            message: message.into(),
+        }
+        .build()
+    }
+
     #[track_caller]
     pub fn internal(message: impl Into<String>) -> Self {
         InternalSnafu {
@@ -334,6 +367,14 @@ impl Error {
         .build()
     }
 
+    #[track_caller]
+    pub fn cancelled(message: impl Into<String>) -> Self {
+        CancelledSnafu {
+            message: message.into(),
+        }
+        .build()
+    }
+
     #[track_caller]
     pub fn namespace(message: impl Into<String>) -> Self {
         NamespaceSnafu.into_error(message.into().into())
@@ -465,6 +506,212 @@ impl Error {
             other => Err(other),
         }
     }
+
+    /// Classify this error for retry and reporting decisions.
+    ///
+    /// Lance-level variants (e.g. [`Error::CommitConflict`]) determine the class
+    /// directly. Errors that wrap a storage error ([`Error::IO`], [`Error::External`],
+    /// ...) are classified by inspecting their source chain, so the result is the
+    /// same regardless of which object store provider produced the failure.
+    pub fn classification(&self) -> Classification {
+        let from_source = match self {
+            Self::Wrapped { error, .. } => classify_source(error.as_ref()),
+            other => std::error::Error::source(other).and_then(classify_source),
+        };
+        let Some((class, retryable)) = self.variant_class() else {
+            return from_source.unwrap_or(Classification::OTHER);
+        };
+        // Only keep the status of the underlying response if it agrees with the
+        // variant, e.g. a 404 behind `DatasetNotFound`.
+        let status_code = from_source
+            .filter(|source| source.class == class)
+            .and_then(|source| source.status_code);
+        Classification {
+            class,
+            status_code,
+            retryable,
+        }
+    }
+
+    /// The [`ErrorClass`] of this error, see [`Error::classification`].
+    pub fn class(&self) -> ErrorClass {
+        self.classification().class
+    }
+
+    /// Whether the operation that produced this error may succeed if retried.
+    pub fn is_retryable(&self) -> bool {
+        self.classification().retryable
+    }
+
+    /// The HTTP status code of the storage response behind this error, if known.
+    pub fn status_code(&self) -> Option<u16> {
+        self.classification().status_code
+    }
+
+    fn variant_class(&self) -> Option<(ErrorClass, bool)> {
+        match self {
+            Self::NotFound { .. }
+            | Self::DatasetNotFound { .. }
+            | Self::IndexNotFound { .. }
+            | Self::RefNotFound { .. }
+            | Self::VersionNotFound { .. } => Some((ErrorClass::NotFound, false)),
+            Self::PreconditionFailed { .. }
+            | Self::CommitConflict { .. }
+            | Self::IncompatibleTransaction { .. }
+            | Self::RefConflict { .. }
+            | Self::VersionConflict { .. } => Some((ErrorClass::Conflict, false)),
+            Self::RetryableCommitConflict { .. } => Some((ErrorClass::Conflict, true)),
+            Self::TooMuchWriteContention { .. } => Some((ErrorClass::Throttled, true)),
+            Self::Timeout { .. } => Some((ErrorClass::Transient, true)),
+            Self::CorruptFile { .. } => Some((ErrorClass::Corruption, false)),
+            _ => None,
+        }
+    }
+}
+
+/// Coarse category of an [`Error`], independent of the variant that carries it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorClass {
+    /// The object, dataset, version or index does not exist.
+    NotFound,
+    /// A precondition on the write failed, e.g. a conditional put or a commit
+    /// conflict with a concurrent writer.
+    Conflict,
+    /// The storage service or the commit handler asked us to slow down.
+    Throttled,
+    /// The request was rejected because the credentials are missing, expired or
+    /// lack permission.
+    AuthExpired,
+    /// Data read back from storage is invalid.
+    Corruption,
+    /// A temporary failure such as a timeout, a dropped connection or a 5xx
+    /// response.
+    Transient,
+    /// Anything else.
+    Other,
+}
+
+/// The result of [`Error::classification`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Classification {
+    pub class: ErrorClass,
+    /// HTTP status code of the storage response, if known.
+    pub status_code: Option<u16>,
+    pub retryable: bool,
+}
+
+impl Classification {
+    pub const OTHER: Self = Self::new(ErrorClass::Other, None);
+
+    /// Create a classification, retryable if the class usually is.
+    pub const fn new(class: ErrorClass, status_code: Option<u16>) -> Self {
+        Self {
+            class,
+            status_code,
+            retryable: matches!(class, ErrorClass::Throttled | ErrorClass::Transient),
+        }
+    }
+
+    pub const fn with_retryable(mut self, retryable: bool) -> Self {
+        self.retryable = retryable;
+        self
+    }
+
+    /// Classify any error the way [`Error::classification`] classifies the
+    /// source of a storage error, e.g. an `object_store::Error` that has not
+    /// been converted into an [`Error`] yet.
+    pub fn of(err: &(dyn std::error::Error + 'static)) -> Self {
+        classify_source(err).unwrap_or(Self::OTHER)
+    }
+}
+
+/// An error that carries the classification of its source.
+///
+/// Crates that wrap storage clients `lance-core` cannot depend on, such as
+/// OpenDAL, wrap the errors they know how to classify in this type so that
+/// [`Error::classification`] picks it up.
+#[derive(Debug)]
+pub struct ClassifiedError {
+    classification: Classification,
+    source: BoxedError,
+}
+
+impl ClassifiedError {
+    pub fn new(classification: Classification, source: BoxedError) -> Self {
+        Self {
+            classification,
+            source,
+        }
+    }
+
+    pub fn classification(&self) -> Classification {
+        self.classification
+    }
+}
+
+impl fmt::Display for ClassifiedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.source.fmt(f)
+    }
+}
+
+impl std::error::Error for ClassifiedError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+fn classify_source(err: &(dyn std::error::Error + 'static)) -> Option<Classification> {
+    if let Some(err) = err.downcast_ref::<ClassifiedError>() {
+        return Some(err.classification());
+    }
+    if let Some(err) = err.downcast_ref::<Error>() {
+        return Some(err.classification());
+    }
+    if let Some(err) = err.downcast_ref::<object_store::Error>() {
+        return classify_object_store_error(err);
+    }
+    if let Some(err) = err.downcast_ref::<std::io::Error>() {
+        return classify_io_error(err);
+    }
+    err.source().and_then(classify_source)
+}
+
+fn classify_object_store_error(err: &object_store::Error) -> Option<Classification> {
+    use object_store::Error as OSError;
+    match err {
+        OSError::NotFound { .. } => Some(Classification::new(ErrorClass::NotFound, Some(404))),
+        OSError::Precondition { .. } => Some(Classification::new(ErrorClass::Conflict, Some(412))),
+        OSError::AlreadyExists { .. } => Some(Classification::new(ErrorClass::Conflict, Some(409))),
+        OSError::PermissionDenied { .. } => {
+            Some(Classification::new(ErrorClass::AuthExpired, Some(403)))
+        }
+        OSError::Unauthenticated { .. } => {
+            Some(Classification::new(ErrorClass::AuthExpired, Some(401)))
+        }
+        // object_store_opendal reports the OpenDAL error kind as the store name.
+        OSError::Generic { store, source } => classify_source(source.as_ref()).or(match *store {
+            "RateLimited" => Some(Classification::new(ErrorClass::Throttled, Some(429))),
+            "PermissionDenied" => Some(Classification::new(ErrorClass::AuthExpired, Some(403))),
+            _ => None,
+        }),
+        other => std::error::Error::source(other).and_then(classify_source),
+    }
+}
+
+fn classify_io_error(err: &std::io::Error) -> Option<Classification> {
+    use std::io::ErrorKind;
+    match err.kind() {
+        ErrorKind::NotFound => Some(Classification::new(ErrorClass::NotFound, None)),
+        ErrorKind::TimedOut
+        | ErrorKind::Interrupted
+        | ErrorKind::ConnectionReset
+        | ErrorKind::ConnectionAborted
+        | ErrorKind::BrokenPipe => Some(Classification::new(ErrorClass::Transient, None)),
+        // `io::Error::source` skips the wrapped error itself, so look at it directly.
+        _ => err.get_ref().and_then(|inner| classify_source(inner)),
+    }
 }
 
 pub trait LanceOptionExt<T> {
@@ -905,6 +1152,165 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_classify_object_store_errors() {
+        let cases = [
+            (
+                object_store::Error::NotFound {
+                    path: "a".into(),
+                    source: "missing".into(),
+                },
+                ErrorClass::NotFound,
+                Some(404),
+                false,
+            ),
+            (
+                object_store::Error::Precondition {
+                    path: "a".into(),
+                    source: "etag mismatch".into(),
+                },
+                ErrorClass::Conflict,
+                Some(412),
+                false,
+            ),
+            (
+                object_store::Error::Unauthenticated {
+                    path: "a".into(),
+                    source: "token expired".into(),
+                },
+                ErrorClass::AuthExpired,
+                Some(401),
+                false,
+            ),
+            (
+                object_store::Error::Generic {
+                    store: "RateLimited",
+                    source: "slow down".into(),
+                },
+                ErrorClass::Throttled,
+                Some(429),
+                true,
+            ),
+            (
+                object_store::Error::Generic {
+                    store: "PermissionDenied",
+                    source: "denied".into(),
+                },
+                ErrorClass::AuthExpired,
+                Some(403),
+                false,
+            ),
+            (
+                object_store::Error::Generic {
+                    store: "S3",
+                    source: "boom".into(),
+                },
+                ErrorClass::Other,
+                None,
+                false,
+            ),
+        ];
+        for (source, class, status_code, retryable) in cases {
+            let message = source.to_string();
+            let err = Error::from(source);
+            assert_eq!(err.class(), class, "{message}");
+            assert_eq!(err.status_code(), status_code, "{message}");
+            assert_eq!(err.is_retryable(), retryable, "{message}");
+        }
+    }
+
+    #[test]
+    fn test_classify_variants() {
+        let conflict = Error::commit_conflict_source(3, "concurrent append".into());
+        assert_eq!(conflict.class(), ErrorClass::Conflict);
+        assert!(!conflict.is_retryable());
+
+        let retryable = Error::retryable_commit_conflict_source(3, "concurrent append".into());
+        assert_eq!(retryable.class(), ErrorClass::Conflict);
+        assert!(retryable.is_retryable());
+
+        let corrupt = Error::corrupt_file("a.lance".into(), "bad magic");
+        assert_eq!(corrupt.class(), ErrorClass::Corruption);
+        assert!(!corrupt.is_retryable());
+
+        assert_eq!(Error::timeout("slow").class(), ErrorClass::Transient);
+        assert!(Error::timeout("slow").is_retryable());
+        assert_eq!(
+            Error::cancelled("stop").classification(),
+            Classification::OTHER
+        );
+        assert_eq!(
+            Error::invalid_input("bad").classification(),
+            Classification::OTHER
+        );
+
+        // The status of the underlying response is kept when it agrees with the variant.
+        let not_found = Error::dataset_not_found(
+            "s3://bucket/ds",
+            box_error(object_store::Error::NotFound {
+                path: "ds".into(),
+                source: "missing".into(),
+            }),
+        );
+        assert_eq!(not_found.class(), ErrorClass::NotFound);
+        assert_eq!(not_found.status_code(), Some(404));
+        assert_eq!(Error::not_found("ds").status_code(), None);
+    }
+
+    #[test]
+    fn test_classify_nested_sources() {
+        // lance error -> object_store::Error -> lance error
+        let inner = Error::retryable_commit_conflict_source(1, "conflict".into());
+        let err = Error::from(object_store::Error::from(inner));
+        assert_eq!(err.class(), ErrorClass::Conflict);
+        assert!(err.is_retryable());
+
+        // io::Error wrapping an object_store::Error
+        let io = std::io::Error::other(object_store::Error::NotFound {
+            path: "a".into(),
+            source: "missing".into(),
+        });
+        assert_eq!(Error::from(io).status_code(), Some(404));
+
+        let reset = std::io::Error::from(std::io::ErrorKind::ConnectionReset);
+        let err = Error::wrapped(box_error(reset));
+        assert_eq!(err.class(), ErrorClass::Transient);
+        assert!(err.is_retryable());
+    }
+
+    #[derive(Debug)]
+    struct ThrottledError;
+
+    impl fmt::Display for ThrottledError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "throttled")
+        }
+    }
+
+    impl std::error::Error for ThrottledError {}
+
+    #[test]
+    fn test_classified_source() {
+        let err = Error::from(object_store::Error::Generic {
+            store: "Unexpected",
+            source: Box::new(ThrottledError),
+        });
+        assert_eq!(err.class(), ErrorClass::Other);
+
+        let classified = ClassifiedError::new(
+            Classification::new(ErrorClass::Throttled, Some(503)),
+            Box::new(ThrottledError),
+        );
+        assert_eq!(classified.to_string(), "throttled");
+        let err = Error::from(object_store::Error::Generic {
+            store: "Unexpected",
+            source: Box::new(classified),
+        });
+        assert_eq!(err.class(), ErrorClass::Throttled);
+        assert_eq!(err.status_code(), Some(503));
+        assert!(err.is_retryable());
+    }
+
     /// Test that lance_core::Error round-trips through DataFusionError.
     ///
     /// This simulates the case where a user defines a stream in terms of
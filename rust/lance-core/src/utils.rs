@@ -9,10 +9,13 @@ pub mod bit;
 pub mod blob;
 pub mod cpu;
 pub mod deletion;
+pub mod encryption;
 pub mod futures;
 pub mod hash;
+pub mod metrics;
 pub mod parse;
 pub mod path;
+pub mod resources;
 pub mod tempfile;
 pub mod testing;
 pub mod tokio;
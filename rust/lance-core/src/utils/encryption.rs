@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Keys for column-level encryption
+//!
+//! A top-level field is encrypted by setting [`ENCRYPTION_KEY_ID_META_KEY`] in its
+//! metadata.  Only the id of the key is stored in the dataset, the key itself is
+//! looked up in a [`KeyProvider`] whenever the column is written or read.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::{Error, Result};
+
+/// Field metadata key holding the id of the key a column is encrypted with
+pub const ENCRYPTION_KEY_ID_META_KEY: &str = "lance-encryption:key-id";
+
+/// Length in bytes of an [`EncryptionKey`]
+pub const ENCRYPTION_KEY_LEN: usize = 32;
+
+/// A 256-bit key used to encrypt columns
+///
+/// The key material is never printed by the `Debug` implementation.
+#[derive(Clone, PartialEq, Eq)]
+pub struct EncryptionKey([u8; ENCRYPTION_KEY_LEN]);
+
+impl EncryptionKey {
+    pub fn new(key: [u8; ENCRYPTION_KEY_LEN]) -> Self {
+        Self(key)
+    }
+
+    /// Create a key from a slice, which must be exactly [`ENCRYPTION_KEY_LEN`] bytes
+    pub fn try_from_slice(key: &[u8]) -> Result<Self> {
+        let key = <[u8; ENCRYPTION_KEY_LEN]>::try_from(key).map_err(|_| {
+            Error::invalid_input(format!(
+                "Encryption keys must be {} bytes, got {} bytes",
+                ENCRYPTION_KEY_LEN,
+                key.len()
+            ))
+        })?;
+        Ok(Self(key))
+    }
+
+    pub fn as_bytes(&self) -> &[u8; ENCRYPTION_KEY_LEN] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EncryptionKey(..)")
+    }
+}
+
+/// Supplies the keys of encrypted columns
+///
+/// Keys are looked up by the id stored in [`ENCRYPTION_KEY_ID_META_KEY`], e.g. to
+/// fetch them from a key management service.  Returning `None` means the key is
+/// unknown: writing the column fails and reading it fails with an error naming
+/// the key, while the other columns can still be read.
+#[async_trait]
+pub trait KeyProvider: fmt::Debug + Send + Sync {
+    async fn get_key(&self, key_id: &str) -> Result<Option<EncryptionKey>>;
+}
+
+/// A [`KeyProvider`] holding a fixed set of keys in memory
+#[derive(Debug, Clone, Default)]
+pub struct StaticKeyProvider {
+    keys: HashMap<String, EncryptionKey>,
+}
+
+impl StaticKeyProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_key(mut self, key_id: impl Into<String>, key: EncryptionKey) -> Self {
+        self.keys.insert(key_id.into(), key);
+        self
+    }
+}
+
+#[async_trait]
+impl KeyProvider for StaticKeyProvider {
+    async fn get_key(&self, key_id: &str) -> Result<Option<EncryptionKey>> {
+        Ok(self.keys.get(key_id).cloned())
+    }
+}
+
+/// Look up a key that must exist, `column` is only used in the error message
+pub async fn require_key(
+    provider: Option<&Arc<dyn KeyProvider>>,
+    key_id: &str,
+    column: &str,
+) -> Result<EncryptionKey> {
+    let key = match provider {
+        Some(provider) => provider.get_key(key_id).await?,
+        None => None,
+    };
+    key.ok_or_else(|| {
+        Error::invalid_input(format!(
+            "Column '{}' is encrypted with the key '{}' but {}",
+            column,
+            key_id,
+            if provider.is_some() {
+                "the key provider does not have that key"
+            } else {
+                "no key provider was configured"
+            }
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_static_key_provider() {
+        let key = EncryptionKey::new([7; ENCRYPTION_KEY_LEN]);
+        let provider: Arc<dyn KeyProvider> =
+            Arc::new(StaticKeyProvider::new().with_key("pii", key.clone()));
+
+        assert_eq!(
+            require_key(Some(&provider), "pii", "ssn").await.unwrap(),
+            key
+        );
+        let err = require_key(Some(&provider), "other", "ssn")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("'other'"), "{err}");
+        assert!(err.to_string().contains("does not have that key"), "{err}");
+        let err = require_key(None, "pii", "ssn").await.unwrap_err();
+        assert!(err.to_string().contains("no key provider"), "{err}");
+
+        assert_eq!(format!("{key:?}"), "EncryptionKey(..)");
+        assert!(EncryptionKey::try_from_slice(&[1; 16]).is_err());
+    }
+}
@@ -0,0 +1,416 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Process wide metrics in the Prometheus text exposition format.
+//!
+//! Services that embed Lance can scrape IO, cache and commit activity with
+//! [`render`]. Instruments are registered once, e.g. when an object store or
+//! cache is created, and the returned [`Counter`] and [`Histogram`] handles are
+//! updated with a single atomic operation per event.
+//!
+//! Everything here is a no-op unless the `metrics` feature is enabled: the
+//! handles are then zero sized and their methods compile to nothing.
+
+#[cfg(feature = "metrics")]
+use std::sync::Arc;
+#[cfg(feature = "metrics")]
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "metrics")]
+use std::time::Instant;
+
+/// Histogram buckets, in seconds, for request latencies.
+pub const LATENCY_BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// A monotonically increasing count.
+///
+/// The default counter is not registered and ignores updates.
+#[derive(Debug, Clone, Default)]
+pub struct Counter {
+    #[cfg(feature = "metrics")]
+    value: Option<Arc<AtomicU64>>,
+}
+
+impl Counter {
+    #[inline]
+    pub fn inc(&self) {
+        self.inc_by(1);
+    }
+
+    #[inline]
+    pub fn inc_by(&self, #[allow(unused_variables)] n: u64) {
+        #[cfg(feature = "metrics")]
+        if let Some(value) = &self.value {
+            value.fetch_add(n, Ordering::Relaxed);
+        }
+    }
+}
+
+/// A distribution of observed values over fixed buckets.
+///
+/// The default histogram is not registered and ignores updates.
+#[derive(Debug, Clone, Default)]
+pub struct Histogram {
+    #[cfg(feature = "metrics")]
+    inner: Option<Arc<registry::HistogramCore>>,
+}
+
+impl Histogram {
+    #[inline]
+    pub fn observe(&self, #[allow(unused_variables)] value: f64) {
+        #[cfg(feature = "metrics")]
+        if let Some(inner) = &self.inner {
+            inner.observe(value);
+        }
+    }
+
+    /// Start timing an operation, its duration in seconds is observed when
+    /// the returned timer is dropped.
+    #[inline]
+    pub fn start_timer(&self) -> HistogramTimer<'_> {
+        HistogramTimer {
+            #[cfg(feature = "metrics")]
+            started: self.inner.as_ref().map(|inner| (inner, Instant::now())),
+            #[cfg(not(feature = "metrics"))]
+            _histogram: std::marker::PhantomData,
+        }
+    }
+}
+
+/// Observes the elapsed time on its [`Histogram`] when dropped.
+#[must_use = "the duration is observed when the timer is dropped"]
+pub struct HistogramTimer<'a> {
+    #[cfg(feature = "metrics")]
+    started: Option<(&'a Arc<registry::HistogramCore>, Instant)>,
+    #[cfg(not(feature = "metrics"))]
+    _histogram: std::marker::PhantomData<&'a Histogram>,
+}
+
+impl Drop for HistogramTimer<'_> {
+    #[inline]
+    fn drop(&mut self) {
+        #[cfg(feature = "metrics")]
+        if let Some((inner, start)) = self.started.take() {
+            inner.observe(start.elapsed().as_secs_f64());
+        }
+    }
+}
+
+/// Register, or look up, the counter `name` with the given labels.
+pub fn counter(
+    #[allow(unused_variables)] name: &'static str,
+    #[allow(unused_variables)] help: &'static str,
+    #[allow(unused_variables)] labels: &[(&str, &str)],
+) -> Counter {
+    Counter {
+        #[cfg(feature = "metrics")]
+        value: Some(registry::counter(name, help, labels)),
+    }
+}
+
+/// Register, or look up, the histogram `name` with the given labels.
+///
+/// `buckets` are the upper bounds of the buckets in increasing order, e.g.
+/// [`LATENCY_BUCKETS`].
+pub fn histogram(
+    #[allow(unused_variables)] name: &'static str,
+    #[allow(unused_variables)] help: &'static str,
+    #[allow(unused_variables)] labels: &[(&str, &str)],
+    #[allow(unused_variables)] buckets: &'static [f64],
+) -> Histogram {
+    Histogram {
+        #[cfg(feature = "metrics")]
+        inner: Some(registry::histogram(name, help, labels, buckets)),
+    }
+}
+
+/// Render every registered instrument in the Prometheus text format.
+///
+/// Returns an empty string when the `metrics` feature is disabled.
+pub fn render() -> String {
+    #[cfg(feature = "metrics")]
+    return registry::render();
+    #[cfg(not(feature = "metrics"))]
+    String::new()
+}
+
+/// The current value of a registered counter, `None` if it was never registered.
+pub fn counter_value(
+    #[allow(unused_variables)] name: &str,
+    #[allow(unused_variables)] labels: &[(&str, &str)],
+) -> Option<u64> {
+    #[cfg(feature = "metrics")]
+    return registry::counter_value(name, labels);
+    #[cfg(not(feature = "metrics"))]
+    None
+}
+
+/// A short, stable label value identifying a dataset by its URI.
+///
+/// URIs can be long and may carry credentials, so instruments are labeled
+/// with the 64-bit FNV-1a hash of the URI instead.
+pub fn dataset_label(uri: &str) -> String {
+    let hash = uri.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{hash:016x}")
+}
+
+#[cfg(feature = "metrics")]
+mod registry {
+    use std::collections::BTreeMap;
+    use std::fmt::Write;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, LazyLock, Mutex};
+
+    #[derive(Debug)]
+    pub(super) struct HistogramCore {
+        bounds: &'static [f64],
+        /// Non-cumulative counts, the last bucket is `+Inf`
+        buckets: Box<[AtomicU64]>,
+        /// Sum of observations, as `f64` bits
+        sum: AtomicU64,
+    }
+
+    impl HistogramCore {
+        fn new(bounds: &'static [f64]) -> Self {
+            Self {
+                bounds,
+                buckets: (0..=bounds.len()).map(|_| AtomicU64::new(0)).collect(),
+                sum: AtomicU64::new(0f64.to_bits()),
+            }
+        }
+
+        pub(super) fn observe(&self, value: f64) {
+            let bucket = self.bounds.partition_point(|bound| *bound < value);
+            self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+            let _ = self
+                .sum
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |sum| {
+                    Some((f64::from_bits(sum) + value).to_bits())
+                });
+        }
+    }
+
+    #[derive(Debug)]
+    enum Series {
+        Counter(Arc<AtomicU64>),
+        Histogram(Arc<HistogramCore>),
+    }
+
+    #[derive(Debug)]
+    struct Family {
+        help: &'static str,
+        /// Rendered label sets, e.g. `scheme="s3"`
+        series: BTreeMap<String, Series>,
+    }
+
+    static REGISTRY: LazyLock<Mutex<BTreeMap<&'static str, Family>>> =
+        LazyLock::new(Default::default);
+
+    fn render_labels(labels: &[(&str, &str)]) -> String {
+        let mut rendered = String::new();
+        for (i, (key, value)) in labels.iter().enumerate() {
+            if i > 0 {
+                rendered.push(',');
+            }
+            rendered.push_str(key);
+            rendered.push_str("=\"");
+            for c in value.chars() {
+                match c {
+                    '\\' => rendered.push_str("\\\\"),
+                    '"' => rendered.push_str("\\\""),
+                    '\n' => rendered.push_str("\\n"),
+                    c => rendered.push(c),
+                }
+            }
+            rendered.push('"');
+        }
+        rendered
+    }
+
+    fn series<T>(
+        name: &'static str,
+        help: &'static str,
+        labels: &[(&str, &str)],
+        create: impl FnOnce() -> Series,
+        extract: impl Fn(&Series) -> Option<T>,
+    ) -> T {
+        let mut registry = REGISTRY.lock().unwrap();
+        let family = registry.entry(name).or_insert_with(|| Family {
+            help,
+            series: BTreeMap::new(),
+        });
+        let series = family
+            .series
+            .entry(render_labels(labels))
+            .or_insert_with(create);
+        extract(series).unwrap_or_else(|| panic!("metric {name} registered with a different type"))
+    }
+
+    pub(super) fn counter(
+        name: &'static str,
+        help: &'static str,
+        labels: &[(&str, &str)],
+    ) -> Arc<AtomicU64> {
+        series(
+            name,
+            help,
+            labels,
+            || Series::Counter(Arc::new(AtomicU64::new(0))),
+            |series| match series {
+                Series::Counter(value) => Some(value.clone()),
+                Series::Histogram(_) => None,
+            },
+        )
+    }
+
+    pub(super) fn histogram(
+        name: &'static str,
+        help: &'static str,
+        labels: &[(&str, &str)],
+        buckets: &'static [f64],
+    ) -> Arc<HistogramCore> {
+        series(
+            name,
+            help,
+            labels,
+            || Series::Histogram(Arc::new(HistogramCore::new(buckets))),
+            |series| match series {
+                Series::Histogram(inner) => Some(inner.clone()),
+                Series::Counter(_) => None,
+            },
+        )
+    }
+
+    pub(super) fn counter_value(name: &str, labels: &[(&str, &str)]) -> Option<u64> {
+        let registry = REGISTRY.lock().unwrap();
+        match registry.get(name)?.series.get(&render_labels(labels))? {
+            Series::Counter(value) => Some(value.load(Ordering::Relaxed)),
+            Series::Histogram(_) => None,
+        }
+    }
+
+    pub(super) fn render() -> String {
+        let registry = REGISTRY.lock().unwrap();
+        let mut out = String::new();
+        for (name, family) in registry.iter() {
+            let kind = match family.series.values().next() {
+                Some(Series::Histogram(_)) => "histogram",
+                _ => "counter",
+            };
+            let _ = writeln!(out, "# HELP {name} {}", family.help);
+            let _ = writeln!(out, "# TYPE {name} {kind}");
+            for (labels, series) in &family.series {
+                match series {
+                    Series::Counter(value) => {
+                        let value = value.load(Ordering::Relaxed);
+                        if labels.is_empty() {
+                            let _ = writeln!(out, "{name} {value}");
+                        } else {
+                            let _ = writeln!(out, "{name}{{{labels}}} {value}");
+                        }
+                    }
+                    Series::Histogram(inner) => render_histogram(&mut out, name, labels, inner),
+                }
+            }
+        }
+        out
+    }
+
+    fn render_histogram(out: &mut String, name: &str, labels: &str, inner: &HistogramCore) {
+        let separator = if labels.is_empty() { "" } else { "," };
+        let mut cumulative = 0;
+        for (i, count) in inner.buckets.iter().enumerate() {
+            cumulative += count.load(Ordering::Relaxed);
+            let le = inner
+                .bounds
+                .get(i)
+                .map_or_else(|| "+Inf".to_string(), |bound| bound.to_string());
+            let _ = writeln!(
+                out,
+                "{name}_bucket{{{labels}{separator}le=\"{le}\"}} {cumulative}"
+            );
+        }
+        let sum = f64::from_bits(inner.sum.load(Ordering::Relaxed));
+        let braces = if labels.is_empty() {
+            String::new()
+        } else {
+            format!("{{{labels}}}")
+        };
+        let _ = writeln!(out, "{name}_sum{braces} {sum}");
+        let _ = writeln!(out, "{name}_count{braces} {cumulative}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dataset_label() {
+        assert_eq!(dataset_label(""), "cbf29ce484222325");
+        assert_eq!(dataset_label("s3://bucket/a.lance").len(), 16);
+        assert_ne!(
+            dataset_label("s3://bucket/a.lance"),
+            dataset_label("s3://bucket/b.lance")
+        );
+    }
+
+    #[cfg(not(feature = "metrics"))]
+    #[test]
+    fn test_disabled_is_noop() {
+        assert_eq!(std::mem::size_of::<Counter>(), 0);
+        assert_eq!(std::mem::size_of::<Histogram>(), 0);
+        let counter = counter("lance_test_disabled_total", "test", &[]);
+        counter.inc();
+        assert_eq!(counter_value("lance_test_disabled_total", &[]), None);
+        assert!(render().is_empty());
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_counter_and_render() {
+        let labels = [("scheme", "memory"), ("dataset", "a\"b")];
+        let counter = counter("lance_test_events_total", "Test events.", &labels);
+        counter.inc();
+        // Registering again returns the same series.
+        super::counter("lance_test_events_total", "Test events.", &labels).inc_by(2);
+        assert_eq!(counter_value("lance_test_events_total", &labels), Some(3));
+        assert_eq!(counter_value("lance_test_events_total", &[]), None);
+
+        let rendered = render();
+        assert!(rendered.contains("# TYPE lance_test_events_total counter\n"));
+        assert!(
+            rendered.contains("lance_test_events_total{scheme=\"memory\",dataset=\"a\\\"b\"} 3\n"),
+            "{rendered}"
+        );
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_histogram_render() {
+        let histogram = histogram(
+            "lance_test_latency_seconds",
+            "Test latency.",
+            &[("op", "read")],
+            &[0.1, 1.0],
+        );
+        histogram.observe(0.05);
+        histogram.observe(0.5);
+        histogram.observe(5.0);
+        drop(histogram.start_timer());
+
+        let rendered = render();
+        for line in [
+            "# TYPE lance_test_latency_seconds histogram",
+            "lance_test_latency_seconds_bucket{op=\"read\",le=\"0.1\"} 2",
+            "lance_test_latency_seconds_bucket{op=\"read\",le=\"1\"} 3",
+            "lance_test_latency_seconds_bucket{op=\"read\",le=\"+Inf\"} 4",
+            "lance_test_latency_seconds_count{op=\"read\"} 4",
+        ] {
+            assert!(rendered.contains(line), "missing {line} in {rendered}");
+        }
+    }
+}
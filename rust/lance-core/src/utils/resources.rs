@@ -0,0 +1,172 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Approximate accounting of the memory and temporary storage used by a job.
+//!
+//! Maintenance jobs such as compaction and index builds run under
+//! [`with_resource_tracker`].  Code that allocates a large buffer (a decoded
+//! batch, a shuffle buffer, kmeans training data) holds a [`MemoryReservation`]
+//! for as long as the buffer lives, and code that writes scratch files reports
+//! the bytes with [`TrackedResources::add_temp_bytes`].  Outside of a tracked
+//! job both are no-ops that cost a task-local lookup.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+tokio::task_local! {
+    static RESOURCE_TRACKERS: Arc<[Arc<ResourceTracker>]>;
+}
+
+/// The memory and temporary storage used by a job.
+///
+/// Both figures only grow: the peak is the most memory held in reservations at
+/// any one time and the temporary bytes are summed over every scratch file.
+#[derive(Debug, Default)]
+pub struct ResourceTracker {
+    memory_bytes: AtomicU64,
+    peak_memory_bytes: AtomicU64,
+    temp_bytes: AtomicU64,
+}
+
+impl ResourceTracker {
+    /// The most memory, in bytes, held in reservations at any one time
+    pub fn peak_memory_bytes(&self) -> u64 {
+        self.peak_memory_bytes.load(Ordering::Relaxed)
+    }
+
+    /// The bytes written to temporary storage
+    pub fn temp_bytes(&self) -> u64 {
+        self.temp_bytes.load(Ordering::Relaxed)
+    }
+
+    fn reserve(&self, bytes: u64) {
+        let memory_bytes = self.memory_bytes.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        self.peak_memory_bytes
+            .fetch_max(memory_bytes, Ordering::Relaxed);
+    }
+
+    fn release(&self, bytes: u64) {
+        self.memory_bytes.fetch_sub(bytes, Ordering::Relaxed);
+    }
+}
+
+/// Run `fut` with its resources counted by `tracker`
+///
+/// Resources are also counted by the trackers of any enclosing call, so a job
+/// can track its own usage while being part of a bigger one.  Task-locals are
+/// not inherited by spawned tasks; capture [`TrackedResources::current`] and
+/// move it into the task instead.
+pub async fn with_resource_tracker<F: Future>(tracker: Arc<ResourceTracker>, fut: F) -> F::Output {
+    let trackers = TrackedResources::current()
+        .0
+        .iter()
+        .flat_map(|trackers| trackers.iter().cloned())
+        .chain(std::iter::once(tracker))
+        .collect();
+    RESOURCE_TRACKERS.scope(trackers, fut).await
+}
+
+/// The trackers of the enclosing [`with_resource_tracker`] calls
+#[derive(Debug, Clone, Default)]
+pub struct TrackedResources(Option<Arc<[Arc<ResourceTracker>]>>);
+
+impl TrackedResources {
+    pub fn current() -> Self {
+        Self(RESOURCE_TRACKERS.try_with(Clone::clone).ok())
+    }
+
+    /// Whether any tracker counts the resources
+    pub fn is_tracked(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Count `bytes` of memory until the returned reservation is dropped
+    pub fn reserve_memory(&self, bytes: usize) -> MemoryReservation {
+        let mut reservation = MemoryReservation {
+            resources: self.clone(),
+            bytes: 0,
+        };
+        reservation.resize(bytes);
+        reservation
+    }
+
+    /// Count `bytes` written to temporary storage
+    pub fn add_temp_bytes(&self, bytes: u64) {
+        for tracker in self.trackers() {
+            tracker.temp_bytes.fetch_add(bytes, Ordering::Relaxed);
+        }
+    }
+
+    fn trackers(&self) -> impl Iterator<Item = &Arc<ResourceTracker>> {
+        self.0.iter().flat_map(|trackers| trackers.iter())
+    }
+}
+
+/// Memory counted by the enclosing trackers until this is dropped
+#[derive(Debug, Default)]
+pub struct MemoryReservation {
+    resources: TrackedResources,
+    bytes: u64,
+}
+
+impl MemoryReservation {
+    /// Change the reserved memory to `bytes`, e.g. as a buffer grows
+    pub fn resize(&mut self, bytes: usize) {
+        let bytes = bytes as u64;
+        for tracker in self.resources.trackers() {
+            if bytes >= self.bytes {
+                tracker.reserve(bytes - self.bytes);
+            } else {
+                tracker.release(self.bytes - bytes);
+            }
+        }
+        self.bytes = bytes;
+    }
+}
+
+impl Drop for MemoryReservation {
+    fn drop(&mut self) {
+        self.resize(0);
+    }
+}
+
+/// Count `bytes` of memory until the returned reservation is dropped, see
+/// [`TrackedResources::reserve_memory`]
+pub fn reserve_memory(bytes: usize) -> MemoryReservation {
+    TrackedResources::current().reserve_memory(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_resource_tracker() {
+        let outer = Arc::new(ResourceTracker::default());
+        let inner = Arc::new(ResourceTracker::default());
+        with_resource_tracker(outer.clone(), async {
+            let _held = reserve_memory(100);
+            with_resource_tracker(inner.clone(), async {
+                let mut buffer = reserve_memory(50);
+                buffer.resize(80);
+                buffer.resize(10);
+                TrackedResources::current().add_temp_bytes(7);
+            })
+            .await;
+            drop(reserve_memory(20));
+        })
+        .await;
+
+        assert_eq!(outer.peak_memory_bytes(), 180);
+        assert_eq!(outer.temp_bytes(), 7);
+        assert_eq!(inner.peak_memory_bytes(), 80);
+        assert_eq!(inner.temp_bytes(), 7);
+
+        // Nothing is counted outside of a tracked job
+        let untracked = TrackedResources::current();
+        assert!(!untracked.is_tracked());
+        drop(untracked.reserve_memory(1000));
+        assert_eq!(outer.peak_memory_bytes(), 180);
+    }
+}
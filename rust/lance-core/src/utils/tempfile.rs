@@ -53,6 +53,12 @@ impl TempDir {
         Ok(Self { tempdir })
     }
 
+    /// Create a temporary directory inside `parent`, exposing any potential errors.
+    pub fn try_new_in(parent: impl AsRef<StdPath>) -> Result<Self> {
+        let tempdir = tempfile::tempdir_in(parent)?;
+        Ok(Self { tempdir })
+    }
+
     /// Get the path as a string
     ///
     /// This path will be safe to use as a URI on Windows
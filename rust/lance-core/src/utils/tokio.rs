@@ -42,6 +42,35 @@ fn calculate_num_compute_intensive_cpus() -> usize {
     num_cpus::get() - *IO_CORE_RESERVATION
 }
 
+tokio::task_local! {
+    static COMPUTE_PARALLELISM_LIMIT: usize;
+}
+
+/// Run `fut` with compute-intensive fan-out capped at `limit` tasks
+///
+/// Code that sizes its parallelism with [`compute_parallelism`] (decoding, kmeans training
+/// and shuffling) will use at most `limit` threads while `fut` runs.  If a limit is already
+/// set by an enclosing call then the smaller of the two is used.  Task-locals are not
+/// inherited by spawned tasks, so the limit only applies to work driven by `fut` itself.
+pub async fn with_compute_parallelism<F: Future>(limit: usize, fut: F) -> F::Output {
+    let limit = compute_parallelism_limit().map_or(limit, |outer| outer.min(limit));
+    COMPUTE_PARALLELISM_LIMIT.scope(limit.max(1), fut).await
+}
+
+/// The limit set by an enclosing [`with_compute_parallelism`] call, if any
+pub fn compute_parallelism_limit() -> Option<usize> {
+    COMPUTE_PARALLELISM_LIMIT.try_with(|limit| *limit).ok()
+}
+
+/// The number of compute-intensive tasks to run concurrently
+///
+/// This is [`get_num_compute_intensive_cpus`], capped by any enclosing
+/// [`with_compute_parallelism`] call.
+pub fn compute_parallelism() -> usize {
+    let num_cpus = get_num_compute_intensive_cpus();
+    compute_parallelism_limit().map_or(num_cpus, |limit| limit.min(num_cpus))
+}
+
 pub static IO_CORE_RESERVATION: LazyLock<usize> = LazyLock::new(|| {
     std::env::var("LANCE_IO_CORE_RESERVATION")
         .unwrap_or("2".to_string())
@@ -85,3 +85,4 @@ pub const DATASET_COMPACTING_EVENT: &str = "compacting";
 pub const DATASET_CLEANING_EVENT: &str = "cleaning";
 pub const DATASET_LOADING_EVENT: &str = "loading";
 pub const TRACE_OBJECT_STORE_THROTTLE: &str = "lance::object_store::throttle";
+pub const TRACE_INDEX_BUILD: &str = "lance::index_build";
@@ -39,7 +39,7 @@ use datafusion_common::{DataFusionError, Statistics};
 use datafusion_physical_expr::{EquivalenceProperties, Partitioning};
 
 use futures::{StreamExt, stream};
-use lance_arrow::SchemaExt;
+use lance_arrow::{SchemaExt, contiguous::make_vectors_contiguous};
 use lance_core::{
     Error, Result,
     utils::{
@@ -48,6 +48,7 @@ use lance_core::{
     },
 };
 use log::{debug, info, warn};
+use tokio_util::sync::CancellationToken;
 use tracing::Span;
 
 use crate::udf::register_functions;
@@ -290,6 +291,8 @@ pub struct LanceExecutionOptions {
     pub target_partition: Option<usize>,
     pub execution_stats_callback: Option<ExecutionStatsCallback>,
     pub skip_logging: bool,
+    /// Cancels the I/O of the plan when cancelled, see [`task_cancellation_token`]
+    pub cancellation_token: Option<CancellationToken>,
 }
 
 impl std::fmt::Debug for LanceExecutionOptions {
@@ -305,6 +308,7 @@ impl std::fmt::Debug for LanceExecutionOptions {
                 "execution_stats_callback",
                 &self.execution_stats_callback.is_some(),
             )
+            .field("cancellation_token", &self.cancellation_token)
             .finish()
     }
 }
@@ -464,10 +468,24 @@ fn get_task_context(
     if let Some(batch_size) = options.batch_size.as_ref() {
         state.config_mut().options_mut().execution.batch_size = *batch_size;
     }
+    if let Some(token) = options.cancellation_token.as_ref() {
+        state.config_mut().set_extension(Arc::new(token.clone()));
+    }
 
     state.task_ctx()
 }
 
+/// The cancellation token of the query running in `context`, if any
+///
+/// Nodes that read data pass this to their I/O scheduler so that the reads of a
+/// cancelled or timed out query stop instead of running to completion.
+pub fn task_cancellation_token(context: &TaskContext) -> Option<CancellationToken> {
+    context
+        .session_config()
+        .get_extension::<CancellationToken>()
+        .map(|token| token.as_ref().clone())
+}
+
 #[derive(Default, Clone, Debug, PartialEq, Eq)]
 pub struct ExecutionSummaryCounts {
     /// The number of I/O operations performed
@@ -944,6 +962,95 @@ impl ExecutionPlan for StrictBatchSizeExec {
     }
 }
 
+/// Exec node that makes the values of vector columns contiguous and aligned
+///
+/// See [`lance_arrow::contiguous::make_vectors_contiguous`].  Filters, takes and
+/// rechunking all slice batches after they are decoded, so this runs at the end of
+/// the plan.  Batches that are already contiguous are passed through without a copy.
+#[derive(Clone, Debug)]
+pub struct ContiguousVectorsExec {
+    input: Arc<dyn ExecutionPlan>,
+}
+
+impl ContiguousVectorsExec {
+    pub fn new(input: Arc<dyn ExecutionPlan>) -> Self {
+        Self { input }
+    }
+}
+
+impl DisplayAs for ContiguousVectorsExec {
+    fn fmt_as(
+        &self,
+        _t: datafusion::physical_plan::DisplayFormatType,
+        f: &mut std::fmt::Formatter,
+    ) -> std::fmt::Result {
+        write!(f, "ContiguousVectorsExec")
+    }
+}
+
+impl ExecutionPlan for ContiguousVectorsExec {
+    fn name(&self) -> &str {
+        "ContiguousVectorsExec"
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn properties(&self) -> &Arc<PlanProperties> {
+        self.input.properties()
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![&self.input]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> datafusion_common::Result<Arc<dyn ExecutionPlan>> {
+        Ok(Arc::new(Self {
+            input: children[0].clone(),
+        }))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> datafusion_common::Result<SendableRecordBatchStream> {
+        let stream = self.input.execute(partition, context)?;
+        let schema = stream.schema();
+        let stream = stream.map(|batch| -> datafusion_common::Result<RecordBatch> {
+            Ok(make_vectors_contiguous(&batch?)?)
+        });
+        Ok(Box::pin(RecordBatchStreamAdapter::new(schema, stream)))
+    }
+
+    fn maintains_input_order(&self) -> Vec<bool> {
+        vec![true]
+    }
+
+    fn benefits_from_input_partitioning(&self) -> Vec<bool> {
+        vec![false]
+    }
+
+    fn partition_statistics(
+        &self,
+        partition: Option<usize>,
+    ) -> datafusion_common::Result<Statistics> {
+        self.input.partition_statistics(partition)
+    }
+
+    fn cardinality_effect(&self) -> CardinalityEffect {
+        CardinalityEffect::Equal
+    }
+
+    fn supports_limit_pushdown(&self) -> bool {
+        true
+    }
+}
+
 /// Exec node that rechunks batches so no output batch exceeds `max_bytes`.
 ///
 /// # Why this exists
@@ -1208,4 +1315,21 @@ mod tests {
         };
         assert_eq!(opts.mem_pool_size(), 50 * 1024 * 1024);
     }
+
+    #[test]
+    fn test_task_cancellation_token() {
+        let token = CancellationToken::new();
+        let opts = LanceExecutionOptions {
+            cancellation_token: Some(token.clone()),
+            ..Default::default()
+        };
+        let session_ctx = get_session_context(&opts);
+        let task_ctx = get_task_context(&session_ctx, &opts);
+        token.cancel();
+        assert!(task_cancellation_token(&task_ctx).unwrap().is_cancelled());
+
+        // The token is not kept in the cached session context
+        let task_ctx = get_task_context(&session_ctx, &LanceExecutionOptions::default());
+        assert!(task_cancellation_token(&task_ctx).is_none());
+    }
 }
@@ -7,7 +7,8 @@ use std::sync::Arc;
 
 use arrow::compute::cast;
 use arrow_array::{ArrayRef, cast::AsArray};
-use arrow_schema::{DataType, TimeUnit};
+use arrow_buffer::i256;
+use arrow_schema::{DECIMAL128_MAX_PRECISION, DECIMAL256_MAX_PRECISION, DataType, TimeUnit};
 use datafusion_common::ScalarValue;
 
 const MS_PER_DAY: i64 = 86400000;
@@ -208,11 +209,29 @@ pub fn safe_coerce_scalar(value: &ScalarValue, ty: &DataType) -> Option<ScalarVa
         ScalarValue::Float32(val) => match ty {
             DataType::Float32 => Some(value.clone()),
             DataType::Float64 => val.map(|v| ScalarValue::Float64(Some(f64::from(v)))),
+            // Only if the literal, as written, fits the scale and precision of the column
+            DataType::Decimal128(_, _) | DataType::Decimal256(_, _) => {
+                let (unscaled, scale) = decimal_parts(value)?;
+                rescale_decimal(unscaled, scale, ty)
+            }
             _ => None,
         },
         ScalarValue::Float64(val) => match ty {
             DataType::Float32 => val.map(|v| ScalarValue::Float32(Some(v as f32))),
             DataType::Float64 => Some(value.clone()),
+            DataType::Decimal128(_, _) | DataType::Decimal256(_, _) => {
+                let (unscaled, scale) = decimal_parts(value)?;
+                rescale_decimal(unscaled, scale, ty)
+            }
+            _ => None,
+        },
+        ScalarValue::Decimal128(_, _, _) | ScalarValue::Decimal256(_, _, _) => match ty {
+            DataType::Decimal128(_, _) | DataType::Decimal256(_, _) => {
+                let (unscaled, scale) = decimal_parts(value)?;
+                rescale_decimal(unscaled, scale, ty)
+            }
+            // Float columns cannot hold the exact value anyways
+            DataType::Float32 | DataType::Float64 => value.cast_to(ty).ok(),
             _ => None,
         },
         ScalarValue::Utf8(val) => match ty {
@@ -440,6 +459,134 @@ pub fn safe_coerce_scalar(value: &ScalarValue, ty: &DataType) -> Option<ScalarVa
     }
 }
 
+/// The unscaled value and scale of a non-null decimal, float or integer literal.
+///
+/// Floats are read as their shortest decimal representation, so `0.1_f64` is `(1, 1)`
+/// and not the binary value it is stored as.
+fn decimal_parts(value: &ScalarValue) -> Option<(i256, i8)> {
+    match value {
+        ScalarValue::Int64(Some(v)) => Some((i256::from_i128(i128::from(*v)), 0)),
+        ScalarValue::Decimal128(Some(v), _, scale) => Some((i256::from_i128(*v), *scale)),
+        ScalarValue::Decimal256(Some(v), _, scale) => Some((*v, *scale)),
+        ScalarValue::Float32(Some(v)) if v.is_finite() => parse_decimal_parts(&v.to_string()),
+        ScalarValue::Float64(Some(v)) if v.is_finite() => parse_decimal_parts(&v.to_string()),
+        _ => None,
+    }
+}
+
+/// Parse a plain decimal number such as `-12.340` into its unscaled value and scale,
+/// ignoring trailing zeros of the fraction.
+fn parse_decimal_parts(value: &str) -> Option<(i256, i8)> {
+    let (negative, digits) = match value.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    if integer.is_empty() && fraction.is_empty()
+        || !integer
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let fraction = fraction.trim_end_matches('0');
+    let digits = format!("{integer}{fraction}");
+    let digits = digits.trim_start_matches('0');
+    if digits.len() > DECIMAL256_MAX_PRECISION as usize {
+        return None;
+    }
+    let unscaled = if digits.is_empty() {
+        i256::ZERO
+    } else {
+        i256::from_string(digits)?
+    };
+    let unscaled = if negative {
+        unscaled.wrapping_neg()
+    } else {
+        unscaled
+    };
+    Some((unscaled, i8::try_from(fraction.len()).ok()?))
+}
+
+/// The decimal `unscaled * 10^-scale` as a value of the decimal type `ty`, or `None` if
+/// it cannot be represented exactly.
+fn rescale_decimal(unscaled: i256, scale: i8, ty: &DataType) -> Option<ScalarValue> {
+    let (precision, target_scale) = match ty {
+        DataType::Decimal128(precision, scale) | DataType::Decimal256(precision, scale) => {
+            (*precision, *scale)
+        }
+        _ => return None,
+    };
+    let ten = i256::from_i128(10);
+    let shift = i32::from(target_scale) - i32::from(scale);
+    let rescaled = if shift >= 0 {
+        unscaled.checked_mul(ten.checked_pow(shift as u32)?)?
+    } else {
+        let divisor = ten.checked_pow(shift.unsigned_abs())?;
+        if unscaled.checked_rem(divisor)? != i256::ZERO {
+            return None;
+        }
+        unscaled.checked_div(divisor)?
+    };
+    if rescaled.checked_abs()? >= ten.checked_pow(u32::from(precision))? {
+        return None;
+    }
+    match ty {
+        DataType::Decimal128(_, _) => Some(ScalarValue::Decimal128(
+            Some(rescaled.to_i128()?),
+            precision,
+            target_scale,
+        )),
+        _ => Some(ScalarValue::Decimal256(
+            Some(rescaled),
+            precision,
+            target_scale,
+        )),
+    }
+}
+
+/// The decimal `unscaled * 10^-scale` with the smallest precision that holds it, as
+/// a Decimal256 if `wide` or if it does not fit a Decimal128.
+fn natural_decimal(unscaled: i256, scale: i8, wide: bool) -> Option<ScalarValue> {
+    let num_digits = unscaled
+        .checked_abs()?
+        .to_string()
+        .trim_start_matches('0')
+        .len();
+    let precision = u8::try_from(num_digits.max(scale.max(1) as usize)).ok()?;
+    let ty = if !wide && precision <= DECIMAL128_MAX_PRECISION {
+        DataType::Decimal128(precision, scale)
+    } else {
+        DataType::Decimal256(precision, scale)
+    };
+    rescale_decimal(unscaled, scale, &ty)
+}
+
+/// Parse a number literal such as `12.345` into a decimal literal of its own
+/// precision and scale.
+pub fn parse_decimal_literal(value: &str) -> Option<ScalarValue> {
+    let (unscaled, scale) = parse_decimal_parts(value)?;
+    natural_decimal(unscaled, scale, false)
+}
+
+/// The exact value of a numeric literal as a decimal literal comparable with the
+/// decimal type `ty`.
+///
+/// This is used when a literal compared with a decimal column does not fit the column's
+/// scale or precision, e.g. `price > 1.005` for a `Decimal128(10, 2)` column.  Instead of
+/// rounding the literal, both sides are then widened by DataFusion's decimal coercion,
+/// the same as in SQL.
+pub fn exact_decimal_literal(value: &ScalarValue, ty: &DataType) -> Option<ScalarValue> {
+    let wide = match ty {
+        DataType::Decimal128(_, _) => false,
+        DataType::Decimal256(_, _) => true,
+        _ => return None,
+    };
+    let (unscaled, scale) = decimal_parts(value)?;
+    natural_decimal(unscaled, scale, wide)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -775,4 +922,124 @@ mod tests {
             Some(ScalarValue::BinaryView(Some(vec![1, 2, 3])))
         );
     }
+
+    #[test]
+    fn test_decimal_coerce() {
+        let dec128 = DataType::Decimal128(38, 10);
+        // Decimal -> decimal is exact at any scale that can hold the value
+        assert_eq!(
+            safe_coerce_scalar(&ScalarValue::Decimal128(Some(105), 5, 2), &dec128),
+            Some(ScalarValue::Decimal128(Some(10_500_000_000), 38, 10))
+        );
+        assert_eq!(
+            safe_coerce_scalar(
+                &ScalarValue::Decimal128(Some(10_500_000_000), 38, 10),
+                &DataType::Decimal128(5, 2)
+            ),
+            Some(ScalarValue::Decimal128(Some(105), 5, 2))
+        );
+        assert_eq!(
+            safe_coerce_scalar(
+                &ScalarValue::Decimal128(Some(10_500_000_001), 38, 10),
+                &DataType::Decimal128(5, 2)
+            ),
+            None
+        );
+        // ... and within its precision
+        assert_eq!(
+            safe_coerce_scalar(
+                &ScalarValue::Decimal128(Some(100_000), 6, 0),
+                &DataType::Decimal128(5, 0)
+            ),
+            None
+        );
+        assert_eq!(
+            safe_coerce_scalar(
+                &ScalarValue::Decimal128(Some(-7), 3, 1),
+                &DataType::Decimal256(76, 20)
+            ),
+            Some(ScalarValue::Decimal256(
+                Some(i256::from_i128(-7 * 10_i128.pow(19))),
+                76,
+                20
+            ))
+        );
+        // Floats are read as the decimal they were written as
+        assert_eq!(
+            safe_coerce_scalar(&ScalarValue::Float64(Some(0.1)), &dec128),
+            Some(ScalarValue::Decimal128(Some(1_000_000_000), 38, 10))
+        );
+        assert_eq!(
+            safe_coerce_scalar(&ScalarValue::Float32(Some(-1.25)), &dec128),
+            Some(ScalarValue::Decimal128(Some(-12_500_000_000), 38, 10))
+        );
+        assert_eq!(
+            safe_coerce_scalar(&ScalarValue::Float64(Some(1.00000000001)), &dec128),
+            None
+        );
+        assert_eq!(
+            safe_coerce_scalar(&ScalarValue::Float64(Some(f64::NAN)), &dec128),
+            None
+        );
+        // Decimal -> float
+        assert_eq!(
+            safe_coerce_scalar(
+                &ScalarValue::Decimal128(Some(125), 5, 2),
+                &DataType::Float64
+            ),
+            Some(ScalarValue::Float64(Some(1.25)))
+        );
+    }
+
+    #[test]
+    fn test_exact_decimal_literal() {
+        assert_eq!(
+            parse_decimal_literal("-12.3400"),
+            Some(ScalarValue::Decimal128(Some(-1234), 4, 2))
+        );
+        assert_eq!(
+            parse_decimal_literal("0.001"),
+            Some(ScalarValue::Decimal128(Some(1), 3, 3))
+        );
+        assert_eq!(
+            parse_decimal_literal("0"),
+            Some(ScalarValue::Decimal128(Some(0), 1, 0))
+        );
+        assert_eq!(
+            parse_decimal_literal("12345678901234567890.123456789012345678901"),
+            Some(ScalarValue::Decimal256(
+                Some(i256::from_string("12345678901234567890123456789012345678901").unwrap()),
+                41,
+                21
+            ))
+        );
+        assert_eq!(parse_decimal_literal("1e5"), None);
+        assert_eq!(parse_decimal_literal("."), None);
+
+        let dec128 = DataType::Decimal128(38, 10);
+        assert_eq!(
+            exact_decimal_literal(&ScalarValue::Float64(Some(1.00000000001)), &dec128),
+            Some(ScalarValue::Decimal128(Some(100_000_000_001), 12, 11))
+        );
+        assert_eq!(
+            exact_decimal_literal(&ScalarValue::Int64(Some(-100_000)), &dec128),
+            Some(ScalarValue::Decimal128(Some(-100_000), 6, 0))
+        );
+        // Literals for Decimal256 columns are Decimal256 as well
+        assert_eq!(
+            exact_decimal_literal(
+                &ScalarValue::Decimal128(Some(1), 25, 25),
+                &DataType::Decimal256(76, 20)
+            ),
+            Some(ScalarValue::Decimal256(Some(i256::ONE), 25, 25))
+        );
+        assert_eq!(
+            exact_decimal_literal(&ScalarValue::Float64(None), &dec128),
+            None
+        );
+        assert_eq!(
+            exact_decimal_literal(&ScalarValue::Float64(Some(1.5)), &DataType::Float64),
+            None
+        );
+    }
 }
@@ -23,6 +23,7 @@ pub mod pb {
 }
 pub mod spill;
 pub mod sql;
+pub mod string_match;
 #[cfg(feature = "substrait")]
 pub mod substrait;
 pub mod udf;
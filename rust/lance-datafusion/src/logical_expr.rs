@@ -7,7 +7,7 @@ use std::sync::Arc;
 
 use arrow_schema::DataType;
 
-use crate::expr::safe_coerce_scalar;
+use crate::expr::{exact_decimal_literal, safe_coerce_scalar};
 use datafusion::logical_expr::{Between, ScalarUDF, ScalarUDFImpl};
 use datafusion::logical_expr::{BinaryExpr, Operator, expr::ScalarFunction};
 use datafusion::prelude::*;
@@ -21,9 +21,15 @@ use lance_core::{Error, Result};
 fn resolve_value(expr: &Expr, data_type: &DataType) -> Result<Expr> {
     match expr {
         Expr::Literal(scalar_value, metadata) => {
-            Ok(Expr::Literal(safe_coerce_scalar(scalar_value, data_type).ok_or_else(|| Error::invalid_input(format!("Received literal {expr} and could not convert to literal of type '{data_type:?}'")))?, metadata.clone()))
+            // Decimal literals that do not fit the column type are kept exact and
+            // compared using SQL decimal coercion rather than rounded.
+            let coerced = safe_coerce_scalar(scalar_value, data_type)
+                .or_else(|| exact_decimal_literal(scalar_value, data_type));
+            Ok(Expr::Literal(coerced.ok_or_else(|| Error::invalid_input(format!("Received literal {expr} and could not convert to literal of type '{data_type:?}'")))?, metadata.clone()))
         }
-        _ => Err(Error::invalid_input(format!("Expected a literal of type '{data_type:?}' but received: {expr}"))),
+        _ => Err(Error::invalid_input(format!(
+            "Expected a literal of type '{data_type:?}' but received: {expr}"
+        ))),
     }
 }
 
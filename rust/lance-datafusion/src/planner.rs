@@ -8,9 +8,10 @@ use std::collections::{BTreeSet, VecDeque};
 use std::sync::Arc;
 
 use crate::exec::{LanceExecutionOptions, get_session_context};
-use crate::expr::safe_coerce_scalar;
+use crate::expr::{parse_decimal_literal, safe_coerce_scalar};
 use crate::logical_expr::{coerce_filter_type_to_boolean, get_as_string_scalar_opt, resolve_expr};
 use crate::sql::{parse_sql_expr, parse_sql_filter};
+use crate::string_match::compile_string_matchers;
 use arrow::compute::CastOptions;
 use arrow_array::ListArray;
 use arrow_buffer::OffsetBuffer;
@@ -22,7 +23,7 @@ use datafusion::common::tree_node::{TreeNode, TreeNodeRecursion, TreeNodeVisitor
 use datafusion::config::ConfigOptions;
 use datafusion::error::Result as DFResult;
 use datafusion::execution::context::SessionState;
-use datafusion::logical_expr::expr::ScalarFunction;
+use datafusion::logical_expr::expr::{Placeholder, ScalarFunction};
 use datafusion::logical_expr::planner::{ExprPlanner, PlannerResult, RawFieldAccessExpr};
 use datafusion::logical_expr::{
     AggregateUDF, ColumnarValue, GetFieldAccess, ScalarFunctionArgs, ScalarUDF, ScalarUDFImpl,
@@ -378,12 +379,18 @@ impl Planner {
             Cow::Borrowed(value)
         };
         if let Ok(n) = value.parse::<i64>() {
-            Ok(lit(n))
-        } else {
-            value.parse::<f64>().map(lit).map_err(|_| {
-                Error::invalid_input(format!("'{value}' is not supported number value."))
-            })
+            return Ok(lit(n));
+        }
+        let float = value.parse::<f64>().map_err(|_| {
+            Error::invalid_input(format!("'{value}' is not supported number value."))
+        })?;
+        // Numbers with more significant digits than a f64 holds stay exact decimals
+        if let Some(decimal) = parse_decimal_literal(&value)
+            && parse_decimal_literal(&float.to_string()).as_ref() != Some(&decimal)
+        {
+            return Ok(lit(decimal));
         }
+        Ok(lit(float))
     }
 
     fn value(&self, value: &Value) -> Result<Expr> {
@@ -396,6 +403,10 @@ impl Planner {
             Value::DoubleQuotedString(s) => Expr::Literal(ScalarValue::Utf8(Some(s.clone())), None),
             Value::Boolean(v) => Expr::Literal(ScalarValue::Boolean(Some(*v)), None),
             Value::Null => Expr::Literal(ScalarValue::Null, None),
+            // The type is inferred from the context, see `parse_filter`
+            Value::Placeholder(id) => {
+                Expr::Placeholder(Placeholder::new_with_field(id.clone(), None))
+            }
             _ => todo!(),
         })
     }
@@ -850,9 +861,14 @@ impl Planner {
         let ast_expr = parse_sql_filter(filter)?;
         let expr = self.parse_sql_expr(&ast_expr)?;
         let schema = Schema::try_from(self.schema.as_ref())?;
-        let resolved = resolve_expr(&expr, &schema).map_err(|e| {
+        let mut resolved = resolve_expr(&expr, &schema).map_err(|e| {
             Error::invalid_input(format!("Error resolving filter expression {filter}: {e}"))
         })?;
+        // Placeholders, as in `x = $1`, take the type of what they are compared with
+        if resolved.exists(|expr| Ok(matches!(expr, Expr::Placeholder(_))))? {
+            let df_schema = DFSchema::try_from(self.schema.as_ref().clone())?;
+            resolved = resolved.infer_placeholder_types(&df_schema)?.0;
+        }
 
         Ok(coerce_filter_type_to_boolean(resolved))
     }
@@ -933,13 +949,17 @@ impl Planner {
     }
 
     /// Create the [`PhysicalExpr`] from a logical [`Expr`]
+    ///
+    /// String predicates with a literal pattern are compiled once here, see
+    /// [`compile_string_matchers`].
     pub fn create_physical_expr(&self, expr: &Expr) -> Result<Arc<dyn PhysicalExpr>> {
         let df_schema = Arc::new(DFSchema::try_from(self.schema.as_ref().clone())?);
-        Ok(datafusion::physical_expr::create_physical_expr(
+        let physical_expr = datafusion::physical_expr::create_physical_expr(
             expr,
             df_schema.as_ref(),
             &Default::default(),
-        )?)
+        )?;
+        Ok(compile_string_matchers(physical_expr, &self.schema)?)
     }
 
     /// Collect the columns in the expression.
@@ -1020,10 +1040,11 @@ mod tests {
 
     use arrow::datatypes::Float64Type;
     use arrow_array::{
-        ArrayRef, BooleanArray, Float32Array, Int32Array, Int64Array, RecordBatch, StringArray,
-        StructArray, TimestampMicrosecondArray, TimestampMillisecondArray,
-        TimestampNanosecondArray, TimestampSecondArray,
+        ArrayRef, BooleanArray, Decimal128Array, Decimal256Array, Float32Array, Int32Array,
+        Int64Array, RecordBatch, StringArray, StructArray, TimestampMicrosecondArray,
+        TimestampMillisecondArray, TimestampNanosecondArray, TimestampSecondArray,
     };
+    use arrow_buffer::i256;
     use arrow_schema::{DataType, Fields, Schema};
     use datafusion::{
         logical_expr::{Cast, col, lit},
@@ -1102,6 +1123,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_filter_placeholders() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("i", DataType::Int32, false),
+            Field::new("s", DataType::Utf8, true),
+        ]));
+        let planner = Planner::new(schema);
+
+        let expr = planner
+            .parse_filter("i BETWEEN $1 AND $2 AND s = $3")
+            .unwrap();
+        let mut placeholders = Vec::new();
+        expr.apply(|expr| {
+            if let Expr::Placeholder(placeholder) = expr {
+                placeholders.push((
+                    placeholder.id.clone(),
+                    placeholder.field.as_ref().map(|f| f.data_type().clone()),
+                ));
+            }
+            Ok(TreeNodeRecursion::Continue)
+        })
+        .unwrap();
+        assert_eq!(
+            placeholders,
+            vec![
+                ("$1".to_string(), Some(DataType::Int32)),
+                ("$2".to_string(), Some(DataType::Int32)),
+                ("$3".to_string(), Some(DataType::Utf8)),
+            ]
+        );
+    }
+
     #[derive(Debug, Eq, PartialEq, Hash)]
     struct StrictFloat64Udf {
         signature: Signature,
@@ -1365,6 +1418,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_string_predicates_are_compiled() {
+        use crate::string_match::{SelectiveAndExpr, StringMatchExpr};
+
+        let schema = Arc::new(Schema::new(vec![Field::new("s", DataType::Utf8, true)]));
+        let planner = Planner::new(schema.clone());
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![Arc::new(StringArray::from_iter_values(
+                (0..10).map(|v| format!("str-{}", v)),
+            ))],
+        )
+        .unwrap();
+
+        let expr = planner
+            .parse_filter("regexp_like(s, '^str-[0-3]$')")
+            .unwrap();
+        let physical_expr = planner.create_physical_expr(&expr).unwrap();
+        assert!(physical_expr.as_any().is::<StringMatchExpr>());
+        let predicates = physical_expr.evaluate(&batch).unwrap();
+        assert_eq!(
+            predicates.into_array(0).unwrap().as_ref(),
+            &BooleanArray::from(vec![
+                true, true, true, true, false, false, false, false, false, false
+            ])
+        );
+
+        let expr = planner
+            .parse_filter("starts_with(s, 'str') AND s ILIKE 'STR-_' AND s NOT LIKE '%5'")
+            .unwrap();
+        let physical_expr = planner.create_physical_expr(&expr).unwrap();
+        assert!(physical_expr.as_any().is::<SelectiveAndExpr>());
+        let predicates = physical_expr.evaluate(&batch).unwrap();
+        assert_eq!(
+            predicates.into_array(0).unwrap().as_ref(),
+            &BooleanArray::from(vec![
+                true, true, true, true, true, false, true, true, true, true
+            ])
+        );
+    }
+
     #[test]
     fn test_sql_is_in() {
         let schema = Arc::new(Schema::new(vec![Field::new("s", DataType::Utf8, true)]));
@@ -1767,6 +1861,62 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_sql_decimal_comparison() {
+        // 0.0, 0.1, ..., 0.9
+        let d128 = Decimal128Array::from_iter_values((0..10).map(|i| i * 1_000_000_000))
+            .with_precision_and_scale(38, 10)
+            .unwrap();
+        let d256 = Decimal256Array::from_iter_values(
+            (0..10).map(|i| i256::from_i128(i * 10_i128.pow(19))),
+        )
+        .with_precision_and_scale(76, 20)
+        .unwrap();
+        let batch = RecordBatch::try_from_iter(vec![
+            ("d128", Arc::new(d128) as ArrayRef),
+            ("d256", Arc::new(d256) as ArrayRef),
+        ])
+        .unwrap();
+        let planner = Planner::new(batch.schema());
+
+        let evaluate = |expression: &str| {
+            let logical_expr = planner.parse_filter(expression).unwrap();
+            let logical_expr = planner.optimize_expr(logical_expr).unwrap();
+            let physical_expr = planner.create_physical_expr(&logical_expr).unwrap();
+            physical_expr
+                .evaluate(&batch)
+                .unwrap()
+                .into_array(batch.num_rows())
+                .unwrap()
+        };
+
+        // Each expression is meant to select the final 5 rows, including literals with
+        // a larger scale than the column or more digits than a f64 holds.
+        let expected: ArrayRef = Arc::new(BooleanArray::from_iter(
+            std::iter::repeat_n(Some(false), 5).chain(std::iter::repeat_n(Some(true), 5)),
+        ));
+        for expression in [
+            "d128 >= 0.5",
+            "d128 > 0.4999999999",
+            "d128 > 0.49999999999",
+            "d128 > 0.40000000000000000001",
+            "0.5 <= d128",
+            "d256 > 0.49999999999999999999",
+            "d256 > 0.4000000000000000000000001",
+        ] {
+            assert_eq!(&expected, &evaluate(expression), "{expression}");
+        }
+
+        let expected: ArrayRef = Arc::new(BooleanArray::from_iter((0..10).map(|i| Some(i == 5))));
+        assert_eq!(&expected, &evaluate("d128 = 0.5"));
+        assert_eq!(
+            &expected,
+            &evaluate("d256 IN (0.5, 0.50000000000000000001)")
+        );
+        let expected: ArrayRef = Arc::new(BooleanArray::from(vec![false; 10]));
+        assert_eq!(&expected, &evaluate("d128 = 0.50000000001"));
+    }
+
     #[test]
     fn test_columns_in_expr() {
         let expr = col("s0").gt(lit("value")).and(
@@ -0,0 +1,977 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Pre-compiled string matchers for filters
+//!
+//! DataFusion evaluates `LIKE`, `starts_with` and regular expression predicates by
+//! building a matcher (often a regex) for every batch and then matching the rows one
+//! `&str` at a time.  [`compile_string_matchers`] replaces these expressions with a
+//! [`StringMatchExpr`] which builds the matcher once, when the filter is planned, and
+//! runs it over the offsets and values buffers of the string array.
+
+use std::any::Any;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use arrow::compute::kernels::boolean::and_kleene;
+use arrow_array::cast::AsArray;
+use arrow_array::{
+    Array, BooleanArray, GenericStringArray, OffsetSizeTrait, RecordBatch, StringViewArray,
+};
+use arrow_buffer::{ArrowNativeType, BooleanBuffer, BooleanBufferBuilder, NullBuffer};
+use arrow_schema::{DataType, Schema};
+use datafusion::logical_expr::{ColumnarValue, Operator};
+use datafusion::physical_plan::PhysicalExpr;
+use datafusion_common::tree_node::{Transformed, TransformedResult, TreeNode};
+use datafusion_common::{DataFusionError, Result};
+use datafusion_physical_expr::ScalarFunctionExpr;
+use datafusion_physical_expr::expressions::{BinaryExpr, Column, LikeExpr, Literal};
+use memchr::memchr3;
+use memchr::memmem::Finder;
+use regex::bytes::{Regex, RegexBuilder};
+
+/// A single way of matching the bytes of a string
+#[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
+enum Kernel {
+    Equals(Box<[u8]>),
+    StartsWith(Box<[u8]>),
+    EndsWith(Box<[u8]>),
+    Contains(Finder<'static>),
+    /// Only correct if both the pattern and the haystack are ASCII
+    IEqualsAscii(Box<[u8]>),
+    IStartsWithAscii(Box<[u8]>),
+    IEndsWithAscii(Box<[u8]>),
+    Regex(Regex),
+}
+
+/// A matcher for string values, compiled once and reused for every batch
+#[derive(Debug)]
+pub struct StringMatcher {
+    /// A cheaper kernel that gives the same result as `general` when the array is ASCII
+    ascii: Option<Kernel>,
+    general: Kernel,
+}
+
+impl StringMatcher {
+    /// Matches a SQL `LIKE` pattern, with the same semantics as arrow's `like` and
+    /// `ilike` kernels (`\` escapes, `_` matches a single character)
+    pub fn like(pattern: &str, case_insensitive: bool) -> Result<Self> {
+        if case_insensitive {
+            // ASCII case folding only agrees with unicode case folding when both sides
+            // are ASCII, e.g. 'k' also matches the Kelvin sign
+            let ascii = if !pattern.is_ascii() {
+                None
+            } else if !contains_like_pattern(pattern) {
+                Some(Kernel::IEqualsAscii(pattern.as_bytes().into()))
+            } else if let Some(prefix) = pattern.strip_suffix('%')
+                && !contains_like_pattern(prefix)
+            {
+                Some(Kernel::IStartsWithAscii(prefix.as_bytes().into()))
+            } else if let Some(suffix) = pattern.strip_prefix('%')
+                && !contains_like_pattern(suffix)
+            {
+                Some(Kernel::IEndsWithAscii(suffix.as_bytes().into()))
+            } else {
+                None
+            };
+            return Ok(Self {
+                ascii,
+                general: Kernel::Regex(like_to_regex(pattern, true)?),
+            });
+        }
+
+        let general = if !contains_like_pattern(pattern) {
+            Kernel::Equals(pattern.as_bytes().into())
+        } else if let Some(prefix) = pattern.strip_suffix('%')
+            && !contains_like_pattern(prefix)
+        {
+            Kernel::StartsWith(prefix.as_bytes().into())
+        } else if let Some(suffix) = pattern.strip_prefix('%')
+            && !contains_like_pattern(suffix)
+        {
+            Kernel::EndsWith(suffix.as_bytes().into())
+        } else if let Some(needle) = pattern
+            .strip_prefix('%')
+            .and_then(|pattern| pattern.strip_suffix('%'))
+            && !contains_like_pattern(needle)
+        {
+            Kernel::Contains(Finder::new(needle.as_bytes()).into_owned())
+        } else {
+            Kernel::Regex(like_to_regex(pattern, false)?)
+        };
+        Ok(Self {
+            ascii: None,
+            general,
+        })
+    }
+
+    pub fn starts_with(prefix: &str) -> Self {
+        Self {
+            ascii: None,
+            general: Kernel::StartsWith(prefix.as_bytes().into()),
+        }
+    }
+
+    pub fn ends_with(suffix: &str) -> Self {
+        Self {
+            ascii: None,
+            general: Kernel::EndsWith(suffix.as_bytes().into()),
+        }
+    }
+
+    pub fn contains(needle: &str) -> Self {
+        Self {
+            ascii: None,
+            general: Kernel::Contains(Finder::new(needle.as_bytes()).into_owned()),
+        }
+    }
+
+    /// Matches a regular expression anywhere in the string
+    pub fn regex(pattern: &str, case_insensitive: bool) -> Result<Self> {
+        // Same as arrow's `regexp_is_match_scalar`
+        let pattern = if case_insensitive {
+            format!("(?i){pattern}")
+        } else {
+            pattern.to_string()
+        };
+        let regex = Regex::new(&pattern).map_err(|e| {
+            DataFusionError::Execution(format!("Regular expression did not compile: {e:?}"))
+        })?;
+        Ok(Self {
+            ascii: None,
+            general: Kernel::Regex(regex),
+        })
+    }
+
+    /// Match every value of a string array
+    ///
+    /// If `selection` is given only the selected rows are matched and the other rows
+    /// are null in the result.  Null values are null in the result.
+    pub fn evaluate(
+        &self,
+        array: &dyn Array,
+        negated: bool,
+        selection: Option<&BooleanArray>,
+    ) -> Result<BooleanArray> {
+        if let Some(selection) = selection
+            && selection.len() != array.len()
+        {
+            return Err(DataFusionError::Internal(format!(
+                "Selection array length does not match the input length: {} != {}",
+                selection.len(),
+                array.len()
+            )));
+        }
+        // Null selections don't select the row
+        let selected = selection.map(|selection| match selection.nulls() {
+            Some(nulls) => selection.values() & nulls.inner(),
+            None => selection.values().clone(),
+        });
+
+        let values = match array.data_type() {
+            DataType::Utf8 => self.evaluate_offsets(array.as_string::<i32>(), negated, &selected),
+            DataType::LargeUtf8 => {
+                self.evaluate_offsets(array.as_string::<i64>(), negated, &selected)
+            }
+            DataType::Utf8View => {
+                let array = array.as_string_view();
+                let kernel = self.kernel(|| array.is_ascii());
+                kernel.evaluate(&ViewHaystacks(array), negated, &selected)
+            }
+            other => {
+                return Err(DataFusionError::Internal(format!(
+                    "String matchers can not be applied to {other}"
+                )));
+            }
+        };
+        let nulls = match selected {
+            Some(selected) => NullBuffer::union(array.nulls(), Some(&NullBuffer::new(selected))),
+            None => array.nulls().cloned(),
+        };
+        Ok(BooleanArray::new(values, nulls))
+    }
+
+    fn evaluate_offsets<O: OffsetSizeTrait>(
+        &self,
+        array: &GenericStringArray<O>,
+        negated: bool,
+        selected: &Option<BooleanBuffer>,
+    ) -> BooleanBuffer {
+        let kernel = self.kernel(|| array.is_ascii());
+        let haystacks = OffsetHaystacks {
+            offsets: array.value_offsets(),
+            values: array.value_data(),
+        };
+        kernel.evaluate(&haystacks, negated, selected)
+    }
+
+    fn kernel(&self, is_ascii: impl FnOnce() -> bool) -> &Kernel {
+        match &self.ascii {
+            Some(ascii) if is_ascii() => ascii,
+            _ => &self.general,
+        }
+    }
+}
+
+/// The bytes of each value of a string array, without UTF-8 validation
+trait Haystacks {
+    fn len(&self) -> usize;
+    fn get(&self, index: usize) -> &[u8];
+}
+
+struct OffsetHaystacks<'a, O> {
+    offsets: &'a [O],
+    values: &'a [u8],
+}
+
+impl<O: ArrowNativeType> Haystacks for OffsetHaystacks<'_, O> {
+    fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    #[inline]
+    fn get(&self, index: usize) -> &[u8] {
+        &self.values[self.offsets[index].as_usize()..self.offsets[index + 1].as_usize()]
+    }
+}
+
+struct ViewHaystacks<'a>(&'a StringViewArray);
+
+impl Haystacks for ViewHaystacks<'_> {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[inline]
+    fn get(&self, index: usize) -> &[u8] {
+        self.0.value(index).as_bytes()
+    }
+}
+
+impl Kernel {
+    fn evaluate(
+        &self,
+        haystacks: &impl Haystacks,
+        negated: bool,
+        selected: &Option<BooleanBuffer>,
+    ) -> BooleanBuffer {
+        // Dispatch once per array so each loop is specialized to a single kernel
+        match self {
+            Self::Equals(v) => collect(haystacks, selected, |h| (h == v.as_ref()) != negated),
+            Self::StartsWith(v) => collect(haystacks, selected, |h| h.starts_with(v) != negated),
+            Self::EndsWith(v) => collect(haystacks, selected, |h| h.ends_with(v) != negated),
+            Self::Contains(finder) => {
+                collect(haystacks, selected, |h| finder.find(h).is_some() != negated)
+            }
+            Self::IEqualsAscii(v) => collect(haystacks, selected, |h| {
+                h.eq_ignore_ascii_case(v) != negated
+            }),
+            Self::IStartsWithAscii(v) => collect(haystacks, selected, |h| {
+                (h.len() >= v.len() && h[..v.len()].eq_ignore_ascii_case(v)) != negated
+            }),
+            Self::IEndsWithAscii(v) => collect(haystacks, selected, |h| {
+                (h.len() >= v.len() && h[h.len() - v.len()..].eq_ignore_ascii_case(v)) != negated
+            }),
+            Self::Regex(regex) => collect(haystacks, selected, |h| regex.is_match(h) != negated),
+        }
+    }
+}
+
+fn collect(
+    haystacks: &impl Haystacks,
+    selected: &Option<BooleanBuffer>,
+    matches: impl Fn(&[u8]) -> bool,
+) -> BooleanBuffer {
+    match selected {
+        None => BooleanBuffer::collect_bool(haystacks.len(), |i| matches(haystacks.get(i))),
+        Some(selected) => {
+            let mut builder = BooleanBufferBuilder::new(haystacks.len());
+            builder.append_n(haystacks.len(), false);
+            for i in selected.set_indices() {
+                if matches(haystacks.get(i)) {
+                    builder.set_bit(i, true);
+                }
+            }
+            builder.finish()
+        }
+    }
+}
+
+fn contains_like_pattern(pattern: &str) -> bool {
+    memchr3(b'%', b'_', b'\\', pattern.as_bytes()).is_some()
+}
+
+/// Translate a `LIKE` pattern to a regex the same way arrow does
+fn like_to_regex(pattern: &str, case_insensitive: bool) -> Result<Regex> {
+    let mut result = String::with_capacity(pattern.len() * 2);
+    let mut chars = pattern.chars().peekable();
+    // A leading `%` is the same as an unanchored regex
+    if chars.peek() == Some(&'%') {
+        chars.next();
+    } else {
+        result.push('^');
+    }
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(next) => result.push_str(&regex::escape(next.encode_utf8(&mut [0; 4]))),
+                // A trailing backslash matches itself
+                None => result.push_str(r"\\"),
+            },
+            '%' => result.push_str(".*"),
+            '_' => result.push('.'),
+            c => result.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    if result.ends_with(".*") {
+        result.truncate(result.len() - 2);
+    } else {
+        result.push('$');
+    }
+    RegexBuilder::new(&result)
+        .case_insensitive(case_insensitive)
+        .dot_matches_new_line(true)
+        .build()
+        .map_err(|e| {
+            DataFusionError::Execution(format!("Unable to build regex from LIKE pattern: {e}"))
+        })
+}
+
+/// A string predicate with a pre-compiled [`StringMatcher`]
+///
+/// This is displayed, compared and hashed as the DataFusion expression it replaces.
+#[derive(Debug)]
+pub struct StringMatchExpr {
+    input: Arc<dyn PhysicalExpr>,
+    matcher: Arc<StringMatcher>,
+    negated: bool,
+    original: Arc<dyn PhysicalExpr>,
+}
+
+impl StringMatchExpr {
+    fn evaluate_input(
+        &self,
+        input: ColumnarValue,
+        batch: &RecordBatch,
+        selection: Option<&BooleanArray>,
+    ) -> Result<ColumnarValue> {
+        match input {
+            ColumnarValue::Array(array) => Ok(ColumnarValue::Array(Arc::new(
+                self.matcher.evaluate(&array, self.negated, selection)?,
+            ))),
+            // Constant inputs are left to DataFusion
+            ColumnarValue::Scalar(_) => match selection {
+                Some(selection) => self.original.evaluate_selection(batch, selection),
+                None => self.original.evaluate(batch),
+            },
+        }
+    }
+}
+
+impl fmt::Display for StringMatchExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.original.fmt(f)
+    }
+}
+
+impl PartialEq for StringMatchExpr {
+    fn eq(&self, other: &Self) -> bool {
+        self.original.as_ref() == other.original.as_ref()
+    }
+}
+
+impl Eq for StringMatchExpr {}
+
+impl Hash for StringMatchExpr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.original.hash(state);
+    }
+}
+
+impl PhysicalExpr for StringMatchExpr {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn data_type(&self, input_schema: &Schema) -> Result<DataType> {
+        self.original.data_type(input_schema)
+    }
+
+    fn nullable(&self, input_schema: &Schema) -> Result<bool> {
+        self.original.nullable(input_schema)
+    }
+
+    fn evaluate(&self, batch: &RecordBatch) -> Result<ColumnarValue> {
+        let input = self.input.evaluate(batch)?;
+        self.evaluate_input(input, batch, None)
+    }
+
+    fn evaluate_selection(
+        &self,
+        batch: &RecordBatch,
+        selection: &BooleanArray,
+    ) -> Result<ColumnarValue> {
+        if selection.true_count() == batch.num_rows() {
+            return self.evaluate(batch);
+        }
+        // Reading a whole column is free, other inputs may fail on rows that aren't selected
+        let input = if self.input.as_any().is::<Column>() {
+            self.input.evaluate(batch)?
+        } else {
+            self.input.evaluate_selection(batch, selection)?
+        };
+        self.evaluate_input(input, batch, Some(selection))
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn PhysicalExpr>> {
+        vec![&self.input]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn PhysicalExpr>>,
+    ) -> Result<Arc<dyn PhysicalExpr>> {
+        let input = children[0].clone();
+        // The input is always the first child of the original expression
+        let mut original_children = self
+            .original
+            .children()
+            .into_iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        original_children[0] = input.clone();
+        Ok(Arc::new(Self {
+            input,
+            matcher: self.matcher.clone(),
+            negated: self.negated,
+            original: self.original.clone().with_new_children(original_children)?,
+        }))
+    }
+
+    fn fmt_sql(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.original.fmt_sql(f)
+    }
+}
+
+/// `left AND right` where `right` is a [`StringMatchExpr`]
+///
+/// `right` is only evaluated for the rows that `left` doesn't rule out.  DataFusion's
+/// `AND` does this by copying those rows out of the batch, here they are passed to
+/// `right` as a selection vector instead.
+#[derive(Debug)]
+pub struct SelectiveAndExpr {
+    left: Arc<dyn PhysicalExpr>,
+    right: Arc<dyn PhysicalExpr>,
+    original: Arc<dyn PhysicalExpr>,
+}
+
+impl fmt::Display for SelectiveAndExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.original.fmt(f)
+    }
+}
+
+impl PartialEq for SelectiveAndExpr {
+    fn eq(&self, other: &Self) -> bool {
+        self.original.as_ref() == other.original.as_ref()
+    }
+}
+
+impl Eq for SelectiveAndExpr {}
+
+impl Hash for SelectiveAndExpr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.original.hash(state);
+    }
+}
+
+impl PhysicalExpr for SelectiveAndExpr {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn data_type(&self, input_schema: &Schema) -> Result<DataType> {
+        self.original.data_type(input_schema)
+    }
+
+    fn nullable(&self, input_schema: &Schema) -> Result<bool> {
+        self.original.nullable(input_schema)
+    }
+
+    fn evaluate(&self, batch: &RecordBatch) -> Result<ColumnarValue> {
+        let ColumnarValue::Array(left) = self.left.evaluate(batch)? else {
+            return self.original.evaluate(batch);
+        };
+        let left = left.as_boolean();
+        // Only rows where `left` is false are known to be false
+        let candidates = match left.nulls() {
+            Some(nulls) => left.values() | &!nulls.inner(),
+            None => left.values().clone(),
+        };
+        if candidates.count_set_bits() == 0 {
+            return Ok(ColumnarValue::Array(Arc::new(left.clone())));
+        }
+        let right = self
+            .right
+            .evaluate_selection(batch, &BooleanArray::new(candidates, None))?
+            .into_array(batch.num_rows())?;
+        Ok(ColumnarValue::Array(Arc::new(and_kleene(
+            left,
+            right.as_boolean(),
+        )?)))
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn PhysicalExpr>> {
+        vec![&self.left, &self.right]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        children: Vec<Arc<dyn PhysicalExpr>>,
+    ) -> Result<Arc<dyn PhysicalExpr>> {
+        Ok(Arc::new(Self {
+            left: children[0].clone(),
+            right: children[1].clone(),
+            original: self.original.clone().with_new_children(children)?,
+        }))
+    }
+
+    fn fmt_sql(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.original.fmt_sql(f)
+    }
+}
+
+/// Replace the string predicates in `expr` that match a string column against a
+/// literal pattern with [`StringMatchExpr`]s
+///
+/// This covers `LIKE` / `ILIKE`, the regex match operators, and the `starts_with`,
+/// `ends_with`, `contains` and `regexp_like` functions.  Predicates with a pattern
+/// that fails to compile are left as they are, so DataFusion reports the error.
+pub fn compile_string_matchers(
+    expr: Arc<dyn PhysicalExpr>,
+    schema: &Schema,
+) -> Result<Arc<dyn PhysicalExpr>> {
+    expr.transform_up(|expr| {
+        if let Some(compiled) = compile_string_match(&expr, schema)? {
+            return Ok(Transformed::yes(compiled));
+        }
+        if let Some(binary) = expr.as_any().downcast_ref::<BinaryExpr>()
+            && *binary.op() == Operator::And
+            && binary.right().as_any().is::<StringMatchExpr>()
+        {
+            return Ok(Transformed::yes(Arc::new(SelectiveAndExpr {
+                left: binary.left().clone(),
+                right: binary.right().clone(),
+                original: expr.clone(),
+            })));
+        }
+        Ok(Transformed::no(expr))
+    })
+    .data()
+}
+
+fn compile_string_match(
+    expr: &Arc<dyn PhysicalExpr>,
+    schema: &Schema,
+) -> Result<Option<Arc<dyn PhysicalExpr>>> {
+    let any = expr.as_any();
+    let (input, matcher, negated) = if let Some(like) = any.downcast_ref::<LikeExpr>() {
+        let Some(pattern) = literal_str(like.pattern()) else {
+            return Ok(None);
+        };
+        (
+            like.expr(),
+            StringMatcher::like(pattern, like.case_insensitive()),
+            like.negated(),
+        )
+    } else if let Some(binary) = any.downcast_ref::<BinaryExpr>() {
+        let (case_insensitive, negated) = match binary.op() {
+            Operator::RegexMatch => (false, false),
+            Operator::RegexIMatch => (true, false),
+            Operator::RegexNotMatch => (false, true),
+            Operator::RegexNotIMatch => (true, true),
+            _ => return Ok(None),
+        };
+        let Some(pattern) = literal_str(binary.right()) else {
+            return Ok(None);
+        };
+        (
+            binary.left(),
+            StringMatcher::regex(pattern, case_insensitive),
+            negated,
+        )
+    } else if let Some(func) = any.downcast_ref::<ScalarFunctionExpr>() {
+        let [input, pattern] = func.args() else {
+            return Ok(None);
+        };
+        let Some(pattern) = literal_str(pattern) else {
+            return Ok(None);
+        };
+        let matcher = match func.name() {
+            "starts_with" => Ok(StringMatcher::starts_with(pattern)),
+            "ends_with" => Ok(StringMatcher::ends_with(pattern)),
+            "contains" => Ok(StringMatcher::contains(pattern)),
+            "regexp_like" => StringMatcher::regex(pattern, false),
+            _ => return Ok(None),
+        };
+        (input, matcher, false)
+    } else {
+        return Ok(None);
+    };
+
+    if !matches!(
+        input.data_type(schema)?,
+        DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View
+    ) {
+        return Ok(None);
+    }
+    let Ok(matcher) = matcher else {
+        return Ok(None);
+    };
+    Ok(Some(Arc::new(StringMatchExpr {
+        input: input.clone(),
+        matcher: Arc::new(matcher),
+        negated,
+        original: expr.clone(),
+    })))
+}
+
+fn literal_str(expr: &Arc<dyn PhysicalExpr>) -> Option<&str> {
+    expr.as_any()
+        .downcast_ref::<Literal>()?
+        .value()
+        .try_as_str()
+        .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use arrow_array::{ArrayRef, BooleanArray, Int32Array, StringArray};
+    use arrow_schema::Field;
+    use datafusion::config::ConfigOptions;
+    use datafusion::scalar::ScalarValue;
+    use datafusion_physical_expr::expressions::{binary, col, like};
+
+    use super::*;
+
+    const VALUES: &[Option<&str>] = &[
+        Some(""),
+        Some("a"),
+        Some("A"),
+        Some("abc"),
+        Some("ABC"),
+        Some("a_c"),
+        Some("a%c"),
+        Some(r"a\c"),
+        Some("k"),
+        Some("K"),
+        // Kelvin sign, which folds to 'k'
+        Some("\u{212A}"),
+        Some("\u{212A}elvin"),
+        Some("kelvin"),
+        Some("ß"),
+        Some("ẞ"),
+        Some("ss"),
+        Some("straße"),
+        Some("STRASSE"),
+        Some("İstanbul"),
+        Some("istanbul"),
+        Some("é"),
+        Some("É"),
+        // e followed by a combining acute accent
+        Some("e\u{301}"),
+        Some("日本語"),
+        Some("🦀 rust"),
+        Some("ΣΊΣΥΦΟΣ"),
+        Some("σίσυφος"),
+        Some("a\nb"),
+        Some("a much longer string that is not inlined in a view, with \u{212A}"),
+        None,
+    ];
+
+    const LIKE_PATTERNS: &[&str] = &[
+        "", "%", "%%", "_", "__", "a%", "A%", "%c", "%b%", "a_c", r"a\_c", r"a\%c", r"a\\c", r"\",
+        "k", "k%", "K%", "%ELVIN", "ß", "ß%", "SS", "STRAßE", "%tanbul", "é", "É", "e_", "_本%",
+        "%本%", "🦀%", "σ%", "%Σ", "a%b", "%with _",
+    ];
+
+    const REGEX_PATTERNS: &[&str] = &[
+        "",
+        "^a",
+        "k",
+        "^k$",
+        "ß",
+        "^.$",
+        "日本",
+        "^[[:alpha:]]+$",
+        r"\w+ \w+",
+        "σ",
+        "^$",
+        "a.b",
+    ];
+
+    fn arrays() -> Vec<ArrayRef> {
+        let ascii = VALUES
+            .iter()
+            .filter(|value| value.is_none_or(|value| value.is_ascii()))
+            .copied()
+            .collect::<StringArray>();
+        let all = VALUES.iter().copied().collect::<StringArray>();
+        let mut arrays = Vec::new();
+        for array in [all, ascii] {
+            let array: ArrayRef = Arc::new(array);
+            let sliced = array.slice(1, array.len() - 2);
+            for array in [array, sliced] {
+                for data_type in [DataType::Utf8, DataType::LargeUtf8, DataType::Utf8View] {
+                    arrays.push(arrow::compute::cast(&array, &data_type).unwrap());
+                }
+            }
+        }
+        arrays
+    }
+
+    fn batch(array: &ArrayRef) -> RecordBatch {
+        let schema = Schema::new(vec![
+            Field::new("s", array.data_type().clone(), true),
+            Field::new("i", DataType::Int32, false),
+        ]);
+        let ints = Int32Array::from_iter_values(0..array.len() as i32);
+        RecordBatch::try_new(Arc::new(schema), vec![array.clone(), Arc::new(ints)]).unwrap()
+    }
+
+    fn literal(pattern: &str, data_type: &DataType) -> Arc<dyn PhysicalExpr> {
+        let value = ScalarValue::from(pattern).cast_to(data_type).unwrap();
+        Arc::new(Literal::new(value))
+    }
+
+    fn evaluate(expr: &Arc<dyn PhysicalExpr>, batch: &RecordBatch) -> BooleanArray {
+        let result = expr.evaluate(batch).unwrap();
+        result
+            .into_array(batch.num_rows())
+            .unwrap()
+            .as_boolean()
+            .clone()
+    }
+
+    /// Check that the compiled expression gives the same results as DataFusion
+    fn check_compiled(original: Arc<dyn PhysicalExpr>, batch: &RecordBatch) {
+        let compiled = compile_string_matchers(original.clone(), batch.schema_ref()).unwrap();
+        assert!(
+            compiled.as_any().is::<StringMatchExpr>(),
+            "{original} was not compiled"
+        );
+        assert_eq!(compiled.to_string(), original.to_string());
+        assert_eq!(
+            evaluate(&compiled, batch),
+            evaluate(&original, batch),
+            "{original} on {:?}",
+            batch.column(0)
+        );
+
+        let selection = (0..batch.num_rows())
+            .map(|i| match i % 3 {
+                0 => Some(true),
+                1 => Some(false),
+                _ => None,
+            })
+            .collect::<BooleanArray>();
+        let expected = original.evaluate_selection(batch, &selection).unwrap();
+        let actual = compiled.evaluate_selection(batch, &selection).unwrap();
+        assert_eq!(
+            actual.into_array(batch.num_rows()).unwrap().as_boolean(),
+            expected.into_array(batch.num_rows()).unwrap().as_boolean(),
+            "{original} on {:?} with selection",
+            batch.column(0)
+        );
+    }
+
+    #[test]
+    fn test_like_matches_datafusion() {
+        for array in arrays() {
+            let batch = batch(&array);
+            let schema = batch.schema();
+            for pattern in LIKE_PATTERNS {
+                for negated in [false, true] {
+                    for case_insensitive in [false, true] {
+                        let original = like(
+                            negated,
+                            case_insensitive,
+                            col("s", &schema).unwrap(),
+                            literal(pattern, array.data_type()),
+                            &schema,
+                        )
+                        .unwrap();
+                        check_compiled(original, &batch);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_regex_matches_datafusion() {
+        for array in arrays() {
+            let batch = batch(&array);
+            let schema = batch.schema();
+            for pattern in REGEX_PATTERNS {
+                for op in [
+                    Operator::RegexMatch,
+                    Operator::RegexIMatch,
+                    Operator::RegexNotMatch,
+                    Operator::RegexNotIMatch,
+                ] {
+                    let original = binary(
+                        col("s", &schema).unwrap(),
+                        op,
+                        literal(pattern, array.data_type()),
+                        &schema,
+                    )
+                    .unwrap();
+                    check_compiled(original, &batch);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_functions_match_datafusion() {
+        let functions = [
+            (datafusion_functions::string::starts_with(), LIKE_PATTERNS),
+            (datafusion_functions::string::ends_with(), LIKE_PATTERNS),
+            (datafusion_functions::string::contains(), LIKE_PATTERNS),
+            (datafusion_functions::regex::regexp_like(), REGEX_PATTERNS),
+        ];
+        for array in arrays() {
+            let batch = batch(&array);
+            let schema = batch.schema();
+            for (func, patterns) in &functions {
+                for pattern in *patterns {
+                    let original = Arc::new(
+                        ScalarFunctionExpr::try_new(
+                            func.clone(),
+                            vec![
+                                col("s", &schema).unwrap(),
+                                literal(pattern, array.data_type()),
+                            ],
+                            &schema,
+                            Arc::new(ConfigOptions::default()),
+                        )
+                        .unwrap(),
+                    );
+                    check_compiled(original, &batch);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_uncompiled_expressions() {
+        let array: ArrayRef = Arc::new(VALUES.iter().copied().collect::<StringArray>());
+        let batch = batch(&array);
+        let schema = batch.schema();
+        let s = col("s", &schema).unwrap();
+
+        // Patterns that aren't literals, and invalid regexes, are left to DataFusion
+        let column_pattern = like(false, false, s.clone(), s.clone(), &schema).unwrap();
+        let invalid_regex = binary(
+            s.clone(),
+            Operator::RegexMatch,
+            literal("(", &DataType::Utf8),
+            &schema,
+        )
+        .unwrap();
+        let null_pattern = Arc::new(Literal::new(ScalarValue::Utf8(None)));
+        let null_like = like(false, false, s.clone(), null_pattern, &schema).unwrap();
+        for expr in [column_pattern, invalid_regex, null_like] {
+            let compiled = compile_string_matchers(expr.clone(), &schema).unwrap();
+            assert!(Arc::ptr_eq(&compiled, &expr), "{compiled}");
+        }
+    }
+
+    #[test]
+    fn test_selective_and() {
+        let array: ArrayRef = Arc::new(VALUES.iter().copied().collect::<StringArray>());
+        let batch = batch(&array);
+        let schema = batch.schema();
+
+        for (op, value) in [
+            (Operator::Lt, ScalarValue::Int32(Some(10))),
+            (Operator::Lt, ScalarValue::Int32(Some(0))),
+            (Operator::Lt, ScalarValue::Int32(None)),
+        ] {
+            let left = binary(
+                col("i", &schema).unwrap(),
+                op,
+                Arc::new(Literal::new(value)),
+                &schema,
+            )
+            .unwrap();
+            let right = like(
+                false,
+                true,
+                col("s", &schema).unwrap(),
+                literal("%s%", &DataType::Utf8),
+                &schema,
+            )
+            .unwrap();
+            let original = binary(left, Operator::And, right, &schema).unwrap();
+            let compiled = compile_string_matchers(original.clone(), &schema).unwrap();
+            assert!(compiled.as_any().is::<SelectiveAndExpr>());
+            assert_eq!(compiled.to_string(), original.to_string());
+            assert_eq!(evaluate(&compiled, &batch), evaluate(&original, &batch));
+        }
+    }
+
+    fn time_batches(expr: &Arc<dyn PhysicalExpr>, batches: &[RecordBatch]) -> Duration {
+        (0..3)
+            .map(|_| {
+                let start = Instant::now();
+                for batch in batches {
+                    expr.evaluate(batch).unwrap();
+                }
+                start.elapsed()
+            })
+            .min()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_compiled_matcher_per_batch_time() {
+        // Many small batches of unicode strings, where DataFusion builds a unicode case
+        // insensitive regex for every batch
+        let batches = (0..200)
+            .map(|b| {
+                let array: ArrayRef = Arc::new(StringArray::from_iter_values(
+                    (0..64).map(|i| format!("row {b}-{i} straße")),
+                ));
+                batch(&array)
+            })
+            .collect::<Vec<_>>();
+        let schema = batches[0].schema();
+        let original = like(
+            false,
+            true,
+            col("s", &schema).unwrap(),
+            literal("row 1_-%STRASSE", &DataType::Utf8),
+            &schema,
+        )
+        .unwrap();
+        let compiled = compile_string_matchers(original.clone(), &schema).unwrap();
+        for batch in &batches {
+            assert_eq!(evaluate(&compiled, batch), evaluate(&original, batch));
+        }
+
+        let original_time = time_batches(&original, &batches);
+        let compiled_time = time_batches(&compiled, &batches);
+        assert!(
+            compiled_time < original_time,
+            "compiled: {:?} per batch, DataFusion: {:?} per batch",
+            compiled_time / batches.len() as u32,
+            original_time / batches.len() as u32,
+        );
+    }
+}
@@ -6,18 +6,26 @@ use datafusion::{execution::SessionState, logical_expr::Expr};
 
 use crate::aggregate::Aggregate;
 use datafusion_common::DFSchema;
+use datafusion_common::DataFusionError;
 use datafusion_substrait::extensions::Extensions;
 use datafusion_substrait::logical_plan::consumer::{
     DefaultSubstraitConsumer, from_substrait_agg_func, from_substrait_rex, from_substrait_sorts,
+    substrait_fun_name,
 };
 use datafusion_substrait::substrait::proto::{
-    AggregateRel, Expression, ExpressionReference, ExtendedExpression, NamedStruct, Plan, Type,
+    AggregateRel, Expression, ExpressionReference, ExtendedExpression, FunctionArgument,
+    NamedStruct, Plan, Type,
     expression::{
-        RexType,
+        Literal, RexType, ScalarFunction,
         field_reference::{ReferenceType, RootType},
-        reference_segment,
+        literal::LiteralType,
+        reference_segment::{self, StructField},
     },
     expression_reference::ExprType,
+    extensions::{
+        SimpleExtensionDeclaration,
+        simple_extension_declaration::{ExtensionFunction, MappingType},
+    },
     function_argument::ArgType,
     rel::RelType,
     r#type::{Kind, Struct},
@@ -107,11 +115,19 @@ fn remove_extension_types(
     }
     let mut kept_substrait_fields = Vec::with_capacity(fields.types.len());
     let mut kept_arrow_fields = Vec::with_capacity(arrow_schema.fields.len());
+    // Names are listed depth-first so nested fields are included in this mapping
     let mut index_mapping = HashMap::with_capacity(arrow_schema.fields.len());
+    // Field references index top-level fields by position
+    let mut field_mapping = HashMap::with_capacity(arrow_schema.fields.len());
     let mut field_counter = 0;
     let mut field_index = 0;
     // TODO: this logic doesn't catch user defined fields inside of struct fields
-    for (substrait_field, arrow_field) in fields.types.iter().zip(arrow_schema.fields.iter()) {
+    for (position, (substrait_field, arrow_field)) in fields
+        .types
+        .iter()
+        .zip(arrow_schema.fields.iter())
+        .enumerate()
+    {
         let num_fields = count_fields(substrait_field);
 
         let kind = substrait_field.kind.as_ref().unwrap();
@@ -126,6 +142,7 @@ fn remove_extension_types(
         if !substrait_schema.names[field_index].starts_with("__unlikely_name_placeholder")
             && !is_user_defined
         {
+            field_mapping.insert(position, kept_arrow_fields.len());
             kept_substrait_fields.push(substrait_field.clone());
             kept_arrow_fields.push(arrow_field.clone());
             for i in 0..num_fields {
@@ -150,10 +167,14 @@ fn remove_extension_types(
             types: kept_substrait_fields,
         }),
     };
-    Ok((new_substrait_schema, new_arrow_schema, index_mapping))
+    Ok((new_substrait_schema, new_arrow_schema, field_mapping))
 }
 
-fn remap_expr_references(expr: &mut Expression, mapping: &HashMap<usize, usize>) -> Result<()> {
+/// Calls `f` on every field reference in `expr` that refers to the input schema
+fn visit_field_references(
+    expr: &mut Expression,
+    f: &mut impl FnMut(&mut Expression) -> Result<()>,
+) -> Result<()> {
     match expr.rex_type.as_mut().unwrap() {
         // Simple, no field references possible
         RexType::Literal(_) | RexType::Nested(_) | RexType::DynamicParameter(_) => Ok(()),
@@ -168,11 +189,11 @@ fn remap_expr_references(expr: &mut Expression, mapping: &HashMap<usize, usize>)
         RexType::ScalarFunction(func) => {
             #[allow(deprecated)]
             for arg in &mut func.args {
-                remap_expr_references(arg, mapping)?;
+                visit_field_references(arg, f)?;
             }
             for arg in &mut func.arguments {
                 match arg.arg_type.as_mut().unwrap() {
-                    ArgType::Value(expr) => remap_expr_references(expr, mapping)?,
+                    ArgType::Value(expr) => visit_field_references(expr, f)?,
                     ArgType::Enum(_) | ArgType::Type(_) => {}
                 }
             }
@@ -180,82 +201,222 @@ fn remap_expr_references(expr: &mut Expression, mapping: &HashMap<usize, usize>)
         }
         RexType::IfThen(ifthen) => {
             for clause in ifthen.ifs.iter_mut() {
-                remap_expr_references(clause.r#if.as_mut().unwrap(), mapping)?;
-                remap_expr_references(clause.then.as_mut().unwrap(), mapping)?;
+                visit_field_references(clause.r#if.as_mut().unwrap(), f)?;
+                visit_field_references(clause.then.as_mut().unwrap(), f)?;
             }
-            remap_expr_references(ifthen.r#else.as_mut().unwrap(), mapping)?;
+            visit_field_references(ifthen.r#else.as_mut().unwrap(), f)?;
             Ok(())
         }
         RexType::SwitchExpression(switch) => {
             for clause in switch.ifs.iter_mut() {
-                remap_expr_references(clause.then.as_mut().unwrap(), mapping)?;
+                visit_field_references(clause.then.as_mut().unwrap(), f)?;
             }
-            remap_expr_references(switch.r#else.as_mut().unwrap(), mapping)?;
+            visit_field_references(switch.r#else.as_mut().unwrap(), f)?;
             Ok(())
         }
         RexType::SingularOrList(orlist) => {
             for opt in orlist.options.iter_mut() {
-                remap_expr_references(opt, mapping)?;
+                visit_field_references(opt, f)?;
             }
-            remap_expr_references(orlist.value.as_mut().unwrap(), mapping)?;
+            visit_field_references(orlist.value.as_mut().unwrap(), f)?;
             Ok(())
         }
         RexType::MultiOrList(orlist) => {
             for opt in orlist.options.iter_mut() {
                 for field in opt.fields.iter_mut() {
-                    remap_expr_references(field, mapping)?;
+                    visit_field_references(field, f)?;
                 }
             }
             for val in orlist.value.iter_mut() {
-                remap_expr_references(val, mapping)?;
+                visit_field_references(val, f)?;
             }
             Ok(())
         }
         RexType::Cast(cast) => {
-            remap_expr_references(cast.input.as_mut().unwrap(), mapping)?;
+            visit_field_references(cast.input.as_mut().unwrap(), f)?;
             Ok(())
         }
         RexType::Selection(sel) => {
             // Finally, the selection, which might actually have field references
             let root_type = sel.root_type.as_mut().unwrap();
-            // These types of references do not reference input fields so no remap needed
+            // These types of references do not reference input fields
             if matches!(
                 root_type,
                 RootType::Expression(_) | RootType::OuterReference(_)
             ) {
                 return Ok(());
             }
-            match sel.reference_type.as_mut().unwrap() {
-                ReferenceType::DirectReference(direct) => {
-                    match direct.reference_type.as_mut().unwrap() {
-                        reference_segment::ReferenceType::ListElement(_)
-                        | reference_segment::ReferenceType::MapKey(_) => Err(Error::invalid_input(
-                            "map/list nested references not supported in pushdown filters",
-                        )),
-                        reference_segment::ReferenceType::StructField(field) => {
-                            if field.child.is_some() {
-                                Err(Error::invalid_input(
-                                    "nested references in pushdown filters not yet supported",
-                                ))
-                            } else {
-                                if let Some(new_index) = mapping.get(&(field.field as usize)) {
-                                    field.field = *new_index as i32;
-                                } else {
-                                    return Err(Error::invalid_input(
-                                        "pushdown filter referenced a field that is not yet supported by Substrait conversion",
-                                    ));
-                                }
-                                Ok(())
-                            }
-                        }
-                    }
+            f(expr)
+        }
+    }
+}
+
+/// The top-level struct field of a field reference, and any nested struct fields below it
+fn struct_field_reference(expr: &mut Expression) -> Result<&mut StructField> {
+    let Some(RexType::Selection(sel)) = expr.rex_type.as_mut() else {
+        return Err(Error::internal(
+            "visit_field_references only visits selections",
+        ));
+    };
+    match sel.reference_type.as_mut().unwrap() {
+        ReferenceType::DirectReference(direct) => match direct.reference_type.as_mut().unwrap() {
+            reference_segment::ReferenceType::ListElement(_)
+            | reference_segment::ReferenceType::MapKey(_) => Err(Error::invalid_input(
+                "map/list nested references not supported in pushdown filters",
+            )),
+            reference_segment::ReferenceType::StructField(field) => Ok(field),
+        },
+        ReferenceType::MaskedReference(_) => Err(Error::invalid_input(
+            "masked references not yet supported in filter expressions",
+        )),
+    }
+}
+
+fn remap_expr_references(expr: &mut Expression, mapping: &HashMap<usize, usize>) -> Result<()> {
+    visit_field_references(expr, &mut |selection| {
+        // Only top-level fields are removed so nested references stay the same
+        let field = struct_field_reference(selection)?;
+        if let Some(new_index) = mapping.get(&(field.field as usize)) {
+            field.field = *new_index as i32;
+            Ok(())
+        } else {
+            Err(Error::invalid_input(
+                "pushdown filter referenced a field that is not yet supported by Substrait conversion",
+            ))
+        }
+    })
+}
+
+/// Rewrites references to nested struct fields into `get_field` calls
+///
+/// The DataFusion consumer only supports references to top-level fields.  A reference
+/// such as `s.b.c` is converted to `get_field(get_field(s, 'b'), 'c')`, which is also how
+/// the DataFusion producer encodes nested fields.  Returns true if anything was rewritten.
+fn bind_nested_references(
+    expr: &mut Expression,
+    schema: &ArrowSchema,
+    get_field_anchor: u32,
+) -> Result<bool> {
+    let mut rewritten = false;
+    visit_field_references(expr, &mut |selection| {
+        let field = struct_field_reference(selection)?;
+        let Some(mut child) = field.child.take() else {
+            return Ok(());
+        };
+        let top_level = schema.fields().get(field.field as usize).ok_or_else(|| {
+            Error::invalid_input(format!(
+                "filter referenced field {} but the schema only has {} fields",
+                field.field,
+                schema.fields().len()
+            ))
+        })?;
+        let mut current = top_level.clone();
+        let mut path = top_level.name().clone();
+        loop {
+            let nested = match child.reference_type.as_mut().unwrap() {
+                reference_segment::ReferenceType::StructField(nested) => nested,
+                reference_segment::ReferenceType::ListElement(_)
+                | reference_segment::ReferenceType::MapKey(_) => {
+                    return Err(Error::invalid_input(
+                        "map/list nested references not supported in pushdown filters",
+                    ));
                 }
-                ReferenceType::MaskedReference(_) => Err(Error::invalid_input(
-                    "masked references not yet supported in filter expressions",
-                )),
+            };
+            let DataType::Struct(children) = current.data_type() else {
+                return Err(Error::invalid_input(format!(
+                    "filter referenced a child of '{}' which is not a struct",
+                    path
+                )));
+            };
+            let nested_field = children
+                .get(nested.field as usize)
+                .ok_or_else(|| {
+                    Error::invalid_input(format!(
+                        "filter referenced child {} of '{}' which only has {} children",
+                        nested.field,
+                        path,
+                        children.len()
+                    ))
+                })?
+                .clone();
+            let parent = std::mem::take(selection);
+            *selection = get_field_call(parent, nested_field.name(), get_field_anchor);
+            path = format!("{}.{}", path, nested_field.name());
+            current = nested_field;
+            match nested.child.take() {
+                Some(next) => child = next,
+                None => break,
+            }
+        }
+        rewritten = true;
+        Ok(())
+    })?;
+    Ok(rewritten)
+}
+
+fn get_field_call(input: Expression, name: &str, function_reference: u32) -> Expression {
+    let arguments = vec![
+        FunctionArgument {
+            arg_type: Some(ArgType::Value(input)),
+        },
+        FunctionArgument {
+            arg_type: Some(ArgType::Value(Expression {
+                rex_type: Some(RexType::Literal(Literal {
+                    nullable: false,
+                    type_variation_reference: 0,
+                    literal_type: Some(LiteralType::String(name.to_string())),
+                })),
+            })),
+        },
+    ];
+    Expression {
+        rex_type: Some(RexType::ScalarFunction(ScalarFunction {
+            function_reference,
+            arguments,
+            ..Default::default()
+        })),
+    }
+}
+
+/// The URI of the extension that declares `func`, if the message includes it
+fn function_extension_uri(
+    envelope: &ExtendedExpression,
+    func: &ExtensionFunction,
+) -> Option<String> {
+    let urn = envelope
+        .extension_urns
+        .iter()
+        .find(|urn| urn.extension_urn_anchor == func.extension_urn_reference)
+        .map(|urn| urn.urn.clone());
+    // Older producers only set the (deprecated) URI
+    #[allow(deprecated)]
+    urn.or_else(|| {
+        envelope
+            .extension_uris
+            .iter()
+            .find(|uri| uri.extension_uri_anchor == func.extension_uri_reference)
+            .map(|uri| uri.uri.clone())
+    })
+}
+
+/// Replaces DataFusion's error for an unknown function with one that names its extension URI
+fn unsupported_function_error(envelope: &ExtendedExpression, err: DataFusionError) -> Error {
+    if let DataFusionError::NotImplemented(msg) = &err {
+        for ext in &envelope.extensions {
+            let Some(MappingType::ExtensionFunction(func)) = &ext.mapping_type else {
+                continue;
+            };
+            let name = substrait_fun_name(&func.name);
+            if msg.contains(&format!("Unsupported function name: {:?}", name)) {
+                return Error::not_supported(format!(
+                    "Substrait function '{}' from extension '{}' is not supported",
+                    func.name,
+                    function_extension_uri(envelope, func).unwrap_or_default()
+                ));
             }
         }
     }
+    err.into()
 }
 
 /// Convert a Substrait ExtendedExpressions message into a DF Expr
@@ -294,28 +455,52 @@ pub async fn parse_substrait(
     // The Substrait may have come from a producer that uses extension types that DF doesn't support (e.g.
     // from pyarrow) so we need to remove them and remap expr references (since they are indexes into the
     // schema and we may have removed some fields)
-    let substrait_schema = if envelope.base_schema.as_ref().unwrap().r#struct.is_some() {
-        let (substrait_schema, _, index_mapping) =
-            remove_extension_types(envelope.base_schema.as_ref().unwrap(), input_schema.clone())?;
-
-        if substrait_schema.r#struct.as_ref().unwrap().types.len()
-            != envelope
-                .base_schema
-                .as_ref()
-                .unwrap()
-                .r#struct
-                .as_ref()
-                .unwrap()
-                .types
-                .len()
-        {
-            remap_expr_references(&mut expr, &index_mapping)?;
-        }
+    let (substrait_schema, bound_schema) =
+        if envelope.base_schema.as_ref().unwrap().r#struct.is_some() {
+            let (substrait_schema, bound_schema, field_mapping) = remove_extension_types(
+                envelope.base_schema.as_ref().unwrap(),
+                input_schema.clone(),
+            )?;
+
+            if substrait_schema.r#struct.as_ref().unwrap().types.len()
+                != envelope
+                    .base_schema
+                    .as_ref()
+                    .unwrap()
+                    .r#struct
+                    .as_ref()
+                    .unwrap()
+                    .types
+                    .len()
+            {
+                remap_expr_references(&mut expr, &field_mapping)?;
+            }
 
-        substrait_schema
-    } else {
-        envelope.base_schema.as_ref().unwrap().clone()
-    };
+            (substrait_schema, bound_schema)
+        } else {
+            (envelope.base_schema.as_ref().unwrap().clone(), input_schema)
+        };
+
+    let mut extensions = envelope.extensions.clone();
+    let get_field_anchor = extensions
+        .iter()
+        .filter_map(|ext| match &ext.mapping_type {
+            Some(MappingType::ExtensionFunction(func)) => Some(func.function_anchor + 1),
+            _ => None,
+        })
+        .max()
+        .unwrap_or_default();
+    if bind_nested_references(&mut expr, &bound_schema, get_field_anchor)? {
+        #[allow(deprecated)]
+        extensions.push(SimpleExtensionDeclaration {
+            mapping_type: Some(MappingType::ExtensionFunction(ExtensionFunction {
+                extension_uri_reference: 0,
+                extension_urn_reference: 0,
+                function_anchor: get_field_anchor,
+                name: "get_field".to_string(),
+            })),
+        });
+    }
 
     let extended_expr = ExtendedExpression {
         base_schema: Some(substrait_schema),
@@ -323,7 +508,8 @@ pub async fn parse_substrait(
             output_names: envelope.referred_expr[0].output_names.clone(),
             expr_type: Some(ExprType::Expression(expr)),
         }],
-        ..envelope
+        extensions,
+        ..envelope.clone()
     };
 
     let mut expr_container =
@@ -331,7 +517,8 @@ pub async fn parse_substrait(
             state,
             &extended_expr,
         )
-        .await?;
+        .await
+        .map_err(|err| unsupported_function_error(&envelope, err))?;
 
     if expr_container.exprs.is_empty() {
         return Err(Error::invalid_input(
@@ -1163,4 +1350,259 @@ mod tests {
 
         assert_substrait_roundtrip(schema, starts_with_expr).await;
     }
+
+    // ==================== Field binding tests ====================
+
+    fn int32_type() -> Type {
+        Type {
+            kind: Some(Kind::I32(I32 {
+                type_variation_reference: 0,
+                nullability: Nullability::Nullable as i32,
+            })),
+        }
+    }
+
+    fn struct_type(types: Vec<Type>) -> Type {
+        Type {
+            kind: Some(Kind::Struct(Struct {
+                types,
+                type_variation_reference: 0,
+                nullability: Nullability::Nullable as i32,
+            })),
+        }
+    }
+
+    /// A reference to the field at `path`, e.g. `[1, 1, 0]` is `s.b.c` in `nested_schema`
+    fn field_ref(path: &[i32]) -> Expression {
+        let mut segment = None;
+        for field in path.iter().rev() {
+            segment = Some(Box::new(ReferenceSegment {
+                reference_type: Some(reference_segment::ReferenceType::StructField(Box::new(
+                    StructField {
+                        field: *field,
+                        child: segment,
+                    },
+                ))),
+            }));
+        }
+        Expression {
+            rex_type: Some(RexType::Selection(Box::new(FieldReference {
+                reference_type: Some(ReferenceType::DirectReference(*segment.unwrap())),
+                root_type: Some(RootType::RootReference(RootReference {})),
+            }))),
+        }
+    }
+
+    /// `function(arg, 0)` where `function` is declared by the extension at `uri`
+    fn compare_to_zero(
+        function: &str,
+        uri: &str,
+        arg: Expression,
+        base_schema: NamedStruct,
+    ) -> Vec<u8> {
+        let zero = Expression {
+            rex_type: Some(RexType::Literal(Literal {
+                nullable: false,
+                type_variation_reference: 0,
+                literal_type: Some(LiteralType::I32(0)),
+            })),
+        };
+        ExtendedExpression {
+            extension_urns: vec![SimpleExtensionUrn {
+                extension_urn_anchor: 1,
+                urn: uri.to_string(),
+            }],
+            extensions: vec![SimpleExtensionDeclaration {
+                mapping_type: Some(MappingType::ExtensionFunction(ExtensionFunction {
+                    #[expect(deprecated)]
+                    extension_uri_reference: 0,
+                    extension_urn_reference: 1,
+                    function_anchor: 1,
+                    name: function.to_string(),
+                })),
+            }],
+            referred_expr: vec![ExpressionReference {
+                output_names: vec!["filter_mask".to_string()],
+                expr_type: Some(ExprType::Expression(Expression {
+                    rex_type: Some(RexType::ScalarFunction(ScalarFunction {
+                        function_reference: 1,
+                        arguments: [arg, zero]
+                            .into_iter()
+                            .map(|value| FunctionArgument {
+                                arg_type: Some(ArgType::Value(value)),
+                            })
+                            .collect(),
+                        ..Default::default()
+                    })),
+                })),
+            }],
+            base_schema: Some(base_schema),
+            ..Default::default()
+        }
+        .encode_to_vec()
+    }
+
+    /// x: int32, s: struct<a: int32, b: struct<c: int32>>
+    fn nested_schema() -> (Schema, NamedStruct) {
+        let schema = Schema::new(vec![
+            Field::new("x", DataType::Int32, true),
+            Field::new(
+                "s",
+                DataType::Struct(
+                    vec![
+                        Field::new("a", DataType::Int32, true),
+                        Field::new(
+                            "b",
+                            DataType::Struct(vec![Field::new("c", DataType::Int32, true)].into()),
+                            true,
+                        ),
+                    ]
+                    .into(),
+                ),
+                true,
+            ),
+        ]);
+        let named_struct = NamedStruct {
+            names: ["x", "s", "a", "b", "c"].map(String::from).to_vec(),
+            r#struct: Some(Struct {
+                types: vec![
+                    int32_type(),
+                    struct_type(vec![int32_type(), struct_type(vec![int32_type()])]),
+                ],
+                type_variation_reference: 0,
+                nullability: Nullability::Required as i32,
+            }),
+        };
+        (schema, named_struct)
+    }
+
+    #[tokio::test]
+    async fn test_substrait_nested_field_references() {
+        use datafusion::functions::core::expr_fn::get_field;
+        use datafusion::prelude::{col, lit};
+
+        let (schema, named_struct) = nested_schema();
+        let schema = Arc::new(schema);
+        let comparison_uri = "https://github.com/substrait-io/substrait/blob/main/extensions/functions_comparison.yaml";
+
+        let bytes = compare_to_zero(
+            "gt",
+            comparison_uri,
+            field_ref(&[1, 0]),
+            named_struct.clone(),
+        );
+        let decoded = parse_substrait(&bytes, schema.clone(), &session_state())
+            .await
+            .unwrap();
+        assert_eq!(decoded, get_field(col("s"), "a").gt(lit(0)));
+
+        let bytes = compare_to_zero("gt", comparison_uri, field_ref(&[1, 1, 0]), named_struct);
+        let decoded = parse_substrait(&bytes, schema.clone(), &session_state())
+            .await
+            .unwrap();
+        assert_eq!(decoded, get_field(get_field(col("s"), "b"), "c").gt(lit(0)));
+
+        // The DataFusion producer encodes nested fields as get_field calls
+        assert_substrait_roundtrip(
+            schema.as_ref().clone(),
+            get_field(get_field(col("s"), "b"), "c").lt(lit(5)),
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_substrait_invalid_nested_field_reference() {
+        let (schema, named_struct) = nested_schema();
+        let schema = Arc::new(schema);
+        let comparison_uri = "https://github.com/substrait-io/substrait/blob/main/extensions/functions_comparison.yaml";
+
+        // s.a is not a struct
+        let bytes = compare_to_zero(
+            "gt",
+            comparison_uri,
+            field_ref(&[1, 0, 0]),
+            named_struct.clone(),
+        );
+        let err = parse_substrait(&bytes, schema.clone(), &session_state())
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("'s.a' which is not a struct"),
+            "{}",
+            err
+        );
+
+        // s.b only has one child
+        let bytes = compare_to_zero("gt", comparison_uri, field_ref(&[1, 1, 3]), named_struct);
+        let err = parse_substrait(&bytes, schema, &session_state())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("child 3 of 's.b'"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_substrait_unsupported_function_names_uri() {
+        let (schema, named_struct) = nested_schema();
+        let uri = "https://example.com/extensions/functions_custom.yaml";
+        let bytes = compare_to_zero("frobnicate:i32_i32", uri, field_ref(&[0]), named_struct);
+        let err = parse_substrait(&bytes, Arc::new(schema), &session_state())
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, lance_core::Error::NotSupported { .. }),
+            "{}",
+            err
+        );
+        assert!(
+            err.to_string().contains(&format!(
+                "Substrait function 'frobnicate:i32_i32' from extension '{}' is not supported",
+                uri
+            )),
+            "{}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn test_substrait_roundtrip_after_struct_and_extension_columns() {
+        // Field references are top-level positions, so the struct's children must not
+        // shift the remapped position of `name` when `embedding` is removed
+        let schema = Schema::new(vec![
+            Field::new(
+                "s",
+                DataType::Struct(
+                    vec![
+                        Field::new("a", DataType::Int32, true),
+                        Field::new("b", DataType::Int32, true),
+                    ]
+                    .into(),
+                ),
+                true,
+            ),
+            Field::new("embedding", DataType::Float16, true),
+            Field::new("id", DataType::Utf8, true),
+        ]);
+
+        assert_substrait_roundtrip(schema, id_filter("test-id")).await;
+    }
+
+    #[tokio::test]
+    async fn test_substrait_roundtrip_filters() {
+        use datafusion::prelude::{col, lit};
+
+        let schema = Schema::new(vec![
+            Field::new("x", DataType::Int32, true),
+            Field::new("y", DataType::Float64, true),
+            Field::new("name", DataType::Utf8, true),
+        ]);
+        let filters = vec![
+            col("x").in_list(vec![lit(1), lit(2), lit(3)], false),
+            col("name").is_null().or(col("y").lt_eq(lit(1.5))),
+            !col("x").gt(lit(1)).and(col("name").not_eq(lit("a"))),
+            col("x").is_not_null().and(col("y").gt(lit(0.0))),
+        ];
+        for filter in filters {
+            assert_substrait_roundtrip(schema.clone(), filter).await;
+        }
+    }
 }
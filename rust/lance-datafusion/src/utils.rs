@@ -174,8 +174,12 @@ impl MetricsExt for MetricsSet {
     }
 
     fn iter_counts(&self) -> impl Iterator<Item = (impl AsRef<str>, &Count)> {
+        // Spill metrics are counts too, reported under their own name
         self.iter().filter_map(|m| match m.value() {
-            MetricValue::Count { name, count } => Some((name, count)),
+            MetricValue::Count { count, .. }
+            | MetricValue::SpillCount(count)
+            | MetricValue::SpilledBytes(count)
+            | MetricValue::SpilledRows(count) => Some((m.value().name(), count)),
             _ => None,
         })
     }
@@ -17,10 +17,12 @@ use arrow_array::{
     RecordBatchOptions, RecordBatchReader, StringArray, StructArray, make_array,
     types::{ArrowDictionaryKeyType, BinaryType, ByteArrayType, Utf8Type},
 };
-use arrow_schema::{ArrowError, DataType, Field, Fields, IntervalUnit, Schema, SchemaRef};
+use arrow_schema::{
+    ArrowError, DataType, Field, Fields, IntervalUnit, Schema, SchemaRef, TimeUnit,
+};
 use futures::{StreamExt, stream::BoxStream};
 use rand::{Rng, RngCore, SeedableRng, distr::Uniform};
-use rand_distr::Zipf;
+use rand_distr::{LogNormal, Normal, Zipf};
 use random_word;
 
 use self::array::rand_with_distribution;
@@ -1318,6 +1320,162 @@ impl ArrayGenerator for RandomWordGenerator {
     }
 }
 
+/// Distribution of the number of tokens in each document produced by
+/// [`array::zipf_text`]
+#[derive(Copy, Clone, Debug)]
+pub enum DocumentLength {
+    /// Every document has exactly this many tokens
+    Fixed(usize),
+    /// Lengths are uniformly distributed between `min` and `max` (inclusive)
+    Uniform { min: usize, max: usize },
+    /// Lengths follow a log-normal distribution with the given mean and standard deviation
+    ///
+    /// This matches the long tail of document lengths seen in real corpora.  Every document
+    /// has at least one token.
+    LogNormal { mean: f64, std_dev: f64 },
+}
+
+#[derive(Debug)]
+enum DocumentLengthSampler {
+    Fixed(usize),
+    Uniform(Uniform<usize>),
+    LogNormal(LogNormal<f64>),
+}
+
+impl DocumentLengthSampler {
+    fn try_new(document_length: DocumentLength) -> Result<Self, ArrowError> {
+        let invalid = |err: &dyn std::fmt::Display| {
+            ArrowError::InvalidArgumentError(format!(
+                "Invalid document length {:?}: {}",
+                document_length, err
+            ))
+        };
+        match document_length {
+            DocumentLength::Fixed(len) => Ok(Self::Fixed(len)),
+            DocumentLength::Uniform { min, max } => Uniform::new_inclusive(min, max)
+                .map(Self::Uniform)
+                .map_err(|err| invalid(&err)),
+            DocumentLength::LogNormal { mean, std_dev } => {
+                if mean <= 0.0 {
+                    return Err(invalid(&"mean must be positive"));
+                }
+                // Convert the mean / standard deviation of the lengths into the parameters of
+                // the underlying normal distribution.
+                let sigma_sq = (1.0 + (std_dev * std_dev) / (mean * mean)).ln();
+                let mu = mean.ln() - sigma_sq / 2.0;
+                LogNormal::new(mu, sigma_sq.sqrt())
+                    .map(Self::LogNormal)
+                    .map_err(|err| invalid(&err))
+            }
+        }
+    }
+
+    fn sample(&self, rng: &mut rand_xoshiro::Xoshiro256PlusPlus) -> usize {
+        match self {
+            Self::Fixed(len) => *len,
+            Self::Uniform(dist) => rng.sample(dist),
+            Self::LogNormal(dist) => (rng.sample(dist).round() as usize).max(1),
+        }
+    }
+}
+
+/// Generates documents of synthetic tokens whose frequencies follow a Zipf distribution
+///
+/// Token `i` (0-indexed by rank) is spelled `w{i}`, so the most frequent tokens are also the
+/// shortest, as in natural language.
+struct ZipfTextGenerator {
+    vocabulary: Vec<String>,
+    zipf: Zipf<f64>,
+    document_length: DocumentLength,
+    lengths: DocumentLengthSampler,
+    is_large: bool,
+}
+
+impl std::fmt::Debug for ZipfTextGenerator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ZipfTextGenerator")
+            .field("vocabulary_size", &self.vocabulary.len())
+            .field("document_length", &self.document_length)
+            .field("is_large", &self.is_large)
+            .finish()
+    }
+}
+
+impl ZipfTextGenerator {
+    fn try_new(
+        vocabulary_size: usize,
+        exponent: f64,
+        document_length: DocumentLength,
+        is_large: bool,
+    ) -> Result<Self, ArrowError> {
+        if vocabulary_size == 0 {
+            return Err(ArrowError::InvalidArgumentError(
+                "Vocabulary size must be positive".to_string(),
+            ));
+        }
+        let zipf = Zipf::new(vocabulary_size as f64, exponent).map_err(|err| {
+            ArrowError::InvalidArgumentError(format!("Invalid Zipf exponent {exponent}: {err}"))
+        })?;
+        Ok(Self {
+            vocabulary: (0..vocabulary_size)
+                .map(|rank| format!("w{rank}"))
+                .collect(),
+            zipf,
+            document_length,
+            lengths: DocumentLengthSampler::try_new(document_length)?,
+            is_large,
+        })
+    }
+}
+
+impl ArrayGenerator for ZipfTextGenerator {
+    fn generate(
+        &mut self,
+        length: RowCount,
+        rng: &mut rand_xoshiro::Xoshiro256PlusPlus,
+    ) -> Result<Arc<dyn Array>, ArrowError> {
+        let mut values = Vec::with_capacity(length.0 as usize);
+
+        for _ in 0..length.0 {
+            let num_tokens = self.lengths.sample(rng);
+            let document = (0..num_tokens)
+                .map(|_| {
+                    // Zipf returns 1-indexed values, subtract 1 for 0-indexed array
+                    let rank = rng.sample(self.zipf) as usize - 1;
+                    self.vocabulary[rank].as_str()
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            values.push(document);
+        }
+
+        if self.is_large {
+            Ok(Arc::new(LargeStringArray::from(values)))
+        } else {
+            Ok(Arc::new(StringArray::from(values)))
+        }
+    }
+
+    fn data_type(&self) -> &DataType {
+        if self.is_large {
+            &DataType::LargeUtf8
+        } else {
+            &DataType::Utf8
+        }
+    }
+
+    fn element_size_bytes(&self) -> Option<ByteCount> {
+        let mean_tokens = match self.document_length {
+            DocumentLength::Fixed(len) => len as f64,
+            DocumentLength::Uniform { min, max } => (min + max) as f64 / 2.0,
+            DocumentLength::LogNormal { mean, .. } => mean,
+        };
+        // Frequent tokens are only a few characters long, estimate 4 bytes per token
+        // including the separating space
+        Some(ByteCount::from((mean_tokens * 4.0) as u64))
+    }
+}
+
 #[derive(Debug)]
 pub struct VariableRandomBinaryGenerator {
     lengths_gen: Box<dyn ArrayGenerator>,
@@ -1947,6 +2105,191 @@ impl ArrayGenerator for JitterCentroidsGenerator {
         Some(ByteCount::from(self.dimension as u64 * 4))
     }
 }
+
+/// Samples vectors from a mixture of isotropic Gaussians
+///
+/// The cluster centers are drawn uniformly from `[-1, 1)^dimension` on the first call, so they
+/// are covered by the batch seed.  Each row is assigned to a uniformly random cluster.
+#[derive(Debug)]
+struct GaussianMixtureGenerator {
+    num_clusters: usize,
+    dimension: u32,
+    noise: Normal<f32>,
+    normalize: bool,
+    data_type: DataType,
+    data_field: Arc<Field>,
+
+    /// Cluster centers, generated on first call
+    centroids: Option<Vec<f32>>,
+}
+
+impl GaussianMixtureGenerator {
+    fn try_new(
+        num_clusters: usize,
+        dimension: Dimension,
+        spread: f32,
+        normalize: bool,
+    ) -> Result<Self, ArrowError> {
+        if num_clusters == 0 {
+            return Err(ArrowError::InvalidArgumentError(
+                "Number of clusters must be positive".to_string(),
+            ));
+        }
+        let noise = Normal::new(0.0, spread).map_err(|err| {
+            ArrowError::InvalidArgumentError(format!("Invalid cluster spread {spread}: {err}"))
+        })?;
+        let data_field = Arc::new(Field::new("item", DataType::Float32, true));
+        let data_type = DataType::FixedSizeList(data_field.clone(), dimension.0 as i32);
+        Ok(Self {
+            num_clusters,
+            dimension: dimension.0,
+            noise,
+            normalize,
+            data_type,
+            data_field,
+            centroids: None,
+        })
+    }
+}
+
+impl ArrayGenerator for GaussianMixtureGenerator {
+    fn generate(
+        &mut self,
+        length: RowCount,
+        rng: &mut rand_xoshiro::Xoshiro256PlusPlus,
+    ) -> Result<Arc<dyn Array>, ArrowError> {
+        let dimension = self.dimension as usize;
+        let centroids = self.centroids.get_or_insert_with(|| {
+            (0..self.num_clusters * dimension)
+                .map(|_| rng.random_range(-1.0..1.0))
+                .collect()
+        });
+
+        let mut values = Vec::with_capacity(length.0 as usize * dimension);
+        for _ in 0..length.0 {
+            let cluster = rng.random_range(0..self.num_clusters);
+            let centroid = &centroids[cluster * dimension..(cluster + 1) * dimension];
+            let start = values.len();
+            values.extend(centroid.iter().map(|c| c + rng.sample(self.noise)));
+            if self.normalize {
+                let vector = &mut values[start..];
+                let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+                if norm > 0.0 {
+                    vector.iter_mut().for_each(|v| *v /= norm);
+                }
+            }
+        }
+
+        let vectors = FixedSizeListArray::try_new(
+            self.data_field.clone(),
+            self.dimension as i32,
+            Arc::new(Float32Array::from(values)),
+            None,
+        )?;
+        Ok(Arc::new(vectors))
+    }
+
+    fn data_type(&self) -> &DataType {
+        &self.data_type
+    }
+
+    fn element_size_bytes(&self) -> Option<ByteCount> {
+        Some(ByteCount::from(self.dimension as u64 * 4))
+    }
+}
+
+/// Generates timestamps that advance by a fixed interval per row, plus optional jitter
+///
+/// The row counter carries over between calls, so the output tracks a [`array::step`] column
+/// generated in the same batch.
+#[derive(Debug)]
+struct CorrelatedTimestampGenerator {
+    start: i64,
+    interval: i64,
+    jitter: i64,
+    data_type: DataType,
+
+    row: i64,
+}
+
+impl CorrelatedTimestampGenerator {
+    fn try_new(
+        start: chrono::DateTime<chrono::Utc>,
+        interval: chrono::TimeDelta,
+        jitter: chrono::TimeDelta,
+        data_type: &DataType,
+    ) -> Result<Self, ArrowError> {
+        let DataType::Timestamp(unit, _) = data_type else {
+            return Err(ArrowError::InvalidArgumentError(format!(
+                "Correlated timestamps must be a timestamp type, got {data_type}"
+            )));
+        };
+        let out_of_range = || {
+            ArrowError::InvalidArgumentError(format!(
+                "Timestamp parameters do not fit in {data_type}"
+            ))
+        };
+        let delta_ticks = |delta: chrono::TimeDelta| match unit {
+            TimeUnit::Second => Some(delta.num_seconds()),
+            TimeUnit::Millisecond => Some(delta.num_milliseconds()),
+            TimeUnit::Microsecond => delta.num_microseconds(),
+            TimeUnit::Nanosecond => delta.num_nanoseconds(),
+        };
+        let start = match unit {
+            TimeUnit::Second => Some(start.timestamp()),
+            TimeUnit::Millisecond => Some(start.timestamp_millis()),
+            TimeUnit::Microsecond => Some(start.timestamp_micros()),
+            TimeUnit::Nanosecond => start.timestamp_nanos_opt(),
+        }
+        .ok_or_else(out_of_range)?;
+        let jitter = delta_ticks(jitter).ok_or_else(out_of_range)?;
+        if jitter < 0 {
+            return Err(ArrowError::InvalidArgumentError(
+                "Timestamp jitter must not be negative".to_string(),
+            ));
+        }
+        Ok(Self {
+            start,
+            interval: delta_ticks(interval).ok_or_else(out_of_range)?,
+            jitter,
+            data_type: data_type.clone(),
+            row: 0,
+        })
+    }
+}
+
+impl ArrayGenerator for CorrelatedTimestampGenerator {
+    fn generate(
+        &mut self,
+        length: RowCount,
+        rng: &mut rand_xoshiro::Xoshiro256PlusPlus,
+    ) -> Result<Arc<dyn Array>, ArrowError> {
+        let mut values = Vec::with_capacity(length.0 as usize);
+        for _ in 0..length.0 {
+            let mut value = self.start + self.row * self.interval;
+            if self.jitter > 0 {
+                value += rng.random_range(-self.jitter..=self.jitter);
+            }
+            values.push(value);
+            self.row += 1;
+        }
+        let data = PrimitiveArray::<Int64Type>::from(values)
+            .into_data()
+            .into_builder()
+            .data_type(self.data_type.clone())
+            .build()?;
+        Ok(make_array(data))
+    }
+
+    fn data_type(&self) -> &DataType {
+        &self.data_type
+    }
+
+    fn element_size_bytes(&self) -> Option<ByteCount> {
+        Some(ByteCount::from(8))
+    }
+}
+
 #[derive(Debug)]
 struct RandomStructGenerator {
     fields: Fields,
@@ -2318,6 +2661,22 @@ pub mod array {
         Box::new(JitterCentroidsGenerator::try_new(centroids, jitter).unwrap())
     }
 
+    /// Create a generator of clustered vectors sampled from a Gaussian mixture
+    ///
+    /// Cluster centers are drawn uniformly from `[-1, 1)^dimension` and each value adds Gaussian
+    /// noise with standard deviation `spread` to a randomly chosen center.  If `normalize` is
+    /// true the vectors are scaled to unit length, which suits cosine / dot indexes.
+    pub fn rand_clustered_vec(
+        dimension: Dimension,
+        num_clusters: usize,
+        spread: f32,
+        normalize: bool,
+    ) -> Box<dyn ArrayGenerator> {
+        Box::new(
+            GaussianMixtureGenerator::try_new(num_clusters, dimension, spread, normalize).unwrap(),
+        )
+    }
+
     /// Create a generator from a vector of values
     ///
     /// If more rows are requested than the length of values then it will restart
@@ -2716,6 +3075,20 @@ pub mod array {
         }
     }
 
+    /// Create a generator of timestamps that increase with the row number
+    ///
+    /// Row `i` is `start + i * interval`, offset by uniform noise in `[-jitter, jitter]`.  Paired
+    /// with a [`step`] id column this gives correlated id / timestamp columns: sorted when the
+    /// jitter is zero (or less than half the interval) and nearly sorted otherwise.
+    pub fn correlated_timestamp(
+        start: chrono::DateTime<Utc>,
+        interval: chrono::TimeDelta,
+        jitter: chrono::TimeDelta,
+        data_type: &DataType,
+    ) -> Box<dyn ArrayGenerator> {
+        Box::new(CorrelatedTimestampGenerator::try_new(start, interval, jitter, data_type).unwrap())
+    }
+
     pub fn rand_timestamp(data_type: &DataType) -> Box<dyn ArrayGenerator> {
         let now = chrono::Utc::now();
         let one_year_ago = now - chrono::Duration::try_days(365).unwrap();
@@ -2814,6 +3187,24 @@ pub mod array {
         Box::new(RandomSentenceGenerator::new(min_words, max_words, is_large))
     }
 
+    /// Create a generator of documents made of synthetic tokens with Zipf-distributed frequencies
+    ///
+    /// Tokens are drawn from a vocabulary of `vocabulary_size` tokens with Zipf `exponent`
+    /// (1.0 approximates natural language) and each document's token count is drawn from
+    /// `document_length`.  Unlike [`random_sentence`] the vocabulary size is configurable, which
+    /// makes it suitable for benchmarking full text search at different scales.
+    pub fn zipf_text(
+        vocabulary_size: usize,
+        exponent: f64,
+        document_length: DocumentLength,
+        is_large: bool,
+    ) -> Box<dyn ArrayGenerator> {
+        Box::new(
+            ZipfTextGenerator::try_new(vocabulary_size, exponent, document_length, is_large)
+                .unwrap(),
+        )
+    }
+
     /// Create a generator of random words (one word per row)
     ///
     /// Generates strings containing a single random English word per row
@@ -3329,6 +3720,245 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_zipf_text_skew() {
+        let mut rng = rand_xoshiro::Xoshiro256PlusPlus::seed_from_u64(DEFAULT_SEED.0);
+        let mut genn = array::zipf_text(
+            1000,
+            1.0,
+            DocumentLength::Uniform { min: 10, max: 20 },
+            false,
+        );
+        let arr = genn.generate(RowCount::from(2000), &mut rng).unwrap();
+
+        let mut counts = HashMap::<&str, usize>::new();
+        let mut total = 0;
+        for doc in arr.as_string::<i32>().iter().map(|doc| doc.unwrap()) {
+            let num_tokens = doc.split(' ').count();
+            assert!((10..=20).contains(&num_tokens));
+            for token in doc.split(' ') {
+                *counts.entry(token).or_default() += 1;
+                total += 1;
+            }
+        }
+
+        // Frequency is roughly proportional to 1 / rank
+        let freq = |token: &str| counts.get(token).copied().unwrap_or_default() as f64;
+        let ratio = freq("w0") / freq("w1");
+        assert!((1.6..2.4).contains(&ratio), "ratio = {ratio}");
+        assert!(freq("w0") > 20.0 * freq("w99"));
+
+        // With exponent 1.0 the top 10 of 1000 tokens cover about 39% of all tokens
+        let mut sorted = counts.values().copied().collect::<Vec<_>>();
+        sorted.sort_unstable_by(|a, b| b.cmp(a));
+        let top10 = sorted[..10].iter().sum::<usize>() as f64 / total as f64;
+        assert!((0.35..0.43).contains(&top10), "top10 = {top10}");
+    }
+
+    #[test]
+    fn test_zipf_text_document_length() {
+        let mut rng = rand_xoshiro::Xoshiro256PlusPlus::seed_from_u64(DEFAULT_SEED.0);
+        let mut genn = array::zipf_text(
+            100,
+            1.0,
+            DocumentLength::LogNormal {
+                mean: 50.0,
+                std_dev: 20.0,
+            },
+            true,
+        );
+        let arr = genn.generate(RowCount::from(2000), &mut rng).unwrap();
+        assert_eq!(arr.data_type(), &DataType::LargeUtf8);
+
+        let lengths = arr
+            .as_string::<i64>()
+            .iter()
+            .map(|doc| doc.unwrap().split(' ').count() as f64)
+            .collect::<Vec<_>>();
+        let mean = lengths.iter().sum::<f64>() / lengths.len() as f64;
+        let std_dev =
+            (lengths.iter().map(|l| (l - mean).powi(2)).sum::<f64>() / lengths.len() as f64).sqrt();
+        assert!((47.0..53.0).contains(&mean), "mean = {mean}");
+        assert!((16.0..24.0).contains(&std_dev), "std_dev = {std_dev}");
+
+        let mut genn = array::zipf_text(100, 1.0, DocumentLength::Fixed(3), false);
+        let arr = genn.generate(RowCount::from(10), &mut rng).unwrap();
+        assert!(
+            arr.as_string::<i32>()
+                .iter()
+                .all(|doc| doc.unwrap().split(' ').count() == 3)
+        );
+    }
+
+    #[test]
+    fn test_clustered_vec_separable() {
+        let mut rng = rand_xoshiro::Xoshiro256PlusPlus::seed_from_u64(DEFAULT_SEED.0);
+        let mut genn = array::rand_clustered_vec(Dimension::from(16), 5, 0.01, false);
+        let arr = genn.generate(RowCount::from(1000), &mut rng).unwrap();
+        assert_eq!(arr.len(), 1000);
+
+        let values = arr
+            .as_fixed_size_list()
+            .values()
+            .as_primitive::<Float32Type>()
+            .values()
+            .to_vec();
+        let vectors = values.chunks_exact(16).collect::<Vec<_>>();
+        let dist = |a: &[f32], b: &[f32]| {
+            a.iter()
+                .zip(b)
+                .map(|(x, y)| (x - y).powi(2))
+                .sum::<f32>()
+                .sqrt()
+        };
+
+        // Greedily group vectors, every vector should be far closer to its own group than to
+        // any other group
+        let mut representatives: Vec<&[f32]> = Vec::new();
+        for vector in &vectors {
+            let mut dists = representatives.iter().map(|rep| dist(rep, vector));
+            if !dists.any(|d| d < 0.5) {
+                representatives.push(vector);
+            }
+        }
+        assert_eq!(representatives.len(), 5);
+        for vector in &vectors {
+            let mut dists = representatives
+                .iter()
+                .map(|rep| dist(rep, vector))
+                .collect::<Vec<_>>();
+            dists.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            assert!(dists[0] < 0.2);
+            assert!(dists[1] > 1.0);
+        }
+
+        // Later calls keep sampling around the same centers
+        let arr = genn.generate(RowCount::from(10), &mut rng).unwrap();
+        let values = arr
+            .as_fixed_size_list()
+            .values()
+            .as_primitive::<Float32Type>();
+        for vector in values.values().chunks_exact(16) {
+            assert!(representatives.iter().any(|rep| dist(rep, vector) < 0.2));
+        }
+
+        let mut genn = array::rand_clustered_vec(Dimension::from(8), 3, 0.1, true);
+        let arr = genn.generate(RowCount::from(100), &mut rng).unwrap();
+        let values = arr
+            .as_fixed_size_list()
+            .values()
+            .as_primitive::<Float32Type>();
+        for vector in values.values().chunks_exact(8) {
+            let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+            assert!((norm - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_correlated_timestamp() {
+        let start = chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let data_type = DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into()));
+        let batch = gen_batch()
+            .col("id", array::step::<Int64Type>())
+            .col(
+                "ts",
+                array::correlated_timestamp(
+                    start,
+                    chrono::TimeDelta::seconds(1),
+                    chrono::TimeDelta::zero(),
+                    &data_type,
+                ),
+            )
+            .col(
+                "ts_jitter",
+                array::correlated_timestamp(
+                    start,
+                    chrono::TimeDelta::seconds(1),
+                    chrono::TimeDelta::milliseconds(1500),
+                    &data_type,
+                ),
+            )
+            .into_batch_rows(RowCount::from(1000))
+            .unwrap();
+        assert_eq!(batch.column(1).data_type(), &data_type);
+
+        let ids = batch.column(0).as_primitive::<Int64Type>();
+        let ts = batch
+            .column(1)
+            .as_primitive::<arrow::datatypes::TimestampMillisecondType>();
+        let ts_jitter = batch
+            .column(2)
+            .as_primitive::<arrow::datatypes::TimestampMillisecondType>();
+        let start_ms = start.timestamp_millis();
+        let mut out_of_order = 0;
+        for i in 0..1000 {
+            let expected = start_ms + ids.value(i) * 1000;
+            assert_eq!(ts.value(i), expected);
+            assert!((ts_jitter.value(i) - expected).abs() <= 1500);
+            if i > 0 && ts_jitter.value(i) < ts_jitter.value(i - 1) {
+                out_of_order += 1;
+            }
+        }
+        // Nearly, but not entirely, sorted
+        assert!(out_of_order > 0 && out_of_order < 500);
+
+        // Rows keep counting across calls
+        let mut rng = rand_xoshiro::Xoshiro256PlusPlus::seed_from_u64(DEFAULT_SEED.0);
+        let mut genn = array::correlated_timestamp(
+            start,
+            chrono::TimeDelta::seconds(1),
+            chrono::TimeDelta::zero(),
+            &DataType::Timestamp(TimeUnit::Second, None),
+        );
+        genn.generate(RowCount::from(5), &mut rng).unwrap();
+        let arr = genn.generate(RowCount::from(1), &mut rng).unwrap();
+        assert_eq!(
+            arr.as_primitive::<arrow::datatypes::TimestampSecondType>()
+                .value(0),
+            1_700_000_005
+        );
+    }
+
+    #[test]
+    fn test_realistic_generators_reproducible() {
+        let make_batch = |seed: u64| {
+            gen_batch()
+                .with_seed(Seed::from(seed))
+                .col(
+                    "text",
+                    array::zipf_text(
+                        500,
+                        1.1,
+                        DocumentLength::LogNormal {
+                            mean: 20.0,
+                            std_dev: 5.0,
+                        },
+                        false,
+                    ),
+                )
+                .col(
+                    "vector",
+                    array::rand_clustered_vec(Dimension::from(8), 4, 0.05, true),
+                )
+                .col(
+                    "ts",
+                    array::correlated_timestamp(
+                        chrono::DateTime::from_timestamp(0, 0).unwrap(),
+                        chrono::TimeDelta::seconds(1),
+                        chrono::TimeDelta::seconds(5),
+                        &DataType::Timestamp(TimeUnit::Second, None),
+                    ),
+                )
+                .into_batch_rows(RowCount::from(100))
+                .unwrap()
+        };
+        assert_eq!(make_batch(7), make_batch(7));
+        let (a, b) = (make_batch(7), make_batch(8));
+        for (a, b) in a.columns().iter().zip(b.columns()) {
+            assert_ne!(a, b);
+        }
+    }
+
     #[test]
     fn test_rand_schema() {
         let schema = Schema::new(vec![
@@ -10,6 +10,7 @@ use futures::StreamExt;
 use lance_core::cache::LanceCache;
 use lance_datagen::ArrayGeneratorExt;
 use lance_encoding::{
+    buffer_pool::BufferPool,
     decoder::{
         DecodeBatchScheduler, DecoderConfig, DecoderPlugins, FilterExpression, create_decode_stream,
     },
@@ -542,6 +543,7 @@ fn bench_decode_compressed_parallel(c: &mut Criterion) {
                                 false,
                                 rx,
                                 None,
+                                None,
                             )
                             .unwrap();
 
@@ -563,6 +565,102 @@ fn bench_decode_compressed_parallel(c: &mut Criterion) {
     }
 }
 
+fn bench_decode_wide_scan(c: &mut Criterion) {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let mut group = c.benchmark_group("decode_wide_scan");
+
+    const NUM_ROWS: u64 = 200_000;
+    const NUM_COLUMNS: usize = 100;
+    const BATCH_SIZE: u32 = 8 * 1024;
+    const PARALLELISM: usize = 8;
+
+    let mut batch_gen = lance_datagen::gen_batch();
+    for i in 0..NUM_COLUMNS {
+        let data_type = if i % 2 == 0 {
+            DataType::Float32
+        } else {
+            DataType::Int64
+        };
+        batch_gen = batch_gen.col(
+            format!("c{}", i),
+            lance_datagen::array::rand_type(&data_type),
+        );
+    }
+    let data = batch_gen
+        .into_batch_rows(lance_datagen::RowCount::from(NUM_ROWS))
+        .unwrap();
+    let lance_schema =
+        Arc::new(lance_core::datatypes::Schema::try_from(data.schema().as_ref()).unwrap());
+    let encoding_strategy = default_encoding_strategy(LanceFileVersion::V2_1);
+    let encoded = Arc::new(
+        rt.block_on(encode_batch(
+            &data,
+            lance_schema,
+            encoding_strategy.as_ref(),
+            &EncodingOptions::default(),
+        ))
+        .unwrap(),
+    );
+
+    let buffer_pool = Arc::new(BufferPool::new(256 * 1024 * 1024));
+    group.throughput(criterion::Throughput::Bytes(
+        data.get_array_memory_size() as u64
+    ));
+    for pooled in [false, true] {
+        let buffer_pool = pooled.then(|| buffer_pool.clone());
+        group.bench_function(format!("{}cols_pooled_{}", NUM_COLUMNS, pooled), |b| {
+            b.iter(|| {
+                rt.block_on(async {
+                    let io_scheduler =
+                        Arc::new(lance_encoding::BufferScheduler::new(encoded.data.clone()))
+                            as Arc<dyn lance_encoding::EncodingsIo>;
+                    let filter = FilterExpression::no_filter();
+                    let mut decode_scheduler = DecodeBatchScheduler::try_new(
+                        encoded.schema.as_ref(),
+                        &encoded.top_level_columns,
+                        &encoded.page_table,
+                        &vec![],
+                        encoded.num_rows,
+                        Arc::<DecoderPlugins>::default(),
+                        io_scheduler.clone(),
+                        Arc::new(LanceCache::no_cache()),
+                        &filter,
+                        &DecoderConfig::default(),
+                    )
+                    .await
+                    .unwrap();
+
+                    let (tx, rx) = unbounded_channel();
+                    decode_scheduler.schedule_range(0..encoded.num_rows, &filter, tx, io_scheduler);
+
+                    let decode_stream = create_decode_stream(
+                        &encoded.schema,
+                        encoded.num_rows,
+                        BATCH_SIZE,
+                        true,
+                        false,
+                        true,
+                        rx,
+                        None,
+                        buffer_pool.clone(),
+                    )
+                    .unwrap();
+
+                    // Batches are dropped as soon as they are counted, like a streaming scan
+                    let total_rows = decode_stream
+                        .map(|task| task.task)
+                        .buffered(PARALLELISM)
+                        .fold(0, |total_rows, batch| async move {
+                            total_rows + batch.unwrap().num_rows()
+                        })
+                        .await;
+                    assert_eq!(total_rows, NUM_ROWS as usize);
+                })
+            })
+        });
+    }
+}
+
 #[cfg(target_os = "linux")]
 criterion_group!(
     name=benches;
@@ -570,7 +668,7 @@ criterion_group!(
         .with_profiler(lance_testing::pprof::PProfProfiler::new(100, lance_testing::pprof::Output::Flamegraph(None)));
     targets = bench_decode, bench_decode_fsl, bench_decode_str_with_dict_encoding, bench_decode_packed_struct,
                 bench_decode_str_with_fixed_size_binary_encoding, bench_decode_compressed,
-                bench_decode_compressed_parallel);
+                bench_decode_compressed_parallel, bench_decode_wide_scan);
 
 // Non-linux version does not support pprof.
 #[cfg(not(target_os = "linux"))]
@@ -578,5 +676,5 @@ criterion_group!(
     name=benches;
     config = Criterion::default().significance_level(0.1).sample_size(10);
     targets = bench_decode, bench_decode_fsl, bench_decode_str_with_dict_encoding, bench_decode_packed_struct,
-                bench_decode_compressed, bench_decode_compressed_parallel);
+                bench_decode_compressed, bench_decode_compressed_parallel, bench_decode_wide_scan);
 criterion_main!(benches);
@@ -0,0 +1,304 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! A pool of byte buffers that decoders reuse from one batch to the next
+//!
+//! Decoding a batch allocates a handful of large buffers per column, both for the
+//! decoded output and for transient data such as decompressed chunks.  On a wide scan
+//! this allocation (and the allocator contention between decode threads) shows up as
+//! a large share of the CPU time.  A [`BufferPool`] keeps the allocations of dropped
+//! buffers around so later batches can reuse them.
+//!
+//! Buffers are grouped into power-of-two size classes.  Decoders ask for a buffer with
+//! [`pooled_vec`] and hand it out with [`freeze_pooled`].  The frozen buffer returns its
+//! allocation to the pool when the last reference to it, including any arrow array
+//! built on top of it, is dropped.  A reused allocation is always handed out as an
+//! empty `Vec` so the bytes of an earlier batch are never observable.
+//!
+//! Pooling only applies while decoding inside [`BufferPool::scope`], which the decode
+//! stream enters for every batch when [`crate::decoder::DecoderConfig::buffer_pool`] is
+//! set.  Set `LANCE_DISABLE_BUFFER_POOL=true` to bypass the pool when debugging.
+
+use std::{
+    cell::RefCell,
+    ptr::NonNull,
+    sync::{
+        Arc, Mutex, OnceLock, Weak,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use arrow_buffer::Buffer;
+use lance_core::utils::parse::parse_env_as_bool;
+
+use crate::buffer::LanceBuffer;
+
+const ENV_LANCE_DISABLE_BUFFER_POOL: &str = "LANCE_DISABLE_BUFFER_POOL";
+
+// Smaller buffers are cheap to allocate and not worth pooling
+const MIN_CLASS_BITS: u32 = 12;
+// Larger buffers are rare and would tie up too much of the bound
+const MAX_CLASS_BITS: u32 = 26;
+const NUM_CLASSES: usize = (MAX_CLASS_BITS - MIN_CLASS_BITS + 1) as usize;
+
+fn pooling_disabled() -> bool {
+    static DISABLED: OnceLock<bool> = OnceLock::new();
+    *DISABLED.get_or_init(|| parse_env_as_bool(ENV_LANCE_DISABLE_BUFFER_POOL, false))
+}
+
+thread_local! {
+    static CURRENT_POOL: RefCell<Option<Arc<BufferPool>>> = const { RefCell::new(None) };
+}
+
+/// A bounded, size-classed pool of byte buffers, see the [module docs](self)
+#[derive(Debug)]
+pub struct BufferPool {
+    max_bytes: u64,
+    pooled_bytes: AtomicU64,
+    num_reused: AtomicU64,
+    classes: Vec<Mutex<Vec<Vec<u8>>>>,
+}
+
+impl BufferPool {
+    /// Create a pool that keeps at most `max_bytes` of idle buffers
+    pub fn new(max_bytes: u64) -> Self {
+        Self {
+            max_bytes,
+            pooled_bytes: AtomicU64::new(0),
+            num_reused: AtomicU64::new(0),
+            classes: (0..NUM_CLASSES).map(|_| Mutex::new(Vec::new())).collect(),
+        }
+    }
+
+    /// The maximum number of idle bytes the pool keeps
+    pub fn max_bytes(&self) -> u64 {
+        self.max_bytes
+    }
+
+    /// The number of idle bytes currently in the pool
+    pub fn pooled_bytes(&self) -> u64 {
+        self.pooled_bytes.load(Ordering::Relaxed)
+    }
+
+    /// The number of buffers handed out that reused a pooled allocation
+    pub fn num_reused(&self) -> u64 {
+        self.num_reused.load(Ordering::Relaxed)
+    }
+
+    /// An empty vector with room for at least `capacity` bytes
+    pub fn acquire(&self, capacity: usize) -> Vec<u8> {
+        let Some(class) = Self::class_for_request(capacity) else {
+            return Vec::with_capacity(capacity);
+        };
+        let reused = self.classes[class].lock().unwrap().pop();
+        match reused {
+            Some(buf) => {
+                self.pooled_bytes
+                    .fetch_sub(buf.capacity() as u64, Ordering::Relaxed);
+                self.num_reused.fetch_add(1, Ordering::Relaxed);
+                buf
+            }
+            None => Vec::with_capacity(1 << (class as u32 + MIN_CLASS_BITS)),
+        }
+    }
+
+    /// Wrap `buf` in a buffer that returns its allocation to this pool when dropped
+    ///
+    /// This never copies the data.
+    pub fn freeze(self: &Arc<Self>, buf: Vec<u8>) -> LanceBuffer {
+        let ptr = NonNull::new(buf.as_ptr() as *mut u8).expect("Vec pointers are never null");
+        let len = buf.len();
+        let allocation = Arc::new(PooledAllocation {
+            buf,
+            pool: Arc::downgrade(self),
+        });
+        // SAFETY: the pointer is valid for `len` bytes for as long as the allocation, which
+        // owns the Vec and never touches it until dropped, is alive
+        LanceBuffer::from(unsafe { Buffer::from_custom_allocation(ptr, len, allocation) })
+    }
+
+    /// Run `f` with `pool` as the pool that [`pooled_vec`] and [`freeze_pooled`] use on
+    /// this thread
+    pub fn scope<R>(pool: Option<&Arc<Self>>, f: impl FnOnce() -> R) -> R {
+        struct Restore(Option<Arc<BufferPool>>);
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                CURRENT_POOL.with(|current| *current.borrow_mut() = self.0.take());
+            }
+        }
+
+        let pool = pool.filter(|_| !pooling_disabled()).cloned();
+        let _restore = Restore(CURRENT_POOL.with(|current| current.replace(pool)));
+        f()
+    }
+
+    fn release(&self, mut buf: Vec<u8>) {
+        let Some(class) = Self::class_for_release(buf.capacity()) else {
+            return;
+        };
+        let capacity = buf.capacity() as u64;
+        let admitted = self
+            .pooled_bytes
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |pooled| {
+                (pooled + capacity <= self.max_bytes).then_some(pooled + capacity)
+            })
+            .is_ok();
+        if admitted {
+            buf.clear();
+            self.classes[class].lock().unwrap().push(buf);
+        }
+    }
+
+    // The smallest class whose buffers can hold `capacity` bytes
+    fn class_for_request(capacity: usize) -> Option<usize> {
+        let bits = capacity
+            .max(1)
+            .checked_next_power_of_two()?
+            .trailing_zeros();
+        (MIN_CLASS_BITS..=MAX_CLASS_BITS)
+            .contains(&bits)
+            .then(|| (bits - MIN_CLASS_BITS) as usize)
+    }
+
+    // The largest class whose requests a buffer of `capacity` bytes can satisfy
+    fn class_for_release(capacity: usize) -> Option<usize> {
+        let bits = capacity.checked_ilog2()?;
+        (MIN_CLASS_BITS..=MAX_CLASS_BITS)
+            .contains(&bits)
+            .then(|| (bits - MIN_CLASS_BITS) as usize)
+    }
+}
+
+struct PooledAllocation {
+    buf: Vec<u8>,
+    pool: Weak<BufferPool>,
+}
+
+impl Drop for PooledAllocation {
+    fn drop(&mut self) {
+        if let Some(pool) = self.pool.upgrade() {
+            pool.release(std::mem::take(&mut self.buf));
+        }
+    }
+}
+
+fn current_pool() -> Option<Arc<BufferPool>> {
+    CURRENT_POOL.with(|current| current.borrow().clone())
+}
+
+/// An empty vector with room for at least `capacity` bytes, taken from the pool of the
+/// enclosing [`BufferPool::scope`] if there is one
+pub fn pooled_vec(capacity: usize) -> Vec<u8> {
+    match current_pool() {
+        Some(pool) => pool.acquire(capacity),
+        None => Vec::with_capacity(capacity),
+    }
+}
+
+/// Convert `buf` into a buffer that returns its allocation to the pool of the enclosing
+/// [`BufferPool::scope`], if there is one, when dropped
+pub fn freeze_pooled(buf: Vec<u8>) -> LanceBuffer {
+    match current_pool() {
+        Some(pool) => pool.freeze(buf),
+        None => LanceBuffer::from(buf),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rstest::rstest;
+
+    use super::*;
+
+    const KIB: usize = 1024;
+
+    #[rstest]
+    #[case::tiny(100, None)]
+    #[case::smallest(4 * KIB, Some(0))]
+    #[case::rounds_up(4 * KIB + 1, Some(1))]
+    #[case::largest(64 * KIB * KIB, Some(14))]
+    #[case::huge(64 * KIB * KIB + 1, None)]
+    fn test_class_for_request(#[case] capacity: usize, #[case] expected: Option<usize>) {
+        assert_eq!(BufferPool::class_for_request(capacity), expected);
+    }
+
+    #[test]
+    fn test_reuse_after_drop() {
+        let pool = Arc::new(BufferPool::new(1024 * 1024));
+
+        let mut buf = pool.acquire(5000);
+        let ptr = buf.as_ptr();
+        assert!(buf.capacity() >= 5000);
+        buf.extend(std::iter::repeat_n(0xAB, 5000));
+        let frozen = pool.freeze(buf);
+        assert_eq!(frozen.as_ptr(), ptr);
+        assert_eq!(pool.pooled_bytes(), 0);
+
+        // Still referenced, so a new request can't reuse the allocation
+        let other = pool.acquire(5000);
+        assert_ne!(other.as_ptr(), ptr);
+        drop(other);
+
+        let sliced = frozen.slice_with_length(10, 10);
+        drop(frozen);
+        assert_eq!(pool.pooled_bytes(), 0);
+        drop(sliced);
+        assert_eq!(pool.pooled_bytes(), 8 * KIB as u64);
+
+        // The allocation comes back empty, nothing of the old contents is visible
+        let reused = pool.acquire(6000);
+        assert_eq!(reused.as_ptr(), ptr);
+        assert!(reused.is_empty());
+        assert_eq!(pool.num_reused(), 1);
+        assert_eq!(pool.pooled_bytes(), 0);
+    }
+
+    #[test]
+    fn test_bound_respected() {
+        let pool = Arc::new(BufferPool::new(20 * KIB as u64));
+        let buffers = (0..10)
+            .map(|_| {
+                let mut buf = pool.acquire(8 * KIB);
+                buf.resize(8 * KIB, 1);
+                pool.freeze(buf)
+            })
+            .collect::<Vec<_>>();
+        drop(buffers);
+        // Only two 8KiB buffers fit in 20KiB
+        assert_eq!(pool.pooled_bytes(), 16 * KIB as u64);
+        assert!(pool.pooled_bytes() <= pool.max_bytes());
+
+        let big = pool.acquire(64 * KIB);
+        drop(pool.freeze(big));
+        assert_eq!(pool.pooled_bytes(), 16 * KIB as u64);
+    }
+
+    #[test]
+    fn test_scope() {
+        let pool = Arc::new(BufferPool::new(1024 * 1024));
+        let buf = BufferPool::scope(Some(&pool), || {
+            let mut buf = pooled_vec(8 * KIB);
+            buf.push(1);
+            freeze_pooled(buf)
+        });
+        drop(buf);
+        let expected = if pooling_disabled() { 0 } else { 8 * KIB };
+        assert_eq!(pool.pooled_bytes(), expected as u64);
+
+        // Outside of a scope nothing goes back to the pool
+        let mut buf = pooled_vec(8 * KIB);
+        buf.push(1);
+        drop(freeze_pooled(buf));
+        assert_eq!(pool.pooled_bytes(), expected as u64);
+    }
+
+    #[test]
+    fn test_pool_dropped_first() {
+        let pool = Arc::new(BufferPool::new(1024 * 1024));
+        let buf = pool.freeze(pool.acquire(8 * KIB));
+        drop(pool);
+        drop(buf);
+    }
+}
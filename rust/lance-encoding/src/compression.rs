@@ -22,7 +22,8 @@ use crate::{
     buffer::LanceBuffer,
     compression_config::{BssMode, CompressionFieldParams, CompressionParams},
     constants::{
-        BSS_META_KEY, COMPRESSION_LEVEL_META_KEY, COMPRESSION_META_KEY, RLE_THRESHOLD_META_KEY,
+        BSS_META_KEY, COMPRESSION_LEVEL_META_KEY, COMPRESSION_META_KEY, CUSTOM_ENCODING_META_KEY,
+        CUSTOM_ENCODING_PARAMS_META_KEY, RLE_THRESHOLD_META_KEY,
     },
     data::{DataBlock, FixedWidthDataBlock, VariableWidthBlock},
     encodings::{
@@ -40,6 +41,9 @@ use crate::{
                 ByteStreamSplitDecompressor, ByteStreamSplitEncoder, should_use_bss,
             },
             constant::ConstantDecompressor,
+            custom::{
+                CustomEncodingRegistry, CustomMiniBlockCompressor, CustomMiniBlockDecompressor,
+            },
             fsst::{
                 FsstMiniBlockDecompressor, FsstMiniBlockEncoder, FsstPerValueDecompressor,
                 FsstPerValueEncoder,
@@ -65,6 +69,7 @@ use crate::{
 
 use arrow_array::{cast::AsArray, types::UInt64Type};
 use arrow_schema::DataType;
+use bytes::Bytes;
 use fsst::fsst::{FSST_LEAST_INPUT_MAX_LENGTH, FSST_LEAST_INPUT_SIZE};
 use lance_core::{Error, Result, datatypes::Field, error::LanceOptionExt};
 use std::{str::FromStr, sync::Arc};
@@ -140,6 +145,8 @@ pub struct DefaultCompressionStrategy {
     params: CompressionParams,
     /// The lance file version for compatibilities.
     version: LanceFileVersion,
+    /// Codecs that fields may select with [`CUSTOM_ENCODING_META_KEY`]
+    custom_encodings: CustomEncodingRegistry,
 }
 
 fn try_bss_for_mini_block(
@@ -383,7 +390,7 @@ impl DefaultCompressionStrategy {
     pub fn with_params(params: CompressionParams) -> Self {
         Self {
             params,
-            version: LanceFileVersion::default(),
+            ..Default::default()
         }
     }
 
@@ -393,6 +400,62 @@ impl DefaultCompressionStrategy {
         self
     }
 
+    /// Make custom codecs available to fields that request them with
+    /// [`CUSTOM_ENCODING_META_KEY`]
+    pub fn with_custom_encodings(mut self, custom_encodings: CustomEncodingRegistry) -> Self {
+        self.custom_encodings = custom_encodings;
+        self
+    }
+
+    /// Wrap `compressor` with the custom codec requested by the field metadata, if any
+    fn maybe_wrap_custom_for_mini_block(
+        &self,
+        field: &Field,
+        compressor: Box<dyn MiniBlockCompressor>,
+    ) -> Result<Box<dyn MiniBlockCompressor>> {
+        let Some(encoding_id) = field.metadata.get(CUSTOM_ENCODING_META_KEY) else {
+            return Ok(compressor);
+        };
+        if self.version.resolve() < LanceFileVersion::V2_3 {
+            return Err(Error::not_supported(format!(
+                "Field '{}' requests the custom encoding '{}' but custom encodings require \
+                 Lance file version 2.3 or later",
+                field.name, encoding_id
+            )));
+        }
+        let encoding = self.custom_encodings.get(encoding_id).ok_or_else(|| {
+            Error::invalid_input(format!(
+                "Field '{}' requests the custom encoding '{}' but no codec is registered under \
+                 that id.  Register it with DefaultCompressionStrategy::with_custom_encodings",
+                field.name, encoding_id
+            ))
+        })?;
+        let parameters = field
+            .metadata
+            .get(CUSTOM_ENCODING_PARAMS_META_KEY)
+            .map(|params| Bytes::copy_from_slice(params.as_bytes()))
+            .unwrap_or_default();
+        Ok(Box::new(CustomMiniBlockCompressor::new(
+            compressor,
+            encoding_id.as_str(),
+            parameters,
+            encoding.clone(),
+        )))
+    }
+
+    /// Custom codecs only run on mini-block pages, so fail instead of silently
+    /// writing a field that requests one with another layout
+    fn ensure_no_custom_encoding(field: &Field, layout: &str) -> Result<()> {
+        match field.metadata.get(CUSTOM_ENCODING_META_KEY) {
+            Some(encoding_id) => Err(Error::not_supported(format!(
+                "Field '{}' requests the custom encoding '{}' but custom encodings are only \
+                 supported for mini-block pages, not {}",
+                field.name, encoding_id, layout
+            ))),
+            None => Ok(()),
+        }
+    }
+
     /// Parse compression parameters from field metadata
     fn parse_field_metadata(field: &Field, version: &LanceFileVersion) -> CompressionFieldParams {
         let mut params = CompressionFieldParams::default();
@@ -534,12 +597,17 @@ impl CompressionStrategy for DefaultCompressionStrategy {
         match data {
             DataBlock::FixedWidth(fixed_width_data) => {
                 let field_params = self.get_merged_field_params(field);
-                self.build_fixed_width_compressor(&field_params, fixed_width_data)
+                let compressor =
+                    self.build_fixed_width_compressor(&field_params, fixed_width_data)?;
+                self.maybe_wrap_custom_for_mini_block(field, compressor)
             }
             DataBlock::VariableWidth(variable_width_data) => {
-                self.build_variable_width_compressor(field, variable_width_data)
+                let compressor =
+                    self.build_variable_width_compressor(field, variable_width_data)?;
+                self.maybe_wrap_custom_for_mini_block(field, compressor)
             }
             DataBlock::Struct(struct_data_block) => {
+                Self::ensure_no_custom_encoding(field, "packed struct pages")?;
                 // this condition is actually checked at `PrimitiveStructuralEncoder::do_flush`,
                 // just being cautious here.
                 if struct_data_block.has_variable_width_child() {
@@ -557,6 +625,7 @@ impl CompressionStrategy for DefaultCompressionStrategy {
                 //
                 // For now, we just don't compress.  In the future, we might want to consider a more
                 // sophisticated approach.
+                Self::ensure_no_custom_encoding(field, "fixed-size list pages")?;
                 Ok(Box::new(ValueEncoder::default()))
             }
             _ => Err(Error::not_supported_source(
@@ -574,6 +643,7 @@ impl CompressionStrategy for DefaultCompressionStrategy {
         field: &Field,
         data: &DataBlock,
     ) -> Result<Box<dyn PerValueCompressor>> {
+        Self::ensure_no_custom_encoding(field, "full-zip pages")?;
         let field_params = self.get_merged_field_params(field);
 
         match data {
@@ -760,7 +830,16 @@ pub trait DecompressionStrategy: std::fmt::Debug + Send + Sync {
 }
 
 #[derive(Debug, Default)]
-pub struct DefaultDecompressionStrategy {}
+pub struct DefaultDecompressionStrategy {
+    custom_encodings: CustomEncodingRegistry,
+}
+
+impl DefaultDecompressionStrategy {
+    /// Create a strategy that can also decode data written with the given custom codecs
+    pub fn with_custom_encodings(custom_encodings: CustomEncodingRegistry) -> Self {
+        Self { custom_encodings }
+    }
+}
 
 impl DecompressionStrategy for DefaultDecompressionStrategy {
     fn create_miniblock_decompressor(
@@ -851,6 +930,20 @@ impl DecompressionStrategy for DefaultDecompressionStrategy {
                     compression_config,
                 )))
             }
+            Compression::Custom(custom) => {
+                let encoding = self.custom_encodings.get_for_read(&custom.encoding_id)?;
+                let inner_decompressor = self.create_miniblock_decompressor(
+                    custom.values.as_ref().ok_or_else(|| {
+                        Error::invalid_input("Custom encoding missing inner encoding")
+                    })?,
+                    decompression_strategy,
+                )?;
+                Ok(Box::new(CustomMiniBlockDecompressor::new(
+                    inner_decompressor,
+                    custom.parameters.clone(),
+                    encoding,
+                )))
+            }
             _ => todo!(),
         }
     }
@@ -17,6 +17,23 @@ pub const RLE_THRESHOLD_META_KEY: &str = "lance-encoding:rle-threshold";
 pub const MINICHUNK_SIZE_META_KEY: &str = "lance-encoding:minichunk-size";
 
 // Dictionary encoding metadata keys
+/// Metadata key for controlling dictionary encoding
+/// Valid values: "always", "never", "auto"
+/// "always" dictionary encodes every page that supports it, "never" disables dictionary
+/// encoding (input that is already dictionary encoded is still written as a dictionary) and
+/// "auto" decides per page based on the estimated cardinality and size.
+/// Default: "auto"
+pub const DICT_ENCODING_META_KEY: &str = "lance-encoding:dictionary";
+/// Metadata key for the maximum number of dictionary entries when the dictionary encoding
+/// is "auto".  Overrides the LANCE_ENCODING_DICT_MAX_CARDINALITY environment variable.
+/// Default: 100000
+pub const DICT_MAX_CARDINALITY_META_KEY: &str = "lance-encoding:dict-max-cardinality";
+/// Metadata key for the maximum size (in bytes) of a column's shared dictionary
+/// Once the shared dictionary would grow beyond this size the remaining pages of the column
+/// fall back to per-page dictionaries.  Shared dictionaries require file version 2.3+.
+/// Set to 0 to disable shared dictionaries.
+/// Default: 1048576 (1MiB)
+pub const SHARED_DICT_MAX_SIZE_META_KEY: &str = "lance-encoding:shared-dict-max-size";
 /// Metadata key for specifying dictionary encoding threshold divisor
 /// Set to a large value to discourage dictionary encoding
 /// Set to a small value to encourage dictionary encoding
@@ -61,3 +78,11 @@ pub const STRUCTURAL_ENCODING_FULLZIP: &str = "fullzip";
 pub const BSS_META_KEY: &str = "lance-encoding:bss";
 /// Default BSS mode
 pub const DEFAULT_BSS_MODE: &str = "auto";
+
+// Custom encoding metadata keys
+/// Metadata key for selecting a codec registered in a
+/// [`crate::encodings::physical::custom::CustomEncodingRegistry`]
+pub const CUSTOM_ENCODING_META_KEY: &str = "lance-encoding:custom-encoding";
+/// Metadata key for parameters passed to the custom codec
+/// These are stored alongside the encoded data so the reader sees the same values
+pub const CUSTOM_ENCODING_PARAMS_META_KEY: &str = "lance-encoding:custom-encoding-params";
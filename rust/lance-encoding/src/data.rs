@@ -34,6 +34,7 @@ use lance_core::{Error, Result};
 
 use crate::{
     buffer::LanceBuffer,
+    buffer_pool::{freeze_pooled, pooled_vec},
     statistics::{ComputeStat, Stat},
 };
 
@@ -230,7 +231,7 @@ impl<T: OffsetSizeTrait> VariableWidthDataBlockBuilder<T> {
     fn new(estimated_size_bytes: u64) -> Self {
         Self {
             offsets: vec![T::from_usize(0).unwrap()],
-            bytes: Vec::with_capacity(estimated_size_bytes as usize),
+            bytes: pooled_vec(estimated_size_bytes as usize),
         }
     }
 }
@@ -263,7 +264,7 @@ impl<T: OffsetSizeTrait + bytemuck::Pod> DataBlockBuilderImpl for VariableWidthD
     fn finish(self: Box<Self>) -> DataBlock {
         let num_values = (self.offsets.len() - 1) as u64;
         DataBlock::VariableWidth(VariableWidthBlock {
-            data: LanceBuffer::from(self.bytes),
+            data: freeze_pooled(self.bytes),
             offsets: LanceBuffer::reinterpret_vec(self.offsets),
             bits_per_offset: T::get_byte_width() as u8 * 8,
             num_values,
@@ -320,7 +321,7 @@ impl FixedWidthDataBlockBuilder {
         Self {
             bits_per_value,
             bytes_per_value: bits_per_value / 8,
-            values: Vec::with_capacity(estimated_size_bytes as usize),
+            values: pooled_vec(estimated_size_bytes as usize),
         }
     }
 }
@@ -337,7 +338,7 @@ impl DataBlockBuilderImpl for FixedWidthDataBlockBuilder {
     fn finish(self: Box<Self>) -> DataBlock {
         let num_values = (self.values.len() / self.bytes_per_value as usize) as u64;
         DataBlock::FixedWidth(FixedWidthDataBlock {
-            data: LanceBuffer::from(self.values),
+            data: freeze_pooled(self.values),
             bits_per_value: self.bits_per_value,
             num_values,
             block_info: BlockInfo::new(),
@@ -237,6 +237,7 @@ use lance_core::error::LanceOptionExt;
 use lance_core::{ArrowResult, Error, Result};
 use tracing::instrument;
 
+use crate::buffer_pool::BufferPool;
 use crate::compression::{DecompressionStrategy, DefaultDecompressionStrategy};
 use crate::data::DataBlock;
 use crate::encoder::EncodedBatch;
@@ -245,6 +246,7 @@ use crate::encodings::logical::list::StructuralListScheduler;
 use crate::encodings::logical::map::StructuralMapScheduler;
 use crate::encodings::logical::primitive::StructuralPrimitiveFieldScheduler;
 use crate::encodings::logical::r#struct::{StructuralStructDecoder, StructuralStructScheduler};
+use crate::encodings::physical::custom::CustomEncodingRegistry;
 use crate::format::pb::{self, column_encoding};
 use crate::format::pb21;
 use crate::previous::decoder::LogicalPageDecoder;
@@ -371,6 +373,26 @@ impl ColumnInfo {
             .map(|page| page.encoding.is_structural())
             .unwrap_or(false)
     }
+
+    /// True if any page of the column references a dictionary stored in the column buffers
+    pub fn has_shared_dictionary(&self) -> bool {
+        fn layout_has_shared_dictionary(layout: &pb21::PageLayout) -> bool {
+            match layout.layout.as_ref() {
+                Some(pb21::page_layout::Layout::MiniBlockLayout(mini_block)) => {
+                    mini_block.shared_dictionary.is_some()
+                }
+                Some(pb21::page_layout::Layout::BlobLayout(blob)) => blob
+                    .inner_layout
+                    .as_deref()
+                    .is_some_and(layout_has_shared_dictionary),
+                _ => false,
+            }
+        }
+        self.page_infos.iter().any(|page| match &page.encoding {
+            PageEncoding::Structural(layout) => layout_has_shared_dictionary(layout),
+            PageEncoding::Legacy(_) => false,
+        })
+    }
 }
 
 enum RootScheduler {
@@ -509,7 +531,7 @@ impl Default for CoreFieldDecoderStrategy {
     fn default() -> Self {
         Self {
             validate_data: false,
-            decompressor_strategy: Arc::new(DefaultDecompressionStrategy {}),
+            decompressor_strategy: Arc::new(DefaultDecompressionStrategy::default()),
             cache_repetition_index: false,
         }
     }
@@ -526,11 +548,22 @@ impl CoreFieldDecoderStrategy {
     pub fn from_decoder_config(config: &DecoderConfig) -> Self {
         Self {
             validate_data: config.validate_on_decode,
-            decompressor_strategy: Arc::new(DefaultDecompressionStrategy {}),
+            decompressor_strategy: Arc::new(DefaultDecompressionStrategy::default()),
             cache_repetition_index: config.cache_repetition_index,
         }
     }
 
+    /// Decompress with the custom codecs registered in `decoder_plugins`
+    pub fn with_decoder_plugins(mut self, decoder_plugins: &DecoderPlugins) -> Self {
+        if !decoder_plugins.custom_encodings.is_empty() {
+            self.decompressor_strategy =
+                Arc::new(DefaultDecompressionStrategy::with_custom_encodings(
+                    decoder_plugins.custom_encodings.clone(),
+                ));
+        }
+        self
+    }
+
     /// This is just a sanity check to ensure there is no "wrapped encodings"
     /// that haven't been handled.
     fn ensure_values_encoded(column_info: &ColumnInfo, field_name: &str) -> Result<()> {
@@ -1024,7 +1057,7 @@ impl DecodeBatchScheduler {
         column_infos: &[Arc<ColumnInfo>],
         file_buffer_positions_and_sizes: &'a Vec<(u64, u64)>,
         num_rows: u64,
-        _decoder_plugins: Arc<DecoderPlugins>,
+        decoder_plugins: Arc<DecoderPlugins>,
         io: Arc<dyn EncodingsIo>,
         cache: Arc<LanceCache>,
         filter: &FilterExpression,
@@ -1049,7 +1082,8 @@ impl DecodeBatchScheduler {
         if column_infos.is_empty() || column_infos[0].is_structural() {
             let mut column_iter = ColumnInfoIter::new(column_infos.to_vec(), column_indices);
 
-            let strategy = CoreFieldDecoderStrategy::from_decoder_config(decoder_config);
+            let strategy = CoreFieldDecoderStrategy::from_decoder_config(decoder_config)
+                .with_decoder_plugins(&decoder_plugins);
             let mut root_scheduler =
                 strategy.create_structural_field_scheduler(&root_field, &mut column_iter)?;
 
@@ -1488,12 +1522,11 @@ impl BatchDecodeStream {
                     // Real decode work happens inside into_batch, which can block the current
                     // thread for a long time. By spawning it as a new task, we allow Tokio's
                     // worker threads to keep making progress.
-                    let (batch, _data_size) =
-                        tokio::spawn(
-                            async move { next_task.into_batch(emitted_batch_size_warning) },
-                        )
-                        .await
-                        .map_err(|err| Error::wrapped(err.into()))??;
+                    let (batch, _data_size) = tokio::spawn(async move {
+                        next_task.into_batch(emitted_batch_size_warning, None)
+                    })
+                    .await
+                    .map_err(|err| Error::wrapped(err.into()))??;
                     Ok(batch)
                 };
                 (task, num_rows)
@@ -1680,7 +1713,8 @@ impl<T: RootDecoderType> BatchDecodeIterator<T> {
 
         self.rows_drained += to_take;
 
-        let (batch, _data_size) = next_task.into_batch(self.emitted_batch_size_warning.clone())?;
+        let (batch, _data_size) =
+            next_task.into_batch(self.emitted_batch_size_warning.clone(), None)?;
 
         Ok(Some(batch))
     }
@@ -1761,6 +1795,7 @@ pub struct StructuralBatchDecodeStream {
     /// Post-decode feedback: actual bytes-per-row measured from the most
     /// recently decoded batch.  Zero means no feedback yet (use schema estimate).
     bytes_per_row_feedback: Arc<AtomicU64>,
+    buffer_pool: Option<Arc<BufferPool>>,
 }
 
 impl StructuralBatchDecodeStream {
@@ -1799,9 +1834,16 @@ impl StructuralBatchDecodeStream {
             batch_size_bytes,
             schema_bytes_per_row,
             bytes_per_row_feedback: Arc::new(AtomicU64::new(0)),
+            buffer_pool: None,
         }
     }
 
+    /// Decode batches with buffers from `buffer_pool`, see [`DecoderConfig::buffer_pool`]
+    pub fn with_buffer_pool(mut self, buffer_pool: Option<Arc<BufferPool>>) -> Self {
+        self.buffer_pool = buffer_pool;
+        self
+    }
+
     #[instrument(level = "debug", skip_all)]
     async fn wait_for_scheduled(&mut self, scheduled_need: u64) -> Result<u64> {
         if self.scheduler_exhausted {
@@ -1892,16 +1934,17 @@ impl StructuralBatchDecodeStream {
                 // Capture the per-stream policy once so every emitted batch task follows the
                 // same throughput-vs-overhead choice made by the scheduler.
                 let spawn_batch_decode_tasks = slf.spawn_batch_decode_tasks;
+                let buffer_pool = slf.buffer_pool.clone();
                 let task = async move {
                     let next_task = next_task?;
                     let (batch, data_size) = if spawn_batch_decode_tasks {
-                        tokio::spawn(
-                            async move { next_task.into_batch(emitted_batch_size_warning) },
-                        )
+                        tokio::spawn(async move {
+                            next_task.into_batch(emitted_batch_size_warning, buffer_pool.as_ref())
+                        })
                         .await
                         .map_err(|err| Error::wrapped(err.into()))??
                     } else {
-                        next_task.into_batch(emitted_batch_size_warning)?
+                        next_task.into_batch(emitted_batch_size_warning, buffer_pool.as_ref())?
                     };
                     let num_rows = batch.num_rows() as u64;
                     if num_rows > 0 {
@@ -1989,6 +2032,11 @@ pub struct DecoderConfig {
     /// * `Some(false)` - always spawn a task for scheduling so that it can
     ///   overlap with consumption of the decode stream.
     pub inline_scheduling: Option<bool>,
+    /// If set, decoded batches draw their buffers from this pool and return them when
+    /// dropped, see [`crate::buffer_pool`].
+    ///
+    /// This only applies to 2.1+ (structural) files.
+    pub buffer_pool: Option<Arc<BufferPool>>,
 }
 
 impl Default for DecoderConfig {
@@ -1997,6 +2045,7 @@ impl Default for DecoderConfig {
             cache_repetition_index: default_cache_repetition_index(),
             validate_on_decode: false,
             inline_scheduling: None,
+            buffer_pool: None,
         }
     }
 }
@@ -2059,6 +2108,7 @@ pub fn create_decode_stream(
     spawn_structural_batch_decode_tasks: bool,
     rx: mpsc::UnboundedReceiver<Result<DecoderMessage>>,
     batch_size_bytes: Option<u64>,
+    buffer_pool: Option<Arc<BufferPool>>,
 ) -> Result<BoxStream<'static, ReadBatchTask>> {
     if is_structural {
         let arrow_schema = ArrowSchema::from(schema);
@@ -2075,6 +2125,7 @@ pub fn create_decode_stream(
             spawn_structural_batch_decode_tasks,
             batch_size_bytes,
         )
+        .with_buffer_pool(buffer_pool)
         .into_stream())
     } else {
         if batch_size_bytes.is_some() {
@@ -2152,6 +2203,7 @@ async fn create_scheduler_decoder(
         spawn_structural_batch_decode_tasks,
         rx,
         config.batch_size_bytes,
+        config.decoder_config.buffer_pool.clone(),
     )?;
 
     // The scheduler's `initialize` may perform I/O to load column metadata
@@ -2696,11 +2748,15 @@ impl NextDecodeTask {
     //
     // If the batch is very large this function will log a warning message
     // suggesting the user try a smaller batch size.
+    //
+    // Buffers allocated while decoding come from `buffer_pool`, if set.
     #[instrument(name = "task_to_batch", level = "debug", skip_all)]
-    fn into_batch(self, emitted_batch_size_warning: Arc<Once>) -> Result<(RecordBatch, u64)> {
-        let (struct_arr, data_size) = self
-            .task
-            .decode()
+    fn into_batch(
+        self,
+        emitted_batch_size_warning: Arc<Once>,
+        buffer_pool: Option<&Arc<BufferPool>>,
+    ) -> Result<(RecordBatch, u64)> {
+        let (struct_arr, data_size) = BufferPool::scope(buffer_pool, || self.task.decode())
             .map_err(|e| Error::internal(format!("Error decoding batch: {}", e)))?;
         let batch = RecordBatch::from(struct_arr.as_struct());
         if data_size > BATCH_SIZE_BYTES_WARNING {
@@ -2827,8 +2883,19 @@ pub trait StructuralFieldDecoder: std::fmt::Debug + Send {
     fn data_type(&self) -> &DataType;
 }
 
+/// Extensions to the decoder that are supplied by the application
 #[derive(Debug, Default)]
-pub struct DecoderPlugins {}
+pub struct DecoderPlugins {
+    /// Codecs used to decode columns written with a custom encoding
+    pub custom_encodings: CustomEncodingRegistry,
+}
+
+impl DecoderPlugins {
+    pub fn with_custom_encodings(mut self, custom_encodings: CustomEncodingRegistry) -> Self {
+        self.custom_encodings = custom_encodings;
+        self
+    }
+}
 
 /// Decodes a batch of data from an in-memory structure created by [`crate::encoder::encode_batch`]
 pub async fn decode_batch(
@@ -2878,6 +2945,7 @@ pub async fn decode_batch(
         spawn_structural_batch_decode_tasks,
         rx,
         None,
+        None,
     )?;
     decode_stream.next().await.unwrap().task.await
 }
@@ -2986,6 +3054,7 @@ mod tests {
             /*spawn_structural_batch_decode_tasks=*/ true,
             rx,
             batch_size_bytes,
+            None,
         )
         .unwrap();
 
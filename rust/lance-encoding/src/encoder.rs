@@ -34,6 +34,7 @@ use crate::encodings::logical::list::ListStructuralEncoder;
 use crate::encodings::logical::map::MapStructuralEncoder;
 use crate::encodings::logical::primitive::PrimitiveStructuralEncoder;
 use crate::encodings::logical::r#struct::StructStructuralEncoder;
+use crate::encodings::physical::custom::CustomEncodingRegistry;
 use crate::repdef::RepDefBuilder;
 use crate::version::LanceFileVersion;
 use crate::{
@@ -317,6 +318,28 @@ pub fn default_encoding_strategy_with_params(
     }
 }
 
+/// Create the default encoding strategy, making the given custom codecs available to
+/// fields that request them with [`crate::constants::CUSTOM_ENCODING_META_KEY`]
+///
+/// Only Lance file version 2.3 and later support custom encodings, writing a field that
+/// requests one with an older version fails.
+pub fn default_encoding_strategy_with_custom_encodings(
+    version: LanceFileVersion,
+    custom_encodings: CustomEncodingRegistry,
+) -> Box<dyn FieldEncodingStrategy> {
+    match version.resolve() {
+        LanceFileVersion::Legacy | LanceFileVersion::V2_0 => default_encoding_strategy(version),
+        _ => Box::new(StructuralEncodingStrategy {
+            compression_strategy: Arc::new(
+                DefaultCompressionStrategy::new()
+                    .with_version(version)
+                    .with_custom_encodings(custom_encodings),
+            ),
+            version,
+        }),
+    }
+}
+
 /// An encoding strategy used for 2.1+ files
 #[derive(Debug)]
 pub struct StructuralEncodingStrategy {
@@ -380,6 +403,30 @@ impl StructuralEncodingStrategy {
         }
     }
 
+    // Leaves nested in structs, lists and maps decide on dictionary encoding from their
+    // own cardinality sample, see `PrimitiveStructuralEncoder::with_leaf_cardinality_sampling`
+    fn create_primitive_encoder(
+        &self,
+        field: &Field,
+        column_index: &mut ColumnIndexSequence,
+        options: &EncodingOptions,
+        root_field_metadata: &HashMap<String, String>,
+        nested: bool,
+    ) -> Result<Box<dyn FieldEncoder>> {
+        let encoder = PrimitiveStructuralEncoder::try_new(
+            options,
+            self.compression_strategy.clone(),
+            column_index.next_column_index(field.id as u32),
+            field.clone(),
+            Arc::new(root_field_metadata.clone()),
+        )?;
+        if nested {
+            Ok(Box::new(encoder.with_leaf_cardinality_sampling()))
+        } else {
+            Ok(Box::new(encoder))
+        }
+    }
+
     fn do_create_field_encoder(
         &self,
         _encoding_strategy_root: &dyn FieldEncodingStrategy,
@@ -387,6 +434,7 @@ impl StructuralEncodingStrategy {
         column_index: &mut ColumnIndexSequence,
         options: &EncodingOptions,
         root_field_metadata: &HashMap<String, String>,
+        nested: bool,
     ) -> Result<Box<dyn FieldEncoder>> {
         let data_type = field.data_type();
 
@@ -427,13 +475,7 @@ impl StructuralEncodingStrategy {
         }
 
         if Self::is_primitive_type(&data_type) {
-            Ok(Box::new(PrimitiveStructuralEncoder::try_new(
-                options,
-                self.compression_strategy.clone(),
-                column_index.next_column_index(field.id as u32),
-                field.clone(),
-                Arc::new(root_field_metadata.clone()),
-            )?))
+            self.create_primitive_encoder(field, column_index, options, root_field_metadata, nested)
         } else {
             match data_type {
                 DataType::List(_) | DataType::LargeList(_) => {
@@ -444,6 +486,7 @@ impl StructuralEncodingStrategy {
                         column_index,
                         options,
                         root_field_metadata,
+                        true,
                     )?;
                     Ok(Box::new(ListStructuralEncoder::new(
                         options.keep_original_array,
@@ -468,6 +511,7 @@ impl StructuralEncodingStrategy {
                         column_index,
                         options,
                         root_field_metadata,
+                        true,
                     )?;
                     Ok(Box::new(FixedSizeListStructuralEncoder::new(
                         options.keep_original_array,
@@ -514,6 +558,7 @@ impl StructuralEncodingStrategy {
                         column_index,
                         options,
                         root_field_metadata,
+                        true,
                     )?;
                     Ok(Box::new(MapStructuralEncoder::new(
                         options.keep_original_array,
@@ -523,13 +568,13 @@ impl StructuralEncodingStrategy {
                 DataType::Struct(fields) => {
                     if field.is_packed_struct() || fields.is_empty() {
                         // Both packed structs and empty structs are encoded as primitive
-                        Ok(Box::new(PrimitiveStructuralEncoder::try_new(
+                        self.create_primitive_encoder(
+                            field,
+                            column_index,
                             options,
-                            self.compression_strategy.clone(),
-                            column_index.next_column_index(field.id as u32),
-                            field.clone(),
-                            Arc::new(root_field_metadata.clone()),
-                        )?))
+                            root_field_metadata,
+                            nested,
+                        )
                     } else {
                         let children_encoders = field
                             .children
@@ -541,6 +586,7 @@ impl StructuralEncodingStrategy {
                                     column_index,
                                     options,
                                     root_field_metadata,
+                                    true,
                                 )
                             })
                             .collect::<Result<Vec<_>>>()?;
@@ -553,13 +599,13 @@ impl StructuralEncodingStrategy {
                 DataType::Dictionary(_, value_type) => {
                     // A dictionary of primitive is, itself, primitive
                     if Self::is_primitive_type(&value_type) {
-                        Ok(Box::new(PrimitiveStructuralEncoder::try_new(
+                        self.create_primitive_encoder(
+                            field,
+                            column_index,
                             options,
-                            self.compression_strategy.clone(),
-                            column_index.next_column_index(field.id as u32),
-                            field.clone(),
-                            Arc::new(root_field_metadata.clone()),
-                        )?))
+                            root_field_metadata,
+                            nested,
+                        )
                     } else {
                         // A dictionary of logical is, itself, logical and we don't support that today
                         // It could be possible (e.g. store indices in one column and values in remaining columns)
@@ -589,6 +635,7 @@ impl FieldEncodingStrategy for StructuralEncodingStrategy {
             column_index,
             options,
             &field.metadata,
+            false,
         )
     }
 }
@@ -240,8 +240,8 @@ mod tests {
         STRUCTURAL_ENCODING_FULLZIP, STRUCTURAL_ENCODING_META_KEY, STRUCTURAL_ENCODING_MINIBLOCK,
     };
     use arrow_array::{
-        Array, ArrayRef, BooleanArray, DictionaryArray, LargeStringArray, ListArray, StructArray,
-        UInt8Array, UInt64Array,
+        Array, ArrayRef, BooleanArray, DictionaryArray, LargeListArray, LargeStringArray,
+        ListArray, StructArray, UInt8Array, UInt64Array,
         builder::{
             Int32Builder, Int64Builder, LargeListBuilder, ListBuilder, StringBuilder, UInt32Builder,
         },
@@ -962,6 +962,29 @@ mod tests {
         check_round_trip_encoding_of_data(arrs, &test_cases, HashMap::new()).await;
     }
 
+    #[test_log::test(tokio::test)]
+    #[ignore] // This test is quite slow in debug mode
+    async fn test_jumbo_large_list() {
+        // Unlike the test above, the offsets overflow i32 within a single
+        // array, so this only works with 64-bit offsets.
+        let items = BooleanArray::new_null(3 * 1024 * 1024 * 1024);
+        let offsets = OffsetBuffer::new(ScalarBuffer::from(vec![
+            0,
+            1024 * 1024,
+            (2 * 1024 + 1) * 1024 * 1024,
+            3 * 1024 * 1024 * 1024,
+        ]));
+        let list_arr = Arc::new(LargeListArray::new(
+            Arc::new(Field::new("item", DataType::Boolean, true)),
+            offsets,
+            Arc::new(items),
+            None,
+        )) as ArrayRef;
+
+        let test_cases = TestCases::default().without_validation();
+        check_round_trip_encoding_of_data(vec![list_arr], &test_cases, HashMap::new()).await;
+    }
+
     // Regression test for issue with ListArray encoding when crossing 1024 value boundary
     // This test reproduces the bug where rows_avail assertion fails in schedule_instructions
     // when encoding a ListArray with specific size patterns that cross the 1024 value boundary
@@ -8,7 +8,7 @@ use std::{
     fmt::Debug,
     iter,
     ops::Range,
-    sync::Arc,
+    sync::{Arc, Mutex, MutexGuard},
     vec,
 };
 
@@ -62,13 +62,17 @@ use crate::{
     },
     utils::accumulation::AccumulationQueue,
 };
-use lance_core::{Result, datatypes::Field, utils::tokio::spawn_cpu};
+use lance_core::{
+    Result, datatypes::Field, utils::encryption::ENCRYPTION_KEY_ID_META_KEY,
+    utils::tokio::spawn_cpu,
+};
 
 use crate::constants::{
     COMPRESSION_LEVEL_META_KEY, COMPRESSION_META_KEY, DICT_DIVISOR_META_KEY,
-    DICT_SIZE_RATIO_META_KEY, DICT_VALUES_COMPRESSION_ENV_VAR,
-    DICT_VALUES_COMPRESSION_LEVEL_ENV_VAR, DICT_VALUES_COMPRESSION_LEVEL_META_KEY,
-    DICT_VALUES_COMPRESSION_META_KEY,
+    DICT_ENCODING_META_KEY, DICT_MAX_CARDINALITY_META_KEY, DICT_SIZE_RATIO_META_KEY,
+    DICT_VALUES_COMPRESSION_ENV_VAR, DICT_VALUES_COMPRESSION_LEVEL_ENV_VAR,
+    DICT_VALUES_COMPRESSION_LEVEL_META_KEY, DICT_VALUES_COMPRESSION_META_KEY,
+    SHARED_DICT_MAX_SIZE_META_KEY,
 };
 use crate::version::LanceFileVersion;
 use crate::{
@@ -98,6 +102,7 @@ const DEFAULT_DICT_DIVISOR: u64 = 2;
 const DEFAULT_DICT_MAX_CARDINALITY: u64 = 100_000;
 const DEFAULT_DICT_SIZE_RATIO: f64 = 0.8;
 const DEFAULT_DICT_VALUES_COMPRESSION: &str = "lz4";
+const DEFAULT_SHARED_DICT_MAX_SIZE: usize = 1024 * 1024;
 
 struct PageLoadTask {
     decoder_fut: BoxFuture<'static, Result<Box<dyn StructuralPageDecoder>>>,
@@ -1206,6 +1211,104 @@ struct MiniBlockSchedulerDictionary {
     num_dictionary_items: u64,
 }
 
+/// A dictionary that is stored in column buffers and shared by the pages of a column
+#[derive(Debug, Clone)]
+struct SharedDictionarySource {
+    layout: dict::SharedDictionaryLayout,
+    buffer_positions_and_sizes: Vec<(u64, u64)>,
+    // Shared by all pages that reference the dictionary so it is only loaded once
+    dictionary: Arc<tokio::sync::OnceCell<Arc<DataBlock>>>,
+}
+
+impl SharedDictionarySource {
+    async fn load(&self, io: &Arc<dyn EncodingsIo>) -> Result<Arc<DataBlock>> {
+        self.dictionary
+            .get_or_try_init(|| async {
+                let ranges = self
+                    .buffer_positions_and_sizes
+                    .iter()
+                    .filter(|(_, size)| *size > 0)
+                    .map(|(position, size)| *position..*position + *size)
+                    .collect::<Vec<_>>();
+                let mut fetched = io.submit_request(ranges, 0).await?.into_iter();
+                let alignments = match self.layout {
+                    dict::SharedDictionaryLayout::FixedWidth { bits_per_value } => {
+                        vec![bits_per_value / 8]
+                    }
+                    dict::SharedDictionaryLayout::VariableWidth { bits_per_offset } => {
+                        vec![bits_per_offset as u64 / 8, 1]
+                    }
+                };
+                let buffers = self
+                    .buffer_positions_and_sizes
+                    .iter()
+                    .zip(alignments)
+                    .map(|((_, size), alignment)| {
+                        if *size > 0 {
+                            let bytes = fetched.next().ok_or_else(|| {
+                                Error::internal(
+                                    "The I/O scheduler returned fewer buffers than requested for a shared dictionary",
+                                )
+                            })?;
+                            Ok(LanceBuffer::from_bytes(bytes, alignment))
+                        } else {
+                            Ok(LanceBuffer::empty())
+                        }
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(Arc::new(self.layout.decode(buffers)?))
+            })
+            .await
+            .cloned()
+    }
+}
+
+/// The shared dictionaries referenced by the pages of a column
+struct ColumnSharedDictionaries<'a> {
+    column_buffers: &'a [(u64, u64)],
+    dictionaries: HashMap<u32, SharedDictionarySource>,
+}
+
+impl<'a> ColumnSharedDictionaries<'a> {
+    fn new(column_buffers: &'a [(u64, u64)]) -> Self {
+        Self {
+            column_buffers,
+            dictionaries: HashMap::new(),
+        }
+    }
+
+    fn get(
+        &mut self,
+        shared_dictionary: &pb21::SharedDictionary,
+    ) -> Result<SharedDictionarySource> {
+        if let Some(source) = self
+            .dictionaries
+            .get(&shared_dictionary.column_buffer_index)
+        {
+            return Ok(source.clone());
+        }
+        let layout = dict::SharedDictionaryLayout::try_from_proto(shared_dictionary)?;
+        let start = shared_dictionary.column_buffer_index as usize;
+        let end = start + layout.num_buffers();
+        if end > self.column_buffers.len() {
+            return Err(Error::invalid_input(format!(
+                "A page references a shared dictionary in column buffers {}..{} but the column only has {} buffers",
+                start,
+                end,
+                self.column_buffers.len()
+            )));
+        }
+        let source = SharedDictionarySource {
+            layout,
+            buffer_positions_and_sizes: self.column_buffers[start..end].to_vec(),
+            dictionary: Arc::new(tokio::sync::OnceCell::new()),
+        };
+        self.dictionaries
+            .insert(shared_dictionary.column_buffer_index, source.clone());
+        Ok(source)
+    }
+}
+
 /// Individual block metadata within a MiniBlock repetition index.
 #[derive(Debug)]
 struct MiniBlockRepIndexBlock {
@@ -1374,6 +1477,7 @@ pub struct MiniBlockScheduler {
     value_decompressor: Arc<dyn MiniBlockDecompressor>,
     def_meaning: Arc<[DefinitionInterpretation]>,
     dictionary: Option<MiniBlockSchedulerDictionary>,
+    shared_dictionary: Option<SharedDictionarySource>,
     // This is set after initialization
     page_meta: Option<Arc<MiniBlockCacheableState>>,
     has_large_chunk: bool,
@@ -1386,6 +1490,7 @@ impl MiniBlockScheduler {
         items_in_page: u64,
         layout: &pb21::MiniBlockLayout,
         decompressors: &dyn DecompressionStrategy,
+        shared_dictionaries: &mut ColumnSharedDictionaries,
     ) -> Result<Self> {
         let rep_decompressor = layout
             .rep_compression
@@ -1447,6 +1552,11 @@ impl MiniBlockScheduler {
         } else {
             None
         };
+        let shared_dictionary = layout
+            .shared_dictionary
+            .as_ref()
+            .map(|shared_dictionary| shared_dictionaries.get(shared_dictionary))
+            .transpose()?;
 
         Ok(Self {
             buffer_offsets_and_sizes: buffer_offsets_and_sizes.to_vec(),
@@ -1458,6 +1568,7 @@ impl MiniBlockScheduler {
             priority,
             items_in_page,
             dictionary,
+            shared_dictionary,
             def_meaning: def_meaning.into(),
             page_meta: None,
             has_large_chunk: layout.has_large_chunk,
@@ -1867,6 +1978,7 @@ impl StructuralPageScheduler for MiniBlockScheduler {
             required_ranges.push(*rep_index_pos..*rep_index_pos + *rep_index_size);
         }
         let io_req = io.submit_request(required_ranges, 0);
+        let io = io.clone();
 
         async move {
             let mut buffers = io_req.await?.into_iter().fuse();
@@ -1932,6 +2044,9 @@ impl StructuralPageScheduler for MiniBlockScheduler {
                         dictionary.num_dictionary_items,
                     )?));
             };
+            if let Some(ref shared_dictionary) = self.shared_dictionary {
+                page_meta.dictionary = Some(shared_dictionary.load(&io).await?);
+            }
             let page_meta = Arc::new(page_meta);
             self.page_meta = Some(page_meta.clone());
             Ok(page_meta as Arc<dyn CachedPageData>)
@@ -3312,6 +3427,8 @@ impl StructuralPrimitiveFieldScheduler {
         cache_repetition_index: bool,
         target_field: &Field,
     ) -> Result<Self> {
+        let mut shared_dictionaries =
+            ColumnSharedDictionaries::new(&column_info.buffer_offsets_and_sizes);
         let page_schedulers = column_info
             .page_infos
             .iter()
@@ -3323,6 +3440,7 @@ impl StructuralPrimitiveFieldScheduler {
                     decompressors,
                     cache_repetition_index,
                     target_field,
+                    &mut shared_dictionaries,
                 )
             })
             .collect::<Result<Vec<_>>>()?;
@@ -3339,6 +3457,7 @@ impl StructuralPrimitiveFieldScheduler {
         decompressors: &dyn DecompressionStrategy,
         cache_repetition_index: bool,
         target_field: &Field,
+        shared_dictionaries: &mut ColumnSharedDictionaries,
     ) -> Result<Box<dyn StructuralPageScheduler>> {
         use pb21::page_layout::Layout;
         Ok(match page_layout.layout.as_ref().expect_ok()? {
@@ -3348,6 +3467,7 @@ impl StructuralPrimitiveFieldScheduler {
                 mini_block.num_items,
                 mini_block,
                 decompressors,
+                shared_dictionaries,
             )?),
             Layout::FullZipLayout(full_zip) => {
                 let mut scheduler = FullZipScheduler::try_new(
@@ -3412,6 +3532,7 @@ impl StructuralPrimitiveFieldScheduler {
                     decompressors,
                     cache_repetition_index,
                     target_field,
+                    shared_dictionaries,
                 )?;
                 let def_meaning = blob
                     .layers
@@ -3443,6 +3564,7 @@ impl StructuralPrimitiveFieldScheduler {
         decompressors: &dyn DecompressionStrategy,
         cache_repetition_index: bool,
         target_field: &Field,
+        shared_dictionaries: &mut ColumnSharedDictionaries,
     ) -> Result<PageInfoAndScheduler> {
         let page_layout = page_info.encoding.as_structural();
         let scheduler = Self::page_layout_to_scheduler(
@@ -3451,6 +3573,7 @@ impl StructuralPrimitiveFieldScheduler {
             decompressors,
             cache_repetition_index,
             target_field,
+            shared_dictionaries,
         )?;
         Ok(PageInfoAndScheduler {
             page_index,
@@ -3756,6 +3879,21 @@ pub struct PrimitiveStructuralEncoder {
     field: Field,
     encoding_metadata: Arc<HashMap<String, String>>,
     version: LanceFileVersion,
+    // The dictionary shared by the pages of this column (2.3+)
+    shared_dictionary: Option<Arc<Mutex<dict::SharedDictionaryBuilder>>>,
+    // Decides once for the column whether pages try dictionary encoding (nested leaves)
+    leaf_cardinality: Option<Arc<Mutex<dict::LeafCardinalitySampler>>>,
+}
+
+// Locks state shared by the encode tasks of a column, a task that panicked while
+// holding the lock may have left it half updated
+fn lock_column_state<'a, T>(state: &'a Mutex<T>, name: &str) -> Result<MutexGuard<'a, T>> {
+    state.lock().map_err(|_| {
+        Error::internal(format!(
+            "The {} of the column was poisoned by a panicked encode task",
+            name
+        ))
+    })
 }
 
 struct CompressedLevelsChunk {
@@ -3817,6 +3955,18 @@ struct PrimitiveEncodeContext {
     is_simple_validity: bool,
     // True when the field has any non-empty rep/def information.
     has_repdef_info: bool,
+    // Column-level dictionary that pages try before falling back to a page dictionary.
+    shared_dictionary: Option<Arc<Mutex<dict::SharedDictionaryBuilder>>>,
+    // Column-level cardinality sample that gates dictionary encoding of nested leaves.
+    leaf_cardinality: Option<Arc<Mutex<dict::LeafCardinalitySampler>>>,
+}
+
+// Where the dictionary of a dictionary encoded mini-block page is stored
+enum PageDictionary {
+    // The dictionary is stored in the page
+    Local(DataBlock),
+    // The values are indices into the column's shared dictionary
+    Shared(dict::SharedDictionaryLayout),
 }
 
 impl PrimitiveStructuralEncoder {
@@ -3827,6 +3977,20 @@ impl PrimitiveStructuralEncoder {
         field: Field,
         encoding_metadata: Arc<HashMap<String, String>>,
     ) -> Result<Self> {
+        let field = if encoding_metadata.contains_key(ENCRYPTION_KEY_ID_META_KEY) {
+            Self::without_fsst(field)
+        } else {
+            field
+        };
+        let shared_dictionary =
+            Self::shared_dictionary_max_size(&field, options.version).map(|max_size| {
+                let max_entries =
+                    u32::try_from(Self::dict_max_cardinality(&field)).unwrap_or(u32::MAX);
+                Arc::new(Mutex::new(dict::SharedDictionaryBuilder::new(
+                    max_size,
+                    max_entries,
+                )))
+            });
         Ok(Self {
             accumulation_queue: AccumulationQueue::new(
                 options.cache_bytes_per_column,
@@ -3841,9 +4005,81 @@ impl PrimitiveStructuralEncoder {
             field,
             encoding_metadata,
             version: options.version,
+            shared_dictionary,
+            leaf_cardinality: None,
         })
     }
 
+    /// Decide once for the column, from a bounded sample of its first values, whether
+    /// pages try dictionary encoding
+    ///
+    /// Used for the leaves of structs and lists so that each child gets its own
+    /// decision instead of every page probing (and possibly training) a dictionary.
+    pub fn with_leaf_cardinality_sampling(mut self) -> Self {
+        self.leaf_cardinality = Some(Arc::new(
+            Mutex::new(dict::LeafCardinalitySampler::default()),
+        ));
+        self
+    }
+
+    // Page descriptions are not encrypted, so the pages of encrypted columns can't use FSST
+    // because its symbol table, which is made of pieces of the values, is stored there
+    fn without_fsst(mut field: Field) -> Field {
+        let compression = field.metadata.get(COMPRESSION_META_KEY).map(String::as_str);
+        let may_use_fsst = match compression {
+            Some(compression) => compression == "fsst",
+            None => matches!(
+                field.data_type(),
+                DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View
+            ),
+        };
+        if may_use_fsst {
+            field
+                .metadata
+                .insert(COMPRESSION_META_KEY.to_string(), "none".to_string());
+        }
+        field
+    }
+
+    fn dictionary_mode(field: &Field) -> dict::DictionaryMode {
+        let Some(mode) = field.metadata.get(DICT_ENCODING_META_KEY) else {
+            return dict::DictionaryMode::default();
+        };
+        dict::DictionaryMode::parse(mode).unwrap_or_else(|| {
+            log::warn!("Invalid dictionary mode '{}', using default", mode);
+            dict::DictionaryMode::default()
+        })
+    }
+
+    fn dict_max_cardinality(field: &Field) -> u64 {
+        field
+            .metadata
+            .get(DICT_MAX_CARDINALITY_META_KEY)
+            .and_then(|val| val.parse().ok())
+            .or_else(|| {
+                env::var("LANCE_ENCODING_DICT_MAX_CARDINALITY")
+                    .ok()
+                    .and_then(|val| val.parse().ok())
+            })
+            .unwrap_or(DEFAULT_DICT_MAX_CARDINALITY)
+    }
+
+    // Shared dictionaries are stored in column buffers which are only understood by 2.3+
+    // readers.  Returns None if the column should only use per-page dictionaries.
+    fn shared_dictionary_max_size(field: &Field, version: LanceFileVersion) -> Option<usize> {
+        if version.resolve() < LanceFileVersion::V2_3
+            || Self::dictionary_mode(field) == dict::DictionaryMode::Never
+        {
+            return None;
+        }
+        let max_size = field
+            .metadata
+            .get(SHARED_DICT_MAX_SIZE_META_KEY)
+            .and_then(|val| val.parse().ok())
+            .unwrap_or(DEFAULT_SHARED_DICT_MAX_SIZE);
+        (max_size > 0).then_some(max_size)
+    }
+
     // TODO: This is a heuristic we may need to tune at some point
     //
     // As data gets narrow then the "zipping" process gets too expensive
@@ -4548,7 +4784,7 @@ impl PrimitiveStructuralEncoder {
         data: DataBlock,
         repdef: crate::repdef::SerializedRepDefs,
         row_number: u64,
-        dictionary_data: Option<DataBlock>,
+        dictionary: Option<PageDictionary>,
         num_rows: u64,
         support_large_chunk: bool,
     ) -> Result<EncodedPage> {
@@ -4617,6 +4853,11 @@ impl PrimitiveStructuralEncoder {
         data.push(serialized.metadata);
         data.push(serialized.data);
 
+        let (dictionary_data, shared_dictionary) = match dictionary {
+            Some(PageDictionary::Local(dictionary_data)) => (Some(dictionary_data), None),
+            Some(PageDictionary::Shared(layout)) => (None, Some(layout)),
+            None => (None, None),
+        };
         if let Some(dictionary_data) = dictionary_data {
             let num_dictionary_items = dictionary_data.num_values();
             let dict_values_field = Self::build_dict_values_compressor_field(field)?;
@@ -4649,7 +4890,7 @@ impl PrimitiveStructuralEncoder {
                 row_number,
             })
         } else {
-            let description = ProtobufUtils21::miniblock_layout(
+            let mut description = ProtobufUtils21::miniblock_layout(
                 compressed_rep.map(|cr| cr.compression),
                 compressed_def.map(|cd| cd.compression),
                 value_encoding,
@@ -4660,6 +4901,13 @@ impl PrimitiveStructuralEncoder {
                 num_items,
                 support_large_chunk,
             );
+            if let Some(layout) = shared_dictionary
+                && let Some(pb21::page_layout::Layout::MiniBlockLayout(mini_block)) =
+                    description.layout.as_mut()
+            {
+                // The shared dictionary is the only column buffer written by this encoder
+                mini_block.shared_dictionary = Some(layout.to_proto(0));
+            }
 
             if let Some(rep_index) = rep_index {
                 let view = rep_index.borrow_to_typed_slice::<u64>();
@@ -4987,14 +5235,21 @@ impl PrimitiveStructuralEncoder {
         })
     }
 
+    // `probe_uniqueness` is false if the column already knows it isn't near-unique
     fn should_dictionary_encode(
         data_block: &DataBlock,
         field: &Field,
         version: LanceFileVersion,
+        probe_uniqueness: bool,
     ) -> Option<DictEncodingBudget> {
         const DEFAULT_SAMPLE_SIZE: usize = 4096;
         const DEFAULT_SAMPLE_UNIQUE_RATIO: f64 = 0.98;
 
+        let mode = Self::dictionary_mode(field);
+        if mode == dict::DictionaryMode::Never {
+            return None;
+        }
+
         // Since we only dictionary encode FixedWidth and VariableWidth blocks for now, we skip
         // estimating the size for other types.
         match data_block {
@@ -5017,6 +5272,13 @@ impl PrimitiveStructuralEncoder {
             _ => return None,
         }
 
+        if mode == dict::DictionaryMode::Always {
+            return Some(DictEncodingBudget {
+                max_dict_entries: i32::MAX as u32,
+                max_encoded_size: usize::MAX,
+            });
+        }
+
         // Don't dictionary encode tiny arrays.
         let too_small = env::var("LANCE_ENCODING_DICT_TOO_SMALL")
             .ok()
@@ -5041,10 +5303,7 @@ impl PrimitiveStructuralEncoder {
             })
             .unwrap_or(DEFAULT_DICT_DIVISOR);
 
-        let max_cardinality: u64 = env::var("LANCE_ENCODING_DICT_MAX_CARDINALITY")
-            .ok()
-            .and_then(|val| val.parse().ok())
-            .unwrap_or(DEFAULT_DICT_MAX_CARDINALITY);
+        let max_cardinality = Self::dict_max_cardinality(field);
 
         let threshold_cardinality = num_values
             .checked_div(divisor.max(1))
@@ -5082,11 +5341,13 @@ impl PrimitiveStructuralEncoder {
         let max_encoded_size = usize::try_from(max_encoded_size).ok()?;
 
         // Avoid probing dictionary encoding on data that appears to be near-unique.
-        if Self::sample_is_near_unique(
-            data_block,
-            DEFAULT_SAMPLE_SIZE,
-            DEFAULT_SAMPLE_UNIQUE_RATIO,
-        )? {
+        if probe_uniqueness
+            && Self::sample_is_near_unique(
+                data_block,
+                DEFAULT_SAMPLE_SIZE,
+                DEFAULT_SAMPLE_UNIQUE_RATIO,
+            )?
+        {
             return None;
         }
 
@@ -5302,6 +5563,8 @@ impl PrimitiveStructuralEncoder {
             version,
             is_simple_validity,
             has_repdef_info,
+            shared_dictionary,
+            leaf_cardinality,
         } = ctx;
         let PrimitivePageData {
             arrays,
@@ -5386,8 +5649,16 @@ impl PrimitiveStructuralEncoder {
                 num_values,
                 num_rows
             );
+            // Encrypted columns keep the value in a page buffer, which is encrypted, instead
+            // of the page description, which is not
+            let allow_inline = !encoding_metadata.contains_key(ENCRYPTION_KEY_ID_META_KEY);
             return constant::encode_constant_page(
-                column_idx, scalar, repdef, row_number, num_rows,
+                column_idx,
+                scalar,
+                repdef,
+                row_number,
+                num_rows,
+                allow_inline,
             );
         }
 
@@ -5537,15 +5808,55 @@ impl PrimitiveStructuralEncoder {
                 indices_data_block,
                 repdef,
                 row_number,
-                Some(dictionary_data_block),
+                Some(PageDictionary::Local(dictionary_data_block)),
                 num_rows,
                 support_large_chunk,
             );
         }
 
-        // Try dictionary encoding first if applicable. If encoding aborts, fall back to the
-        // preferred structural encoding.
-        let dict_result = Self::should_dictionary_encode(&data_block, &field, version).and_then(|budget| {
+        // Try dictionary encoding first if applicable, preferring the column's shared
+        // dictionary over a page dictionary. If encoding aborts, fall back to the preferred
+        // structural encoding.
+        //
+        // Nested leaves only sample their cardinality once for the column, a near-unique
+        // leaf skips dictionary encoding without probing each page.
+        let leaf_decision = match leaf_cardinality
+            .as_ref()
+            .filter(|_| Self::dictionary_mode(&field) == dict::DictionaryMode::Auto)
+        {
+            Some(sampler) => {
+                lock_column_state(sampler, "cardinality sampler")?.observe(&data_block)
+            }
+            None => dict::LeafDictionaryDecision::Undecided,
+        };
+        let dict_budget = match leaf_decision {
+            dict::LeafDictionaryDecision::Plain => None,
+            dict::LeafDictionaryDecision::Dictionary => {
+                Self::should_dictionary_encode(&data_block, &field, version, false)
+            }
+            dict::LeafDictionaryDecision::Undecided => {
+                Self::should_dictionary_encode(&data_block, &field, version, true)
+            }
+        };
+        let shared_dict_result = match dict_budget.zip(shared_dictionary.as_ref()) {
+            Some((budget, shared_dictionary)) => {
+                let mut shared_dictionary =
+                    lock_column_state(shared_dictionary, "shared dictionary")?;
+                shared_dictionary
+                    .try_encode(&data_block, budget.max_encoded_size)
+                    .and_then(|indices| Some((indices, shared_dictionary.layout()?)))
+            }
+            None => None,
+        };
+        let dict_result = if let Some((indices_data_block, layout)) = shared_dict_result {
+            log::debug!(
+                "Encoding column {} with {} items using shared dictionary encoding (mini-block layout)",
+                column_idx,
+                num_values
+            );
+            Some((indices_data_block, PageDictionary::Shared(layout)))
+        } else {
+            dict_budget.and_then(|budget| {
                 log::debug!(
                     "Encoding column {} with {} items using dictionary encoding (mini-block layout)",
                     column_idx,
@@ -5556,9 +5867,11 @@ impl PrimitiveStructuralEncoder {
                     budget.max_dict_entries,
                     budget.max_encoded_size,
                 )
-            });
+                .map(|(indices, dictionary)| (indices, PageDictionary::Local(dictionary)))
+            })
+        };
 
-        if let Some((indices_data_block, dictionary_data_block)) = dict_result {
+        if let Some((indices_data_block, dictionary)) = dict_result {
             Self::encode_miniblock(
                 column_idx,
                 &field,
@@ -5566,7 +5879,7 @@ impl PrimitiveStructuralEncoder {
                 indices_data_block,
                 repdef,
                 row_number,
-                Some(dictionary_data_block),
+                Some(dictionary),
                 num_rows,
                 support_large_chunk,
             )
@@ -5642,6 +5955,8 @@ impl PrimitiveStructuralEncoder {
             version: self.version,
             is_simple_validity,
             has_repdef_info,
+            shared_dictionary: self.shared_dictionary.clone(),
+            leaf_cardinality: self.leaf_cardinality.clone(),
         };
         for page in pages {
             let ctx = ctx.clone();
@@ -5738,7 +6053,19 @@ impl FieldEncoder for PrimitiveStructuralEncoder {
         &mut self,
         _external_buffers: &mut OutOfLineBuffers,
     ) -> BoxFuture<'_, Result<Vec<crate::encoder::EncodedColumn>>> {
-        std::future::ready(Ok(vec![EncodedColumn::default()])).boxed()
+        let mut encoded_column = EncodedColumn::default();
+        // All pages have been encoded by now so the shared dictionary is complete.  Pages
+        // refer to it as column buffer 0.
+        if let Some(shared_dictionary) = self.shared_dictionary.as_ref() {
+            let finished = match lock_column_state(shared_dictionary, "shared dictionary") {
+                Ok(mut shared_dictionary) => shared_dictionary.finish(),
+                Err(err) => return std::future::ready(Err(err)).boxed(),
+            };
+            if let Some((_, buffers)) = finished {
+                encoded_column.column_buffers = buffers;
+            }
+        }
+        std::future::ready(Ok(vec![encoded_column])).boxed()
     }
 }
 
@@ -7291,6 +7618,7 @@ mod tests {
             repetition_index_depth: 0,
             num_items: rows,
             has_large_chunk: false,
+            shared_dictionary: None,
         };
 
         let buffer_offsets_and_sizes = vec![(0, 0), (0, 0), (0, 0)];
@@ -7300,6 +7628,7 @@ mod tests {
             /*items_in_page=*/ rows,
             &layout,
             &DefaultDecompressionStrategy::default(),
+            &mut super::ColumnSharedDictionaries::new(&[]),
         )
         .unwrap();
 
@@ -7382,6 +7711,7 @@ mod tests {
             &block,
             &field,
             LanceFileVersion::V2_1,
+            true,
         );
 
         assert!(
@@ -7407,6 +7737,7 @@ mod tests {
             &block,
             &field,
             LanceFileVersion::V2_1,
+            true,
         );
 
         assert!(
@@ -7433,6 +7764,7 @@ mod tests {
             &block,
             &field,
             LanceFileVersion::V2_1,
+            true,
         );
 
         assert!(
@@ -7488,6 +7820,331 @@ mod tests {
         pages.into_iter().next().unwrap()
     }
 
+    /// Encodes each array as its own page, returning the pages and the column buffers
+    async fn encode_pages(
+        field: arrow_schema::Field,
+        arrays: Vec<ArrayRef>,
+        version: LanceFileVersion,
+    ) -> (Vec<crate::encoder::EncodedPage>, Vec<LanceBuffer>) {
+        use crate::encoder::{
+            ColumnIndexSequence, EncodingOptions, MIN_PAGE_BUFFER_ALIGNMENT, OutOfLineBuffers,
+            default_encoding_strategy,
+        };
+        use crate::repdef::RepDefBuilder;
+
+        let lance_field = lance_core::datatypes::Field::try_from(&field).unwrap();
+        let encoding_strategy = default_encoding_strategy(version);
+        let mut column_index_seq = ColumnIndexSequence::default();
+        let encoding_options = EncodingOptions {
+            cache_bytes_per_column: 1,
+            max_page_bytes: 32 * 1024 * 1024,
+            keep_original_array: true,
+            buffer_alignment: MIN_PAGE_BUFFER_ALIGNMENT,
+            version,
+        };
+        let mut encoder = encoding_strategy
+            .create_field_encoder(
+                encoding_strategy.as_ref(),
+                &lance_field,
+                &mut column_index_seq,
+                &encoding_options,
+            )
+            .unwrap();
+
+        let mut external_buffers = OutOfLineBuffers::new(0, MIN_PAGE_BUFFER_ALIGNMENT);
+        let mut pages = Vec::new();
+        let mut row_number = 0;
+        for array in arrays {
+            let num_rows = array.len() as u64;
+            let tasks = encoder
+                .maybe_encode(
+                    array,
+                    &mut external_buffers,
+                    RepDefBuilder::default(),
+                    row_number,
+                    num_rows,
+                )
+                .unwrap();
+            for task in tasks {
+                pages.push(task.await.unwrap());
+            }
+            row_number += num_rows;
+        }
+        for task in encoder.flush(&mut external_buffers).unwrap() {
+            pages.push(task.await.unwrap());
+        }
+        let mut columns = encoder.finish(&mut external_buffers).await.unwrap();
+        (pages, columns.remove(0).column_buffers)
+    }
+
+    fn page_dictionary_kind(page: &crate::encoder::EncodedPage) -> &'static str {
+        let PageEncoding::Structural(layout) = &page.description else {
+            panic!("Expected structural page encoding");
+        };
+        match layout.layout.as_ref().unwrap() {
+            pb21::page_layout::Layout::MiniBlockLayout(mini_block) => {
+                if mini_block.shared_dictionary.is_some() {
+                    "shared"
+                } else if mini_block.dictionary.is_some() {
+                    "local"
+                } else {
+                    "none"
+                }
+            }
+            _ => "none",
+        }
+    }
+
+    fn encoded_size(
+        pages: &[crate::encoder::EncodedPage],
+        column_buffers: &[LanceBuffer],
+    ) -> usize {
+        pages
+            .iter()
+            .flat_map(|page| page.data.iter())
+            .chain(column_buffers)
+            .map(|buffer| buffer.len())
+            .sum()
+    }
+
+    /// Low cardinality strings that don't compress well, split into one array per page
+    fn low_cardinality_pages(num_pages: usize, rows_per_page: usize) -> Vec<ArrayRef> {
+        let categories = (0..100_u64)
+            .map(|i| format!("category_{:016x}", i.wrapping_mul(0x9E37_79B9_7F4A_7C15)))
+            .collect::<Vec<_>>();
+        (0..num_pages)
+            .map(|page| {
+                Arc::new(StringArray::from_iter_values((0..rows_per_page).map(
+                    |row| categories[(page * 7 + row * 13) % categories.len()].as_str(),
+                ))) as ArrayRef
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_shared_dictionary_shrinks_low_cardinality_column() {
+        use crate::constants::SHARED_DICT_MAX_SIZE_META_KEY;
+
+        let arrays = low_cardinality_pages(20, 2000);
+        let field = arrow_schema::Field::new("category", DataType::Utf8, false);
+
+        let (shared_pages, shared_buffers) =
+            encode_pages(field.clone(), arrays.clone(), LanceFileVersion::V2_3).await;
+        assert_eq!(shared_pages.len(), arrays.len());
+        assert!(
+            shared_pages
+                .iter()
+                .all(|page| page_dictionary_kind(page) == "shared")
+        );
+        assert_eq!(shared_buffers.len(), 2);
+
+        let mut metadata = HashMap::new();
+        metadata.insert(SHARED_DICT_MAX_SIZE_META_KEY.to_string(), "0".to_string());
+        let (local_pages, local_buffers) = encode_pages(
+            field.with_metadata(metadata),
+            arrays.clone(),
+            LanceFileVersion::V2_3,
+        )
+        .await;
+        assert!(
+            local_pages
+                .iter()
+                .all(|page| page_dictionary_kind(page) == "local")
+        );
+        assert!(local_buffers.is_empty());
+
+        let shared_size = encoded_size(&shared_pages, &shared_buffers);
+        let local_size = encoded_size(&local_pages, &local_buffers);
+        assert!(
+            shared_size * 2 < local_size,
+            "shared dictionary size {} should be well below page dictionary size {}",
+            shared_size,
+            local_size
+        );
+
+        let test_cases = TestCases::default()
+            .with_min_file_version(LanceFileVersion::V2_3)
+            .with_range(0..40_000)
+            .with_indices(vec![0, 1999, 2000, 39_999])
+            .with_expected_encoding("dictionary");
+        check_round_trip_encoding_of_data(arrays, &test_cases, HashMap::new()).await;
+    }
+
+    #[tokio::test]
+    async fn test_dictionary_mode_metadata() {
+        use crate::constants::DICT_ENCODING_META_KEY;
+
+        let unique = vec![Arc::new(StringArray::from_iter_values(
+            (0..2000).map(|i| format!("unique_{i:08}")),
+        )) as ArrayRef];
+        let field_with_mode = |mode: &str| {
+            let mut metadata = HashMap::new();
+            metadata.insert(DICT_ENCODING_META_KEY.to_string(), mode.to_string());
+            arrow_schema::Field::new("value", DataType::Utf8, false).with_metadata(metadata)
+        };
+
+        for version in [LanceFileVersion::V2_1, LanceFileVersion::V2_3] {
+            let (pages, column_buffers) =
+                encode_pages(field_with_mode("never"), unique.clone(), version).await;
+            assert_eq!(page_dictionary_kind(&pages[0]), "none");
+            assert!(column_buffers.is_empty());
+
+            let (pages, _) = encode_pages(
+                field_with_mode("never"),
+                low_cardinality_pages(1, 2000),
+                version,
+            )
+            .await;
+            assert_eq!(page_dictionary_kind(&pages[0]), "none");
+
+            // Unique values are never dictionary encoded by the heuristics
+            let (pages, _) = encode_pages(field_with_mode("auto"), unique.clone(), version).await;
+            assert_eq!(page_dictionary_kind(&pages[0]), "none");
+
+            let (pages, _) = encode_pages(field_with_mode("always"), unique.clone(), version).await;
+            assert_ne!(page_dictionary_kind(&pages[0]), "none");
+        }
+
+        let test_cases = TestCases::default()
+            .with_min_file_version(LanceFileVersion::V2_1)
+            .with_range(0..2000)
+            .with_indices(vec![0, 1999]);
+        let mut metadata = HashMap::new();
+        metadata.insert(DICT_ENCODING_META_KEY.to_string(), "always".to_string());
+        check_round_trip_encoding_of_data(unique, &test_cases, metadata).await;
+    }
+
+    /// Structs of a low cardinality `tag` and a unique `id`, one array per page
+    fn tag_and_id_pages(num_pages: usize, rows_per_page: usize) -> Vec<ArrayRef> {
+        low_cardinality_pages(num_pages, rows_per_page)
+            .into_iter()
+            .enumerate()
+            .map(|(page, tags)| {
+                let ids = StringArray::from_iter_values((0..rows_per_page).map(|row| {
+                    let id = (page * rows_per_page + row) as u64;
+                    format!("id_{:016x}", id.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+                }));
+                Arc::new(arrow_array::StructArray::from(vec![
+                    (
+                        Arc::new(ArrowField::new("tag", DataType::Utf8, false)),
+                        tags,
+                    ),
+                    (
+                        Arc::new(ArrowField::new("id", DataType::Utf8, false)),
+                        Arc::new(ids) as ArrayRef,
+                    ),
+                ])) as ArrayRef
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_nested_leaves_choose_dictionary_independently() {
+        let arrays = tag_and_id_pages(10, 2000);
+        let field = arrow_schema::Field::new("item", arrays[0].data_type().clone(), false);
+
+        for version in [LanceFileVersion::V2_1, LanceFileVersion::V2_3] {
+            let (pages, _) = encode_pages(field.clone(), arrays.clone(), version).await;
+            let column_kinds = |column_idx: u32| {
+                pages
+                    .iter()
+                    .filter(|page| page.column_idx == column_idx)
+                    .map(page_dictionary_kind)
+                    .collect::<Vec<_>>()
+            };
+            // The struct has no column of its own, `tag` is column 0 and `id` column 1
+            let tag_kinds = column_kinds(0);
+            assert_eq!(tag_kinds.len(), arrays.len());
+            assert!(
+                tag_kinds.iter().all(|kind| *kind != "none"),
+                "{tag_kinds:?}"
+            );
+            let id_kinds = column_kinds(1);
+            assert_eq!(id_kinds.len(), arrays.len());
+            assert!(id_kinds.iter().all(|kind| *kind == "none"), "{id_kinds:?}");
+        }
+
+        let test_cases = TestCases::default()
+            .with_min_file_version(LanceFileVersion::V2_1)
+            .with_range(0..20_000)
+            .with_range(1999..2001)
+            .with_indices(vec![0, 1999, 2000, 19_999]);
+        check_round_trip_encoding_of_data(arrays, &test_cases, HashMap::new()).await;
+    }
+
+    #[tokio::test]
+    async fn test_list_of_tags_dictionary_encoded() {
+        let lists = low_cardinality_pages(4, 3000)
+            .into_iter()
+            .map(|tags| {
+                let offsets =
+                    arrow_buffer::OffsetBuffer::from_lengths((0..1000).map(|row| row % 4 + 1));
+                let num_values = *offsets.last().unwrap() as usize;
+                Arc::new(arrow_array::ListArray::new(
+                    Arc::new(ArrowField::new("item", DataType::Utf8, false)),
+                    offsets,
+                    tags.slice(0, num_values),
+                    None,
+                )) as ArrayRef
+            })
+            .collect::<Vec<_>>();
+        let field = arrow_schema::Field::new("tags", lists[0].data_type().clone(), false);
+
+        let (pages, _) = encode_pages(field, lists.clone(), LanceFileVersion::V2_1).await;
+        assert_eq!(pages.len(), lists.len());
+        assert!(
+            pages
+                .iter()
+                .all(|page| page_dictionary_kind(page) == "local")
+        );
+
+        let test_cases = TestCases::default()
+            .with_min_file_version(LanceFileVersion::V2_1)
+            .with_range(0..4000)
+            .with_indices(vec![0, 999, 1000, 3999]);
+        check_round_trip_encoding_of_data(lists, &test_cases, HashMap::new()).await;
+    }
+
+    #[tokio::test]
+    async fn test_mixed_shared_and_local_dictionaries() {
+        use crate::constants::SHARED_DICT_MAX_SIZE_META_KEY;
+
+        // Every page brings 50 new values so the shared dictionary fills up after a few pages
+        let arrays = (0..8)
+            .map(|page| {
+                Arc::new(StringArray::from_iter_values(
+                    (0..1000).map(|row| format!("page_{page}_value_{:02}", row % 50)),
+                )) as ArrayRef
+            })
+            .collect::<Vec<_>>();
+        let mut metadata = HashMap::new();
+        metadata.insert(
+            SHARED_DICT_MAX_SIZE_META_KEY.to_string(),
+            "3000".to_string(),
+        );
+        let field = arrow_schema::Field::new("value", DataType::Utf8, false)
+            .with_metadata(metadata.clone());
+
+        let (pages, column_buffers) =
+            encode_pages(field, arrays.clone(), LanceFileVersion::V2_3).await;
+        let kinds = pages.iter().map(page_dictionary_kind).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                "shared", "shared", "shared", "local", "local", "local", "local", "local"
+            ]
+        );
+        assert_eq!(column_buffers.len(), 2);
+
+        let test_cases = TestCases::default()
+            .with_min_file_version(LanceFileVersion::V2_3)
+            .with_page_sizes(vec![1])
+            .with_range(0..8000)
+            .with_range(2500..3500)
+            .with_indices(vec![0, 2999, 3000, 7999]);
+        check_round_trip_encoding_of_data(arrays, &test_cases, metadata).await;
+    }
+
     #[tokio::test]
     async fn test_constant_layout_out_of_line_fixed_size_binary_v2_2() {
         use crate::format::pb21::page_layout::Layout;
@@ -7545,6 +8202,45 @@ mod tests {
         check_round_trip_encoding_of_data(vec![arr], &test_cases, HashMap::new()).await;
     }
 
+    #[tokio::test]
+    async fn test_encrypted_column_descriptions_hold_no_values() {
+        use crate::format::pb21::page_layout::Layout;
+        use lance_core::utils::encryption::ENCRYPTION_KEY_ID_META_KEY;
+
+        let encrypted =
+            HashMap::from([(ENCRYPTION_KEY_ID_META_KEY.to_string(), "pii".to_string())]);
+
+        // Constants that would be inlined go to a page buffer
+        let arr: ArrayRef = Arc::new(arrow_array::Int32Array::from(vec![7; 512]));
+        let field =
+            arrow_schema::Field::new("c", DataType::Int32, false).with_metadata(encrypted.clone());
+        let page = encode_first_page(field, arr.clone(), LanceFileVersion::V2_2).await;
+        let PageEncoding::Structural(layout) = &page.description else {
+            panic!("Expected structural encoding");
+        };
+        let Layout::ConstantLayout(layout) = layout.layout.as_ref().unwrap() else {
+            panic!("Expected constant layout");
+        };
+        assert!(layout.inline_value.is_none());
+        assert_eq!(page.data.len(), 1);
+
+        // Strings are not FSST encoded because the symbol table is in the description
+        let arr: ArrayRef = Arc::new(arrow_array::StringArray::from_iter_values(
+            (0..10_000).map(|i| format!("customer-{i:08}-address-line")),
+        ));
+        let field = arrow_schema::Field::new("c", DataType::Utf8, false);
+        let page = encode_first_page(field.clone(), arr.clone(), LanceFileVersion::V2_2).await;
+        assert!(format!("{:?}", page.description).contains("Fsst"));
+        let field = field.with_metadata(encrypted.clone());
+        let page = encode_first_page(field, arr.clone(), LanceFileVersion::V2_2).await;
+        assert!(!format!("{:?}", page.description).contains("Fsst"));
+
+        let test_cases = TestCases::default()
+            .with_min_file_version(LanceFileVersion::V2_2)
+            .with_max_file_version(LanceFileVersion::V2_2);
+        check_round_trip_encoding_of_data(vec![arr], &test_cases, encrypted).await;
+    }
+
     #[tokio::test]
     async fn test_constant_layout_nullable_item_v2_2() {
         use crate::format::pb21::page_layout::Layout;
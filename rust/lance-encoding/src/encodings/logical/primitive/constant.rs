@@ -31,8 +31,13 @@ pub(crate) fn encode_constant_page(
     repdef: crate::repdef::SerializedRepDefs,
     row_number: u64,
     num_rows: u64,
+    allow_inline: bool,
 ) -> Result<EncodedPage> {
-    let inline_value = lance_arrow::scalar::try_inline_value(&scalar);
+    let inline_value = if allow_inline {
+        lance_arrow::scalar::try_inline_value(&scalar)
+    } else {
+        None
+    };
     let value_buffer = if inline_value.is_some() {
         None
     } else {
@@ -1,7 +1,10 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-FileCopyrightText: Copyright The Lance Authors
 
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 /// Bits per value for FixedWidth dictionary values (legacy default for 128-bit values)
 pub const DICT_FIXED_WIDTH_BITS_PER_VALUE: u64 = 128;
@@ -20,10 +23,12 @@ use arrow_buffer::ArrowNativeType;
 use arrow_schema::DataType;
 use arrow_select::take::TakeOptions;
 use lance_core::{Error, Result, error::LanceOptionExt, utils::hash::U8SliceKey};
+use xxhash_rust::xxh3::xxh3_64;
 
 use crate::{
     buffer::LanceBuffer,
     data::{BlockInfo, DataBlock, FixedWidthDataBlock, VariableWidthBlock},
+    format::pb21,
     statistics::{ComputeStat, GetStat, Stat},
 };
 
@@ -367,6 +372,465 @@ pub fn dictionary_encode(
     }
 }
 
+/// How the writer decides whether to dictionary encode a page
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DictionaryMode {
+    /// Dictionary encode every page whose values support dictionary encoding
+    Always,
+    /// Never dictionary encode (input that is already dictionary encoded is kept as-is)
+    Never,
+    /// Decide per page based on the estimated cardinality and encoded size
+    #[default]
+    Auto,
+}
+
+impl DictionaryMode {
+    /// Parse from string
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "always" => Some(Self::Always),
+            "never" => Some(Self::Never),
+            "auto" => Some(Self::Auto),
+            _ => None,
+        }
+    }
+}
+
+/// The shape of the values in a shared dictionary
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SharedDictionaryLayout {
+    FixedWidth { bits_per_value: u64 },
+    VariableWidth { bits_per_offset: u8 },
+}
+
+impl SharedDictionaryLayout {
+    fn of(data_block: &DataBlock) -> Option<Self> {
+        match data_block {
+            DataBlock::FixedWidth(fixed) if matches!(fixed.bits_per_value, 64 | 128) => {
+                Some(Self::FixedWidth {
+                    bits_per_value: fixed.bits_per_value,
+                })
+            }
+            DataBlock::VariableWidth(var) if matches!(var.bits_per_offset, 32 | 64) => {
+                Some(Self::VariableWidth {
+                    bits_per_offset: var.bits_per_offset,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    pub fn to_proto(&self, column_buffer_index: u32) -> pb21::SharedDictionary {
+        let layout = match *self {
+            Self::FixedWidth { bits_per_value } => {
+                pb21::shared_dictionary::Layout::BitsPerValue(bits_per_value as u32)
+            }
+            Self::VariableWidth { bits_per_offset } => {
+                pb21::shared_dictionary::Layout::BitsPerOffset(bits_per_offset as u32)
+            }
+        };
+        pb21::SharedDictionary {
+            column_buffer_index,
+            layout: Some(layout),
+        }
+    }
+
+    pub fn try_from_proto(shared_dictionary: &pb21::SharedDictionary) -> Result<Self> {
+        match shared_dictionary.layout.as_ref().expect_ok()? {
+            pb21::shared_dictionary::Layout::BitsPerValue(bits_per_value)
+                if matches!(bits_per_value, 64 | 128) =>
+            {
+                Ok(Self::FixedWidth {
+                    bits_per_value: *bits_per_value as u64,
+                })
+            }
+            pb21::shared_dictionary::Layout::BitsPerOffset(bits_per_offset)
+                if matches!(bits_per_offset, 32 | 64) =>
+            {
+                Ok(Self::VariableWidth {
+                    bits_per_offset: *bits_per_offset as u8,
+                })
+            }
+            layout => Err(Error::invalid_input(format!(
+                "Unsupported shared dictionary layout: {:?}",
+                layout
+            ))),
+        }
+    }
+
+    /// The number of column buffers used to store a dictionary with this layout
+    pub fn num_buffers(&self) -> usize {
+        match self {
+            Self::FixedWidth { .. } => 1,
+            Self::VariableWidth { .. } => 2,
+        }
+    }
+
+    /// Rebuild the dictionary from the column buffers written by [`SharedDictionaryBuilder::finish`]
+    pub fn decode(&self, mut buffers: Vec<LanceBuffer>) -> Result<DataBlock> {
+        if buffers.len() != self.num_buffers() {
+            return Err(Error::invalid_input(format!(
+                "A shared dictionary with layout {:?} needs {} buffers but {} were provided",
+                self,
+                self.num_buffers(),
+                buffers.len()
+            )));
+        }
+        match *self {
+            Self::FixedWidth { bits_per_value } => {
+                let bytes_per_value = bits_per_value / 8;
+                let data = buffers.pop().expect_ok()?;
+                if !(data.len() as u64).is_multiple_of(bytes_per_value) {
+                    return Err(Error::invalid_input(format!(
+                        "Shared dictionary buffer of {} bytes is not a multiple of the value width ({} bytes)",
+                        data.len(),
+                        bytes_per_value
+                    )));
+                }
+                Ok(DataBlock::FixedWidth(FixedWidthDataBlock {
+                    num_values: data.len() as u64 / bytes_per_value,
+                    data,
+                    bits_per_value,
+                    block_info: BlockInfo::default(),
+                }))
+            }
+            Self::VariableWidth { bits_per_offset } => {
+                let data = buffers.pop().expect_ok()?;
+                let offsets = buffers.pop().expect_ok()?;
+                let bytes_per_offset = bits_per_offset as usize / 8;
+                if offsets.is_empty() || !offsets.len().is_multiple_of(bytes_per_offset) {
+                    return Err(Error::invalid_input(format!(
+                        "Shared dictionary offsets buffer of {} bytes is not a valid list of {}-bit offsets",
+                        offsets.len(),
+                        bits_per_offset
+                    )));
+                }
+                Ok(DataBlock::VariableWidth(VariableWidthBlock {
+                    num_values: (offsets.len() / bytes_per_offset - 1) as u64,
+                    data,
+                    offsets,
+                    bits_per_offset,
+                    block_info: BlockInfo::default(),
+                }))
+            }
+        }
+    }
+}
+
+/// Builds a dictionary that is shared by all pages of a column
+///
+/// Entries are only ever appended so the indices handed out to earlier pages stay valid
+/// as later pages add new values.  Once the dictionary would exceed its size or entry
+/// limit it stops accepting pages and the caller should fall back to a per-page dictionary.
+#[derive(Debug)]
+pub struct SharedDictionaryBuilder {
+    layout: Option<SharedDictionaryLayout>,
+    entries: HashMap<Vec<u8>, i32>,
+    values: Vec<u8>,
+    // The end offset (in `values`) of each entry
+    value_ends: Vec<usize>,
+    max_size: usize,
+    max_entries: u32,
+    is_full: bool,
+}
+
+impl SharedDictionaryBuilder {
+    pub fn new(max_size: usize, max_entries: u32) -> Self {
+        Self {
+            layout: None,
+            entries: HashMap::new(),
+            values: Vec::new(),
+            value_ends: Vec::new(),
+            max_size,
+            max_entries: max_entries.min(i32::MAX as u32),
+            is_full: false,
+        }
+    }
+
+    pub fn layout(&self) -> Option<SharedDictionaryLayout> {
+        self.layout
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.is_full
+    }
+
+    pub fn num_entries(&self) -> usize {
+        self.value_ends.len()
+    }
+
+    fn size_bytes(&self) -> usize {
+        let offsets_bytes = match self.layout {
+            Some(SharedDictionaryLayout::VariableWidth { bits_per_offset }) => {
+                (self.value_ends.len() + 1) * (bits_per_offset as usize / 8)
+            }
+            _ => 0,
+        };
+        self.values.len() + offsets_bytes
+    }
+
+    fn rollback(&mut self, num_entries: usize) {
+        for idx in num_entries..self.value_ends.len() {
+            let start = if idx == 0 {
+                0
+            } else {
+                self.value_ends[idx - 1]
+            };
+            self.entries
+                .remove(&self.values[start..self.value_ends[idx]]);
+        }
+        let values_len = num_entries
+            .checked_sub(1)
+            .map(|idx| self.value_ends[idx])
+            .unwrap_or(0);
+        self.value_ends.truncate(num_entries);
+        self.values.truncate(values_len);
+    }
+
+    /// Encode a page as indices into the shared dictionary, adding any new values to it
+    ///
+    /// Returns `None` (and leaves the dictionary unchanged) if the page cannot use the
+    /// shared dictionary.  This happens when the values have a different shape than earlier
+    /// pages, when the indices plus the new entries would not fit in `max_encoded_size`, or
+    /// when the dictionary is full.
+    pub fn try_encode(
+        &mut self,
+        data_block: &DataBlock,
+        max_encoded_size: usize,
+    ) -> Option<DataBlock> {
+        if self.is_full {
+            return None;
+        }
+        let layout = SharedDictionaryLayout::of(data_block)?;
+        if self.layout.is_some_and(|existing| existing != layout) {
+            return None;
+        }
+
+        let values: Vec<&[u8]> = match data_block {
+            DataBlock::FixedWidth(fixed) => fixed
+                .data
+                .chunks_exact(fixed.bits_per_value as usize / 8)
+                .collect(),
+            DataBlock::VariableWidth(var) => {
+                let ends = match var.bits_per_offset {
+                    32 => var
+                        .offsets
+                        .borrow_to_typed_slice::<u32>()
+                        .iter()
+                        .map(|&offset| offset as usize)
+                        .collect::<Vec<_>>(),
+                    _ => var
+                        .offsets
+                        .borrow_to_typed_slice::<u64>()
+                        .iter()
+                        .map(|&offset| usize::try_from(offset).ok())
+                        .collect::<Option<Vec<_>>>()?,
+                };
+                let mut values = Vec::with_capacity(var.num_values as usize);
+                for window in ends.windows(2) {
+                    if window[0] > window[1] || window[1] > var.data.len() {
+                        return None;
+                    }
+                    values.push(&var.data[window[0]..window[1]]);
+                }
+                values
+            }
+            _ => unreachable!(),
+        };
+
+        self.layout = Some(layout);
+        let num_entries_before = self.num_entries();
+        let size_before = self.size_bytes();
+        let indices_bytes = values
+            .len()
+            .saturating_mul(DICT_INDICES_BITS_PER_VALUE as usize / 8);
+        if indices_bytes > max_encoded_size {
+            return None;
+        }
+
+        let mut indices = Vec::with_capacity(values.len());
+        for value in values {
+            let idx = if let Some(idx) = self.entries.get(value) {
+                *idx
+            } else {
+                if self.num_entries() as u32 >= self.max_entries {
+                    self.rollback(num_entries_before);
+                    self.is_full = true;
+                    return None;
+                }
+                let idx = self.num_entries() as i32;
+                self.values.extend_from_slice(value);
+                self.value_ends.push(self.values.len());
+                self.entries.insert(value.to_vec(), idx);
+
+                let size = self.size_bytes();
+                let offsets_overflow = layout
+                    == SharedDictionaryLayout::VariableWidth {
+                        bits_per_offset: 32,
+                    }
+                    && self.values.len() > i32::MAX as usize;
+                if size > self.max_size || offsets_overflow {
+                    self.rollback(num_entries_before);
+                    self.is_full = true;
+                    return None;
+                }
+                if indices_bytes.saturating_add(size - size_before) > max_encoded_size {
+                    self.rollback(num_entries_before);
+                    return None;
+                }
+                idx
+            };
+            indices.push(idx);
+        }
+
+        let mut indices_data_block = DataBlock::FixedWidth(FixedWidthDataBlock {
+            data: LanceBuffer::reinterpret_vec(indices),
+            bits_per_value: DICT_INDICES_BITS_PER_VALUE,
+            num_values: data_block.num_values(),
+            block_info: BlockInfo::default(),
+        });
+        indices_data_block.compute_stat();
+        Some(indices_data_block)
+    }
+
+    /// The column buffers holding the dictionary, or `None` if no page used the dictionary
+    pub fn finish(&mut self) -> Option<(SharedDictionaryLayout, Vec<LanceBuffer>)> {
+        let layout = self.layout?;
+        if self.value_ends.is_empty() {
+            return None;
+        }
+        let values = LanceBuffer::from(std::mem::take(&mut self.values));
+        let buffers = match layout {
+            SharedDictionaryLayout::FixedWidth { .. } => vec![values],
+            SharedDictionaryLayout::VariableWidth { bits_per_offset } => {
+                let ends = std::iter::once(0).chain(self.value_ends.iter().copied());
+                let offsets = if bits_per_offset == 32 {
+                    LanceBuffer::reinterpret_vec(ends.map(|end| end as u32).collect::<Vec<_>>())
+                } else {
+                    LanceBuffer::reinterpret_vec(ends.map(|end| end as u64).collect::<Vec<_>>())
+                };
+                vec![offsets, values]
+            }
+        };
+        self.entries.clear();
+        self.value_ends.clear();
+        Some((layout, buffers))
+    }
+}
+
+/// Whether the pages of a nested leaf column try dictionary encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeafDictionaryDecision {
+    /// Not enough values have been sampled yet, pages decide on their own
+    Undecided,
+    /// The column is not near-unique, pages try dictionary encoding
+    Dictionary,
+    /// The column is near-unique, pages skip dictionary encoding (and are left to the
+    /// compression strategy, e.g. FSST for strings)
+    Plain,
+}
+
+/// Samples the cardinality of a nested leaf column to decide, once for the column,
+/// whether its pages try dictionary encoding
+///
+/// The children of lists and structs are sampled independently of each other and of
+/// top-level columns, so one struct or list can mix dictionary and plain children.  At
+/// most [`Self::MAX_SAMPLES`] values are hashed for the whole column, sampled with a
+/// stride from the first pages, so the cost doesn't grow with the length of the lists.
+#[derive(Debug)]
+pub struct LeafCardinalitySampler {
+    unique: HashSet<u64>,
+    num_samples: usize,
+    decision: LeafDictionaryDecision,
+}
+
+impl Default for LeafCardinalitySampler {
+    fn default() -> Self {
+        Self {
+            unique: HashSet::new(),
+            num_samples: 0,
+            decision: LeafDictionaryDecision::Undecided,
+        }
+    }
+}
+
+impl LeafCardinalitySampler {
+    /// The most values sampled for a column
+    pub const MAX_SAMPLES: usize = 4096;
+    /// The fewest values sampled before deciding
+    pub const MIN_SAMPLES: usize = 1024;
+    /// The ratio of unique sampled values above which the column is considered near-unique
+    pub const UNIQUE_RATIO: f64 = 0.98;
+
+    pub fn decision(&self) -> LeafDictionaryDecision {
+        self.decision
+    }
+
+    pub fn num_samples(&self) -> usize {
+        self.num_samples
+    }
+
+    /// Sample the values of a page, if the column is undecided, and return the decision
+    ///
+    /// Pages with values that can't be dictionary encoded are not sampled.
+    pub fn observe(&mut self, data_block: &DataBlock) -> LeafDictionaryDecision {
+        if self.decision != LeafDictionaryDecision::Undecided {
+            return self.decision;
+        }
+        let num_values = data_block.num_values() as usize;
+        let sample_count = num_values.min(Self::MAX_SAMPLES - self.num_samples);
+        if sample_count == 0 {
+            return self.decision;
+        }
+        let step = (num_values / sample_count).max(1);
+        let indices = (0..num_values).step_by(step).take(sample_count);
+        let sampled = match data_block {
+            DataBlock::FixedWidth(fixed) if fixed.bits_per_value % 8 == 0 => {
+                let width = (fixed.bits_per_value / 8) as usize;
+                for idx in indices {
+                    self.unique
+                        .insert(xxh3_64(&fixed.data[idx * width..(idx + 1) * width]));
+                }
+                true
+            }
+            DataBlock::VariableWidth(var) => match var.bits_per_offset {
+                32 => {
+                    let offsets = var.offsets.borrow_to_typed_slice::<u32>();
+                    for idx in indices {
+                        let (start, end) = (offsets[idx] as usize, offsets[idx + 1] as usize);
+                        self.unique.insert(xxh3_64(&var.data[start..end]));
+                    }
+                    true
+                }
+                64 => {
+                    let offsets = var.offsets.borrow_to_typed_slice::<u64>();
+                    for idx in indices {
+                        let (start, end) = (offsets[idx] as usize, offsets[idx + 1] as usize);
+                        self.unique.insert(xxh3_64(&var.data[start..end]));
+                    }
+                    true
+                }
+                _ => false,
+            },
+            _ => false,
+        };
+        if !sampled {
+            return self.decision;
+        }
+        self.num_samples += sample_count;
+        if self.num_samples >= Self::MIN_SAMPLES {
+            let ratio = self.unique.len() as f64 / self.num_samples as f64;
+            self.decision = if ratio >= Self::UNIQUE_RATIO {
+                LeafDictionaryDecision::Plain
+            } else {
+                LeafDictionaryDecision::Dictionary
+            };
+            self.unique = HashSet::new();
+        }
+        self.decision
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -570,4 +1034,106 @@ mod tests {
         assert!(dictionary_encode(&data_block, 10, max_encoded_size).is_none());
         assert!(dictionary_encode(&data_block, 500, max_encoded_size).is_some());
     }
+
+    #[test]
+    fn test_shared_dictionary_builder() {
+        let page = |values: &[&str]| {
+            DataBlock::from_array(Arc::new(StringArray::from(values.to_vec())) as Arc<dyn Array>)
+        };
+        let indices = |block: DataBlock| {
+            block
+                .as_fixed_width()
+                .unwrap()
+                .data
+                .borrow_to_typed_slice::<i32>()
+                .to_vec()
+        };
+
+        // Room for the offsets and 6 bytes of values
+        let mut builder = SharedDictionaryBuilder::new(4 * 4 + 6, 100);
+        let first = builder.try_encode(&page(&["ab", "cd", "ab"]), usize::MAX);
+        assert_eq!(indices(first.unwrap()), vec![0, 1, 0]);
+        let second = builder.try_encode(&page(&["cd", "ef"]), usize::MAX);
+        assert_eq!(indices(second.unwrap()), vec![1, 2]);
+
+        // Values from a different kind of block are rejected without filling the dictionary
+        let fixed = DataBlock::FixedWidth(FixedWidthDataBlock {
+            bits_per_value: 64,
+            data: LanceBuffer::reinterpret_vec(vec![1_u64, 2]),
+            num_values: 2,
+            block_info: BlockInfo::default(),
+        });
+        assert!(builder.try_encode(&fixed, usize::MAX).is_none());
+        assert!(!builder.is_full());
+
+        // A page that would grow the dictionary beyond the size limit is rolled back
+        assert!(
+            builder
+                .try_encode(&page(&["ab", "gh"]), usize::MAX)
+                .is_none()
+        );
+        assert!(builder.is_full());
+        assert_eq!(builder.num_entries(), 3);
+        assert!(builder.try_encode(&page(&["ab"]), usize::MAX).is_none());
+
+        let (layout, buffers) = builder.finish().unwrap();
+        assert_eq!(buffers.len(), layout.num_buffers());
+        let layout = SharedDictionaryLayout::try_from_proto(&layout.to_proto(0)).unwrap();
+        let dictionary = layout.decode(buffers).unwrap();
+        let dictionary = dictionary.as_variable_width().unwrap();
+        assert_eq!(dictionary.num_values, 3);
+        assert_eq!(dictionary.data.as_ref(), b"abcdef");
+        assert_eq!(
+            dictionary.offsets.borrow_to_typed_slice::<u32>().as_ref(),
+            &[0, 2, 4, 6]
+        );
+    }
+
+    #[test]
+    fn test_leaf_cardinality_sampling_is_bounded() {
+        let strings = |num_values: usize, cardinality: usize| {
+            let array = StringArray::from_iter_values(
+                (0..num_values).map(|i| format!("value_{:08}", i % cardinality)),
+            );
+            DataBlock::from_array(Arc::new(array) as Arc<dyn Array>)
+        };
+
+        // A long unique page decides the column from a bounded sample
+        let mut sampler = LeafCardinalitySampler::default();
+        assert_eq!(
+            sampler.observe(&strings(200_000, 200_000)),
+            LeafDictionaryDecision::Plain
+        );
+        assert_eq!(sampler.num_samples(), LeafCardinalitySampler::MAX_SAMPLES);
+        // Later pages are neither sampled nor dictionary encoded
+        assert_eq!(
+            sampler.observe(&strings(200_000, 10)),
+            LeafDictionaryDecision::Plain
+        );
+        assert_eq!(sampler.num_samples(), LeafCardinalitySampler::MAX_SAMPLES);
+
+        // Short pages are sampled until there are enough samples to decide
+        let mut sampler = LeafCardinalitySampler::default();
+        for _ in 0..3 {
+            assert_eq!(
+                sampler.observe(&strings(300, 10)),
+                LeafDictionaryDecision::Undecided
+            );
+        }
+        assert_eq!(
+            sampler.observe(&strings(300, 10)),
+            LeafDictionaryDecision::Dictionary
+        );
+        assert_eq!(sampler.num_samples(), 1200);
+        assert_eq!(sampler.decision(), LeafDictionaryDecision::Dictionary);
+
+        // Values that can't be dictionary encoded are not sampled
+        let mut sampler = LeafCardinalitySampler::default();
+        let bools =
+            DataBlock::from_array(
+                Arc::new(arrow_array::BooleanArray::from(vec![true; 2000])) as Arc<dyn Array>
+            );
+        assert_eq!(sampler.observe(&bools), LeafDictionaryDecision::Undecided);
+        assert_eq!(sampler.num_samples(), 0);
+    }
 }
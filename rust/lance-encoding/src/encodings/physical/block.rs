@@ -26,6 +26,7 @@ use lance_core::{Error, Result};
 
 use std::str::FromStr;
 
+use crate::buffer_pool::{freeze_pooled, pooled_vec};
 use crate::compression::{BlockCompressor, BlockDecompressor};
 use crate::encodings::physical::binary::{BinaryBlockDecompressor, VariableEncoder};
 use crate::format::{
@@ -440,10 +441,10 @@ impl GeneralBlockDecompressor {
 
 impl BlockDecompressor for GeneralBlockDecompressor {
     fn decompress(&self, data: LanceBuffer, num_values: u64) -> Result<DataBlock> {
-        let mut decompressed = Vec::new();
+        let mut decompressed = pooled_vec(data.len() * 2);
         self.compressor.decompress(&data, &mut decompressed)?;
         self.inner
-            .decompress(LanceBuffer::from(decompressed), num_values)
+            .decompress(freeze_pooled(decompressed), num_values)
     }
 }
 
@@ -0,0 +1,340 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Support for codecs that are implemented outside of Lance
+//!
+//! Applications with domain-specific data (e.g. genomic sequences) can register a
+//! [`CustomEncoding`] under a name in a [`CustomEncodingRegistry`].  The writer selects the
+//! codec for a field with the [`CUSTOM_ENCODING_META_KEY`] field metadata and the reader looks
+//! the codec up by the same name when it finds the encoding in the column metadata.
+//!
+//! Custom encodings are applied to the output of the normal mini-block compression, much like
+//! general compression.  Every buffer of every mini-block chunk is passed through the codec.
+//!
+//! [`CUSTOM_ENCODING_META_KEY`]: crate::constants::CUSTOM_ENCODING_META_KEY
+
+use std::{collections::HashMap, sync::Arc};
+
+use bytes::Bytes;
+
+use crate::{
+    Result,
+    buffer::LanceBuffer,
+    compression::MiniBlockDecompressor,
+    data::DataBlock,
+    encodings::logical::primitive::miniblock::{
+        MiniBlockChunk, MiniBlockCompressed, MiniBlockCompressor,
+    },
+    format::{ProtobufUtils21, pb21::CompressiveEncoding},
+};
+use lance_core::Error;
+
+/// A codec that transforms the bytes of an encoded buffer
+///
+/// The parameters are the (possibly empty) value of the
+/// [`crate::constants::CUSTOM_ENCODING_PARAMS_META_KEY`] field metadata at write time.  They
+/// are stored in the file so `decode` receives the same parameters that `encode` did.
+///
+/// Encoding may grow a buffer.  Custom encodings are only available from file version 2.3,
+/// whose mini-block chunks record buffer sizes with 32 bits, so a buffer only has to stay
+/// below 4GiB once encoded.
+pub trait CustomEncoding: std::fmt::Debug + Send + Sync {
+    /// Encode a single buffer
+    fn encode(&self, data: &[u8], parameters: &[u8]) -> Result<Vec<u8>>;
+    /// Reverse [`Self::encode`]
+    fn decode(&self, data: &[u8], parameters: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// A set of [`CustomEncoding`]s keyed by the id they are stored under
+#[derive(Debug, Default, Clone)]
+pub struct CustomEncodingRegistry {
+    encodings: HashMap<String, Arc<dyn CustomEncoding>>,
+}
+
+impl CustomEncodingRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `encoding` under `encoding_id`, replacing any codec already registered
+    /// under that id
+    pub fn with_encoding(
+        mut self,
+        encoding_id: impl Into<String>,
+        encoding: Arc<dyn CustomEncoding>,
+    ) -> Self {
+        self.encodings.insert(encoding_id.into(), encoding);
+        self
+    }
+
+    pub fn get(&self, encoding_id: &str) -> Option<&Arc<dyn CustomEncoding>> {
+        self.encodings.get(encoding_id)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.encodings.is_empty()
+    }
+
+    /// Look up a codec that was used to write data, returning an error that explains how
+    /// to fix the problem if it has not been registered
+    pub(crate) fn get_for_read(&self, encoding_id: &str) -> Result<Arc<dyn CustomEncoding>> {
+        self.get(encoding_id).cloned().ok_or_else(|| {
+            Error::not_supported(format!(
+                "The data was written with the custom encoding '{}' but no codec is registered \
+                 under that id.  Register it with CustomEncodingRegistry::with_encoding and pass \
+                 the registry to the reader with DecoderPlugins::with_custom_encodings",
+                encoding_id
+            ))
+        })
+    }
+}
+
+/// A miniblock compressor that wraps another miniblock compressor and passes the
+/// resulting buffers through a [`CustomEncoding`]
+#[derive(Debug)]
+pub struct CustomMiniBlockCompressor {
+    inner: Box<dyn MiniBlockCompressor>,
+    encoding_id: String,
+    parameters: Bytes,
+    encoding: Arc<dyn CustomEncoding>,
+}
+
+impl CustomMiniBlockCompressor {
+    pub fn new(
+        inner: Box<dyn MiniBlockCompressor>,
+        encoding_id: impl Into<String>,
+        parameters: Bytes,
+        encoding: Arc<dyn CustomEncoding>,
+    ) -> Self {
+        Self {
+            inner,
+            encoding_id: encoding_id.into(),
+            parameters,
+            encoding,
+        }
+    }
+}
+
+impl MiniBlockCompressor for CustomMiniBlockCompressor {
+    fn compress(&self, page: DataBlock) -> Result<(MiniBlockCompressed, CompressiveEncoding)> {
+        let (inner_compressed, inner_encoding) = self.inner.compress(page)?;
+
+        let mut encoded_buffers = vec![Vec::new(); inner_compressed.data.len()];
+        let mut new_chunks = Vec::with_capacity(inner_compressed.chunks.len());
+        let mut offsets = vec![0usize; inner_compressed.data.len()];
+
+        for chunk in &inner_compressed.chunks {
+            let mut new_buffer_sizes = Vec::with_capacity(chunk.buffer_sizes.len());
+            for (buffer_index, size) in chunk.buffer_sizes.iter().enumerate() {
+                let size = *size as usize;
+                let start = offsets[buffer_index];
+                let chunk_data = &inner_compressed.data[buffer_index].as_ref()[start..start + size];
+                offsets[buffer_index] += size;
+
+                let encoded = self.encoding.encode(chunk_data, &self.parameters)?;
+                let encoded_size = u32::try_from(encoded.len()).map_err(|_| {
+                    Error::invalid_input(format!(
+                        "Custom encoding '{}' grew a mini-block buffer from {} to {} bytes, \
+                         which is more than a mini-block buffer can hold",
+                        self.encoding_id,
+                        size,
+                        encoded.len()
+                    ))
+                })?;
+                new_buffer_sizes.push(encoded_size);
+                encoded_buffers[buffer_index].extend_from_slice(&encoded);
+            }
+            new_chunks.push(MiniBlockChunk {
+                buffer_sizes: new_buffer_sizes,
+                log_num_values: chunk.log_num_values,
+            });
+        }
+
+        let compressed = MiniBlockCompressed {
+            data: encoded_buffers.into_iter().map(LanceBuffer::from).collect(),
+            chunks: new_chunks,
+            num_values: inner_compressed.num_values,
+        };
+        let encoding =
+            ProtobufUtils21::custom(&self.encoding_id, self.parameters.clone(), inner_encoding);
+        Ok((compressed, encoding))
+    }
+}
+
+/// A miniblock decompressor that reverses a [`CustomEncoding`] and then delegates to an
+/// inner miniblock decompressor
+#[derive(Debug)]
+pub struct CustomMiniBlockDecompressor {
+    inner: Box<dyn MiniBlockDecompressor>,
+    parameters: Bytes,
+    encoding: Arc<dyn CustomEncoding>,
+}
+
+impl CustomMiniBlockDecompressor {
+    pub fn new(
+        inner: Box<dyn MiniBlockDecompressor>,
+        parameters: Bytes,
+        encoding: Arc<dyn CustomEncoding>,
+    ) -> Self {
+        Self {
+            inner,
+            parameters,
+            encoding,
+        }
+    }
+}
+
+impl MiniBlockDecompressor for CustomMiniBlockDecompressor {
+    fn decompress(&self, data: Vec<LanceBuffer>, num_values: u64) -> Result<DataBlock> {
+        let data = data
+            .iter()
+            .map(|buffer| {
+                self.encoding
+                    .decode(buffer.as_ref(), &self.parameters)
+                    .map(LanceBuffer::from)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.inner.decompress(data, num_values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compression::{DecompressionStrategy, DefaultDecompressionStrategy};
+    use crate::encodings::physical::value::ValueEncoder;
+    use crate::format::pb21::compressive_encoding::Compression;
+    use arrow_array::Int32Array;
+
+    /// XORs every byte with the first parameter byte (or 0xFF with no parameters)
+    #[derive(Debug)]
+    struct XorEncoding;
+
+    impl CustomEncoding for XorEncoding {
+        fn encode(&self, data: &[u8], parameters: &[u8]) -> Result<Vec<u8>> {
+            let key = parameters.first().copied().unwrap_or(0xFF);
+            Ok(data.iter().map(|b| b ^ key).collect())
+        }
+
+        fn decode(&self, data: &[u8], parameters: &[u8]) -> Result<Vec<u8>> {
+            self.encode(data, parameters)
+        }
+    }
+
+    /// Surrounds the data with a header and a footer, so it always grows
+    #[derive(Debug)]
+    struct FramedEncoding;
+
+    const FRAME: &[u8] = b"lance-frame";
+
+    impl CustomEncoding for FramedEncoding {
+        fn encode(&self, data: &[u8], _parameters: &[u8]) -> Result<Vec<u8>> {
+            Ok([FRAME, data, FRAME].concat())
+        }
+
+        fn decode(&self, data: &[u8], _parameters: &[u8]) -> Result<Vec<u8>> {
+            Ok(data[FRAME.len()..data.len() - FRAME.len()].to_vec())
+        }
+    }
+
+    fn decompress_chunks(
+        compressed: &MiniBlockCompressed,
+        decompressor: &dyn MiniBlockDecompressor,
+    ) -> Vec<u8> {
+        let mut decompressed = Vec::new();
+        let mut offsets = vec![0usize; compressed.data.len()];
+        let mut values_so_far = 0;
+        for chunk in &compressed.chunks {
+            let num_values = chunk.num_values(values_so_far, compressed.num_values);
+            values_so_far += num_values;
+            let buffers = chunk
+                .buffer_sizes
+                .iter()
+                .enumerate()
+                .map(|(buffer_index, size)| {
+                    let start = offsets[buffer_index];
+                    offsets[buffer_index] += *size as usize;
+                    compressed.data[buffer_index].slice_with_length(start, *size as usize)
+                })
+                .collect();
+            let block = decompressor.decompress(buffers, num_values).unwrap();
+            decompressed.extend_from_slice(block.as_fixed_width().unwrap().data.as_ref());
+        }
+        decompressed
+    }
+
+    #[test]
+    fn test_custom_miniblock_round_trip() {
+        let registry = CustomEncodingRegistry::new().with_encoding("xor", Arc::new(XorEncoding));
+        let compressor = CustomMiniBlockCompressor::new(
+            Box::new(ValueEncoder::default()),
+            "xor",
+            Bytes::from_static(b"\x5a"),
+            registry.get("xor").unwrap().clone(),
+        );
+
+        let values = Int32Array::from_iter_values(0..10_000);
+        let block = DataBlock::from_array(values);
+        let (compressed, encoding) = compressor.compress(block.clone()).unwrap();
+
+        let Some(Compression::Custom(custom)) = encoding.compression.as_ref() else {
+            panic!("Expected custom encoding, got {:?}", encoding);
+        };
+        assert_eq!(custom.encoding_id, "xor");
+        assert_eq!(custom.parameters.as_ref(), b"\x5a");
+
+        let strategy = DefaultDecompressionStrategy::with_custom_encodings(registry);
+        let decompressor = strategy
+            .create_miniblock_decompressor(&encoding, &strategy)
+            .unwrap();
+        let original = block.as_fixed_width().unwrap().data;
+        assert_ne!(compressed.data[0].as_ref(), original.as_ref());
+        let decoded = decompress_chunks(&compressed, decompressor.as_ref());
+        assert_eq!(decoded, original.as_ref());
+    }
+
+    #[test]
+    fn test_custom_miniblock_grows_buffers() {
+        let registry =
+            CustomEncodingRegistry::new().with_encoding("framed", Arc::new(FramedEncoding));
+        let compressor = CustomMiniBlockCompressor::new(
+            Box::new(ValueEncoder::default()),
+            "framed",
+            Bytes::new(),
+            registry.get("framed").unwrap().clone(),
+        );
+
+        let values = Int32Array::from_iter_values(0..10_000);
+        let block = DataBlock::from_array(values);
+        let (compressed, encoding) = compressor.compress(block.clone()).unwrap();
+        let original = block.as_fixed_width().unwrap().data;
+        assert_eq!(
+            compressed.data[0].len(),
+            original.len() + 2 * FRAME.len() * compressed.chunks.len()
+        );
+
+        let strategy = DefaultDecompressionStrategy::with_custom_encodings(registry);
+        let decompressor = strategy
+            .create_miniblock_decompressor(&encoding, &strategy)
+            .unwrap();
+        let decoded = decompress_chunks(&compressed, decompressor.as_ref());
+        assert_eq!(decoded, original.as_ref());
+    }
+
+    #[test]
+    fn test_custom_encoding_must_be_registered_to_read() {
+        let encoding =
+            ProtobufUtils21::custom("genome-2bit", Bytes::new(), ProtobufUtils21::flat(32, None));
+        let strategy = DefaultDecompressionStrategy::default();
+        let err = strategy
+            .create_miniblock_decompressor(&encoding, &strategy)
+            .unwrap_err();
+        assert!(matches!(err, Error::NotSupported { .. }));
+        let message = err.to_string();
+        assert!(message.contains("'genome-2bit'"), "{message}");
+        assert!(
+            message.contains("DecoderPlugins::with_custom_encodings"),
+            "{message}"
+        );
+    }
+}
@@ -6,6 +6,7 @@ use log::trace;
 use crate::{
     Result,
     buffer::LanceBuffer,
+    buffer_pool::{freeze_pooled, pooled_vec},
     compression::MiniBlockDecompressor,
     data::DataBlock,
     encodings::{
@@ -124,11 +125,13 @@ impl GeneralMiniBlockDecompressor {
 
 impl MiniBlockDecompressor for GeneralMiniBlockDecompressor {
     fn decompress(&self, mut data: Vec<LanceBuffer>, num_values: u64) -> Result<DataBlock> {
-        let mut decompressed_buffer = Vec::new();
+        // The decompressed chunk is transient so reuse pooled buffers for it.  The
+        // capacity is only a guess, the buffer grows if the data compressed better.
+        let mut decompressed_buffer = pooled_vec(data[0].len() * 2);
 
         let decompressor = GeneralBufferCompressor::get_compressor(self.compression)?;
         decompressor.decompress(&data[0], &mut decompressed_buffer)?;
-        data[0] = LanceBuffer::from(decompressed_buffer);
+        data[0] = freeze_pooled(decompressed_buffer);
 
         self.inner.decompress(data, num_values)
     }
@@ -0,0 +1,360 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! AES-256-GCM encryption of page buffers
+//!
+//! The buffers of an encrypted column are sealed one buffer at a time by the file
+//! writer.  Each file derives its own key per column from the user's key and a random
+//! salt, so the nonce of a buffer only has to be unique within the column and is
+//! derived from the position of the buffer ([`BufferLocation`]).  The sealed buffer
+//! (ciphertext followed by the [`TAG_LEN`] byte tag) is written where the plain buffer
+//! would be, while the metadata records the plain size, so decoders are unaware of
+//! the encryption.  Reads go through an [`EncryptedIo`] which fetches and decrypts the
+//! whole buffer for any range inside of it.
+
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures::FutureExt;
+use futures::future::BoxFuture;
+use lance_core::utils::encryption::EncryptionKey;
+use lance_core::{Error, Result};
+use ring::aead::{AES_256_GCM, Aad, LessSafeKey, NONCE_LEN, Nonce, UnboundKey};
+use ring::hkdf;
+use ring::rand::{SecureRandom, SystemRandom};
+
+use crate::EncodingsIo;
+
+/// Bytes added to each buffer by encryption
+pub const TAG_LEN: usize = 16;
+/// Length of the random salt a column key is derived with
+pub const SALT_LEN: usize = 16;
+
+const KEY_DERIVATION_INFO: &[u8] = b"lance column encryption v1";
+
+/// The position of a buffer within its column
+///
+/// Every buffer of a column has a different location and so a different nonce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferLocation {
+    /// The `buffer`th buffer of the `page`th page
+    Page { page: u32, buffer: u32 },
+    /// The `buffer`th column-level buffer
+    Column { buffer: u32 },
+}
+
+impl BufferLocation {
+    fn nonce(&self) -> Nonce {
+        let (kind, page, buffer) = match *self {
+            Self::Page { page, buffer } => (0_u32, page, buffer),
+            Self::Column { buffer } => (1_u32, 0, buffer),
+        };
+        let mut nonce = [0_u8; NONCE_LEN];
+        nonce[0..4].copy_from_slice(&kind.to_le_bytes());
+        nonce[4..8].copy_from_slice(&page.to_le_bytes());
+        nonce[8..12].copy_from_slice(&buffer.to_le_bytes());
+        Nonce::assume_unique_for_key(nonce)
+    }
+}
+
+/// Encrypts and decrypts the buffers of one column of one file
+pub struct ColumnCipher {
+    key: LessSafeKey,
+}
+
+impl std::fmt::Debug for ColumnCipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ColumnCipher(..)")
+    }
+}
+
+impl ColumnCipher {
+    /// Derive the key of a column from the user's key and the column's salt
+    pub fn try_new(key: &EncryptionKey, salt: &[u8]) -> Result<Self> {
+        let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, salt).extract(key.as_bytes());
+        let okm = prk
+            .expand(&[KEY_DERIVATION_INFO], &AES_256_GCM)
+            .map_err(|_| Error::internal("Failed to derive the column encryption key"))?;
+        Ok(Self {
+            key: LessSafeKey::new(UnboundKey::from(okm)),
+        })
+    }
+
+    /// A new random salt for a column
+    pub fn generate_salt() -> Result<Vec<u8>> {
+        let mut salt = vec![0_u8; SALT_LEN];
+        SystemRandom::new()
+            .fill(&mut salt)
+            .map_err(|_| Error::internal("Failed to generate a random salt for encryption"))?;
+        Ok(salt)
+    }
+
+    /// Seal a buffer, the result is [`TAG_LEN`] bytes longer than `plaintext`
+    pub fn encrypt(&self, location: BufferLocation, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let mut sealed = Vec::with_capacity(plaintext.len() + TAG_LEN);
+        sealed.extend_from_slice(plaintext);
+        self.key
+            .seal_in_place_append_tag(location.nonce(), Aad::empty(), &mut sealed)
+            .map_err(|_| Error::internal("Failed to encrypt a buffer"))?;
+        Ok(sealed)
+    }
+
+    /// Open a buffer sealed by [`Self::encrypt`]
+    ///
+    /// Fails if the buffer was sealed with another key or was modified.
+    pub fn decrypt(&self, location: BufferLocation, sealed: &[u8]) -> Result<Vec<u8>> {
+        let mut buffer = sealed.to_vec();
+        let plain_len = self
+            .key
+            .open_in_place(location.nonce(), Aad::empty(), &mut buffer)
+            .map_err(|_| {
+                Error::invalid_input(
+                    "Failed to decrypt a buffer, the key is wrong or the data is corrupted",
+                )
+            })?
+            .len();
+        buffer.truncate(plain_len);
+        Ok(buffer)
+    }
+}
+
+/// An encrypted column as seen by a reader
+#[derive(Debug)]
+pub struct EncryptedColumn {
+    /// The name of the column, for error messages
+    pub name: String,
+    pub key_id: String,
+    /// `None` if the key could not be found, reading the column then fails
+    pub cipher: Option<ColumnCipher>,
+}
+
+impl EncryptedColumn {
+    fn cipher(&self) -> Result<&ColumnCipher> {
+        self.cipher.as_ref().ok_or_else(|| {
+            Error::invalid_input(format!(
+                "Cannot read column '{}' because it is encrypted with the key '{}' and that key \
+                 was not provided.  Configure a KeyProvider that has the key to read this column",
+                self.name, self.key_id
+            ))
+        })
+    }
+}
+
+/// An encrypted buffer of a file
+#[derive(Debug, Clone)]
+pub struct EncryptedBuffer {
+    /// The size of the plain buffer, the sealed buffer is [`TAG_LEN`] bytes longer
+    pub size: u64,
+    pub location: BufferLocation,
+    pub column: Arc<EncryptedColumn>,
+}
+
+/// The encrypted buffers of a file keyed by their offset in the file
+pub type EncryptedBuffers = BTreeMap<u64, EncryptedBuffer>;
+
+/// An [`EncodingsIo`] that decrypts the encrypted buffers of a file
+///
+/// A request for any range inside an encrypted buffer reads and decrypts the whole
+/// buffer, because the tag can only be checked for the whole buffer.  Other ranges
+/// are passed through unchanged.
+#[derive(Debug)]
+pub struct EncryptedIo {
+    inner: Arc<dyn EncodingsIo>,
+    buffers: Arc<EncryptedBuffers>,
+}
+
+impl EncryptedIo {
+    pub fn new(inner: Arc<dyn EncodingsIo>, buffers: Arc<EncryptedBuffers>) -> Self {
+        Self { inner, buffers }
+    }
+
+    // The offset of the encrypted buffer that holds `range`, if any
+    fn find_buffer(&self, range: &Range<u64>) -> Result<Option<u64>> {
+        let Some((offset, buffer)) = self.buffers.range(..=range.start).next_back() else {
+            return Ok(None);
+        };
+        if range.start >= offset + buffer.size {
+            return Ok(None);
+        }
+        if range.end > offset + buffer.size {
+            return Err(Error::internal(format!(
+                "The read of {:?} is not contained in the encrypted buffer at {}..{}",
+                range,
+                offset,
+                offset + buffer.size
+            )));
+        }
+        Ok(Some(*offset))
+    }
+}
+
+// What to do with a requested range once the inner I/O completes
+enum Source {
+    Plain(usize),
+    Encrypted { buffer: usize, range: Range<usize> },
+}
+
+impl EncodingsIo for EncryptedIo {
+    fn submit_request(
+        &self,
+        ranges: Vec<Range<u64>>,
+        priority: u64,
+    ) -> BoxFuture<'static, Result<Vec<Bytes>>> {
+        let mut inner_ranges = Vec::with_capacity(ranges.len());
+        let mut sources = Vec::with_capacity(ranges.len());
+        // Each encrypted buffer is read once even if several ranges fall in it
+        let mut encrypted = Vec::<(usize, EncryptedBuffer)>::new();
+        let mut encrypted_by_offset = HashMap::<u64, usize>::new();
+        for range in ranges {
+            let offset = if range.is_empty() {
+                None
+            } else {
+                match self.find_buffer(&range) {
+                    Ok(offset) => offset,
+                    Err(err) => return std::future::ready(Err(err)).boxed(),
+                }
+            };
+            let Some(offset) = offset else {
+                sources.push(Source::Plain(inner_ranges.len()));
+                inner_ranges.push(range);
+                continue;
+            };
+            let buffer = &self.buffers[&offset];
+            if let Err(err) = buffer.column.cipher() {
+                return std::future::ready(Err(err)).boxed();
+            }
+            let idx = *encrypted_by_offset.entry(offset).or_insert_with(|| {
+                encrypted.push((inner_ranges.len(), buffer.clone()));
+                inner_ranges.push(offset..offset + buffer.size + TAG_LEN as u64);
+                encrypted.len() - 1
+            });
+            sources.push(Source::Encrypted {
+                buffer: idx,
+                range: (range.start - offset) as usize..(range.end - offset) as usize,
+            });
+        }
+
+        let read = self.inner.submit_request(inner_ranges, priority);
+        async move {
+            let data = read.await?;
+            let decrypted = encrypted
+                .iter()
+                .map(|(data_idx, buffer)| {
+                    let cipher = buffer.column.cipher()?;
+                    let plain = cipher.decrypt(buffer.location, &data[*data_idx])?;
+                    Ok(Bytes::from(plain))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(sources
+                .into_iter()
+                .map(|source| match source {
+                    Source::Plain(idx) => data[idx].clone(),
+                    Source::Encrypted { buffer, range } => decrypted[buffer].slice(range),
+                })
+                .collect())
+        }
+        .boxed()
+    }
+
+    fn with_bypass_backpressure(&self) -> Option<Arc<dyn EncodingsIo>> {
+        self.inner.with_bypass_backpressure().map(|inner| {
+            Arc::new(Self {
+                inner,
+                buffers: self.buffers.clone(),
+            }) as Arc<dyn EncodingsIo>
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BufferScheduler;
+
+    fn key(byte: u8) -> EncryptionKey {
+        EncryptionKey::new([byte; 32])
+    }
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let salt = ColumnCipher::generate_salt().unwrap();
+        let cipher = ColumnCipher::try_new(&key(1), &salt).unwrap();
+        let location = BufferLocation::Page { page: 3, buffer: 1 };
+        let sealed = cipher.encrypt(location, b"123-45-6789").unwrap();
+        assert_eq!(sealed.len(), 11 + TAG_LEN);
+        assert!(!sealed.windows(4).any(|w| w == b"6789"));
+        assert_eq!(cipher.decrypt(location, &sealed).unwrap(), b"123-45-6789");
+
+        // Another location, key or salt fails authentication
+        let other_location = BufferLocation::Column { buffer: 1 };
+        assert!(cipher.decrypt(other_location, &sealed).is_err());
+        let wrong_key = ColumnCipher::try_new(&key(2), &salt).unwrap();
+        assert!(wrong_key.decrypt(location, &sealed).is_err());
+        let other_salt = ColumnCipher::generate_salt().unwrap();
+        assert_ne!(salt, other_salt);
+        let wrong_salt = ColumnCipher::try_new(&key(1), &other_salt).unwrap();
+        assert!(wrong_salt.decrypt(location, &sealed).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_io() {
+        let cipher = ColumnCipher::try_new(&key(1), b"salt").unwrap();
+        let location = BufferLocation::Page { page: 0, buffer: 0 };
+        let plain = (0..100_u8).collect::<Vec<_>>();
+        // 10 plain bytes, then the sealed buffer (116 bytes), then 10 more plain bytes
+        let mut file = vec![9_u8; 10];
+        file.extend(cipher.encrypt(location, &plain).unwrap());
+        file.extend([8_u8; 10]);
+        let inner = Arc::new(BufferScheduler::new(Bytes::from(file)));
+
+        let column = Arc::new(EncryptedColumn {
+            name: "ssn".to_string(),
+            key_id: "pii".to_string(),
+            cipher: Some(cipher),
+        });
+        let buffer = EncryptedBuffer {
+            size: 100,
+            location,
+            column,
+        };
+        let io = EncryptedIo::new(
+            inner.clone(),
+            Arc::new(EncryptedBuffers::from([(10, buffer.clone())])),
+        );
+        let data = io
+            .submit_request(vec![0..2, 15..20, 10..110, 126..128, 50..50], 0)
+            .await
+            .unwrap();
+        assert_eq!(data[0].as_ref(), &[9, 9]);
+        assert_eq!(data[1].as_ref(), &plain[5..10]);
+        assert_eq!(data[2].as_ref(), &plain[..]);
+        assert_eq!(data[3].as_ref(), &[8, 8]);
+        assert!(data[4].is_empty());
+
+        // Reads that straddle the end of a buffer are rejected
+        assert!(io.submit_single(100..120, 0).await.is_err());
+
+        // Without the key only the plain ranges can be read
+        let column = Arc::new(EncryptedColumn {
+            name: "ssn".to_string(),
+            key_id: "pii".to_string(),
+            cipher: None,
+        });
+        let io = EncryptedIo::new(
+            inner,
+            Arc::new(EncryptedBuffers::from([(
+                10,
+                EncryptedBuffer {
+                    column,
+                    ..buffer.clone()
+                },
+            )])),
+        );
+        assert!(io.submit_single(0..10, 0).await.is_ok());
+        let err = io.submit_single(20..30, 0).await.unwrap_err();
+        assert!(err.to_string().contains("column 'ssn'"), "{err}");
+        assert!(err.to_string().contains("key 'pii'"), "{err}");
+    }
+}
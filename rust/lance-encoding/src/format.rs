@@ -430,6 +430,24 @@ macro_rules! impl_common_protobuf_utils {
                 })
             }
 
+            pub fn custom(
+                encoding_id: &str,
+                parameters: bytes::Bytes,
+                values: crate::format::$module::CompressiveEncoding,
+            ) -> crate::format::$module::CompressiveEncoding {
+                crate::format::$module::CompressiveEncoding {
+                    compression: Some(
+                        crate::format::$module::compressive_encoding::Compression::Custom(
+                            Box::new(crate::format::$module::Custom {
+                                encoding_id: encoding_id.to_string(),
+                                parameters,
+                                values: Some(Box::new(values)),
+                            }),
+                        ),
+                    ),
+                }
+            }
+
             pub fn rle(
                 values: crate::format::$module::CompressiveEncoding,
                 run_lengths: crate::format::$module::CompressiveEncoding,
@@ -564,6 +582,7 @@ macro_rules! impl_common_protobuf_utils {
                                     .collect(),
                                 num_items,
                                 has_large_chunk,
+                                shared_dictionary: None,
                 },
                         ),
                     ),
@@ -9,6 +9,7 @@ use futures::{FutureExt, TryFutureExt, future::BoxFuture};
 use lance_core::Result;
 
 pub mod buffer;
+pub mod buffer_pool;
 pub mod compression;
 pub mod compression_config;
 pub mod constants;
@@ -16,6 +17,7 @@ pub mod data;
 pub mod decoder;
 pub mod encoder;
 pub mod encodings;
+pub mod encryption;
 pub mod format;
 pub mod previous;
 pub mod repdef;
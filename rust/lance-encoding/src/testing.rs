@@ -27,6 +27,7 @@ use lance_datagen::{ArrayGenerator, RowCount, Seed, array, gen_batch};
 use crate::{
     EncodingsIo,
     buffer::LanceBuffer,
+    buffer_pool::BufferPool,
     decoder::{
         ColumnInfo, DecodeBatchScheduler, DecoderMessage, DecoderPlugins, FilterExpression,
         PageInfo, create_decode_stream,
@@ -179,6 +180,7 @@ async fn test_decode(
     expected: Option<Arc<dyn Array>>,
     io: Arc<dyn EncodingsIo>,
     is_structural_encoding: bool,
+    buffer_pool: Option<Arc<BufferPool>>,
     schedule_fn: impl FnOnce(
         DecodeBatchScheduler,
         UnboundedSender<Result<DecoderMessage>>,
@@ -219,6 +221,7 @@ async fn test_decode(
         /*spawn_structural_batch_decode_tasks=*/ is_structural_encoding,
         rx,
         /*batch_size_bytes=*/ None,
+        buffer_pool,
     )
     .unwrap();
 
@@ -545,6 +548,7 @@ fn tag(e: &Compression) -> &'static str {
         FixedSizeList(_) => "fixed_size_list",
         PackedStruct(_) => "packed_struct",
         VariablePackedStruct(_) => "variable_packed_struct",
+        Custom(_) => "custom",
     }
 }
 
@@ -571,6 +575,7 @@ fn child(c: &Compression) -> Option<Vec<&CompressiveEncoding>> {
         Fsst(f) => f.values.as_ref().map(|b| vec![b.as_ref()]),
         ByteStreamSplit(b) => b.values.as_ref().map(|b| vec![b.as_ref()]),
         General(g) => g.values.as_ref().map(|b| vec![b.as_ref()]),
+        Custom(c) => c.values.as_ref().map(|b| vec![b.as_ref()]),
         Dictionary(d) => {
             let mut children = Vec::new();
             if let Some(values) = d.items.as_ref() {
@@ -642,7 +647,7 @@ fn collect_page_encoding(layout: &PageLayout, actual_chain: &mut Vec<String>) ->
     if let Some(ref layout_type) = layout.layout {
         match layout_type {
             Layout::MiniBlockLayout(mini_block) => {
-                if mini_block.dictionary.is_some() {
+                if mini_block.dictionary.is_some() || mini_block.shared_dictionary.is_some() {
                     actual_chain.push("dictionary".to_string());
                 }
                 // Check value compression
@@ -993,29 +998,33 @@ async fn check_round_trip_encoding_inner(
 
     let schema = Schema::new(vec![decode_field]);
 
-    debug!("Testing full decode");
-    let scheduler_copy = scheduler.clone();
-    test_decode(
-        num_rows,
-        test_cases.batch_size,
-        &schema,
-        &column_infos,
-        expected_data.clone(),
-        scheduler_copy.clone(),
-        is_structural_encoding,
-        |mut decode_scheduler, tx| {
-            async move {
-                decode_scheduler.schedule_range(
-                    0..num_rows,
-                    &FilterExpression::no_filter(),
-                    tx,
-                    scheduler_copy,
-                )
-            }
-            .boxed()
-        },
-    )
-    .await;
+    // With a pool, later batches reuse the buffers of earlier (dropped) ones
+    for buffer_pool in [None, Some(Arc::new(BufferPool::new(1024 * 1024)))] {
+        debug!("Testing full decode (pooled={})", buffer_pool.is_some());
+        let scheduler_copy = scheduler.clone();
+        test_decode(
+            num_rows,
+            test_cases.batch_size,
+            &schema,
+            &column_infos,
+            expected_data.clone(),
+            scheduler_copy.clone(),
+            is_structural_encoding,
+            buffer_pool,
+            |mut decode_scheduler, tx| {
+                async move {
+                    decode_scheduler.schedule_range(
+                        0..num_rows,
+                        &FilterExpression::no_filter(),
+                        tx,
+                        scheduler_copy,
+                    )
+                }
+                .boxed()
+            },
+        )
+        .await;
+    }
 
     // Test range scheduling
     for range in &test_cases.ranges {
@@ -1034,6 +1043,7 @@ async fn check_round_trip_encoding_inner(
             expected,
             scheduler.clone(),
             is_structural_encoding,
+            /*buffer_pool=*/ None,
             |mut decode_scheduler, tx| {
                 async move {
                     decode_scheduler.schedule_range(
@@ -1090,6 +1100,7 @@ async fn check_round_trip_encoding_inner(
             expected,
             scheduler.clone(),
             is_structural_encoding,
+            /*buffer_pool=*/ None,
             |mut decode_scheduler, tx| {
                 async move {
                     decode_scheduler.schedule_take(
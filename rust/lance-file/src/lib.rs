@@ -6,10 +6,12 @@ pub mod format;
 pub(crate) mod io;
 pub mod previous;
 pub mod reader;
+pub mod standalone;
 pub mod testing;
 pub mod writer;
 
 pub use io::LanceEncodingsIo;
+pub use standalone::{LanceFileReader, LanceFileReaderOptions};
 
 use format::MAGIC;
 pub use lance_encoding::version;
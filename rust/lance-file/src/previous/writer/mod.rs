@@ -1,7 +1,7 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-FileCopyrightText: Copyright The Lance Authors
 
-mod statistics;
+pub(crate) mod statistics;
 
 use std::collections::HashMap;
 use std::marker::PhantomData;
@@ -81,6 +81,7 @@ pub struct FileWriter<M: ManifestProvider + Send + Sync> {
     page_table: PageTable,
     metadata: Metadata,
     stats_collector: Option<statistics::StatisticsCollector>,
+    stats_prefix_length: usize,
     manifest_provider: PhantomData<M>,
 }
 
@@ -91,6 +92,12 @@ pub struct FileWriterOptions {
     /// If None, will collect for all fields in the schema (that support stats).
     /// If an empty vector, will not collect any statistics.
     pub collect_stats_for_fields: Option<Vec<i32>>,
+    /// The max number of bytes of string and binary values kept in the min and
+    /// max statistics.
+    ///
+    /// Longer values are truncated and the max value is incremented so that it
+    /// remains an upper bound.  If None, 64 bytes are kept.
+    pub stats_prefix_length: Option<usize>,
 }
 
 impl<M: ManifestProvider + Send + Sync> FileWriter<M> {
@@ -130,6 +137,9 @@ impl<M: ManifestProvider + Send + Sync> FileWriter<M> {
             page_table: PageTable::default(),
             metadata: Metadata::default(),
             stats_collector,
+            stats_prefix_length: options
+                .stats_prefix_length
+                .unwrap_or(statistics::BINARY_PREFIX_LENGTH),
             manifest_provider: PhantomData,
         })
     }
@@ -190,7 +200,8 @@ impl<M: ManifestProvider + Send + Sync> FileWriter<M> {
         if let Some(stats_collector) = &mut self.stats_collector {
             for (field, arrays) in fields_in_batches(batches, &self.schema) {
                 if let Some(stats_builder) = stats_collector.get_builder(field.id) {
-                    let stats_row = statistics::collect_statistics(&arrays);
+                    let stats_row =
+                        statistics::collect_statistics(&arrays, self.stats_prefix_length);
                     stats_builder.append(stats_row);
                 }
             }
@@ -1053,6 +1064,7 @@ mod tests {
 
         let options = FileWriterOptions {
             collect_stats_for_fields: Some(vec![0, 1, 5, 6]),
+            ..Default::default()
         };
         let mut file_writer =
             FileWriter::<NotSelfDescribing>::try_new(&store, &path, schema.clone(), &options)
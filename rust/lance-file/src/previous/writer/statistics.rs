@@ -14,12 +14,12 @@ use arrow_array::{
     builder::{GenericBinaryBuilder, GenericStringBuilder},
     cast::{AsArray, as_generic_binary_array, as_primitive_array},
     types::{
-        ArrowDictionaryKeyType, Date32Type, Date64Type, Decimal128Type, DurationMicrosecondType,
-        DurationMillisecondType, DurationNanosecondType, DurationSecondType, Float32Type,
-        Float64Type, Int8Type, Int16Type, Int32Type, Int64Type, Time32MillisecondType,
-        Time32SecondType, Time64MicrosecondType, Time64NanosecondType, TimestampMicrosecondType,
-        TimestampMillisecondType, TimestampNanosecondType, TimestampSecondType, UInt8Type,
-        UInt16Type, UInt32Type, UInt64Type,
+        ArrowDictionaryKeyType, Date32Type, Date64Type, Decimal128Type, Decimal256Type,
+        DecimalType, DurationMicrosecondType, DurationMillisecondType, DurationNanosecondType,
+        DurationSecondType, Float32Type, Float64Type, Int8Type, Int16Type, Int32Type, Int64Type,
+        Time32MillisecondType, Time32SecondType, Time64MicrosecondType, Time64NanosecondType,
+        TimestampMicrosecondType, TimestampMillisecondType, TimestampNanosecondType,
+        TimestampSecondType, UInt8Type, UInt16Type, UInt32Type, UInt64Type,
     },
 };
 use arrow_schema::{ArrowError, DataType, Field as ArrowField, Schema as ArrowSchema, TimeUnit};
@@ -30,8 +30,8 @@ use lance_core::datatypes::{Field, Schema};
 use num_traits::{Float, Zero, bounds::Bounded};
 use std::str;
 
-/// Max number of bytes that are included in statistics for binary columns.
-const BINARY_PREFIX_LENGTH: usize = 64;
+/// Default max number of bytes that are included in statistics for binary columns.
+pub const BINARY_PREFIX_LENGTH: usize = 64;
 
 /// Statistics for a single column chunk.
 #[derive(Debug, PartialEq)]
@@ -160,16 +160,16 @@ where
     }
 }
 
-fn get_decimal_statistics(arrays: &[&ArrayRef]) -> StatisticsRow {
-    let (min_value, max_value, null_count) = compute_primitive_statistics::<Decimal128Type>(arrays);
-    let array = as_primitive_array::<Decimal128Type>(arrays[0]);
-    let precision = array.precision();
-    let scale = array.scale();
-
+fn get_decimal_statistics<T: DecimalType>(arrays: &[&ArrayRef]) -> StatisticsRow
+where
+    T::Native: Bounded,
+{
+    // The data type carries the precision and scale of the statistics
+    let (min_value, max_value, null_count) = compute_primitive_statistics::<T>(arrays);
     StatisticsRow {
         null_count,
-        min_value: ScalarValue::Decimal128(Some(min_value), precision, scale),
-        max_value: ScalarValue::Decimal128(Some(max_value), precision, scale),
+        min_value: ScalarValue::new_primitive::<T>(Some(min_value), arrays[0].data_type()).unwrap(),
+        max_value: ScalarValue::new_primitive::<T>(Some(max_value), arrays[0].data_type()).unwrap(),
     }
 }
 
@@ -238,11 +238,18 @@ fn increment(mut data: Vec<u8>) -> Option<Vec<u8>> {
     None
 }
 
-fn get_string_statistics<T: OffsetSizeTrait>(arrays: &[&ArrayRef]) -> StatisticsRow {
+fn get_string_statistics<T: OffsetSizeTrait>(
+    arrays: &[&ArrayRef],
+    prefix_length: usize,
+) -> StatisticsRow {
+    // A UTF8 character is at most 4 bytes, so this guarantees that truncation
+    // always keeps at least one character.
+    let prefix_length = prefix_length.max(4);
     let mut min_value: Option<&str> = None;
     let mut max_value: Option<&str> = None;
     let mut null_count: i64 = 0;
-    let mut bounds_truncated = false;
+    // Whether any value that shares the (truncated) max_value prefix was truncated.
+    let mut max_truncated = false;
 
     let array_iterator = arrays.iter().map(|x| x.as_string::<T>());
 
@@ -254,9 +261,10 @@ fn get_string_statistics<T: OffsetSizeTrait>(arrays: &[&ArrayRef]) -> Statistics
 
         array.iter().for_each(|value| {
             if let Some(mut val) = value {
-                if val.len() > BINARY_PREFIX_LENGTH {
-                    val = truncate_utf8(val, BINARY_PREFIX_LENGTH).unwrap();
-                    bounds_truncated = true;
+                let mut truncated = false;
+                if val.len() > prefix_length {
+                    val = truncate_utf8(val, prefix_length).unwrap();
+                    truncated = true;
                 }
 
                 if let Some(v) = min_value {
@@ -268,11 +276,17 @@ fn get_string_statistics<T: OffsetSizeTrait>(arrays: &[&ArrayRef]) -> Statistics
                 }
 
                 if let Some(v) = max_value {
-                    if let Some(Ordering::Greater) = val.partial_cmp(v) {
-                        max_value = Some(val);
+                    match val.partial_cmp(v) {
+                        Some(Ordering::Greater) => {
+                            max_value = Some(val);
+                            max_truncated = truncated;
+                        }
+                        Some(Ordering::Equal) => max_truncated |= truncated,
+                        _ => {}
                     }
                 } else {
                     max_value = Some(val);
+                    max_truncated = truncated;
                 }
             }
         });
@@ -280,10 +294,10 @@ fn get_string_statistics<T: OffsetSizeTrait>(arrays: &[&ArrayRef]) -> Statistics
 
     let max_value_bound: Option<Vec<u8>>;
     if let Some(v) = max_value {
-        // If the bounds were truncated, then we need to increment the max_value,
-        // since shorter values are considered smaller than longer values if the
+        // If the max value was truncated, then we need to increment it, since
+        // shorter values are considered smaller than longer values if the
         // short values are a prefix of the long ones.
-        if bounds_truncated {
+        if max_truncated {
             max_value_bound = increment_utf8(v.as_bytes().to_vec());
             // We can safely unwrap because increment_utf8 will only return
             // Some() if the bound is valid UTF-8 bytes.
@@ -313,11 +327,16 @@ fn get_string_statistics<T: OffsetSizeTrait>(arrays: &[&ArrayRef]) -> Statistics
     }
 }
 
-fn get_binary_statistics<T: OffsetSizeTrait>(arrays: &[&ArrayRef]) -> StatisticsRow {
+fn get_binary_statistics<T: OffsetSizeTrait>(
+    arrays: &[&ArrayRef],
+    prefix_length: usize,
+) -> StatisticsRow {
+    let prefix_length = prefix_length.max(1);
     let mut min_value: Option<&[u8]> = None;
     let mut max_value: Option<&[u8]> = None;
     let mut null_count: i64 = 0;
-    let mut bounds_truncated = false;
+    // Whether any value that shares the (truncated) max_value prefix was truncated.
+    let mut max_truncated = false;
 
     let array_iterator = arrays.iter().map(|x| as_generic_binary_array::<T>(x));
 
@@ -329,11 +348,12 @@ fn get_binary_statistics<T: OffsetSizeTrait>(arrays: &[&ArrayRef]) -> Statistics
 
         array.iter().for_each(|value| {
             if let Some(mut val) = value {
-                // Truncate binary buffer to BINARY_PREFIX_LENGTH to avoid comparing potentially
+                // Truncate binary buffer to prefix_length to avoid comparing potentially
                 // very long buffers.
-                if val.len() > BINARY_PREFIX_LENGTH {
-                    val = truncate_binary(val, BINARY_PREFIX_LENGTH).unwrap();
-                    bounds_truncated = true;
+                let mut truncated = false;
+                if val.len() > prefix_length {
+                    val = truncate_binary(val, prefix_length).unwrap();
+                    truncated = true;
                 }
 
                 if let Some(v) = min_value {
@@ -345,22 +365,28 @@ fn get_binary_statistics<T: OffsetSizeTrait>(arrays: &[&ArrayRef]) -> Statistics
                 }
 
                 if let Some(v) = max_value {
-                    if let Some(Ordering::Greater) = val.partial_cmp(v) {
-                        max_value = Some(val);
+                    match val.partial_cmp(v) {
+                        Some(Ordering::Greater) => {
+                            max_value = Some(val);
+                            max_truncated = truncated;
+                        }
+                        Some(Ordering::Equal) => max_truncated |= truncated,
+                        _ => {}
                     }
                 } else {
                     max_value = Some(val);
+                    max_truncated = truncated;
                 }
             }
         });
     }
 
     let min_value = min_value.map(|x| x.to_vec());
-    // If the bounds were truncated, then we need to increment the max_value,
-    // since shorter values are considered smaller than longer values if the
+    // If the max value was truncated, then we need to increment it, since
+    // shorter values are considered smaller than longer values if the
     // short values are a prefix of the long ones.
     let max_value = if let Some(v) = max_value {
-        if bounds_truncated {
+        if max_truncated {
             increment(v.to_vec())
         } else {
             Some(v.to_vec())
@@ -386,7 +412,8 @@ fn get_binary_statistics<T: OffsetSizeTrait>(arrays: &[&ArrayRef]) -> Statistics
     }
 }
 
-fn get_fixed_size_binary_statistics(arrays: &[&ArrayRef]) -> StatisticsRow {
+fn get_fixed_size_binary_statistics(arrays: &[&ArrayRef], prefix_length: usize) -> StatisticsRow {
+    let prefix_length = prefix_length.max(1);
     let mut min_value: Option<&[u8]> = None;
     let mut max_value: Option<&[u8]> = None;
     let mut null_count: i64 = 0;
@@ -394,9 +421,9 @@ fn get_fixed_size_binary_statistics(arrays: &[&ArrayRef]) -> StatisticsRow {
     let array_iterator = arrays.iter().map(|x| as_fixed_size_binary_array(x));
 
     let length = as_fixed_size_binary_array(arrays[0]).value_length() as usize;
-    // Truncate binary buffer to BINARY_PREFIX_LENGTH to avoid comparing potentially
+    // Truncate binary buffer to prefix_length to avoid comparing potentially
     // very long buffers.
-    let do_truncate = length > BINARY_PREFIX_LENGTH;
+    let do_truncate = length > prefix_length;
 
     for array in array_iterator {
         null_count += array.null_count() as i64;
@@ -407,7 +434,7 @@ fn get_fixed_size_binary_statistics(arrays: &[&ArrayRef]) -> StatisticsRow {
         array.iter().for_each(|value| {
             if let Some(mut val) = value {
                 if do_truncate {
-                    val = truncate_binary(val, BINARY_PREFIX_LENGTH).unwrap();
+                    val = truncate_binary(val, prefix_length).unwrap();
                 }
 
                 if let Some(v) = min_value {
@@ -490,18 +517,34 @@ fn cast_dictionary_arrays<'a, T: ArrowDictionaryKeyType + 'static>(
         .collect::<Vec<_>>()
 }
 
-fn get_dictionary_statistics(arrays: &[&ArrayRef]) -> StatisticsRow {
+fn get_dictionary_statistics(arrays: &[&ArrayRef], prefix_length: usize) -> StatisticsRow {
     let data_type = arrays[0].data_type();
     match data_type {
         DataType::Dictionary(key_type, _) => match key_type.as_ref() {
-            DataType::Int8 => collect_statistics(&cast_dictionary_arrays::<Int8Type>(arrays)),
-            DataType::Int16 => collect_statistics(&cast_dictionary_arrays::<Int16Type>(arrays)),
-            DataType::Int32 => collect_statistics(&cast_dictionary_arrays::<Int32Type>(arrays)),
-            DataType::Int64 => collect_statistics(&cast_dictionary_arrays::<Int64Type>(arrays)),
-            DataType::UInt8 => collect_statistics(&cast_dictionary_arrays::<UInt8Type>(arrays)),
-            DataType::UInt16 => collect_statistics(&cast_dictionary_arrays::<UInt16Type>(arrays)),
-            DataType::UInt32 => collect_statistics(&cast_dictionary_arrays::<UInt32Type>(arrays)),
-            DataType::UInt64 => collect_statistics(&cast_dictionary_arrays::<UInt64Type>(arrays)),
+            DataType::Int8 => {
+                collect_statistics(&cast_dictionary_arrays::<Int8Type>(arrays), prefix_length)
+            }
+            DataType::Int16 => {
+                collect_statistics(&cast_dictionary_arrays::<Int16Type>(arrays), prefix_length)
+            }
+            DataType::Int32 => {
+                collect_statistics(&cast_dictionary_arrays::<Int32Type>(arrays), prefix_length)
+            }
+            DataType::Int64 => {
+                collect_statistics(&cast_dictionary_arrays::<Int64Type>(arrays), prefix_length)
+            }
+            DataType::UInt8 => {
+                collect_statistics(&cast_dictionary_arrays::<UInt8Type>(arrays), prefix_length)
+            }
+            DataType::UInt16 => {
+                collect_statistics(&cast_dictionary_arrays::<UInt16Type>(arrays), prefix_length)
+            }
+            DataType::UInt32 => {
+                collect_statistics(&cast_dictionary_arrays::<UInt32Type>(arrays), prefix_length)
+            }
+            DataType::UInt64 => {
+                collect_statistics(&cast_dictionary_arrays::<UInt64Type>(arrays), prefix_length)
+            }
             _ => {
                 panic!("Unsupported dictionary key type: {}", key_type);
             }
@@ -547,7 +590,11 @@ fn get_temporal_statistics(arrays: &[&ArrayRef]) -> StatisticsRow {
     }
 }
 
-pub fn collect_statistics(arrays: &[&ArrayRef]) -> StatisticsRow {
+/// Collect statistics, keeping at most `prefix_length` bytes of string and
+/// binary values in the min and max values.
+///
+/// Truncated max values are incremented so they remain an upper bound.
+pub fn collect_statistics(arrays: &[&ArrayRef], prefix_length: usize) -> StatisticsRow {
     if arrays.is_empty() {
         panic!("No arrays to collect statistics from");
     }
@@ -569,14 +616,14 @@ pub fn collect_statistics(arrays: &[&ArrayRef]) -> StatisticsRow {
         | DataType::Time64(_)
         | DataType::Timestamp(_, _)
         | DataType::Duration(_) => get_temporal_statistics(arrays),
-        DataType::Decimal128(_, _) => get_decimal_statistics(arrays),
-        // TODO: Decimal256
-        DataType::Binary => get_binary_statistics::<i32>(arrays),
-        DataType::LargeBinary => get_binary_statistics::<i64>(arrays),
-        DataType::FixedSizeBinary(_) => get_fixed_size_binary_statistics(arrays),
-        DataType::Utf8 => get_string_statistics::<i32>(arrays),
-        DataType::LargeUtf8 => get_string_statistics::<i64>(arrays),
-        DataType::Dictionary(_, _) => get_dictionary_statistics(arrays),
+        DataType::Decimal128(_, _) => get_decimal_statistics::<Decimal128Type>(arrays),
+        DataType::Decimal256(_, _) => get_decimal_statistics::<Decimal256Type>(arrays),
+        DataType::Binary => get_binary_statistics::<i32>(arrays, prefix_length),
+        DataType::LargeBinary => get_binary_statistics::<i64>(arrays, prefix_length),
+        DataType::FixedSizeBinary(_) => get_fixed_size_binary_statistics(arrays, prefix_length),
+        DataType::Utf8 => get_string_statistics::<i32>(arrays, prefix_length),
+        DataType::LargeUtf8 => get_string_statistics::<i64>(arrays, prefix_length),
+        DataType::Dictionary(_, _) => get_dictionary_statistics(arrays, prefix_length),
         // DataType::List(_) => get_list_statistics(arrays),
         // DataType::LargeList(_) => get_list_statistics(arrays),
         _ => unreachable!(),
@@ -605,6 +652,7 @@ pub fn supports_stats_collection(datatype: &DataType) -> bool {
             | DataType::LargeBinary
             // | DataType::FixedSizeBinary(_)
             | DataType::Decimal128(_, _)
+            | DataType::Decimal256(_, _)
     )
 }
 
@@ -640,10 +688,12 @@ impl StatisticsCollector {
             let null_count = Arc::new(builder.null_count.finish());
             let min_value = Arc::new(builder.min_value.finish());
             let max_value = Arc::new(builder.max_value.finish());
+            // The bounds are null when a page has no values, or when a truncated
+            // max value can't be incremented, even if the field is not nullable
             let struct_fields = vec![
                 ArrowField::new("null_count", DataType::Int64, false),
-                ArrowField::new("min_value", field.data_type(), field.nullable),
-                ArrowField::new("max_value", field.data_type(), field.nullable),
+                ArrowField::new("min_value", field.data_type(), true),
+                ArrowField::new("max_value", field.data_type(), true),
             ];
 
             let stats = StructArray::new(
@@ -914,7 +964,7 @@ impl StatisticsBuilder {
                 self.statistics_appender::<DurationNanosecondType>(row)
             }
             DataType::Decimal128(_, _) => self.statistics_appender::<Decimal128Type>(row),
-            // TODO: Decimal256
+            DataType::Decimal256(_, _) => self.statistics_appender::<Decimal256Type>(row),
             DataType::Binary => self.binary_statistics_appender::<i32>(row),
             DataType::LargeBinary => self.binary_statistics_appender::<i64>(row),
             DataType::Utf8 => self.string_statistics_appender::<i32>(row),
@@ -931,7 +981,7 @@ impl StatisticsBuilder {
 mod tests {
     use arrow_array::{
         BinaryArray, BooleanArray, Date32Array, Date64Array, Datum, Decimal128Array,
-        DictionaryArray, DurationMicrosecondArray, DurationMillisecondArray,
+        Decimal256Array, DictionaryArray, DurationMicrosecondArray, DurationMillisecondArray,
         DurationNanosecondArray, DurationSecondArray, FixedSizeBinaryArray, Float32Array,
         Float64Array, Int8Array, Int16Array, Int32Array, Int64Array, LargeBinaryArray,
         LargeStringArray, StringArray, Time32MillisecondArray, Time32SecondArray,
@@ -940,6 +990,7 @@ mod tests {
         UInt16Array, UInt32Array, UInt64Array, builder::StringDictionaryBuilder, make_array,
         new_empty_array, new_null_array,
     };
+    use arrow_buffer::i256;
     use arrow_select::interleave::interleave;
     use num_traits::One;
     use proptest::{prop_assert, prop_assert_eq, strategy::Strategy, test_runner::TestCaseError};
@@ -952,7 +1003,7 @@ mod tests {
     fn test_no_arrays() {
         let arrays: Vec<ArrayRef> = vec![];
         let array_refs = arrays.iter().collect::<Vec<_>>();
-        collect_statistics(array_refs.as_slice());
+        collect_statistics(array_refs.as_slice(), BINARY_PREFIX_LENGTH);
     }
 
     #[test]
@@ -961,7 +1012,7 @@ mod tests {
         let arrays: Vec<ArrayRef> = vec![Arc::new(UInt32Array::from_iter_values(vec![]))];
         let array_refs = arrays.iter().collect::<Vec<_>>();
         assert_eq!(
-            collect_statistics(array_refs.as_slice()),
+            collect_statistics(array_refs.as_slice(), BINARY_PREFIX_LENGTH),
             StatisticsRow {
                 null_count: 0,
                 min_value: ScalarValue::from(u32::MIN),
@@ -973,7 +1024,7 @@ mod tests {
         let arrays: Vec<ArrayRef> = vec![Arc::new(StringArray::from(empty_string_vec))];
         let array_refs = arrays.iter().collect::<Vec<_>>();
         assert_eq!(
-            collect_statistics(array_refs.as_slice()),
+            collect_statistics(array_refs.as_slice(), BINARY_PREFIX_LENGTH),
             StatisticsRow {
                 null_count: 0,
                 min_value: ScalarValue::Utf8(None),
@@ -984,7 +1035,7 @@ mod tests {
         let arrays: Vec<ArrayRef> = vec![Arc::new(LargeStringArray::from(empty_string_vec))];
         let array_refs = arrays.iter().collect::<Vec<_>>();
         assert_eq!(
-            collect_statistics(array_refs.as_slice()),
+            collect_statistics(array_refs.as_slice(), BINARY_PREFIX_LENGTH),
             StatisticsRow {
                 null_count: 0,
                 min_value: ScalarValue::LargeUtf8(None),
@@ -996,7 +1047,7 @@ mod tests {
         let arrays: Vec<ArrayRef> = vec![Arc::new(BinaryArray::from(empty_binary_vec))];
         let array_refs = arrays.iter().collect::<Vec<_>>();
         assert_eq!(
-            collect_statistics(array_refs.as_slice()),
+            collect_statistics(array_refs.as_slice(), BINARY_PREFIX_LENGTH),
             StatisticsRow {
                 null_count: 0,
                 min_value: ScalarValue::Binary(None),
@@ -1007,7 +1058,7 @@ mod tests {
         let arrays: Vec<ArrayRef> = vec![Arc::new(LargeBinaryArray::from(empty_binary_vec))];
         let array_refs = arrays.iter().collect::<Vec<_>>();
         assert_eq!(
-            collect_statistics(array_refs.as_slice()),
+            collect_statistics(array_refs.as_slice(), BINARY_PREFIX_LENGTH),
             StatisticsRow {
                 null_count: 0,
                 min_value: ScalarValue::LargeBinary(None),
@@ -1019,7 +1070,7 @@ mod tests {
         let arrays: Vec<ArrayRef> = vec![Arc::new(BooleanArray::from(empty_boolean_vec))];
         let array_refs = arrays.iter().collect::<Vec<_>>();
         assert_eq!(
-            collect_statistics(array_refs.as_slice()),
+            collect_statistics(array_refs.as_slice(), BINARY_PREFIX_LENGTH),
             StatisticsRow {
                 null_count: 0,
                 min_value: ScalarValue::from(false),
@@ -1289,11 +1340,48 @@ mod tests {
                 expected_max: ScalarValue::try_new_decimal128(68, 38, 10).unwrap(),
                 expected_null_count: 0,
             },
+            TestCase {
+                source_arrays: vec![
+                    Arc::new(
+                        Decimal128Array::from(vec![Some(i128::MAX / 10), None])
+                            .with_precision_and_scale(38, 0)
+                            .unwrap(),
+                    ),
+                    Arc::new(
+                        Decimal128Array::from(vec![-(i128::MAX / 10)])
+                            .with_precision_and_scale(38, 0)
+                            .unwrap(),
+                    ),
+                ],
+                expected_min: ScalarValue::Decimal128(Some(-(i128::MAX / 10)), 38, 0),
+                expected_max: ScalarValue::Decimal128(Some(i128::MAX / 10), 38, 0),
+                expected_null_count: 1,
+            },
+            TestCase {
+                source_arrays: vec![
+                    Arc::new(
+                        Decimal256Array::from(vec![
+                            i256::from_i128(53),
+                            i256::from_i128(i128::MAX),
+                        ])
+                        .with_precision_and_scale(76, 20)
+                        .unwrap(),
+                    ),
+                    Arc::new(
+                        Decimal256Array::from(vec![i256::from_i128(-68), i256::from_i128(32)])
+                            .with_precision_and_scale(76, 20)
+                            .unwrap(),
+                    ),
+                ],
+                expected_min: ScalarValue::Decimal256(Some(i256::from_i128(-68)), 76, 20),
+                expected_max: ScalarValue::Decimal256(Some(i256::from_i128(i128::MAX)), 76, 20),
+                expected_null_count: 0,
+            },
         ];
 
         for case in cases {
             let array_refs = case.source_arrays.iter().collect::<Vec<_>>();
-            let stats = collect_statistics(&array_refs);
+            let stats = collect_statistics(&array_refs, BINARY_PREFIX_LENGTH);
             assert_eq!(
                 stats,
                 StatisticsRow {
@@ -1315,7 +1403,7 @@ mod tests {
             Arc::new(Float32Array::from(vec![-10.0f32, 3.0, 5.0, f32::NAN])),
         ];
         let array_refs = arrays.iter().collect::<Vec<_>>();
-        let stats = collect_statistics(&array_refs);
+        let stats = collect_statistics(&array_refs, BINARY_PREFIX_LENGTH);
         assert_eq!(
             stats,
             StatisticsRow {
@@ -1332,7 +1420,7 @@ mod tests {
             f64::infinity(),
         ]))];
         let array_refs = arrays.iter().collect::<Vec<_>>();
-        let stats = collect_statistics(&array_refs);
+        let stats = collect_statistics(&array_refs, BINARY_PREFIX_LENGTH);
         assert_eq!(
             stats,
             StatisticsRow {
@@ -1345,7 +1433,7 @@ mod tests {
         // Max value for zero is always positive, min value for zero is always negative
         let arrays: Vec<ArrayRef> = vec![Arc::new(Float32Array::from(vec![-0.0, 0.0]))];
         let array_refs = arrays.iter().collect::<Vec<_>>();
-        let stats = collect_statistics(&array_refs);
+        let stats = collect_statistics(&array_refs, BINARY_PREFIX_LENGTH);
         assert_eq!(
             stats,
             StatisticsRow {
@@ -1363,7 +1451,7 @@ mod tests {
             f64::NAN,
         ]))];
         let array_refs = arrays.iter().collect::<Vec<_>>();
-        let stats = collect_statistics(&array_refs);
+        let stats = collect_statistics(&array_refs, BINARY_PREFIX_LENGTH);
         assert_eq!(
             stats,
             StatisticsRow {
@@ -1590,7 +1678,7 @@ mod tests {
         for case in cases {
             let array_refs = case.source_arrays.iter().collect::<Vec<_>>();
             assert_eq!(
-                collect_statistics(&array_refs),
+                collect_statistics(&array_refs, BINARY_PREFIX_LENGTH),
                 case.stats,
                 "Statistics are wrong for input data: {:?}",
                 case.source_arrays
@@ -1598,6 +1686,64 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_collect_truncated_stats_with_prefix_length() {
+        // Values sharing a common prefix longer than the prefix length collapse
+        // to the same truncated bound, so the max must be incremented.
+        let common = "x".repeat(100);
+        let array: ArrayRef = Arc::new(StringArray::from(vec![
+            format!("{common}1"),
+            format!("{common}2"),
+        ]));
+        let stats = collect_statistics(&[&array], 8);
+        assert_eq!(
+            stats,
+            StatisticsRow {
+                null_count: 0,
+                min_value: ScalarValue::from("xxxxxxxx"),
+                max_value: ScalarValue::from("xxxxxxxy"),
+            }
+        );
+
+        // Only the max value determines whether the upper bound is incremented.
+        let array: ArrayRef = Arc::new(StringArray::from(vec![
+            Some("aaaaaaaaaaaa"),
+            None,
+            Some("b"),
+        ]));
+        let stats = collect_statistics(&[&array], 8);
+        assert_eq!(
+            stats,
+            StatisticsRow {
+                null_count: 1,
+                min_value: ScalarValue::from("aaaaaaaa"),
+                max_value: ScalarValue::from("b"),
+            }
+        );
+
+        // A short value equal to the truncated prefix of a longer one still
+        // needs the incremented bound.
+        let array: ArrayRef = Arc::new(StringArray::from(vec!["abcdefgh", "abcdefghij"]));
+        let stats = collect_statistics(&[&array], 8);
+        assert_eq!(stats.max_value, ScalarValue::from("abcdefgi"));
+
+        // Non-UTF8 binary values are incremented byte-wise, carrying over 0xFF.
+        let array: ArrayRef = Arc::new(BinaryArray::from(vec![
+            [0x00u8, 0x80, 0xFE].as_ref(),
+            [0x01u8, 0xFF, 0xFF, 0x10].as_ref(),
+            [0x01u8, 0xFF].as_ref(),
+        ]));
+        let stats = collect_statistics(&[&array], 3);
+        assert_eq!(
+            stats,
+            StatisticsRow {
+                null_count: 0,
+                min_value: ScalarValue::Binary(Some(vec![0x00, 0x80, 0xFE])),
+                max_value: ScalarValue::Binary(Some(vec![0x02, 0x00, 0x00])),
+            }
+        );
+    }
+
     #[test]
     fn test_collect_dictionary_stats() {
         // Dictionary stats are collected from the underlying values
@@ -1613,7 +1759,7 @@ mod tests {
         let arr = builder.finish();
         let arrays: Vec<ArrayRef> = vec![Arc::new(arr)];
         let array_refs = arrays.iter().collect::<Vec<_>>();
-        let stats = collect_statistics(&array_refs);
+        let stats = collect_statistics(&array_refs, BINARY_PREFIX_LENGTH);
         assert_eq!(
             stats,
             StatisticsRow {
@@ -1631,7 +1777,7 @@ mod tests {
         let dictionary_array_2 =
             Arc::new(DictionaryArray::try_new(indices_2, dictionary).unwrap()) as ArrayRef;
         let array_refs = vec![&dictionary_array_1, &dictionary_array_2];
-        let stats = collect_statistics(&array_refs);
+        let stats = collect_statistics(&array_refs, BINARY_PREFIX_LENGTH);
         assert_eq!(
             stats,
             StatisticsRow {
@@ -1964,7 +2110,7 @@ mod tests {
             array
         };
 
-        let stats = collect_statistics(&[&array]);
+        let stats = collect_statistics(&[&array], BINARY_PREFIX_LENGTH);
         prop_assert_eq!(
             stats,
             StatisticsRow {
@@ -2060,7 +2206,7 @@ mod tests {
         // Turn into an array and compute statistics
         let results = subset.prop_map(|subset| {
             let array = Arc::new(PrimitiveArray::<F>::from_iter_values(subset.clone())) as ArrayRef;
-            let statistics = collect_statistics(&[&array]);
+            let statistics = collect_statistics(&[&array], BINARY_PREFIX_LENGTH);
             (subset, statistics)
         });
 
@@ -2105,7 +2251,7 @@ mod tests {
         #[test]
         fn test_min_max_ordering_string(values in proptest::collection::vec(".{0, 100}", 0..10)) {
             let array = Arc::new(StringArray::from(values.clone())) as ArrayRef;
-            let statistics = collect_statistics(&[&array]);
+            let statistics = collect_statistics(&[&array], BINARY_PREFIX_LENGTH);
 
             // If array is empty, assert min and max are null
             if array.is_empty() {
@@ -2137,7 +2283,7 @@ mod tests {
         #[test]
         fn test_min_max_ordering_binary(values in proptest::collection::vec(proptest::collection::vec(0..u8::MAX, 0..100), 0..10)) {
             let array = Arc::new(BinaryArray::from_iter_values(values.clone())) as ArrayRef;
-            let statistics = collect_statistics(&[&array]);
+            let statistics = collect_statistics(&[&array], BINARY_PREFIX_LENGTH);
 
             // If array is empty, assert min and max are null
             if array.is_empty() {
@@ -2175,7 +2321,7 @@ mod tests {
                 Arc::new(FixedSizeBinaryArray::try_from_iter(values.iter()).unwrap()) as ArrayRef
             };
 
-            let statistics = collect_statistics(&[&array]);
+            let statistics = collect_statistics(&[&array], BINARY_PREFIX_LENGTH);
 
             // If array is empty, assert min and max are null
             if array.is_empty() {
@@ -2218,14 +2364,14 @@ mod tests {
         let array2_ref: ArrayRef = Arc::new(bool_array2);
 
         // Test individual arrays first
-        let stats1 = collect_statistics(&[&array1_ref]);
-        let stats2 = collect_statistics(&[&array2_ref]);
+        let stats1 = collect_statistics(&[&array1_ref], BINARY_PREFIX_LENGTH);
+        let stats2 = collect_statistics(&[&array2_ref], BINARY_PREFIX_LENGTH);
 
         assert_eq!(stats1.null_count, 2, "First array should have 2 nulls");
         assert_eq!(stats2.null_count, 2, "Second array should have 2 nulls");
 
         let array_refs: Vec<&ArrayRef> = vec![&array1_ref, &array2_ref];
-        let combined_stats = collect_statistics(&array_refs);
+        let combined_stats = collect_statistics(&array_refs, BINARY_PREFIX_LENGTH);
 
         assert_eq!(
             combined_stats.null_count, 4,
@@ -2,14 +2,14 @@
 // SPDX-FileCopyrightText: Copyright The Lance Authors
 
 use std::{
-    collections::{BTreeMap, BTreeSet},
+    collections::{BTreeMap, BTreeSet, HashMap},
     io::Cursor,
     ops::Range,
     pin::Pin,
     sync::Arc,
 };
 
-use arrow_array::RecordBatchReader;
+use arrow_array::{RecordBatch, RecordBatchReader};
 use arrow_schema::Schema as ArrowSchema;
 use byteorder::{ByteOrder, LittleEndian, ReadBytesExt};
 use bytes::{Bytes, BytesMut};
@@ -19,10 +19,14 @@ use lance_encoding::{
     EncodingsIo,
     decoder::{
         ColumnInfo, DecoderConfig, DecoderPlugins, FilterExpression, PageEncoding, PageInfo,
-        ReadBatchTask, RequestedRows, SchedulerDecoderConfig, schedule_and_decode,
+        ReadBatchTask, RequestedRows, SchedulerDecoderConfig, decode_batch, schedule_and_decode,
         schedule_and_decode_blocking,
     },
     encoder::EncodedBatch,
+    encryption::{
+        BufferLocation, ColumnCipher, EncryptedBuffer, EncryptedBuffers, EncryptedColumn,
+        EncryptedIo,
+    },
     version::LanceFileVersion,
 };
 use log::debug;
@@ -33,6 +37,7 @@ use lance_core::{
     Error, Result,
     cache::LanceCache,
     datatypes::{Field, Schema},
+    utils::encryption::KeyProvider,
 };
 use lance_encoding::format::pb as pbenc;
 use lance_encoding::format::pb21 as pbenc21;
@@ -46,7 +51,7 @@ use crate::{
     datatypes::{Fields, FieldsWithMeta},
     format::{MAGIC, MAJOR_VERSION, MINOR_VERSION, pb, pbfile},
     io::LanceEncodingsIo,
-    writer::PAGE_BUFFER_ALIGNMENT,
+    writer::{PAGE_BUFFER_ALIGNMENT, PAGE_STATS_META_KEY},
 };
 
 /// Default chunk size for reading large pages (8MiB)
@@ -310,6 +315,39 @@ impl ReaderProjection {
         Ok(())
     }
 
+    /// Maps the id of each field stored in a column of its own to that column's index
+    ///
+    /// This follows the column layout of [`Self::from_whole_schema`]: the children of
+    /// a packed struct are stored in the struct's column and aren't in the mapping.
+    pub(crate) fn field_id_to_column_index(
+        schema: &Schema,
+        version: LanceFileVersion,
+    ) -> BTreeMap<u32, u32> {
+        fn visit<'a>(
+            fields: impl Iterator<Item = &'a Field>,
+            is_structural: bool,
+            mapping: &mut BTreeMap<u32, u32>,
+        ) {
+            for field in fields {
+                if field.is_packed_struct() {
+                    mapping.insert(field.id as u32, mapping.len() as u32);
+                    continue;
+                }
+                if field.children.is_empty() || !is_structural {
+                    mapping.insert(field.id as u32, mapping.len() as u32);
+                }
+                visit(field.children.iter(), is_structural, mapping);
+            }
+        }
+        let mut mapping = BTreeMap::new();
+        visit(
+            schema.fields.iter(),
+            version >= LanceFileVersion::V2_1,
+            &mut mapping,
+        );
+        mapping
+    }
+
     /// Creates a projection using a mapping from field IDs to column indices
     ///
     /// You can obtain such a mapping when the file is written using the
@@ -377,16 +415,7 @@ impl ReaderProjection {
         schema: &Schema,
         column_names: &[&str],
     ) -> Result<Self> {
-        let field_id_to_column_index = schema
-            .fields_pre_order()
-            // In the 2.0 system we needed ids for intermediate fields.  In 2.1+
-            // we only need ids for leaf fields.
-            .filter(|field| {
-                file_version < LanceFileVersion::V2_1 || field.is_leaf() || field.is_packed_struct()
-            })
-            .enumerate()
-            .map(|(idx, field)| (field.id as u32, idx as u32))
-            .collect::<BTreeMap<_, _>>();
+        let field_id_to_column_index = Self::field_id_to_column_index(schema, file_version);
         let projected = schema.project(column_names)?;
         let mut column_indices = Vec::new();
         Self::from_field_ids_helper(
@@ -417,6 +446,11 @@ pub struct FileReaderOptions {
     /// to provide a default for all scans, or at the scanner level (via
     /// `Scanner::batch_size_bytes`) to override per scan.
     pub batch_size_bytes: Option<u64>,
+    /// Supplies the keys of encrypted columns
+    ///
+    /// A file with encrypted columns can be opened without the keys but reading
+    /// a column whose key is missing fails.
+    pub key_provider: Option<Arc<dyn KeyProvider>>,
 }
 
 impl Default for FileReaderOptions {
@@ -425,6 +459,7 @@ impl Default for FileReaderOptions {
             decoder_config: DecoderConfig::default(),
             read_chunk_size: DEFAULT_READ_CHUNK_SIZE,
             batch_size_bytes: None,
+            key_provider: None,
         }
     }
 }
@@ -439,6 +474,8 @@ pub struct FileReader {
     decoder_plugins: Arc<DecoderPlugins>,
     cache: Arc<LanceCache>,
     options: FileReaderOptions,
+    // The buffers `scheduler` decrypts, kept to wrap schedulers given to `with_scheduler`
+    encrypted_buffers: Arc<EncryptedBuffers>,
 }
 #[derive(Debug)]
 struct Footer {
@@ -460,14 +497,124 @@ const FOOTER_LEN: usize = 40;
 impl FileReader {
     pub fn with_scheduler(&self, scheduler: Arc<dyn EncodingsIo>) -> Self {
         Self {
-            scheduler,
+            scheduler: Self::decrypting(scheduler, &self.encrypted_buffers),
             base_projection: self.base_projection.clone(),
             cache: self.cache.clone(),
             decoder_plugins: self.decoder_plugins.clone(),
             metadata: self.metadata.clone(),
             options: self.options.clone(),
             num_rows: self.num_rows,
+            encrypted_buffers: self.encrypted_buffers.clone(),
+        }
+    }
+
+    fn decrypting(
+        scheduler: Arc<dyn EncodingsIo>,
+        encrypted_buffers: &Arc<EncryptedBuffers>,
+    ) -> Arc<dyn EncodingsIo> {
+        if encrypted_buffers.is_empty() {
+            scheduler
+        } else {
+            Arc::new(EncryptedIo::new(scheduler, encrypted_buffers.clone()))
+        }
+    }
+
+    // Finds the buffers of the encrypted columns and looks up their keys
+    //
+    // A missing key isn't an error here so that the other columns of the file can
+    // still be read.
+    async fn encrypted_buffers(
+        file_metadata: &CachedFileMetadata,
+        key_provider: Option<&Arc<dyn KeyProvider>>,
+    ) -> Result<EncryptedBuffers> {
+        let mut encrypted_buffers = EncryptedBuffers::new();
+        if file_metadata
+            .column_metadatas
+            .iter()
+            .all(|col_meta| col_meta.encryption.is_none())
+        {
+            return Ok(encrypted_buffers);
+        }
+
+        // Only top-level fields are encrypted so name each column after its top-level field
+        let field_id_to_column_index = ReaderProjection::field_id_to_column_index(
+            &file_metadata.file_schema,
+            file_metadata.version(),
+        );
+        let mut column_names = HashMap::new();
+        for field in &file_metadata.file_schema.fields {
+            let mut stack = vec![field];
+            while let Some(child) = stack.pop() {
+                if let Some(column_idx) = field_id_to_column_index.get(&(child.id as u32)) {
+                    column_names.insert(*column_idx as usize, field.name.as_str());
+                }
+                stack.extend(child.children.iter());
+            }
+        }
+
+        let mut keys = HashMap::new();
+        for (column_idx, col_meta) in file_metadata.column_metadatas.iter().enumerate() {
+            let Some(encryption) = &col_meta.encryption else {
+                continue;
+            };
+            if !keys.contains_key(&encryption.key_id) {
+                let key = match key_provider {
+                    Some(key_provider) => key_provider.get_key(&encryption.key_id).await?,
+                    None => None,
+                };
+                keys.insert(encryption.key_id.clone(), key);
+            }
+            let cipher = keys[&encryption.key_id]
+                .as_ref()
+                .map(|key| ColumnCipher::try_new(key, &encryption.salt))
+                .transpose()?;
+            let column = Arc::new(EncryptedColumn {
+                name: column_names
+                    .get(&column_idx)
+                    .map(|name| name.to_string())
+                    .unwrap_or_else(|| format!("#{}", column_idx)),
+                key_id: encryption.key_id.clone(),
+                cipher,
+            });
+            for (page_idx, page) in col_meta.pages.iter().enumerate() {
+                for (buffer_idx, (offset, size)) in page
+                    .buffer_offsets
+                    .iter()
+                    .zip(page.buffer_sizes.iter())
+                    .enumerate()
+                {
+                    encrypted_buffers.insert(
+                        *offset,
+                        EncryptedBuffer {
+                            size: *size,
+                            location: BufferLocation::Page {
+                                page: page_idx as u32,
+                                buffer: buffer_idx as u32,
+                            },
+                            column: column.clone(),
+                        },
+                    );
+                }
+            }
+            for (buffer_idx, (offset, size)) in col_meta
+                .buffer_offsets
+                .iter()
+                .zip(col_meta.buffer_sizes.iter())
+                .enumerate()
+            {
+                encrypted_buffers.insert(
+                    *offset,
+                    EncryptedBuffer {
+                        size: *size,
+                        location: BufferLocation::Column {
+                            buffer: buffer_idx as u32,
+                        },
+                        column: column.clone(),
+                    },
+                );
+            }
         }
+        Ok(encrypted_buffers)
     }
 
     pub fn num_rows(&self) -> u64 {
@@ -520,6 +667,39 @@ impl FileReader {
             .await
     }
 
+    /// Read the statistics collected when the file was written with
+    /// [`FileWriterOptions::collect_stats`](crate::writer::FileWriterOptions::collect_stats)
+    ///
+    /// The batch has a row per batch written to the file.  The
+    /// [`PAGE_STATS_NUM_ROWS_COLUMN`](crate::writer::PAGE_STATS_NUM_ROWS_COLUMN) column has the number of rows of each batch
+    /// and there is a struct column, named after the field id, with the
+    /// `null_count`, `min_value` and `max_value` of each field that has statistics.
+    ///
+    /// Returns None if the file has no statistics.
+    pub async fn read_page_stats(&self) -> Result<Option<RecordBatch>> {
+        let Some(index) = self.schema().metadata.get(PAGE_STATS_META_KEY) else {
+            return Ok(None);
+        };
+        let index = index.parse::<u32>().map_err(|err| {
+            Error::internal(format!(
+                "invalid page statistics buffer index {}: {}",
+                index, err
+            ))
+        })?;
+        let bytes = self.read_global_buffer(index).await?;
+        let encoded = EncodedBatch::try_from_self_described_lance(bytes)?;
+        let stats = decode_batch(
+            &encoded,
+            &FilterExpression::no_filter(),
+            Arc::<DecoderPlugins>::default(),
+            false,
+            self.metadata.version(),
+            None,
+        )
+        .await?;
+        Ok(Some(stats))
+    }
+
     async fn read_tail(scheduler: &FileScheduler) -> Result<(Bytes, u64)> {
         let file_size = scheduler.reader().size().await? as u64;
         let begin = if file_size < scheduler.reader().block_size() as u64 {
@@ -935,8 +1115,10 @@ impl FileReader {
             Self::validate_projection(base_projection, &file_metadata)?;
         }
         let num_rows = file_metadata.num_rows;
+        let encrypted_buffers =
+            Arc::new(Self::encrypted_buffers(&file_metadata, options.key_provider.as_ref()).await?);
         Ok(Self {
-            scheduler,
+            scheduler: Self::decrypting(scheduler, &encrypted_buffers),
             base_projection: base_projection.unwrap_or(ReaderProjection::from_whole_schema(
                 file_metadata.file_schema.as_ref(),
                 file_metadata.version(),
@@ -946,6 +1128,7 @@ impl FileReader {
             decoder_plugins,
             cache,
             options,
+            encrypted_buffers,
         })
     }
 
@@ -1709,18 +1892,29 @@ mod tests {
     use std::{collections::BTreeMap, pin::Pin, sync::Arc};
 
     use arrow_array::{
-        RecordBatch, UInt32Array,
-        types::{Float64Type, Int32Type},
+        BinaryArray, Int32Array, Int64Array, RecordBatch, RecordBatchIterator, StringArray,
+        UInt32Array, UInt64Array,
+        cast::AsArray,
+        types::{Float64Type, Int32Type, Int64Type, UInt64Type},
     };
     use arrow_schema::{DataType, Field, Fields, Schema as ArrowSchema};
+    use arrow_select::concat::concat_batches;
     use bytes::Bytes;
     use futures::{StreamExt, prelude::stream::TryStreamExt};
     use lance_arrow::RecordBatchExt;
-    use lance_core::{ArrowResult, datatypes::Schema};
+    use lance_core::{
+        ArrowResult,
+        datatypes::Schema,
+        utils::encryption::{
+            ENCRYPTION_KEY_ID_META_KEY, EncryptionKey, KeyProvider, StaticKeyProvider,
+        },
+    };
     use lance_datagen::{BatchCount, ByteCount, RowCount, array, gen_batch};
     use lance_encoding::{
+        constants::{CUSTOM_ENCODING_META_KEY, CUSTOM_ENCODING_PARAMS_META_KEY},
         decoder::{DecodeBatchScheduler, DecoderPlugins, FilterExpression, decode_batch},
         encoder::{EncodedBatch, EncodingOptions, default_encoding_strategy, encode_batch},
+        encodings::physical::custom::{CustomEncoding, CustomEncodingRegistry},
         version::LanceFileVersion,
     };
     use lance_io::{stream::RecordBatchStream, utils::CachedFileSize};
@@ -1729,8 +1923,10 @@ mod tests {
     use tokio::sync::mpsc;
 
     use crate::reader::{EncodedBatchReaderExt, FileReader, FileReaderOptions, ReaderProjection};
-    use crate::testing::{FsFixture, WrittenFile, test_cache, write_lance_file};
-    use crate::writer::{EncodedBatchWriteExt, FileWriter, FileWriterOptions};
+    use crate::testing::{FsFixture, WrittenFile, read_lance_file, test_cache, write_lance_file};
+    use crate::writer::{
+        EncodedBatchWriteExt, FileWriter, FileWriterOptions, PAGE_STATS_NUM_ROWS_COLUMN,
+    };
     use lance_encoding::decoder::DecoderConfig;
 
     async fn create_some_file(fs: &FsFixture, version: LanceFileVersion) -> WrittenFile {
@@ -2591,4 +2787,456 @@ mod tests {
         let msg = err.to_string();
         assert!(msg.contains('2'), "error should mention the index: {msg}");
     }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_read_page_stats(
+        #[values(LanceFileVersion::V2_0, LanceFileVersion::V2_1, LanceFileVersion::V2_2)]
+        version: LanceFileVersion,
+    ) {
+        let fs = FsFixture::default();
+        let schema = Arc::new(ArrowSchema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("name", DataType::Utf8, true),
+            Field::new("data", DataType::Binary, false),
+        ]));
+        let batches = vec![
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(Int32Array::from(vec![0, 1, 2])),
+                    Arc::new(StringArray::from(vec![
+                        Some("user_00000001"),
+                        None,
+                        Some("user_00000002"),
+                    ])),
+                    Arc::new(BinaryArray::from(vec![
+                        &[0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x10][..],
+                        &[0x80, 0x01][..],
+                        &[0x7F][..],
+                    ])),
+                ],
+            )
+            .unwrap(),
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(Int32Array::from(vec![3, 4])),
+                    Arc::new(StringArray::from(vec!["abc", "abd"])),
+                    Arc::new(BinaryArray::from(vec![&[0x01; 12][..], &[0xFF; 12][..]])),
+                ],
+            )
+            .unwrap(),
+        ];
+
+        let mut file_writer = FileWriter::try_new(
+            fs.object_store.create(&fs.tmp_path).await.unwrap(),
+            Schema::try_from(schema.as_ref()).unwrap(),
+            FileWriterOptions {
+                format_version: Some(version),
+                collect_stats: true,
+                stats_prefix_length: Some(8),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        file_writer.write_batches(batches.iter()).await.unwrap();
+        file_writer.finish().await.unwrap();
+
+        let file_scheduler = fs
+            .scheduler
+            .open_file(&fs.tmp_path, &CachedFileSize::unknown())
+            .await
+            .unwrap();
+        let file_reader = FileReader::try_open(
+            file_scheduler,
+            None,
+            Arc::<DecoderPlugins>::default(),
+            &test_cache(),
+            FileReaderOptions::default(),
+        )
+        .await
+        .unwrap();
+        let stats = file_reader.read_page_stats().await.unwrap().unwrap();
+        assert_eq!(stats.num_rows(), 2);
+        assert_eq!(
+            stats[PAGE_STATS_NUM_ROWS_COLUMN].as_primitive::<UInt64Type>(),
+            &UInt64Array::from(vec![3, 2])
+        );
+
+        let id_stats = stats["0"].as_struct();
+        assert_eq!(
+            id_stats["min_value"].as_primitive::<Int32Type>(),
+            &Int32Array::from(vec![0, 3])
+        );
+        assert_eq!(
+            id_stats["max_value"].as_primitive::<Int32Type>(),
+            &Int32Array::from(vec![2, 4])
+        );
+
+        // Values sharing a prefix longer than the prefix length are truncated and
+        // the max value is incremented so it remains an upper bound
+        let name_stats = stats["1"].as_struct();
+        assert_eq!(
+            name_stats["null_count"].as_primitive::<Int64Type>(),
+            &Int64Array::from(vec![1, 0])
+        );
+        assert_eq!(
+            name_stats["min_value"].as_string::<i32>(),
+            &StringArray::from(vec!["user_000", "abc"])
+        );
+        assert_eq!(
+            name_stats["max_value"].as_string::<i32>(),
+            &StringArray::from(vec!["user_001", "abd"])
+        );
+
+        // Binary bounds don't have to be valid UTF-8, and a truncated max value that
+        // can't be incremented has no upper bound
+        let data_stats = stats["2"].as_struct();
+        assert_eq!(
+            data_stats["min_value"].as_binary::<i32>(),
+            &BinaryArray::from(vec![
+                &[0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF][..],
+                &[0x01; 8][..],
+            ])
+        );
+        assert_eq!(
+            data_stats["max_value"].as_binary::<i32>(),
+            &BinaryArray::from(vec![Some(&[0x80, 0x01][..]), None])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_page_stats_not_collected() {
+        let fs = FsFixture::default();
+        create_some_file(&fs, LanceFileVersion::V2_1).await;
+
+        let file_scheduler = fs
+            .scheduler
+            .open_file(&fs.tmp_path, &CachedFileSize::unknown())
+            .await
+            .unwrap();
+        let file_reader = FileReader::try_open(
+            file_scheduler,
+            None,
+            Arc::<DecoderPlugins>::default(),
+            &test_cache(),
+            FileReaderOptions::default(),
+        )
+        .await
+        .unwrap();
+        assert!(file_reader.read_page_stats().await.unwrap().is_none());
+    }
+
+    #[derive(Debug)]
+    struct XorEncoding;
+
+    impl CustomEncoding for XorEncoding {
+        fn encode(&self, data: &[u8], parameters: &[u8]) -> lance_core::Result<Vec<u8>> {
+            let key = parameters.first().copied().unwrap_or(0xFF);
+            Ok(data.iter().map(|b| b ^ key).collect())
+        }
+
+        fn decode(&self, data: &[u8], parameters: &[u8]) -> lance_core::Result<Vec<u8>> {
+            self.encode(data, parameters)
+        }
+    }
+
+    fn custom_encoded_batch() -> RecordBatch {
+        let field = Field::new("sequence", DataType::Utf8, true).with_metadata(
+            [
+                (CUSTOM_ENCODING_META_KEY.to_string(), "xor".to_string()),
+                (CUSTOM_ENCODING_PARAMS_META_KEY.to_string(), "k".to_string()),
+            ]
+            .into(),
+        );
+        let sequences = StringArray::from_iter_values((0..10_000).map(|i| format!("ACGT{i}TTGCA")));
+        RecordBatch::try_new(
+            Arc::new(ArrowSchema::new(vec![field])),
+            vec![Arc::new(sequences)],
+        )
+        .unwrap()
+    }
+
+    async fn write_custom_encoded_file(
+        fs: &FsFixture,
+        registry: CustomEncodingRegistry,
+    ) -> RecordBatch {
+        let batch = custom_encoded_batch();
+        write_lance_file(
+            RecordBatchIterator::new([Ok(batch.clone())], batch.schema()),
+            fs,
+            FileWriterOptions {
+                format_version: Some(LanceFileVersion::V2_3),
+                custom_encodings: Some(registry),
+                ..Default::default()
+            },
+        )
+        .await;
+        batch
+    }
+
+    #[tokio::test]
+    async fn test_custom_encoding_round_trip() {
+        let fs = FsFixture::default();
+        let registry = CustomEncodingRegistry::new().with_encoding("xor", Arc::new(XorEncoding));
+        let expected = write_custom_encoded_file(&fs, registry.clone()).await;
+
+        let plugins = DecoderPlugins::default().with_custom_encodings(registry);
+        let batches = read_lance_file(&fs, Arc::new(plugins), FilterExpression::no_filter()).await;
+        let actual = concat_batches(&expected.schema(), &batches).unwrap();
+        assert_eq!(actual.column(0), expected.column(0));
+    }
+
+    #[tokio::test]
+    async fn test_custom_encoding_unregistered_on_read() {
+        let fs = FsFixture::default();
+        let registry = CustomEncodingRegistry::new().with_encoding("xor", Arc::new(XorEncoding));
+        write_custom_encoded_file(&fs, registry).await;
+
+        let file_scheduler = fs
+            .scheduler
+            .open_file(&fs.tmp_path, &CachedFileSize::unknown())
+            .await
+            .unwrap();
+        let file_reader = FileReader::try_open(
+            file_scheduler,
+            None,
+            Arc::<DecoderPlugins>::default(),
+            &test_cache(),
+            FileReaderOptions::default(),
+        )
+        .await
+        .unwrap();
+        let err = match file_reader
+            .read_stream(
+                lance_io::ReadBatchParams::RangeFull,
+                1024,
+                16,
+                FilterExpression::no_filter(),
+            )
+            .await
+        {
+            Ok(stream) => stream
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap_err()
+                .to_string(),
+            Err(err) => err.to_string(),
+        };
+        assert!(err.contains("custom encoding 'xor'"), "{err}");
+        assert!(
+            err.contains("DecoderPlugins::with_custom_encodings"),
+            "{err}"
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_custom_encoding_requires_v2_3(
+        #[values(LanceFileVersion::V2_0, LanceFileVersion::V2_1, LanceFileVersion::V2_2)]
+        version: LanceFileVersion,
+    ) {
+        let fs = FsFixture::default();
+        let registry = CustomEncodingRegistry::new().with_encoding("xor", Arc::new(XorEncoding));
+        let batch = custom_encoded_batch();
+        let err = FileWriter::try_new(
+            fs.object_store.create(&fs.tmp_path).await.unwrap(),
+            Schema::try_from(batch.schema().as_ref()).unwrap(),
+            FileWriterOptions {
+                format_version: Some(version),
+                custom_encodings: Some(registry),
+                ..Default::default()
+            },
+        )
+        .err()
+        .unwrap();
+        assert!(matches!(err, lance_core::Error::NotSupported { .. }));
+        assert!(err.to_string().contains("version 2.3"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_custom_encoding_full_zip_unsupported() {
+        let fs = FsFixture::default();
+        let registry = CustomEncodingRegistry::new().with_encoding("xor", Arc::new(XorEncoding));
+        let field = Field::new("sequence", DataType::Utf8, false)
+            .with_metadata([(CUSTOM_ENCODING_META_KEY.to_string(), "xor".to_string())].into());
+        // Values of 256 bytes or more are written with the full-zip layout
+        let sequences = StringArray::from_iter_values((0..4).map(|i| "ACGT".repeat(100 + i)));
+        let batch = RecordBatch::try_new(
+            Arc::new(ArrowSchema::new(vec![field])),
+            vec![Arc::new(sequences)],
+        )
+        .unwrap();
+
+        let mut file_writer = FileWriter::try_new(
+            fs.object_store.create(&fs.tmp_path).await.unwrap(),
+            Schema::try_from(batch.schema().as_ref()).unwrap(),
+            FileWriterOptions {
+                format_version: Some(LanceFileVersion::V2_3),
+                custom_encodings: Some(registry),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let err = match file_writer.write_batch(&batch).await {
+            Ok(()) => file_writer.finish().await.unwrap_err(),
+            Err(err) => err,
+        };
+        assert!(matches!(err, lance_core::Error::NotSupported { .. }));
+        assert!(err.to_string().contains("full-zip"), "{err}");
+    }
+
+    fn pii_key_provider() -> Arc<dyn KeyProvider> {
+        Arc::new(StaticKeyProvider::new().with_key("pii", EncryptionKey::new([7; 32])))
+    }
+
+    // Writes an `id` column and an `ssn` column encrypted with the key `pii`
+    async fn write_encrypted_file(fs: &FsFixture) -> RecordBatch {
+        let schema = Arc::new(ArrowSchema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("ssn", DataType::Utf8, true).with_metadata(
+                [(ENCRYPTION_KEY_ID_META_KEY.to_string(), "pii".to_string())].into(),
+            ),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from_iter_values(0..1000)),
+                Arc::new(StringArray::from_iter_values(
+                    (0..1000).map(|i| format!("ssn-{:06}", i)),
+                )),
+            ],
+        )
+        .unwrap();
+        write_lance_file(
+            RecordBatchIterator::new(vec![Ok(batch.clone())], schema),
+            fs,
+            FileWriterOptions {
+                format_version: Some(LanceFileVersion::V2_1),
+                key_provider: Some(pii_key_provider()),
+                ..Default::default()
+            },
+        )
+        .await;
+        batch
+    }
+
+    async fn open_encrypted_file(
+        fs: &FsFixture,
+        key_provider: Option<Arc<dyn KeyProvider>>,
+    ) -> FileReader {
+        let file_scheduler = fs
+            .scheduler
+            .open_file(&fs.tmp_path, &CachedFileSize::unknown())
+            .await
+            .unwrap();
+        FileReader::try_open(
+            file_scheduler,
+            None,
+            Arc::<DecoderPlugins>::default(),
+            &test_cache(),
+            FileReaderOptions {
+                key_provider,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_column_round_trip() {
+        let fs = FsFixture::default();
+        let expected = write_encrypted_file(&fs).await;
+
+        let bytes = fs.object_store.read_one_all(&fs.tmp_path).await.unwrap();
+        assert!(
+            !bytes.windows(10).any(|window| window == b"ssn-000123"),
+            "the file contains a plain value of the encrypted column"
+        );
+
+        let file_reader = open_encrypted_file(&fs, Some(pii_key_provider())).await;
+        let batches = file_reader
+            .read_stream(
+                lance_io::ReadBatchParams::RangeFull,
+                100,
+                16,
+                FilterExpression::no_filter(),
+            )
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(
+            concat_batches(&expected.schema(), &batches).unwrap(),
+            expected
+        );
+
+        let taken = file_reader
+            .read_stream(
+                lance_io::ReadBatchParams::Indices(UInt32Array::from(vec![3, 500, 999])),
+                1024,
+                16,
+                FilterExpression::no_filter(),
+            )
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        let taken = concat_batches(&expected.schema(), &taken).unwrap();
+        assert_eq!(
+            taken.column(1).as_ref(),
+            &StringArray::from(vec!["ssn-000003", "ssn-000500", "ssn-000999"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_column_without_key() {
+        let fs = FsFixture::default();
+        let expected = write_encrypted_file(&fs).await;
+
+        let file_reader = open_encrypted_file(&fs, None).await;
+        let schema = file_reader.schema().clone();
+
+        // The columns that aren't encrypted can still be read
+        let ids = file_reader
+            .read_stream_projected(
+                lance_io::ReadBatchParams::RangeFull,
+                1024,
+                16,
+                ReaderProjection::from_column_names(LanceFileVersion::V2_1, &schema, &["id"])
+                    .unwrap(),
+                FilterExpression::no_filter(),
+            )
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(ids[0].column(0), expected.column(0));
+
+        let err = match file_reader
+            .read_stream_projected(
+                lance_io::ReadBatchParams::RangeFull,
+                1024,
+                16,
+                ReaderProjection::from_column_names(LanceFileVersion::V2_1, &schema, &["ssn"])
+                    .unwrap(),
+                FilterExpression::no_filter(),
+            )
+            .await
+        {
+            Ok(stream) => stream
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap_err()
+                .to_string(),
+            Err(err) => err.to_string(),
+        };
+        assert!(err.contains("column 'ssn'"), "{err}");
+        assert!(err.contains("key 'pii'"), "{err}");
+    }
 }
@@ -0,0 +1,604 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Reading a single Lance file outside of a dataset
+//!
+//! [`LanceFileReader`] opens a file written by [`crate::writer::FileWriter`] from
+//! any object store path and reads it with the same [`FileReader`], decoders and
+//! caches that datasets use.  Columns are selected by name, with the syntax of
+//! [`Schema::project`], instead of by column index.
+
+use std::ops::Range;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use arrow_array::{RecordBatch, UInt32Array};
+use arrow_schema::{Schema as ArrowSchema, SchemaRef};
+use arrow_select::concat::concat_batches;
+use futures::{StreamExt, TryStreamExt};
+use lance_arrow::RecordBatchExt;
+use lance_core::cache::LanceCache;
+use lance_core::datatypes::{Field, Schema};
+use lance_core::{Error, Result};
+use lance_encoding::decoder::{DecoderPlugins, FilterExpression};
+use lance_encoding::version::LanceFileVersion;
+use lance_io::ReadBatchParams;
+use lance_io::object_store::ObjectStore;
+use lance_io::scheduler::{ScanScheduler, SchedulerConfig};
+use lance_io::stream::{RecordBatchStream, RecordBatchStreamAdapter};
+use lance_io::utils::CachedFileSize;
+use object_store::path::Path;
+
+use crate::reader::{
+    FileReader, FileReaderOptions, FileStatistics, ReaderProjection, describe_encoding,
+};
+
+pub const DEFAULT_BATCH_SIZE: u32 = 8192;
+pub const DEFAULT_BATCH_READAHEAD: u32 = 16;
+
+/// Options for opening a [`LanceFileReader`]
+#[derive(Clone, Debug)]
+pub struct LanceFileReaderOptions {
+    /// The maximum number of rows in a batch of [`LanceFileReader::read_stream`]
+    pub batch_size: u32,
+    /// The number of batches decoded in parallel
+    pub batch_readahead: u32,
+    pub reader_options: FileReaderOptions,
+    /// The I/O scheduler to read through, e.g. the one of a dataset, so that the
+    /// reader shares its I/O budget.  If unset, the reader gets its own.
+    pub scan_scheduler: Option<Arc<ScanScheduler>>,
+    /// The cache for decoder metadata, e.g. dictionaries and repetition indices.
+    /// If unset, nothing is cached.
+    pub cache: Option<Arc<LanceCache>>,
+    pub decoder_plugins: Arc<DecoderPlugins>,
+}
+
+impl Default for LanceFileReaderOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: DEFAULT_BATCH_SIZE,
+            batch_readahead: DEFAULT_BATCH_READAHEAD,
+            reader_options: FileReaderOptions::default(),
+            scan_scheduler: None,
+            cache: None,
+            decoder_plugins: Arc::default(),
+        }
+    }
+}
+
+/// Describes one page of a column
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageMetadata {
+    /// The number of values in the page
+    pub num_rows: u64,
+    /// The top-level row the page starts at
+    pub priority: u64,
+    /// The number of bytes of the page's buffers on disk
+    pub size_bytes: u64,
+    /// A description of the page's encoding, see [`describe_encoding`]
+    pub encoding: String,
+}
+
+/// A reader of a standalone Lance file
+#[derive(Debug, Clone)]
+pub struct LanceFileReader {
+    reader: FileReader,
+    batch_size: u32,
+    batch_readahead: u32,
+}
+
+impl LanceFileReader {
+    /// Open the file at `path` with the default options
+    pub async fn open(object_store: Arc<ObjectStore>, path: &Path) -> Result<Self> {
+        Self::open_with_options(object_store, path, LanceFileReaderOptions::default()).await
+    }
+
+    pub async fn open_with_options(
+        object_store: Arc<ObjectStore>,
+        path: &Path,
+        options: LanceFileReaderOptions,
+    ) -> Result<Self> {
+        if options.batch_size == 0 {
+            return Err(Error::invalid_input("batch_size must be greater than 0"));
+        }
+        let scheduler = options.scan_scheduler.unwrap_or_else(|| {
+            let config = SchedulerConfig::max_bandwidth(&object_store);
+            ScanScheduler::new(object_store, config)
+        });
+        let file = scheduler
+            .open_file(path, &CachedFileSize::unknown())
+            .await?;
+        let cache = options
+            .cache
+            .unwrap_or_else(|| Arc::new(LanceCache::no_cache()));
+        let reader = FileReader::try_open(
+            file,
+            None,
+            options.decoder_plugins,
+            &cache,
+            options.reader_options,
+        )
+        .await?;
+        Ok(Self {
+            reader,
+            batch_size: options.batch_size,
+            batch_readahead: options.batch_readahead.max(1),
+        })
+    }
+
+    /// The underlying reader, for reads that need column level projections or filters
+    pub fn file_reader(&self) -> &FileReader {
+        &self.reader
+    }
+
+    pub fn schema(&self) -> &Arc<Schema> {
+        self.reader.schema()
+    }
+
+    pub fn arrow_schema(&self) -> SchemaRef {
+        Arc::new(ArrowSchema::from(self.schema().as_ref()))
+    }
+
+    pub fn num_rows(&self) -> u64 {
+        self.reader.num_rows()
+    }
+
+    pub fn version(&self) -> LanceFileVersion {
+        self.reader.metadata().version()
+    }
+
+    /// The number of columns the file stores
+    ///
+    /// This is not the number of fields: a struct may be stored as one column per
+    /// child, and a packed struct as a single column.
+    pub fn num_columns(&self) -> usize {
+        self.reader.metadata().column_metadatas.len()
+    }
+
+    pub fn statistics(&self) -> FileStatistics {
+        self.reader.file_statistics()
+    }
+
+    /// The pages of the column at `column_index`
+    pub fn column_pages(&self, column_index: usize) -> Result<Vec<PageMetadata>> {
+        let column = self
+            .reader
+            .metadata()
+            .column_metadatas
+            .get(column_index)
+            .ok_or_else(|| {
+                Error::invalid_input(format!(
+                    "column {} requested from a file with {} columns",
+                    column_index,
+                    self.num_columns()
+                ))
+            })?;
+        Ok(column
+            .pages
+            .iter()
+            .map(|page| PageMetadata {
+                num_rows: page.length,
+                priority: page.priority,
+                size_bytes: page.buffer_sizes.iter().sum(),
+                encoding: describe_encoding(page),
+            })
+            .collect())
+    }
+
+    /// The projection to read `columns` with, and the schema to narrow the decoded
+    /// batches to, if it differs from the projection's
+    fn projection(
+        &self,
+        columns: Option<&[&str]>,
+    ) -> Result<(ReaderProjection, Option<SchemaRef>)> {
+        let file_schema = self.schema();
+        let version = self.version();
+        let Some(columns) = columns else {
+            return Ok((
+                ReaderProjection::from_whole_schema(file_schema, version),
+                None,
+            ));
+        };
+        let projected = file_schema.project(columns)?;
+
+        // A packed struct is stored in a single column, so it is read whole and the
+        // fields that weren't asked for are dropped once decoded
+        fn widen_packed(field: &Field, file_schema: &Schema, widened: &mut bool) -> Field {
+            if field.is_packed_struct() {
+                let stored = file_schema
+                    .field_by_id(field.id)
+                    .expect("projected from the file schema");
+                *widened |= stored != field;
+                return stored.clone();
+            }
+            let mut field = field.clone();
+            field.children = field
+                .children
+                .iter()
+                .map(|child| widen_packed(child, file_schema, widened))
+                .collect();
+            field
+        }
+        let mut widened = false;
+        let read_schema = Schema {
+            fields: projected
+                .fields
+                .iter()
+                .map(|field| widen_packed(field, file_schema, &mut widened))
+                .collect(),
+            metadata: projected.metadata.clone(),
+        };
+
+        let projection = ReaderProjection::from_field_ids(
+            version,
+            &read_schema,
+            &ReaderProjection::field_id_to_column_index(file_schema, version),
+        )?;
+        let output_schema = widened.then(|| Arc::new(ArrowSchema::from(&projected)));
+        Ok((projection, output_schema))
+    }
+
+    /// Read `params` as a stream of batches of at most the configured batch size
+    ///
+    /// `columns` selects the fields to read, all of them if `None`.
+    pub async fn read_stream(
+        &self,
+        params: ReadBatchParams,
+        columns: Option<&[&str]>,
+    ) -> Result<Pin<Box<dyn RecordBatchStream>>> {
+        let (projection, output_schema) = self.projection(columns)?;
+        let stream = self
+            .reader
+            .read_stream_projected(
+                params,
+                self.batch_size,
+                self.batch_readahead,
+                projection,
+                FilterExpression::no_filter(),
+            )
+            .await?;
+        let Some(output_schema) = output_schema else {
+            return Ok(stream);
+        };
+        let narrowed = stream.map({
+            let output_schema = output_schema.clone();
+            move |batch| Ok::<_, Error>(batch?.project_by_schema(&output_schema)?)
+        });
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            output_schema,
+            narrowed,
+        )))
+    }
+
+    fn empty_batch(&self, columns: Option<&[&str]>) -> Result<RecordBatch> {
+        let (projection, output_schema) = self.projection(columns)?;
+        let schema = output_schema
+            .unwrap_or_else(|| Arc::new(ArrowSchema::from(projection.schema.as_ref())));
+        Ok(RecordBatch::new_empty(schema))
+    }
+
+    async fn read_batch(
+        &self,
+        params: ReadBatchParams,
+        columns: Option<&[&str]>,
+    ) -> Result<RecordBatch> {
+        let stream = self.read_stream(params, columns).await?;
+        let schema = stream.schema();
+        let batches = stream.try_collect::<Vec<_>>().await?;
+        Ok(concat_batches(&schema, &batches)?)
+    }
+
+    /// Read the rows in `rows` as a single batch
+    pub async fn read_range(
+        &self,
+        rows: Range<u64>,
+        columns: Option<&[&str]>,
+    ) -> Result<RecordBatch> {
+        if rows.is_empty() {
+            return self.empty_batch(columns);
+        }
+        self.read_batch(ReadBatchParams::Ranges(Arc::new([rows])), columns)
+            .await
+    }
+
+    /// Read the rows at `indices`, in the order given, as a single batch
+    ///
+    /// Indices may repeat.
+    pub async fn take(&self, indices: &[u64], columns: Option<&[&str]>) -> Result<RecordBatch> {
+        if indices.is_empty() {
+            return self.empty_batch(columns);
+        }
+        let mut sorted = indices.to_vec();
+        sorted.sort_unstable();
+        sorted.dedup();
+        let sorted_u32 = sorted
+            .iter()
+            .map(|idx| {
+                u32::try_from(*idx).map_err(|_| {
+                    Error::invalid_input(format!(
+                        "cannot take row {} from file with {} rows",
+                        idx,
+                        self.num_rows()
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let batch = self
+            .read_batch(
+                ReadBatchParams::Indices(UInt32Array::from(sorted_u32)),
+                columns,
+            )
+            .await?;
+        if sorted.as_slice() == indices {
+            return Ok(batch);
+        }
+        let positions = UInt32Array::from_iter_values(
+            indices
+                .iter()
+                .map(|idx| sorted.binary_search(idx).unwrap() as u32),
+        );
+        Ok(arrow_select::take::take_record_batch(&batch, &positions)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Float32Type, Float64Type, Int32Type, Int64Type};
+    use arrow_array::{
+        ArrayRef, Float32Array, Float64Array, Int32Array, Int64Array, ListArray,
+        RecordBatchIterator, StringArray, StructArray,
+    };
+    use arrow_buffer::OffsetBuffer;
+    use arrow_schema::{DataType, Field as ArrowField, Fields};
+    use rstest::rstest;
+
+    use super::*;
+    use crate::testing::{FsFixture, write_lance_file};
+    use crate::writer::FileWriterOptions;
+
+    const NUM_ROWS: usize = 1000;
+
+    fn nested_batches() -> (SchemaRef, Vec<RecordBatch>) {
+        let location_fields = Fields::from(vec![
+            ArrowField::new("x", DataType::Float64, true),
+            ArrowField::new("y", DataType::Float64, true),
+        ]);
+        let packed_fields = Fields::from(vec![
+            ArrowField::new("a", DataType::Int32, false),
+            ArrowField::new("b", DataType::Float32, false),
+            ArrowField::new("c", DataType::Int64, false),
+        ]);
+        let tag_field = Arc::new(ArrowField::new("item", DataType::Utf8, true));
+        let schema = Arc::new(ArrowSchema::new(vec![
+            ArrowField::new("id", DataType::Int32, false),
+            ArrowField::new("location", DataType::Struct(location_fields.clone()), true),
+            ArrowField::new("tags", DataType::List(tag_field.clone()), true),
+            ArrowField::new("packed", DataType::Struct(packed_fields.clone()), false)
+                .with_metadata(HashMap::from([("packed".to_string(), "true".to_string())])),
+        ]));
+
+        let batches = (0..NUM_ROWS)
+            .step_by(250)
+            .map(|start| {
+                let ids = (start as i32)..(start as i32 + 250);
+                let location = StructArray::new(
+                    location_fields.clone(),
+                    vec![
+                        Arc::new(Float64Array::from_iter_values(ids.clone().map(f64::from)))
+                            as ArrayRef,
+                        Arc::new(Float64Array::from_iter_values(
+                            ids.clone().map(|id| -f64::from(id)),
+                        )),
+                    ],
+                    None,
+                );
+                let tag_counts = ids.clone().map(|id| (id % 3) as usize);
+                let tags = StringArray::from_iter_values(
+                    ids.clone()
+                        .flat_map(|id| (0..id % 3).map(move |n| format!("tag-{}", (id + n) % 7))),
+                );
+                let tags = ListArray::new(
+                    tag_field.clone(),
+                    OffsetBuffer::from_lengths(tag_counts),
+                    Arc::new(tags),
+                    None,
+                );
+                let packed = StructArray::new(
+                    packed_fields.clone(),
+                    vec![
+                        Arc::new(Int32Array::from_iter_values(ids.clone().map(|id| id * 2)))
+                            as ArrayRef,
+                        Arc::new(Float32Array::from_iter_values(
+                            ids.clone().map(|id| id as f32 / 2.0),
+                        )),
+                        Arc::new(Int64Array::from_iter_values(ids.clone().map(i64::from))),
+                    ],
+                    None,
+                );
+                RecordBatch::try_new(
+                    schema.clone(),
+                    vec![
+                        Arc::new(Int32Array::from_iter_values(ids)),
+                        Arc::new(location),
+                        Arc::new(tags),
+                        Arc::new(packed),
+                    ],
+                )
+                .unwrap()
+            })
+            .collect();
+        (schema, batches)
+    }
+
+    async fn write_nested_file(fs: &FsFixture, version: LanceFileVersion) -> RecordBatch {
+        let (schema, batches) = nested_batches();
+        write_lance_file(
+            RecordBatchIterator::new(batches.clone().into_iter().map(Ok), schema.clone()),
+            fs,
+            FileWriterOptions {
+                format_version: Some(version),
+                ..Default::default()
+            },
+        )
+        .await;
+        concat_batches(&schema, &batches).unwrap()
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_read_nested_file(
+        #[values(LanceFileVersion::V2_1, LanceFileVersion::V2_2)] version: LanceFileVersion,
+    ) {
+        let fs = FsFixture::default();
+        let expected = write_nested_file(&fs, version).await;
+        let reader = LanceFileReader::open_with_options(
+            fs.object_store.clone(),
+            &fs.tmp_path,
+            LanceFileReaderOptions {
+                batch_size: 300,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(reader.num_rows(), NUM_ROWS as u64);
+        assert_eq!(reader.version(), version);
+        assert_eq!(
+            reader
+                .arrow_schema()
+                .fields()
+                .iter()
+                .map(|field| field.name().as_str())
+                .collect::<Vec<_>>(),
+            ["id", "location", "tags", "packed"]
+        );
+        // id, location.x, location.y, tags and packed
+        assert_eq!(reader.num_columns(), 5);
+        for column in 0..reader.num_columns() {
+            let pages = reader.column_pages(column).unwrap();
+            assert!(!pages.is_empty());
+            assert!(pages.iter().all(|page| !page.encoding.is_empty()));
+        }
+        let id_pages = reader.column_pages(0).unwrap();
+        assert_eq!(
+            id_pages.iter().map(|page| page.num_rows).sum::<u64>(),
+            NUM_ROWS as u64
+        );
+        assert!(reader.column_pages(5).is_err());
+        assert_eq!(reader.statistics().columns.len(), 5);
+
+        let batches = reader
+            .read_stream(ReadBatchParams::RangeFull, None)
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert!(batches.iter().all(|batch| batch.num_rows() <= 300));
+        let read = concat_batches(&batches[0].schema(), &batches).unwrap();
+        assert_eq!(read.columns(), expected.columns());
+
+        let range = reader.read_range(240..260, None).await.unwrap();
+        assert_eq!(range.columns(), expected.slice(240, 20).columns());
+        assert_eq!(reader.read_range(10..10, None).await.unwrap().num_rows(), 0);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_take_at_file_boundaries(
+        #[values(LanceFileVersion::V2_1, LanceFileVersion::V2_2)] version: LanceFileVersion,
+    ) {
+        let fs = FsFixture::default();
+        let expected = write_nested_file(&fs, version).await;
+        let reader = LanceFileReader::open(fs.object_store.clone(), &fs.tmp_path)
+            .await
+            .unwrap();
+
+        let last = NUM_ROWS as u64 - 1;
+        for indices in [
+            vec![0],
+            vec![last],
+            vec![last, 0],
+            vec![249, 250, last, 0, 250],
+        ] {
+            let taken = reader.take(&indices, None).await.unwrap();
+            let positions = UInt32Array::from_iter_values(indices.iter().map(|idx| *idx as u32));
+            assert_eq!(
+                taken.columns(),
+                arrow_select::take::take_record_batch(&expected, &positions)
+                    .unwrap()
+                    .columns()
+            );
+        }
+        assert_eq!(reader.take(&[], None).await.unwrap().num_rows(), 0);
+
+        assert!(matches!(
+            reader.take(&[0, NUM_ROWS as u64], None).await,
+            Err(Error::InvalidInput { .. })
+        ));
+        assert!(matches!(
+            reader.read_range(990..1001, None).await,
+            Err(Error::InvalidInput { .. })
+        ));
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_projection_of_packed_struct(
+        #[values(LanceFileVersion::V2_1, LanceFileVersion::V2_2)] version: LanceFileVersion,
+    ) {
+        let fs = FsFixture::default();
+        let expected = write_nested_file(&fs, version).await;
+        let reader = LanceFileReader::open(fs.object_store.clone(), &fs.tmp_path)
+            .await
+            .unwrap();
+
+        let columns = ["tags", "packed.c", "packed.a"];
+        let range = reader.read_range(100..400, Some(&columns)).await.unwrap();
+        assert_eq!(range.schema().fields().len(), 2);
+        assert_eq!(range["tags"], expected["tags"].slice(100, 300));
+        let packed = range["packed"].as_struct();
+        assert_eq!(
+            packed
+                .fields()
+                .iter()
+                .map(|field| field.name().as_str())
+                .collect::<Vec<_>>(),
+            ["c", "a"]
+        );
+        assert_eq!(
+            packed.column(0).as_primitive::<Int64Type>().values()[..3],
+            [100, 101, 102]
+        );
+        assert_eq!(
+            packed.column(1).as_primitive::<Int32Type>().values()[..3],
+            [200, 202, 204]
+        );
+
+        let taken = reader.take(&[999, 3], Some(&["packed.b"])).await.unwrap();
+        let packed = taken["packed"].as_struct();
+        assert_eq!(packed.num_columns(), 1);
+        assert_eq!(
+            packed.column(0).as_primitive::<Float32Type>().values()[..],
+            [499.5, 1.5]
+        );
+
+        // Other structs are read column by column
+        let location = reader
+            .read_range(0..2, Some(&["location.y"]))
+            .await
+            .unwrap();
+        assert_eq!(
+            location["location"]
+                .as_struct()
+                .column(0)
+                .as_primitive::<Float64Type>()
+                .values()[..],
+            [0.0, -1.0]
+        );
+    }
+}
@@ -2,24 +2,31 @@
 // SPDX-FileCopyrightText: Copyright The Lance Authors
 
 use core::panic;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
 
-use arrow_array::RecordBatch;
+use arrow_array::cast::AsArray;
+use arrow_array::{ArrayRef, RecordBatch, UInt64Array};
 
 use arrow_data::ArrayData;
+use arrow_schema::{DataType, Field as ArrowField, Schema as ArrowSchema};
 use bytes::{Buf, BufMut, Bytes, BytesMut};
 use futures::StreamExt;
 use futures::stream::FuturesOrdered;
 use lance_core::datatypes::{Field, Schema as LanceSchema};
 use lance_core::utils::bit::pad_bytes;
+use lance_core::utils::encryption::{ENCRYPTION_KEY_ID_META_KEY, KeyProvider, require_key};
 use lance_core::{Error, Result};
+use lance_encoding::constants::CUSTOM_ENCODING_META_KEY;
 use lance_encoding::decoder::PageEncoding;
 use lance_encoding::encoder::{
     BatchEncoder, EncodeTask, EncodedBatch, EncodedPage, EncodingOptions, FieldEncoder,
     FieldEncodingStrategy, OutOfLineBuffers, default_encoding_strategy,
+    default_encoding_strategy_with_custom_encodings, encode_batch,
 };
+use lance_encoding::encodings::physical::custom::CustomEncodingRegistry;
+use lance_encoding::encryption::{BufferLocation, ColumnCipher};
 use lance_encoding::repdef::RepDefBuilder;
 use lance_encoding::version::LanceFileVersion;
 use lance_io::object_store::ObjectStore;
@@ -37,6 +44,7 @@ use crate::format::MAGIC;
 use crate::format::pb;
 use crate::format::pbfile;
 use crate::format::pbfile::DirectEncoding;
+use crate::previous::writer::statistics::{self, StatisticsCollector};
 
 /// Pages buffers are aligned to 64 bytes
 pub(crate) const PAGE_BUFFER_ALIGNMENT: usize = 64;
@@ -97,8 +105,37 @@ pub struct FileWriterOptions {
     /// versions may have more efficient encodings.  However, newer format versions will
     /// require more up-to-date readers to read the data.
     pub format_version: Option<LanceFileVersion>,
+    /// Supplies the keys of the columns marked for encryption
+    ///
+    /// A top-level field is encrypted when its metadata has the key id under
+    /// [`ENCRYPTION_KEY_ID_META_KEY`].  Writing fails if the key can't be found.
+    pub key_provider: Option<Arc<dyn KeyProvider>>,
+    /// Custom codecs that fields may request with [`CUSTOM_ENCODING_META_KEY`]
+    ///
+    /// Ignored if `encoding_strategy` is set.  Requesting a custom encoding requires
+    /// format version 2.3 or later.
+    pub custom_encodings: Option<CustomEncodingRegistry>,
+    /// If true, min, max and null count statistics are collected for each batch
+    /// written, see [`FileReader::read_page_stats`](crate::reader::FileReader::read_page_stats).
+    ///
+    /// Statistics are collected for the fields (and the children of struct fields)
+    /// whose data type supports them.  Defaults to false.
+    pub collect_stats: bool,
+    /// The max number of bytes of string and binary values kept in the min and
+    /// max statistics.
+    ///
+    /// Longer values are truncated and the max value is incremented so that it
+    /// remains an upper bound.  If None, 64 bytes are kept.
+    pub stats_prefix_length: Option<usize>,
 }
 
+/// The schema metadata key holding the index of the global buffer with the page
+/// statistics, if the file has them
+pub const PAGE_STATS_META_KEY: &str = "lance-file:page-stats";
+
+/// The name of the column of the page statistics with the number of rows of each page
+pub const PAGE_STATS_NUM_ROWS_COLUMN: &str = "num_rows";
+
 // Total in-memory budget for buffering serialized page metadata before flushing
 // to the spill file. Divided evenly across columns (with a floor of 64 bytes).
 const DEFAULT_SPILL_BUFFER_LIMIT: usize = 256 * 1024;
@@ -204,6 +241,19 @@ enum PageSpillState {
     Active(PageMetadataSpill),
 }
 
+// A column whose buffers are encrypted
+struct EncryptedColumn {
+    column_idx: u32,
+    // The top-level field the column belongs to, for error messages
+    field_name: String,
+    key_id: String,
+}
+
+struct ColumnEncryptor {
+    cipher: ColumnCipher,
+    num_pages: u32,
+}
+
 pub struct FileWriter {
     writer: Box<dyn Writer>,
     schema: Option<LanceSchema>,
@@ -216,6 +266,12 @@ pub struct FileWriter {
     schema_metadata: HashMap<String, String>,
     options: FileWriterOptions,
     page_spill: Option<PageSpillState>,
+    encrypted_columns: Vec<EncryptedColumn>,
+    // One entry per column once the keys of `encrypted_columns` have been looked up
+    encryptors: Option<Vec<Option<ColumnEncryptor>>>,
+    stats_collector: Option<StatisticsCollector>,
+    // The number of rows of each batch that statistics were collected for
+    stats_num_rows: Vec<u64>,
 }
 
 fn initial_column_metadata() -> pbfile::ColumnMetadata {
@@ -224,6 +280,7 @@ fn initial_column_metadata() -> pbfile::ColumnMetadata {
         buffer_offsets: Vec::new(),
         buffer_sizes: Vec::new(),
         encoding: None,
+        encryption: None,
     }
 }
 
@@ -272,6 +329,10 @@ impl FileWriter {
             global_buffers: Vec::new(),
             schema_metadata: HashMap::new(),
             page_spill: None,
+            encrypted_columns: Vec::new(),
+            encryptors: None,
+            stats_collector: None,
+            stats_num_rows: Vec::new(),
             options,
         }
     }
@@ -319,13 +380,29 @@ impl FileWriter {
     }
 
     async fn write_page(&mut self, encoded_page: EncodedPage) -> Result<()> {
+        let col_idx = encoded_page.column_idx as usize;
+        let mut encryptor = self.encryptor(col_idx).await?;
         let buffers = encoded_page.data;
         let mut buffer_offsets = Vec::with_capacity(buffers.len());
         let mut buffer_sizes = Vec::with_capacity(buffers.len());
-        for buffer in buffers {
+        for (buffer_idx, buffer) in buffers.into_iter().enumerate() {
             buffer_offsets.push(self.writer.tell().await? as u64);
             buffer_sizes.push(buffer.len() as u64);
-            Self::do_write_buffer(&mut self.writer, &buffer).await?;
+            match encryptor.as_mut() {
+                Some(encryptor) => {
+                    let location = BufferLocation::Page {
+                        page: encryptor.num_pages,
+                        buffer: buffer_idx as u32,
+                    };
+                    let sealed = encryptor.cipher.encrypt(location, &buffer)?;
+                    Self::do_write_buffer(&mut self.writer, &sealed).await?;
+                }
+                None => Self::do_write_buffer(&mut self.writer, &buffer).await?,
+            }
+        }
+        if let Some(mut encryptor) = encryptor {
+            encryptor.num_pages += 1;
+            self.encryptors.as_mut().unwrap()[col_idx] = Some(encryptor);
         }
         let encoded_encoding = match encoded_page.description {
             PageEncoding::Legacy(array_encoding) => Any::from_msg(&array_encoding)?.encode_to_vec(),
@@ -342,7 +419,6 @@ impl FileWriter {
             length: encoded_page.num_rows,
             priority: encoded_page.row_number,
         };
-        let col_idx = encoded_page.column_idx as usize;
         if matches!(&self.page_spill, Some(PageSpillState::Pending(..))) {
             let Some(PageSpillState::Pending(store, path)) = self.page_spill.take() else {
                 unreachable!()
@@ -441,10 +517,29 @@ impl FileWriter {
 
         schema.validate()?;
 
+        if self.version().resolve() < LanceFileVersion::V2_3
+            && let Some(field) = schema
+                .fields_pre_order()
+                .find(|f| f.metadata.contains_key(CUSTOM_ENCODING_META_KEY))
+        {
+            return Err(Error::not_supported(format!(
+                "Field '{}' requests a custom encoding but custom encodings require Lance file \
+                 version 2.3 or later",
+                field.name
+            )));
+        }
+
         let keep_original_array = self.options.keep_original_array.unwrap_or(false);
         let encoding_strategy = self.options.encoding_strategy.clone().unwrap_or_else(|| {
             let version = self.version();
-            default_encoding_strategy(version).into()
+            match &self.options.custom_encodings {
+                Some(custom_encodings) => default_encoding_strategy_with_custom_encodings(
+                    version,
+                    custom_encodings.clone(),
+                )
+                .into(),
+                None => default_encoding_strategy(version).into(),
+            }
         });
 
         let encoding_options = EncodingOptions {
@@ -458,15 +553,114 @@ impl FileWriter {
             BatchEncoder::try_new(&schema, encoding_strategy.as_ref(), &encoding_options)?;
         self.num_columns = encoder.num_columns();
 
+        self.encrypted_columns =
+            Self::encrypted_columns(&schema, &encoder.field_id_to_column_index, self.version())?;
         self.column_writers = encoder.field_encoders;
         self.column_metadata = vec![initial_column_metadata(); self.num_columns as usize];
         self.field_id_to_column_indices = encoder.field_id_to_column_index;
+        if self.options.collect_stats {
+            // Blob values are stored out of line, so blob fields have no statistics
+            let stats_field_ids = schema
+                .fields
+                .iter()
+                .filter(|f| !Self::is_blob(f))
+                .map(|f| f.id)
+                .collect::<Vec<_>>();
+            self.stats_collector =
+                StatisticsCollector::try_new(&schema.project_by_ids(&stats_field_ids, true));
+        }
         self.schema_metadata
             .extend(std::mem::take(&mut schema.metadata));
         self.schema = Some(schema);
         Ok(())
     }
 
+    // Finds the columns of the top-level fields that are marked for encryption
+    fn encrypted_columns(
+        schema: &LanceSchema,
+        field_id_to_column_index: &[(u32, u32)],
+        version: LanceFileVersion,
+    ) -> Result<Vec<EncryptedColumn>> {
+        let top_level_ids = schema.fields.iter().map(|f| f.id).collect::<HashSet<_>>();
+        if let Some(nested) = schema.fields_pre_order().find(|f| {
+            !top_level_ids.contains(&f.id) && f.metadata.contains_key(ENCRYPTION_KEY_ID_META_KEY)
+        }) {
+            return Err(Error::invalid_input(format!(
+                "The field `{}` has the metadata key {} but only top-level fields can be encrypted",
+                nested.name, ENCRYPTION_KEY_ID_META_KEY
+            )));
+        }
+        let mut encrypted_columns = Vec::new();
+        for field in &schema.fields {
+            let Some(key_id) = field.metadata.get(ENCRYPTION_KEY_ID_META_KEY) else {
+                continue;
+            };
+            if version.resolve() < LanceFileVersion::V2_1 {
+                return Err(Error::not_supported(format!(
+                    "Cannot encrypt the field `{}`, encryption requires file version 2.1 or later but the file version is {}",
+                    field.name, version
+                )));
+            }
+            let mut field_ids = HashSet::new();
+            let mut stack = vec![field];
+            while let Some(f) = stack.pop() {
+                if f.is_blob() {
+                    return Err(Error::not_supported(format!(
+                        "Cannot encrypt the field `{}`, blob columns can't be encrypted",
+                        field.name
+                    )));
+                }
+                field_ids.insert(f.id as u32);
+                stack.extend(f.children.iter());
+            }
+            encrypted_columns.extend(
+                field_id_to_column_index
+                    .iter()
+                    .filter(|(field_id, _)| field_ids.contains(field_id))
+                    .map(|(_, column_idx)| EncryptedColumn {
+                        column_idx: *column_idx,
+                        field_name: field.name.clone(),
+                        key_id: key_id.clone(),
+                    }),
+            );
+        }
+        Ok(encrypted_columns)
+    }
+
+    // Looks up the keys of the encrypted columns the first time it is called
+    //
+    // Returns the encryptor of the column, which the caller must put back.
+    async fn encryptor(&mut self, col_idx: usize) -> Result<Option<ColumnEncryptor>> {
+        if self.encryptors.is_none() {
+            let mut encryptors = (0..self.num_columns).map(|_| None).collect::<Vec<_>>();
+            let mut keys = HashMap::new();
+            for column in &self.encrypted_columns {
+                if !keys.contains_key(&column.key_id) {
+                    let key = require_key(
+                        self.options.key_provider.as_ref(),
+                        &column.key_id,
+                        &column.field_name,
+                    )
+                    .await?;
+                    keys.insert(column.key_id.clone(), key);
+                }
+                let salt = ColumnCipher::generate_salt()?;
+                let cipher = ColumnCipher::try_new(&keys[&column.key_id], &salt)?;
+                self.column_metadata[column.column_idx as usize].encryption =
+                    Some(pbfile::ColumnEncryption {
+                        key_id: column.key_id.clone(),
+                        salt,
+                    });
+                encryptors[column.column_idx as usize] = Some(ColumnEncryptor {
+                    cipher,
+                    num_pages: 0,
+                });
+            }
+            self.encryptors = Some(encryptors);
+        }
+        Ok(self.encryptors.as_mut().unwrap()[col_idx].take())
+    }
+
     fn ensure_initialized(&mut self, batch: &RecordBatch) -> Result<&LanceSchema> {
         if self.schema.is_none() {
             let schema = LanceSchema::try_from(batch.schema().as_ref())?;
@@ -538,6 +732,7 @@ impl FileWriter {
         let mut external_buffers =
             OutOfLineBuffers::new(self.tell().await?, PAGE_BUFFER_ALIGNMENT as u64);
         let encoding_tasks = self.encode_batch(batch, &mut external_buffers)?;
+        self.collect_stats(batch);
         // Next, write external buffers
         for external_buffer in external_buffers.take_buffers() {
             Self::do_write_buffer(&mut self.writer, &external_buffer).await?;
@@ -560,6 +755,86 @@ impl FileWriter {
         Ok(())
     }
 
+    fn collect_stats(&mut self, batch: &RecordBatch) {
+        let Some(stats_collector) = self.stats_collector.as_mut() else {
+            return;
+        };
+        let prefix_length = self
+            .options
+            .stats_prefix_length
+            .unwrap_or(statistics::BINARY_PREFIX_LENGTH);
+        // encode_batch has already checked that every field is in the batch
+        for field in &self.schema.as_ref().unwrap().fields {
+            if Self::is_blob(field) {
+                continue;
+            }
+            if let Some(array) = batch.column_by_name(&field.name) {
+                Self::collect_field_stats(stats_collector, field, array, prefix_length);
+            }
+        }
+        self.stats_num_rows.push(batch.num_rows() as u64);
+    }
+
+    fn is_blob(field: &Field) -> bool {
+        field.is_blob() || field.is_blob_v2()
+    }
+
+    fn collect_field_stats(
+        stats_collector: &mut StatisticsCollector,
+        field: &Field,
+        array: &ArrayRef,
+        prefix_length: usize,
+    ) {
+        if matches!(field.data_type(), DataType::Struct(_)) {
+            for (child, child_array) in field.children.iter().zip(array.as_struct().columns()) {
+                Self::collect_field_stats(stats_collector, child, child_array, prefix_length);
+            }
+        } else if let Some(stats_builder) = stats_collector.get_builder(field.id) {
+            stats_builder.append(statistics::collect_statistics(&[array], prefix_length));
+        }
+    }
+
+    // Stores the collected statistics as a self-described Lance file in a global buffer
+    async fn write_stats(&mut self) -> Result<()> {
+        let Some(mut stats_collector) = self.stats_collector.take() else {
+            return Ok(());
+        };
+        let num_rows = std::mem::take(&mut self.stats_num_rows);
+        if num_rows.is_empty() {
+            return Ok(());
+        }
+        let stats = stats_collector.finish()?;
+        let mut fields = vec![ArrowField::new(
+            PAGE_STATS_NUM_ROWS_COLUMN,
+            DataType::UInt64,
+            false,
+        )];
+        fields.extend(stats.schema().fields().iter().map(|f| f.as_ref().clone()));
+        let mut columns: Vec<ArrayRef> = vec![Arc::new(UInt64Array::from(num_rows))];
+        columns.extend(stats.columns().iter().cloned());
+        let stats = RecordBatch::try_new(Arc::new(ArrowSchema::new(fields)), columns)?;
+
+        let version = self.version();
+        let encoding_options = EncodingOptions {
+            cache_bytes_per_column: 8 * 1024 * 1024,
+            max_page_bytes: MAX_PAGE_BYTES as u64,
+            keep_original_array: true,
+            buffer_alignment: PAGE_BUFFER_ALIGNMENT as u64,
+            version,
+        };
+        let encoded = encode_batch(
+            &stats,
+            Arc::new(LanceSchema::try_from(stats.schema().as_ref())?),
+            default_encoding_strategy(version).as_ref(),
+            &encoding_options,
+        )
+        .await?;
+        let buffer = encoded.try_to_self_described_lance(version)?;
+        let index = self.add_global_buffer(buffer).await?;
+        self.add_schema_metadata(PAGE_STATS_META_KEY, index.to_string());
+        Ok(())
+    }
+
     async fn write_column_metadata(
         &mut self,
         metadata: pbfile::ColumnMetadata,
@@ -707,16 +982,24 @@ impl FileWriter {
                 for page in column.final_pages {
                     self.write_page(page).await?;
                 }
-                let column_metadata = &mut self.column_metadata[col_idx];
-                let mut buffer_pos = self.writer.tell().await? as u64;
-                for buffer in column.column_buffers {
+                let encryptor = self.encryptor(col_idx).await?;
+                for (buffer_idx, buffer) in column.column_buffers.into_iter().enumerate() {
+                    let buffer_pos = self.writer.tell().await? as u64;
+                    match encryptor.as_ref() {
+                        Some(encryptor) => {
+                            let location = BufferLocation::Column {
+                                buffer: buffer_idx as u32,
+                            };
+                            let sealed = encryptor.cipher.encrypt(location, &buffer)?;
+                            Self::do_write_buffer(&mut self.writer, &sealed).await?;
+                        }
+                        None => Self::do_write_buffer(&mut self.writer, &buffer).await?,
+                    }
+                    let column_metadata = &mut self.column_metadata[col_idx];
                     column_metadata.buffer_offsets.push(buffer_pos);
-                    let mut size = 0;
-                    Self::do_write_buffer(&mut self.writer, &buffer).await?;
-                    size += buffer.len() as u64;
-                    buffer_pos += size;
-                    column_metadata.buffer_sizes.push(size);
+                    column_metadata.buffer_sizes.push(buffer.len() as u64);
                 }
+                let column_metadata = &mut self.column_metadata[col_idx];
                 let encoded_encoding = Any::from_msg(&column.encoding)?.encode_to_vec();
                 column_metadata.encoding = Some(pbfile::Encoding {
                     location: Some(pbfile::encoding::Location::Direct(pbfile::DirectEncoding {
@@ -778,6 +1061,9 @@ impl FileWriter {
             self.finish_writers().await?;
         }
 
+        // 2. write the statistics (a global buffer)
+        self.write_stats().await?;
+
         // 3. write global buffers (we write the schema here)
         let global_buffer_offsets = self.write_global_buffers().await?;
         let num_global_buffers = global_buffer_offsets.len() as u32;
@@ -851,6 +1137,17 @@ fn concat_lance_footer(
     write_schema: bool,
     version: LanceFileVersion,
 ) -> Result<Bytes> {
+    if let Some(field) = batch
+        .schema
+        .fields
+        .iter()
+        .find(|field| field.metadata.contains_key(ENCRYPTION_KEY_ID_META_KEY))
+    {
+        return Err(Error::not_supported(format!(
+            "Cannot serialize the encrypted field `{}` as an encoded batch, encrypted columns can only be written by a FileWriter",
+            field.name
+        )));
+    }
     // Estimating 1MiB for file footer
     let mut data = BytesMut::with_capacity(batch.data.len() + 1024 * 1024);
     data.put(batch.data.clone());
@@ -916,6 +1213,7 @@ fn concat_lance_footer(
                     encoding: encoded_col_encoding,
                 })),
             }),
+            encryption: None,
         };
         let column_bytes = column.encode_to_vec();
         col_metadata_positions.push((position, column_bytes.len() as u64));
@@ -3,7 +3,10 @@
 
 use async_trait::async_trait;
 use lance_core::Result;
-use std::sync::Arc;
+use lance_core::utils::tracing::TRACE_INDEX_BUILD;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 /// Progress callback for index building and distributed index finalization.
 ///
@@ -52,3 +55,122 @@ impl IndexBuildProgress for NoopIndexBuildProgress {
 pub fn noop_progress() -> Arc<dyn IndexBuildProgress> {
     Arc::new(NoopIndexBuildProgress)
 }
+
+/// How often [`TracingIndexBuildProgress`] emits a progress event for a stage.
+pub const DEFAULT_PROGRESS_EVENT_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug)]
+struct StageState {
+    started: Instant,
+    total: Option<u64>,
+    unit: String,
+    completed: u64,
+    last_event: Option<Instant>,
+}
+
+/// Forwards progress to another [`IndexBuildProgress`] and emits matching
+/// `tracing` events under the [`TRACE_INDEX_BUILD`] target.
+///
+/// Every stage start and completion is an `INFO` event, as is the first
+/// progress update of a stage and then at most one update per interval. A
+/// progress event carries the rate in units per second and, when the stage
+/// total is known, the total and the estimated seconds remaining. Events are
+/// emitted from the caller, so they nest under whichever span the index build
+/// has entered.
+#[derive(Debug)]
+pub struct TracingIndexBuildProgress {
+    inner: Arc<dyn IndexBuildProgress>,
+    interval: Duration,
+    stages: Mutex<HashMap<String, StageState>>,
+}
+
+impl TracingIndexBuildProgress {
+    pub fn new(inner: Arc<dyn IndexBuildProgress>) -> Self {
+        Self {
+            inner,
+            interval: DEFAULT_PROGRESS_EVENT_INTERVAL,
+            stages: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Minimum time between two progress events of a stage.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+}
+
+#[async_trait]
+impl IndexBuildProgress for TracingIndexBuildProgress {
+    async fn stage_start(&self, stage: &str, total: Option<u64>, unit: &str) -> Result<()> {
+        tracing::info!(
+            target: TRACE_INDEX_BUILD,
+            stage,
+            total,
+            unit,
+            "index build stage started"
+        );
+        self.stages.lock().unwrap().insert(
+            stage.to_string(),
+            StageState {
+                started: Instant::now(),
+                total,
+                unit: unit.to_string(),
+                completed: 0,
+                last_event: None,
+            },
+        );
+        self.inner.stage_start(stage, total, unit).await
+    }
+
+    async fn stage_progress(&self, stage: &str, completed: u64) -> Result<()> {
+        {
+            let mut stages = self.stages.lock().unwrap();
+            if let Some(state) = stages.get_mut(stage) {
+                state.completed = completed;
+                let now = Instant::now();
+                if state
+                    .last_event
+                    .is_none_or(|last| now.duration_since(last) >= self.interval)
+                {
+                    state.last_event = Some(now);
+                    let elapsed = now.duration_since(state.started).as_secs_f64();
+                    let rate = if elapsed > 0.0 {
+                        completed as f64 / elapsed
+                    } else {
+                        0.0
+                    };
+                    let eta_secs = state
+                        .total
+                        .filter(|_| rate > 0.0)
+                        .map(|total| total.saturating_sub(completed) as f64 / rate);
+                    tracing::info!(
+                        target: TRACE_INDEX_BUILD,
+                        stage,
+                        completed,
+                        total = state.total,
+                        unit = state.unit.as_str(),
+                        rate,
+                        eta_secs,
+                        "index build stage progress"
+                    );
+                }
+            }
+        }
+        self.inner.stage_progress(stage, completed).await
+    }
+
+    async fn stage_complete(&self, stage: &str) -> Result<()> {
+        if let Some(state) = self.stages.lock().unwrap().remove(stage) {
+            tracing::info!(
+                target: TRACE_INDEX_BUILD,
+                stage,
+                completed = state.completed,
+                unit = state.unit.as_str(),
+                elapsed_secs = state.started.elapsed().as_secs_f64(),
+                "index build stage completed"
+            );
+        }
+        self.inner.stage_complete(stage).await
+    }
+}
@@ -11,7 +11,8 @@ use crate::{
     scalar::{
         bitmap::BitmapIndexPlugin, bloomfilter::BloomFilterIndexPlugin, btree::BTreeIndexPlugin,
         inverted::InvertedIndexPlugin, json::JsonIndexPlugin, label_list::LabelListIndexPlugin,
-        ngram::NGramIndexPlugin, registry::ScalarIndexPlugin, zonemap::ZoneMapIndexPlugin,
+        ngram::NGramIndexPlugin, registry::ScalarIndexPlugin, sparse::SparseVectorIndexPlugin,
+        zonemap::ZoneMapIndexPlugin,
     },
 };
 
@@ -66,6 +67,7 @@ impl IndexPluginRegistry {
         registry.add_plugin::<pb::BloomFilterIndexDetails, BloomFilterIndexPlugin>();
         registry.add_plugin::<pbold::InvertedIndexDetails, InvertedIndexPlugin>();
         registry.add_plugin::<pb::JsonIndexDetails, JsonIndexPlugin>();
+        registry.add_plugin::<pb::SparseVectorIndexDetails, SparseVectorIndexPlugin>();
         #[cfg(feature = "geo")]
         registry.add_plugin::<pb::RTreeIndexDetails, RTreeIndexPlugin>();
 
@@ -122,6 +124,7 @@ mod tests {
             ("ZONEMAP", "ZoneMap"),
             ("BLOOMFILTER", "BloomFilter"),
             ("JSON", "Json"),
+            ("SPARSEVECTOR", "SparseVector"),
         ] {
             let plugin = registry.get_plugin_by_name(requested_name).unwrap();
             assert_eq!(plugin.name(), expected_name);
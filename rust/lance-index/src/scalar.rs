@@ -43,6 +43,7 @@ pub mod ngram;
 pub mod registry;
 #[cfg(feature = "geo")]
 pub mod rtree;
+pub mod sparse;
 pub mod zoned;
 pub mod zonemap;
 
@@ -67,6 +68,7 @@ pub enum BuiltinIndexType {
     BloomFilter,
     RTree,
     Inverted,
+    SparseVector,
 }
 
 impl BuiltinIndexType {
@@ -80,6 +82,7 @@ impl BuiltinIndexType {
             Self::Inverted => "inverted",
             Self::BloomFilter => "bloomfilter",
             Self::RTree => "rtree",
+            Self::SparseVector => "sparsevector",
         }
     }
 }
@@ -97,6 +100,7 @@ impl TryFrom<IndexType> for BuiltinIndexType {
             IndexType::Inverted => Ok(Self::Inverted),
             IndexType::BloomFilter => Ok(Self::BloomFilter),
             IndexType::RTree => Ok(Self::RTree),
+            IndexType::SparseVector => Ok(Self::SparseVector),
             _ => Err(Error::index("Invalid index type".to_string())),
         }
     }
@@ -27,6 +27,7 @@ use crate::{
 };
 use crate::{metrics::NoOpMetricsCollector, scalar::registry::TrainingCriteria};
 use crate::{pbold, scalar::btree::flat::FlatIndex};
+use arrow::datatypes::i256;
 use arrow_arith::numeric::add;
 use arrow_array::{Array, RecordBatch, UInt32Array, new_empty_array};
 use arrow_schema::{DataType, Field, Schema, SortOptions};
@@ -98,7 +99,53 @@ impl Display for OrderableScalarValue {
 
 impl PartialEq for OrderableScalarValue {
     fn eq(&self, other: &Self) -> bool {
-        self.0.eq(&other.0)
+        match (decimal_parts(&self.0), decimal_parts(&other.0)) {
+            // Consistent with `cmp`, 1.0 and 1.00 are the same value
+            (Some(_), Some(_)) => self.cmp(other) == Ordering::Equal,
+            _ => self.0.eq(&other.0),
+        }
+    }
+}
+
+/// The unscaled value and scale of a decimal scalar, `None` if it is not a decimal
+fn decimal_parts(value: &ScalarValue) -> Option<(Option<i256>, i8)> {
+    match value {
+        ScalarValue::Decimal32(v, _, s) => Some((v.map(i256::from), *s)),
+        ScalarValue::Decimal64(v, _, s) => Some((v.map(i256::from), *s)),
+        ScalarValue::Decimal128(v, _, s) => Some((v.map(i256::from_i128), *s)),
+        ScalarValue::Decimal256(v, _, s) => Some((*v, *s)),
+        _ => None,
+    }
+}
+
+/// Compare the decimals `v1 * 10^-s1` and `v2 * 10^-s2` by value, nulls first
+fn cmp_decimals(v1: Option<i256>, s1: i8, v2: Option<i256>, s2: i8) -> Ordering {
+    let (Some(v1), Some(v2)) = (v1, v2) else {
+        return v1.is_some().cmp(&v2.is_some());
+    };
+    if s1 == s2 {
+        return v1.cmp(&v2);
+    }
+    // Bring the value with the smaller scale to the larger one.  If that overflows its
+    // magnitude is larger than anything the other value can hold.
+    let (low, high, reversed) = if s1 < s2 {
+        (v1, v2, false)
+    } else {
+        (v2, v1, true)
+    };
+    let shift = (i16::from(s1) - i16::from(s2)).unsigned_abs();
+    let ordering = match i256::from_i128(10)
+        .checked_pow(u32::from(shift))
+        .and_then(|factor| low.checked_mul(factor))
+    {
+        Some(low) => low.cmp(&high),
+        None if low.is_negative() => Ordering::Less,
+        None => Ordering::Greater,
+    };
+    if reversed {
+        ordering.reverse()
+    } else {
+        ordering
     }
 }
 
@@ -111,7 +158,8 @@ impl PartialOrd for OrderableScalarValue {
 }
 
 // manual implementation of `Ord` that panics when asked to compare scalars of different type
-// and always puts nulls before non-nulls (this is consistent with Option<T>'s implementation
+// (other than decimals of different precision / scale, which are compared by value) and
+// always puts nulls before non-nulls (this is consistent with Option<T>'s implementation
 // of Ord)
 //
 // TODO: Consider upstreaming this
@@ -122,13 +170,14 @@ impl Ord for OrderableScalarValue {
         // any newly added enum variant will require editing this list
         // or else face a compile error
         match (&self.0, &other.0) {
-            (Decimal32(v1, p1, s1), Decimal32(v2, p2, s2)) => {
-                if p1.eq(p2) && s1.eq(s2) {
-                    v1.cmp(v2)
-                } else {
-                    // Two decimal values can only be compared if they have the same precision and scale.
-                    panic!("Attempt to compare decimals with unequal precision / scale")
-                }
+            // Decimals of any precision and scale are compared by value
+            (
+                Decimal32(_, _, _) | Decimal64(_, _, _) | Decimal128(_, _, _) | Decimal256(_, _, _),
+                Decimal32(_, _, _) | Decimal64(_, _, _) | Decimal128(_, _, _) | Decimal256(_, _, _),
+            ) => {
+                let (v1, s1) = decimal_parts(&self.0).unwrap();
+                let (v2, s2) = decimal_parts(&other.0).unwrap();
+                cmp_decimals(v1, s1, v2, s2)
             }
             (Decimal32(v1, _, _), Null) => {
                 if v1.is_none() {
@@ -138,14 +187,6 @@ impl Ord for OrderableScalarValue {
                 }
             }
             (Decimal32(_, _, _), _) => panic!("Attempt to compare decimal with non-decimal"),
-            (Decimal64(v1, p1, s1), Decimal64(v2, p2, s2)) => {
-                if p1.eq(p2) && s1.eq(s2) {
-                    v1.cmp(v2)
-                } else {
-                    // Two decimal values can only be compared if they have the same precision and scale.
-                    panic!("Attempt to compare decimals with unequal precision / scale")
-                }
-            }
             (Decimal64(v1, _, _), Null) => {
                 if v1.is_none() {
                     Ordering::Equal
@@ -154,14 +195,6 @@ impl Ord for OrderableScalarValue {
                 }
             }
             (Decimal64(_, _, _), _) => panic!("Attempt to compare decimal with non-decimal"),
-            (Decimal128(v1, p1, s1), Decimal128(v2, p2, s2)) => {
-                if p1.eq(p2) && s1.eq(s2) {
-                    v1.cmp(v2)
-                } else {
-                    // Two decimal values can only be compared if they have the same precision and scale.
-                    panic!("Attempt to compare decimals with unequal precision / scale")
-                }
-            }
             (Decimal128(v1, _, _), Null) => {
                 if v1.is_none() {
                     Ordering::Equal
@@ -170,14 +203,6 @@ impl Ord for OrderableScalarValue {
                 }
             }
             (Decimal128(_, _, _), _) => panic!("Attempt to compare decimal with non-decimal"),
-            (Decimal256(v1, p1, s1), Decimal256(v2, p2, s2)) => {
-                if p1.eq(p2) && s1.eq(s2) {
-                    v1.cmp(v2)
-                } else {
-                    // Two decimal values can only be compared if they have the same precision and scale.
-                    panic!("Attempt to compare decimals with unequal precision / scale")
-                }
-            }
             (Decimal256(v1, _, _), Null) => {
                 if v1.is_none() {
                     Ordering::Equal
@@ -2951,7 +2976,8 @@ mod tests {
 
     use super::{
         BTreeIndexPlugin, BTreeIndexState, BTreePageKey, DEFAULT_BTREE_BATCH_SIZE,
-        OrderableScalarValue, part_lookup_file_path, part_page_data_file_path, train_btree_index,
+        OrderableScalarValue, i256, part_lookup_file_path, part_page_data_file_path,
+        train_btree_index,
     };
     use crate::scalar::registry::ScalarIndexPlugin;
     use arrow_array::RecordBatch;
@@ -2979,6 +3005,46 @@ mod tests {
         assert!(size_of_many_i32 > 128 * 4);
     }
 
+    #[test]
+    fn test_decimal_ordering() {
+        let dec128 =
+            |v: i128, p: u8, s: i8| OrderableScalarValue(ScalarValue::Decimal128(Some(v), p, s));
+        let dec256 =
+            |v: i256, p: u8, s: i8| OrderableScalarValue(ScalarValue::Decimal256(Some(v), p, s));
+
+        // 1.05 at different precision and scale
+        assert_eq!(dec128(105, 5, 2), dec128(10_500_000_000, 38, 10));
+        assert_eq!(
+            dec128(105, 5, 2).cmp(&dec128(10_500_000_000, 38, 10)),
+            std::cmp::Ordering::Equal
+        );
+        assert!(dec128(105, 5, 2) < dec128(10_500_000_001, 38, 10));
+        assert!(dec128(-105, 5, 2) > dec128(-10_500_000_001, 38, 10));
+        assert!(dec128(106, 5, 2) > dec256(i256::from_i128(10_599), 76, 4));
+        // Rescaling overflows, the value with the larger magnitude wins
+        assert!(dec256(i256::ONE, 76, -50) > dec256(i256::MAX, 76, 76));
+        assert!(dec256(i256::MINUS_ONE, 76, -50) < dec256(i256::MIN, 76, 76));
+        // Nulls first
+        assert!(OrderableScalarValue(ScalarValue::Decimal128(None, 38, 10)) < dec128(0, 5, 2));
+
+        let mut values = vec![
+            dec128(3, 5, 0),
+            dec128(-25, 5, 1),
+            dec128(29_999, 10, 4),
+            dec128(0, 38, 10),
+        ];
+        values.sort();
+        assert_eq!(
+            values,
+            vec![
+                dec128(-25, 5, 1),
+                dec128(0, 38, 10),
+                dec128(29_999, 10, 4),
+                dec128(3, 5, 0),
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn test_null_ids() {
         let tmpdir = TempObjDir::default();
@@ -25,6 +25,7 @@ use lance_arrow::json::JSON_EXT_NAME;
 use lance_arrow::{ARROW_EXT_NAME_KEY, iter_str_array};
 use lance_core::cache::LanceCache;
 use lance_core::error::LanceOptionExt;
+use lance_core::utils::resources::{MemoryReservation, TrackedResources};
 use lance_core::utils::tokio::{IO_CORE_RESERVATION, get_num_compute_intensive_cpus, spawn_cpu};
 use lance_core::{Error, ROW_ID, ROW_ID_FIELD, Result};
 use lance_io::object_store::ObjectStore;
@@ -442,6 +443,7 @@ impl InvertedIndexBuilder {
         let tokenized_count = Arc::new(AtomicU64::new(0));
         let (sender, receiver) = async_channel::bounded(num_workers);
         let dest_store = dest_store.clone_arc();
+        let resources = TrackedResources::current();
         let mut index_tasks = Vec::with_capacity(num_workers);
         for _ in 0..num_workers {
             let tokenizer = tokenizer.clone();
@@ -450,9 +452,11 @@ impl InvertedIndexBuilder {
             let id_alloc = id_alloc.clone();
             let progress = self.progress.clone();
             let tokenized_count = tokenized_count.clone();
+            let resources = resources.clone();
             index_tasks.push(tokio::task::spawn(async move {
                 let mut worker =
                     IndexWorker::new(tokenizer, dest_store, id_alloc, worker_config).await?;
+                worker.memory_reservation = resources.reserve_memory(0);
                 while let Ok(batch) = receiver.recv().await {
                     let num_rows = batch.num_rows();
                     worker.process_batch(batch).await?;
@@ -1105,6 +1109,8 @@ struct IndexWorker {
     partitions: Vec<u64>,
     schema: SchemaRef,
     memory_size: u64,
+    /// Counts `memory_size` toward the memory of the enclosing job
+    memory_reservation: MemoryReservation,
     worker_memory_limit_bytes: u64,
     total_doc_length: usize,
     fragment_mask: Option<u64>,
@@ -1188,6 +1194,7 @@ impl IndexWorker {
             id_alloc,
             schema,
             memory_size: 0,
+            memory_reservation: MemoryReservation::default(),
             worker_memory_limit_bytes: config.worker_memory_limit_bytes,
             total_doc_length: 0,
             fragment_mask: config.fragment_mask,
@@ -1378,9 +1385,11 @@ impl IndexWorker {
             if self.builder.docs.len() as u32 == u32::MAX
                 || (!builder_was_empty && self.memory_size >= self.worker_memory_limit_bytes)
             {
+                self.memory_reservation.resize(self.memory_size as usize);
                 self.flush().await?;
             }
         }
+        self.memory_reservation.resize(self.memory_size as usize);
 
         Ok(())
     }
@@ -0,0 +1,977 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Sparse Vector Index
+//!
+//! An inverted index over sparse vectors (see [`lance_arrow::sparse`]) that
+//! answers top-k dot-product queries.  Every dimension has a posting list of
+//! the rows with a value in that dimension.
+//!
+//! A query walks the posting lists of its dimensions in impact order, the
+//! postings that contribute the most to the score first, and scores every row
+//! it meets exactly.  A row that has not been met yet scores at most the sum of
+//! the contributions at the heads of the lists, so the search stops as soon as
+//! that bound no longer beats the k-th best score.
+//!
+//! Only rows with a positive score are returned, a row that shares no
+//! dimension with the query is not a match.
+
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet};
+use std::sync::Arc;
+
+use arrow_array::cast::AsArray;
+use arrow_array::types::UInt64Type;
+use arrow_array::{Float32Array, Int32Array, RecordBatch, UInt64Array};
+use arrow_schema::{DataType, Field, Schema};
+use async_trait::async_trait;
+use datafusion::execution::SendableRecordBatchStream;
+use deepsize::DeepSizeOf;
+use futures::TryStreamExt;
+use lance_arrow::sparse::{SparseVectorArray, is_sparse_vector_data_type};
+use lance_core::cache::LanceCache;
+use lance_core::{Error, ROW_ID, Result};
+use roaring::RoaringBitmap;
+
+use super::registry::{
+    DefaultTrainingRequest, ScalarIndexPlugin, TrainingCriteria, TrainingOrdering, TrainingRequest,
+    VALUE_COLUMN_NAME,
+};
+use super::{
+    AnyQuery, BuiltinIndexType, CreatedIndex, IndexStore, MetricsCollector, OldIndexDataFilter,
+    ScalarIndex, ScalarIndexParams, SearchResult, UpdateCriteria,
+};
+use crate::frag_reuse::FragReuseIndex;
+use crate::prefilter::PreFilter;
+use crate::scalar::expression::ScalarQueryParser;
+use crate::vector::VectorIndex;
+use crate::vector::graph::OrderedFloat;
+use crate::{Any, Index, IndexType, pb};
+
+pub const SPARSE_POSTINGS_FILE: &str = "sparse_postings.lance";
+const SPARSE_VECTOR_INDEX_VERSION: u32 = 0;
+
+const DIMENSION_COL: &str = "dimension";
+const VALUE_COL: &str = "value";
+const WRITE_BATCH_SIZE: usize = 64 * 1024;
+
+fn postings_schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        Field::new(DIMENSION_COL, DataType::Int32, false),
+        Field::new(ROW_ID, DataType::UInt64, false),
+        Field::new(VALUE_COL, DataType::Float32, false),
+    ]))
+}
+
+/// A sparse query vector
+///
+/// Dimensions are sorted and zero weights are dropped, as they can't change
+/// a score.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SparseQuery {
+    indices: Vec<i32>,
+    values: Vec<f32>,
+}
+
+impl SparseQuery {
+    pub fn try_new(indices: &[i32], values: &[f32]) -> Result<Self> {
+        if indices.len() != values.len() {
+            return Err(Error::invalid_input(format!(
+                "Sparse query has {} indices but {} values",
+                indices.len(),
+                values.len()
+            )));
+        }
+        let mut entries = indices
+            .iter()
+            .copied()
+            .zip(values.iter().copied())
+            .collect::<Vec<_>>();
+        entries.sort_unstable_by_key(|(dim, _)| *dim);
+        if let Some(pair) = entries.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            return Err(Error::invalid_input(format!(
+                "Sparse query has dimension {} more than once",
+                pair[0].0
+            )));
+        }
+        let (indices, values) = entries
+            .into_iter()
+            .filter(|(_, value)| *value != 0.0)
+            .unzip();
+        Ok(Self { indices, values })
+    }
+
+    pub fn indices(&self) -> &[i32] {
+        &self.indices
+    }
+
+    pub fn values(&self) -> &[f32] {
+        &self.values
+    }
+
+    /// Whether the query has no non-zero dimension, such a query matches nothing
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// The dot product of the query with a sparse vector
+    pub fn dot(&self, indices: &[i32], values: &[f32]) -> f32 {
+        indices
+            .iter()
+            .zip(values)
+            .filter_map(|(dim, value)| {
+                self.indices
+                    .binary_search(dim)
+                    .ok()
+                    .map(|pos| self.values[pos] * value)
+            })
+            .sum()
+    }
+}
+
+/// Collects the k rows with the highest positive scores
+#[derive(Debug)]
+pub struct SparseTopK {
+    k: usize,
+    // A min-heap, so the k-th best score is at the top
+    heap: BinaryHeap<std::cmp::Reverse<(OrderedFloat, u64)>>,
+}
+
+impl SparseTopK {
+    pub fn new(k: usize) -> Self {
+        Self {
+            k,
+            heap: BinaryHeap::with_capacity(k + 1),
+        }
+    }
+
+    /// The score a row must beat to make it into the top k
+    pub fn threshold(&self) -> f32 {
+        if self.heap.len() < self.k {
+            0.0
+        } else {
+            self.heap.peek().map(|top| top.0.0.0).unwrap_or(f32::MAX)
+        }
+    }
+
+    pub fn push(&mut self, row_id: u64, score: f32) {
+        if self.k == 0 || score <= self.threshold() {
+            return;
+        }
+        self.heap
+            .push(std::cmp::Reverse((OrderedFloat(score), row_id)));
+        if self.heap.len() > self.k {
+            self.heap.pop();
+        }
+    }
+
+    /// The row ids and scores, best first
+    pub fn into_sorted(self) -> (Vec<u64>, Vec<f32>) {
+        self.heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|std::cmp::Reverse((score, row_id))| (row_id, score.0))
+            .unzip()
+    }
+}
+
+/// The rows with a value in one dimension
+#[derive(Debug, Clone, DeepSizeOf)]
+struct PostingList {
+    /// Sorted and unique
+    row_ids: Vec<u64>,
+    values: Vec<f32>,
+    /// Positions into `row_ids` by descending value
+    impact_order: Vec<u32>,
+}
+
+impl PostingList {
+    /// Build from unsorted postings, the values of repeated rows are summed
+    fn new(mut postings: Vec<(u64, f32)>) -> Self {
+        postings.sort_unstable_by_key(|(row_id, _)| *row_id);
+        let mut row_ids = Vec::with_capacity(postings.len());
+        let mut values: Vec<f32> = Vec::with_capacity(postings.len());
+        for (row_id, value) in postings {
+            if row_ids.last() == Some(&row_id) {
+                *values.last_mut().unwrap() += value;
+            } else {
+                row_ids.push(row_id);
+                values.push(value);
+            }
+        }
+        let mut impact_order = (0..row_ids.len() as u32).collect::<Vec<_>>();
+        impact_order.sort_unstable_by(|a, b| values[*b as usize].total_cmp(&values[*a as usize]));
+        Self {
+            row_ids,
+            values,
+            impact_order,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.row_ids.len()
+    }
+
+    fn get(&self, row_id: u64) -> Option<f32> {
+        self.row_ids
+            .binary_search(&row_id)
+            .ok()
+            .map(|pos| self.values[pos])
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (u64, f32)> + '_ {
+        self.row_ids
+            .iter()
+            .copied()
+            .zip(self.values.iter().copied())
+    }
+}
+
+/// A query dimension's position in its posting list during a search
+struct Cursor<'a> {
+    weight: f32,
+    list: &'a PostingList,
+    /// Number of postings visited so far
+    visited: usize,
+}
+
+impl Cursor<'_> {
+    /// The position of the next posting, lists are walked backwards for
+    /// negative weights so that contributions are always descending.
+    fn next_pos(&self) -> Option<usize> {
+        let order = &self.list.impact_order;
+        if self.visited >= order.len() {
+            return None;
+        }
+        let idx = if self.weight > 0.0 {
+            self.visited
+        } else {
+            order.len() - 1 - self.visited
+        };
+        Some(order[idx] as usize)
+    }
+
+    /// The most any row not visited yet can get from this dimension
+    fn bound(&self) -> f32 {
+        self.next_pos()
+            .map(|pos| (self.weight * self.list.values[pos]).max(0.0))
+            .unwrap_or(0.0)
+    }
+}
+
+/// An inverted index for dot-product search over sparse vectors
+#[derive(Debug, Clone)]
+pub struct SparseVectorIndex {
+    postings: HashMap<i32, PostingList>,
+}
+
+impl DeepSizeOf for SparseVectorIndex {
+    fn deep_size_of_children(&self, context: &mut deepsize::Context) -> usize {
+        self.postings.deep_size_of_children(context)
+    }
+}
+
+impl SparseVectorIndex {
+    async fn load(
+        store: Arc<dyn IndexStore>,
+        frag_reuse_index: Option<Arc<FragReuseIndex>>,
+    ) -> Result<Arc<Self>> {
+        let reader = store.open_index_file(SPARSE_POSTINGS_FILE).await?;
+        let batch = reader.read_range(0..reader.num_rows(), None).await?;
+        let mut builder = SparsePostingsBuilder::default();
+        for (dim, row_id, value) in postings_from_batch(&batch)? {
+            let row_id = match &frag_reuse_index {
+                Some(fri) => match fri.remap_row_id(row_id) {
+                    Some(row_id) => row_id,
+                    None => continue,
+                },
+                None => row_id,
+            };
+            builder.add(dim, row_id, value);
+        }
+        Ok(Arc::new(builder.into_index()))
+    }
+
+    fn num_postings(&self) -> usize {
+        self.postings.values().map(PostingList::len).sum()
+    }
+
+    /// Find the `k` rows with the highest positive dot product with `query`
+    ///
+    /// Rows rejected by `prefilter` are skipped, the prefilter must be ready.
+    /// Returns the row ids and scores, best first.
+    pub fn search(
+        &self,
+        query: &SparseQuery,
+        k: usize,
+        prefilter: &dyn PreFilter,
+        metrics: &dyn MetricsCollector,
+    ) -> Result<(Vec<u64>, Vec<f32>)> {
+        let mask = (!prefilter.is_empty()).then(|| prefilter.mask());
+        let mut cursors = query
+            .indices()
+            .iter()
+            .zip(query.values())
+            .filter_map(|(dim, weight)| {
+                self.postings.get(dim).map(|list| Cursor {
+                    weight: *weight,
+                    list,
+                    visited: 0,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut top_k = SparseTopK::new(k);
+        let mut seen = HashSet::new();
+        let mut visited = 0;
+        loop {
+            let bounds = cursors.iter().map(Cursor::bound).collect::<Vec<_>>();
+            if bounds.iter().sum::<f32>() <= top_k.threshold() {
+                break;
+            }
+            let (best, _) = bounds
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.total_cmp(b.1))
+                .unwrap();
+            let cursor = &mut cursors[best];
+            let pos = cursor.next_pos().unwrap();
+            cursor.visited += 1;
+            visited += 1;
+
+            let row_id = cursor.list.row_ids[pos];
+            if !seen.insert(row_id) {
+                continue;
+            }
+            if mask.as_ref().is_some_and(|mask| !mask.selected(row_id)) {
+                continue;
+            }
+            let score = cursors
+                .iter()
+                .filter_map(|cursor| cursor.list.get(row_id).map(|value| cursor.weight * value))
+                .sum();
+            top_k.push(row_id, score);
+        }
+        metrics.record_comparisons(visited);
+        Ok(top_k.into_sorted())
+    }
+}
+
+/// Read `(dimension, row_id, value)` triples from a batch of postings
+fn postings_from_batch(batch: &RecordBatch) -> Result<impl Iterator<Item = (i32, u64, f32)> + '_> {
+    let column = |name: &str| {
+        batch.column_by_name(name).ok_or_else(|| {
+            Error::internal(format!("Sparse vector index file has no {} column", name))
+        })
+    };
+    let dims = column(DIMENSION_COL)?.as_primitive::<arrow_array::types::Int32Type>();
+    let row_ids = column(ROW_ID)?.as_primitive::<UInt64Type>();
+    let values = column(VALUE_COL)?.as_primitive::<arrow_array::types::Float32Type>();
+    Ok((0..batch.num_rows()).map(|i| (dims.value(i), row_ids.value(i), values.value(i))))
+}
+
+/// Accumulates postings by dimension
+#[derive(Debug, Default)]
+struct SparsePostingsBuilder {
+    postings: BTreeMap<i32, Vec<(u64, f32)>>,
+}
+
+impl SparsePostingsBuilder {
+    fn add(&mut self, dim: i32, row_id: u64, value: f32) {
+        if value != 0.0 {
+            self.postings.entry(dim).or_default().push((row_id, value));
+        }
+    }
+
+    /// Add the sparse vectors of a training batch
+    fn add_batch(&mut self, batch: &RecordBatch) -> Result<()> {
+        let vectors = batch.column_by_name(VALUE_COLUMN_NAME).ok_or_else(|| {
+            Error::invalid_input(format!(
+                "Sparse vector training data has no {} column",
+                VALUE_COLUMN_NAME
+            ))
+        })?;
+        let vectors = SparseVectorArray::try_from(vectors.as_ref())?;
+        let row_ids = batch
+            .column_by_name(ROW_ID)
+            .ok_or_else(|| Error::internal("Sparse vector training data has no row ids"))?
+            .as_primitive::<UInt64Type>();
+        for (row_id, vector) in row_ids.values().iter().zip(vectors.iter()) {
+            let Some((indices, values)) = vector else {
+                continue;
+            };
+            for (dim, value) in indices.iter().zip(values) {
+                self.add(*dim, *row_id, *value);
+            }
+        }
+        Ok(())
+    }
+
+    async fn train(&mut self, mut data: SendableRecordBatchStream) -> Result<()> {
+        while let Some(batch) = data.try_next().await? {
+            self.add_batch(&batch)?;
+        }
+        Ok(())
+    }
+
+    async fn write(self, store: &dyn IndexStore) -> Result<CreatedIndex> {
+        let schema = postings_schema();
+        let mut writer = store
+            .new_index_file(SPARSE_POSTINGS_FILE, schema.clone())
+            .await?;
+        let index = self.into_index();
+        let mut dims = index.postings.keys().copied().collect::<Vec<_>>();
+        dims.sort_unstable();
+
+        let mut batch = (Vec::new(), Vec::new(), Vec::new());
+        for dim in dims {
+            for (row_id, value) in index.postings[&dim].iter() {
+                batch.0.push(dim);
+                batch.1.push(row_id);
+                batch.2.push(value);
+                if batch.0.len() >= WRITE_BATCH_SIZE {
+                    let (dims, row_ids, values) = std::mem::take(&mut batch);
+                    writer
+                        .write_record_batch(postings_batch(&schema, dims, row_ids, values)?)
+                        .await?;
+                }
+            }
+        }
+        if !batch.0.is_empty() {
+            let (dims, row_ids, values) = batch;
+            writer
+                .write_record_batch(postings_batch(&schema, dims, row_ids, values)?)
+                .await?;
+        }
+        writer.finish().await?;
+
+        Ok(CreatedIndex {
+            index_details: prost_types::Any::from_msg(&pb::SparseVectorIndexDetails::default())
+                .unwrap(),
+            index_version: SPARSE_VECTOR_INDEX_VERSION,
+            files: Some(store.list_files_with_sizes().await?),
+        })
+    }
+
+    fn into_index(self) -> SparseVectorIndex {
+        SparseVectorIndex {
+            postings: self
+                .postings
+                .into_iter()
+                .map(|(dim, postings)| (dim, PostingList::new(postings)))
+                .collect(),
+        }
+    }
+}
+
+fn postings_batch(
+    schema: &Arc<Schema>,
+    dims: Vec<i32>,
+    row_ids: Vec<u64>,
+    values: Vec<f32>,
+) -> Result<RecordBatch> {
+    Ok(RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(Int32Array::from(dims)),
+            Arc::new(UInt64Array::from(row_ids)),
+            Arc::new(Float32Array::from(values)),
+        ],
+    )?)
+}
+
+#[async_trait]
+impl Index for SparseVectorIndex {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_index(self: Arc<Self>) -> Arc<dyn Index> {
+        self
+    }
+
+    fn as_vector_index(self: Arc<Self>) -> Result<Arc<dyn VectorIndex>> {
+        Err(Error::invalid_input_source(
+            "SparseVector is not a vector index".into(),
+        ))
+    }
+
+    async fn prewarm(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn statistics(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::json!({
+            "type": "SparseVector",
+            "num_dimensions": self.postings.len(),
+            "num_postings": self.num_postings(),
+        }))
+    }
+
+    fn index_type(&self) -> IndexType {
+        IndexType::SparseVector
+    }
+
+    async fn calculate_included_frags(&self) -> Result<RoaringBitmap> {
+        let mut frag_ids = RoaringBitmap::new();
+        for list in self.postings.values() {
+            frag_ids.extend(list.row_ids.iter().map(|row_id| (row_id >> 32) as u32));
+        }
+        Ok(frag_ids)
+    }
+}
+
+#[async_trait]
+impl ScalarIndex for SparseVectorIndex {
+    async fn search(
+        &self,
+        _query: &dyn AnyQuery,
+        _metrics: &dyn MetricsCollector,
+    ) -> Result<SearchResult> {
+        Err(Error::not_supported(
+            "SparseVector index can only be used by sparse vector search",
+        ))
+    }
+
+    fn can_remap(&self) -> bool {
+        true
+    }
+
+    async fn remap(
+        &self,
+        mapping: &HashMap<u64, Option<u64>>,
+        dest_store: &dyn IndexStore,
+    ) -> Result<CreatedIndex> {
+        let mut builder = SparsePostingsBuilder::default();
+        for (dim, list) in &self.postings {
+            for (row_id, value) in list.iter() {
+                let row_id = match mapping.get(&row_id) {
+                    Some(Some(new_id)) => *new_id,
+                    Some(None) => continue,
+                    None => row_id,
+                };
+                builder.add(*dim, row_id, value);
+            }
+        }
+        builder.write(dest_store).await
+    }
+
+    async fn update(
+        &self,
+        new_data: SendableRecordBatchStream,
+        dest_store: &dyn IndexStore,
+        old_data_filter: Option<OldIndexDataFilter>,
+    ) -> Result<CreatedIndex> {
+        let mut builder = SparsePostingsBuilder::default();
+        for (dim, list) in &self.postings {
+            let keep = old_data_filter
+                .as_ref()
+                .map(|filter| filter.filter_row_ids(&UInt64Array::from(list.row_ids.clone())));
+            for (i, (row_id, value)) in list.iter().enumerate() {
+                if keep.as_ref().is_none_or(|keep| keep.value(i)) {
+                    builder.add(*dim, row_id, value);
+                }
+            }
+        }
+        builder.train(new_data).await?;
+        builder.write(dest_store).await
+    }
+
+    fn update_criteria(&self) -> UpdateCriteria {
+        UpdateCriteria::only_new_data(TrainingCriteria::new(TrainingOrdering::None).with_row_id())
+    }
+
+    fn derive_index_params(&self) -> Result<ScalarIndexParams> {
+        Ok(ScalarIndexParams::for_builtin(
+            BuiltinIndexType::SparseVector,
+        ))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SparseVectorIndexPlugin;
+
+#[async_trait]
+impl ScalarIndexPlugin for SparseVectorIndexPlugin {
+    fn name(&self) -> &str {
+        "SparseVector"
+    }
+
+    fn new_training_request(
+        &self,
+        _params: &str,
+        field: &Field,
+    ) -> Result<Box<dyn TrainingRequest>> {
+        if !is_sparse_vector_data_type(field.data_type()) {
+            return Err(Error::invalid_input_source(
+                format!(
+                    "SparseVector index can only be created on sparse vector columns. Column has type {:?}",
+                    field.data_type()
+                )
+                .into(),
+            ));
+        }
+
+        Ok(Box::new(DefaultTrainingRequest::new(
+            TrainingCriteria::new(TrainingOrdering::None).with_row_id(),
+        )))
+    }
+
+    async fn train_index(
+        &self,
+        data: SendableRecordBatchStream,
+        index_store: &dyn IndexStore,
+        _request: Box<dyn TrainingRequest>,
+        fragment_ids: Option<Vec<u32>>,
+        _progress: Arc<dyn crate::progress::IndexBuildProgress>,
+    ) -> Result<CreatedIndex> {
+        if fragment_ids.is_some() {
+            return Err(Error::invalid_input_source(
+                "SparseVector index does not support fragment training".into(),
+            ));
+        }
+
+        let mut builder = SparsePostingsBuilder::default();
+        builder.train(data).await?;
+        builder.write(index_store).await
+    }
+
+    fn provides_exact_answer(&self) -> bool {
+        false
+    }
+
+    fn version(&self) -> u32 {
+        SPARSE_VECTOR_INDEX_VERSION
+    }
+
+    fn new_query_parser(
+        &self,
+        _index_name: String,
+        _index_details: &prost_types::Any,
+    ) -> Option<Box<dyn ScalarQueryParser>> {
+        None
+    }
+
+    async fn load_index(
+        &self,
+        index_store: Arc<dyn IndexStore>,
+        _index_details: &prost_types::Any,
+        frag_reuse_index: Option<Arc<FragReuseIndex>>,
+        _cache: &LanceCache,
+    ) -> Result<Arc<dyn ScalarIndex>> {
+        Ok(SparseVectorIndex::load(index_store, frag_reuse_index).await? as Arc<dyn ScalarIndex>)
+    }
+
+    async fn load_statistics(
+        &self,
+        _index_store: Arc<dyn IndexStore>,
+        _index_details: &prost_types::Any,
+    ) -> Result<Option<serde_json::Value>> {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use arrow_array::ArrayRef;
+    use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+    use lance_core::utils::tempfile::TempObjDir;
+    use lance_io::object_store::ObjectStore;
+    use lance_select::RowAddrMask;
+    use rand::{Rng, SeedableRng};
+
+    use crate::metrics::{LocalMetricsCollector, NoOpMetricsCollector};
+    use crate::prefilter::NoFilter;
+    use crate::progress::noop_progress;
+    use crate::scalar::lance_format::LanceIndexStore;
+
+    type Vectors = Vec<Option<(Vec<i32>, Vec<f32>)>>;
+
+    /// Vectors with a long-tailed distribution of dimensions, like the output
+    /// of learned sparse models
+    fn random_vectors(num_rows: usize, seed: u64) -> Vectors {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+        (0..num_rows)
+            .map(|i| {
+                if i % 50 == 7 {
+                    return None;
+                }
+                if i % 50 == 13 {
+                    return Some((vec![], vec![]));
+                }
+                let nnz = rng.random_range(1..20);
+                let mut dims = (0..nnz)
+                    .map(|_| {
+                        let x: f32 = rng.random_range(0.0..1.0);
+                        (x * x * x * 1000.0) as i32
+                    })
+                    .collect::<Vec<_>>();
+                dims.sort_unstable();
+                dims.dedup();
+                let values = dims.iter().map(|_| rng.random_range(0.01..2.0)).collect();
+                Some((dims, values))
+            })
+            .collect()
+    }
+
+    fn training_stream(vectors: &Vectors, first_row_id: u64) -> SendableRecordBatchStream {
+        let array = SparseVectorArray::try_from_iter(vectors.clone()).unwrap();
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(
+                VALUE_COLUMN_NAME,
+                array.clone().into_inner().data_type().clone(),
+                true,
+            ),
+            Field::new(ROW_ID, DataType::UInt64, false),
+        ]));
+        let row_ids =
+            UInt64Array::from_iter_values((0..vectors.len() as u64).map(|i| first_row_id + i));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(array.into_inner()) as ArrayRef,
+                Arc::new(row_ids) as ArrayRef,
+            ],
+        )
+        .unwrap();
+        Box::pin(RecordBatchStreamAdapter::new(
+            schema,
+            futures::stream::once(std::future::ready(Ok(batch))),
+        ))
+    }
+
+    async fn train(vectors: &Vectors) -> (TempObjDir, Arc<dyn IndexStore>, Arc<SparseVectorIndex>) {
+        let tmpdir = TempObjDir::default();
+        let store: Arc<dyn IndexStore> = Arc::new(LanceIndexStore::new(
+            Arc::new(ObjectStore::local()),
+            tmpdir.clone(),
+            Arc::new(LanceCache::no_cache()),
+        ));
+        let plugin = SparseVectorIndexPlugin;
+        let field = Field::new(
+            VALUE_COLUMN_NAME,
+            lance_arrow::sparse::sparse_vector_data_type(),
+            true,
+        );
+        let request = plugin.new_training_request("{}", &field).unwrap();
+        plugin
+            .train_index(
+                training_stream(vectors, 0),
+                store.as_ref(),
+                request,
+                None,
+                noop_progress(),
+            )
+            .await
+            .unwrap();
+        let index = SparseVectorIndex::load(store.clone(), None).await.unwrap();
+        (tmpdir, store, index)
+    }
+
+    fn brute_force(
+        vectors: &Vectors,
+        query: &SparseQuery,
+        k: usize,
+        selected: impl Fn(u64) -> bool,
+    ) -> (Vec<u64>, Vec<f32>) {
+        let mut top_k = SparseTopK::new(k);
+        for (row_id, vector) in vectors.iter().enumerate() {
+            if let Some((indices, values)) = vector
+                && selected(row_id as u64)
+            {
+                top_k.push(row_id as u64, query.dot(indices, values));
+            }
+        }
+        top_k.into_sorted()
+    }
+
+    fn assert_same_results(actual: (Vec<u64>, Vec<f32>), expected: (Vec<u64>, Vec<f32>)) {
+        assert_eq!(actual.0, expected.0);
+        for (actual, expected) in actual.1.iter().zip(&expected.1) {
+            assert!((actual - expected).abs() < 1e-4, "{actual} != {expected}");
+        }
+    }
+
+    #[test]
+    fn test_sparse_query() {
+        let query = SparseQuery::try_new(&[7, 2, 5], &[1.0, 2.0, 0.0]).unwrap();
+        assert_eq!(query.indices(), &[2, 7]);
+        assert_eq!(query.values(), &[2.0, 1.0]);
+        assert_eq!(query.dot(&[1, 2, 7], &[10.0, 0.5, 3.0]), 4.0);
+        assert_eq!(query.dot(&[], &[]), 0.0);
+
+        assert!(SparseQuery::try_new(&[1, 1], &[1.0, 2.0]).is_err());
+        assert!(SparseQuery::try_new(&[1], &[1.0, 2.0]).is_err());
+        assert!(SparseQuery::try_new(&[], &[]).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_search_matches_brute_force() {
+        let vectors = random_vectors(2000, 42);
+        let (_tmpdir, _store, index) = train(&vectors).await;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        for k in [1, 10, 100] {
+            for _ in 0..10 {
+                let dims = (0..rng.random_range(1..8))
+                    .map(|_| rng.random_range(0..100))
+                    .collect::<HashSet<i32>>()
+                    .into_iter()
+                    .collect::<Vec<_>>();
+                let weights = dims
+                    .iter()
+                    .map(|_| rng.random_range(-0.5..2.0))
+                    .collect::<Vec<f32>>();
+                let query = SparseQuery::try_new(&dims, &weights).unwrap();
+                let actual = index
+                    .search(&query, k, &NoFilter, &NoOpMetricsCollector)
+                    .unwrap();
+                assert!(actual.1.iter().all(|score| *score > 0.0));
+                assert_same_results(actual, brute_force(&vectors, &query, k, |_| true));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_prunes_postings() {
+        let vectors = random_vectors(5000, 3);
+        let (_tmpdir, _store, index) = train(&vectors).await;
+
+        // Frequent dimensions, so the lists are long
+        let query = SparseQuery::try_new(&[0, 1, 2], &[1.0, 0.8, 0.6]).unwrap();
+        let total = [0, 1, 2]
+            .iter()
+            .map(|dim| index.postings[dim].len())
+            .sum::<usize>();
+        let metrics = LocalMetricsCollector::default();
+        let actual = index.search(&query, 10, &NoFilter, &metrics).unwrap();
+        let visited = metrics
+            .comparisons
+            .load(std::sync::atomic::Ordering::Relaxed);
+        assert!(visited < total, "visited {visited} of {total} postings");
+        assert_same_results(actual, brute_force(&vectors, &query, 10, |_| true));
+    }
+
+    struct MaskFilter(Arc<RowAddrMask>);
+
+    #[async_trait]
+    impl PreFilter for MaskFilter {
+        async fn wait_for_ready(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn is_empty(&self) -> bool {
+            false
+        }
+
+        fn mask(&self) -> Arc<RowAddrMask> {
+            self.0.clone()
+        }
+
+        fn filter_row_ids<'a>(&self, row_ids: Box<dyn Iterator<Item = &'a u64> + 'a>) -> Vec<u64> {
+            self.0.selected_indices(row_ids)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_with_prefilter() {
+        let vectors = random_vectors(1000, 11);
+        let (_tmpdir, _store, index) = train(&vectors).await;
+
+        let allowed = lance_select::RowAddrTreeMap::from_iter((0..1000).filter(|i| i % 3 == 0));
+        let filter = MaskFilter(Arc::new(RowAddrMask::from_allowed(allowed)));
+        let query = SparseQuery::try_new(&[0, 3, 10], &[1.0, 1.0, 1.0]).unwrap();
+        let actual = index
+            .search(&query, 20, &filter, &NoOpMetricsCollector)
+            .unwrap();
+        assert_same_results(
+            actual,
+            brute_force(&vectors, &query, 20, |row_id| row_id % 3 == 0),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_empty_vectors_and_queries() {
+        let vectors: Vectors = vec![Some((vec![], vec![])), None, Some((vec![4], vec![1.0]))];
+        let (_tmpdir, _store, index) = train(&vectors).await;
+        assert_eq!(index.num_postings(), 1);
+
+        let empty = SparseQuery::try_new(&[], &[]).unwrap();
+        let (row_ids, _) = index
+            .search(&empty, 10, &NoFilter, &NoOpMetricsCollector)
+            .unwrap();
+        assert!(row_ids.is_empty());
+
+        // Only rows sharing a dimension with the query match
+        let query = SparseQuery::try_new(&[4, 9], &[2.0, 1.0]).unwrap();
+        let (row_ids, scores) = index
+            .search(&query, 10, &NoFilter, &NoOpMetricsCollector)
+            .unwrap();
+        assert_eq!(row_ids, vec![2]);
+        assert_eq!(scores, vec![2.0]);
+
+        // An index of empty vectors only
+        let (_tmpdir, _store, index) = train(&vec![Some((vec![], vec![]))]).await;
+        assert_eq!(index.num_postings(), 0);
+        let (row_ids, _) = index
+            .search(&query, 10, &NoFilter, &NoOpMetricsCollector)
+            .unwrap();
+        assert!(row_ids.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_update_and_remap() {
+        let vectors = random_vectors(200, 5);
+        let (_tmpdir, _store, index) = train(&vectors).await;
+
+        // Append rows from a second fragment
+        let new_vectors = random_vectors(100, 6);
+        let new_dir = TempObjDir::default();
+        let new_store = LanceIndexStore::new(
+            Arc::new(ObjectStore::local()),
+            new_dir.clone(),
+            Arc::new(LanceCache::no_cache()),
+        );
+        index
+            .update(training_stream(&new_vectors, 1 << 32), &new_store, None)
+            .await
+            .unwrap();
+        let updated = SparseVectorIndex::load(Arc::new(new_store), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            updated.calculate_included_frags().await.unwrap(),
+            RoaringBitmap::from_iter([0, 1])
+        );
+        assert_eq!(
+            updated.num_postings(),
+            index.num_postings()
+                + new_vectors
+                    .iter()
+                    .flatten()
+                    .map(|(dims, _)| dims.len())
+                    .sum::<usize>()
+        );
+
+        // Remap row 0 away and move row 1
+        let mapping = HashMap::from([(0, None), (1, Some(1000))]);
+        let remap_dir = TempObjDir::default();
+        let remap_store = LanceIndexStore::new(
+            Arc::new(ObjectStore::local()),
+            remap_dir.clone(),
+            Arc::new(LanceCache::no_cache()),
+        );
+        index.remap(&mapping, &remap_store).await.unwrap();
+        let remapped = SparseVectorIndex::load(Arc::new(remap_store), None)
+            .await
+            .unwrap();
+        let (dims, values) = vectors[1].clone().unwrap();
+        let query = SparseQuery::try_new(&dims, &values).unwrap();
+        let (row_ids, _) = remapped
+            .search(&query, 300, &NoFilter, &NoOpMetricsCollector)
+            .unwrap();
+        assert!(row_ids.contains(&1000));
+        assert!(!row_ids.contains(&0) && !row_ids.contains(&1));
+    }
+}
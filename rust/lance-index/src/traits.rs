@@ -17,6 +17,8 @@ pub struct IndexCriteria<'a> {
     pub must_support_fts: bool,
     /// If true, only consider indices that support exact equality
     pub must_support_exact_equality: bool,
+    /// If true, only consider indices that support sparse vector search
+    pub must_support_sparse_search: bool,
 }
 
 impl<'a> IndexCriteria<'a> {
@@ -47,6 +49,12 @@ impl<'a> IndexCriteria<'a> {
         self.must_support_exact_equality = true;
         self
     }
+
+    /// Only consider indices that support sparse vector search
+    pub fn supports_sparse_search(mut self) -> Self {
+        self.must_support_sparse_search = true;
+        self
+    }
 }
 
 #[deprecated(since = "0.39.0", note = "Use IndexCriteria instead")]
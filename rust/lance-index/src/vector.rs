@@ -82,6 +82,40 @@ pub static CENTROID_DIST_FIELD: LazyLock<arrow_schema::Field> = LazyLock::new(||
 
 pub const DEFAULT_QUERY_PARALLELISM: i32 = 0;
 
+/// What a flat vector search does with rows whose distance to the query is
+/// NaN, e.g. because the stored vector contains NaN.
+///
+/// Vectors that are not finite are left out when an index is built, so this
+/// only applies to rows that are searched without an index.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NanHandling {
+    /// Leave the rows out of the results.
+    #[default]
+    Drop,
+    /// Keep the rows, ranked after every row with a distance. They are still
+    /// left out when a distance range is set.
+    SortLast,
+}
+
+impl NanHandling {
+    /// The distance `distance` is ranked by, or `None` if the row is not a
+    /// match for the given distance range.
+    pub fn rank(self, distance: f32, lower: Option<f32>, upper: Option<f32>) -> Option<f32> {
+        if distance.is_nan() {
+            // NaN may carry either sign. Positive NaN sorts after every other
+            // value, negative NaN before.
+            return (self == Self::SortLast && lower.is_none() && upper.is_none())
+                .then_some(f32::NAN);
+        }
+        if lower.is_some_and(|lower| distance < lower)
+            || upper.is_some_and(|upper| distance >= upper)
+        {
+            return None;
+        }
+        Some(distance)
+    }
+}
+
 /// Query parameters for the vector indices
 
 #[derive(Debug, Clone)]
@@ -223,6 +257,28 @@ pub trait VectorIndex: Send + Sync + std::fmt::Debug + Index {
         metrics: &dyn MetricsCollector,
     ) -> Result<RecordBatch>;
 
+    /// Search a single partition for the nearest neighbors of several queries.
+    ///
+    /// Returns one batch per query, in the order of `queries`.  The default
+    /// implementation calls [`VectorIndex::search_in_partition`] for each query.
+    /// Implementations can override this to load the partition only once.
+    async fn search_in_partition_batch(
+        &self,
+        partition_id: usize,
+        queries: &[Query],
+        pre_filter: Arc<dyn PreFilter>,
+        metrics: &dyn MetricsCollector,
+    ) -> Result<Vec<RecordBatch>> {
+        let mut batches = Vec::with_capacity(queries.len());
+        for query in queries {
+            batches.push(
+                self.search_in_partition(partition_id, query, pre_filter.clone(), metrics)
+                    .await?,
+            );
+        }
+        Ok(batches)
+    }
+
     /// Asynchronously prepare a single-partition search so the CPU-heavy portion
     /// can be executed separately.
     async fn prepare_partition_search(
@@ -10,7 +10,7 @@ use crate::vector::storage::{DistCalculator, VectorStore};
 use crate::vector::utils::do_prefetch;
 use arrow::array::AsArray;
 use arrow::compute::concat_batches;
-use arrow::datatypes::{Float16Type, Float64Type, UInt8Type};
+use arrow::datatypes::{Float16Type, Float64Type, Int8Type, UInt8Type};
 use arrow_array::ArrowPrimitiveType;
 use arrow_array::{
     Array, ArrayRef, FixedSizeListArray, RecordBatch, UInt64Array,
@@ -458,6 +458,7 @@ pub enum FlatFloatDistanceCalc<'a> {
     Float16(FlatDistanceCal<'a, Float16Type>),
     Float32(FlatDistanceCal<'a, Float32Type>),
     Float64(FlatDistanceCal<'a, Float64Type>),
+    Int8(FlatDistanceCal<'a, Int8Type>),
 }
 
 impl<'a> FlatFloatDistanceCalc<'a> {
@@ -478,6 +479,11 @@ impl<'a> FlatFloatDistanceCalc<'a> {
                 query,
                 distance_type,
             )),
+            DataType::Int8 => Self::Int8(FlatDistanceCal::<Int8Type>::new(
+                vectors,
+                query,
+                distance_type,
+            )),
             dt => panic!("flat float storage does not support data type {dt}"),
         }
     }
@@ -499,6 +505,11 @@ impl<'a> FlatFloatDistanceCalc<'a> {
                 id,
                 distance_type,
             )),
+            DataType::Int8 => Self::Int8(FlatDistanceCal::<Int8Type>::new_from_id(
+                vectors,
+                id,
+                distance_type,
+            )),
             dt => panic!("flat float storage does not support data type {dt}"),
         }
     }
@@ -510,6 +521,7 @@ impl DistCalculator for FlatFloatDistanceCalc<'_> {
             Self::Float16(calc) => calc.distance(id),
             Self::Float32(calc) => calc.distance(id),
             Self::Float64(calc) => calc.distance(id),
+            Self::Int8(calc) => calc.distance(id),
         }
     }
 
@@ -518,6 +530,7 @@ impl DistCalculator for FlatFloatDistanceCalc<'_> {
             Self::Float16(calc) => calc.distance_all(k_hint),
             Self::Float32(calc) => calc.distance_all(k_hint),
             Self::Float64(calc) => calc.distance_all(k_hint),
+            Self::Int8(calc) => calc.distance_all(k_hint),
         }
     }
 
@@ -526,6 +539,7 @@ impl DistCalculator for FlatFloatDistanceCalc<'_> {
             Self::Float16(calc) => calc.prefetch(id),
             Self::Float32(calc) => calc.prefetch(id),
             Self::Float64(calc) => calc.prefetch(id),
+            Self::Int8(calc) => calc.prefetch(id),
         }
     }
 }
@@ -534,7 +548,7 @@ impl DistCalculator for FlatFloatDistanceCalc<'_> {
 mod tests {
     use super::*;
 
-    use arrow_array::{Float16Array, Float64Array};
+    use arrow_array::{Float16Array, Float64Array, Int8Array};
     use half::f16;
     use lance_arrow::FixedSizeListArrayExt;
 
@@ -583,4 +597,18 @@ mod tests {
         assert_eq!(distances[0], 0.0);
         assert!((distances[1] - 25.0).abs() < 1e-6);
     }
+
+    #[test]
+    fn test_flat_float_storage_distance_i8() {
+        let values = Int8Array::from(vec![1, 2, -128, 127]);
+        let vectors = FixedSizeListArray::try_new_from_values(values, 2).unwrap();
+        let storage = FlatFloatStorage::new(vectors, DistanceType::L2);
+        let query: ArrayRef = Arc::new(Int8Array::from(vec![1, 2]));
+
+        let calc = storage.dist_calculator(query, 0.0);
+        let distances = calc.distance_all(2);
+
+        assert_eq!(distances, vec![0.0, (129 * 129 + 125 * 125) as f32]);
+        assert_eq!(calc.distance(1), distances[1]);
+    }
 }
@@ -69,6 +69,13 @@ pub struct HnswBuildParams {
 
     /// number of vectors ahead to prefetch while building the graph
     pub prefetch_distance: Option<usize>,
+
+    /// max number of neighbors kept per node on the levels above 0,
+    /// `m` if not set
+    pub m_max: Option<usize>,
+
+    /// max number of neighbors kept per node on level 0, `2 * m` if not set
+    pub m_max0: Option<usize>,
 }
 
 impl From<&HnswBuildParams> for crate::pb::HnswParameters {
@@ -77,6 +84,8 @@ impl From<&HnswBuildParams> for crate::pb::HnswParameters {
             max_connections: params.m as u32,
             construction_ef: params.ef_construction as u32,
             max_level: params.max_level as u32,
+            max_neighbors: params.m_max.unwrap_or_default() as u32,
+            max_neighbors_level0: params.m_max0.unwrap_or_default() as u32,
         }
     }
 }
@@ -88,6 +97,8 @@ impl Default for HnswBuildParams {
             m: 20,
             ef_construction: 150,
             prefetch_distance: Some(2),
+            m_max: None,
+            m_max0: None,
         }
     }
 }
@@ -116,6 +127,31 @@ impl HnswBuildParams {
         self
     }
 
+    /// The maximum number of neighbors a node keeps on the levels above 0.
+    /// Extra edges are pruned with the neighbor selection heuristic.
+    ///
+    /// The default value is `m`.
+    pub fn m_max(mut self, m_max: usize) -> Self {
+        self.m_max = Some(m_max);
+        self
+    }
+
+    /// The maximum number of neighbors a node keeps on level 0.
+    ///
+    /// The default value is `2 * m`.
+    pub fn m_max0(mut self, m_max0: usize) -> Self {
+        self.m_max0 = Some(m_max0);
+        self
+    }
+
+    /// The maximum number of neighbors a node keeps on the given level.
+    pub fn max_neighbors(&self, level: u16) -> usize {
+        match level {
+            0 => self.m_max0.unwrap_or(self.m * 2),
+            _ => self.m_max.unwrap_or(self.m),
+        }
+    }
+
     /// Build the HNSW index from the given data.
     ///
     /// # Parameters
@@ -640,10 +676,7 @@ impl HnswBuilder {
         for (level, pruned_neighbors) in pruned_neighbors_per_level.iter().enumerate() {
             for unpruned_edge in pruned_neighbors {
                 let level = level as u16;
-                let m_max = match level {
-                    0 => self.params.m * 2,
-                    _ => self.params.m,
-                };
+                let m_max = self.params.max_neighbors(level);
                 if unpruned_edge.dist
                     < nodes[unpruned_edge.id as usize]
                         .read()
@@ -685,10 +718,7 @@ impl HnswBuilder {
     }
 
     fn prune(&self, storage: &impl VectorStore, builder_node: &mut GraphBuilderNode, level: u16) {
-        let m_max = match level {
-            0 => self.params.m * 2,
-            _ => self.params.m,
-        };
+        let m_max = self.params.max_neighbors(level);
 
         let neighbors_ranked = &mut builder_node.level_neighbors_ranked[level as usize];
         if neighbors_ranked.len() <= m_max {
@@ -960,6 +990,8 @@ impl DeepSizeOf for HnswGraph {
 
 #[derive(Debug, Clone, Copy)]
 pub struct HnswQueryParams {
+    /// Size of the dynamic candidate list while searching, must be at least `k`.
+    /// `0` lets the index pick `max(k, ef_construction)`.
     pub ef: usize,
     pub lower_bound: Option<f32>,
     pub upper_bound: Option<f32>,
@@ -968,9 +1000,8 @@ pub struct HnswQueryParams {
 
 impl From<&Query> for HnswQueryParams {
     fn from(query: &Query) -> Self {
-        let k = query.k * query.refine_factor.unwrap_or(1) as usize;
         Self {
-            ef: query.ef.unwrap_or(k + k / 2),
+            ef: query.ef.unwrap_or(0),
             lower_bound: query.lower_bound,
             upper_bound: query.upper_bound,
             dist_q_c: query.dist_q_c,
@@ -1134,11 +1165,17 @@ impl IvfSubIndex for HNSW {
         prefilter: Arc<dyn PreFilter>,
         _metrics: &dyn MetricsCollector,
     ) -> Result<RecordBatch> {
-        if params.ef < k {
-            return Err(Error::index(
-                "ef must be greater than or equal to k".to_string(),
-            ));
-        }
+        let ef = match params.ef {
+            0 => k.max(self.inner.params.ef_construction),
+            ef if ef < k => {
+                return Err(Error::invalid_input(format!(
+                    "ef ({}) must be greater than or equal to k ({})",
+                    ef, k
+                )));
+            }
+            ef => ef,
+        };
+        let params = HnswQueryParams { ef, ..params };
 
         let schema = VECTOR_RESULT_SCHEMA.clone();
         if self.is_empty() {
@@ -1323,6 +1360,8 @@ mod tests {
     use rstest::rstest;
 
     use super::HnswGraph;
+    use crate::metrics::NoOpMetricsCollector;
+    use crate::prefilter::NoFilter;
     use crate::scalar::IndexWriter;
     use crate::vector::storage::{DistCalculator, VectorStore};
     use crate::vector::v3::subindex::IvfSubIndex;
@@ -1783,4 +1822,150 @@ mod tests {
             builder.deep_size_of(),
         );
     }
+
+    #[tokio::test]
+    async fn test_recall_improves_with_ef() {
+        const DIM: usize = 32;
+        const TOTAL: usize = 4096;
+        const NUM_QUERIES: usize = 32;
+        let fsl =
+            FixedSizeListArray::try_new_from_values(generate_random_array(TOTAL * DIM), DIM as i32)
+                .unwrap();
+        let store = Arc::new(FlatFloatStorage::new(fsl.clone(), DistanceType::L2));
+        // A deliberately weak graph, so the query-time beam width dominates recall.
+        let hnsw = HNSW::index_vectors(
+            store.as_ref(),
+            HnswBuildParams::default().num_edges(4).ef_construction(8),
+        )
+        .unwrap();
+
+        let k = 10;
+        let truths = (0..NUM_QUERIES)
+            .map(|i| {
+                brute_force_topk(store.as_ref(), fsl.value(i * 97), k)
+                    .into_iter()
+                    .collect::<std::collections::HashSet<_>>()
+            })
+            .collect::<Vec<_>>();
+        let recalls = [10, 40, 160, 640]
+            .into_iter()
+            .map(|ef| {
+                let params = HnswQueryParams {
+                    ef,
+                    lower_bound: None,
+                    upper_bound: None,
+                    dist_q_c: 0.0,
+                };
+                let hits = truths
+                    .iter()
+                    .enumerate()
+                    .map(|(i, truth)| {
+                        hnsw.search_basic(fsl.value(i * 97), k, &params, None, store.as_ref())
+                            .unwrap()
+                            .iter()
+                            .filter(|n| truth.contains(&n.id))
+                            .count()
+                    })
+                    .sum::<usize>();
+                hits as f32 / (k * NUM_QUERIES) as f32
+            })
+            .collect::<Vec<_>>();
+
+        assert!(
+            recalls.windows(2).all(|w| w[0] <= w[1]),
+            "recall should not drop as ef grows: {recalls:?}"
+        );
+        assert!(recalls[3] > recalls[0], "{recalls:?}");
+    }
+
+    #[tokio::test]
+    async fn test_search_ef() {
+        const DIM: usize = 16;
+        const TOTAL: usize = 512;
+        let fsl =
+            FixedSizeListArray::try_new_from_values(generate_random_array(TOTAL * DIM), DIM as i32)
+                .unwrap();
+        let store = Arc::new(FlatFloatStorage::new(fsl.clone(), DistanceType::L2));
+        let hnsw = HNSW::index_vectors(
+            store.as_ref(),
+            HnswBuildParams::default().num_edges(8).ef_construction(20),
+        )
+        .unwrap();
+
+        let search = |ef: usize, k: usize| {
+            hnsw.search(
+                fsl.value(0),
+                k,
+                HnswQueryParams {
+                    ef,
+                    lower_bound: None,
+                    upper_bound: None,
+                    dist_q_c: 0.0,
+                },
+                store.as_ref(),
+                Arc::new(NoFilter),
+                &NoOpMetricsCollector,
+            )
+        };
+
+        // ef unset falls back to max(k, ef_construction)
+        assert_eq!(search(0, 10).unwrap().num_rows(), 10);
+        assert_eq!(search(0, 50).unwrap().num_rows(), 50);
+        assert_eq!(search(10, 10).unwrap().num_rows(), 10);
+
+        let err = search(5, 10).unwrap_err();
+        assert!(
+            matches!(err, lance_core::Error::InvalidInput { .. }),
+            "unexpected error: {err}"
+        );
+        assert!(err.to_string().contains("ef (5)"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_max_neighbors() {
+        const DIM: usize = 16;
+        const TOTAL: usize = 1024;
+        let fsl =
+            FixedSizeListArray::try_new_from_values(generate_random_array(TOTAL * DIM), DIM as i32)
+                .unwrap();
+        let store = Arc::new(FlatFloatStorage::new(fsl, DistanceType::L2));
+        let params = HnswBuildParams::default()
+            .num_edges(8)
+            .ef_construction(40)
+            .m_max(3)
+            .m_max0(5);
+        assert_eq!(params.max_neighbors(0), 5);
+        assert_eq!(params.max_neighbors(1), 3);
+        assert_eq!(HnswBuildParams::default().num_edges(8).max_neighbors(0), 16);
+        assert_eq!(HnswBuildParams::default().num_edges(8).max_neighbors(2), 8);
+
+        let hnsw = HNSW::index_vectors(store.as_ref(), params).unwrap();
+        let HnswGraph::Built(nodes) = &hnsw.inner.graph else {
+            panic!("expected a built graph");
+        };
+        for node in nodes.iter() {
+            for (level, neighbors) in node.level_neighbors.iter().enumerate() {
+                let m_max = if level == 0 { 5 } else { 3 };
+                assert!(
+                    neighbors.len() <= m_max,
+                    "level {level} has {} neighbors",
+                    neighbors.len()
+                );
+            }
+        }
+
+        // The pruning limits are persisted in the HNSW metadata.
+        let loaded = HNSW::load(hnsw.to_batch().unwrap()).unwrap();
+        assert_eq!(loaded.metadata().params.m_max, Some(3));
+        assert_eq!(loaded.metadata().params.m_max0, Some(5));
+
+        // Metadata written before the limits existed still decodes.
+        let params: HnswBuildParams = serde_json::from_str(
+            r#"{"max_level":7,"m":20,"ef_construction":150,"prefetch_distance":2}"#,
+        )
+        .unwrap();
+        assert_eq!(params.m_max, None);
+        assert_eq!(params.m_max0, None);
+        assert_eq!(params.max_neighbors(0), 40);
+    }
 }
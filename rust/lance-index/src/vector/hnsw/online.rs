@@ -310,11 +310,7 @@ impl OnlineHnswBuilder {
         // Add reverse edges to chosen neighbors, prune them too.
         for (level, pruned_neighbors) in pruned_neighbors_per_level.iter().enumerate() {
             let level = level as u16;
-            let m_max = if level == 0 {
-                self.params.m * 2
-            } else {
-                self.params.m
-            };
+            let m_max = self.params.max_neighbors(level);
             for unpruned_edge in pruned_neighbors {
                 let chosen = &nodes[unpruned_edge.id as usize];
                 if unpruned_edge.dist < chosen.cutoff(level, m_max) {
@@ -368,11 +364,7 @@ impl OnlineHnswBuilder {
     }
 
     fn prune(&self, storage: &impl VectorStore, node: &OnlineGraphBuilderNode, level: u16) {
-        let m_max = if level == 0 {
-            self.params.m * 2
-        } else {
-            self.params.m
-        };
+        let m_max = self.params.max_neighbors(level);
 
         let mut ranked = node
             .level_neighbors_ranked
@@ -4,15 +4,18 @@
 //! Build IVF model
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use arrow_array::cast::AsArray;
 use arrow_array::{Array, FixedSizeListArray, UInt32Array, UInt64Array};
+use arrow_schema::DataType;
 use futures::TryStreamExt;
 use object_store::path::Path;
 
 use lance_core::error::{Error, Result};
 use lance_io::stream::RecordBatchStream;
+use lance_linalg::distance::DistanceType;
 
 /// Parameters to build IVF partitions
 #[derive(Debug, Clone)]
@@ -33,6 +36,13 @@ pub struct IvfBuildParams {
     /// Use provided IVF centroids.
     pub centroids: Option<Arc<FixedSizeListArray>>,
 
+    /// Load the IVF centroids from a Lance dataset instead.
+    ///
+    /// The dataset must have exactly one FixedSizeList column, with one row
+    /// per partition. It is opened with `storage_options`. Mutually exclusive
+    /// with `centroids`.
+    pub centroids_file: Option<String>,
+
     /// Retrain centroids.
     /// If true, the centroids will be retrained based on provided `centroids`.
     pub retrain: bool,
@@ -81,7 +91,22 @@ pub struct IvfBuildParams {
 
     pub shuffle_partition_concurrency: usize,
 
-    /// Storage options used to load precomputed partitions.
+    /// Bytes of partitioned vectors the shuffle buffers in memory before it
+    /// sorts them by partition and spills them to disk.
+    ///
+    /// Lower it to bound the memory of the shuffle on very large datasets.
+    /// Defaults to `LANCE_SHUFFLE_BATCH_BYTES`, or 128 MiB if that is unset.
+    pub shuffle_memory_budget_bytes: Option<usize>,
+
+    /// Directory the shuffle spills to, the system temporary directory by
+    /// default.
+    ///
+    /// Each build writes to its own sub-directory, which is removed once the
+    /// build finishes or fails.
+    pub shuffle_temp_dir: Option<PathBuf>,
+
+    /// Storage options used to load precomputed partitions, and the
+    /// centroids and codebook files.
     pub storage_options: Option<HashMap<String, String>>,
 }
 
@@ -92,6 +117,7 @@ impl Default for IvfBuildParams {
             target_partition_size: None,
             max_iters: 50,
             centroids: None,
+            centroids_file: None,
             retrain: false,
             sample_rate: 256, // See faiss
             streaming_sample_rate: None,
@@ -101,6 +127,8 @@ impl Default for IvfBuildParams {
             precomputed_shuffle_buffers: None,
             shuffle_partition_batches: 1024 * 10,
             shuffle_partition_concurrency: 2,
+            shuffle_memory_budget_bytes: None,
+            shuffle_temp_dir: None,
             storage_options: None,
         }
     }
@@ -140,6 +168,51 @@ impl IvfBuildParams {
             ..Default::default()
         })
     }
+
+    /// Create a new instance of [`IvfBuildParams`] with centroids loaded from
+    /// a Lance dataset, see [`Self::centroids_file`].
+    pub fn with_centroids_file(num_partitions: usize, uri: impl Into<String>) -> Self {
+        Self {
+            num_partitions: Some(num_partitions),
+            centroids_file: Some(uri.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Check the provided `centroids` fit vectors of `dim` dimensions compared
+    /// with `distance_type`, so a mismatch fails before any data is read.
+    pub fn validate_centroids(&self, dim: usize, distance_type: DistanceType) -> Result<()> {
+        let Some(centroids) = self.centroids.as_deref() else {
+            return Ok(());
+        };
+        if centroids.value_length() as usize != dim {
+            return Err(Error::invalid_input(format!(
+                "IVF centroids have {} dimensions but the vectors have {dim}",
+                centroids.value_length()
+            )));
+        }
+        if let Some(num_partitions) = self.num_partitions
+            && num_partitions != centroids.len()
+        {
+            return Err(Error::invalid_input(format!(
+                "num_partitions is {num_partitions} but {} IVF centroids were provided",
+                centroids.len()
+            )));
+        }
+        if centroids.null_count() > 0 || centroids.values().null_count() > 0 {
+            return Err(Error::invalid_input("IVF centroids must not contain nulls"));
+        }
+        match (distance_type, centroids.value_type()) {
+            (DistanceType::Hamming, DataType::UInt8) => Ok(()),
+            (DistanceType::Hamming, value_type) => Err(Error::invalid_input(format!(
+                "IVF centroids for hamming distance must be UInt8, got {value_type}"
+            ))),
+            (_, DataType::Float16 | DataType::Float32 | DataType::Float64) => Ok(()),
+            (distance_type, value_type) => Err(Error::invalid_input(format!(
+                "IVF centroids of type {value_type} do not support {distance_type} distance"
+            ))),
+        }
+    }
 }
 
 pub fn recommended_num_partitions(num_rows: usize, target_partition_size: usize) -> usize {
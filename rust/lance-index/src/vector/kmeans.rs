@@ -27,7 +27,8 @@ use arrow_ord::sort::sort_to_indices;
 use arrow_schema::{ArrowError, DataType};
 use bitvec::prelude::*;
 use lance_arrow::FixedSizeListArrayExt;
-use lance_core::utils::tokio::get_num_compute_intensive_cpus;
+use lance_core::utils::tokio::{compute_parallelism, compute_parallelism_limit};
+use lance_core::utils::tracing::TRACE_INDEX_BUILD;
 use lance_linalg::distance::hamming::{hamming, hamming_distance_batch};
 use lance_linalg::distance::{DistanceType, Normalize, dot_distance_batch};
 use lance_linalg::kernels::{argmin_value_float, argmin_value_float_with_bias};
@@ -401,7 +402,7 @@ where
     ) -> KMeans {
         let mut centroids = vec![T::Native::zero(); k * dimension];
 
-        let mut num_cpus = get_num_compute_intensive_cpus();
+        let mut num_cpus = compute_parallelism();
         if k < num_cpus || k < 16 {
             num_cpus = 1;
         }
@@ -795,6 +796,7 @@ impl KMeans {
                     data.value_type()
                 )))?;
 
+        let _span = tracing::debug_span!("kmeans", k, num_vectors = n, dimension).entered();
         let mut best_kmeans = Self::empty(dimension, params.distance_type);
         let mut cluster_sizes = vec![0; k];
         let mut adjusted_balance_factor = f32::MAX;
@@ -851,6 +853,14 @@ impl KMeans {
                     compute_cluster_sizes(&membership, &radius, &losses, &mut cluster_sizes);
                 let balance_loss = compute_balance_loss(&cluster_sizes, n, balance_factor);
                 let last_loss = losses.iter().sum::<f64>() + balance_loss as f64;
+                tracing::debug!(
+                    target: TRACE_INDEX_BUILD,
+                    iteration = i,
+                    max_iters = params.max_iters,
+                    redo,
+                    inertia = last_loss,
+                    "kmeans iteration"
+                );
 
                 kmeans = Algo::to_kmeans(
                     data.values(),
@@ -1513,10 +1523,29 @@ where
     let data = FixedSizeListArray::try_new_from_values(data, dimension as i32)?;
 
     params.balance_factor /= data.len() as f32;
-    let model = KMeans::new_with_params(&data, k, &params)?;
+    let model = with_capped_rayon(|| KMeans::new_with_params(&data, k, &params))?;
     Ok(model)
 }
 
+/// Run `f` on a rayon pool sized to the enclosing compute parallelism limit, if any
+///
+/// Without a limit `f` runs on the global rayon pool.
+fn with_capped_rayon<R: Send>(f: impl FnOnce() -> R + Send) -> R {
+    let Some(limit) = compute_parallelism_limit() else {
+        return f();
+    };
+    match rayon::ThreadPoolBuilder::new()
+        .num_threads(limit.min(compute_parallelism()))
+        .build()
+    {
+        Ok(pool) => pool.install(f),
+        Err(e) => {
+            warn!("Failed to build a rayon pool of {limit} threads for kmeans: {e}");
+            f()
+        }
+    }
+}
+
 #[inline]
 pub fn compute_partition<T: Float + L2 + Dot>(
     centroids: &[T],
@@ -1553,6 +1582,33 @@ mod tests {
     use lance_linalg::distance::l2;
     use lance_linalg::kernels::argmin;
 
+    #[tokio::test]
+    async fn test_kmeans_respects_compute_parallelism() {
+        use lance_core::utils::tokio::with_compute_parallelism;
+        use std::collections::HashSet;
+        use std::sync::Mutex;
+
+        let threads = with_compute_parallelism(1, async {
+            with_capped_rayon(|| {
+                let threads = Mutex::new(HashSet::new());
+                (0..10_000).into_par_iter().for_each(|_| {
+                    threads.lock().unwrap().insert(std::thread::current().id());
+                });
+                threads.into_inner().unwrap()
+            })
+        })
+        .await;
+        assert_eq!(threads.len(), 1);
+
+        let data = generate_random_array(32 * 1000);
+        let kmeans = with_compute_parallelism(1, async {
+            train_kmeans::<Float32Type>(&data, KMeansParams::default(), 32, 16, 256)
+        })
+        .await
+        .unwrap();
+        assert_eq!(kmeans.centroids.len(), 16 * 32);
+    }
+
     #[test]
     fn test_train_with_small_dataset() {
         let data = Float32Array::from(vec![1.0, 2.0, 3.0, 4.0]);
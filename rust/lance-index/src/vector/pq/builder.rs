@@ -40,6 +40,13 @@ pub struct PQBuildParams {
     /// User provided codebook.
     pub codebook: Option<ArrayRef>,
 
+    /// Load the codebook from a Lance dataset instead.
+    ///
+    /// The dataset must have exactly one FixedSizeList column, whose values
+    /// are the codebook. It is opened with the IVF stage's storage options.
+    /// Mutually exclusive with `codebook`.
+    pub codebook_file: Option<String>,
+
     /// Sample rate to train PQ codebook.
     pub sample_rate: usize,
 }
@@ -61,6 +68,7 @@ impl Default for PQBuildParams {
             max_iters: 50,
             kmeans_redos: 1,
             codebook: None,
+            codebook_file: None,
             sample_rate: 256,
         }
     }
@@ -74,6 +82,10 @@ impl QuantizerBuildParams for PQBuildParams {
     fn use_residual(distance_type: DistanceType) -> bool {
         matches!(distance_type, DistanceType::L2 | DistanceType::Cosine)
     }
+
+    fn is_pretrained(&self) -> bool {
+        self.codebook.is_some()
+    }
 }
 
 impl PQBuildParams {
@@ -94,6 +106,66 @@ impl PQBuildParams {
         }
     }
 
+    /// Create a new instance of [`PQBuildParams`] with the codebook loaded
+    /// from a Lance dataset, see [`Self::codebook_file`].
+    pub fn with_codebook_file(
+        num_sub_vectors: usize,
+        num_bits: usize,
+        uri: impl Into<String>,
+    ) -> Self {
+        Self {
+            num_sub_vectors,
+            num_bits,
+            codebook_file: Some(uri.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Check the provided `codebook` fits vectors of `dim` dimensions compared
+    /// with `distance_type`, so a mismatch fails before any data is read.
+    ///
+    /// The codebook may be given flat, or as a FixedSizeList whose values are
+    /// the codebook.
+    pub fn validate_codebook(&self, dim: usize, distance_type: DistanceType) -> Result<()> {
+        let Some(codebook) = &self.codebook else {
+            return Ok(());
+        };
+        if distance_type == DistanceType::Hamming {
+            return Err(Error::invalid_input("PQ does not support hamming distance"));
+        }
+        if self.num_sub_vectors == 0 || dim % self.num_sub_vectors != 0 {
+            return Err(Error::invalid_input(format!(
+                "PQ codebook: dimension {dim} is not divisible by num_sub_vectors {}",
+                self.num_sub_vectors
+            )));
+        }
+        let values = match codebook.as_fixed_size_list_opt() {
+            Some(fsl) => fsl.values().as_ref(),
+            None => codebook.as_ref(),
+        };
+        if !matches!(
+            values.data_type(),
+            DataType::Float16 | DataType::Float32 | DataType::Float64
+        ) {
+            return Err(Error::invalid_input(format!(
+                "PQ codebook must be floating point, got {}",
+                values.data_type()
+            )));
+        }
+        let expected = super::num_centroids(self.num_bits as u32) * dim;
+        if values.len() != expected {
+            return Err(Error::invalid_input(format!(
+                "PQ codebook has {} values, but {} bits over {dim} dimensions need {expected}",
+                values.len(),
+                self.num_bits
+            )));
+        }
+        if values.null_count() > 0 {
+            return Err(Error::invalid_input("PQ codebook must not contain nulls"));
+        }
+        Ok(())
+    }
+
     fn build_from_fsl<T: ArrowNumericType>(
         &self,
         data: &FixedSizeListArray,
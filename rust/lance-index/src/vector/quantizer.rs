@@ -103,6 +103,12 @@ pub trait QuantizerBuildParams: Send + Sync {
     fn use_residual(_: DistanceType) -> bool {
         false
     }
+
+    /// Whether the params fully describe the quantizer, e.g. a codebook
+    /// trained elsewhere, so building it needs no training data.
+    fn is_pretrained(&self) -> bool {
+        false
+    }
 }
 
 impl QuantizerBuildParams for () {
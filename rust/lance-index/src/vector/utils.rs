@@ -269,6 +269,28 @@ pub fn is_finite(fsl: &FixedSizeListArray) -> BooleanArray {
     BooleanArray::from(is_finite)
 }
 
+/// Whether any value of `arr` is NaN. Arrays that are not floats have none.
+pub fn has_nan(arr: &dyn Array) -> bool {
+    match arr.data_type() {
+        DataType::Float16 => arr
+            .as_primitive::<Float16Type>()
+            .values()
+            .iter()
+            .any(|v| v.is_nan()),
+        DataType::Float32 => arr
+            .as_primitive::<Float32Type>()
+            .values()
+            .iter()
+            .any(|v| v.is_nan()),
+        DataType::Float64 => arr
+            .as_primitive::<Float64Type>()
+            .values()
+            .iter()
+            .any(|v| v.is_nan()),
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -3,6 +3,10 @@
 
 //! Shuffler is a component that takes a stream of record batches and shuffles them into
 //! the corresponding IVF partitions.
+//!
+//! Shuffled data is spilled to files in a scratch directory, so the memory used does
+//! not grow with the size of the dataset. [`create_scratch_ivf_shuffler`] creates that
+//! directory and removes it once the shuffler and its reader are dropped.
 
 use std::ops::Range;
 use std::sync::atomic::AtomicU64;
@@ -19,7 +23,9 @@ use lance_arrow::{RecordBatchExt, SchemaExt};
 use lance_core::{
     Error, Result,
     cache::LanceCache,
-    utils::tokio::{get_num_compute_intensive_cpus, spawn_cpu},
+    utils::resources::TrackedResources,
+    utils::tempfile::TempDir,
+    utils::tokio::{compute_parallelism, spawn_cpu},
 };
 use lance_encoding::decoder::{DecoderPlugins, FilterExpression};
 use lance_encoding::version::LanceFileVersion;
@@ -34,6 +40,7 @@ use lance_io::{
 };
 use object_store::path::Path;
 
+use crate::vector::ivf::builder::IvfBuildParams;
 use crate::vector::{LOSS_METADATA_KEY, PART_ID_COLUMN};
 
 #[async_trait::async_trait]
@@ -74,6 +81,9 @@ pub struct IvfShuffler {
     output_dir: Path,
     num_partitions: usize,
     format_version: LanceFileVersion,
+    /// Keeps the scratch directory holding `output_dir` alive, see
+    /// [`create_scratch_ivf_shuffler`]
+    scratch_dir: Option<Arc<TempDir>>,
 
     progress: Arc<dyn crate::progress::IndexBuildProgress>,
 }
@@ -85,6 +95,7 @@ impl IvfShuffler {
             output_dir,
             num_partitions,
             format_version: LanceFileVersion::V2_0,
+            scratch_dir: None,
             progress: crate::progress::noop_progress(),
         }
     }
@@ -107,6 +118,7 @@ impl Shuffler for IvfShuffler {
         data: Box<dyn RecordBatchStream + Unpin + 'static>,
     ) -> Result<Box<dyn ShuffleReader>> {
         let num_partitions = self.num_partitions;
+        let resources = TrackedResources::current();
         let mut partition_sizes = vec![0; num_partitions];
         let schema = data.schema().without_column(PART_ID_COLUMN);
         let mut writers = stream::iter(0..num_partitions)
@@ -176,13 +188,20 @@ impl Shuffler for IvfShuffler {
                     Ok::<(Vec<Vec<RecordBatch>>, f64), Error>((partition_buffers, loss))
                 })
             })
-            .buffered(get_num_compute_intensive_cpus());
+            .buffered(compute_parallelism());
 
         let mut total_loss = 0.0;
         let mut num_rows = 0u64;
         while let Some(shuffled) = parallel_sort_stream.next().await {
             let (shuffled, loss) = shuffled?;
             total_loss += loss;
+            let _shuffled_memory = resources.reserve_memory(
+                shuffled
+                    .iter()
+                    .flatten()
+                    .map(RecordBatch::get_array_memory_size)
+                    .sum(),
+            );
 
             let mut futs = Vec::new();
             for (part_id, (writer, batches)) in writers.iter_mut().zip(shuffled.iter()).enumerate()
@@ -203,13 +222,24 @@ impl Shuffler for IvfShuffler {
         for writer in writers.iter_mut() {
             writer.finish().await?;
         }
+        if resources.is_tracked() {
+            for partition_id in 0..num_partitions {
+                let part_path = self
+                    .output_dir
+                    .clone()
+                    .join(format!("ivf_{}.lance", partition_id));
+                resources.add_temp_bytes(self.object_store.size(&part_path).await?);
+            }
+        }
 
-        Ok(Box::new(IvfShufflerReader::new(
+        let mut reader = IvfShufflerReader::new(
             self.object_store.clone(),
             self.output_dir.clone(),
             partition_sizes,
             total_loss,
-        )))
+        );
+        reader._scratch_dir = self.scratch_dir.clone();
+        Ok(Box::new(reader))
     }
 }
 
@@ -218,6 +248,7 @@ pub struct IvfShufflerReader {
     output_dir: Path,
     partition_sizes: Vec<usize>,
     loss: f64,
+    _scratch_dir: Option<Arc<TempDir>>,
 }
 
 impl IvfShufflerReader {
@@ -234,6 +265,7 @@ impl IvfShufflerReader {
             output_dir,
             partition_sizes,
             loss,
+            _scratch_dir: None,
         }
     }
 }
@@ -319,6 +351,24 @@ pub fn create_ivf_shuffler(
     num_partitions: usize,
     format_version: LanceFileVersion,
     progress: Option<Arc<dyn crate::progress::IndexBuildProgress>>,
+) -> Box<dyn Shuffler> {
+    new_ivf_shuffler(
+        output_dir,
+        num_partitions,
+        format_version,
+        progress,
+        None,
+        None,
+    )
+}
+
+fn new_ivf_shuffler(
+    output_dir: Path,
+    num_partitions: usize,
+    format_version: LanceFileVersion,
+    progress: Option<Arc<dyn crate::progress::IndexBuildProgress>>,
+    memory_budget_bytes: Option<usize>,
+    scratch_dir: Option<Arc<TempDir>>,
 ) -> Box<dyn Shuffler> {
     let use_legacy = std::env::var("LANCE_LEGACY_SHUFFLER")
         .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
@@ -326,12 +376,17 @@ pub fn create_ivf_shuffler(
     if use_legacy {
         let mut shuffler =
             IvfShuffler::new(output_dir, num_partitions).with_format_version(format_version);
+        shuffler.scratch_dir = scratch_dir;
         if let Some(progress) = progress {
             shuffler = shuffler.with_progress(progress);
         }
         Box::new(shuffler)
     } else {
         let mut shuffler = TwoFileShuffler::new(output_dir, num_partitions);
+        shuffler.scratch_dir = scratch_dir;
+        if let Some(budget) = memory_budget_bytes {
+            shuffler = shuffler.with_batch_size_bytes(budget);
+        }
         if let Some(progress) = progress {
             shuffler = shuffler.with_progress(progress);
         }
@@ -339,6 +394,35 @@ pub fn create_ivf_shuffler(
     }
 }
 
+/// Create an IVF shuffler like [`create_ivf_shuffler`] that spills to a new
+/// scratch directory.
+///
+/// The directory is created in [`IvfBuildParams::shuffle_temp_dir`], or the
+/// system temporary directory, and is removed once the shuffler and the reader
+/// it returns are dropped, whether the build succeeded or not. The shuffle
+/// buffers up to [`IvfBuildParams::shuffle_memory_budget_bytes`] before each
+/// spill, which the legacy shuffler ignores.
+pub fn create_scratch_ivf_shuffler(
+    params: &IvfBuildParams,
+    num_partitions: usize,
+    format_version: LanceFileVersion,
+    progress: Option<Arc<dyn crate::progress::IndexBuildProgress>>,
+) -> Result<Box<dyn Shuffler>> {
+    let scratch_dir = Arc::new(match &params.shuffle_temp_dir {
+        Some(parent) => TempDir::try_new_in(parent)?,
+        None => TempDir::try_new()?,
+    });
+    let output_dir = Path::from_filesystem_path(scratch_dir.std_path())?;
+    Ok(new_ivf_shuffler(
+        output_dir,
+        num_partitions,
+        format_version,
+        progress,
+        params.shuffle_memory_budget_bytes,
+        Some(scratch_dir),
+    ))
+}
+
 const DEFAULT_SHUFFLE_BATCH_BYTES: usize = 128 * 1024 * 1024;
 
 /// Limit of how much transformed data we accumulate before spilling to disk.
@@ -380,6 +464,9 @@ pub struct TwoFileShuffler {
     output_dir: Path,
     num_partitions: usize,
     batch_size_bytes: usize,
+    /// Keeps the scratch directory holding `output_dir` alive, see
+    /// [`create_scratch_ivf_shuffler`]
+    scratch_dir: Option<Arc<TempDir>>,
 
     progress: Arc<dyn crate::progress::IndexBuildProgress>,
 }
@@ -391,6 +478,7 @@ impl TwoFileShuffler {
             output_dir,
             num_partitions,
             batch_size_bytes: shuffle_batch_bytes(),
+            scratch_dir: None,
             progress: crate::progress::noop_progress(),
         }
     }
@@ -400,9 +488,10 @@ impl TwoFileShuffler {
         self
     }
 
-    #[cfg(test)]
-    fn with_batch_size_bytes(mut self, batch_size_bytes: usize) -> Self {
-        self.batch_size_bytes = batch_size_bytes;
+    /// Buffer up to `batch_size_bytes` of data in memory before each spill,
+    /// instead of `LANCE_SHUFFLE_BATCH_BYTES`.
+    pub fn with_batch_size_bytes(mut self, batch_size_bytes: usize) -> Self {
+        self.batch_size_bytes = batch_size_bytes.max(1);
         self
     }
 }
@@ -423,6 +512,7 @@ impl Shuffler for TwoFileShuffler {
             false,
         )]));
         let batch_size_bytes = self.batch_size_bytes;
+        let resources = TrackedResources::current();
 
         // Extract loss from batch metadata before rechunking (concat_batches drops metadata)
         let total_loss = Arc::new(Mutex::new(0.0f64));
@@ -480,6 +570,8 @@ impl Shuffler for TwoFileShuffler {
             let batch = batch?;
             let np = num_partitions;
             let num_rows = batch.num_rows() as u64;
+            // The buffered batch is held until it is sorted and spilled
+            let _batch_memory = resources.reserve_memory(batch.get_array_memory_size());
 
             // Sort by partition ID and compute offsets on CPU
             let (sorted_batch, batch_offsets) = spawn_cpu(move || {
@@ -542,6 +634,11 @@ impl Shuffler for TwoFileShuffler {
         // Finish files
         file_writer.finish().await?;
         offsets_writer.finish().await?;
+        if resources.is_tracked() {
+            for path in [&data_path, &offsets_path] {
+                resources.add_temp_bytes(self.object_store.size(path).await?);
+            }
+        }
 
         let num_batches = num_batches.load(std::sync::atomic::Ordering::Relaxed);
 
@@ -554,6 +651,7 @@ impl Shuffler for TwoFileShuffler {
             num_batches,
             partition_counts,
             total_loss_val,
+            self.scratch_dir.clone(),
         )
         .await
     }
@@ -567,6 +665,7 @@ pub struct TwoFileShuffleReader {
     num_batches: u64,
     partition_counts: Vec<u64>,
     total_loss: f64,
+    _scratch_dir: Option<Arc<TempDir>>,
 }
 
 impl TwoFileShuffleReader {
@@ -577,6 +676,7 @@ impl TwoFileShuffleReader {
         num_batches: u64,
         partition_counts: Vec<u64>,
         total_loss: f64,
+        scratch_dir: Option<Arc<TempDir>>,
     ) -> Result<Box<dyn ShuffleReader>> {
         if num_batches == 0 {
             return Ok(Box::new(EmptyReader));
@@ -617,6 +717,7 @@ impl TwoFileShuffleReader {
             num_batches,
             partition_counts,
             total_loss,
+            _scratch_dir: scratch_dir,
         }))
     }
 
@@ -723,7 +824,7 @@ mod tests {
     use arrow_schema::{DataType, Field, Schema as ArrowSchema};
     use futures::stream;
     use lance_arrow::RecordBatchExt;
-    use lance_core::utils::tempfile::TempStrDir;
+    use lance_core::utils::tempfile::{TempStdDir, TempStrDir};
     use lance_io::stream::RecordBatchStreamAdapter;
 
     use crate::vector::{LOSS_METADATA_KEY, PART_ID_COLUMN};
@@ -934,4 +1035,44 @@ mod tests {
 
         assert!((reader.total_loss().unwrap() - 6.0).abs() < 1e-10);
     }
+
+    #[tokio::test]
+    async fn test_scratch_shuffler_removes_files() {
+        let parent = TempStdDir::default();
+        let params = IvfBuildParams {
+            shuffle_memory_budget_bytes: Some(16),
+            shuffle_temp_dir: Some(parent.to_path_buf()),
+            ..Default::default()
+        };
+        let scratch_files = || std::fs::read_dir(&*parent).unwrap().count();
+
+        let shuffler =
+            create_scratch_ivf_shuffler(&params, 2, LanceFileVersion::V2_0, None).unwrap();
+        assert_eq!(scratch_files(), 1);
+        let batch1 = make_batch(&[0, 1, 0], &[10, 20, 30], None);
+        let batch2 = make_batch(&[1, 0, 1], &[40, 50, 60], None);
+        let reader = shuffler
+            .shuffle(batches_to_stream(vec![batch1, batch2]))
+            .await
+            .unwrap();
+
+        // The reader keeps the spilled files after the shuffler is gone.
+        drop(shuffler);
+        let p0 = collect_partition(reader.as_ref(), 0).await.unwrap();
+        assert_eq!(p0.num_rows(), 3);
+        drop(reader);
+        assert_eq!(scratch_files(), 0);
+
+        // A failed shuffle removes its files too.
+        let shuffler =
+            create_scratch_ivf_shuffler(&params, 2, LanceFileVersion::V2_0, None).unwrap();
+        let batch = make_batch(&[0, 1], &[10, 20], None);
+        let failing = Box::new(RecordBatchStreamAdapter::new(
+            batch.schema(),
+            stream::iter(vec![Ok(batch), Err(Error::io("disk full"))]),
+        ));
+        assert!(shuffler.shuffle(failing).await.is_err());
+        drop(shuffler);
+        assert_eq!(scratch_files(), 0);
+    }
 }
@@ -0,0 +1,99 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Absolute deadlines for I/O operations
+//!
+//! A deadline bounds the total time spent on an operation, including all of its retries.
+//! The retry loops in this crate (the outer download retry, list retries, the AIMD throttle
+//! and multipart upload retries) check the remaining time before they back off and retry.
+//! If the backoff would end after the deadline then the last error is returned instead of
+//! starting another attempt.
+//!
+//! The deadline is carried in a task-local context so that it does not need to be threaded
+//! through every API:
+//!
+//! ```ignore
+//! use std::time::{Duration, Instant};
+//!
+//! let deadline = Instant::now() + Duration::from_secs(3);
+//! let bytes = lance_io::deadline::with_deadline(deadline, reader.get_all()).await?;
+//! ```
+//!
+//! Deadlines do not interrupt an attempt that is already in flight.  Use the object store
+//! timeout options to bound a single request.
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+tokio::task_local! {
+    static DEADLINE: Instant;
+}
+
+/// Run `fut` with an absolute deadline for all I/O it performs
+///
+/// If a deadline is already set by an enclosing call then the earlier of the two is used.
+pub async fn with_deadline<F: Future>(deadline: Instant, fut: F) -> F::Output {
+    let deadline = current_deadline().map_or(deadline, |outer| outer.min(deadline));
+    DEADLINE.scope(deadline, fut).await
+}
+
+/// The deadline set by an enclosing [`with_deadline`] call, if any
+pub fn current_deadline() -> Option<Instant> {
+    DEADLINE.try_with(|deadline| *deadline).ok()
+}
+
+/// Run `fut` with the given deadline, if any
+///
+/// Task-locals are not inherited by spawned tasks.  This is used to carry a deadline captured
+/// with [`current_deadline`] into work that runs on a different task.
+pub(crate) async fn scoped<F: Future>(deadline: Option<Instant>, fut: F) -> F::Output {
+    match deadline {
+        Some(deadline) => DEADLINE.scope(deadline, fut).await,
+        None => fut.await,
+    }
+}
+
+/// Whether a retry that starts after `backoff` would still start before `deadline`
+pub(crate) fn can_retry_before(deadline: Option<Instant>, backoff: Duration) -> bool {
+    deadline.is_none_or(|deadline| Instant::now() + backoff < deadline)
+}
+
+/// Whether a retry that starts after `backoff` would still start before the current deadline
+pub(crate) fn can_retry_after(backoff: Duration) -> bool {
+    can_retry_before(current_deadline(), backoff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_nested_deadlines_use_earliest() {
+        assert_eq!(current_deadline(), None);
+        assert!(can_retry_after(Duration::from_secs(3600)));
+
+        let now = Instant::now();
+        let early = now + Duration::from_secs(10);
+        let late = now + Duration::from_secs(20);
+        with_deadline(early, async {
+            assert_eq!(current_deadline(), Some(early));
+            with_deadline(late, async {
+                assert_eq!(current_deadline(), Some(early));
+            })
+            .await;
+            assert!(can_retry_after(Duration::ZERO));
+            assert!(!can_retry_after(Duration::from_secs(60)));
+
+            // Spawned tasks don't inherit the deadline unless it is carried over
+            let deadline = current_deadline();
+            let spawned = tokio::spawn(async { current_deadline() }).await.unwrap();
+            assert_eq!(spawned, None);
+            let spawned = tokio::spawn(scoped(deadline, async { current_deadline() }))
+                .await
+                .unwrap();
+            assert_eq!(spawned, Some(early));
+        })
+        .await;
+        assert_eq!(current_deadline(), None);
+    }
+}
@@ -1,20 +1,45 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-FileCopyrightText: Copyright The Lance Authors
 
+use std::pin::Pin;
+use std::sync::LazyLock;
+
 use arrow::ffi_stream::FFI_ArrowArrayStream;
 use arrow_array::RecordBatch;
 use arrow_schema::{ArrowError, SchemaRef};
 use futures::StreamExt;
-use lance_core::Result;
+use lance_core::{Error, Result};
 
 use crate::stream::RecordBatchStream;
 
-#[pin_project::pin_project]
+/// Drives exported streams when the caller does not provide a runtime
+static FFI_RUNTIME: LazyLock<std::io::Result<tokio::runtime::Runtime>> = LazyLock::new(|| {
+    tokio::runtime::Builder::new_multi_thread()
+        .thread_name("lance-ffi")
+        .enable_all()
+        .build()
+});
+
+/// A handle to the runtime used by [`to_ffi_arrow_array_stream`] callers that do not
+/// have a runtime of their own
+///
+/// Returns an error if the runtime could not be created.
+pub fn ffi_runtime() -> Result<tokio::runtime::Handle> {
+    match &*FFI_RUNTIME {
+        Ok(runtime) => Ok(runtime.handle().clone()),
+        Err(err) => Err(Error::io(format!(
+            "Failed to create the runtime for FFI streams: {}",
+            err
+        ))),
+    }
+}
+
+#[pin_project::pin_project(PinnedDrop)]
 struct RecordBatchIteratorAdaptor<S: RecordBatchStream> {
     schema: SchemaRef,
 
     #[pin]
-    stream: S,
+    stream: Option<S>,
 
     handle: tokio::runtime::Handle,
 }
@@ -23,12 +48,23 @@ impl<S: RecordBatchStream> RecordBatchIteratorAdaptor<S> {
     fn new(stream: S, schema: SchemaRef, handle: tokio::runtime::Handle) -> Self {
         Self {
             schema,
-            stream,
+            stream: Some(stream),
             handle,
         }
     }
 }
 
+#[pin_project::pinned_drop]
+impl<S: RecordBatchStream> PinnedDrop for RecordBatchIteratorAdaptor<S> {
+    fn drop(self: Pin<&mut Self>) {
+        // The consumer releases the stream from its own thread.  Dropping the stream cancels
+        // the I/O it has in flight, which may need the runtime.
+        let mut this = self.project();
+        let _guard = this.handle.enter();
+        this.stream.set(None);
+    }
+}
+
 impl<S: RecordBatchStream + Unpin> arrow::record_batch::RecordBatchReader
     for RecordBatchIteratorAdaptor<S>
 {
@@ -41,13 +77,19 @@ impl<S: RecordBatchStream + Unpin> Iterator for RecordBatchIteratorAdaptor<S> {
     type Item = std::result::Result<RecordBatch, ArrowError>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        let stream = self.stream.as_mut()?;
         self.handle
-            .block_on(async { self.stream.next().await })
+            .block_on(stream.next())
             .map(|r| r.map_err(|e| ArrowError::ExternalError(Box::new(e))))
     }
 }
 
 /// Wrap a [`RecordBatchStream`] into an [FFI_ArrowArrayStream].
+///
+/// Each call to `get_next` blocks the calling thread on `handle` until the next batch is
+/// ready, so the stream must not be read from a thread that is already driving a tokio
+/// runtime.  Errors are returned through `get_last_error`.  Releasing the stream drops
+/// `stream`, which cancels any I/O it still has in flight.
 pub fn to_ffi_arrow_array_stream(
     stream: impl RecordBatchStream + std::marker::Unpin + 'static,
     handle: tokio::runtime::Handle,
@@ -58,3 +100,71 @@ pub fn to_ffi_arrow_array_stream(
 
     Ok(reader)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use arrow::ffi_stream::ArrowArrayStreamReader;
+    use arrow_array::{Int32Array, RecordBatch};
+    use arrow_schema::{DataType, Field, Schema};
+    use futures::stream;
+
+    use super::*;
+    use crate::stream::RecordBatchStreamAdapter;
+
+    struct SetOnDrop(Arc<AtomicBool>);
+
+    impl Drop for SetOnDrop {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_release_cancels_in_flight_io() {
+        let schema = Arc::new(Schema::new(vec![Field::new("x", DataType::Int32, false)]));
+        let batch =
+            RecordBatch::try_new(schema.clone(), vec![Arc::new(Int32Array::from(vec![1, 2]))])
+                .unwrap();
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        // The first batch is returned once the second read is in flight, and that read
+        // never finishes
+        let reads = {
+            let batch = batch.clone();
+            let cancelled = cancelled.clone();
+            let started = Arc::new(tokio::sync::Notify::new());
+            stream::iter(0..2)
+                .map(move |i| {
+                    let batch = batch.clone();
+                    let cancelled = cancelled.clone();
+                    let started = started.clone();
+                    async move {
+                        if i == 0 {
+                            started.notified().await;
+                        } else {
+                            let _in_flight = SetOnDrop(cancelled);
+                            started.notify_one();
+                            tokio::time::sleep(std::time::Duration::from_secs(3600)).await;
+                        }
+                        Ok(batch)
+                    }
+                })
+                .buffered(2)
+                .boxed()
+        };
+        let stream = to_ffi_arrow_array_stream(
+            RecordBatchStreamAdapter::new(schema, reads),
+            ffi_runtime().unwrap(),
+        )
+        .unwrap();
+
+        let mut reader = ArrowArrayStreamReader::try_new(stream).unwrap();
+        assert_eq!(reader.next().unwrap().unwrap(), batch);
+        assert!(!cancelled.load(Ordering::SeqCst));
+        drop(reader);
+        assert!(cancelled.load(Ordering::SeqCst));
+    }
+}
@@ -26,7 +26,7 @@ use tracing::instrument;
 
 use crate::object_reader::stream_local_range;
 use crate::object_store::DEFAULT_LOCAL_IO_PARALLELISM;
-use crate::object_writer::WriteResult;
+use crate::object_writer::{WriteResult, get_etag};
 use crate::traits::{ByteStream, Reader, Writer};
 use crate::utils::tracking_store::IOTracker;
 
@@ -68,6 +68,37 @@ pub fn copy_file(from: &Path, to: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Copy the local file at `from` to `to`, replacing `to` atomically.
+///
+/// The copy is done by [`std::fs::copy`] so the data does not pass through user space
+/// where the platform allows it.  The file is copied to a temporary file next to `to`
+/// and then renamed into place so readers never observe a partial file.
+pub fn put_file(from: &std::path::Path, to: &Path) -> Result<WriteResult> {
+    let to_path = std::path::PathBuf::from(to_local_path(to));
+    let parent = to_path.parent().expect("file path must have parent");
+    std::fs::create_dir_all(parent).map_err(Error::from)?;
+
+    let temp_path = tempfile::NamedTempFile::new_in(parent)?.into_temp_path();
+    let size = std::fs::copy(from, &temp_path).map_err(|err| match err.kind() {
+        ErrorKind::NotFound => Error::not_found(from.display().to_string()),
+        _ => Error::from(err),
+    })?;
+    temp_path.persist(&to_path).map_err(|e| {
+        Error::io(format!(
+            "failed to persist temp file to {}: {}",
+            to_path.display(),
+            e.error
+        ))
+    })?;
+
+    let metadata = std::fs::metadata(&to_path)?;
+    Ok(WriteResult {
+        size: size as usize,
+        e_tag: Some(get_etag(&metadata)),
+        version: None,
+    })
+}
+
 /// Object reader for local file system.
 #[derive(Debug)]
 pub struct LocalObjectReader {
@@ -312,6 +343,10 @@ impl Writer for tokio::fs::File {
     async fn shutdown(&mut self) -> Result<WriteResult> {
         let size = self.seek(SeekFrom::Current(0)).await? as usize;
         tokio::io::AsyncWriteExt::shutdown(self).await?;
-        Ok(WriteResult { size, e_tag: None })
+        Ok(WriteResult {
+            size,
+            e_tag: None,
+            version: None,
+        })
     }
 }
@@ -4,6 +4,7 @@
 use std::fs::File;
 use std::ops::Range;
 use std::sync::Arc;
+use std::time::Duration;
 
 #[cfg(windows)]
 use crate::local::read_exact_at;
@@ -24,7 +25,11 @@ use tokio::sync::OnceCell;
 use tracing::instrument;
 
 use crate::{
-    object_store::DEFAULT_CLOUD_IO_PARALLELISM,
+    deadline,
+    object_store::{
+        DEFAULT_CLOUD_IO_PARALLELISM,
+        classification::{RetryClassifier, should_retry},
+    },
     traits::{ByteStream, Reader},
 };
 
@@ -71,6 +76,7 @@ pub struct CloudObjectReader {
 
     block_size: usize,
     download_retry_count: usize,
+    retry_classifier: Option<RetryClassifier>,
 }
 
 impl DeepSizeOf for CloudObjectReader {
@@ -95,21 +101,37 @@ impl CloudObjectReader {
             size: OnceCell::new_with(known_size),
             block_size,
             download_retry_count,
+            retry_classifier: None,
         })
     }
+
+    /// Decide which failed downloads are retried with `classifier` instead of
+    /// retrying every error.
+    pub fn with_retry_classifier(mut self, classifier: Option<RetryClassifier>) -> Self {
+        self.retry_classifier = classifier;
+        self
+    }
 }
 
 // Retries for the initial request are handled by object store, but
 // there are no retries for failures that occur during the streaming
 // of the response body. Thus we add an outer retry loop here.
-async fn do_with_retry<'a, O>(f: impl Fn() -> BoxFuture<'a, OSResult<O>> + Clone) -> OSResult<O> {
+//
+// Retries stop early once the deadline (see [`crate::deadline`]) has passed.
+async fn do_with_retry<'a, O>(
+    retry_classifier: Option<&RetryClassifier>,
+    f: impl Fn() -> BoxFuture<'a, OSResult<O>> + Clone,
+) -> OSResult<O> {
     let mut retries = 3;
     loop {
         let f = f.clone();
         match f().await {
             Ok(val) => return Ok(val),
             Err(err) => {
-                if retries == 0 {
+                if retries == 0
+                    || !should_retry(retry_classifier, &err, |_| true)
+                    || !deadline::can_retry_after(Duration::ZERO)
+                {
                     return Err(err);
                 }
                 retries -= 1;
@@ -126,16 +148,21 @@ async fn do_with_retry<'a, O>(f: impl Fn() -> BoxFuture<'a, OSResult<O>> + Clone
 // failures cases.
 async fn do_get_with_outer_retry(
     download_retry_count: usize,
+    retry_classifier: Option<&RetryClassifier>,
     get_request: Arc<GetRequest>,
     desc: impl Fn() -> String,
 ) -> OSResult<Bytes> {
     let mut retries = download_retry_count;
     loop {
         let get_request_clone = get_request.clone();
-        let get_result = do_with_retry(move || get_request_clone.get_range()).await?;
+        let get_result =
+            do_with_retry(retry_classifier, move || get_request_clone.get_range()).await?;
         match get_result.bytes().await {
             Ok(bytes) => return Ok(bytes),
             Err(err) => {
+                if !should_retry(retry_classifier, &err, |_| true) {
+                    return Err(err);
+                }
                 if retries == 0 {
                     log::warn!(
                         "Failed to download {} from {} after {} attempts.  This may indicate that cloud storage is overloaded or your timeout settings are too restrictive.  Error details: {:?}",
@@ -146,6 +173,16 @@ async fn do_get_with_outer_retry(
                     );
                     return Err(err);
                 }
+                if !deadline::can_retry_after(Duration::ZERO) {
+                    log::warn!(
+                        "Failed to download {} from {} before the deadline with {} retries remaining.  Error details: {:?}",
+                        desc(),
+                        get_request.path(),
+                        retries,
+                        err
+                    );
+                    return Err(err);
+                }
                 log::debug!(
                     "Retrying {} from {} (remaining retries: {}).  Error details: {:?}",
                     desc(),
@@ -177,8 +214,10 @@ impl Reader for CloudObjectReader {
         Box::pin(async move {
             self.size
                 .get_or_try_init(|| async move {
-                    let meta =
-                        do_with_retry(|| Box::pin(self.object_store.head(&self.path))).await?;
+                    let meta = do_with_retry(self.retry_classifier.as_ref(), || {
+                        Box::pin(self.object_store.head(&self.path))
+                    })
+                    .await?;
                     Ok(meta.size as usize)
                 })
                 .await
@@ -190,12 +229,13 @@ impl Reader for CloudObjectReader {
     fn get_range(&self, range: Range<usize>) -> BoxFuture<'static, OSResult<Bytes>> {
         let object_store = self.object_store.clone();
         let path = self.path.clone();
+        let retry_classifier = self.retry_classifier.clone();
         let get_range = Range {
             start: range.start as u64,
             end: range.end as u64,
         };
         Box::pin(async move {
-            let bytes = do_with_retry(move || {
+            let bytes = do_with_retry(retry_classifier.as_ref(), move || {
                 let object_store = object_store.clone();
                 let path = path.clone();
                 let get_range = get_range.clone();
@@ -221,9 +261,12 @@ impl Reader for CloudObjectReader {
             options: GetOptions::default(),
         });
         Box::pin(async move {
-            do_get_with_outer_retry(self.download_retry_count, get_request, || {
-                "read_all".to_string()
-            })
+            do_get_with_outer_retry(
+                self.download_retry_count,
+                self.retry_classifier.as_ref(),
+                get_request,
+                || "read_all".to_string(),
+            )
             .await
         })
     }
@@ -236,7 +279,10 @@ impl Reader for CloudObjectReader {
         });
         Box::pin(async move {
             let get_request_clone = get_request.clone();
-            let get_result = do_with_retry(move || get_request_clone.get_range()).await?;
+            let get_result = do_with_retry(self.retry_classifier.as_ref(), move || {
+                get_request_clone.get_range()
+            })
+            .await?;
             Ok(get_result.into_stream())
         })
     }
@@ -258,7 +304,10 @@ impl Reader for CloudObjectReader {
         });
         Box::pin(async move {
             let get_request_clone = get_request.clone();
-            let get_result = do_with_retry(move || get_request_clone.get_range()).await?;
+            let get_result = do_with_retry(self.retry_classifier.as_ref(), move || {
+                get_request_clone.get_range()
+            })
+            .await?;
             Ok(get_result.into_stream())
         })
     }
@@ -308,13 +357,26 @@ impl SmallReader {
         path: Path,
         download_retry_count: usize,
         size: usize,
+    ) -> Self {
+        Self::new_with_retry_classifier(store, path, download_retry_count, size, None)
+    }
+
+    /// Like [`Self::new`], deciding which failed downloads are retried with
+    /// `retry_classifier`, see [`CloudObjectReader::with_retry_classifier`].
+    pub fn new_with_retry_classifier(
+        store: Arc<dyn ObjectStore>,
+        path: Path,
+        download_retry_count: usize,
+        size: usize,
+        retry_classifier: Option<RetryClassifier>,
     ) -> Self {
         let path_ref = path.clone();
         let state = SmallReaderState::Loading(
             Box::pin(async move {
                 let object_reader =
                     CloudObjectReader::new(store, path_ref, 0, None, download_retry_count)
-                        .map_err(CloneableError)?;
+                        .map_err(CloneableError)?
+                        .with_retry_classifier(retry_classifier);
                 object_reader
                     .get_all()
                     .await
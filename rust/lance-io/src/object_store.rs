@@ -13,42 +13,72 @@ use std::time::Duration;
 use async_trait::async_trait;
 use bytes::Bytes;
 use chrono::{DateTime, Utc};
+use classification::ClassifyingStore;
+use compress::TransparentCompression;
 use deepsize::DeepSizeOf;
+use directory_markers::DirectoryMarkerFilterStore;
 use futures::{FutureExt, Stream};
 use futures::{StreamExt, TryStreamExt, future, stream::BoxStream};
 use lance_core::error::LanceOptionExt;
+use lance_core::utils::encryption::KeyProvider;
 use lance_core::utils::parse::str_is_truthy;
 use list_retry::ListRetryStream;
 use object_store::DynObjectStore;
 use object_store::ObjectStoreExt as OSObjectStoreExt;
 #[cfg(feature = "aws")]
 use object_store::aws::AwsCredentialProvider;
+use object_store::{
+    Attribute, GetOptions, ObjectMeta, ObjectStore as OSObjectStore, PutMode, PutOptions, TagSet,
+    UpdateVersion, path::Path,
+};
 #[cfg(any(feature = "aws", feature = "azure", feature = "gcp"))]
 use object_store::{ClientOptions, HeaderMap, HeaderValue};
-use object_store::{ObjectMeta, ObjectStore as OSObjectStore, path::Path};
+use priority::{Priority, PriorityLayer};
 use providers::local::FileStoreProvider;
 use providers::memory::MemoryStoreProvider;
+use rand::Rng;
+use read_only::ReadOnlyStore;
 use tokio::io::AsyncWriteExt;
 use url::Url;
+use verify::{VerifyMode, VerifyingStore};
 
 use super::local::LocalObjectReader;
 #[cfg(target_os = "linux")]
 use crate::uring::{UringCurrentThreadReader, UringReader};
+pub mod checksums;
+pub(crate) mod classification;
+pub mod compress;
+pub mod delete;
+#[cfg(feature = "diagnose")]
+pub mod diagnose;
+pub mod directory_markers;
+pub mod disk_cache;
 #[cfg(any(feature = "aws", feature = "azure", feature = "gcp"))]
 pub(crate) mod dynamic_credentials;
-#[cfg(any(feature = "oss", feature = "huggingface"))]
+#[cfg(any(feature = "oss", feature = "huggingface", feature = "tencent"))]
 pub(crate) mod dynamic_opendal;
+pub mod idempotency;
+pub mod interceptor;
+pub mod list_filter;
 mod list_retry;
+pub mod metadata_cache;
+pub mod metadata_endpoint;
+pub mod part_retry;
+pub mod priority;
 pub mod providers;
+pub mod read_buffer;
+pub mod read_only;
 pub mod storage_options;
 #[cfg(test)]
 pub(crate) mod test_utils;
 pub mod throttle;
 mod tracing;
+pub mod verify;
+pub mod verify_read;
 use crate::object_reader::SmallReader;
-use crate::object_writer::{LocalWriter, WriteResult};
+use crate::object_writer::{LocalWriter, UploadLimiter, WriteResult};
 use crate::traits::{WriteExt, Writer};
-use crate::utils::tracking_store::{IOTracker, IoStats};
+use crate::utils::tracking_store::{IOTracker, IoObserver, IoStats, PathAccess};
 use crate::{object_reader::CloudObjectReader, object_writer::ObjectWriter, traits::Reader};
 use lance_core::{Error, Result};
 
@@ -64,6 +94,58 @@ const DEFAULT_LOCAL_BLOCK_SIZE: usize = 4 * 1024; // 4KB block size
 #[cfg(any(feature = "aws", feature = "gcp", feature = "azure"))]
 const DEFAULT_CLOUD_BLOCK_SIZE: usize = 64 * 1024; // 64KB block size
 
+/// Storage option for the gap below which [`ObjectStore::get_ranges`] merges ranges.
+pub const COALESCE_GAP_KEY: &str = "storage_coalesce_gap";
+pub const DEFAULT_COALESCE_GAP: u64 = object_store::OBJECT_STORE_COALESCE_DEFAULT;
+
+/// Storage option for a sub-folder of the dataset root, e.g. `_data`, that
+/// manifest discovery lists instead of the root itself.
+pub const MANIFEST_DISCOVERY_PREFIX_KEY: &str = "manifest_discovery_prefix";
+
+/// Storage option for how many multipart uploads can be in progress at once
+/// across all writers of a store. This is separate from how many parts each
+/// upload sends in parallel.
+pub const MAX_CONCURRENT_UPLOADS_KEY: &str = "storage_max_concurrent_uploads";
+pub const DEFAULT_MAX_CONCURRENT_UPLOADS: usize = 1024;
+
+/// Storage option for the size, in bytes, of the parts of multipart uploads.
+/// Parts grow past it for very large objects unless the store uses constant
+/// size parts. Must be within the provider's
+/// [`ObjectStoreProvider::part_size_bounds`]. Default, the
+/// `LANCE_INITIAL_UPLOAD_SIZE` environment variable or 5MB.
+pub const MULTIPART_PART_SIZE_KEY: &str = "storage_multipart_part_size";
+
+/// Storage option for how many times a failed part of a multipart upload is
+/// retried before the whole upload fails, see [`part_retry`]. This is separate
+/// from the retries of the object store client.
+///
+/// If unset, the deprecated `LANCE_CONN_RESET_RETRIES` environment variable
+/// is used, then [`DEFAULT_PART_UPLOAD_RETRIES`].
+pub const PART_UPLOAD_RETRIES_KEY: &str = "storage_part_upload_retries";
+pub const DEFAULT_PART_UPLOAD_RETRIES: usize = 10;
+
+/// The retries of each upload part set by `LANCE_CONN_RESET_RETRIES`, which
+/// used to bound the connection resets of a whole upload.
+static CONN_RESET_RETRIES: std::sync::LazyLock<Option<usize>> = std::sync::LazyLock::new(|| {
+    let retries = std::env::var("LANCE_CONN_RESET_RETRIES")
+        .ok()?
+        .parse()
+        .ok()?;
+    log::warn!(
+        "LANCE_CONN_RESET_RETRIES is deprecated, set the {} storage option instead",
+        PART_UPLOAD_RETRIES_KEY
+    );
+    Some(retries)
+});
+
+/// Tag set by [`ObjectStore::put_with_expiry`] to the day (UTC, `YYYY-MM-DD`)
+/// from which the object may be deleted.
+///
+/// The store deletes nothing by itself: the bucket needs lifecycle rules that
+/// filter on this tag, e.g. an S3 rule expiring objects tagged
+/// `lance-expire-at=2026-10-15`.
+pub const EXPIRE_AT_TAG: &str = "lance-expire-at";
+
 pub static DEFAULT_MAX_IOP_SIZE: std::sync::LazyLock<u64> = std::sync::LazyLock::new(|| {
     std::env::var("LANCE_MAX_IOP_SIZE")
         .map(|val| val.parse().unwrap())
@@ -72,6 +154,14 @@ pub static DEFAULT_MAX_IOP_SIZE: std::sync::LazyLock<u64> = std::sync::LazyLock:
 
 pub const DEFAULT_DOWNLOAD_RETRY_COUNT: usize = 3;
 
+pub use checksums::{
+    ChecksumMismatch, ChecksumMode, ChecksumStatus, ObjectChecksum, VerifyChecksumsOptions,
+    VerifyReport,
+};
+pub use classification::{RetryClassifier, RetryClassifierFn};
+pub use delete::DeletedPath;
+pub use interceptor::{InterceptedRequest, RequestInterceptor};
+pub use list_filter::ListFilter;
 pub use providers::{ObjectStoreProvider, ObjectStoreRegistry};
 pub use storage_options::{
     EXPIRES_AT_MILLIS_KEY, LanceNamespaceStorageOptionsProvider, REFRESH_OFFSET_MILLIS_KEY,
@@ -119,6 +209,157 @@ impl<O: OSObjectStore + ?Sized> ObjectStoreExt for O {
     }
 }
 
+/// How the keys under a prefix relate to the time they were written, see
+/// [`ObjectStore::list_since`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyTimeOrder {
+    /// Keys say nothing about when they were written.
+    #[default]
+    Unordered,
+    /// Newer keys sort first, e.g. manifests named with an inverted version
+    /// like the V2 manifest naming scheme.
+    NewestFirst,
+}
+
+/// The result of [`ObjectStore::audit`], comparing the objects under a prefix
+/// against a list of expected objects.
+///
+/// All lists are sorted by path.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditReport {
+    /// The number of expected objects that exist with the expected size
+    pub num_ok: usize,
+    /// Expected objects that do not exist
+    pub missing: Vec<Path>,
+    /// Expected objects that exist but have a different size
+    pub size_mismatched: Vec<SizeMismatch>,
+    /// Objects under the prefix that were not expected, with their size
+    pub extra: Vec<(Path, u64)>,
+}
+
+impl AuditReport {
+    /// True if every expected object exists with the expected size and there are
+    /// no unexpected objects.
+    pub fn is_clean(&self) -> bool {
+        self.missing.is_empty() && self.size_mismatched.is_empty() && self.extra.is_empty()
+    }
+}
+
+/// An expected object whose size differs from the expected size, see [`AuditReport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeMismatch {
+    pub path: Path,
+    pub expected: u64,
+    pub actual: u64,
+}
+
+/// The number and total size of the objects under a prefix, see
+/// [`ObjectStore::prefix_size`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PrefixSize {
+    pub num_objects: u64,
+    pub num_bytes: u64,
+}
+
+/// The headers of an object, see [`ObjectStore::list_headers`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectHeader {
+    /// Location, size, last modified time and e-tag of the object
+    pub meta: ObjectMeta,
+    /// The user-defined metadata stored with the object
+    pub metadata: HashMap<String, String>,
+}
+
+impl AuditReport {
+    fn check_size(&mut self, path: Path, expected: u64, actual: u64) {
+        if expected == actual {
+            self.num_ok += 1;
+        } else {
+            self.size_mismatched.push(SizeMismatch {
+                path,
+                expected,
+                actual,
+            });
+        }
+    }
+}
+
+impl PrefixSize {
+    fn add_object(mut self, meta: &ObjectMeta) -> Self {
+        self.num_objects += 1;
+        self.num_bytes += meta.size;
+        self
+    }
+}
+
+impl std::ops::Add for PrefixSize {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            num_objects: self.num_objects + other.num_objects,
+            num_bytes: self.num_bytes + other.num_bytes,
+        }
+    }
+}
+
+/// Read throughput of one object at increasing concurrency, see
+/// [`ObjectStore::probe_throughput`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThroughputReport {
+    /// Size of the sample object
+    pub object_size: u64,
+    /// Size of each read, the store's block size or the whole object if smaller
+    pub read_size: u64,
+    /// One entry per concurrency level, from lowest to highest
+    pub levels: Vec<ConcurrencyThroughput>,
+}
+
+impl ThroughputReport {
+    /// The level with the highest throughput
+    pub fn best(&self) -> Option<&ConcurrencyThroughput> {
+        self.levels
+            .iter()
+            .max_by(|left, right| left.mb_per_sec().total_cmp(&right.mb_per_sec()))
+    }
+}
+
+/// Reads completed at one concurrency level, see [`ThroughputReport`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConcurrencyThroughput {
+    pub concurrency: usize,
+    pub num_reads: u64,
+    pub num_bytes: u64,
+    pub elapsed: Duration,
+}
+
+impl ConcurrencyThroughput {
+    /// Achieved throughput in MB/s (10^6 bytes per second)
+    pub fn mb_per_sec(&self) -> f64 {
+        if self.elapsed.is_zero() {
+            return 0.0;
+        }
+        self.num_bytes as f64 / 1_000_000.0 / self.elapsed.as_secs_f64()
+    }
+}
+
+/// Guarantees of an object store that callers may rely on, see
+/// [`ObjectStore::capabilities`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObjectStoreCapabilities {
+    /// Whether a rename replaces the destination atomically, as `rename(2)`
+    /// does on a POSIX filesystem. Object stores rename by copying and
+    /// deleting, so readers can observe both or neither object.
+    pub atomic_rename: bool,
+    /// Whether many objects are deleted with one request, such as S3
+    /// `DeleteObjects`, see [`ObjectStore::delete_batch_size`].
+    pub batch_delete: bool,
+    /// Whether a put with [`PutMode::Create`](object_store::PutMode::Create)
+    /// fails when the object exists. `None` unless the store was probed, as
+    /// S3 stores opened with an `s3_compatibility_mode` are.
+    pub conditional_put: Option<bool>,
+}
+
 /// Wraps [ObjectStore](object_store::ObjectStore)
 #[derive(Debug, Clone)]
 pub struct ObjectStore {
@@ -136,12 +377,49 @@ pub struct ObjectStore {
     io_parallelism: usize,
     /// Number of times to retry a failed download
     download_retry_count: usize,
+    /// Ranges closer than this many bytes are merged by [`Self::get_ranges`]
+    coalesce_gap: u64,
+    /// Extra prefix below the dataset root to discover manifests under, see
+    /// [`MANIFEST_DISCOVERY_PREFIX_KEY`]
+    manifest_discovery_prefix: Option<Path>,
+    /// Bounds the multipart uploads in progress, see [`MAX_CONCURRENT_UPLOADS_KEY`]
+    pub(crate) upload_limiter: UploadLimiter,
+    /// Size of the first parts of multipart uploads, see
+    /// [`MULTIPART_PART_SIZE_KEY`]
+    pub(crate) upload_part_size: Option<usize>,
+    /// Whether writes are rejected, see [`read_only::READ_ONLY_KEY`]
+    read_only: bool,
+    /// Whether conditional puts are supported, see
+    /// [`ObjectStoreCapabilities::conditional_put`]
+    pub(crate) conditional_put: Option<bool>,
+    /// Compression of writes to compressed keys, see
+    /// [`compress::TRANSPARENT_COMPRESS_KEY`]
+    transparent_compression: Option<TransparentCompression>,
+    /// Overrides which failed requests are retried, see
+    /// [`ObjectStoreParams::is_retryable`]
+    pub(crate) retry_classifier: Option<RetryClassifier>,
+    /// Request permits and the priority `inner` takes them at, see
+    /// [`Self::with_priority`]
+    pub(crate) priority_layer: Option<PriorityLayer>,
+    /// What [`Self::rebind`] builds the store again from, set for stores
+    /// created by an [`ObjectStoreRegistry`]
+    pub(crate) rebind_source: Option<Arc<providers::RebindSource>>,
     /// IO tracker for monitoring read/write operations
     io_tracker: IOTracker,
     /// The datastore prefix that uniquely identifies this object store. It encodes information
     /// which usually cannot be found in the URL such as Azure account name. The prefix plus the
     /// path uniquely identifies any object inside the store.
     pub store_prefix: String,
+    /// The OpenDAL operator behind `inner`, see [`Self::opendal_operator`]
+    #[cfg(any(
+        feature = "aws",
+        feature = "azure",
+        feature = "gcp",
+        feature = "oss",
+        feature = "huggingface",
+        feature = "tencent"
+    ))]
+    pub(crate) opendal_operator: Option<opendal::Operator>,
 }
 
 impl DeepSizeOf for ObjectStore {
@@ -210,12 +488,38 @@ pub struct ObjectStoreParams {
     /// credential refresh. Use `StorageOptionsAccessor::with_static_options()` for static
     /// options or `StorageOptionsAccessor::with_initial_and_provider()` for dynamic refresh.
     pub storage_options_accessor: Option<Arc<StorageOptionsAccessor>>,
+    /// Setting this to true is the same as setting
+    /// [`Self::constant_size_upload_parts`] to true.
+    #[deprecated(note = "Use `constant_size_upload_parts` instead")]
+    pub use_constant_size_upload_parts: bool,
     /// Use constant size upload parts for multipart uploads. Only necessary
     /// for Cloudflare R2, which doesn't support variable size parts. When this
     /// is false, max upload size is 2.5TB. When this is true, the max size is
     /// 50GB.
-    pub use_constant_size_upload_parts: bool,
+    ///
+    /// When unset, the provider's
+    /// [`ObjectStoreProvider::default_use_constant_size_upload_parts`] is used.
+    pub constant_size_upload_parts: Option<bool>,
     pub list_is_lexically_ordered: Option<bool>,
+    /// Decides which failed requests are retried, replacing the builtin
+    /// classification.
+    ///
+    /// This applies to every retry loop lance runs on top of the store:
+    /// idempotent puts, multipart upload parts, list pages and downloads.
+    /// Retries done inside the underlying client are not affected. When
+    /// unset, each loop keeps its default behavior.
+    pub is_retryable: Option<RetryClassifier>,
+    /// Told about every request to the store, e.g. to export the IO to an
+    /// existing metrics system, see [`IoObserver`].
+    pub io_observer: Option<Arc<dyn IoObserver>>,
+    /// Called with every HTTP request to a cloud store before it is sent,
+    /// e.g. to sign it for a custom auth service, see [`RequestInterceptor`].
+    pub request_interceptor: Option<Arc<dyn RequestInterceptor>>,
+    /// Supplies the keys of encrypted columns, see [`KeyProvider`].
+    ///
+    /// The keys are never stored in the dataset.  Takes precedence over the
+    /// key provider of the session.
+    pub key_provider: Option<Arc<dyn KeyProvider>>,
 }
 
 impl Default for ObjectStoreParams {
@@ -230,7 +534,12 @@ impl Default for ObjectStoreParams {
             object_store_wrapper: None,
             storage_options_accessor: None,
             use_constant_size_upload_parts: false,
+            constant_size_upload_parts: None,
             list_is_lexically_ordered: None,
+            is_retryable: None,
+            io_observer: None,
+            request_interceptor: None,
+            key_provider: None,
         }
     }
 }
@@ -249,13 +558,22 @@ impl ObjectStoreParams {
             .as_ref()
             .and_then(|a| a.initial_storage_options())
     }
+
+    /// Whether multipart uploads use constant size parts, `default` applies
+    /// unless the params set it
+    pub fn use_constant_size_upload_parts_or(&self, default: bool) -> bool {
+        #[allow(deprecated)]
+        self.constant_size_upload_parts
+            .unwrap_or(self.use_constant_size_upload_parts || default)
+    }
 }
 
 // We implement hash for caching
 impl std::hash::Hash for ObjectStoreParams {
     #[allow(deprecated)]
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        // For hashing, we use pointer values for ObjectStore, S3 credentials, wrapper
+        // For hashing, we use pointer values for ObjectStore, S3 credentials, wrapper,
+        // retry classifier, IO observer, request interceptor and key provider
         self.block_size.hash(state);
         if let Some((store, url)) = &self.object_store {
             Arc::as_ptr(store).hash(state);
@@ -273,7 +591,20 @@ impl std::hash::Hash for ObjectStoreParams {
             accessor.accessor_id().hash(state);
         }
         self.use_constant_size_upload_parts.hash(state);
+        self.constant_size_upload_parts.hash(state);
         self.list_is_lexically_ordered.hash(state);
+        if let Some(is_retryable) = &self.is_retryable {
+            Arc::as_ptr(is_retryable).hash(state);
+        }
+        if let Some(io_observer) = &self.io_observer {
+            Arc::as_ptr(io_observer).hash(state);
+        }
+        if let Some(interceptor) = &self.request_interceptor {
+            Arc::as_ptr(interceptor).hash(state);
+        }
+        if let Some(key_provider) = &self.key_provider {
+            Arc::as_ptr(key_provider).hash(state);
+        }
     }
 }
 
@@ -310,7 +641,16 @@ impl PartialEq for ObjectStoreParams {
                     .as_ref()
                     .map(|a| a.accessor_id())
             && self.use_constant_size_upload_parts == other.use_constant_size_upload_parts
+            && self.constant_size_upload_parts == other.constant_size_upload_parts
             && self.list_is_lexically_ordered == other.list_is_lexically_ordered
+            && self.is_retryable.as_ref().map(Arc::as_ptr)
+                == other.is_retryable.as_ref().map(Arc::as_ptr)
+            && self.io_observer.as_ref().map(Arc::as_ptr)
+                == other.io_observer.as_ref().map(Arc::as_ptr)
+            && self.request_interceptor.as_ref().map(Arc::as_ptr)
+                == other.request_interceptor.as_ref().map(Arc::as_ptr)
+            && self.key_provider.as_ref().map(Arc::as_ptr)
+                == other.key_provider.as_ref().map(Arc::as_ptr)
     }
 }
 
@@ -450,28 +790,76 @@ impl ObjectStore {
     ) -> Result<(Arc<Self>, Path)> {
         #[allow(deprecated)]
         if let Some((store, path)) = params.object_store.as_ref() {
-            let mut inner = store.clone();
+            let mut inner: Arc<DynObjectStore> = Arc::new(ClassifyingStore::new(store.clone()));
             let store_prefix =
                 registry.calculate_object_store_prefix(uri, params.storage_options())?;
             if let Some(wrapper) = params.object_store_wrapper.as_ref() {
                 inner = wrapper.wrap(&store_prefix, inner);
             }
+            if directory_markers::ignore_directory_markers(params.storage_options()) {
+                inner = Arc::new(DirectoryMarkerFilterStore::new(inner));
+            }
 
             // Always wrap with IO tracking
-            let io_tracker = IOTracker::default();
-            let tracked_store = io_tracker.wrap("", inner);
+            let mut io_tracker = IOTracker::from_storage_options(params.storage_options())?
+                .with_metrics(path.scheme());
+            if let Some(observer) = &params.io_observer {
+                io_tracker = io_tracker.with_observer(observer.clone());
+            }
+            let mut tracked_store = io_tracker.wrap("", inner);
+
+            if let Some(mode) = VerifyMode::from_storage_options(params.storage_options())? {
+                tracked_store = Arc::new(VerifyingStore::new(tracked_store, mode));
+            }
+
+            let read_only = read_only::is_read_only(params.storage_options());
+            if read_only {
+                tracked_store = Arc::new(ReadOnlyStore::new(tracked_store));
+            }
 
             let store = Self {
                 inner: tracked_store,
                 scheme: path.scheme().to_string(),
                 block_size: params.block_size.unwrap_or(64 * 1024),
                 max_iop_size: *DEFAULT_MAX_IOP_SIZE,
-                use_constant_size_upload_parts: params.use_constant_size_upload_parts,
+                use_constant_size_upload_parts: params.use_constant_size_upload_parts_or(
+                    registry
+                        .get_provider(path.scheme())
+                        .is_some_and(|provider| provider.default_use_constant_size_upload_parts()),
+                ),
                 list_is_lexically_ordered: params.list_is_lexically_ordered.unwrap_or_default(),
                 io_parallelism: DEFAULT_CLOUD_IO_PARALLELISM,
                 download_retry_count: DEFAULT_DOWNLOAD_RETRY_COUNT,
+                coalesce_gap: StorageOptions(params.storage_options().cloned().unwrap_or_default())
+                    .coalesce_gap(),
+                manifest_discovery_prefix: StorageOptions(
+                    params.storage_options().cloned().unwrap_or_default(),
+                )
+                .manifest_discovery_prefix(),
+                upload_limiter: UploadLimiter::new(
+                    StorageOptions(params.storage_options().cloned().unwrap_or_default())
+                        .max_concurrent_uploads(),
+                ),
+                read_only,
+                conditional_put: None,
+                transparent_compression: TransparentCompression::from_storage_options(
+                    params.storage_options(),
+                )?,
+                retry_classifier: params.is_retryable.clone(),
+                priority_layer: None,
+                upload_part_size: None,
+                rebind_source: None,
                 io_tracker,
                 store_prefix,
+                #[cfg(any(
+                    feature = "aws",
+                    feature = "azure",
+                    feature = "gcp",
+                    feature = "oss",
+                    feature = "huggingface",
+                    feature = "tencent"
+                ))]
+                opendal_operator: None,
             };
             let path = Path::parse(path.path())?;
             return Ok((Arc::new(store), path));
@@ -530,7 +918,7 @@ impl ObjectStore {
 
     /// Create a in-memory object store directly for testing.
     pub fn memory() -> Self {
-        let provider = MemoryStoreProvider;
+        let provider = MemoryStoreProvider::default();
         provider
             .new_store(Url::parse("memory:///").unwrap(), &Default::default())
             .now_or_never()
@@ -538,11 +926,40 @@ impl ObjectStore {
             .unwrap()
     }
 
+    /// The [`opendal::Operator`] backing this store, if it is implemented with OpenDAL.
+    ///
+    /// **Unstable**: this is an escape hatch for OpenDAL features that Lance does not
+    /// surface yet (e.g. presigning) and may change or be removed in any release.
+    ///
+    /// Requests made through the operator bypass Lance's IO tracking, throttling and any
+    /// [`WrappingObjectStore`].  Stores backed by the native `object_store` implementations
+    /// return `None`, as do OpenDAL stores that rebuild their operator to refresh credentials.
+    #[cfg(any(
+        feature = "aws",
+        feature = "azure",
+        feature = "gcp",
+        feature = "oss",
+        feature = "huggingface",
+        feature = "tencent"
+    ))]
+    pub fn opendal_operator(&self) -> Option<opendal::Operator> {
+        self.opendal_operator.clone()
+    }
+
     /// Returns true if the object store pointed to a local file system.
     pub fn is_local(&self) -> bool {
         self.scheme == "file" || self.scheme == "file+uring"
     }
 
+    /// The guarantees of this object store.
+    pub fn capabilities(&self) -> ObjectStoreCapabilities {
+        ObjectStoreCapabilities {
+            atomic_rename: self.is_local(),
+            batch_delete: self.delete_batch_size() > 1,
+            conditional_put: self.conditional_put,
+        }
+    }
+
     pub fn is_cloud(&self) -> bool {
         if self.is_local() || self.scheme == "memory" || self.scheme == "shared-memory" {
             return false;
@@ -570,6 +987,86 @@ impl ObjectStore {
         self.max_iop_size
     }
 
+    /// Ranges closer than this many bytes may be fetched with a single request,
+    /// see [`Self::get_ranges`]
+    pub fn coalesce_gap(&self) -> u64 {
+        self.coalesce_gap
+    }
+
+    /// The prefix manifest discovery lists for the dataset at `base`.
+    ///
+    /// This is `base` itself unless the store was opened with the
+    /// `manifest_discovery_prefix` storage option, in which case that prefix is
+    /// appended to it. Like `base`, the result is relative to the store, so
+    /// [`Self::store_prefix`] followed by it names the listed location.
+    pub fn manifest_discovery_base(&self, base: &Path) -> Path {
+        match &self.manifest_discovery_prefix {
+            Some(prefix) => prefix
+                .parts()
+                .fold(base.clone(), |path, part| path.join(part)),
+            None => base.clone(),
+        }
+    }
+
+    /// Whether the store was opened with the `storage_read_only` storage option.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    fn check_writable(&self) -> Result<()> {
+        if self.read_only {
+            return Err(Error::not_supported(read_only::READ_ONLY_MESSAGE));
+        }
+        Ok(())
+    }
+
+    /// A handle to this store whose requests take request permits at
+    /// `priority`, ahead of queued requests of lower priorities.
+    ///
+    /// Handles share the permits of the store they were made from. Without
+    /// the [`priority::MAX_CONCURRENT_REQUESTS_KEY`] storage option requests
+    /// are not limited and the priority has no effect.
+    pub fn with_priority(&self, priority: Priority) -> Self {
+        let mut store = self.clone();
+        if let Some(layer) = &self.priority_layer {
+            let layer = layer.with_priority(priority);
+            store.inner = layer.wrap();
+            if self.read_only {
+                store.inner = Arc::new(ReadOnlyStore::new(store.inner));
+            }
+            store.priority_layer = Some(layer);
+        }
+        store
+    }
+
+    /// The priority requests made through this handle take request permits
+    /// at, see [`Self::with_priority`].
+    pub fn priority(&self) -> Priority {
+        self.priority_layer
+            .as_ref()
+            .map(|layer| layer.priority)
+            .unwrap_or_default()
+    }
+
+    /// A store like this one with `overrides` on top of its storage options.
+    ///
+    /// When the overrides only change options applied on top of the
+    /// provider's store, like [`read_only::READ_ONLY_KEY`] or
+    /// [`MANIFEST_DISCOVERY_PREFIX_KEY`], the new store shares its
+    /// connections and credentials with this one. Other options, like an
+    /// endpoint, build a new store from the provider. Either way the new
+    /// store has its own caches and IO stats.
+    ///
+    /// Only stores created by an [`ObjectStoreRegistry`] can be rebound.
+    pub async fn rebind(&self, overrides: StorageOptions) -> Result<Self> {
+        let Some(source) = &self.rebind_source else {
+            return Err(Error::not_supported(format!(
+                "{self} was not created by an ObjectStoreRegistry and cannot be rebound"
+            )));
+        };
+        source.rebind(overrides).await
+    }
+
     pub fn io_parallelism(&self) -> usize {
         std::env::var("LANCE_IO_THREADS")
             .map(|val| val.parse::<usize>().unwrap())
@@ -589,7 +1086,9 @@ impl ObjectStore {
     /// Returns the current IO statistics without modifying the internal state.
     /// Use this when you need to check stats without resetting them.
     pub fn io_stats_snapshot(&self) -> IoStats {
-        self.io_tracker.stats()
+        let mut stats = self.io_tracker.stats();
+        stats.multipart_uploads_in_flight = self.upload_limiter.in_flight() as u64;
+        stats
     }
 
     /// Get incremental IO statistics since the last call to this method
@@ -598,7 +1097,18 @@ impl ObjectStore {
     /// counters to zero. This is useful for tracking IO operations between
     /// different stages of processing.
     pub fn io_stats_incremental(&self) -> IoStats {
-        self.io_tracker.incremental_stats()
+        let mut stats = self.io_tracker.incremental_stats();
+        stats.multipart_uploads_in_flight = self.upload_limiter.in_flight() as u64;
+        stats
+    }
+
+    /// The most read paths of this store, ordered by descending read count
+    ///
+    /// Only populated when the store was opened with the
+    /// `storage_access_histogram_size` storage option, see
+    /// [`IOTracker::access_histogram`].
+    pub fn access_histogram(&self) -> Vec<PathAccess> {
+        self.io_tracker.access_histogram()
     }
 
     /// Open a file for path.
@@ -641,13 +1151,16 @@ impl ObjectStore {
                     .await
                 }
             }
-            _ => Ok(Box::new(CloudObjectReader::new(
-                self.inner.clone(),
-                path.clone(),
-                self.block_size,
-                None,
-                self.download_retry_count,
-            )?)),
+            _ => Ok(Box::new(
+                CloudObjectReader::new(
+                    self.inner.clone(),
+                    path.clone(),
+                    self.block_size,
+                    None,
+                    self.download_retry_count,
+                )?
+                .with_retry_classifier(self.retry_classifier.clone()),
+            )),
         }
     }
 
@@ -660,11 +1173,12 @@ impl ObjectStore {
         // If we know the file is really small, we can read the whole thing
         // as a single request.
         if known_size <= self.block_size {
-            return Ok(Box::new(SmallReader::new(
+            return Ok(Box::new(SmallReader::new_with_retry_classifier(
                 self.inner.clone(),
                 path.clone(),
                 self.download_retry_count,
                 known_size,
+                self.retry_classifier.clone(),
             )));
         }
 
@@ -703,13 +1217,16 @@ impl ObjectStore {
                     .await
                 }
             }
-            _ => Ok(Box::new(CloudObjectReader::new(
-                self.inner.clone(),
-                path.clone(),
-                self.block_size,
-                Some(known_size),
-                self.download_retry_count,
-            )?)),
+            _ => Ok(Box::new(
+                CloudObjectReader::new(
+                    self.inner.clone(),
+                    path.clone(),
+                    self.block_size,
+                    Some(known_size),
+                    self.download_retry_count,
+                )?
+                .with_retry_classifier(self.retry_classifier.clone()),
+            )),
         }
     }
 
@@ -730,8 +1247,18 @@ impl ObjectStore {
     }
 
     /// Create a new file.
+    ///
+    /// With the `storage_transparent_compress` storage option, what is written
+    /// to `.gz` and `.zst` keys is compressed, see [`compress`].
     pub async fn create(&self, path: &Path) -> Result<Box<dyn Writer>> {
-        match self.scheme.as_str() {
+        self.create_as(path, path).await
+    }
+
+    /// Create `path`, compressing what is written as [`Self::create`] would
+    /// for `final_path`.
+    async fn create_as(&self, path: &Path, final_path: &Path) -> Result<Box<dyn Writer>> {
+        self.check_writable()?;
+        let writer: Box<dyn Writer> = match self.scheme.as_str() {
             "file" => {
                 let local_path = super::local::to_local_path(path);
                 let local_path = std::path::PathBuf::from(&local_path);
@@ -748,14 +1275,18 @@ impl ObjectStore {
                         .map_err(|e| Error::io(format!("spawn_blocking failed: {}", e)))??;
                 let (std_file, temp_path) = named_temp.into_parts();
                 let file = tokio::fs::File::from_std(std_file);
-                Ok(Box::new(LocalWriter::new(
+                Box::new(LocalWriter::new(
                     file,
                     path.clone(),
                     temp_path,
                     Arc::new(self.io_tracker.clone()),
-                )))
+                ))
             }
-            _ => Ok(Box::new(ObjectWriter::new(self, path).await?)),
+            _ => Box::new(ObjectWriter::new(self, path).await?),
+        };
+        match &self.transparent_compression {
+            Some(compression) => compression.wrap(final_path, writer),
+            None => Ok(writer),
         }
     }
 
@@ -766,7 +1297,167 @@ impl ObjectStore {
         Writer::shutdown(writer.as_mut()).await
     }
 
+    /// Write `content` to `path` so that readers see either the old object or
+    /// the whole new one.
+    ///
+    /// When the store [renames atomically](ObjectStoreCapabilities::atomic_rename)
+    /// the content is written to a temporary key next to `path`, named
+    /// `.tmp_<filename>_<random>`, and then renamed into place. Other stores
+    /// fall back to [`Self::put`]: a single put is not torn on object stores,
+    /// but this is **not** atomic in the sense of write-then-rename, and a
+    /// failed upload may leave nothing at `path`.
+    pub async fn put_atomic(&self, path: &Path, content: &[u8]) -> Result<WriteResult> {
+        if !self.capabilities().atomic_rename {
+            return self.put(path, content).await;
+        }
+        let Some(filename) = path.filename() else {
+            return Err(Error::invalid_input(format!(
+                "Cannot atomically write to {path}, it has no file name"
+            )));
+        };
+        let temp_name = format!(".tmp_{filename}_{:016x}", rand::rng().random::<u64>());
+        let num_parts = path.parts().count();
+        let temp_path = path
+            .parts()
+            .take(num_parts - 1)
+            .chain(std::iter::once(temp_name.into()))
+            .collect::<Path>();
+
+        let mut writer = self.create_as(&temp_path, path).await?;
+        writer.write_all(content).await?;
+        let result = Writer::shutdown(writer.as_mut()).await?;
+        if let Err(err) = self.inner.rename(&temp_path, path).await {
+            let _ = self.inner.delete(&temp_path).await;
+            return Err(err.into());
+        }
+        Ok(result)
+    }
+
+    /// Upload the local file at `local_file` to `path`.
+    ///
+    /// The file is never read into memory as a whole.  For the local filesystem the file is
+    /// copied with [`std::fs::copy`], which avoids copying the data through user space where
+    /// the platform allows it:
+    ///
+    /// - Linux uses `copy_file_range`, which shares extents (a reflink) on filesystems that
+    ///   support it, such as Btrfs and XFS.  Older kernels and copies between filesystems
+    ///   fall back to `sendfile` and then to a plain read/write loop.
+    /// - macOS clones the file with `fclonefileat` on APFS and otherwise uses `fcopyfile`.
+    /// - Windows uses `CopyFileExW`.
+    ///
+    /// Other stores stream the file in chunks through a (multipart) upload, so memory use is
+    /// bounded by the upload part size rather than the file size.
+    pub async fn put_file(&self, path: &Path, local_file: &std::path::Path) -> Result<WriteResult> {
+        self.check_writable()?;
+        if self.is_local() {
+            let to = path.clone();
+            let from = local_file.to_owned();
+            let result = tokio::task::spawn_blocking(move || super::local::put_file(&from, &to))
+                .await
+                .map_err(|e| Error::io(format!("spawn_blocking failed: {}", e)))??;
+            self.io_tracker
+                .record_write("put", path.clone(), result.size as u64);
+            return Ok(result);
+        }
+        let reader = Self::open_local(local_file).await?;
+        let mut writer = self.create(path).await?;
+        writer.copy_from_reader(reader.as_ref()).await?;
+        Writer::shutdown(writer.as_mut()).await
+    }
+
+    /// Overwrite `path` with `content` only if the object's current ETag is `etag`.
+    ///
+    /// Returns [`Error::PreconditionFailed`] if the object has been modified
+    /// since `etag` was observed, and [`Error::NotSupported`] if the backend
+    /// cannot perform conditional updates.
+    pub async fn put_if_match(
+        &self,
+        path: &Path,
+        content: Bytes,
+        etag: impl Into<String>,
+    ) -> Result<WriteResult> {
+        self.check_writable()?;
+        let opts = PutOptions {
+            mode: PutMode::Update(UpdateVersion {
+                e_tag: Some(etag.into()),
+                version: None,
+            }),
+            ..Default::default()
+        };
+        let size = content.len();
+        match self.inner.put_opts(path, content.into(), opts).await {
+            Ok(res) => Ok(WriteResult {
+                size,
+                e_tag: res.e_tag,
+                version: res.version,
+            }),
+            Err(object_store::Error::Precondition { path, source }) => {
+                Err(Error::precondition_failed(path, source))
+            }
+            Err(
+                err @ (object_store::Error::NotImplemented { .. }
+                | object_store::Error::NotSupported { .. }),
+            ) => Err(Error::not_supported(format!(
+                "Conditional put is not supported by the {} object store: {}",
+                self.scheme, err
+            ))),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Write `content` to `path` and mark it to expire at `expire_at`.
+    ///
+    /// The object is tagged with [`EXPIRE_AT_TAG`] so lifecycle rules of the
+    /// bucket can delete it. Object tags are only sent by the native S3 and
+    /// Azure stores; other stores, including those backed by OpenDAL, return
+    /// [`Error::NotSupported`] rather than writing an object that never
+    /// expires. Stores configured to skip tagging drop the tag silently.
+    pub async fn put_with_expiry(
+        &self,
+        path: &Path,
+        content: Bytes,
+        expire_at: DateTime<Utc>,
+    ) -> Result<WriteResult> {
+        self.check_writable()?;
+        if !self.supports_object_tags() {
+            return Err(Error::not_supported(format!(
+                "Object expiry is not supported by the {} object store",
+                self.scheme
+            )));
+        }
+        let mut tags = TagSet::default();
+        tags.push(EXPIRE_AT_TAG, &expire_at_tag_value(expire_at));
+        let opts = PutOptions {
+            tags,
+            ..Default::default()
+        };
+        let size = content.len();
+        let res = self.inner.put_opts(path, content.into(), opts).await?;
+        Ok(WriteResult {
+            size,
+            e_tag: res.e_tag,
+            version: res.version,
+        })
+    }
+
+    /// Whether `inner` sends the tags of [`PutOptions`] to the service.
+    fn supports_object_tags(&self) -> bool {
+        #[cfg(any(
+            feature = "aws",
+            feature = "azure",
+            feature = "gcp",
+            feature = "oss",
+            feature = "huggingface",
+            feature = "tencent"
+        ))]
+        if self.opendal_operator.is_some() {
+            return false;
+        }
+        matches!(self.scheme.as_str(), "s3" | "az" | "abfss")
+    }
+
     pub async fn delete(&self, path: &Path) -> Result<()> {
+        self.check_writable()?;
         self.inner.delete(path).await?;
         Ok(())
     }
@@ -803,6 +1494,7 @@ impl ObjectStore {
         multipart_copy_fallback: bool,
         max_single_copy: u64,
     ) -> Result<()> {
+        self.check_writable()?;
         if self.is_local() {
             // Use std::fs::copy for local filesystem to support cross-filesystem copies
             return super::local::copy_file(from, to);
@@ -838,7 +1530,169 @@ impl ObjectStore {
         &self,
         path: Option<Path>,
     ) -> Pin<Box<dyn Stream<Item = Result<ObjectMeta>> + Send>> {
-        Box::pin(ListRetryStream::new(self.inner.clone(), path, 5).map(|m| m.map_err(|e| e.into())))
+        Box::pin(
+            ListRetryStream::new(self.inner.clone(), path, 5)
+                .with_retry_classifier(self.retry_classifier.clone())
+                .map(|m| m.map_err(|e| e.into())),
+        )
+    }
+
+    /// List the objects under `prefix`, recursively, in lexical order of their
+    /// paths, whatever order the store lists them in.
+    ///
+    /// When [`Self::list_is_lexically_ordered`] is set the listing is streamed
+    /// as is. Otherwise the whole listing is buffered and sorted before the
+    /// first object is returned.
+    pub fn list_sorted(
+        &self,
+        prefix: Option<Path>,
+    ) -> Pin<Box<dyn Stream<Item = Result<ObjectMeta>> + Send>> {
+        self.sort_listing(self.list(prefix))
+    }
+
+    fn sort_listing(
+        &self,
+        listing: Pin<Box<dyn Stream<Item = Result<ObjectMeta>> + Send>>,
+    ) -> Pin<Box<dyn Stream<Item = Result<ObjectMeta>> + Send>> {
+        if self.list_is_lexically_ordered {
+            return listing;
+        }
+        Box::pin(
+            futures::stream::once(async move {
+                let mut objects = listing.try_collect::<Vec<_>>().await?;
+                objects.sort_unstable_by(|a, b| a.location.cmp(&b.location));
+                Ok::<_, Error>(futures::stream::iter(objects.into_iter().map(Ok)))
+            })
+            .try_flatten(),
+        )
+    }
+
+    /// List the objects under `prefix`, recursively, that were last modified
+    /// after `since`.
+    ///
+    /// Object stores can't filter a listing by time, so the whole prefix is
+    /// listed and filtered here. When the keys under `prefix` sort newest first
+    /// and [`Self::list_is_lexically_ordered`] is set, pass
+    /// [`KeyTimeOrder::NewestFirst`] to stop listing at the first object that
+    /// is not newer than `since`.
+    pub fn list_since(
+        &self,
+        prefix: Option<Path>,
+        since: DateTime<Utc>,
+        order: KeyTimeOrder,
+    ) -> Pin<Box<dyn Stream<Item = Result<ObjectMeta>> + Send>> {
+        let listing = self.list(prefix);
+        if order == KeyTimeOrder::NewestFirst && self.list_is_lexically_ordered {
+            Box::pin(
+                listing.try_take_while(move |meta| future::ready(Ok(meta.last_modified > since))),
+            )
+        } else {
+            Box::pin(listing.try_filter(move |meta| future::ready(meta.last_modified > since)))
+        }
+    }
+
+    /// List the objects under `prefix`, recursively, that pass `filter`.
+    ///
+    /// Objects are filtered as the listing streams in. A
+    /// [`ListFilter::name_regex`] that starts with a literal directory narrows
+    /// the listing to it, and a filter no object can pass lists nothing.
+    pub fn list_filtered(
+        &self,
+        prefix: Option<Path>,
+        filter: ListFilter,
+    ) -> Pin<Box<dyn Stream<Item = Result<ObjectMeta>> + Send>> {
+        if filter.is_empty() {
+            return Box::pin(futures::stream::empty());
+        }
+        Box::pin(
+            self.list(filter.listing_prefix(prefix))
+                .try_filter(move |meta| future::ready(filter.matches(meta))),
+        )
+    }
+
+    /// List the objects directly under `prefix` whose paths sort after
+    /// `start_after`.
+    ///
+    /// Unlike [`Self::list`], objects in sub-directories are left out. The
+    /// objects come in lexical order if [`Self::list_is_lexically_ordered`] is
+    /// set. Otherwise every object after `start_after` is still returned, in no
+    /// particular order.
+    ///
+    /// Stores backed by OpenDAL list with a delimiter and send `start_after` to
+    /// the service when it supports it, so a caller that stops after a few
+    /// objects only fetches the first page. Like requests made through
+    /// [`Self::opendal_operator`], these bypass the IO tracking of `inner`.
+    pub fn list_from(
+        &self,
+        prefix: Option<Path>,
+        start_after: Option<Path>,
+    ) -> Pin<Box<dyn Stream<Item = Result<ObjectMeta>> + Send>> {
+        #[cfg(any(
+            feature = "aws",
+            feature = "azure",
+            feature = "gcp",
+            feature = "oss",
+            feature = "huggingface",
+            feature = "tencent"
+        ))]
+        if let Some(operator) = &self.opendal_operator {
+            return opendal_list_from(operator.clone(), prefix, start_after);
+        }
+
+        let depth = prefix.as_ref().map_or(0, |prefix| prefix.parts().count()) + 1;
+        Box::pin(
+            ListRetryStream::new(self.inner.clone(), prefix, 5)
+                .with_start_after(start_after)
+                .with_retry_classifier(self.retry_classifier.clone())
+                .map(|m| m.map_err(|e| e.into()))
+                .try_filter(move |meta| future::ready(meta.location.parts().count() == depth)),
+        )
+    }
+
+    /// List the objects under `prefix`, recursively, along with their headers.
+    ///
+    /// Listings don't return user-defined metadata, so each listed object is
+    /// fetched with a HEAD request, up to `concurrency` at a time, while the
+    /// listing continues. Headers are returned as their requests finish, not
+    /// in listing order, so the stream can be counted to report progress.
+    /// Objects deleted between the listing and their HEAD are left out.
+    pub fn list_headers(
+        &self,
+        prefix: Option<Path>,
+        concurrency: usize,
+    ) -> Pin<Box<dyn Stream<Item = Result<ObjectHeader>> + Send>> {
+        let inner = self.inner.clone();
+        Box::pin(
+            self.list(prefix)
+                .map_ok(move |meta| {
+                    let inner = inner.clone();
+                    async move {
+                        let opts = GetOptions {
+                            head: true,
+                            ..Default::default()
+                        };
+                        match inner.get_opts(&meta.location, opts).await {
+                            Ok(result) => Ok(Some(ObjectHeader {
+                                metadata: result
+                                    .attributes
+                                    .iter()
+                                    .filter_map(|(attribute, value)| match attribute {
+                                        Attribute::Metadata(key) => {
+                                            Some((key.to_string(), value.to_string()))
+                                        }
+                                        _ => None,
+                                    })
+                                    .collect(),
+                                meta: result.meta,
+                            })),
+                            Err(object_store::Error::NotFound { .. }) => Ok(None),
+                            Err(err) => Err(err.into()),
+                        }
+                    }
+                })
+                .try_buffer_unordered(concurrency.max(1))
+                .try_filter_map(|header| future::ready(Ok(header))),
+        )
     }
 
     /// Read all files (start from base directory) recursively
@@ -852,8 +1706,217 @@ impl ObjectStore {
         self.inner.read_dir_all(dir_path, unmodified_since)
     }
 
+    /// Count and sum the sizes of all objects under a prefix, recursively.
+    ///
+    /// The sub-directories directly under the prefix are listed concurrently,
+    /// up to [`Self::io_parallelism`] at a time. Sizes are added up as the
+    /// listings stream in, so the metadata of a large prefix is never held in
+    /// memory at once.
+    pub async fn prefix_size(&self, prefix: impl Into<Path>) -> Result<PrefixSize> {
+        let prefix = prefix.into();
+        let prefix = Path::parse(&prefix)?;
+        let top_level = self.inner.list_with_delimiter(Some(&prefix)).await?;
+        let direct = top_level
+            .objects
+            .iter()
+            .fold(PrefixSize::default(), PrefixSize::add_object);
+        futures::stream::iter(top_level.common_prefixes)
+            .map(|sub_dir| {
+                self.list(Some(sub_dir))
+                    .try_fold(PrefixSize::default(), |size, meta| {
+                        future::ready(Ok(size.add_object(&meta)))
+                    })
+            })
+            .buffer_unordered(self.io_parallelism())
+            .try_fold(direct, |total, size| future::ready(Ok(total + size)))
+            .await
+    }
+
+    /// Check the objects under a prefix against a list of expected objects and sizes.
+    ///
+    /// The prefix is listed once to find the size of every object under it.  Expected
+    /// objects that the listing did not return (e.g. because they live outside of the
+    /// prefix) are checked with a HEAD request, up to [`Self::io_parallelism`] at a time.
+    ///
+    /// The report lists expected objects that are missing or have a different size as
+    /// well as objects under the prefix that were not expected.
+    pub async fn audit(&self, prefix: &Path, expected: &[(Path, u64)]) -> Result<AuditReport> {
+        let mut remaining = expected.iter().cloned().collect::<HashMap<_, _>>();
+        let mut report = AuditReport::default();
+
+        let mut listing = self.list(Some(prefix.clone()));
+        while let Some(meta) = listing.try_next().await? {
+            match remaining.remove(&meta.location) {
+                Some(expected) => report.check_size(meta.location, expected, meta.size),
+                None => report.extra.push((meta.location, meta.size)),
+            }
+        }
+
+        let mut heads = futures::stream::iter(remaining)
+            .map(|(path, expected)| async move {
+                match self.inner.head(&path).await {
+                    Ok(meta) => Ok((path, expected, Some(meta.size))),
+                    Err(object_store::Error::NotFound { .. }) => Ok((path, expected, None)),
+                    Err(e) => Err(Error::from(e)),
+                }
+            })
+            .buffer_unordered(self.io_parallelism());
+        while let Some((path, expected, actual)) = heads.try_next().await? {
+            match actual {
+                Some(actual) => report.check_size(path, expected, actual),
+                None => report.missing.push(path),
+            }
+        }
+
+        report.missing.sort();
+        report
+            .size_mismatched
+            .sort_by(|left, right| left.path.cmp(&right.path));
+        report.extra.sort();
+        Ok(report)
+    }
+
+    /// Check the objects under `prefix` against their checksums, up to
+    /// `concurrency` at a time, see [`checksums`] for how.
+    ///
+    /// Objects are listed in lexical order, from after `start_after` if set,
+    /// and their results come in the same order, so the location of the last
+    /// result is where to resume an interrupted audit. Stores whose listings
+    /// are not lexically ordered, see [`Self::list_is_lexically_ordered`], are
+    /// listed in full and sorted before the first object is checked.
+    pub fn verify_checksums_stream(
+        &self,
+        prefix: Option<Path>,
+        start_after: Option<Path>,
+        concurrency: usize,
+        mode: ChecksumMode,
+    ) -> Pin<Box<dyn Stream<Item = Result<ObjectChecksum>> + Send>> {
+        let listing = Box::pin(
+            ListRetryStream::new(self.inner.clone(), prefix, 5)
+                .with_start_after(start_after)
+                .with_retry_classifier(self.retry_classifier.clone())
+                .map(|m| m.map_err(|e| e.into())),
+        );
+        let inner = self.inner.clone();
+        Box::pin(
+            self.sort_listing(listing)
+                .map_ok(move |meta| checksums::check_object(inner.clone(), meta, mode))
+                .try_buffered(concurrency.max(1)),
+        )
+    }
+
+    /// Check the objects under `prefix` against their checksums, up to
+    /// `concurrency` at a time.
+    ///
+    /// With [`VerifyChecksumsOptions::max_objects`] set, only that many
+    /// objects are checked and the report has a continuation token to pass
+    /// to the next call. The audit is done once a report has none; the last
+    /// chunk may be empty.
+    pub async fn verify_checksums(
+        &self,
+        prefix: &Path,
+        concurrency: usize,
+        options: VerifyChecksumsOptions,
+    ) -> Result<VerifyReport> {
+        let start_after = options
+            .continuation_token
+            .as_deref()
+            .map(checksums::parse_continuation_token)
+            .transpose()?;
+        let mut checks = self.verify_checksums_stream(
+            Some(prefix.clone()),
+            start_after,
+            concurrency,
+            options.mode,
+        );
+        let mut report = VerifyReport::default();
+        let mut num_checked = 0;
+        while let Some(check) = checks.try_next().await? {
+            num_checked += 1;
+            let location = check.location.clone();
+            report.add(check);
+            if options.max_objects.is_some_and(|max| num_checked >= max) {
+                report.continuation_token = Some(location.to_string());
+                break;
+            }
+        }
+        Ok(report)
+    }
+
+    /// Measure how fast `sample_path` can be read at increasing concurrency.
+    ///
+    /// For each concurrency level, 1, 2, 4, ... up to [`Self::io_parallelism`], that
+    /// many readers fetch [`Self::block_size`] ranges of the object, walking through
+    /// it in turn, until `duration` has passed. Every reader completes at least one
+    /// read. All reads have finished when this returns, and nothing is written, so
+    /// this is safe to run against production data and read-only stores.
+    ///
+    /// Use the report to pick `io_parallelism` and `block_size` for an endpoint;
+    /// the level where throughput stops growing is usually the one to use.
+    pub async fn probe_throughput(
+        &self,
+        sample_path: &Path,
+        duration: Duration,
+    ) -> Result<ThroughputReport> {
+        let object_size = self.inner.head(sample_path).await?.size;
+        if object_size == 0 {
+            return Err(Error::invalid_input(format!(
+                "cannot probe throughput with empty object {}",
+                sample_path
+            )));
+        }
+        let read_size = (self.block_size as u64).min(object_size);
+        let num_ranges = object_size.div_ceil(read_size);
+
+        let max_concurrency = self.io_parallelism().max(1);
+        let mut concurrencies = std::iter::successors(Some(1_usize), |c| Some(c * 2))
+            .take_while(|c| *c < max_concurrency)
+            .collect::<Vec<_>>();
+        concurrencies.push(max_concurrency);
+
+        let mut levels = Vec::with_capacity(concurrencies.len());
+        for concurrency in concurrencies {
+            let next_range = std::sync::atomic::AtomicU64::new(0);
+            let start = std::time::Instant::now();
+            let (num_reads, num_bytes) = futures::stream::iter(0..concurrency)
+                .map(|_| async {
+                    let (mut num_reads, mut num_bytes) = (0_u64, 0_u64);
+                    loop {
+                        let range = next_range.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+                            % num_ranges;
+                        let offset = range * read_size;
+                        let end = (offset + read_size).min(object_size);
+                        let bytes = self.inner.get_range(sample_path, offset..end).await?;
+                        num_reads += 1;
+                        num_bytes += bytes.len() as u64;
+                        if start.elapsed() >= duration {
+                            return Ok::<_, Error>((num_reads, num_bytes));
+                        }
+                    }
+                })
+                .buffer_unordered(concurrency)
+                .try_fold((0, 0), |(reads, bytes), (num_reads, num_bytes)| {
+                    future::ready(Ok((reads + num_reads, bytes + num_bytes)))
+                })
+                .await?;
+            levels.push(ConcurrencyThroughput {
+                concurrency,
+                num_reads,
+                num_bytes,
+                elapsed: start.elapsed(),
+            });
+        }
+
+        Ok(ThroughputReport {
+            object_size,
+            read_size,
+            levels,
+        })
+    }
+
     /// Remove a directory recursively.
     pub async fn remove_dir_all(&self, dir_path: impl Into<Path>) -> Result<()> {
+        self.check_writable()?;
         let path = dir_path.into();
         let path = Path::parse(&path)?;
 
@@ -878,10 +1941,61 @@ impl ObjectStore {
         Ok(())
     }
 
+    /// The most paths [`Self::delete_paths`] deletes with one request.
+    ///
+    /// The builtin S3 and Azure stores delete in bulk, and so do the OpenDAL
+    /// services that support batch deletes. Other stores delete one path per
+    /// request.
+    pub fn delete_batch_size(&self) -> usize {
+        #[cfg(any(
+            feature = "aws",
+            feature = "azure",
+            feature = "gcp",
+            feature = "oss",
+            feature = "huggingface",
+            feature = "tencent"
+        ))]
+        if let Some(operator) = &self.opendal_operator {
+            return operator
+                .info()
+                .full_capability()
+                .delete_max_size
+                .unwrap_or(1)
+                .max(1);
+        }
+        match self.scheme.as_str() {
+            "s3" | "s3+ddb" => 1000,
+            "az" | "abfss" => 256,
+            _ => 1,
+        }
+    }
+
+    /// Delete `paths`, up to `concurrency` requests at a time.
+    ///
+    /// Paths are deleted in batches of [`Self::delete_batch_size`]. Paths that
+    /// fail with a retryable error, such as throttling, are retried with
+    /// backoff. A path that still fails is returned with its error rather than
+    /// failing the stream, which only fails if `paths` does. Paths that do not
+    /// exist count as deleted, so deleting again after a partial failure is
+    /// safe.
+    pub fn delete_paths<'a>(
+        &'a self,
+        paths: BoxStream<'a, Result<Path>>,
+        concurrency: usize,
+    ) -> BoxStream<'a, Result<DeletedPath>> {
+        if let Err(err) = self.check_writable() {
+            return futures::stream::once(async move { Err(err) }).boxed();
+        }
+        delete::delete_paths(self, paths, concurrency)
+    }
+
     pub fn remove_stream<'a>(
         &'a self,
         locations: BoxStream<'a, Result<Path>>,
     ) -> BoxStream<'a, Result<Path>> {
+        if let Err(err) = self.check_writable() {
+            return futures::stream::once(async move { Err(err) }).boxed();
+        }
         let store = Arc::clone(&self.inner);
         locations
             .and_then(move |location| {
@@ -908,12 +2022,50 @@ impl ObjectStore {
         Ok(self.inner.head(path).await?.size)
     }
 
+    /// HEAD several files concurrently, up to [`Self::io_parallelism`] at a time.
+    ///
+    /// Returns one result per path, in the order of `paths`, so a missing or
+    /// unreadable file is reported on its own instead of failing the others.
+    pub async fn head_batch(&self, paths: Vec<Path>) -> Vec<Result<ObjectMeta>> {
+        futures::stream::iter(paths)
+            .map(|path| async move { Ok(self.inner.head(&path).await?) })
+            .buffered(self.io_parallelism())
+            .collect()
+            .await
+    }
+
     /// Convenience function to open a reader and read all the bytes
     pub async fn read_one_all(&self, path: &Path) -> Result<Bytes> {
         let reader = self.open(path).await?;
         Ok(reader.get_all().await?)
     }
 
+    /// Read all of `path` unless it has not been modified since `since`.
+    ///
+    /// Returns `None` when the object is unchanged, which for cloud stores is a
+    /// `304 Not Modified` answer to an `If-Modified-Since` request, so polling
+    /// an unchanged object costs no download. Fails if the object does not
+    /// exist.
+    pub async fn get_if_modified_since(
+        &self,
+        path: &Path,
+        since: DateTime<Utc>,
+    ) -> Result<Option<Bytes>> {
+        let opts = GetOptions {
+            if_modified_since: Some(since),
+            ..Default::default()
+        };
+        match self.inner.get_opts(path, opts).await {
+            Ok(result) => Ok(Some(result.bytes().await?)),
+            // OpenDAL reports a 304 as a failed condition, and this request
+            // carries no other condition.
+            Err(
+                object_store::Error::NotModified { .. } | object_store::Error::Precondition { .. },
+            ) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
     /// Convenience function open a reader and make a single request
     ///
     /// If you will be making multiple requests to the path it is more efficient to call [`Self::open`]
@@ -922,6 +2074,121 @@ impl ObjectStore {
         let reader = self.open(path).await?;
         Ok(reader.get_range(range).await?)
     }
+
+    /// Read several byte ranges of one object.
+    ///
+    /// Ranges separated by less than the `storage_coalesce_gap` storage option
+    /// (1 MiB by default) are fetched with a single request and sliced apart
+    /// afterwards, so nearby pages cost one GET instead of many. The results
+    /// are returned in the order of `ranges`. Ranges that shared a request are
+    /// counted in [`IoStats::coalesced_ranges`].
+    pub async fn get_ranges(&self, path: &Path, ranges: Vec<Range<u64>>) -> Result<Vec<Bytes>> {
+        let mut num_requests = 0;
+        let bytes = object_store::coalesce_ranges(
+            &ranges,
+            |range| {
+                num_requests += 1;
+                self.inner.get_range(path, range)
+            },
+            self.coalesce_gap,
+        )
+        .await?;
+        self.io_tracker
+            .record_coalesced_ranges((ranges.len() - num_requests) as u64);
+        Ok(bytes)
+    }
+
+    /// Stream the lines of a text file, e.g. a JSONL file
+    ///
+    /// The file is read one [`Self::block_size`] range at a time, so only the current block and
+    /// the partial line that spans into it are kept in memory.  Lines are split on `\n` and a
+    /// trailing `\r` is removed.  The last line is returned even if it has no line break.
+    pub fn read_lines(&self, path: &Path) -> BoxStream<'_, Result<String>> {
+        struct LineReader {
+            path: Path,
+            reader: Option<(Box<dyn Reader>, usize)>,
+            offset: usize,
+            line_number: usize,
+            partial_line: Vec<u8>,
+        }
+
+        impl LineReader {
+            /// Read the next block and return the lines that end in it
+            async fn next_lines(
+                mut self,
+                store: &ObjectStore,
+                block_size: usize,
+            ) -> Result<Option<(Vec<Result<String>>, Self)>> {
+                if self.reader.is_none() {
+                    let reader = store.open(&self.path).await?;
+                    let size = reader.size().await?;
+                    self.reader = Some((reader, size));
+                }
+                let (reader, size) = self.reader.as_ref().expect_ok()?;
+                let size = *size;
+                if self.offset >= size {
+                    if self.partial_line.is_empty() {
+                        return Ok(None);
+                    }
+                    let partial_line = std::mem::take(&mut self.partial_line);
+                    let line = self.decode(&partial_line);
+                    return Ok(Some((vec![line], self)));
+                }
+
+                let end = (self.offset + block_size).min(size);
+                let block = reader.get_range(self.offset..end).await?;
+                self.offset = end;
+
+                // A line may start in an earlier block, so complete lines are only split off
+                // once their line break has been read
+                let mut lines = Vec::new();
+                let mut buffer = std::mem::take(&mut self.partial_line);
+                // The partial line has no line break, so a long line is only searched once
+                let mut search_from = buffer.len();
+                buffer.extend_from_slice(&block);
+                let mut start = 0;
+                while let Some(len) = buffer[search_from..].iter().position(|b| *b == b'\n') {
+                    let line = self.decode(&buffer[start..search_from + len]);
+                    start = search_from + len + 1;
+                    search_from = start;
+                    if line.is_err() {
+                        // Return the lines before the invalid one, then stop
+                        lines.push(line);
+                        self.offset = size;
+                        return Ok(Some((lines, self)));
+                    }
+                    lines.push(line);
+                }
+                buffer.drain(..start);
+                self.partial_line = buffer;
+                Ok(Some((lines, self)))
+            }
+
+            fn decode(&mut self, line: &[u8]) -> Result<String> {
+                self.line_number += 1;
+                let line = line.strip_suffix(b"\r").unwrap_or(line);
+                String::from_utf8(line.to_vec()).map_err(|err| {
+                    Error::invalid_input(format!(
+                        "Line {} of '{}' is not valid UTF-8: {}",
+                        self.line_number, self.path, err
+                    ))
+                })
+            }
+        }
+
+        let state = LineReader {
+            path: path.clone(),
+            reader: None,
+            offset: 0,
+            line_number: 0,
+            partial_line: Vec::new(),
+        };
+        let block_size = self.block_size.max(1);
+        futures::stream::try_unfold(state, move |state| state.next_lines(self, block_size))
+            .map_ok(futures::stream::iter)
+            .try_flatten()
+            .boxed()
+    }
 }
 
 /// Options that can be set for multiple object stores
@@ -985,6 +2252,68 @@ impl StorageOptions {
             .unwrap_or(3)
     }
 
+    /// Maximum gap, in bytes, between ranges that [`ObjectStore::get_ranges`]
+    /// fetches with a single request
+    pub fn coalesce_gap(&self) -> u64 {
+        self.0
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(COALESCE_GAP_KEY))
+            .and_then(|(_, value)| value.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_COALESCE_GAP)
+    }
+
+    /// Sub-folder of the dataset root that manifests are discovered under,
+    /// see [`MANIFEST_DISCOVERY_PREFIX_KEY`]. Leading and trailing slashes are
+    /// ignored, so `_data`, `/_data` and `_data/` are the same prefix.
+    pub fn manifest_discovery_prefix(&self) -> Option<Path> {
+        self.0
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(MANIFEST_DISCOVERY_PREFIX_KEY))
+            .map(|(_, value)| Path::from(value.as_str()))
+            .filter(|prefix| !prefix.as_ref().is_empty())
+    }
+
+    /// Number of multipart uploads that can be in progress at once, see
+    /// [`MAX_CONCURRENT_UPLOADS_KEY`]
+    pub fn max_concurrent_uploads(&self) -> usize {
+        self.0
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(MAX_CONCURRENT_UPLOADS_KEY))
+            .and_then(|(_, value)| value.parse::<usize>().ok())
+            .unwrap_or(DEFAULT_MAX_CONCURRENT_UPLOADS)
+    }
+
+    /// Size of the parts of multipart uploads, see [`MULTIPART_PART_SIZE_KEY`],
+    /// `None` if not set. Fails if it is not within `bounds`, the
+    /// [`ObjectStoreProvider::part_size_bounds`] of the store.
+    pub fn multipart_part_size(&self, bounds: (usize, usize)) -> Result<Option<usize>> {
+        let Some((_, value)) = self
+            .0
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(MULTIPART_PART_SIZE_KEY))
+        else {
+            return Ok(None);
+        };
+        let (min, max) = bounds;
+        match value.parse::<usize>() {
+            Ok(size) if (min..=max).contains(&size) => Ok(Some(size)),
+            _ => Err(Error::invalid_input(format!(
+                "Invalid value for storage option '{MULTIPART_PART_SIZE_KEY}': '{value}', expected a size between {min} and {max} bytes"
+            ))),
+        }
+    }
+
+    /// Number of times a failed part of a multipart upload is retried, see
+    /// [`PART_UPLOAD_RETRIES_KEY`]
+    pub fn part_upload_retries(&self) -> usize {
+        self.0
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(PART_UPLOAD_RETRIES_KEY))
+            .and_then(|(_, value)| value.parse::<usize>().ok())
+            .or(*CONN_RESET_RETRIES)
+            .unwrap_or(DEFAULT_PART_UPLOAD_RETRIES)
+    }
+
     /// Max retry times to set in RetryConfig for object store client
     pub fn client_max_retries(&self) -> usize {
         self.0
@@ -1051,6 +2380,18 @@ impl From<HashMap<String, String>> for StorageOptions {
     }
 }
 
+/// The value of [`EXPIRE_AT_TAG`] for `expire_at`, rounded up to the next day
+/// so that the object is never deleted early.
+fn expire_at_tag_value(expire_at: DateTime<Utc>) -> String {
+    let day = expire_at.date_naive();
+    let day = if expire_at == day.and_time(chrono::NaiveTime::MIN).and_utc() {
+        day
+    } else {
+        day.succ_opt().unwrap_or(day)
+    };
+    day.format("%Y-%m-%d").to_string()
+}
+
 static DEFAULT_OBJECT_STORE_REGISTRY: std::sync::LazyLock<ObjectStoreRegistry> =
     std::sync::LazyLock::new(ObjectStoreRegistry::default);
 
@@ -1082,14 +2423,40 @@ impl ObjectStore {
                 store_prefix
             }
         };
-        let store = match wrapper {
+        let store = Arc::new(ClassifyingStore::new(store));
+        let mut store = match wrapper {
             Some(wrapper) => wrapper.wrap(&store_prefix, store),
             None => store,
         };
+        if directory_markers::ignore_directory_markers(storage_options) {
+            store = Arc::new(DirectoryMarkerFilterStore::new(store));
+        }
 
         // Always wrap with IO tracking
-        let io_tracker = IOTracker::default();
-        let tracked_store = io_tracker.wrap("", store);
+        let io_tracker = IOTracker::from_storage_options(storage_options)
+            .unwrap_or_else(|e| {
+                log::warn!("Ignoring access histogram configuration: {}", e);
+                IOTracker::default()
+            })
+            .with_metrics(scheme);
+        let mut tracked_store = io_tracker.wrap("", store);
+
+        match VerifyMode::from_storage_options(storage_options) {
+            Ok(Some(mode)) => tracked_store = Arc::new(VerifyingStore::new(tracked_store, mode)),
+            Ok(None) => {}
+            Err(e) => log::warn!("Ignoring write verification configuration: {}", e),
+        }
+
+        let read_only = read_only::is_read_only(storage_options);
+        if read_only {
+            tracked_store = Arc::new(ReadOnlyStore::new(tracked_store));
+        }
+
+        let transparent_compression = TransparentCompression::from_storage_options(storage_options)
+            .unwrap_or_else(|e| {
+                log::warn!("Ignoring transparent compression configuration: {}", e);
+                None
+            });
 
         Self {
             inner: tracked_store,
@@ -1100,8 +2467,32 @@ impl ObjectStore {
             list_is_lexically_ordered,
             io_parallelism,
             download_retry_count,
+            coalesce_gap: StorageOptions(storage_options.cloned().unwrap_or_default())
+                .coalesce_gap(),
+            manifest_discovery_prefix: StorageOptions(storage_options.cloned().unwrap_or_default())
+                .manifest_discovery_prefix(),
+            upload_limiter: UploadLimiter::new(
+                StorageOptions(storage_options.cloned().unwrap_or_default())
+                    .max_concurrent_uploads(),
+            ),
+            read_only,
+            conditional_put: None,
+            transparent_compression,
+            retry_classifier: None,
+            priority_layer: None,
+            upload_part_size: None,
+            rebind_source: None,
             io_tracker,
             store_prefix,
+            #[cfg(any(
+                feature = "aws",
+                feature = "azure",
+                feature = "gcp",
+                feature = "oss",
+                feature = "huggingface",
+                feature = "tencent"
+            ))]
+            opendal_operator: None,
         }
     }
 }
@@ -1116,24 +2507,90 @@ fn infer_block_size(scheme: &str) -> usize {
     }
 }
 
+/// [`ObjectStore::list_from`] for a store backed by an OpenDAL operator.
+#[cfg(any(
+    feature = "aws",
+    feature = "azure",
+    feature = "gcp",
+    feature = "oss",
+    feature = "huggingface",
+    feature = "tencent"
+))]
+fn opendal_list_from(
+    operator: opendal::Operator,
+    prefix: Option<Path>,
+    start_after: Option<Path>,
+) -> Pin<Box<dyn Stream<Item = Result<ObjectMeta>> + Send>> {
+    use opendal::raw::percent_decode_path;
+
+    let to_error = |source: opendal::Error| -> Error {
+        object_store::Error::Generic {
+            store: "OpenDAL",
+            source: Box::new(source),
+        }
+        .into()
+    };
+    // OpenDAL lists the children of a directory, whose path ends with a slash.
+    let dir = prefix.map_or_else(String::new, |prefix| {
+        format!("{}/", percent_decode_path(prefix.as_ref()))
+    });
+    let native_start_after = operator.info().full_capability().list_with_start_after;
+    let listing = async move {
+        let mut lister = operator.lister_with(&dir);
+        let decoded_start_after = start_after
+            .as_ref()
+            .map(|start_after| percent_decode_path(start_after.as_ref()));
+        if native_start_after && let Some(decoded_start_after) = &decoded_start_after {
+            lister = lister.start_after(decoded_start_after);
+        }
+        let lister = lister.await.map_err(to_error)?;
+        Ok::<_, Error>(lister.map_err(to_error).try_filter_map(move |entry| {
+            let metadata = entry.metadata();
+            // Keys are escaped the same way `object_store_opendal` does.
+            let location = Path::from(entry.path());
+            if metadata.is_dir() || start_after.as_ref().is_some_and(|start| location <= *start) {
+                return future::ready(Ok(None));
+            }
+            let last_modified = metadata
+                .last_modified()
+                .map(|ts| ts.into_inner())
+                .and_then(|ts| {
+                    DateTime::from_timestamp(ts.as_second(), ts.subsec_nanosecond() as u32)
+                })
+                .unwrap_or_default();
+            future::ready(Ok(Some(ObjectMeta {
+                location,
+                last_modified,
+                size: metadata.content_length(),
+                e_tag: metadata.etag().map(str::to_string),
+                version: metadata.version().map(str::to_string),
+            })))
+        }))
+    };
+    Box::pin(futures::stream::once(listing).try_flatten())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::tracking_store::{ACCESS_HISTOGRAM_SIZE_KEY, IoOperation};
     use async_trait::async_trait;
     use bytes::Bytes;
+    use lance_core::error::ErrorClass;
     use lance_core::utils::tempfile::{TempStdDir, TempStdFile, TempStrDir};
     use object_store::memory::InMemory;
     use object_store::{
         CopyOptions, GetOptions, GetResult, ListResult, MultipartUpload, PutMultipartOptions,
         PutOptions, PutPayload, PutResult, Result as OSResult,
     };
+    use regex::Regex;
     use rstest::rstest;
     use std::env::set_current_dir;
     use std::fmt::{Display, Formatter};
     use std::fs::{create_dir_all, write};
     use std::ops::Range;
     use std::path::Path as StdPath;
-    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 
     /// Write test content to file.
     fn write_to_file(path_str: &str, contents: &str) -> std::io::Result<()> {
@@ -1312,6 +2769,230 @@ mod tests {
         assert_eq!(sub_dirs, vec!["bar", "zoo", "test_file"]);
     }
 
+    #[rstest]
+    #[case::memory("memory:///")]
+    #[case::local("")]
+    #[tokio::test]
+    async fn test_prefix_size(#[case] uri: &str) {
+        let dir = TempStrDir::default();
+        let uri = if uri.is_empty() { dir.as_str() } else { uri };
+        let (store, base) = ObjectStore::from_uri(uri).await.unwrap();
+        let prefix = base.clone().join("dataset");
+        let files = [
+            (prefix.clone().join("_versions").join("1.manifest"), 10),
+            (prefix.clone().join("data").join("a.lance"), 100),
+            (prefix.clone().join("data").join("b.lance"), 200),
+            (
+                prefix.clone().join("data").join("nested").join("c.lance"),
+                300,
+            ),
+            (prefix.clone().join("top_level.txt"), 5),
+            // Shares the name prefix but is outside the directory
+            (base.clone().join("dataset2").join("other.lance"), 1000),
+        ];
+        for (path, size) in &files {
+            store.put(path, &vec![0; *size]).await.unwrap();
+        }
+
+        assert_eq!(
+            store.prefix_size(prefix.clone()).await.unwrap(),
+            PrefixSize {
+                num_objects: 5,
+                num_bytes: 615,
+            }
+        );
+        assert_eq!(
+            store
+                .prefix_size(prefix.clone().join("data"))
+                .await
+                .unwrap(),
+            PrefixSize {
+                num_objects: 3,
+                num_bytes: 600,
+            }
+        );
+        assert_eq!(
+            store
+                .prefix_size(base.clone().join("missing"))
+                .await
+                .unwrap(),
+            PrefixSize::default()
+        );
+    }
+
+    #[rstest]
+    #[case::memory("memory:///")]
+    #[case::local("")]
+    #[tokio::test]
+    async fn test_audit(#[case] uri: &str) {
+        let dir = TempStrDir::default();
+        let uri = if uri.is_empty() { dir.as_str() } else { uri };
+        let (store, base) = ObjectStore::from_uri(uri).await.unwrap();
+        let prefix = base.clone().join("dataset");
+        let ok = prefix.clone().join("data").join("a.lance");
+        let wrong_size = prefix.clone().join("data").join("b.lance");
+        let missing = prefix.clone().join("data").join("c.lance");
+        let extra = prefix.clone().join("data").join("nested").join("d.lance");
+        // Outside of the prefix, only found with a HEAD
+        let outside = base.clone().join("other").join("e.lance");
+        let outside_missing = base.clone().join("other").join("f.lance");
+        for (path, size) in [(&ok, 10), (&wrong_size, 20), (&extra, 30), (&outside, 40)] {
+            store.put(path, &vec![0; size]).await.unwrap();
+        }
+
+        let expected = vec![
+            (ok.clone(), 10),
+            (wrong_size.clone(), 25),
+            (missing.clone(), 5),
+            (outside.clone(), 40),
+            (outside_missing.clone(), 50),
+        ];
+        let report = store.audit(&prefix, &expected).await.unwrap();
+        assert_eq!(
+            report,
+            AuditReport {
+                num_ok: 2,
+                missing: vec![missing, outside_missing],
+                size_mismatched: vec![SizeMismatch {
+                    path: wrong_size.clone(),
+                    expected: 25,
+                    actual: 20,
+                }],
+                extra: vec![(extra.clone(), 30)],
+            }
+        );
+        assert!(!report.is_clean());
+
+        let expected = vec![(ok, 10), (wrong_size, 20), (extra, 30)];
+        let report = store.audit(&prefix, &expected).await.unwrap();
+        assert!(report.is_clean(), "{report:?}");
+        assert_eq!(report.num_ok, 3);
+
+        let report = store.audit(&base.clone().join("empty"), &[]).await.unwrap();
+        assert_eq!(report, AuditReport::default());
+    }
+
+    #[tokio::test]
+    async fn test_probe_throughput() {
+        let mut store = ObjectStore::memory();
+        store.block_size = 4096;
+        store.io_parallelism = 8;
+        let sample = Path::from("sample/data.lance");
+        // Not a multiple of the block size, so the last read is shorter
+        let data = (0..10_000).map(|i| i as u8).collect::<Vec<_>>();
+        store.put(&sample, &data).await.unwrap();
+        let e_tag = store.inner.head(&sample).await.unwrap().e_tag;
+
+        let report = store
+            .probe_throughput(&sample, Duration::from_millis(20))
+            .await
+            .unwrap();
+        assert_eq!(report.object_size, 10_000);
+        assert_eq!(report.read_size, 4096);
+        assert_eq!(
+            report
+                .levels
+                .iter()
+                .map(|level| level.concurrency)
+                .collect::<Vec<_>>(),
+            vec![1, 2, 4, 8]
+        );
+        for level in &report.levels {
+            assert!(level.num_reads >= level.concurrency as u64, "{level:?}");
+            assert!(level.num_bytes >= level.num_reads * (10_000 - 2 * 4096));
+            assert!(level.num_bytes <= level.num_reads * 4096);
+            assert!(level.elapsed >= Duration::from_millis(20));
+            assert!(level.mb_per_sec() > 0.0);
+        }
+        assert!(report.best().is_some());
+
+        // The probe only reads
+        let listed = store
+            .list(None)
+            .map_ok(|meta| meta.location)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(listed, vec![sample.clone()]);
+        assert_eq!(store.inner.head(&sample).await.unwrap().e_tag, e_tag);
+
+        let empty = Path::from("sample/empty");
+        store.put(&empty, b"").await.unwrap();
+        let err = store
+            .probe_throughput(&empty, Duration::from_millis(1))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidInput { .. }), "{err:?}");
+        assert!(
+            store
+                .probe_throughput(&Path::from("sample/missing"), Duration::from_millis(1))
+                .await
+                .is_err()
+        );
+    }
+
+    #[rstest]
+    #[case::memory("memory:///")]
+    #[case::local("")]
+    #[tokio::test]
+    async fn test_put_file(#[case] uri: &str) {
+        let source_dir = TempStdDir::default();
+        let source = source_dir.join("source.bin");
+        let content = (0..300_000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        std::fs::write(&source, &content).unwrap();
+
+        let dir = TempStrDir::default();
+        let uri = if uri.is_empty() { dir.as_str() } else { uri };
+        let (store, base) = ObjectStore::from_uri(uri).await.unwrap();
+        let path = base.clone().join("nested").join("dest.bin");
+
+        store.io_stats_incremental();
+        let result = store.put_file(&path, &source).await.unwrap();
+        assert_eq!(result.size, content.len());
+        let stats = store.io_stats_incremental();
+        assert_eq!(stats.written_bytes, content.len() as u64);
+        assert_eq!(store.read_one_all(&path).await.unwrap(), content);
+
+        // Overwrites an existing object
+        std::fs::write(&source, b"replaced").unwrap();
+        store.put_file(&path, &source).await.unwrap();
+        assert_eq!(store.read_one_all(&path).await.unwrap(), &b"replaced"[..]);
+
+        let err = store
+            .put_file(&path, &source_dir.join("missing.bin"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("missing.bin"), "{err}");
+    }
+
+    #[rstest]
+    #[case::memory("memory:///", false)]
+    #[case::local("", true)]
+    #[tokio::test]
+    async fn test_put_atomic(#[case] uri: &str, #[case] atomic_rename: bool) {
+        let dir = TempStrDir::default();
+        let uri = if uri.is_empty() { dir.as_str() } else { uri };
+        let (store, base) = ObjectStore::from_uri(uri).await.unwrap();
+        assert_eq!(store.capabilities().atomic_rename, atomic_rename);
+
+        let path = base.clone().join("nested").join("hint.json");
+        let result = store.put_atomic(&path, b"first").await.unwrap();
+        assert_eq!(result.size, 5);
+        assert_eq!(store.read_one_all(&path).await.unwrap(), &b"first"[..]);
+
+        store.put_atomic(&path, b"second").await.unwrap();
+        assert_eq!(store.read_one_all(&path).await.unwrap(), &b"second"[..]);
+
+        // The temporary key is renamed away
+        let listed = store
+            .list(Some(base.clone()))
+            .map_ok(|meta| meta.location)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(listed, vec![path]);
+    }
+
     #[tokio::test]
     async fn test_delete_directory_local_store() {
         test_delete_directory("").await;
@@ -1441,101 +3122,1149 @@ mod tests {
         assert_eq!(buf.as_ref(), b"LOCAL");
     }
 
+    #[rstest]
+    #[case::single_byte_blocks(1)]
+    #[case::lines_span_blocks(3)]
+    #[case::one_block(4096)]
     #[tokio::test]
-    #[cfg(windows)]
-    async fn test_windows_paths() {
-        use std::path::Component;
-        use std::path::Prefix;
-        use std::path::Prefix::*;
-
-        fn get_path_prefix(path: &StdPath) -> Prefix<'_> {
-            match path.components().next().unwrap() {
-                Component::Prefix(prefix_component) => prefix_component.kind(),
-                _ => panic!(),
-            }
-        }
+    async fn test_read_lines(#[case] block_size: usize) {
+        let mut store = ObjectStore::memory();
+        store.block_size = block_size;
+        let path = Path::from("meta.jsonl");
+        store
+            .put(
+                &path,
+                "{\"a\": 1}\r\n\nh\u{e9}llo w\u{f6}rld\nlast".as_bytes(),
+            )
+            .await
+            .unwrap();
 
-        fn get_drive_letter(prefix: Prefix) -> String {
-            match prefix {
-                Disk(bytes) => String::from_utf8(vec![bytes]).unwrap(),
-                _ => panic!(),
-            }
-        }
+        let lines = store
+            .read_lines(&path)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(
+            lines,
+            vec!["{\"a\": 1}", "", "h\u{e9}llo w\u{f6}rld", "last"]
+        );
 
-        let tmp_path = TempStdFile::default();
-        let prefix = get_path_prefix(&tmp_path);
-        let drive_letter = get_drive_letter(prefix);
+        let path = Path::from("trailing.jsonl");
+        store.put(&path, b"one\ntwo\n").await.unwrap();
+        let lines = store
+            .read_lines(&path)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(lines, vec!["one", "two"]);
 
-        write_to_file(
-            &(format!("{drive_letter}:/test_folder/test.lance") + "/test_file"),
-            "WINDOWS",
-        )
-        .unwrap();
+        let path = Path::from("long.jsonl");
+        let long_line = "x".repeat(10_000);
+        store
+            .put(&path, format!("{long_line}\nshort\n").as_bytes())
+            .await
+            .unwrap();
+        let lines = store
+            .read_lines(&path)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(lines, vec![long_line.as_str(), "short"]);
 
-        for uri in &[
-            format!("{drive_letter}:/test_folder/test.lance"),
-            format!("{drive_letter}:\\test_folder\\test.lance"),
-        ] {
-            let (store, base) = ObjectStore::from_uri(uri).await.unwrap();
-            let contents = read_from_store(store.as_ref(), &base.clone().join("test_file"))
-                .await
-                .unwrap();
-            assert_eq!(contents, "WINDOWS");
-        }
+        let path = Path::from("empty.jsonl");
+        store.put(&path, b"").await.unwrap();
+        let lines = store
+            .read_lines(&path)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert!(lines.is_empty());
     }
 
     #[tokio::test]
-    async fn test_cross_filesystem_copy() {
-        // Create two temporary directories that simulate different filesystems
-        let source_dir = TempStdDir::default();
-        let dest_dir = TempStdDir::default();
-
-        // Create a test file in the source directory
-        let source_file_name = "test_file.txt";
-        let source_file = source_dir.join(source_file_name);
-        std::fs::write(&source_file, b"test content").unwrap();
+    async fn test_read_lines_errors() {
+        let store = ObjectStore::memory();
+        let path = Path::from("invalid.jsonl");
+        store.put(&path, b"ok\n\xff\xfe\n").await.unwrap();
+        let mut lines = store.read_lines(&path);
+        assert_eq!(lines.try_next().await.unwrap().unwrap(), "ok");
+        let err = lines.try_next().await.unwrap_err();
+        assert!(matches!(err, Error::InvalidInput { .. }));
+        assert!(
+            err.to_string().contains("Line 2 of 'invalid.jsonl'"),
+            "{err}"
+        );
 
-        // Create ObjectStore for local filesystem
-        let (store, base_path) = ObjectStore::from_uri(source_dir.to_str().unwrap())
+        let err = store
+            .read_lines(&Path::from("missing.jsonl"))
+            .try_collect::<Vec<_>>()
             .await
-            .unwrap();
-
-        // Create paths relative to the ObjectStore base
-        let from_path = base_path.clone().join(source_file_name);
-
-        // Use object_store::Path::parse for the destination
-        let dest_file = dest_dir.join("copied_file.txt");
-        let dest_str = dest_file.to_str().unwrap();
-        let to_path = object_store::path::Path::parse(dest_str).unwrap();
+            .unwrap_err();
+        assert!(err.to_string().contains("not found"), "{err}");
+    }
 
-        // Perform the copy operation
-        store.copy(&from_path, &to_path).await.unwrap();
+    #[tokio::test]
+    async fn test_list_from() {
+        async fn check(store: &ObjectStore, base: &Path) {
+            for name in ["a/1", "a/2", "a/3", "a/sub/4", "b/5"] {
+                store
+                    .put(&Path::from(format!("{base}/{name}")), b"".as_slice())
+                    .await
+                    .unwrap();
+            }
+            let list_from = |start_after: Option<&str>| {
+                let start_after = start_after.map(|name| Path::from(format!("{base}/{name}")));
+                async move {
+                    let mut names = store
+                        .list_from(Some(base.clone().join("a")), start_after)
+                        .map_ok(|meta| meta.location.filename().unwrap().to_string())
+                        .try_collect::<Vec<_>>()
+                        .await
+                        .unwrap();
+                    if !store.list_is_lexically_ordered {
+                        names.sort();
+                    }
+                    names
+                }
+            };
+            assert_eq!(list_from(None).await, ["1", "2", "3"]);
+            assert_eq!(list_from(Some("a/1")).await, ["2", "3"]);
+            assert_eq!(list_from(Some("a/3")).await, Vec::<String>::new());
+        }
 
-        // Verify the file was copied correctly
-        assert!(dest_file.exists());
-        let copied_content = std::fs::read(&dest_file).unwrap();
-        assert_eq!(copied_content, b"test content");
+        check(&ObjectStore::memory(), &Path::from("base")).await;
+
+        // Listing a local directory is not lexically ordered.
+        let dir = TempStdDir::default();
+        let local = ObjectStore::local();
+        assert!(!local.list_is_lexically_ordered);
+        check(&local, &Path::from_filesystem_path(&dir).unwrap()).await;
+
+        // Stores backed by OpenDAL list through the operator.
+        #[cfg(any(
+            feature = "aws",
+            feature = "azure",
+            feature = "gcp",
+            feature = "oss",
+            feature = "huggingface",
+            feature = "tencent"
+        ))]
+        {
+            let operator = opendal::Operator::new(opendal::services::Memory::default())
+                .unwrap()
+                .finish();
+            let opendal_store = ObjectStore {
+                inner: Arc::new(object_store_opendal::OpendalStore::new(operator.clone())),
+                opendal_operator: Some(operator),
+                ..ObjectStore::memory()
+            };
+            check(&opendal_store, &Path::from("base")).await;
+        }
     }
 
+    #[rstest]
+    #[case::memory("memory:///")]
+    #[case::local("file")]
     #[tokio::test]
-    async fn test_copy_creates_parent_directories() {
-        let source_dir = TempStdDir::default();
-        let dest_dir = TempStdDir::default();
-
-        // Create a test file in the source directory
-        let source_file_name = "test_file.txt";
-        let source_file = source_dir.join(source_file_name);
-        std::fs::write(&source_file, b"test content").unwrap();
+    async fn test_list_since(#[case] uri: &str) {
+        let tmp = TempStrDir::default();
+        let uri = if uri == "file" { tmp.as_str() } else { uri };
+        let (store, base) = ObjectStore::from_uri(uri).await.unwrap();
+        let put = |path: Path| {
+            let store = &store;
+            async move {
+                store.put(&path, b"").await.unwrap();
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        };
 
-        // Create ObjectStore for local filesystem
-        let (store, base_path) = ObjectStore::from_uri(source_dir.to_str().unwrap())
+        // Written in the order b, a, c: "a" and "c" are newer than `since`, but
+        // "c" breaks the newest-first order of the keys.
+        put(base.clone().join("b")).await;
+        let since = store
+            .inner
+            .head(&base.clone().join("b"))
             .await
-            .unwrap();
-
-        // Create paths
-        let from_path = base_path.clone().join(source_file_name);
+            .unwrap()
+            .last_modified;
+        put(base.clone().join("a")).await;
+        put(base.clone().join("sub").join("c")).await;
+
+        let list_since = |order: KeyTimeOrder| {
+            let store = &store;
+            let base = base.clone();
+            async move {
+                let mut names = store
+                    .list_since(Some(base), since, order)
+                    .map_ok(|meta| meta.location.filename().unwrap().to_string())
+                    .try_collect::<Vec<_>>()
+                    .await
+                    .unwrap();
+                names.sort();
+                names
+            }
+        };
+        assert_eq!(list_since(KeyTimeOrder::Unordered).await, ["a", "c"]);
+        // Without a lexical listing the order can't be relied on.
+        let expected: &[&str] = if store.list_is_lexically_ordered {
+            &["a"]
+        } else {
+            &["a", "c"]
+        };
+        assert_eq!(list_since(KeyTimeOrder::NewestFirst).await, expected);
+    }
 
-        // Create destination with nested directories that don't exist yet
+    #[rstest]
+    #[case::memory("memory:///")]
+    #[case::local("file")]
+    #[tokio::test]
+    async fn test_list_filtered(#[case] uri: &str) {
+        let tmp = TempStrDir::default();
+        let uri = if uri == "file" { tmp.as_str() } else { uri };
+        let (store, base) = ObjectStore::from_uri(uri).await.unwrap();
+        let put = |name: &str, size: usize| {
+            let store = &store;
+            let path = Path::parse(format!("{}/{}", base, name)).unwrap();
+            async move {
+                store.put(&path, &vec![0; size]).await.unwrap();
+                store.inner.head(&path).await.unwrap().last_modified
+            }
+        };
+
+        put("data/small.lance", 3).await;
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let first = put("logs/old.log", 5).await;
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let second = put("data/big.lance", 100).await;
+        put("data/notes.txt", 10).await;
+
+        let list = |filter: ListFilter| {
+            let store = &store;
+            let base = base.clone();
+            async move {
+                let mut names = store
+                    .list_filtered(Some(base.clone()), filter)
+                    .map_ok(|meta| {
+                        meta.location
+                            .prefix_match(&base)
+                            .unwrap()
+                            .map(|part| part.as_ref().to_string())
+                            .collect::<Vec<_>>()
+                            .join("/")
+                    })
+                    .try_collect::<Vec<_>>()
+                    .await
+                    .unwrap();
+                names.sort();
+                names
+            }
+        };
+        let regex = |pattern: &str| Some(Regex::new(pattern).unwrap());
+
+        assert_eq!(
+            list(ListFilter::default()).await,
+            [
+                "data/big.lance",
+                "data/notes.txt",
+                "data/small.lance",
+                "logs/old.log"
+            ]
+        );
+        let older = ListFilter {
+            older_than: Some(second),
+            ..Default::default()
+        };
+        assert_eq!(list(older).await, ["data/small.lance", "logs/old.log"]);
+        let newer_and_small = ListFilter {
+            newer_than: Some(first),
+            max_size: Some(50),
+            ..Default::default()
+        };
+        assert_eq!(list(newer_and_small).await, ["data/notes.txt"]);
+        let large_lance = ListFilter {
+            min_size: Some(4),
+            name_regex: regex(r"\.lance$"),
+            ..Default::default()
+        };
+        assert_eq!(list(large_lance).await, ["data/big.lance"]);
+        // Anchored on a directory, only that directory is listed.
+        let anchored = ListFilter {
+            max_size: Some(10),
+            name_regex: regex(&format!(
+                r"^{}/.*\.lance$",
+                regex::escape(base.clone().join("data").as_ref())
+            )),
+            ..Default::default()
+        };
+        assert_eq!(
+            anchored.listing_prefix(Some(base.clone())),
+            Some(base.clone().join("data"))
+        );
+        assert_eq!(list(anchored).await, ["data/small.lance"]);
+        let window = ListFilter {
+            older_than: Some(second),
+            newer_than: Some(first - chrono::TimeDelta::milliseconds(1)),
+            ..Default::default()
+        };
+        assert_eq!(list(window).await, ["logs/old.log"]);
+        let empty_window = ListFilter {
+            older_than: Some(first),
+            newer_than: Some(first),
+            ..Default::default()
+        };
+        assert!(list(empty_window).await.is_empty());
+        let sizes = ListFilter {
+            min_size: Some(10),
+            max_size: Some(5),
+            ..Default::default()
+        };
+        assert!(list(sizes).await.is_empty());
+    }
+
+    #[rstest]
+    #[case::memory("memory:///")]
+    #[case::local("file")]
+    #[tokio::test]
+    async fn test_get_if_modified_since(#[case] uri: &str) {
+        let tmp = TempStrDir::default();
+        let uri = if uri == "file" { tmp.as_str() } else { uri };
+        let (store, base) = ObjectStore::from_uri(uri).await.unwrap();
+        let path = base.clone().join("pointer");
+        store.put(&path, b"v1").await.unwrap();
+        let modified = store.inner.head(&path).await.unwrap().last_modified;
+
+        let before = modified - chrono::TimeDelta::try_hours(1).unwrap();
+        let data = store.get_if_modified_since(&path, before).await.unwrap();
+        assert_eq!(data.as_deref(), Some(b"v1".as_slice()));
+
+        let after = modified + chrono::TimeDelta::try_hours(1).unwrap();
+        assert_eq!(
+            store.get_if_modified_since(&path, after).await.unwrap(),
+            None
+        );
+
+        let err = store
+            .get_if_modified_since(&base.clone().join("missing"), before)
+            .await
+            .unwrap_err();
+        assert_eq!(err.classification().class, ErrorClass::NotFound, "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_head_batch() {
+        let store = ObjectStore::memory();
+        store.put(&Path::from("a.lance"), b"aaaa").await.unwrap();
+        store.put(&Path::from("b.lance"), b"bb").await.unwrap();
+
+        let paths = ["a.lance", "missing.lance", "b.lance"].map(Path::from);
+        let results = store.head_batch(paths.to_vec()).await;
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap().size, 4);
+        let err = results[1].as_ref().unwrap_err();
+        assert_eq!(err.classification().class, ErrorClass::NotFound);
+        assert!(err.to_string().contains("missing.lance"), "{err}");
+        assert_eq!(results[2].as_ref().unwrap().location, paths[2]);
+        assert_eq!(results[2].as_ref().unwrap().size, 2);
+
+        assert!(store.head_batch(vec![]).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_headers() {
+        let store = ObjectStore::memory();
+        let attributes =
+            object_store::Attributes::from_iter([(Attribute::Metadata("owner".into()), "search")]);
+        store
+            .inner
+            .put_opts(
+                &Path::from("docs/a.json"),
+                Bytes::from_static(b"aaaa").into(),
+                PutOptions::from(attributes),
+            )
+            .await
+            .unwrap();
+        store.put(&Path::from("docs/b.json"), b"bb").await.unwrap();
+        store.put(&Path::from("other/c.json"), b"c").await.unwrap();
+
+        let mut headers = store
+            .list_headers(Some(Path::from("docs")), 4)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        headers.sort_by(|a, b| a.meta.location.cmp(&b.meta.location));
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers[0].meta.location, Path::from("docs/a.json"));
+        assert_eq!(headers[0].meta.size, 4);
+        assert!(headers[0].meta.e_tag.is_some());
+        assert_eq!(
+            headers[0].metadata,
+            HashMap::from([("owner".to_string(), "search".to_string())])
+        );
+        assert_eq!(headers[1].meta.size, 2);
+        assert!(headers[1].metadata.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_put_if_match() {
+        let store = ObjectStore::memory();
+        let path = Path::from("pointer");
+        store.put(&path, b"v1").await.unwrap();
+        let etag = store.inner.head(&path).await.unwrap().e_tag.unwrap();
+
+        store
+            .put_if_match(&path, Bytes::from_static(b"v2"), etag.clone())
+            .await
+            .unwrap();
+        assert_eq!(store.read_one_all(&path).await.unwrap().as_ref(), b"v2");
+
+        // The etag is stale now that the object has been overwritten.
+        let err = store
+            .put_if_match(&path, Bytes::from_static(b"v3"), etag)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, Error::PreconditionFailed { path, .. } if path == "pointer"),
+            "{err:?}"
+        );
+        assert!(err.to_string().contains("Precondition failed for pointer"));
+        assert_eq!(store.read_one_all(&path).await.unwrap().as_ref(), b"v2");
+    }
+
+    #[tokio::test]
+    async fn test_put_if_match_unsupported() {
+        let temp_dir = TempStdDir::default();
+        let (store, base_path) = ObjectStore::from_uri(temp_dir.to_str().unwrap())
+            .await
+            .unwrap();
+        let path = base_path.join("pointer");
+        store.put(&path, b"v1").await.unwrap();
+
+        let err = store
+            .put_if_match(&path, Bytes::from_static(b"v2"), "etag")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::NotSupported { .. }), "{err:?}");
+        assert!(
+            err.to_string()
+                .contains("Conditional put is not supported by the file object store")
+        );
+    }
+
+    /// Inner store that remembers the tags of the last put.
+    #[derive(Debug, Default)]
+    struct TagRecordingStore {
+        inner: InMemory,
+        tags: std::sync::Mutex<String>,
+    }
+
+    impl Display for TagRecordingStore {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "TagRecordingStore")
+        }
+    }
+
+    #[async_trait]
+    impl OSObjectStore for TagRecordingStore {
+        async fn put_opts(
+            &self,
+            location: &Path,
+            bytes: PutPayload,
+            opts: PutOptions,
+        ) -> OSResult<PutResult> {
+            *self.tags.lock().unwrap() = opts.tags.encoded().to_string();
+            self.inner.put_opts(location, bytes, opts).await
+        }
+        async fn put_multipart_opts(
+            &self,
+            location: &Path,
+            opts: PutMultipartOptions,
+        ) -> OSResult<Box<dyn MultipartUpload>> {
+            self.inner.put_multipart_opts(location, opts).await
+        }
+        async fn get_opts(&self, location: &Path, options: GetOptions) -> OSResult<GetResult> {
+            self.inner.get_opts(location, options).await
+        }
+        async fn get_ranges(&self, location: &Path, ranges: &[Range<u64>]) -> OSResult<Vec<Bytes>> {
+            self.inner.get_ranges(location, ranges).await
+        }
+        fn delete_stream(
+            &self,
+            locations: BoxStream<'static, OSResult<Path>>,
+        ) -> BoxStream<'static, OSResult<Path>> {
+            self.inner.delete_stream(locations)
+        }
+        fn list(&self, prefix: Option<&Path>) -> BoxStream<'static, OSResult<ObjectMeta>> {
+            self.inner.list(prefix)
+        }
+        fn list_with_offset(
+            &self,
+            prefix: Option<&Path>,
+            offset: &Path,
+        ) -> BoxStream<'static, OSResult<ObjectMeta>> {
+            self.inner.list_with_offset(prefix, offset)
+        }
+        async fn list_with_delimiter(&self, prefix: Option<&Path>) -> OSResult<ListResult> {
+            self.inner.list_with_delimiter(prefix).await
+        }
+        async fn copy_opts(&self, from: &Path, to: &Path, opts: CopyOptions) -> OSResult<()> {
+            self.inner.copy_opts(from, to, opts).await
+        }
+    }
+
+    /// Inner store that versions objects, like a bucket with versioning enabled.
+    #[derive(Debug, Default)]
+    struct VersioningStore {
+        inner: InMemory,
+        next_version: Arc<AtomicU64>,
+    }
+
+    impl VersioningStore {
+        fn with_version(next_version: &AtomicU64, mut res: PutResult) -> PutResult {
+            res.version = Some(format!("v{}", next_version.fetch_add(1, Ordering::Relaxed)));
+            res
+        }
+    }
+
+    impl Display for VersioningStore {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "VersioningStore")
+        }
+    }
+
+    #[derive(Debug)]
+    struct VersioningUpload {
+        inner: Box<dyn MultipartUpload>,
+        next_version: Arc<AtomicU64>,
+    }
+
+    #[async_trait]
+    impl MultipartUpload for VersioningUpload {
+        fn put_part(&mut self, data: PutPayload) -> object_store::UploadPart {
+            self.inner.put_part(data)
+        }
+        async fn complete(&mut self) -> OSResult<PutResult> {
+            let res = self.inner.complete().await?;
+            Ok(VersioningStore::with_version(&self.next_version, res))
+        }
+        async fn abort(&mut self) -> OSResult<()> {
+            self.inner.abort().await
+        }
+    }
+
+    #[async_trait]
+    impl OSObjectStore for VersioningStore {
+        async fn put_opts(
+            &self,
+            location: &Path,
+            bytes: PutPayload,
+            opts: PutOptions,
+        ) -> OSResult<PutResult> {
+            let res = self.inner.put_opts(location, bytes, opts).await?;
+            Ok(Self::with_version(&self.next_version, res))
+        }
+        async fn put_multipart_opts(
+            &self,
+            location: &Path,
+            opts: PutMultipartOptions,
+        ) -> OSResult<Box<dyn MultipartUpload>> {
+            Ok(Box::new(VersioningUpload {
+                inner: self.inner.put_multipart_opts(location, opts).await?,
+                next_version: self.next_version.clone(),
+            }))
+        }
+        async fn get_opts(&self, location: &Path, options: GetOptions) -> OSResult<GetResult> {
+            self.inner.get_opts(location, options).await
+        }
+        async fn get_ranges(&self, location: &Path, ranges: &[Range<u64>]) -> OSResult<Vec<Bytes>> {
+            self.inner.get_ranges(location, ranges).await
+        }
+        fn delete_stream(
+            &self,
+            locations: BoxStream<'static, OSResult<Path>>,
+        ) -> BoxStream<'static, OSResult<Path>> {
+            self.inner.delete_stream(locations)
+        }
+        fn list(&self, prefix: Option<&Path>) -> BoxStream<'static, OSResult<ObjectMeta>> {
+            self.inner.list(prefix)
+        }
+        fn list_with_offset(
+            &self,
+            prefix: Option<&Path>,
+            offset: &Path,
+        ) -> BoxStream<'static, OSResult<ObjectMeta>> {
+            self.inner.list_with_offset(prefix, offset)
+        }
+        async fn list_with_delimiter(&self, prefix: Option<&Path>) -> OSResult<ListResult> {
+            self.inner.list_with_delimiter(prefix).await
+        }
+        async fn copy_opts(&self, from: &Path, to: &Path, opts: CopyOptions) -> OSResult<()> {
+            self.inner.copy_opts(from, to, opts).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_result_version() {
+        let mut store = ObjectStore::memory();
+        store.inner = Arc::new(VersioningStore::default());
+        let path = Path::from("data.lance");
+
+        let res = store.put(&path, b"v0").await.unwrap();
+        assert_eq!(res.size, 2);
+        assert!(res.e_tag.is_some());
+        assert_eq!(res.version.as_deref(), Some("v0"));
+
+        let res = store
+            .put_if_match(&path, Bytes::from_static(b"v1"), res.e_tag.unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res.size, 2);
+        assert_eq!(res.version.as_deref(), Some("v1"));
+
+        // Large enough for a multipart upload, the version is the one of the
+        // completed upload.
+        let content = vec![7; 12 * 1024 * 1024];
+        let res = store.put(&path, &content).await.unwrap();
+        assert_eq!(res.size, content.len());
+        assert_eq!(res.version.as_deref(), Some("v2"));
+        assert_eq!(
+            store.inner.head(&path).await.unwrap().size,
+            content.len() as u64
+        );
+
+        // Stores without versioning don't report one.
+        let res = ObjectStore::memory().put(&path, b"v0").await.unwrap();
+        assert!(res.e_tag.is_some());
+        assert!(res.version.is_none());
+    }
+
+    #[rstest]
+    #[case("2026-10-15T00:00:00Z", "2026-10-15")]
+    #[case("2026-10-15T00:00:01Z", "2026-10-16")]
+    #[case("2026-12-31T23:59:59Z", "2027-01-01")]
+    fn test_expire_at_tag_value(#[case] expire_at: &str, #[case] expected: &str) {
+        let expire_at = DateTime::parse_from_rfc3339(expire_at).unwrap().to_utc();
+        assert_eq!(expire_at_tag_value(expire_at), expected);
+    }
+
+    #[tokio::test]
+    async fn test_put_with_expiry() {
+        let expire_at = DateTime::parse_from_rfc3339("2026-10-15T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let path = Path::from("scratch/tmp.lance");
+
+        let recording = Arc::new(TagRecordingStore::default());
+        let mut store = ObjectStore::memory();
+        store.inner = recording.clone();
+        store.scheme = "s3".to_string();
+        store
+            .put_with_expiry(&path, Bytes::from_static(b"data"), expire_at)
+            .await
+            .unwrap();
+        assert_eq!(
+            recording.tags.lock().unwrap().as_str(),
+            "lance-expire-at=2026-10-16"
+        );
+        assert_eq!(store.read_one_all(&path).await.unwrap().as_ref(), b"data");
+
+        // The memory store would drop the tag, so the object would never expire.
+        let err = ObjectStore::memory()
+            .put_with_expiry(&path, Bytes::from_static(b"data"), expire_at)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::NotSupported { .. }), "{err:?}");
+        assert!(
+            err.to_string()
+                .contains("Object expiry is not supported by the memory object store"),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_metadata_cache_storage_option() {
+        let registry = Arc::new(ObjectStoreRegistry::default());
+        let params = ObjectStoreParams {
+            storage_options_accessor: Some(Arc::new(StorageOptionsAccessor::with_static_options(
+                HashMap::from([(
+                    metadata_cache::METADATA_CACHE_SIZE_KEY.to_string(),
+                    "16".to_string(),
+                )]),
+            ))),
+            ..ObjectStoreParams::default()
+        };
+        let (store, base_path) = ObjectStore::from_uri_and_params(registry, "memory:///", &params)
+            .await
+            .unwrap();
+        let path = base_path.join("data.lance");
+        store.put(&path, b"LANCE").await.unwrap();
+        store.io_stats_incremental();
+
+        assert_eq!(store.size(&path).await.unwrap(), 5);
+        assert_eq!(store.size(&path).await.unwrap(), 5);
+
+        let stats = store.io_stats_incremental();
+        assert_eq!(stats.read_iops, 1);
+        assert_eq!(stats.metadata_cache_misses, 1);
+        assert_eq!(stats.metadata_cache_hits, 1);
+    }
+
+    #[derive(Debug, Default)]
+    struct RecordingObserver {
+        requests: std::sync::Mutex<Vec<(IoOperation, u64, bool)>>,
+    }
+
+    impl IoObserver for RecordingObserver {
+        fn on_request(
+            &self,
+            op: IoOperation,
+            num_bytes: u64,
+            _duration: Duration,
+            result: std::result::Result<(), &object_store::Error>,
+        ) {
+            self.requests
+                .lock()
+                .unwrap()
+                .push((op, num_bytes, result.is_ok()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_io_observer() {
+        let observer = Arc::new(RecordingObserver::default());
+        let params = ObjectStoreParams {
+            io_observer: Some(observer.clone()),
+            ..ObjectStoreParams::default()
+        };
+        let registry = Arc::new(ObjectStoreRegistry::default());
+        let (store, base_path) = ObjectStore::from_uri_and_params(registry, "memory:///", &params)
+            .await
+            .unwrap();
+        let take = || std::mem::take(&mut *observer.requests.lock().unwrap());
+
+        let path = base_path.join("data.lance");
+        store.put(&path, b"LANCE").await.unwrap();
+        assert_eq!(take(), vec![(IoOperation::Write, 5, true)]);
+
+        store.read_one_all(&path).await.unwrap();
+        assert_eq!(take(), vec![(IoOperation::Read, 5, true)]);
+
+        let listed = store.list(None).try_collect::<Vec<_>>().await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(take(), vec![(IoOperation::List, 0, true)]);
+
+        store.delete(&path).await.unwrap();
+        assert_eq!(take(), vec![(IoOperation::Delete, 0, true)]);
+
+        // Every attempt of a retried read is reported.
+        assert!(store.read_one_all(&path).await.is_err());
+        let failed = take();
+        assert!(!failed.is_empty());
+        assert!(
+            failed
+                .iter()
+                .all(|request| *request == (IoOperation::Read, 0, false)),
+            "{failed:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_buffer_storage_option() {
+        let registry = Arc::new(ObjectStoreRegistry::default());
+        let params = ObjectStoreParams {
+            storage_options_accessor: Some(Arc::new(StorageOptionsAccessor::with_static_options(
+                HashMap::from([(
+                    read_buffer::READ_BUFFER_BYTES_KEY.to_string(),
+                    "4".to_string(),
+                )]),
+            ))),
+            ..ObjectStoreParams::default()
+        };
+        let (store, base_path) = ObjectStore::from_uri_and_params(registry, "memory:///", &params)
+            .await
+            .unwrap();
+        let path = base_path.join("data.lance");
+        store.put(&path, b"LANCE").await.unwrap();
+
+        let result = store
+            .inner
+            .get_opts(
+                &path,
+                GetOptions {
+                    range: Some((0..5).into()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        // Larger than the budget, so the read takes all of it.
+        assert_eq!(store.io_stats_snapshot().read_buffer_bytes, 4);
+        assert_eq!(result.bytes().await.unwrap().as_ref(), b"LANCE");
+        assert_eq!(store.io_stats_snapshot().read_buffer_bytes, 0);
+
+        assert_eq!(store.read_one_all(&path).await.unwrap().as_ref(), b"LANCE");
+        assert_eq!(store.io_stats_incremental().read_buffer_bytes, 0);
+    }
+
+    #[tokio::test]
+    async fn test_with_priority() {
+        let registry = Arc::new(ObjectStoreRegistry::default());
+        let params = ObjectStoreParams {
+            storage_options_accessor: Some(Arc::new(StorageOptionsAccessor::with_static_options(
+                HashMap::from([(
+                    priority::MAX_CONCURRENT_REQUESTS_KEY.to_string(),
+                    "1".to_string(),
+                )]),
+            ))),
+            ..ObjectStoreParams::default()
+        };
+        let (store, base_path) = ObjectStore::from_uri_and_params(registry, "memory:///", &params)
+            .await
+            .unwrap();
+        let path = base_path.join("data.lance");
+        store.put(&path, b"LANCE").await.unwrap();
+        assert_eq!(store.priority(), Priority::Normal);
+
+        let bulk = store.with_priority(Priority::Low);
+        let interactive = store.with_priority(Priority::High);
+        assert_eq!(bulk.priority(), Priority::Low);
+        assert_eq!(interactive.priority(), Priority::High);
+
+        // The handles share one permit: the bulk read holds it until its
+        // body is consumed.
+        let body = bulk.inner.get(&path).await.unwrap();
+        let head = interactive.inner.head(&path);
+        tokio::pin!(head);
+        assert!(
+            tokio::time::timeout(Duration::from_millis(20), &mut head)
+                .await
+                .is_err()
+        );
+        assert_eq!(body.bytes().await.unwrap().as_ref(), b"LANCE");
+        assert_eq!(head.await.unwrap().size, 5);
+
+        let waits = store.io_stats_incremental().request_wait;
+        assert!(waits.high >= Duration::from_millis(20), "{waits:?}");
+        assert!(waits.low < Duration::from_millis(20), "{waits:?}");
+
+        // Without the option, priorities have no effect.
+        let (store, _) = ObjectStore::from_uri_and_params(
+            Arc::new(ObjectStoreRegistry::default()),
+            "memory:///",
+            &ObjectStoreParams::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            store.with_priority(Priority::High).priority(),
+            Priority::Normal
+        );
+    }
+
+    #[rstest]
+    #[case::default_gap(None, 1, 2)]
+    #[case::no_gap(Some("0"), 3, 0)]
+    #[tokio::test]
+    async fn test_get_ranges_coalesces(
+        #[case] gap: Option<&str>,
+        #[case] expected_requests: u64,
+        #[case] expected_coalesced: u64,
+    ) {
+        let registry = Arc::new(ObjectStoreRegistry::default());
+        let storage_options = gap
+            .map(|gap| HashMap::from([(COALESCE_GAP_KEY.to_string(), gap.to_string())]))
+            .unwrap_or_default();
+        let params = ObjectStoreParams {
+            storage_options_accessor: Some(Arc::new(StorageOptionsAccessor::with_static_options(
+                storage_options,
+            ))),
+            ..ObjectStoreParams::default()
+        };
+        let (store, base_path) = ObjectStore::from_uri_and_params(registry, "memory:///", &params)
+            .await
+            .unwrap();
+        let path = base_path.join("data.lance");
+        let content = (0..100u8).collect::<Vec<_>>();
+        store.put(&path, &content).await.unwrap();
+        store.io_stats_incremental();
+
+        let ranges = vec![50..60, 0..10, 20..30];
+        let bytes = store.get_ranges(&path, ranges.clone()).await.unwrap();
+        for (range, bytes) in ranges.iter().zip(&bytes) {
+            assert_eq!(
+                bytes.as_ref(),
+                &content[range.start as usize..range.end as usize]
+            );
+        }
+
+        let stats = store.io_stats_incremental();
+        assert_eq!(stats.read_iops, expected_requests);
+        assert_eq!(stats.coalesced_ranges, expected_coalesced);
+    }
+
+    #[tokio::test]
+    async fn test_access_histogram() {
+        let registry = Arc::new(ObjectStoreRegistry::default());
+        let (store, base_path) =
+            ObjectStore::from_uri_and_params(registry.clone(), "memory:///", &Default::default())
+                .await
+                .unwrap();
+        let path = base_path.clone().join("a");
+        store.put(&path, b"hello").await.unwrap();
+        store.read_one_all(&path).await.unwrap();
+        assert!(store.access_histogram().is_empty());
+
+        let params = ObjectStoreParams {
+            storage_options_accessor: Some(Arc::new(StorageOptionsAccessor::with_static_options(
+                HashMap::from([(ACCESS_HISTOGRAM_SIZE_KEY.to_string(), "2".to_string())]),
+            ))),
+            ..ObjectStoreParams::default()
+        };
+        let (store, base_path) =
+            ObjectStore::from_uri_and_params(registry.clone(), "memory:///", &params)
+                .await
+                .unwrap();
+        for (name, reads) in [("a", 3), ("b", 1), ("c", 1)] {
+            let path = base_path.clone().join(name);
+            store.put(&path, b"hello").await.unwrap();
+            for _ in 0..reads {
+                store.read_one_all(&path).await.unwrap();
+            }
+        }
+        // "c" replaced the least read path "b" and inherited its count.
+        let histogram = store.access_histogram();
+        assert_eq!(
+            histogram,
+            vec![
+                PathAccess {
+                    path: base_path.clone().join("a"),
+                    count: 3,
+                    bytes: 15,
+                },
+                PathAccess {
+                    path: base_path.join("c"),
+                    count: 2,
+                    bytes: 5,
+                },
+            ]
+        );
+
+        let params = ObjectStoreParams {
+            storage_options_accessor: Some(Arc::new(StorageOptionsAccessor::with_static_options(
+                HashMap::from([(ACCESS_HISTOGRAM_SIZE_KEY.to_string(), "many".to_string())]),
+            ))),
+            ..ObjectStoreParams::default()
+        };
+        let err = ObjectStore::from_uri_and_params(registry, "memory:///", &params)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidInput { .. }), "{err:?}");
+    }
+
+    #[tokio::test]
+    async fn test_disk_cache_storage_options() {
+        let cache_dir = TempStrDir::default();
+        let registry = Arc::new(ObjectStoreRegistry::default());
+        let params = ObjectStoreParams {
+            storage_options_accessor: Some(Arc::new(StorageOptionsAccessor::with_static_options(
+                HashMap::from([(disk_cache::CACHE_DIR_KEY.to_string(), cache_dir.to_string())]),
+            ))),
+            ..ObjectStoreParams::default()
+        };
+        let (store, base_path) = ObjectStore::from_uri_and_params(registry, "memory:///", &params)
+            .await
+            .unwrap();
+        let path = base_path.join("data.lance");
+        store.put(&path, b"LANCE").await.unwrap();
+
+        for _ in 0..2 {
+            assert_eq!(store.read_one_all(&path).await.unwrap().as_ref(), b"LANCE");
+        }
+        let stats = store.io_stats_incremental();
+        assert_eq!(stats.disk_cache_misses, 1);
+        assert_eq!(stats.disk_cache_hits, 1);
+    }
+
+    #[tokio::test]
+    async fn test_read_only_storage_option() {
+        let tmp_path = TempStrDir::default();
+        write_to_file(&format!("{tmp_path}/foo.lance/data"), "LANCE").unwrap();
+
+        let registry = Arc::new(ObjectStoreRegistry::default());
+        let params = ObjectStoreParams {
+            storage_options_accessor: Some(Arc::new(StorageOptionsAccessor::with_static_options(
+                HashMap::from([(read_only::READ_ONLY_KEY.to_string(), "true".to_string())]),
+            ))),
+            ..ObjectStoreParams::default()
+        };
+        let uri = format!("file://{tmp_path}/foo.lance");
+        let (store, base_path) = ObjectStore::from_uri_and_params(registry, &uri, &params)
+            .await
+            .unwrap();
+        assert!(store.is_read_only());
+
+        let data = base_path.clone().join("data");
+        assert_eq!(store.read_one_all(&data).await.unwrap().as_ref(), b"LANCE");
+
+        let other = base_path.join("other");
+        let err = store.put(&other, b"nope").await.unwrap_err();
+        assert!(matches!(err, Error::NotSupported { .. }), "{err:?}");
+        assert!(err.to_string().contains("store is read-only"));
+        assert!(matches!(
+            store.delete(&data).await.unwrap_err(),
+            Error::NotSupported { .. }
+        ));
+        assert!(matches!(
+            store.copy(&data, &other).await.unwrap_err(),
+            Error::NotSupported { .. }
+        ));
+
+        // The backend is protected even when the inner store is used directly.
+        assert!(
+            store
+                .inner
+                .put(&other, PutPayload::from_static(b"nope"))
+                .await
+                .is_err()
+        );
+        assert!(!store.exists(&other).await.unwrap());
+        assert!(store.exists(&data).await.unwrap());
+    }
+
+    #[rstest]
+    #[case::relative("_data", "bucket/datasets/foo.lance/_data")]
+    #[case::slashes("/_data/", "bucket/datasets/foo.lance/_data")]
+    #[case::nested("_data/v1", "bucket/datasets/foo.lance/_data/v1")]
+    #[case::empty("/", "bucket/datasets/foo.lance")]
+    #[tokio::test]
+    async fn test_manifest_discovery_prefix(#[case] prefix: &str, #[case] expected: &str) {
+        let registry = Arc::new(ObjectStoreRegistry::default());
+        let uri = "memory://bucket/datasets/foo.lance";
+        let (plain, plain_base) =
+            ObjectStore::from_uri_and_params(registry.clone(), uri, &ObjectStoreParams::default())
+                .await
+                .unwrap();
+        assert_eq!(plain.manifest_discovery_base(&plain_base), plain_base);
+
+        let params = ObjectStoreParams {
+            storage_options_accessor: Some(Arc::new(StorageOptionsAccessor::with_static_options(
+                HashMap::from([(
+                    MANIFEST_DISCOVERY_PREFIX_KEY.to_string(),
+                    prefix.to_string(),
+                )]),
+            ))),
+            ..ObjectStoreParams::default()
+        };
+        let (store, base) = ObjectStore::from_uri_and_params(registry, uri, &params)
+            .await
+            .unwrap();
+        assert_eq!(base, plain_base);
+
+        // The prefix goes below the dataset root, not in front of the store
+        // prefix, so the two still name one location when joined.
+        let discovery_base = store.manifest_discovery_base(&base);
+        assert_eq!(discovery_base.as_ref(), expected);
+        assert_eq!(store.store_prefix, plain.store_prefix);
+        assert_eq!(
+            format!("{}/{}", store.store_prefix, discovery_base),
+            format!("memory/{expected}")
+        );
+    }
+
+    #[tokio::test]
+    #[cfg(windows)]
+    async fn test_windows_paths() {
+        use std::path::Component;
+        use std::path::Prefix;
+        use std::path::Prefix::*;
+
+        fn get_path_prefix(path: &StdPath) -> Prefix<'_> {
+            match path.components().next().unwrap() {
+                Component::Prefix(prefix_component) => prefix_component.kind(),
+                _ => panic!(),
+            }
+        }
+
+        fn get_drive_letter(prefix: Prefix) -> String {
+            match prefix {
+                Disk(bytes) => String::from_utf8(vec![bytes]).unwrap(),
+                _ => panic!(),
+            }
+        }
+
+        let tmp_path = TempStdFile::default();
+        let prefix = get_path_prefix(&tmp_path);
+        let drive_letter = get_drive_letter(prefix);
+
+        write_to_file(
+            &(format!("{drive_letter}:/test_folder/test.lance") + "/test_file"),
+            "WINDOWS",
+        )
+        .unwrap();
+
+        for uri in &[
+            format!("{drive_letter}:/test_folder/test.lance"),
+            format!("{drive_letter}:\\test_folder\\test.lance"),
+        ] {
+            let (store, base) = ObjectStore::from_uri(uri).await.unwrap();
+            let contents = read_from_store(store.as_ref(), &base.clone().join("test_file"))
+                .await
+                .unwrap();
+            assert_eq!(contents, "WINDOWS");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cross_filesystem_copy() {
+        // Create two temporary directories that simulate different filesystems
+        let source_dir = TempStdDir::default();
+        let dest_dir = TempStdDir::default();
+
+        // Create a test file in the source directory
+        let source_file_name = "test_file.txt";
+        let source_file = source_dir.join(source_file_name);
+        std::fs::write(&source_file, b"test content").unwrap();
+
+        // Create ObjectStore for local filesystem
+        let (store, base_path) = ObjectStore::from_uri(source_dir.to_str().unwrap())
+            .await
+            .unwrap();
+
+        // Create paths relative to the ObjectStore base
+        let from_path = base_path.clone().join(source_file_name);
+
+        // Use object_store::Path::parse for the destination
+        let dest_file = dest_dir.join("copied_file.txt");
+        let dest_str = dest_file.to_str().unwrap();
+        let to_path = object_store::path::Path::parse(dest_str).unwrap();
+
+        // Perform the copy operation
+        store.copy(&from_path, &to_path).await.unwrap();
+
+        // Verify the file was copied correctly
+        assert!(dest_file.exists());
+        let copied_content = std::fs::read(&dest_file).unwrap();
+        assert_eq!(copied_content, b"test content");
+    }
+
+    #[tokio::test]
+    async fn test_copy_creates_parent_directories() {
+        let source_dir = TempStdDir::default();
+        let dest_dir = TempStdDir::default();
+
+        // Create a test file in the source directory
+        let source_file_name = "test_file.txt";
+        let source_file = source_dir.join(source_file_name);
+        std::fs::write(&source_file, b"test content").unwrap();
+
+        // Create ObjectStore for local filesystem
+        let (store, base_path) = ObjectStore::from_uri(source_dir.to_str().unwrap())
+            .await
+            .unwrap();
+
+        // Create paths
+        let from_path = base_path.clone().join(source_file_name);
+
+        // Create destination with nested directories that don't exist yet
         let dest_file = dest_dir.join("nested").join("dirs").join("copied_file.txt");
         let dest_str = dest_file.to_str().unwrap();
         let to_path = object_store::path::Path::parse(dest_str).unwrap();
@@ -1615,6 +4344,111 @@ mod tests {
         }
     }
 
+    /// Inner store that lists objects in reverse lexical order.
+    #[derive(Debug)]
+    struct ReverseListStore {
+        inner: InMemory,
+    }
+
+    impl Display for ReverseListStore {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "ReverseListStore")
+        }
+    }
+
+    #[async_trait]
+    impl OSObjectStore for ReverseListStore {
+        async fn put_opts(
+            &self,
+            location: &Path,
+            bytes: PutPayload,
+            opts: PutOptions,
+        ) -> OSResult<PutResult> {
+            self.inner.put_opts(location, bytes, opts).await
+        }
+        async fn put_multipart_opts(
+            &self,
+            location: &Path,
+            opts: PutMultipartOptions,
+        ) -> OSResult<Box<dyn MultipartUpload>> {
+            self.inner.put_multipart_opts(location, opts).await
+        }
+        async fn get_opts(&self, location: &Path, options: GetOptions) -> OSResult<GetResult> {
+            self.inner.get_opts(location, options).await
+        }
+        async fn get_ranges(&self, location: &Path, ranges: &[Range<u64>]) -> OSResult<Vec<Bytes>> {
+            self.inner.get_ranges(location, ranges).await
+        }
+        fn delete_stream(
+            &self,
+            locations: BoxStream<'static, OSResult<Path>>,
+        ) -> BoxStream<'static, OSResult<Path>> {
+            self.inner.delete_stream(locations)
+        }
+        fn list(&self, prefix: Option<&Path>) -> BoxStream<'static, OSResult<ObjectMeta>> {
+            let listing = self.inner.list(prefix);
+            futures::stream::once(async move {
+                let mut objects = listing.collect::<Vec<_>>().await;
+                objects.reverse();
+                futures::stream::iter(objects)
+            })
+            .flatten()
+            .boxed()
+        }
+        fn list_with_offset(
+            &self,
+            prefix: Option<&Path>,
+            offset: &Path,
+        ) -> BoxStream<'static, OSResult<ObjectMeta>> {
+            self.inner.list_with_offset(prefix, offset)
+        }
+        async fn list_with_delimiter(&self, prefix: Option<&Path>) -> OSResult<ListResult> {
+            self.inner.list_with_delimiter(prefix).await
+        }
+        async fn copy_opts(&self, from: &Path, to: &Path, opts: CopyOptions) -> OSResult<()> {
+            self.inner.copy_opts(from, to, opts).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_sorted() {
+        let mut store = ObjectStore::memory();
+        store.inner = Arc::new(ReverseListStore {
+            inner: InMemory::new(),
+        });
+        store.list_is_lexically_ordered = false;
+        for name in ["b/2", "a/1", "b/1", "a", "c"] {
+            store.put(&Path::from(name), b"".as_slice()).await.unwrap();
+        }
+        let names = |listing: Pin<Box<dyn Stream<Item = Result<ObjectMeta>> + Send>>| async move {
+            listing
+                .map_ok(|meta| meta.location.to_string())
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap()
+        };
+
+        assert_eq!(
+            names(store.list(None)).await,
+            ["c", "b/2", "b/1", "a/1", "a"]
+        );
+        assert_eq!(
+            names(store.list_sorted(None)).await,
+            ["a", "a/1", "b/1", "b/2", "c"]
+        );
+        assert_eq!(
+            names(store.list_sorted(Some(Path::from("b")))).await,
+            ["b/1", "b/2"]
+        );
+
+        // Lexically ordered stores are streamed as they are listed.
+        store.list_is_lexically_ordered = true;
+        assert_eq!(
+            names(store.list_sorted(None)).await,
+            ["c", "b/2", "b/1", "a/1", "a"]
+        );
+    }
+
     #[tokio::test]
     async fn test_copy_streams_objects_larger_than_threshold() {
         // memory:// is non-local but isn't an S3/GCS scheme, so copy() wouldn't
@@ -0,0 +1,501 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Audits of the objects under a prefix against their checksums, see
+//! [`ObjectStore::verify_checksums`](super::ObjectStore::verify_checksums).
+//!
+//! Unlike [`verify_read`](super::verify_read), which checks objects as they
+//! are read, an audit walks a whole dataset to find corruption before a reader
+//! trips over it. Each listed object is checked one of two ways:
+//!
+//! - [`ChecksumMode::Download`] reads the object and compares the MD5 of its
+//!   content with its ETag. Only ETags that are a plain MD5, like those of
+//!   single-put objects on S3, OSS or COS, can be checked; other objects are
+//!   counted as unverifiable.
+//! - [`ChecksumMode::Head`] only HEADs the object and compares its size with
+//!   the listing, one cheap request per object.
+//!
+//! Both requests are conditional on the ETag of the listing, which keeps the
+//! caching layers of the store out of the way. Objects deleted or overwritten
+//! after they were listed are counted as changed, not as corrupt.
+//!
+//! Objects are listed and reported in lexical order, so an audit of millions
+//! of objects can be done in chunks, each starting after the last object of
+//! the previous one.
+
+use std::sync::Arc;
+
+use futures::TryStreamExt;
+use lance_core::{Error, Result};
+use md5::{Digest, Md5};
+use object_store::path::Path;
+use object_store::{GetOptions, GetRange, ObjectMeta, ObjectStore as OSObjectStore};
+
+use super::verify_read::{etag_md5, hex_string};
+
+/// How [`ObjectStore::verify_checksums`](super::ObjectStore::verify_checksums)
+/// checks each object, see the [module documentation](self).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChecksumMode {
+    /// Read the object and compare the MD5 of its content with its ETag.
+    #[default]
+    Download,
+    /// HEAD the object and compare its size with the listing.
+    Head,
+}
+
+/// Options of [`ObjectStore::verify_checksums`](super::ObjectStore::verify_checksums).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyChecksumsOptions {
+    pub mode: ChecksumMode,
+    /// Stop after checking this many objects and return a continuation token,
+    /// unset to check every object under the prefix at once.
+    pub max_objects: Option<usize>,
+    /// The [`VerifyReport::continuation_token`] of the previous chunk.
+    pub continuation_token: Option<String>,
+}
+
+/// The outcome of checking one object, see [`ObjectChecksum`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChecksumStatus {
+    /// The object matches its checksum.
+    Verified,
+    /// The object does not match its checksum. In [`ChecksumMode::Download`]
+    /// `expected` and `actual` are MD5s in hex, in [`ChecksumMode::Head`]
+    /// sizes in bytes.
+    Mismatched { expected: String, actual: String },
+    /// The ETag is not the MD5 of the content, e.g. for a multipart upload.
+    Unverifiable,
+    /// The object was deleted or overwritten after it was listed.
+    Changed,
+}
+
+/// One object checked by
+/// [`ObjectStore::verify_checksums_stream`](super::ObjectStore::verify_checksums_stream).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectChecksum {
+    pub location: Path,
+    pub status: ChecksumStatus,
+}
+
+/// An object that does not match its checksum, see [`VerifyReport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecksumMismatch {
+    pub path: Path,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// The result of [`ObjectStore::verify_checksums`](super::ObjectStore::verify_checksums).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// The number of objects that match their checksum
+    pub num_verified: u64,
+    /// The number of objects without a checksum to compare with
+    pub num_unverifiable: u64,
+    /// The number of objects deleted or overwritten during the audit
+    pub num_changed: u64,
+    /// Objects that do not match their checksum, in lexical order
+    pub mismatched: Vec<ChecksumMismatch>,
+    /// Where to continue the audit, unset once every object was checked
+    pub continuation_token: Option<String>,
+}
+
+impl VerifyReport {
+    /// True if no object failed its check.
+    pub fn is_clean(&self) -> bool {
+        self.mismatched.is_empty()
+    }
+
+    pub(crate) fn add(&mut self, check: ObjectChecksum) {
+        match check.status {
+            ChecksumStatus::Verified => self.num_verified += 1,
+            ChecksumStatus::Unverifiable => self.num_unverifiable += 1,
+            ChecksumStatus::Changed => self.num_changed += 1,
+            ChecksumStatus::Mismatched { expected, actual } => {
+                self.mismatched.push(ChecksumMismatch {
+                    path: check.location,
+                    expected,
+                    actual,
+                })
+            }
+        }
+    }
+}
+
+/// The path to list after to continue from `token`.
+pub(crate) fn parse_continuation_token(token: &str) -> Result<Path> {
+    Path::parse(token).map_err(|err| {
+        Error::invalid_input(format!(
+            "Invalid checksum continuation token '{token}': {err}"
+        ))
+    })
+}
+
+fn is_changed(error: &object_store::Error) -> bool {
+    matches!(
+        error,
+        object_store::Error::NotFound { .. } | object_store::Error::Precondition { .. }
+    )
+}
+
+/// Check the object `listed` against its checksum.
+pub(crate) async fn check_object(
+    store: Arc<dyn OSObjectStore>,
+    listed: ObjectMeta,
+    mode: ChecksumMode,
+) -> Result<ObjectChecksum> {
+    let status = match mode {
+        ChecksumMode::Download => check_content(store.as_ref(), &listed).await,
+        ChecksumMode::Head => check_head(store.as_ref(), &listed).await,
+    };
+    let status = match status {
+        Ok(status) => status,
+        Err(err) if is_changed(&err) => ChecksumStatus::Changed,
+        Err(err) => return Err(err.into()),
+    };
+    Ok(ObjectChecksum {
+        location: listed.location,
+        status,
+    })
+}
+
+async fn check_content(
+    store: &dyn OSObjectStore,
+    listed: &ObjectMeta,
+) -> object_store::Result<ChecksumStatus> {
+    let Some(expected) = listed.e_tag.as_deref().and_then(etag_md5) else {
+        return Ok(ChecksumStatus::Unverifiable);
+    };
+    let mut md5 = Md5::new();
+    // An empty range is invalid, and there is nothing to read anyway.
+    if listed.size > 0 {
+        // Reading a range keeps a verify-on-read layer from failing the read
+        // before the mismatch can be reported.
+        let options = GetOptions {
+            if_match: listed.e_tag.clone(),
+            range: Some(GetRange::Bounded(0..listed.size)),
+            ..Default::default()
+        };
+        let mut body = store
+            .get_opts(&listed.location, options)
+            .await?
+            .into_stream();
+        while let Some(chunk) = body.try_next().await? {
+            md5.update(&chunk);
+        }
+    }
+    let actual: [u8; 16] = md5.finalize().into();
+    if actual == expected {
+        Ok(ChecksumStatus::Verified)
+    } else {
+        Ok(ChecksumStatus::Mismatched {
+            expected: hex_string(&expected),
+            actual: hex_string(&actual),
+        })
+    }
+}
+
+async fn check_head(
+    store: &dyn OSObjectStore,
+    listed: &ObjectMeta,
+) -> object_store::Result<ChecksumStatus> {
+    let options = GetOptions {
+        head: true,
+        if_match: listed.e_tag.clone(),
+        ..Default::default()
+    };
+    let meta = store.get_opts(&listed.location, options).await?.meta;
+    if meta.size == listed.size {
+        Ok(ChecksumStatus::Verified)
+    } else {
+        Ok(ChecksumStatus::Mismatched {
+            expected: listed.size.to_string(),
+            actual: meta.size.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::fmt::{Display, Formatter};
+    use std::sync::Mutex;
+
+    use futures::StreamExt;
+    use futures::stream::BoxStream;
+    use object_store::memory::InMemory;
+    use object_store::{
+        CopyOptions, GetResult, ListResult, MultipartUpload, ObjectStoreExt, PutMultipartOptions,
+        PutOptions, PutPayload, PutResult, Result as OSResult,
+    };
+    use url::Url;
+
+    use super::*;
+
+    fn md5_hex(data: &[u8]) -> String {
+        hex_string(&Md5::digest(data))
+    }
+
+    /// Keeps the MD5 of what was put as the ETag, the way S3 does, so objects
+    /// can be corrupted behind its back by writing to `inner`.
+    #[derive(Debug, Default)]
+    struct S3LikeStore {
+        inner: InMemory,
+        e_tags: Mutex<HashMap<Path, String>>,
+    }
+
+    impl S3LikeStore {
+        fn e_tag(&self, location: &Path) -> Option<String> {
+            self.e_tags.lock().unwrap().get(location).cloned()
+        }
+    }
+
+    impl Display for S3LikeStore {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "S3LikeStore")
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl OSObjectStore for S3LikeStore {
+        async fn put_opts(
+            &self,
+            location: &Path,
+            payload: PutPayload,
+            opts: PutOptions,
+        ) -> OSResult<PutResult> {
+            let mut md5 = Md5::new();
+            payload.iter().for_each(|chunk| md5.update(chunk));
+            let e_tag = format!("\"{}\"", hex_string(&md5.finalize()));
+            self.inner.put_opts(location, payload, opts).await?;
+            self.e_tags
+                .lock()
+                .unwrap()
+                .insert(location.clone(), e_tag.clone());
+            Ok(PutResult {
+                e_tag: Some(e_tag),
+                version: None,
+            })
+        }
+
+        async fn put_multipart_opts(
+            &self,
+            location: &Path,
+            opts: PutMultipartOptions,
+        ) -> OSResult<Box<dyn MultipartUpload>> {
+            self.inner.put_multipart_opts(location, opts).await
+        }
+
+        async fn get_opts(&self, location: &Path, mut options: GetOptions) -> OSResult<GetResult> {
+            if let Some(if_match) = options.if_match.take()
+                && self.e_tag(location).as_ref() != Some(&if_match)
+            {
+                return Err(object_store::Error::Precondition {
+                    path: location.to_string(),
+                    source: "ETag does not match".into(),
+                });
+            }
+            let mut result = self.inner.get_opts(location, options).await?;
+            result.meta.e_tag = self.e_tag(location);
+            Ok(result)
+        }
+
+        fn delete_stream(
+            &self,
+            locations: BoxStream<'static, OSResult<Path>>,
+        ) -> BoxStream<'static, OSResult<Path>> {
+            self.inner.delete_stream(locations)
+        }
+
+        fn list(&self, prefix: Option<&Path>) -> BoxStream<'static, OSResult<ObjectMeta>> {
+            let e_tags = self.e_tags.lock().unwrap().clone();
+            self.inner
+                .list(prefix)
+                .map_ok(move |mut meta| {
+                    meta.e_tag = e_tags.get(&meta.location).cloned();
+                    meta
+                })
+                .boxed()
+        }
+
+        async fn list_with_delimiter(&self, prefix: Option<&Path>) -> OSResult<ListResult> {
+            self.inner.list_with_delimiter(prefix).await
+        }
+
+        async fn copy_opts(&self, from: &Path, to: &Path, opts: CopyOptions) -> OSResult<()> {
+            self.inner.copy_opts(from, to, opts).await
+        }
+    }
+
+    fn lance_store(inner: Arc<S3LikeStore>) -> super::super::ObjectStore {
+        super::super::ObjectStore::new(
+            inner,
+            Url::parse("memory:///").unwrap(),
+            None,
+            None,
+            false,
+            true,
+            8,
+            3,
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_verify_checksums() {
+        let inner = Arc::new(S3LikeStore::default());
+        for (path, data) in [
+            ("data/0.lance", &b"lance"[..]),
+            ("data/1.lance", b"lance"),
+            ("data/2.lance", b"lance"),
+            ("data/empty.txt", b""),
+            ("other/3.lance", b"lance"),
+        ] {
+            inner
+                .put(&Path::from(path), PutPayload::from_static(data))
+                .await
+                .unwrap();
+        }
+        // Corrupted without changing the size or the ETag.
+        inner
+            .inner
+            .put(
+                &Path::from("data/1.lance"),
+                PutPayload::from_static(b"lancE"),
+            )
+            .await
+            .unwrap();
+        // The ETag of a multipart upload is not the MD5 of the content.
+        inner.e_tags.lock().unwrap().insert(
+            Path::from("data/2.lance"),
+            format!("\"{}-2\"", md5_hex(b"lance")),
+        );
+        let store = lance_store(inner);
+
+        let report = store
+            .verify_checksums(&Path::from("data"), 2, VerifyChecksumsOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(
+            report,
+            VerifyReport {
+                num_verified: 2,
+                num_unverifiable: 1,
+                num_changed: 0,
+                mismatched: vec![ChecksumMismatch {
+                    path: Path::from("data/1.lance"),
+                    expected: md5_hex(b"lance"),
+                    actual: md5_hex(b"lancE"),
+                }],
+                continuation_token: None,
+            }
+        );
+        assert!(!report.is_clean());
+
+        // A HEAD can't tell the corruption apart.
+        let options = VerifyChecksumsOptions {
+            mode: ChecksumMode::Head,
+            ..Default::default()
+        };
+        let report = store
+            .verify_checksums(&Path::from("data"), 2, options)
+            .await
+            .unwrap();
+        assert!(report.is_clean(), "{report:?}");
+        assert_eq!(report.num_verified, 4);
+    }
+
+    #[tokio::test]
+    async fn test_verify_checksums_in_chunks() {
+        let inner = Arc::new(S3LikeStore::default());
+        for i in 0..5 {
+            inner
+                .put(
+                    &Path::from(format!("data/{i}")),
+                    PutPayload::from_static(b"lance"),
+                )
+                .await
+                .unwrap();
+        }
+        let store = lance_store(inner);
+
+        let mut chunks = vec![];
+        let mut continuation_token = None;
+        loop {
+            let options = VerifyChecksumsOptions {
+                max_objects: Some(2),
+                continuation_token,
+                ..Default::default()
+            };
+            let report = store
+                .verify_checksums(&Path::from("data"), 4, options)
+                .await
+                .unwrap();
+            chunks.push((report.num_verified, report.continuation_token.clone()));
+            continuation_token = report.continuation_token;
+            if continuation_token.is_none() {
+                break;
+            }
+        }
+        assert_eq!(
+            chunks,
+            vec![
+                (2, Some("data/1".to_string())),
+                (2, Some("data/3".to_string())),
+                (1, None),
+            ]
+        );
+
+        let checked = store
+            .verify_checksums_stream(
+                Some(Path::from("data")),
+                Some(Path::from("data/2")),
+                4,
+                ChecksumMode::Download,
+            )
+            .map_ok(|check| check.location.to_string())
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(checked, vec!["data/3", "data/4"]);
+    }
+
+    #[tokio::test]
+    async fn test_objects_changed_after_listing() {
+        let store = Arc::new(S3LikeStore::default());
+        for path in ["data/0", "data/1", "data/2"] {
+            store
+                .put(&Path::from(path), PutPayload::from_static(b"lance"))
+                .await
+                .unwrap();
+        }
+        let listed = store
+            .list(Some(&Path::from("data")))
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        store.delete(&Path::from("data/0")).await.unwrap();
+        store
+            .put(&Path::from("data/1"), PutPayload::from_static(b"other"))
+            .await
+            .unwrap();
+
+        for mode in [ChecksumMode::Download, ChecksumMode::Head] {
+            let mut statuses = vec![];
+            for meta in listed.clone() {
+                let check = check_object(store.clone(), meta, mode).await.unwrap();
+                statuses.push(check.status);
+            }
+            assert_eq!(
+                statuses,
+                vec![
+                    ChecksumStatus::Changed,
+                    ChecksumStatus::Changed,
+                    ChecksumStatus::Verified
+                ],
+                "{mode:?}"
+            );
+        }
+    }
+}
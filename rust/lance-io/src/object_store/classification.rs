@@ -0,0 +1,529 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Storage-specific classification for [`lance_core::Error::classification`].
+//!
+//! `lance-core` classifies the structured `object_store` errors on its own. The
+//! classification here covers what it cannot see: throttle responses that the
+//! builtin stores only report in the message, and the OpenDAL errors behind the
+//! opendal backed providers. Every store a provider builds is wrapped in a
+//! [`ClassifyingStore`], which attaches this classification to the errors
+//! leaving lance-io.
+//!
+//! A store can also replace the retry decision entirely with a [`RetryClassifier`],
+//! see [`ObjectStoreParams::is_retryable`](super::ObjectStoreParams::is_retryable).
+//!
+//! Throttle responses may say how long to back off in a `Retry-After` header,
+//! which the retry loops honor through [`retry_delay`].
+
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
+use lance_core::error::{Classification, ClassifiedError, ErrorClass};
+use object_store::path::Path;
+use object_store::{
+    CopyOptions, GetOptions, GetResult, GetResultPayload, ListResult, MultipartUpload, ObjectMeta,
+    ObjectStore, PutMultipartOptions, PutOptions, PutPayload, PutResult, RenameOptions,
+    Result as OSResult, UploadPart,
+};
+
+use super::throttle::is_throttle_error;
+
+fn classify_storage_error(err: &(dyn std::error::Error + 'static)) -> Option<Classification> {
+    if let Some(err) = err.downcast_ref::<ClassifiedError>() {
+        return Some(err.classification());
+    }
+    #[cfg(any(
+        feature = "aws",
+        feature = "azure",
+        feature = "gcp",
+        feature = "oss",
+        feature = "tencent",
+        feature = "huggingface"
+    ))]
+    if let Some(err) = err.downcast_ref::<opendal::Error>() {
+        return Some(opendal_errors::classify(err));
+    }
+    let err = err.downcast_ref::<object_store::Error>()?;
+    if let object_store::Error::Generic { source, .. } = err
+        && let Some(classification) = classify_storage_error(source.as_ref())
+    {
+        // The OpenDAL source knows the kind and status, prefer it over the message.
+        return Some(classification);
+    }
+    is_throttle_error(err).then(|| Classification::new(ErrorClass::Throttled, None))
+}
+
+/// Classify a storage error that has not been converted into a
+/// [`lance_core::Error`] yet, e.g. in the retry loops.
+pub fn classify(err: &object_store::Error) -> Classification {
+    classify_storage_error(err).unwrap_or_else(|| Classification::of(err))
+}
+
+/// Attach the lance-io classification to `err`, so it is kept once `err` is
+/// converted into a [`lance_core::Error`].
+///
+/// Only [`object_store::Error::Generic`] needs it, `lance-core` classifies the
+/// other variants from the variant itself.
+pub fn with_classification(err: object_store::Error) -> object_store::Error {
+    let Some(classification) = classify_storage_error(&err) else {
+        return err;
+    };
+    match err {
+        object_store::Error::Generic { store, source } if !source.is::<ClassifiedError>() => {
+            object_store::Error::Generic {
+                store,
+                source: Box::new(ClassifiedError::new(classification, source)),
+            }
+        }
+        err => err,
+    }
+}
+
+/// An [`ObjectStore`] wrapper that classifies every error of the wrapped
+/// store with [`with_classification`].
+#[derive(Debug)]
+pub struct ClassifyingStore {
+    target: Arc<dyn ObjectStore>,
+}
+
+impl ClassifyingStore {
+    pub fn new(target: Arc<dyn ObjectStore>) -> Self {
+        Self { target }
+    }
+}
+
+impl Display for ClassifyingStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ClassifyingStore({})", self.target)
+    }
+}
+
+#[async_trait]
+#[deny(clippy::missing_trait_methods)]
+impl ObjectStore for ClassifyingStore {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        payload: PutPayload,
+        opts: PutOptions,
+    ) -> OSResult<PutResult> {
+        self.target
+            .put_opts(location, payload, opts)
+            .await
+            .map_err(with_classification)
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        opts: PutMultipartOptions,
+    ) -> OSResult<Box<dyn MultipartUpload>> {
+        let upload = self
+            .target
+            .put_multipart_opts(location, opts)
+            .await
+            .map_err(with_classification)?;
+        Ok(Box::new(ClassifyingUpload { target: upload }))
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> OSResult<GetResult> {
+        let mut result = self
+            .target
+            .get_opts(location, options)
+            .await
+            .map_err(with_classification)?;
+        result.payload = match result.payload {
+            GetResultPayload::Stream(stream) => {
+                GetResultPayload::Stream(stream.map_err(with_classification).boxed())
+            }
+            payload => payload,
+        };
+        Ok(result)
+    }
+
+    async fn get_ranges(&self, location: &Path, ranges: &[Range<u64>]) -> OSResult<Vec<Bytes>> {
+        self.target
+            .get_ranges(location, ranges)
+            .await
+            .map_err(with_classification)
+    }
+
+    fn delete_stream(
+        &self,
+        locations: BoxStream<'static, OSResult<Path>>,
+    ) -> BoxStream<'static, OSResult<Path>> {
+        self.target
+            .delete_stream(locations)
+            .map_err(with_classification)
+            .boxed()
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'static, OSResult<ObjectMeta>> {
+        self.target
+            .list(prefix)
+            .map_err(with_classification)
+            .boxed()
+    }
+
+    fn list_with_offset(
+        &self,
+        prefix: Option<&Path>,
+        offset: &Path,
+    ) -> BoxStream<'static, OSResult<ObjectMeta>> {
+        self.target
+            .list_with_offset(prefix, offset)
+            .map_err(with_classification)
+            .boxed()
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> OSResult<ListResult> {
+        self.target
+            .list_with_delimiter(prefix)
+            .await
+            .map_err(with_classification)
+    }
+
+    async fn copy_opts(&self, from: &Path, to: &Path, opts: CopyOptions) -> OSResult<()> {
+        self.target
+            .copy_opts(from, to, opts)
+            .await
+            .map_err(with_classification)
+    }
+
+    async fn rename_opts(&self, from: &Path, to: &Path, opts: RenameOptions) -> OSResult<()> {
+        self.target
+            .rename_opts(from, to, opts)
+            .await
+            .map_err(with_classification)
+    }
+}
+
+#[derive(Debug)]
+struct ClassifyingUpload {
+    target: Box<dyn MultipartUpload>,
+}
+
+#[async_trait]
+impl MultipartUpload for ClassifyingUpload {
+    fn put_part(&mut self, data: PutPayload) -> UploadPart {
+        let part = self.target.put_part(data);
+        Box::pin(async move { part.await.map_err(with_classification) })
+    }
+
+    async fn complete(&mut self) -> OSResult<PutResult> {
+        self.target.complete().await.map_err(with_classification)
+    }
+
+    async fn abort(&mut self) -> OSResult<()> {
+        self.target.abort().await.map_err(with_classification)
+    }
+}
+
+/// Whether `err` is a throttle response, e.g. HTTP 429 or a 503 `SlowDown`.
+pub fn is_throttled(err: &object_store::Error) -> bool {
+    classify_storage_error(err)
+        .is_some_and(|classification| classification.class == ErrorClass::Throttled)
+}
+
+/// The longest `Retry-After` the retry loops wait for. A store asking for more
+/// is not retried, so the throttled error reaches the caller, which can shed
+/// load instead of holding the request open.
+pub const MAX_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+/// How long the store asked to wait before retrying `err`, from the
+/// `Retry-After` header of the response.
+///
+/// Neither `object_store` nor OpenDAL expose the response headers, so they are
+/// found in the rendered error chain: OpenDAL attaches the response parts as
+/// context (`headers: {"retry-after": "2"}`) and some stores echo the header in
+/// the message. Only the delay-seconds form is understood, an HTTP date is ignored.
+pub fn retry_after(err: &object_store::Error) -> Option<Duration> {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(err);
+    while let Some(err) = source {
+        if let Some(delay) = parse_retry_after(&err.to_string()) {
+            return Some(delay);
+        }
+        source = err.source();
+    }
+    None
+}
+
+/// The delay before retrying `err`: the `backoff` of the retry loop, or the
+/// `Retry-After` of the response if it is longer.
+///
+/// Returns `None` if the store asked to wait longer than [`MAX_RETRY_AFTER`],
+/// in which case the error should not be retried.
+pub fn retry_delay(err: &object_store::Error, backoff: Duration) -> Option<Duration> {
+    match retry_after(err) {
+        Some(retry_after) if retry_after > MAX_RETRY_AFTER => None,
+        Some(retry_after) => Some(retry_after.max(backoff)),
+        None => Some(backoff),
+    }
+}
+
+fn parse_retry_after(rendered: &str) -> Option<Duration> {
+    const HEADER: &str = "retry-after";
+    let lowercase = rendered.to_ascii_lowercase();
+    let mut rest = lowercase.as_str();
+    while let Some(start) = rest.find(HEADER) {
+        rest = &rest[start + HEADER.len()..];
+        let value = rest.trim_start_matches(['"', '\'', ':', '=', ' ']);
+        let end = value
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(value.len());
+        if let Ok(seconds) = value[..end].parse::<f64>()
+            && seconds.is_finite()
+        {
+            return Some(Duration::from_secs_f64(seconds));
+        }
+    }
+    None
+}
+
+/// Decides whether a failed object store request should be retried.
+pub trait RetryClassifierFn: Fn(&object_store::Error) -> bool + Send + Sync {}
+
+impl<F> RetryClassifierFn for F where F: Fn(&object_store::Error) -> bool + Send + Sync {}
+
+impl std::fmt::Debug for dyn RetryClassifierFn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "RetryClassifierFn")
+    }
+}
+
+/// A per-store override of the builtin retry classification.
+pub type RetryClassifier = Arc<dyn RetryClassifierFn>;
+
+/// Whether `err` should be retried, asking `classifier` if the store has one
+/// and falling back to the `builtin` decision of the retry loop otherwise.
+pub fn should_retry(
+    classifier: Option<&RetryClassifier>,
+    err: &object_store::Error,
+    builtin: impl FnOnce(&object_store::Error) -> bool,
+) -> bool {
+    match classifier {
+        Some(classifier) => classifier(err),
+        None => builtin(err),
+    }
+}
+
+#[cfg(any(
+    feature = "aws",
+    feature = "azure",
+    feature = "gcp",
+    feature = "oss",
+    feature = "tencent",
+    feature = "huggingface"
+))]
+mod opendal_errors {
+    use lance_core::error::{Classification, ErrorClass};
+    use opendal::ErrorKind;
+
+    use super::parse_retry_after;
+
+    pub(super) fn classify(err: &opendal::Error) -> Classification {
+        let status_code = response_status(err);
+        match err.kind() {
+            ErrorKind::NotFound => Classification::new(ErrorClass::NotFound, Some(404)),
+            ErrorKind::ConditionNotMatch => {
+                Classification::new(ErrorClass::Conflict, status_code.or(Some(412)))
+            }
+            ErrorKind::AlreadyExists => Classification::new(ErrorClass::Conflict, Some(409)),
+            ErrorKind::RateLimited => {
+                Classification::new(ErrorClass::Throttled, status_code.or(Some(429)))
+            }
+            ErrorKind::PermissionDenied => {
+                Classification::new(ErrorClass::AuthExpired, status_code.or(Some(403)))
+            }
+            _ if status_code == Some(429)
+                || is_slow_down(err.message())
+                || (status_code == Some(503) && parse_retry_after(&err.to_string()).is_some()) =>
+            {
+                Classification::new(ErrorClass::Throttled, status_code)
+            }
+            _ if err.is_temporary() => Classification::new(ErrorClass::Transient, status_code),
+            _ => Classification::new(ErrorClass::Other, status_code),
+        }
+    }
+
+    /// COS, OSS and S3 answer `503 SlowDown` when a prefix is hot, which OpenDAL
+    /// reports as a temporary `Unexpected` error with the service code in the message.
+    fn is_slow_down(message: &str) -> bool {
+        let lowercase = message.to_ascii_lowercase();
+        lowercase.contains("slowdown")
+            || lowercase.contains("serverbusy")
+            || lowercase.contains("please reduce your request rate")
+    }
+
+    /// The HTTP status of the response that produced `err`.
+    ///
+    /// OpenDAL does not expose it directly, but the services attach the response
+    /// parts as context, which `Display` renders as `response: Parts { status: 503, .. }`.
+    fn response_status(err: &opendal::Error) -> Option<u16> {
+        let rendered = err.to_string();
+        let parts = &rendered[rendered.find("response: Parts {")?..];
+        let status = &parts[parts.find("status: ")? + "status: ".len()..];
+        status.get(..3)?.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lance_core::Error;
+
+    fn classify(source: object_store::Error) -> Classification {
+        Error::from(with_classification(source)).classification()
+    }
+
+    #[test]
+    fn test_classify_throttle_message() {
+        let classification = classify(object_store::Error::Generic {
+            store: "S3",
+            source: "Error after 10 retries, max_retries: 10, retry_timeout: 180s".into(),
+        });
+        assert_eq!(classification.class, ErrorClass::Throttled);
+        assert!(classification.retryable);
+
+        let classification = classify(object_store::Error::Generic {
+            store: "S3",
+            source: "connection refused".into(),
+        });
+        assert_eq!(classification, Classification::OTHER);
+    }
+
+    #[rstest::rstest]
+    #[case::seconds("HTTP 429 Too Many Requests, Retry-After: 2", Some(2000))]
+    #[case::fraction("throttled, retry-after=0.5", Some(500))]
+    #[case::header_map(
+        r#"response: Parts { status: 503, headers: {"x-cos-request-id": "abc", "retry-after": "3"} }"#,
+        Some(3000)
+    )]
+    #[case::http_date("Retry-After: Wed, 21 Oct 2026 07:28:00 GMT", None)]
+    #[case::missing("SlowDown: Please reduce your request rate", None)]
+    fn test_retry_after(#[case] msg: &str, #[case] expected_ms: Option<u64>) {
+        let err = object_store::Error::Generic {
+            store: "S3",
+            source: msg.into(),
+        };
+        assert_eq!(retry_after(&err), expected_ms.map(Duration::from_millis));
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let backoff = Duration::from_millis(100);
+        let err = |msg: &str| object_store::Error::Generic {
+            store: "S3",
+            source: msg.to_string().into(),
+        };
+        assert_eq!(retry_delay(&err("SlowDown"), backoff), Some(backoff));
+        assert_eq!(
+            retry_delay(&err("SlowDown, Retry-After: 1"), backoff),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(
+            retry_delay(&err("SlowDown, Retry-After: 0"), backoff),
+            Some(backoff)
+        );
+        assert_eq!(
+            retry_delay(&err("SlowDown, Retry-After: 3600"), backoff),
+            None
+        );
+    }
+
+    #[cfg(any(
+        feature = "aws",
+        feature = "azure",
+        feature = "gcp",
+        feature = "oss",
+        feature = "tencent",
+        feature = "huggingface"
+    ))]
+    #[rstest::rstest]
+    #[case::not_found(
+        opendal::Error::new(opendal::ErrorKind::NotFound, "no such key"),
+        ErrorClass::NotFound,
+        Some(404),
+        false
+    )]
+    #[case::condition_not_match(
+        opendal::Error::new(opendal::ErrorKind::ConditionNotMatch, "etag mismatch"),
+        ErrorClass::Conflict,
+        Some(412),
+        false
+    )]
+    #[case::rate_limited(
+        opendal::Error::new(opendal::ErrorKind::RateLimited, "too many requests"),
+        ErrorClass::Throttled,
+        Some(429),
+        true
+    )]
+    #[case::permission_denied(
+        opendal::Error::new(opendal::ErrorKind::PermissionDenied, "token expired")
+            .with_context("response", "Parts { status: 403, version: HTTP/1.1 }"),
+        ErrorClass::AuthExpired,
+        Some(403),
+        false
+    )]
+    #[case::slow_down(
+        opendal::Error::new(
+            opendal::ErrorKind::Unexpected,
+            r#"CosError { code: "SlowDown", message: "Please reduce your request rate." }"#
+        )
+        .with_context("response", "Parts { status: 503, version: HTTP/1.1 }")
+        .set_temporary(),
+        ErrorClass::Throttled,
+        Some(503),
+        true
+    )]
+    #[case::unavailable_retry_after(
+        opendal::Error::new(opendal::ErrorKind::Unexpected, "service unavailable")
+            .with_context(
+                "response",
+                r#"Parts { status: 503, version: HTTP/1.1, headers: {"retry-after": "1"} }"#
+            )
+            .set_temporary(),
+        ErrorClass::Throttled,
+        Some(503),
+        true
+    )]
+    #[case::temporary(
+        opendal::Error::new(opendal::ErrorKind::Unexpected, "internal error")
+            .with_context("response", "Parts { status: 500, version: HTTP/1.1 }")
+            .set_temporary(),
+        ErrorClass::Transient,
+        Some(500),
+        true
+    )]
+    #[case::permanent(
+        opendal::Error::new(opendal::ErrorKind::Unexpected, "bad request")
+            .with_context("response", "Parts { status: 400, version: HTTP/1.1 }"),
+        ErrorClass::Other,
+        Some(400),
+        false
+    )]
+    fn test_classify_opendal_error(
+        #[case] err: opendal::Error,
+        #[case] class: ErrorClass,
+        #[case] status_code: Option<u16>,
+        #[case] retryable: bool,
+    ) {
+        // The same conversion object_store_opendal applies to kinds it has no
+        // dedicated variant for.
+        let source = object_store::Error::Generic {
+            store: err.kind().into_static(),
+            source: Box::new(err),
+        };
+        let classification = classify(source);
+        assert_eq!(classification.class, class);
+        assert_eq!(classification.status_code, status_code);
+        assert_eq!(classification.retryable, retryable);
+    }
+}
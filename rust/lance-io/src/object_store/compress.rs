@@ -0,0 +1,370 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Compress objects on write.
+//!
+//! Setting the `storage_transparent_compress` storage option makes
+//! [`ObjectStore::create`] and [`ObjectStore::put`] compress what is written to
+//! keys ending in `.gz` or `.zst`, so callers write plain bytes and the object
+//! holds the compressed stream. Each codec needs the `gzip` or `zstd` feature
+//! of this crate; writing to a key whose codec was not compiled in is an error
+//! rather than silently storing uncompressed bytes. Writes to other keys are
+//! untouched.
+//!
+//! [`ObjectStore::create`]: crate::object_store::ObjectStore::create
+//! [`ObjectStore::put`]: crate::object_store::ObjectStore::put
+
+use std::collections::HashMap;
+
+use lance_core::utils::parse::str_is_truthy;
+use lance_core::{Error, Result};
+use object_store::path::Path;
+
+use crate::traits::Writer;
+
+/// Storage option enabling compression of `.gz` and `.zst` keys on write.
+/// Default, `false`.
+pub const TRANSPARENT_COMPRESS_KEY: &str = "storage_transparent_compress";
+
+/// Storage option for the compression level, the codec's default if unset.
+pub const TRANSPARENT_COMPRESS_LEVEL_KEY: &str = "storage_transparent_compress_level";
+
+/// The codec selected by the suffix of a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Codec {
+    Gzip,
+    Zstd,
+}
+
+impl Codec {
+    fn from_path(path: &Path) -> Option<Self> {
+        match path.extension() {
+            Some("gz") => Some(Self::Gzip),
+            Some("zst") => Some(Self::Zstd),
+            _ => None,
+        }
+    }
+}
+
+/// Compression applied to writes of compressed keys, see the module docs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TransparentCompression {
+    level: Option<i32>,
+}
+
+impl TransparentCompression {
+    /// The compression selected by [`TRANSPARENT_COMPRESS_KEY`] and
+    /// [`TRANSPARENT_COMPRESS_LEVEL_KEY`], `None` if writes are not compressed.
+    pub fn from_storage_options(
+        storage_options: Option<&HashMap<String, String>>,
+    ) -> Result<Option<Self>> {
+        let Some(storage_options) = storage_options else {
+            return Ok(None);
+        };
+        if !storage_options
+            .get(TRANSPARENT_COMPRESS_KEY)
+            .is_some_and(|value| str_is_truthy(value))
+        {
+            return Ok(None);
+        }
+        let level = storage_options
+            .get(TRANSPARENT_COMPRESS_LEVEL_KEY)
+            .map(|value| {
+                value.parse::<i32>().map_err(|_| {
+                    Error::invalid_input(format!(
+                        "Invalid value for storage option '{TRANSPARENT_COMPRESS_LEVEL_KEY}': '{value}', expected an integer"
+                    ))
+                })
+            })
+            .transpose()?;
+        Ok(Some(Self { level }))
+    }
+
+    /// Wrap `writer` so that it compresses what is written to it, if `path`
+    /// ends in a compressed suffix.
+    pub fn wrap(&self, path: &Path, writer: Box<dyn Writer>) -> Result<Box<dyn Writer>> {
+        match Codec::from_path(path) {
+            None => Ok(writer),
+            Some(codec) => self.wrap_with(codec, path, writer),
+        }
+    }
+
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    fn level(&self) -> async_compression::Level {
+        self.level.map_or(
+            async_compression::Level::Default,
+            async_compression::Level::Precise,
+        )
+    }
+
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    #[cfg_attr(all(feature = "gzip", feature = "zstd"), allow(unused_variables))]
+    fn wrap_with(
+        &self,
+        codec: Codec,
+        path: &Path,
+        writer: Box<dyn Writer>,
+    ) -> Result<Box<dyn Writer>> {
+        let encoder: Box<dyn Encoder> = match codec {
+            #[cfg(feature = "gzip")]
+            Codec::Gzip => Box::new(async_compression::tokio::write::GzipEncoder::with_quality(
+                writer,
+                self.level(),
+            )),
+            #[cfg(feature = "zstd")]
+            Codec::Zstd => Box::new(async_compression::tokio::write::ZstdEncoder::with_quality(
+                writer,
+                self.level(),
+            )),
+            #[cfg(not(feature = "gzip"))]
+            Codec::Gzip => return Err(missing_feature(codec, path)),
+            #[cfg(not(feature = "zstd"))]
+            Codec::Zstd => return Err(missing_feature(codec, path)),
+        };
+        Ok(Box::new(CompressingWriter { encoder, cursor: 0 }))
+    }
+
+    #[cfg(not(any(feature = "gzip", feature = "zstd")))]
+    fn wrap_with(
+        &self,
+        codec: Codec,
+        path: &Path,
+        _writer: Box<dyn Writer>,
+    ) -> Result<Box<dyn Writer>> {
+        Err(missing_feature(codec, path))
+    }
+}
+
+#[cfg(not(all(feature = "gzip", feature = "zstd")))]
+fn missing_feature(codec: Codec, path: &Path) -> Error {
+    let feature = match codec {
+        Codec::Gzip => "gzip",
+        Codec::Zstd => "zstd",
+    };
+    Error::not_supported(format!(
+        "Cannot compress {path} on write, lance-io was built without the {feature} feature"
+    ))
+}
+
+/// An encoder from `async_compression` wrapping the writer of the object.
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+trait Encoder: tokio::io::AsyncWrite + Unpin + Send {
+    fn inner(&mut self) -> &mut Box<dyn Writer>;
+}
+
+#[cfg(feature = "gzip")]
+impl Encoder for async_compression::tokio::write::GzipEncoder<Box<dyn Writer>> {
+    fn inner(&mut self) -> &mut Box<dyn Writer> {
+        self.get_mut()
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl Encoder for async_compression::tokio::write::ZstdEncoder<Box<dyn Writer>> {
+    fn inner(&mut self) -> &mut Box<dyn Writer> {
+        self.get_mut()
+    }
+}
+
+/// A [`Writer`] that compresses what is written to it.
+///
+/// [`Writer::tell`] reports the uncompressed bytes written so far, while the
+/// [`WriteResult`](crate::object_writer::WriteResult) returned by
+/// [`Writer::shutdown`] describes the compressed object.
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+struct CompressingWriter {
+    encoder: Box<dyn Encoder>,
+    cursor: usize,
+}
+
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+impl tokio::io::AsyncWrite for CompressingWriter {
+    fn poll_write(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &[u8],
+    ) -> std::task::Poll<std::io::Result<usize>> {
+        let poll = std::pin::Pin::new(&mut self.encoder).poll_write(cx, buf);
+        if let std::task::Poll::Ready(Ok(written)) = poll {
+            self.cursor += written;
+        }
+        poll
+    }
+
+    fn poll_flush(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        std::pin::Pin::new(&mut self.encoder).poll_flush(cx)
+    }
+
+    fn poll_shutdown(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<std::io::Result<()>> {
+        std::pin::Pin::new(&mut self.encoder).poll_shutdown(cx)
+    }
+}
+
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+#[async_trait::async_trait]
+impl Writer for CompressingWriter {
+    async fn tell(&mut self) -> Result<usize> {
+        Ok(self.cursor)
+    }
+
+    async fn shutdown(&mut self) -> Result<crate::object_writer::WriteResult> {
+        // Writes the trailer of the compressed stream and shuts down the
+        // object writer, whose result is then available from it.
+        tokio::io::AsyncWriteExt::shutdown(&mut self.encoder).await?;
+        self.encoder.inner().shutdown().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use bytes::Bytes;
+    use lance_core::utils::tempfile::TempStrDir;
+    use rstest::rstest;
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    use tokio::io::AsyncReadExt;
+    use tokio::io::AsyncWriteExt;
+
+    use super::*;
+    use crate::object_store::{
+        ObjectStore, ObjectStoreParams, ObjectStoreRegistry, StorageOptionsAccessor,
+    };
+
+    /// Write `content` to `name` with transparent compression and return the
+    /// stored object.
+    async fn write_compressed(uri: &str, name: &str, content: &[u8]) -> Result<Bytes> {
+        let params = ObjectStoreParams {
+            storage_options_accessor: Some(Arc::new(StorageOptionsAccessor::with_static_options(
+                HashMap::from([
+                    (TRANSPARENT_COMPRESS_KEY.to_string(), "true".to_string()),
+                    (TRANSPARENT_COMPRESS_LEVEL_KEY.to_string(), "3".to_string()),
+                ]),
+            ))),
+            ..ObjectStoreParams::default()
+        };
+        let (store, base_path) = ObjectStore::from_uri_and_params(
+            Arc::new(ObjectStoreRegistry::default()),
+            uri,
+            &params,
+        )
+        .await?;
+        let path = base_path.clone().join(name);
+
+        let mut writer = store.create(&path).await?;
+        for chunk in content.chunks(1000) {
+            writer.write_all(chunk).await?;
+        }
+        assert_eq!(writer.tell().await?, content.len());
+        let result = Writer::shutdown(writer.as_mut()).await?;
+
+        let stored = store.read_one_all(&path).await?;
+        assert_eq!(result.size, stored.len());
+        Ok(stored)
+    }
+
+    fn content() -> Vec<u8> {
+        "lance sidecar text\n".repeat(1000).into_bytes()
+    }
+
+    #[rstest]
+    #[case::memory("memory:///")]
+    #[case::local("file")]
+    #[tokio::test]
+    async fn test_uncompressed_suffix(#[case] uri: &str) {
+        let tmp = TempStrDir::default();
+        let uri = if uri == "file" { tmp.as_str() } else { uri };
+        let content = content();
+        let stored = write_compressed(uri, "notes.txt", &content).await.unwrap();
+        assert_eq!(stored.as_ref(), content.as_slice());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[rstest]
+    #[case::memory("memory:///")]
+    #[case::local("file")]
+    #[tokio::test]
+    async fn test_gzip_round_trip(#[case] uri: &str) {
+        let tmp = TempStrDir::default();
+        let uri = if uri == "file" { tmp.as_str() } else { uri };
+        let content = content();
+        let stored = write_compressed(uri, "notes.txt.gz", &content)
+            .await
+            .unwrap();
+        assert!(stored.len() < content.len());
+
+        let mut decompressed = Vec::new();
+        async_compression::tokio::bufread::GzipDecoder::new(stored.as_ref())
+            .read_to_end(&mut decompressed)
+            .await
+            .unwrap();
+        assert_eq!(decompressed, content);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[rstest]
+    #[case::memory("memory:///")]
+    #[case::local("file")]
+    #[tokio::test]
+    async fn test_zstd_round_trip(#[case] uri: &str) {
+        let tmp = TempStrDir::default();
+        let uri = if uri == "file" { tmp.as_str() } else { uri };
+        let content = content();
+        let stored = write_compressed(uri, "notes.txt.zst", &content)
+            .await
+            .unwrap();
+        assert!(stored.len() < content.len());
+
+        let mut decompressed = Vec::new();
+        async_compression::tokio::bufread::ZstdDecoder::new(stored.as_ref())
+            .read_to_end(&mut decompressed)
+            .await
+            .unwrap();
+        assert_eq!(decompressed, content);
+    }
+
+    #[cfg(not(feature = "zstd"))]
+    #[tokio::test]
+    async fn test_missing_codec() {
+        let err = write_compressed("memory:///", "notes.txt.zst", &content())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::NotSupported { .. }), "{err:?}");
+    }
+
+    #[test]
+    fn test_from_storage_options() {
+        assert_eq!(
+            TransparentCompression::from_storage_options(None).unwrap(),
+            None
+        );
+        let options = HashMap::from([(TRANSPARENT_COMPRESS_KEY.to_string(), "false".to_string())]);
+        assert_eq!(
+            TransparentCompression::from_storage_options(Some(&options)).unwrap(),
+            None
+        );
+
+        let mut options =
+            HashMap::from([(TRANSPARENT_COMPRESS_KEY.to_string(), "true".to_string())]);
+        assert_eq!(
+            TransparentCompression::from_storage_options(Some(&options)).unwrap(),
+            Some(TransparentCompression { level: None })
+        );
+        options.insert(TRANSPARENT_COMPRESS_LEVEL_KEY.to_string(), "9".to_string());
+        assert_eq!(
+            TransparentCompression::from_storage_options(Some(&options)).unwrap(),
+            Some(TransparentCompression { level: Some(9) })
+        );
+        options.insert(
+            TRANSPARENT_COMPRESS_LEVEL_KEY.to_string(),
+            "max".to_string(),
+        );
+        assert!(TransparentCompression::from_storage_options(Some(&options)).is_err());
+    }
+}
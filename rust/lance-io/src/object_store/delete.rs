@@ -0,0 +1,408 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Deletes of many paths, see [`ObjectStore::delete_paths`].
+//!
+//! Paths are grouped into batches of as many paths as the store deletes with
+//! one request, see [`ObjectStore::delete_batch_size`], and a bounded number
+//! of batches is deleted at a time. Paths that fail with a retryable error,
+//! most often throttling, are retried with backoff. Paths that still fail are
+//! reported one by one instead of failing the whole delete, so the caller can
+//! record them and try again later.
+
+use std::sync::Arc;
+
+use futures::{StreamExt, TryStreamExt, stream::BoxStream};
+use lance_core::Result;
+use lance_core::utils::backoff::Backoff;
+use object_store::path::Path;
+
+use super::ObjectStore;
+use super::classification::{classify, retry_delay, should_retry};
+use crate::deadline;
+
+/// How many times a path that failed with a retryable error is retried.
+const MAX_DELETE_RETRIES: usize = 5;
+
+/// The outcome of deleting one path with [`ObjectStore::delete_paths`].
+#[derive(Debug, Clone)]
+pub struct DeletedPath {
+    pub path: Path,
+    /// Why the path could not be deleted, after retries. `None` if it was
+    /// deleted or did not exist.
+    pub error: Option<Arc<object_store::Error>>,
+}
+
+impl DeletedPath {
+    pub fn is_deleted(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+pub(super) fn delete_paths<'a>(
+    store: &'a ObjectStore,
+    paths: BoxStream<'a, Result<Path>>,
+    concurrency: usize,
+) -> BoxStream<'a, Result<DeletedPath>> {
+    paths
+        .try_chunks(store.delete_batch_size())
+        .map(move |batch| async move {
+            let batch = batch.map_err(|e| e.1)?;
+            Ok::<_, lance_core::Error>(delete_batch_with_retry(store, batch).await)
+        })
+        .buffer_unordered(concurrency.max(1))
+        .map_ok(|deleted| futures::stream::iter(deleted).map(Ok))
+        .try_flatten()
+        .boxed()
+}
+
+async fn delete_batch_with_retry(store: &ObjectStore, paths: Vec<Path>) -> Vec<DeletedPath> {
+    let mut deleted = Vec::with_capacity(paths.len());
+    let mut pending = paths;
+    let mut backoff = Backoff::default();
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let failed: Vec<(Path, Arc<object_store::Error>)> =
+            match delete_batch(store, &pending).await {
+                Ok(errors) => pending
+                    .into_iter()
+                    .zip(errors)
+                    .filter_map(|(path, error)| match error {
+                        None => {
+                            deleted.push(DeletedPath { path, error: None });
+                            None
+                        }
+                        Some(error) => Some((path, Arc::new(error))),
+                    })
+                    .collect(),
+                Err(error) => {
+                    let error = Arc::new(error);
+                    pending
+                        .into_iter()
+                        .map(|path| (path, error.clone()))
+                        .collect()
+                }
+            };
+        if failed.is_empty() {
+            return deleted;
+        }
+
+        // Wait for the longest Retry-After of the failed paths, a store asking
+        // for more than we wait for fails them all.
+        let delay = failed
+            .iter()
+            .try_fold(backoff.next_backoff(), |delay, (_, error)| {
+                retry_delay(error, delay)
+            });
+        let can_retry =
+            attempt <= MAX_DELETE_RETRIES && delay.is_some_and(deadline::can_retry_after);
+        let delay = delay.unwrap_or_default();
+        let (retry, permanent): (Vec<_>, Vec<_>) = failed.into_iter().partition(|(_, error)| {
+            can_retry
+                && should_retry(store.retry_classifier.as_ref(), error.as_ref(), |error| {
+                    classify(error).retryable
+                })
+        });
+        deleted.extend(permanent.into_iter().map(|(path, error)| {
+            log::warn!("Failed to delete {}: {}", path, error);
+            DeletedPath {
+                path,
+                error: Some(error),
+            }
+        }));
+        if retry.is_empty() {
+            return deleted;
+        }
+        log::debug!(
+            "Retrying the delete of {} paths in {:?}: {}",
+            retry.len(),
+            delay,
+            retry[0].1
+        );
+        tokio::time::sleep(delay).await;
+        pending = retry.into_iter().map(|(path, _)| path).collect();
+    }
+}
+
+/// Delete `paths` with one request where the store supports it.
+///
+/// Returns the error of each path, or the error of the whole request. A path
+/// that does not exist counts as deleted.
+async fn delete_batch(
+    store: &ObjectStore,
+    paths: &[Path],
+) -> std::result::Result<Vec<Option<object_store::Error>>, object_store::Error> {
+    // `object_store_opendal` deletes one path per request, so batches go
+    // through the operator of services that delete in bulk.
+    #[cfg(any(
+        feature = "aws",
+        feature = "azure",
+        feature = "gcp",
+        feature = "oss",
+        feature = "huggingface",
+        feature = "tencent"
+    ))]
+    if let Some(operator) = &store.opendal_operator
+        && paths.len() > 1
+    {
+        opendal_delete_batch(operator, paths).await?;
+        return Ok(paths.iter().map(|_| None).collect());
+    }
+
+    let locations = futures::stream::iter(paths.to_vec()).map(Ok).boxed();
+    let results = store
+        .inner
+        .delete_stream(locations)
+        .collect::<Vec<_>>()
+        .await;
+    if results.len() != paths.len() {
+        // Stores that batch deletes return a single error when the whole
+        // request fails.
+        let num_results = results.len();
+        return Err(results
+            .into_iter()
+            .find_map(|result| result.err())
+            .unwrap_or_else(|| object_store::Error::Generic {
+                store: "ObjectStore",
+                source: format!(
+                    "delete returned {} results for {} paths",
+                    num_results,
+                    paths.len()
+                )
+                .into(),
+            }));
+    }
+    Ok(results
+        .into_iter()
+        .map(|result| match result {
+            Ok(_) | Err(object_store::Error::NotFound { .. }) => None,
+            Err(error) => Some(error),
+        })
+        .collect())
+}
+
+#[cfg(any(
+    feature = "aws",
+    feature = "azure",
+    feature = "gcp",
+    feature = "oss",
+    feature = "huggingface",
+    feature = "tencent"
+))]
+async fn opendal_delete_batch(
+    operator: &opendal::Operator,
+    paths: &[Path],
+) -> std::result::Result<(), object_store::Error> {
+    use opendal::raw::percent_decode_path;
+
+    let to_error = |source: opendal::Error| object_store::Error::Generic {
+        store: "OpenDAL",
+        source: Box::new(source),
+    };
+    let mut deleter = operator.deleter().await.map_err(to_error)?;
+    for path in paths {
+        deleter
+            .delete(percent_decode_path(path.as_ref()))
+            .await
+            .map_err(to_error)?;
+    }
+    deleter.close().await.map_err(to_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::fmt::{Display, Formatter};
+    use std::sync::Mutex;
+
+    use futures::{TryFutureExt, stream};
+    use object_store::{
+        CopyOptions, GetOptions, GetResult, ListResult, MultipartUpload, ObjectMeta,
+        PutMultipartOptions, PutOptions, PutPayload, PutResult, Result as OSResult,
+    };
+    use rstest::rstest;
+    use url::Url;
+
+    use super::*;
+
+    /// Records the paths of every delete request. Every `throttle_every`th
+    /// request is throttled, and `denied` paths fail.
+    #[derive(Debug, Default)]
+    struct DeleteState {
+        requests: Mutex<Vec<Vec<Path>>>,
+        throttle_every: Option<usize>,
+        denied: HashSet<Path>,
+    }
+
+    #[derive(Debug)]
+    struct DeleteRecordingStore(Arc<DeleteState>);
+
+    impl Display for DeleteRecordingStore {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "DeleteRecordingStore")
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl object_store::ObjectStore for DeleteRecordingStore {
+        async fn put_opts(
+            &self,
+            _location: &Path,
+            _payload: PutPayload,
+            _opts: PutOptions,
+        ) -> OSResult<PutResult> {
+            unimplemented!()
+        }
+
+        async fn put_multipart_opts(
+            &self,
+            _location: &Path,
+            _opts: PutMultipartOptions,
+        ) -> OSResult<Box<dyn MultipartUpload>> {
+            unimplemented!()
+        }
+
+        async fn get_opts(&self, _location: &Path, _options: GetOptions) -> OSResult<GetResult> {
+            unimplemented!()
+        }
+
+        fn delete_stream(
+            &self,
+            locations: BoxStream<'static, OSResult<Path>>,
+        ) -> BoxStream<'static, OSResult<Path>> {
+            let state = self.0.clone();
+            async move {
+                let locations = locations.try_collect::<Vec<_>>().await?;
+                let throttled = {
+                    let mut requests = state.requests.lock().unwrap();
+                    requests.push(locations.clone());
+                    state
+                        .throttle_every
+                        .is_some_and(|every| requests.len().is_multiple_of(every))
+                };
+                if throttled {
+                    return Err(object_store::Error::Generic {
+                        store: "S3",
+                        source: "503 SlowDown: Please reduce your request rate".into(),
+                    });
+                }
+                Ok(stream::iter(locations).map(move |location| {
+                    if state.denied.contains(&location) {
+                        Err(object_store::Error::PermissionDenied {
+                            path: location.to_string(),
+                            source: "access denied".into(),
+                        })
+                    } else {
+                        Ok(location)
+                    }
+                }))
+            }
+            .try_flatten_stream()
+            .boxed()
+        }
+
+        fn list(&self, _prefix: Option<&Path>) -> BoxStream<'static, OSResult<ObjectMeta>> {
+            unimplemented!()
+        }
+
+        async fn list_with_delimiter(&self, _prefix: Option<&Path>) -> OSResult<ListResult> {
+            unimplemented!()
+        }
+
+        async fn copy_opts(&self, _from: &Path, _to: &Path, _options: CopyOptions) -> OSResult<()> {
+            unimplemented!()
+        }
+    }
+
+    fn store_with(url: &str, state: Arc<DeleteState>) -> ObjectStore {
+        ObjectStore::new(
+            Arc::new(DeleteRecordingStore(state)),
+            Url::parse(url).unwrap(),
+            None,
+            None,
+            false,
+            true,
+            8,
+            3,
+            None,
+        )
+    }
+
+    fn paths(n: usize) -> Vec<Path> {
+        (0..n)
+            .map(|i| Path::from(format!("data/{i}.lance")))
+            .collect()
+    }
+
+    async fn delete_all(store: &ObjectStore, paths: Vec<Path>) -> Vec<DeletedPath> {
+        store
+            .delete_paths(stream::iter(paths).map(Ok).boxed(), 4)
+            .try_collect()
+            .await
+            .unwrap()
+    }
+
+    #[rstest]
+    #[case::s3("s3://bucket/", 2500, 1000)]
+    #[case::azure("az://container@account/", 300, 256)]
+    #[case::memory("memory:///", 10, 1)]
+    #[tokio::test]
+    async fn test_delete_paths_batches_when_advertised(
+        #[case] url: &str,
+        #[case] num_paths: usize,
+        #[case] batch_size: usize,
+    ) {
+        let state = Arc::new(DeleteState::default());
+        let store = store_with(url, state.clone());
+        assert_eq!(store.delete_batch_size(), batch_size);
+        assert_eq!(store.capabilities().batch_delete, batch_size > 1);
+
+        let deleted = delete_all(&store, paths(num_paths)).await;
+        assert_eq!(deleted.len(), num_paths);
+        assert!(deleted.iter().all(DeletedPath::is_deleted));
+        let requests = state.requests.lock().unwrap();
+        assert_eq!(requests.len(), num_paths.div_ceil(batch_size));
+        assert!(requests.iter().all(|paths| paths.len() <= batch_size));
+    }
+
+    #[tokio::test]
+    async fn test_delete_paths_retries_throttling() {
+        let state = Arc::new(DeleteState {
+            throttle_every: Some(3),
+            ..Default::default()
+        });
+        let store = store_with("s3://bucket/", state.clone());
+
+        // Four batches of 1000. The third request is throttled and retried.
+        let deleted = delete_all(&store, paths(4000)).await;
+        assert_eq!(deleted.len(), 4000);
+        assert!(deleted.iter().all(DeletedPath::is_deleted));
+        let requests = state.requests.lock().unwrap();
+        assert_eq!(requests.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_delete_paths_reports_failures() {
+        let state = Arc::new(DeleteState {
+            denied: HashSet::from([Path::from("data/1.lance")]),
+            ..Default::default()
+        });
+        let store = store_with("s3://bucket/", state.clone());
+
+        let deleted = delete_all(&store, paths(4)).await;
+        assert_eq!(deleted.len(), 4);
+        let failed = deleted
+            .iter()
+            .filter(|deleted| !deleted.is_deleted())
+            .collect::<Vec<_>>();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].path, Path::from("data/1.lance"));
+        assert!(matches!(
+            failed[0].error.as_deref(),
+            Some(object_store::Error::PermissionDenied { .. })
+        ));
+        // Permission errors are not retried.
+        assert_eq!(state.requests.lock().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,445 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Reachability diagnostics for the endpoint of an object store.
+//!
+//! When opening a dataset is slow it is not obvious which part of reaching the
+//! storage is to blame. [`diagnose_endpoint`] connects to an endpoint one step
+//! at a time and times each of them: resolving the host, opening the TCP
+//! connection, the TLS handshake and a small `HEAD` request until its first
+//! byte comes back. [`ObjectStoreProvider::diagnose`] does this for the
+//! endpoint a store would use.
+//!
+//! The request is sent without credentials, so an error status like 403 still
+//! means the endpoint is reachable.
+//!
+//! Only built with the `diagnose` feature, which brings in the TLS client used
+//! to time the handshake.
+//!
+//! [`ObjectStoreProvider::diagnose`]: crate::object_store::providers::ObjectStoreProvider::diagnose
+
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use lance_core::{Error, Result};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+use url::Url;
+
+/// How long a single phase may take before it is reported as failed.
+const PHASE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The endpoint to diagnose and how to reach it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiagnoseTarget {
+    pub endpoint: Url,
+    /// Connect to this address instead of resolving the host of the endpoint,
+    /// as stores pinned with a `storage_resolve` option do.
+    pub address: Option<IpAddr>,
+}
+
+impl DiagnoseTarget {
+    pub fn new(endpoint: Url) -> Self {
+        Self {
+            endpoint,
+            address: None,
+        }
+    }
+
+    pub fn with_address(mut self, address: Option<IpAddr>) -> Self {
+        self.address = address;
+        self
+    }
+}
+
+/// A step of reaching an endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosePhase {
+    Dns,
+    TcpConnect,
+    TlsHandshake,
+    FirstByte,
+}
+
+impl DiagnosePhase {
+    const ALL: [Self; 4] = [
+        Self::Dns,
+        Self::TcpConnect,
+        Self::TlsHandshake,
+        Self::FirstByte,
+    ];
+}
+
+impl Display for DiagnosePhase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Dns => "DNS resolution",
+            Self::TcpConnect => "TCP connect",
+            Self::TlsHandshake => "TLS handshake",
+            Self::FirstByte => "First byte",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The outcome of [`diagnose_endpoint`].
+///
+/// Phases that completed have a duration. A phase that failed is recorded in
+/// `failure` and the phases after it were not attempted. Print it with
+/// [`Display`] for a human-readable summary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiagnoseReport {
+    pub endpoint: Url,
+    /// The addresses the host resolved to, or the pinned address.
+    pub addresses: Vec<SocketAddr>,
+    /// The address the connection was made to.
+    pub connected_to: Option<SocketAddr>,
+    /// `None` if the address was pinned, so no lookup was made.
+    pub dns: Option<Duration>,
+    pub tcp_connect: Option<Duration>,
+    /// `None` for plain HTTP endpoints.
+    pub tls_handshake: Option<Duration>,
+    /// From sending the request until the first byte of the response.
+    pub first_byte: Option<Duration>,
+    /// The HTTP status of the response.
+    pub status: Option<u16>,
+    pub failure: Option<(DiagnosePhase, String)>,
+}
+
+impl DiagnoseReport {
+    fn new(endpoint: Url) -> Self {
+        Self {
+            endpoint,
+            addresses: Vec::new(),
+            connected_to: None,
+            dns: None,
+            tcp_connect: None,
+            tls_handshake: None,
+            first_byte: None,
+            status: None,
+            failure: None,
+        }
+    }
+
+    /// Whether every phase succeeded.
+    pub fn is_reachable(&self) -> bool {
+        self.failure.is_none()
+    }
+
+    /// The duration of a phase, `None` if it was not run or failed.
+    pub fn duration(&self, phase: DiagnosePhase) -> Option<Duration> {
+        match phase {
+            DiagnosePhase::Dns => self.dns,
+            DiagnosePhase::TcpConnect => self.tcp_connect,
+            DiagnosePhase::TlsHandshake => self.tls_handshake,
+            DiagnosePhase::FirstByte => self.first_byte,
+        }
+    }
+
+    /// The sum of the phase durations.
+    pub fn total(&self) -> Duration {
+        DiagnosePhase::ALL
+            .iter()
+            .filter_map(|phase| self.duration(*phase))
+            .sum()
+    }
+
+    fn fail(mut self, phase: DiagnosePhase, message: impl Into<String>) -> Self {
+        self.failure = Some((phase, message.into()));
+        self
+    }
+}
+
+impl Display for DiagnoseReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Endpoint:        {}", self.endpoint)?;
+        if !self.addresses.is_empty() {
+            let addresses = self
+                .addresses
+                .iter()
+                .map(|addr| match self.connected_to {
+                    Some(connected) if connected == *addr => format!("{} (connected)", addr),
+                    _ => addr.to_string(),
+                })
+                .collect::<Vec<_>>();
+            writeln!(f, "Addresses:       {}", addresses.join(", "))?;
+        }
+        let failed = self.failure.as_ref().map(|(phase, _)| *phase);
+        let mut skipped = false;
+        for phase in DiagnosePhase::ALL {
+            let label = format!("{}:", phase);
+            let value = if skipped {
+                "not attempted".to_string()
+            } else if let Some(duration) = self.duration(phase) {
+                match (phase, self.status) {
+                    (DiagnosePhase::FirstByte, Some(status)) => {
+                        format!("{:>10.1?} (HTTP {})", duration, status)
+                    }
+                    _ => format!("{:>10.1?}", duration),
+                }
+            } else if failed == Some(phase) {
+                skipped = true;
+                let (_, message) = self.failure.as_ref().unwrap();
+                format!("FAILED: {}", message)
+            } else {
+                match phase {
+                    DiagnosePhase::Dns => "skipped (address pinned)".to_string(),
+                    DiagnosePhase::TlsHandshake => "skipped (plain HTTP)".to_string(),
+                    _ => "not attempted".to_string(),
+                }
+            };
+            writeln!(f, "{:<17}{}", label, value)?;
+        }
+        write!(f, "{:<17}{:>10.1?}", "Total:", self.total())
+    }
+}
+
+/// Time how long it takes to reach `target`, phase by phase.
+///
+/// Only an endpoint that is not a valid HTTP(S) URL is an error. Failures to
+/// reach it are recorded in the report.
+pub async fn diagnose_endpoint(target: &DiagnoseTarget) -> Result<DiagnoseReport> {
+    let endpoint = &target.endpoint;
+    let use_tls = match endpoint.scheme() {
+        "https" => true,
+        "http" => false,
+        scheme => {
+            return Err(Error::invalid_input(format!(
+                "Cannot diagnose endpoint {}: unsupported scheme '{}'",
+                endpoint, scheme
+            )));
+        }
+    };
+    let host = endpoint
+        .host_str()
+        .ok_or_else(|| Error::invalid_input(format!("Endpoint {} has no host", endpoint)))?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string();
+    let port = endpoint.port_or_known_default().unwrap_or(443);
+    let mut report = DiagnoseReport::new(endpoint.clone());
+
+    match target.address {
+        Some(ip) => report.addresses.push(SocketAddr::new(ip, port)),
+        None => {
+            let start = Instant::now();
+            match timed(tokio::net::lookup_host((host.as_str(), port))).await {
+                Ok(addresses) => {
+                    report.dns = Some(start.elapsed());
+                    report.addresses = addresses.collect();
+                }
+                Err(message) => return Ok(report.fail(DiagnosePhase::Dns, message)),
+            }
+            if report.addresses.is_empty() {
+                return Ok(report.fail(DiagnosePhase::Dns, "no addresses found"));
+            }
+        }
+    }
+
+    let start = Instant::now();
+    let mut errors = Vec::new();
+    let mut stream = None;
+    for address in &report.addresses {
+        match timed(TcpStream::connect(address)).await {
+            Ok(connected) => {
+                report.connected_to = Some(*address);
+                stream = Some(connected);
+                break;
+            }
+            Err(message) => errors.push(format!("{}: {}", address, message)),
+        }
+    }
+    let Some(stream) = stream else {
+        return Ok(report.fail(DiagnosePhase::TcpConnect, errors.join("; ")));
+    };
+    report.tcp_connect = Some(start.elapsed());
+
+    let request = format!(
+        "HEAD {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: lance\r\nConnection: close\r\n\r\n",
+        endpoint.path(),
+        endpoint.host_str().unwrap_or_default()
+    );
+    let result = if use_tls {
+        let server_name = match ServerName::try_from(host) {
+            Ok(name) => name,
+            Err(e) => return Ok(report.fail(DiagnosePhase::TlsHandshake, e.to_string())),
+        };
+        let start = Instant::now();
+        let stream =
+            match timed(TlsConnector::from(tls_config()).connect(server_name, stream)).await {
+                Ok(stream) => stream,
+                Err(message) => return Ok(report.fail(DiagnosePhase::TlsHandshake, message)),
+            };
+        report.tls_handshake = Some(start.elapsed());
+        first_byte(stream, &request).await
+    } else {
+        first_byte(stream, &request).await
+    };
+    match result {
+        Ok((duration, status)) => {
+            report.first_byte = Some(duration);
+            report.status = status;
+            Ok(report)
+        }
+        Err(message) => Ok(report.fail(DiagnosePhase::FirstByte, message)),
+    }
+}
+
+/// Send `request` and time it until the first byte of the response, which is
+/// returned with the status parsed from the status line.
+async fn first_byte<S: AsyncRead + AsyncWrite + Unpin>(
+    mut stream: S,
+    request: &str,
+) -> std::result::Result<(Duration, Option<u16>), String> {
+    let start = Instant::now();
+    timed(stream.write_all(request.as_bytes())).await?;
+    let mut buf = vec![0; 256];
+    let read = timed(stream.read(&mut buf)).await?;
+    let duration = start.elapsed();
+    if read == 0 {
+        return Err("connection closed without a response".to_string());
+    }
+    // Best effort: the rest of the status line is not needed for the timing.
+    let mut len = read;
+    while !buf[..len].windows(2).any(|w| w == b"\r\n") && len < buf.len() {
+        match timed(stream.read(&mut buf[len..])).await {
+            Ok(0) | Err(_) => break,
+            Ok(read) => len += read,
+        }
+    }
+    let status = std::str::from_utf8(&buf[..len])
+        .ok()
+        .and_then(|response| response.split_whitespace().nth(1))
+        .and_then(|status| status.parse().ok());
+    Ok((duration, status))
+}
+
+/// Run a phase under [`PHASE_TIMEOUT`], turning failures into messages.
+async fn timed<T, E: Display>(
+    fut: impl Future<Output = std::result::Result<T, E>>,
+) -> std::result::Result<T, String> {
+    match tokio::time::timeout(PHASE_TIMEOUT, fut).await {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err(format!("timed out after {:?}", PHASE_TIMEOUT)),
+    }
+}
+
+/// A TLS client trusting the root certificates of the platform.
+fn tls_config() -> Arc<ClientConfig> {
+    static CONFIG: OnceLock<Arc<ClientConfig>> = OnceLock::new();
+    CONFIG
+        .get_or_init(|| {
+            let mut roots = RootCertStore::empty();
+            let native = rustls_native_certs::load_native_certs();
+            for error in &native.errors {
+                log::debug!("Failed to load a native root certificate: {}", error);
+            }
+            roots.add_parsable_certificates(native.certs);
+            let provider = Arc::new(tokio_rustls::rustls::crypto::ring::default_provider());
+            let config = ClientConfig::builder_with_provider(provider)
+                .with_safe_default_protocol_versions()
+                .expect("the ring provider supports the default protocol versions")
+                .with_root_certificates(roots)
+                .with_no_client_auth();
+            Arc::new(config)
+        })
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    /// Serve a single connection, answering with `response` once the request
+    /// headers are in.
+    async fn serve_once(response: &'static str) -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let read = socket.read(&mut buf).await.unwrap();
+                if read == 0 {
+                    return;
+                }
+                request.extend_from_slice(&buf[..read]);
+            }
+            assert!(request.starts_with(b"HEAD /bucket HTTP/1.1\r\n"));
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+        Url::parse(&format!("http://{}/bucket", addr)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_diagnose_reachable_endpoint() {
+        let endpoint = serve_once("HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\n\r\n").await;
+        let report = diagnose_endpoint(&DiagnoseTarget::new(endpoint.clone()))
+            .await
+            .unwrap();
+
+        assert!(report.is_reachable(), "{report}");
+        assert_eq!(report.endpoint, endpoint);
+        assert!(report.dns.is_some());
+        assert!(report.tcp_connect.is_some());
+        assert_eq!(report.tls_handshake, None);
+        assert!(report.first_byte.is_some());
+        assert_eq!(report.status, Some(403));
+        assert_eq!(report.connected_to, report.addresses.first().copied());
+
+        let printed = report.to_string();
+        assert!(printed.contains("skipped (plain HTTP)"), "{printed}");
+        assert!(printed.contains("(HTTP 403)"), "{printed}");
+    }
+
+    #[tokio::test]
+    async fn test_diagnose_pinned_address() {
+        let endpoint = serve_once("HTTP/1.1 200 OK\r\n\r\n").await;
+        let port = endpoint.port().unwrap();
+        let pinned = Url::parse(&format!("http://storage.invalid:{}/bucket", port)).unwrap();
+        let target = DiagnoseTarget::new(pinned).with_address(Some(IpAddr::from([127, 0, 0, 1])));
+        let report = diagnose_endpoint(&target).await.unwrap();
+
+        assert!(report.is_reachable(), "{report}");
+        assert_eq!(report.dns, None);
+        assert_eq!(report.status, Some(200));
+        assert!(report.to_string().contains("skipped (address pinned)"));
+    }
+
+    #[tokio::test]
+    async fn test_diagnose_refused_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let endpoint = Url::parse(&format!("https://{}/", addr)).unwrap();
+        let report = diagnose_endpoint(&DiagnoseTarget::new(endpoint))
+            .await
+            .unwrap();
+
+        let (phase, _) = report.failure.as_ref().unwrap();
+        assert_eq!(*phase, DiagnosePhase::TcpConnect);
+        assert_eq!(report.tcp_connect, None);
+        assert_eq!(report.tls_handshake, None);
+        let printed = report.to_string();
+        assert!(printed.contains("TCP connect:     FAILED"), "{printed}");
+        assert!(printed.contains("not attempted"), "{printed}");
+    }
+
+    #[tokio::test]
+    async fn test_diagnose_unsupported_scheme() {
+        let target = DiagnoseTarget::new(Url::parse("file:///tmp").unwrap());
+        let err = diagnose_endpoint(&target).await.unwrap_err();
+        assert!(err.to_string().contains("unsupported scheme"), "{err}");
+    }
+}
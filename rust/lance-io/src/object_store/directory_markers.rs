@@ -0,0 +1,292 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Directory marker objects left out of listings.
+//!
+//! Some tools, like the S3 console or Hadoop connectors, create zero-byte
+//! objects with keys ending in `/` so that a prefix shows up as a folder.
+//! `object_store` strips the trailing slash, so the marker of `data/` is listed
+//! as an empty object at `data`, which Lance would take for a file.
+//!
+//! [`DirectoryMarkerFilterStore`] drops these from listings. A zero-byte object
+//! is taken for a marker when its path is the listed prefix, when it is a
+//! common prefix of the same delimited listing, or when the next object of a
+//! recursive listing is inside it. Markers sort right before what they
+//! contain, so only one object is held back at a time. A marker of an empty
+//! directory can't be told apart from an empty file and is kept.
+//!
+//! Markers are left out unless the `storage_ignore_directory_markers` storage
+//! option is `false`.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures::stream::{self, BoxStream};
+use futures::{StreamExt, future};
+use lance_core::utils::parse::str_is_truthy;
+use object_store::path::Path;
+use object_store::{
+    CopyOptions, GetOptions, GetResult, ListResult, MultipartUpload, ObjectMeta, ObjectStore,
+    PutMultipartOptions, PutOptions, PutPayload, PutResult, RenameOptions, Result as OSResult,
+};
+
+/// Storage option that keeps directory markers in listings when `false`.
+/// Default, `true`.
+pub const IGNORE_DIRECTORY_MARKERS_KEY: &str = "storage_ignore_directory_markers";
+
+/// Whether the storage options ask for directory markers to be left out of
+/// listings.
+pub fn ignore_directory_markers(storage_options: Option<&HashMap<String, String>>) -> bool {
+    storage_options
+        .and_then(|opts| opts.get(IGNORE_DIRECTORY_MARKERS_KEY))
+        .is_none_or(|value| str_is_truthy(value))
+}
+
+/// Whether `child` is strictly inside the directory `parent`.
+fn is_inside(child: &Path, parent: &Path) -> bool {
+    child
+        .prefix_match(parent)
+        .is_some_and(|mut rest| rest.next().is_some())
+}
+
+/// Leave the markers out of the recursive `listing` of `prefix`.
+fn filter_listing(
+    prefix: Option<&Path>,
+    listing: BoxStream<'static, OSResult<ObjectMeta>>,
+) -> BoxStream<'static, OSResult<ObjectMeta>> {
+    let prefix = prefix.cloned();
+    // A zero-byte object, held until the next object shows whether it is a
+    // marker.
+    let mut held: Option<ObjectMeta> = None;
+    listing
+        .map(Some)
+        .chain(stream::once(future::ready(None)))
+        .flat_map(move |item| {
+            let mut out = Vec::with_capacity(2);
+            match item {
+                Some(Ok(meta)) if meta.size == 0 && prefix.as_ref() == Some(&meta.location) => {}
+                Some(Ok(meta)) => {
+                    if let Some(empty) = held.take()
+                        && !is_inside(&meta.location, &empty.location)
+                    {
+                        out.push(Ok(empty));
+                    }
+                    if meta.size == 0 {
+                        held = Some(meta);
+                    } else {
+                        out.push(Ok(meta));
+                    }
+                }
+                Some(Err(err)) => out.push(Err(err)),
+                None => out.extend(held.take().map(Ok)),
+            }
+            stream::iter(out)
+        })
+        .boxed()
+}
+
+/// An [`ObjectStore`] wrapper that leaves directory markers out of listings,
+/// see the [module documentation](self).
+#[derive(Debug)]
+pub struct DirectoryMarkerFilterStore {
+    target: Arc<dyn ObjectStore>,
+}
+
+impl DirectoryMarkerFilterStore {
+    pub fn new(target: Arc<dyn ObjectStore>) -> Self {
+        Self { target }
+    }
+}
+
+impl Display for DirectoryMarkerFilterStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DirectoryMarkerFilterStore({})", self.target)
+    }
+}
+
+#[async_trait::async_trait]
+#[deny(clippy::missing_trait_methods)]
+impl ObjectStore for DirectoryMarkerFilterStore {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        payload: PutPayload,
+        opts: PutOptions,
+    ) -> OSResult<PutResult> {
+        self.target.put_opts(location, payload, opts).await
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        opts: PutMultipartOptions,
+    ) -> OSResult<Box<dyn MultipartUpload>> {
+        self.target.put_multipart_opts(location, opts).await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> OSResult<GetResult> {
+        self.target.get_opts(location, options).await
+    }
+
+    async fn get_ranges(&self, location: &Path, ranges: &[Range<u64>]) -> OSResult<Vec<Bytes>> {
+        self.target.get_ranges(location, ranges).await
+    }
+
+    fn delete_stream(
+        &self,
+        locations: BoxStream<'static, OSResult<Path>>,
+    ) -> BoxStream<'static, OSResult<Path>> {
+        self.target.delete_stream(locations)
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'static, OSResult<ObjectMeta>> {
+        filter_listing(prefix, self.target.list(prefix))
+    }
+
+    fn list_with_offset(
+        &self,
+        prefix: Option<&Path>,
+        offset: &Path,
+    ) -> BoxStream<'static, OSResult<ObjectMeta>> {
+        filter_listing(prefix, self.target.list_with_offset(prefix, offset))
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> OSResult<ListResult> {
+        let mut result = self.target.list_with_delimiter(prefix).await?;
+        let common_prefixes = &result.common_prefixes;
+        result.objects.retain(|meta| {
+            meta.size > 0
+                || (prefix != Some(&meta.location) && !common_prefixes.contains(&meta.location))
+        });
+        Ok(result)
+    }
+
+    async fn copy_opts(&self, from: &Path, to: &Path, opts: CopyOptions) -> OSResult<()> {
+        self.target.copy_opts(from, to, opts).await
+    }
+
+    async fn rename_opts(&self, from: &Path, to: &Path, opts: RenameOptions) -> OSResult<()> {
+        self.target.rename_opts(from, to, opts).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::TryStreamExt;
+    use object_store::ObjectStoreExt as _;
+    use object_store::memory::InMemory;
+    use rstest::rstest;
+    use url::Url;
+
+    use super::*;
+
+    /// A bucket with the markers the S3 console would create for `data/` and
+    /// `data/sub/`, as `object_store` lists them, and a real empty file.
+    async fn bucket_with_markers() -> Arc<InMemory> {
+        let store = Arc::new(InMemory::new());
+        for (path, data) in [
+            ("data", &b""[..]),
+            ("data/0.lance", b"lance"),
+            ("data/empty.txt", b""),
+            ("data/sub", b""),
+            ("data/sub/1.lance", b"lance"),
+        ] {
+            store
+                .put(&Path::from(path), PutPayload::from_static(data))
+                .await
+                .unwrap();
+        }
+        store
+    }
+
+    fn lance_store(inner: Arc<InMemory>, ignore_markers: bool) -> crate::object_store::ObjectStore {
+        crate::object_store::ObjectStore::new(
+            inner,
+            Url::parse("memory:///").unwrap(),
+            None,
+            None,
+            false,
+            true,
+            8,
+            3,
+            Some(&HashMap::from([(
+                IGNORE_DIRECTORY_MARKERS_KEY.to_string(),
+                ignore_markers.to_string(),
+            )])),
+        )
+    }
+
+    #[test]
+    fn test_ignore_directory_markers() {
+        let options = |value: &str| {
+            HashMap::from([(IGNORE_DIRECTORY_MARKERS_KEY.to_string(), value.to_string())])
+        };
+        assert!(ignore_directory_markers(None));
+        assert!(ignore_directory_markers(Some(&options("true"))));
+        assert!(!ignore_directory_markers(Some(&options("false"))));
+    }
+
+    #[rstest]
+    #[case::ignored(true, &["data/0.lance", "data/empty.txt", "data/sub/1.lance"])]
+    #[case::kept(false, &["data/0.lance", "data/empty.txt", "data/sub", "data/sub/1.lance"])]
+    #[tokio::test]
+    async fn test_recursive_listing(#[case] ignore_markers: bool, #[case] expected: &[&str]) {
+        let store = lance_store(bucket_with_markers().await, ignore_markers);
+        let mut listed = store
+            .read_dir_all(&Path::from("data"), None)
+            .map_ok(|meta| meta.location.to_string())
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        listed.sort();
+        assert_eq!(listed, expected);
+
+        let size = store.prefix_size("data").await.unwrap();
+        assert_eq!(size.num_objects, expected.len() as u64);
+    }
+
+    #[tokio::test]
+    async fn test_marker_of_listed_prefix() {
+        // S3 lists the marker `data/` itself when listing `data/`, unlike
+        // the in-memory store.
+        let meta = |path: &str, size: u64| ObjectMeta {
+            location: Path::from(path),
+            last_modified: Default::default(),
+            size,
+            e_tag: None,
+            version: None,
+        };
+        let listing = stream::iter([meta("data", 0), meta("data/0.lance", 5), meta("data/x", 0)])
+            .map(Ok)
+            .boxed();
+        let listed = filter_listing(Some(&Path::from("data")), listing)
+            .map_ok(|meta| meta.location.to_string())
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(listed, vec!["data/0.lance", "data/x"]);
+    }
+
+    #[tokio::test]
+    async fn test_delimited_listing() {
+        let store = DirectoryMarkerFilterStore::new(bucket_with_markers().await);
+        let root = store.list_with_delimiter(None).await.unwrap();
+        assert!(root.objects.is_empty());
+        assert_eq!(root.common_prefixes, vec![Path::from("data")]);
+
+        let data = store
+            .list_with_delimiter(Some(&Path::from("data")))
+            .await
+            .unwrap();
+        let objects = data
+            .objects
+            .iter()
+            .map(|meta| meta.location.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(objects, vec!["data/0.lance", "data/empty.txt"]);
+        assert_eq!(data.common_prefixes, vec![Path::from("data/sub")]);
+    }
+}
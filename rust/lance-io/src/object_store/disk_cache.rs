@@ -0,0 +1,507 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Read-through cache of object contents on local disk.
+//!
+//! Queries against high-latency stores such as COS tend to read the same
+//! fragments again and again. When enabled with the `storage_cache_dir`
+//! storage option, [`CachingObjectStore`] keeps the blocks it has read in files
+//! below that directory and serves repeated reads from there, evicting the
+//! least recently used blocks once `storage_cache_size_bytes` is exceeded.
+//!
+//! Blocks are keyed by the object's etag. Every read first HEADs the object,
+//! so a block is only served while the object still has the etag it was read
+//! with. Pair the cache with `storage_metadata_cache_size` to answer those HEADs
+//! from memory.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use bytes::{Bytes, BytesMut};
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
+use moka::sync::Cache;
+use object_store::path::Path;
+use object_store::{
+    CopyOptions, GetOptions, GetRange, GetResult, GetResultPayload, ListResult, MultipartUpload,
+    ObjectMeta, ObjectStore, ObjectStoreExt, PutMultipartOptions, PutOptions, PutPayload,
+    PutResult, RenameOptions, Result as OSResult,
+};
+use tempfile::TempDir;
+
+use crate::utils::tracking_store::IOTracker;
+use lance_core::{Error, Result};
+
+/// Storage option that sets the directory the disk cache keeps its blocks in.
+pub const CACHE_DIR_KEY: &str = "storage_cache_dir";
+/// Storage option that sets how many bytes the disk cache may hold.
+pub const CACHE_SIZE_BYTES_KEY: &str = "storage_cache_size_bytes";
+
+const DEFAULT_CACHE_SIZE_BYTES: u64 = 1024 * 1024 * 1024;
+/// Objects are cached in aligned blocks of this size, the last block of an
+/// object may be shorter.
+const CACHE_BLOCK_SIZE: u64 = 1024 * 1024;
+
+/// Configuration for [`CachingObjectStore`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiskCacheConfig {
+    /// Directory to keep cached blocks in. `None` disables the cache.
+    pub dir: Option<PathBuf>,
+    /// Maximum number of bytes to keep on disk. Zero disables the cache.
+    pub capacity_bytes: u64,
+}
+
+impl Default for DiskCacheConfig {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            capacity_bytes: DEFAULT_CACHE_SIZE_BYTES,
+        }
+    }
+}
+
+impl DiskCacheConfig {
+    /// Build the config from storage options.
+    ///
+    /// | Storage Option Key         | Default       |
+    /// |----------------------------|---------------|
+    /// | `storage_cache_dir`        | unset (off)   |
+    /// | `storage_cache_size_bytes` | 1073741824    |
+    pub fn from_storage_options(storage_options: Option<&HashMap<String, String>>) -> Result<Self> {
+        let mut config = Self::default();
+        let Some(options) = storage_options else {
+            return Ok(config);
+        };
+        config.dir = options
+            .get(CACHE_DIR_KEY)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from);
+        if let Some(val) = options.get(CACHE_SIZE_BYTES_KEY) {
+            config.capacity_bytes = val.parse::<u64>().map_err(|_| {
+                Error::invalid_input(format!(
+                    "Invalid value for storage option '{CACHE_SIZE_BYTES_KEY}': '{val}'"
+                ))
+            })?;
+        }
+        Ok(config)
+    }
+
+    /// Returns `true` when the cache layer should be skipped entirely.
+    pub fn is_disabled(&self) -> bool {
+        self.dir.is_none() || self.capacity_bytes == 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct BlockKey {
+    path: Path,
+    e_tag: String,
+    index: u64,
+}
+
+#[derive(Debug, Clone)]
+struct CachedBlock {
+    file: PathBuf,
+    len: u32,
+}
+
+/// An [`ObjectStore`] wrapper that caches object contents on local disk.
+///
+/// Only plain GETs (no preconditions or version) of objects that report an
+/// etag are served from the cache, everything else falls through to the
+/// target. Each block served from disk counts as a hit on the provided
+/// [`IOTracker`], each block fetched from the target as a miss.
+#[derive(Debug)]
+pub struct CachingObjectStore {
+    target: Arc<dyn ObjectStore>,
+    cache: Cache<BlockKey, CachedBlock>,
+    /// Removed, with any remaining blocks, when the store is dropped
+    dir: Arc<TempDir>,
+    next_file: AtomicU64,
+    io_tracker: IOTracker,
+}
+
+impl CachingObjectStore {
+    /// Wrap `target`, keeping blocks in a fresh directory below `config.dir`.
+    pub fn try_new(
+        target: Arc<dyn ObjectStore>,
+        config: &DiskCacheConfig,
+        io_tracker: IOTracker,
+    ) -> Result<Self> {
+        let parent = config
+            .dir
+            .as_ref()
+            .ok_or_else(|| Error::invalid_input(format!("'{CACHE_DIR_KEY}' is not set")))?;
+        std::fs::create_dir_all(parent)?;
+        let dir = tempfile::Builder::new()
+            .prefix("lance-cache-")
+            .tempdir_in(parent)?;
+        let cache = Cache::builder()
+            .max_capacity(config.capacity_bytes)
+            .weigher(|_, block: &CachedBlock| block.len)
+            .support_invalidation_closures()
+            .eviction_listener(|_, block: CachedBlock, _| {
+                // The directory may already be gone if the store was dropped.
+                let _ = std::fs::remove_file(block.file);
+            })
+            .build();
+        Ok(Self {
+            target,
+            cache,
+            dir: Arc::new(dir),
+            next_file: AtomicU64::new(0),
+            io_tracker,
+        })
+    }
+
+    fn is_plain_get(options: &GetOptions) -> bool {
+        !options.head
+            && options.if_match.is_none()
+            && options.if_none_match.is_none()
+            && options.if_modified_since.is_none()
+            && options.if_unmodified_since.is_none()
+            && options.version.is_none()
+    }
+
+    /// Drop every block of `location`, whatever its etag.
+    fn invalidate(&self, location: &Path) {
+        let location = location.clone();
+        // Only fails if the cache was built without invalidation closures.
+        let _ = self
+            .cache
+            .invalidate_entries_if(move |key, _| key.path == location);
+    }
+
+    /// Read `range` of the object described by `meta`, which must have an etag.
+    async fn read_range(&self, meta: &ObjectMeta, range: Range<u64>) -> OSResult<Bytes> {
+        let e_tag = meta.e_tag.as_ref().expect("checked by caller");
+        let first = range.start / CACHE_BLOCK_SIZE;
+        let last = range.end.div_ceil(CACHE_BLOCK_SIZE);
+        let blocks = futures::future::try_join_all((first..last).map(|index| {
+            self.read_block(
+                BlockKey {
+                    path: meta.location.clone(),
+                    e_tag: e_tag.clone(),
+                    index,
+                },
+                meta.size,
+            )
+        }))
+        .await?;
+
+        let mut bytes = BytesMut::with_capacity((range.end - range.start) as usize);
+        for (index, block) in (first..last).zip(blocks) {
+            let block_start = index * CACHE_BLOCK_SIZE;
+            let start = range.start.max(block_start) - block_start;
+            let end = range.end.min(block_start + block.len() as u64) - block_start;
+            bytes.extend_from_slice(&block[start as usize..end as usize]);
+        }
+        Ok(bytes.freeze())
+    }
+
+    async fn read_block(&self, key: BlockKey, object_size: u64) -> OSResult<Bytes> {
+        if let Some(block) = self.cache.get(&key) {
+            match tokio::fs::read(&block.file).await {
+                Ok(data) if data.len() == block.len as usize => {
+                    self.io_tracker.record_disk_cache_hit();
+                    return Ok(data.into());
+                }
+                // Removed or truncated behind our back, read it again.
+                _ => self.cache.invalidate(&key),
+            }
+        }
+
+        self.io_tracker.record_disk_cache_miss();
+        let start = key.index * CACHE_BLOCK_SIZE;
+        let end = (start + CACHE_BLOCK_SIZE).min(object_size);
+        // Pin the etag so a block of a newer version is never cached under
+        // the old one.
+        let options = GetOptions {
+            if_match: Some(key.e_tag.clone()),
+            range: Some(GetRange::Bounded(start..end)),
+            ..Default::default()
+        };
+        let data = self
+            .target
+            .get_opts(&key.path, options)
+            .await?
+            .bytes()
+            .await?;
+
+        let file = self.dir.path().join(format!(
+            "{:016x}.block",
+            self.next_file.fetch_add(1, Ordering::Relaxed)
+        ));
+        // Failing to populate the cache must not fail the read.
+        match tokio::fs::write(&file, &data).await {
+            Ok(()) => self.cache.insert(
+                key,
+                CachedBlock {
+                    file,
+                    len: data.len() as u32,
+                },
+            ),
+            Err(e) => log::warn!("Failed to write disk cache block {}: {}", file.display(), e),
+        }
+        Ok(data)
+    }
+}
+
+impl Display for CachingObjectStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CachingObjectStore({})", self.target)
+    }
+}
+
+#[async_trait::async_trait]
+#[deny(clippy::missing_trait_methods)]
+impl ObjectStore for CachingObjectStore {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        payload: PutPayload,
+        opts: PutOptions,
+    ) -> OSResult<PutResult> {
+        let result = self.target.put_opts(location, payload, opts).await;
+        self.invalidate(location);
+        result
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        opts: PutMultipartOptions,
+    ) -> OSResult<Box<dyn MultipartUpload>> {
+        // Blocks are pinned to the etag, the old ones are never served after
+        // the upload completes, only kept until they are evicted.
+        self.target.put_multipart_opts(location, opts).await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> OSResult<GetResult> {
+        if !Self::is_plain_get(&options) {
+            return self.target.get_opts(location, options).await;
+        }
+
+        let head = self
+            .target
+            .get_opts(
+                location,
+                GetOptions {
+                    head: true,
+                    ..Default::default()
+                },
+            )
+            .await?;
+        let range = match &options.range {
+            Some(range) => range.as_range(head.meta.size),
+            None => Ok(0..head.meta.size),
+        };
+        let (Some(_), Ok(range)) = (&head.meta.e_tag, range) else {
+            // Without an etag we cannot tell stale blocks apart, and invalid
+            // ranges should fail the way the target reports them.
+            return self.target.get_opts(location, options).await;
+        };
+
+        let bytes = self.read_range(&head.meta, range.clone()).await?;
+        Ok(GetResult {
+            payload: GetResultPayload::Stream(futures::stream::once(async { Ok(bytes) }).boxed()),
+            meta: head.meta,
+            range,
+            attributes: head.attributes,
+        })
+    }
+
+    async fn get_ranges(&self, location: &Path, ranges: &[Range<u64>]) -> OSResult<Vec<Bytes>> {
+        let meta = self.target.head(location).await?;
+        if meta.e_tag.is_none() || ranges.iter().any(|range| range.end > meta.size) {
+            return self.target.get_ranges(location, ranges).await;
+        }
+        futures::future::try_join_all(
+            ranges
+                .iter()
+                .map(|range| self.read_range(&meta, range.clone())),
+        )
+        .await
+    }
+
+    fn delete_stream(
+        &self,
+        locations: BoxStream<'static, OSResult<Path>>,
+    ) -> BoxStream<'static, OSResult<Path>> {
+        let cache = self.cache.clone();
+        self.target
+            .delete_stream(locations)
+            .inspect_ok(move |path| {
+                let path = path.clone();
+                let _ = cache.invalidate_entries_if(move |key, _| key.path == path);
+            })
+            .boxed()
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'static, OSResult<ObjectMeta>> {
+        self.target.list(prefix)
+    }
+
+    fn list_with_offset(
+        &self,
+        prefix: Option<&Path>,
+        offset: &Path,
+    ) -> BoxStream<'static, OSResult<ObjectMeta>> {
+        self.target.list_with_offset(prefix, offset)
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> OSResult<ListResult> {
+        self.target.list_with_delimiter(prefix).await
+    }
+
+    async fn copy_opts(&self, from: &Path, to: &Path, opts: CopyOptions) -> OSResult<()> {
+        let result = self.target.copy_opts(from, to, opts).await;
+        self.invalidate(to);
+        result
+    }
+
+    async fn rename_opts(&self, from: &Path, to: &Path, opts: RenameOptions) -> OSResult<()> {
+        let result = self.target.rename_opts(from, to, opts).await;
+        self.invalidate(from);
+        self.invalidate(to);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use object_store::memory::InMemory;
+
+    use super::*;
+
+    fn caching_store(
+        dir: &std::path::Path,
+        capacity_bytes: u64,
+    ) -> (CachingObjectStore, Arc<InMemory>, IOTracker) {
+        let io_tracker = IOTracker::default();
+        let target = Arc::new(InMemory::new());
+        let config = DiskCacheConfig {
+            dir: Some(dir.to_path_buf()),
+            capacity_bytes,
+        };
+        let store =
+            CachingObjectStore::try_new(target.clone(), &config, io_tracker.clone()).unwrap();
+        (store, target, io_tracker)
+    }
+
+    fn content(len: usize) -> Bytes {
+        (0..len).map(|i| (i % 251) as u8).collect::<Vec<_>>().into()
+    }
+
+    #[test]
+    fn test_config_from_storage_options() {
+        assert!(
+            DiskCacheConfig::from_storage_options(None)
+                .unwrap()
+                .is_disabled()
+        );
+
+        let options = HashMap::from([
+            (CACHE_DIR_KEY.to_string(), "/tmp/lance".to_string()),
+            (CACHE_SIZE_BYTES_KEY.to_string(), "4096".to_string()),
+        ]);
+        let config = DiskCacheConfig::from_storage_options(Some(&options)).unwrap();
+        assert_eq!(
+            config,
+            DiskCacheConfig {
+                dir: Some(PathBuf::from("/tmp/lance")),
+                capacity_bytes: 4096,
+            }
+        );
+
+        let options = HashMap::from([(CACHE_SIZE_BYTES_KEY.to_string(), "lots".to_string())]);
+        let err = DiskCacheConfig::from_storage_options(Some(&options)).unwrap_err();
+        assert!(matches!(err, Error::InvalidInput { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_repeated_reads_hit_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let (store, _, io_tracker) = caching_store(dir.path(), 64 * 1024 * 1024);
+        let path = Path::from("data/a.lance");
+        let data = content(3 * CACHE_BLOCK_SIZE as usize / 2);
+        store.put(&path, data.clone().into()).await.unwrap();
+
+        let range = 1000..CACHE_BLOCK_SIZE + 10;
+        let first = store.get_range(&path, range.clone()).await.unwrap();
+        assert_eq!(first, data.slice(1000..CACHE_BLOCK_SIZE as usize + 10));
+        let stats = io_tracker.incremental_stats();
+        assert_eq!((stats.disk_cache_hits, stats.disk_cache_misses), (0, 2));
+
+        assert_eq!(store.get_range(&path, range).await.unwrap(), first);
+        assert_eq!(store.get(&path).await.unwrap().bytes().await.unwrap(), data);
+        let ranges = store.get_ranges(&path, &[0..10, 20..30]).await.unwrap();
+        assert_eq!(ranges, vec![data.slice(0..10), data.slice(20..30)]);
+        let stats = io_tracker.incremental_stats();
+        assert_eq!((stats.disk_cache_hits, stats.disk_cache_misses), (6, 0));
+    }
+
+    #[tokio::test]
+    async fn test_changed_etag_is_not_served() {
+        let dir = tempfile::tempdir().unwrap();
+        let (store, target, io_tracker) = caching_store(dir.path(), 64 * 1024 * 1024);
+        let path = Path::from("a");
+        store.put(&path, content(100).into()).await.unwrap();
+        store.get_range(&path, 0..10).await.unwrap();
+
+        // Overwritten behind the cache's back, e.g. by another process.
+        target
+            .put(&path, PutPayload::from_static(b"0123456789"))
+            .await
+            .unwrap();
+        let bytes = store.get_range(&path, 0..10).await.unwrap();
+        assert_eq!(bytes.as_ref(), b"0123456789");
+        let stats = io_tracker.incremental_stats();
+        assert_eq!((stats.disk_cache_hits, stats.disk_cache_misses), (0, 2));
+
+        // Writes through the store drop the blocks of the old version.
+        store.put(&path, content(50).into()).await.unwrap();
+        store.cache.run_pending_tasks();
+        assert_eq!(store.cache.entry_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_evicts_to_capacity() {
+        let dir = tempfile::tempdir().unwrap();
+        let (store, _, _) = caching_store(dir.path(), 2 * CACHE_BLOCK_SIZE);
+        for name in ["a", "b", "c", "d"] {
+            let path = Path::from(name);
+            store
+                .put(&path, content(CACHE_BLOCK_SIZE as usize).into())
+                .await
+                .unwrap();
+            store.get(&path).await.unwrap().bytes().await.unwrap();
+        }
+        store.cache.run_pending_tasks();
+        assert!(store.cache.weighted_size() <= 2 * CACHE_BLOCK_SIZE);
+
+        let cache_dir = std::fs::read_dir(dir.path())
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        let files = std::fs::read_dir(&cache_dir).unwrap().count() as u64;
+        assert_eq!(files, store.cache.entry_count());
+
+        drop(store);
+        assert!(!cache_dir.exists());
+    }
+
+    #[tokio::test]
+    async fn test_missing_objects_fall_through() {
+        let dir = tempfile::tempdir().unwrap();
+        let (store, _, _) = caching_store(dir.path(), 1024);
+        let err = store.get(&Path::from("missing")).await.unwrap_err();
+        assert!(matches!(err, object_store::Error::NotFound { .. }), "{err}");
+    }
+}
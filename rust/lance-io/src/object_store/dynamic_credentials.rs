@@ -96,6 +96,7 @@ where
     if !compatible {
         return Ok(None);
     }
+    accessor.maybe_spawn_background_refresh();
 
     Ok(Some(
         Arc::new(NamespaceCredentialsProvider::<T>::new(accessor))
@@ -3,6 +3,7 @@
 
 use std::collections::HashMap;
 use std::fmt;
+use std::future::Future;
 use std::ops::Range;
 use std::sync::Arc;
 
@@ -15,13 +16,16 @@ use object_store::{
     RenameOptions,
 };
 use object_store_opendal::OpendalStore;
+use opendal::Operator;
 use tokio::sync::RwLock;
 
 use crate::object_store::StorageOptionsAccessor;
+use crate::object_store::interceptor::{RequestInterceptor, intercept_operator};
+use crate::object_store::part_retry::PartRetryLayer;
 use lance_core::Result;
 
 type NormalizeConfigFn = fn(&HashMap<String, String>) -> Result<HashMap<String, String>>;
-type BuildStoreFn = fn(HashMap<String, String>) -> Result<OpendalStore>;
+type BuildOperatorFn = fn(HashMap<String, String>) -> Result<Operator>;
 
 #[derive(Debug, Clone)]
 struct CachedOpenDalStore {
@@ -35,8 +39,11 @@ pub(in crate::object_store) struct DynamicOpenDalStore {
     base_options: Arc<HashMap<String, String>>,
     accessor: Arc<StorageOptionsAccessor>,
     normalize_config: NormalizeConfigFn,
-    build_store: BuildStoreFn,
+    build_operator: BuildOperatorFn,
     protected_keys: Vec<&'static str>,
+    reload_on_auth_error: bool,
+    request_interceptor: Option<Arc<dyn RequestInterceptor>>,
+    part_retry_layer: Option<PartRetryLayer>,
     cache: Arc<RwLock<Option<CachedOpenDalStore>>>,
 }
 
@@ -55,15 +62,19 @@ impl DynamicOpenDalStore {
         base_options: HashMap<String, String>,
         accessor: Arc<StorageOptionsAccessor>,
         normalize_config: NormalizeConfigFn,
-        build_store: BuildStoreFn,
+        build_operator: BuildOperatorFn,
     ) -> Self {
+        accessor.maybe_spawn_background_refresh();
         Self {
             name: name.into(),
             base_options: Arc::new(base_options),
             accessor,
             normalize_config,
-            build_store,
+            build_operator,
             protected_keys: Vec::new(),
+            reload_on_auth_error: false,
+            request_interceptor: None,
+            part_retry_layer: None,
             cache: Arc::new(RwLock::new(None)),
         }
     }
@@ -76,6 +87,31 @@ impl DynamicOpenDalStore {
         self
     }
 
+    /// Force a provider refresh and retry once when a request is rejected as
+    /// unauthenticated. This lets providers whose credentials rotate without
+    /// an advertised expiration (e.g. a credentials file rewritten by a
+    /// sidecar) pick up the new credentials.
+    #[cfg(feature = "tencent")]
+    pub(in crate::object_store) fn with_reload_on_auth_error(mut self, reload: bool) -> Self {
+        self.reload_on_auth_error = reload;
+        self
+    }
+
+    /// Send the requests of every store built through `interceptor`.
+    pub(in crate::object_store) fn with_request_interceptor(
+        mut self,
+        interceptor: Option<Arc<dyn RequestInterceptor>>,
+    ) -> Self {
+        self.request_interceptor = interceptor;
+        self
+    }
+
+    /// Retry the failed upload parts of every store built with `layer`.
+    pub(in crate::object_store) fn with_part_retry_layer(mut self, layer: PartRetryLayer) -> Self {
+        self.part_retry_layer = Some(layer);
+        self
+    }
+
     fn merge_options(
         &self,
         mut dynamic_options: HashMap<String, String>,
@@ -104,7 +140,14 @@ impl DynamicOpenDalStore {
             }
         }
 
-        let store = Arc::new((self.build_store)(config.clone())?);
+        let mut operator = intercept_operator(
+            (self.build_operator)(config.clone())?,
+            self.request_interceptor.as_ref(),
+        );
+        if let Some(layer) = &self.part_retry_layer {
+            operator = operator.layer(layer.clone());
+        }
+        let store = Arc::new(OpendalStore::new(operator));
         let mut cache = self.cache.write().await;
         if let Some(cached) = cache.as_ref()
             && cached.config == config
@@ -125,6 +168,47 @@ impl DynamicOpenDalStore {
             source: Box::new(error),
         }
     }
+
+    async fn with_store<T, F, Fut>(&self, op: F) -> object_store::Result<T>
+    where
+        F: Fn(Arc<OpendalStore>) -> Fut,
+        Fut: Future<Output = object_store::Result<T>>,
+    {
+        let store = self
+            .current_store()
+            .await
+            .map_err(|e| self.map_store_error(e))?;
+        match op(store).await {
+            Err(error) if self.reload_on_auth_error && is_auth_error(&error) => {
+                log::debug!(
+                    "{} rejected credentials, reloading storage options: {}",
+                    self.name,
+                    error
+                );
+                self.accessor
+                    .refresh_storage_options()
+                    .await
+                    .map_err(|e| self.map_store_error(e))?;
+                let store = self
+                    .current_store()
+                    .await
+                    .map_err(|e| self.map_store_error(e))?;
+                op(store).await
+            }
+            result => result,
+        }
+    }
+}
+
+/// OpenDAL reports rejected credentials as `PermissionDenied`, which
+/// object_store_opendal surfaces as a generic error tagged with the kind.
+fn is_auth_error(error: &object_store::Error) -> bool {
+    match error {
+        object_store::Error::PermissionDenied { .. }
+        | object_store::Error::Unauthenticated { .. } => true,
+        object_store::Error::Generic { store, .. } => *store == "PermissionDenied",
+        _ => false,
+    }
 }
 
 impl fmt::Display for DynamicOpenDalStore {
@@ -141,11 +225,12 @@ impl OSObjectStore for DynamicOpenDalStore {
         payload: PutPayload,
         opts: PutOptions,
     ) -> object_store::Result<PutResult> {
-        self.current_store()
-            .await
-            .map_err(|e| self.map_store_error(e))?
-            .put_opts(location, payload, opts)
-            .await
+        self.with_store(|store| {
+            let payload = payload.clone();
+            let opts = opts.clone();
+            async move { store.put_opts(location, payload, opts).await }
+        })
+        .await
     }
 
     async fn put_multipart_opts(
@@ -153,11 +238,11 @@ impl OSObjectStore for DynamicOpenDalStore {
         location: &Path,
         opts: PutMultipartOptions,
     ) -> object_store::Result<Box<dyn MultipartUpload>> {
-        self.current_store()
-            .await
-            .map_err(|e| self.map_store_error(e))?
-            .put_multipart_opts(location, opts)
-            .await
+        self.with_store(|store| {
+            let opts = opts.clone();
+            async move { store.put_multipart_opts(location, opts).await }
+        })
+        .await
     }
 
     async fn get_opts(
@@ -165,11 +250,11 @@ impl OSObjectStore for DynamicOpenDalStore {
         location: &Path,
         options: GetOptions,
     ) -> object_store::Result<GetResult> {
-        self.current_store()
-            .await
-            .map_err(|e| self.map_store_error(e))?
-            .get_opts(location, options)
-            .await
+        self.with_store(|store| {
+            let options = options.clone();
+            async move { store.get_opts(location, options).await }
+        })
+        .await
     }
 
     async fn get_ranges(
@@ -177,10 +262,7 @@ impl OSObjectStore for DynamicOpenDalStore {
         location: &Path,
         ranges: &[Range<u64>],
     ) -> object_store::Result<Vec<Bytes>> {
-        self.current_store()
-            .await
-            .map_err(|e| self.map_store_error(e))?
-            .get_ranges(location, ranges)
+        self.with_store(|store| async move { store.get_ranges(location, ranges).await })
             .await
     }
 
@@ -215,10 +297,7 @@ impl OSObjectStore for DynamicOpenDalStore {
     }
 
     async fn list_with_delimiter(&self, prefix: Option<&Path>) -> object_store::Result<ListResult> {
-        self.current_store()
-            .await
-            .map_err(|e| self.map_store_error(e))?
-            .list_with_delimiter(prefix)
+        self.with_store(|store| async move { store.list_with_delimiter(prefix).await })
             .await
     }
 
@@ -228,11 +307,11 @@ impl OSObjectStore for DynamicOpenDalStore {
         to: &Path,
         opts: CopyOptions,
     ) -> object_store::Result<()> {
-        self.current_store()
-            .await
-            .map_err(|e| self.map_store_error(e))?
-            .copy_opts(from, to, opts)
-            .await
+        self.with_store(|store| {
+            let opts = opts.clone();
+            async move { store.copy_opts(from, to, opts).await }
+        })
+        .await
     }
 
     async fn rename_opts(
@@ -241,11 +320,11 @@ impl OSObjectStore for DynamicOpenDalStore {
         to: &Path,
         opts: RenameOptions,
     ) -> object_store::Result<()> {
-        self.current_store()
-            .await
-            .map_err(|e| self.map_store_error(e))?
-            .rename_opts(from, to, opts)
-            .await
+        self.with_store(|store| {
+            let opts = opts.clone();
+            async move { store.rename_opts(from, to, opts).await }
+        })
+        .await
     }
 }
 
@@ -278,7 +357,7 @@ mod tests {
                         "Failed to create memory operator: {e:?}"
                     ))
                 })?;
-                Ok(OpendalStore::new(operator.finish()))
+                Ok(operator.finish())
             },
         );
 
@@ -316,7 +395,7 @@ mod tests {
                         "Failed to create memory operator: {e:?}"
                     ))
                 })?;
-                Ok(OpendalStore::new(operator.finish()))
+                Ok(operator.finish())
             },
         )
         .with_protected_keys(["bucket", "root"]);
@@ -331,4 +410,21 @@ mod tests {
         assert_eq!(merged.get("root").unwrap(), "/");
         assert_eq!(merged.get("token").unwrap(), "provider-token");
     }
+
+    #[test]
+    fn test_is_auth_error() {
+        let opendal_error = object_store::Error::Generic {
+            store: "PermissionDenied",
+            source: "access denied".into(),
+        };
+        assert!(is_auth_error(&opendal_error));
+        assert!(is_auth_error(&object_store::Error::Unauthenticated {
+            path: "a".to_string(),
+            source: "expired".into(),
+        }));
+        assert!(!is_auth_error(&object_store::Error::NotFound {
+            path: "a".to_string(),
+            source: "missing".into(),
+        }));
+    }
 }
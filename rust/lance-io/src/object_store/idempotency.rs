@@ -0,0 +1,539 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Safe retries of puts after ambiguous failures.
+//!
+//! A put that times out or loses its connection may or may not have been
+//! applied. The object store clients only retry such failures for requests
+//! that are idempotent, so a conditional put (create if absent, update if the
+//! ETag matches) fails outright: retrying it blindly would turn a put that did
+//! land into a spurious conflict.
+//!
+//! [`IdempotentPutStore`] retries transient put failures itself. Every put gets
+//! an [`IdempotencyKey`], a client request token plus the MD5 of the content,
+//! which is attached to the [`PutOptions::extensions`] so that stores wrapped
+//! below it can send it along. When a retried conditional put is rejected with a
+//! conflict, the object is read back: if its ETag is the MD5 of the content we
+//! sent, the earlier attempt landed and the put succeeded.
+//!
+//! Which backends honor the key:
+//!
+//! - `s3`, `cos` and `oss`: the ETag of an object written with a single put is
+//!   the MD5 of its content, so conditional puts are retried. Objects encrypted
+//!   with SSE-KMS or SSE-C on S3 have other ETags; the check then never matches
+//!   and the conflict is returned as it would be without retries.
+//! - Every other backend: ETags are opaque, so only unconditional puts, which
+//!   are idempotent anyway, are retried.
+//!
+//! None of the builtin backends deduplicate by the token on the server side.
+//! A conditional put that conflicts with a concurrent writer who wrote the
+//! exact same content is also reported as a success.
+//!
+//! The number of retries is set with the [`PUT_RETRY_COUNT_KEY`] storage
+//! option, 0 turns them off.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use lance_core::utils::backoff::Backoff;
+use md5::{Digest, Md5};
+use object_store::path::Path;
+use object_store::{
+    CopyOptions, GetOptions, GetResult, ListResult, MultipartUpload, ObjectMeta, ObjectStore,
+    ObjectStoreExt, PutMode, PutMultipartOptions, PutOptions, PutPayload, PutResult, RenameOptions,
+    Result as OSResult,
+};
+use rand::Rng;
+
+use crate::deadline;
+use crate::object_store::classification::{RetryClassifier, classify, retry_delay, should_retry};
+
+/// Storage option with the number of times a failed put is retried.
+pub const PUT_RETRY_COUNT_KEY: &str = "put_retry_count";
+
+const DEFAULT_PUT_RETRY_COUNT: usize = 3;
+
+/// The number of put retries asked for by the storage options.
+pub fn put_retry_count(storage_options: Option<&HashMap<String, String>>) -> usize {
+    storage_options
+        .and_then(|opts| {
+            opts.iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(PUT_RETRY_COUNT_KEY))
+        })
+        .and_then(|(_, value)| value.parse().ok())
+        .unwrap_or(DEFAULT_PUT_RETRY_COUNT)
+}
+
+/// Identifies one put across its retries.
+///
+/// Callers may set their own key in the [`PutOptions::extensions`], e.g. to
+/// reuse the token of an earlier attempt made before a crash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdempotencyKey {
+    /// A random token, unique per put.
+    pub token: String,
+    /// The MD5 of the content.
+    pub content_md5: [u8; 16],
+}
+
+impl IdempotencyKey {
+    pub fn new(payload: &PutPayload) -> Self {
+        let mut md5 = Md5::new();
+        for chunk in payload.iter() {
+            md5.update(chunk);
+        }
+        Self {
+            token: format!("{:032x}", rand::rng().random::<u128>()),
+            content_md5: md5.finalize().into(),
+        }
+    }
+
+    /// The content MD5 as lowercase hex, the form it takes in an ETag.
+    pub fn content_md5_hex(&self) -> String {
+        self.content_md5
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    /// Whether `e_tag` is the ETag of an object written with this key's content.
+    pub fn matches_etag(&self, e_tag: &str) -> bool {
+        e_tag
+            .trim_matches('"')
+            .eq_ignore_ascii_case(&self.content_md5_hex())
+    }
+}
+
+/// An [`ObjectStore`] wrapper that retries transient put failures, see the
+/// [module documentation](self).
+#[derive(Debug)]
+pub struct IdempotentPutStore {
+    target: Arc<dyn ObjectStore>,
+    max_retries: usize,
+    md5_etags: bool,
+    retry_classifier: Option<RetryClassifier>,
+}
+
+impl IdempotentPutStore {
+    /// Set `md5_etags` if the ETag of an object written by a single put is the
+    /// MD5 of its content.
+    pub fn new(target: Arc<dyn ObjectStore>, max_retries: usize, md5_etags: bool) -> Self {
+        Self {
+            target,
+            max_retries,
+            md5_etags,
+            retry_classifier: None,
+        }
+    }
+
+    /// Decide which failed puts are retried with `classifier` instead of
+    /// their [`classify`] result.
+    pub fn with_retry_classifier(mut self, classifier: Option<RetryClassifier>) -> Self {
+        self.retry_classifier = classifier;
+        self
+    }
+}
+
+impl Display for IdempotentPutStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "IdempotentPutStore({})", self.target)
+    }
+}
+
+#[async_trait::async_trait]
+#[deny(clippy::missing_trait_methods)]
+impl ObjectStore for IdempotentPutStore {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        payload: PutPayload,
+        mut opts: PutOptions,
+    ) -> OSResult<PutResult> {
+        let key = match opts.extensions.get::<IdempotencyKey>() {
+            Some(key) => key.clone(),
+            None => {
+                let key = IdempotencyKey::new(&payload);
+                opts.extensions.insert(key.clone());
+                key
+            }
+        };
+        let conditional = !matches!(opts.mode, PutMode::Overwrite);
+        let max_retries = if conditional && !self.md5_etags {
+            0
+        } else {
+            self.max_retries
+        };
+
+        let mut backoff = Backoff::default();
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let err = match self
+                .target
+                .put_opts(location, payload.clone(), opts.clone())
+                .await
+            {
+                Ok(result) => return Ok(result),
+                Err(err) => err,
+            };
+            match err {
+                // A conflict on the first attempt is real, on a retry it may
+                // be our own earlier attempt.
+                object_store::Error::AlreadyExists { .. }
+                | object_store::Error::Precondition { .. }
+                    if attempt > 1 =>
+                {
+                    return match self.target.head(location).await {
+                        Ok(meta) if meta.e_tag.as_deref().is_some_and(|e| key.matches_etag(e)) => {
+                            log::debug!(
+                                "Put {} with token {} landed before it was retried",
+                                location,
+                                key.token
+                            );
+                            Ok(PutResult {
+                                e_tag: meta.e_tag,
+                                version: meta.version,
+                            })
+                        }
+                        _ => Err(err),
+                    };
+                }
+                err if attempt <= max_retries
+                    && should_retry(self.retry_classifier.as_ref(), &err, |err| {
+                        classify(err).retryable
+                    }) =>
+                {
+                    let Some(delay) = retry_delay(&err, backoff.next_backoff())
+                        .filter(|delay| deadline::can_retry_after(*delay))
+                    else {
+                        return Err(err);
+                    };
+                    log::debug!(
+                        "Retrying put {} with token {} in {:?}: {}",
+                        location,
+                        key.token,
+                        delay,
+                        err
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                err => return Err(err),
+            }
+        }
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        opts: PutMultipartOptions,
+    ) -> OSResult<Box<dyn MultipartUpload>> {
+        self.target.put_multipart_opts(location, opts).await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> OSResult<GetResult> {
+        self.target.get_opts(location, options).await
+    }
+
+    async fn get_ranges(&self, location: &Path, ranges: &[Range<u64>]) -> OSResult<Vec<Bytes>> {
+        self.target.get_ranges(location, ranges).await
+    }
+
+    fn delete_stream(
+        &self,
+        locations: BoxStream<'static, OSResult<Path>>,
+    ) -> BoxStream<'static, OSResult<Path>> {
+        self.target.delete_stream(locations)
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'static, OSResult<ObjectMeta>> {
+        self.target.list(prefix)
+    }
+
+    fn list_with_offset(
+        &self,
+        prefix: Option<&Path>,
+        offset: &Path,
+    ) -> BoxStream<'static, OSResult<ObjectMeta>> {
+        self.target.list_with_offset(prefix, offset)
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> OSResult<ListResult> {
+        self.target.list_with_delimiter(prefix).await
+    }
+
+    async fn copy_opts(&self, from: &Path, to: &Path, opts: CopyOptions) -> OSResult<()> {
+        self.target.copy_opts(from, to, opts).await
+    }
+
+    async fn rename_opts(&self, from: &Path, to: &Path, opts: RenameOptions) -> OSResult<()> {
+        self.target.rename_opts(from, to, opts).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use rstest::rstest;
+
+    use super::*;
+
+    fn timeout() -> object_store::Error {
+        object_store::Error::Generic {
+            store: "Flaky",
+            source: Box::new(std::io::Error::from(std::io::ErrorKind::TimedOut)),
+        }
+    }
+
+    /// Stores objects in memory with MD5 ETags, and fails puts with `error`
+    /// while `failures` is not empty. A failure that is `true` is applied
+    /// before the error, like a response lost on the way back.
+    #[derive(Debug)]
+    struct FlakyStore {
+        objects: Mutex<HashMap<Path, Bytes>>,
+        failures: Mutex<Vec<bool>>,
+        error: fn() -> object_store::Error,
+        puts: Mutex<Vec<IdempotencyKey>>,
+    }
+
+    impl FlakyStore {
+        fn new(failures: Vec<bool>) -> Self {
+            Self {
+                objects: Mutex::new(HashMap::new()),
+                failures: Mutex::new(failures),
+                error: timeout,
+                puts: Mutex::new(Vec::new()),
+            }
+        }
+
+        fn meta(location: &Path, content: &Bytes) -> ObjectMeta {
+            let key = IdempotencyKey::new(&PutPayload::from(content.clone()));
+            ObjectMeta {
+                location: location.clone(),
+                last_modified: Default::default(),
+                size: content.len() as u64,
+                e_tag: Some(format!("\"{}\"", key.content_md5_hex())),
+                version: None,
+            }
+        }
+    }
+
+    impl Display for FlakyStore {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "FlakyStore")
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ObjectStore for FlakyStore {
+        async fn put_opts(
+            &self,
+            location: &Path,
+            payload: PutPayload,
+            opts: PutOptions,
+        ) -> OSResult<PutResult> {
+            self.puts
+                .lock()
+                .unwrap()
+                .push(opts.extensions.get::<IdempotencyKey>().unwrap().clone());
+            let failure = self.failures.lock().unwrap().pop();
+            if failure == Some(false) {
+                return Err((self.error)());
+            }
+            let content = Bytes::from(payload);
+            let mut objects = self.objects.lock().unwrap();
+            if opts.mode == PutMode::Create && objects.contains_key(location) {
+                return Err(object_store::Error::AlreadyExists {
+                    path: location.to_string(),
+                    source: "exists".into(),
+                });
+            }
+            objects.insert(location.clone(), content.clone());
+            if failure == Some(true) {
+                return Err((self.error)());
+            }
+            Ok(PutResult {
+                e_tag: Self::meta(location, &content).e_tag,
+                version: None,
+            })
+        }
+
+        async fn put_multipart_opts(
+            &self,
+            _location: &Path,
+            _opts: PutMultipartOptions,
+        ) -> OSResult<Box<dyn MultipartUpload>> {
+            unimplemented!()
+        }
+
+        async fn get_opts(&self, location: &Path, _options: GetOptions) -> OSResult<GetResult> {
+            let objects = self.objects.lock().unwrap();
+            let content = objects
+                .get(location)
+                .ok_or_else(|| object_store::Error::NotFound {
+                    path: location.to_string(),
+                    source: "missing".into(),
+                })?;
+            Ok(GetResult {
+                payload: object_store::GetResultPayload::Stream(Box::pin(futures::stream::once(
+                    futures::future::ready(Ok(content.clone())),
+                ))),
+                meta: Self::meta(location, content),
+                range: 0..content.len() as u64,
+                attributes: Default::default(),
+            })
+        }
+
+        fn delete_stream(
+            &self,
+            _locations: BoxStream<'static, OSResult<Path>>,
+        ) -> BoxStream<'static, OSResult<Path>> {
+            unimplemented!()
+        }
+
+        fn list(&self, _prefix: Option<&Path>) -> BoxStream<'static, OSResult<ObjectMeta>> {
+            unimplemented!()
+        }
+
+        async fn list_with_delimiter(&self, _prefix: Option<&Path>) -> OSResult<ListResult> {
+            unimplemented!()
+        }
+
+        async fn copy_opts(&self, _from: &Path, _to: &Path, _opts: CopyOptions) -> OSResult<()> {
+            unimplemented!()
+        }
+    }
+
+    #[test]
+    fn test_put_retry_count() {
+        assert_eq!(put_retry_count(None), DEFAULT_PUT_RETRY_COUNT);
+        let options = HashMap::from([(PUT_RETRY_COUNT_KEY.to_string(), "0".to_string())]);
+        assert_eq!(put_retry_count(Some(&options)), 0);
+    }
+
+    #[test]
+    fn test_matches_etag() {
+        let key = IdempotencyKey::new(&PutPayload::from_static(b"hello"));
+        // The MD5 of "hello".
+        assert!(key.matches_etag("\"5d41402abc4b2a76b9719d911017c592\""));
+        assert!(key.matches_etag("5D41402ABC4B2A76B9719D911017C592"));
+        // Multipart ETags are never the MD5 of the content.
+        assert!(!key.matches_etag("\"5d41402abc4b2a76b9719d911017c592-2\""));
+        let other = IdempotencyKey::new(&PutPayload::from_static(b"hello"));
+        assert_ne!(key.token, other.token);
+    }
+
+    #[rstest]
+    #[case::overwrite(PutMode::Overwrite, true, true)]
+    #[case::overwrite_opaque_etags(PutMode::Overwrite, false, true)]
+    #[case::create(PutMode::Create, true, true)]
+    #[case::create_opaque_etags(PutMode::Create, false, false)]
+    #[tokio::test]
+    async fn test_retry_ambiguous_put(
+        #[case] mode: PutMode,
+        #[case] md5_etags: bool,
+        #[case] succeeds: bool,
+    ) {
+        let target = Arc::new(FlakyStore::new(vec![true]));
+        let store = IdempotentPutStore::new(target.clone(), 3, md5_etags);
+        let path = Path::from("_versions/1.manifest");
+        let opts = PutOptions {
+            mode,
+            ..Default::default()
+        };
+        let result = store
+            .put_opts(&path, PutPayload::from_static(b"manifest"), opts)
+            .await;
+        assert_eq!(result.is_ok(), succeeds, "{result:?}");
+        let puts = target.puts.lock().unwrap();
+        // Retries reuse the key of the first attempt.
+        assert_eq!(puts.len(), if succeeds { 2 } else { 1 });
+        assert!(puts.iter().all(|key| key == &puts[0]));
+    }
+
+    #[tokio::test]
+    async fn test_retry_conflict_is_kept() {
+        let target = Arc::new(FlakyStore::new(vec![false]));
+        let store = IdempotentPutStore::new(target.clone(), 3, true);
+        let path = Path::from("_versions/1.manifest");
+        let create = || PutOptions {
+            mode: PutMode::Create,
+            ..Default::default()
+        };
+        // Another writer creates the object while our first attempt, which
+        // never landed, is retried.
+        target
+            .objects
+            .lock()
+            .unwrap()
+            .insert(path.clone(), Bytes::from_static(b"theirs"));
+        let err = store
+            .put_opts(&path, PutPayload::from_static(b"ours"), create())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, object_store::Error::AlreadyExists { .. }));
+        assert_eq!(target.puts.lock().unwrap().len(), 2);
+
+        // With retries off the timeout is returned as is.
+        let target = Arc::new(FlakyStore::new(vec![true]));
+        let store = IdempotentPutStore::new(target.clone(), 0, true);
+        let err = store
+            .put(&path, PutPayload::from_static(b"ours"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, object_store::Error::Generic { .. }));
+        assert_eq!(target.puts.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_classifier() {
+        // S3 answers 409 OperationAborted while a conflicting operation on
+        // the key is in flight, which is not retried by default.
+        fn aborted() -> object_store::Error {
+            object_store::Error::Generic {
+                store: "S3",
+                source: "409 Conflict: OperationAborted".into(),
+            }
+        }
+        let path = Path::from("data/0.lance");
+        let flaky = || {
+            let mut store = FlakyStore::new(vec![false]);
+            store.error = aborted;
+            Arc::new(store)
+        };
+
+        let target = flaky();
+        let store = IdempotentPutStore::new(target.clone(), 3, true);
+        store
+            .put(&path, PutPayload::from_static(b"data"))
+            .await
+            .unwrap_err();
+        assert_eq!(target.puts.lock().unwrap().len(), 1);
+
+        let target = flaky();
+        let classifier: RetryClassifier = Arc::new(|err| err.to_string().contains("409"));
+        let store = IdempotentPutStore::new(target.clone(), 3, true)
+            .with_retry_classifier(Some(classifier));
+        store
+            .put(&path, PutPayload::from_static(b"data"))
+            .await
+            .unwrap();
+        assert_eq!(target.puts.lock().unwrap().len(), 2);
+
+        // The classifier also replaces the builtin decision for errors that
+        // would be retried otherwise.
+        let target = Arc::new(FlakyStore::new(vec![false]));
+        let classifier: RetryClassifier = Arc::new(|_| false);
+        let store = IdempotentPutStore::new(target.clone(), 3, true)
+            .with_retry_classifier(Some(classifier));
+        store
+            .put(&path, PutPayload::from_static(b"data"))
+            .await
+            .unwrap_err();
+        assert_eq!(target.puts.lock().unwrap().len(), 1);
+    }
+}
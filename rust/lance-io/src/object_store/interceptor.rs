@@ -0,0 +1,250 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Hooks into the HTTP requests of the cloud stores.
+//!
+//! A [`RequestInterceptor`] set on
+//! [`ObjectStoreParams::request_interceptor`](super::ObjectStoreParams::request_interceptor)
+//! is called with every request right before it is sent, once the store has
+//! signed it, and can add or replace headers. This is the place to sign
+//! requests for a custom auth service, e.g. a proxy in front of the bucket
+//! that expects its own token.
+//!
+//! It applies to the `object_store` S3, GCS and Azure clients and to the
+//! OpenDAL operators. Requests the clients make to fetch credentials, such as
+//! to an instance metadata service, are intercepted as well, and so is every
+//! retry of a request. Local and in-memory stores make no HTTP requests.
+//!
+//! A request the interceptor fails is not sent. The store reports it as an
+//! authentication error for the request path, which is not retried.
+
+use std::fmt::Debug;
+#[cfg(any(
+    feature = "aws",
+    feature = "azure",
+    feature = "gcp",
+    feature = "oss",
+    feature = "tencent",
+    feature = "huggingface"
+))]
+use std::sync::Arc;
+
+#[cfg(any(
+    feature = "aws",
+    feature = "azure",
+    feature = "gcp",
+    feature = "oss",
+    feature = "tencent",
+    feature = "huggingface"
+))]
+use http::Uri;
+use http::{HeaderMap, Method};
+use lance_core::Result;
+use object_store::GetRange;
+
+/// A request about to be sent to the object store, see the
+/// [module documentation](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterceptedRequest {
+    pub method: Method,
+    /// The path of the request URL, still percent-encoded. Depending on the
+    /// store it starts with the bucket, e.g. for path-style S3 URLs.
+    pub path: String,
+    /// The bytes requested by the `Range` header, if any.
+    pub range: Option<GetRange>,
+}
+
+#[cfg(any(
+    feature = "aws",
+    feature = "azure",
+    feature = "gcp",
+    feature = "oss",
+    feature = "tencent",
+    feature = "huggingface"
+))]
+impl InterceptedRequest {
+    fn new(method: &Method, uri: &Uri, headers: &HeaderMap) -> Self {
+        Self {
+            method: method.clone(),
+            path: uri.path().to_string(),
+            range: headers
+                .get(http::header::RANGE)
+                .and_then(|value| value.to_str().ok())
+                .and_then(parse_range),
+        }
+    }
+
+    fn rejection(&self, error: &lance_core::Error) -> String {
+        format!(
+            "request interceptor rejected {} {}: {}",
+            self.method, self.path, error
+        )
+    }
+}
+
+/// Parse a single range of a `Range: bytes=...` header.
+#[cfg(any(
+    feature = "aws",
+    feature = "azure",
+    feature = "gcp",
+    feature = "oss",
+    feature = "tencent",
+    feature = "huggingface"
+))]
+fn parse_range(value: &str) -> Option<GetRange> {
+    let (start, end) = value.strip_prefix("bytes=")?.split_once('-')?;
+    match (start, end) {
+        ("", suffix) => Some(GetRange::Suffix(suffix.parse().ok()?)),
+        (start, "") => Some(GetRange::Offset(start.parse().ok()?)),
+        (start, end) => {
+            let end = end.parse::<u64>().ok()?.checked_add(1)?;
+            Some(GetRange::Bounded(start.parse().ok()?..end))
+        }
+    }
+}
+
+/// Changes the headers of the requests sent to the object store, see the
+/// [module documentation](self).
+///
+/// Headers the store signed, like `Authorization` or `x-amz-*` headers of
+/// S3, should be left alone, the signature would no longer match.
+#[async_trait::async_trait]
+pub trait RequestInterceptor: Debug + Send + Sync {
+    async fn intercept(&self, request: &InterceptedRequest, headers: &mut HeaderMap) -> Result<()>;
+}
+
+#[cfg(any(feature = "aws", feature = "azure", feature = "gcp"))]
+pub(crate) use connector::InterceptingConnector;
+
+#[cfg(any(feature = "aws", feature = "azure", feature = "gcp"))]
+mod connector {
+    use object_store::ClientOptions;
+    use object_store::client::{
+        HttpClient, HttpConnector, HttpError, HttpRequest, HttpResponse, HttpService,
+        ReqwestConnector,
+    };
+
+    use super::*;
+
+    /// Connects the `object_store` clients through the interceptor.
+    #[derive(Debug)]
+    pub struct InterceptingConnector {
+        interceptor: Arc<dyn RequestInterceptor>,
+    }
+
+    impl InterceptingConnector {
+        pub fn new(interceptor: Arc<dyn RequestInterceptor>) -> Self {
+            Self { interceptor }
+        }
+    }
+
+    impl HttpConnector for InterceptingConnector {
+        fn connect(&self, options: &ClientOptions) -> object_store::Result<HttpClient> {
+            Ok(HttpClient::new(InterceptingService {
+                inner: ReqwestConnector::default().connect(options)?,
+                interceptor: self.interceptor.clone(),
+            }))
+        }
+    }
+
+    #[derive(Debug)]
+    struct InterceptingService {
+        inner: HttpClient,
+        interceptor: Arc<dyn RequestInterceptor>,
+    }
+
+    #[async_trait::async_trait]
+    impl HttpService for InterceptingService {
+        async fn call(&self, mut req: HttpRequest) -> std::result::Result<HttpResponse, HttpError> {
+            let request = InterceptedRequest::new(req.method(), req.uri(), req.headers());
+            if let Err(error) = self
+                .interceptor
+                .intercept(&request, req.headers_mut())
+                .await
+            {
+                // The client turns a 401 into an unauthenticated error for
+                // the path of the operation and doesn't retry it.
+                return Ok(http::Response::builder()
+                    .status(http::StatusCode::UNAUTHORIZED)
+                    .body(request.rejection(&error).into())
+                    .expect("valid response"));
+            }
+            self.inner.execute(req).await
+        }
+    }
+}
+
+/// Send the requests of `operator` through the interceptor, if any.
+#[cfg(any(
+    feature = "aws",
+    feature = "azure",
+    feature = "gcp",
+    feature = "oss",
+    feature = "tencent",
+    feature = "huggingface"
+))]
+pub(crate) fn intercept_operator(
+    operator: opendal::Operator,
+    interceptor: Option<&Arc<dyn RequestInterceptor>>,
+) -> opendal::Operator {
+    use opendal::layers::HttpClientLayer;
+    use opendal::raw::{Access, HttpBody, HttpClient, HttpFetch};
+
+    struct InterceptingFetch {
+        inner: HttpClient,
+        interceptor: Arc<dyn RequestInterceptor>,
+    }
+
+    impl HttpFetch for InterceptingFetch {
+        async fn fetch(
+            &self,
+            mut req: http::Request<opendal::Buffer>,
+        ) -> opendal::Result<http::Response<HttpBody>> {
+            let request = InterceptedRequest::new(req.method(), req.uri(), req.headers());
+            if let Err(error) = self
+                .interceptor
+                .intercept(&request, req.headers_mut())
+                .await
+            {
+                return Err(opendal::Error::new(
+                    opendal::ErrorKind::PermissionDenied,
+                    request.rejection(&error),
+                )
+                .with_context("path", &request.path));
+            }
+            self.inner.fetch(req).await
+        }
+    }
+
+    let Some(interceptor) = interceptor else {
+        return operator;
+    };
+    // Wrap the client the operator was built with, which may be customized.
+    let inner = operator.inner().info().http_client();
+    operator.layer(HttpClientLayer::new(HttpClient::with(InterceptingFetch {
+        inner,
+        interceptor: interceptor.clone(),
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(any(
+        feature = "aws",
+        feature = "azure",
+        feature = "gcp",
+        feature = "oss",
+        feature = "tencent",
+        feature = "huggingface"
+    ))]
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=2-4"), Some(GetRange::Bounded(2..5)));
+        assert_eq!(parse_range("bytes=10-"), Some(GetRange::Offset(10)));
+        assert_eq!(parse_range("bytes=-8"), Some(GetRange::Suffix(8)));
+        assert_eq!(parse_range("bytes=a-4"), None);
+        assert_eq!(parse_range("items=2-4"), None);
+    }
+}
@@ -0,0 +1,146 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Filters of [`ObjectStore::list_filtered`](super::ObjectStore::list_filtered).
+
+use chrono::{DateTime, Utc};
+use object_store::ObjectMeta;
+use object_store::path::{DELIMITER, Path};
+use regex::Regex;
+
+/// Which of the listed objects to return. Every field is optional and an
+/// object is returned if it passes all that are set.
+#[derive(Debug, Clone, Default)]
+pub struct ListFilter {
+    /// Only objects last modified before this time.
+    pub older_than: Option<DateTime<Utc>>,
+    /// Only objects last modified after this time.
+    pub newer_than: Option<DateTime<Utc>>,
+    /// Only objects of at least this many bytes.
+    pub min_size: Option<u64>,
+    /// Only objects of at most this many bytes.
+    pub max_size: Option<u64>,
+    /// Only objects whose full path matches, e.g. `\.lance$`.
+    ///
+    /// A pattern anchored with `^` and starting with a literal directory,
+    /// like `^data/2024/.*\.lance$`, narrows the listing to that directory.
+    pub name_regex: Option<Regex>,
+}
+
+impl ListFilter {
+    pub fn matches(&self, meta: &ObjectMeta) -> bool {
+        self.older_than
+            .is_none_or(|older_than| meta.last_modified < older_than)
+            && self
+                .newer_than
+                .is_none_or(|newer_than| meta.last_modified > newer_than)
+            && self.min_size.is_none_or(|min_size| meta.size >= min_size)
+            && self.max_size.is_none_or(|max_size| meta.size <= max_size)
+            && self
+                .name_regex
+                .as_ref()
+                .is_none_or(|regex| regex.is_match(meta.location.as_ref()))
+    }
+
+    /// Whether no object can pass the filter.
+    pub(crate) fn is_empty(&self) -> bool {
+        let empty_times = self
+            .older_than
+            .zip(self.newer_than)
+            .is_some_and(|(older_than, newer_than)| older_than <= newer_than);
+        let empty_sizes = self
+            .min_size
+            .zip(self.max_size)
+            .is_some_and(|(min_size, max_size)| min_size > max_size);
+        empty_times || empty_sizes
+    }
+
+    /// The prefix to list instead of `prefix`, the directory under it that
+    /// every path matching [`Self::name_regex`] is in.
+    pub(crate) fn listing_prefix(&self, prefix: Option<Path>) -> Option<Path> {
+        let Some(directory) = self.name_regex.as_ref().and_then(literal_directory) else {
+            return prefix;
+        };
+        let within_prefix = match &prefix {
+            Some(prefix) => {
+                let prefix = prefix.as_ref();
+                prefix.is_empty()
+                    || directory
+                        .strip_prefix(prefix)
+                        .is_some_and(|rest| rest.starts_with(DELIMITER))
+            }
+            None => true,
+        };
+        match Path::parse(&directory) {
+            Ok(directory) if within_prefix => Some(directory),
+            _ => prefix,
+        }
+    }
+}
+
+/// The directory every match of an anchored `regex` is in, if it starts with
+/// one, e.g. `data/2024` for `^data/2024/.*`.
+fn literal_directory(regex: &Regex) -> Option<String> {
+    let pattern = regex.as_str().strip_prefix('^')?;
+    // A match of another branch could be anywhere.
+    if pattern.contains('|') {
+        return None;
+    }
+    let mut literal = String::new();
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        let c = match c {
+            '\\' => match chars.next() {
+                Some(escaped) if !escaped.is_ascii_alphanumeric() => escaped,
+                _ => break,
+            },
+            '.' | '+' | '*' | '?' | '(' | ')' | '[' | ']' | '{' | '}' | '^' | '$' => break,
+            c => c,
+        };
+        // A quantifier makes the character optional.
+        if matches!(chars.peek(), Some('?' | '*' | '{')) {
+            break;
+        }
+        literal.push(c);
+    }
+    let (directory, _) = literal.rsplit_once(DELIMITER)?;
+    (!directory.is_empty()).then(|| directory.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case::directory(r"^data/2024/.*\.lance$", Some("data/2024"))]
+    #[case::escaped(r"^data\.v2/x", Some("data.v2"))]
+    #[case::partial_name(r"^data/ab+", Some("data"))]
+    #[case::optional_delimiter(r"^data/?x", None)]
+    #[case::unanchored(r"data/2024/", None)]
+    #[case::alternation(r"^data/2024/|^other/", None)]
+    #[case::class(r"^[dD]ata/", None)]
+    #[case::root_file(r"^file\.txt$", None)]
+    fn test_literal_directory(#[case] pattern: &str, #[case] expected: Option<&str>) {
+        let regex = Regex::new(pattern).unwrap();
+        assert_eq!(literal_directory(&regex).as_deref(), expected);
+    }
+
+    #[rstest]
+    #[case::no_prefix(None, Some("data/2024"))]
+    #[case::parent(Some("data"), Some("data/2024"))]
+    #[case::same(Some("data/2024"), Some("data/2024"))]
+    #[case::sibling(Some("dat"), Some("dat"))]
+    #[case::unrelated(Some("other"), Some("other"))]
+    fn test_listing_prefix(#[case] prefix: Option<&str>, #[case] expected: Option<&str>) {
+        let filter = ListFilter {
+            name_regex: Some(Regex::new(r"^data/2024/.*").unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(
+            filter.listing_prefix(prefix.map(Path::from)),
+            expected.map(Path::from)
+        );
+    }
+}
@@ -1,7 +1,13 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-FileCopyrightText: Copyright The Lance Authors
 
-use std::{future::Future, pin::Pin, sync::Arc, task::Poll, time::Duration};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::Poll,
+    time::{Duration, Instant},
+};
 
 use futures::stream::BoxStream;
 use futures::{Stream, StreamExt};
@@ -9,6 +15,9 @@ use object_store::{ObjectMeta, ObjectStore, path::Path};
 use rand::Rng;
 use tokio::time::Sleep;
 
+use crate::deadline;
+use crate::object_store::classification::{RetryClassifier, retry_delay, should_retry};
+
 const DEFAULT_BASE_RETRY_DELAY: Duration = Duration::from_millis(100);
 const DEFAULT_MAX_RETRY_DELAY: Duration = Duration::from_secs(5);
 
@@ -16,6 +25,9 @@ const DEFAULT_MAX_RETRY_DELAY: Duration = Duration::from_secs(5);
 ///
 /// This is to handle request responses that ObjectStore doesn't handle, such as
 /// the error `error decoding response body` from queries to GCS.
+///
+/// The [deadline](crate::deadline) in effect when the stream is created bounds the
+/// retries, since the stream may be polled from a different task.
 pub struct ListRetryStream {
     object_store: Arc<dyn ObjectStore>,
     current_stream: BoxStream<'static, object_store::Result<ObjectMeta>>,
@@ -26,6 +38,8 @@ pub struct ListRetryStream {
     retry_sleep: Option<Pin<Box<Sleep>>>,
     base_retry_delay: Duration,
     max_retry_delay: Duration,
+    deadline: Option<Instant>,
+    retry_classifier: Option<RetryClassifier>,
 }
 
 impl ListRetryStream {
@@ -45,6 +59,8 @@ impl ListRetryStream {
             retry_sleep: None,
             base_retry_delay: DEFAULT_BASE_RETRY_DELAY,
             max_retry_delay: DEFAULT_MAX_RETRY_DELAY,
+            deadline: deadline::current_deadline(),
+            retry_classifier: None,
         }
     }
 
@@ -67,7 +83,25 @@ impl ListRetryStream {
             retry_sleep: None,
             base_retry_delay,
             max_retry_delay,
+            deadline: deadline::current_deadline(),
+            retry_classifier: None,
+        }
+    }
+
+    /// Only list the objects whose paths sort after `start_after`.
+    pub fn with_start_after(mut self, start_after: Option<Path>) -> Self {
+        if start_after.is_some() {
+            self.last_successful_key = start_after;
+            self.recreate_stream();
         }
+        self
+    }
+
+    /// Decide which failed list requests are retried with `classifier` instead
+    /// of retrying every error but those about the request itself.
+    pub fn with_retry_classifier(mut self, classifier: Option<RetryClassifier>) -> Self {
+        self.retry_classifier = classifier;
+        self
     }
 
     fn is_retryable(error: &object_store::Error) -> bool {
@@ -132,10 +166,17 @@ impl Stream for ListRetryStream {
                     // If the stream is done, return None
                     return Poll::Ready(None);
                 }
-                Poll::Ready(Some(Err(error))) if Self::is_retryable(&error) => {
+                Poll::Ready(Some(Err(error)))
+                    if should_retry(this.retry_classifier.as_ref(), &error, Self::is_retryable) =>
+                {
                     if this.current_retries < this.max_retries {
                         this.current_retries += 1;
-                        this.retry_sleep = Some(Box::pin(tokio::time::sleep(this.retry_delay())));
+                        let Some(delay) = retry_delay(&error, this.retry_delay())
+                            .filter(|delay| deadline::can_retry_before(this.deadline, *delay))
+                        else {
+                            return Poll::Ready(Some(Err(error)));
+                        };
+                        this.retry_sleep = Some(Box::pin(tokio::time::sleep(delay)));
 
                         continue;
                     } else {
@@ -161,7 +202,6 @@ mod tests {
     use std::ops::Range;
     use std::sync::Mutex;
     use std::sync::atomic::{AtomicUsize, Ordering};
-    use std::time::Instant;
 
     use async_trait::async_trait;
     use bytes::Bytes;
@@ -400,4 +440,33 @@ mod tests {
         assert_eq!(store.list_calls(), 1);
         assert_eq!(store.offset_calls(), 0);
     }
+
+    #[tokio::test]
+    async fn test_list_retry_stream_stops_at_deadline() {
+        let store = Arc::new(ScriptedListStore::new(
+            vec![
+                vec![Err(retryable_error())],
+                vec![Ok(object_meta("prefix/file"))],
+            ],
+            vec![],
+        ));
+        let deadline = Instant::now() + Duration::from_millis(20);
+        let stream = deadline::with_deadline(deadline, async {
+            ListRetryStream::new_with_backoff(
+                store.clone(),
+                Some(Path::from("prefix")),
+                5,
+                Duration::from_millis(100),
+                Duration::from_millis(100),
+            )
+        })
+        .await;
+
+        // The deadline is captured when the stream is created
+        let items = stream.collect::<Vec<_>>().await;
+
+        assert_eq!(items.len(), 1);
+        assert!(items[0].is_err());
+        assert_eq!(store.list_calls(), 1);
+    }
 }
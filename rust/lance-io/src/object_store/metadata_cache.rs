@@ -0,0 +1,475 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Short-lived cache of object metadata returned by HEAD requests.
+//!
+//! Scans tend to HEAD the same handful of files over and over. When enabled
+//! with the `storage_metadata_cache_size` storage option, [`MetadataCachingStore`]
+//! keeps the most recently seen [`ObjectMeta`] for up to that many paths and
+//! answers repeated HEADs from memory for a short TTL. Any write, copy, rename
+//! or delete through the store invalidates the affected paths, so the cache
+//! never serves metadata for an object this process has since modified.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
+use moka::sync::Cache;
+use object_store::path::Path;
+use object_store::{
+    Attributes, CopyOptions, GetOptions, GetResult, GetResultPayload, ListResult, MultipartUpload,
+    ObjectMeta, ObjectStore, PutMultipartOptions, PutOptions, PutPayload, PutResult, RenameOptions,
+    Result as OSResult, UploadPart,
+};
+
+use crate::utils::tracking_store::IOTracker;
+use lance_core::{Error, Result};
+
+/// Storage option that sets how many paths the metadata cache may hold.
+pub const METADATA_CACHE_SIZE_KEY: &str = "storage_metadata_cache_size";
+/// Storage option that sets how long, in milliseconds, a cached entry is used.
+pub const METADATA_CACHE_TTL_KEY: &str = "storage_metadata_cache_ttl_ms";
+
+const DEFAULT_METADATA_CACHE_TTL: Duration = Duration::from_secs(1);
+
+/// Configuration for [`MetadataCachingStore`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataCacheConfig {
+    /// Maximum number of paths to keep. Zero disables the cache.
+    pub capacity: u64,
+    /// How long a cached HEAD result is served before it is fetched again.
+    pub ttl: Duration,
+}
+
+impl Default for MetadataCacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 0,
+            ttl: DEFAULT_METADATA_CACHE_TTL,
+        }
+    }
+}
+
+impl MetadataCacheConfig {
+    /// Build the config from storage options.
+    ///
+    /// | Storage Option Key              | Default |
+    /// |---------------------------------|---------|
+    /// | `storage_metadata_cache_size`   | 0 (off) |
+    /// | `storage_metadata_cache_ttl_ms` | 1000    |
+    pub fn from_storage_options(storage_options: Option<&HashMap<String, String>>) -> Result<Self> {
+        fn parse_u64(options: Option<&HashMap<String, String>>, key: &str) -> Result<Option<u64>> {
+            options
+                .and_then(|opts| opts.get(key))
+                .map(|val| {
+                    val.parse::<u64>().map_err(|_| {
+                        Error::invalid_input(format!(
+                            "Invalid value for storage option '{key}': '{val}'"
+                        ))
+                    })
+                })
+                .transpose()
+        }
+
+        let mut config = Self::default();
+        if let Some(capacity) = parse_u64(storage_options, METADATA_CACHE_SIZE_KEY)? {
+            config.capacity = capacity;
+        }
+        if let Some(ttl_ms) = parse_u64(storage_options, METADATA_CACHE_TTL_KEY)? {
+            config.ttl = Duration::from_millis(ttl_ms);
+        }
+        Ok(config)
+    }
+
+    /// Returns `true` when the cache layer should be skipped entirely.
+    pub fn is_disabled(&self) -> bool {
+        self.capacity == 0 || self.ttl.is_zero()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CachedMetadata {
+    meta: ObjectMeta,
+    attributes: Attributes,
+}
+
+/// The cached entries, and a generation that every invalidation bumps.
+///
+/// A HEAD still in flight when a path is invalidated must not put the
+/// metadata it fetched before the write back into the cache. Inserts are
+/// dropped if any invalidation ran since the request started.
+#[derive(Debug, Clone)]
+struct MetadataCache {
+    entries: Cache<Path, CachedMetadata>,
+    generation: Arc<AtomicU64>,
+}
+
+impl MetadataCache {
+    fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    fn invalidate(&self, location: &Path) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        self.entries.invalidate(location);
+    }
+
+    /// Cache `metadata` fetched by a request that started at `generation`.
+    fn insert(&self, location: &Path, generation: u64, metadata: CachedMetadata) {
+        if self.generation() != generation {
+            return;
+        }
+        self.entries.insert(location.clone(), metadata);
+        // An invalidation between the check and the insert may have run
+        // before the entry existed, so check again and undo the insert.
+        if self.generation() != generation {
+            self.entries.invalidate(location);
+        }
+    }
+}
+
+/// An [`ObjectStore`] wrapper that caches HEAD results by path.
+///
+/// Only plain HEADs (no preconditions, range or version) are served from the
+/// cache. Hits and misses are recorded on the provided [`IOTracker`].
+#[derive(Debug)]
+pub struct MetadataCachingStore {
+    target: Arc<dyn ObjectStore>,
+    cache: MetadataCache,
+    io_tracker: IOTracker,
+}
+
+impl MetadataCachingStore {
+    pub fn new(
+        target: Arc<dyn ObjectStore>,
+        config: &MetadataCacheConfig,
+        io_tracker: IOTracker,
+    ) -> Self {
+        let cache = MetadataCache {
+            entries: Cache::builder()
+                .max_capacity(config.capacity)
+                .time_to_live(config.ttl)
+                .build(),
+            generation: Arc::new(AtomicU64::new(0)),
+        };
+        Self {
+            target,
+            cache,
+            io_tracker,
+        }
+    }
+
+    fn is_plain_head(options: &GetOptions) -> bool {
+        options.head
+            && options.if_match.is_none()
+            && options.if_none_match.is_none()
+            && options.if_modified_since.is_none()
+            && options.if_unmodified_since.is_none()
+            && options.range.is_none()
+            && options.version.is_none()
+    }
+}
+
+impl Display for MetadataCachingStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MetadataCachingStore({})", self.target)
+    }
+}
+
+#[async_trait::async_trait]
+#[deny(clippy::missing_trait_methods)]
+impl ObjectStore for MetadataCachingStore {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        payload: PutPayload,
+        opts: PutOptions,
+    ) -> OSResult<PutResult> {
+        self.cache.invalidate(location);
+        let result = self.target.put_opts(location, payload, opts).await;
+        self.cache.invalidate(location);
+        result
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        opts: PutMultipartOptions,
+    ) -> OSResult<Box<dyn MultipartUpload>> {
+        self.cache.invalidate(location);
+        let target = self.target.put_multipart_opts(location, opts).await?;
+        Ok(Box::new(InvalidatingMultipartUpload {
+            target,
+            location: location.clone(),
+            cache: self.cache.clone(),
+        }))
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> OSResult<GetResult> {
+        if !Self::is_plain_head(&options) {
+            return self.target.get_opts(location, options).await;
+        }
+
+        if let Some(cached) = self.cache.entries.get(location) {
+            self.io_tracker.record_metadata_cache_hit();
+            return Ok(GetResult {
+                payload: GetResultPayload::Stream(futures::stream::empty().boxed()),
+                range: 0..cached.meta.size,
+                meta: cached.meta,
+                attributes: cached.attributes,
+            });
+        }
+
+        self.io_tracker.record_metadata_cache_miss();
+        let generation = self.cache.generation();
+        let result = self.target.get_opts(location, options).await?;
+        self.cache.insert(
+            location,
+            generation,
+            CachedMetadata {
+                meta: result.meta.clone(),
+                attributes: result.attributes.clone(),
+            },
+        );
+        Ok(result)
+    }
+
+    async fn get_ranges(&self, location: &Path, ranges: &[Range<u64>]) -> OSResult<Vec<Bytes>> {
+        self.target.get_ranges(location, ranges).await
+    }
+
+    fn delete_stream(
+        &self,
+        locations: BoxStream<'static, OSResult<Path>>,
+    ) -> BoxStream<'static, OSResult<Path>> {
+        let before = self.cache.clone();
+        let after = self.cache.clone();
+        let locations = locations
+            .inspect_ok(move |path| before.invalidate(path))
+            .boxed();
+        self.target
+            .delete_stream(locations)
+            .inspect_ok(move |path| after.invalidate(path))
+            .boxed()
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'static, OSResult<ObjectMeta>> {
+        self.target.list(prefix)
+    }
+
+    fn list_with_offset(
+        &self,
+        prefix: Option<&Path>,
+        offset: &Path,
+    ) -> BoxStream<'static, OSResult<ObjectMeta>> {
+        self.target.list_with_offset(prefix, offset)
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> OSResult<ListResult> {
+        self.target.list_with_delimiter(prefix).await
+    }
+
+    async fn copy_opts(&self, from: &Path, to: &Path, opts: CopyOptions) -> OSResult<()> {
+        self.cache.invalidate(to);
+        let result = self.target.copy_opts(from, to, opts).await;
+        self.cache.invalidate(to);
+        result
+    }
+
+    async fn rename_opts(&self, from: &Path, to: &Path, opts: RenameOptions) -> OSResult<()> {
+        self.cache.invalidate(from);
+        self.cache.invalidate(to);
+        let result = self.target.rename_opts(from, to, opts).await;
+        self.cache.invalidate(from);
+        self.cache.invalidate(to);
+        result
+    }
+}
+
+/// Invalidates the upload's path once the object becomes visible.
+#[derive(Debug)]
+struct InvalidatingMultipartUpload {
+    target: Box<dyn MultipartUpload>,
+    location: Path,
+    cache: MetadataCache,
+}
+
+#[async_trait::async_trait]
+impl MultipartUpload for InvalidatingMultipartUpload {
+    fn put_part(&mut self, data: PutPayload) -> UploadPart {
+        self.target.put_part(data)
+    }
+
+    async fn complete(&mut self) -> OSResult<PutResult> {
+        let result = self.target.complete().await;
+        self.cache.invalidate(&self.location);
+        result
+    }
+
+    async fn abort(&mut self) -> OSResult<()> {
+        self.target.abort().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use object_store::ObjectStoreExt;
+    use object_store::memory::InMemory;
+
+    use super::*;
+
+    fn caching_store(ttl: Duration) -> (MetadataCachingStore, IOTracker) {
+        let io_tracker = IOTracker::default();
+        let config = MetadataCacheConfig { capacity: 16, ttl };
+        let store =
+            MetadataCachingStore::new(Arc::new(InMemory::new()), &config, io_tracker.clone());
+        (store, io_tracker)
+    }
+
+    #[test]
+    fn test_config_from_storage_options() {
+        assert!(
+            MetadataCacheConfig::from_storage_options(None)
+                .unwrap()
+                .is_disabled()
+        );
+
+        let options = HashMap::from([
+            (METADATA_CACHE_SIZE_KEY.to_string(), "128".to_string()),
+            (METADATA_CACHE_TTL_KEY.to_string(), "250".to_string()),
+        ]);
+        let config = MetadataCacheConfig::from_storage_options(Some(&options)).unwrap();
+        assert_eq!(
+            config,
+            MetadataCacheConfig {
+                capacity: 128,
+                ttl: Duration::from_millis(250),
+            }
+        );
+
+        let options = HashMap::from([(METADATA_CACHE_SIZE_KEY.to_string(), "many".to_string())]);
+        let err = MetadataCacheConfig::from_storage_options(Some(&options)).unwrap_err();
+        assert!(matches!(err, Error::InvalidInput { .. }));
+        assert!(
+            err.to_string()
+                .contains("Invalid value for storage option 'storage_metadata_cache_size': 'many'")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_repeated_heads_hit_cache() {
+        let (store, io_tracker) = caching_store(Duration::from_secs(60));
+        let path = Path::from("data/a.lance");
+        store
+            .put(&path, PutPayload::from_static(b"abc"))
+            .await
+            .unwrap();
+
+        let first = store.head(&path).await.unwrap();
+        let second = store.head(&path).await.unwrap();
+        assert_eq!(first, second);
+        assert_eq!(second.size, 3);
+
+        let stats = io_tracker.stats();
+        assert_eq!(stats.metadata_cache_misses, 1);
+        assert_eq!(stats.metadata_cache_hits, 1);
+    }
+
+    #[tokio::test]
+    async fn test_writes_and_deletes_invalidate() {
+        let (store, io_tracker) = caching_store(Duration::from_secs(60));
+        let path = Path::from("data/a.lance");
+        store
+            .put(&path, PutPayload::from_static(b"abc"))
+            .await
+            .unwrap();
+        assert_eq!(store.head(&path).await.unwrap().size, 3);
+
+        store
+            .put(&path, PutPayload::from_static(b"abcdef"))
+            .await
+            .unwrap();
+        assert_eq!(store.head(&path).await.unwrap().size, 6);
+
+        let copy = Path::from("data/b.lance");
+        store
+            .put(&copy, PutPayload::from_static(b"x"))
+            .await
+            .unwrap();
+        assert_eq!(store.head(&copy).await.unwrap().size, 1);
+        store.copy(&path, &copy).await.unwrap();
+        assert_eq!(store.head(&copy).await.unwrap().size, 6);
+
+        store.delete(&path).await.unwrap();
+        assert!(matches!(
+            store.head(&path).await,
+            Err(object_store::Error::NotFound { .. })
+        ));
+
+        let mut upload = store.put_multipart(&path).await.unwrap();
+        upload
+            .put_part(PutPayload::from_static(b"multipart"))
+            .await
+            .unwrap();
+        upload.complete().await.unwrap();
+        assert_eq!(store.head(&path).await.unwrap().size, 9);
+
+        let stats = io_tracker.stats();
+        assert_eq!(stats.metadata_cache_hits, 0);
+        assert_eq!(stats.metadata_cache_misses, 6);
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_head_is_not_cached_after_invalidation() {
+        let (store, io_tracker) = caching_store(Duration::from_secs(60));
+        let path = Path::from("data/a.lance");
+        store
+            .put(&path, PutPayload::from_static(b"abc"))
+            .await
+            .unwrap();
+
+        // A HEAD starts, the object is overwritten and the HEAD then returns
+        // the metadata it fetched before the write.
+        let generation = store.cache.generation();
+        let stale = store.target.head(&path).await.unwrap();
+        store
+            .put(&path, PutPayload::from_static(b"abcdef"))
+            .await
+            .unwrap();
+        store.cache.insert(
+            &path,
+            generation,
+            CachedMetadata {
+                meta: stale,
+                attributes: Attributes::default(),
+            },
+        );
+
+        assert_eq!(store.head(&path).await.unwrap().size, 6);
+        assert_eq!(io_tracker.stats().metadata_cache_hits, 0);
+    }
+
+    #[tokio::test]
+    async fn test_entries_expire_after_ttl() {
+        let (store, io_tracker) = caching_store(Duration::from_millis(50));
+        let path = Path::from("a");
+        store
+            .put(&path, PutPayload::from_static(b"abc"))
+            .await
+            .unwrap();
+
+        store.head(&path).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        store.head(&path).await.unwrap();
+
+        let stats = io_tracker.stats();
+        assert_eq!(stats.metadata_cache_hits, 0);
+        assert_eq!(stats.metadata_cache_misses, 2);
+    }
+}
@@ -0,0 +1,343 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Routing of metadata requests to a second endpoint.
+//!
+//! Data files are often read through an endpoint that is not strongly
+//! consistent, such as a CDN-accelerated domain in front of the bucket. That
+//! is fine for data files, which are never modified, but a stale manifest can
+//! hide new versions or fail a commit. The `metadata_endpoint` storage option
+//! names a second endpoint of the same bucket, and [`MetadataRoutingStore`]
+//! sends the requests for manifests, transaction files and index metadata
+//! there while everything else keeps using the primary endpoint.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures::StreamExt;
+use futures::stream::{self, BoxStream};
+use object_store::path::Path;
+use object_store::{
+    CopyOptions, GetOptions, GetResult, ListResult, MultipartUpload, ObjectMeta, ObjectStore,
+    PutMultipartOptions, PutOptions, PutPayload, PutResult, RenameOptions, Result as OSResult,
+};
+
+use super::{ObjectStoreParams, StorageOptionsAccessor};
+use lance_core::{Error, Result};
+
+/// Storage option with the endpoint used for manifests, transaction files
+/// and index metadata. Other requests use the provider's endpoint option.
+pub const METADATA_ENDPOINT_KEY: &str = "metadata_endpoint";
+
+/// Deletes are routed in batches of up to this many paths.
+const DELETE_BATCH_SIZE: usize = 1000;
+
+/// Whether `path` is a manifest, transaction file or index metadata file.
+///
+/// These live under `_versions/` and `_transactions/` (of the dataset or of a
+/// branch), and index metadata in `.idx` files under `_indices/`.
+pub fn is_metadata_path(path: &Path) -> bool {
+    let mut in_indices = false;
+    let mut is_idx = false;
+    for part in path.parts() {
+        match part.as_ref() {
+            "_versions" | "_transactions" => return true,
+            "_indices" => in_indices = true,
+            name => is_idx = name.ends_with(".idx"),
+        }
+    }
+    in_indices && is_idx
+}
+
+/// The params of the store serving the metadata endpoint, or `None` when
+/// [`METADATA_ENDPOINT_KEY`] is not set.
+///
+/// `endpoint_keys` are the storage options the provider reads its endpoint
+/// from, see [`super::providers::ObjectStoreProvider::endpoint_keys`]. They
+/// are all replaced by the metadata endpoint, set on the first key.
+pub(crate) fn metadata_endpoint_params(
+    scheme: &str,
+    params: &ObjectStoreParams,
+    endpoint_keys: &[&str],
+) -> Result<Option<ObjectStoreParams>> {
+    let Some(storage_options) = params.storage_options() else {
+        return Ok(None);
+    };
+    let Some(endpoint) = storage_options.get(METADATA_ENDPOINT_KEY) else {
+        return Ok(None);
+    };
+    let Some((endpoint_key, _)) = endpoint_keys.split_first() else {
+        return Err(Error::invalid_input(format!(
+            "Storage option '{METADATA_ENDPOINT_KEY}' is not supported for {scheme} stores"
+        )));
+    };
+
+    let mut options: HashMap<String, String> = storage_options
+        .iter()
+        .filter(|(key, _)| {
+            key.as_str() != METADATA_ENDPOINT_KEY
+                && !endpoint_keys
+                    .iter()
+                    .any(|endpoint_key| key.eq_ignore_ascii_case(endpoint_key))
+        })
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    options.insert(endpoint_key.to_string(), endpoint.clone());
+
+    let accessor = match params.get_accessor().and_then(|a| a.provider().cloned()) {
+        Some(provider) => StorageOptionsAccessor::with_initial_and_provider(options, provider),
+        None => StorageOptionsAccessor::with_static_options(options),
+    };
+    Ok(Some(ObjectStoreParams {
+        storage_options_accessor: Some(Arc::new(accessor)),
+        ..params.clone()
+    }))
+}
+
+/// An [`ObjectStore`] that sends metadata requests to one store and all
+/// other requests to another, see [`is_metadata_path`].
+#[derive(Debug)]
+pub struct MetadataRoutingStore {
+    data: Arc<dyn ObjectStore>,
+    metadata: Arc<dyn ObjectStore>,
+}
+
+impl MetadataRoutingStore {
+    pub fn new(data: Arc<dyn ObjectStore>, metadata: Arc<dyn ObjectStore>) -> Self {
+        Self { data, metadata }
+    }
+
+    fn route(&self, path: &Path) -> &Arc<dyn ObjectStore> {
+        if is_metadata_path(path) {
+            &self.metadata
+        } else {
+            &self.data
+        }
+    }
+
+    /// Listing a prefix under `_versions/` or `_transactions/` goes to the
+    /// metadata endpoint so new manifests are seen immediately.
+    fn route_prefix(&self, prefix: Option<&Path>) -> &Arc<dyn ObjectStore> {
+        match prefix {
+            Some(prefix) => self.route(prefix),
+            None => &self.data,
+        }
+    }
+}
+
+impl Display for MetadataRoutingStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "MetadataRoutingStore(data={}, metadata={})",
+            self.data, self.metadata
+        )
+    }
+}
+
+#[async_trait::async_trait]
+#[deny(clippy::missing_trait_methods)]
+impl ObjectStore for MetadataRoutingStore {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        payload: PutPayload,
+        opts: PutOptions,
+    ) -> OSResult<PutResult> {
+        self.route(location).put_opts(location, payload, opts).await
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        opts: PutMultipartOptions,
+    ) -> OSResult<Box<dyn MultipartUpload>> {
+        self.route(location)
+            .put_multipart_opts(location, opts)
+            .await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> OSResult<GetResult> {
+        self.route(location).get_opts(location, options).await
+    }
+
+    async fn get_ranges(&self, location: &Path, ranges: &[Range<u64>]) -> OSResult<Vec<Bytes>> {
+        self.route(location).get_ranges(location, ranges).await
+    }
+
+    fn delete_stream(
+        &self,
+        locations: BoxStream<'static, OSResult<Path>>,
+    ) -> BoxStream<'static, OSResult<Path>> {
+        let data = self.data.clone();
+        let metadata = self.metadata.clone();
+        locations
+            .ready_chunks(DELETE_BATCH_SIZE)
+            .flat_map(move |batch| {
+                let (metadata_paths, data_paths): (Vec<_>, Vec<_>) = batch
+                    .into_iter()
+                    .partition(|location| matches!(location, Ok(path) if is_metadata_path(path)));
+                metadata
+                    .delete_stream(stream::iter(metadata_paths).boxed())
+                    .chain(data.delete_stream(stream::iter(data_paths).boxed()))
+            })
+            .boxed()
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'static, OSResult<ObjectMeta>> {
+        self.route_prefix(prefix).list(prefix)
+    }
+
+    fn list_with_offset(
+        &self,
+        prefix: Option<&Path>,
+        offset: &Path,
+    ) -> BoxStream<'static, OSResult<ObjectMeta>> {
+        self.route_prefix(prefix).list_with_offset(prefix, offset)
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> OSResult<ListResult> {
+        self.route_prefix(prefix).list_with_delimiter(prefix).await
+    }
+
+    async fn copy_opts(&self, from: &Path, to: &Path, opts: CopyOptions) -> OSResult<()> {
+        self.route(to).copy_opts(from, to, opts).await
+    }
+
+    async fn rename_opts(&self, from: &Path, to: &Path, opts: RenameOptions) -> OSResult<()> {
+        self.route(to).rename_opts(from, to, opts).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::TryStreamExt;
+    use object_store::ObjectStoreExt;
+    use object_store::memory::InMemory;
+
+    use super::*;
+
+    #[test]
+    fn test_is_metadata_path() {
+        for path in [
+            "table.lance/_versions/1.manifest",
+            "table.lance/_transactions/0-uuid.txn",
+            "table.lance/_indices/uuid/index.idx",
+            "table.lance/tree/branch/_versions/2.manifest",
+            "_versions",
+        ] {
+            assert!(is_metadata_path(&Path::from(path)), "{path}");
+        }
+        for path in [
+            "table.lance/data/uuid.lance",
+            "table.lance/_deletions/0-1-2.arrow",
+            "table.lance/_indices/uuid/auxiliary.lance",
+            "table.lance/data/index.idx",
+            "table.lance",
+        ] {
+            assert!(!is_metadata_path(&Path::from(path)), "{path}");
+        }
+    }
+
+    #[test]
+    fn test_metadata_endpoint_params() {
+        let params = |options: &[(&str, &str)]| ObjectStoreParams {
+            storage_options_accessor: Some(Arc::new(StorageOptionsAccessor::with_static_options(
+                options
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            ))),
+            ..Default::default()
+        };
+
+        let without = params(&[("aws_endpoint", "https://cdn")]);
+        assert!(
+            metadata_endpoint_params("s3", &without, &["aws_endpoint", "endpoint"])
+                .unwrap()
+                .is_none()
+        );
+
+        let with = params(&[
+            ("endpoint", "https://cdn"),
+            ("metadata_endpoint", "https://origin"),
+            ("region", "us-east-1"),
+        ]);
+        let metadata = metadata_endpoint_params("s3", &with, &["aws_endpoint", "endpoint"])
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            metadata.storage_options().unwrap(),
+            &HashMap::from([
+                ("aws_endpoint".to_string(), "https://origin".to_string()),
+                ("region".to_string(), "us-east-1".to_string()),
+            ])
+        );
+
+        let err = metadata_endpoint_params("memory", &with, &[]).unwrap_err();
+        assert!(err.to_string().contains(METADATA_ENDPOINT_KEY), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_routing_by_path() {
+        let data = Arc::new(InMemory::new());
+        let metadata = Arc::new(InMemory::new());
+        let store = MetadataRoutingStore::new(data.clone(), metadata.clone());
+
+        let manifest = Path::from("t.lance/_versions/1.manifest");
+        let transaction = Path::from("t.lance/_transactions/0-a.txn");
+        let index = Path::from("t.lance/_indices/a/index.idx");
+        let data_file = Path::from("t.lance/data/a.lance");
+        let aux_file = Path::from("t.lance/_indices/a/auxiliary.lance");
+        for path in [&manifest, &transaction, &index, &data_file, &aux_file] {
+            store
+                .put(path, PutPayload::from_static(b"x"))
+                .await
+                .unwrap();
+        }
+
+        let paths = |store: Arc<InMemory>| async move {
+            let mut paths = store
+                .list(None)
+                .map_ok(|meta| meta.location)
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap();
+            paths.sort();
+            paths
+        };
+        assert_eq!(
+            paths(metadata.clone()).await,
+            vec![index.clone(), transaction.clone(), manifest.clone()]
+        );
+        assert_eq!(
+            paths(data.clone()).await,
+            vec![aux_file.clone(), data_file.clone()]
+        );
+
+        // Reads and listings of metadata are served by the metadata store.
+        assert_eq!(
+            store.get(&manifest).await.unwrap().bytes().await.unwrap(),
+            "x"
+        );
+        assert_eq!(store.head(&data_file).await.unwrap().size, 1);
+        let versions = store
+            .list(Some(&Path::from("t.lance/_versions")))
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(versions.len(), 1);
+
+        let deleted = store
+            .delete_stream(stream::iter([Ok(manifest.clone()), Ok(data_file.clone())]).boxed())
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(deleted.len(), 2);
+        assert!(metadata.head(&manifest).await.is_err());
+        assert!(data.head(&data_file).await.is_err());
+    }
+}
@@ -0,0 +1,860 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Retries of the individual parts of multipart uploads.
+//!
+//! A [`MultipartUpload`] numbers its parts in the order they are submitted, so
+//! a part that failed cannot be submitted again: it would be uploaded as a new
+//! part at the end of the object, and the failed number would be missing when
+//! the upload is completed. A transient failure of one part therefore used to
+//! fail the whole upload.
+//!
+//! [`PartRetryStore`] uploads parts through the [`MultipartStore`] API of the
+//! builtin stores instead, which takes the part number explicitly, and retries
+//! a part that fails transiently under the same number, with backoff. The
+//! upload is only failed once a part has run out of retries.
+//!
+//! The number of retries of each part is set with the
+//! [`PART_UPLOAD_RETRIES_KEY`](super::PART_UPLOAD_RETRIES_KEY) storage option,
+//! independently of the retries of the object store client, which also apply
+//! to reads. 0 turns them off. A part is retried if the store's
+//! [`ObjectStoreParams::is_retryable`](super::ObjectStoreParams::is_retryable)
+//! classifier says so, or, without one, if [`is_transient_part_error`] does.
+//!
+//! The stores built on OpenDAL do not implement [`MultipartStore`], their
+//! operators are given a [`PartRetryLayer`] instead.
+
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use lance_core::utils::backoff::Backoff;
+use object_store::multipart::{MultipartStore, PartId};
+use object_store::path::Path;
+use object_store::{
+    CopyOptions, GetOptions, GetResult, ListResult, MultipartId, MultipartUpload, ObjectMeta,
+    ObjectStore, PutMultipartOptions, PutOptions, PutPayload, PutResult, RenameOptions,
+    Result as OSResult, UploadPart,
+};
+
+use crate::deadline;
+use crate::object_store::classification::{RetryClassifier, classify, retry_delay, should_retry};
+
+/// Whether a failed part upload is worth retrying.
+///
+/// Besides the errors classified as retryable, S3 reports a connection that
+/// was idle for too long as a 400 `RequestTimeout`, and a reset connection is
+/// only visible in the message.
+pub(crate) fn is_transient_part_error(err: &object_store::Error) -> bool {
+    if classify(err).retryable {
+        return true;
+    }
+    let object_store::Error::Generic { source, .. } = err else {
+        return false;
+    };
+    let message = source.to_string().to_ascii_lowercase();
+    message.contains("connection reset by peer") || message.contains("requesttimeout")
+}
+
+/// An [`ObjectStore`] wrapper that retries failed parts of multipart uploads,
+/// see the [module documentation](self).
+#[derive(Debug)]
+pub struct PartRetryStore<S> {
+    target: Arc<S>,
+    max_retries: usize,
+    retry_classifier: Option<RetryClassifier>,
+}
+
+impl<S: ObjectStore + MultipartStore> PartRetryStore<S> {
+    pub fn new(target: S, max_retries: usize) -> Self {
+        Self {
+            target: Arc::new(target),
+            max_retries,
+            retry_classifier: None,
+        }
+    }
+
+    /// Decide which failed parts are retried with `classifier` instead of
+    /// [`is_transient_part_error`].
+    pub fn with_retry_classifier(mut self, classifier: Option<RetryClassifier>) -> Self {
+        self.retry_classifier = classifier;
+        self
+    }
+}
+
+impl<S: Display> Display for PartRetryStore<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PartRetryStore({})", self.target)
+    }
+}
+
+#[async_trait::async_trait]
+#[deny(clippy::missing_trait_methods)]
+impl<S: ObjectStore + MultipartStore> ObjectStore for PartRetryStore<S> {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        payload: PutPayload,
+        opts: PutOptions,
+    ) -> OSResult<PutResult> {
+        self.target.put_opts(location, payload, opts).await
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        opts: PutMultipartOptions,
+    ) -> OSResult<Box<dyn MultipartUpload>> {
+        // Creating an upload through the `MultipartStore` API cannot set tags
+        // or attributes.
+        if self.max_retries == 0 || opts != PutMultipartOptions::default() {
+            return self.target.put_multipart_opts(location, opts).await;
+        }
+        let id = self.target.create_multipart(location).await?;
+        Ok(Box::new(PartRetryUpload {
+            store: self.target.clone(),
+            location: location.clone(),
+            id,
+            next_part_idx: 0,
+            parts: Default::default(),
+            max_retries: self.max_retries,
+            retry_classifier: self.retry_classifier.clone(),
+        }))
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> OSResult<GetResult> {
+        self.target.get_opts(location, options).await
+    }
+
+    async fn get_ranges(&self, location: &Path, ranges: &[Range<u64>]) -> OSResult<Vec<Bytes>> {
+        self.target.get_ranges(location, ranges).await
+    }
+
+    fn delete_stream(
+        &self,
+        locations: BoxStream<'static, OSResult<Path>>,
+    ) -> BoxStream<'static, OSResult<Path>> {
+        self.target.delete_stream(locations)
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'static, OSResult<ObjectMeta>> {
+        self.target.list(prefix)
+    }
+
+    fn list_with_offset(
+        &self,
+        prefix: Option<&Path>,
+        offset: &Path,
+    ) -> BoxStream<'static, OSResult<ObjectMeta>> {
+        self.target.list_with_offset(prefix, offset)
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> OSResult<ListResult> {
+        self.target.list_with_delimiter(prefix).await
+    }
+
+    async fn copy_opts(&self, from: &Path, to: &Path, opts: CopyOptions) -> OSResult<()> {
+        self.target.copy_opts(from, to, opts).await
+    }
+
+    async fn rename_opts(&self, from: &Path, to: &Path, opts: RenameOptions) -> OSResult<()> {
+        self.target.rename_opts(from, to, opts).await
+    }
+}
+
+/// The backoff between the attempts of a part: from 500ms, capped at 30s.
+fn part_backoff() -> Backoff {
+    Backoff::default()
+        .with_unit(500)
+        .with_jitter(250)
+        .with_max(30_000)
+}
+
+#[derive(Debug)]
+struct PartRetryUpload<S> {
+    store: Arc<S>,
+    location: Path,
+    id: MultipartId,
+    next_part_idx: usize,
+    parts: Arc<Mutex<Vec<Option<PartId>>>>,
+    max_retries: usize,
+    retry_classifier: Option<RetryClassifier>,
+}
+
+#[async_trait::async_trait]
+impl<S: ObjectStore + MultipartStore> MultipartUpload for PartRetryUpload<S> {
+    fn put_part(&mut self, data: PutPayload) -> UploadPart {
+        let part_idx = self.next_part_idx;
+        self.next_part_idx += 1;
+        let store = self.store.clone();
+        let location = self.location.clone();
+        let id = self.id.clone();
+        let parts = self.parts.clone();
+        let max_retries = self.max_retries;
+        let retry_classifier = self.retry_classifier.clone();
+        Box::pin(async move {
+            let mut backoff = part_backoff();
+            let mut attempt = 0;
+            let part = loop {
+                attempt += 1;
+                match store.put_part(&location, &id, part_idx, data.clone()).await {
+                    Ok(part) => break part,
+                    Err(err)
+                        if attempt <= max_retries
+                            && should_retry(
+                                retry_classifier.as_ref(),
+                                &err,
+                                is_transient_part_error,
+                            ) =>
+                    {
+                        let Some(delay) = retry_delay(&err, backoff.next_backoff())
+                            .filter(|delay| deadline::can_retry_after(*delay))
+                        else {
+                            return Err(err);
+                        };
+                        log::debug!(
+                            "Retrying part {} of the upload to {} in {:?}: {}",
+                            part_idx,
+                            location,
+                            delay,
+                            err
+                        );
+                        tokio::time::sleep(delay).await;
+                    }
+                    Err(err) => return Err(err),
+                }
+            };
+            let mut parts = parts.lock().unwrap();
+            if parts.len() <= part_idx {
+                parts.resize(part_idx + 1, None);
+            }
+            parts[part_idx] = Some(part);
+            Ok(())
+        })
+    }
+
+    async fn complete(&mut self) -> OSResult<PutResult> {
+        let parts = {
+            let mut parts = std::mem::take(&mut *self.parts.lock().unwrap());
+            parts.resize(self.next_part_idx, None);
+            parts
+                .into_iter()
+                .enumerate()
+                .map(|(part_idx, part)| {
+                    part.ok_or_else(|| object_store::Error::Generic {
+                        store: "PartRetryStore",
+                        source: format!(
+                            "Part {part_idx} of the upload to {} was not uploaded",
+                            self.location
+                        )
+                        .into(),
+                    })
+                })
+                .collect::<OSResult<Vec<_>>>()?
+        };
+        self.store
+            .complete_multipart(&self.location, &self.id, parts)
+            .await
+    }
+
+    async fn abort(&mut self) -> OSResult<()> {
+        self.store.abort_multipart(&self.location, &self.id).await
+    }
+}
+
+#[cfg(any(
+    feature = "aws",
+    feature = "azure",
+    feature = "gcp",
+    feature = "oss",
+    feature = "tencent",
+    feature = "huggingface"
+))]
+pub use opendal_layer::PartRetryLayer;
+
+#[cfg(any(
+    feature = "aws",
+    feature = "azure",
+    feature = "gcp",
+    feature = "oss",
+    feature = "tencent",
+    feature = "huggingface"
+))]
+mod opendal_layer {
+    use std::time::Duration;
+
+    use lance_core::utils::backoff::Backoff;
+    use opendal::raw::{
+        Access, Layer, LayeredAccess, OpCopier, OpCopy, OpList, OpRead, OpWrite, RpCopy, RpDelete,
+        RpList, RpRead, RpWrite, oio,
+    };
+    use opendal::{Buffer, Metadata};
+
+    use crate::deadline;
+    use crate::object_store::classification::{RetryClassifier, retry_delay, should_retry};
+    use crate::object_store::{ObjectStoreParams, StorageOptions};
+
+    /// An OpenDAL layer that retries the failed parts of multipart uploads,
+    /// the counterpart of [`PartRetryStore`](super::PartRetryStore) for the
+    /// stores built on OpenDAL.
+    ///
+    /// OpenDAL keeps a part that failed with a temporary error, under its
+    /// number, and reports the failure from the next `write` or `close` of
+    /// the writer, which upload it again. This layer retries those calls with
+    /// backoff. Persistent errors fail the writer in OpenDAL, so the store's
+    /// retry classifier can only narrow the temporary errors that are retried.
+    /// Other operations are passed through.
+    #[derive(Debug, Clone)]
+    pub struct PartRetryLayer {
+        max_retries: usize,
+        retry_classifier: Option<RetryClassifier>,
+    }
+
+    impl PartRetryLayer {
+        pub fn new(max_retries: usize) -> Self {
+            Self {
+                max_retries,
+                retry_classifier: None,
+            }
+        }
+
+        /// Decide which temporary errors are retried with `classifier`.
+        ///
+        /// The classifier is given the error as an
+        /// [`OpendalStore`](object_store_opendal::OpendalStore) reports it.
+        pub fn with_retry_classifier(mut self, classifier: Option<RetryClassifier>) -> Self {
+            self.retry_classifier = classifier;
+            self
+        }
+
+        /// The layer for a store built with `params`: retries as set by
+        /// [`PART_UPLOAD_RETRIES_KEY`](crate::object_store::PART_UPLOAD_RETRIES_KEY)
+        /// and the store's retry classifier.
+        pub fn from_params(params: &ObjectStoreParams) -> Self {
+            let storage_options =
+                StorageOptions(params.storage_options().cloned().unwrap_or_default());
+            Self::new(storage_options.part_upload_retries())
+                .with_retry_classifier(params.is_retryable.clone())
+        }
+    }
+
+    impl<A: Access> Layer<A> for PartRetryLayer {
+        type LayeredAccess = PartRetryAccessor<A>;
+
+        fn layer(&self, inner: A) -> Self::LayeredAccess {
+            PartRetryAccessor {
+                inner,
+                layer: self.clone(),
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    pub struct PartRetryAccessor<A> {
+        inner: A,
+        layer: PartRetryLayer,
+    }
+
+    impl<A: Access> LayeredAccess for PartRetryAccessor<A> {
+        type Inner = A;
+        type Reader = A::Reader;
+        type Writer = PartRetryWriter<A::Writer>;
+        type Lister = A::Lister;
+        type Deleter = A::Deleter;
+        type Copier = A::Copier;
+
+        fn inner(&self) -> &Self::Inner {
+            &self.inner
+        }
+
+        async fn read(&self, path: &str, args: OpRead) -> opendal::Result<(RpRead, Self::Reader)> {
+            self.inner.read(path, args).await
+        }
+
+        async fn write(
+            &self,
+            path: &str,
+            args: OpWrite,
+        ) -> opendal::Result<(RpWrite, Self::Writer)> {
+            let (rp, writer) = self.inner.write(path, args).await?;
+            Ok((rp, PartRetryWriter::new(writer, path, self.layer.clone())))
+        }
+
+        async fn copy(
+            &self,
+            from: &str,
+            to: &str,
+            args: OpCopy,
+            opts: OpCopier,
+        ) -> opendal::Result<(RpCopy, Self::Copier)> {
+            self.inner.copy(from, to, args, opts).await
+        }
+
+        async fn delete(&self) -> opendal::Result<(RpDelete, Self::Deleter)> {
+            self.inner.delete().await
+        }
+
+        async fn list(&self, path: &str, args: OpList) -> opendal::Result<(RpList, Self::Lister)> {
+            self.inner.list(path, args).await
+        }
+    }
+
+    pub struct PartRetryWriter<W> {
+        inner: W,
+        path: String,
+        layer: PartRetryLayer,
+    }
+
+    impl<W> PartRetryWriter<W> {
+        pub(super) fn new(inner: W, path: &str, layer: PartRetryLayer) -> Self {
+            Self {
+                inner,
+                path: path.to_string(),
+                layer,
+            }
+        }
+
+        /// The delay before retrying `err`, or `err` back if it is not retried.
+        fn retry_delay(
+            &self,
+            err: opendal::Error,
+            attempt: usize,
+            backoff: &mut Backoff,
+        ) -> std::result::Result<Duration, opendal::Error> {
+            if attempt > self.layer.max_retries || !err.is_temporary() {
+                return Err(err);
+            }
+            let err = object_store::Error::Generic {
+                store: "OpenDAL",
+                source: Box::new(err),
+            };
+            let delay = should_retry(self.layer.retry_classifier.as_ref(), &err, |_| true)
+                .then(|| retry_delay(&err, backoff.next_backoff()))
+                .flatten()
+                .filter(|delay| deadline::can_retry_after(*delay));
+            match (delay, err) {
+                (Some(delay), _) => Ok(delay),
+                (None, object_store::Error::Generic { source, .. }) => Err(*source
+                    .downcast::<opendal::Error>()
+                    .expect("the source is the OpenDAL error")),
+                (None, _) => unreachable!("the error is built as generic"),
+            }
+        }
+    }
+
+    impl<W: oio::Write> oio::Write for PartRetryWriter<W> {
+        async fn write(&mut self, bs: Buffer) -> opendal::Result<()> {
+            let mut backoff = super::part_backoff();
+            let mut attempt = 0;
+            loop {
+                attempt += 1;
+                let err = match self.inner.write(bs.clone()).await {
+                    Ok(()) => return Ok(()),
+                    Err(err) => err,
+                };
+                let delay = self.retry_delay(err, attempt, &mut backoff)?;
+                log::debug!(
+                    "Retrying a part of the upload to {} in {:?}",
+                    self.path,
+                    delay
+                );
+                tokio::time::sleep(delay).await;
+            }
+        }
+
+        async fn close(&mut self) -> opendal::Result<Metadata> {
+            let mut backoff = super::part_backoff();
+            let mut attempt = 0;
+            loop {
+                attempt += 1;
+                let err = match self.inner.close().await {
+                    Ok(metadata) => return Ok(metadata),
+                    Err(err) => err,
+                };
+                let delay = self.retry_delay(err, attempt, &mut backoff)?;
+                log::debug!(
+                    "Retrying the last parts of the upload to {} in {:?}",
+                    self.path,
+                    delay
+                );
+                tokio::time::sleep(delay).await;
+            }
+        }
+
+        async fn abort(&mut self) -> opendal::Result<()> {
+            self.inner.abort().await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use object_store::ObjectStoreExt;
+    use object_store::memory::InMemory;
+    use tokio::io::AsyncWriteExt;
+    use url::Url;
+
+    use super::*;
+    use crate::object_store::ObjectStore as LanceObjectStore;
+    use crate::object_writer::ObjectWriter;
+
+    fn connection_reset() -> object_store::Error {
+        object_store::Error::Generic {
+            store: "S3",
+            source: Box::new(std::io::Error::new(
+                std::io::ErrorKind::ConnectionReset,
+                "connection reset by peer",
+            )),
+        }
+    }
+
+    /// An in-memory store whose part uploads fail with a connection reset,
+    /// as many times per part as set in `failures`. Parts are staged here,
+    /// since `InMemory` only accepts them in order.
+    #[derive(Debug, Default)]
+    struct FlakyPartStore {
+        target: InMemory,
+        uploads: Mutex<HashMap<MultipartId, Vec<Option<PutPayload>>>>,
+        failures: Mutex<HashMap<usize, usize>>,
+        created: AtomicUsize,
+        parts_put: AtomicUsize,
+        aborted: AtomicUsize,
+    }
+
+    impl FlakyPartStore {
+        fn new(failures: impl IntoIterator<Item = (usize, usize)>) -> Self {
+            Self {
+                failures: Mutex::new(failures.into_iter().collect()),
+                ..Default::default()
+            }
+        }
+    }
+
+    impl Display for FlakyPartStore {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "FlakyPartStore")
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ObjectStore for FlakyPartStore {
+        async fn put_opts(
+            &self,
+            location: &Path,
+            payload: PutPayload,
+            opts: PutOptions,
+        ) -> OSResult<PutResult> {
+            self.target.put_opts(location, payload, opts).await
+        }
+
+        async fn put_multipart_opts(
+            &self,
+            location: &Path,
+            opts: PutMultipartOptions,
+        ) -> OSResult<Box<dyn MultipartUpload>> {
+            self.target.put_multipart_opts(location, opts).await
+        }
+
+        async fn get_opts(&self, location: &Path, options: GetOptions) -> OSResult<GetResult> {
+            self.target.get_opts(location, options).await
+        }
+
+        fn delete_stream(
+            &self,
+            locations: BoxStream<'static, OSResult<Path>>,
+        ) -> BoxStream<'static, OSResult<Path>> {
+            self.target.delete_stream(locations)
+        }
+
+        fn list(&self, prefix: Option<&Path>) -> BoxStream<'static, OSResult<ObjectMeta>> {
+            self.target.list(prefix)
+        }
+
+        async fn list_with_delimiter(&self, prefix: Option<&Path>) -> OSResult<ListResult> {
+            self.target.list_with_delimiter(prefix).await
+        }
+
+        async fn copy_opts(&self, from: &Path, to: &Path, opts: CopyOptions) -> OSResult<()> {
+            self.target.copy_opts(from, to, opts).await
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl MultipartStore for FlakyPartStore {
+        async fn create_multipart(&self, _path: &Path) -> OSResult<MultipartId> {
+            let id = self.created.fetch_add(1, Ordering::SeqCst).to_string();
+            self.uploads.lock().unwrap().insert(id.clone(), Vec::new());
+            Ok(id)
+        }
+
+        async fn put_part(
+            &self,
+            _path: &Path,
+            id: &MultipartId,
+            part_idx: usize,
+            data: PutPayload,
+        ) -> OSResult<PartId> {
+            self.parts_put.fetch_add(1, Ordering::SeqCst);
+            if let Some(failures) = self.failures.lock().unwrap().get_mut(&part_idx)
+                && *failures > 0
+            {
+                *failures -= 1;
+                return Err(connection_reset());
+            }
+            let mut uploads = self.uploads.lock().unwrap();
+            let parts = uploads.get_mut(id).unwrap();
+            if parts.len() <= part_idx {
+                parts.resize(part_idx + 1, None);
+            }
+            parts[part_idx] = Some(data);
+            Ok(PartId {
+                content_id: part_idx.to_string(),
+            })
+        }
+
+        async fn complete_multipart(
+            &self,
+            path: &Path,
+            id: &MultipartId,
+            _parts: Vec<PartId>,
+        ) -> OSResult<PutResult> {
+            let parts = self.uploads.lock().unwrap().remove(id).unwrap();
+            let payload = parts
+                .into_iter()
+                .flat_map(|part| part.unwrap().into_iter())
+                .collect::<PutPayload>();
+            self.target.put(path, payload).await
+        }
+
+        async fn abort_multipart(&self, _path: &Path, id: &MultipartId) -> OSResult<()> {
+            self.aborted.fetch_add(1, Ordering::SeqCst);
+            self.uploads.lock().unwrap().remove(id);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_is_transient_part_error() {
+        let request_timeout = object_store::Error::Generic {
+            store: "S3",
+            source: Box::new(std::io::Error::other(
+                "Server returned non-2xx status code: 400 Bad Request: \
+                 <Error><Code>RequestTimeout</Code><Message>Your socket connection to the server \
+                 was not read from or written to within the timeout period. Idle connections will \
+                 be closed.</Message></Error>",
+            )),
+        };
+        assert!(is_transient_part_error(&request_timeout));
+        assert!(is_transient_part_error(&connection_reset()));
+
+        let not_retryable = object_store::Error::Generic {
+            store: "S3",
+            source: Box::new(std::io::Error::other("access denied")),
+        };
+        assert!(!is_transient_part_error(&not_retryable));
+    }
+
+    #[tokio::test]
+    async fn test_failed_part_is_retried_in_place() {
+        let store = PartRetryStore::new(FlakyPartStore::new([(1, 2)]), 2);
+        let path = Path::from("object");
+
+        let mut upload = store.put_multipart(&path).await.unwrap();
+        let uploads = (0..3u8)
+            .map(|i| upload.put_part(vec![i; 4].into()))
+            .collect::<Vec<_>>();
+        for result in futures::future::join_all(uploads).await {
+            result.unwrap();
+        }
+        upload.complete().await.unwrap();
+
+        let data = store.get(&path).await.unwrap().bytes().await.unwrap();
+        assert_eq!(data.as_ref(), [0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2]);
+        assert_eq!(store.target.created.load(Ordering::SeqCst), 1);
+        assert_eq!(store.target.parts_put.load(Ordering::SeqCst), 5);
+    }
+
+    #[tokio::test]
+    async fn test_retry_classifier_decides_part_retries() {
+        let classifier: RetryClassifier = Arc::new(|_: &object_store::Error| false);
+        let store = PartRetryStore::new(FlakyPartStore::new([(0, 1)]), 3)
+            .with_retry_classifier(Some(classifier));
+        let path = Path::from("object");
+
+        let mut upload = store.put_multipart(&path).await.unwrap();
+        upload.put_part(vec![0; 4].into()).await.unwrap_err();
+        assert_eq!(store.target.parts_put.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_part_out_of_retries() {
+        let store = PartRetryStore::new(FlakyPartStore::new([(0, 2)]), 1);
+        let path = Path::from("object");
+
+        let mut upload = store.put_multipart(&path).await.unwrap();
+        let err = upload.put_part(vec![0; 4].into()).await.unwrap_err();
+        assert!(err.to_string().contains("connection reset"), "{err}");
+        assert_eq!(store.target.parts_put.load(Ordering::SeqCst), 2);
+
+        // The part that failed is never completed as missing data.
+        let err = upload.complete().await.unwrap_err();
+        assert!(err.to_string().contains("Part 0"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_writer_recovers_from_part_failure() {
+        let inner = Arc::new(PartRetryStore::new(FlakyPartStore::new([(1, 1)]), 3));
+        let store = LanceObjectStore::new(
+            inner.clone(),
+            Url::parse("memory:///").unwrap(),
+            None,
+            None,
+            false,
+            true,
+            8,
+            3,
+            None,
+        );
+        let path = Path::from("object");
+
+        // Large enough for several parts.
+        let data = (0..16 * 1024 * 1024)
+            .map(|i| (i % 251) as u8)
+            .collect::<Vec<_>>();
+        let mut writer = ObjectWriter::new(&store, &path).await.unwrap();
+        writer.write_all(&data).await.unwrap();
+        writer.shutdown().await.unwrap();
+
+        let written = inner.get(&path).await.unwrap().bytes().await.unwrap();
+        assert_eq!(written.as_ref(), data.as_slice());
+        assert_eq!(inner.target.created.load(Ordering::SeqCst), 1);
+        assert_eq!(inner.target.aborted.load(Ordering::SeqCst), 0);
+        assert!(inner.target.parts_put.load(Ordering::SeqCst) > 3);
+    }
+
+    #[cfg(any(
+        feature = "aws",
+        feature = "azure",
+        feature = "gcp",
+        feature = "oss",
+        feature = "tencent",
+        feature = "huggingface"
+    ))]
+    mod opendal_writer {
+        use opendal::raw::oio::{self, Write as _};
+
+        use super::*;
+
+        /// An OpenDAL writer whose writes and closes fail with `failures`, in
+        /// order, before they succeed.
+        #[derive(Clone)]
+        struct FlakyWriter {
+            failures: Arc<Mutex<Vec<opendal::Error>>>,
+            attempts: Arc<AtomicUsize>,
+        }
+
+        impl FlakyWriter {
+            fn new(failures: impl IntoIterator<Item = opendal::Error>) -> Self {
+                Self {
+                    failures: Arc::new(Mutex::new(failures.into_iter().collect())),
+                    attempts: Default::default(),
+                }
+            }
+
+            fn attempt(&self) -> opendal::Result<()> {
+                self.attempts.fetch_add(1, Ordering::SeqCst);
+                let mut failures = self.failures.lock().unwrap();
+                if failures.is_empty() {
+                    Ok(())
+                } else {
+                    Err(failures.remove(0))
+                }
+            }
+
+            fn attempts(&self) -> usize {
+                self.attempts.load(Ordering::SeqCst)
+            }
+        }
+
+        impl oio::Write for FlakyWriter {
+            async fn write(&mut self, _: opendal::Buffer) -> opendal::Result<()> {
+                self.attempt()
+            }
+
+            async fn close(&mut self) -> opendal::Result<opendal::Metadata> {
+                self.attempt()?;
+                Ok(opendal::Metadata::new(opendal::EntryMode::FILE))
+            }
+
+            async fn abort(&mut self) -> opendal::Result<()> {
+                Ok(())
+            }
+        }
+
+        fn temporary_error() -> opendal::Error {
+            opendal::Error::new(opendal::ErrorKind::Unexpected, "connection reset by peer")
+                .set_temporary()
+        }
+
+        #[tokio::test]
+        async fn test_opendal_part_is_retried() {
+            let flaky = FlakyWriter::new([temporary_error(), temporary_error()]);
+            let mut writer = opendal_layer::PartRetryWriter::new(
+                flaky.clone(),
+                "object",
+                PartRetryLayer::new(2),
+            );
+            writer.write(vec![0; 4].into()).await.unwrap();
+            assert_eq!(flaky.attempts(), 3);
+
+            // The last part is uploaded when the writer is closed.
+            flaky.failures.lock().unwrap().push(temporary_error());
+            writer.close().await.unwrap();
+            assert_eq!(flaky.attempts(), 5);
+        }
+
+        #[tokio::test]
+        async fn test_opendal_part_is_not_retried() {
+            // Out of retries
+            let flaky = FlakyWriter::new([temporary_error(), temporary_error()]);
+            let mut writer = opendal_layer::PartRetryWriter::new(
+                flaky.clone(),
+                "object",
+                PartRetryLayer::new(1),
+            );
+            writer.write(vec![0; 4].into()).await.unwrap_err();
+            assert_eq!(flaky.attempts(), 2);
+
+            // OpenDAL fails the writer on persistent errors.
+            let flaky = FlakyWriter::new([opendal::Error::new(
+                opendal::ErrorKind::PermissionDenied,
+                "access denied",
+            )]);
+            let mut writer = opendal_layer::PartRetryWriter::new(
+                flaky.clone(),
+                "object",
+                PartRetryLayer::new(2),
+            );
+            let err = writer.write(vec![0; 4].into()).await.unwrap_err();
+            assert_eq!(err.kind(), opendal::ErrorKind::PermissionDenied);
+            assert_eq!(flaky.attempts(), 1);
+
+            // The retry classifier of the store declines.
+            let classifier: RetryClassifier = Arc::new(|_: &object_store::Error| false);
+            let flaky = FlakyWriter::new([temporary_error()]);
+            let mut writer = opendal_layer::PartRetryWriter::new(
+                flaky.clone(),
+                "object",
+                PartRetryLayer::new(2).with_retry_classifier(Some(classifier)),
+            );
+            let err = writer.write(vec![0; 4].into()).await.unwrap_err();
+            assert!(err.is_temporary(), "{err}");
+            assert_eq!(flaky.attempts(), 1);
+        }
+    }
+}
@@ -0,0 +1,522 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Request priorities for the requests of a store.
+//!
+//! Interactive reads and bulk traffic such as compaction often share one
+//! store. With the `storage_max_concurrent_requests` storage option set, every
+//! request takes a permit from a limiter shared by all handles of the store,
+//! and queued requests are granted permits highest [`Priority`] first, in
+//! arrival order within a priority. A handle issuing requests at another
+//! priority is made with [`ObjectStore::with_priority`](crate::object_store::ObjectStore::with_priority).
+//!
+//! Permits are held for the whole request, including the consumption of a
+//! GET's body. Lists and deletes stream their results and are not limited,
+//! multipart uploads are bounded by `storage_max_concurrent_uploads` instead.
+//! The time requests spent waiting for a permit is reported per priority in
+//! [`IoStats::request_wait`](crate::utils::tracking_store::IoStats::request_wait).
+//!
+//! Priorities are strict: low priority requests only run while no higher
+//! priority request is waiting.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use futures::StreamExt;
+use futures::stream::BoxStream;
+use object_store::path::Path;
+use object_store::{
+    CopyOptions, GetOptions, GetResult, GetResultPayload, ListResult, MultipartUpload, ObjectMeta,
+    ObjectStore, PutMultipartOptions, PutOptions, PutPayload, PutResult, RenameOptions,
+    Result as OSResult,
+};
+use tokio::sync::oneshot;
+
+use crate::utils::tracking_store::IOTracker;
+use lance_core::{Error, Result};
+
+/// Storage option for the number of requests that may be in flight at once
+/// across all priorities. `0` (the default) disables the limit, and with it
+/// priorities.
+pub const MAX_CONCURRENT_REQUESTS_KEY: &str = "storage_max_concurrent_requests";
+
+/// The priority class of the requests made through a store handle.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Priority {
+    /// Latency sensitive requests, e.g. point lookups serving a query
+    High,
+    #[default]
+    Normal,
+    /// Bulk traffic that can wait, e.g. compaction or index builds
+    Low,
+}
+
+impl Priority {
+    const ALL: [Self; 3] = [Self::High, Self::Normal, Self::Low];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::High => "high",
+            Self::Normal => "normal",
+            Self::Low => "low",
+        }
+    }
+}
+
+impl Display for Priority {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Time spent waiting for a request permit, per [`Priority`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PriorityWaits {
+    pub high: Duration,
+    pub normal: Duration,
+    pub low: Duration,
+}
+
+impl PriorityWaits {
+    pub fn get(&self, priority: Priority) -> Duration {
+        match priority {
+            Priority::High => self.high,
+            Priority::Normal => self.normal,
+            Priority::Low => self.low,
+        }
+    }
+
+    pub(crate) fn add(&mut self, priority: Priority, wait: Duration) {
+        match priority {
+            Priority::High => self.high += wait,
+            Priority::Normal => self.normal += wait,
+            Priority::Low => self.low += wait,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct LimiterState {
+    available: usize,
+    /// Waiters per priority, indexed by [`Priority::index`]
+    queues: [VecDeque<oneshot::Sender<RequestPermit>>; 3],
+}
+
+#[derive(Debug)]
+struct LimiterInner {
+    capacity: usize,
+    state: Mutex<LimiterState>,
+}
+
+/// Shared request permits of a store, see [`MAX_CONCURRENT_REQUESTS_KEY`].
+///
+/// Clones share the same permits.
+#[derive(Debug, Clone)]
+pub struct RequestLimiter {
+    inner: Arc<LimiterInner>,
+    io_tracker: IOTracker,
+}
+
+/// A request permit, handed to the next waiter when dropped.
+#[derive(Debug)]
+pub struct RequestPermit {
+    limiter: Option<Arc<LimiterInner>>,
+}
+
+impl RequestPermit {
+    /// Take the permit back without releasing it, for a handoff that failed
+    /// while the limiter's lock is held.
+    fn disarm(mut self) {
+        self.limiter = None;
+    }
+}
+
+impl Drop for RequestPermit {
+    fn drop(&mut self) {
+        if let Some(limiter) = self.limiter.take() {
+            LimiterInner::release(&limiter);
+        }
+    }
+}
+
+impl LimiterInner {
+    fn release(this: &Arc<Self>) {
+        let mut state = this.state.lock().unwrap();
+        for priority in Priority::ALL {
+            while let Some(waiter) = state.queues[priority.index()].pop_front() {
+                let permit = RequestPermit {
+                    limiter: Some(this.clone()),
+                };
+                // A waiter whose request was dropped no longer needs it. If
+                // the receiver is dropped after the handoff, the permit is
+                // dropped with it and released again.
+                match waiter.send(permit) {
+                    Ok(()) => return,
+                    Err(permit) => permit.disarm(),
+                }
+            }
+        }
+        state.available += 1;
+    }
+}
+
+impl RequestLimiter {
+    pub fn new(capacity: usize, io_tracker: IOTracker) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            inner: Arc::new(LimiterInner {
+                capacity,
+                state: Mutex::new(LimiterState {
+                    available: capacity,
+                    queues: Default::default(),
+                }),
+            }),
+            io_tracker,
+        }
+    }
+
+    /// Build the limiter configured by [`MAX_CONCURRENT_REQUESTS_KEY`], or
+    /// `None` when requests are not limited.
+    pub fn from_storage_options(
+        storage_options: Option<&HashMap<String, String>>,
+        io_tracker: IOTracker,
+    ) -> Result<Option<Self>> {
+        let capacity = storage_options
+            .and_then(|opts| opts.get(MAX_CONCURRENT_REQUESTS_KEY))
+            .map(|val| {
+                val.parse::<usize>().map_err(|_| {
+                    Error::invalid_input(format!(
+                        "Invalid value for storage option '{MAX_CONCURRENT_REQUESTS_KEY}': '{val}'"
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(0);
+        Ok((capacity > 0).then(|| Self::new(capacity, io_tracker)))
+    }
+
+    /// Requests currently holding a permit.
+    pub fn in_flight(&self) -> usize {
+        self.inner.capacity - self.inner.state.lock().unwrap().available
+    }
+
+    /// Requests currently waiting for a permit at `priority`.
+    pub fn waiting(&self, priority: Priority) -> usize {
+        self.inner.state.lock().unwrap().queues[priority.index()]
+            .iter()
+            .filter(|waiter| !waiter.is_closed())
+            .count()
+    }
+
+    /// Wait for a permit, behind every queued request of the same or a
+    /// higher priority.
+    pub async fn acquire(&self, priority: Priority) -> OSResult<RequestPermit> {
+        let start = Instant::now();
+        let receiver = {
+            let mut state = self.inner.state.lock().unwrap();
+            if state.available > 0 {
+                state.available -= 1;
+                drop(state);
+                self.io_tracker
+                    .record_request_wait(priority, start.elapsed());
+                return Ok(RequestPermit {
+                    limiter: Some(self.inner.clone()),
+                });
+            }
+            let (sender, receiver) = oneshot::channel();
+            state.queues[priority.index()].push_back(sender);
+            receiver
+        };
+        let permit = receiver.await.map_err(|err| object_store::Error::Generic {
+            store: "RequestLimiter",
+            source: Box::new(err),
+        })?;
+        self.io_tracker
+            .record_request_wait(priority, start.elapsed());
+        Ok(permit)
+    }
+
+    /// Wrap `target` so its requests take permits at `priority`.
+    pub fn wrap(&self, target: Arc<dyn ObjectStore>, priority: Priority) -> Arc<dyn ObjectStore> {
+        Arc::new(PrioritizedStore {
+            target,
+            limiter: self.clone(),
+            priority,
+        })
+    }
+}
+
+/// The store below the priority wrapper, kept so handles at other priorities
+/// can be made from it, see [`crate::object_store::ObjectStore::with_priority`].
+#[derive(Debug, Clone)]
+pub(crate) struct PriorityLayer {
+    target: Arc<dyn ObjectStore>,
+    limiter: RequestLimiter,
+    pub(crate) priority: Priority,
+}
+
+impl PriorityLayer {
+    pub(crate) fn new(target: Arc<dyn ObjectStore>, limiter: RequestLimiter) -> Self {
+        Self {
+            target,
+            limiter,
+            priority: Priority::default(),
+        }
+    }
+
+    pub(crate) fn with_priority(&self, priority: Priority) -> Self {
+        Self {
+            priority,
+            ..self.clone()
+        }
+    }
+
+    pub(crate) fn wrap(&self) -> Arc<dyn ObjectStore> {
+        self.limiter.wrap(self.target.clone(), self.priority)
+    }
+}
+
+/// An [`ObjectStore`] wrapper whose requests wait for a permit at a fixed
+/// priority.
+#[derive(Debug)]
+pub struct PrioritizedStore {
+    target: Arc<dyn ObjectStore>,
+    limiter: RequestLimiter,
+    priority: Priority,
+}
+
+impl PrioritizedStore {
+    pub fn new(target: Arc<dyn ObjectStore>, limiter: RequestLimiter, priority: Priority) -> Self {
+        Self {
+            target,
+            limiter,
+            priority,
+        }
+    }
+
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    async fn acquire(&self) -> OSResult<RequestPermit> {
+        self.limiter.acquire(self.priority).await
+    }
+}
+
+impl Display for PrioritizedStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PrioritizedStore({}, {})", self.priority, self.target)
+    }
+}
+
+/// Keep `permit` until the body of `result` has been consumed or dropped.
+fn hold_until_consumed(mut result: GetResult, permit: RequestPermit) -> GetResult {
+    if let GetResultPayload::Stream(stream) = result.payload {
+        result.payload = GetResultPayload::Stream(
+            stream
+                .map(move |chunk| {
+                    let _ = &permit;
+                    chunk
+                })
+                .boxed(),
+        );
+    }
+    result
+}
+
+#[async_trait::async_trait]
+#[deny(clippy::missing_trait_methods)]
+impl ObjectStore for PrioritizedStore {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        payload: PutPayload,
+        opts: PutOptions,
+    ) -> OSResult<PutResult> {
+        let _permit = self.acquire().await?;
+        self.target.put_opts(location, payload, opts).await
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        opts: PutMultipartOptions,
+    ) -> OSResult<Box<dyn MultipartUpload>> {
+        self.target.put_multipart_opts(location, opts).await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> OSResult<GetResult> {
+        let permit = self.acquire().await?;
+        let result = self.target.get_opts(location, options).await?;
+        Ok(hold_until_consumed(result, permit))
+    }
+
+    async fn get_ranges(&self, location: &Path, ranges: &[Range<u64>]) -> OSResult<Vec<Bytes>> {
+        let _permit = self.acquire().await?;
+        self.target.get_ranges(location, ranges).await
+    }
+
+    fn delete_stream(
+        &self,
+        locations: BoxStream<'static, OSResult<Path>>,
+    ) -> BoxStream<'static, OSResult<Path>> {
+        self.target.delete_stream(locations)
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'static, OSResult<ObjectMeta>> {
+        self.target.list(prefix)
+    }
+
+    fn list_with_offset(
+        &self,
+        prefix: Option<&Path>,
+        offset: &Path,
+    ) -> BoxStream<'static, OSResult<ObjectMeta>> {
+        self.target.list_with_offset(prefix, offset)
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> OSResult<ListResult> {
+        let _permit = self.acquire().await?;
+        self.target.list_with_delimiter(prefix).await
+    }
+
+    async fn copy_opts(&self, from: &Path, to: &Path, opts: CopyOptions) -> OSResult<()> {
+        let _permit = self.acquire().await?;
+        self.target.copy_opts(from, to, opts).await
+    }
+
+    async fn rename_opts(&self, from: &Path, to: &Path, opts: RenameOptions) -> OSResult<()> {
+        let _permit = self.acquire().await?;
+        self.target.rename_opts(from, to, opts).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use object_store::ObjectStoreExt;
+    use object_store::memory::InMemory;
+
+    use super::*;
+
+    #[test]
+    fn test_from_storage_options() {
+        assert!(
+            RequestLimiter::from_storage_options(None, IOTracker::default())
+                .unwrap()
+                .is_none()
+        );
+        let options = |value: &str| {
+            HashMap::from([(MAX_CONCURRENT_REQUESTS_KEY.to_string(), value.to_string())])
+        };
+        let limiter =
+            RequestLimiter::from_storage_options(Some(&options("8")), IOTracker::default())
+                .unwrap()
+                .unwrap();
+        assert_eq!(limiter.inner.capacity, 8);
+        assert!(
+            RequestLimiter::from_storage_options(Some(&options("0")), IOTracker::default())
+                .unwrap()
+                .is_none()
+        );
+        let err =
+            RequestLimiter::from_storage_options(Some(&options("many")), IOTracker::default())
+                .unwrap_err();
+        assert!(
+            err.to_string().contains(MAX_CONCURRENT_REQUESTS_KEY),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_high_priority_granted_first() {
+        let io_tracker = IOTracker::default();
+        let limiter = RequestLimiter::new(1, io_tracker.clone());
+        let held = limiter.acquire(Priority::Normal).await.unwrap();
+
+        // Low queues first, but High is granted the permit ahead of it.
+        let (order_tx, mut order_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut tasks = Vec::new();
+        for priority in [Priority::Low, Priority::High] {
+            let task_limiter = limiter.clone();
+            let order_tx = order_tx.clone();
+            tasks.push(tokio::spawn(async move {
+                let _permit = task_limiter.acquire(priority).await.unwrap();
+                order_tx.send(priority).unwrap();
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }));
+            while limiter.waiting(priority) == 0 {
+                tokio::task::yield_now().await;
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        drop(held);
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        assert_eq!(order_rx.recv().await, Some(Priority::High));
+        assert_eq!(order_rx.recv().await, Some(Priority::Low));
+        assert_eq!(limiter.in_flight(), 0);
+
+        let waits = io_tracker.stats().request_wait;
+        assert!(waits.high >= Duration::from_millis(20), "{waits:?}");
+        assert!(waits.low > waits.high, "{waits:?}");
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_waiter_passes_permit_on() {
+        let limiter = RequestLimiter::new(1, IOTracker::default());
+        let held = limiter.acquire(Priority::Normal).await.unwrap();
+
+        let mut cancelled = Box::pin(limiter.acquire(Priority::High));
+        assert!(
+            tokio::time::timeout(Duration::from_millis(10), &mut cancelled)
+                .await
+                .is_err()
+        );
+        let waiting = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.acquire(Priority::Low).await.map(|_| ()) }
+        });
+        while limiter.waiting(Priority::Low) == 0 {
+            tokio::task::yield_now().await;
+        }
+        drop(cancelled);
+        drop(held);
+        waiting.await.unwrap().unwrap();
+        assert_eq!(limiter.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_permit_held_until_body_consumed() {
+        let inner = Arc::new(InMemory::new());
+        let path = Path::from("data.lance");
+        inner
+            .put(&path, PutPayload::from(vec![7u8; 64]))
+            .await
+            .unwrap();
+        let limiter = RequestLimiter::new(1, IOTracker::default());
+        let store = limiter.wrap(inner, Priority::High);
+
+        let result = store.get(&path).await.unwrap();
+        assert_eq!(limiter.in_flight(), 1);
+        let second = store.head(&path);
+        tokio::pin!(second);
+        assert!(
+            tokio::time::timeout(Duration::from_millis(20), &mut second)
+                .await
+                .is_err()
+        );
+        assert_eq!(result.bytes().await.unwrap().len(), 64);
+        assert_eq!(second.await.unwrap().size, 64);
+        assert_eq!(limiter.in_flight(), 0);
+    }
+}
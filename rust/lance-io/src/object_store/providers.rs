@@ -12,8 +12,35 @@ use std::{
 use object_store::path::Path;
 use url::Url;
 
-use crate::object_store::WrappingObjectStore;
+use crate::object_store::classification::ClassifyingStore;
+use crate::object_store::compress::{
+    TRANSPARENT_COMPRESS_KEY, TRANSPARENT_COMPRESS_LEVEL_KEY, TransparentCompression,
+};
+#[cfg(feature = "diagnose")]
+use crate::object_store::diagnose::{DiagnoseReport, DiagnoseTarget, diagnose_endpoint};
+use crate::object_store::directory_markers::{self, DirectoryMarkerFilterStore};
+use crate::object_store::disk_cache::{
+    CACHE_DIR_KEY, CACHE_SIZE_BYTES_KEY, CachingObjectStore, DiskCacheConfig,
+};
+use crate::object_store::idempotency::{self, IdempotentPutStore};
+use crate::object_store::metadata_cache::{
+    METADATA_CACHE_SIZE_KEY, METADATA_CACHE_TTL_KEY, MetadataCacheConfig, MetadataCachingStore,
+};
+use crate::object_store::metadata_endpoint::{MetadataRoutingStore, metadata_endpoint_params};
+use crate::object_store::priority::{MAX_CONCURRENT_REQUESTS_KEY, PriorityLayer, RequestLimiter};
+use crate::object_store::read_buffer::{
+    READ_BUFFER_BYTES_KEY, ReadBufferLimitedStore, ReadBufferLimiter,
+};
+use crate::object_store::read_only::{self, ReadOnlyStore};
 use crate::object_store::uri_to_url;
+use crate::object_store::verify::{VERIFY_AFTER_WRITE_KEY, VerifyMode, VerifyingStore};
+use crate::object_store::verify_read::{self, ReadVerifyingStore};
+use crate::object_store::{
+    COALESCE_GAP_KEY, MANIFEST_DISCOVERY_PREFIX_KEY, MAX_CONCURRENT_UPLOADS_KEY,
+    MULTIPART_PART_SIZE_KEY, StorageOptions, StorageOptionsAccessor, WrappingObjectStore,
+};
+use crate::object_writer::{MAX_UPLOAD_PART_SIZE, MIN_UPLOAD_PART_SIZE, UploadLimiter};
+use crate::utils::tracking_store::{ACCESS_HISTOGRAM_SIZE_KEY, IOTracker};
 
 use super::{ObjectStore, ObjectStoreParams, tracing::ObjectStoreTracingExt};
 use lance_core::error::{Error, LanceOptionExt, Result};
@@ -69,6 +96,78 @@ pub trait ObjectStoreProvider: std::fmt::Debug + Sync + Send {
     ) -> Result<String> {
         Ok(format!("{}${}", url.scheme(), url.authority()))
     }
+
+    /// Whether multipart uploads should use constant size parts when
+    /// [`ObjectStoreParams::constant_size_upload_parts`] is not set.
+    ///
+    /// Backends without native multipart support (e.g. SFTP or WebDAV style
+    /// stores) should return true.
+    fn default_use_constant_size_upload_parts(&self) -> bool {
+        false
+    }
+
+    /// The smallest and largest part, in bytes, the backend accepts in a
+    /// multipart upload, which bound [`MULTIPART_PART_SIZE_KEY`]. The last
+    /// part of an upload may be smaller. Defaults to the limits of S3 and GCS,
+    /// 5MB and 5GB.
+    fn part_size_bounds(&self) -> (usize, usize) {
+        (MIN_UPLOAD_PART_SIZE, MAX_UPLOAD_PART_SIZE)
+    }
+
+    /// Whether the ETag of an object written with a single put is the MD5 of
+    /// its content, which lets [`IdempotentPutStore`] retry conditional puts.
+    fn md5_etags(&self) -> bool {
+        false
+    }
+
+    /// The storage options the endpoint of the store is read from, preferred
+    /// key first.
+    ///
+    /// Providers that return any keys support a second endpoint for metadata
+    /// requests, see [`crate::object_store::metadata_endpoint`].
+    fn endpoint_keys(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// The endpoint [`Self::diagnose`] connects to for a store at `base_path`.
+    ///
+    /// Defaults to the endpoint set in the storage options under one of
+    /// [`Self::endpoint_keys`]. Providers whose stores have a default endpoint
+    /// should override this.
+    #[cfg(feature = "diagnose")]
+    fn diagnose_target(
+        &self,
+        base_path: &Url,
+        params: &ObjectStoreParams,
+    ) -> Result<DiagnoseTarget> {
+        let options = params.storage_options();
+        self.endpoint_keys()
+            .iter()
+            .find_map(|key| options.and_then(|options| options.get(*key)))
+            .map(|endpoint| {
+                Url::parse(endpoint).map_err(|e| {
+                    Error::invalid_input(format!("Invalid endpoint '{}': {}", endpoint, e))
+                })
+            })
+            .transpose()?
+            .map(DiagnoseTarget::new)
+            .ok_or_else(|| {
+                Error::not_supported(format!(
+                    "Cannot diagnose {}: the store has no network endpoint configured",
+                    base_path
+                ))
+            })
+    }
+
+    /// Time reaching the endpoint of a store at `base_path`: DNS resolution,
+    /// TCP connect, TLS handshake and the first byte of a small request.
+    ///
+    /// See [`crate::object_store::diagnose`].
+    #[cfg(feature = "diagnose")]
+    async fn diagnose(&self, base_path: Url, params: &ObjectStoreParams) -> Result<DiagnoseReport> {
+        let target = self.diagnose_target(&base_path, params)?;
+        diagnose_endpoint(&target).await
+    }
 }
 
 /// Statistics for the object store registry cache.
@@ -137,6 +236,22 @@ impl ObjectStoreRegistry {
             .cloned()
     }
 
+    /// The schemes of all registered providers, sorted.
+    ///
+    /// This includes the default providers as well as any added with
+    /// [`Self::insert()`].
+    pub fn schemes(&self) -> Vec<String> {
+        let mut schemes = self
+            .providers
+            .read()
+            .expect("ObjectStoreRegistry lock poisoned")
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        schemes.sort();
+        schemes
+    }
+
     /// Get a list of all active object stores.
     ///
     /// Calling this will also clean up any weak references to object stores that
@@ -187,11 +302,11 @@ impl ObjectStoreRegistry {
     }
 
     fn scheme_not_found_error(&self, scheme: &str) -> Error {
-        let mut message = format!("No object store provider found for scheme: '{}'", scheme);
-        if let Ok(providers) = self.providers.read() {
-            let valid_schemes = providers.keys().cloned().collect::<Vec<_>>().join(", ");
-            message.push_str(&format!("\nValid schemes: {}", valid_schemes));
-        }
+        let message = format!(
+            "No object store provider found for scheme: '{}'\nValid schemes: {}",
+            scheme,
+            self.schemes().join(", ")
+        );
         Error::invalid_input(message)
     }
 
@@ -245,16 +360,15 @@ impl ObjectStoreRegistry {
 
         self.misses.fetch_add(1, Ordering::Relaxed);
 
-        let mut store = provider.new_store(base_path, params).await?;
-
-        store.inner = store.inner.traced();
-
-        if let Some(wrapper) = &params.object_store_wrapper {
-            store.inner = wrapper.wrap(&cache_path, store.inner);
-        }
-
-        // Always wrap with IO tracking
-        store.inner = store.io_tracker.wrap("", store.inner);
+        let client = build_client(provider.as_ref(), base_path.clone(), params).await?;
+        let mut store = wrap_store(provider.as_ref(), &cache_path, client.clone(), params)?;
+        store.rebind_source = Some(Arc::new(RebindSource {
+            provider,
+            base_path,
+            cache_path,
+            params: params.clone(),
+            client,
+        }));
 
         let store = Arc::new(store);
 
@@ -288,11 +402,264 @@ impl ObjectStoreRegistry {
     }
 }
 
+/// Storage options applied on top of the store a provider built. Changing
+/// only these lets [`ObjectStore::rebind`] keep the provider's store.
+const LAYER_OPTION_KEYS: &[&str] = &[
+    "download_retry_count",
+    COALESCE_GAP_KEY,
+    MANIFEST_DISCOVERY_PREFIX_KEY,
+    MAX_CONCURRENT_UPLOADS_KEY,
+    MULTIPART_PART_SIZE_KEY,
+    directory_markers::IGNORE_DIRECTORY_MARKERS_KEY,
+    idempotency::PUT_RETRY_COUNT_KEY,
+    verify_read::VERIFY_ON_READ_KEY,
+    ACCESS_HISTOGRAM_SIZE_KEY,
+    VERIFY_AFTER_WRITE_KEY,
+    METADATA_CACHE_SIZE_KEY,
+    METADATA_CACHE_TTL_KEY,
+    CACHE_DIR_KEY,
+    CACHE_SIZE_BYTES_KEY,
+    READ_BUFFER_BYTES_KEY,
+    MAX_CONCURRENT_REQUESTS_KEY,
+    read_only::READ_ONLY_KEY,
+    TRANSPARENT_COMPRESS_KEY,
+    TRANSPARENT_COMPRESS_LEVEL_KEY,
+];
+
+/// What [`ObjectStore::rebind`] builds a store of the registry again from.
+#[derive(Debug)]
+pub(crate) struct RebindSource {
+    provider: Arc<dyn ObjectStoreProvider>,
+    base_path: Url,
+    cache_path: String,
+    params: ObjectStoreParams,
+    /// The store as the provider built it, before [`wrap_store`].
+    client: ObjectStore,
+}
+
+impl RebindSource {
+    pub(crate) async fn rebind(&self, overrides: StorageOptions) -> Result<ObjectStore> {
+        let mut options = self.params.storage_options().cloned().unwrap_or_default();
+        let reuse_client = overrides.0.iter().all(|(key, value)| {
+            options.get(key) == Some(value)
+                || LAYER_OPTION_KEYS
+                    .iter()
+                    .any(|layer_key| key.eq_ignore_ascii_case(layer_key))
+        });
+        options.extend(overrides.0);
+
+        let accessor = match self.params.get_accessor() {
+            Some(accessor) if accessor.has_provider() => {
+                if accessor.initial_storage_options().is_none() && !reuse_client {
+                    return Err(Error::not_supported(format!(
+                        "Cannot rebind {} to options that change its client: its storage options only come from a provider",
+                        self.base_path
+                    )));
+                }
+                StorageOptionsAccessor::with_initial_and_provider(
+                    options.clone(),
+                    accessor.provider().expect_ok()?.clone(),
+                )
+                .with_refresh_offset(accessor.refresh_offset())
+                .with_refresh_jitter(accessor.refresh_jitter())
+                .with_refresh_in_background(accessor.refresh_in_background())
+            }
+            _ => StorageOptionsAccessor::with_static_options(options.clone()),
+        };
+        let params = ObjectStoreParams {
+            storage_options_accessor: Some(Arc::new(accessor)),
+            ..self.params.clone()
+        };
+
+        let (client, cache_path) = if reuse_client {
+            let options = StorageOptions(options);
+            let mut client = self.client.clone();
+            client.download_retry_count = options.download_retry_count();
+            client.coalesce_gap = options.coalesce_gap();
+            client.manifest_discovery_prefix = options.manifest_discovery_prefix();
+            client.upload_limiter = UploadLimiter::new(options.max_concurrent_uploads());
+            (client, self.cache_path.clone())
+        } else {
+            let cache_path = self
+                .provider
+                .calculate_object_store_prefix(&self.base_path, params.storage_options())?;
+            let client =
+                build_client(self.provider.as_ref(), self.base_path.clone(), &params).await?;
+            (client, cache_path)
+        };
+
+        let mut store = wrap_store(self.provider.as_ref(), &cache_path, client.clone(), &params)?;
+        store.rebind_source = Some(Arc::new(Self {
+            provider: self.provider.clone(),
+            base_path: self.base_path.clone(),
+            cache_path,
+            params,
+            client,
+        }));
+        Ok(store)
+    }
+}
+
+/// Build the store of `provider`, with metadata requests routed to their own
+/// endpoint if the storage options ask for it.
+async fn build_client(
+    provider: &dyn ObjectStoreProvider,
+    base_path: Url,
+    params: &ObjectStoreParams,
+) -> Result<ObjectStore> {
+    let mut store = provider.new_store(base_path.clone(), params).await?;
+
+    // Metadata requests are routed to their own endpoint before anything
+    // else wraps the store, so both endpoints share the caches and tracking.
+    if let Some(metadata_params) =
+        metadata_endpoint_params(base_path.scheme(), params, provider.endpoint_keys())?
+    {
+        let metadata_store = provider.new_store(base_path, &metadata_params).await?;
+        store.inner = Arc::new(MetadataRoutingStore::new(store.inner, metadata_store.inner));
+        // `ObjectStore::list_from` lists versions through the operator, so
+        // it must be the one that sees the latest manifests.
+        #[cfg(any(
+            feature = "aws",
+            feature = "azure",
+            feature = "gcp",
+            feature = "oss",
+            feature = "huggingface",
+            feature = "tencent"
+        ))]
+        {
+            store.opendal_operator = metadata_store.opendal_operator;
+        }
+    }
+    Ok(store)
+}
+
+/// Wrap the store a provider built in the layers the storage options ask for.
+fn wrap_store(
+    provider: &dyn ObjectStoreProvider,
+    cache_path: &str,
+    mut store: ObjectStore,
+    params: &ObjectStoreParams,
+) -> Result<ObjectStore> {
+    store.retry_classifier = params.is_retryable.clone();
+    // A provider may already require constant size parts for the endpoint it
+    // connected to, e.g. S3 for Cloudflare R2
+    store.use_constant_size_upload_parts = params.use_constant_size_upload_parts_or(
+        store.use_constant_size_upload_parts || provider.default_use_constant_size_upload_parts(),
+    );
+    store.upload_part_size = params
+        .storage_options()
+        .map(|options| {
+            StorageOptions(options.clone()).multipart_part_size(provider.part_size_bounds())
+        })
+        .transpose()?
+        .flatten();
+
+    // Classify the errors of the backend before any layer retries or
+    // reports them.
+    store.inner = Arc::new(ClassifyingStore::new(store.inner));
+
+    // Markers are dropped below the caches so cached listings leave them
+    // out too.
+    if directory_markers::ignore_directory_markers(params.storage_options()) {
+        store.inner = Arc::new(DirectoryMarkerFilterStore::new(store.inner));
+    }
+
+    // Every attempt of a retried put is traced and counted on its own.
+    let put_retries = idempotency::put_retry_count(params.storage_options());
+    if put_retries > 0 {
+        store.inner = Arc::new(
+            IdempotentPutStore::new(store.inner, put_retries, provider.md5_etags())
+                .with_retry_classifier(params.is_retryable.clone()),
+        );
+    }
+
+    // Reads can only be checked against ETags that are content MD5s.
+    if verify_read::verify_on_read(params.storage_options()) && provider.md5_etags() {
+        store.inner = Arc::new(ReadVerifyingStore::new(store.inner));
+    }
+
+    store.inner = store.inner.traced();
+
+    if let Some(wrapper) = &params.object_store_wrapper {
+        store.inner = wrapper.wrap(cache_path, store.inner);
+    }
+
+    // Always wrap with IO tracking
+    store.io_tracker =
+        IOTracker::from_storage_options(params.storage_options())?.with_metrics(&store.scheme);
+    if let Some(observer) = &params.io_observer {
+        store.io_tracker = store.io_tracker.clone().with_observer(observer.clone());
+    }
+    store.inner = store.io_tracker.wrap("", store.inner);
+
+    // Verification sits inside the caches so its HEADs and reads reach
+    // the store, and are counted by the IO tracker.
+    if let Some(mode) = VerifyMode::from_storage_options(params.storage_options())? {
+        store.inner = Arc::new(VerifyingStore::new(store.inner, mode));
+    }
+
+    // The metadata cache sits outside IO tracking so cache hits are not
+    // counted as requests against the store.
+    let metadata_cache = MetadataCacheConfig::from_storage_options(params.storage_options())?;
+    if !metadata_cache.is_disabled() {
+        store.inner = Arc::new(MetadataCachingStore::new(
+            store.inner,
+            &metadata_cache,
+            store.io_tracker.clone(),
+        ));
+    }
+
+    // The disk cache sits outside the metadata cache so the HEADs it
+    // validates etags with can be answered from memory.
+    let disk_cache = DiskCacheConfig::from_storage_options(params.storage_options())?;
+    if !disk_cache.is_disabled() {
+        store.inner = Arc::new(CachingObjectStore::try_new(
+            store.inner,
+            &disk_cache,
+            store.io_tracker.clone(),
+        )?);
+    }
+
+    // The read buffer budget sits outside the caches so reads they serve
+    // are bounded too.
+    if let Some(limiter) =
+        ReadBufferLimiter::from_storage_options(params.storage_options(), store.io_tracker.clone())?
+    {
+        store.inner = Arc::new(ReadBufferLimitedStore::new(store.inner, limiter));
+    }
+
+    // Request permits are taken outside the caches so handles at other
+    // priorities can be made by re-wrapping the store below. Cache hits
+    // take a permit too, but only hold it briefly.
+    if let Some(limiter) =
+        RequestLimiter::from_storage_options(params.storage_options(), store.io_tracker.clone())?
+    {
+        let layer = PriorityLayer::new(store.inner, limiter);
+        store.inner = layer.wrap();
+        store.priority_layer = Some(layer);
+    }
+
+    // Read-only is applied last so rejected writes never reach the
+    // backend, the IO tracker or the caches.
+    if read_only::is_read_only(params.storage_options()) {
+        store.inner = Arc::new(ReadOnlyStore::new(store.inner));
+        store.read_only = true;
+    }
+
+    store.transparent_compression =
+        TransparentCompression::from_storage_options(params.storage_options())?;
+
+    Ok(store)
+}
+
 impl Default for ObjectStoreRegistry {
     fn default() -> Self {
         let mut providers: HashMap<String, Arc<dyn ObjectStoreProvider>> = HashMap::new();
 
-        providers.insert("memory".into(), Arc::new(memory::MemoryStoreProvider));
+        providers.insert(
+            "memory".into(),
+            Arc::new(memory::MemoryStoreProvider::default()),
+        );
         providers.insert(
             "shared-memory".into(),
             Arc::new(shared_memory::SharedMemoryStoreProvider::default()),
@@ -312,7 +679,7 @@ impl Default for ObjectStoreRegistry {
 
         #[cfg(feature = "aws")]
         {
-            let aws = Arc::new(aws::AwsStoreProvider);
+            let aws = Arc::new(aws::AwsStoreProvider::default());
             providers.insert("s3".into(), aws.clone());
             providers.insert("s3+ddb".into(), aws);
         }
@@ -370,6 +737,40 @@ mod tests {
         }
     }
 
+    /// Gives every endpoint its own in-memory bucket
+    #[derive(Debug, Default)]
+    struct EndpointProvider {
+        buckets: std::sync::Mutex<HashMap<String, Arc<object_store::memory::InMemory>>>,
+        builds: AtomicU64,
+    }
+
+    #[async_trait::async_trait]
+    impl ObjectStoreProvider for EndpointProvider {
+        async fn new_store(
+            &self,
+            base_path: Url,
+            params: &ObjectStoreParams,
+        ) -> Result<ObjectStore> {
+            self.builds.fetch_add(1, Ordering::Relaxed);
+            let endpoint = params.storage_options().unwrap()["endpoint"].clone();
+            let mut store = memory::MemoryStoreProvider::default()
+                .new_store(base_path, params)
+                .await?;
+            store.inner = self
+                .buckets
+                .lock()
+                .unwrap()
+                .entry(endpoint)
+                .or_default()
+                .clone();
+            Ok(store)
+        }
+
+        fn endpoint_keys(&self) -> &'static [&'static str] {
+            &["endpoint"]
+        }
+    }
+
     #[test]
     fn test_calculate_object_store_prefix() {
         let provider = DummyProvider;
@@ -392,6 +793,20 @@ mod tests {
         assert_eq!(s, &result[..s.len()]);
     }
 
+    #[test]
+    fn test_schemes() {
+        let registry = ObjectStoreRegistry::empty();
+        assert!(registry.schemes().is_empty());
+        registry.insert("dummy", Arc::new(DummyProvider));
+        registry.insert("another", Arc::new(DummyProvider));
+        assert_eq!(registry.schemes(), ["another", "dummy"]);
+
+        let schemes = ObjectStoreRegistry::default().schemes();
+        for scheme in ["file", "memory"] {
+            assert!(schemes.iter().any(|s| s == scheme), "{schemes:?}");
+        }
+    }
+
     // Test that paths without a scheme get treated as local paths.
     #[test]
     fn test_calculate_object_store_prefix_for_local() {
@@ -463,4 +878,127 @@ mod tests {
         // Same params returns same instance
         assert!(Arc::ptr_eq(&stores[0], &stores[1]));
     }
+
+    #[rstest::rstest]
+    #[case::provider_default(None, false, false)]
+    #[case::params_override(Some(true), false, true)]
+    #[case::deprecated_param(None, true, true)]
+    #[case::params_take_precedence(Some(false), true, false)]
+    #[tokio::test]
+    #[allow(deprecated)]
+    async fn test_use_constant_size_upload_parts(
+        #[case] param: Option<bool>,
+        #[case] deprecated_param: bool,
+        #[case] expected: bool,
+    ) {
+        let registry = ObjectStoreRegistry::default();
+        let params = ObjectStoreParams {
+            constant_size_upload_parts: param,
+            use_constant_size_upload_parts: deprecated_param,
+            ..Default::default()
+        };
+        let store = registry
+            .get_store(Url::parse("memory:///path").unwrap(), &params)
+            .await
+            .unwrap();
+        assert_eq!(store.use_constant_size_upload_parts, expected);
+    }
+
+    #[tokio::test]
+    async fn test_metadata_endpoint() {
+        use object_store::ObjectStoreExt;
+
+        let provider = Arc::new(EndpointProvider::default());
+        let registry = ObjectStoreRegistry::empty();
+        registry.insert("endpoint", provider.clone());
+        registry.insert("memory", Arc::new(memory::MemoryStoreProvider::default()));
+        let params = ObjectStoreParams {
+            storage_options_accessor: Some(Arc::new(
+                crate::object_store::StorageOptionsAccessor::with_static_options(HashMap::from([
+                    ("endpoint".to_string(), "cdn".to_string()),
+                    ("metadata_endpoint".to_string(), "origin".to_string()),
+                ])),
+            )),
+            ..Default::default()
+        };
+
+        let store = registry
+            .get_store(Url::parse("endpoint://bucket/t.lance").unwrap(), &params)
+            .await
+            .unwrap();
+        let manifest = Path::from("t.lance/_versions/1.manifest");
+        let data_file = Path::from("t.lance/data/a.lance");
+        for path in [&manifest, &data_file] {
+            store.inner.put(path, "x".into()).await.unwrap();
+        }
+
+        let buckets = provider.buckets.lock().unwrap().clone();
+        assert!(buckets["origin"].head(&manifest).await.is_ok());
+        assert!(buckets["origin"].head(&data_file).await.is_err());
+        assert!(buckets["cdn"].head(&data_file).await.is_ok());
+        assert!(buckets["cdn"].head(&manifest).await.is_err());
+
+        let err = registry
+            .get_store(Url::parse("memory://bucket").unwrap(), &params)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("metadata_endpoint"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_rebind() {
+        let provider = Arc::new(EndpointProvider::default());
+        let registry = ObjectStoreRegistry::empty();
+        registry.insert("endpoint", provider.clone());
+        let params = ObjectStoreParams {
+            storage_options_accessor: Some(Arc::new(
+                crate::object_store::StorageOptionsAccessor::with_static_options(HashMap::from([
+                    ("endpoint".to_string(), "a".to_string()),
+                ])),
+            )),
+            ..Default::default()
+        };
+        let store = registry
+            .get_store(Url::parse("endpoint://bucket/t.lance").unwrap(), &params)
+            .await
+            .unwrap();
+        let path = Path::from("t.lance/data.lance");
+        store.put(&path, b"x").await.unwrap();
+        let overrides = |key: &str, value: &str| {
+            StorageOptions(HashMap::from([(key.to_string(), value.to_string())]))
+        };
+
+        // Layered options keep the provider's store.
+        let read_only = store
+            .rebind(overrides(read_only::READ_ONLY_KEY, "true"))
+            .await
+            .unwrap();
+        assert!(read_only.is_read_only());
+        assert!(!store.is_read_only());
+        assert_eq!(read_only.read_one_all(&path).await.unwrap().as_ref(), b"x");
+        read_only.put(&path, b"y").await.unwrap_err();
+        let prefixed = read_only
+            .rebind(overrides(MANIFEST_DISCOVERY_PREFIX_KEY, "_data"))
+            .await
+            .unwrap();
+        assert!(prefixed.is_read_only());
+        assert_eq!(
+            prefixed.manifest_discovery_base(&Path::from("t.lance")),
+            Path::from("t.lance/_data")
+        );
+        assert_eq!(provider.builds.load(Ordering::Relaxed), 1);
+
+        // Other options build a new one.
+        let moved = store.rebind(overrides("endpoint", "b")).await.unwrap();
+        assert_eq!(provider.builds.load(Ordering::Relaxed), 2);
+        moved.read_one_all(&path).await.unwrap_err();
+        moved.put(&path, b"z").await.unwrap();
+        assert_eq!(store.read_one_all(&path).await.unwrap().as_ref(), b"x");
+
+        let err = ObjectStore::memory()
+            .rebind(overrides(read_only::READ_ONLY_KEY, "true"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("cannot be rebound"), "{err}");
+    }
 }
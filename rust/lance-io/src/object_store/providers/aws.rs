@@ -10,6 +10,7 @@ use mock_instant::thread_local::{SystemTime, UNIX_EPOCH};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use object_store::ObjectStore as OSObjectStore;
+use object_store::path::Path;
 use object_store_opendal::OpendalStore;
 use opendal::{Operator, services::S3};
 
@@ -26,16 +27,34 @@ use object_store::{
 use tokio::sync::RwLock;
 use url::Url;
 
+#[cfg(feature = "diagnose")]
+use crate::object_store::diagnose::DiagnoseTarget;
 use crate::object_store::{
     DEFAULT_CLOUD_BLOCK_SIZE, DEFAULT_CLOUD_IO_PARALLELISM, DEFAULT_MAX_IOP_SIZE, ObjectStore,
     ObjectStoreParams, ObjectStoreProvider, StorageOptions, StorageOptionsAccessor,
     dynamic_credentials::{NamespaceCredentialsProvider, build_dynamic_credential_provider},
+    interceptor::{InterceptingConnector, intercept_operator},
+    part_retry::{PartRetryLayer, PartRetryStore},
+    read_only::is_read_only,
     throttle::{AimdThrottleConfig, AimdThrottledStore},
 };
+use crate::object_writer::UploadLimiter;
+use compatibility::{S3CompatibilityMode, conditional_put_support};
+use container_credentials::{
+    CREDENTIAL_SOURCE_KEY, ContainerCredentialCache, CredentialEndpoint, CredentialEnv,
+    CredentialSource,
+};
 use lance_core::error::{Error, Result};
 
+pub mod compatibility;
+pub mod container_credentials;
+
 #[derive(Default, Debug)]
-pub struct AwsStoreProvider;
+pub struct AwsStoreProvider {
+    /// Container credentials shared by the stores of this provider, and so of
+    /// its registry
+    container_credentials: ContainerCredentialCache,
+}
 
 impl AwsStoreProvider {
     async fn build_amazon_s3_store(
@@ -59,9 +78,11 @@ impl AwsStoreProvider {
         // Get accessor from params
         let accessor = params.get_accessor();
 
+        let container_credentials =
+            self.container_credentials(params, &s3_storage_options, storage_options)?;
         let (aws_creds, region) = build_aws_credential(
             params.s3_credentials_refresh_offset,
-            params.aws_credentials.clone(),
+            params.aws_credentials.clone().or(container_credentials),
             Some(&s3_storage_options),
             region,
             accessor,
@@ -88,15 +109,53 @@ impl AwsStoreProvider {
             .with_credentials(aws_creds)
             .with_retry(retry_config)
             .with_region(region);
+        if let Some(interceptor) = &params.request_interceptor {
+            builder = builder.with_http_connector(InterceptingConnector::new(interceptor.clone()));
+        }
+
+        Ok(Arc::new(
+            PartRetryStore::new(builder.build()?, storage_options.part_upload_retries())
+                .with_retry_classifier(params.is_retryable.clone()),
+        ) as Arc<dyn OSObjectStore>)
+    }
 
-        Ok(Arc::new(builder.build()?) as Arc<dyn OSObjectStore>)
+    /// The shared credentials of the container or instance, see
+    /// [`container_credentials`], unless the params or storage options have
+    /// credentials or the environment configures no container credentials.
+    fn container_credentials(
+        &self,
+        params: &ObjectStoreParams,
+        s3_storage_options: &HashMap<AmazonS3ConfigKey, String>,
+        storage_options: &StorageOptions,
+    ) -> Result<Option<AwsCredentialProvider>> {
+        if params.aws_credentials.is_some()
+            || params.get_accessor().is_some_and(|a| a.has_provider())
+            || extract_static_s3_credentials(s3_storage_options).is_some()
+        {
+            return Ok(None);
+        }
+        let forced = storage_options
+            .0
+            .get(CREDENTIAL_SOURCE_KEY)
+            .map(|source| source.parse::<CredentialSource>())
+            .transpose()?;
+        let Some(endpoint) = CredentialEndpoint::resolve(&CredentialEnv::from_env(), forced)?
+        else {
+            return Ok(None);
+        };
+        let provider = self.container_credentials.get_or_create(
+            endpoint,
+            params.s3_credentials_refresh_offset,
+            params.request_interceptor.as_ref(),
+        )?;
+        Ok(Some(provider as AwsCredentialProvider))
     }
 
-    async fn build_opendal_s3_store(
+    async fn build_opendal_s3_operator(
         &self,
         base_path: &Url,
         storage_options: &StorageOptions,
-    ) -> Result<Arc<dyn OSObjectStore>> {
+    ) -> Result<Operator> {
         let bucket = base_path
             .host_str()
             .ok_or_else(|| Error::invalid_input("S3 URL must contain bucket name"))?
@@ -115,11 +174,9 @@ impl AwsStoreProvider {
             config_map.insert("root".to_string(), "/".to_string());
         }
 
-        let operator = Operator::from_iter::<S3>(config_map)
+        Ok(Operator::from_iter::<S3>(config_map)
             .map_err(|e| Error::invalid_input(format!("Failed to create S3 operator: {:?}", e)))?
-            .finish();
-
-        Ok(Arc::new(OpendalStore::new(operator)) as Arc<dyn OSObjectStore>)
+            .finish())
     }
 }
 
@@ -134,6 +191,10 @@ impl ObjectStoreProvider for AwsStoreProvider {
         let mut storage_options =
             StorageOptions::new(params.storage_options().cloned().unwrap_or_default());
         storage_options.with_env_s3();
+        let compatibility_mode = S3CompatibilityMode::from_storage_options(&storage_options)?;
+        if let Some(mode) = compatibility_mode {
+            mode.apply(&mut storage_options);
+        }
         let download_retry_count = storage_options.download_retry_count();
 
         let use_opendal = storage_options
@@ -151,14 +212,24 @@ impl ObjectStoreProvider for AwsStoreProvider {
             .map(|endpoint| endpoint.contains("r2.cloudflarestorage.com"))
             .unwrap_or(false);
 
-        let inner = if use_opendal {
+        let (inner, opendal_operator) = if use_opendal {
             // Use OpenDAL implementation
-            self.build_opendal_s3_store(&base_path, &storage_options)
-                .await?
+            let operator = intercept_operator(
+                self.build_opendal_s3_operator(&base_path, &storage_options)
+                    .await?,
+                params.request_interceptor.as_ref(),
+            )
+            .layer(PartRetryLayer::from_params(params));
+            (
+                Arc::new(OpendalStore::new(operator.clone())) as Arc<dyn OSObjectStore>,
+                Some(operator),
+            )
         } else {
             // Use default Amazon S3 implementation
-            self.build_amazon_s3_store(&mut base_path, params, &storage_options, is_s3_express)
-                .await?
+            let store = self
+                .build_amazon_s3_store(&mut base_path, params, &storage_options, is_s3_express)
+                .await?;
+            (store, None)
         };
         let throttle_config = AimdThrottleConfig::from_storage_options(params.storage_options())?;
         let inner = if throttle_config.is_disabled() {
@@ -167,6 +238,23 @@ impl ObjectStoreProvider for AwsStoreProvider {
             Arc::new(AimdThrottledStore::new(inner, throttle_config)?) as Arc<dyn OSObjectStore>
         };
 
+        // S3-compatible stores may not support the conditional puts commits rely on
+        let conditional_put = match compatibility_mode {
+            Some(_) if !is_read_only(params.storage_options()) => {
+                let endpoint = format!(
+                    "{}/{}",
+                    storage_options
+                        .as_s3_options()
+                        .get(&AmazonS3ConfigKey::Endpoint)
+                        .map_or("", String::as_str),
+                    base_path.host_str().unwrap_or_default()
+                );
+                conditional_put_support(inner.as_ref(), &endpoint, &Path::parse(base_path.path())?)
+                    .await
+            }
+            _ => None,
+        };
+
         Ok(ObjectStore {
             inner,
             scheme: String::from(base_path.scheme()),
@@ -176,11 +264,69 @@ impl ObjectStoreProvider for AwsStoreProvider {
             list_is_lexically_ordered: !is_s3_express,
             io_parallelism: DEFAULT_CLOUD_IO_PARALLELISM,
             download_retry_count,
+            coalesce_gap: storage_options.coalesce_gap(),
+            manifest_discovery_prefix: storage_options.manifest_discovery_prefix(),
+            upload_limiter: UploadLimiter::new(storage_options.max_concurrent_uploads()),
+            read_only: false,
+            conditional_put,
+            transparent_compression: None,
+            retry_classifier: None,
+            priority_layer: None,
+            upload_part_size: None,
+            rebind_source: None,
             io_tracker: Default::default(),
             store_prefix: self
                 .calculate_object_store_prefix(&base_path, params.storage_options())?,
+            opendal_operator,
         })
     }
+
+    /// S3 ETags are the content MD5 unless the object is encrypted with SSE-KMS
+    /// or SSE-C.
+    fn md5_etags(&self) -> bool {
+        true
+    }
+
+    fn endpoint_keys(&self) -> &'static [&'static str] {
+        &[
+            "aws_endpoint",
+            "aws_endpoint_url",
+            "endpoint",
+            "endpoint_url",
+        ]
+    }
+
+    /// The configured endpoint, or the regional endpoint of the bucket.
+    ///
+    /// Without a region in the options or environment the global endpoint is
+    /// used, which redirects to the region of the bucket.
+    #[cfg(feature = "diagnose")]
+    fn diagnose_target(
+        &self,
+        base_path: &Url,
+        params: &ObjectStoreParams,
+    ) -> Result<DiagnoseTarget> {
+        let mut storage_options =
+            StorageOptions::new(params.storage_options().cloned().unwrap_or_default());
+        storage_options.with_env_s3();
+        let s3_options = storage_options.as_s3_options();
+        let endpoint = match s3_options.get(&AmazonS3ConfigKey::Endpoint) {
+            Some(endpoint) => endpoint.clone(),
+            None => {
+                let bucket = base_path.host_str().ok_or_else(|| {
+                    Error::invalid_input(format!("S3 URL {} has no bucket", base_path))
+                })?;
+                match s3_options.get(&AmazonS3ConfigKey::Region) {
+                    Some(region) => format!("https://{}.s3.{}.amazonaws.com/", bucket, region),
+                    None => format!("https://{}.s3.amazonaws.com/", bucket),
+                }
+            }
+        };
+        let endpoint = Url::parse(&endpoint).map_err(|e| {
+            Error::invalid_input(format!("Invalid S3 endpoint '{}': {}", endpoint, e))
+        })?;
+        Ok(DiagnoseTarget::new(endpoint))
+    }
 }
 
 /// Check if the storage is S3 Express
@@ -516,7 +662,7 @@ mod tests {
 
     #[test]
     fn test_s3_path_parsing() {
-        let provider = AwsStoreProvider;
+        let provider = AwsStoreProvider::default();
 
         let cases = [
             ("s3://bucket/path/to/file", "path/to/file"),
@@ -576,7 +722,7 @@ mod tests {
     #[tokio::test]
     async fn test_use_opendal_flag() {
         use crate::object_store::StorageOptionsAccessor;
-        let provider = AwsStoreProvider;
+        let provider = AwsStoreProvider::default();
         let url = Url::parse("s3://test-bucket/path").unwrap();
         let params_with_flag = ObjectStoreParams {
             storage_options_accessor: Some(Arc::new(StorageOptionsAccessor::with_static_options(
@@ -1094,4 +1240,22 @@ mod tests {
         // Storage options provider should have been called once
         assert_eq!(mock_storage_provider.get_call_count().await, 1);
     }
+
+    #[cfg(feature = "diagnose")]
+    #[rstest::rstest]
+    #[case::region(("aws_region", "eu-west-1"), "https://bucket.s3.eu-west-1.amazonaws.com/")]
+    #[case::endpoint(("aws_endpoint", "http://localhost:9000"), "http://localhost:9000/")]
+    fn test_diagnose_target(#[case] option: (&str, &str), #[case] expected: &str) {
+        let params = ObjectStoreParams {
+            storage_options_accessor: Some(Arc::new(StorageOptionsAccessor::with_static_options(
+                HashMap::from([(option.0.to_string(), option.1.to_string())]),
+            ))),
+            ..Default::default()
+        };
+        let target = AwsStoreProvider::default()
+            .diagnose_target(&Url::parse("s3://bucket/path").unwrap(), &params)
+            .unwrap();
+        assert_eq!(target.endpoint.as_str(), expected);
+        assert_eq!(target.address, None);
+    }
 }
@@ -0,0 +1,360 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Settings for S3-compatible stores, such as MinIO and Ceph RGW.
+//!
+//! These stores implement most, but not all, of the S3 API, and the defaults
+//! of the AWS client suit none of them. The [`S3_COMPATIBILITY_MODE_KEY`]
+//! storage option turns on a bundle of settings:
+//!
+//! * Path-style requests, `http://endpoint/bucket/key`, since a bucket is rarely
+//!   resolvable as a sub-domain of a self-hosted endpoint.
+//! * A signing region of `us-east-1`, since there is no bucket region to look up.
+//! * Plain HTTP when the endpoint is an `http://` URL.
+//! * No request checksums, which older releases reject.
+//!
+//! Options set explicitly take precedence over the bundle. Lance never relies
+//! on object versions, so buckets without versioning need nothing more.
+//!
+//! The commit handler of S3 relies on conditional puts, which these stores
+//! added only recently, so whether the store supports them is probed when it
+//! is opened, see [`probe_conditional_put`].
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{LazyLock, Mutex};
+
+use lance_core::error::{Error, Result};
+use object_store::aws::AmazonS3ConfigKey;
+use object_store::path::Path;
+use object_store::{
+    ClientConfigKey, ObjectStore as OSObjectStore, ObjectStoreExt, PutMode, PutPayload,
+};
+
+use crate::object_store::StorageOptions;
+
+/// Storage option naming the kind of S3-compatible store at `aws_endpoint`,
+/// `minio`, `ceph` or `generic`, see [`S3CompatibilityMode`].
+pub const S3_COMPATIBILITY_MODE_KEY: &str = "s3_compatibility_mode";
+
+/// The default signing region of S3-compatible stores
+const DEFAULT_REGION: &str = "us-east-1";
+
+/// The kind of S3-compatible store, see the [module docs](self).
+///
+/// The modes share one bundle of settings today. They are separate so that
+/// the quirks of one store can be handled without changing the others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum S3CompatibilityMode {
+    Minio,
+    Ceph,
+    Generic,
+}
+
+impl FromStr for S3CompatibilityMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "minio" => Ok(Self::Minio),
+            "ceph" => Ok(Self::Ceph),
+            "generic" => Ok(Self::Generic),
+            _ => Err(Error::invalid_input(format!(
+                "Invalid value for storage option '{S3_COMPATIBILITY_MODE_KEY}': '{s}', expected one of 'minio', 'ceph' or 'generic'"
+            ))),
+        }
+    }
+}
+
+impl S3CompatibilityMode {
+    /// The mode set in `storage_options`, if any
+    pub fn from_storage_options(storage_options: &StorageOptions) -> Result<Option<Self>> {
+        storage_options
+            .0
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(S3_COMPATIBILITY_MODE_KEY))
+            .map(|(_, value)| value.parse())
+            .transpose()
+    }
+
+    /// The settings of this mode given the configured S3 options
+    fn defaults(
+        self,
+        s3_options: &HashMap<AmazonS3ConfigKey, String>,
+    ) -> Vec<(AmazonS3ConfigKey, String)> {
+        let mut defaults = vec![
+            (AmazonS3ConfigKey::VirtualHostedStyleRequest, "false".into()),
+            (AmazonS3ConfigKey::Region, DEFAULT_REGION.into()),
+        ];
+        if s3_options
+            .get(&AmazonS3ConfigKey::Endpoint)
+            .is_some_and(|endpoint| endpoint.starts_with("http://"))
+        {
+            defaults.push((
+                AmazonS3ConfigKey::Client(ClientConfigKey::AllowHttp),
+                "true".into(),
+            ));
+        }
+        defaults
+    }
+
+    /// Add the settings of this mode to `storage_options`, keeping any that
+    /// are set explicitly, and drop the request checksum.
+    pub fn apply(self, storage_options: &mut StorageOptions) {
+        let s3_options = storage_options.as_s3_options();
+        for (key, value) in self.defaults(&s3_options) {
+            if !s3_options.contains_key(&key) {
+                storage_options.0.insert(key.as_ref().to_string(), value);
+            }
+        }
+        storage_options.0.retain(|key, _| {
+            !matches!(
+                AmazonS3ConfigKey::from_str(&key.to_ascii_lowercase()),
+                Ok(AmazonS3ConfigKey::Checksum)
+            )
+        });
+    }
+}
+
+/// Results of [`probe_conditional_put`], by endpoint and bucket
+static CONDITIONAL_PUT_SUPPORT: LazyLock<Mutex<HashMap<String, bool>>> =
+    LazyLock::new(Default::default);
+
+/// Whether `store` supports conditional puts, probed under `base` the first
+/// time `endpoint` is seen in this process.
+///
+/// `None` if the probe failed, as it does with read-only credentials. The
+/// failure is logged and the probe retried the next time.
+pub async fn conditional_put_support(
+    store: &dyn OSObjectStore,
+    endpoint: &str,
+    base: &Path,
+) -> Option<bool> {
+    if let Some(supported) = CONDITIONAL_PUT_SUPPORT.lock().unwrap().get(endpoint) {
+        return Some(*supported);
+    }
+    match probe_conditional_put(store, base).await {
+        Ok(supported) => {
+            if !supported {
+                log::warn!(
+                    "The S3-compatible store at {} does not support conditional puts, \
+                     concurrent commits may overwrite each other",
+                    endpoint
+                );
+            }
+            CONDITIONAL_PUT_SUPPORT
+                .lock()
+                .unwrap()
+                .insert(endpoint.to_string(), supported);
+            Some(supported)
+        }
+        Err(e) => {
+            log::debug!(
+                "Could not probe the S3-compatible store at {} for conditional puts: {}",
+                endpoint,
+                e
+            );
+            None
+        }
+    }
+}
+
+/// Whether a put with [`PutMode::Create`] fails when the object exists.
+///
+/// Puts a probe object under `base` twice and deletes it again. Stores that
+/// ignore `If-None-Match` let the second put overwrite the first.
+pub async fn probe_conditional_put(store: &dyn OSObjectStore, base: &Path) -> Result<bool> {
+    let path = base.clone().join(format!(
+        "_conditional_put_probe_{:016x}",
+        rand::random::<u64>()
+    ));
+    let put = || store.put_opts(&path, PutPayload::new(), PutMode::Create.into());
+    match put().await {
+        Ok(_) => {}
+        Err(object_store::Error::NotImplemented { .. }) => return Ok(false),
+        Err(e) => return Err(e.into()),
+    }
+    let supported = match put().await {
+        Err(
+            object_store::Error::AlreadyExists { .. } | object_store::Error::Precondition { .. },
+        ) => Ok(true),
+        Ok(_) | Err(object_store::Error::NotImplemented { .. }) => Ok(false),
+        Err(e) => Err(e.into()),
+    };
+    if let Err(e) = store.delete(&path).await {
+        log::warn!("Failed to delete conditional put probe {}: {}", path, e);
+    }
+    supported
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use object_store::memory::InMemory;
+
+    fn options(pairs: &[(&str, &str)]) -> StorageOptions {
+        StorageOptions(
+            pairs
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_parse_mode() {
+        let mode = |value| S3CompatibilityMode::from_storage_options(&options(&[value]));
+        assert_eq!(
+            mode(("s3_compatibility_mode", "MinIO")).unwrap(),
+            Some(S3CompatibilityMode::Minio)
+        );
+        assert_eq!(
+            mode(("s3_compatibility_mode", "ceph")).unwrap(),
+            Some(S3CompatibilityMode::Ceph)
+        );
+        assert_eq!(mode(("aws_region", "eu-west-1")).unwrap(), None);
+        let err = mode(("s3_compatibility_mode", "r2")).unwrap_err();
+        assert!(err.to_string().contains("'minio', 'ceph' or 'generic'"));
+    }
+
+    #[test]
+    fn test_apply_bundle() {
+        let mut storage_options = options(&[
+            ("aws_endpoint", "http://localhost:9000"),
+            ("aws_checksum_algorithm", "sha256"),
+        ]);
+        S3CompatibilityMode::Minio.apply(&mut storage_options);
+        let s3_options = storage_options.as_s3_options();
+        assert_eq!(
+            s3_options[&AmazonS3ConfigKey::VirtualHostedStyleRequest],
+            "false"
+        );
+        assert_eq!(s3_options[&AmazonS3ConfigKey::Region], DEFAULT_REGION);
+        assert!(storage_options.allow_http());
+        assert!(!s3_options.contains_key(&AmazonS3ConfigKey::Checksum));
+
+        // Explicit options win, and HTTPS endpoints don't allow HTTP
+        let mut storage_options = options(&[
+            ("aws_endpoint", "https://ceph.internal"),
+            ("region", "default"),
+            ("virtual_hosted_style_request", "true"),
+        ]);
+        S3CompatibilityMode::Ceph.apply(&mut storage_options);
+        let s3_options = storage_options.as_s3_options();
+        assert_eq!(s3_options[&AmazonS3ConfigKey::Region], "default");
+        assert_eq!(
+            s3_options[&AmazonS3ConfigKey::VirtualHostedStyleRequest],
+            "true"
+        );
+        assert!(!storage_options.allow_http());
+    }
+
+    /// A store that ignores the put mode, like stores without `If-None-Match`
+    #[derive(Debug, Default)]
+    struct UnconditionalStore(InMemory);
+
+    impl std::fmt::Display for UnconditionalStore {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "UnconditionalStore")
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl OSObjectStore for UnconditionalStore {
+        async fn put_opts(
+            &self,
+            location: &Path,
+            payload: PutPayload,
+            mut opts: object_store::PutOptions,
+        ) -> object_store::Result<object_store::PutResult> {
+            opts.mode = PutMode::Overwrite;
+            self.0.put_opts(location, payload, opts).await
+        }
+
+        async fn put_multipart_opts(
+            &self,
+            location: &Path,
+            opts: object_store::PutMultipartOptions,
+        ) -> object_store::Result<Box<dyn object_store::MultipartUpload>> {
+            self.0.put_multipart_opts(location, opts).await
+        }
+
+        async fn get_opts(
+            &self,
+            location: &Path,
+            options: object_store::GetOptions,
+        ) -> object_store::Result<object_store::GetResult> {
+            self.0.get_opts(location, options).await
+        }
+
+        fn delete_stream(
+            &self,
+            locations: futures::stream::BoxStream<'static, object_store::Result<Path>>,
+        ) -> futures::stream::BoxStream<'static, object_store::Result<Path>> {
+            self.0.delete_stream(locations)
+        }
+
+        fn list(
+            &self,
+            prefix: Option<&Path>,
+        ) -> futures::stream::BoxStream<'static, object_store::Result<object_store::ObjectMeta>>
+        {
+            self.0.list(prefix)
+        }
+
+        async fn list_with_delimiter(
+            &self,
+            prefix: Option<&Path>,
+        ) -> object_store::Result<object_store::ListResult> {
+            self.0.list_with_delimiter(prefix).await
+        }
+
+        async fn copy_opts(
+            &self,
+            from: &Path,
+            to: &Path,
+            options: object_store::CopyOptions,
+        ) -> object_store::Result<()> {
+            self.0.copy_opts(from, to, options).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_probe_conditional_put() {
+        use futures::TryStreamExt;
+
+        let base = Path::from("datasets/probe");
+        let store = InMemory::new();
+        assert!(probe_conditional_put(&store, &base).await.unwrap());
+        assert!(
+            store
+                .list(None)
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        let store = UnconditionalStore::default();
+        assert!(!probe_conditional_put(&store, &base).await.unwrap());
+        assert!(
+            store
+                .list(None)
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        // Results are cached by endpoint
+        let endpoint = "http://unconditional.test:9000/bucket";
+        assert_eq!(
+            conditional_put_support(&store, endpoint, &base).await,
+            Some(false)
+        );
+        assert_eq!(
+            conditional_put_support(&InMemory::new(), endpoint, &base).await,
+            Some(false)
+        );
+    }
+}
@@ -0,0 +1,855 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Credentials served by the metadata endpoints of containers and instances.
+//!
+//! On ECS, EKS and EC2 the credentials of the role come from a local endpoint
+//! and expire after a few hours. The source is picked like the AWS SDKs do:
+//!
+//! 1. ECS task role, when `AWS_CONTAINER_CREDENTIALS_RELATIVE_URI` is set.
+//! 2. EKS Pod Identity, when `AWS_CONTAINER_CREDENTIALS_FULL_URI` points at
+//!    the pod identity agent, or ECS for any other full URI. The request is
+//!    authorized with `AWS_CONTAINER_AUTHORIZATION_TOKEN_FILE`, which is read
+//!    again for every request since the agent rotates it, or
+//!    `AWS_CONTAINER_AUTHORIZATION_TOKEN`.
+//!
+//! Otherwise, or when `AWS_WEB_IDENTITY_TOKEN_FILE` is set for IAM roles for
+//! service accounts, the default AWS credential chain is used, which ends with the
+//! instance metadata service. The [`CREDENTIAL_SOURCE_KEY`] storage option
+//! forces a source, including `imds` for the instance metadata service
+//! (IMDSv2) on `AWS_EC2_METADATA_SERVICE_ENDPOINT` or its default address.
+//!
+//! A [`ContainerCredentialProvider`] refreshes the credentials in a background
+//! task ahead of their expiry, and is shared by all the S3 stores of an
+//! [`ObjectStoreRegistry`](crate::object_store::providers::ObjectStoreRegistry),
+//! so of a session. If a refresh fails the current credentials keep being used,
+//! and the refresh retried, until they expire.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use http::{Method, Request, StatusCode};
+use lance_core::error::{Error, Result};
+use object_store::aws::AwsCredential;
+use object_store::client::{HttpClient, HttpConnector, HttpRequestBody, ReqwestConnector};
+use object_store::{ClientOptions, CredentialProvider, Result as ObjectStoreResult};
+use tokio::task::JoinHandle;
+use url::Url;
+
+use crate::object_store::interceptor::{InterceptingConnector, RequestInterceptor};
+
+/// Storage option forcing the source of the credentials, one of
+/// `pod_identity`, `ecs` or `imds`.
+pub const CREDENTIAL_SOURCE_KEY: &str = "aws_credential_source";
+
+const ECS_ENDPOINT: &str = "http://169.254.170.2";
+const IMDS_ENDPOINT: &str = "http://169.254.169.254";
+const POD_IDENTITY_HOSTS: [&str; 2] = ["169.254.170.23", "[fd00:ec2::23]"];
+const IMDS_TOKEN_TTL_HEADER: &str = "x-aws-ec2-metadata-token-ttl-seconds";
+const IMDS_TOKEN_HEADER: &str = "x-aws-ec2-metadata-token";
+const IMDS_TOKEN_TTL_SECONDS: u64 = 21600;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long to wait before retrying a failed refresh.
+const REFRESH_RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// A source of container or instance credentials, see the
+/// [module documentation](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CredentialSource {
+    PodIdentity,
+    Ecs,
+    Imds,
+}
+
+impl FromStr for CredentialSource {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "pod_identity" => Ok(Self::PodIdentity),
+            "ecs" => Ok(Self::Ecs),
+            "imds" => Ok(Self::Imds),
+            _ => Err(Error::invalid_input(format!(
+                "Invalid {CREDENTIAL_SOURCE_KEY} '{s}', expected one of pod_identity, ecs or imds"
+            ))),
+        }
+    }
+}
+
+/// The environment variables that configure the credential endpoints.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CredentialEnv {
+    pub relative_uri: Option<String>,
+    pub full_uri: Option<String>,
+    pub authorization_token: Option<String>,
+    pub authorization_token_file: Option<String>,
+    pub imds_endpoint: Option<String>,
+    pub imds_disabled: bool,
+    pub web_identity: bool,
+}
+
+impl CredentialEnv {
+    pub fn from_env() -> Self {
+        let var = |name| std::env::var(name).ok().filter(|value| !value.is_empty());
+        Self {
+            relative_uri: var("AWS_CONTAINER_CREDENTIALS_RELATIVE_URI"),
+            full_uri: var("AWS_CONTAINER_CREDENTIALS_FULL_URI"),
+            authorization_token: var("AWS_CONTAINER_AUTHORIZATION_TOKEN"),
+            authorization_token_file: var("AWS_CONTAINER_AUTHORIZATION_TOKEN_FILE"),
+            imds_endpoint: var("AWS_EC2_METADATA_SERVICE_ENDPOINT"),
+            imds_disabled: var("AWS_EC2_METADATA_DISABLED")
+                .is_some_and(|value| value.eq_ignore_ascii_case("true")),
+            web_identity: var("AWS_WEB_IDENTITY_TOKEN_FILE").is_some(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Authorization {
+    Token(String),
+    /// A file with the token, read for every request
+    TokenFile(String),
+}
+
+/// Where and how to fetch the credentials of a [`CredentialSource`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct CredentialEndpoint {
+    source: CredentialSource,
+    /// The URL of the credentials, or the base URL of the service for IMDS
+    url: Url,
+    authorization: Option<Authorization>,
+}
+
+impl CredentialEndpoint {
+    /// The endpoint of the `forced` source, or of the source `env` configures.
+    ///
+    /// Returns `None` when nothing is forced and `env` configures no container
+    /// credentials, the default credential chain applies then.
+    pub fn resolve(env: &CredentialEnv, forced: Option<CredentialSource>) -> Result<Option<Self>> {
+        let authorization = env
+            .authorization_token_file
+            .clone()
+            .map(Authorization::TokenFile)
+            .or_else(|| env.authorization_token.clone().map(Authorization::Token));
+        let container = if let Some(relative_uri) = &env.relative_uri {
+            let url = parse_url(&format!("{ECS_ENDPOINT}{relative_uri}"))?;
+            Some((CredentialSource::Ecs, url))
+        } else if let Some(full_uri) = &env.full_uri {
+            let url = parse_url(full_uri)?;
+            check_full_uri(&url)?;
+            let source = if url
+                .host_str()
+                .is_some_and(|host| POD_IDENTITY_HOSTS.contains(&host))
+            {
+                CredentialSource::PodIdentity
+            } else {
+                CredentialSource::Ecs
+            };
+            Some((source, url))
+        } else {
+            None
+        };
+
+        match (forced, container) {
+            (Some(CredentialSource::Imds), _) => {
+                if env.imds_disabled {
+                    return Err(Error::invalid_input(format!(
+                        "{CREDENTIAL_SOURCE_KEY} is imds but AWS_EC2_METADATA_DISABLED is set"
+                    )));
+                }
+                let url = parse_url(env.imds_endpoint.as_deref().unwrap_or(IMDS_ENDPOINT))?;
+                Ok(Some(Self {
+                    source: CredentialSource::Imds,
+                    url,
+                    authorization: None,
+                }))
+            }
+            // The default chain tries web identity before container credentials
+            (None, _) if env.web_identity => Ok(None),
+            (None, None) => Ok(None),
+            (Some(forced), None) => Err(Error::invalid_input(format!(
+                "{CREDENTIAL_SOURCE_KEY} is {forced:?} but neither \
+                 AWS_CONTAINER_CREDENTIALS_RELATIVE_URI nor AWS_CONTAINER_CREDENTIALS_FULL_URI is set"
+            ))),
+            (forced, Some((source, url))) => {
+                // A full URI is the pod identity agent for EKS, whatever its host
+                let source = match (forced, &env.full_uri) {
+                    (Some(CredentialSource::PodIdentity), Some(_))
+                        if env.relative_uri.is_none() =>
+                    {
+                        CredentialSource::PodIdentity
+                    }
+                    (Some(forced), _) if forced != source => {
+                        return Err(Error::invalid_input(format!(
+                            "{CREDENTIAL_SOURCE_KEY} is {forced:?} but the environment \
+                             configures {source:?} credentials"
+                        )));
+                    }
+                    _ => source,
+                };
+                if source == CredentialSource::PodIdentity
+                    && !matches!(authorization, Some(Authorization::TokenFile(_)))
+                {
+                    return Err(Error::invalid_input(
+                        "EKS Pod Identity credentials need AWS_CONTAINER_AUTHORIZATION_TOKEN_FILE",
+                    ));
+                }
+                Ok(Some(Self {
+                    source,
+                    url,
+                    authorization,
+                }))
+            }
+        }
+    }
+}
+
+fn parse_url(url: &str) -> Result<Url> {
+    Url::parse(url)
+        .map_err(|e| Error::invalid_input(format!("Invalid credentials endpoint '{url}': {e}")))
+}
+
+/// Plain HTTP full URIs must stay on the host or the container endpoints, as
+/// the AWS SDKs require, so that credentials are not sent over the network.
+fn check_full_uri(url: &Url) -> Result<()> {
+    let host = url.host_str().unwrap_or_default();
+    let allowed = url.scheme() == "https"
+        || host == "localhost"
+        || host == "169.254.170.2"
+        || POD_IDENTITY_HOSTS.contains(&host)
+        || match url.host() {
+            Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+            Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+            _ => false,
+        };
+    if allowed {
+        Ok(())
+    } else {
+        Err(Error::invalid_input(format!(
+            "AWS_CONTAINER_CREDENTIALS_FULL_URI must use https or a local host, got {url}"
+        )))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CachedCredential {
+    credential: Arc<AwsCredential>,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+/// Credentials of a [`CredentialEndpoint`], refreshed in the background, see
+/// the [module documentation](self).
+#[derive(Debug)]
+pub struct ContainerCredentialProvider {
+    endpoint: CredentialEndpoint,
+    client: HttpClient,
+    refresh_offset: Duration,
+    cached: tokio::sync::RwLock<Option<CachedCredential>>,
+    // Held while fetching, so concurrent callers share one fetch
+    fetching: tokio::sync::Mutex<()>,
+    // When the last fetch failed, if it did
+    failed_at: Mutex<Option<Instant>>,
+    background_refresh: OnceLock<JoinHandle<()>>,
+}
+
+impl ContainerCredentialProvider {
+    /// Create a provider and start refreshing its credentials `refresh_offset`
+    /// before they expire.
+    ///
+    /// The requests go through `interceptor`, if any. Must be called within a
+    /// tokio runtime.
+    pub(crate) fn try_new(
+        endpoint: CredentialEndpoint,
+        refresh_offset: Duration,
+        interceptor: Option<Arc<dyn RequestInterceptor>>,
+    ) -> Result<Arc<Self>> {
+        let options = ClientOptions::new()
+            .with_allow_http(true)
+            .with_timeout(REQUEST_TIMEOUT);
+        let client = match interceptor {
+            Some(interceptor) => InterceptingConnector::new(interceptor).connect(&options)?,
+            None => ReqwestConnector::default().connect(&options)?,
+        };
+        let provider = Arc::new(Self {
+            endpoint,
+            client,
+            refresh_offset,
+            cached: tokio::sync::RwLock::new(None),
+            fetching: tokio::sync::Mutex::new(()),
+            failed_at: Mutex::new(None),
+            background_refresh: OnceLock::new(),
+        });
+        let task = tokio::spawn(Self::background_refresh(Arc::downgrade(&provider)));
+        provider
+            .background_refresh
+            .set(task)
+            .expect("the task is only set here");
+        Ok(provider)
+    }
+
+    async fn background_refresh(provider: Weak<Self>) {
+        loop {
+            let delay = {
+                let Some(provider) = provider.upgrade() else {
+                    return;
+                };
+                let refreshed = match provider.cached_unless_due().await {
+                    Some(cached) => Ok(cached),
+                    None => provider.refresh().await,
+                };
+                match refreshed {
+                    Ok(cached) => match cached.expires_at {
+                        Some(expires_at) => (expires_at - Utc::now())
+                            .to_std()
+                            .unwrap_or_default()
+                            .saturating_sub(provider.refresh_offset),
+                        // Credentials without expiry are never refreshed
+                        None => return,
+                    },
+                    Err(e) => {
+                        log::warn!(
+                            "Background refresh of {:?} credentials failed: {}",
+                            provider.endpoint.source,
+                            e
+                        );
+                        REFRESH_RETRY_DELAY
+                    }
+                }
+            };
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// The cached credentials if they are not due for a refresh, or fresh ones.
+    ///
+    /// When the refresh fails the cached credentials are returned until they
+    /// expire, and the refresh is not retried for [`REFRESH_RETRY_DELAY`].
+    async fn credential(&self) -> Result<CachedCredential> {
+        if let Some(cached) = self.cached_unless_due().await {
+            return Ok(cached);
+        }
+        let cached = self.cached.read().await.clone();
+        let unexpired = cached.filter(|cached| cached.expires_at.is_none_or(|at| at > Utc::now()));
+        let recently_failed = self
+            .failed_at
+            .lock()
+            .unwrap()
+            .is_some_and(|at| at.elapsed() < REFRESH_RETRY_DELAY);
+        match unexpired {
+            Some(cached) if recently_failed => Ok(cached),
+            Some(cached) => match self.refresh().await {
+                Ok(refreshed) => Ok(refreshed),
+                Err(e) => {
+                    log::warn!(
+                        "Failed to refresh {:?} credentials, using the current ones until they expire: {}",
+                        self.endpoint.source,
+                        e
+                    );
+                    Ok(cached)
+                }
+            },
+            None => self.refresh().await,
+        }
+    }
+
+    /// Fetch the credentials, unless another caller refreshed them meanwhile
+    async fn refresh(&self) -> Result<CachedCredential> {
+        let _fetching = self.fetching.lock().await;
+        if let Some(cached) = self.cached_unless_due().await {
+            return Ok(cached);
+        }
+        match self.fetch().await {
+            Ok(fetched) => {
+                *self.cached.write().await = Some(fetched.clone());
+                *self.failed_at.lock().unwrap() = None;
+                Ok(fetched)
+            }
+            Err(e) => {
+                *self.failed_at.lock().unwrap() = Some(Instant::now());
+                Err(e)
+            }
+        }
+    }
+
+    async fn cached_unless_due(&self) -> Option<CachedCredential> {
+        let cached = self.cached.read().await.clone()?;
+        let due = cached
+            .expires_at
+            .is_some_and(|at| at - self.refresh_offset <= Utc::now());
+        (!due).then_some(cached)
+    }
+
+    async fn fetch(&self) -> Result<CachedCredential> {
+        let body = match self.endpoint.source {
+            CredentialSource::Imds => self.fetch_imds().await?,
+            CredentialSource::Ecs | CredentialSource::PodIdentity => {
+                let authorization = match &self.endpoint.authorization {
+                    Some(Authorization::Token(token)) => Some(token.clone()),
+                    Some(Authorization::TokenFile(path)) => Some(
+                        tokio::fs::read_to_string(path)
+                            .await
+                            .map_err(|e| {
+                                Error::io(format!(
+                                    "Failed to read the authorization token file {path}: {e}"
+                                ))
+                            })?
+                            .trim()
+                            .to_string(),
+                    ),
+                    None => None,
+                };
+                let headers = authorization
+                    .iter()
+                    .map(|token| (http::header::AUTHORIZATION.as_str(), token.as_str()))
+                    .collect::<Vec<_>>();
+                self.send(Method::GET, self.endpoint.url.clone(), &headers)
+                    .await?
+            }
+        };
+        parse_credential(&body)
+    }
+
+    /// Fetch the credentials of the instance role with the IMDSv2 handshake
+    async fn fetch_imds(&self) -> Result<String> {
+        let base = &self.endpoint.url;
+        let join = |path: &str| {
+            base.join(path)
+                .map_err(|e| Error::invalid_input(format!("Invalid IMDS endpoint {base}: {e}")))
+        };
+        let ttl = IMDS_TOKEN_TTL_SECONDS.to_string();
+        let token = self
+            .send(
+                Method::PUT,
+                join("/latest/api/token")?,
+                &[(IMDS_TOKEN_TTL_HEADER, &ttl)],
+            )
+            .await?;
+        let headers = [(IMDS_TOKEN_HEADER, token.trim())];
+        let roles_path = "/latest/meta-data/iam/security-credentials/";
+        let roles = self.send(Method::GET, join(roles_path)?, &headers).await?;
+        let role = roles
+            .lines()
+            .next()
+            .filter(|role| !role.is_empty())
+            .ok_or_else(|| Error::io("The instance has no IAM role"))?;
+        self.send(Method::GET, join(&format!("{roles_path}{role}"))?, &headers)
+            .await
+    }
+
+    async fn send(&self, method: Method, url: Url, headers: &[(&str, &str)]) -> Result<String> {
+        let mut request = Request::builder().method(method.clone()).uri(url.as_str());
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        let request = request
+            .body(HttpRequestBody::empty())
+            .map_err(|e| Error::invalid_input(format!("Invalid request to {url}: {e}")))?;
+        let response = self
+            .client
+            .execute(request)
+            .await
+            .map_err(|e| Error::io(format!("{method} {url} failed: {e}")))?;
+        let status = response.status();
+        let body = response
+            .into_body()
+            .bytes()
+            .await
+            .map_err(|e| Error::io(format!("{method} {url} failed: {e}")))?;
+        let body = String::from_utf8_lossy(&body).into_owned();
+        if status != StatusCode::OK {
+            return Err(Error::io(format!(
+                "{method} {url} failed with status {status}: {body}"
+            )));
+        }
+        Ok(body)
+    }
+}
+
+impl Drop for ContainerCredentialProvider {
+    fn drop(&mut self) {
+        if let Some(task) = self.background_refresh.get() {
+            task.abort();
+        }
+    }
+}
+
+fn parse_credential(body: &str) -> Result<CachedCredential> {
+    let invalid = |message: &str| Error::io(format!("Invalid credentials response: {message}"));
+    let json =
+        serde_json::from_str::<serde_json::Value>(body).map_err(|e| invalid(&e.to_string()))?;
+    let field = |name: &str| json.get(name).and_then(|value| value.as_str());
+    let required = |name: &str| {
+        field(name)
+            .map(str::to_string)
+            .ok_or_else(|| invalid(&format!("missing {name}")))
+    };
+    let expires_at = field("Expiration")
+        .map(|at| {
+            DateTime::parse_from_rfc3339(at)
+                .map(|at| at.with_timezone(&Utc))
+                .map_err(|e| invalid(&format!("Expiration '{at}': {e}")))
+        })
+        .transpose()?;
+    Ok(CachedCredential {
+        credential: Arc::new(AwsCredential {
+            key_id: required("AccessKeyId")?,
+            secret_key: required("SecretAccessKey")?,
+            token: field("Token").map(str::to_string),
+        }),
+        expires_at,
+    })
+}
+
+#[async_trait::async_trait]
+impl CredentialProvider for ContainerCredentialProvider {
+    type Credential = AwsCredential;
+
+    async fn get_credential(&self) -> ObjectStoreResult<Arc<AwsCredential>> {
+        self.credential()
+            .await
+            .map(|cached| cached.credential)
+            .map_err(|e| object_store::Error::Generic {
+                store: "ContainerCredentialProvider",
+                source: Box::new(e),
+            })
+    }
+}
+
+type ProviderKey = (CredentialEndpoint, Duration, Option<usize>);
+
+/// The [`ContainerCredentialProvider`]s in use by the stores of a registry.
+///
+/// Stores with the same endpoint, refresh offset and interceptor share a
+/// provider. Like the registry's store cache it holds weak references, so a
+/// provider, and its refresh, stops once no store uses it.
+#[derive(Debug, Default)]
+pub(crate) struct ContainerCredentialCache {
+    providers: Mutex<HashMap<ProviderKey, Weak<ContainerCredentialProvider>>>,
+}
+
+impl ContainerCredentialCache {
+    pub fn get_or_create(
+        &self,
+        endpoint: CredentialEndpoint,
+        refresh_offset: Duration,
+        interceptor: Option<&Arc<dyn RequestInterceptor>>,
+    ) -> Result<Arc<ContainerCredentialProvider>> {
+        let key = (
+            endpoint,
+            refresh_offset,
+            interceptor.map(|interceptor| Arc::as_ptr(interceptor) as *const () as usize),
+        );
+        let mut providers = self.providers.lock().unwrap();
+        if let Some(provider) = providers.get(&key).and_then(Weak::upgrade) {
+            return Ok(provider);
+        }
+        providers.retain(|_, provider| provider.strong_count() > 0);
+        let provider = ContainerCredentialProvider::try_new(
+            key.0.clone(),
+            refresh_offset,
+            interceptor.cloned(),
+        )?;
+        providers.insert(key, Arc::downgrade(&provider));
+        Ok(provider)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    /// A request received by [`serve`]: method, path and lowercase headers
+    type Received = (String, String, HashMap<String, String>);
+
+    /// Serve HTTP on a local port, answering every request with the status and
+    /// body `handler` returns. Returns the base URL of the server.
+    async fn serve(handler: impl Fn(&Received) -> (u16, String) + Send + Sync + 'static) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handler = Arc::new(handler);
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let handler = handler.clone();
+                tokio::spawn(async move {
+                    let mut buf = Vec::new();
+                    while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+                        let mut chunk = [0; 1024];
+                        let n = socket.read(&mut chunk).await.unwrap();
+                        if n == 0 {
+                            return;
+                        }
+                        buf.extend_from_slice(&chunk[..n]);
+                    }
+                    let head = String::from_utf8_lossy(&buf).into_owned();
+                    let mut lines = head.lines();
+                    let mut request_line = lines.next().unwrap().split(' ');
+                    let method = request_line.next().unwrap().to_string();
+                    let path = request_line.next().unwrap().to_string();
+                    let headers = lines
+                        .filter_map(|line| line.split_once(": "))
+                        .map(|(name, value)| (name.to_ascii_lowercase(), value.to_string()))
+                        .collect();
+                    let (status, body) = handler(&(method, path, headers));
+                    let response = format!(
+                        "HTTP/1.1 {status} X\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                    socket.write_all(response.as_bytes()).await.unwrap();
+                    socket.shutdown().await.ok();
+                });
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    fn credential_json(key_id: &str, expires_in: Duration) -> String {
+        let expiration = Utc::now() + expires_in;
+        serde_json::json!({
+            "AccessKeyId": key_id,
+            "SecretAccessKey": "secret",
+            "Token": "token",
+            "Expiration": expiration.to_rfc3339(),
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_imds_token_handshake() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let url = serve({
+            let received = received.clone();
+            move |request: &Received| {
+                let (method, path, headers) = request;
+                received.lock().unwrap().push(format!("{method} {path}"));
+                match (method.as_str(), path.as_str()) {
+                    ("PUT", "/latest/api/token") => {
+                        assert_eq!(headers[IMDS_TOKEN_TTL_HEADER], "21600");
+                        (200, "imds-token".to_string())
+                    }
+                    _ if headers.get(IMDS_TOKEN_HEADER).map(String::as_str)
+                        != Some("imds-token") =>
+                    {
+                        (401, String::new())
+                    }
+                    ("GET", "/latest/meta-data/iam/security-credentials/") => {
+                        (200, "my-role\n".to_string())
+                    }
+                    ("GET", "/latest/meta-data/iam/security-credentials/my-role") => {
+                        (200, credential_json("AKID", Duration::from_secs(3600)))
+                    }
+                    _ => (404, String::new()),
+                }
+            }
+        })
+        .await;
+
+        let env = CredentialEnv {
+            imds_endpoint: Some(url),
+            ..Default::default()
+        };
+        let endpoint = CredentialEndpoint::resolve(&env, Some(CredentialSource::Imds))
+            .unwrap()
+            .unwrap();
+        let provider =
+            ContainerCredentialProvider::try_new(endpoint, Duration::from_secs(60), None).unwrap();
+        let credential = provider.get_credential().await.unwrap();
+        assert_eq!(credential.key_id, "AKID");
+        assert_eq!(credential.secret_key, "secret");
+        assert_eq!(credential.token.as_deref(), Some("token"));
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec![
+                "PUT /latest/api/token",
+                "GET /latest/meta-data/iam/security-credentials/",
+                "GET /latest/meta-data/iam/security-credentials/my-role",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refresh_before_expiry() {
+        let token_file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(token_file.path(), "pod-token\n").unwrap();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let url = serve({
+            let calls = calls.clone();
+            move |(_, path, headers): &Received| {
+                assert_eq!(path, "/v1/credentials");
+                assert_eq!(headers["authorization"], "pod-token");
+                let call = calls.fetch_add(1, Ordering::SeqCst) + 1;
+                (
+                    200,
+                    credential_json(&format!("AKID{call}"), Duration::from_secs(3)),
+                )
+            }
+        })
+        .await;
+
+        let env = CredentialEnv {
+            full_uri: Some(format!("{url}/v1/credentials")),
+            authorization_token_file: Some(token_file.path().to_str().unwrap().to_string()),
+            ..Default::default()
+        };
+        let endpoint = CredentialEndpoint::resolve(&env, Some(CredentialSource::PodIdentity))
+            .unwrap()
+            .unwrap();
+        let cache = ContainerCredentialCache::default();
+        let provider = cache
+            .get_or_create(endpoint.clone(), Duration::from_secs(2), None)
+            .unwrap();
+        let shared = cache
+            .get_or_create(endpoint, Duration::from_secs(2), None)
+            .unwrap();
+        assert!(Arc::ptr_eq(&provider, &shared));
+
+        // The first fetch is made by the background task
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(provider.get_credential().await.unwrap().key_id, "AKID1");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // And it refreshes the credentials 2s before they expire, without
+        // callers waiting on the fetch
+        tokio::time::sleep(Duration::from_millis(1200)).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(shared.get_credential().await.unwrap().key_id, "AKID2");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // The refresh stops with the last user of the provider
+        drop(provider);
+        drop(shared);
+        tokio::time::sleep(Duration::from_millis(1200)).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failed_refresh_keeps_credentials_until_expiry() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let url = serve({
+            let calls = calls.clone();
+            move |_: &Received| {
+                if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                    (200, credential_json("AKID", Duration::from_secs(2)))
+                } else {
+                    (500, "unavailable".to_string())
+                }
+            }
+        })
+        .await;
+
+        let env = CredentialEnv {
+            full_uri: Some(format!("{url}/creds")),
+            authorization_token: Some("ecs-token".to_string()),
+            ..Default::default()
+        };
+        let endpoint = CredentialEndpoint::resolve(&env, None).unwrap().unwrap();
+        assert_eq!(endpoint.source, CredentialSource::Ecs);
+        let provider =
+            ContainerCredentialProvider::try_new(endpoint, Duration::from_millis(1500), None)
+                .unwrap();
+        assert_eq!(provider.get_credential().await.unwrap().key_id, "AKID");
+
+        // Due for a refresh, which fails and is not retried right away
+        tokio::time::sleep(Duration::from_millis(800)).await;
+        assert_eq!(provider.get_credential().await.unwrap().key_id, "AKID");
+        assert_eq!(provider.get_credential().await.unwrap().key_id, "AKID");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert!(provider.get_credential().await.is_err());
+    }
+
+    #[test]
+    fn test_endpoint_selection_precedence() {
+        let pod_identity_uri = "http://169.254.170.23/v1/credentials";
+        let resolve = |env: &CredentialEnv, forced: Option<&str>| {
+            let forced = forced.map(|forced| forced.parse().unwrap());
+            CredentialEndpoint::resolve(env, forced).map(|endpoint| {
+                endpoint.map(|endpoint| (endpoint.source, endpoint.url.to_string()))
+            })
+        };
+
+        // Nothing configured leaves it to the default chain
+        let env = CredentialEnv::default();
+        assert_eq!(resolve(&env, None).unwrap(), None);
+        assert_eq!(
+            resolve(&env, Some("imds")).unwrap(),
+            Some((
+                CredentialSource::Imds,
+                "http://169.254.169.254/".to_string()
+            ))
+        );
+        assert!(resolve(&env, Some("ecs")).is_err());
+
+        // The pod identity agent is found by its address
+        let pod_env = CredentialEnv {
+            full_uri: Some(pod_identity_uri.to_string()),
+            authorization_token_file: Some("/var/run/token".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            resolve(&pod_env, None).unwrap(),
+            Some((CredentialSource::PodIdentity, pod_identity_uri.to_string()))
+        );
+        assert!(resolve(&pod_env, Some("ecs")).is_err());
+        let web_identity = CredentialEnv {
+            web_identity: true,
+            ..pod_env.clone()
+        };
+        assert_eq!(resolve(&web_identity, None).unwrap(), None);
+        assert_eq!(
+            resolve(&web_identity, Some("pod_identity"))
+                .unwrap()
+                .unwrap()
+                .0,
+            CredentialSource::PodIdentity
+        );
+        // Forcing IMDS wins over the environment
+        assert_eq!(
+            resolve(&pod_env, Some("imds")).unwrap().unwrap().0,
+            CredentialSource::Imds
+        );
+
+        // The relative URI of ECS takes precedence over a full URI
+        let ecs_env = CredentialEnv {
+            relative_uri: Some("/v2/credentials/abc".to_string()),
+            ..pod_env.clone()
+        };
+        assert_eq!(
+            resolve(&ecs_env, None).unwrap(),
+            Some((
+                CredentialSource::Ecs,
+                "http://169.254.170.2/v2/credentials/abc".to_string()
+            ))
+        );
+        assert!(resolve(&ecs_env, Some("pod_identity")).is_err());
+
+        // Pod identity needs its token file, and full URIs must stay local
+        let no_token = CredentialEnv {
+            authorization_token_file: None,
+            authorization_token: Some("token".to_string()),
+            ..pod_env
+        };
+        assert!(resolve(&no_token, None).is_err());
+        let remote = CredentialEnv {
+            full_uri: Some("http://example.com/creds".to_string()),
+            ..Default::default()
+        };
+        assert!(resolve(&remote, None).is_err());
+
+        let disabled = CredentialEnv {
+            imds_disabled: true,
+            ..Default::default()
+        };
+        assert!(resolve(&disabled, Some("imds")).is_err());
+        assert!("instance".parse::<CredentialSource>().is_err());
+    }
+}
@@ -18,12 +18,17 @@ use object_store::{
 };
 use url::Url;
 
+#[cfg(feature = "diagnose")]
+use crate::object_store::diagnose::DiagnoseTarget;
 use crate::object_store::{
     DEFAULT_CLOUD_BLOCK_SIZE, DEFAULT_CLOUD_IO_PARALLELISM, DEFAULT_MAX_IOP_SIZE, ObjectStore,
     ObjectStoreParams, ObjectStoreProvider, StorageOptions, StorageOptionsAccessor,
     dynamic_credentials::build_dynamic_credential_provider,
+    interceptor::{InterceptingConnector, intercept_operator},
+    part_retry::{PartRetryLayer, PartRetryStore},
     throttle::{AimdThrottleConfig, AimdThrottledStore},
 };
+use crate::object_writer::UploadLimiter;
 use lance_core::error::{Error, Result};
 
 #[derive(Default, Debug)]
@@ -155,20 +160,12 @@ impl AzureBlobStoreProvider {
         }
     }
 
-    async fn build_opendal_azure_store(
-        &self,
-        base_path: &Url,
-        storage_options: &StorageOptions,
-    ) -> Result<Arc<dyn OSObjectStore>> {
-        let operator = Self::build_opendal_operator(base_path, storage_options)?;
-        Ok(Arc::new(OpendalStore::new(operator)))
-    }
-
     async fn build_microsoft_azure_store(
         &self,
         base_path: &Url,
         storage_options: &StorageOptions,
         accessor: Option<Arc<StorageOptionsAccessor>>,
+        params: &ObjectStoreParams,
     ) -> Result<Arc<dyn OSObjectStore>> {
         // Use a low retry count since the AIMD throttle layer handles
         // throttle recovery with its own retry loop.
@@ -191,8 +188,14 @@ impl AzureBlobStoreProvider {
         {
             builder = builder.with_credentials(credentials);
         }
+        if let Some(interceptor) = &params.request_interceptor {
+            builder = builder.with_http_connector(InterceptingConnector::new(interceptor.clone()));
+        }
 
-        Ok(Arc::new(builder.build()?) as Arc<dyn OSObjectStore>)
+        Ok(Arc::new(
+            PartRetryStore::new(builder.build()?, storage_options.part_upload_retries())
+                .with_retry_classifier(params.is_retryable.clone()),
+        ) as Arc<dyn OSObjectStore>)
     }
 
     fn calculate_object_store_prefix_with_env(
@@ -257,14 +260,23 @@ impl ObjectStoreProvider for AzureBlobStoreProvider {
 
         let accessor = params.get_accessor();
 
-        let inner: Arc<dyn OSObjectStore> = if use_opendal {
+        let (inner, opendal_operator): (Arc<dyn OSObjectStore>, _) = if use_opendal {
             // OpenDAL Azure intentionally uses static/environment-backed configuration only.
             // Namespace-vended dynamic credentials are supported on the native object_store path.
-            self.build_opendal_azure_store(&base_path, &storage_options)
-                .await?
+            let operator = intercept_operator(
+                Self::build_opendal_operator(&base_path, &storage_options)?,
+                params.request_interceptor.as_ref(),
+            )
+            .layer(PartRetryLayer::from_params(params));
+            (
+                Arc::new(OpendalStore::new(operator.clone())),
+                Some(operator),
+            )
         } else {
-            self.build_microsoft_azure_store(&base_path, &storage_options, accessor)
-                .await?
+            let store = self
+                .build_microsoft_azure_store(&base_path, &storage_options, accessor, params)
+                .await?;
+            (store, None)
         };
         let throttle_config = AimdThrottleConfig::from_storage_options(params.storage_options())?;
         let inner = if throttle_config.is_disabled() {
@@ -282,9 +294,20 @@ impl ObjectStoreProvider for AzureBlobStoreProvider {
             list_is_lexically_ordered: true,
             io_parallelism: DEFAULT_CLOUD_IO_PARALLELISM,
             download_retry_count,
+            coalesce_gap: storage_options.coalesce_gap(),
+            manifest_discovery_prefix: storage_options.manifest_discovery_prefix(),
+            upload_limiter: UploadLimiter::new(storage_options.max_concurrent_uploads()),
+            read_only: false,
+            conditional_put: None,
+            transparent_compression: None,
+            retry_classifier: None,
+            priority_layer: None,
+            upload_part_size: None,
+            rebind_source: None,
             io_tracker: Default::default(),
             store_prefix: self
                 .calculate_object_store_prefix(&base_path, params.storage_options())?,
+            opendal_operator,
         })
     }
 
@@ -295,6 +318,46 @@ impl ObjectStoreProvider for AzureBlobStoreProvider {
     ) -> Result<String> {
         Self::calculate_object_store_prefix_with_env(url, storage_options, &ENV_OPTIONS.0)
     }
+
+    /// The configured endpoint, or the blob endpoint of the storage account.
+    #[cfg(feature = "diagnose")]
+    fn diagnose_target(
+        &self,
+        base_path: &Url,
+        params: &ObjectStoreParams,
+    ) -> Result<DiagnoseTarget> {
+        let mut storage_options =
+            StorageOptions::new(params.storage_options().cloned().unwrap_or_default());
+        storage_options.with_env_azure();
+        let endpoint = match storage_options
+            .as_azure_options()
+            .get(&AzureConfigKey::Endpoint)
+        {
+            Some(endpoint) => endpoint.clone(),
+            None => {
+                let host = match base_path.authority().split_once('@') {
+                    Some((_, host)) if host.contains('.') => host.to_string(),
+                    Some((_, account)) => format!("{}.blob.core.windows.net", account),
+                    None => {
+                        let account = StorageOptions::find_configured_storage_account(
+                            &storage_options.0,
+                        )
+                        .ok_or_else(|| {
+                            Error::invalid_input(
+                                "Cannot diagnose Azure store: no account name in the URL and no storage account configured",
+                            )
+                        })?;
+                        format!("{}.blob.core.windows.net", account)
+                    }
+                };
+                format!("https://{}/", host)
+            }
+        };
+        let endpoint = Url::parse(&endpoint).map_err(|e| {
+            Error::invalid_input(format!("Invalid Azure endpoint '{}': {}", endpoint, e))
+        })?;
+        Ok(DiagnoseTarget::new(endpoint))
+    }
 }
 
 static ENV_OPTIONS: LazyLock<StorageOptions> = LazyLock::new(StorageOptions::from_env);
@@ -1,35 +1,68 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-FileCopyrightText: Copyright The Lance Authors
 
-use std::{collections::HashMap, str::FromStr, sync::Arc, time::Duration};
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter},
+    ops::Range,
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
 
-use object_store::ObjectStore as OSObjectStore;
+use base64::{Engine, prelude::BASE64_STANDARD};
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use object_store::path::Path;
+use object_store::{ObjectStore as OSObjectStore, ObjectStoreExt};
 use object_store_opendal::OpendalStore;
 use opendal::{Operator, services::Gcs};
+use sha2::{Digest, Sha256};
 
 use object_store::{
-    RetryConfig, StaticCredentialProvider,
-    gcp::{GcpCredential, GoogleCloudStorageBuilder, GoogleConfigKey},
+    CopyMode, CopyOptions, GetOptions, GetResult, ListResult, MultipartUpload, ObjectMeta,
+    PutMultipartOptions, PutOptions, PutPayload, PutResult, RenameOptions, RenameTargetMode,
+    Result as OSResult, RetryConfig, StaticCredentialProvider,
+    gcp::{GcpCredential, GcpCredentialProvider, GoogleCloudStorageBuilder, GoogleConfigKey},
 };
 use url::Url;
 
+#[cfg(feature = "diagnose")]
+use crate::object_store::diagnose::DiagnoseTarget;
 use crate::object_store::{
     DEFAULT_CLOUD_BLOCK_SIZE, DEFAULT_CLOUD_IO_PARALLELISM, DEFAULT_MAX_IOP_SIZE, ObjectStore,
     ObjectStoreParams, ObjectStoreProvider, StorageOptions, StorageOptionsAccessor,
     dynamic_credentials::build_dynamic_credential_provider,
+    interceptor::{InterceptingConnector, intercept_operator},
+    part_retry::{PartRetryLayer, PartRetryStore},
     throttle::{AimdThrottleConfig, AimdThrottledStore},
 };
+use crate::object_writer::UploadLimiter;
 use lance_core::error::{Error, Result};
 
+/// Base64-encoded AES-256 customer-supplied encryption key (CSEK) sent with
+/// every GCS request, so new objects are written encrypted with it.
+pub const GCS_ENCRYPTION_KEY: &str = "google_encryption_key";
+
+/// Base64-encoded SHA-256 of [`GCS_ENCRYPTION_KEY`]. Derived from the key
+/// when omitted and checked against it when given.
+pub const GCS_ENCRYPTION_KEY_SHA256: &str = "google_encryption_key_sha256";
+
+/// Comma-separated list of older encryption keys. Reads that fail with
+/// [`GCS_ENCRYPTION_KEY`] are retried with each of these in order, which
+/// lets a dataset keep being read while its objects are rewritten under a new
+/// key.
+pub const GCS_ENCRYPTION_KEY_CANDIDATES: &str = "google_encryption_key_candidates";
+
 #[derive(Default, Debug)]
 pub struct GcsStoreProvider;
 
 impl GcsStoreProvider {
-    async fn build_opendal_gcs_store(
+    async fn build_opendal_gcs_operator(
         &self,
         base_path: &Url,
         storage_options: &StorageOptions,
-    ) -> Result<Arc<dyn OSObjectStore>> {
+    ) -> Result<Operator> {
         let bucket = base_path
             .host_str()
             .ok_or_else(|| Error::invalid_input("GCS URL must contain bucket name"))?
@@ -48,11 +81,9 @@ impl GcsStoreProvider {
             config_map.insert("root".to_string(), format!("/{}", prefix));
         }
 
-        let operator = Operator::from_iter::<Gcs>(config_map)
+        Ok(Operator::from_iter::<Gcs>(config_map)
             .map_err(|e| Error::invalid_input(format!("Failed to create GCS operator: {:?}", e)))?
-            .finish();
-
-        Ok(Arc::new(OpendalStore::new(operator)) as Arc<dyn OSObjectStore>)
+            .finish())
     }
 
     async fn build_google_cloud_store(
@@ -60,36 +91,68 @@ impl GcsStoreProvider {
         base_path: &Url,
         storage_options: &StorageOptions,
         accessor: Option<Arc<StorageOptionsAccessor>>,
+        params: &ObjectStoreParams,
     ) -> Result<Arc<dyn OSObjectStore>> {
-        // Use a low retry count since the AIMD throttle layer handles
-        // throttle recovery with its own retry loop.
-        let retry_config = RetryConfig {
-            backoff: Default::default(),
-            max_retries: storage_options.client_max_retries(),
-            retry_timeout: Duration::from_secs(storage_options.client_retry_timeout()),
-        };
-
-        let mut builder = GoogleCloudStorageBuilder::new()
-            .with_url(base_path.as_ref())
-            .with_retry(retry_config)
-            .with_client_options(storage_options.client_options()?);
-        for (key, value) in storage_options.as_gcs_options() {
-            builder = builder.with_config(key, value);
-        }
-
-        if let Some(credentials) =
+        let credentials: Option<GcpCredentialProvider> = if let Some(credentials) =
             build_dynamic_credential_provider::<GcpCredential>(accessor).await?
         {
-            builder = builder.with_credentials(credentials);
-        } else if let Some(storage_token) = storage_options.get("google_storage_token") {
-            let credential = GcpCredential {
-                bearer: storage_token.clone(),
+            Some(credentials)
+        } else {
+            storage_options.get("google_storage_token").map(|token| {
+                let credential = GcpCredential {
+                    bearer: token.clone(),
+                };
+                Arc::new(StaticCredentialProvider::new(credential)) as _
+            })
+        };
+
+        let build = |storage_options: &StorageOptions| -> Result<Arc<dyn OSObjectStore>> {
+            // Use a low retry count since the AIMD throttle layer handles
+            // throttle recovery with its own retry loop.
+            let retry_config = RetryConfig {
+                backoff: Default::default(),
+                max_retries: storage_options.client_max_retries(),
+                retry_timeout: Duration::from_secs(storage_options.client_retry_timeout()),
             };
-            let credential_provider = Arc::new(StaticCredentialProvider::new(credential)) as _;
-            builder = builder.with_credentials(credential_provider);
-        }
 
-        Ok(Arc::new(builder.build()?) as Arc<dyn OSObjectStore>)
+            let mut builder = GoogleCloudStorageBuilder::new()
+                .with_url(base_path.as_ref())
+                .with_retry(retry_config)
+                .with_client_options(storage_options.client_options()?);
+            for (key, value) in storage_options.as_gcs_options() {
+                builder = builder.with_config(key, value);
+            }
+            if let Some(credentials) = &credentials {
+                builder = builder.with_credentials(credentials.clone());
+            }
+            if let Some(interceptor) = &params.request_interceptor {
+                builder =
+                    builder.with_http_connector(InterceptingConnector::new(interceptor.clone()));
+            }
+            Ok(Arc::new(
+                PartRetryStore::new(builder.build()?, storage_options.part_upload_retries())
+                    .with_retry_classifier(params.is_retryable.clone()),
+            ) as Arc<dyn OSObjectStore>)
+        };
+
+        let mut keys = storage_options.gcs_encryption_keys()?.into_iter();
+        let Some(key) = keys.next() else {
+            let store = build(storage_options)?;
+            return Ok(Arc::new(CustomerKeyStore {
+                primary: store.clone(),
+                copier: store,
+                candidates: Vec::new(),
+                has_key: false,
+            }));
+        };
+        Ok(Arc::new(CustomerKeyStore {
+            primary: build(&key.apply(storage_options, false))?,
+            copier: build(&key.apply(storage_options, true))?,
+            candidates: keys
+                .map(|key| build(&key.apply(storage_options, false)))
+                .collect::<Result<_>>()?,
+            has_key: true,
+        }))
     }
 }
 
@@ -110,14 +173,29 @@ impl ObjectStoreProvider for GcsStoreProvider {
 
         let accessor = params.get_accessor();
 
-        let inner = if use_opendal {
+        let (inner, opendal_operator) = if use_opendal {
+            if !storage_options.gcs_encryption_keys()?.is_empty() {
+                return Err(Error::not_supported(format!(
+                    "{GCS_ENCRYPTION_KEY} is not supported together with use_opendal"
+                )));
+            }
             // OpenDAL GCS intentionally uses static/environment-backed configuration only.
             // Namespace-vended dynamic credentials are supported on the native object_store path.
-            self.build_opendal_gcs_store(&base_path, &storage_options)
-                .await?
+            let operator = intercept_operator(
+                self.build_opendal_gcs_operator(&base_path, &storage_options)
+                    .await?,
+                params.request_interceptor.as_ref(),
+            )
+            .layer(PartRetryLayer::from_params(params));
+            (
+                Arc::new(OpendalStore::new(operator.clone())) as Arc<dyn OSObjectStore>,
+                Some(operator),
+            )
         } else {
-            self.build_google_cloud_store(&base_path, &storage_options, accessor)
-                .await?
+            let store = self
+                .build_google_cloud_store(&base_path, &storage_options, accessor, params)
+                .await?;
+            (store, None)
         };
         let throttle_config = AimdThrottleConfig::from_storage_options(params.storage_options())?;
         let inner = if throttle_config.is_disabled() {
@@ -135,11 +213,239 @@ impl ObjectStoreProvider for GcsStoreProvider {
             list_is_lexically_ordered: true,
             io_parallelism: DEFAULT_CLOUD_IO_PARALLELISM,
             download_retry_count,
+            coalesce_gap: storage_options.coalesce_gap(),
+            manifest_discovery_prefix: storage_options.manifest_discovery_prefix(),
+            upload_limiter: UploadLimiter::new(storage_options.max_concurrent_uploads()),
+            read_only: false,
+            conditional_put: None,
+            transparent_compression: None,
+            retry_classifier: None,
+            priority_layer: None,
+            upload_part_size: None,
+            rebind_source: None,
             io_tracker: Default::default(),
             store_prefix: self
                 .calculate_object_store_prefix(&base_path, params.storage_options())?,
+            opendal_operator,
         })
     }
+    /// The JSON API endpoint all buckets are served from.
+    #[cfg(feature = "diagnose")]
+    fn diagnose_target(
+        &self,
+        _base_path: &Url,
+        _params: &ObjectStoreParams,
+    ) -> Result<DiagnoseTarget> {
+        Ok(DiagnoseTarget::new(
+            Url::parse("https://storage.googleapis.com/").unwrap(),
+        ))
+    }
+}
+
+/// A customer-supplied encryption key and its digest, as GCS expects them.
+#[derive(Clone, PartialEq, Eq)]
+struct EncryptionKey {
+    key: String,
+    key_sha256: String,
+}
+
+impl std::fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        // Only the digest; the key itself must not end up in logs.
+        f.debug_struct("EncryptionKey")
+            .field("key_sha256", &self.key_sha256)
+            .finish_non_exhaustive()
+    }
+}
+
+impl EncryptionKey {
+    fn try_new(key: &str, key_sha256: Option<&str>) -> Result<Self> {
+        let key = key.trim();
+        let raw = BASE64_STANDARD.decode(key).map_err(|e| {
+            Error::invalid_input(format!("{GCS_ENCRYPTION_KEY} must be base64 encoded: {e}"))
+        })?;
+        if raw.len() != 32 {
+            return Err(Error::invalid_input(format!(
+                "{GCS_ENCRYPTION_KEY} must be a 256-bit AES key, got {} bytes",
+                raw.len()
+            )));
+        }
+        let digest = BASE64_STANDARD.encode(Sha256::digest(&raw));
+        if let Some(expected) = key_sha256
+            && expected.trim() != digest
+        {
+            return Err(Error::invalid_input(format!(
+                "{GCS_ENCRYPTION_KEY_SHA256} does not match {GCS_ENCRYPTION_KEY}"
+            )));
+        }
+        Ok(Self {
+            key: key.to_string(),
+            key_sha256: digest,
+        })
+    }
+
+    /// Storage options that send this key as default headers on every request,
+    /// which covers plain puts, reads and each call of a multipart upload.
+    ///
+    /// With `copy_source`, the key is also sent as the key of the copy source,
+    /// which GCS requires when copying an encrypted object. Those headers are
+    /// only meaningful on copies, so they get a client of their own.
+    fn apply(&self, storage_options: &StorageOptions, copy_source: bool) -> StorageOptions {
+        let mut options = storage_options.0.clone();
+        let prefixes: &[&str] = if copy_source {
+            &["x-goog-encryption", "x-goog-copy-source-encryption"]
+        } else {
+            &["x-goog-encryption"]
+        };
+        for prefix in prefixes {
+            options.insert(format!("headers.{prefix}-algorithm"), "AES256".to_string());
+            options.insert(format!("headers.{prefix}-key"), self.key.clone());
+            options.insert(
+                format!("headers.{prefix}-key-sha256"),
+                self.key_sha256.clone(),
+            );
+        }
+        StorageOptions(options)
+    }
+}
+
+/// Returns `true` if GCS rejected a request because of the encryption key it
+/// carried, or because it carried none for an encrypted object.
+fn is_encryption_key_error(err: &object_store::Error) -> bool {
+    if matches!(err, object_store::Error::NotFound { .. }) {
+        return false;
+    }
+    let message = err.to_string().to_ascii_lowercase();
+    message.contains("encryption key") && !message.contains("not encrypted")
+}
+
+/// Routes GCS calls to clients that carry the configured encryption keys.
+///
+/// Every call goes to `primary`, except copies (including the copy half of a
+/// rename) which go to `copier` so the source key is sent as well. Reads rejected because of the
+/// key are retried against each of the `candidates`, and if none of them
+/// match the error is replaced by one that names the storage options to set.
+#[derive(Debug)]
+struct CustomerKeyStore {
+    primary: Arc<dyn OSObjectStore>,
+    copier: Arc<dyn OSObjectStore>,
+    candidates: Vec<Arc<dyn OSObjectStore>>,
+    has_key: bool,
+}
+
+impl CustomerKeyStore {
+    fn key_error(&self, location: &Path, err: object_store::Error) -> object_store::Error {
+        let message = if self.has_key {
+            format!(
+                "none of the keys in {GCS_ENCRYPTION_KEY} or {GCS_ENCRYPTION_KEY_CANDIDATES} \
+                 can decrypt {location}"
+            )
+        } else {
+            format!(
+                "{location} is encrypted with a customer-supplied key; \
+                 set {GCS_ENCRYPTION_KEY} to read it"
+            )
+        };
+        object_store::Error::Generic {
+            store: "GCS",
+            source: format!("{message}: {err}").into(),
+        }
+    }
+}
+
+impl Display for CustomerKeyStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.primary)
+    }
+}
+
+#[async_trait::async_trait]
+#[deny(clippy::missing_trait_methods)]
+impl OSObjectStore for CustomerKeyStore {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        payload: PutPayload,
+        opts: PutOptions,
+    ) -> OSResult<PutResult> {
+        self.primary.put_opts(location, payload, opts).await
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        opts: PutMultipartOptions,
+    ) -> OSResult<Box<dyn MultipartUpload>> {
+        self.primary.put_multipart_opts(location, opts).await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> OSResult<GetResult> {
+        let mut err = match self.primary.get_opts(location, options.clone()).await {
+            Err(err) if is_encryption_key_error(&err) => err,
+            result => return result,
+        };
+        for candidate in &self.candidates {
+            err = match candidate.get_opts(location, options.clone()).await {
+                Err(err) if is_encryption_key_error(&err) => err,
+                result => return result,
+            };
+        }
+        Err(self.key_error(location, err))
+    }
+
+    async fn get_ranges(&self, location: &Path, ranges: &[Range<u64>]) -> OSResult<Vec<Bytes>> {
+        let mut err = match self.primary.get_ranges(location, ranges).await {
+            Err(err) if is_encryption_key_error(&err) => err,
+            result => return result,
+        };
+        for candidate in &self.candidates {
+            err = match candidate.get_ranges(location, ranges).await {
+                Err(err) if is_encryption_key_error(&err) => err,
+                result => return result,
+            };
+        }
+        Err(self.key_error(location, err))
+    }
+
+    fn delete_stream(
+        &self,
+        locations: BoxStream<'static, OSResult<Path>>,
+    ) -> BoxStream<'static, OSResult<Path>> {
+        self.primary.delete_stream(locations)
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'static, OSResult<ObjectMeta>> {
+        self.primary.list(prefix)
+    }
+
+    fn list_with_offset(
+        &self,
+        prefix: Option<&Path>,
+        offset: &Path,
+    ) -> BoxStream<'static, OSResult<ObjectMeta>> {
+        self.primary.list_with_offset(prefix, offset)
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> OSResult<ListResult> {
+        self.primary.list_with_delimiter(prefix).await
+    }
+
+    async fn copy_opts(&self, from: &Path, to: &Path, opts: CopyOptions) -> OSResult<()> {
+        self.copier.copy_opts(from, to, opts).await
+    }
+
+    async fn rename_opts(&self, from: &Path, to: &Path, opts: RenameOptions) -> OSResult<()> {
+        let mode = match opts.target_mode {
+            RenameTargetMode::Overwrite => CopyMode::Overwrite,
+            RenameTargetMode::Create => CopyMode::Create,
+        };
+        let copy_opts = CopyOptions {
+            mode,
+            extensions: opts.extensions,
+        };
+        self.copier.copy_opts(from, to, copy_opts).await?;
+        self.primary.delete(from).await
+    }
 }
 
 impl StorageOptions {
@@ -149,6 +455,11 @@ impl StorageOptions {
             if let (Some(key), Some(value)) = (os_key.to_str(), os_value.to_str()) {
                 let lowercase_key = key.to_ascii_lowercase();
                 let token_key = "google_storage_token";
+                let encryption_keys = [
+                    GCS_ENCRYPTION_KEY,
+                    GCS_ENCRYPTION_KEY_SHA256,
+                    GCS_ENCRYPTION_KEY_CANDIDATES,
+                ];
 
                 if let Ok(config_key) = GoogleConfigKey::from_str(&lowercase_key) {
                     if !self.0.contains_key(config_key.as_ref()) {
@@ -159,6 +470,10 @@ impl StorageOptions {
                 // Check for GOOGLE_STORAGE_TOKEN until GoogleConfigKey supports storage token
                 else if lowercase_key == token_key && !self.0.contains_key(token_key) {
                     self.0.insert(token_key.to_string(), value.to_string());
+                } else if encryption_keys.contains(&lowercase_key.as_str())
+                    && !self.0.contains_key(&lowercase_key)
+                {
+                    self.0.insert(lowercase_key, value.to_string());
                 }
             }
         }
@@ -174,6 +489,35 @@ impl StorageOptions {
             })
             .collect()
     }
+
+    /// The configured GCS encryption keys: [`GCS_ENCRYPTION_KEY`] first,
+    /// followed by any [`GCS_ENCRYPTION_KEY_CANDIDATES`]. Empty if no key is set.
+    fn gcs_encryption_keys(&self) -> Result<Vec<EncryptionKey>> {
+        let Some(key) = self.get(GCS_ENCRYPTION_KEY) else {
+            if self.get(GCS_ENCRYPTION_KEY_CANDIDATES).is_some() {
+                return Err(Error::invalid_input(format!(
+                    "{GCS_ENCRYPTION_KEY_CANDIDATES} requires {GCS_ENCRYPTION_KEY} to be set"
+                )));
+            }
+            return Ok(Vec::new());
+        };
+        let mut keys = vec![EncryptionKey::try_new(
+            key,
+            self.get(GCS_ENCRYPTION_KEY_SHA256).map(String::as_str),
+        )?];
+        for candidate in self
+            .get(GCS_ENCRYPTION_KEY_CANDIDATES)
+            .into_iter()
+            .flat_map(|candidates| candidates.split(','))
+            .filter(|candidate| !candidate.trim().is_empty())
+        {
+            let candidate = EncryptionKey::try_new(candidate, None)?;
+            if !keys.contains(&candidate) {
+                keys.push(candidate);
+            }
+        }
+        Ok(keys)
+    }
 }
 
 #[cfg(test)]
@@ -182,8 +526,426 @@ mod tests {
     use std::sync::Arc;
 
     use crate::object_store::test_utils::StaticMockStorageOptionsProvider;
-    use crate::object_store::{ObjectStoreParams, StorageOptionsAccessor};
+    use crate::object_store::{
+        InterceptedRequest, ObjectStoreParams, RequestInterceptor, StorageOptionsAccessor,
+    };
+    use object_store::GetRange;
     use std::collections::HashMap;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // Two AES-256 keys and the base64 SHA-256 of the first.
+    const KEY: &str = "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=";
+    const KEY_SHA256: &str = "Yw3NKWbEM2aRElRIu7JbT/QSpJxzLbLIq8G4WBvXEN0=";
+    const OLD_KEY: &str = "ICEiIyQlJicoKSorLC0uLzAxMjM0NTY3ODk6Ozw9Pj8=";
+
+    /// Serve HTTP on a local port, answering each request with `respond` and
+    /// recording the raw request text.
+    async fn mock_http(
+        mut respond: impl FnMut(&str) -> String + Send + 'static,
+    ) -> (String, Arc<std::sync::Mutex<Vec<String>>>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let received = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                loop {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request);
+                    let complete = text.split_once("\r\n\r\n").is_some_and(|(head, body)| {
+                        let length = head
+                            .lines()
+                            .find_map(|line| line.strip_prefix("content-length: "))
+                            .map_or(0, |length| length.parse().unwrap());
+                        body.len() >= length
+                    });
+                    if complete || n == 0 {
+                        break;
+                    }
+                }
+                let request = String::from_utf8(request).unwrap();
+                let response = respond(&request);
+                received.lock().unwrap().push(request);
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (endpoint, requests)
+    }
+
+    /// A minimal GCS XML API: multipart initiate and complete, part and object
+    /// puts, and object reads that always return `hello`.
+    fn gcs_response(request: &str) -> String {
+        let (status, body) = if request.starts_with("POST") && request.contains("?uploads") {
+            (
+                "200 OK",
+                "<InitiateMultipartUploadResult><UploadId>upload</UploadId></InitiateMultipartUploadResult>",
+            )
+        } else if request.starts_with("POST") {
+            (
+                "200 OK",
+                "<CompleteMultipartUploadResult><ETag>\"done\"</ETag></CompleteMultipartUploadResult>",
+            )
+        } else if request.starts_with("GET") {
+            if let Some(range) = request
+                .lines()
+                .find_map(|line| line.strip_prefix("range: bytes="))
+            {
+                let (start, end) = range.split_once('-').unwrap();
+                let (start, end): (usize, usize) = (start.parse().unwrap(), end.parse().unwrap());
+                let body = &"hello"[start..=end];
+                return http_response_with(
+                    "206 Partial Content",
+                    &format!("Content-Range: bytes {start}-{end}/5\r\n"),
+                    body,
+                );
+            }
+            ("200 OK", "hello")
+        } else {
+            ("200 OK", "")
+        };
+        http_response(status, body)
+    }
+
+    fn http_response(status: &str, body: &str) -> String {
+        http_response_with(status, "", body)
+    }
+
+    fn http_response_with(status: &str, headers: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {status}\r\nContent-Length: {}\r\nETag: \"etag\"\r\n\
+             Last-Modified: Thu, 01 Jan 2026 00:00:00 GMT\r\n{headers}Connection: close\r\n\r\n{body}",
+            body.len()
+        )
+    }
+
+    async fn mock_store(endpoint: &str, options: &[(&str, &str)]) -> Result<ObjectStore> {
+        let mut storage_options = HashMap::from([
+            ("google_base_url".to_string(), endpoint.to_string()),
+            ("google_storage_token".to_string(), "token".to_string()),
+            ("allow_http".to_string(), "true".to_string()),
+            ("client_max_retries".to_string(), "0".to_string()),
+        ]);
+        for (key, value) in options {
+            storage_options.insert(key.to_string(), value.to_string());
+        }
+        let params = ObjectStoreParams {
+            storage_options_accessor: Some(Arc::new(StorageOptionsAccessor::with_static_options(
+                storage_options,
+            ))),
+            ..Default::default()
+        };
+        GcsStoreProvider
+            .new_store(Url::parse("gs://bucket/path").unwrap(), &params)
+            .await
+    }
+
+    #[test]
+    fn test_gcs_encryption_key_options() {
+        let keys = |options: &[(&str, &str)]| {
+            StorageOptions::new(
+                options
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect(),
+            )
+            .gcs_encryption_keys()
+        };
+
+        assert!(keys(&[]).unwrap().is_empty());
+        let parsed = keys(&[(GCS_ENCRYPTION_KEY, KEY)]).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].key_sha256, KEY_SHA256);
+        keys(&[
+            (GCS_ENCRYPTION_KEY, KEY),
+            (GCS_ENCRYPTION_KEY_SHA256, KEY_SHA256),
+        ])
+        .unwrap();
+        let rotated = keys(&[
+            (GCS_ENCRYPTION_KEY, KEY),
+            (GCS_ENCRYPTION_KEY_CANDIDATES, &format!("{OLD_KEY}, {KEY}")),
+        ])
+        .unwrap();
+        assert_eq!(rotated.len(), 2);
+        assert_eq!(rotated[1].key, OLD_KEY);
+
+        for (options, message) in [
+            (vec![(GCS_ENCRYPTION_KEY, "not base64!")], "base64"),
+            (vec![(GCS_ENCRYPTION_KEY, "AAEC")], "256-bit"),
+            (
+                vec![
+                    (GCS_ENCRYPTION_KEY, KEY),
+                    (GCS_ENCRYPTION_KEY_SHA256, OLD_KEY),
+                ],
+                "does not match",
+            ),
+            (
+                vec![(GCS_ENCRYPTION_KEY_CANDIDATES, OLD_KEY)],
+                "requires google_encryption_key",
+            ),
+        ] {
+            let err = keys(&options).unwrap_err().to_string();
+            assert!(err.contains(message), "{err}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_gcs_encryption_key_sent_on_every_request() {
+        let (endpoint, requests) = mock_http(gcs_response).await;
+        let store = mock_store(&endpoint, &[(GCS_ENCRYPTION_KEY, KEY)])
+            .await
+            .unwrap();
+        let path = Path::from("path/data");
+
+        store.inner.put(&path, "hello".into()).await.unwrap();
+        let data = store.inner.get(&path).await.unwrap().bytes().await.unwrap();
+        assert_eq!(data.as_ref(), b"hello");
+        let mut upload = store.inner.put_multipart(&path).await.unwrap();
+        upload.put_part("part".into()).await.unwrap();
+        upload.complete().await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        let methods = requests
+            .iter()
+            .map(|request| request.split(' ').next().unwrap())
+            .collect::<Vec<_>>();
+        // put, get, multipart initiate, part and complete
+        assert_eq!(methods, ["PUT", "GET", "POST", "PUT", "POST"]);
+        for request in requests.iter() {
+            assert!(
+                request.contains("x-goog-encryption-algorithm: AES256"),
+                "{request}"
+            );
+            assert!(
+                request.contains(&format!("x-goog-encryption-key: {KEY}")),
+                "{request}"
+            );
+            assert!(
+                request.contains(&format!("x-goog-encryption-key-sha256: {KEY_SHA256}")),
+                "{request}"
+            );
+            assert!(!request.contains("x-goog-copy-source"), "{request}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_gcs_encryption_key_rotation() {
+        // Objects were written with OLD_KEY; GCS rejects reads with any other key.
+        let (endpoint, requests) = mock_http(|request| {
+            if request.contains(&format!("x-goog-encryption-key: {OLD_KEY}")) {
+                gcs_response(request)
+            } else {
+                http_response(
+                    "400 Bad Request",
+                    "The provided encryption key is incorrect",
+                )
+            }
+        })
+        .await;
+        let path = Path::from("path/data");
+
+        let store = mock_store(
+            &endpoint,
+            &[
+                (GCS_ENCRYPTION_KEY, KEY),
+                (GCS_ENCRYPTION_KEY_CANDIDATES, OLD_KEY),
+            ],
+        )
+        .await
+        .unwrap();
+        let data = store.inner.get(&path).await.unwrap().bytes().await.unwrap();
+        assert_eq!(data.as_ref(), b"hello");
+        let ranges = store.inner.get_ranges(&path, &[0..2]).await.unwrap();
+        assert_eq!(ranges, [Bytes::from_static(b"he")]);
+        // Writes only ever use the current key.
+        store.inner.put(&path, "hello".into()).await.unwrap_err();
+        let put = requests.lock().unwrap().pop().unwrap();
+        assert!(put.starts_with("PUT"), "{put}");
+        assert!(
+            put.contains(&format!("x-goog-encryption-key: {KEY}")),
+            "{put}"
+        );
+
+        let store = mock_store(&endpoint, &[(GCS_ENCRYPTION_KEY, KEY)])
+            .await
+            .unwrap();
+        let err = store.inner.get(&path).await.unwrap_err().to_string();
+        assert!(err.contains(GCS_ENCRYPTION_KEY_CANDIDATES), "{err}");
+        assert!(err.contains("path/data"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_gcs_encrypted_read_without_key() {
+        let (endpoint, _) = mock_http(|_| {
+            http_response(
+                "400 Bad Request",
+                "The target object is encrypted by a customer-supplied encryption key.",
+            )
+        })
+        .await;
+        let store = mock_store(&endpoint, &[]).await.unwrap();
+        let err = store
+            .inner
+            .get(&Path::from("path/data"))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("set google_encryption_key"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_gcs_encryption_key_requires_native_client() {
+        let err = mock_store(
+            "http://localhost",
+            &[(GCS_ENCRYPTION_KEY, KEY), ("use_opendal", "true")],
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("use_opendal"), "{err}");
+    }
+
+    /// Signs every request with a counter, or fails them all.
+    #[derive(Debug, Default)]
+    struct SigningInterceptor {
+        seen: std::sync::Mutex<Vec<InterceptedRequest>>,
+        fail: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl RequestInterceptor for SigningInterceptor {
+        async fn intercept(
+            &self,
+            request: &InterceptedRequest,
+            headers: &mut http::HeaderMap,
+        ) -> Result<()> {
+            if self.fail {
+                return Err(Error::invalid_input("auth service unavailable"));
+            }
+            let mut seen = self.seen.lock().unwrap();
+            headers.insert(
+                "x-auth-signature",
+                format!("sig-{}", seen.len()).parse().unwrap(),
+            );
+            seen.push(request.clone());
+            Ok(())
+        }
+    }
+
+    async fn intercepted_store(
+        endpoint: &str,
+        interceptor: Arc<SigningInterceptor>,
+        options: &[(&str, &str)],
+    ) -> ObjectStore {
+        let mut storage_options = HashMap::from([
+            ("google_base_url".to_string(), endpoint.to_string()),
+            ("google_storage_token".to_string(), "token".to_string()),
+            ("allow_http".to_string(), "true".to_string()),
+            ("client_max_retries".to_string(), "0".to_string()),
+        ]);
+        for (key, value) in options {
+            storage_options.insert(key.to_string(), value.to_string());
+        }
+        let params = ObjectStoreParams {
+            storage_options_accessor: Some(Arc::new(StorageOptionsAccessor::with_static_options(
+                storage_options,
+            ))),
+            request_interceptor: Some(interceptor),
+            ..Default::default()
+        };
+        GcsStoreProvider
+            .new_store(Url::parse("gs://bucket/path").unwrap(), &params)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_request_interceptor_signs_every_request() {
+        let (endpoint, requests) = mock_http(gcs_response).await;
+        let interceptor = Arc::new(SigningInterceptor::default());
+        let store = intercepted_store(&endpoint, interceptor.clone(), &[]).await;
+        let path = Path::from("path/data");
+
+        store.size(&path).await.unwrap();
+        let data = store.read_one_range(&path, 1..3).await.unwrap();
+        assert_eq!(data.as_ref(), b"el");
+        let data = store.read_one_all(&path).await.unwrap();
+        assert_eq!(data.as_ref(), b"hello");
+
+        let requests = requests.lock().unwrap();
+        let seen = interceptor.seen.lock().unwrap();
+        assert_eq!(seen.len(), requests.len());
+        for (i, (request, seen)) in requests.iter().zip(seen.iter()).enumerate() {
+            assert!(
+                request.contains(&format!("x-auth-signature: sig-{i}")),
+                "{request}"
+            );
+            assert!(
+                request.starts_with(&format!("{} {} ", seen.method, seen.path)),
+                "{request}"
+            );
+        }
+        assert_eq!(
+            seen.iter()
+                .map(|request| (request.method.as_str(), request.range.clone()))
+                .collect::<Vec<_>>(),
+            [
+                ("HEAD", None),
+                ("GET", Some(GetRange::Bounded(1..3))),
+                ("GET", None),
+            ]
+        );
+    }
+
+    #[rstest::rstest]
+    #[case::native(false)]
+    #[case::opendal(true)]
+    #[tokio::test]
+    async fn test_request_interceptor_error(#[case] use_opendal: bool) {
+        let (endpoint, requests) = mock_http(gcs_response).await;
+        let interceptor = Arc::new(SigningInterceptor {
+            fail: true,
+            ..Default::default()
+        });
+        let use_opendal = use_opendal.to_string();
+        let store = intercepted_store(
+            &endpoint,
+            interceptor,
+            &[
+                ("use_opendal", &use_opendal),
+                ("endpoint", &endpoint),
+                ("allow_anonymous", "true"),
+                ("disable_vm_metadata", "true"),
+                ("disable_config_load", "true"),
+            ],
+        )
+        .await;
+
+        let err = store.inner.get(&Path::from("path/data")).await.unwrap_err();
+        if use_opendal.parse().unwrap() {
+            assert!(
+                matches!(
+                    err,
+                    object_store::Error::Generic {
+                        store: "PermissionDenied",
+                        ..
+                    }
+                ),
+                "{err:?}"
+            );
+        } else {
+            assert!(
+                matches!(err, object_store::Error::Unauthenticated { .. }),
+                "{err:?}"
+            );
+        }
+        let err = err.to_string();
+        assert!(err.contains("auth service unavailable"), "{err}");
+        assert!(err.contains("path/data"), "{err}");
+        assert!(requests.lock().unwrap().is_empty());
+    }
 
     #[test]
     fn test_gcs_store_path() {
@@ -11,11 +11,14 @@ use opendal::{Operator, services::Huggingface};
 use url::Url;
 
 use crate::object_store::dynamic_opendal::DynamicOpenDalStore;
+use crate::object_store::interceptor::intercept_operator;
 use crate::object_store::parse_hf_repo_id;
+use crate::object_store::part_retry::PartRetryLayer;
 use crate::object_store::{
     DEFAULT_CLOUD_BLOCK_SIZE, DEFAULT_CLOUD_IO_PARALLELISM, DEFAULT_MAX_IOP_SIZE, ObjectStore,
     ObjectStoreParams, ObjectStoreProvider, StorageOptions,
 };
+use crate::object_writer::UploadLimiter;
 use lance_core::error::{Error, Result};
 
 /// Hugging Face object store provider backed by OpenDAL.
@@ -138,7 +141,7 @@ fn normalize_hf_config(options: &HashMap<String, String>) -> Result<HashMap<Stri
     Ok(config_map)
 }
 
-fn build_hf_store(config_map: HashMap<String, String>) -> Result<OpendalStore> {
+fn build_hf_operator(config_map: HashMap<String, String>) -> Result<Operator> {
     let repo_type = config_map
         .get("repo_type")
         .ok_or_else(|| Error::invalid_input("Huggingface repo_type is required"))?;
@@ -160,13 +163,11 @@ fn build_hf_store(config_map: HashMap<String, String>) -> Result<OpendalStore> {
         builder = builder.download_mode(download_mode);
     }
 
-    let operator = Operator::new(builder)
+    Ok(Operator::new(builder)
         .map_err(|e| {
             Error::invalid_input(format!("Failed to create Huggingface operator: {:?}", e))
         })?
-        .finish();
-
-    Ok(OpendalStore::new(operator))
+        .finish())
 }
 
 #[async_trait::async_trait]
@@ -190,20 +191,31 @@ impl ObjectStoreProvider for HuggingfaceStoreProvider {
         }
 
         let accessor = params.get_accessor();
-        let inner: Arc<dyn OSObjectStore> =
+        let (inner, opendal_operator): (Arc<dyn OSObjectStore>, _) =
             if let Some(accessor) = accessor.filter(|a| a.has_provider()) {
-                Arc::new(
+                let store = Arc::new(
                     DynamicOpenDalStore::new(
                         format!("hf:{}", base_path),
                         base_options,
                         accessor,
                         normalize_hf_config,
-                        build_hf_store,
+                        build_hf_operator,
                     )
-                    .with_protected_keys(["repo_type", "repo_id"]),
-                )
+                    .with_protected_keys(["repo_type", "repo_id"])
+                    .with_request_interceptor(params.request_interceptor.clone())
+                    .with_part_retry_layer(PartRetryLayer::from_params(params)),
+                );
+                (store, None)
             } else {
-                Arc::new(build_hf_store(normalize_hf_config(&base_options)?)?)
+                let operator = intercept_operator(
+                    build_hf_operator(normalize_hf_config(&base_options)?)?,
+                    params.request_interceptor.as_ref(),
+                )
+                .layer(PartRetryLayer::from_params(params));
+                (
+                    Arc::new(OpendalStore::new(operator.clone())),
+                    Some(operator),
+                )
             };
 
         Ok(ObjectStore {
@@ -211,13 +223,24 @@ impl ObjectStoreProvider for HuggingfaceStoreProvider {
             inner,
             block_size,
             max_iop_size: *DEFAULT_MAX_IOP_SIZE,
-            use_constant_size_upload_parts: params.use_constant_size_upload_parts,
+            use_constant_size_upload_parts: false,
             list_is_lexically_ordered: params.list_is_lexically_ordered.unwrap_or(true),
             io_parallelism: DEFAULT_CLOUD_IO_PARALLELISM,
             download_retry_count,
+            coalesce_gap: storage_options.coalesce_gap(),
+            manifest_discovery_prefix: storage_options.manifest_discovery_prefix(),
+            upload_limiter: UploadLimiter::new(storage_options.max_concurrent_uploads()),
+            read_only: false,
+            conditional_put: None,
+            transparent_compression: None,
+            retry_classifier: None,
+            priority_layer: None,
+            upload_part_size: None,
+            rebind_source: None,
             io_tracker: Default::default(),
             store_prefix: self
                 .calculate_object_store_prefix(&base_path, params.storage_options())?,
+            opendal_operator,
         })
     }
 
@@ -429,7 +452,7 @@ mod tests {
             ),
             accessor,
             normalize_hf_config,
-            build_hf_store,
+            build_hf_operator,
         );
 
         let current_store = store
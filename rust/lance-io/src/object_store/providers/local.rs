@@ -7,6 +7,7 @@ use crate::object_store::{
     DEFAULT_LOCAL_BLOCK_SIZE, DEFAULT_LOCAL_IO_PARALLELISM, DEFAULT_MAX_IOP_SIZE, ObjectStore,
     ObjectStoreParams, ObjectStoreProvider, StorageOptions,
 };
+use crate::object_writer::UploadLimiter;
 use lance_core::Error;
 use lance_core::error::Result;
 use object_store::{local::LocalFileSystem, path::Path};
@@ -30,9 +31,28 @@ impl ObjectStoreProvider for FileStoreProvider {
             list_is_lexically_ordered: false,
             io_parallelism: DEFAULT_LOCAL_IO_PARALLELISM,
             download_retry_count,
+            coalesce_gap: storage_options.coalesce_gap(),
+            manifest_discovery_prefix: storage_options.manifest_discovery_prefix(),
+            upload_limiter: UploadLimiter::new(storage_options.max_concurrent_uploads()),
+            read_only: false,
+            conditional_put: None,
+            transparent_compression: None,
+            retry_classifier: None,
+            priority_layer: None,
+            upload_part_size: None,
+            rebind_source: None,
             io_tracker: Default::default(),
             store_prefix: self
                 .calculate_object_store_prefix(&base_path, params.storage_options())?,
+            #[cfg(any(
+                feature = "aws",
+                feature = "azure",
+                feature = "gcp",
+                feature = "oss",
+                feature = "huggingface",
+                feature = "tencent"
+            ))]
+            opendal_operator: None,
         })
     }
 
@@ -7,13 +7,22 @@ use crate::object_store::{
     DEFAULT_CLOUD_IO_PARALLELISM, DEFAULT_LOCAL_BLOCK_SIZE, DEFAULT_MAX_IOP_SIZE, ObjectStore,
     ObjectStoreParams, ObjectStoreProvider, StorageOptions,
 };
+use crate::object_writer::UploadLimiter;
 use lance_core::error::Result;
 use object_store::{memory::InMemory, path::Path};
 use url::Url;
 
-/// Provides a fresh in-memory object store for each call to `new_store`.
+/// Provides in-memory object stores that all share one backend.
+///
+/// Every store made by the same provider sees the same bytes, so two handles
+/// on `memory://bucket/x` from one [`crate::object_store::ObjectStoreRegistry`]
+/// read each other's writes, even after the first store is dropped. Each
+/// registry has its own provider, so stores from other registries are
+/// isolated. The data lives as long as the provider.
 #[derive(Default, Debug)]
-pub struct MemoryStoreProvider;
+pub struct MemoryStoreProvider {
+    backend: Arc<InMemory>,
+}
 
 #[async_trait::async_trait]
 impl ObjectStoreProvider for MemoryStoreProvider {
@@ -22,7 +31,7 @@ impl ObjectStoreProvider for MemoryStoreProvider {
         let storage_options = StorageOptions(params.storage_options().cloned().unwrap_or_default());
         let download_retry_count = storage_options.download_retry_count();
         Ok(ObjectStore {
-            inner: Arc::new(InMemory::new()),
+            inner: self.backend.clone(),
             scheme: String::from("memory"),
             block_size,
             max_iop_size: *DEFAULT_MAX_IOP_SIZE,
@@ -30,9 +39,28 @@ impl ObjectStoreProvider for MemoryStoreProvider {
             list_is_lexically_ordered: true,
             io_parallelism: DEFAULT_CLOUD_IO_PARALLELISM,
             download_retry_count,
+            coalesce_gap: storage_options.coalesce_gap(),
+            manifest_discovery_prefix: storage_options.manifest_discovery_prefix(),
+            upload_limiter: UploadLimiter::new(storage_options.max_concurrent_uploads()),
+            read_only: false,
+            conditional_put: None,
+            transparent_compression: None,
+            retry_classifier: None,
+            priority_layer: None,
+            upload_part_size: None,
+            rebind_source: None,
             io_tracker: Default::default(),
             store_prefix: self
                 .calculate_object_store_prefix(&base_path, params.storage_options())?,
+            #[cfg(any(
+                feature = "aws",
+                feature = "azure",
+                feature = "gcp",
+                feature = "oss",
+                feature = "huggingface",
+                feature = "tencent"
+            ))]
+            opendal_operator: None,
         })
     }
 
@@ -57,10 +85,49 @@ impl ObjectStoreProvider for MemoryStoreProvider {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::object_store::ObjectStoreRegistry;
+    use bytes::Bytes;
+    use object_store::{ObjectStoreExt as _, PutPayload};
+
+    #[tokio::test]
+    async fn test_stores_share_backend_per_registry() {
+        let registry = Arc::new(ObjectStoreRegistry::default());
+        let (writer, path) = ObjectStore::from_uri_and_params(
+            registry.clone(),
+            "memory://bucket/a",
+            &Default::default(),
+        )
+        .await
+        .unwrap();
+        writer
+            .inner
+            .put(&path, PutPayload::from_static(b"hello"))
+            .await
+            .unwrap();
+        drop(writer);
+
+        // The data outlives the store it was written through.
+        let (reader, path) =
+            ObjectStore::from_uri_and_params(registry, "memory://bucket/a", &Default::default())
+                .await
+                .unwrap();
+        let bytes = reader.inner.get(&path).await.unwrap();
+        assert_eq!(bytes.bytes().await.unwrap(), Bytes::from_static(b"hello"));
+
+        // Another registry has its own backend.
+        let (other, path) = ObjectStore::from_uri_and_params(
+            Arc::new(ObjectStoreRegistry::default()),
+            "memory://bucket/a",
+            &Default::default(),
+        )
+        .await
+        .unwrap();
+        assert!(other.inner.get(&path).await.is_err());
+    }
 
     #[test]
     fn test_memory_store_path() {
-        let provider = MemoryStoreProvider;
+        let provider = MemoryStoreProvider::default();
 
         let url = Url::parse("memory://path/to/file").unwrap();
         let path = provider.extract_path(&url).unwrap();
@@ -70,7 +137,7 @@ mod tests {
 
     #[test]
     fn test_calculate_object_store_prefix() {
-        let provider = MemoryStoreProvider;
+        let provider = MemoryStoreProvider::default();
         assert_eq!(
             "memory",
             provider
@@ -78,4 +145,17 @@ mod tests {
                 .unwrap()
         );
     }
+
+    #[cfg(feature = "diagnose")]
+    #[tokio::test]
+    async fn test_memory_store_cannot_be_diagnosed() {
+        let err = MemoryStoreProvider::default()
+            .diagnose(
+                Url::parse("memory://path").unwrap(),
+                &ObjectStoreParams::default(),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("no network endpoint"), "{err}");
+    }
 }
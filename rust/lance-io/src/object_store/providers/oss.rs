@@ -10,10 +10,13 @@ use opendal::{Operator, services::Oss};
 use url::Url;
 
 use crate::object_store::dynamic_opendal::DynamicOpenDalStore;
+use crate::object_store::interceptor::intercept_operator;
+use crate::object_store::part_retry::PartRetryLayer;
 use crate::object_store::{
     DEFAULT_CLOUD_BLOCK_SIZE, DEFAULT_CLOUD_IO_PARALLELISM, DEFAULT_MAX_IOP_SIZE, ObjectStore,
     ObjectStoreParams, ObjectStoreProvider, StorageOptions,
 };
+use crate::object_writer::UploadLimiter;
 use lance_core::error::{Error, Result};
 
 #[derive(Default, Debug)]
@@ -93,12 +96,10 @@ impl OssStoreProvider {
         Ok(config_map)
     }
 
-    fn build_oss_store(config_map: HashMap<String, String>) -> Result<OpendalStore> {
-        let operator = Operator::from_iter::<Oss>(config_map)
+    fn build_oss_operator(config_map: HashMap<String, String>) -> Result<Operator> {
+        Ok(Operator::from_iter::<Oss>(config_map)
             .map_err(|e| Error::invalid_input(format!("Failed to create OSS operator: {:?}", e)))?
-            .finish();
-
-        Ok(OpendalStore::new(operator))
+            .finish())
     }
 }
 
@@ -111,22 +112,31 @@ impl ObjectStoreProvider for OssStoreProvider {
         let base_options = Self::base_oss_options(&base_path, &storage_options)?;
         let accessor = params.get_accessor();
 
-        let inner: Arc<dyn OSObjectStore> =
+        let (inner, opendal_operator): (Arc<dyn OSObjectStore>, _) =
             if let Some(accessor) = accessor.filter(|a| a.has_provider()) {
-                Arc::new(
+                let store = Arc::new(
                     DynamicOpenDalStore::new(
                         format!("oss:{}", base_path),
                         base_options,
                         accessor,
                         Self::normalize_oss_config,
-                        Self::build_oss_store,
+                        Self::build_oss_operator,
                     )
-                    .with_protected_keys(["bucket", "root"]),
-                )
+                    .with_protected_keys(["bucket", "root"])
+                    .with_request_interceptor(params.request_interceptor.clone())
+                    .with_part_retry_layer(PartRetryLayer::from_params(params)),
+                );
+                (store, None)
             } else {
-                Arc::new(Self::build_oss_store(Self::normalize_oss_config(
-                    &base_options,
-                )?)?)
+                let operator = intercept_operator(
+                    Self::build_oss_operator(Self::normalize_oss_config(&base_options)?)?,
+                    params.request_interceptor.as_ref(),
+                )
+                .layer(PartRetryLayer::from_params(params));
+                (
+                    Arc::new(OpendalStore::new(operator.clone())),
+                    Some(operator),
+                )
             };
 
         let mut url = base_path;
@@ -139,14 +149,34 @@ impl ObjectStoreProvider for OssStoreProvider {
             inner,
             block_size,
             max_iop_size: *DEFAULT_MAX_IOP_SIZE,
-            use_constant_size_upload_parts: params.use_constant_size_upload_parts,
+            use_constant_size_upload_parts: false,
             list_is_lexically_ordered: params.list_is_lexically_ordered.unwrap_or(true),
             io_parallelism: DEFAULT_CLOUD_IO_PARALLELISM,
             download_retry_count: storage_options.download_retry_count(),
+            coalesce_gap: storage_options.coalesce_gap(),
+            manifest_discovery_prefix: storage_options.manifest_discovery_prefix(),
+            upload_limiter: UploadLimiter::new(storage_options.max_concurrent_uploads()),
+            read_only: false,
+            conditional_put: None,
+            transparent_compression: None,
+            retry_classifier: None,
+            priority_layer: None,
+            upload_part_size: None,
+            rebind_source: None,
             io_tracker: Default::default(),
             store_prefix: self.calculate_object_store_prefix(&url, params.storage_options())?,
+            opendal_operator,
         })
     }
+
+    /// The ETag of a PutObject upload is the content MD5.
+    fn md5_etags(&self) -> bool {
+        true
+    }
+
+    fn endpoint_keys(&self) -> &'static [&'static str] {
+        &["oss_endpoint", "endpoint"]
+    }
 }
 
 #[cfg(test)]
@@ -273,7 +303,7 @@ mod tests {
             base_options,
             accessor,
             OssStoreProvider::normalize_oss_config,
-            OssStoreProvider::build_oss_store,
+            OssStoreProvider::build_oss_operator,
         );
 
         let current_store = store
@@ -39,9 +39,8 @@ fn shared_backend_for(url: &Url) -> Arc<InMemory> {
 /// fence simulations). Choose distinct authorities for isolation
 /// (`shared-memory://test-a` vs `shared-memory://test-b`).
 ///
-/// Unlike `memory://` — which mints a fresh `InMemory` per `new_store` call — this
-/// provider is opt-in precisely so existing tests relying on per-call isolation are
-/// unaffected.
+/// Unlike `memory://` — which shares one `InMemory` per registry, so each session is
+/// isolated — this provider shares across the whole process.
 #[derive(Default, Debug)]
 pub struct SharedMemoryStoreProvider {
     inner: MemoryStoreProvider,
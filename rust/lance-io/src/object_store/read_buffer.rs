@@ -0,0 +1,342 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! A store-wide budget for the bytes of in-flight reads.
+//!
+//! Wide scans issue many concurrent reads, and read-ahead and range coalescing
+//! make each of them larger. [`ReadBufferLimitedStore`] bounds the total bytes
+//! those reads hold at once with a byte semaphore shared by every reader of the
+//! store: a read waits for budget before it is fetched, and gives it back once
+//! its data has been handed over (or its body stream is dropped).
+//!
+//! The budget is set with the `storage_max_read_buffer_bytes` storage option.
+//! The current usage and the time spent waiting for budget are reported in
+//! [`IoStats`](crate::utils::tracking_store::IoStats). Local files read with
+//! [`LocalObjectReader`](crate::local::LocalObjectReader) bypass the store and
+//! are not counted.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Instant;
+
+use bytes::Bytes;
+use futures::StreamExt;
+use futures::stream::BoxStream;
+use object_store::path::Path;
+use object_store::{
+    CopyOptions, GetOptions, GetRange, GetResult, GetResultPayload, ListResult, MultipartUpload,
+    ObjectMeta, ObjectStore, PutMultipartOptions, PutOptions, PutPayload, PutResult, RenameOptions,
+    Result as OSResult,
+};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::utils::tracking_store::IOTracker;
+use lance_core::{Error, Result};
+
+/// Storage option for the total bytes in-flight reads may buffer at once.
+/// `0` disables the budget.
+pub const READ_BUFFER_BYTES_KEY: &str = "storage_max_read_buffer_bytes";
+
+/// Large enough that a single wide scan, which the scan scheduler already
+/// bounds to a few hundred MiB, never waits on it.
+pub const DEFAULT_READ_BUFFER_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+/// Shared byte budget for the reads of a store, see [`READ_BUFFER_BYTES_KEY`].
+///
+/// Clones share the same budget.
+#[derive(Debug, Clone)]
+pub struct ReadBufferLimiter {
+    permits: Arc<Semaphore>,
+    capacity: usize,
+    io_tracker: IOTracker,
+}
+
+/// Budget held by one read, given back to the limiter when dropped.
+#[derive(Debug)]
+pub struct ReadBufferPermit {
+    _permit: OwnedSemaphorePermit,
+    num_bytes: u64,
+    io_tracker: IOTracker,
+}
+
+impl Drop for ReadBufferPermit {
+    fn drop(&mut self) {
+        self.io_tracker.record_read_buffer_release(self.num_bytes);
+    }
+}
+
+impl ReadBufferLimiter {
+    pub fn new(capacity: u64, io_tracker: IOTracker) -> Self {
+        let capacity = (capacity as usize).clamp(1, Semaphore::MAX_PERMITS);
+        Self {
+            permits: Arc::new(Semaphore::new(capacity)),
+            capacity,
+            io_tracker,
+        }
+    }
+
+    /// Build the limiter configured by [`READ_BUFFER_BYTES_KEY`], or `None`
+    /// when the budget is disabled.
+    pub fn from_storage_options(
+        storage_options: Option<&HashMap<String, String>>,
+        io_tracker: IOTracker,
+    ) -> Result<Option<Self>> {
+        let capacity = storage_options
+            .and_then(|opts| opts.get(READ_BUFFER_BYTES_KEY))
+            .map(|val| {
+                val.parse::<u64>().map_err(|_| {
+                    Error::invalid_input(format!(
+                        "Invalid value for storage option '{READ_BUFFER_BYTES_KEY}': '{val}'"
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or(DEFAULT_READ_BUFFER_BYTES);
+        Ok((capacity > 0).then(|| Self::new(capacity, io_tracker)))
+    }
+
+    /// Bytes currently held by in-flight reads.
+    pub fn buffered_bytes(&self) -> usize {
+        self.capacity - self.permits.available_permits()
+    }
+
+    /// Wait until `num_bytes` of budget are free and take them.
+    ///
+    /// Reads larger than the whole budget take all of it, so they run alone
+    /// instead of waiting forever.
+    pub async fn acquire(&self, num_bytes: u64) -> OSResult<ReadBufferPermit> {
+        let num_bytes = num_bytes.min(self.capacity as u64).min(u32::MAX as u64);
+        let start = Instant::now();
+        let permit = self
+            .permits
+            .clone()
+            .acquire_many_owned(num_bytes as u32)
+            .await
+            .map_err(|err| object_store::Error::Generic {
+                store: "ReadBufferLimiter",
+                source: Box::new(err),
+            })?;
+        self.io_tracker
+            .record_read_buffer_acquire(num_bytes, start.elapsed());
+        Ok(ReadBufferPermit {
+            _permit: permit,
+            num_bytes,
+            io_tracker: self.io_tracker.clone(),
+        })
+    }
+}
+
+/// An [`ObjectStore`] wrapper that makes reads wait for read buffer budget.
+#[derive(Debug)]
+pub struct ReadBufferLimitedStore {
+    target: Arc<dyn ObjectStore>,
+    limiter: ReadBufferLimiter,
+}
+
+impl ReadBufferLimitedStore {
+    pub fn new(target: Arc<dyn ObjectStore>, limiter: ReadBufferLimiter) -> Self {
+        Self { target, limiter }
+    }
+}
+
+impl Display for ReadBufferLimitedStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ReadBufferLimitedStore({})", self.target)
+    }
+}
+
+/// Keep `permit` until the body of `result` has been consumed or dropped.
+fn hold_until_consumed(mut result: GetResult, permit: ReadBufferPermit) -> GetResult {
+    if let GetResultPayload::Stream(stream) = result.payload {
+        result.payload = GetResultPayload::Stream(
+            stream
+                .map(move |chunk| {
+                    let _ = &permit;
+                    chunk
+                })
+                .boxed(),
+        );
+    }
+    result
+}
+
+#[async_trait::async_trait]
+#[deny(clippy::missing_trait_methods)]
+impl ObjectStore for ReadBufferLimitedStore {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        payload: PutPayload,
+        opts: PutOptions,
+    ) -> OSResult<PutResult> {
+        self.target.put_opts(location, payload, opts).await
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        opts: PutMultipartOptions,
+    ) -> OSResult<Box<dyn MultipartUpload>> {
+        self.target.put_multipart_opts(location, opts).await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> OSResult<GetResult> {
+        if options.head {
+            return self.target.get_opts(location, options).await;
+        }
+        // With a bounded range the size is known up front, otherwise wait for
+        // budget once the response says how large the body is.
+        if let Some(GetRange::Bounded(range)) = &options.range {
+            let permit = self.limiter.acquire(range.end - range.start).await?;
+            let result = self.target.get_opts(location, options).await?;
+            return Ok(hold_until_consumed(result, permit));
+        }
+        let result = self.target.get_opts(location, options).await?;
+        let permit = self
+            .limiter
+            .acquire(result.range.end - result.range.start)
+            .await?;
+        Ok(hold_until_consumed(result, permit))
+    }
+
+    async fn get_ranges(&self, location: &Path, ranges: &[Range<u64>]) -> OSResult<Vec<Bytes>> {
+        let num_bytes = ranges.iter().map(|range| range.end - range.start).sum();
+        let _permit = self.limiter.acquire(num_bytes).await?;
+        self.target.get_ranges(location, ranges).await
+    }
+
+    fn delete_stream(
+        &self,
+        locations: BoxStream<'static, OSResult<Path>>,
+    ) -> BoxStream<'static, OSResult<Path>> {
+        self.target.delete_stream(locations)
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'static, OSResult<ObjectMeta>> {
+        self.target.list(prefix)
+    }
+
+    fn list_with_offset(
+        &self,
+        prefix: Option<&Path>,
+        offset: &Path,
+    ) -> BoxStream<'static, OSResult<ObjectMeta>> {
+        self.target.list_with_offset(prefix, offset)
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> OSResult<ListResult> {
+        self.target.list_with_delimiter(prefix).await
+    }
+
+    async fn copy_opts(&self, from: &Path, to: &Path, opts: CopyOptions) -> OSResult<()> {
+        self.target.copy_opts(from, to, opts).await
+    }
+
+    async fn rename_opts(&self, from: &Path, to: &Path, opts: RenameOptions) -> OSResult<()> {
+        self.target.rename_opts(from, to, opts).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use object_store::ObjectStoreExt;
+    use object_store::memory::InMemory;
+
+    use super::*;
+
+    async fn store_with_budget(capacity: u64) -> (ReadBufferLimitedStore, IOTracker, Path) {
+        let inner = Arc::new(InMemory::new());
+        let path = Path::from("data.lance");
+        inner
+            .put(&path, PutPayload::from(vec![7u8; 1024]))
+            .await
+            .unwrap();
+        let io_tracker = IOTracker::default();
+        let limiter = ReadBufferLimiter::new(capacity, io_tracker.clone());
+        (
+            ReadBufferLimitedStore::new(inner, limiter),
+            io_tracker,
+            path,
+        )
+    }
+
+    #[test]
+    fn test_from_storage_options() {
+        let limiter = ReadBufferLimiter::from_storage_options(None, IOTracker::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(limiter.capacity as u64, DEFAULT_READ_BUFFER_BYTES);
+
+        let options =
+            |value: &str| HashMap::from([(READ_BUFFER_BYTES_KEY.to_string(), value.to_string())]);
+        let limiter =
+            ReadBufferLimiter::from_storage_options(Some(&options("4096")), IOTracker::default())
+                .unwrap()
+                .unwrap();
+        assert_eq!(limiter.capacity, 4096);
+        assert!(
+            ReadBufferLimiter::from_storage_options(Some(&options("0")), IOTracker::default())
+                .unwrap()
+                .is_none()
+        );
+        let err =
+            ReadBufferLimiter::from_storage_options(Some(&options("lots")), IOTracker::default())
+                .unwrap_err();
+        assert!(err.to_string().contains(READ_BUFFER_BYTES_KEY), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_budget_held_until_body_consumed() {
+        let (store, io_tracker, path) = store_with_budget(1000).await;
+
+        let result = store
+            .get_opts(
+                &path,
+                GetOptions {
+                    range: Some((0..600).into()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(store.limiter.buffered_bytes(), 600);
+        assert_eq!(io_tracker.stats().read_buffer_bytes, 600);
+
+        // A second read that does not fit waits for the first to be consumed.
+        let second = store.get_range(&path, 0..600);
+        tokio::pin!(second);
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), &mut second)
+                .await
+                .is_err()
+        );
+        assert_eq!(result.bytes().await.unwrap().len(), 600);
+        assert_eq!(second.await.unwrap().len(), 600);
+
+        assert_eq!(store.limiter.buffered_bytes(), 0);
+        let stats = io_tracker.stats();
+        assert_eq!(stats.read_buffer_bytes, 0);
+        assert!(stats.read_buffer_wait >= Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn test_whole_object_and_ranges() {
+        let (store, io_tracker, path) = store_with_budget(256).await;
+
+        // Larger than the whole budget: runs alone instead of waiting forever.
+        let bytes = store.get(&path).await.unwrap().bytes().await.unwrap();
+        assert_eq!(bytes.len(), 1024);
+
+        let ranges = store.get_ranges(&path, &[0..100, 200..300]).await.unwrap();
+        assert_eq!(ranges.iter().map(Bytes::len).sum::<usize>(), 200);
+
+        // HEADs carry no body and take no budget.
+        store.head(&path).await.unwrap();
+        assert_eq!(store.limiter.buffered_bytes(), 0);
+        assert_eq!(io_tracker.stats().read_buffer_bytes, 0);
+    }
+}
@@ -0,0 +1,169 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Read-only access to any object store.
+//!
+//! Setting the `storage_read_only` storage option wraps the store in a
+//! [`ReadOnlyStore`], which rejects every put, multipart upload, copy, rename
+//! and delete before it reaches the backend. Reads and listings pass through
+//! unchanged.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures::StreamExt;
+use futures::stream::BoxStream;
+use lance_core::utils::parse::str_is_truthy;
+use object_store::path::Path;
+use object_store::{
+    CopyOptions, GetOptions, GetResult, ListResult, MultipartUpload, ObjectMeta, ObjectStore,
+    PutMultipartOptions, PutOptions, PutPayload, PutResult, RenameOptions, Result as OSResult,
+};
+
+/// Storage option that makes the store reject all writes.
+pub const READ_ONLY_KEY: &str = "storage_read_only";
+
+/// Message carried by every rejected write.
+pub(crate) const READ_ONLY_MESSAGE: &str = "store is read-only";
+
+/// Returns `true` if the storage options ask for a read-only store.
+pub fn is_read_only(storage_options: Option<&HashMap<String, String>>) -> bool {
+    storage_options
+        .and_then(|opts| opts.get(READ_ONLY_KEY))
+        .is_some_and(|value| str_is_truthy(value))
+}
+
+fn read_only_error() -> object_store::Error {
+    object_store::Error::NotSupported {
+        source: READ_ONLY_MESSAGE.into(),
+    }
+}
+
+/// An [`ObjectStore`] wrapper that fails every mutating call.
+#[derive(Debug)]
+pub struct ReadOnlyStore {
+    target: Arc<dyn ObjectStore>,
+}
+
+impl ReadOnlyStore {
+    pub fn new(target: Arc<dyn ObjectStore>) -> Self {
+        Self { target }
+    }
+}
+
+impl Display for ReadOnlyStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ReadOnlyStore({})", self.target)
+    }
+}
+
+#[async_trait::async_trait]
+#[deny(clippy::missing_trait_methods)]
+impl ObjectStore for ReadOnlyStore {
+    async fn put_opts(
+        &self,
+        _location: &Path,
+        _payload: PutPayload,
+        _opts: PutOptions,
+    ) -> OSResult<PutResult> {
+        Err(read_only_error())
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        _location: &Path,
+        _opts: PutMultipartOptions,
+    ) -> OSResult<Box<dyn MultipartUpload>> {
+        Err(read_only_error())
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> OSResult<GetResult> {
+        self.target.get_opts(location, options).await
+    }
+
+    async fn get_ranges(&self, location: &Path, ranges: &[Range<u64>]) -> OSResult<Vec<Bytes>> {
+        self.target.get_ranges(location, ranges).await
+    }
+
+    fn delete_stream(
+        &self,
+        locations: BoxStream<'static, OSResult<Path>>,
+    ) -> BoxStream<'static, OSResult<Path>> {
+        locations
+            .map(|location| location.and_then(|_| Err(read_only_error())))
+            .boxed()
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'static, OSResult<ObjectMeta>> {
+        self.target.list(prefix)
+    }
+
+    fn list_with_offset(
+        &self,
+        prefix: Option<&Path>,
+        offset: &Path,
+    ) -> BoxStream<'static, OSResult<ObjectMeta>> {
+        self.target.list_with_offset(prefix, offset)
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> OSResult<ListResult> {
+        self.target.list_with_delimiter(prefix).await
+    }
+
+    async fn copy_opts(&self, _from: &Path, _to: &Path, _opts: CopyOptions) -> OSResult<()> {
+        Err(read_only_error())
+    }
+
+    async fn rename_opts(&self, _from: &Path, _to: &Path, _opts: RenameOptions) -> OSResult<()> {
+        Err(read_only_error())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use object_store::ObjectStoreExt;
+    use object_store::memory::InMemory;
+
+    use super::*;
+
+    #[test]
+    fn test_is_read_only() {
+        assert!(!is_read_only(None));
+        let options = |value: &str| HashMap::from([(READ_ONLY_KEY.to_string(), value.to_string())]);
+        assert!(is_read_only(Some(&options("true"))));
+        assert!(is_read_only(Some(&options("1"))));
+        assert!(!is_read_only(Some(&options("false"))));
+    }
+
+    #[tokio::test]
+    async fn test_writes_are_rejected() {
+        let inner = Arc::new(InMemory::new());
+        let path = Path::from("data.lance");
+        inner
+            .put(&path, PutPayload::from_static(b"LANCE"))
+            .await
+            .unwrap();
+        let store = ReadOnlyStore::new(inner.clone());
+
+        let bytes = store.get(&path).await.unwrap().bytes().await.unwrap();
+        assert_eq!(bytes.as_ref(), b"LANCE");
+
+        let other = Path::from("other.lance");
+        let err = store
+            .put(&other, PutPayload::from_static(b"x"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, object_store::Error::NotSupported { .. }));
+        assert!(store.put_multipart(&other).await.is_err());
+        assert!(store.copy(&path, &other).await.is_err());
+        assert!(store.rename(&path, &other).await.is_err());
+        assert!(store.delete(&path).await.is_err());
+
+        // Nothing reached the backend.
+        assert!(inner.head(&path).await.is_ok());
+        assert!(inner.head(&other).await.is_err());
+    }
+}
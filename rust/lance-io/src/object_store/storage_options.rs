@@ -11,7 +11,8 @@
 
 use std::collections::HashMap;
 use std::fmt;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Weak};
 use std::time::Duration;
 
 #[cfg(test)]
@@ -21,8 +22,10 @@ use mock_instant::thread_local::{SystemTime, UNIX_EPOCH};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use async_trait::async_trait;
+use lance_core::utils::parse::str_is_truthy;
 use lance_namespace::LanceNamespace;
 use lance_namespace::models::DescribeTableRequest;
+use rand::Rng;
 use tokio::sync::RwLock;
 
 use crate::{Error, Result};
@@ -33,9 +36,31 @@ pub const EXPIRES_AT_MILLIS_KEY: &str = "expires_at_millis";
 /// Key for the refresh offset in storage options HashMap (milliseconds before expiry to refresh)
 pub const REFRESH_OFFSET_MILLIS_KEY: &str = "refresh_offset_millis";
 
+/// Key for the refresh jitter in storage options HashMap (milliseconds)
+///
+/// Each time options are fetched, the next refresh is moved earlier by a random amount
+/// of up to this many milliseconds on top of [`REFRESH_OFFSET_MILLIS_KEY`].  Processes
+/// that were handed credentials with the same expiration then renew them at different
+/// times instead of all calling the provider at once.
+pub const REFRESH_JITTER_MILLIS_KEY: &str = "refresh_jitter_millis";
+
+/// Key to refresh options from a background task (`true`/`false`)
+///
+/// By default options are refreshed by the first request after the refresh time.  With
+/// this set the refresh runs in the background at the refresh time, so requests never
+/// wait on the provider while options are still valid.
+pub const REFRESH_IN_BACKGROUND_KEY: &str = "refresh_in_background";
+
 /// Default refresh offset: 60 seconds before expiration
 const DEFAULT_REFRESH_OFFSET_MILLIS: u64 = 60_000;
 
+/// Minimum time between two background refreshes, so a provider that vends options
+/// which are already within the refresh window is not called in a tight loop.
+const MIN_BACKGROUND_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Delay before a failed background refresh is retried
+const BACKGROUND_REFRESH_RETRY_DELAY: Duration = Duration::from_secs(5);
+
 /// Trait for providing storage options with expiration tracking
 ///
 /// Implementations can fetch storage options from various sources (namespace servers,
@@ -184,6 +209,15 @@ pub struct StorageOptionsAccessor {
 
     /// Duration before expiry to trigger refresh
     refresh_offset: Duration,
+
+    /// Maximum random duration added to `refresh_offset` for each fetch
+    refresh_jitter: Duration,
+
+    /// Whether to refresh from a background task instead of on access
+    refresh_in_background: bool,
+
+    /// Set once the background refresh task has been spawned
+    background_refresh_started: AtomicBool,
 }
 
 impl fmt::Debug for StorageOptionsAccessor {
@@ -192,6 +226,8 @@ impl fmt::Debug for StorageOptionsAccessor {
             .field("has_initial_options", &self.initial_options.is_some())
             .field("has_provider", &self.provider.is_some())
             .field("refresh_offset", &self.refresh_offset)
+            .field("refresh_jitter", &self.refresh_jitter)
+            .field("refresh_in_background", &self.refresh_in_background)
             .finish()
     }
 }
@@ -200,6 +236,15 @@ impl fmt::Debug for StorageOptionsAccessor {
 struct CachedStorageOptions {
     options: HashMap<String, String>,
     expires_at_millis: Option<u64>,
+    /// When to refresh, `expires_at_millis` less the refresh offset and jitter
+    refresh_at_millis: Option<u64>,
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::from_secs(0))
+        .as_millis() as u64
 }
 
 impl StorageOptionsAccessor {
@@ -212,25 +257,61 @@ impl StorageOptionsAccessor {
             .unwrap_or(Duration::from_millis(DEFAULT_REFRESH_OFFSET_MILLIS))
     }
 
+    /// Extract refresh jitter from storage options, or use no jitter
+    fn extract_refresh_jitter(options: &HashMap<String, String>) -> Duration {
+        options
+            .get(REFRESH_JITTER_MILLIS_KEY)
+            .and_then(|s| s.parse::<u64>().ok())
+            .map(Duration::from_millis)
+            .unwrap_or_default()
+    }
+
+    fn extract_refresh_in_background(options: &HashMap<String, String>) -> bool {
+        options
+            .get(REFRESH_IN_BACKGROUND_KEY)
+            .is_some_and(|value| str_is_truthy(value))
+    }
+
+    /// Cache `options`, picking the time to refresh them
+    fn cache_entry(&self, options: HashMap<String, String>) -> CachedStorageOptions {
+        let expires_at_millis = options
+            .get(EXPIRES_AT_MILLIS_KEY)
+            .and_then(|s| s.parse::<u64>().ok());
+        let refresh_at_millis = expires_at_millis.map(|expires_at| {
+            let jitter_millis = self.refresh_jitter.as_millis() as u64;
+            let jitter_millis = if jitter_millis > 0 {
+                rand::rng().random_range(0..=jitter_millis)
+            } else {
+                0
+            };
+            expires_at
+                .saturating_sub(self.refresh_offset.as_millis() as u64)
+                .saturating_sub(jitter_millis)
+        });
+        CachedStorageOptions {
+            options,
+            expires_at_millis,
+            refresh_at_millis,
+        }
+    }
+
     /// Create an accessor with only static options (no refresh capability)
     ///
     /// The returned accessor will always return the provided options.
     /// This is useful when credentials don't expire or are managed externally.
     pub fn with_static_options(options: HashMap<String, String>) -> Self {
-        let expires_at_millis = options
-            .get(EXPIRES_AT_MILLIS_KEY)
-            .and_then(|s| s.parse::<u64>().ok());
-        let refresh_offset = Self::extract_refresh_offset(&options);
-
-        Self {
+        let accessor = Self {
             initial_options: Some(options.clone()),
             provider: None,
-            cache: Arc::new(RwLock::new(Some(CachedStorageOptions {
-                options,
-                expires_at_millis,
-            }))),
-            refresh_offset,
-        }
+            cache: Arc::new(RwLock::new(None)),
+            refresh_offset: Self::extract_refresh_offset(&options),
+            refresh_jitter: Self::extract_refresh_jitter(&options),
+            refresh_in_background: false,
+            background_refresh_started: AtomicBool::new(false),
+        };
+        let cached = accessor.cache_entry(options);
+        *accessor.cache.try_write().expect("cache is not shared yet") = Some(cached);
+        accessor
     }
 
     /// Create an accessor with a dynamic provider (no initial options)
@@ -247,6 +328,9 @@ impl StorageOptionsAccessor {
             provider: Some(provider),
             cache: Arc::new(RwLock::new(None)),
             refresh_offset: Duration::from_millis(DEFAULT_REFRESH_OFFSET_MILLIS),
+            refresh_jitter: Duration::ZERO,
+            refresh_in_background: false,
+            background_refresh_started: AtomicBool::new(false),
         }
     }
 
@@ -254,7 +338,8 @@ impl StorageOptionsAccessor {
     ///
     /// Initial options are used until they expire, then the provider is called.
     /// This avoids an immediate fetch when initial credentials are still valid.
-    /// The `refresh_offset_millis` key in initial_options controls refresh timing.
+    /// The `refresh_offset_millis`, `refresh_jitter_millis` and `refresh_in_background`
+    /// keys in initial_options control refresh timing.
     ///
     /// # Arguments
     /// * `initial_options` - Initial storage options to cache
@@ -263,20 +348,69 @@ impl StorageOptionsAccessor {
         initial_options: HashMap<String, String>,
         provider: Arc<dyn StorageOptionsProvider>,
     ) -> Self {
-        let expires_at_millis = initial_options
-            .get(EXPIRES_AT_MILLIS_KEY)
-            .and_then(|s| s.parse::<u64>().ok());
-        let refresh_offset = Self::extract_refresh_offset(&initial_options);
-
-        Self {
+        let accessor = Self {
             initial_options: Some(initial_options.clone()),
             provider: Some(provider),
-            cache: Arc::new(RwLock::new(Some(CachedStorageOptions {
-                options: initial_options,
-                expires_at_millis,
-            }))),
-            refresh_offset,
+            cache: Arc::new(RwLock::new(None)),
+            refresh_offset: Self::extract_refresh_offset(&initial_options),
+            refresh_jitter: Self::extract_refresh_jitter(&initial_options),
+            refresh_in_background: Self::extract_refresh_in_background(&initial_options),
+            background_refresh_started: AtomicBool::new(false),
+        };
+        let cached = accessor.cache_entry(initial_options);
+        *accessor.cache.try_write().expect("cache is not shared yet") = Some(cached);
+        accessor
+    }
+
+    /// Set the duration before expiry to refresh options
+    pub fn with_refresh_offset(mut self, refresh_offset: Duration) -> Self {
+        self.refresh_offset = refresh_offset;
+        self.reschedule()
+    }
+
+    /// Refresh up to `refresh_jitter` earlier than the refresh offset, picked at random
+    ///
+    /// See [`REFRESH_JITTER_MILLIS_KEY`].
+    pub fn with_refresh_jitter(mut self, refresh_jitter: Duration) -> Self {
+        self.refresh_jitter = refresh_jitter;
+        self.reschedule()
+    }
+
+    /// Refresh options from a background task, see [`REFRESH_IN_BACKGROUND_KEY`]
+    ///
+    /// The task is started by [`Self::spawn_background_refresh`], which the object
+    /// stores call when they are built with this accessor.
+    pub fn with_refresh_in_background(mut self, refresh_in_background: bool) -> Self {
+        self.refresh_in_background = refresh_in_background;
+        self
+    }
+
+    /// Apply the refresh settings found in `options`
+    ///
+    /// This reads [`REFRESH_OFFSET_MILLIS_KEY`], [`REFRESH_JITTER_MILLIS_KEY`] and
+    /// [`REFRESH_IN_BACKGROUND_KEY`] and leaves settings whose key is absent unchanged.
+    pub fn with_refresh_settings_from(mut self, options: &HashMap<String, String>) -> Self {
+        if options.contains_key(REFRESH_OFFSET_MILLIS_KEY) {
+            self.refresh_offset = Self::extract_refresh_offset(options);
+        }
+        if options.contains_key(REFRESH_JITTER_MILLIS_KEY) {
+            self.refresh_jitter = Self::extract_refresh_jitter(options);
+        }
+        if options.contains_key(REFRESH_IN_BACKGROUND_KEY) {
+            self.refresh_in_background = Self::extract_refresh_in_background(options);
+        }
+        self.reschedule()
+    }
+
+    /// Recompute the refresh time of the cached options after a settings change
+    fn reschedule(self) -> Self {
+        {
+            let mut cache = self.cache.try_write().expect("cache is not shared yet");
+            if let Some(cached) = cache.take() {
+                *cache = Some(self.cache_entry(cached.options));
+            }
         }
+        self
     }
 
     /// Get current valid storage options
@@ -307,7 +441,14 @@ impl StorageOptionsAccessor {
     ///
     /// This bypasses the cache for callers that need to validate provider-vended
     /// credentials even when initial metadata has no expiration.
-    #[cfg(any(feature = "aws", feature = "azure", feature = "gcp"))]
+    #[cfg(any(
+        feature = "aws",
+        feature = "azure",
+        feature = "gcp",
+        feature = "oss",
+        feature = "huggingface",
+        feature = "tencent"
+    ))]
     pub(crate) async fn refresh_storage_options(&self) -> Result<super::StorageOptions> {
         let Some(provider) = &self.provider else {
             return self.get_storage_options().await;
@@ -336,15 +477,8 @@ impl StorageOptionsAccessor {
             return Ok(super::StorageOptions(HashMap::new()));
         };
 
-        let expires_at_millis = options
-            .get(EXPIRES_AT_MILLIS_KEY)
-            .and_then(|s| s.parse::<u64>().ok());
-
         let mut cache = self.cache.write().await;
-        *cache = Some(CachedStorageOptions {
-            options: options.clone(),
-            expires_at_millis,
-        });
+        *cache = Some(self.cache_entry(options.clone()));
 
         Ok(super::StorageOptions(options))
     }
@@ -409,16 +543,9 @@ impl StorageOptionsAccessor {
             return Ok(Some(super::StorageOptions(HashMap::new())));
         };
 
-        let expires_at_millis = options
-            .get(EXPIRES_AT_MILLIS_KEY)
-            .and_then(|s| s.parse::<u64>().ok());
-
-        if let Some(expires_at) = expires_at_millis {
-            let now_ms = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or(Duration::from_secs(0))
-                .as_millis() as u64;
-            let expires_in_secs = (expires_at.saturating_sub(now_ms)) / 1000;
+        let cached = self.cache_entry(options.clone());
+        if let Some(expires_at) = cached.expires_at_millis {
+            let expires_in_secs = (expires_at.saturating_sub(now_millis())) / 1000;
             log::debug!(
                 "Successfully refreshed storage options from provider: {}, options expire in {} seconds",
                 provider.provider_id(),
@@ -431,10 +558,7 @@ impl StorageOptionsAccessor {
             );
         }
 
-        *cache = Some(CachedStorageOptions {
-            options: options.clone(),
-            expires_at_millis,
-        });
+        *cache = Some(cached);
 
         Ok(Some(super::StorageOptions(options)))
     }
@@ -442,21 +566,11 @@ impl StorageOptionsAccessor {
     fn needs_refresh(&self, cached: &Option<CachedStorageOptions>) -> bool {
         match cached {
             None => true,
-            Some(cached_opts) => {
-                if let Some(expires_at_millis) = cached_opts.expires_at_millis {
-                    let now_ms = SystemTime::now()
-                        .duration_since(UNIX_EPOCH)
-                        .unwrap_or(Duration::from_secs(0))
-                        .as_millis() as u64;
-
-                    // Refresh if we're within the refresh offset of expiration
-                    let refresh_offset_millis = self.refresh_offset.as_millis() as u64;
-                    now_ms + refresh_offset_millis >= expires_at_millis
-                } else {
-                    // No expiration means options never expire
-                    false
-                }
-            }
+            // Refresh if we're within the refresh offset (and jitter) of expiration.
+            // No expiration means options never expire.
+            Some(cached_opts) => cached_opts
+                .refresh_at_millis
+                .is_some_and(|refresh_at| now_millis() >= refresh_at),
         }
     }
 
@@ -503,14 +617,91 @@ impl StorageOptionsAccessor {
         self.refresh_offset
     }
 
+    /// Get the refresh jitter duration
+    pub fn refresh_jitter(&self) -> Duration {
+        self.refresh_jitter
+    }
+
+    /// Whether options should be refreshed from a background task
+    pub fn refresh_in_background(&self) -> bool {
+        self.refresh_in_background
+    }
+
     /// Get the storage options provider, if any
     pub fn provider(&self) -> Option<&Arc<dyn StorageOptionsProvider>> {
         self.provider.as_ref()
     }
+
+    /// The epoch time in milliseconds at which the cached options will next be refreshed
+    ///
+    /// Returns None if nothing is cached yet or the cached options never expire.
+    pub async fn next_refresh_at_millis(&self) -> Option<u64> {
+        self.cache.read().await.as_ref()?.refresh_at_millis
+    }
+
+    /// Spawn a task that refreshes the options when they reach their refresh time
+    ///
+    /// The task holds a weak reference and exits once the accessor is dropped or the
+    /// options no longer expire.  A failed refresh is logged and retried after a short
+    /// delay; requests still refresh on access in the meantime.
+    ///
+    /// Returns None without spawning a task if there is no provider or a task has already
+    /// been spawned for this accessor.
+    pub fn spawn_background_refresh(self: &Arc<Self>) -> Option<tokio::task::JoinHandle<()>> {
+        if self.provider.is_none() || self.background_refresh_started.swap(true, Ordering::AcqRel) {
+            return None;
+        }
+        let accessor = Arc::downgrade(self);
+        Some(tokio::spawn(Self::background_refresh(accessor)))
+    }
+
+    async fn background_refresh(accessor: Weak<Self>) {
+        loop {
+            let delay = {
+                let Some(accessor) = accessor.upgrade() else {
+                    return;
+                };
+                match accessor.get_storage_options().await {
+                    Ok(_) => {
+                        let Some(refresh_at) = accessor.next_refresh_at_millis().await else {
+                            return;
+                        };
+                        Duration::from_millis(refresh_at.saturating_sub(now_millis()))
+                    }
+                    Err(e) => {
+                        log::warn!(
+                            "Background refresh of storage options from {} failed: {}",
+                            accessor.accessor_id(),
+                            e
+                        );
+                        BACKGROUND_REFRESH_RETRY_DELAY
+                    }
+                }
+            };
+            tokio::time::sleep(delay.max(MIN_BACKGROUND_REFRESH_INTERVAL)).await;
+        }
+    }
+
+    /// Spawn the background refresh task if [`REFRESH_IN_BACKGROUND_KEY`] is set
+    #[cfg(any(
+        feature = "aws",
+        feature = "azure",
+        feature = "gcp",
+        feature = "oss",
+        feature = "huggingface",
+        feature = "tencent"
+    ))]
+    pub(crate) fn maybe_spawn_background_refresh(self: &Arc<Self>) {
+        if self.refresh_in_background {
+            self.spawn_background_refresh();
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
+
     use super::*;
     use mock_instant::thread_local::MockClock;
 
@@ -653,6 +844,78 @@ mod tests {
         assert_eq!(mock_provider.get_call_count().await, 1);
     }
 
+    #[tokio::test]
+    async fn test_refresh_jitter_spreads_refreshes() {
+        MockClock::set_system_time(Duration::from_secs(100_000));
+
+        let now_ms = MockClock::system_time().as_millis() as u64;
+        let expires_at = now_ms + 600_000;
+        let initial = HashMap::from([
+            (EXPIRES_AT_MILLIS_KEY.to_string(), expires_at.to_string()),
+            (REFRESH_OFFSET_MILLIS_KEY.to_string(), "60000".to_string()),
+            (REFRESH_JITTER_MILLIS_KEY.to_string(), "120000".to_string()),
+        ]);
+
+        let mut refresh_times = HashSet::new();
+        for _ in 0..50 {
+            let accessor = StorageOptionsAccessor::with_initial_and_provider(
+                initial.clone(),
+                Arc::new(MockStorageOptionsProvider::new(Some(600_000))),
+            );
+            let refresh_at = accessor.next_refresh_at_millis().await.unwrap();
+            assert!(
+                (expires_at - 180_000..=expires_at - 60_000).contains(&refresh_at),
+                "{refresh_at}"
+            );
+            refresh_times.insert(refresh_at);
+        }
+        assert!(refresh_times.len() > 1);
+
+        // The accessor refreshes at its own jittered time
+        let mock_provider = Arc::new(MockStorageOptionsProvider::new(Some(600_000)));
+        let accessor =
+            StorageOptionsAccessor::with_initial_and_provider(initial, mock_provider.clone());
+        let refresh_at = accessor.next_refresh_at_millis().await.unwrap();
+        MockClock::set_system_time(Duration::from_millis(refresh_at - 1));
+        accessor.get_storage_options().await.unwrap();
+        assert_eq!(mock_provider.get_call_count().await, 0);
+        MockClock::set_system_time(Duration::from_millis(refresh_at));
+        accessor.get_storage_options().await.unwrap();
+        assert_eq!(mock_provider.get_call_count().await, 1);
+        assert!(accessor.next_refresh_at_millis().await.unwrap() > refresh_at);
+    }
+
+    #[tokio::test]
+    async fn test_background_refresh() {
+        MockClock::set_system_time(Duration::from_secs(100_000));
+
+        let now_ms = MockClock::system_time().as_millis() as u64;
+        let initial = HashMap::from([
+            (
+                EXPIRES_AT_MILLIS_KEY.to_string(),
+                (now_ms + 600_000).to_string(),
+            ),
+            (REFRESH_IN_BACKGROUND_KEY.to_string(), "true".to_string()),
+        ]);
+        let mock_provider = Arc::new(MockStorageOptionsProvider::new(Some(600_000)));
+        let accessor = Arc::new(StorageOptionsAccessor::with_initial_and_provider(
+            initial,
+            mock_provider.clone(),
+        ));
+        assert!(accessor.refresh_in_background());
+        // Move past the refresh time.  The options are renewed without any request.
+        MockClock::set_system_time(Duration::from_secs(100_000 + 590));
+        let handle = accessor.spawn_background_refresh().unwrap();
+        assert!(accessor.spawn_background_refresh().is_none());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(mock_provider.get_call_count().await, 1);
+        assert_eq!(
+            accessor.next_refresh_at_millis().await,
+            Some((100_000 + 590) * 1000 + 600_000 - DEFAULT_REFRESH_OFFSET_MILLIS)
+        );
+        handle.abort();
+    }
+
     #[tokio::test]
     async fn test_expired_initial_triggers_refresh() {
         MockClock::set_system_time(Duration::from_secs(100_000));
@@ -43,6 +43,9 @@ use rand::Rng;
 use tokio::sync::Mutex;
 use tracing::{debug, warn};
 
+use crate::deadline;
+use crate::object_store::classification::{is_throttled, retry_delay};
+
 /// Check whether an `object_store::Error` represents a throttle response
 /// (HTTP 429 / 503) from a cloud object store.
 ///
@@ -397,7 +400,7 @@ impl OperationThrottle {
     fn observe_outcome<T>(&self, result: &OSResult<T>) {
         let outcome = match result {
             Ok(_) => RequestOutcome::Success,
-            Err(err) if is_throttle_error(err) => {
+            Err(err) if is_throttled(err) => {
                 debug!(
                     target: TRACE_OBJECT_STORE_THROTTLE,
                     error = %err,
@@ -425,9 +428,24 @@ impl OperationThrottle {
         }
     }
 
+    /// The backoff before retrying the throttle error `err`: a random backoff
+    /// between `min_backoff_ms` and `max_backoff_ms`, or the `Retry-After` of
+    /// the response if it is longer.
+    ///
+    /// Returns `None` if the backoff would end after the current
+    /// [deadline](crate::deadline) or the store asked to wait longer than
+    /// [`MAX_RETRY_AFTER`](crate::object_store::classification::MAX_RETRY_AFTER).
+    fn retry_backoff(&self, err: &object_store::Error) -> Option<std::time::Duration> {
+        let backoff_ms = rand::rng().random_range(self.min_backoff_ms..=self.max_backoff_ms);
+        retry_delay(err, std::time::Duration::from_millis(backoff_ms))
+            .filter(|backoff| deadline::can_retry_after(*backoff))
+    }
+
     /// Execute an operation with throttling: acquire token, run, classify result.
-    /// On throttle errors, retries up to `max_retries` times with a random
-    /// backoff between `min_backoff_ms` and `max_backoff_ms` between attempts.
+    /// On throttle errors, retries up to `max_retries` times, waiting
+    /// [`Self::retry_backoff`] between attempts. Once out of retries the
+    /// throttle error is returned, which classifies as
+    /// [`ErrorClass::Throttled`](lance_core::error::ErrorClass::Throttled).
     async fn throttled<T, F, Fut>(&self, f: F) -> OSResult<T>
     where
         F: Fn() -> Fut,
@@ -438,7 +456,7 @@ impl OperationThrottle {
             let result = f().await;
             let outcome = match &result {
                 Ok(_) => RequestOutcome::Success,
-                Err(err) if is_throttle_error(err) => {
+                Err(err) if is_throttled(err) => {
                     debug!(
                         target: TRACE_OBJECT_STORE_THROTTLE,
                         error = %err,
@@ -464,18 +482,25 @@ impl OperationThrottle {
             self.update_bucket_rate(new_rate).await;
 
             match &result {
-                Err(err) if is_throttle_error(err) && attempt < self.max_retries => {
-                    let backoff_ms =
-                        rand::rng().random_range(self.min_backoff_ms..=self.max_backoff_ms);
+                Err(err) if is_throttled(err) && attempt < self.max_retries => {
+                    let Some(backoff) = self.retry_backoff(err) else {
+                        debug!(
+                            target: TRACE_OBJECT_STORE_THROTTLE,
+                            attempt = attempt + 1,
+                            error = %err,
+                            "Not retrying throttle error, the backoff would end too late"
+                        );
+                        return result;
+                    };
                     debug!(
                         target: TRACE_OBJECT_STORE_THROTTLE,
                         attempt = attempt + 1,
                         max_retries = self.max_retries,
-                        backoff_ms,
+                        backoff_ms = backoff.as_millis() as u64,
                         error = %err,
                         "Retrying after throttle error"
                     );
-                    tokio::time::sleep(std::time::Duration::from_millis(backoff_ms)).await;
+                    tokio::time::sleep(backoff).await;
                     continue;
                 }
                 _ => return result,
@@ -531,10 +556,11 @@ impl MultipartUpload for ThrottledMultipartUpload {
             self.write.observe_outcome(&result);
 
             match &result {
-                Err(err) if is_throttle_error(err) && attempt < self.write.max_retries => {
-                    let backoff_ms = rand::rng()
-                        .random_range(self.write.min_backoff_ms..=self.write.max_backoff_ms);
-                    tokio::time::sleep(std::time::Duration::from_millis(backoff_ms)).await;
+                Err(err) if is_throttled(err) && attempt < self.write.max_retries => {
+                    let Some(backoff) = self.write.retry_backoff(err) else {
+                        return result;
+                    };
+                    tokio::time::sleep(backoff).await;
                     continue;
                 }
                 _ => return result,
@@ -551,10 +577,11 @@ impl MultipartUpload for ThrottledMultipartUpload {
             self.write.observe_outcome(&result);
 
             match &result {
-                Err(err) if is_throttle_error(err) && attempt < self.write.max_retries => {
-                    let backoff_ms = rand::rng()
-                        .random_range(self.write.min_backoff_ms..=self.write.max_backoff_ms);
-                    tokio::time::sleep(std::time::Duration::from_millis(backoff_ms)).await;
+                Err(err) if is_throttled(err) && attempt < self.write.max_retries => {
+                    let Some(backoff) = self.write.retry_backoff(err) else {
+                        return result;
+                    };
+                    tokio::time::sleep(backoff).await;
                     continue;
                 }
                 _ => return result,
@@ -764,6 +791,9 @@ impl ObjectStore for AimdThrottledStore {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::object_store::WrappingObjectStore;
+    use crate::object_store::classification::with_classification;
+    use crate::utils::tracking_store::IOTracker;
     use object_store::memory::InMemory;
     use rstest::rstest;
     use std::collections::VecDeque;
@@ -1536,6 +1566,8 @@ mod tests {
         errors_remaining: std::sync::Mutex<usize>,
         /// Total number of `get` calls observed.
         get_call_count: AtomicU64,
+        /// The message of the throttle errors.
+        error_message: &'static str,
     }
 
     impl RetryTestMockStore {
@@ -1544,6 +1576,14 @@ mod tests {
                 inner: InMemory::new(),
                 errors_remaining: std::sync::Mutex::new(errors_before_success),
                 get_call_count: AtomicU64::new(0),
+                error_message: THROTTLE_ERROR_RESPONSE,
+            }
+        }
+
+        fn with_error_message(self, error_message: &'static str) -> Self {
+            Self {
+                error_message,
+                ..self
             }
         }
     }
@@ -1591,7 +1631,7 @@ mod tests {
             if should_error {
                 Err(object_store::Error::Generic {
                     store: "RetryTestMock",
-                    source: THROTTLE_ERROR_RESPONSE.into(),
+                    source: self.error_message.into(),
                 })
             } else {
                 self.inner.get_opts(location, options).await
@@ -1675,6 +1715,106 @@ mod tests {
         assert_eq!(mock.get_call_count.load(Ordering::Relaxed), 4);
     }
 
+    #[tokio::test]
+    async fn test_throttled_does_not_retry_past_deadline() {
+        let mock = Arc::new(RetryTestMockStore::new(10));
+        let path = Path::from("test/deadline.txt");
+        mock.put(&path, PutPayload::from_static(b"deadline data"))
+            .await
+            .unwrap();
+
+        let config = AimdThrottleConfig {
+            min_backoff_ms: 50,
+            max_backoff_ms: 50,
+            ..Default::default()
+        };
+        let throttled =
+            AimdThrottledStore::new(mock.clone() as Arc<dyn ObjectStore>, config).unwrap();
+
+        // The deadline leaves room for the first backoff only
+        let deadline = std::time::Instant::now() + std::time::Duration::from_millis(80);
+        let result = deadline::with_deadline(deadline, throttled.get(&path)).await;
+        assert!(is_throttle_error(&result.unwrap_err()));
+        assert_eq!(mock.get_call_count.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn test_throttled_waits_for_retry_after() {
+        let mock = Arc::new(
+            RetryTestMockStore::new(1)
+                .with_error_message("HTTP 429 Too Many Requests, Retry-After: 0.3"),
+        );
+        let path = Path::from("test/retry_after.txt");
+        mock.put(&path, PutPayload::from_static(b"data"))
+            .await
+            .unwrap();
+
+        let config = AimdThrottleConfig {
+            min_backoff_ms: 1,
+            max_backoff_ms: 1,
+            ..Default::default()
+        };
+        let throttled =
+            AimdThrottledStore::new(mock.clone() as Arc<dyn ObjectStore>, config).unwrap();
+
+        let start = std::time::Instant::now();
+        throttled.get(&path).await.unwrap();
+        let elapsed = start.elapsed();
+        assert!(
+            elapsed >= std::time::Duration::from_millis(300),
+            "Expected to wait for the Retry-After, but elapsed was {:?}",
+            elapsed
+        );
+        assert_eq!(mock.get_call_count.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn test_throttled_gives_up_on_long_retry_after() {
+        let mock = Arc::new(
+            RetryTestMockStore::new(10)
+                .with_error_message("HTTP 429 Too Many Requests, Retry-After: 3600"),
+        );
+        let path = Path::from("test/retry_after.txt");
+        mock.put(&path, PutPayload::from_static(b"data"))
+            .await
+            .unwrap();
+
+        let throttled = AimdThrottledStore::new(
+            mock.clone() as Arc<dyn ObjectStore>,
+            AimdThrottleConfig::default(),
+        )
+        .unwrap();
+
+        // The error is handed to the caller right away, classified as throttled
+        let err = throttled.get(&path).await.unwrap_err();
+        let err = lance_core::Error::from(with_classification(err));
+        assert_eq!(err.class(), lance_core::error::ErrorClass::Throttled);
+        assert_eq!(mock.get_call_count.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn test_throttle_events_are_counted() {
+        let mock =
+            Arc::new(RetryTestMockStore::new(1).with_error_message("HTTP 429 Too Many Requests"));
+        let path = Path::from("test/counted.txt");
+        mock.put(&path, PutPayload::from_static(b"data"))
+            .await
+            .unwrap();
+
+        // Without retries the tracker sees every throttle response
+        let config = AimdThrottleConfig {
+            max_retries: 0,
+            ..Default::default()
+        };
+        let throttled = AimdThrottledStore::new(mock as Arc<dyn ObjectStore>, config).unwrap();
+        let tracker = IOTracker::default();
+        let store = tracker.wrap("", Arc::new(throttled));
+
+        assert!(store.get(&path).await.is_err());
+        assert!(store.get(&path).await.is_ok());
+        assert_eq!(tracker.stats().throttle_events, 1);
+    }
+
     #[tokio::test]
     async fn test_throttled_multipart_reorders_parts() {
         let store = Arc::new(InMemory::new()) as Arc<dyn ObjectStore>;
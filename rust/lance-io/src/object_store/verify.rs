@@ -0,0 +1,481 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Check that writes landed intact.
+//!
+//! Setting the `storage_verify_after_write` storage option wraps the store in a
+//! [`VerifyingStore`]. After every successful put, and after completing a
+//! multipart upload, it HEADs the object and fails the write if the size, or
+//! the etag when both the put and the HEAD report one, differs from what was
+//! sent. With the value `strict` the object is read back and compared byte for
+//! byte instead, which also catches corruption that keeps the size intact.
+//!
+//! A failed verification does not delete the object: it may be a manifest
+//! that other writers already observed.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures::stream::BoxStream;
+use lance_core::utils::parse::str_is_truthy;
+use lance_core::{Error, Result};
+use object_store::path::Path;
+use object_store::{
+    CopyOptions, GetOptions, GetResult, ListResult, MultipartUpload, ObjectMeta, ObjectStore,
+    ObjectStoreExt, PutMultipartOptions, PutOptions, PutPayload, PutResult, RenameOptions,
+    Result as OSResult, UploadPart,
+};
+
+/// Storage option enabling verification of writes, `true` to HEAD the object
+/// after each write or `strict` to read it back. Default, `false`.
+pub const VERIFY_AFTER_WRITE_KEY: &str = "storage_verify_after_write";
+
+const STORE_NAME: &str = "VerifyAfterWrite";
+
+/// How a [`VerifyingStore`] checks a written object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyMode {
+    /// Compare the size and etag reported by a HEAD request.
+    Head,
+    /// Read the object back and compare every byte.
+    Strict,
+}
+
+impl VerifyMode {
+    /// The mode selected by [`VERIFY_AFTER_WRITE_KEY`], `None` if writes are
+    /// not verified.
+    pub fn from_storage_options(
+        storage_options: Option<&HashMap<String, String>>,
+    ) -> Result<Option<Self>> {
+        let Some(value) = storage_options.and_then(|opts| opts.get(VERIFY_AFTER_WRITE_KEY)) else {
+            return Ok(None);
+        };
+        if value.eq_ignore_ascii_case("strict") {
+            Ok(Some(Self::Strict))
+        } else if str_is_truthy(value) {
+            Ok(Some(Self::Head))
+        } else if ["0", "false", "off", "no", "n"]
+            .iter()
+            .any(|falsy| value.eq_ignore_ascii_case(falsy))
+        {
+            Ok(None)
+        } else {
+            Err(Error::invalid_input(format!(
+                "Invalid value for storage option '{VERIFY_AFTER_WRITE_KEY}': '{value}', expected a boolean or 'strict'"
+            )))
+        }
+    }
+}
+
+fn mismatch(location: &Path, detail: String) -> object_store::Error {
+    object_store::Error::Generic {
+        store: STORE_NAME,
+        source: format!("Verification of {location} after write failed: {detail}").into(),
+    }
+}
+
+/// Check that the object at `location` is the `size` bytes that were written.
+///
+/// `payloads` holds the written data in order, and is only needed in
+/// [`VerifyMode::Strict`].
+async fn verify(
+    store: &dyn ObjectStore,
+    mode: VerifyMode,
+    location: &Path,
+    size: u64,
+    e_tag: Option<&str>,
+    payloads: &[PutPayload],
+) -> OSResult<()> {
+    match mode {
+        VerifyMode::Head => {
+            let meta = store.head(location).await?;
+            if meta.size != size {
+                return Err(mismatch(
+                    location,
+                    format!("wrote {size} bytes but the store has {}", meta.size),
+                ));
+            }
+            if let (Some(expected), Some(actual)) = (e_tag, meta.e_tag.as_deref())
+                && expected != actual
+            {
+                return Err(mismatch(
+                    location,
+                    format!("the put returned etag {expected} but the store has {actual}"),
+                ));
+            }
+        }
+        VerifyMode::Strict => {
+            let data = store
+                .get_opts(location, GetOptions::default())
+                .await?
+                .bytes()
+                .await?;
+            if data.len() as u64 != size {
+                return Err(mismatch(
+                    location,
+                    format!("wrote {size} bytes but read back {}", data.len()),
+                ));
+            }
+            let mut offset = 0;
+            for chunk in payloads.iter().flat_map(|payload| payload.iter()) {
+                if data[offset..offset + chunk.len()] != chunk[..] {
+                    let position = data[offset..]
+                        .iter()
+                        .zip(chunk.iter())
+                        .position(|(a, b)| a != b)
+                        .unwrap_or_default();
+                    return Err(mismatch(
+                        location,
+                        format!("the data read back differs at byte {}", offset + position),
+                    ));
+                }
+                offset += chunk.len();
+            }
+        }
+    }
+    Ok(())
+}
+
+/// An [`ObjectStore`] wrapper that verifies every write, see the module docs.
+#[derive(Debug)]
+pub struct VerifyingStore {
+    target: Arc<dyn ObjectStore>,
+    mode: VerifyMode,
+}
+
+impl VerifyingStore {
+    pub fn new(target: Arc<dyn ObjectStore>, mode: VerifyMode) -> Self {
+        Self { target, mode }
+    }
+}
+
+impl Display for VerifyingStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "VerifyingStore({:?}, {})", self.mode, self.target)
+    }
+}
+
+#[async_trait::async_trait]
+#[deny(clippy::missing_trait_methods)]
+impl ObjectStore for VerifyingStore {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        payload: PutPayload,
+        opts: PutOptions,
+    ) -> OSResult<PutResult> {
+        let size = payload.content_length() as u64;
+        // Cloning only keeps the buffers alive, it does not copy them.
+        let retained = match self.mode {
+            VerifyMode::Head => vec![],
+            VerifyMode::Strict => vec![payload.clone()],
+        };
+        let result = self.target.put_opts(location, payload, opts).await?;
+        verify(
+            self.target.as_ref(),
+            self.mode,
+            location,
+            size,
+            result.e_tag.as_deref(),
+            &retained,
+        )
+        .await?;
+        Ok(result)
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        opts: PutMultipartOptions,
+    ) -> OSResult<Box<dyn MultipartUpload>> {
+        let target = self.target.put_multipart_opts(location, opts).await?;
+        Ok(Box::new(VerifyingUpload {
+            target,
+            store: self.target.clone(),
+            location: location.clone(),
+            mode: self.mode,
+            size: 0,
+            parts: vec![],
+        }))
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> OSResult<GetResult> {
+        self.target.get_opts(location, options).await
+    }
+
+    async fn get_ranges(&self, location: &Path, ranges: &[Range<u64>]) -> OSResult<Vec<Bytes>> {
+        self.target.get_ranges(location, ranges).await
+    }
+
+    fn delete_stream(
+        &self,
+        locations: BoxStream<'static, OSResult<Path>>,
+    ) -> BoxStream<'static, OSResult<Path>> {
+        self.target.delete_stream(locations)
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'static, OSResult<ObjectMeta>> {
+        self.target.list(prefix)
+    }
+
+    fn list_with_offset(
+        &self,
+        prefix: Option<&Path>,
+        offset: &Path,
+    ) -> BoxStream<'static, OSResult<ObjectMeta>> {
+        self.target.list_with_offset(prefix, offset)
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> OSResult<ListResult> {
+        self.target.list_with_delimiter(prefix).await
+    }
+
+    async fn copy_opts(&self, from: &Path, to: &Path, opts: CopyOptions) -> OSResult<()> {
+        self.target.copy_opts(from, to, opts).await
+    }
+
+    async fn rename_opts(&self, from: &Path, to: &Path, opts: RenameOptions) -> OSResult<()> {
+        self.target.rename_opts(from, to, opts).await
+    }
+}
+
+/// Counts, and in strict mode keeps, the uploaded parts to verify the
+/// object once the upload completes.
+#[derive(Debug)]
+struct VerifyingUpload {
+    target: Box<dyn MultipartUpload>,
+    store: Arc<dyn ObjectStore>,
+    location: Path,
+    mode: VerifyMode,
+    size: u64,
+    parts: Vec<PutPayload>,
+}
+
+#[async_trait::async_trait]
+impl MultipartUpload for VerifyingUpload {
+    fn put_part(&mut self, data: PutPayload) -> UploadPart {
+        self.size += data.content_length() as u64;
+        if self.mode == VerifyMode::Strict {
+            self.parts.push(data.clone());
+        }
+        self.target.put_part(data)
+    }
+
+    async fn complete(&mut self) -> OSResult<PutResult> {
+        let result = self.target.complete().await?;
+        verify(
+            self.store.as_ref(),
+            self.mode,
+            &self.location,
+            self.size,
+            result.e_tag.as_deref(),
+            &self.parts,
+        )
+        .await?;
+        Ok(result)
+    }
+
+    async fn abort(&mut self) -> OSResult<()> {
+        self.target.abort().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use object_store::memory::InMemory;
+    use rstest::rstest;
+
+    use super::*;
+
+    #[derive(Debug, Clone, Copy)]
+    enum Corruption {
+        /// Drop the last byte
+        Truncate,
+        /// Flip the lowest bit of the last byte
+        FlipBit,
+        /// Store the data intact but report another etag
+        WrongETag,
+    }
+
+    /// A store that silently corrupts every put.
+    #[derive(Debug)]
+    struct CorruptingStore {
+        inner: InMemory,
+        corruption: Corruption,
+    }
+
+    impl Display for CorruptingStore {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "CorruptingStore")
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ObjectStore for CorruptingStore {
+        async fn put_opts(
+            &self,
+            location: &Path,
+            payload: PutPayload,
+            opts: PutOptions,
+        ) -> OSResult<PutResult> {
+            let mut data = Bytes::from(payload).to_vec();
+            match self.corruption {
+                Corruption::Truncate => {
+                    data.pop();
+                }
+                Corruption::FlipBit => *data.last_mut().unwrap() ^= 1,
+                Corruption::WrongETag => {
+                    let result = self.inner.put_opts(location, data.into(), opts).await?;
+                    return Ok(PutResult {
+                        e_tag: Some("bogus".to_string()),
+                        ..result
+                    });
+                }
+            }
+            self.inner.put_opts(location, data.into(), opts).await
+        }
+
+        async fn put_multipart_opts(
+            &self,
+            location: &Path,
+            opts: PutMultipartOptions,
+        ) -> OSResult<Box<dyn MultipartUpload>> {
+            self.inner.put_multipart_opts(location, opts).await
+        }
+
+        async fn get_opts(&self, location: &Path, options: GetOptions) -> OSResult<GetResult> {
+            self.inner.get_opts(location, options).await
+        }
+
+        fn delete_stream(
+            &self,
+            locations: BoxStream<'static, OSResult<Path>>,
+        ) -> BoxStream<'static, OSResult<Path>> {
+            self.inner.delete_stream(locations)
+        }
+
+        fn list(&self, prefix: Option<&Path>) -> BoxStream<'static, OSResult<ObjectMeta>> {
+            self.inner.list(prefix)
+        }
+
+        async fn list_with_delimiter(&self, prefix: Option<&Path>) -> OSResult<ListResult> {
+            self.inner.list_with_delimiter(prefix).await
+        }
+
+        async fn copy_opts(&self, from: &Path, to: &Path, opts: CopyOptions) -> OSResult<()> {
+            self.inner.copy_opts(from, to, opts).await
+        }
+    }
+
+    #[test]
+    fn test_from_storage_options() {
+        let mode = |value: &str| {
+            VerifyMode::from_storage_options(Some(&HashMap::from([(
+                VERIFY_AFTER_WRITE_KEY.to_string(),
+                value.to_string(),
+            )])))
+        };
+        assert_eq!(VerifyMode::from_storage_options(None).unwrap(), None);
+        assert_eq!(mode("true").unwrap(), Some(VerifyMode::Head));
+        assert_eq!(mode("STRICT").unwrap(), Some(VerifyMode::Strict));
+        assert_eq!(mode("false").unwrap(), None);
+        let err = mode("maybe").unwrap_err();
+        assert!(err.to_string().contains(VERIFY_AFTER_WRITE_KEY), "{err}");
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_intact_writes_pass(
+        #[values(VerifyMode::Head, VerifyMode::Strict)] mode: VerifyMode,
+    ) {
+        let store = VerifyingStore::new(Arc::new(InMemory::new()), mode);
+        let path = Path::from("_versions/1.manifest");
+        store
+            .put(&path, PutPayload::from_static(b"manifest"))
+            .await
+            .unwrap();
+
+        let mut upload = store
+            .put_multipart(&Path::from("data.lance"))
+            .await
+            .unwrap();
+        upload
+            .put_part(PutPayload::from_static(b"abc"))
+            .await
+            .unwrap();
+        upload
+            .put_part(PutPayload::from_static(b"def"))
+            .await
+            .unwrap();
+        upload.complete().await.unwrap();
+        let data = store
+            .get(&Path::from("data.lance"))
+            .await
+            .unwrap()
+            .bytes()
+            .await
+            .unwrap();
+        assert_eq!(data.as_ref(), b"abcdef");
+    }
+
+    #[rstest]
+    #[case::truncated_head(
+        VerifyMode::Head,
+        Corruption::Truncate,
+        Some("wrote 8 bytes but the store has 7")
+    )]
+    #[case::truncated_strict(
+        VerifyMode::Strict,
+        Corruption::Truncate,
+        Some("wrote 8 bytes but read back 7")
+    )]
+    // A flipped bit keeps the size, only reading the object back notices it.
+    #[case::flipped_head(VerifyMode::Head, Corruption::FlipBit, None)]
+    #[case::flipped_strict(VerifyMode::Strict, Corruption::FlipBit, Some("differs at byte 7"))]
+    #[case::etag_head(
+        VerifyMode::Head,
+        Corruption::WrongETag,
+        Some("the put returned etag bogus")
+    )]
+    #[case::etag_strict(VerifyMode::Strict, Corruption::WrongETag, None)]
+    #[tokio::test]
+    async fn test_mismatched_write_fails(
+        #[case] mode: VerifyMode,
+        #[case] corruption: Corruption,
+        #[case] expected: Option<&str>,
+    ) {
+        let target = Arc::new(CorruptingStore {
+            inner: InMemory::new(),
+            corruption,
+        });
+        let store = VerifyingStore::new(target, mode);
+        let result = store
+            .put(
+                &Path::from("_versions/1.manifest"),
+                PutPayload::from_static(b"manifest"),
+            )
+            .await;
+        match expected {
+            None => {
+                result.unwrap();
+            }
+            Some(detail) => {
+                let err = result.unwrap_err();
+                assert!(
+                    matches!(
+                        err,
+                        object_store::Error::Generic {
+                            store: STORE_NAME,
+                            ..
+                        }
+                    ),
+                    "{err}"
+                );
+                assert!(err.to_string().contains(detail), "{err}");
+            }
+        }
+    }
+}
@@ -0,0 +1,397 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Check that whole-object reads are intact as they stream.
+//!
+//! Setting the `storage_verify_on_read` storage option wraps the store in a
+//! [`ReadVerifyingStore`]. When a get returns a whole object whose ETag is the
+//! MD5 of its content, the MD5 of the body is computed while it streams and
+//! compared with the ETag when the body ends. Nothing is buffered, so objects
+//! of any size can be verified.
+//!
+//! A mismatch is only known at the end of the body, after the data has been
+//! handed out. It is returned as the last item of the stream, so a consumer
+//! that reads the body to the end, e.g. with [`GetResult::bytes`], fails and
+//! must discard what it read.
+//!
+//! Only backends whose ETags are the content MD5 are verified, see
+//! [`ObjectStoreProvider::md5_etags`](super::providers::ObjectStoreProvider::md5_etags).
+//! Objects written by multipart uploads have ETags ending in `-<parts>`, and
+//! ranged gets return only part of the object; neither is checked. See
+//! [`verify`](super::verify) for checking writes.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use futures::Stream;
+use futures::stream::BoxStream;
+use futures::{StreamExt, ready};
+use lance_core::utils::parse::str_is_truthy;
+use md5::{Digest, Md5};
+use object_store::path::Path;
+use object_store::{
+    CopyOptions, GetOptions, GetResult, GetResultPayload, ListResult, MultipartUpload, ObjectMeta,
+    ObjectStore, PutMultipartOptions, PutOptions, PutPayload, PutResult, RenameOptions,
+    Result as OSResult,
+};
+
+/// Storage option enabling verification of whole-object reads against their
+/// MD5 ETag. Default, `false`.
+pub const VERIFY_ON_READ_KEY: &str = "storage_verify_on_read";
+
+const STORE_NAME: &str = "VerifyOnRead";
+
+/// Whether the storage options ask for reads to be verified.
+pub fn verify_on_read(storage_options: Option<&HashMap<String, String>>) -> bool {
+    storage_options
+        .and_then(|opts| opts.get(VERIFY_ON_READ_KEY))
+        .is_some_and(|value| str_is_truthy(value))
+}
+
+/// The MD5 an ETag holds, `None` if it is not a plain MD5, like the ETag of a
+/// multipart upload.
+pub fn etag_md5(e_tag: &str) -> Option<[u8; 16]> {
+    let hex = e_tag.trim_matches('"');
+    if hex.len() != 32 || !hex.is_ascii() {
+        return None;
+    }
+    let mut md5 = [0; 16];
+    for (byte, pair) in md5.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Some(md5)
+}
+
+/// Verify that `stream`, the body of the object at `location`, has the MD5
+/// `expected`, see the [module documentation](self).
+pub fn verify_md5_stream(
+    location: Path,
+    expected: [u8; 16],
+    stream: BoxStream<'static, OSResult<Bytes>>,
+) -> BoxStream<'static, OSResult<Bytes>> {
+    Md5VerifyingStream {
+        inner: stream,
+        location,
+        expected,
+        md5: Some(Md5::new()),
+    }
+    .boxed()
+}
+
+struct Md5VerifyingStream {
+    inner: BoxStream<'static, OSResult<Bytes>>,
+    location: Path,
+    expected: [u8; 16],
+    /// `None` once the result of the verification was returned.
+    md5: Option<Md5>,
+}
+
+impl Stream for Md5VerifyingStream {
+    type Item = OSResult<Bytes>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let Some(md5) = this.md5.as_mut() else {
+            return Poll::Ready(None);
+        };
+        match ready!(this.inner.poll_next_unpin(cx)) {
+            Some(Ok(bytes)) => {
+                md5.update(&bytes);
+                Poll::Ready(Some(Ok(bytes)))
+            }
+            Some(Err(err)) => {
+                // The body is incomplete, so there is nothing to verify.
+                this.md5 = None;
+                Poll::Ready(Some(Err(err)))
+            }
+            None => {
+                let actual: [u8; 16] = this.md5.take().unwrap().finalize().into();
+                if actual == this.expected {
+                    return Poll::Ready(None);
+                }
+                Poll::Ready(Some(Err(object_store::Error::Generic {
+                    store: STORE_NAME,
+                    source: format!(
+                        "Verification of {} failed: the ETag is MD5 {} but the data read has MD5 {}",
+                        this.location,
+                        hex_string(&this.expected),
+                        hex_string(&actual)
+                    )
+                    .into(),
+                })))
+            }
+        }
+    }
+}
+
+pub(crate) fn hex_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// An [`ObjectStore`] wrapper that verifies whole-object reads, see the
+/// [module documentation](self).
+#[derive(Debug)]
+pub struct ReadVerifyingStore {
+    target: Arc<dyn ObjectStore>,
+}
+
+impl ReadVerifyingStore {
+    pub fn new(target: Arc<dyn ObjectStore>) -> Self {
+        Self { target }
+    }
+}
+
+impl Display for ReadVerifyingStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ReadVerifyingStore({})", self.target)
+    }
+}
+
+#[async_trait::async_trait]
+#[deny(clippy::missing_trait_methods)]
+impl ObjectStore for ReadVerifyingStore {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        payload: PutPayload,
+        opts: PutOptions,
+    ) -> OSResult<PutResult> {
+        self.target.put_opts(location, payload, opts).await
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        opts: PutMultipartOptions,
+    ) -> OSResult<Box<dyn MultipartUpload>> {
+        self.target.put_multipart_opts(location, opts).await
+    }
+
+    async fn get_opts(&self, location: &Path, options: GetOptions) -> OSResult<GetResult> {
+        let whole_object = options.range.is_none() && !options.head;
+        let result = self.target.get_opts(location, options).await?;
+        let expected = result.meta.e_tag.as_deref().and_then(etag_md5);
+        match (result.payload, expected) {
+            (GetResultPayload::Stream(stream), Some(expected))
+                if whole_object && result.range == (0..result.meta.size) =>
+            {
+                Ok(GetResult {
+                    payload: GetResultPayload::Stream(verify_md5_stream(
+                        location.clone(),
+                        expected,
+                        stream,
+                    )),
+                    ..result
+                })
+            }
+            (payload, _) => Ok(GetResult { payload, ..result }),
+        }
+    }
+
+    async fn get_ranges(&self, location: &Path, ranges: &[Range<u64>]) -> OSResult<Vec<Bytes>> {
+        self.target.get_ranges(location, ranges).await
+    }
+
+    fn delete_stream(
+        &self,
+        locations: BoxStream<'static, OSResult<Path>>,
+    ) -> BoxStream<'static, OSResult<Path>> {
+        self.target.delete_stream(locations)
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'static, OSResult<ObjectMeta>> {
+        self.target.list(prefix)
+    }
+
+    fn list_with_offset(
+        &self,
+        prefix: Option<&Path>,
+        offset: &Path,
+    ) -> BoxStream<'static, OSResult<ObjectMeta>> {
+        self.target.list_with_offset(prefix, offset)
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> OSResult<ListResult> {
+        self.target.list_with_delimiter(prefix).await
+    }
+
+    async fn copy_opts(&self, from: &Path, to: &Path, opts: CopyOptions) -> OSResult<()> {
+        self.target.copy_opts(from, to, opts).await
+    }
+
+    async fn rename_opts(&self, from: &Path, to: &Path, opts: RenameOptions) -> OSResult<()> {
+        self.target.rename_opts(from, to, opts).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{TryStreamExt, stream};
+    use object_store::memory::InMemory;
+    use object_store::{GetRange, ObjectStoreExt};
+    use rstest::rstest;
+
+    use super::*;
+
+    fn md5_of(data: &[u8]) -> [u8; 16] {
+        Md5::digest(data).into()
+    }
+
+    /// Serves objects from memory with the MD5 of `etag_content` as ETag,
+    /// the way S3 would if `etag_content` had been written.
+    #[derive(Debug)]
+    struct Md5ETagStore {
+        inner: InMemory,
+        etag_content: &'static [u8],
+    }
+
+    impl Display for Md5ETagStore {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "Md5ETagStore")
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl ObjectStore for Md5ETagStore {
+        async fn put_opts(
+            &self,
+            location: &Path,
+            payload: PutPayload,
+            opts: PutOptions,
+        ) -> OSResult<PutResult> {
+            self.inner.put_opts(location, payload, opts).await
+        }
+
+        async fn put_multipart_opts(
+            &self,
+            location: &Path,
+            opts: PutMultipartOptions,
+        ) -> OSResult<Box<dyn MultipartUpload>> {
+            self.inner.put_multipart_opts(location, opts).await
+        }
+
+        async fn get_opts(&self, location: &Path, options: GetOptions) -> OSResult<GetResult> {
+            let mut result = self.inner.get_opts(location, options).await?;
+            result.meta.e_tag = Some(format!("\"{}\"", hex_string(&md5_of(self.etag_content))));
+            Ok(result)
+        }
+
+        fn delete_stream(
+            &self,
+            locations: BoxStream<'static, OSResult<Path>>,
+        ) -> BoxStream<'static, OSResult<Path>> {
+            self.inner.delete_stream(locations)
+        }
+
+        fn list(&self, prefix: Option<&Path>) -> BoxStream<'static, OSResult<ObjectMeta>> {
+            self.inner.list(prefix)
+        }
+
+        async fn list_with_delimiter(&self, prefix: Option<&Path>) -> OSResult<ListResult> {
+            self.inner.list_with_delimiter(prefix).await
+        }
+
+        async fn copy_opts(&self, from: &Path, to: &Path, opts: CopyOptions) -> OSResult<()> {
+            self.inner.copy_opts(from, to, opts).await
+        }
+    }
+
+    #[rstest]
+    #[case::quoted("\"900150983cd24fb0d6963f7d28e17f72\"", Some(md5_of(b"abc")))]
+    #[case::unquoted("900150983CD24FB0D6963F7D28E17F72", Some(md5_of(b"abc")))]
+    #[case::multipart("\"900150983cd24fb0d6963f7d28e17f72-2\"", None)]
+    #[case::opaque("0x8DC1A2B3C4D5E6F", None)]
+    #[case::not_hex("\"zz0150983cd24fb0d6963f7d28e17f72\"", None)]
+    fn test_etag_md5(#[case] e_tag: &str, #[case] expected: Option<[u8; 16]>) {
+        assert_eq!(etag_md5(e_tag), expected);
+    }
+
+    #[test]
+    fn test_verify_on_read() {
+        let options = |value: &str| HashMap::from([(VERIFY_ON_READ_KEY.to_string(), value.into())]);
+        assert!(!verify_on_read(None));
+        assert!(verify_on_read(Some(&options("true"))));
+        assert!(!verify_on_read(Some(&options("false"))));
+    }
+
+    #[tokio::test]
+    async fn test_stream_verifies_at_end() {
+        let chunks = || {
+            stream::iter([Bytes::from_static(b"ab"), Bytes::from_static(b"c")])
+                .map(Ok)
+                .boxed()
+        };
+        let location = Path::from("data/0.lance");
+
+        let intact = verify_md5_stream(location.clone(), md5_of(b"abc"), chunks())
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(intact.concat(), b"abc");
+
+        // Every chunk is handed out before the mismatch is reported.
+        let items = verify_md5_stream(location, md5_of(b"abd"), chunks())
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(items.len(), 3);
+        assert!(items[..2].iter().all(Result::is_ok));
+        let err = items[2].as_ref().unwrap_err();
+        assert!(
+            matches!(
+                err,
+                object_store::Error::Generic {
+                    store: STORE_NAME,
+                    ..
+                }
+            ),
+            "{err}"
+        );
+        assert!(err.to_string().contains("data/0.lance"), "{err}");
+    }
+
+    #[rstest]
+    #[case::intact(b"manifest", true)]
+    #[case::corrupted(b"manifesT", false)]
+    #[tokio::test]
+    async fn test_whole_object_reads_are_verified(
+        #[case] etag_content: &'static [u8],
+        #[case] intact: bool,
+    ) {
+        let store = ReadVerifyingStore::new(Arc::new(Md5ETagStore {
+            inner: InMemory::new(),
+            etag_content,
+        }));
+        let path = Path::from("_versions/1.manifest");
+        store
+            .put(&path, PutPayload::from_static(b"manifest"))
+            .await
+            .unwrap();
+
+        let result = store.get(&path).await.unwrap().bytes().await;
+        if intact {
+            assert_eq!(result.unwrap().as_ref(), b"manifest");
+        } else {
+            let err = result.unwrap_err();
+            assert!(err.to_string().contains("Verification of"), "{err}");
+        }
+
+        // Ranged reads are never verified.
+        let options = GetOptions {
+            range: Some(GetRange::Bounded(0..4)),
+            ..Default::default()
+        };
+        let data = store
+            .get_opts(&path, options)
+            .await
+            .unwrap()
+            .bytes()
+            .await
+            .unwrap();
+        assert_eq!(data.as_ref(), b"mani");
+    }
+}
@@ -13,8 +13,8 @@ use futures::FutureExt;
 use futures::future::BoxFuture;
 use object_store::{Error as OSError, ObjectStore, Result as OSResult, path::Path};
 use object_store::{MultipartUpload, ObjectStoreExt};
-use rand::Rng;
 use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tokio::task::JoinSet;
 
 use lance_core::{Error, Result};
@@ -37,23 +37,16 @@ fn max_upload_parallelism() -> usize {
     })
 }
 
-fn max_conn_reset_retries() -> u16 {
-    static MAX_CONN_RESET_RETRIES: OnceLock<u16> = OnceLock::new();
-    *MAX_CONN_RESET_RETRIES.get_or_init(|| {
-        std::env::var("LANCE_CONN_RESET_RETRIES")
-            .ok()
-            .and_then(|s| s.parse::<u16>().ok())
-            .unwrap_or(20)
-    })
-}
+/// Minimum part size in GCS and S3: 5MB.
+pub(crate) const MIN_UPLOAD_PART_SIZE: usize = INITIAL_UPLOAD_STEP;
 
 /// Maximum part size in GCS and S3: 5GB.
-const MAX_UPLOAD_PART_SIZE: usize = 1024 * 1024 * 1024 * 5;
+pub(crate) const MAX_UPLOAD_PART_SIZE: usize = 1024 * 1024 * 1024 * 5;
 
 /// Clamps a requested upload part size to the valid [5MB, 5GB] range.
 /// Returns the clamped value and whether clamping was necessary.
 fn clamp_initial_upload_size(raw: usize) -> (usize, bool) {
-    let clamped = raw.clamp(INITIAL_UPLOAD_STEP, MAX_UPLOAD_PART_SIZE);
+    let clamped = raw.clamp(MIN_UPLOAD_PART_SIZE, MAX_UPLOAD_PART_SIZE);
     (clamped, clamped != raw)
 }
 
@@ -79,27 +72,77 @@ fn initial_upload_size() -> usize {
     })
 }
 
+/// Bounds how many multipart uploads are in progress at once across all the
+/// writers of a store, see [`crate::object_store::MAX_CONCURRENT_UPLOADS_KEY`].
+///
+/// Clones share the same limit.
+#[derive(Debug, Clone)]
+pub struct UploadLimiter {
+    permits: Arc<Semaphore>,
+    max_uploads: usize,
+}
+
+impl UploadLimiter {
+    pub fn new(max_uploads: usize) -> Self {
+        let max_uploads = max_uploads.clamp(1, Semaphore::MAX_PERMITS);
+        Self {
+            permits: Arc::new(Semaphore::new(max_uploads)),
+            max_uploads,
+        }
+    }
+
+    /// Number of multipart uploads currently in progress.
+    pub fn in_flight(&self) -> usize {
+        self.max_uploads - self.permits.available_permits()
+    }
+
+    async fn acquire(&self) -> OSResult<OwnedSemaphorePermit> {
+        self.permits
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|err| OSError::Generic {
+                store: "UploadLimiter",
+                source: Box::new(err),
+            })
+    }
+}
+
 /// Writer to an object in an object store.
 ///
 /// If the object is small enough, the writer will upload the object in a single
 /// PUT request. If the object is larger, the writer will create a multipart
-/// upload and upload parts in parallel.
+/// upload and upload parts in parallel. The store's [`UploadLimiter`] is held
+/// from the creation of the multipart upload until it is completed or aborted.
+///
+/// Failed parts are retried by the store, see
+/// [`crate::object_store::part_retry`]. A part that still fails fails the
+/// writer, and the multipart upload is aborted when the writer is dropped.
 ///
 /// This implements the `AsyncWrite` trait.
 pub struct ObjectWriter {
     state: UploadState,
     path: Arc<Path>,
     cursor: usize,
-    connection_resets: u16,
     buffer: Vec<u8>,
+    /// Size of the first parts, see [`crate::object_store::MULTIPART_PART_SIZE_KEY`]
+    part_size: usize,
     // TODO: use constant size to support R2
     use_constant_size_upload_parts: bool,
+    upload_limiter: UploadLimiter,
+    upload_permit: Option<OwnedSemaphorePermit>,
 }
 
+/// What was committed by a completed write
 #[derive(Debug, Clone, Default)]
 pub struct WriteResult {
+    /// The number of bytes written
     pub size: usize,
+    /// The ETag of the written object, if the store returns one
     pub e_tag: Option<String>,
+    /// The version of the written object, set by stores with object versioning
+    /// enabled, e.g. the version id of a versioned S3 or COS bucket
+    pub version: Option<String>,
 }
 
 enum UploadState {
@@ -107,12 +150,12 @@ enum UploadState {
     /// this state until the buffer is full or the writer is shut down.
     Started(Arc<dyn ObjectStore>),
     /// The writer is in the process of creating a multipart upload.
-    CreatingUpload(BoxFuture<'static, OSResult<Box<dyn MultipartUpload>>>),
+    CreatingUpload(BoxFuture<'static, OSResult<(OwnedSemaphorePermit, Box<dyn MultipartUpload>)>>),
     /// The writer is in the process of uploading parts.
     InProgress {
         part_idx: u16,
         upload: Box<dyn MultipartUpload>,
-        futures: JoinSet<std::result::Result<(), UploadPutError>>,
+        futures: JoinSet<OSResult<()>>,
     },
     /// The writer is in the process of uploading data in a single PUT request.
     /// This happens when shutdown is called before the buffer is full.
@@ -136,6 +179,7 @@ impl UploadState {
                     Ok(WriteResult {
                         size,
                         e_tag: res.e_tag,
+                        version: res.version,
                     })
                 };
                 Self::PuttingSingle(Box::pin(fut))
@@ -159,6 +203,7 @@ impl UploadState {
                     Ok(WriteResult {
                         size: 0, // This will be set properly later.
                         e_tag: res.e_tag,
+                        version: res.version,
                     })
                 };
                 Self::Completing(Box::pin(fut))
@@ -170,25 +215,37 @@ impl UploadState {
 
 impl ObjectWriter {
     pub async fn new(object_store: &LanceObjectStore, path: &Path) -> Result<Self> {
+        let part_size = object_store
+            .upload_part_size
+            .unwrap_or_else(initial_upload_size);
         Ok(Self {
             state: UploadState::Started(object_store.inner.clone()),
             cursor: 0,
             path: Arc::new(path.clone()),
-            connection_resets: 0,
-            buffer: Vec::with_capacity(initial_upload_size()),
+            buffer: Vec::with_capacity(part_size),
+            part_size,
             use_constant_size_upload_parts: object_store.use_constant_size_upload_parts,
+            upload_limiter: object_store.upload_limiter.clone(),
+            upload_permit: None,
         })
     }
 
     /// Returns the contents of `buffer` as a `Bytes` object and resets `buffer`.
     /// The new capacity of `buffer` is determined by the current part index.
-    fn next_part_buffer(buffer: &mut Vec<u8>, part_idx: u16, constant_upload_size: bool) -> Bytes {
+    fn next_part_buffer(
+        buffer: &mut Vec<u8>,
+        part_idx: u16,
+        part_size: usize,
+        constant_upload_size: bool,
+    ) -> Bytes {
         let new_capacity = if constant_upload_size {
             // The store does not support variable part sizes, so use the initial size.
-            initial_upload_size()
+            part_size
         } else {
             // Increase the upload size every 100 parts. This gives maximum part size of 2.5TB.
-            initial_upload_size().max(((part_idx / 100) as usize + 1) * INITIAL_UPLOAD_STEP)
+            // Parts below the 5MB step grow by their own size instead.
+            let step = part_size.min(INITIAL_UPLOAD_STEP);
+            part_size.max(((part_idx / 100) as usize + 1) * step)
         };
         let new_buffer = Vec::with_capacity(new_capacity);
         let part = std::mem::replace(buffer, new_buffer);
@@ -198,25 +255,12 @@ impl ObjectWriter {
     fn put_part(
         upload: &mut dyn MultipartUpload,
         buffer: Bytes,
-        part_idx: u16,
-        sleep: Option<std::time::Duration>,
-    ) -> BoxFuture<'static, std::result::Result<(), UploadPutError>> {
+    ) -> BoxFuture<'static, OSResult<()>> {
         log::debug!(
             "MultipartUpload submitting part with {} bytes",
             buffer.len()
         );
-        let fut = upload.put_part(buffer.clone().into());
-        Box::pin(async move {
-            if let Some(sleep) = sleep {
-                tokio::time::sleep(sleep).await;
-            }
-            fut.await.map_err(|source| UploadPutError {
-                part_idx,
-                buffer,
-                source,
-            })?;
-            Ok(())
-        })
+        upload.put_part(buffer.into())
     }
 
     fn poll_tasks(
@@ -228,15 +272,17 @@ impl ObjectWriter {
             match &mut mut_self.state {
                 UploadState::Started(_) | UploadState::Done(_) => break,
                 UploadState::CreatingUpload(fut) => match fut.poll_unpin(cx) {
-                    Poll::Ready(Ok(mut upload)) => {
+                    Poll::Ready(Ok((permit, mut upload))) => {
+                        mut_self.upload_permit = Some(permit);
                         let mut futures = JoinSet::new();
 
                         let data = Self::next_part_buffer(
                             &mut mut_self.buffer,
                             0,
+                            mut_self.part_size,
                             mut_self.use_constant_size_upload_parts,
                         );
-                        futures.spawn(Self::put_part(upload.as_mut(), data, 0, None));
+                        futures.spawn(Self::put_part(upload.as_mut(), data));
 
                         mut_self.state = UploadState::InProgress {
                             part_idx: 1, // We just used 0
@@ -247,43 +293,12 @@ impl ObjectWriter {
                     Poll::Ready(Err(e)) => return Err(std::io::Error::other(e)),
                     Poll::Pending => break,
                 },
-                UploadState::InProgress {
-                    upload, futures, ..
-                } => {
+                UploadState::InProgress { futures, .. } => {
                     while let Poll::Ready(Some(res)) = futures.poll_join_next(cx) {
                         match res {
                             Ok(Ok(())) => {}
                             Err(err) => return Err(std::io::Error::other(err)),
-                            Ok(Err(err)) if should_retry_upload_put(&err.source) => {
-                                if mut_self.connection_resets < max_conn_reset_retries() {
-                                    // Retry, but only up to max_conn_reset_retries of them.
-                                    mut_self.connection_resets += 1;
-
-                                    // Resubmit with random jitter
-                                    let sleep_time_ms = rand::rng().random_range(2_000..8_000);
-                                    let sleep_time =
-                                        std::time::Duration::from_millis(sleep_time_ms);
-
-                                    futures.spawn(Self::put_part(
-                                        upload.as_mut(),
-                                        err.buffer,
-                                        err.part_idx,
-                                        Some(sleep_time),
-                                    ));
-                                } else {
-                                    return Err(io::Error::new(
-                                        io::ErrorKind::ConnectionReset,
-                                        Box::new(ConnectionResetError {
-                                            message: format!(
-                                                "Hit max retries ({}) for retryable upload error",
-                                                max_conn_reset_retries()
-                                            ),
-                                            source: Box::new(err.source),
-                                        }),
-                                    ));
-                                }
-                            }
-                            Ok(Err(err)) => return Err(err.source.into()),
+                            Ok(Err(err)) => return Err(err.into()),
                         }
                     }
                     break;
@@ -292,7 +307,8 @@ impl ObjectWriter {
                     match fut.poll_unpin(cx) {
                         Poll::Ready(Ok(mut res)) => {
                             res.size = mut_self.cursor;
-                            mut_self.state = UploadState::Done(res)
+                            mut_self.state = UploadState::Done(res);
+                            mut_self.upload_permit = None;
                         }
                         Poll::Ready(Err(e)) => return Err(std::io::Error::other(e)),
                         Poll::Pending => break,
@@ -308,6 +324,7 @@ impl ObjectWriter {
         if let UploadState::InProgress { mut upload, .. } = state {
             let _ = upload.abort().await;
         }
+        self.upload_permit = None;
     }
 }
 
@@ -321,46 +338,16 @@ impl Drop for ObjectWriter {
             if let UploadState::InProgress { mut upload, .. } = state
                 && let Ok(handle) = Handle::try_current()
             {
+                let permit = self.upload_permit.take();
                 handle.spawn(async move {
                     let _ = upload.abort().await;
+                    drop(permit);
                 });
             }
         }
     }
 }
 
-/// Returned error from trying to upload a part.
-/// Has the part_idx and buffer so we can pass
-/// them to the retry logic.
-struct UploadPutError {
-    part_idx: u16,
-    buffer: Bytes,
-    source: OSError,
-}
-
-fn should_retry_upload_put(source: &OSError) -> bool {
-    let OSError::Generic { source, .. } = source else {
-        return false;
-    };
-
-    let message = source.to_string().to_ascii_lowercase();
-    message.contains("connection reset by peer") || message.contains("requesttimeout")
-}
-
-#[derive(Debug)]
-struct ConnectionResetError {
-    message: String,
-    source: Box<dyn std::error::Error + Send + Sync>,
-}
-
-impl std::error::Error for ConnectionResetError {}
-
-impl std::fmt::Display for ConnectionResetError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}: {}", self.message, self.source)
-    }
-}
-
 impl AsyncWrite for ObjectWriter {
     fn poll_write(
         mut self: std::pin::Pin<&mut Self>,
@@ -385,7 +372,12 @@ impl AsyncWrite for ObjectWriter {
                 UploadState::Started(store) => {
                     let path = mut_self.path.clone();
                     let store = store.clone();
-                    let fut = Box::pin(async move { store.put_multipart(path.as_ref()).await });
+                    let upload_limiter = mut_self.upload_limiter.clone();
+                    let fut = Box::pin(async move {
+                        let permit = upload_limiter.acquire().await?;
+                        let upload = store.put_multipart(path.as_ref()).await?;
+                        Ok((permit, upload))
+                    });
                     self.state = UploadState::CreatingUpload(fut);
                 }
                 UploadState::InProgress {
@@ -399,10 +391,11 @@ impl AsyncWrite for ObjectWriter {
                         let data = Self::next_part_buffer(
                             &mut mut_self.buffer,
                             *part_idx,
+                            mut_self.part_size,
                             mut_self.use_constant_size_upload_parts,
                         );
                         futures.spawn(
-                            Self::put_part(upload.as_mut(), data, *part_idx, None)
+                            Self::put_part(upload.as_mut(), data)
                                 .instrument(tracing::Span::current()),
                         );
                         *part_idx += 1;
@@ -463,16 +456,14 @@ impl AsyncWrite for ObjectWriter {
                     self.state.started_to_putting_single(path, part);
                 }
                 UploadState::InProgress {
-                    upload,
-                    futures,
-                    part_idx,
+                    upload, futures, ..
                 } => {
                     // Flush final batch
                     if !mut_self.buffer.is_empty() && futures.len() < max_upload_parallelism() {
                         // We can just use `take` since we don't need the buffer anymore.
                         let data = Bytes::from(std::mem::take(&mut mut_self.buffer));
                         futures.spawn(
-                            Self::put_part(upload.as_mut(), data, *part_idx, None)
+                            Self::put_part(upload.as_mut(), data)
                                 .instrument(tracing::Span::current()),
                         );
                         // We need to go back to beginning of loop to poll the
@@ -595,6 +586,7 @@ impl LocalWriter {
         Ok(WriteResult {
             size,
             e_tag: Some(e_tag),
+            version: None,
         })
     }
 }
@@ -726,9 +718,16 @@ fn get_inode(_metadata: &std::fs::Metadata) -> u64 {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+    use std::time::Duration;
+
     use tokio::io::AsyncWriteExt;
 
     use super::*;
+    use crate::object_store::{
+        MAX_CONCURRENT_UPLOADS_KEY, MULTIPART_PART_SIZE_KEY, ObjectStoreParams,
+        ObjectStoreRegistry, StorageOptionsAccessor,
+    };
 
     #[tokio::test]
     async fn test_write() {
@@ -768,6 +767,104 @@ mod tests {
         assert_eq!(res.size, buf.len() * 5);
     }
 
+    #[tokio::test]
+    async fn test_multipart_part_size() {
+        let open = |part_size: usize| async move {
+            let params = ObjectStoreParams {
+                storage_options_accessor: Some(Arc::new(
+                    StorageOptionsAccessor::with_static_options(HashMap::from([(
+                        MULTIPART_PART_SIZE_KEY.to_string(),
+                        part_size.to_string(),
+                    )])),
+                )),
+                ..ObjectStoreParams::default()
+            };
+            LanceObjectStore::from_uri_and_params(
+                Arc::new(ObjectStoreRegistry::default()),
+                "memory:///",
+                &params,
+            )
+            .await
+        };
+
+        // The memory store has the part size bounds of S3.
+        let err = open(1024 * 1024).await.unwrap_err();
+        assert!(err.to_string().contains(MULTIPART_PART_SIZE_KEY), "{err}");
+
+        let part_size = MIN_UPLOAD_PART_SIZE * 2;
+        let (store, base_path) = open(part_size).await.unwrap();
+        let mut writer = ObjectWriter::new(&store, &base_path.join("data"))
+            .await
+            .unwrap();
+        // Larger than the default part size, but still fits in the first part.
+        writer
+            .write_all(&vec![0; MIN_UPLOAD_PART_SIZE + 1])
+            .await
+            .unwrap();
+        assert_eq!(store.io_stats_snapshot().multipart_uploads_in_flight, 0);
+        writer.write_all(&vec![0; part_size]).await.unwrap();
+        assert_eq!(store.io_stats_snapshot().multipart_uploads_in_flight, 1);
+        let res = Writer::shutdown(&mut writer).await.unwrap();
+        assert_eq!(res.size, MIN_UPLOAD_PART_SIZE + 1 + part_size);
+    }
+
+    #[test]
+    fn test_next_part_buffer_grows() {
+        let capacity = |part_idx: u16, part_size: usize, constant: bool| {
+            let mut buffer = vec![];
+            ObjectWriter::next_part_buffer(&mut buffer, part_idx, part_size, constant);
+            buffer.capacity()
+        };
+        const MB: usize = 1024 * 1024;
+        assert_eq!(capacity(0, 5 * MB, false), 5 * MB);
+        assert_eq!(capacity(250, 5 * MB, false), 15 * MB);
+        assert_eq!(capacity(250, 8 * MB, false), 15 * MB);
+        // Small parts, e.g. on COS, grow by their own size.
+        assert_eq!(capacity(0, MB, false), MB);
+        assert_eq!(capacity(250, MB, false), 3 * MB);
+        assert_eq!(capacity(250, MB, true), MB);
+    }
+
+    #[tokio::test]
+    async fn test_max_concurrent_uploads() {
+        let params = ObjectStoreParams {
+            storage_options_accessor: Some(Arc::new(StorageOptionsAccessor::with_static_options(
+                HashMap::from([(MAX_CONCURRENT_UPLOADS_KEY.to_string(), "1".to_string())]),
+            ))),
+            ..ObjectStoreParams::default()
+        };
+        let (store, base_path) = LanceObjectStore::from_uri_and_params(
+            Arc::new(ObjectStoreRegistry::default()),
+            "memory:///",
+            &params,
+        )
+        .await
+        .unwrap();
+        let buf = vec![0; initial_upload_size() + 1];
+
+        let mut first = ObjectWriter::new(&store, &base_path.clone().join("first"))
+            .await
+            .unwrap();
+        first.write_all(&buf).await.unwrap();
+        assert_eq!(store.io_stats_snapshot().multipart_uploads_in_flight, 1);
+
+        // The second upload waits until the first one is finished.
+        let mut second = ObjectWriter::new(&store, &base_path.clone().join("second"))
+            .await
+            .unwrap();
+        let blocked =
+            tokio::time::timeout(Duration::from_millis(100), second.write_all(&buf)).await;
+        assert!(blocked.is_err());
+        assert_eq!(store.io_stats_incremental().multipart_uploads_in_flight, 1);
+
+        Writer::shutdown(&mut first).await.unwrap();
+        second.write_all(&buf).await.unwrap();
+        assert_eq!(store.io_stats_snapshot().multipart_uploads_in_flight, 1);
+
+        second.abort().await;
+        assert_eq!(store.io_stats_snapshot().multipart_uploads_in_flight, 0);
+    }
+
     #[tokio::test]
     async fn test_abort_write() {
         let store = LanceObjectStore::memory();
@@ -858,35 +955,6 @@ mod tests {
         assert_eq!(clamp_initial_upload_size(mid), (mid, false));
     }
 
-    #[test]
-    fn should_retry_upload_put_detects_transient_errors() {
-        let request_timeout = OSError::Generic {
-            store: "S3",
-            source: Box::new(io::Error::other(
-                "Server returned non-2xx status code: 400 Bad Request: \
-                 <Error><Code>RequestTimeout</Code><Message>Your socket connection to the server \
-                 was not read from or written to within the timeout period. Idle connections will \
-                 be closed.</Message></Error>",
-            )),
-        };
-        assert!(should_retry_upload_put(&request_timeout));
-
-        let connection_reset = OSError::Generic {
-            store: "S3",
-            source: Box::new(io::Error::new(
-                io::ErrorKind::ConnectionReset,
-                "connection reset by peer",
-            )),
-        };
-        assert!(should_retry_upload_put(&connection_reset));
-
-        let not_retryable = OSError::Generic {
-            store: "S3",
-            source: Box::new(io::Error::other("access denied")),
-        };
-        assert!(!should_retry_upload_put(&not_retryable));
-    }
-
     #[test]
     fn clamp_initial_upload_size_above_max_is_clamped_down() {
         assert_eq!(
@@ -14,10 +14,12 @@ use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
 
 use lance_core::utils::parse::str_is_truthy;
 use lance_core::{Error, Result};
 
+use crate::deadline;
 use crate::object_store::ObjectStore;
 use crate::traits::Reader;
 use crate::utils::CachedFileSize;
@@ -265,7 +267,7 @@ struct MutableBatch<F: FnOnce(Response) + Send> {
     num_bytes: u64,
     priority: u128,
     num_reqs: usize,
-    err: Option<Box<dyn std::error::Error + Send + Sync + 'static>>,
+    err: Option<Error>,
     // When true, report 0 bytes consumed so the backpressure budget is unaffected
     bypass_backpressure: bool,
 }
@@ -297,8 +299,8 @@ impl<F: FnOnce(Response) + Send> MutableBatch<F> {
 impl<F: FnOnce(Response) + Send> Drop for MutableBatch<F> {
     fn drop(&mut self) {
         // If we have an error, return that.  Otherwise return the data
-        let result = if self.err.is_some() {
-            Err(Error::wrapped(self.err.take().unwrap()))
+        let result = if let Some(err) = self.err.take() {
+            Err(err)
         } else {
             let mut data = Vec::new();
             std::mem::swap(&mut data, &mut self.data_buffers);
@@ -341,7 +343,7 @@ impl<F: FnOnce(Response) + Send> DataSink for MutableBatch<F> {
             }
             Err(err) => {
                 // This keeps the original error, if present
-                self.err.get_or_insert(Box::new(err));
+                self.err.get_or_insert(err);
             }
         }
     }
@@ -353,6 +355,11 @@ struct IoTask {
     when_done: Box<dyn FnOnce(Result<Bytes>) + Send>,
     priority: u128,
     bypass_backpressure: bool,
+    // The I/O runs on its own task so the caller's deadline is carried over explicitly
+    deadline: Option<Instant>,
+    // Cancelled when the caller drops the request future or when the scheduler is
+    // cancelled, which aborts the read
+    cancelled: CancellationToken,
 }
 
 impl Eq for IoTask {}
@@ -394,18 +401,29 @@ impl IoTask {
         let num_bytes = self.num_bytes();
         let bytes = if self.to_read.start == self.to_read.end {
             Ok(Bytes::new())
+        } else if self.cancelled.is_cancelled() {
+            Err(Error::cancelled(
+                "I/O request was cancelled before it started",
+            ))
         } else {
-            let bytes_fut = self
-                .reader
-                .get_range(self.to_read.start as usize..self.to_read.end as usize);
+            let bytes_fut = deadline::scoped(
+                self.deadline,
+                self.reader
+                    .get_range(self.to_read.start as usize..self.to_read.end as usize),
+            );
             IOPS_COUNTER.fetch_add(1, Ordering::Release);
             let num_bytes = self.num_bytes();
-            bytes_fut
-                .inspect(move |_| {
-                    BYTES_READ_COUNTER.fetch_add(num_bytes, Ordering::Release);
-                })
-                .await
-                .map_err(Error::from)
+            let bytes_fut = bytes_fut.inspect(move |_| {
+                BYTES_READ_COUNTER.fetch_add(num_bytes, Ordering::Release);
+            });
+            // Dropping the read future aborts the request instead of letting it run to
+            // completion in the background
+            tokio::select! {
+                bytes = bytes_fut => bytes.map_err(Error::from),
+                _ = self.cancelled.cancelled() => {
+                    Err(Error::cancelled("I/O request was cancelled"))
+                }
+            }
         };
         // Emit per-file I/O trace event only when tracing is enabled
         tracing::trace!(
@@ -506,10 +524,15 @@ enum IoQueueType {
 ///
 /// Note: The 2.X file readers already do this so this is only a concern if you are
 /// using the ScanScheduler directly.
+///
+/// A scheduler created with [`ScanScheduler::new_with_cancellation`] can also be cancelled
+/// while it is still in use, e.g. when a query times out.  In-flight and queued reads
+/// then fail with [`Error::Cancelled`] and no new reads are started.
 pub struct ScanScheduler {
     object_store: Arc<ObjectStore>,
     io_queue: IoQueueType,
     stats: Arc<StatsCollector>,
+    cancellation: Option<CancellationToken>,
 }
 
 impl Debug for ScanScheduler {
@@ -581,6 +604,18 @@ impl ScanScheduler {
     /// * object_store - the store to wrap
     /// * config - configuration settings for the scheduler
     pub fn new(object_store: Arc<ObjectStore>, config: SchedulerConfig) -> Arc<Self> {
+        Self::new_with_cancellation(object_store, config, None)
+    }
+
+    /// Create a new scheduler whose I/O is cancelled when `cancellation` is cancelled
+    ///
+    /// Once cancelled, queued and in-flight reads fail with [`Error::Cancelled`] and new
+    /// requests fail immediately without reaching the object store.
+    pub fn new_with_cancellation(
+        object_store: Arc<ObjectStore>,
+        config: SchedulerConfig,
+        cancellation: Option<CancellationToken>,
+    ) -> Arc<Self> {
         let io_capacity = object_store.io_parallelism();
         let use_lite = config
             .use_lite_scheduler
@@ -607,9 +642,21 @@ impl ScanScheduler {
             object_store,
             io_queue,
             stats: Arc::new(StatsCollector::new()),
+            cancellation,
         })
     }
 
+    /// Whether the scheduler was cancelled, see [`Self::new_with_cancellation`]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation
+            .as_ref()
+            .is_some_and(|token| token.is_cancelled())
+    }
+
+    fn cancelled_error() -> Error {
+        Error::cancelled("I/O scheduler was cancelled")
+    }
+
     /// Open a file for reading
     ///
     /// # Arguments
@@ -639,6 +686,7 @@ impl ScanScheduler {
             .await?;
         let block_size = self.object_store.block_size() as u64;
         let max_iop_size = self.object_store.max_iop_size();
+        let coalesce_gap = self.object_store.coalesce_gap();
         Ok(FileScheduler {
             reader: reader.into(),
             block_size,
@@ -646,6 +694,8 @@ impl ScanScheduler {
             base_priority,
             max_iop_size,
             bypass_backpressure: false,
+            coalesce_requests: false,
+            coalesce_gap,
         })
     }
 
@@ -660,6 +710,7 @@ impl ScanScheduler {
         self.open_file_with_priority(path, 0, file_size_bytes).await
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn do_submit_request(
         &self,
         reader: Arc<dyn Reader>,
@@ -668,12 +719,18 @@ impl ScanScheduler {
         priority: u128,
         io_queue: &Arc<IoQueue>,
         bypass_backpressure: bool,
+        cancelled: CancellationToken,
     ) {
         let num_iops = request.len() as u32;
+        let deadline = deadline::current_deadline();
 
+        let io_queue_clone = io_queue.clone();
         let when_all_io_done = move |bytes_and_permits| {
-            // We don't care if the receiver has given up so discard the result
-            let _ = tx.send(bytes_and_permits);
+            // If the receiver has given up then nobody will consume the bytes, so release
+            // them here
+            if let Err(rsp) = tx.send(bytes_and_permits) {
+                io_queue_clone.on_bytes_consumed(rsp.num_bytes, rsp.priority, rsp.num_reqs);
+            }
         };
 
         let dest = Arc::new(Mutex::new(Box::new(MutableBatch::new(
@@ -693,6 +750,8 @@ impl ScanScheduler {
                 to_read: iop,
                 priority,
                 bypass_backpressure,
+                deadline,
+                cancelled: cancelled.clone(),
                 when_done: Box::new(move |data| {
                     io_queue_clone.on_iop_complete();
                     let mut dest = dest.lock().unwrap();
@@ -717,14 +776,28 @@ impl ScanScheduler {
         bypass_backpressure: bool,
     ) -> impl Future<Output = Result<Vec<Bytes>>> + Send + use<> {
         let (tx, rx) = oneshot::channel::<Response>();
-
-        self.do_submit_request(reader, request, tx, priority, io_queue, bypass_backpressure);
+        let cancelled = self
+            .cancellation
+            .as_ref()
+            .map_or_else(CancellationToken::new, CancellationToken::child_token);
+        let cancel_on_drop = cancelled.clone().drop_guard();
+
+        self.do_submit_request(
+            reader,
+            request,
+            tx,
+            priority,
+            io_queue,
+            bypass_backpressure,
+            cancelled,
+        );
 
         let io_queue_clone = io_queue.clone();
 
         rx.map(move |wrapped_rsp| {
-            // Right now, it isn't possible for I/O to be cancelled so a cancel error should
-            // not occur
+            // The I/O for this request is finished by the time the response arrives
+            cancel_on_drop.disarm();
+            // The sender is only dropped after sending, so a cancel error should not occur
             let rsp = wrapped_rsp.unwrap();
             io_queue_clone.on_bytes_consumed(rsp.num_bytes, rsp.priority, rsp.num_reqs);
             rsp.data
@@ -740,16 +813,29 @@ impl ScanScheduler {
         bypass_backpressure: bool,
     ) -> impl Future<Output = Result<Vec<Bytes>>> + Send + use<> {
         // It's important that we submit all requests _before_ we await anything
+        let deadline = deadline::current_deadline();
         let maybe_tasks = request
             .into_iter()
             .map(|task| {
                 let reader = reader.clone();
                 let queue = io_queue.clone();
+                let cancellation = self.cancellation.clone();
                 let run_fn = Box::new(move || {
-                    reader
-                        .get_range(task.start as usize..task.end as usize)
-                        .map_err(Error::from)
-                        .boxed()
+                    let read = deadline::scoped(
+                        deadline,
+                        reader.get_range(task.start as usize..task.end as usize),
+                    )
+                    .map_err(Error::from);
+                    match cancellation {
+                        Some(cancellation) => async move {
+                            tokio::select! {
+                                bytes = read => bytes,
+                                _ = cancellation.cancelled() => Err(Self::cancelled_error()),
+                            }
+                        }
+                        .boxed(),
+                        None => read.boxed(),
+                    }
                 });
                 queue.submit(task, priority, run_fn, bypass_backpressure)
             })
@@ -774,7 +860,12 @@ impl ScanScheduler {
         priority: u128,
         bypass_backpressure: bool,
     ) -> impl Future<Output = Result<Vec<Bytes>>> + Send + use<> {
-        match &self.io_queue {
+        if self.is_cancelled() {
+            return futures::future::Either::Left(futures::future::ready(Err(
+                Self::cancelled_error(),
+            )));
+        }
+        futures::future::Either::Right(match &self.io_queue {
             IoQueueType::Standard(io_queue) => {
                 futures::future::Either::Left(self.submit_request_standard(
                     reader,
@@ -787,7 +878,7 @@ impl ScanScheduler {
             IoQueueType::Lite(io_queue) => futures::future::Either::Right(
                 self.submit_request_lite(reader, request, priority, io_queue, bypass_backpressure),
             ),
-        }
+        })
     }
 
     pub fn stats(&self) -> ScanStats {
@@ -829,6 +920,9 @@ pub struct FileScheduler {
     base_priority: u64,
     max_iop_size: u64,
     bypass_backpressure: bool,
+    coalesce_requests: bool,
+    /// Ranges further apart than this are never coalesced
+    coalesce_gap: u64,
 }
 
 fn is_close_together(range1: &Range<u64>, range2: &Range<u64>, block_size: u64) -> bool {
@@ -867,7 +961,11 @@ impl FileScheduler {
             let mut curr_interval = request[0].clone();
 
             for req in request.iter().skip(1) {
-                if is_close_together(&curr_interval, req, self.block_size) {
+                let fits_in_iop = self.coalesce_requests
+                    && req.start >= curr_interval.start
+                    && req.start <= curr_interval.end + self.coalesce_gap
+                    && req.end - curr_interval.start <= self.max_iop_size;
+                if fits_in_iop || is_close_together(&curr_interval, req, self.block_size) {
                     curr_interval.end = curr_interval.end.max(req.end);
                 } else {
                     merged_requests.push(curr_interval);
@@ -898,7 +996,10 @@ impl FileScheduler {
             }
         }
 
-        self.root.stats.record_request(&updated_requests);
+        // A cancelled scheduler fails the request without reading anything
+        if !self.root.is_cancelled() {
+            self.root.stats.record_request(&updated_requests);
+        }
 
         let bytes_vec_fut = self.root.submit_request(
             self.reader.clone(),
@@ -964,6 +1065,8 @@ impl FileScheduler {
             max_iop_size: self.max_iop_size,
             base_priority: priority,
             bypass_backpressure: self.bypass_backpressure,
+            coalesce_requests: self.coalesce_requests,
+            coalesce_gap: self.coalesce_gap,
         }
     }
 
@@ -978,6 +1081,25 @@ impl FileScheduler {
         }
     }
 
+    /// Returns a copy of this scheduler that coalesces all ranges of a request into as few
+    /// IOPS as possible.
+    ///
+    /// Normally ranges are only merged when the gap between them is smaller than the block
+    /// size.  With this enabled, ranges of a single request are merged as long as the gap
+    /// between them is within the object store's coalesce gap (the `storage_coalesce_gap`
+    /// storage option, 1 MiB by default) and the merged read stays within `max_iop_size`,
+    /// even if that means reading (and discarding) the bytes in between.  This is useful for
+    /// random access (e.g. take) where each request covers many nearby values from a single
+    /// page and the cost of an extra IOP is much higher than the cost of the wasted bytes.
+    /// Values that are further apart are still read on their own, so sparse requests don't
+    /// read more bytes than without coalescing.
+    pub fn with_request_coalescing(&self) -> Self {
+        Self {
+            coalesce_requests: true,
+            ..self.clone()
+        }
+    }
+
     /// Submit a single IOP to the reader
     ///
     /// If you have multiple IOPS to perform then [`Self::submit_request`] is going
@@ -1032,6 +1154,8 @@ mod tests {
             when_done: Box::new(|_| {}),
             priority,
             bypass_backpressure,
+            deadline: None,
+            cancelled: CancellationToken::new(),
         }
     }
 
@@ -1183,6 +1307,70 @@ mod tests {
         assert_eq!(11, scheduler.stats().iops);
     }
 
+    #[tokio::test]
+    async fn test_request_coalescing() {
+        let tmp_file = TempObjFile::default();
+
+        let obj_store = Arc::new(ObjectStore::local());
+
+        const DATA_SIZE: u64 = 1024 * 1024;
+        let mut some_data = vec![0; DATA_SIZE as usize];
+        rand::rng().fill_bytes(&mut some_data);
+        obj_store.put(&tmp_file, &some_data).await.unwrap();
+
+        let scheduler = ScanScheduler::new(obj_store, SchedulerConfig::default_for_testing());
+        let file_scheduler = scheduler
+            .open_file(&tmp_file, &CachedFileSize::unknown())
+            .await
+            .unwrap();
+
+        // Scattered values, far more than a block apart
+        let reads = (0..100)
+            .map(|i| i * 10_000..i * 10_000 + 8)
+            .collect::<Vec<_>>();
+
+        let bytes = file_scheduler
+            .submit_request(reads.clone(), 0)
+            .await
+            .unwrap();
+        for (range, bytes) in reads.iter().zip(bytes.iter()) {
+            assert_eq!(bytes, &some_data[range.start as usize..range.end as usize]);
+        }
+        assert_eq!(100, scheduler.stats().iops);
+
+        let coalescing = file_scheduler.with_request_coalescing();
+        let bytes = coalescing.submit_request(reads.clone(), 0).await.unwrap();
+        for (range, bytes) in reads.iter().zip(bytes.iter()) {
+            assert_eq!(bytes, &some_data[range.start as usize..range.end as usize]);
+        }
+        assert_eq!(101, scheduler.stats().iops);
+
+        // Coalesced reads never grow past the max IOP size
+        let small_iops = FileScheduler {
+            max_iop_size: 100_000,
+            ..coalescing
+        };
+        let bytes = small_iops.submit_request(reads.clone(), 0).await.unwrap();
+        for (range, bytes) in reads.iter().zip(bytes.iter()) {
+            assert_eq!(bytes, &some_data[range.start as usize..range.end as usize]);
+        }
+        assert_eq!(111, scheduler.stats().iops);
+
+        // Values further apart than the coalesce gap are read on their own, so
+        // sparse requests read no more bytes than without coalescing
+        let sparse = FileScheduler {
+            coalesce_gap: 1_000,
+            ..file_scheduler.with_request_coalescing()
+        };
+        let bytes_read = scheduler.stats().bytes_read;
+        let bytes = sparse.submit_request(reads.clone(), 0).await.unwrap();
+        for (range, bytes) in reads.iter().zip(bytes.iter()) {
+            assert_eq!(bytes, &some_data[range.start as usize..range.end as usize]);
+        }
+        assert_eq!(211, scheduler.stats().iops);
+        assert_eq!(800, scheduler.stats().bytes_read - bytes_read);
+    }
+
     #[tokio::test]
     async fn test_priority() {
         let some_path = Path::parse("foo").unwrap();
@@ -1475,6 +1663,314 @@ mod tests {
         assert_eq!(fut3.await.unwrap()[0].len(), 100);
     }
 
+    /// Records the deadline in effect when the read runs
+    #[derive(Debug)]
+    struct DeadlineReader {
+        observed: Arc<Mutex<Vec<Option<Instant>>>>,
+        path: Path,
+    }
+
+    impl deepsize::DeepSizeOf for DeadlineReader {
+        fn deep_size_of_children(&self, _context: &mut deepsize::Context) -> usize {
+            0
+        }
+    }
+
+    impl Reader for DeadlineReader {
+        fn path(&self) -> &Path {
+            &self.path
+        }
+
+        fn block_size(&self) -> usize {
+            4096
+        }
+
+        fn io_parallelism(&self) -> usize {
+            1
+        }
+
+        fn size(&self) -> futures::future::BoxFuture<'_, object_store::Result<usize>> {
+            Box::pin(async { Ok(1_000_000) })
+        }
+
+        fn get_range(
+            &self,
+            range: Range<usize>,
+        ) -> futures::future::BoxFuture<'static, object_store::Result<Bytes>> {
+            let observed = self.observed.clone();
+            Box::pin(async move {
+                observed.lock().unwrap().push(deadline::current_deadline());
+                Ok(Bytes::from(vec![0u8; range.end - range.start]))
+            })
+        }
+
+        fn get_all(&self) -> futures::future::BoxFuture<'_, object_store::Result<Bytes>> {
+            Box::pin(async { Ok(Bytes::from(vec![0u8; 1_000_000])) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_propagates_deadline() {
+        let deadline = Instant::now() + Duration::from_secs(60);
+        for config in [
+            SchedulerConfig::default_for_testing(),
+            SchedulerConfig::default_for_testing().with_lite_scheduler(),
+        ] {
+            let scheduler = ScanScheduler::new(Arc::new(ObjectStore::memory()), config);
+            let observed = Arc::new(Mutex::new(Vec::new()));
+            let reader: Arc<dyn Reader> = Arc::new(DeadlineReader {
+                observed: observed.clone(),
+                path: Path::parse("test").unwrap(),
+            });
+
+            // The deadline is captured when the request is submitted
+            deadline::with_deadline(deadline, async {
+                scheduler
+                    .submit_request(reader.clone(), vec![0..100], 0, false)
+                    .await
+            })
+            .await
+            .unwrap();
+            scheduler
+                .submit_request(reader, vec![0..100], 0, false)
+                .await
+                .unwrap();
+
+            assert_eq!(*observed.lock().unwrap(), vec![Some(deadline), None]);
+        }
+    }
+
+    /// Reads starting at offset 0 never finish and record when they are dropped
+    #[derive(Debug)]
+    struct SlowReader {
+        in_flight: Arc<AtomicU64>,
+        path: Path,
+    }
+
+    struct InFlight(Arc<AtomicU64>);
+
+    impl Drop for InFlight {
+        fn drop(&mut self) {
+            self.0.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    impl deepsize::DeepSizeOf for SlowReader {
+        fn deep_size_of_children(&self, _context: &mut deepsize::Context) -> usize {
+            0
+        }
+    }
+
+    impl Reader for SlowReader {
+        fn path(&self) -> &Path {
+            &self.path
+        }
+
+        fn block_size(&self) -> usize {
+            4096
+        }
+
+        fn io_parallelism(&self) -> usize {
+            1
+        }
+
+        fn size(&self) -> futures::future::BoxFuture<'_, object_store::Result<usize>> {
+            Box::pin(async { Ok(1_000_000) })
+        }
+
+        fn get_range(
+            &self,
+            range: Range<usize>,
+        ) -> futures::future::BoxFuture<'static, object_store::Result<Bytes>> {
+            let in_flight = self.in_flight.clone();
+            Box::pin(async move {
+                if range.start == 0 {
+                    in_flight.fetch_add(1, Ordering::SeqCst);
+                    let _in_flight = InFlight(in_flight);
+                    futures::future::pending::<()>().await;
+                }
+                Ok(Bytes::from(vec![0u8; range.end - range.start]))
+            })
+        }
+
+        fn get_all(&self) -> futures::future::BoxFuture<'_, object_store::Result<Bytes>> {
+            Box::pin(async { Ok(Bytes::from(vec![0u8; 1_000_000])) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dropping_request_aborts_io() {
+        for use_lite_scheduler in [false, true] {
+            // Only one request fits in the backpressure budget at a time
+            let config = SchedulerConfig {
+                io_buffer_size_bytes: 100,
+                use_lite_scheduler: Some(use_lite_scheduler),
+            };
+            let scheduler = ScanScheduler::new(Arc::new(ObjectStore::memory()), config);
+            let in_flight = Arc::new(AtomicU64::new(0));
+            let reader: Arc<dyn Reader> = Arc::new(SlowReader {
+                in_flight: in_flight.clone(),
+                path: Path::parse("test").unwrap(),
+            });
+
+            let mut slow =
+                Box::pin(scheduler.submit_request(reader.clone(), vec![0..100], 0, false));
+            timeout(Duration::from_secs(5), async {
+                while in_flight.load(Ordering::SeqCst) == 0 {
+                    assert!(poll!(slow.as_mut()).is_pending());
+                    tokio::time::sleep(Duration::from_millis(1)).await;
+                }
+            })
+            .await
+            .unwrap();
+
+            drop(slow);
+            timeout(Duration::from_secs(5), async {
+                while in_flight.load(Ordering::SeqCst) > 0 {
+                    tokio::time::sleep(Duration::from_millis(1)).await;
+                }
+            })
+            .await
+            .expect("the read was not aborted");
+
+            // The aborted read gave back its share of the backpressure budget
+            let bytes = timeout(
+                Duration::from_secs(5),
+                scheduler.submit_request(reader, vec![100..200], 1, false),
+            )
+            .await
+            .unwrap()
+            .unwrap();
+            assert_eq!(bytes[0].len(), 100);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancelling_scheduler_aborts_io() {
+        for use_lite_scheduler in [false, true] {
+            let config = SchedulerConfig {
+                io_buffer_size_bytes: 1024 * 1024,
+                use_lite_scheduler: Some(use_lite_scheduler),
+            };
+            let obj_store = Arc::new(ObjectStore::memory());
+            let path = Path::parse("foo").unwrap();
+            obj_store.put(&path, &[0; 1000]).await.unwrap();
+            let cancellation = CancellationToken::new();
+            let scheduler =
+                ScanScheduler::new_with_cancellation(obj_store, config, Some(cancellation.clone()));
+            let in_flight = Arc::new(AtomicU64::new(0));
+            let reader: Arc<dyn Reader> = Arc::new(SlowReader {
+                in_flight: in_flight.clone(),
+                path: Path::parse("test").unwrap(),
+            });
+
+            let file = scheduler
+                .open_file(&path, &CachedFileSize::unknown())
+                .await
+                .unwrap();
+            file.submit_request(vec![0..10], 0).await.unwrap();
+            assert_eq!(scheduler.stats().requests, 1);
+
+            let mut slow =
+                Box::pin(scheduler.submit_request(reader.clone(), vec![0..100], 0, false));
+            timeout(Duration::from_secs(5), async {
+                while in_flight.load(Ordering::SeqCst) == 0 {
+                    assert!(poll!(slow.as_mut()).is_pending());
+                    tokio::time::sleep(Duration::from_millis(1)).await;
+                }
+            })
+            .await
+            .unwrap();
+
+            // The in-flight read fails instead of returning partial data
+            cancellation.cancel();
+            assert!(scheduler.is_cancelled());
+            let err = timeout(Duration::from_secs(5), slow)
+                .await
+                .expect("the read was not aborted")
+                .unwrap_err();
+            assert!(matches!(err, Error::Cancelled { .. }), "{err:?}");
+            assert_eq!(in_flight.load(Ordering::SeqCst), 0);
+
+            // New requests fail without issuing any I/O
+            let err = file.submit_request(vec![10..20], 1).await.unwrap_err();
+            assert!(matches!(err, Error::Cancelled { .. }), "{err:?}");
+            assert_eq!(scheduler.stats().requests, 1);
+        }
+    }
+
+    #[cfg(feature = "aws")]
+    #[tokio::test]
+    async fn test_dropping_request_closes_connection() {
+        use object_store::aws::AmazonS3Builder;
+        use tokio::io::AsyncReadExt;
+
+        // A server that accepts one request and never responds
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let (received_tx, received_rx) = tokio::sync::oneshot::channel();
+        let closed = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            let mut received_tx = Some(received_tx);
+            loop {
+                let n = socket.read(&mut buf).await.unwrap_or(0);
+                if n == 0 {
+                    // The client closed the connection
+                    return;
+                }
+                request.extend_from_slice(&buf[..n]);
+                if request.windows(4).any(|w| w == b"\r\n\r\n")
+                    && let Some(tx) = received_tx.take()
+                {
+                    tx.send(()).unwrap();
+                }
+            }
+        });
+
+        let s3 = AmazonS3Builder::new()
+            .with_endpoint(&endpoint)
+            .with_allow_http(true)
+            .with_bucket_name("bucket")
+            .with_region("us-east-1")
+            .with_access_key_id("access-key")
+            .with_secret_access_key("secret-key")
+            .build()
+            .unwrap();
+        let store = Arc::new(ObjectStore::new(
+            Arc::new(s3),
+            Url::parse("s3://bucket/").unwrap(),
+            None,
+            None,
+            false,
+            false,
+            1,
+            0,
+            None,
+        ));
+        let scheduler = ScanScheduler::new(store, SchedulerConfig::default_for_testing());
+        let file_scheduler = scheduler
+            // Large enough that the file isn't downloaded up front by a small file reader
+            .open_file(
+                &Path::parse("slow").unwrap(),
+                &CachedFileSize::new(1024 * 1024),
+            )
+            .await
+            .unwrap();
+
+        let read = file_scheduler.submit_request(vec![0..100], 0);
+        tokio::select! {
+            _ = read => panic!("the server never responds"),
+            _ = received_rx => {}
+        }
+        // `read` was dropped by leaving the select
+        timeout(Duration::from_secs(5), closed)
+            .await
+            .expect("the connection was not closed")
+            .unwrap();
+    }
+
     #[tokio::test]
     async fn test_object_store_selects_scheduler() {
         // A memory:// store should use the standard scheduler when config is None
@@ -142,6 +142,24 @@ impl IoTask {
         was_running
     }
 
+    fn backpressure_reservation(&self) -> Option<BackpressureReservation> {
+        match &self.state {
+            TaskState::Reserved {
+                backpressure_reservation,
+                ..
+            }
+            | TaskState::Running {
+                backpressure_reservation,
+                ..
+            }
+            | TaskState::Finished {
+                backpressure_reservation,
+                ..
+            } => Some(*backpressure_reservation),
+            TaskState::Broken | TaskState::Initial { .. } => None,
+        }
+    }
+
     fn reserve(&mut self, backpressure_reservation: BackpressureReservation) -> TaskResult {
         let state = std::mem::replace(&mut self.state, TaskState::Broken);
         let TaskState::Initial { idle_waker, run_fn } = state else {
@@ -516,7 +534,8 @@ impl IoQueue {
             // Unwrap safe here since we just checked the queue is not empty
             let next_task = state_ref.pending_tasks.peek().unwrap();
             let Some(task) = state_ref.tasks.get_mut(&next_task.task_id) else {
-                log::warn!("Task with id {} was lost", next_task.task_id);
+                // The handle was dropped before the task could start
+                state_ref.pending_tasks.pop();
                 continue;
             };
             if !task.is_reserved() {
@@ -564,6 +583,27 @@ impl IoQueue {
         }
     }
 
+    // Called when a task handle is dropped.  If the task has not been consumed then its I/O
+    // is aborted and its backpressure reservation released.
+    fn cancel(&self, task_id: u64) {
+        let mut state = self.state.lock().unwrap();
+        let Some(task) = state.tasks.remove(&task_id) else {
+            return;
+        };
+        if let Some(reservation) = task.backpressure_reservation() {
+            state.backpressure_throttle.release(reservation);
+        }
+        if let Err(e) = self.on_task_complete(state) {
+            log::warn!(
+                "Error starting I/O after cancelling task {}: {}",
+                task_id,
+                e
+            );
+        }
+        // Drop the task, and with it any in-flight read, outside of the lock
+        drop(task);
+    }
+
     pub(super) fn close(&self) {
         let mut state = self.state.lock().unwrap();
         for task in std::mem::take(&mut state.tasks).values_mut() {
@@ -577,6 +617,12 @@ pub(super) struct TaskHandle {
     queue: Arc<IoQueue>,
 }
 
+impl Drop for TaskHandle {
+    fn drop(&mut self) {
+        self.queue.cancel(self.task_id);
+    }
+}
+
 impl Future for TaskHandle {
     type Output = Result<Bytes>;
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
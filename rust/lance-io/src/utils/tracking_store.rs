@@ -8,16 +8,29 @@
 //! written, and the number of disjoint periods where at least one IO is in-flight.
 //!
 //! This modules provides [`IOTracker`] which can be used to wrap any object store.
+//!
+//! The tracker can also keep per-path read counts, see [`ACCESS_HISTOGRAM_SIZE_KEY`],
+//! which a cache layer can use to decide what to pre-warm.
+//!
+//! To feed the IO into an existing metrics pipeline instead of polling the
+//! stats, give the tracker an [`IoObserver`], e.g. through
+//! [`crate::object_store::ObjectStoreParams::io_observer`].
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 use std::ops::Range;
 #[cfg(feature = "test-util")]
 use std::sync::atomic::AtomicU16;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use bytes::Bytes;
 use futures::StreamExt;
 use futures::TryStreamExt;
 use futures::stream::BoxStream;
+use futures::{Future, FutureExt};
+use lance_core::utils::metrics::{self, Counter, Histogram, LATENCY_BUCKETS};
+use lance_core::{Error, Result};
 use object_store::path::Path;
 use object_store::{
     CopyOptions, GetOptions, GetRange, GetResult, ListResult, MultipartUpload, ObjectMeta,
@@ -26,17 +39,135 @@ use object_store::{
 };
 
 use crate::object_store::WrappingObjectStore;
+use crate::object_store::classification::is_throttled;
+use crate::object_store::priority::{Priority, PriorityWaits};
+
+/// Storage option for the number of paths whose read counts are kept by the
+/// [`IOTracker`], see [`IOTracker::access_histogram`]. Default, `0` (disabled).
+pub const ACCESS_HISTOGRAM_SIZE_KEY: &str = "storage_access_histogram_size";
+
+/// The kind of a request reported to an [`IoObserver`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IoOperation {
+    /// A GET, ranged GET or HEAD
+    Read,
+    /// A put or the upload of one part of a multipart upload
+    Write,
+    /// A listing, reported once its stream is done
+    List,
+    Delete,
+    Copy,
+    Rename,
+}
+
+impl IoOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Write => "write",
+            Self::List => "list",
+            Self::Delete => "delete",
+            Self::Copy => "copy",
+            Self::Rename => "rename",
+        }
+    }
+}
+
+/// Receives every request made through an [`IoTrackingStore`] as it completes.
+///
+/// `num_bytes` is the size of the data read or written, zero for failed
+/// requests and for requests without a body. The `duration` of a read is the
+/// time until the response started, not until its body was consumed. Deletes
+/// are reported per path as the store confirms them, with the time since the
+/// previous confirmation.
+///
+/// The callback runs inline on the request path, so it should be cheap, e.g.
+/// bumping a counter or observing a histogram. Reads and writes of local
+/// files that bypass the object store are not reported.
+pub trait IoObserver: Send + Sync + std::fmt::Debug {
+    fn on_request(
+        &self,
+        op: IoOperation,
+        num_bytes: u64,
+        duration: Duration,
+        result: std::result::Result<(), &object_store::Error>,
+    );
+}
 
 #[derive(Debug, Default, Clone)]
-pub struct IOTracker(Arc<Mutex<IoStats>>);
+pub struct IOTracker {
+    stats: Arc<Mutex<IoStats>>,
+    access: Option<Arc<Mutex<AccessHistogram>>>,
+    metrics: IoMetrics,
+    observer: Option<Arc<dyn IoObserver>>,
+    /// Bytes held by in-flight reads, a gauge kept out of `stats` so
+    /// incremental stats don't reset it.
+    read_buffer_bytes: Arc<AtomicU64>,
+}
 
 impl IOTracker {
+    /// Create a tracker that also counts reads per path, keeping the
+    /// `capacity` most accessed paths. A capacity of zero disables it.
+    pub fn with_access_histogram(capacity: usize) -> Self {
+        Self {
+            stats: Default::default(),
+            access: (capacity > 0).then(|| Arc::new(Mutex::new(AccessHistogram::new(capacity)))),
+            metrics: IoMetrics::default(),
+            observer: None,
+            read_buffer_bytes: Default::default(),
+        }
+    }
+
+    /// Also export the tracked IO as metrics labeled with `scheme`, see
+    /// [`lance_core::utils::metrics`].
+    pub fn with_metrics(mut self, scheme: &str) -> Self {
+        self.metrics = IoMetrics::new(scheme);
+        self
+    }
+
+    /// Also report every request to `observer`.
+    pub fn with_observer(mut self, observer: Arc<dyn IoObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Create a tracker configured by [`ACCESS_HISTOGRAM_SIZE_KEY`].
+    pub fn from_storage_options(storage_options: Option<&HashMap<String, String>>) -> Result<Self> {
+        let capacity = storage_options
+            .and_then(|opts| opts.get(ACCESS_HISTOGRAM_SIZE_KEY))
+            .map(|val| {
+                val.parse::<usize>().map_err(|_| {
+                    Error::invalid_input(format!(
+                        "Invalid value for storage option '{ACCESS_HISTOGRAM_SIZE_KEY}': '{val}'"
+                    ))
+                })
+            })
+            .transpose()?
+            .unwrap_or_default();
+        Ok(Self::with_access_histogram(capacity))
+    }
+
+    /// The most read paths, ordered by descending read count.
+    ///
+    /// Empty unless the tracker was created with an access histogram. Once
+    /// the histogram is full a newly read path replaces the least read one and
+    /// inherits its count, so counts are upper bounds for paths that entered
+    /// late, while paths read more often than that are never evicted.
+    pub fn access_histogram(&self) -> Vec<PathAccess> {
+        self.access
+            .as_ref()
+            .map(|access| access.lock().unwrap().snapshot())
+            .unwrap_or_default()
+    }
+
     /// Get IO statistics and reset the counters (incremental pattern).
     ///
     /// This returns the accumulated statistics since the last call and resets
     /// the internal counters to zero.
     pub fn incremental_stats(&self) -> IoStats {
-        std::mem::take(&mut *self.0.lock().unwrap())
+        let mut stats = std::mem::take(&mut *self.stats.lock().unwrap());
+        stats.read_buffer_bytes = self.read_buffer_bytes.load(Ordering::Relaxed);
+        stats
     }
 
     /// Get a snapshot of current IO statistics without resetting counters.
@@ -44,7 +175,9 @@ impl IOTracker {
     /// This returns a clone of the current statistics without modifying the
     /// internal state. Use this when you need to check stats without resetting.
     pub fn stats(&self) -> IoStats {
-        self.0.lock().unwrap().clone()
+        let mut stats = self.stats.lock().unwrap().clone();
+        stats.read_buffer_bytes = self.read_buffer_bytes.load(Ordering::Relaxed);
+        stats
     }
 
     /// Record a read operation for tracking.
@@ -58,7 +191,11 @@ impl IOTracker {
         num_bytes: u64,
         #[allow(unused_variables)] range: Option<Range<u64>>,
     ) {
-        let mut stats = self.0.lock().unwrap();
+        if let Some(access) = &self.access {
+            access.lock().unwrap().record(&path, num_bytes);
+        }
+        self.metrics.record_read(num_bytes);
+        let mut stats = self.stats.lock().unwrap();
         stats.read_iops += 1;
         stats.read_bytes += num_bytes;
         #[cfg(feature = "test-util")]
@@ -79,7 +216,8 @@ impl IOTracker {
         #[allow(unused_variables)] path: Path,
         num_bytes: u64,
     ) {
-        let mut stats = self.0.lock().unwrap();
+        self.metrics.record_write(num_bytes);
+        let mut stats = self.stats.lock().unwrap();
         stats.write_iops += 1;
         stats.written_bytes += num_bytes;
         #[cfg(feature = "test-util")]
@@ -89,11 +227,132 @@ impl IOTracker {
             range: None,
         });
     }
+
+    /// Record a HEAD request served by the metadata cache.
+    pub fn record_metadata_cache_hit(&self) {
+        self.stats.lock().unwrap().metadata_cache_hits += 1;
+    }
+
+    /// Record a HEAD request that had to go to the underlying store.
+    pub fn record_metadata_cache_miss(&self) {
+        self.stats.lock().unwrap().metadata_cache_misses += 1;
+    }
+
+    /// Record a block read served by the disk cache.
+    pub fn record_disk_cache_hit(&self) {
+        self.stats.lock().unwrap().disk_cache_hits += 1;
+    }
+
+    /// Record a block read that had to go to the underlying store.
+    pub fn record_disk_cache_miss(&self) {
+        self.stats.lock().unwrap().disk_cache_misses += 1;
+    }
+
+    /// Record ranges that were merged into another range's request.
+    pub fn record_coalesced_ranges(&self, num_ranges: u64) {
+        self.stats.lock().unwrap().coalesced_ranges += num_ranges;
+    }
+
+    /// Record a read taking `num_bytes` of read buffer budget after waiting
+    /// `wait` for it.
+    pub fn record_read_buffer_acquire(&self, num_bytes: u64, wait: Duration) {
+        self.read_buffer_bytes
+            .fetch_add(num_bytes, Ordering::Relaxed);
+        self.stats.lock().unwrap().read_buffer_wait += wait;
+    }
+
+    /// Record a request waiting `wait` for a request permit at `priority`.
+    pub fn record_request_wait(&self, priority: Priority, wait: Duration) {
+        self.stats.lock().unwrap().request_wait.add(priority, wait);
+    }
+
+    /// Record a read giving back `num_bytes` of read buffer budget.
+    pub fn record_read_buffer_release(&self, num_bytes: u64) {
+        self.read_buffer_bytes
+            .fetch_sub(num_bytes, Ordering::Relaxed);
+    }
 }
 
 impl WrappingObjectStore for IOTracker {
     fn wrap(&self, _store_prefix: &str, target: Arc<dyn ObjectStore>) -> Arc<dyn ObjectStore> {
-        Arc::new(IoTrackingStore::new(target, self.0.clone()))
+        let mut store = IoTrackingStore::new(target, self.stats.clone());
+        store.access = self.access.clone();
+        store.metrics = self.metrics.clone();
+        store.observer = self.observer.clone();
+        Arc::new(store)
+    }
+}
+
+/// Instruments for the IO of one object store, no-ops unless registered
+/// with [`IoMetrics::new`].
+#[derive(Debug, Default, Clone)]
+struct IoMetrics {
+    read_requests: Counter,
+    read_bytes: Counter,
+    write_requests: Counter,
+    written_bytes: Counter,
+    read_duration: Histogram,
+    write_duration: Histogram,
+    throttled_requests: Counter,
+}
+
+impl IoMetrics {
+    fn new(scheme: &str) -> Self {
+        let requests = |op| {
+            metrics::counter(
+                "lance_io_requests_total",
+                "Object store requests.",
+                &[("scheme", scheme), ("op", op)],
+            )
+        };
+        let duration = |op| {
+            metrics::histogram(
+                "lance_io_request_duration_seconds",
+                "Latency of object store reads and writes.",
+                &[("scheme", scheme), ("op", op)],
+                LATENCY_BUCKETS,
+            )
+        };
+        let labels = [("scheme", scheme)];
+        Self {
+            read_requests: requests("read"),
+            read_bytes: metrics::counter(
+                "lance_io_read_bytes_total",
+                "Bytes read from object stores.",
+                &labels,
+            ),
+            write_requests: requests("write"),
+            written_bytes: metrics::counter(
+                "lance_io_written_bytes_total",
+                "Bytes written to object stores.",
+                &labels,
+            ),
+            read_duration: duration("read"),
+            write_duration: duration("write"),
+            throttled_requests: metrics::counter(
+                "lance_io_throttled_requests_total",
+                "Object store requests rejected with a throttle response.",
+                &labels,
+            ),
+        }
+    }
+
+    fn record_read(&self, num_bytes: u64) {
+        self.read_requests.inc();
+        self.read_bytes.inc_by(num_bytes);
+    }
+
+    fn record_write(&self, num_bytes: u64) {
+        self.write_requests.inc();
+        self.written_bytes.inc_by(num_bytes);
+    }
+}
+
+/// Count `err` in [`IoStats::throttle_events`] if it is a throttle response.
+fn record_error(stats: &Mutex<IoStats>, metrics: &IoMetrics, err: &object_store::Error) {
+    if is_throttled(err) {
+        metrics.throttled_requests.inc();
+        stats.lock().unwrap().throttle_events += 1;
     }
 }
 
@@ -103,6 +362,33 @@ pub struct IoStats {
     pub read_bytes: u64,
     pub write_iops: u64,
     pub written_bytes: u64,
+    /// HEAD requests answered from the object metadata cache.
+    pub metadata_cache_hits: u64,
+    /// HEAD requests that missed the object metadata cache.
+    pub metadata_cache_misses: u64,
+    /// Blocks read from the local disk cache.
+    pub disk_cache_hits: u64,
+    /// Blocks the local disk cache had to fetch from the store.
+    pub disk_cache_misses: u64,
+    /// Ranges passed to `ObjectStore::get_ranges` that were served by a
+    /// request shared with another range.
+    pub coalesced_ranges: u64,
+    /// Multipart uploads in progress when the stats were taken. This is a
+    /// gauge, so it is not reset by incremental stats.
+    pub multipart_uploads_in_flight: u64,
+    /// Bytes held by in-flight reads when the stats were taken, see
+    /// [`crate::object_store::read_buffer::READ_BUFFER_BYTES_KEY`]. This is a
+    /// gauge, so it is not reset by incremental stats.
+    pub read_buffer_bytes: u64,
+    /// Time reads spent waiting for read buffer budget.
+    pub read_buffer_wait: Duration,
+    /// Time requests spent waiting for a request permit, per priority, see
+    /// [`crate::object_store::priority::MAX_CONCURRENT_REQUESTS_KEY`].
+    pub request_wait: PriorityWaits,
+    /// Requests that failed with a throttle response, e.g. HTTP 429 or 503
+    /// `SlowDown`. Retries made below the tracker, such as those of the AIMD
+    /// throttle, only count once per request.
+    pub throttle_events: u64,
     // This is only really meaningful in tests where there isn't any concurrent IO.
     #[cfg(feature = "test-util")]
     /// Number of disjoint periods where at least one IO is in-flight.
@@ -219,6 +505,64 @@ impl std::fmt::Debug for IoRequestRecord {
     }
 }
 
+/// Read count and bytes for one path, see [`IOTracker::access_histogram`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathAccess {
+    pub path: Path,
+    pub count: u64,
+    pub bytes: u64,
+}
+
+/// Bounded per-path read counts, using the space-saving heavy hitter scheme.
+#[derive(Debug)]
+struct AccessHistogram {
+    capacity: usize,
+    /// Read count and bytes read by path
+    entries: HashMap<Path, (u64, u64)>,
+}
+
+impl AccessHistogram {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::with_capacity(capacity),
+        }
+    }
+
+    fn record(&mut self, path: &Path, num_bytes: u64) {
+        if let Some((count, bytes)) = self.entries.get_mut(path) {
+            *count += 1;
+            *bytes += num_bytes;
+            return;
+        }
+        let mut count = 0;
+        if self.entries.len() >= self.capacity {
+            let evicted = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (count, _))| *count)
+                .map(|(path, _)| path.clone())
+                .expect("histogram capacity is non-zero");
+            count = self.entries.remove(&evicted).unwrap().0;
+        }
+        self.entries.insert(path.clone(), (count + 1, num_bytes));
+    }
+
+    fn snapshot(&self) -> Vec<PathAccess> {
+        let mut histogram = self
+            .entries
+            .iter()
+            .map(|(path, (count, bytes))| PathAccess {
+                path: path.clone(),
+                count: *count,
+                bytes: *bytes,
+            })
+            .collect::<Vec<_>>();
+        histogram.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.path.cmp(&b.path)));
+        histogram
+    }
+}
+
 impl Display for IoStats {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:#?}", self)
@@ -229,6 +573,9 @@ impl Display for IoStats {
 pub struct IoTrackingStore {
     target: Arc<dyn ObjectStore>,
     stats: Arc<Mutex<IoStats>>,
+    access: Option<Arc<Mutex<AccessHistogram>>>,
+    metrics: IoMetrics,
+    observer: Option<Arc<dyn IoObserver>>,
     #[cfg(feature = "test-util")]
     active_requests: Arc<AtomicU16>,
 }
@@ -244,11 +591,58 @@ impl IoTrackingStore {
         Self {
             target,
             stats,
+            access: None,
+            metrics: IoMetrics::default(),
+            observer: None,
             #[cfg(feature = "test-util")]
             active_requests: Arc::new(AtomicU16::new(0)),
         }
     }
 
+    /// Run `request`, counting throttle errors and reporting it to the
+    /// observer once it completes.
+    async fn observed<T>(
+        &self,
+        op: IoOperation,
+        request: impl Future<Output = OSResult<T>>,
+        num_bytes: impl FnOnce(&T) -> u64,
+    ) -> OSResult<T> {
+        let start = Instant::now();
+        let result = request.await;
+        if let Err(err) = &result {
+            record_error(&self.stats, &self.metrics, err);
+        }
+        if let Some(observer) = &self.observer {
+            match &result {
+                Ok(value) => observer.on_request(op, num_bytes(value), start.elapsed(), Ok(())),
+                Err(err) => observer.on_request(op, 0, start.elapsed(), Err(err)),
+            }
+        }
+        result
+    }
+
+    /// Report a listing once its stream fails, ends or is dropped.
+    fn observed_list(
+        &self,
+        stream: BoxStream<'static, OSResult<ObjectMeta>>,
+    ) -> BoxStream<'static, OSResult<ObjectMeta>> {
+        let stats = self.stats.clone();
+        let metrics = self.metrics.clone();
+        let mut listing = ListObservation {
+            observer: self.observer.clone(),
+            start: Instant::now(),
+        };
+        stream
+            .map(move |meta| {
+                if let Err(err) = &meta {
+                    record_error(&stats, &metrics, err);
+                    listing.finish(Err(err));
+                }
+                meta
+            })
+            .boxed()
+    }
+
     fn record_read(
         &self,
         method: &'static str,
@@ -256,6 +650,7 @@ impl IoTrackingStore {
         num_bytes: u64,
         range: Option<Range<u64>>,
     ) {
+        self.metrics.record_read(num_bytes);
         let mut stats = self.stats.lock().unwrap();
         stats.read_iops += 1;
         stats.read_bytes += num_bytes;
@@ -269,7 +664,15 @@ impl IoTrackingStore {
         let _ = (method, path, range); // Suppress unused variable warnings
     }
 
+    /// Count an object read in the access histogram, if enabled. Lists are not counted.
+    fn record_access(&self, path: &Path, num_bytes: u64) {
+        if let Some(access) = &self.access {
+            access.lock().unwrap().record(path, num_bytes);
+        }
+    }
+
     fn record_write(&self, method: &'static str, path: Path, num_bytes: u64) {
+        self.metrics.record_write(num_bytes);
         let mut stats = self.stats.lock().unwrap();
         stats.write_iops += 1;
         stats.written_bytes += num_bytes;
@@ -309,7 +712,14 @@ impl ObjectStore for IoTrackingStore {
             location.to_owned(),
             bytes.content_length() as u64,
         );
-        self.target.put_opts(location, bytes, opts).await
+        let _timer = self.metrics.write_duration.start_timer();
+        let num_bytes = bytes.content_length() as u64;
+        self.observed(
+            IoOperation::Write,
+            self.target.put_opts(location, bytes, opts),
+            |_| num_bytes,
+        )
+        .await
     }
 
     async fn put_multipart_opts(
@@ -322,6 +732,8 @@ impl ObjectStore for IoTrackingStore {
         Ok(Box::new(IoTrackingMultipartUpload {
             target,
             stats: self.stats.clone(),
+            metrics: self.metrics.clone(),
+            observer: self.observer.clone(),
             #[cfg(feature = "test-util")]
             path: location.to_owned(),
             #[cfg(feature = "test-util")]
@@ -335,10 +747,19 @@ impl ObjectStore for IoTrackingStore {
             Some(GetRange::Bounded(range)) => Some(range.clone()),
             _ => None, // TODO: fill in other options.
         };
-        let result = self.target.get_opts(location, options).await;
+        let result = {
+            let _timer = self.metrics.read_duration.start_timer();
+            self.observed(
+                IoOperation::Read,
+                self.target.get_opts(location, options),
+                |result| result.range.end - result.range.start,
+            )
+            .await
+        };
         if let Ok(result) = &result {
             let num_bytes = result.range.end - result.range.start;
 
+            self.record_access(location, num_bytes);
             self.record_read("get_opts", location.to_owned(), num_bytes, range);
         }
         result
@@ -346,14 +767,19 @@ impl ObjectStore for IoTrackingStore {
 
     async fn get_ranges(&self, location: &Path, ranges: &[Range<u64>]) -> OSResult<Vec<Bytes>> {
         let _guard = self.stage_guard();
-        let result = self.target.get_ranges(location, ranges).await;
+        let result = {
+            let _timer = self.metrics.read_duration.start_timer();
+            self.observed(
+                IoOperation::Read,
+                self.target.get_ranges(location, ranges),
+                |result| result.iter().map(|b| b.len() as u64).sum(),
+            )
+            .await
+        };
         if let Ok(result) = &result {
-            self.record_read(
-                "get_ranges",
-                location.to_owned(),
-                result.iter().map(|b| b.len() as u64).sum(),
-                None,
-            );
+            let num_bytes = result.iter().map(|b| b.len() as u64).sum();
+            self.record_access(location, num_bytes);
+            self.record_read("get_ranges", location.to_owned(), num_bytes, None);
         }
         result
     }
@@ -363,8 +789,10 @@ impl ObjectStore for IoTrackingStore {
         locations: BoxStream<'static, OSResult<Path>>,
     ) -> BoxStream<'static, OSResult<Path>> {
         let stats = Arc::clone(&self.stats);
+        let metrics = self.metrics.clone();
         let tracked = locations
             .map_ok(move |path| {
+                metrics.record_write(0);
                 let mut stats = stats.lock().unwrap();
                 stats.write_iops += 1;
                 #[cfg(feature = "test-util")]
@@ -376,13 +804,35 @@ impl ObjectStore for IoTrackingStore {
                 path
             })
             .boxed();
-        self.target.delete_stream(tracked)
+        let deleted = self.target.delete_stream(tracked);
+        let stats = Arc::clone(&self.stats);
+        let metrics = self.metrics.clone();
+        let observer = self.observer.clone();
+        let mut last = Instant::now();
+        deleted
+            .map(move |result| {
+                if let Err(err) = &result {
+                    record_error(&stats, &metrics, err);
+                }
+                if let Some(observer) = &observer {
+                    let duration = last.elapsed();
+                    last = Instant::now();
+                    observer.on_request(
+                        IoOperation::Delete,
+                        0,
+                        duration,
+                        result.as_ref().map(|_| ()),
+                    );
+                }
+                result
+            })
+            .boxed()
     }
 
     fn list(&self, prefix: Option<&Path>) -> BoxStream<'static, OSResult<ObjectMeta>> {
         let _guard = self.stage_guard();
         self.record_read("list", prefix.cloned().unwrap_or_default(), 0, None);
-        self.target.list(prefix)
+        self.observed_list(self.target.list(prefix))
     }
 
     fn list_with_offset(
@@ -396,7 +846,7 @@ impl ObjectStore for IoTrackingStore {
             0,
             None,
         );
-        self.target.list_with_offset(prefix, offset)
+        self.observed_list(self.target.list_with_offset(prefix, offset))
     }
 
     async fn list_with_delimiter(&self, prefix: Option<&Path>) -> OSResult<ListResult> {
@@ -407,19 +857,54 @@ impl ObjectStore for IoTrackingStore {
             0,
             None,
         );
-        self.target.list_with_delimiter(prefix).await
+        self.observed(
+            IoOperation::List,
+            self.target.list_with_delimiter(prefix),
+            |_| 0,
+        )
+        .await
     }
 
     async fn copy_opts(&self, from: &Path, to: &Path, opts: CopyOptions) -> OSResult<()> {
         let _guard = self.stage_guard();
         self.record_write("copy", from.to_owned(), 0);
-        self.target.copy_opts(from, to, opts).await
+        self.observed(
+            IoOperation::Copy,
+            self.target.copy_opts(from, to, opts),
+            |_| 0,
+        )
+        .await
     }
 
     async fn rename_opts(&self, from: &Path, to: &Path, opts: RenameOptions) -> OSResult<()> {
         let _guard = self.stage_guard();
         self.record_write("rename", from.to_owned(), 0);
-        self.target.rename_opts(from, to, opts).await
+        self.observed(
+            IoOperation::Rename,
+            self.target.rename_opts(from, to, opts),
+            |_| 0,
+        )
+        .await
+    }
+}
+
+/// A listing being timed for an [`IoObserver`], reported once.
+struct ListObservation {
+    observer: Option<Arc<dyn IoObserver>>,
+    start: Instant,
+}
+
+impl ListObservation {
+    fn finish(&mut self, result: std::result::Result<(), &object_store::Error>) {
+        if let Some(observer) = self.observer.take() {
+            observer.on_request(IoOperation::List, 0, self.start.elapsed(), result);
+        }
+    }
+}
+
+impl Drop for ListObservation {
+    fn drop(&mut self) {
+        self.finish(Ok(()));
     }
 }
 
@@ -429,6 +914,8 @@ struct IoTrackingMultipartUpload {
     #[cfg(feature = "test-util")]
     path: Path,
     stats: Arc<Mutex<IoStats>>,
+    metrics: IoMetrics,
+    observer: Option<Arc<dyn IoObserver>>,
     #[cfg(feature = "test-util")]
     _guard: StageGuard,
 }
@@ -444,6 +931,7 @@ impl MultipartUpload for IoTrackingMultipartUpload {
     }
 
     fn put_part(&mut self, payload: PutPayload) -> UploadPart {
+        self.metrics.record_write(payload.content_length() as u64);
         {
             let mut stats = self.stats.lock().unwrap();
             stats.write_iops += 1;
@@ -455,7 +943,28 @@ impl MultipartUpload for IoTrackingMultipartUpload {
                 range: None,
             });
         }
-        self.target.put_part(payload)
+        let num_bytes = payload.content_length() as u64;
+        let upload = self.target.put_part(payload);
+        let stats = self.stats.clone();
+        let metrics = self.metrics.clone();
+        let observer = self.observer.clone();
+        let start = Instant::now();
+        upload
+            .inspect(move |result| {
+                if let Err(err) = result {
+                    record_error(&stats, &metrics, err);
+                }
+                if let Some(observer) = &observer {
+                    let num_bytes = if result.is_ok() { num_bytes } else { 0 };
+                    observer.on_request(
+                        IoOperation::Write,
+                        num_bytes,
+                        start.elapsed(),
+                        result.as_ref().map(|_| ()),
+                    );
+                }
+            })
+            .boxed()
     }
 }
 
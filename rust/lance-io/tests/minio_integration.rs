@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+//! These integration tests run against a MinIO server, by default at
+//! `http://127.0.0.1:9000` with the `minioadmin` credentials and a bucket
+//! named `lance-test`, see `MINIO_ENDPOINT` and `MINIO_BUCKET`.
+#![cfg(feature = "minio-test")]
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use lance_io::object_store::{ObjectStore, ObjectStoreParams, StorageOptionsAccessor};
+use object_store::{PutMode, PutPayload, path::Path};
+
+async fn get_store(prefix: &str) -> (Arc<ObjectStore>, Path) {
+    let endpoint =
+        std::env::var("MINIO_ENDPOINT").unwrap_or_else(|_| "http://127.0.0.1:9000".into());
+    let bucket = std::env::var("MINIO_BUCKET").unwrap_or_else(|_| "lance-test".into());
+    let options = HashMap::from([
+        ("s3_compatibility_mode".to_string(), "minio".to_string()),
+        ("aws_endpoint".to_string(), endpoint),
+        ("aws_access_key_id".to_string(), "minioadmin".to_string()),
+        (
+            "aws_secret_access_key".to_string(),
+            "minioadmin".to_string(),
+        ),
+    ]);
+    let params = ObjectStoreParams {
+        storage_options_accessor: Some(Arc::new(StorageOptionsAccessor::with_static_options(
+            options,
+        ))),
+        ..Default::default()
+    };
+    ObjectStore::from_uri_and_params(
+        Default::default(),
+        &format!("s3://{}/{}", bucket, prefix),
+        &params,
+    )
+    .await
+    .unwrap()
+}
+
+#[ignore = "Must be run manually against MinIO"]
+#[tokio::test]
+async fn test_conditional_put_is_probed() {
+    let (store, base) = get_store("test_conditional_put_is_probed").await;
+    assert_eq!(store.capabilities().conditional_put, Some(true));
+
+    let path = base.join("object");
+    store.delete(&path).await.ok();
+    store
+        .inner
+        .put_opts(&path, PutPayload::from_static(b"a"), PutMode::Create.into())
+        .await
+        .unwrap();
+    let err = store
+        .inner
+        .put_opts(&path, PutPayload::from_static(b"b"), PutMode::Create.into())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, object_store::Error::AlreadyExists { .. }));
+    store.delete(&path).await.unwrap();
+}
+
+#[ignore = "Must be run manually against MinIO"]
+#[tokio::test]
+async fn test_path_style_round_trip() {
+    let (store, base) = get_store("test_path_style_round_trip").await;
+    let path = base.join("hello");
+    store.put(&path, b"world").await.unwrap();
+    assert_eq!(store.read_one_all(&path).await.unwrap().as_ref(), b"world");
+    store.delete(&path).await.unwrap();
+}
@@ -10,6 +10,7 @@ use std::hint::black_box;
 use criterion::{Criterion, criterion_group, criterion_main};
 use lance_arrow::{ArrowFloatType, FloatArray, bfloat16::BFloat16Type};
 use lance_linalg::distance::cosine::{Cosine, cosine_distance_batch};
+use lance_linalg::distance::cosine_i8::{cosine_i8, cosine_i8_scalar};
 use lance_linalg::distance::cosine_u8::{cosine_u8, cosine_u8_scalar};
 use num_traits::Float;
 
@@ -115,6 +116,42 @@ fn bench_distance(c: &mut Criterion) {
             });
         });
     }
+
+    // i8 cosine benchmarks
+    {
+        use rand::Rng;
+        use std::iter::repeat_with;
+
+        const DIMENSION: usize = 1024;
+        const TOTAL: usize = 1024 * 1024;
+        let mut rng = rand::rng();
+        let key_i8: Vec<i8> = repeat_with(|| rng.random()).take(DIMENSION).collect();
+        let target_i8: Vec<i8> = repeat_with(|| rng.random())
+            .take(TOTAL * DIMENSION)
+            .collect();
+
+        c.bench_function("Cosine(i8, scalar)", |b| {
+            b.iter(|| {
+                black_box(
+                    target_i8
+                        .chunks_exact(DIMENSION)
+                        .map(|tgt| cosine_i8_scalar(&key_i8, tgt))
+                        .fold(0.0, |acc: f32, v| acc + v),
+                );
+            });
+        });
+
+        c.bench_function("Cosine(i8, SIMD)", |b| {
+            b.iter(|| {
+                black_box(
+                    target_i8
+                        .chunks_exact(DIMENSION)
+                        .map(|tgt| cosine_i8(&key_i8, tgt))
+                        .fold(0.0, |acc: f32, v| acc + v),
+                );
+            });
+        });
+    }
 }
 
 #[cfg(target_os = "linux")]
@@ -143,6 +143,40 @@ fn bench_distance(c: &mut Criterion) {
         }
     }
 
+    // i8 dot product benchmarks: scalar baseline vs SIMD dispatch
+    {
+        use lance_linalg::distance::dot_i8::{dot_i8, dot_i8_scalar};
+
+        for &dim in &[128, 256, 512, 1024] {
+            let num_vectors = 1024 * 1024 / dim; // ~1M elements total
+            let mut rng = rand::rng();
+            let key_i8: Vec<i8> = (0..dim).map(|_| rng.random()).collect();
+            let target_i8: Vec<i8> = (0..num_vectors * dim).map(|_| rng.random()).collect();
+
+            c.bench_function(&format!("Dot(i8, scalar, dim={dim})"), |b| {
+                b.iter(|| {
+                    black_box(
+                        target_i8
+                            .chunks(dim)
+                            .map(|y| dot_i8_scalar(key_i8.as_slice(), y))
+                            .collect::<Vec<_>>(),
+                    )
+                });
+            });
+
+            c.bench_function(&format!("Dot(i8, dispatch, dim={dim})"), |b| {
+                b.iter(|| {
+                    black_box(
+                        target_i8
+                            .chunks(dim)
+                            .map(|y| dot_i8(key_i8.as_slice(), y))
+                            .collect::<Vec<_>>(),
+                    )
+                });
+            });
+        }
+    }
+
     run_bench::<Float32Type>(c);
     c.bench_function("Dot(f32, SIMD)", |b| {
         let key = generate_random_array_with_seed::<Float32Type>(DIMENSION, [0; 32]);
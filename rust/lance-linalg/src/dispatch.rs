@@ -0,0 +1,428 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Runtime selection of the SIMD kernels.
+//!
+//! The distance and norm kernels are compiled for several instruction sets, and
+//! the best [`Implementation`] the CPU supports is picked when they are first
+//! used. A single binary therefore runs on every CPU of its architecture, and
+//! only uses AVX-512 where it is available.
+//!
+//! [`LanceLinalg::force_implementation`] and the `LANCE_LINALG_IMPLEMENTATION`
+//! environment variable override the choice, to compare the portable kernels
+//! with the SIMD ones or to fall back if a SIMD kernel misbehaves.
+//!
+//! Each kernel caches a function pointer per implementation, so a call costs an
+//! atomic load and an indirect call on top of the kernel itself.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use lance_core::utils::cpu::{SIMD_SUPPORT, SimdSupport};
+
+use crate::{Error, Result};
+
+/// Environment variable that forces an [`Implementation`], e.g. `scalar`.
+///
+/// It is read once, when the first kernel runs. Unknown values and
+/// implementations the CPU does not support are ignored.
+pub const IMPLEMENTATION_ENV: &str = "LANCE_LINALG_IMPLEMENTATION";
+
+/// A set of kernels, each for an instruction set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Implementation {
+    /// Portable kernels, vectorized by the compiler for the target CPU of the build.
+    Scalar,
+    /// x86_64 AVX2 and FMA.
+    Avx2,
+    /// x86_64 AVX-512F. Integer kernels use AVX-512 VNNI where the CPU has it,
+    /// and the AVX2 kernels otherwise.
+    Avx512,
+    /// aarch64 NEON. There are no SVE kernels, CPUs with SVE use these.
+    Neon,
+    /// loongarch64 LSX.
+    Lsx,
+    /// loongarch64 LASX.
+    Lasx,
+}
+
+impl Implementation {
+    const ALL: [Self; 6] = [
+        Self::Scalar,
+        Self::Avx2,
+        Self::Avx512,
+        Self::Neon,
+        Self::Lsx,
+        Self::Lasx,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Scalar => "scalar",
+            Self::Avx2 => "avx2",
+            Self::Avx512 => "avx512",
+            Self::Neon => "neon",
+            Self::Lsx => "lsx",
+            Self::Lasx => "lasx",
+        }
+    }
+
+    /// Whether the CPU can run these kernels.
+    pub fn is_supported(self) -> bool {
+        match self {
+            Self::Scalar => true,
+            #[cfg(target_arch = "x86_64")]
+            Self::Avx2 => is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma"),
+            #[cfg(target_arch = "x86_64")]
+            Self::Avx512 => Self::Avx2.is_supported() && is_x86_feature_detected!("avx512f"),
+            #[cfg(target_arch = "aarch64")]
+            Self::Neon => std::arch::is_aarch64_feature_detected!("neon"),
+            #[cfg(target_arch = "loongarch64")]
+            Self::Lsx => matches!(*SIMD_SUPPORT, SimdSupport::Lsx | SimdSupport::Lasx),
+            #[cfg(target_arch = "loongarch64")]
+            Self::Lasx => *SIMD_SUPPORT == SimdSupport::Lasx,
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+
+    /// The implementations the CPU supports.
+    pub fn supported() -> impl Iterator<Item = Self> {
+        Self::ALL
+            .into_iter()
+            .filter(|implementation| implementation.is_supported())
+    }
+
+    /// The fastest implementation the CPU supports.
+    pub fn detect() -> Self {
+        Self::supported().last().unwrap_or(Self::Scalar)
+    }
+
+    /// The level the C kernels for half precision floats may use.
+    pub(crate) fn simd_support(self) -> SimdSupport {
+        match self {
+            Self::Scalar => SimdSupport::None,
+            Self::Avx2
+                if matches!(*SIMD_SUPPORT, SimdSupport::Avx512 | SimdSupport::Avx512FP16) =>
+            {
+                SimdSupport::Avx2
+            }
+            Self::Lsx if *SIMD_SUPPORT == SimdSupport::Lasx => SimdSupport::Lsx,
+            _ => *SIMD_SUPPORT,
+        }
+    }
+}
+
+impl Display for Implementation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Implementation {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|implementation| implementation.name().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| {
+                Error::InvalidArgumentError(format!(
+                    "Unknown linalg implementation '{s}', expected one of {}",
+                    Self::ALL.map(Self::name).join(", ")
+                ))
+            })
+    }
+}
+
+const UNSET: u8 = u8::MAX;
+
+/// Index into [`Implementation::ALL`] of the active implementation.
+static ACTIVE: AtomicU8 = AtomicU8::new(UNSET);
+
+/// Process-wide control of the kernels used by this crate.
+pub struct LanceLinalg;
+
+impl LanceLinalg {
+    /// The implementation the kernels currently use.
+    #[inline]
+    pub fn implementation() -> Implementation {
+        match ACTIVE.load(Ordering::Relaxed) {
+            UNSET => Self::init(),
+            index => Implementation::ALL[index as usize],
+        }
+    }
+
+    /// Use `implementation` for all kernels from now on.
+    ///
+    /// Fails if the CPU does not support it.
+    pub fn force_implementation(implementation: Implementation) -> Result<()> {
+        if !implementation.is_supported() {
+            return Err(Error::InvalidArgumentError(format!(
+                "The CPU does not support the {implementation} linalg implementation"
+            )));
+        }
+        Self::set(implementation);
+        Ok(())
+    }
+
+    /// Go back to the implementation detected for the CPU, or the one set by
+    /// [`IMPLEMENTATION_ENV`].
+    pub fn reset_implementation() {
+        Self::set(Self::default_implementation());
+    }
+
+    #[cold]
+    fn init() -> Implementation {
+        let implementation = Self::default_implementation();
+        // Keep an implementation forced while we were detecting.
+        match ACTIVE.compare_exchange(
+            UNSET,
+            Self::index(implementation),
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+        ) {
+            Ok(_) => implementation,
+            Err(index) => Implementation::ALL[index as usize],
+        }
+    }
+
+    fn default_implementation() -> Implementation {
+        std::env::var(IMPLEMENTATION_ENV)
+            .ok()
+            .and_then(|value| value.parse::<Implementation>().ok())
+            .filter(|implementation| implementation.is_supported())
+            .unwrap_or_else(Implementation::detect)
+    }
+
+    fn set(implementation: Implementation) {
+        ACTIVE.store(Self::index(implementation), Ordering::Relaxed);
+    }
+
+    fn index(implementation: Implementation) -> u8 {
+        Implementation::ALL
+            .iter()
+            .position(|other| *other == implementation)
+            .unwrap() as u8
+    }
+}
+
+/// The level the C kernels for half precision floats may use, see
+/// [`Implementation::simd_support`].
+#[inline]
+pub(crate) fn simd_support() -> SimdSupport {
+    LanceLinalg::implementation().simd_support()
+}
+
+/// Whether the integer kernels of [`Implementation::Avx512`] can use VNNI.
+#[cfg(target_arch = "x86_64")]
+pub(crate) fn has_avx512_vnni() -> bool {
+    is_x86_feature_detected!("avx512f")
+        && is_x86_feature_detected!("avx512bw")
+        && is_x86_feature_detected!("avx512vnni")
+}
+
+/// A kernel with a function pointer cached for each [`Implementation`].
+///
+/// `select` is only called with implementations the CPU supports, so it may
+/// return functions that use their instructions.
+pub(crate) struct Dispatch<F: Copy + 'static> {
+    select: fn(Implementation) -> F,
+    kernels: [OnceLock<F>; Implementation::ALL.len()],
+}
+
+impl<F: Copy + 'static> Dispatch<F> {
+    pub(crate) const fn new(select: fn(Implementation) -> F) -> Self {
+        Self {
+            select,
+            kernels: [const { OnceLock::new() }; Implementation::ALL.len()],
+        }
+    }
+
+    /// The kernel of the active implementation.
+    #[inline]
+    pub(crate) fn get(&self) -> F {
+        self.get_for(LanceLinalg::implementation())
+    }
+
+    /// The kernel of `implementation`, which must be supported by the CPU.
+    #[inline]
+    pub(crate) fn get_for(&self, implementation: Implementation) -> F {
+        debug_assert!(implementation.is_supported());
+        *self.kernels[LanceLinalg::index(implementation) as usize]
+            .get_or_init(|| (self.select)(implementation))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use super::*;
+    use crate::distance::{cosine, dot, hamming, l2, norm_l2};
+
+    const SIZES: &[usize] = &[
+        1, 3, 7, 8, 15, 16, 17, 31, 32, 33, 100, 128, 1000, 1024, 4097,
+    ];
+
+    /// The SIMD float kernels use FMA or sum in a different order than the
+    /// scalar ones, so they may differ by rounding.
+    const TOLERANCE: f32 = 1e-5;
+
+    fn assert_close(actual: f32, expected: f32, case: &str) {
+        assert!(
+            (actual - expected).abs() <= TOLERANCE * expected.abs().max(1.0),
+            "{case}: {actual} != {expected}"
+        );
+    }
+
+    /// Runs `check` on random vectors of many lengths, for every implementation.
+    fn for_all_inputs<T>(
+        mut random: impl FnMut(&mut StdRng) -> T,
+        check: impl Fn(Implementation, &[T], &[T]),
+    ) {
+        let mut rng = StdRng::seed_from_u64(42);
+        for &n in SIZES {
+            let x = (0..n).map(|_| random(&mut rng)).collect::<Vec<_>>();
+            let y = (0..n).map(|_| random(&mut rng)).collect::<Vec<_>>();
+            for implementation in Implementation::supported() {
+                check(implementation, &x, &y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_f32_kernels_agree() {
+        for_all_inputs(
+            |rng| rng.random_range(-1.0..1.0_f32),
+            |implementation, x, y| {
+                let case = format!("{implementation} n={}", x.len());
+                let scalar = Implementation::Scalar;
+                // The AVX2 kernels are the scalar code compiled for AVX2.
+                for (kernel, name) in [(&dot::DOT_F32, "dot"), (&l2::L2_F32, "l2")] {
+                    assert_eq!(
+                        kernel.get_for(implementation)(x, y).to_bits(),
+                        kernel.get_for(scalar)(x, y).to_bits(),
+                        "{name} {case}"
+                    );
+                }
+                assert_eq!(
+                    norm_l2::NORM_L2_F32.get_for(implementation)(x).to_bits(),
+                    norm_l2::NORM_L2_F32.get_for(scalar)(x).to_bits(),
+                    "norm_l2 {case}"
+                );
+
+                let expected = dot::DOT_F32.get_for(scalar)(x, y);
+                assert_close(
+                    dot::DOT_F32_WIDE.get_for(implementation)(x, y),
+                    expected,
+                    &case,
+                );
+                let expected = l2::L2_F32.get_for(scalar)(x, y);
+                assert_close(
+                    l2::L2_F32_WIDE.get_for(implementation)(x, y),
+                    expected,
+                    &case,
+                );
+
+                let (x_norm, y_norm) = (norm_l2::norm_l2(x), norm_l2::norm_l2(y));
+                let expected = cosine::COSINE_F32.get_for(scalar)(x, x_norm, y);
+                assert_close(
+                    cosine::COSINE_F32.get_for(implementation)(x, x_norm, y),
+                    expected,
+                    &case,
+                );
+                let expected = cosine::COSINE_WITH_NORMS_F32.get_for(scalar)(x, x_norm, y_norm, y);
+                let actual =
+                    cosine::COSINE_WITH_NORMS_F32.get_for(implementation)(x, x_norm, y_norm, y);
+                assert_close(actual, expected, &case);
+            },
+        );
+    }
+
+    #[test]
+    fn test_f64_kernels_agree() {
+        for_all_inputs(
+            |rng| rng.random_range(-1.0..1.0_f64),
+            |implementation, x, y| {
+                let case = format!("{implementation} n={}", x.len());
+                let scalar = Implementation::Scalar;
+                let expected = dot::DOT_F64.get_for(scalar)(x, y);
+                assert_close(dot::DOT_F64.get_for(implementation)(x, y), expected, &case);
+                let expected = l2::L2_F64.get_for(scalar)(x, y);
+                assert_close(l2::L2_F64.get_for(implementation)(x, y), expected, &case);
+                let expected = norm_l2::NORM_L2_F64.get_for(scalar)(x);
+                assert_close(
+                    norm_l2::NORM_L2_F64.get_for(implementation)(x),
+                    expected,
+                    &case,
+                );
+                let x_norm = norm_l2::norm_l2(x);
+                let expected = cosine::COSINE_F64.get_for(scalar)(x, x_norm, y);
+                assert_close(
+                    cosine::COSINE_F64.get_for(implementation)(x, x_norm, y),
+                    expected,
+                    &case,
+                );
+            },
+        );
+    }
+
+    #[test]
+    fn test_hamming_kernels_agree() {
+        for_all_inputs(
+            |rng| rng.random::<u8>(),
+            |implementation, x, y| {
+                assert_eq!(
+                    hamming::HAMMING.get_for(implementation)(x, y),
+                    hamming::hamming_scalar(x, y),
+                    "{implementation} n={}",
+                    x.len()
+                );
+            },
+        );
+    }
+
+    #[test]
+    fn test_force_unsupported_implementation() {
+        let unsupported = Implementation::ALL
+            .into_iter()
+            .find(|implementation| !implementation.is_supported())
+            .unwrap();
+        let err = LanceLinalg::force_implementation(unsupported).unwrap_err();
+        assert!(err.to_string().contains("does not support"), "{err}");
+    }
+
+    #[test]
+    fn test_parse_implementation() {
+        for implementation in Implementation::ALL {
+            assert_eq!(
+                implementation
+                    .to_string()
+                    .parse::<Implementation>()
+                    .unwrap(),
+                implementation
+            );
+        }
+        assert_eq!(
+            " AVX512 ".parse::<Implementation>().unwrap(),
+            Implementation::Avx512
+        );
+        let err = "sve".parse::<Implementation>().unwrap_err();
+        assert!(err.to_string().contains("scalar, avx2"), "{err}");
+    }
+
+    #[test]
+    fn test_detect() {
+        let detected = Implementation::detect();
+        assert!(detected.is_supported());
+        assert!(Implementation::Scalar.is_supported());
+        assert_eq!(Implementation::Scalar.simd_support(), SimdSupport::None);
+        #[cfg(target_arch = "x86_64")]
+        assert!(!Implementation::Neon.is_supported());
+        #[cfg(target_arch = "aarch64")]
+        assert!(!Implementation::Avx2.is_supported());
+    }
+}
@@ -5,7 +5,8 @@
 //!
 //! This module provides distance metrics for vectors.
 //!
-//! - `bf16, f16, f32, f64` types are supported.
+//! - `bf16, f16, f32, f64` types are supported, plus `i8` and `u8` through
+//!   integer kernels that accumulate in 32 bits.
 //! - SIMD is used when available, on `x86_64`, `aarch64` and `loongarch64`
 //!   architectures.
 
@@ -17,11 +18,14 @@ use arrow_array::{Array, ArrowPrimitiveType, FixedSizeListArray, Float32Array, L
 use arrow_schema::{ArrowError, DataType};
 
 pub mod cosine;
+pub mod cosine_i8;
 pub mod cosine_u8;
 pub mod dot;
+pub mod dot_i8;
 pub mod dot_u8;
 pub mod hamming;
 pub mod l2;
+pub mod l2_i8;
 pub mod l2_u8;
 pub mod norm_l2;
 
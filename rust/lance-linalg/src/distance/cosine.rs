@@ -5,24 +5,24 @@
 //!
 //! <https://en.wikipedia.org/wiki/Cosine_similarity>
 //!
-//! `bf16, f16, f32, f64` types are supported.
+//! `bf16, f16, f32, f64`, `i8` and `u8` types are supported.
 
 use std::sync::Arc;
 
 use arrow_array::{
-    Array, FixedSizeListArray, Float32Array,
+    Array, ArrowPrimitiveType, FixedSizeListArray, Float32Array, PrimitiveArray,
     cast::AsArray,
-    types::{Float16Type, Float32Type, Float64Type, Int8Type},
+    types::{Float16Type, Float32Type, Float64Type, Int8Type, UInt8Type},
 };
 use arrow_schema::DataType;
 use half::{bf16, f16};
-use lance_arrow::{ArrowFloatType, FixedSizeListArrayExt, FloatArray};
-use lance_core::utils::cpu::SIMD_SUPPORT;
+use lance_arrow::{ArrowFloatType, FloatArray};
 #[cfg(feature = "fp16kernels")]
 use lance_core::utils::cpu::SimdSupport;
 
 use super::{Dot, norm_l2::norm_l2};
 use super::{Normalize, dot::dot};
+use crate::dispatch::{Dispatch, Implementation, LanceLinalg, simd_support};
 use crate::simd::{
     FloatSimd, SIMD,
     f32::{f32x8, f32x16},
@@ -72,6 +72,27 @@ impl Cosine for u8 {
     }
 }
 
+impl Cosine for i8 {
+    #[inline]
+    fn cosine(x: &[Self], other: &[Self]) -> f32 {
+        super::cosine_i8::cosine_i8(x, other)
+    }
+
+    fn cosine_batch<'a>(
+        x: &'a [Self],
+        batch: &'a [Self],
+        dimension: usize,
+    ) -> Box<dyn Iterator<Item = f32> + 'a> {
+        // The fused kernel recomputes ‖x‖ alongside the dot product, which
+        // is cheaper than a separate f32 pass over each target.
+        Box::new(
+            batch
+                .chunks_exact(dimension)
+                .map(move |y| super::cosine_i8::cosine_i8(x, y)),
+        )
+    }
+}
+
 #[cfg(feature = "fp16kernels")]
 mod bf16_kernel {
     use half::bf16;
@@ -102,7 +123,7 @@ mod bf16_kernel {
 
 impl Cosine for bf16 {
     fn cosine_fast(x: &[Self], x_norm: f32, y: &[Self]) -> f32 {
-        match *SIMD_SUPPORT {
+        match simd_support() {
             #[cfg(all(feature = "fp16kernels", target_arch = "aarch64"))]
             SimdSupport::Neon => unsafe {
                 bf16_kernel::cosine_bf16_neon(x.as_ptr(), x_norm, y.as_ptr(), y.len() as u32)
@@ -154,7 +175,7 @@ mod kernel {
 
 impl Cosine for f16 {
     fn cosine_fast(x: &[Self], x_norm: f32, y: &[Self]) -> f32 {
-        match *SIMD_SUPPORT {
+        match simd_support() {
             #[cfg(all(feature = "fp16kernels", target_arch = "aarch64"))]
             SimdSupport::Neon => unsafe {
                 kernel::cosine_f16_neon(x.as_ptr(), x_norm, y.as_ptr(), y.len() as u32)
@@ -201,11 +222,9 @@ mod f32 {
         let xy = x * y;
         1.0 - xy.reduce_sum() / x_norm / y2.reduce_sum().sqrt()
     }
-}
 
-impl Cosine for f32 {
     #[inline]
-    fn cosine_fast(x: &[Self], x_norm: Self, other: &[Self]) -> f32 {
+    pub(super) fn cosine_fast(x: &[f32], x_norm: f32, other: &[f32]) -> f32 {
         let dim = x.len();
         let unrolled_len = dim / 16 * 16;
         let mut y_norm16 = f32x16::zeros();
@@ -237,7 +256,7 @@ impl Cosine for f32 {
     }
 
     #[inline]
-    fn cosine_with_norms(x: &[Self], x_norm: Self, y_norm: Self, y: &[Self]) -> Self {
+    pub(super) fn cosine_with_norms(x: &[f32], x_norm: f32, y_norm: f32, y: &[f32]) -> f32 {
         let dim = x.len();
         let unrolled_len = dim / 16 * 16;
         let mut xy16 = f32x16::zeros();
@@ -260,6 +279,41 @@ impl Cosine for f32 {
         let xy = xy16.reduce_sum() + xy8.reduce_sum() + dot(&x[aligned_len..], &y[aligned_len..]);
         1.0 - xy / x_norm / y_norm
     }
+}
+
+type CosineFn<T> = fn(&[T], f32, &[T]) -> f32;
+type CosineWithNormsFn<T> = fn(&[T], f32, f32, &[T]) -> f32;
+
+/// The SIMD kernels accumulate in a different order, so they differ from
+/// the scalar ones by rounding.
+pub(crate) static COSINE_F32: Dispatch<CosineFn<f32>> =
+    Dispatch::new(|implementation| match implementation {
+        Implementation::Scalar => cosine_scalar,
+        _ => f32::cosine_fast,
+    });
+
+pub(crate) static COSINE_WITH_NORMS_F32: Dispatch<CosineWithNormsFn<f32>> =
+    Dispatch::new(|implementation| match implementation {
+        Implementation::Scalar => |x, x_norm, y_norm, y| cosine_scalar_fast(x, x_norm, y, y_norm),
+        _ => f32::cosine_with_norms,
+    });
+
+pub(crate) static COSINE_F64: Dispatch<CosineFn<f64>> =
+    Dispatch::new(|implementation| match implementation {
+        Implementation::Scalar => cosine_scalar,
+        _ => cosine_f64_simd,
+    });
+
+impl Cosine for f32 {
+    #[inline]
+    fn cosine_fast(x: &[Self], x_norm: Self, other: &[Self]) -> f32 {
+        (COSINE_F32.get())(x, x_norm, other)
+    }
+
+    #[inline]
+    fn cosine_with_norms(x: &[Self], x_norm: Self, y_norm: Self, y: &[Self]) -> Self {
+        (COSINE_WITH_NORMS_F32.get())(x, x_norm, y_norm, y)
+    }
 
     fn cosine_batch<'a>(
         x: &'a [Self],
@@ -268,6 +322,13 @@ impl Cosine for f32 {
     ) -> Box<dyn Iterator<Item = f32> + 'a> {
         let x_norm = norm_l2(x);
 
+        if LanceLinalg::implementation() == Implementation::Scalar {
+            return Box::new(
+                batch
+                    .chunks_exact(dimension)
+                    .map(move |y| Self::cosine_fast(x, x_norm, y)),
+            );
+        }
         match dimension {
             8 => Box::new(
                 batch
@@ -291,43 +352,48 @@ impl Cosine for f32 {
 impl Cosine for f64 {
     #[inline]
     fn cosine_fast(x: &[Self], x_norm: f32, y: &[Self]) -> f32 {
-        use crate::simd::f64::{f64x4, f64x8};
-        use crate::simd::{FloatSimd, SIMD};
+        (COSINE_F64.get())(x, x_norm, y)
+    }
+}
 
-        let dim = x.len();
-        let unrolled_len = dim / 8 * 8;
-        let mut y_norm8 = f64x8::zeros();
-        let mut xy8 = f64x8::zeros();
-        for i in (0..unrolled_len).step_by(8) {
-            unsafe {
-                let xv = f64x8::load_unaligned(x.as_ptr().add(i));
-                let yv = f64x8::load_unaligned(y.as_ptr().add(i));
-                xy8.multiply_add(xv, yv);
-                y_norm8.multiply_add(yv, yv);
-            }
+#[inline]
+fn cosine_f64_simd(x: &[f64], x_norm: f32, y: &[f64]) -> f32 {
+    use crate::simd::f64::{f64x4, f64x8};
+    use crate::simd::{FloatSimd, SIMD};
+
+    let dim = x.len();
+    let unrolled_len = dim / 8 * 8;
+    let mut y_norm8 = f64x8::zeros();
+    let mut xy8 = f64x8::zeros();
+    for i in (0..unrolled_len).step_by(8) {
+        unsafe {
+            let xv = f64x8::load_unaligned(x.as_ptr().add(i));
+            let yv = f64x8::load_unaligned(y.as_ptr().add(i));
+            xy8.multiply_add(xv, yv);
+            y_norm8.multiply_add(yv, yv);
         }
-        let aligned_len = dim / 4 * 4;
-        let mut y_norm4 = f64x4::zeros();
-        let mut xy4 = f64x4::zeros();
-        for i in (unrolled_len..aligned_len).step_by(4) {
-            unsafe {
-                let xv = f64x4::load_unaligned(x.as_ptr().add(i));
-                let yv = f64x4::load_unaligned(y.as_ptr().add(i));
-                xy4.multiply_add(xv, yv);
-                y_norm4.multiply_add(yv, yv);
-            }
+    }
+    let aligned_len = dim / 4 * 4;
+    let mut y_norm4 = f64x4::zeros();
+    let mut xy4 = f64x4::zeros();
+    for i in (unrolled_len..aligned_len).step_by(4) {
+        unsafe {
+            let xv = f64x4::load_unaligned(x.as_ptr().add(i));
+            let yv = f64x4::load_unaligned(y.as_ptr().add(i));
+            xy4.multiply_add(xv, yv);
+            y_norm4.multiply_add(yv, yv);
         }
-        let tail_y_norm: Self = y[aligned_len..].iter().map(|&v| v * v).sum();
-        let tail_xy: Self = x[aligned_len..]
-            .iter()
-            .zip(y[aligned_len..].iter())
-            .map(|(&a, &b)| a * b)
-            .sum();
-
-        let y_norm_sq = (y_norm8.reduce_sum() + y_norm4.reduce_sum() + tail_y_norm) as f32;
-        let xy = (xy8.reduce_sum() + xy4.reduce_sum() + tail_xy) as f32;
-        1.0 - xy / x_norm / y_norm_sq.sqrt()
     }
+    let tail_y_norm: f64 = y[aligned_len..].iter().map(|&v| v * v).sum();
+    let tail_xy: f64 = x[aligned_len..]
+        .iter()
+        .zip(y[aligned_len..].iter())
+        .map(|(&a, &b)| a * b)
+        .sum();
+
+    let y_norm_sq = (y_norm8.reduce_sum() + y_norm4.reduce_sum() + tail_y_norm) as f32;
+    let xy = (xy8.reduce_sum() + xy4.reduce_sum() + tail_xy) as f32;
+    1.0 - xy / x_norm / y_norm_sq.sqrt()
 }
 
 /// Fallback non-SIMD implementation
@@ -402,6 +468,33 @@ where
     )))
 }
 
+/// Integer vectors go straight to the integer kernels instead of being
+/// widened to f32 first.
+fn do_cosine_distance_int_arrow_batch<T: ArrowPrimitiveType>(
+    from: &PrimitiveArray<T>,
+    to: &FixedSizeListArray,
+) -> Result<Arc<Float32Array>>
+where
+    T::Native: Cosine,
+{
+    let dimension = to.value_length() as usize;
+    debug_assert_eq!(from.len(), dimension);
+
+    let to_values = to
+        .values()
+        .as_primitive_opt::<T>()
+        .ok_or(Error::InvalidArgumentError(format!(
+            "Unsupported data type {:?}",
+            to.values().data_type()
+        )))?;
+    let dists = cosine_distance_batch(from.values(), to_values.values(), dimension);
+
+    Ok(Arc::new(Float32Array::new(
+        dists.collect(),
+        to.nulls().cloned(),
+    )))
+}
+
 /// Compute Cosine distance between a vector and a batch of vectors.
 ///
 /// Null buffer of `to` is propagated to the returned array.
@@ -422,14 +515,8 @@ pub fn cosine_distance_arrow_batch(
         DataType::Float16 => do_cosine_distance_arrow_batch::<Float16Type>(from.as_primitive(), to),
         DataType::Float32 => do_cosine_distance_arrow_batch::<Float32Type>(from.as_primitive(), to),
         DataType::Float64 => do_cosine_distance_arrow_batch::<Float64Type>(from.as_primitive(), to),
-        DataType::Int8 => do_cosine_distance_arrow_batch::<Float32Type>(
-            &from
-                .as_primitive::<Int8Type>()
-                .into_iter()
-                .map(|x| x.unwrap() as f32)
-                .collect(),
-            &to.convert_to_floating_point()?,
-        ),
+        DataType::Int8 => do_cosine_distance_int_arrow_batch::<Int8Type>(from.as_primitive(), to),
+        DataType::UInt8 => do_cosine_distance_int_arrow_batch::<UInt8Type>(from.as_primitive(), to),
         _ => Err(Error::InvalidArgumentError(format!(
             "Unsupported data type {:?}",
             from.data_type()
@@ -0,0 +1,373 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Signed int8 cosine distance with runtime-dispatched SIMD backends.
+//!
+//! Computes `1 - dot(a,b) / (‖a‖ × ‖b‖)` for i8 slices in a single pass,
+//! keeping `Σ(a·b)`, `Σ(a²)` and `Σ(b²)` in i32 accumulators until the
+//! final f32 normalization.
+//!
+//! Backends (selected at runtime, best available wins):
+//!   1. scalar     — portable reference, also used for tails
+//!   2. avx2       — sign-extend i8→i16, triple VPMADDWD, 32 elements/iter
+//!   3. avx512vnni — same with VPDPWSSD accumulation, 64 elements/iter
+//!   4. neon       — triple SMULL + SADALP, 16 elements/iter
+
+#[cfg(target_arch = "x86_64")]
+use crate::dispatch::has_avx512_vnni;
+use crate::dispatch::{Dispatch, Implementation};
+
+/// Intermediate results from the fused i8 cosine kernel.
+///
+/// Separated from the final normalization so SIMD backends can be tested
+/// for exact integer equality before the f32 division.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CosineI8Accumulators {
+    pub dot_ab: i32,
+    pub norm_a_sq: u32,
+    pub norm_b_sq: u32,
+}
+
+/// Portable scalar fused cosine accumulation.
+#[inline]
+pub fn cosine_i8_accum_scalar(a: &[i8], b: &[i8]) -> CosineI8Accumulators {
+    debug_assert_eq!(a.len(), b.len());
+    let (mut dot_ab, mut norm_a_sq, mut norm_b_sq) = (0i32, 0u32, 0u32);
+    for (&x, &y) in a.iter().zip(b.iter()) {
+        let (xi, yi) = (x as i32, y as i32);
+        dot_ab += xi * yi;
+        norm_a_sq += (xi * xi) as u32;
+        norm_b_sq += (yi * yi) as u32;
+    }
+    CosineI8Accumulators {
+        dot_ab,
+        norm_a_sq,
+        norm_b_sq,
+    }
+}
+
+/// Convert accumulators to cosine distance: `1 - dot / (‖a‖ × ‖b‖)`.
+#[inline]
+fn normalize(acc: CosineI8Accumulators) -> f32 {
+    let na = (acc.norm_a_sq as f32).sqrt();
+    let nb = (acc.norm_b_sq as f32).sqrt();
+    let denom = na * nb;
+    if denom == 0.0 {
+        // Same convention as the u8 kernel: a zero-norm input yields 0.
+        return 0.0;
+    }
+    1.0 - acc.dot_ab as f32 / denom
+}
+
+/// Portable scalar i8 cosine distance.
+#[inline]
+pub fn cosine_i8_scalar(a: &[i8], b: &[i8]) -> f32 {
+    normalize(cosine_i8_accum_scalar(a, b))
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use super::CosineI8Accumulators;
+    use std::arch::x86_64::*;
+
+    /// Horizontal sum of all 8 × i32 lanes in a __m256i.
+    #[inline(always)]
+    unsafe fn hsum_epi32_avx2(v: __m256i) -> i32 {
+        let lo128 = _mm256_castsi256_si128(v);
+        let hi128 = _mm256_extracti128_si256(v, 1);
+        let mut sum128 = _mm_add_epi32(lo128, hi128);
+        sum128 = _mm_hadd_epi32(sum128, sum128);
+        sum128 = _mm_hadd_epi32(sum128, sum128);
+        _mm_cvtsi128_si32(sum128)
+    }
+
+    /// AVX2 fused cosine: three VPMADDWD products per half, 32 elements/iter.
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn cosine_i8_accum_avx2(a: &[i8], b: &[i8]) -> CosineI8Accumulators {
+        debug_assert_eq!(a.len(), b.len());
+        let n = a.len();
+        let mut acc_dot = _mm256_setzero_si256();
+        let mut acc_na = _mm256_setzero_si256();
+        let mut acc_nb = _mm256_setzero_si256();
+        let mut i = 0usize;
+
+        while i + 32 <= n {
+            let av = _mm256_loadu_si256(a.as_ptr().add(i) as *const __m256i);
+            let bv = _mm256_loadu_si256(b.as_ptr().add(i) as *const __m256i);
+
+            // Sign-extend each 128-bit half to 16 × i16.
+            let a_lo = _mm256_cvtepi8_epi16(_mm256_castsi256_si128(av));
+            let a_hi = _mm256_cvtepi8_epi16(_mm256_extracti128_si256(av, 1));
+            let b_lo = _mm256_cvtepi8_epi16(_mm256_castsi256_si128(bv));
+            let b_hi = _mm256_cvtepi8_epi16(_mm256_extracti128_si256(bv, 1));
+
+            acc_dot = _mm256_add_epi32(acc_dot, _mm256_madd_epi16(a_lo, b_lo));
+            acc_dot = _mm256_add_epi32(acc_dot, _mm256_madd_epi16(a_hi, b_hi));
+            acc_na = _mm256_add_epi32(acc_na, _mm256_madd_epi16(a_lo, a_lo));
+            acc_na = _mm256_add_epi32(acc_na, _mm256_madd_epi16(a_hi, a_hi));
+            acc_nb = _mm256_add_epi32(acc_nb, _mm256_madd_epi16(b_lo, b_lo));
+            acc_nb = _mm256_add_epi32(acc_nb, _mm256_madd_epi16(b_hi, b_hi));
+            i += 32;
+        }
+
+        let mut dot_ab = hsum_epi32_avx2(acc_dot);
+        let mut norm_a_sq = hsum_epi32_avx2(acc_na) as u32;
+        let mut norm_b_sq = hsum_epi32_avx2(acc_nb) as u32;
+
+        // Scalar tail
+        while i < n {
+            let (xi, yi) = (a[i] as i32, b[i] as i32);
+            dot_ab += xi * yi;
+            norm_a_sq += (xi * xi) as u32;
+            norm_b_sq += (yi * yi) as u32;
+            i += 1;
+        }
+
+        CosineI8Accumulators {
+            dot_ab,
+            norm_a_sq,
+            norm_b_sq,
+        }
+    }
+
+    /// AVX-512 VNNI fused cosine: VPDPWSSD for each product, 64 elements/iter.
+    #[target_feature(enable = "avx512f,avx512bw,avx512vnni")]
+    pub unsafe fn cosine_i8_accum_avx512_vnni(a: &[i8], b: &[i8]) -> CosineI8Accumulators {
+        debug_assert_eq!(a.len(), b.len());
+        let n = a.len();
+        let mut acc_dot = _mm512_setzero_si512();
+        let mut acc_na = _mm512_setzero_si512();
+        let mut acc_nb = _mm512_setzero_si512();
+        let mut i = 0usize;
+
+        while i + 64 <= n {
+            let av = _mm512_loadu_si512(a.as_ptr().add(i) as *const __m512i);
+            let bv = _mm512_loadu_si512(b.as_ptr().add(i) as *const __m512i);
+
+            // Sign-extend each 256-bit half to 32 × i16.
+            let a_lo = _mm512_cvtepi8_epi16(_mm512_castsi512_si256(av));
+            let a_hi = _mm512_cvtepi8_epi16(_mm512_extracti64x4_epi64(av, 1));
+            let b_lo = _mm512_cvtepi8_epi16(_mm512_castsi512_si256(bv));
+            let b_hi = _mm512_cvtepi8_epi16(_mm512_extracti64x4_epi64(bv, 1));
+
+            acc_dot = _mm512_dpwssd_epi32(acc_dot, a_lo, b_lo);
+            acc_dot = _mm512_dpwssd_epi32(acc_dot, a_hi, b_hi);
+            acc_na = _mm512_dpwssd_epi32(acc_na, a_lo, a_lo);
+            acc_na = _mm512_dpwssd_epi32(acc_na, a_hi, a_hi);
+            acc_nb = _mm512_dpwssd_epi32(acc_nb, b_lo, b_lo);
+            acc_nb = _mm512_dpwssd_epi32(acc_nb, b_hi, b_hi);
+            i += 64;
+        }
+
+        let mut dot_ab = _mm512_reduce_add_epi32(acc_dot);
+        let mut norm_a_sq = _mm512_reduce_add_epi32(acc_na) as u32;
+        let mut norm_b_sq = _mm512_reduce_add_epi32(acc_nb) as u32;
+
+        // Scalar tail
+        while i < n {
+            let (xi, yi) = (a[i] as i32, b[i] as i32);
+            dot_ab += xi * yi;
+            norm_a_sq += (xi * xi) as u32;
+            norm_b_sq += (yi * yi) as u32;
+            i += 1;
+        }
+
+        CosineI8Accumulators {
+            dot_ab,
+            norm_a_sq,
+            norm_b_sq,
+        }
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod neon {
+    use super::CosineI8Accumulators;
+    use std::arch::aarch64::*;
+
+    /// NEON fused cosine: SMULL + SADALP for each product, 16 elements/iter.
+    #[target_feature(enable = "neon")]
+    pub unsafe fn cosine_i8_accum_neon(a: &[i8], b: &[i8]) -> CosineI8Accumulators {
+        debug_assert_eq!(a.len(), b.len());
+        let n = a.len();
+        let mut acc_dot = vdupq_n_s32(0);
+        let mut acc_na = vdupq_n_s32(0);
+        let mut acc_nb = vdupq_n_s32(0);
+        let mut i = 0usize;
+
+        while i + 16 <= n {
+            let av = vld1q_s8(a.as_ptr().add(i));
+            let bv = vld1q_s8(b.as_ptr().add(i));
+            let (a_lo, b_lo) = (vget_low_s8(av), vget_low_s8(bv));
+            acc_dot = vpadalq_s16(acc_dot, vmull_s8(a_lo, b_lo));
+            acc_dot = vpadalq_s16(acc_dot, vmull_high_s8(av, bv));
+            acc_na = vpadalq_s16(acc_na, vmull_s8(a_lo, a_lo));
+            acc_na = vpadalq_s16(acc_na, vmull_high_s8(av, av));
+            acc_nb = vpadalq_s16(acc_nb, vmull_s8(b_lo, b_lo));
+            acc_nb = vpadalq_s16(acc_nb, vmull_high_s8(bv, bv));
+            i += 16;
+        }
+
+        let mut dot_ab = vaddvq_s32(acc_dot);
+        let mut norm_a_sq = vaddvq_u32(vreinterpretq_u32_s32(acc_na));
+        let mut norm_b_sq = vaddvq_u32(vreinterpretq_u32_s32(acc_nb));
+
+        // Scalar tail
+        while i < n {
+            let (xi, yi) = (a[i] as i32, b[i] as i32);
+            dot_ab += xi * yi;
+            norm_a_sq += (xi * xi) as u32;
+            norm_b_sq += (yi * yi) as u32;
+            i += 1;
+        }
+
+        CosineI8Accumulators {
+            dot_ab,
+            norm_a_sq,
+            norm_b_sq,
+        }
+    }
+}
+
+type CosineI8AccumFn = fn(&[i8], &[i8]) -> CosineI8Accumulators;
+
+static DISPATCH: Dispatch<CosineI8AccumFn> = Dispatch::new(select_backend);
+
+fn select_backend(implementation: Implementation) -> CosineI8AccumFn {
+    match implementation {
+        #[cfg(target_arch = "x86_64")]
+        Implementation::Avx512 if has_avx512_vnni() => {
+            |a, b| unsafe { x86::cosine_i8_accum_avx512_vnni(a, b) }
+        }
+        #[cfg(target_arch = "x86_64")]
+        Implementation::Avx2 | Implementation::Avx512 => {
+            |a, b| unsafe { x86::cosine_i8_accum_avx2(a, b) }
+        }
+        #[cfg(target_arch = "aarch64")]
+        Implementation::Neon => |a, b| unsafe { neon::cosine_i8_accum_neon(a, b) },
+        _ => cosine_i8_accum_scalar,
+    }
+}
+
+/// Dispatched fused i8 cosine accumulation.
+#[inline]
+fn cosine_i8_accum(a: &[i8], b: &[i8]) -> CosineI8Accumulators {
+    (DISPATCH.get())(a, b)
+}
+
+/// Dispatched i8 cosine distance, selecting the best available SIMD backend.
+///
+/// Returns `1 - dot(a,b) / (‖a‖ × ‖b‖)` computed in a single pass.
+#[inline]
+pub fn cosine_i8(a: &[i8], b: &[i8]) -> f32 {
+    normalize(cosine_i8_accum(a, b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill_random(buf: &mut [i8], seed: &mut u32) {
+        for slot in buf.iter_mut() {
+            *seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            *slot = (*seed >> 16) as i8;
+        }
+    }
+
+    /// Verify SIMD backends produce identical integer accumulators to scalar.
+    fn check_all_backends_accum(a: &[i8], b: &[i8], case: &str) {
+        let reference = cosine_i8_accum_scalar(a, b);
+
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("avx2") {
+                let got = unsafe { x86::cosine_i8_accum_avx2(a, b) };
+                assert_eq!(got, reference, "avx2 [{case}] n={}", a.len());
+            }
+
+            if is_x86_feature_detected!("avx512f")
+                && is_x86_feature_detected!("avx512bw")
+                && is_x86_feature_detected!("avx512vnni")
+            {
+                let got = unsafe { x86::cosine_i8_accum_avx512_vnni(a, b) };
+                assert_eq!(got, reference, "avx512_vnni [{case}] n={}", a.len());
+            }
+        }
+
+        #[cfg(target_arch = "aarch64")]
+        {
+            let got = unsafe { neon::cosine_i8_accum_neon(a, b) };
+            assert_eq!(got, reference, "neon [{case}] n={}", a.len());
+        }
+
+        let dispatched = cosine_i8_accum(a, b);
+        assert_eq!(dispatched, reference, "dispatch [{case}] n={}", a.len());
+        for implementation in Implementation::supported() {
+            let got = (DISPATCH.get_for(implementation))(a, b);
+            assert_eq!(got, reference, "{implementation} [{case}] n={}", a.len());
+        }
+    }
+
+    #[test]
+    fn random_inputs_all_dimensions_and_offsets() {
+        let mut a = vec![0i8; 1024 + 3];
+        let mut b = vec![0i8; 1024 + 3];
+        let mut seed = 0xC0FFEE_u32;
+        fill_random(&mut a, &mut seed);
+        fill_random(&mut b, &mut seed);
+
+        // Offsets start the slices off any natural alignment of the buffer.
+        for offset in 0..4 {
+            for n in 1..=1024 {
+                check_all_backends_accum(
+                    &a[offset..offset + n],
+                    &b[3 - offset..3 - offset + n],
+                    "random",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn boundary_values() {
+        let mut a = vec![0i8; 1024];
+        let mut b = vec![0i8; 1024];
+
+        for n in 1..=1024 {
+            a[..n].fill(i8::MIN);
+            b[..n].fill(i8::MIN);
+            check_all_backends_accum(&a[..n], &b[..n], "min-min");
+
+            a[..n].fill(i8::MIN);
+            b[..n].fill(i8::MAX);
+            check_all_backends_accum(&a[..n], &b[..n], "min-max");
+
+            a[..n].fill(0);
+            b[..n].fill(i8::MAX);
+            check_all_backends_accum(&a[..n], &b[..n], "0-max");
+
+            for i in 0..n {
+                a[i] = if i & 1 == 0 { i8::MIN } else { i8::MAX };
+                b[i] = if i & 1 == 0 { i8::MAX } else { i8::MIN };
+            }
+            check_all_backends_accum(&a[..n], &b[..n], "alt min/max");
+        }
+    }
+
+    #[test]
+    fn cosine_known_values() {
+        // Identical vectors → distance 0
+        let v = [10i8, -20, 30, -40];
+        assert!(cosine_i8(&v, &v).abs() < 1e-6);
+
+        // Opposite vectors → distance 2
+        let w = [-10i8, 20, -30, 40];
+        assert!((cosine_i8(&v, &w) - 2.0).abs() < 1e-6);
+
+        // Orthogonal vectors → distance 1
+        assert_eq!(cosine_i8(&[1, 0], &[0, -1]), 1.0);
+
+        // Zero vectors → distance 0 (by convention)
+        assert_eq!(cosine_i8(&[0, 0], &[1, 2]), 0.0);
+    }
+}
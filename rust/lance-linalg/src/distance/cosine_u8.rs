@@ -13,7 +13,9 @@
 //!   2. avx2       — zero-extend u8→i16, triple VPMADDWD, 32 elements/iter
 //!   3. avx512vnni — same with VPDPWSSD accumulation, 64 elements/iter
 
-use std::sync::OnceLock;
+#[cfg(target_arch = "x86_64")]
+use crate::dispatch::has_avx512_vnni;
+use crate::dispatch::{Dispatch, Implementation};
 
 /// Intermediate results from the fused u8 cosine kernel: (dot_ab, norm_a², norm_b²).
 ///
@@ -184,30 +186,26 @@ mod x86 {
 
 type CosineU8AccumFn = fn(&[u8], &[u8]) -> CosineAccumulators;
 
-static DISPATCH: OnceLock<CosineU8AccumFn> = OnceLock::new();
+static DISPATCH: Dispatch<CosineU8AccumFn> = Dispatch::new(select_backend);
 
-fn select_backend() -> CosineU8AccumFn {
-    #[cfg(target_arch = "x86_64")]
-    {
-        if is_x86_feature_detected!("avx512f")
-            && is_x86_feature_detected!("avx512bw")
-            && is_x86_feature_detected!("avx512vnni")
-        {
-            return |a, b| unsafe { x86::cosine_u8_accum_avx512_vnni(a, b) };
+fn select_backend(implementation: Implementation) -> CosineU8AccumFn {
+    match implementation {
+        #[cfg(target_arch = "x86_64")]
+        Implementation::Avx512 if has_avx512_vnni() => {
+            |a, b| unsafe { x86::cosine_u8_accum_avx512_vnni(a, b) }
         }
-
-        if is_x86_feature_detected!("avx2") {
-            return |a, b| unsafe { x86::cosine_u8_accum_avx2(a, b) };
+        #[cfg(target_arch = "x86_64")]
+        Implementation::Avx2 | Implementation::Avx512 => {
+            |a, b| unsafe { x86::cosine_u8_accum_avx2(a, b) }
         }
+        _ => cosine_u8_accum_scalar,
     }
-
-    cosine_u8_accum_scalar
 }
 
 /// Dispatched fused u8 cosine accumulation.
 #[inline]
 fn cosine_u8_accum(a: &[u8], b: &[u8]) -> CosineAccumulators {
-    (DISPATCH.get_or_init(select_backend))(a, b)
+    (DISPATCH.get())(a, b)
 }
 
 /// Dispatched u8 cosine distance, selecting the best available SIMD backend.
@@ -255,6 +253,10 @@ mod tests {
 
         let dispatched = cosine_u8_accum(a, b);
         assert_eq!(dispatched, reference, "dispatch [{case}] n={}", a.len());
+        for implementation in Implementation::supported() {
+            let got = (DISPATCH.get_for(implementation))(a, b);
+            assert_eq!(got, reference, "{implementation} [{case}] n={}", a.len());
+        }
     }
 
     #[test]
@@ -8,18 +8,19 @@ use std::ops::AddAssign;
 use std::sync::Arc;
 
 use crate::Error;
-use arrow_array::types::{Float16Type, Float64Type, Int8Type};
+use arrow_array::types::{Float16Type, Float64Type, Int8Type, UInt8Type};
 use arrow_array::{Array, FixedSizeListArray, Float32Array, cast::AsArray, types::Float32Type};
+use arrow_array::{ArrowPrimitiveType, PrimitiveArray};
 use arrow_schema::DataType;
 use half::{bf16, f16};
-use lance_arrow::{ArrowFloatType, FixedSizeListArrayExt, FloatArray};
+use lance_arrow::{ArrowFloatType, FloatArray};
 use lance_core::assume_eq;
-use lance_core::utils::cpu::SIMD_SUPPORT;
 #[cfg(feature = "fp16kernels")]
 use lance_core::utils::cpu::SimdSupport;
 use num_traits::{AsPrimitive, Num, real::Real};
 
 use crate::Result;
+use crate::dispatch::{Dispatch, Implementation, simd_support};
 
 /// Default implementation of dot product.
 ///
@@ -68,15 +69,40 @@ pub fn dot<T: Dot>(from: &[T], to: &[T]) -> f32 {
 /// needed on top of the generic [`dot`].
 #[inline]
 pub fn dot_f32(x: &[f32], y: &[f32]) -> f32 {
-    #[cfg(target_arch = "x86_64")]
-    {
-        use lance_core::utils::cpu::SimdSupport;
-        if matches!(*SIMD_SUPPORT, SimdSupport::Avx512 | SimdSupport::Avx512FP16) {
-            // SAFETY: guarded by the runtime AVX-512 detection above.
-            return unsafe { dot_f32_avx512(x, y) };
-        }
-    }
-    dot(x, y)
+    (DOT_F32_WIDE.get())(x, y)
+}
+
+type DotFn<T> = fn(&[T], &[T]) -> f32;
+
+/// Kernels behind [`Dot`] for f32. The AVX2 one is the scalar code compiled
+/// for AVX2, so all of them return the same bits.
+pub(crate) static DOT_F32: Dispatch<DotFn<f32>> =
+    Dispatch::new(|implementation| match implementation {
+        // SAFETY: the implementation is only selected on CPUs that support it.
+        #[cfg(target_arch = "x86_64")]
+        Implementation::Avx2 | Implementation::Avx512 => |x, y| unsafe { dot_f32_avx2(x, y) },
+        _ => dot_scalar::<f32, f32, 16>,
+    });
+
+/// Kernels behind [`dot_f32`], which uses FMA on AVX-512.
+pub(crate) static DOT_F32_WIDE: Dispatch<DotFn<f32>> =
+    Dispatch::new(|implementation| match implementation {
+        // SAFETY: the implementation is only selected on CPUs that support it.
+        #[cfg(target_arch = "x86_64")]
+        Implementation::Avx512 => |x, y| unsafe { dot_f32_avx512(x, y) },
+        _ => DOT_F32.get_for(implementation),
+    });
+
+pub(crate) static DOT_F64: Dispatch<DotFn<f64>> =
+    Dispatch::new(|implementation| match implementation {
+        Implementation::Scalar => |x, y| dot_scalar::<f64, f64, 8>(x, y) as f32,
+        _ => dot_f64_simd,
+    });
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2,fma")]
+unsafe fn dot_f32_avx2(x: &[f32], y: &[f32]) -> f32 {
+    dot_scalar::<f32, f32, 16>(x, y)
 }
 
 #[cfg(target_arch = "x86_64")]
@@ -136,7 +162,7 @@ mod bf16_kernel {
 impl Dot for bf16 {
     #[inline]
     fn dot(x: &[Self], y: &[Self]) -> f32 {
-        match *SIMD_SUPPORT {
+        match simd_support() {
             #[cfg(all(feature = "fp16kernels", target_arch = "aarch64"))]
             SimdSupport::Neon => unsafe {
                 bf16_kernel::dot_bf16_neon(x.as_ptr(), y.as_ptr(), x.len() as u32)
@@ -189,7 +215,7 @@ mod kernel {
 impl Dot for f16 {
     #[inline]
     fn dot(x: &[Self], y: &[Self]) -> f32 {
-        match *SIMD_SUPPORT {
+        match simd_support() {
             #[cfg(all(feature = "fp16kernels", target_arch = "aarch64"))]
             SimdSupport::Neon => unsafe {
                 kernel::dot_f16_neon(x.as_ptr(), y.as_ptr(), x.len() as u32)
@@ -222,14 +248,14 @@ impl Dot for f16 {
 impl Dot for f32 {
     #[inline]
     fn dot(x: &[Self], y: &[Self]) -> f32 {
-        dot_scalar::<Self, Self, 16>(x, y)
+        (DOT_F32.get())(x, y)
     }
 }
 
 impl Dot for f64 {
     #[inline]
     fn dot(x: &[Self], y: &[Self]) -> f32 {
-        dot_f64_simd(x, y)
+        (DOT_F64.get())(x, y)
     }
 }
 
@@ -277,6 +303,13 @@ impl Dot for u8 {
     }
 }
 
+impl Dot for i8 {
+    #[inline]
+    fn dot(x: &[Self], y: &[Self]) -> f32 {
+        super::dot_i8::dot_i8(x, y) as f32
+    }
+}
+
 /// Negative dot product, to present the relative order of dot distance.
 pub fn dot_distance_batch<'a, T: Dot>(
     from: &'a [T],
@@ -320,6 +353,38 @@ where
     )))
 }
 
+/// Integer vectors go straight to the integer kernels instead of being
+/// widened to f32 first.
+fn do_dot_distance_int_arrow_batch<T: ArrowPrimitiveType>(
+    from: &PrimitiveArray<T>,
+    to: &FixedSizeListArray,
+) -> Result<Arc<Float32Array>>
+where
+    T::Native: Dot,
+{
+    let dimension = to.value_length() as usize;
+    debug_assert_eq!(from.len(), dimension);
+
+    let to_values = to
+        .values()
+        .as_primitive_opt::<T>()
+        .ok_or(Error::InvalidArgumentError(format!(
+            "Invalid type: expect {:?} got {:?}",
+            from.data_type(),
+            to.value_type()
+        )))?;
+
+    let dists = to_values
+        .values()
+        .chunks_exact(dimension)
+        .map(|v| dot_distance(from.values(), v));
+
+    Ok(Arc::new(Float32Array::new(
+        dists.collect(),
+        to.nulls().cloned(),
+    )))
+}
+
 /// Compute negative dot product distance between a vector and a batch of vectors.
 ///
 /// Null buffer of `to` is propagated to the returned array.
@@ -343,14 +408,8 @@ pub fn dot_distance_arrow_batch(
         DataType::Float16 => do_dot_distance_arrow_batch::<Float16Type>(from.as_primitive(), to),
         DataType::Float32 => do_dot_distance_arrow_batch::<Float32Type>(from.as_primitive(), to),
         DataType::Float64 => do_dot_distance_arrow_batch::<Float64Type>(from.as_primitive(), to),
-        DataType::Int8 => do_dot_distance_arrow_batch::<Float32Type>(
-            &from
-                .as_primitive::<Int8Type>()
-                .into_iter()
-                .map(|x| x.unwrap() as f32)
-                .collect(),
-            &to.convert_to_floating_point()?,
-        ),
+        DataType::Int8 => do_dot_distance_int_arrow_batch::<Int8Type>(from.as_primitive(), to),
+        DataType::UInt8 => do_dot_distance_int_arrow_batch::<UInt8Type>(from.as_primitive(), to),
         _ => Err(Error::InvalidArgumentError(format!(
             "Unsupported data type: {:?}",
             from.data_type()
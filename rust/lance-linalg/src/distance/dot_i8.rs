@@ -0,0 +1,268 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Signed int8 dot product with runtime-dispatched SIMD backends.
+//!
+//! Used for int8-quantized embeddings stored as `FixedSizeList<Int8>`.
+//! Products are accumulated in i32, so the result is exact and only
+//! converted to f32 by the caller.
+//!
+//! Backends (selected at runtime, best available wins):
+//!   1. scalar     — portable reference, also used for tails
+//!   2. avx2       — sign-extend i8→i16, then VPMADDWD, 32 elements/iter
+//!   3. avx512vnni — VPDPBUSD with XOR-0x80 bias trick, 64 elements/iter
+//!   4. neon       — SMULL widening multiply + SADALP, 16 elements/iter
+//!
+//! ## The VNNI bias trick
+//!
+//! VPDPBUSD multiplies an unsigned operand by a signed one. We flip the
+//! sign bit of `a`, which maps it to `a + 128` as u8, feed `b` directly as
+//! signed, and subtract 128·Σb at the end:
+//!
+//!   DPBUSD(a ⊕ 0x80, b) = Σ (a + 128)·b = Σ a·b + 128·Σb
+//!
+//! Σb comes from a second VPDPBUSD against a vector of ones. Both sums use
+//! wrapping i32 arithmetic, so the correction is exact whenever the true
+//! dot product fits in an i32.
+
+#[cfg(target_arch = "x86_64")]
+use crate::dispatch::has_avx512_vnni;
+use crate::dispatch::{Dispatch, Implementation};
+
+/// Portable scalar i8 dot product, also used for SIMD tail elements.
+#[inline]
+pub fn dot_i8_scalar(a: &[i8], b: &[i8]) -> i32 {
+    debug_assert_eq!(a.len(), b.len());
+    a.iter()
+        .zip(b.iter())
+        .map(|(&x, &y)| x as i32 * y as i32)
+        .sum()
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use std::arch::x86_64::*;
+
+    /// AVX2 path: sign-extend i8→i16, then VPMADDWD. 32 elements/iter.
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn dot_i8_avx2(a: &[i8], b: &[i8]) -> i32 {
+        debug_assert_eq!(a.len(), b.len());
+        let n = a.len();
+        let mut acc = _mm256_setzero_si256();
+        let mut i = 0usize;
+
+        while i + 32 <= n {
+            let av = _mm256_loadu_si256(a.as_ptr().add(i) as *const __m256i);
+            let bv = _mm256_loadu_si256(b.as_ptr().add(i) as *const __m256i);
+
+            // |a·b| ≤ 128², so each VPMADDWD pair sum fits comfortably in i32.
+            let a_lo = _mm256_cvtepi8_epi16(_mm256_castsi256_si128(av));
+            let a_hi = _mm256_cvtepi8_epi16(_mm256_extracti128_si256(av, 1));
+            let b_lo = _mm256_cvtepi8_epi16(_mm256_castsi256_si128(bv));
+            let b_hi = _mm256_cvtepi8_epi16(_mm256_extracti128_si256(bv, 1));
+
+            acc = _mm256_add_epi32(acc, _mm256_madd_epi16(a_lo, b_lo));
+            acc = _mm256_add_epi32(acc, _mm256_madd_epi16(a_hi, b_hi));
+            i += 32;
+        }
+
+        let lo128 = _mm256_castsi256_si128(acc);
+        let hi128 = _mm256_extracti128_si256(acc, 1);
+        let mut sum128 = _mm_add_epi32(lo128, hi128);
+        sum128 = _mm_hadd_epi32(sum128, sum128);
+        sum128 = _mm_hadd_epi32(sum128, sum128);
+        let mut result = _mm_cvtsi128_si32(sum128);
+
+        while i < n {
+            result += a[i] as i32 * b[i] as i32;
+            i += 1;
+        }
+        result
+    }
+
+    /// AVX-512 VNNI path (Ice Lake+, Zen 4+). 64 elements/iter.
+    ///
+    /// VPDPBUSD expects (unsigned, signed) operands. We XOR a with 0x80 to
+    /// map it to u8, then correct: result − 128·Σb.
+    #[target_feature(enable = "avx512f,avx512bw,avx512vnni")]
+    pub unsafe fn dot_i8_avx512_vnni(a: &[i8], b: &[i8]) -> i32 {
+        debug_assert_eq!(a.len(), b.len());
+        let n = a.len();
+
+        let mut acc_dot = _mm512_setzero_si512();
+        let mut acc_sumb = _mm512_setzero_si512();
+        let sign_flip = _mm512_set1_epi8(0x80u8 as i8);
+        let ones = _mm512_set1_epi8(1);
+        let mut i = 0usize;
+
+        while i + 64 <= n {
+            let av = _mm512_loadu_si512(a.as_ptr().add(i) as *const __m512i);
+            let bv = _mm512_loadu_si512(b.as_ptr().add(i) as *const __m512i);
+            let a_biased = _mm512_xor_si512(av, sign_flip);
+            acc_dot = _mm512_dpbusd_epi32(acc_dot, a_biased, bv);
+            acc_sumb = _mm512_dpbusd_epi32(acc_sumb, ones, bv);
+            i += 64;
+        }
+
+        let biased_dot = _mm512_reduce_add_epi32(acc_dot);
+        let sum_b = _mm512_reduce_add_epi32(acc_sumb);
+        let mut result = biased_dot.wrapping_sub(sum_b.wrapping_mul(128));
+
+        while i < n {
+            result += a[i] as i32 * b[i] as i32;
+            i += 1;
+        }
+        result
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod neon {
+    use std::arch::aarch64::*;
+
+    /// NEON path: SMULL widens each product to i16, SADALP folds pairs into
+    /// i32 lanes. 16 elements/iter.
+    #[target_feature(enable = "neon")]
+    pub unsafe fn dot_i8_neon(a: &[i8], b: &[i8]) -> i32 {
+        debug_assert_eq!(a.len(), b.len());
+        let n = a.len();
+        let mut acc = vdupq_n_s32(0);
+        let mut i = 0usize;
+
+        while i + 16 <= n {
+            let av = vld1q_s8(a.as_ptr().add(i));
+            let bv = vld1q_s8(b.as_ptr().add(i));
+            acc = vpadalq_s16(acc, vmull_s8(vget_low_s8(av), vget_low_s8(bv)));
+            acc = vpadalq_s16(acc, vmull_high_s8(av, bv));
+            i += 16;
+        }
+
+        let mut result = vaddvq_s32(acc);
+        while i < n {
+            result += a[i] as i32 * b[i] as i32;
+            i += 1;
+        }
+        result
+    }
+}
+
+type DotI8Fn = fn(&[i8], &[i8]) -> i32;
+
+static DISPATCH: Dispatch<DotI8Fn> = Dispatch::new(select_backend);
+
+fn select_backend(implementation: Implementation) -> DotI8Fn {
+    match implementation {
+        #[cfg(target_arch = "x86_64")]
+        Implementation::Avx512 if has_avx512_vnni() => {
+            |a, b| unsafe { x86::dot_i8_avx512_vnni(a, b) }
+        }
+        #[cfg(target_arch = "x86_64")]
+        Implementation::Avx2 | Implementation::Avx512 => |a, b| unsafe { x86::dot_i8_avx2(a, b) },
+        #[cfg(target_arch = "aarch64")]
+        Implementation::Neon => |a, b| unsafe { neon::dot_i8_neon(a, b) },
+        _ => dot_i8_scalar,
+    }
+}
+
+/// Dispatched i8 dot product, selecting the best available SIMD backend.
+#[inline]
+pub fn dot_i8(a: &[i8], b: &[i8]) -> i32 {
+    (DISPATCH.get())(a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill_random(buf: &mut [i8], seed: &mut u32) {
+        for slot in buf.iter_mut() {
+            *seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            *slot = (*seed >> 16) as i8;
+        }
+    }
+
+    fn check_all_backends(a: &[i8], b: &[i8], case: &str) {
+        let reference = dot_i8_scalar(a, b);
+
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("avx2") {
+                let got = unsafe { x86::dot_i8_avx2(a, b) };
+                assert_eq!(got, reference, "avx2 [{case}] n={}", a.len());
+            }
+
+            if is_x86_feature_detected!("avx512f")
+                && is_x86_feature_detected!("avx512bw")
+                && is_x86_feature_detected!("avx512vnni")
+            {
+                let got = unsafe { x86::dot_i8_avx512_vnni(a, b) };
+                assert_eq!(got, reference, "avx512_vnni [{case}] n={}", a.len());
+            }
+        }
+
+        #[cfg(target_arch = "aarch64")]
+        {
+            let got = unsafe { neon::dot_i8_neon(a, b) };
+            assert_eq!(got, reference, "neon [{case}] n={}", a.len());
+        }
+
+        assert_eq!(dot_i8(a, b), reference, "dispatch [{case}] n={}", a.len());
+        for implementation in Implementation::supported() {
+            let got = (DISPATCH.get_for(implementation))(a, b);
+            assert_eq!(got, reference, "{implementation} [{case}] n={}", a.len());
+        }
+    }
+
+    #[test]
+    fn random_inputs_all_dimensions_and_offsets() {
+        let mut a = vec![0i8; 1024 + 3];
+        let mut b = vec![0i8; 1024 + 3];
+        let mut seed = 0xC0FFEE_u32;
+        fill_random(&mut a, &mut seed);
+        fill_random(&mut b, &mut seed);
+
+        // Offsets start the slices off any natural alignment of the buffer.
+        for offset in 0..4 {
+            for n in 1..=1024 {
+                check_all_backends(
+                    &a[offset..offset + n],
+                    &b[3 - offset..3 - offset + n],
+                    "random",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn boundary_values() {
+        let mut a = vec![0i8; 1024];
+        let mut b = vec![0i8; 1024];
+
+        for n in 1..=1024 {
+            a[..n].fill(i8::MIN);
+            b[..n].fill(i8::MIN);
+            check_all_backends(&a[..n], &b[..n], "min*min");
+            assert_eq!(dot_i8_scalar(&a[..n], &b[..n]), 16384 * n as i32);
+
+            a[..n].fill(i8::MIN);
+            b[..n].fill(i8::MAX);
+            check_all_backends(&a[..n], &b[..n], "min*max");
+
+            a[..n].fill(i8::MAX);
+            b[..n].fill(0);
+            check_all_backends(&a[..n], &b[..n], "max*0");
+
+            for i in 0..n {
+                a[i] = if i & 1 == 0 { i8::MIN } else { i8::MAX };
+                b[i] = if i & 1 == 0 { i8::MAX } else { i8::MIN };
+            }
+            check_all_backends(&a[..n], &b[..n], "alt min/max");
+        }
+    }
+
+    #[test]
+    fn known_values() {
+        assert_eq!(dot_i8(&[1, -2, 3], &[-4, 5, 6]), -4 - 10 + 18);
+        assert_eq!(dot_i8(&[], &[]), 0);
+    }
+}
@@ -26,7 +26,9 @@
 //! runs on port 0 on Intel. The two instructions execute in parallel,
 //! making the correction effectively free.
 
-use std::sync::OnceLock;
+#[cfg(target_arch = "x86_64")]
+use crate::dispatch::has_avx512_vnni;
+use crate::dispatch::{Dispatch, Implementation};
 
 /// Portable scalar u8 dot product, also used for SIMD tail elements.
 #[inline]
@@ -119,30 +121,24 @@ mod x86 {
 
 type DotU8Fn = fn(&[u8], &[u8]) -> u32;
 
-static DISPATCH: OnceLock<DotU8Fn> = OnceLock::new();
-
-fn select_backend() -> DotU8Fn {
-    #[cfg(target_arch = "x86_64")]
-    {
-        if is_x86_feature_detected!("avx512f")
-            && is_x86_feature_detected!("avx512bw")
-            && is_x86_feature_detected!("avx512vnni")
-        {
-            return |a, b| unsafe { x86::dot_u8_avx512_vnni(a, b) };
-        }
+static DISPATCH: Dispatch<DotU8Fn> = Dispatch::new(select_backend);
 
-        if is_x86_feature_detected!("avx2") {
-            return |a, b| unsafe { x86::dot_u8_avx2(a, b) };
+fn select_backend(implementation: Implementation) -> DotU8Fn {
+    match implementation {
+        #[cfg(target_arch = "x86_64")]
+        Implementation::Avx512 if has_avx512_vnni() => {
+            |a, b| unsafe { x86::dot_u8_avx512_vnni(a, b) }
         }
+        #[cfg(target_arch = "x86_64")]
+        Implementation::Avx2 | Implementation::Avx512 => |a, b| unsafe { x86::dot_u8_avx2(a, b) },
+        _ => dot_u8_scalar,
     }
-
-    dot_u8_scalar
 }
 
 /// Dispatched u8 dot product, selecting the best available SIMD backend.
 #[inline]
 pub fn dot_u8(a: &[u8], b: &[u8]) -> u32 {
-    (DISPATCH.get_or_init(select_backend))(a, b)
+    (DISPATCH.get())(a, b)
 }
 
 #[cfg(test)]
@@ -180,6 +176,10 @@ mod tests {
         }
 
         assert_eq!(dot_u8(a, b), reference, "dispatch [{case}] n={}", a.len());
+        for implementation in Implementation::supported() {
+            let got = (DISPATCH.get_for(implementation))(a, b);
+            assert_eq!(got, reference, "{implementation} [{case}] n={}", a.len());
+        }
     }
 
     #[test]
@@ -5,6 +5,7 @@
 
 use std::sync::Arc;
 
+use crate::dispatch::{Dispatch, Implementation};
 use crate::{Error, Result};
 use arrow_array::cast::AsArray;
 use arrow_array::types::UInt8Type;
@@ -19,6 +20,22 @@ pub trait Hamming {
 /// Hamming distance between two vectors.
 #[inline]
 pub fn hamming(x: &[u8], y: &[u8]) -> f32 {
+    (HAMMING.get())(x, y)
+}
+
+type HammingFn = fn(&[u8], &[u8]) -> f32;
+
+pub(crate) static HAMMING: Dispatch<HammingFn> =
+    Dispatch::new(|implementation| match implementation {
+        // SAFETY: the implementation is only selected on CPUs that support it.
+        #[cfg(target_arch = "x86_64")]
+        Implementation::Avx2 | Implementation::Avx512 => |x, y| unsafe { hamming_avx2(x, y) },
+        _ => hamming_autovec::<64>,
+    });
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn hamming_avx2(x: &[u8], y: &[u8]) -> f32 {
     hamming_autovec::<64>(x, y)
 }
 
@@ -10,19 +10,20 @@ use std::sync::Arc;
 
 use crate::{Error, Result};
 use arrow_array::{
-    Array, FixedSizeListArray, Float32Array,
+    Array, ArrowPrimitiveType, FixedSizeListArray, Float32Array, PrimitiveArray,
     cast::AsArray,
-    types::{Float16Type, Float32Type, Float64Type, Int8Type},
+    types::{Float16Type, Float32Type, Float64Type, Int8Type, UInt8Type},
 };
 use arrow_schema::DataType;
 use half::{bf16, f16};
-use lance_arrow::{ArrowFloatType, FixedSizeListArrayExt, FloatArray};
+use lance_arrow::{ArrowFloatType, FloatArray};
 use lance_core::assume_eq;
-use lance_core::utils::cpu::SIMD_SUPPORT;
 #[cfg(feature = "fp16kernels")]
 use lance_core::utils::cpu::SimdSupport;
 use num_traits::{AsPrimitive, Num};
 
+use crate::dispatch::{Dispatch, Implementation, simd_support};
+
 /// Calculate the L2 distance between two vectors.
 ///
 pub trait L2: Num {
@@ -49,15 +50,42 @@ pub fn l2<T: L2>(from: &[T], to: &[T]) -> f32 {
 /// that throughput for callers in the hot path (e.g. the in-memory HNSW index).
 #[inline]
 pub fn l2_f32(x: &[f32], y: &[f32]) -> f32 {
-    #[cfg(target_arch = "x86_64")]
-    {
-        use lance_core::utils::cpu::SimdSupport;
-        if matches!(*SIMD_SUPPORT, SimdSupport::Avx512 | SimdSupport::Avx512FP16) {
-            // SAFETY: guarded by the runtime AVX-512 detection above.
-            return unsafe { l2_f32_avx512(x, y) };
-        }
-    }
-    l2(x, y)
+    (L2_F32_WIDE.get())(x, y)
+}
+
+type L2Fn<T> = fn(&[T], &[T]) -> f32;
+
+/// Kernels behind [`L2`] for f32. The AVX2 one is the scalar code compiled
+/// for AVX2, so all of them return the same bits.
+pub(crate) static L2_F32: Dispatch<L2Fn<f32>> =
+    Dispatch::new(|implementation| match implementation {
+        // SAFETY: the implementation is only selected on CPUs that support it.
+        #[cfg(target_arch = "x86_64")]
+        Implementation::Avx2 | Implementation::Avx512 => |x, y| unsafe { l2_f32_avx2(x, y) },
+        // 16 = 512 (avx512) / 8 bits / 4 (sizeof(f32))
+        // See https://github.com/lance-format/lance/pull/2450.
+        _ => l2_scalar::<f32, f32, 16>,
+    });
+
+/// Kernels behind [`l2_f32`], which uses FMA on AVX-512.
+pub(crate) static L2_F32_WIDE: Dispatch<L2Fn<f32>> =
+    Dispatch::new(|implementation| match implementation {
+        // SAFETY: the implementation is only selected on CPUs that support it.
+        #[cfg(target_arch = "x86_64")]
+        Implementation::Avx512 => |x, y| unsafe { l2_f32_avx512(x, y) },
+        _ => L2_F32.get_for(implementation),
+    });
+
+pub(crate) static L2_F64: Dispatch<L2Fn<f64>> =
+    Dispatch::new(|implementation| match implementation {
+        Implementation::Scalar => |x, y| l2_scalar::<f64, f64, 8>(x, y) as f32,
+        _ => l2_f64_simd,
+    });
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2,fma")]
+unsafe fn l2_f32_avx2(x: &[f32], y: &[f32]) -> f32 {
+    l2_scalar::<f32, f32, 16>(x, y)
 }
 
 #[cfg(target_arch = "x86_64")]
@@ -142,6 +170,13 @@ impl L2 for u8 {
     }
 }
 
+impl L2 for i8 {
+    #[inline]
+    fn l2(x: &[Self], y: &[Self]) -> f32 {
+        super::l2_i8::l2_i8(x, y) as f32
+    }
+}
+
 #[cfg(feature = "fp16kernels")]
 mod bf16_kernel {
     use half::bf16;
@@ -165,7 +200,7 @@ mod bf16_kernel {
 impl L2 for bf16 {
     #[inline]
     fn l2(x: &[Self], y: &[Self]) -> f32 {
-        match *SIMD_SUPPORT {
+        match simd_support() {
             #[cfg(all(feature = "fp16kernels", target_arch = "aarch64"))]
             SimdSupport::Neon => unsafe {
                 bf16_kernel::l2_bf16_neon(x.as_ptr(), y.as_ptr(), x.len() as u32)
@@ -218,7 +253,7 @@ mod kernel {
 impl L2 for f16 {
     #[inline]
     fn l2(x: &[Self], y: &[Self]) -> f32 {
-        match *SIMD_SUPPORT {
+        match simd_support() {
             #[cfg(all(feature = "fp16kernels", target_arch = "aarch64"))]
             SimdSupport::Neon => unsafe {
                 kernel::l2_f16_neon(x.as_ptr(), y.as_ptr(), x.len() as u32)
@@ -251,16 +286,14 @@ impl L2 for f16 {
 impl L2 for f32 {
     #[inline]
     fn l2(x: &[Self], y: &[Self]) -> f32 {
-        // 16 = 512 (avx512) / 8 bits / 4 (sizeof(f32))
-        // See https://github.com/lance-format/lance/pull/2450.
-        l2_scalar::<Self, Self, 16>(x, y)
+        (L2_F32.get())(x, y)
     }
 }
 
 impl L2 for f64 {
     #[inline]
     fn l2(x: &[Self], y: &[Self]) -> f32 {
-        l2_f64_simd(x, y)
+        (L2_F64.get())(x, y)
     }
 }
 
@@ -464,6 +497,34 @@ where
     )))
 }
 
+/// Integer vectors go straight to the integer kernels instead of being
+/// widened to f32 first.
+fn do_l2_distance_int_arrow_batch<T: ArrowPrimitiveType>(
+    from: &PrimitiveArray<T>,
+    to: &FixedSizeListArray,
+) -> Result<Arc<Float32Array>>
+where
+    T::Native: L2,
+{
+    let dimension = to.value_length() as usize;
+    debug_assert_eq!(from.len(), dimension);
+
+    let to_values = to
+        .values()
+        .as_primitive_opt::<T>()
+        .ok_or(Error::ComputeError(format!(
+            "Cannot downcast to the same type: {} != {}",
+            from.data_type(),
+            to.value_type()
+        )))?;
+    let dists = l2_distance_batch(from.values(), to_values.values(), dimension);
+
+    Ok(Arc::new(Float32Array::new(
+        dists.collect(),
+        to.nulls().cloned(),
+    )))
+}
+
 /// Compute L2 distance between a vector and a batch of vectors.
 ///
 /// Null buffer of `to` is propagated to the returned array.
@@ -484,14 +545,8 @@ pub fn l2_distance_arrow_batch(
         DataType::Float16 => do_l2_distance_arrow_batch::<Float16Type>(from.as_primitive(), to),
         DataType::Float32 => do_l2_distance_arrow_batch::<Float32Type>(from.as_primitive(), to),
         DataType::Float64 => do_l2_distance_arrow_batch::<Float64Type>(from.as_primitive(), to),
-        DataType::Int8 => do_l2_distance_arrow_batch::<Float32Type>(
-            &from
-                .as_primitive::<Int8Type>()
-                .into_iter()
-                .map(|x| x.unwrap() as f32)
-                .collect(),
-            &to.convert_to_floating_point()?,
-        ),
+        DataType::Int8 => do_l2_distance_int_arrow_batch::<Int8Type>(from.as_primitive(), to),
+        DataType::UInt8 => do_l2_distance_int_arrow_batch::<UInt8Type>(from.as_primitive(), to),
         _ => Err(Error::ComputeError(format!(
             "Unsupported data type: {}",
             from.data_type()
@@ -672,6 +727,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_int8_l2_arrow_batch() {
+        let mat = FixedSizeListArray::from_iter_primitive::<Int8Type, _, _>(
+            vec![
+                Some(vec![Some(-128_i8), Some(0), Some(127)]),
+                None,
+                Some(vec![Some(1_i8), Some(-1), Some(2)]),
+            ],
+            3,
+        );
+        let point = arrow_array::Int8Array::from(vec![127_i8, 0, -128]);
+        let distances = l2_distance_arrow_batch(&point, &mat).unwrap();
+
+        assert_eq!(distances.value(0), (2 * 255_u32.pow(2)) as f32);
+        assert!(distances.is_null(1));
+        assert_eq!(distances.value(2), (126 * 126 + 1 + 130 * 130) as f32);
+    }
+
     #[test]
     fn test_uint8_l2_edge_cases() {
         let q = vec![0_u8; 2048];
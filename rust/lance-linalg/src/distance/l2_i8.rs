@@ -0,0 +1,277 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Signed int8 squared L2 distance with runtime-dispatched SIMD backends.
+//!
+//! Computes `Σ(a[i] - b[i])²` for i8 slices, returning a u32 result.
+//! Differences span -255..=255, so every backend widens to i16 before
+//! subtracting and accumulates the squares in 32-bit lanes.
+//!
+//! Backends (selected at runtime, best available wins):
+//!   1. scalar     — portable reference, also used for tails
+//!   2. avx2       — sign-extend i8→i16, subtract, VPMADDWD, 32 elements/iter
+//!   3. avx512vnni — same approach with VPDPWSSD accumulation, 64 elements/iter
+//!   4. neon       — SSUBL widening subtract + SMLAL, 16 elements/iter
+
+#[cfg(target_arch = "x86_64")]
+use crate::dispatch::has_avx512_vnni;
+use crate::dispatch::{Dispatch, Implementation};
+
+/// Portable scalar i8 squared L2 distance, also used for SIMD tail elements.
+#[inline]
+pub fn l2_i8_scalar(a: &[i8], b: &[i8]) -> u32 {
+    debug_assert_eq!(a.len(), b.len());
+    a.iter()
+        .zip(b.iter())
+        .map(|(&x, &y)| (x.abs_diff(y) as u32).pow(2))
+        .sum()
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use std::arch::x86_64::*;
+
+    /// Horizontal sum of all 8 × i32 lanes in a __m256i.
+    #[inline(always)]
+    unsafe fn hsum_epi32_avx2(v: __m256i) -> u32 {
+        let lo128 = _mm256_castsi256_si128(v);
+        let hi128 = _mm256_extracti128_si256(v, 1);
+        let mut sum128 = _mm_add_epi32(lo128, hi128);
+        sum128 = _mm_hadd_epi32(sum128, sum128);
+        sum128 = _mm_hadd_epi32(sum128, sum128);
+        _mm_cvtsi128_si32(sum128) as u32
+    }
+
+    /// AVX2 path: sign-extend to i16, subtract, VPMADDWD to square.
+    /// 32 elements/iter.
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn l2_i8_avx2(a: &[i8], b: &[i8]) -> u32 {
+        debug_assert_eq!(a.len(), b.len());
+        let n = a.len();
+        let mut acc = _mm256_setzero_si256();
+        let mut i = 0usize;
+
+        while i + 32 <= n {
+            let av = _mm256_loadu_si256(a.as_ptr().add(i) as *const __m256i);
+            let bv = _mm256_loadu_si256(b.as_ptr().add(i) as *const __m256i);
+
+            let a_lo = _mm256_cvtepi8_epi16(_mm256_castsi256_si128(av));
+            let a_hi = _mm256_cvtepi8_epi16(_mm256_extracti128_si256(av, 1));
+            let b_lo = _mm256_cvtepi8_epi16(_mm256_castsi256_si128(bv));
+            let b_hi = _mm256_cvtepi8_epi16(_mm256_extracti128_si256(bv, 1));
+            let diff_lo = _mm256_sub_epi16(a_lo, b_lo);
+            let diff_hi = _mm256_sub_epi16(a_hi, b_hi);
+
+            // VPMADDWD squares adjacent i16 pairs and sums into i32.
+            acc = _mm256_add_epi32(acc, _mm256_madd_epi16(diff_lo, diff_lo));
+            acc = _mm256_add_epi32(acc, _mm256_madd_epi16(diff_hi, diff_hi));
+            i += 32;
+        }
+
+        let mut result = hsum_epi32_avx2(acc);
+
+        // Scalar tail
+        while i < n {
+            let d = a[i].abs_diff(b[i]) as u32;
+            result += d * d;
+            i += 1;
+        }
+        result
+    }
+
+    /// AVX-512 VNNI path: widened difference + VPDPWSSD for fused
+    /// square-accumulate. 64 elements/iter.
+    #[target_feature(enable = "avx512f,avx512bw,avx512vnni")]
+    pub unsafe fn l2_i8_avx512_vnni(a: &[i8], b: &[i8]) -> u32 {
+        debug_assert_eq!(a.len(), b.len());
+        let n = a.len();
+        let mut acc = _mm512_setzero_si512();
+        let mut i = 0usize;
+
+        while i + 64 <= n {
+            let av = _mm512_loadu_si512(a.as_ptr().add(i) as *const __m512i);
+            let bv = _mm512_loadu_si512(b.as_ptr().add(i) as *const __m512i);
+
+            let a_lo = _mm512_cvtepi8_epi16(_mm512_castsi512_si256(av));
+            let a_hi = _mm512_cvtepi8_epi16(_mm512_extracti64x4_epi64(av, 1));
+            let b_lo = _mm512_cvtepi8_epi16(_mm512_castsi512_si256(bv));
+            let b_hi = _mm512_cvtepi8_epi16(_mm512_extracti64x4_epi64(bv, 1));
+            let diff_lo = _mm512_sub_epi16(a_lo, b_lo);
+            let diff_hi = _mm512_sub_epi16(a_hi, b_hi);
+
+            acc = _mm512_dpwssd_epi32(acc, diff_lo, diff_lo);
+            acc = _mm512_dpwssd_epi32(acc, diff_hi, diff_hi);
+            i += 64;
+        }
+
+        let mut result = _mm512_reduce_add_epi32(acc) as u32;
+
+        // Scalar tail
+        while i < n {
+            let d = a[i].abs_diff(b[i]) as u32;
+            result += d * d;
+            i += 1;
+        }
+        result
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod neon {
+    use std::arch::aarch64::*;
+
+    /// NEON path: SSUBL widens the difference to i16, SMLAL squares it into
+    /// i32 lanes. 16 elements/iter.
+    #[target_feature(enable = "neon")]
+    pub unsafe fn l2_i8_neon(a: &[i8], b: &[i8]) -> u32 {
+        debug_assert_eq!(a.len(), b.len());
+        let n = a.len();
+        let mut acc = vdupq_n_s32(0);
+        let mut i = 0usize;
+
+        while i + 16 <= n {
+            let av = vld1q_s8(a.as_ptr().add(i));
+            let bv = vld1q_s8(b.as_ptr().add(i));
+            let diff_lo = vsubl_s8(vget_low_s8(av), vget_low_s8(bv));
+            let diff_hi = vsubl_high_s8(av, bv);
+            acc = vmlal_s16(acc, vget_low_s16(diff_lo), vget_low_s16(diff_lo));
+            acc = vmlal_high_s16(acc, diff_lo, diff_lo);
+            acc = vmlal_s16(acc, vget_low_s16(diff_hi), vget_low_s16(diff_hi));
+            acc = vmlal_high_s16(acc, diff_hi, diff_hi);
+            i += 16;
+        }
+
+        let mut result = vaddvq_u32(vreinterpretq_u32_s32(acc));
+        while i < n {
+            let d = a[i].abs_diff(b[i]) as u32;
+            result += d * d;
+            i += 1;
+        }
+        result
+    }
+}
+
+type L2I8Fn = fn(&[i8], &[i8]) -> u32;
+
+static DISPATCH: Dispatch<L2I8Fn> = Dispatch::new(select_backend);
+
+fn select_backend(implementation: Implementation) -> L2I8Fn {
+    match implementation {
+        #[cfg(target_arch = "x86_64")]
+        Implementation::Avx512 if has_avx512_vnni() => {
+            |a, b| unsafe { x86::l2_i8_avx512_vnni(a, b) }
+        }
+        #[cfg(target_arch = "x86_64")]
+        Implementation::Avx2 | Implementation::Avx512 => |a, b| unsafe { x86::l2_i8_avx2(a, b) },
+        #[cfg(target_arch = "aarch64")]
+        Implementation::Neon => |a, b| unsafe { neon::l2_i8_neon(a, b) },
+        _ => l2_i8_scalar,
+    }
+}
+
+/// Dispatched i8 squared L2 distance, selecting the best available SIMD backend.
+#[inline]
+pub fn l2_i8(a: &[i8], b: &[i8]) -> u32 {
+    (DISPATCH.get())(a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill_random(buf: &mut [i8], seed: &mut u32) {
+        for slot in buf.iter_mut() {
+            *seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            *slot = (*seed >> 16) as i8;
+        }
+    }
+
+    fn check_all_backends(a: &[i8], b: &[i8], case: &str) {
+        let reference = l2_i8_scalar(a, b);
+
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("avx2") {
+                let got = unsafe { x86::l2_i8_avx2(a, b) };
+                assert_eq!(got, reference, "avx2 [{case}] n={}", a.len());
+            }
+
+            if is_x86_feature_detected!("avx512f")
+                && is_x86_feature_detected!("avx512bw")
+                && is_x86_feature_detected!("avx512vnni")
+            {
+                let got = unsafe { x86::l2_i8_avx512_vnni(a, b) };
+                assert_eq!(got, reference, "avx512_vnni [{case}] n={}", a.len());
+            }
+        }
+
+        #[cfg(target_arch = "aarch64")]
+        {
+            let got = unsafe { neon::l2_i8_neon(a, b) };
+            assert_eq!(got, reference, "neon [{case}] n={}", a.len());
+        }
+
+        assert_eq!(l2_i8(a, b), reference, "dispatch [{case}] n={}", a.len());
+        for implementation in Implementation::supported() {
+            let got = (DISPATCH.get_for(implementation))(a, b);
+            assert_eq!(got, reference, "{implementation} [{case}] n={}", a.len());
+        }
+    }
+
+    #[test]
+    fn random_inputs_all_dimensions_and_offsets() {
+        let mut a = vec![0i8; 1024 + 3];
+        let mut b = vec![0i8; 1024 + 3];
+        let mut seed = 0xC0FFEE_u32;
+        fill_random(&mut a, &mut seed);
+        fill_random(&mut b, &mut seed);
+
+        // Offsets start the slices off any natural alignment of the buffer.
+        for offset in 0..4 {
+            for n in 1..=1024 {
+                check_all_backends(
+                    &a[offset..offset + n],
+                    &b[3 - offset..3 - offset + n],
+                    "random",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn boundary_values() {
+        let mut a = vec![0i8; 1024];
+        let mut b = vec![0i8; 1024];
+
+        for n in 1..=1024 {
+            // max diff: |127 - (-128)|² × n
+            a[..n].fill(i8::MAX);
+            b[..n].fill(i8::MIN);
+            check_all_backends(&a[..n], &b[..n], "max-min");
+            assert_eq!(l2_i8_scalar(&a[..n], &b[..n]), 65025 * n as u32);
+
+            a[..n].fill(i8::MIN);
+            b[..n].fill(i8::MAX);
+            check_all_backends(&a[..n], &b[..n], "min-max");
+
+            // identical vectors: distance = 0
+            a[..n].fill(i8::MIN);
+            b[..n].fill(i8::MIN);
+            check_all_backends(&a[..n], &b[..n], "min-min");
+            assert_eq!(l2_i8_scalar(&a[..n], &b[..n]), 0);
+
+            for i in 0..n {
+                a[i] = if i & 1 == 0 { i8::MIN } else { i8::MAX };
+                b[i] = if i & 1 == 0 { i8::MAX } else { i8::MIN };
+            }
+            check_all_backends(&a[..n], &b[..n], "alt min/max");
+        }
+    }
+
+    #[test]
+    fn known_values() {
+        // 3² + 1² = 10
+        assert_eq!(l2_i8(&[-10, 20], &[-7, 21]), 10);
+        assert_eq!(l2_i8(&[-128], &[127]), 65025);
+    }
+}
@@ -20,7 +20,9 @@
 //! zero-extended to i16 and squared via VPMADDWD (which also pairwise-
 //! accumulates adjacent products into i32).
 
-use std::sync::OnceLock;
+#[cfg(target_arch = "x86_64")]
+use crate::dispatch::has_avx512_vnni;
+use crate::dispatch::{Dispatch, Implementation};
 
 /// Portable scalar u8 squared L2 distance, also used for SIMD tail elements.
 #[inline]
@@ -128,30 +130,24 @@ mod x86 {
 
 type L2U8Fn = fn(&[u8], &[u8]) -> u32;
 
-static DISPATCH: OnceLock<L2U8Fn> = OnceLock::new();
-
-fn select_backend() -> L2U8Fn {
-    #[cfg(target_arch = "x86_64")]
-    {
-        if is_x86_feature_detected!("avx512f")
-            && is_x86_feature_detected!("avx512bw")
-            && is_x86_feature_detected!("avx512vnni")
-        {
-            return |a, b| unsafe { x86::l2_u8_avx512_vnni(a, b) };
-        }
+static DISPATCH: Dispatch<L2U8Fn> = Dispatch::new(select_backend);
 
-        if is_x86_feature_detected!("avx2") {
-            return |a, b| unsafe { x86::l2_u8_avx2(a, b) };
+fn select_backend(implementation: Implementation) -> L2U8Fn {
+    match implementation {
+        #[cfg(target_arch = "x86_64")]
+        Implementation::Avx512 if has_avx512_vnni() => {
+            |a, b| unsafe { x86::l2_u8_avx512_vnni(a, b) }
         }
+        #[cfg(target_arch = "x86_64")]
+        Implementation::Avx2 | Implementation::Avx512 => |a, b| unsafe { x86::l2_u8_avx2(a, b) },
+        _ => l2_u8_scalar,
     }
-
-    l2_u8_scalar
 }
 
 /// Dispatched u8 squared L2 distance, selecting the best available SIMD backend.
 #[inline]
 pub fn l2_u8(a: &[u8], b: &[u8]) -> u32 {
-    (DISPATCH.get_or_init(select_backend))(a, b)
+    (DISPATCH.get())(a, b)
 }
 
 #[cfg(test)]
@@ -189,6 +185,10 @@ mod tests {
         }
 
         assert_eq!(l2_u8(a, b), reference, "dispatch [{case}] n={}", a.len());
+        for implementation in Implementation::supported() {
+            let got = (DISPATCH.get_for(implementation))(a, b);
+            assert_eq!(got, reference, "{implementation} [{case}] n={}", a.len());
+        }
     }
 
     #[test]
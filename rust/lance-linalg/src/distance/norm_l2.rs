@@ -8,12 +8,12 @@ use arrow_array::cast::AsArray;
 use arrow_array::types::{Float16Type, Float32Type, Float64Type};
 use arrow_schema::DataType;
 use half::{bf16, f16};
-#[allow(unused_imports)]
-use lance_core::utils::cpu::SIMD_SUPPORT;
 #[cfg(feature = "fp16kernels")]
 use lance_core::utils::cpu::SimdSupport;
 use num_traits::{AsPrimitive, Float, Num};
 
+use crate::dispatch::{Dispatch, Implementation, simd_support};
+
 /// L2 normalization
 pub trait Normalize: Num {
     /// L2 Normalization over a Vector.
@@ -47,10 +47,17 @@ impl Normalize for u8 {
     }
 }
 
+impl Normalize for i8 {
+    #[inline]
+    fn norm_l2(vector: &[Self]) -> f32 {
+        norm_l2_impl::<Self, f32, 16>(vector)
+    }
+}
+
 impl Normalize for f16 {
     #[inline]
     fn norm_l2(vector: &[Self]) -> f32 {
-        match *SIMD_SUPPORT {
+        match simd_support() {
             #[cfg(all(feature = "fp16kernels", target_arch = "aarch64"))]
             SimdSupport::Neon => unsafe {
                 kernel::norm_l2_f16_neon(vector.as_ptr(), vector.len() as u32)
@@ -101,7 +108,7 @@ mod bf16_kernel {
 impl Normalize for bf16 {
     #[inline]
     fn norm_l2(vector: &[Self]) -> f32 {
-        match *SIMD_SUPPORT {
+        match simd_support() {
             #[cfg(all(feature = "fp16kernels", target_arch = "aarch64"))]
             SimdSupport::Neon => unsafe {
                 bf16_kernel::norm_l2_bf16_neon(vector.as_ptr(), vector.len() as u32)
@@ -131,17 +138,41 @@ impl Normalize for bf16 {
     }
 }
 
+type NormL2Fn<T> = fn(&[T]) -> f32;
+
+/// The AVX2 kernel is the scalar code compiled for AVX2, so both return the
+/// same bits.
+pub(crate) static NORM_L2_F32: Dispatch<NormL2Fn<f32>> =
+    Dispatch::new(|implementation| match implementation {
+        // SAFETY: the implementation is only selected on CPUs that support it.
+        #[cfg(target_arch = "x86_64")]
+        Implementation::Avx2 | Implementation::Avx512 => |v| unsafe { norm_l2_f32_avx2(v) },
+        _ => norm_l2_impl::<f32, f32, 16>,
+    });
+
+pub(crate) static NORM_L2_F64: Dispatch<NormL2Fn<f64>> =
+    Dispatch::new(|implementation| match implementation {
+        Implementation::Scalar => |v| norm_l2_impl::<f64, f64, 8>(v) as f32,
+        _ => norm_l2_f64_simd,
+    });
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2,fma")]
+unsafe fn norm_l2_f32_avx2(vector: &[f32]) -> f32 {
+    norm_l2_impl::<f32, f32, 16>(vector)
+}
+
 impl Normalize for f32 {
     #[inline]
     fn norm_l2(vector: &[Self]) -> f32 {
-        norm_l2_impl::<Self, Self, 16>(vector)
+        (NORM_L2_F32.get())(vector)
     }
 }
 
 impl Normalize for f64 {
     #[inline]
     fn norm_l2(vector: &[Self]) -> f32 {
-        norm_l2_f64_simd(vector)
+        (NORM_L2_F64.get())(vector)
     }
 }
 
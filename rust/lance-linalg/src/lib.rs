@@ -9,6 +9,7 @@
 use arrow_schema::ArrowError;
 
 mod clustering;
+pub mod dispatch;
 pub mod distance;
 pub mod kernels;
 pub mod simd;
@@ -17,6 +18,7 @@ pub mod simd;
 pub(crate) mod test_utils;
 
 pub use clustering::Clustering;
+pub use dispatch::{Implementation, LanceLinalg};
 
 type Error = ArrowError;
 pub type Result<T> = std::result::Result<T, Error>;
@@ -7,7 +7,9 @@ use std::arch::aarch64::*;
 use std::arch::x86_64::*;
 
 #[allow(unused_imports)]
-use lance_core::utils::cpu::{SIMD_SUPPORT, SimdSupport};
+use lance_core::utils::cpu::SimdSupport;
+
+use crate::dispatch::simd_support;
 
 pub const PERM0: [usize; 16] = [0, 8, 1, 9, 2, 10, 3, 11, 4, 12, 5, 13, 6, 14, 7, 15];
 pub const PERM0_INVERSE: [usize; 16] = [0, 2, 4, 6, 1, 3, 5, 7, 8, 10, 12, 14, 9, 11, 13, 15];
@@ -36,7 +38,7 @@ pub fn sum_4bit_dist_table(
 ) {
     debug_assert!(n.is_multiple_of(BATCH_SIZE));
 
-    match *SIMD_SUPPORT {
+    match simd_support() {
         #[cfg(all(kernel_support = "avx512", target_arch = "x86_64"))]
         SimdSupport::Avx512 | SimdSupport::Avx512FP16 => unsafe {
             for i in (0..n).step_by(BATCH_SIZE) {
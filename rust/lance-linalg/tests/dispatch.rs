@@ -0,0 +1,48 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! The kernel implementation is process-wide, so the override is tested in its
+//! own binary, with a single test.
+
+use lance_linalg::dispatch::IMPLEMENTATION_ENV;
+use lance_linalg::distance::{dot, l2};
+use lance_linalg::{Implementation, LanceLinalg};
+
+fn scalar_dot(x: &[f32], y: &[f32]) -> f32 {
+    let chunks = x.len() / 16 * 16;
+    let mut sums = [0.0_f32; 16];
+    for (i, (a, b)) in x[..chunks].iter().zip(&y[..chunks]).enumerate() {
+        sums[i % 16] += a * b;
+    }
+    let tail = x[chunks..]
+        .iter()
+        .zip(&y[chunks..])
+        .map(|(a, b)| a * b)
+        .sum::<f32>();
+    tail + sums.iter().sum::<f32>()
+}
+
+#[test]
+fn test_override_implementation() {
+    // SAFETY: this is the only test in the binary, and no kernel has run yet.
+    unsafe { std::env::set_var(IMPLEMENTATION_ENV, "Scalar") };
+    assert_eq!(LanceLinalg::implementation(), Implementation::Scalar);
+
+    let x = (0..1000).map(|i| (i as f32).sin()).collect::<Vec<_>>();
+    let y = (0..1000).map(|i| (i as f32).cos()).collect::<Vec<_>>();
+    assert_eq!(dot(&x, &y).to_bits(), scalar_dot(&x, &y).to_bits());
+
+    let detected = Implementation::detect();
+    LanceLinalg::force_implementation(detected).unwrap();
+    assert_eq!(LanceLinalg::implementation(), detected);
+    assert!(l2(&x, &y) > 0.0);
+
+    // Resetting goes back to the environment variable.
+    LanceLinalg::reset_implementation();
+    assert_eq!(LanceLinalg::implementation(), Implementation::Scalar);
+
+    // SAFETY: see above.
+    unsafe { std::env::set_var(IMPLEMENTATION_ENV, "sve") };
+    LanceLinalg::reset_implementation();
+    assert_eq!(LanceLinalg::implementation(), detected);
+}
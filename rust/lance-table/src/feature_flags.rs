@@ -3,8 +3,11 @@
 
 //! Feature flags
 
-use crate::format::Manifest;
+use crate::format::{Manifest, pb};
+use deepsize::DeepSizeOf;
+use lance_core::utils::encryption::ENCRYPTION_KEY_ID_META_KEY;
 use lance_core::{Error, Result};
+use lance_file::version::LanceFileVersion;
 
 /// Fragments may contain deletion files, which record the tombstones of
 /// soft-deleted rows.
@@ -20,8 +23,170 @@ pub const FLAG_TABLE_CONFIG: u64 = 8;
 pub const FLAG_BASE_PATHS: u64 = 16;
 /// Disable writing transaction file under _transaction/, this flag is set when we only want to write inline transaction in manifest
 pub const FLAG_DISABLE_TRANSACTION_FILE: u64 = 32;
+/// Some data files are referenced by absolute URIs, possibly on other object stores
+pub const FLAG_ABSOLUTE_DATA_FILE_URIS: u64 = 64;
 /// The first bit that is unknown as a feature flag
-pub const FLAG_UNKNOWN: u64 = 64;
+pub const FLAG_UNKNOWN: u64 = 128;
+
+/// The reader feature level of this library. Datasets that require a feature
+/// of a higher level can't be read.
+pub const READER_FEATURE_LEVEL: u32 = 6;
+
+/// The features readers may need to support, in the order they were added:
+/// the name, the reader feature level and the first library version that can
+/// read datasets using the feature.
+pub const KNOWN_READER_FEATURES: &[(&str, u32, &str)] = &[
+    ("data_storage_v2.0", 1, "0.16.0"),
+    ("deletion_files", 1, "0.16.0"),
+    ("stable_row_ids", 1, "0.16.0"),
+    ("blob_columns", 2, "0.20.0"),
+    ("data_storage_v2.1", 3, "0.38.1"),
+    ("base_paths", 4, "1.0.0"),
+    ("data_storage_v2.2", 5, "4.0.0-beta.1"),
+    ("blob_v2_columns", 5, "4.0.0-beta.1"),
+    ("data_storage_v2.3", 6, "8.0.0"),
+    ("absolute_data_file_uris", 6, "8.0.0"),
+    ("encrypted_columns", 6, "8.0.0"),
+];
+
+/// A feature of the table format that readers must support to read a dataset.
+///
+/// The features a dataset uses are stored in its manifest, so readers that
+/// don't support one can name it instead of failing to decode the data.
+#[derive(Debug, Clone, PartialEq, Eq, DeepSizeOf)]
+pub struct ReaderFeature {
+    /// The name of the feature, e.g. `stable_row_ids`.
+    pub name: String,
+    /// The reader feature level that introduced the feature.
+    pub level: u32,
+    /// The first library version that can read datasets using the feature.
+    pub min_version: String,
+}
+
+impl ReaderFeature {
+    /// Look up a feature known to this library by name.
+    pub fn known(name: &str) -> Option<Self> {
+        KNOWN_READER_FEATURES
+            .iter()
+            .find(|(known, _, _)| *known == name)
+            .map(|(name, level, min_version)| Self {
+                name: name.to_string(),
+                level: *level,
+                min_version: min_version.to_string(),
+            })
+    }
+}
+
+impl From<pb::ReaderFeature> for ReaderFeature {
+    fn from(p: pb::ReaderFeature) -> Self {
+        Self {
+            name: p.name,
+            level: p.level,
+            min_version: p.min_version,
+        }
+    }
+}
+
+impl From<&ReaderFeature> for pb::ReaderFeature {
+    fn from(feature: &ReaderFeature) -> Self {
+        Self {
+            name: feature.name.clone(),
+            level: feature.level,
+            min_version: feature.min_version.clone(),
+        }
+    }
+}
+
+/// The reader features used by the dataset described by the manifest, ordered
+/// by level. The reader feature flags must already be set.
+pub fn reader_features(manifest: &Manifest) -> Result<Vec<ReaderFeature>> {
+    let mut names = Vec::new();
+    match manifest.data_storage_format.lance_file_version()?.resolve() {
+        LanceFileVersion::V2_0 => names.push("data_storage_v2.0"),
+        LanceFileVersion::V2_1 => names.push("data_storage_v2.1"),
+        LanceFileVersion::V2_2 => names.push("data_storage_v2.2"),
+        LanceFileVersion::V2_3 => names.push("data_storage_v2.3"),
+        _ => {}
+    }
+
+    let flags = manifest.reader_feature_flags;
+    if flags & FLAG_DELETION_FILES != 0 {
+        names.push("deletion_files");
+    }
+    if flags & FLAG_STABLE_ROW_IDS != 0 {
+        names.push("stable_row_ids");
+    }
+    if flags & FLAG_BASE_PATHS != 0 {
+        names.push("base_paths");
+    }
+    if flags & FLAG_ABSOLUTE_DATA_FILE_URIS != 0 {
+        names.push("absolute_data_file_uris");
+    }
+
+    let fields = manifest.schema.fields_pre_order().collect::<Vec<_>>();
+    if fields.iter().any(|f| f.is_blob() && !f.is_blob_v2()) {
+        names.push("blob_columns");
+    }
+    if fields.iter().any(|f| f.is_blob_v2()) {
+        names.push("blob_v2_columns");
+    }
+    if manifest
+        .schema
+        .fields
+        .iter()
+        .any(|f| f.metadata.contains_key(ENCRYPTION_KEY_ID_META_KEY))
+    {
+        names.push("encrypted_columns");
+    }
+
+    let mut features = names
+        .into_iter()
+        .filter_map(ReaderFeature::known)
+        .collect::<Vec<_>>();
+    features.sort_by_key(|feature| feature.level);
+    Ok(features)
+}
+
+/// Check that a reader of the given feature level can read the dataset
+/// described by the manifest.
+pub fn check_reader_features(manifest: &Manifest, reader_level: u32) -> Result<()> {
+    let unsupported = manifest.reader_features.iter().find(|feature| {
+        feature.level > reader_level || ReaderFeature::known(&feature.name).is_none()
+    });
+    if let Some(feature) = unsupported {
+        return Err(Error::not_supported(format!(
+            "Dataset requires feature `{}` (min version {}), which this reader does not support. \
+             Please upgrade Lance to read this dataset.",
+            feature.name, feature.min_version
+        )));
+    }
+
+    if !can_read_dataset(manifest.reader_feature_flags) {
+        return Err(Error::not_supported(format!(
+            "This dataset cannot be read by this version of Lance. \
+             Please upgrade Lance to read this dataset.\n Flags: {}",
+            manifest.reader_feature_flags
+        )));
+    }
+    Ok(())
+}
+
+/// Check that readers of `max_level` can read the dataset described by the
+/// manifest, to refuse writing features they don't support.
+pub fn check_max_reader_feature_level(manifest: &Manifest, max_level: u32) -> Result<()> {
+    match manifest
+        .reader_features
+        .iter()
+        .find(|feature| feature.level > max_level)
+    {
+        Some(feature) => Err(Error::not_supported(format!(
+            "The write requires reader feature `{}` (level {}, min version {}), \
+             above the max reader feature level {max_level}",
+            feature.name, feature.level, feature.min_version
+        ))),
+        None => Ok(()),
+    }
+}
 
 /// Set the reader and writer feature flags in the manifest based on the contents of the manifest.
 pub fn apply_feature_flags(
@@ -74,6 +239,20 @@ pub fn apply_feature_flags(
     if disable_transaction_file {
         manifest.writer_feature_flags |= FLAG_DISABLE_TRANSACTION_FILE;
     }
+
+    // Readers that only resolve paths against the dataset's bases would look for
+    // these files in the wrong place
+    let has_absolute_uris = manifest
+        .fragments
+        .iter()
+        .flat_map(|frag| frag.files.iter())
+        .any(|file| file.has_absolute_uri());
+    if has_absolute_uris {
+        manifest.reader_feature_flags |= FLAG_ABSOLUTE_DATA_FILE_URIS;
+        manifest.writer_feature_flags |= FLAG_ABSOLUTE_DATA_FILE_URIS;
+    }
+
+    manifest.reader_features = reader_features(manifest)?;
     Ok(())
 }
 
@@ -103,6 +282,7 @@ mod tests {
         assert!(can_read_dataset(super::FLAG_TABLE_CONFIG));
         assert!(can_read_dataset(super::FLAG_BASE_PATHS));
         assert!(can_read_dataset(super::FLAG_DISABLE_TRANSACTION_FILE));
+        assert!(can_read_dataset(super::FLAG_ABSOLUTE_DATA_FILE_URIS));
         assert!(can_read_dataset(
             super::FLAG_DELETION_FILES
                 | super::FLAG_STABLE_ROW_IDS
@@ -120,6 +300,7 @@ mod tests {
         assert!(can_write_dataset(super::FLAG_TABLE_CONFIG));
         assert!(can_write_dataset(super::FLAG_BASE_PATHS));
         assert!(can_write_dataset(super::FLAG_DISABLE_TRANSACTION_FILE));
+        assert!(can_write_dataset(super::FLAG_ABSOLUTE_DATA_FILE_URIS));
         assert!(can_write_dataset(
             super::FLAG_DELETION_FILES
                 | super::FLAG_STABLE_ROW_IDS
@@ -130,6 +311,65 @@ mod tests {
         assert!(!can_write_dataset(super::FLAG_UNKNOWN));
     }
 
+    #[test]
+    fn test_reader_features() {
+        use crate::format::{DataStorageFormat, Manifest};
+        use arrow_schema::{Field as ArrowField, Schema as ArrowSchema};
+        use lance_core::datatypes::Schema;
+        use std::collections::HashMap;
+        use std::sync::Arc;
+
+        assert!(
+            KNOWN_READER_FEATURES
+                .windows(2)
+                .all(|pair| pair[0].1 <= pair[1].1)
+        );
+        assert_eq!(
+            KNOWN_READER_FEATURES.last().unwrap().1,
+            READER_FEATURE_LEVEL
+        );
+
+        let arrow_schema = ArrowSchema::new(vec![ArrowField::new(
+            "test_field",
+            arrow_schema::DataType::Int64,
+            false,
+        )]);
+        let mut manifest = Manifest::new(
+            Schema::try_from(&arrow_schema).unwrap(),
+            Arc::new(vec![]),
+            DataStorageFormat::new(LanceFileVersion::V2_1),
+            HashMap::from([(
+                1,
+                BasePath::new(1, "file:///path/to/original".to_string(), None, true),
+            )]),
+        );
+        apply_feature_flags(&mut manifest, false, false).unwrap();
+        let names = manifest
+            .reader_features
+            .iter()
+            .map(|feature| feature.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["data_storage_v2.1", "base_paths"]);
+
+        check_reader_features(&manifest, READER_FEATURE_LEVEL).unwrap();
+        check_reader_features(&manifest, 4).unwrap();
+        let err = check_reader_features(&manifest, 3).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Dataset requires feature `base_paths` (min version 1.0.0)"),
+            "{err}"
+        );
+        check_max_reader_feature_level(&manifest, 4).unwrap();
+        assert!(check_max_reader_feature_level(&manifest, 3).is_err());
+
+        manifest.reader_features.push(ReaderFeature {
+            name: "unknown".to_string(),
+            level: 1,
+            min_version: "99.0.0".to_string(),
+        });
+        assert!(check_reader_features(&manifest, READER_FEATURE_LEVEL).is_err());
+    }
+
     #[test]
     fn test_base_paths_feature_flags() {
         use crate::format::{DataStorageFormat, Manifest};
@@ -183,6 +183,14 @@ impl DataFile {
         self.file_major_version == 0 && self.file_minor_version < 3
     }
 
+    /// Whether the path is an absolute URI, e.g. `s3://bucket/data/a.lance`, rather
+    /// than a path relative to the data directory of the file's base.
+    ///
+    /// Such files may live on a different object store than the dataset.
+    pub fn has_absolute_uri(&self) -> bool {
+        self.path.contains("://")
+    }
+
     pub fn validate(&self, base_path: &Path) -> Result<()> {
         if self.is_legacy_file() {
             if !self.fields.windows(2).all(|w| w[0] < w[1]) {
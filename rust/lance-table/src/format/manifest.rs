@@ -16,7 +16,7 @@ use std::ops::Range;
 use std::sync::Arc;
 
 use super::Fragment;
-use crate::feature_flags::{FLAG_STABLE_ROW_IDS, has_deprecated_v2_feature_flag};
+use crate::feature_flags::{FLAG_STABLE_ROW_IDS, ReaderFeature, has_deprecated_v2_feature_flag};
 use crate::format::fragment::DataFileFieldInterner;
 use crate::format::pb;
 use lance_core::cache::LanceCache;
@@ -69,6 +69,10 @@ pub struct Manifest {
     /// The writer flags
     pub writer_feature_flags: u64,
 
+    /// The features readers must support to read the dataset, see
+    /// [`reader_features`](crate::feature_flags::reader_features).
+    pub reader_features: Vec<ReaderFeature>,
+
     /// The max fragment id used so far
     /// None means never set, Some(0) means max ID used so far is 0
     pub max_fragment_id: Option<u32>,
@@ -187,6 +191,7 @@ impl Manifest {
             tag: None,
             reader_feature_flags: 0,
             writer_feature_flags: 0,
+            reader_features: Vec::new(),
             max_fragment_id: None,
             transaction_file: None,
             transaction_section: None,
@@ -218,6 +223,7 @@ impl Manifest {
             tag: None,
             reader_feature_flags: 0, // These will be set on commit
             writer_feature_flags: 0, // These will be set on commit
+            reader_features: Vec::new(),
             max_fragment_id: previous.max_fragment_id,
             transaction_file: None,
             transaction_section: None,
@@ -275,6 +281,7 @@ impl Manifest {
             tag: None,
             reader_feature_flags: 0, // These will be set on commit
             writer_feature_flags: 0, // These will be set on commit
+            reader_features: Vec::new(),
             max_fragment_id: self.max_fragment_id,
             transaction_file: Some(transaction_file),
             transaction_section: None,
@@ -913,6 +920,11 @@ impl TryFrom<pb::Manifest> for Manifest {
             tag: if p.tag.is_empty() { None } else { Some(p.tag) },
             reader_feature_flags: p.reader_feature_flags,
             writer_feature_flags: p.writer_feature_flags,
+            reader_features: p
+                .reader_features
+                .into_iter()
+                .map(ReaderFeature::from)
+                .collect(),
             max_fragment_id: p.max_fragment_id,
             fragments,
             transaction_file: if p.transaction_file.is_empty() {
@@ -975,6 +987,11 @@ impl From<&Manifest> for pb::Manifest {
             tag: m.tag.clone().unwrap_or_default(),
             reader_feature_flags: m.reader_feature_flags,
             writer_feature_flags: m.writer_feature_flags,
+            reader_features: m
+                .reader_features
+                .iter()
+                .map(pb::ReaderFeature::from)
+                .collect(),
             max_fragment_id: m.max_fragment_id,
             transaction_file: m.transaction_file.clone().unwrap_or_default(),
             next_row_id: m.next_row_id,
@@ -196,6 +196,87 @@ pub async fn migrate_scheme_to_v2(object_store: &ObjectStore, dataset_base: &Pat
     Ok(())
 }
 
+/// Rename every manifest under `dataset_base` to the `target` naming scheme.
+///
+/// Unlike [`migrate_scheme_to_v2`] this works in either direction, and it is
+/// meant to repair a versions directory holding both schemes, e.g. one that
+/// was copied between object stores. Each manifest is first copied to its new
+/// name, then every copy is checked against the size of its original, and only
+/// then are the originals deleted. A migration that is interrupted never loses
+/// a version, and running it again finishes it.
+///
+/// It should not be run while other operations write to the dataset.
+pub async fn migrate_manifest_naming_scheme(
+    object_store: &ObjectStore,
+    dataset_base: &Path,
+    target: ManifestNamingScheme,
+) -> Result<()> {
+    let migrations = object_store
+        .list(Some(dataset_base.clone().join(VERSIONS_DIR)))
+        .try_filter_map(|meta| {
+            // Detached versions don't parse, they always use the V2 scheme.
+            let migration = meta.location.filename().and_then(|filename| {
+                let scheme = ManifestNamingScheme::detect_scheme(filename)?;
+                let version = scheme.parse_version(filename)?;
+                (scheme != target).then(|| target.manifest_path(dataset_base, version))
+            });
+            future::ready(Ok(migration.map(|target_path| (meta, target_path))))
+        })
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    futures::stream::iter(&migrations)
+        .map(|(meta, target_path)| object_store.copy(&meta.location, target_path))
+        .buffer_unordered(object_store.io_parallelism())
+        .try_collect::<Vec<_>>()
+        .await?;
+    futures::stream::iter(&migrations)
+        .map(|(meta, target_path)| async move {
+            let size = object_store.size(target_path).await?;
+            if size != meta.size {
+                return Err(Error::corrupt_file(
+                    target_path.clone(),
+                    format!(
+                        "Copy of manifest {} has {} bytes, expected {}",
+                        meta.location, size, meta.size
+                    ),
+                ));
+            }
+            Ok(())
+        })
+        .buffer_unordered(object_store.io_parallelism())
+        .try_collect::<Vec<_>>()
+        .await?;
+    object_store
+        .remove_stream(
+            futures::stream::iter(migrations)
+                .map(|(meta, _)| Ok(meta.location))
+                .boxed(),
+        )
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    Ok(())
+}
+
+/// The error for a versions directory holding manifests of both naming schemes.
+///
+/// The latest version of such a directory can't be resolved reliably, so the
+/// dataset fails to open rather than pick a version from one of the schemes.
+fn mixed_naming_schemes_err(
+    base: &Path,
+    first: ManifestNamingScheme,
+    second: ManifestNamingScheme,
+) -> Error {
+    Error::corrupt_file(
+        base.clone().join(VERSIONS_DIR),
+        format!(
+            "Found manifests with both the {first:?} and {second:?} naming schemes. \
+             Use `Dataset::migrate_manifest_paths` to rename them to a single scheme."
+        ),
+    )
+}
+
 /// Function that writes the manifest to the object store.
 ///
 /// Returns the size of the written manifest.
@@ -276,12 +357,16 @@ impl TryFrom<object_store::ObjectMeta> for ManifestLocation {
 ///   listing on these stores is O(n) in the number of versions.
 /// - Lexicographically ordered stores (e.g. S3 Standard, GCS): the listing
 ///   already resolves the latest version in roughly one request.
+///
+/// Directory reads and listings honor the `manifest_discovery_prefix` storage
+/// option, see [`ObjectStore::manifest_discovery_base`].
 async fn current_manifest_path(
     object_store: &ObjectStore,
     base: &Path,
 ) -> Result<ManifestLocation> {
+    let discovery_base = object_store.manifest_discovery_base(base);
     if object_store.is_local() {
-        if let Ok(Some(location)) = current_manifest_local(base) {
+        if let Ok(Some(location)) = current_manifest_local(&discovery_base) {
             return Ok(location);
         }
     } else if uses_version_hint(object_store)
@@ -290,7 +375,7 @@ async fn current_manifest_path(
         return Ok(location);
     }
 
-    resolve_version_from_listing(object_store, base).await
+    resolve_version_from_listing(object_store, &discovery_base).await
 }
 
 /// JSON body of the version hint file: `{"version":N}`.
@@ -548,12 +633,19 @@ async fn list_manifests_since_version_with_hint(
     Some(locations)
 }
 
+/// How many manifests after the latest one are checked to be V2 and in
+/// descending order when the latest is resolved from a lexically ordered list.
+const LEXICAL_ORDER_CHECK_COUNT: usize = 9;
+
 /// Resolve the latest manifest by listing the versions directory.
+///
+/// On lexically ordered stores with V2 manifests the latest one is listed
+/// first, so only the first few keys are read. Otherwise every manifest is.
 async fn resolve_version_from_listing(
     object_store: &ObjectStore,
     base: &Path,
 ) -> Result<ManifestLocation> {
-    let manifest_files = object_store.list(Some(base.clone().join(VERSIONS_DIR)));
+    let manifest_files = object_store.list_from(Some(base.clone().join(VERSIONS_DIR)), None);
 
     let mut valid_manifests = manifest_files.try_filter_map(|res| {
         let filename = res.location.filename().unwrap();
@@ -578,16 +670,20 @@ async fn resolve_version_from_listing(
                 .parse_version(meta.location.filename().unwrap())
                 .unwrap();
 
-            // Sanity check: verify at least for the first 1k files that they are all V2
-            // and that the version numbers are decreasing. We use the first 1k because
-            // this is the typical size of an object store list endpoint response page.
-            for (scheme, meta) in valid_manifests.take(999).try_collect::<Vec<_>>().await? {
+            // Sanity check: verify for the next few files that they are all V2 and
+            // that the version numbers are decreasing. Only a few are checked so the
+            // listing can stop early on old datasets with many versions.
+            for (scheme, meta) in valid_manifests
+                .take(LEXICAL_ORDER_CHECK_COUNT)
+                .try_collect::<Vec<_>>()
+                .await?
+            {
                 if scheme != ManifestNamingScheme::V2 {
-                    warn!(
-                        "Found V1 Manifest in a V2 directory. Use `migrate_manifest_paths_v2` \
-                         to migrate the directory."
-                    );
-                    break;
+                    return Err(mixed_naming_schemes_err(
+                        base,
+                        ManifestNamingScheme::V2,
+                        scheme,
+                    ));
                 }
                 let next_version = scheme
                     .parse_version(meta.location.filename().unwrap())
@@ -621,11 +717,7 @@ async fn resolve_version_from_listing(
 
             while let Some((entry_scheme, meta)) = valid_manifests.next().await.transpose()? {
                 if entry_scheme != scheme {
-                    return Err(Error::internal(format!(
-                        "Found multiple manifest naming schemes in the same directory: {:?} and {:?}. \
-                         Use `migrate_manifest_paths_v2` to migrate the directory.",
-                        scheme, entry_scheme
-                    )));
+                    return Err(mixed_naming_schemes_err(base, scheme, entry_scheme));
                 }
                 let version = entry_scheme
                     .parse_version(meta.location.filename().unwrap())
@@ -806,7 +898,8 @@ pub trait CommitHandler: Debug + Send + Sync {
         base_path: &Path,
         object_store: &'a ObjectStore,
     ) -> BoxStream<'a, Result<ManifestLocation>> {
-        list_detached_manifests(base_path, &object_store.inner).boxed()
+        let base_path = object_store.manifest_discovery_base(base_path);
+        list_detached_manifests(&base_path, &object_store.inner).boxed()
     }
 
     /// If `sorted_descending` is `true`, the stream will yield manifests in descending
@@ -821,7 +914,8 @@ pub trait CommitHandler: Debug + Send + Sync {
         object_store: &'a ObjectStore,
         sorted_descending: bool,
     ) -> BoxStream<'a, Result<ManifestLocation>> {
-        let underlying_stream = list_manifests(base_path, &object_store.inner);
+        let base_path = object_store.manifest_discovery_base(base_path);
+        let underlying_stream = list_manifests(&base_path, &object_store.inner);
 
         if !sorted_descending {
             return underlying_stream.boxed();
@@ -901,7 +995,8 @@ pub trait CommitHandler: Debug + Send + Sync {
             {
                 Some(locations) => locations,
                 None => {
-                    let mut locations = list_manifests(&base_path, &object_store.inner)
+                    let discovery_base = object_store.manifest_discovery_base(&base_path);
+                    let mut locations = list_manifests(&discovery_base, &object_store.inner)
                         .try_collect::<Vec<_>>()
                         .await?;
                     locations.retain(|loc| loc.version > since_version);
@@ -1467,6 +1562,23 @@ impl CommitHandler for ConditionalPutCommitHandler {
         naming_scheme: ManifestNamingScheme,
         transaction: Option<Transaction>,
     ) -> std::result::Result<ManifestLocation, CommitError> {
+        // S3-compatible stores that were probed and ignore the put mode would
+        // let a conflicting commit overwrite the manifest, as would an unsafe
+        // commit, which at least warns about it.
+        if object_store.capabilities().conditional_put == Some(false) {
+            return UnsafeCommitHandler
+                .commit(
+                    manifest,
+                    indices,
+                    base_path,
+                    object_store,
+                    manifest_writer,
+                    naming_scheme,
+                    transaction,
+                )
+                .await;
+        }
+
         let path = naming_scheme.manifest_path(base_path, manifest.version);
 
         let memory_store = ObjectStore::memory();
@@ -1682,6 +1794,166 @@ mod tests {
         assert_eq!(location.path, naming_scheme.manifest_path(&base, 11));
     }
 
+    /// Counts the objects listed from the store it wraps.
+    #[derive(Debug)]
+    struct CountingListStore {
+        inner: object_store::memory::InMemory,
+        listed: Arc<AtomicUsize>,
+    }
+
+    impl std::fmt::Display for CountingListStore {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "CountingListStore")
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl OSObjectStore for CountingListStore {
+        async fn put_opts(
+            &self,
+            location: &Path,
+            payload: object_store::PutPayload,
+            opts: PutOptions,
+        ) -> object_store::Result<object_store::PutResult> {
+            self.inner.put_opts(location, payload, opts).await
+        }
+
+        async fn put_multipart_opts(
+            &self,
+            location: &Path,
+            opts: object_store::PutMultipartOptions,
+        ) -> object_store::Result<Box<dyn object_store::MultipartUpload>> {
+            self.inner.put_multipart_opts(location, opts).await
+        }
+
+        async fn get_opts(
+            &self,
+            location: &Path,
+            options: object_store::GetOptions,
+        ) -> object_store::Result<object_store::GetResult> {
+            self.inner.get_opts(location, options).await
+        }
+
+        fn delete_stream(
+            &self,
+            locations: BoxStream<'static, object_store::Result<Path>>,
+        ) -> BoxStream<'static, object_store::Result<Path>> {
+            self.inner.delete_stream(locations)
+        }
+
+        fn list(
+            &self,
+            prefix: Option<&Path>,
+        ) -> BoxStream<'static, object_store::Result<object_store::ObjectMeta>> {
+            let listed = self.listed.clone();
+            self.inner
+                .list(prefix)
+                .inspect(move |_| {
+                    listed.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                })
+                .boxed()
+        }
+
+        async fn list_with_delimiter(
+            &self,
+            prefix: Option<&Path>,
+        ) -> object_store::Result<object_store::ListResult> {
+            self.inner.list_with_delimiter(prefix).await
+        }
+
+        async fn copy_opts(
+            &self,
+            from: &Path,
+            to: &Path,
+            opts: object_store::CopyOptions,
+        ) -> object_store::Result<()> {
+            self.inner.copy_opts(from, to, opts).await
+        }
+    }
+
+    #[tokio::test]
+    #[rstest::rstest]
+    async fn test_current_manifest_path_many_versions(
+        #[values(true, false)] lexical_list_store: bool,
+    ) {
+        let listed = Arc::new(AtomicUsize::new(0));
+        let mut object_store = ObjectStore::memory();
+        object_store.inner = Arc::new(CountingListStore {
+            inner: object_store::memory::InMemory::new(),
+            listed: listed.clone(),
+        });
+        object_store.list_is_lexically_ordered = lexical_list_store;
+        let base = Path::from("base");
+
+        let scheme = ManifestNamingScheme::V2;
+        for version in 0..10_000 {
+            let path = scheme.manifest_path(&base, version);
+            object_store.put(&path, b"".as_slice()).await.unwrap();
+        }
+
+        let location = current_manifest_path(&object_store, &base).await.unwrap();
+        assert_eq!(location.version, 9_999);
+        assert_eq!(location.path, scheme.manifest_path(&base, 9_999));
+
+        // With descending V2 names the latest version is listed first, but
+        // only if the store lists in lexical order.
+        let listed = listed.load(std::sync::atomic::Ordering::SeqCst);
+        if lexical_list_store {
+            assert!(listed <= LEXICAL_ORDER_CHECK_COUNT + 1, "listed {listed}");
+        } else {
+            assert_eq!(listed, 10_000);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_manifest_discovery_prefix() {
+        use std::collections::HashMap;
+
+        use lance_io::object_store::{
+            MANIFEST_DISCOVERY_PREFIX_KEY, ObjectStoreRegistry, StorageOptionsAccessor,
+        };
+
+        let params = ObjectStoreParams {
+            storage_options_accessor: Some(Arc::new(StorageOptionsAccessor::with_static_options(
+                HashMap::from([(
+                    MANIFEST_DISCOVERY_PREFIX_KEY.to_string(),
+                    "_data/".to_string(),
+                )]),
+            ))),
+            ..Default::default()
+        };
+        let (object_store, base) = ObjectStore::from_uri_and_params(
+            Arc::new(ObjectStoreRegistry::default()),
+            "memory://bucket/base",
+            &params,
+        )
+        .await
+        .unwrap();
+        let data_base = base.clone().join("_data");
+        assert_eq!(object_store.manifest_discovery_base(&base), data_base);
+
+        // Manifests at the dataset root are out of scope and must not be found.
+        let scheme = ManifestNamingScheme::V2;
+        for version in [1, 2, 3] {
+            let path = scheme.manifest_path(&data_base, version);
+            object_store.put(&path, b"".as_slice()).await.unwrap();
+        }
+        let decoy = scheme.manifest_path(&base, 10);
+        object_store.put(&decoy, b"".as_slice()).await.unwrap();
+
+        let location = current_manifest_path(&object_store, &base).await.unwrap();
+        assert_eq!(location.version, 3);
+        assert_eq!(location.path, scheme.manifest_path(&data_base, 3));
+
+        let versions = ConditionalPutCommitHandler
+            .list_manifest_locations(&base, &object_store, true)
+            .map_ok(|location| location.version)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(versions, vec![3, 2, 1]);
+    }
+
     /// A memory store that reports `list_is_lexically_ordered == false`, like
     /// S3 Express, so the version-hint paths are exercised.
     fn non_lexical_memory_store() -> Box<ObjectStore> {
@@ -1945,6 +2217,68 @@ mod tests {
         assert_eq!(found_versions, expected_versions);
     }
 
+    #[tokio::test]
+    #[rstest::rstest]
+    async fn test_migrate_mixed_manifest_naming(
+        #[values(true, false)] lexical_list_store: bool,
+        #[values(ManifestNamingScheme::V1, ManifestNamingScheme::V2)] target: ManifestNamingScheme,
+    ) {
+        let mut object_store = ObjectStore::memory();
+        object_store.list_is_lexically_ordered = lexical_list_store;
+        let base = Path::from("base");
+
+        // The V2 manifests are listed first on lexical stores, followed by the
+        // newer V1 manifests.
+        for version in 0..9 {
+            let scheme = if version < 6 {
+                ManifestNamingScheme::V2
+            } else {
+                ManifestNamingScheme::V1
+            };
+            let path = scheme.manifest_path(&base, version);
+            object_store
+                .put(&path, version.to_string().as_bytes())
+                .await
+                .unwrap();
+        }
+
+        let err = current_manifest_path(&object_store, &base)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::CorruptFile { .. }), "{err:?}");
+        assert!(err.to_string().contains("migrate_manifest_paths"), "{err}");
+
+        // Migrating again is a no-op.
+        for _ in 0..2 {
+            migrate_manifest_naming_scheme(&object_store, &base, target)
+                .await
+                .unwrap();
+
+            let mut manifests = object_store
+                .read_dir_all(&base.clone().join(VERSIONS_DIR), None)
+                .map_ok(|meta| meta.location)
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap();
+            manifests.sort();
+            let mut expected = (0..9)
+                .map(|version| target.manifest_path(&base, version))
+                .collect::<Vec<_>>();
+            expected.sort();
+            assert_eq!(manifests, expected);
+            for version in 0..9 {
+                let path = target.manifest_path(&base, version);
+                let content = object_store.read_one_all(&path).await.unwrap();
+                assert_eq!(content, version.to_string().as_bytes());
+            }
+
+            let location = current_manifest_path(&object_store, &base).await.unwrap();
+            assert_eq!(location.version, 8);
+            assert_eq!(location.naming_scheme, target);
+            assert_eq!(location.path, target.manifest_path(&base, 8));
+        }
+    }
+
     #[tokio::test]
     async fn test_commit_handler_from_url_memory_schemes() {
         // Both `memory://` and `shared-memory://` must route to
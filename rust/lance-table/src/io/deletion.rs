@@ -58,6 +58,57 @@ pub fn relative_deletion_file_path(fragment_id: u64, deletion_file: &DeletionFil
     format!("{DELETIONS_DIR}/{fragment_id}-{read_version}-{id}.{suffix}")
 }
 
+/// Default number of deleted rows above which [`DeletionFileEncoding::Auto`]
+/// writes a bitmap instead of an Arrow array.
+pub const DEFAULT_BITMAP_THRESHOLD: usize = 5_000;
+
+/// How deleted row offsets are encoded in a deletion file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DeletionFileEncoding {
+    /// Choose based on the number of deleted rows, see
+    /// [`DeletionFileOptions::bitmap_threshold`].
+    #[default]
+    Auto,
+    /// Always write an Arrow file of row offsets.
+    Array,
+    /// Always write a serialized roaring bitmap.
+    Bitmap,
+}
+
+/// Options controlling how deletion files are written.
+///
+/// Readers handle both formats regardless of these options.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeletionFileOptions {
+    pub encoding: DeletionFileEncoding,
+    /// With [`DeletionFileEncoding::Auto`], fragments with more deleted rows
+    /// than this are written as bitmaps.
+    pub bitmap_threshold: usize,
+}
+
+impl Default for DeletionFileOptions {
+    fn default() -> Self {
+        Self {
+            encoding: DeletionFileEncoding::Auto,
+            bitmap_threshold: DEFAULT_BITMAP_THRESHOLD,
+        }
+    }
+}
+
+impl DeletionFileOptions {
+    /// The file type to write for a fragment with `num_deleted_rows` deletions.
+    pub fn file_type(&self, num_deleted_rows: usize) -> DeletionFileType {
+        match self.encoding {
+            DeletionFileEncoding::Array => DeletionFileType::Array,
+            DeletionFileEncoding::Bitmap => DeletionFileType::Bitmap,
+            DeletionFileEncoding::Auto if num_deleted_rows > self.bitmap_threshold => {
+                DeletionFileType::Bitmap
+            }
+            DeletionFileEncoding::Auto => DeletionFileType::Array,
+        }
+    }
+}
+
 /// Write a deletion file for a fragment for a given deletion vector.
 ///
 /// Returns the deletion file if one was written. If no deletions were present,
@@ -69,67 +120,70 @@ pub async fn write_deletion_file(
     removed_rows: &DeletionVector,
     object_store: &ObjectStore,
 ) -> Result<Option<DeletionFile>> {
-    let deletion_file = match removed_rows {
-        DeletionVector::NoDeletions => None,
-        DeletionVector::Set(set) => {
-            let id = rand::rng().random::<u64>();
-            let deletion_file = DeletionFile {
-                read_version,
-                id,
-                file_type: DeletionFileType::Array,
-                num_deleted_rows: Some(set.len()),
-                base_id: None,
-            };
-            let path = deletion_file_path(base, fragment_id, &deletion_file);
+    write_deletion_file_with_options(
+        base,
+        fragment_id,
+        read_version,
+        removed_rows,
+        object_store,
+        &DeletionFileOptions::default(),
+    )
+    .await
+}
+
+/// Write a deletion file, choosing its encoding from `options`.
+pub async fn write_deletion_file_with_options(
+    base: &Path,
+    fragment_id: u64,
+    read_version: u64,
+    removed_rows: &DeletionVector,
+    object_store: &ObjectStore,
+    options: &DeletionFileOptions,
+) -> Result<Option<DeletionFile>> {
+    if matches!(removed_rows, DeletionVector::NoDeletions) {
+        return Ok(None);
+    }
+
+    let num_deleted_rows = removed_rows.len();
+    let deletion_file = DeletionFile {
+        read_version,
+        id: rand::rng().random::<u64>(),
+        file_type: options.file_type(num_deleted_rows),
+        num_deleted_rows: Some(num_deleted_rows),
+        base_id: None,
+    };
+    let path = deletion_file_path(base, fragment_id, &deletion_file);
 
-            let array = UInt32Array::from_iter(set.iter().copied());
+    let mut out: Vec<u8> = Vec::new();
+    match deletion_file.file_type {
+        DeletionFileType::Array => {
+            let array = UInt32Array::from_iter_values(removed_rows.to_sorted_iter());
             let array = Arc::new(array);
 
             let schema = deletion_arrow_schema();
             let batch = RecordBatch::try_new(schema.clone(), vec![array])?;
 
-            let mut out: Vec<u8> = Vec::new();
             let write_options =
                 IpcWriteOptions::default().try_with_compression(Some(CompressionType::ZSTD))?;
-            {
-                let mut writer = ArrowFileWriter::try_new_with_options(
-                    &mut out,
-                    schema.as_ref(),
-                    write_options,
-                )?;
-                writer.write(&batch)?;
-                writer.finish()?;
-                // Drop writer so out is no longer borrowed.
-            }
-
-            object_store.put(&path, &out).await?;
-
-            info!(target: TRACE_FILE_AUDIT, mode=AUDIT_MODE_CREATE, r#type=AUDIT_TYPE_DELETION, path = path.to_string());
-
-            Some(deletion_file)
+            let mut writer =
+                ArrowFileWriter::try_new_with_options(&mut out, schema.as_ref(), write_options)?;
+            writer.write(&batch)?;
+            writer.finish()?;
         }
-        DeletionVector::Bitmap(bitmap) => {
-            let id = rand::rng().random::<u64>();
-            let deletion_file = DeletionFile {
-                read_version,
-                id,
-                file_type: DeletionFileType::Bitmap,
-                num_deleted_rows: Some(bitmap.len() as usize),
-                base_id: None,
-            };
-            let path = deletion_file_path(base, fragment_id, &deletion_file);
-
-            let mut out: Vec<u8> = Vec::new();
-            bitmap.serialize_into(&mut out)?;
+        DeletionFileType::Bitmap => match removed_rows {
+            DeletionVector::Bitmap(bitmap) => bitmap.serialize_into(&mut out)?,
+            _ => removed_rows
+                .iter()
+                .collect::<RoaringBitmap>()
+                .serialize_into(&mut out)?,
+        },
+    }
 
-            object_store.put(&path, &out).await?;
+    object_store.put(&path, &out).await?;
 
-            info!(target: TRACE_FILE_AUDIT, mode=AUDIT_MODE_CREATE, r#type=AUDIT_TYPE_DELETION, path = path.to_string());
+    info!(target: TRACE_FILE_AUDIT, mode=AUDIT_MODE_CREATE, r#type=AUDIT_TYPE_DELETION, path = path.to_string());
 
-            Some(deletion_file)
-        }
-    };
-    Ok(deletion_file)
+    Ok(Some(deletion_file))
 }
 
 #[instrument(
@@ -221,6 +275,28 @@ mod test {
     use super::*;
     use object_store::ObjectStoreExt;
 
+    fn bitmap_options() -> DeletionFileOptions {
+        DeletionFileOptions {
+            encoding: DeletionFileEncoding::Bitmap,
+            ..Default::default()
+        }
+    }
+
+    async fn write_with(dv: &DeletionVector, options: &DeletionFileOptions) -> DeletionFile {
+        let object_store = ObjectStore::memory();
+        let path = Path::from("/encoding");
+        let file = write_deletion_file_with_options(&path, 3, 1, dv, &object_store, options)
+            .await
+            .unwrap()
+            .unwrap();
+        let read_dv = read_deletion_file(3, &file, &path, &object_store)
+            .await
+            .unwrap();
+        assert_eq!(&read_dv, dv);
+        assert_eq!(file.num_deleted_rows, Some(dv.len()));
+        file
+    }
+
     #[tokio::test]
     async fn test_write_no_deletions() {
         let dv = DeletionVector::NoDeletions;
@@ -297,9 +373,16 @@ mod test {
 
         let object_store = ObjectStore::memory();
         let path = Path::from("/bitmap");
-        let file = write_deletion_file(&path, fragment_id, read_version, &dv, &object_store)
-            .await
-            .unwrap();
+        let file = write_deletion_file_with_options(
+            &path,
+            fragment_id,
+            read_version,
+            &dv,
+            &object_store,
+            &bitmap_options(),
+        )
+        .await
+        .unwrap();
 
         assert!(matches!(
             file,
@@ -358,13 +441,69 @@ mod test {
 
         let object_store = ObjectStore::memory();
         let path = Path::from("/bitmap");
-        let file = write_deletion_file(&path, fragment_id, read_version, &dv, &object_store)
-            .await
-            .unwrap();
+        let file = write_deletion_file_with_options(
+            &path,
+            fragment_id,
+            read_version,
+            &dv,
+            &object_store,
+            &bitmap_options(),
+        )
+        .await
+        .unwrap();
 
         let read_dv = read_deletion_file(fragment_id, &file.unwrap(), &path, &object_store)
             .await
             .unwrap();
         assert_eq!(read_dv, dv);
     }
+
+    #[tokio::test]
+    async fn test_forced_bitmap_on_sparse_deletes() {
+        let dv = DeletionVector::Set(HashSet::from_iter([3, 1_000, 70_000]));
+        let file = write_with(&dv, &bitmap_options()).await;
+        assert_eq!(file.file_type, DeletionFileType::Bitmap);
+    }
+
+    #[tokio::test]
+    async fn test_forced_array_on_dense_deletes() {
+        let dv = DeletionVector::Bitmap(RoaringBitmap::from_iter(0..20_000));
+        let options = DeletionFileOptions {
+            encoding: DeletionFileEncoding::Array,
+            ..Default::default()
+        };
+        let file = write_with(&dv, &options).await;
+        assert_eq!(file.file_type, DeletionFileType::Array);
+    }
+
+    #[tokio::test]
+    async fn test_auto_encoding_threshold() {
+        let options = DeletionFileOptions {
+            encoding: DeletionFileEncoding::Auto,
+            bitmap_threshold: 100,
+        };
+
+        // At the threshold the representation in memory doesn't matter.
+        let at = DeletionVector::Bitmap(RoaringBitmap::from_iter(0..100));
+        assert_eq!(
+            write_with(&at, &options).await.file_type,
+            DeletionFileType::Array
+        );
+
+        let above = DeletionVector::Set(HashSet::from_iter(0..101));
+        assert_eq!(
+            write_with(&above, &options).await.file_type,
+            DeletionFileType::Bitmap
+        );
+
+        let default = DeletionFileOptions::default();
+        assert_eq!(
+            default.file_type(DEFAULT_BITMAP_THRESHOLD),
+            DeletionFileType::Array
+        );
+        assert_eq!(
+            default.file_type(DEFAULT_BITMAP_THRESHOLD + 1),
+            DeletionFileType::Bitmap
+        );
+    }
 }
@@ -9,7 +9,8 @@ use clap::{Parser, Subcommand, ValueEnum};
 use futures::TryStreamExt;
 use futures::stream::StreamExt;
 
-use lance::dataset::Dataset;
+use lance::dataset::{Dataset, WriteParams};
+use lance::import::ParquetImportOptions;
 use lance::index::DatasetIndexExt;
 use lance::index::vector::VectorIndexParams;
 use lance::{Error, Result};
@@ -39,6 +40,27 @@ enum Commands {
         n: i64,
     },
 
+    /// Import a Parquet file, or a directory of Parquet files
+    Import {
+        /// The URI of the Parquet file or directory.
+        source: String,
+
+        /// The URI of the dataset to write.
+        uri: String,
+
+        /// Max number of rows per data file.
+        #[arg(long, value_name = "NUM")]
+        max_rows_per_file: Option<usize>,
+
+        /// Carry over the field and schema metadata of the Parquet files.
+        #[arg(long)]
+        keep_metadata: bool,
+
+        /// Skip the files that an interrupted import already committed.
+        #[arg(long)]
+        resume: bool,
+    },
+
     /// Index operations
     Index {
         /// Actions on index
@@ -114,6 +136,33 @@ async fn main() -> Result<()> {
 
             Ok(())
         }
+        Commands::Import {
+            source,
+            uri,
+            max_rows_per_file,
+            keep_metadata,
+            resume,
+        } => {
+            let mut write_params = WriteParams::default();
+            if let Some(max_rows_per_file) = max_rows_per_file {
+                write_params.max_rows_per_file = *max_rows_per_file;
+            }
+            let options = ParquetImportOptions {
+                write_params,
+                keep_metadata: *keep_metadata,
+                resume: *resume,
+                ..Default::default()
+            };
+            let dataset = lance::import::parquet(source, uri, &options).await?;
+            println!(
+                "Imported {} rows into {} (version {})",
+                dataset.count_rows(None).await?,
+                uri,
+                dataset.version().version
+            );
+
+            Ok(())
+        }
         Commands::Index {
             action,
             uri,
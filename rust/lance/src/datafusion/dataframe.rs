@@ -3,24 +3,36 @@
 
 use std::{
     any::Any,
-    sync::{Arc, Mutex},
+    sync::{
+        Arc, Mutex, RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
 };
 
 use arrow_schema::{Schema, SchemaRef};
 use async_trait::async_trait;
 use datafusion::{
     catalog::{Session, streaming::StreamingTable},
+    common::{not_impl_err, plan_err},
     dataframe::DataFrame,
-    datasource::TableProvider,
+    datasource::{
+        TableProvider,
+        sink::{DataSink, DataSinkExec},
+    },
     error::DataFusionError,
     execution::{TaskContext, context::SessionContext},
-    logical_expr::{Expr, TableProviderFilterPushDown, TableType},
-    physical_plan::{ExecutionPlan, SendableRecordBatchStream, streaming::PartitionStream},
+    logical_expr::{Expr, TableProviderFilterPushDown, TableType, dml::InsertOp},
+    physical_plan::{
+        DisplayAs, DisplayFormatType, ExecutionPlan, SendableRecordBatchStream,
+        stream::RecordBatchStreamAdapter, streaming::PartitionStream,
+    },
 };
+use futures::StreamExt;
 use lance_arrow::SchemaExt;
-use lance_core::{ROW_ADDR_FIELD, ROW_ID_FIELD};
+use lance_core::{ROW_ADDR, ROW_ADDR_FIELD, ROW_ID, ROW_ID_FIELD};
 
 use crate::Dataset;
+use crate::dataset::{InsertBuilder, WriteMode, WriteParams};
 
 /// A [TableProvider] for Lance datasets.
 ///
@@ -32,16 +44,22 @@ use crate::Dataset;
 ///  - Filter pushdown
 ///  - Limit pushdown
 ///  - Projection pushdown
+///  - `INSERT INTO` and `INSERT OVERWRITE`
+///
+/// Inserts are written with the [WriteParams] given to [Self::with_write_params] and
+/// subsequent scans through the same provider read the newly committed version.  Any
+/// row id or row address columns in the inserted data are ignored.
 ///
 /// Note that LanceDB also has a TableProvider implementation that should be preferred
 /// if you are working in LanceDB.
 #[derive(Debug)]
 pub struct LanceTableProvider {
-    dataset: Arc<Dataset>,
+    dataset: Arc<RwLock<Arc<Dataset>>>,
     full_schema: Arc<Schema>,
     row_id_idx: Option<usize>,
     row_addr_idx: Option<usize>,
     ordered: bool,
+    write_params: WriteParams,
 }
 
 impl LanceTableProvider {
@@ -67,16 +85,62 @@ impl LanceTableProvider {
             row_addr_idx = Some(full_schema.fields.len() - 1);
         }
         Self {
-            dataset,
+            dataset: Arc::new(RwLock::new(dataset)),
             full_schema: Arc::new(full_schema),
             row_id_idx,
             row_addr_idx,
             ordered,
+            write_params: WriteParams::default(),
         }
     }
 
+    /// Set the parameters used to write data inserted through this provider
+    ///
+    /// The `mode` is ignored, it is determined by the kind of insert.
+    pub fn with_write_params(mut self, write_params: WriteParams) -> Self {
+        self.write_params = write_params;
+        self
+    }
+
+    /// The version of the dataset that is currently read by this provider
     pub fn dataset(&self) -> Arc<Dataset> {
-        self.dataset.clone()
+        self.dataset.read().unwrap().clone()
+    }
+
+    /// Check that the inserted data matches the dataset schema, returning the indices
+    /// of the input columns that should be written
+    fn insert_projection(&self, input_schema: &Schema) -> datafusion::common::Result<Vec<usize>> {
+        let dataset_schema = Schema::from(self.dataset().schema());
+        let mut projection = Vec::with_capacity(input_schema.fields.len());
+        for (idx, field) in input_schema.fields.iter().enumerate() {
+            if field.name() == ROW_ID || field.name() == ROW_ADDR {
+                continue;
+            }
+            let Ok(expected) = dataset_schema.field_with_name(field.name()) else {
+                return plan_err!(
+                    "Cannot insert into Lance table: column '{}' does not exist in the table",
+                    field.name()
+                );
+            };
+            if !field.data_type().equals_datatype(expected.data_type()) {
+                return plan_err!(
+                    "Cannot insert into Lance table: column '{}' has type {} but the table expects {}",
+                    field.name(),
+                    field.data_type(),
+                    expected.data_type()
+                );
+            }
+            projection.push(idx);
+        }
+        for field in dataset_schema.fields.iter() {
+            if input_schema.field_with_name(field.name()).is_err() {
+                return plan_err!(
+                    "Cannot insert into Lance table: column '{}' is missing from the inserted data",
+                    field.name()
+                );
+            }
+        }
+        Ok(projection)
     }
 }
 
@@ -101,7 +165,7 @@ impl TableProvider for LanceTableProvider {
         filters: &[Expr],
         limit: Option<usize>,
     ) -> datafusion::common::Result<Arc<dyn ExecutionPlan>> {
-        let mut scan = self.dataset.scan();
+        let mut scan = self.dataset().scan();
         match projection {
             Some(projection) if projection.is_empty() => {
                 scan.empty_project()?;
@@ -156,6 +220,92 @@ impl TableProvider for LanceTableProvider {
             .map(|_| TableProviderFilterPushDown::Exact)
             .collect())
     }
+
+    async fn insert_into(
+        &self,
+        _state: &dyn Session,
+        input: Arc<dyn ExecutionPlan>,
+        insert_op: InsertOp,
+    ) -> datafusion::common::Result<Arc<dyn ExecutionPlan>> {
+        let mode = match insert_op {
+            InsertOp::Append => WriteMode::Append,
+            InsertOp::Overwrite => WriteMode::Overwrite,
+            InsertOp::Replace => return not_impl_err!("Lance tables do not support REPLACE INTO"),
+        };
+        let projection = self.insert_projection(&input.schema())?;
+        let sink = LanceDataSink {
+            dataset: self.dataset.clone(),
+            schema: input.schema(),
+            projection,
+            write_params: WriteParams {
+                mode,
+                ..self.write_params.clone()
+            },
+        };
+        Ok(Arc::new(DataSinkExec::new(input, Arc::new(sink), None)))
+    }
+}
+
+/// Writes the output of an `INSERT` into a [LanceTableProvider]'s dataset
+#[derive(Debug)]
+struct LanceDataSink {
+    dataset: Arc<RwLock<Arc<Dataset>>>,
+    schema: SchemaRef,
+    projection: Vec<usize>,
+    write_params: WriteParams,
+}
+
+impl DisplayAs for LanceDataSink {
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default | DisplayFormatType::Verbose => {
+                write!(f, "LanceDataSink: mode={:?}", self.write_params.mode)
+            }
+            DisplayFormatType::TreeRender => write!(f, "mode={:?}", self.write_params.mode),
+        }
+    }
+}
+
+#[async_trait]
+impl DataSink for LanceDataSink {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+
+    async fn write_all(
+        &self,
+        data: SendableRecordBatchStream,
+        _context: &Arc<TaskContext>,
+    ) -> datafusion::common::Result<u64> {
+        let dataset = self.dataset.read().unwrap().clone();
+        let projection = self.projection.clone();
+        let num_rows = Arc::new(AtomicU64::new(0));
+        let rows_counter = num_rows.clone();
+        let stream = data.map(move |batch| {
+            let batch = batch?.project(&projection)?;
+            rows_counter.fetch_add(batch.num_rows() as u64, Ordering::Relaxed);
+            Ok::<_, DataFusionError>(batch)
+        });
+        let stream: SendableRecordBatchStream = Box::pin(RecordBatchStreamAdapter::new(
+            Arc::new(self.schema.project(&self.projection)?),
+            stream,
+        ));
+
+        let written = InsertBuilder::new(dataset)
+            .with_params(&self.write_params)
+            .execute_stream(stream)
+            .await?;
+
+        let mut current = self.dataset.write().unwrap();
+        if written.version().version > current.version().version {
+            *current = Arc::new(written);
+        }
+        Ok(num_rows.load(Ordering::Relaxed))
+    }
 }
 
 pub trait SessionContextExt {
@@ -263,17 +413,39 @@ mod tests {
 
     use arrow::{
         array::AsArray,
-        datatypes::{Int32Type, Int64Type},
+        datatypes::{Int32Type, Int64Type, UInt64Type},
     };
-    use datafusion::prelude::SessionContext;
+    use arrow_schema::{DataType, Field, Schema};
+    use datafusion::{error::DataFusionError, prelude::SessionContext};
     use lance_core::utils::tempfile::TempStrDir;
     use lance_datagen::array;
 
     use crate::{
+        Dataset, Error,
         datafusion::LanceTableProvider,
+        dataset::WriteParams,
         utils::test::{DatagenExt, FragmentCount, FragmentRowCount},
     };
 
+    async fn test_dataset(test_uri: &str) -> Dataset {
+        lance_datagen::gen_batch()
+            .col("x", array::step::<Int32Type>())
+            .col("y", array::step_custom::<Int32Type>(0, 2))
+            .into_dataset(
+                test_uri,
+                FragmentCount::from(10),
+                FragmentRowCount::from(10),
+            )
+            .await
+            .unwrap()
+    }
+
+    async fn count_rows(ctx: &SessionContext, sql: &str) -> u64 {
+        let results = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+        let count = arrow::compute::cast(results[0].column(0), &DataType::UInt64).unwrap();
+        count.as_primitive::<UInt64Type>().value(0)
+    }
+
     #[tokio::test]
     pub async fn test_table_provider() {
         let test_uri = TempStrDir::default();
@@ -309,4 +481,108 @@ mod tests {
         // SUM(0..100) - SUM(0..50) = 3675
         assert_eq!(results.column(0).as_primitive::<Int64Type>().value(0), 3675);
     }
+
+    #[tokio::test]
+    async fn test_insert_into() {
+        let test_uri = TempStrDir::default();
+        let dataset = Arc::new(test_dataset(&test_uri).await);
+        let ctx = SessionContext::new();
+        let provider =
+            LanceTableProvider::new(dataset, true, false).with_write_params(WriteParams {
+                max_rows_per_file: 10,
+                ..Default::default()
+            });
+        ctx.register_table("foo", Arc::new(provider)).unwrap();
+
+        let inserted = count_rows(
+            &ctx,
+            "INSERT INTO foo (x, y) VALUES (1000, 2000), (1001, 2002)",
+        )
+        .await;
+        assert_eq!(inserted, 2);
+        assert_eq!(count_rows(&ctx, "SELECT COUNT(*) FROM foo").await, 102);
+        assert_eq!(
+            count_rows(&ctx, "SELECT COUNT(*) FROM foo WHERE x >= 1000").await,
+            2
+        );
+
+        let overwritten = count_rows(
+            &ctx,
+            "INSERT OVERWRITE foo (x, y) SELECT x, y FROM foo WHERE x < 15",
+        )
+        .await;
+        assert_eq!(overwritten, 15);
+        assert_eq!(count_rows(&ctx, "SELECT COUNT(*) FROM foo").await, 15);
+
+        let dataset = Dataset::open(&test_uri).await.unwrap();
+        assert_eq!(dataset.version().version, 3);
+        assert_eq!(dataset.count_rows(None).await.unwrap(), 15);
+        // The configured write params are used for the insert
+        assert_eq!(dataset.get_fragments().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_insert_into_schema_mismatch() {
+        let test_uri = TempStrDir::default();
+        let provider =
+            LanceTableProvider::new(Arc::new(test_dataset(&test_uri).await), false, false);
+
+        let cases = [
+            (
+                Schema::new(vec![
+                    Field::new("x", DataType::Int32, true),
+                    Field::new("y", DataType::Int32, true),
+                    Field::new("z", DataType::Int32, true),
+                ]),
+                "column 'z' does not exist in the table",
+            ),
+            (
+                Schema::new(vec![
+                    Field::new("x", DataType::Int32, true),
+                    Field::new("y", DataType::Utf8, true),
+                ]),
+                "column 'y' has type Utf8 but the table expects Int32",
+            ),
+            (
+                Schema::new(vec![Field::new("x", DataType::Int32, true)]),
+                "column 'y' is missing from the inserted data",
+            ),
+        ];
+        for (schema, expected) in cases {
+            let err = provider.insert_projection(&schema).unwrap_err();
+            assert!(matches!(err, DataFusionError::Plan(_)), "{err}");
+            assert!(err.to_string().contains(expected), "{err}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_insert_overwrite_conflict() {
+        let test_uri = TempStrDir::default();
+        let dataset = Arc::new(test_dataset(&test_uri).await);
+
+        let ctx = SessionContext::new();
+        ctx.register_table(
+            "first",
+            Arc::new(LanceTableProvider::new(dataset.clone(), false, false)),
+        )
+        .unwrap();
+        ctx.register_table(
+            "second",
+            Arc::new(LanceTableProvider::new(dataset, false, false)),
+        )
+        .unwrap();
+
+        count_rows(&ctx, "INSERT OVERWRITE first VALUES (1, 2)").await;
+        let err = ctx
+            .sql("INSERT OVERWRITE second VALUES (3, 4)")
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(Error::from(err), Error::RetryableCommitConflict { .. }),
+            "expected a retryable commit conflict"
+        );
+    }
 }
@@ -4,6 +4,7 @@
 //! Lance Dataset
 //!
 
+use arrow::ffi_stream::FFI_ArrowArrayStream;
 use arrow_array::{RecordBatch, RecordBatchReader};
 use arrow_schema::DataType;
 use byteorder::{ByteOrder, LittleEndian};
@@ -17,21 +18,27 @@ use crate::dataset::metadata::UpdateFieldMetadataBuilder;
 use crate::dataset::transaction::translate_schema_metadata_updates;
 use crate::index::DatasetIndexExt;
 use crate::session::caches::{DSMetadataCache, ManifestKey, TransactionKey};
+use crate::session::commit_listener::notify_commit_listeners;
 use crate::session::index_caches::DSIndexCache;
 use itertools::Itertools;
 use lance_core::ROW_ADDR;
-use lance_core::datatypes::{OnMissing, OnTypeMismatch, Projectable, Projection};
+use lance_core::datatypes::{
+    OnMetadataConflict, OnMissing, OnTypeMismatch, Projectable, Projection,
+};
 use lance_core::traits::DatasetTakeRows;
 use lance_core::utils::address::RowAddress;
+use lance_core::utils::encryption::KeyProvider;
 use lance_core::utils::tracing::{
     DATASET_CLEANING_EVENT, DATASET_DELETING_EVENT, DATASET_DROPPING_COLUMN_EVENT,
     TRACE_DATASET_EVENTS,
 };
 use lance_datafusion::projection::ProjectionPlan;
+use lance_encoding::decoder::DecoderPlugins;
 use lance_file::datatypes::populate_schema_dictionary;
 use lance_file::reader::{FileReader, FileReaderOptions};
 use lance_file::version::LanceFileVersion;
 use lance_index::{IndexType, progress::IndexBuildProgress};
+use lance_io::ffi::{ffi_runtime, to_ffi_arrow_array_stream};
 use lance_io::object_store::{
     ChainedWrappingObjectStore, LanceNamespaceStorageOptionsProvider, ObjectStore,
     ObjectStoreParams, StorageOptions, StorageOptionsAccessor, StorageOptionsProvider,
@@ -47,8 +54,8 @@ use lance_table::format::{
 };
 use lance_table::io::commit::{
     CommitConfig, CommitError, CommitHandler, CommitLock, ManifestLocation, ManifestNamingScheme,
-    VERSIONS_DIR, external_manifest::ExternalManifestCommitHandler, migrate_scheme_to_v2,
-    write_manifest_file_to_path,
+    VERSIONS_DIR, external_manifest::ExternalManifestCommitHandler, migrate_manifest_naming_scheme,
+    migrate_scheme_to_v2, write_manifest_file_to_path,
 };
 
 use crate::io::commit::namespace_manifest::LanceNamespaceExternalManifestStore;
@@ -63,23 +70,33 @@ use std::borrow::Cow;
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt::Debug;
 use std::num::NonZero;
-use std::ops::Range;
+use std::ops::{Range, RangeBounds};
 use std::pin::Pin;
 use std::sync::Arc;
+#[cfg(feature = "metrics")]
+use std::sync::OnceLock;
 use tracing::{info, instrument};
 
+pub mod archive;
 pub(crate) mod blob;
 mod branch_location;
 pub mod builder;
 pub mod cleanup;
+pub mod compatibility;
 pub mod delta;
+#[cfg(feature = "parquet")]
+pub mod export;
 pub mod fragment;
 mod hash_joiner;
 pub mod index;
+pub mod integrity;
+pub mod lease;
+pub mod maintenance;
 pub mod mem_wal;
 mod metadata;
 pub mod optimize;
 pub mod progress;
+pub mod query_defaults;
 pub mod refs;
 pub(crate) mod rowids;
 pub mod scanner;
@@ -88,6 +105,7 @@ pub mod sql;
 pub mod statistics;
 mod take;
 pub mod transaction;
+pub mod transaction_log;
 pub mod udtf;
 pub mod updater;
 mod utils;
@@ -98,6 +116,7 @@ pub(crate) use take::row_offsets_to_row_addresses;
 use self::builder::DatasetBuilder;
 use self::cleanup::RemovalStats;
 use self::fragment::FileFragment;
+use self::lease::ReadLease;
 use self::refs::Refs;
 use self::scanner::{DatasetRecordBatchStream, Scanner};
 use self::transaction::{Operation, Transaction, TransactionBuilder, UpdateMapEntry};
@@ -108,6 +127,8 @@ use crate::dataset::refs::{BranchContents, BranchIdentifier, Branches, Tags};
 use crate::dataset::sql::SqlQueryBuilder;
 use crate::datatypes::Schema;
 use crate::index::retain_supported_indices;
+#[cfg(feature = "metrics")]
+use crate::io::commit::CommitMetrics;
 use crate::io::commit::{
     commit_detached_transaction, commit_new_dataset, commit_transaction,
     detect_overlapping_fragments,
@@ -121,24 +142,31 @@ pub use lance_core::ROW_ID;
 use lance_core::box_error;
 use lance_index::scalar::lance_format::LanceIndexStore;
 use lance_namespace::models::{DeclareTableRequest, DescribeTableRequest};
-use lance_table::feature_flags::{apply_feature_flags, can_read_dataset};
+use lance_table::feature_flags::{
+    READER_FEATURE_LEVEL, apply_feature_flags, check_max_reader_feature_level,
+    check_reader_features,
+};
 use lance_table::io::deletion::{DELETIONS_DIR, relative_deletion_file_path};
 pub use schema_evolution::{
     BatchInfo, BatchUDF, ColumnAlteration, NewColumnTransform, UDFCheckpointStore,
 };
-pub use take::TakeBuilder;
+pub use take::{PrefetchHandle, TakeBuilder, TakeRequest, TakeResponse};
 pub use write::merge_insert::{
     MergeInsertBuilder, MergeInsertJob, MergeStats, UncommittedMergeInsert, WhenMatched,
     WhenNotMatched, WhenNotMatchedBySource,
 };
 
 use crate::dataset::index::LanceIndexStoreExt;
+pub use write::multi_statement::{MultiStatementBuilder, MultiStatementResult};
 pub use write::update::{UpdateBuilder, UpdateJob};
 #[allow(deprecated)]
 pub use write::{
-    AutoCleanupParams, CommitBuilder, DEFAULT_COMMIT_TIMEOUT, DeleteBuilder, DeleteResult,
-    ExternalBlobMode, InsertBuilder, UncommittedDelete, WriteDestination, WriteMode, WriteParams,
-    WriteProgressFn, WriteStats, write_fragments,
+    AutoCleanupParams, CLUSTERING_CONFIG_KEY, CommitBuilder, DEFAULT_COMMIT_TIMEOUT, DeleteBuilder,
+    DeleteResult, DeletionFileEncoding, DeletionFileOptions, DistributedWriteSession,
+    DuplicateKeyPolicy, ExternalBlobMode, FragmentMetadata, InsertBuilder,
+    SKIPPED_INDEXES_PROPERTY, SchemaCompatibility, SchemaEvolution, UncommittedDelete,
+    UnsortedAppends, WriteDestination, WriteMode, WriteParams, WriteProgressFn, WriteStats,
+    WriterTicket, write_fragments,
 };
 
 pub(crate) const INDICES_DIR: &str = "_indices";
@@ -188,6 +216,12 @@ pub struct Dataset {
     pub(crate) store_params: Option<Box<ObjectStoreParams>>,
     /// Optional runtime-only object store parameters keyed by base path URI.
     pub(crate) base_store_params: Option<Arc<HashMap<String, ObjectStoreParams>>>,
+    /// The read lease taken when the dataset was opened, see
+    /// [`DatasetBuilder::with_read_lease`].
+    pub(crate) read_lease: Option<Arc<ReadLease>>,
+    /// Commit instruments, registered on the first commit.
+    #[cfg(feature = "metrics")]
+    pub(crate) commit_metrics: Arc<OnceLock<CommitMetrics>>,
 }
 
 impl std::fmt::Debug for Dataset {
@@ -638,14 +672,7 @@ impl Dataset {
             read_struct(object_reader.as_ref(), offset).await
         }?;
 
-        if !can_read_dataset(manifest.reader_feature_flags) {
-            let message = format!(
-                "This dataset cannot be read by this version of Lance. \
-                 Please upgrade Lance to read this dataset.\n Flags: {}",
-                manifest.reader_feature_flags
-            );
-            return Err(Error::not_supported_source(message.into()));
-        }
+        check_reader_features(&manifest, READER_FEATURE_LEVEL)?;
 
         // If indices were also in the last block, we can take the opportunity to
         // decode them now and cache them.
@@ -740,6 +767,9 @@ impl Dataset {
             file_reader_options,
             store_params: store_params.map(Box::new),
             base_store_params,
+            read_lease: None,
+            #[cfg(feature = "metrics")]
+            commit_metrics: Default::default(),
         })
     }
 
@@ -941,6 +971,22 @@ impl Dataset {
         &self.uri
     }
 
+    /// Whether the dataset was opened with [`DatasetBuilder::read_only`].
+    pub fn is_read_only(&self) -> bool {
+        self.object_store.is_read_only()
+    }
+
+    /// Fail with [`Error::ReadOnly`] if the dataset was opened read-only.
+    pub(crate) fn check_writable(&self) -> Result<()> {
+        if self.is_read_only() {
+            return Err(Error::read_only(format!(
+                "dataset at {} was opened read-only",
+                self.uri
+            )));
+        }
+        Ok(())
+    }
+
     pub fn branch_location(&self) -> BranchLocation {
         BranchLocation {
             path: self.base.clone(),
@@ -983,6 +1029,24 @@ impl Dataset {
         delta::DatasetDeltaBuilder::new(self.clone())
     }
 
+    /// Row-level changes between `from_version` (exclusive) and `to_version`
+    /// (inclusive), see [`delta::DatasetDelta::get_changes`].
+    ///
+    /// Requires stable row ids.
+    pub async fn changes(
+        &self,
+        from_version: u64,
+        to_version: u64,
+        columns: Option<&[&str]>,
+    ) -> Result<DatasetRecordBatchStream> {
+        self.delta()
+            .with_begin_version(from_version)
+            .with_end_version(to_version)
+            .build()?
+            .get_changes(columns)
+            .await
+    }
+
     // TODO: Cache this
     pub(crate) fn is_legacy_storage(&self) -> bool {
         self.manifest
@@ -1204,6 +1268,22 @@ impl Dataset {
         cleanup::cleanup_old_versions(self, policy).boxed()
     }
 
+    /// Compact files, merge delta indices and remove old versions, whichever
+    /// the dataset needs.
+    ///
+    /// The fragments, index segments and versions are checked against the
+    /// thresholds in `options` and only the steps that cross one run. The
+    /// report says for every step whether it ran and the stats it decided on.
+    /// With `dry_run` set nothing is written.
+    ///
+    /// See [`maintenance::Scheduler`] to run this on an interval instead.
+    pub async fn optimize(
+        &mut self,
+        options: maintenance::DatasetOptimizeOptions,
+    ) -> Result<maintenance::OptimizeReport> {
+        maintenance::optimize(self, options).await
+    }
+
     #[allow(clippy::too_many_arguments)]
     async fn do_commit(
         base_uri: WriteDestination<'_>,
@@ -1346,6 +1426,13 @@ impl Dataset {
             None,
         )
         .await?;
+        notify_commit_listeners(
+            &self.session,
+            &self.uri,
+            &transaction.operation,
+            Some(self.manifest.as_ref()),
+            &manifest,
+        );
 
         self.manifest = Arc::new(manifest);
         self.manifest_location = manifest_location;
@@ -1403,6 +1490,29 @@ impl Dataset {
         take::take(self, row_indices, projection.into()).await
     }
 
+    /// Take rows by indices and export them as an Arrow C stream.
+    ///
+    /// The rows are read when the first batch is requested, driven on `handle` or on an
+    /// internal runtime if `None`.  See [`lance_io::ffi::to_ffi_arrow_array_stream`] for how
+    /// errors and releasing the stream are handled.
+    pub fn take_into_ffi_stream(
+        &self,
+        row_indices: &[u64],
+        projection: impl Into<ProjectionRequest>,
+        handle: Option<tokio::runtime::Handle>,
+    ) -> Result<FFI_ArrowArrayStream> {
+        let projection = projection.into();
+        let schema = take::take_schema(self, projection.clone())?;
+        let dataset = self.clone();
+        let row_indices = row_indices.to_vec();
+        let batches =
+            stream::once(async move { dataset.take(&row_indices, projection).await }).boxed();
+        to_ffi_arrow_array_stream(
+            lance_io::stream::RecordBatchStreamAdapter::new(schema, batches),
+            handle.map_or_else(ffi_runtime, Ok)?,
+        )
+    }
+
     /// Take Rows by the internal ROW ids.
     ///
     /// In Lance format, each row has a unique `u64` id, which is used to identify the row globally.
@@ -1456,6 +1566,34 @@ impl Dataset {
         TakeBuilder::try_new_from_ids(self.clone(), row_ids.to_vec(), projection.into())
     }
 
+    /// Serve a stream of takes, e.g. the many small takes of a feature server
+    ///
+    /// Requests that are waiting when a take can start are coalesced, so rows and pages
+    /// they share are read once, and up to the store's IO parallelism takes run at a time.
+    /// Responses are emitted as they complete, not in the order of the requests, and carry
+    /// the id of their request.  A failing request only fails its own response.
+    pub fn take_stream(
+        &self,
+        requests: impl Stream<Item = TakeRequest> + Send + 'static,
+    ) -> impl Stream<Item = TakeResponse> + Send + 'static {
+        take::take_stream(Arc::new(self.clone()), requests)
+    }
+
+    /// Read `row_ids` in the background so a later [`Self::take_rows`] of them is served
+    /// from the caches
+    ///
+    /// See [`TakeBuilder::prefetch`] for what is cached.  Await the returned handle to wait
+    /// for the prefetch, or drop it to cancel the reads that are still outstanding.
+    pub fn prefetch(
+        &self,
+        row_ids: &[u64],
+        projection: impl Into<ProjectionRequest>,
+    ) -> Result<PrefetchHandle> {
+        Ok(Arc::new(self.clone())
+            .take_builder(row_ids, projection)?
+            .prefetch())
+    }
+
     /// Take [BlobFile] by row IDs.
     pub async fn take_blobs(
         self: &Arc<Self>,
@@ -1613,6 +1751,12 @@ impl Dataset {
         write::delete::delete(self, predicate).await
     }
 
+    /// Start a transaction that commits several writes as one version, see
+    /// [`MultiStatementBuilder`].
+    pub fn transaction(self: &Arc<Self>) -> MultiStatementBuilder {
+        MultiStatementBuilder::new(self.clone())
+    }
+
     /// Truncate the dataset by deleting all rows.
     pub async fn truncate_table(&mut self) -> Result<()> {
         self.delete("true").await.map(|_| ())
@@ -1847,6 +1991,24 @@ impl Dataset {
         self.data_file_dir_for_base(data_file.base_id)
     }
 
+    /// The path of a data file in the store returned by
+    /// [`Self::object_store_for_data_file`].
+    pub(crate) fn data_file_path(&self, data_file: &DataFile) -> Result<Path> {
+        if data_file.has_absolute_uri() {
+            if let Some(base_id) = data_file.base_id {
+                return Err(Error::invalid_input(format!(
+                    "Data file {} has an absolute URI and base_path id {}",
+                    data_file.path, base_id
+                )));
+            }
+            return ObjectStore::extract_path_from_uri(
+                self.session.store_registry(),
+                &data_file.path,
+            );
+        }
+        Ok(self.data_file_dir(data_file)?.join(data_file.path.as_str()))
+    }
+
     /// Create a [`DataFile`] by reading metadata from an existing lance file.
     ///
     /// This reads the file's schema and version information, matches columns to
@@ -1999,9 +2161,37 @@ impl Dataset {
         &self,
         data_file: &DataFile,
     ) -> Result<Arc<ObjectStore>> {
+        if data_file.has_absolute_uri() {
+            return self.object_store_for_uri(&data_file.path).await;
+        }
         self.object_store(data_file.base_id).await
     }
 
+    /// Resolve the object store of an absolute URI, e.g. of a data file on another
+    /// store than the dataset.
+    ///
+    /// Stores are created by, and cached in, the session's object store registry.
+    /// They use the runtime params bound to the longest base path URI that prefixes
+    /// `uri`, see [`DatasetBuilder::with_base_store_params`], or the dataset-level
+    /// params otherwise.
+    async fn object_store_for_uri(&self, uri: &str) -> Result<Arc<ObjectStore>> {
+        let store_params = self
+            .base_store_params
+            .as_ref()
+            .and_then(|params| {
+                params
+                    .iter()
+                    .filter(|(base, _)| uri.starts_with(base.as_str()))
+                    .max_by_key(|(base, _)| base.len())
+                    .map(|(_, params)| params.clone())
+            })
+            .unwrap_or_else(|| self.store_params_for_base(None));
+        let (store, _) =
+            ObjectStore::from_uri_and_params(self.session.store_registry(), uri, &store_params)
+                .await?;
+        Ok(store)
+    }
+
     pub(crate) async fn object_store_for_deletion(
         &self,
         deletion_file: &DeletionFile,
@@ -2066,6 +2256,29 @@ impl Dataset {
         self.session.clone()
     }
 
+    /// The key provider used to read and write encrypted columns
+    ///
+    /// The one of the object store params the dataset was opened with takes
+    /// precedence over the one of the session, see [`Session::with_key_provider`].
+    pub fn key_provider(&self) -> Option<Arc<dyn KeyProvider>> {
+        self.store_params
+            .as_ref()
+            .and_then(|params| params.key_provider.clone())
+            .or_else(|| self.session.key_provider().cloned())
+    }
+
+    /// The decoder plugins used to read the data files of this dataset
+    ///
+    /// Includes the custom encodings registered on the session, see
+    /// [`Session::with_custom_encodings`].
+    pub(crate) fn decoder_plugins(&self) -> Arc<DecoderPlugins> {
+        let plugins = DecoderPlugins::default();
+        Arc::new(match self.session.custom_encodings() {
+            Some(custom_encodings) => plugins.with_custom_encodings(custom_encodings.clone()),
+            None => plugins,
+        })
+    }
+
     /// Get the currently checked-out version id.
     ///
     /// This is a cheap accessor that reads the id directly from the loaded
@@ -2117,6 +2330,18 @@ impl Dataset {
         Ok(versions)
     }
 
+    /// The history of the versions in `versions`, one row per version
+    ///
+    /// The rows are ordered by version and have the columns of
+    /// [`TRANSACTION_LOG_SCHEMA`](transaction_log::TRANSACTION_LOG_SCHEMA), see
+    /// [`transaction_log`] for which are known once old versions are cleaned up.
+    pub async fn transaction_log(
+        &self,
+        versions: impl RangeBounds<u64>,
+    ) -> Result<BoxStream<'static, Result<RecordBatch>>> {
+        transaction_log::transaction_log(self, versions).await
+    }
+
     /// List all detached manifest locations.
     ///
     /// Detached manifests are versions that are not part of the main version history.
@@ -2498,6 +2723,28 @@ impl Dataset {
         Ok(())
     }
 
+    /// Rename all manifests to the `target_scheme` naming scheme.
+    ///
+    /// A dataset whose manifests use both naming schemes, e.g. after being copied
+    /// between object stores, fails to open since its latest version can't be
+    /// resolved reliably. This repairs it, and can also move a dataset back to
+    /// [ManifestNamingScheme::V1]. Manifests are copied to their new names and
+    /// verified before the old names are deleted, so the migration can be run
+    /// again if it is interrupted.
+    ///
+    /// It should not be run while other operations write to the dataset. New
+    /// versions keep using the scheme of the latest manifest.
+    pub async fn migrate_manifest_paths(
+        &mut self,
+        target_scheme: ManifestNamingScheme,
+    ) -> Result<()> {
+        migrate_manifest_naming_scheme(self.object_store.as_ref(), &self.base, target_scheme)
+            .await?;
+        let latest_version = self.latest_version_id().await?;
+        *self = self.checkout_version(latest_version).await?;
+        Ok(())
+    }
+
     /// Shallow clone the target version into a new dataset at target_path.
     /// 'target_path': the uri string to clone the dataset into.
     /// 'version': the version cloned from, could be a version number or tag.
@@ -2947,6 +3194,7 @@ impl Dataset {
         stream: Box<dyn RecordBatchReader + Send>,
         left_on: &str,
         right_on: &str,
+        on_metadata_conflict: OnMetadataConflict,
     ) -> Result<()> {
         // Sanity check.
         if self.schema().field(left_on).is_none() && left_on != ROW_ID && left_on != ROW_ADDR {
@@ -2980,7 +3228,9 @@ impl Dataset {
         let joiner = Arc::new(HashJoiner::try_new(stream, right_on).await?);
         // Final schema is union of current schema, plus the RHS schema without
         // the right_on key.
-        let mut new_schema: Schema = self.schema().merge(joiner.out_schema().as_ref())?;
+        let mut new_schema: Schema = self
+            .schema()
+            .merge_with(joiner.out_schema().as_ref(), on_metadata_conflict)?;
         new_schema.set_field_id(Some(self.manifest.max_field_id()));
 
         // Write new data file to each fragment. Parallelism is done over columns,
@@ -3019,14 +3269,31 @@ impl Dataset {
     /// Returns: a new version of dataset.
     ///
     /// It performs a left-join on the two datasets.
+    ///
+    /// Schema metadata keys of the stream that the dataset already has keep
+    /// the dataset's values, see [`Self::merge_with`].
     pub async fn merge(
         &mut self,
         stream: impl RecordBatchReader + Send + 'static,
         left_on: &str,
         right_on: &str,
+    ) -> Result<()> {
+        self.merge_with(stream, left_on, right_on, OnMetadataConflict::default())
+            .await
+    }
+
+    /// [`Self::merge`], resolving the schema metadata keys that both the
+    /// dataset and the stream have according to `on_metadata_conflict`.
+    pub async fn merge_with(
+        &mut self,
+        stream: impl RecordBatchReader + Send + 'static,
+        left_on: &str,
+        right_on: &str,
+        on_metadata_conflict: OnMetadataConflict,
     ) -> Result<()> {
         let stream = Box::new(stream);
-        self.merge_impl(stream, left_on, right_on).await
+        self.merge_impl(stream, left_on, right_on, on_metadata_conflict)
+            .await
     }
 
     /// Merge a distributed scalar index into a single root artifact and report
@@ -3309,6 +3576,7 @@ pub(crate) struct ManifestWriteConfig {
     use_legacy_format: Option<bool>,           // default None
     storage_format: Option<DataStorageFormat>, // default None
     disable_transaction_file: bool,            // default false
+    max_reader_feature_level: Option<u32>,     // default None
 }
 
 impl Default for ManifestWriteConfig {
@@ -3320,6 +3588,7 @@ impl Default for ManifestWriteConfig {
             disable_transaction_file: false,
             use_legacy_format: None,
             storage_format: None,
+            max_reader_feature_level: None,
         }
     }
 }
@@ -3353,6 +3622,9 @@ pub(crate) async fn write_manifest_file(
             config.disable_transaction_file,
         )?;
     }
+    if let Some(max_level) = config.max_reader_feature_level {
+        check_max_reader_feature_level(manifest, max_level)?;
+    }
 
     manifest.set_timestamp(timestamp_to_nanos(config.timestamp));
 
@@ -0,0 +1,590 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Export a dataset to a single tar archive and import it elsewhere
+//!
+//! The archive holds the manifest, data files, deletion files and index files of a
+//! version, each at its path relative to the dataset root, so it can be restored
+//! under any URI of any object store.  Archives of the same files are identical
+//! byte for byte: entries are sorted by path and carry no timestamps or owners.
+
+use std::collections::BTreeMap;
+
+use futures::{StreamExt, TryStreamExt};
+use lance_io::object_store::ObjectStore;
+use lance_io::traits::Writer;
+use lance_table::io::commit::VERSIONS_DIR;
+use object_store::ObjectStoreExt;
+use object_store::path::Path;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use super::builder::DatasetBuilder;
+use super::{ReadParams, TRANSACTIONS_DIR};
+use crate::{Dataset, Error, Result};
+
+/// Options for [`Dataset::export_archive`]
+#[derive(Debug, Clone, Default)]
+pub struct ArchiveExportOptions {
+    /// Include every version of the dataset rather than only the checked out one
+    pub include_history: bool,
+}
+
+/// Options for [`Dataset::import_archive`]
+#[derive(Debug, Clone, Default)]
+pub struct ArchiveImportOptions {
+    /// Parameters used to open the destination store and the imported dataset
+    pub read_params: ReadParams,
+}
+
+impl Dataset {
+    /// Write the files of this version of the dataset to `writer` as a tar archive
+    ///
+    /// Paths in the archive are relative to the dataset root, so nothing of the
+    /// store or prefix the dataset lives under is recorded.  Datasets with files
+    /// in other base paths cannot be exported yet.
+    ///
+    /// Returns the number of files written.
+    pub async fn export_archive(
+        &self,
+        writer: impl AsyncWrite + Unpin,
+        options: &ArchiveExportOptions,
+    ) -> Result<usize> {
+        let mut files = self.archive_files().await?;
+        if options.include_history {
+            for version in self.versions().await? {
+                if version.version != self.version().version {
+                    let dataset = self.checkout_version(version.version).await?;
+                    files.extend(dataset.archive_files().await?);
+                }
+            }
+        }
+
+        let mut archive = TarWriter::new(writer);
+        for (name, path) in &files {
+            let object = self.object_store.inner.get(path).await?;
+            let size = object.meta.size;
+            archive.start_entry(name, size).await?;
+            let mut stream = object.into_stream();
+            let mut written = 0;
+            while let Some(bytes) = stream.try_next().await? {
+                written += bytes.len() as u64;
+                archive.write_data(&bytes).await?;
+            }
+            if written != size {
+                return Err(Error::io(format!(
+                    "File {} changed size while it was archived: expected {} bytes, read {}",
+                    path, size, written
+                )));
+            }
+            archive.finish_entry(size).await?;
+        }
+        archive.finish().await?;
+        Ok(files.len())
+    }
+
+    /// Restore a dataset written by [`Self::export_archive`] to `dest_uri`
+    ///
+    /// Fails if a dataset already exists at `dest_uri`.  Every version in the
+    /// archive is restored and the latest one is returned.
+    pub async fn import_archive(
+        reader: impl AsyncRead + Unpin,
+        dest_uri: &str,
+        options: &ArchiveImportOptions,
+    ) -> Result<Self> {
+        // Use one session for the writes and the returned dataset so that both see
+        // the same store, which matters for `memory://` stores.
+        let session = options.read_params.session.clone().unwrap_or_default();
+        let store_params = options
+            .read_params
+            .store_options
+            .clone()
+            .unwrap_or_default();
+        let (store, base) =
+            ObjectStore::from_uri_and_params(session.store_registry(), dest_uri, &store_params)
+                .await?;
+        let versions_dir = base.clone().join(VERSIONS_DIR);
+        if store
+            .read_dir_all(&versions_dir, None)
+            .next()
+            .await
+            .is_some()
+        {
+            return Err(Error::dataset_already_exists(dest_uri.to_string()));
+        }
+
+        let mut archive = TarReader::new(reader);
+        while let Some((name, size)) = archive.next_entry().await? {
+            let path = archive_path(&base, &name)?;
+            let mut writer = store.create(&path).await?;
+            archive.copy_data(size, &mut writer).await?;
+            Writer::shutdown(writer.as_mut()).await?;
+        }
+
+        DatasetBuilder::from_uri(dest_uri)
+            .with_read_params(ReadParams {
+                session: Some(session),
+                ..options.read_params.clone()
+            })
+            .load()
+            .await
+    }
+
+    /// The files of this version, by their path relative to the dataset root
+    async fn archive_files(&self) -> Result<BTreeMap<String, Path>> {
+        let mut files = BTreeMap::new();
+        let manifest_name = self.manifest_location.path.filename().ok_or_else(|| {
+            Error::internal(format!(
+                "Manifest path {} has no file name",
+                self.manifest_location.path
+            ))
+        })?;
+        files.insert(
+            format!("{}/{}", VERSIONS_DIR, manifest_name),
+            self.manifest_location.path.clone(),
+        );
+        if let Some(transaction_file) = &self.manifest.transaction_file {
+            files.insert(
+                format!("{}/{}", TRANSACTIONS_DIR, transaction_file),
+                self.base
+                    .clone()
+                    .join(TRANSACTIONS_DIR)
+                    .join(transaction_file.as_str()),
+            );
+        }
+        for (relative_path, base) in self.collect_paths().await? {
+            if base != self.base {
+                return Err(Error::not_supported(format!(
+                    "Cannot archive {}, files in other base paths ({}) are not supported yet",
+                    relative_path, base
+                )));
+            }
+            let path = relative_path
+                .split('/')
+                .filter(|part| !part.is_empty())
+                .fold(base, |path, part| path.join(part));
+            files.insert(relative_path, path);
+        }
+        Ok(files)
+    }
+}
+
+/// The path that the archive entry `name` is restored to under `base`
+fn archive_path(base: &Path, name: &str) -> Result<Path> {
+    let parts = name.split('/').collect::<Vec<_>>();
+    if parts
+        .iter()
+        .any(|part| part.is_empty() || *part == "." || *part == "..")
+    {
+        return Err(Error::invalid_input(format!(
+            "Invalid path in dataset archive: '{}'",
+            name
+        )));
+    }
+    Ok(parts
+        .into_iter()
+        .fold(base.clone(), |path, part| path.join(part)))
+}
+
+const BLOCK_SIZE: usize = 512;
+const NAME_LEN: usize = 100;
+const PREFIX_LEN: usize = 155;
+/// Sizes up to this are written as octal, larger ones in base-256
+const MAX_OCTAL_SIZE: u64 = 0o77777777777;
+
+fn padding(size: u64) -> usize {
+    (BLOCK_SIZE - (size % BLOCK_SIZE as u64) as usize) % BLOCK_SIZE
+}
+
+fn write_octal(field: &mut [u8], value: u64) {
+    let digits = field.len() - 1;
+    field[..digits].copy_from_slice(format!("{:0digits$o}", value).as_bytes());
+    field[digits] = 0;
+}
+
+/// Split `name` into the name and prefix fields of a ustar header
+fn split_name(name: &str) -> Result<(&str, &str)> {
+    if name.len() <= NAME_LEN {
+        return Ok(("", name));
+    }
+    // The prefix ends at a `/` that is not part of either field
+    name.char_indices()
+        .filter(|(i, c)| *c == '/' && *i <= PREFIX_LEN && name.len() - i - 1 <= NAME_LEN)
+        .map(|(i, _)| (&name[..i], &name[i + 1..]))
+        .next()
+        .ok_or_else(|| {
+            Error::not_supported(format!(
+                "Path '{}' is too long to be stored in a dataset archive",
+                name
+            ))
+        })
+}
+
+/// The header of a regular file in a ustar archive, with no owner, a mode of
+/// 0644 and a modification time of 0
+fn file_header(name: &str, size: u64) -> Result<[u8; BLOCK_SIZE]> {
+    let (prefix, name) = split_name(name)?;
+    let mut header = [0u8; BLOCK_SIZE];
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    write_octal(&mut header[100..108], 0o644);
+    write_octal(&mut header[108..116], 0);
+    write_octal(&mut header[116..124], 0);
+    if size <= MAX_OCTAL_SIZE {
+        write_octal(&mut header[124..136], size);
+    } else {
+        header[124] = 0x80;
+        header[128..136].copy_from_slice(&size.to_be_bytes());
+    }
+    write_octal(&mut header[136..148], 0);
+    header[156] = b'0';
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+    header[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+
+    header[148..156].fill(b' ');
+    let checksum = header.iter().map(|b| *b as u64).sum::<u64>();
+    write_octal(&mut header[148..155], checksum);
+    Ok(header)
+}
+
+fn parse_octal(field: &[u8]) -> Result<u64> {
+    let digits = std::str::from_utf8(field)
+        .map_err(|_| Error::invalid_input("Invalid number in dataset archive header"))?
+        .trim_matches(|c: char| c == '\0' || c == ' ');
+    if digits.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(digits, 8).map_err(|_| {
+        Error::invalid_input(format!(
+            "Invalid number in dataset archive header: '{}'",
+            digits
+        ))
+    })
+}
+
+fn parse_size(field: &[u8]) -> Result<u64> {
+    if field[0] & 0x80 != 0 {
+        Ok(field[field.len() - 8..]
+            .iter()
+            .fold(0, |size, b| (size << 8) | *b as u64))
+    } else {
+        parse_octal(field)
+    }
+}
+
+fn parse_field(field: &[u8]) -> Result<&str> {
+    let end = field.iter().position(|b| *b == 0).unwrap_or(field.len());
+    std::str::from_utf8(&field[..end])
+        .map_err(|_| Error::invalid_input("Invalid path in dataset archive header"))
+}
+
+struct TarWriter<W> {
+    writer: W,
+}
+
+impl<W: AsyncWrite + Unpin> TarWriter<W> {
+    fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    async fn start_entry(&mut self, name: &str, size: u64) -> Result<()> {
+        self.writer.write_all(&file_header(name, size)?).await?;
+        Ok(())
+    }
+
+    async fn write_data(&mut self, data: &[u8]) -> Result<()> {
+        self.writer.write_all(data).await?;
+        Ok(())
+    }
+
+    async fn finish_entry(&mut self, size: u64) -> Result<()> {
+        self.writer
+            .write_all(&[0u8; BLOCK_SIZE][..padding(size)])
+            .await?;
+        Ok(())
+    }
+
+    /// Write the two empty blocks that end an archive
+    async fn finish(mut self) -> Result<()> {
+        self.writer.write_all(&[0u8; 2 * BLOCK_SIZE]).await?;
+        self.writer.flush().await?;
+        Ok(())
+    }
+}
+
+struct TarReader<R> {
+    reader: R,
+}
+
+impl<R: AsyncRead + Unpin> TarReader<R> {
+    fn new(reader: R) -> Self {
+        Self { reader }
+    }
+
+    /// The path and size of the next regular file, whose data must be read with
+    /// [`Self::copy_data`] before the next call
+    async fn next_entry(&mut self) -> Result<Option<(String, u64)>> {
+        let mut header = [0u8; BLOCK_SIZE];
+        loop {
+            self.reader.read_exact(&mut header).await?;
+            if header.iter().all(|b| *b == 0) {
+                return Ok(None);
+            }
+            if &header[257..262] != b"ustar" {
+                return Err(Error::invalid_input(
+                    "Dataset archive is not a ustar archive",
+                ));
+            }
+            let expected = parse_octal(&header[148..156])?;
+            let mut unsigned = header;
+            unsigned[148..156].fill(b' ');
+            if unsigned.iter().map(|b| *b as u64).sum::<u64>() != expected {
+                return Err(Error::invalid_input(
+                    "Checksum mismatch in dataset archive header",
+                ));
+            }
+            let size = parse_size(&header[124..136])?;
+            match header[156] {
+                b'0' | 0 => {
+                    let name = parse_field(&header[..NAME_LEN])?;
+                    let prefix = parse_field(&header[345..345 + PREFIX_LEN])?;
+                    let name = if prefix.is_empty() {
+                        name.to_string()
+                    } else {
+                        format!("{}/{}", prefix, name)
+                    };
+                    return Ok(Some((name, size)));
+                }
+                // Directories carry no data and are created as files are written
+                b'5' => {}
+                // Extended headers from other tools, nothing in them is needed
+                b'x' | b'g' => self.skip(size).await?,
+                other => {
+                    return Err(Error::not_supported(format!(
+                        "Unsupported entry type '{}' in dataset archive",
+                        other as char
+                    )));
+                }
+            }
+        }
+    }
+
+    async fn copy_data(&mut self, size: u64, writer: &mut (impl AsyncWrite + Unpin)) -> Result<()> {
+        let copied = tokio::io::copy(&mut (&mut self.reader).take(size), writer).await?;
+        if copied != size {
+            return Err(Error::invalid_input("Dataset archive is truncated"));
+        }
+        self.reader
+            .read_exact(&mut [0u8; BLOCK_SIZE][..padding(size)])
+            .await?;
+        Ok(())
+    }
+
+    async fn skip(&mut self, size: u64) -> Result<()> {
+        self.copy_data(size, &mut tokio::io::sink()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow_array::{Int32Array, RecordBatch, RecordBatchIterator, StringArray};
+    use arrow_schema::{DataType, Field, Schema};
+    use lance_index::IndexType;
+    use lance_index::scalar::ScalarIndexParams;
+
+    use super::*;
+    use crate::dataset::WriteParams;
+    use crate::index::DatasetIndexExt;
+
+    async fn create_dataset(uri: &str) -> Dataset {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("name", DataType::Utf8, true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from_iter_values(0..1000)),
+                Arc::new(StringArray::from_iter_values(
+                    (0..1000).map(|i| format!("name-{}", i)),
+                )),
+            ],
+        )
+        .unwrap();
+        let params = WriteParams {
+            max_rows_per_file: 300,
+            ..Default::default()
+        };
+        let mut dataset = Dataset::write(
+            RecordBatchIterator::new(vec![Ok(batch)], schema),
+            uri,
+            Some(params),
+        )
+        .await
+        .unwrap();
+        dataset.delete("id % 10 = 0").await.unwrap();
+        dataset
+            .create_index(
+                &["id"],
+                IndexType::BTree,
+                Some("id_idx".into()),
+                &ScalarIndexParams::default(),
+                false,
+            )
+            .await
+            .unwrap();
+        dataset
+    }
+
+    async fn export(dataset: &Dataset, options: &ArchiveExportOptions) -> Vec<u8> {
+        let mut archive = Vec::new();
+        dataset.export_archive(&mut archive, options).await.unwrap();
+        archive
+    }
+
+    async fn entries(archive: &[u8]) -> Vec<String> {
+        let mut reader = TarReader::new(archive);
+        let mut names = Vec::new();
+        while let Some((name, size)) = reader.next_entry().await.unwrap() {
+            reader
+                .copy_data(size, &mut tokio::io::sink())
+                .await
+                .unwrap();
+            names.push(name);
+        }
+        names
+    }
+
+    async fn scan(dataset: &Dataset, filter: &str) -> RecordBatch {
+        let mut scanner = dataset.scan();
+        scanner.filter(filter).unwrap();
+        scanner.try_into_batch().await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_archive_round_trip() {
+        // A dataset under a deep prefix, as in a bucket shared by many tenants
+        let source = create_dataset("memory://bucket/tenant-a/datasets/source").await;
+        let archive = export(&source, &ArchiveExportOptions::default()).await;
+
+        let names = entries(&archive).await;
+        let mut sorted = names.clone();
+        sorted.sort();
+        assert_eq!(names, sorted);
+        assert!(names.iter().all(|name| !name.contains("tenant-a")));
+        for dir in [VERSIONS_DIR, "data", "_deletions", "_indices"] {
+            assert!(
+                names.iter().any(|name| name.starts_with(dir)),
+                "No {} in {:?}",
+                dir,
+                names
+            );
+        }
+        // Only the manifest of the checked out version
+        let manifests = names.iter().filter(|name| name.starts_with(VERSIONS_DIR));
+        assert_eq!(manifests.count(), 1);
+
+        let restored = Dataset::import_archive(
+            archive.as_slice(),
+            "memory://restored/copy",
+            &ArchiveImportOptions::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(restored.version().version, source.version().version);
+        assert_eq!(
+            scan(&restored, "id >= 0").await,
+            scan(&source, "id >= 0").await
+        );
+
+        // The index is restored and used
+        let mut scanner = restored.scan();
+        scanner.filter("id >= 200 and id < 300").unwrap();
+        let plan = scanner.explain_plan(false).await.unwrap();
+        assert!(
+            plan.contains("ScalarIndexQuery: query=[id >= 200 && id < 300]@id_idx(BTree)"),
+            "Expected scalar index query in plan: {}",
+            plan
+        );
+        assert_eq!(
+            scan(&restored, "id >= 200 and id < 300").await,
+            scan(&source, "id >= 200 and id < 300").await
+        );
+
+        // An existing dataset is not overwritten
+        let err = Dataset::import_archive(
+            archive.as_slice(),
+            "memory://restored/copy",
+            &ArchiveImportOptions {
+                read_params: ReadParams {
+                    session: Some(restored.session()),
+                    ..Default::default()
+                },
+            },
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, Error::DatasetAlreadyExists { .. }), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_archive_is_deterministic() {
+        let source = create_dataset("memory://deterministic/source").await;
+        let first = export(&source, &ArchiveExportOptions::default()).await;
+        assert_eq!(first.len() % BLOCK_SIZE, 0);
+        assert_eq!(
+            export(&source, &ArchiveExportOptions::default()).await,
+            first
+        );
+
+        // The same files under another URI give the same archive
+        let restored = Dataset::import_archive(
+            first.as_slice(),
+            "memory://elsewhere/nested/copy",
+            &ArchiveImportOptions::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            export(&restored, &ArchiveExportOptions::default()).await,
+            first
+        );
+    }
+
+    #[tokio::test]
+    async fn test_archive_with_history() {
+        let source = create_dataset("memory://history/source").await;
+        let archive = export(
+            &source,
+            &ArchiveExportOptions {
+                include_history: true,
+            },
+        )
+        .await;
+        let names = entries(&archive).await;
+        let manifests = names.iter().filter(|name| name.starts_with(VERSIONS_DIR));
+        assert_eq!(manifests.count() as u64, source.version().version);
+
+        let restored = Dataset::import_archive(
+            archive.as_slice(),
+            "memory://history/restored",
+            &ArchiveImportOptions::default(),
+        )
+        .await
+        .unwrap();
+        let first = restored.checkout_version(1).await.unwrap();
+        assert_eq!(first.count_rows(None).await.unwrap(), 1000);
+        assert_eq!(restored.count_rows(None).await.unwrap(), 900);
+    }
+
+    #[test]
+    fn test_long_paths() {
+        let name = format!("_indices/{}/{}", "a".repeat(120), "b".repeat(90));
+        let header = file_header(&name, 1 << 40).unwrap();
+        let (prefix, rest) = split_name(&name).unwrap();
+        assert_eq!(format!("{}/{}", prefix, rest), name);
+        assert_eq!(parse_size(&header[124..136]).unwrap(), 1 << 40);
+        assert!(file_header(&"c".repeat(300), 0).is_err());
+    }
+}
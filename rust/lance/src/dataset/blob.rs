@@ -32,6 +32,7 @@ use super::write::ExternalBlobMode;
 use super::{Dataset, ProjectionRequest};
 use arrow_array::StructArray;
 use lance_core::datatypes::{BlobKind, BlobVersion, parse_field_path};
+use lance_core::error::LanceOptionExt;
 use lance_core::utils::blob::blob_path;
 use lance_core::{Error, Result, utils::address::RowAddress};
 use lance_io::traits::{Reader, WriteExt, Writer};
@@ -1300,11 +1301,15 @@ impl BlobFile {
 
 /// Blob bytes materialized by [`ReadBlobsBuilder`].
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct ReadBlob {
     /// Row address of the blob that was read.
     pub row_address: u64,
     /// Blob payload bytes.
     pub data: Bytes,
+    /// Whether `data` holds only part of the blob because the read was
+    /// restricted with [`ReadBlobsBuilder::with_range`].
+    pub is_partial: bool,
 }
 
 /// Stream returned by [`ReadBlobsBuilder::try_into_stream`].
@@ -1326,6 +1331,7 @@ enum ReadBlobsSelection {
 struct ReadBlobsOptions {
     io_buffer_size_bytes: Option<u64>,
     preserve_order: bool,
+    value_range: Option<Range<u64>>,
 }
 
 impl Default for ReadBlobsOptions {
@@ -1333,6 +1339,7 @@ impl Default for ReadBlobsOptions {
         Self {
             io_buffer_size_bytes: None,
             preserve_order: true,
+            value_range: None,
         }
     }
 }
@@ -1386,6 +1393,17 @@ impl ReadBlobsBuilder {
         self
     }
 
+    /// Read only `length` bytes starting at `offset` within each selected blob.
+    ///
+    /// Only the requested sub-range is fetched from storage. The range is
+    /// clamped to each blob's size, so a range past the end of a blob yields
+    /// fewer (possibly zero) bytes. Blobs that are not read in full are marked
+    /// with [`ReadBlob::is_partial`].
+    pub fn with_range(mut self, offset: u64, length: u64) -> Self {
+        self.options.value_range = Some(offset..offset.saturating_add(length));
+        self
+    }
+
     /// Whether results must follow the caller's requested row order.
     pub fn preserve_order(mut self, preserve: bool) -> Self {
         self.options.preserve_order = preserve;
@@ -1408,7 +1426,7 @@ impl ReadBlobsBuilder {
             .iter()
             .map(|entry| entry.selection_index)
             .collect::<VecDeque<_>>();
-        let plans = plan_blob_read_plans(entries);
+        let plans = plan_blob_read_plans(entries, self.options.value_range.as_ref());
         let execution = Arc::new(ReadBlobsExecution::new(self.options.io_buffer_size_bytes));
         if plans.is_empty() {
             return Ok(stream::empty().boxed());
@@ -1499,6 +1517,7 @@ struct PlannedBlobRead {
     selection_index: usize,
     row_address: u64,
     physical_range: Range<u64>,
+    is_partial: bool,
 }
 
 /// One per-source read plan emitted by `read_blobs`.
@@ -1551,18 +1570,26 @@ struct IndexedReadBlob {
     selection_index: usize,
     row_address: u64,
     data: Bytes,
+    is_partial: bool,
 }
 
 fn into_read_blob(blob: IndexedReadBlob) -> ReadBlob {
     ReadBlob {
         row_address: blob.row_address,
         data: blob.data,
+        is_partial: blob.is_partial,
     }
 }
 
 /// Group selected blobs by physical source and sort each group's ranges by
 /// physical offset before handing them to the file scheduler.
-fn plan_blob_read_plans(entries: Vec<BlobEntry>) -> Vec<BlobReadPlan> {
+///
+/// When `value_range` is set, only that blob-local range (clamped to each
+/// blob's size) is planned for every entry.
+fn plan_blob_read_plans(
+    entries: Vec<BlobEntry>,
+    value_range: Option<&Range<u64>>,
+) -> Vec<BlobReadPlan> {
     let mut plan_indices = HashMap::<BlobSourceKey, usize>::new();
     let mut plans = Vec::<BlobReadPlan>::new();
 
@@ -1581,10 +1608,16 @@ fn plan_blob_read_plans(entries: Vec<BlobEntry>) -> Vec<BlobReadPlan> {
             plan_index
         };
 
+        let size = entry.file.size;
+        let local_range = value_range
+            .map(|range| range.start.min(size)..range.end.min(size))
+            .unwrap_or(0..size);
         plans[plan_index].reads.push(PlannedBlobRead {
             selection_index: entry.selection_index,
             row_address: entry.row_address,
-            physical_range: entry.file.position..(entry.file.position + entry.file.size),
+            physical_range: (entry.file.position + local_range.start)
+                ..(entry.file.position + local_range.end),
+            is_partial: local_range.end - local_range.start < size,
         });
     }
 
@@ -1613,28 +1646,43 @@ async fn execute_blob_read_plan(
     task: BlobReadPlan,
     execution: Arc<ReadBlobsExecution>,
 ) -> Result<Vec<IndexedReadBlob>> {
+    // Empty ranges (e.g. a sub-range that starts past the end of a blob) need no I/O.
     let ranges = task
         .reads
         .iter()
         .map(|read| read.physical_range.clone())
+        .filter(|range| !range.is_empty())
         .collect::<Vec<_>>();
-    let scheduler = execution.scheduler_for(&task.source);
-    let file_scheduler = scheduler
-        .open_file(&task.source.path, &task.source.file_size)
-        .await?;
-    let priority = ranges[0].start;
-    let bytes = file_scheduler.submit_request(ranges, priority).await?;
+    let mut bytes = if ranges.is_empty() {
+        Vec::new().into_iter()
+    } else {
+        let scheduler = execution.scheduler_for(&task.source);
+        let file_scheduler = scheduler
+            .open_file(&task.source.path, &task.source.file_size)
+            .await?;
+        let priority = ranges[0].start;
+        file_scheduler
+            .submit_request(ranges, priority)
+            .await?
+            .into_iter()
+    };
 
-    Ok(task
-        .reads
+    task.reads
         .into_iter()
-        .zip(bytes)
-        .map(|(read, data)| IndexedReadBlob {
-            selection_index: read.selection_index,
-            row_address: read.row_address,
-            data,
+        .map(|read| {
+            let data = if read.physical_range.is_empty() {
+                Bytes::new()
+            } else {
+                bytes.next().expect_ok()?
+            };
+            Ok(IndexedReadBlob {
+                selection_index: read.selection_index,
+                row_address: read.row_address,
+                data,
+                is_partial: read.is_partial,
+            })
         })
-        .collect())
+        .collect()
 }
 
 pub(super) async fn take_blobs(
@@ -2059,11 +2107,20 @@ async fn resolve_blob_read_location(
     let data_file = frag
         .data_file_for_field(blob_field_id)
         .ok_or_else(|| Error::internal("Data file not found for blob field".to_string()))?;
-    let data_file_dir = dataset.data_file_dir(data_file)?;
-    let data_file_path = data_file_dir.clone().join(data_file.path.as_str());
+    let data_file_path = dataset.data_file_path(data_file)?;
+    let data_file_dir = if data_file.has_absolute_uri() {
+        // Sidecars live next to the data file, on its store
+        let mut parts = data_file_path.parts().collect::<Vec<_>>();
+        parts.pop();
+        Path::from_iter(parts)
+    } else {
+        dataset.data_file_dir(data_file)?
+    };
     let data_file_key = data_file_key_from_path(data_file.path.as_str()).to_string();
 
-    let object_store = if let Some(base_id) = data_file.base_id {
+    let object_store = if data_file.has_absolute_uri() {
+        dataset.object_store_for_data_file(data_file).await?
+    } else if let Some(base_id) = data_file.base_id {
         if let Some(store) = store_cache.get(&base_id) {
             store.clone()
         } else {
@@ -2124,6 +2181,7 @@ mod tests {
         MultipartUpload, ObjectMeta, PutMultipartOptions, PutOptions, PutPayload, PutResult,
         path::Path,
     };
+    use rstest::rstest;
     use tokio::sync::Notify;
     use url::Url;
 
@@ -2689,6 +2747,83 @@ mod tests {
         }
     }
 
+    const RANGED_BLOB_SIZE: u64 = 1024 * 1024;
+
+    #[rstest]
+    #[case::start(0, 64 * 1024)]
+    #[case::middle(RANGED_BLOB_SIZE / 2, 4096)]
+    #[case::end(RANGED_BLOB_SIZE - 4096, 4096)]
+    #[case::past_end(RANGED_BLOB_SIZE - 100, 4096)]
+    #[case::beyond_end(RANGED_BLOB_SIZE + 10, 4096)]
+    #[tokio::test]
+    async fn test_read_blobs_with_range(#[case] offset: u64, #[case] length: u64) {
+        let test_dir = TempStrDir::default();
+        let payload = (0..RANGED_BLOB_SIZE)
+            .map(|i| (i % 251) as u8)
+            .collect::<Vec<_>>();
+        let mut blob_builder = BlobArrayBuilder::new(1);
+        blob_builder.push_bytes(payload.clone()).unwrap();
+        let blob_array: arrow_array::ArrayRef = blob_builder.finish().unwrap();
+        let schema = Arc::new(Schema::new(vec![blob_field("blob", true)]));
+        let batch = RecordBatch::try_new(schema.clone(), vec![blob_array]).unwrap();
+        let reader = RecordBatchIterator::new(vec![batch].into_iter().map(Ok), schema);
+        let params = WriteParams {
+            data_storage_version: Some(LanceFileVersion::V2_2),
+            ..Default::default()
+        };
+        let dataset = Arc::new(
+            Dataset::write(reader, &test_dir, Some(params))
+                .await
+                .unwrap(),
+        );
+
+        dataset.object_store.io_stats_incremental();
+        let blobs = dataset
+            .read_blobs("blob")
+            .unwrap()
+            .with_row_indices(vec![0])
+            .with_range(offset, length)
+            .execute()
+            .await
+            .unwrap();
+        let stats = dataset.object_store.io_stats_incremental();
+
+        let start = offset.min(RANGED_BLOB_SIZE) as usize;
+        let end = (offset + length).min(RANGED_BLOB_SIZE) as usize;
+        assert_eq!(blobs.len(), 1);
+        assert_eq!(blobs[0].data.as_ref(), &payload[start..end]);
+        assert!(blobs[0].is_partial);
+        // HEAD requests are tracked at the object's full size, so check the
+        // ranged reads: only the requested slice of the blob is fetched.
+        let ranged_bytes = stats
+            .requests
+            .iter()
+            .filter(|request| request.method == "get_range")
+            .filter_map(|request| request.range.clone())
+            .map(|range| range.end - range.start)
+            .sum::<u64>();
+        assert_eq!(ranged_bytes, (end - start) as u64, "{stats:?}");
+    }
+
+    #[tokio::test]
+    async fn test_read_blobs_with_range_covering_blob_is_not_partial() {
+        let fixture = BlobTestFixture::new().await;
+        let expected = fixture.data[0].column(1).as_binary::<i64>().value(2);
+
+        let blobs = fixture
+            .dataset
+            .read_blobs("blobs")
+            .unwrap()
+            .with_row_indices(vec![2])
+            .with_range(0, expected.len() as u64 + 1)
+            .execute()
+            .await
+            .unwrap();
+
+        assert_eq!(blobs[0].data.as_ref(), expected);
+        assert!(!blobs[0].is_partial);
+    }
+
     #[tokio::test]
     pub async fn test_take_blobs_by_indices() {
         let fixture = BlobTestFixture::new().await;
@@ -3137,7 +3272,7 @@ mod tests {
         ];
         let execution = Arc::new(ReadBlobsExecution::new(None));
         let blobs = try_join_all(
-            plan_blob_read_plans(entries)
+            plan_blob_read_plans(entries, None)
                 .into_iter()
                 .map(|plan| execute_blob_read_plan(plan, execution.clone())),
         )
@@ -3190,7 +3325,7 @@ mod tests {
         ];
         let execution = Arc::new(ReadBlobsExecution::new(None));
         let mut stream: super::ReadBlobsStream = futures::stream::iter(
-            plan_blob_read_plans(entries)
+            plan_blob_read_plans(entries, None)
                 .into_iter()
                 .map(move |plan| execute_blob_read_plan(plan, execution.clone())),
         )
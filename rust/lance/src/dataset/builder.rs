@@ -4,15 +4,18 @@ use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use lance_core::cache::CacheBackend;
 
+use super::lease::ReadLease;
 use super::refs::{Ref, Refs};
 use super::{DEFAULT_INDEX_CACHE_SIZE, DEFAULT_METADATA_CACHE_SIZE, ReadParams, WriteParams};
 use crate::dataset::branch_location::BranchLocation;
 use crate::io::commit::namespace_manifest::LanceNamespaceExternalManifestStore;
+use crate::session::commit_listener::CommitListener;
 use crate::{Dataset, Error, Result, session::Session};
 use futures::FutureExt;
 use lance_core::utils::tracing::{DATASET_LOADING_EVENT, TRACE_DATASET_EVENTS};
 use lance_file::datatypes::populate_schema_dictionary;
 use lance_file::reader::FileReaderOptions;
+use lance_io::object_store::read_only::READ_ONLY_KEY;
 use lance_io::object_store::{
     DEFAULT_CLOUD_IO_PARALLELISM, LanceNamespaceStorageOptionsProvider, ObjectStore,
     ObjectStoreParams, StorageOptions, StorageOptionsAccessor,
@@ -20,6 +23,7 @@ use lance_io::object_store::{
 use lance_namespace::LanceNamespace;
 use lance_namespace::models::DescribeTableRequest;
 use lance_table::{
+    feature_flags::check_reader_features,
     format::Manifest,
     io::commit::external_manifest::ExternalManifestCommitHandler,
     io::commit::{CommitHandler, commit_handler_from_url},
@@ -53,6 +57,13 @@ pub struct DatasetBuilder {
     storage_options_override: Option<HashMap<String, String>>,
     /// Runtime-only exact object store bindings keyed by base path URI.
     base_store_params: HashMap<String, ObjectStoreParams>,
+    /// Added to the session's commit listeners, see [`Self::with_commit_listener`].
+    commit_listeners: Vec<Arc<dyn CommitListener>>,
+    /// Take a read lease with this ttl, see [`Self::with_read_lease`].
+    read_lease_ttl: Option<Duration>,
+    /// Check the dataset as a reader of this feature level, see
+    /// [`Self::with_reader_feature_level`].
+    reader_feature_level: Option<u32>,
 }
 
 impl std::fmt::Debug for DatasetBuilder {
@@ -71,6 +82,9 @@ impl std::fmt::Debug for DatasetBuilder {
                 &self.storage_options_override.is_some(),
             )
             .field("base_store_params", &!self.base_store_params.is_empty())
+            .field("commit_listeners", &self.commit_listeners)
+            .field("read_lease_ttl", &self.read_lease_ttl)
+            .field("reader_feature_level", &self.reader_feature_level)
             .finish()
     }
 }
@@ -90,6 +104,9 @@ impl DatasetBuilder {
             file_reader_options: None,
             storage_options_override: None,
             base_store_params: HashMap::new(),
+            commit_listeners: Vec::new(),
+            read_lease_ttl: None,
+            reader_feature_level: None,
         }
     }
 
@@ -251,6 +268,17 @@ impl DatasetBuilder {
         self
     }
 
+    /// Open the dataset read-only.
+    ///
+    /// A read-only dataset never sends a put, delete, copy or rename to the
+    /// store. Writes and commits fail up front with [`Error::ReadOnly`], repairs
+    /// that would commit while reading are skipped, and the object store
+    /// rejects any write that gets past those checks. This sets the
+    /// `storage_read_only` storage option.
+    pub fn read_only(self, read_only: bool) -> Self {
+        self.with_storage_option(READ_ONLY_KEY, read_only.to_string())
+    }
+
     pub fn with_commit_handler(mut self, commit_handler: Arc<dyn CommitHandler>) -> Self {
         self.commit_handler = Some(commit_handler);
         self
@@ -516,6 +544,45 @@ impl DatasetBuilder {
         self
     }
 
+    /// Notify `listener` of every version committed through the loaded dataset.
+    ///
+    /// The listener is added to a copy of the session, so other datasets sharing the
+    /// session passed to [`Self::with_session`] are not affected.  See
+    /// [`Session::with_commit_listener`].
+    pub fn with_commit_listener(mut self, listener: Arc<dyn CommitListener>) -> Self {
+        self.commit_listeners.push(listener);
+        self
+    }
+
+    /// Protect the loaded version from cleanup while the dataset is open.
+    ///
+    /// A lease object holding the version and an expiry is written under `_leases/`
+    /// when the dataset is loaded, and [`cleanup_old_versions`] keeps every version
+    /// pinned by an unexpired lease.  The lease is renewed in the background once per
+    /// `ttl`, one small put each time, for as long as the dataset or any clone of it,
+    /// such as those held by running scans, is alive.  It is released when the last
+    /// clone is dropped, and expires on its own if the process dies.
+    ///
+    /// The lease pins the version the dataset was loaded at, even if the dataset is
+    /// later moved to another version.  Writing the lease fails for read-only
+    /// datasets.
+    ///
+    /// [`cleanup_old_versions`]: super::cleanup::cleanup_old_versions
+    pub fn with_read_lease(mut self, ttl: Duration) -> Self {
+        self.read_lease_ttl = Some(ttl);
+        self
+    }
+
+    /// Fail to load the dataset if a reader of this feature level couldn't read
+    /// it, as if this version of Lance only supported the reader features up to
+    /// the level. Useful to check that services running older versions of Lance
+    /// can still read a dataset, see
+    /// [`lance_table::feature_flags::KNOWN_READER_FEATURES`].
+    pub fn with_reader_feature_level(mut self, level: u32) -> Self {
+        self.reader_feature_level = Some(level);
+        self
+    }
+
     /// Set exact object store params used as the dataset-level default binding.
     pub fn with_store_params(mut self, store_params: ObjectStoreParams) -> Self {
         self.options = store_params;
@@ -547,23 +614,31 @@ impl DatasetBuilder {
 
         #[allow(deprecated)]
         match &self.options.object_store {
-            Some(store) => Ok((
-                Arc::new(ObjectStore::new(
-                    store.0.clone(),
-                    store.1.clone(),
-                    self.options.block_size,
-                    self.options.object_store_wrapper,
-                    self.options.use_constant_size_upload_parts,
-                    store.1.scheme() != "file",
-                    // If user supplied an object store then we just assume it's probably
-                    // cloud-like
-                    DEFAULT_CLOUD_IO_PARALLELISM,
-                    download_retry_count,
-                    None, // No storage_options available here
-                )),
-                Path::from(store.1.path()),
-                commit_handler,
-            )),
+            Some(store) => {
+                Ok((
+                    Arc::new(ObjectStore::new(
+                        store.0.clone(),
+                        store.1.clone(),
+                        self.options.block_size,
+                        self.options.object_store_wrapper,
+                        self.options.use_constant_size_upload_parts_or(
+                            store_registry
+                                .get_provider(store.1.scheme())
+                                .is_some_and(|provider| {
+                                    provider.default_use_constant_size_upload_parts()
+                                }),
+                        ),
+                        store.1.scheme() != "file",
+                        // If user supplied an object store then we just assume it's probably
+                        // cloud-like
+                        DEFAULT_CLOUD_IO_PARALLELISM,
+                        download_retry_count,
+                        None, // No storage_options available here
+                    )),
+                    Path::from(store.1.path()),
+                    commit_handler,
+                ))
+            }
             None => {
                 let (store, path) = ObjectStore::from_uri_and_params(
                     store_registry,
@@ -580,7 +655,22 @@ impl DatasetBuilder {
     pub async fn load(self) -> Result<Dataset> {
         let uri = self.table_uri.clone();
         let target_ref = self.version.clone();
-        match self.load_impl().boxed().await {
+        let read_lease_ttl = self.read_lease_ttl;
+        let reader_feature_level = self.reader_feature_level;
+        let result = self.load_impl().boxed().await.and_then(|dataset| {
+            if let Some(level) = reader_feature_level {
+                check_reader_features(&dataset.manifest, level)?;
+            }
+            Ok(dataset)
+        });
+        let result = match (result, read_lease_ttl) {
+            (Ok(mut dataset), Some(ttl)) => ReadLease::acquire(&dataset, ttl).await.map(|lease| {
+                dataset.read_lease = Some(Arc::new(lease));
+                dataset
+            }),
+            (result, _) => result,
+        };
+        match result {
             Ok(dataset) => {
                 info!(target: TRACE_DATASET_EVENTS, event=DATASET_LOADING_EVENT, uri=uri, target_ref = ?target_ref, version=dataset.manifest.version, status="success");
                 Ok(dataset)
@@ -645,6 +735,15 @@ impl DatasetBuilder {
                 )),
             },
         };
+        let session = if self.commit_listeners.is_empty() {
+            session
+        } else {
+            let mut session = session.as_ref().clone();
+            session
+                .commit_listeners
+                .extend(std::mem::take(&mut self.commit_listeners));
+            Arc::new(session)
+        };
 
         let target_ref = self.version.clone();
         let table_uri = self.table_uri.clone();
@@ -33,6 +33,7 @@
 //! (which should only be done if the caller can guarantee there are no updates
 //! happening at the same time)
 
+use super::lease::{LeaseContents, list_leases};
 use super::refs::TagContents;
 use crate::dataset::TRANSACTIONS_DIR;
 use crate::{Dataset, utils::temporal::utc_now};
@@ -44,11 +45,13 @@ use futures::{StreamExt, TryStreamExt, stream};
 use humantime::parse_duration;
 use lance_core::{
     Error, Result,
+    error::ErrorClass,
     utils::tracing::{
         AUDIT_MODE_DELETE, AUDIT_MODE_DELETE_UNVERIFIED, AUDIT_TYPE_DATA, AUDIT_TYPE_DELETION,
         AUDIT_TYPE_INDEX, AUDIT_TYPE_MANIFEST, TRACE_FILE_AUDIT,
     },
 };
+use lance_io::object_store::ObjectStore;
 use lance_table::{
     format::{IndexMetadata, Manifest},
     io::{
@@ -68,11 +71,13 @@ use std::{
 };
 use tokio::time::{MissedTickBehavior, interval};
 use tokio_stream::wrappers::IntervalStream;
-use tracing::{Span, debug, info, instrument};
+use tracing::{Span, debug, info, instrument, warn};
 
 #[derive(Clone, Debug, Default)]
 struct ReferencedFiles {
     data_paths: HashSet<Path>,
+    /// Data files referenced by absolute URIs, with their sizes if known
+    foreign_data_files: HashMap<String, u64>,
     delete_paths: HashSet<Path>,
     tx_paths: HashSet<Path>,
     index_uuids: HashSet<String>,
@@ -86,6 +91,37 @@ pub struct RemovalStats {
     pub transaction_files_removed: u64,
     pub index_files_removed: u64,
     pub deletion_files_removed: u64,
+    /// The versions of the cleaned branch whose manifests were removed, in
+    /// ascending order. With [`CleanupPolicy::dry_run`] these, like the other
+    /// stats, are what a real run would remove.
+    pub removed_versions: Vec<u64>,
+    /// Files and manifests that could not be deleted, after retries. Cleanup
+    /// keeps the old manifests when any file fails, so running it again
+    /// finishes the job.
+    pub failed_paths: Vec<Path>,
+    /// Old versions of the cleaned branch that were kept because an unexpired
+    /// read lease pins them, in ascending order. See
+    /// [`DatasetBuilder::with_read_lease`](super::builder::DatasetBuilder::with_read_lease).
+    pub leased_versions: Vec<u64>,
+}
+
+impl RemovalStats {
+    fn record_removed_file(&mut self, size: u64, file_type: Option<RemovedFileType>) {
+        self.bytes_removed += size;
+        match file_type {
+            Some(RemovedFileType::Data) => self.data_files_removed += 1,
+            Some(RemovedFileType::Transaction) => self.transaction_files_removed += 1,
+            Some(RemovedFileType::Index) => self.index_files_removed += 1,
+            Some(RemovedFileType::Deletion) => self.deletion_files_removed += 1,
+            None => {}
+        }
+    }
+
+    fn record_removed_manifest(&mut self, size: u64, version: u64) {
+        self.bytes_removed += size;
+        self.old_versions += 1;
+        self.removed_versions.push(version);
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -96,6 +132,15 @@ enum RemovedFileType {
     Deletion,
 }
 
+/// Log the deletion of a file to the audit trail, unless the task is a dry run.
+macro_rules! audit_delete {
+    ($task:expr, $($arg:tt)*) => {
+        if !$task.policy.dry_run {
+            info!(target: TRACE_FILE_AUDIT, $($arg)*);
+        }
+    };
+}
+
 fn remove_prefix(path: &Path, prefix: &Path) -> Path {
     let relative_parts = path.prefix_match(prefix);
     if relative_parts.is_none() {
@@ -122,6 +167,8 @@ struct CleanupInspection {
     verified_files: ReferencedFiles,
     /// Track tagged old versions in case we want to raise a `CleanupError`.
     tagged_old_versions: HashSet<u64>,
+    /// Old versions kept because a read lease pins them.
+    leased_old_versions: HashSet<u64>,
     /// The earliest timestamp of all retained manifests.
     earliest_retained_manifest_time: Option<DateTime<Utc>>,
 }
@@ -129,8 +176,6 @@ struct CleanupInspection {
 /// If a file cannot be verified then it will only be deleted if it is at least
 /// this many days old.
 const UNVERIFIED_THRESHOLD_DAYS: i64 = 7;
-const S3_DELETE_STREAM_BATCH_SIZE: u64 = 1_000;
-const AZURE_DELETE_STREAM_BATCH_SIZE: u64 = 256;
 
 impl<'a> CleanupTask<'a> {
     fn new(dataset: &'a Dataset, policy: CleanupPolicy) -> Self {
@@ -145,7 +190,11 @@ impl<'a> CleanupTask<'a> {
 
         let referenced_branches: Vec<(String, u64)> = self.find_referenced_branches().await?;
         if self.policy.clean_referenced_branches {
-            self.clean_referenced_branches(&referenced_branches).await?;
+            if self.policy.dry_run {
+                info!("Skipping the cleanup of referenced branches in a dry run");
+            } else {
+                self.clean_referenced_branches(&referenced_branches).await?;
+            }
         }
 
         // we process all manifest files in parallel to figure
@@ -169,7 +218,22 @@ impl<'a> CleanupTask<'a> {
             .map(|tag_content| tag_content.version)
             .collect();
 
-        let mut inspection = self.process_manifests(&tagged_versions).await?;
+        // Versions pinned by readers that are still alive are kept like tagged ones
+        let now = utc_now();
+        let (live_leases, expired_leases): (Vec<_>, Vec<_>) =
+            list_leases(&self.dataset.object_store, &self.dataset.base)
+                .await?
+                .into_iter()
+                .partition(|(_, lease)| lease.expires_at > now);
+        let leased_versions: HashSet<u64> = live_leases
+            .iter()
+            .filter(|(_, lease)| lease.branch == *current_branch)
+            .map(|(_, lease)| lease.version)
+            .collect();
+
+        let mut inspection = self
+            .process_manifests(&tagged_versions, &leased_versions)
+            .await?;
 
         if self.policy.error_if_tagged_old_versions && !inspection.tagged_old_versions.is_empty() {
             return Err(tagged_old_versions_cleanup_error(
@@ -184,27 +248,76 @@ impl<'a> CleanupTask<'a> {
                 .await?
         };
 
+        let mut leased_old_versions = inspection
+            .leased_old_versions
+            .iter()
+            .copied()
+            .collect::<Vec<_>>();
+        leased_old_versions.sort_unstable();
+
         let stats = self.delete_unreferenced_files(inspection).await?;
+        if !self.policy.dry_run {
+            self.delete_expired_leases(expired_leases).await;
+        }
         final_stats.bytes_removed += stats.bytes_removed;
         final_stats.old_versions += stats.old_versions;
         final_stats.data_files_removed += stats.data_files_removed;
         final_stats.transaction_files_removed += stats.transaction_files_removed;
         final_stats.index_files_removed += stats.index_files_removed;
         final_stats.deletion_files_removed += stats.deletion_files_removed;
+        final_stats.removed_versions = stats.removed_versions;
+        final_stats.failed_paths.extend(stats.failed_paths);
+        final_stats.leased_versions = leased_old_versions;
         Ok(final_stats)
     }
 
+    /// Remove leases left behind by readers that stopped renewing them
+    async fn delete_expired_leases(&self, expired_leases: Vec<(Path, LeaseContents)>) {
+        let object_store = &self.dataset.object_store;
+        stream::iter(expired_leases)
+            .for_each_concurrent(object_store.io_parallelism(), |(path, _)| async move {
+                if let Err(err) = object_store.delete(&path).await {
+                    debug!("Failed to delete the expired read lease {}: {}", path, err);
+                }
+            })
+            .await;
+    }
+
     #[instrument(level = "debug", skip_all)]
     async fn process_manifests(
         &'a self,
         tagged_versions: &HashSet<u64>,
+        leased_versions: &HashSet<u64>,
     ) -> Result<CleanupInspection> {
-        let inspection = Mutex::new(CleanupInspection::default());
-        self.dataset
+        let locations = self
+            .dataset
             .commit_handler
             .list_manifest_locations(&self.dataset.base, &self.dataset.object_store, false)
+            .try_collect::<Vec<_>>()
+            .await?;
+        // The oldest of the last `keep_last_n_versions` versions
+        let keep_from_version = self.policy.keep_last_n_versions.and_then(|n| {
+            let mut versions = locations
+                .iter()
+                .map(|location| location.version)
+                .collect::<Vec<_>>();
+            versions.sort_unstable_by(|a, b| b.cmp(a));
+            versions
+                .get(n.min(versions.len()).saturating_sub(1))
+                .copied()
+        });
+
+        let inspection = Mutex::new(CleanupInspection::default());
+        stream::iter(locations)
+            .map(Ok)
             .try_for_each_concurrent(self.dataset.object_store.io_parallelism(), |location| {
-                self.process_manifest_file(location, &inspection, tagged_versions)
+                self.process_manifest_file(
+                    location,
+                    &inspection,
+                    tagged_versions,
+                    leased_versions,
+                    keep_from_version,
+                )
             })
             .await?;
         Ok(inspection.into_inner().unwrap())
@@ -215,6 +328,8 @@ impl<'a> CleanupTask<'a> {
         location: ManifestLocation,
         inspection: &Mutex<CleanupInspection>,
         tagged_versions: &HashSet<u64>,
+        leased_versions: &HashSet<u64>,
+        keep_from_version: Option<u64>,
     ) -> Result<()> {
         // TODO: We can't cleanup invalid manifests.  There is no way to distinguish
         // between an invalid manifest and a temporary I/O error.  It's also not safe
@@ -226,12 +341,16 @@ impl<'a> CleanupTask<'a> {
             read_manifest(&self.dataset.object_store, &location.path, location.size).await?;
         let dataset_version = self.dataset.version().version;
 
-        // Don't delete the latest version, even if it is old. Don't delete tagged versions,
-        // regardless of age. Don't delete manifests if their version is newer than the dataset
+        // Don't delete the latest version, even if it is old. Don't delete tagged or leased
+        // versions, regardless of age. Don't delete manifests if their version is newer than the dataset
         // version.  These are either in-progress or newly added since we started.
+        // A version is only old if every retention rule of the policy lets it go.
         let is_latest = dataset_version <= manifest.version;
         let is_tagged = tagged_versions.contains(&manifest.version);
-        let in_working_set = is_latest || !self.policy.should_clean(&manifest) || is_tagged;
+        let is_old = self.policy.should_clean(&manifest)
+            && keep_from_version.is_none_or(|keep_from| manifest.version < keep_from);
+        let is_leased = leased_versions.contains(&manifest.version);
+        let in_working_set = is_latest || !is_old || is_tagged || is_leased;
         let indexes =
             read_manifest_indexes(&self.dataset.object_store, &location, &manifest).await?;
 
@@ -239,9 +358,12 @@ impl<'a> CleanupTask<'a> {
 
         // Track tagged old versions in case we want to return a `CleanupError` later.
         // Only track tagged when it is old.
-        if is_tagged && !is_latest && self.policy.should_clean(&manifest) {
+        if is_tagged && !is_latest && is_old {
             inspection.tagged_old_versions.insert(manifest.version);
         }
+        if is_leased && !is_latest && is_old {
+            inspection.leased_old_versions.insert(manifest.version);
+        }
 
         self.process_manifest(&manifest, &indexes, in_working_set, &mut inspection)?;
         if !in_working_set {
@@ -278,6 +400,17 @@ impl<'a> CleanupTask<'a> {
 
         for fragment in manifest.fragments.iter() {
             for file in fragment.files.iter() {
+                if file.has_absolute_uri() {
+                    let size = file
+                        .file_size_bytes
+                        .get()
+                        .map(u64::from)
+                        .unwrap_or_default();
+                    referenced_files
+                        .foreign_data_files
+                        .insert(file.path.clone(), size);
+                    continue;
+                }
                 let full_data_path = self.dataset.data_dir().clone().join(file.path.as_str());
                 let relative_data_path = remove_prefix(&full_data_path, &self.dataset.base);
                 referenced_files.data_paths.insert(relative_data_path);
@@ -320,7 +453,9 @@ impl<'a> CleanupTask<'a> {
         &self,
         inspection: CleanupInspection,
     ) -> Result<RemovalStats> {
-        let removal_stats = Mutex::new(RemovalStats::default());
+        // Size and type of every file sent for deletion, so that the stats
+        // only count the files that were actually deleted.
+        let pending = Mutex::new(HashMap::<Path, (u64, Option<RemovedFileType>)>::new());
         let verification_threshold = utc_now()
             - TimeDelta::try_days(UNVERIFIED_THRESHOLD_DAYS).expect("TimeDelta::try_days");
 
@@ -337,7 +472,7 @@ impl<'a> CleanupTask<'a> {
         // Build stream for a managed subtree
         let build_listing_stream = |dir: Path, file_type: Option<RemovedFileType>| {
             let inspection_ref = &inspection;
-            let removal_stats_ref = &removal_stats;
+            let pending_ref = &pending;
             self.dataset
                 .object_store
                 .read_dir_all(&dir, inspection.earliest_retained_manifest_time)
@@ -361,19 +496,11 @@ impl<'a> CleanupTask<'a> {
                         maybe_in_progress,
                         inspection_ref,
                     );
-                    if matches!(path_to_remove, Ok(Some(..))) {
-                        let mut stats = removal_stats_ref.lock().unwrap();
-                        stats.bytes_removed += obj_meta.size;
-                        if let Some(file_type) = file_type {
-                            match file_type {
-                                RemovedFileType::Data => stats.data_files_removed += 1,
-                                RemovedFileType::Transaction => {
-                                    stats.transaction_files_removed += 1
-                                }
-                                RemovedFileType::Index => stats.index_files_removed += 1,
-                                RemovedFileType::Deletion => stats.deletion_files_removed += 1,
-                            }
-                        }
+                    if let Ok(Some(path)) = &path_to_remove {
+                        pending_ref
+                            .lock()
+                            .unwrap()
+                            .insert(path.clone(), (obj_meta.size, file_type));
                     }
                     future::ready(path_to_remove)
                 })
@@ -396,54 +523,53 @@ impl<'a> CleanupTask<'a> {
         ];
         let unreferenced_paths = stream::iter(streams).flatten().boxed();
 
-        let old_manifests = inspection.old_manifests.clone();
-        let num_old_manifests = old_manifests.len();
-
-        // Ideally this collect shouldn't be needed here but it seems necessary
-        // to avoid https://github.com/rust-lang/rust/issues/102211
-        let manifest_bytes_removed = stream::iter(old_manifests.keys())
-            .map(|path| self.dataset.object_store.size(path))
-            .collect::<Vec<_>>()
-            .await;
-        let manifest_bytes_removed = stream::iter(manifest_bytes_removed)
-            .buffer_unordered(self.dataset.object_store.io_parallelism())
-            .try_fold(0, |acc, size| async move { Ok(acc + (size)) })
-            .await;
-
-        let old_manifests_stream = stream::iter(old_manifests.into_keys())
-            .map(|path| {
-                info!(target: TRACE_FILE_AUDIT, mode=AUDIT_MODE_DELETE, r#type=AUDIT_TYPE_MANIFEST, path = path.as_ref());
-                Ok(path)
-            })
-            .boxed();
-        let all_paths_to_remove =
-            stream::iter(vec![unreferenced_paths, old_manifests_stream]).flatten();
-
-        let paths_to_delete: BoxStream<Result<Path>> = if let Some(rate) =
-            self.policy.delete_rate_limit
-        {
-            let duration = calculate_duration(self.dataset.object_store.scheme().to_string(), rate);
-            let mut ticker = interval(duration);
-            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
-            IntervalStream::new(ticker)
-                .zip(all_paths_to_remove)
-                .map(|(_, path)| path)
-                .boxed()
+        let object_store = &self.dataset.object_store;
+        let concurrency = self
+            .policy
+            .delete_concurrency
+            .unwrap_or_else(|| object_store.io_parallelism());
+        let mut removal_stats = RemovalStats::default();
+        if self.policy.dry_run {
+            let removable = unreferenced_paths.try_collect::<Vec<_>>().await?;
+            let mut pending = pending.lock().unwrap();
+            for path in removable {
+                let (size, file_type) = pending.remove(&path).unwrap_or_default();
+                removal_stats.record_removed_file(size, file_type);
+            }
         } else {
-            all_paths_to_remove.boxed()
-        };
-
-        let delete_fut = self
-            .dataset
-            .object_store
-            .remove_stream(paths_to_delete)
-            .try_for_each(|_| future::ready(Ok(())));
+            let mut deleted =
+                object_store.delete_paths(self.rate_limited(unreferenced_paths), concurrency);
+            while let Some(outcome) = deleted.try_next().await? {
+                let (size, file_type) = pending
+                    .lock()
+                    .unwrap()
+                    .remove(&outcome.path)
+                    .unwrap_or_default();
+                if outcome.is_deleted() {
+                    removal_stats.record_removed_file(size, file_type);
+                } else {
+                    removal_stats.failed_paths.push(outcome.path);
+                }
+            }
+        }
 
-        delete_fut.await?;
+        if self.policy.delete_foreign_files {
+            self.delete_foreign_files(&inspection, &mut removal_stats)
+                .await?;
+        }
 
-        let mut removal_stats = removal_stats.into_inner().unwrap();
-        removal_stats.old_versions = num_old_manifests as u64;
-        removal_stats.bytes_removed += manifest_bytes_removed?;
+        // The old manifests are what lets a later cleanup verify the files
+        // they reference, so they are only deleted once all files are gone.
+        if removal_stats.failed_paths.is_empty() {
+            self.delete_old_manifests(inspection.old_manifests, concurrency, &mut removal_stats)
+                .await?;
+        } else {
+            warn!(
+                "Failed to delete {} files, keeping {} old manifests so cleanup can be run again",
+                removal_stats.failed_paths.len(),
+                inspection.old_manifests.len()
+            );
+        }
 
         let span = Span::current();
         span.record("bytes_removed", removal_stats.bytes_removed);
@@ -461,6 +587,115 @@ impl<'a> CleanupTask<'a> {
         Ok(removal_stats)
     }
 
+    /// Delete the data files on other stores that only old versions reference
+    ///
+    /// Unlike files in the dataset directories, these are never listed, so only
+    /// files referenced by an old manifest are found.
+    async fn delete_foreign_files(
+        &self,
+        inspection: &CleanupInspection,
+        removal_stats: &mut RemovalStats,
+    ) -> Result<()> {
+        let referenced = &inspection.referenced_files.foreign_data_files;
+        let unreferenced = inspection
+            .verified_files
+            .foreign_data_files
+            .iter()
+            .filter(|(uri, _)| !referenced.contains_key(*uri));
+        for (uri, size) in unreferenced {
+            audit_delete!(
+                self,
+                mode = AUDIT_MODE_DELETE,
+                r#type = AUDIT_TYPE_DATA,
+                path = uri.as_str()
+            );
+            if self.policy.dry_run {
+                removal_stats.record_removed_file(*size, Some(RemovedFileType::Data));
+                continue;
+            }
+            let object_store = self.dataset.object_store_for_uri(uri).await?;
+            let path =
+                ObjectStore::extract_path_from_uri(self.dataset.session.store_registry(), uri)?;
+            match object_store.delete(&path).await {
+                Ok(()) => removal_stats.record_removed_file(*size, Some(RemovedFileType::Data)),
+                Err(err) if err.class() == ErrorClass::NotFound => {}
+                Err(err) => {
+                    warn!("Failed to delete {}: {}", uri, err);
+                    removal_stats.failed_paths.push(path);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn delete_old_manifests(
+        &self,
+        old_manifests: HashMap<Path, u64>,
+        concurrency: usize,
+        removal_stats: &mut RemovalStats,
+    ) -> Result<()> {
+        let object_store = &self.dataset.object_store;
+        // Ideally this collect shouldn't be needed here but it seems necessary
+        // to avoid https://github.com/rust-lang/rust/issues/102211
+        let manifest_sizes = stream::iter(old_manifests.keys().cloned())
+            .map(|path| async move {
+                let size = object_store.size(&path).await?;
+                Ok::<_, Error>((path, size))
+            })
+            .collect::<Vec<_>>()
+            .await;
+        let manifest_sizes = stream::iter(manifest_sizes)
+            .buffer_unordered(object_store.io_parallelism())
+            .try_collect::<HashMap<_, _>>()
+            .await?;
+
+        if self.policy.dry_run {
+            for (path, version) in &old_manifests {
+                removal_stats.record_removed_manifest(manifest_sizes[path], *version);
+            }
+            removal_stats.removed_versions.sort_unstable();
+            return Ok(());
+        }
+
+        let old_manifests_stream = stream::iter(manifest_sizes.keys().cloned())
+            .map(|path| {
+                audit_delete!(
+                    self,
+                    mode = AUDIT_MODE_DELETE,
+                    r#type = AUDIT_TYPE_MANIFEST,
+                    path = path.as_ref()
+                );
+                Ok(path)
+            })
+            .boxed();
+        let mut deleted =
+            object_store.delete_paths(self.rate_limited(old_manifests_stream), concurrency);
+        while let Some(outcome) = deleted.try_next().await? {
+            if outcome.is_deleted() {
+                let version = old_manifests[&outcome.path];
+                removal_stats.record_removed_manifest(manifest_sizes[&outcome.path], version);
+            } else {
+                removal_stats.failed_paths.push(outcome.path);
+            }
+        }
+        removal_stats.removed_versions.sort_unstable();
+        Ok(())
+    }
+
+    /// Limit `paths` to the policy's `delete_rate_limit`, if any.
+    fn rate_limited<'b>(&self, paths: BoxStream<'b, Result<Path>>) -> BoxStream<'b, Result<Path>> {
+        let Some(rate) = self.policy.delete_rate_limit else {
+            return paths;
+        };
+        let batch_size = self.dataset.object_store.delete_batch_size() as u64;
+        let mut ticker = interval(calculate_duration(batch_size, rate));
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        IntervalStream::new(ticker)
+            .zip(paths)
+            .map(|(_, path)| path)
+            .boxed()
+    }
+
     fn path_if_not_referenced(
         &self,
         path: Path,
@@ -490,14 +725,24 @@ impl<'a> CleanupTask<'a> {
                 {
                     return Ok(None);
                 } else if !maybe_in_progress {
-                    info!(target: TRACE_FILE_AUDIT, mode=AUDIT_MODE_DELETE_UNVERIFIED, r#type=AUDIT_TYPE_INDEX, path = path.to_string());
+                    audit_delete!(
+                        self,
+                        mode = AUDIT_MODE_DELETE_UNVERIFIED,
+                        r#type = AUDIT_TYPE_INDEX,
+                        path = path.to_string()
+                    );
                     return Ok(Some(path));
                 } else if inspection
                     .verified_files
                     .index_uuids
                     .contains(uuid.as_ref())
                 {
-                    info!(target: TRACE_FILE_AUDIT, mode=AUDIT_MODE_DELETE, r#type=AUDIT_TYPE_INDEX, path = path.to_string());
+                    audit_delete!(
+                        self,
+                        mode = AUDIT_MODE_DELETE,
+                        r#type = AUDIT_TYPE_INDEX,
+                        path = path.to_string()
+                    );
                     return Ok(Some(path));
                 }
             } else {
@@ -514,14 +759,24 @@ impl<'a> CleanupTask<'a> {
                     {
                         Ok(None)
                     } else if !maybe_in_progress {
-                        info!(target: TRACE_FILE_AUDIT, mode=AUDIT_MODE_DELETE_UNVERIFIED, r#type=AUDIT_TYPE_DATA, path = path.to_string());
+                        audit_delete!(
+                            self,
+                            mode = AUDIT_MODE_DELETE_UNVERIFIED,
+                            r#type = AUDIT_TYPE_DATA,
+                            path = path.to_string()
+                        );
                         Ok(Some(path))
                     } else if inspection
                         .verified_files
                         .data_paths
                         .contains(&relative_path)
                     {
-                        info!(target: TRACE_FILE_AUDIT, mode=AUDIT_MODE_DELETE, r#type=AUDIT_TYPE_DATA, path = path.to_string());
+                        audit_delete!(
+                            self,
+                            mode = AUDIT_MODE_DELETE,
+                            r#type = AUDIT_TYPE_DATA,
+                            path = path.to_string()
+                        );
                         Ok(Some(path))
                     } else {
                         Ok(None)
@@ -587,14 +842,24 @@ impl<'a> CleanupTask<'a> {
                 {
                     Ok(None)
                 } else if !maybe_in_progress {
-                    info!(target: TRACE_FILE_AUDIT, mode=AUDIT_MODE_DELETE_UNVERIFIED, r#type=AUDIT_TYPE_DATA, path = path.to_string());
+                    audit_delete!(
+                        self,
+                        mode = AUDIT_MODE_DELETE_UNVERIFIED,
+                        r#type = AUDIT_TYPE_DATA,
+                        path = path.to_string()
+                    );
                     Ok(Some(path))
                 } else if inspection
                     .verified_files
                     .data_paths
                     .contains(&parent_data_path)
                 {
-                    info!(target: TRACE_FILE_AUDIT, mode=AUDIT_MODE_DELETE, r#type=AUDIT_TYPE_DATA, path = path.to_string());
+                    audit_delete!(
+                        self,
+                        mode = AUDIT_MODE_DELETE,
+                        r#type = AUDIT_TYPE_DATA,
+                        path = path.to_string()
+                    );
                     Ok(Some(path))
                 } else {
                     Ok(None)
@@ -613,14 +878,24 @@ impl<'a> CleanupTask<'a> {
                     {
                         Ok(None)
                     } else if !maybe_in_progress {
-                        info!(target: TRACE_FILE_AUDIT, mode=AUDIT_MODE_DELETE_UNVERIFIED, r#type=AUDIT_TYPE_DELETION, path = path.to_string());
+                        audit_delete!(
+                            self,
+                            mode = AUDIT_MODE_DELETE_UNVERIFIED,
+                            r#type = AUDIT_TYPE_DELETION,
+                            path = path.to_string()
+                        );
                         Ok(Some(path))
                     } else if inspection
                         .verified_files
                         .delete_paths
                         .contains(&relative_path)
                     {
-                        info!(target: TRACE_FILE_AUDIT, mode=AUDIT_MODE_DELETE, r#type=AUDIT_TYPE_DELETION, path = path.to_string());
+                        audit_delete!(
+                            self,
+                            mode = AUDIT_MODE_DELETE,
+                            r#type = AUDIT_TYPE_DELETION,
+                            path = path.to_string()
+                        );
                         Ok(Some(path))
                     } else {
                         Ok(None)
@@ -868,14 +1143,7 @@ impl<'a> CleanupTask<'a> {
     }
 }
 
-fn calculate_duration(scheme: String, rate: u64) -> Duration {
-    let batch_size = if scheme.to_lowercase().contains("s3") {
-        S3_DELETE_STREAM_BATCH_SIZE
-    } else if scheme.to_lowercase().contains("az") {
-        AZURE_DELETE_STREAM_BATCH_SIZE
-    } else {
-        1
-    };
+fn calculate_duration(batch_size: u64, rate: u64) -> Duration {
     let effective_rate = rate.max(1);
     let path_rate = effective_rate * batch_size;
     info!(
@@ -905,6 +1173,21 @@ pub struct CleanupPolicy {
     /// On stores with bulk delete, each request can include multiple paths.
     /// For example, `Some(100)` limits deletions to 100 delete requests per second.
     pub delete_rate_limit: Option<u64>,
+    /// Maximum number of delete requests in flight. If None, the object store's
+    /// IO parallelism is used.
+    pub delete_concurrency: Option<usize>,
+    /// If not none, always keep the latest `n` versions, however old they are.
+    ///
+    /// A version is kept if any rule keeps it: this, the age and version
+    /// cutoffs above, or a tag or branch referencing it.
+    pub keep_last_n_versions: Option<usize>,
+    /// If true, delete nothing and only report in the returned stats what
+    /// would have been removed.
+    pub dry_run: bool,
+    /// If true, also delete data files referenced by absolute URIs, which may be
+    /// on other object stores than the dataset, once no retained version
+    /// references them. These files are left alone by default.
+    pub delete_foreign_files: bool,
 }
 
 impl CleanupPolicy {
@@ -929,6 +1212,10 @@ impl Default for CleanupPolicy {
             error_if_tagged_old_versions: true,
             clean_referenced_branches: false,
             delete_rate_limit: None,
+            delete_concurrency: None,
+            keep_last_n_versions: None,
+            dry_run: false,
+            delete_foreign_files: false,
         }
     }
 }
@@ -1001,6 +1288,66 @@ impl CleanupPolicyBuilder {
         Ok(self)
     }
 
+    /// Limit the number of delete requests in flight during cleanup.
+    ///
+    /// By default (None), this is the object store's IO parallelism.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `concurrency` is zero.
+    pub fn delete_concurrency(mut self, concurrency: usize) -> Result<Self> {
+        if concurrency == 0 {
+            return Err(Error::Cleanup {
+                message: format!(
+                    "delete_concurrency must be greater than 0, got {}",
+                    concurrency
+                ),
+            });
+        }
+        self.policy.delete_concurrency = Some(concurrency);
+        Ok(self)
+    }
+
+    /// Always keep the latest `n` versions, even if they are older than the
+    /// cutoff.
+    ///
+    /// Unlike [`Self::retain_n_versions`], which turns `n` into a version cutoff
+    /// that must hold along with the others, this keeps the latest `n` versions
+    /// in addition to everything the other rules keep.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `n` is zero.
+    pub fn keep_last_n_versions(mut self, n: usize) -> Result<Self> {
+        if n == 0 {
+            return Err(Error::Cleanup {
+                message: format!("keep_last_n_versions must be greater than 0, got {}", n),
+            });
+        }
+        self.policy.keep_last_n_versions = Some(n);
+        Ok(self)
+    }
+
+    /// Only report what would be removed, without deleting anything.
+    ///
+    /// The returned [`RemovalStats`] list the versions and count the files and
+    /// bytes that a real run with the same policy would remove. Referenced
+    /// branches are not cleaned in a dry run.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.policy.dry_run = dry_run;
+        self
+    }
+
+    /// Allow deleting data files referenced by absolute URIs.
+    ///
+    /// Such files may be on other object stores, possibly shared with other
+    /// datasets, so by default cleanup never deletes them. When allowed, a file is
+    /// deleted once it is referenced by an old version and no retained version.
+    pub fn delete_foreign_files(mut self, delete: bool) -> Self {
+        self.policy.delete_foreign_files = delete;
+        self
+    }
+
     pub fn build(self) -> CleanupPolicy {
         self.policy
     }
@@ -1144,6 +1491,20 @@ pub async fn build_cleanup_policy(
             Err(e) => return Err(e),
         };
     }
+    if let Some(delete_concurrency) = manifest.config.get("lance.auto_cleanup.delete_concurrency") {
+        let concurrency: usize = match delete_concurrency.parse() {
+            Ok(c) => c,
+            Err(e) => {
+                return Err(Error::Cleanup {
+                    message: format!(
+                        "Error encountered while parsing lance.auto_cleanup.delete_concurrency as usize: {}",
+                        e
+                    ),
+                });
+            }
+        };
+        builder = builder.delete_concurrency(concurrency)?;
+    }
 
     Ok(Some(builder.build()))
 }
@@ -1190,7 +1551,7 @@ mod tests {
     use all_asserts::{assert_gt, assert_lt};
     use arrow::compute;
     use arrow_array::{
-        Int32Array, RecordBatch, RecordBatchIterator, RecordBatchReader, UInt64Array,
+        Float32Array, Int32Array, RecordBatch, RecordBatchIterator, RecordBatchReader, UInt64Array,
     };
     use arrow_schema::{DataType, Field, Schema as ArrowSchema};
     use datafusion::common::assert_contains;
@@ -1472,6 +1833,18 @@ mod tests {
             Ok(Box::new(ds))
         }
 
+        async fn open_leased(&self, version: u64, ttl: Duration) -> Result<Dataset> {
+            DatasetBuilder::from_uri(&self.dataset_path)
+                .with_read_params(ReadParams {
+                    store_options: Some(self.os_params()),
+                    ..Default::default()
+                })
+                .with_version(version)
+                .with_read_lease(ttl)
+                .load()
+                .await
+        }
+
         // Load the fixture's dataset.
         async fn load(&self) -> Result<Dataset> {
             self.load_dataset(&self.dataset_path).await
@@ -2145,6 +2518,66 @@ mod tests {
         assert_eq!(after_count.num_tx_files, 1);
     }
 
+    #[tokio::test]
+    async fn cleanup_dropped_index() {
+        let fixture = MockDatasetFixture::try_new().unwrap();
+        fixture.create_some_data().await.unwrap();
+        fixture.create_some_index().await.unwrap();
+        let indexed_version = fixture.open().await.unwrap().version().version;
+        MockClock::set_system_time(TimeDelta::try_days(10).unwrap().to_std().unwrap());
+        fixture
+            .open()
+            .await
+            .unwrap()
+            .drop_index("some_index")
+            .await
+            .unwrap();
+
+        let before_count = fixture.count_files().await.unwrap();
+        assert_eq!(before_count.num_index_files, 2);
+
+        // Versions inside the retention window still reference the index, so
+        // its files stay and time travel to before the drop keeps working.
+        let removed = fixture
+            .run_cleanup(utc_now() - TimeDelta::try_days(20).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(removed.index_files_removed, 0);
+        let old = fixture
+            .open()
+            .await
+            .unwrap()
+            .checkout_version(indexed_version)
+            .await
+            .unwrap();
+        assert_eq!(old.load_indices().await.unwrap().len(), 1);
+        let mut scanner = old.scan();
+        scanner
+            .nearest("indexable", &Float32Array::from(vec![0.5; 4]), 3)
+            .unwrap();
+        assert!(
+            scanner
+                .explain_plan(false)
+                .await
+                .unwrap()
+                .contains("ANNSubIndex")
+        );
+        assert_eq!(scanner.try_into_batch().await.unwrap().num_rows(), 3);
+
+        let removed = fixture
+            .run_cleanup(utc_now() - TimeDelta::try_days(8).unwrap())
+            .await
+            .unwrap();
+        let after_count = fixture.count_files().await.unwrap();
+        assert_eq!(removed.index_files_removed, 2);
+        assert_eq!(after_count.num_index_files, 0);
+        assert_eq!(
+            removed.bytes_removed,
+            before_count.num_bytes - after_count.num_bytes
+        );
+        assert_eq!(fixture.count_rows().await.unwrap(), 512);
+    }
+
     #[tokio::test]
     async fn clean_old_delete_files() {
         let fixture = MockDatasetFixture::try_new().unwrap();
@@ -2499,18 +2932,17 @@ mod tests {
         assert_eq!(before_count.num_data_files, 2);
         assert_eq!(before_count.num_manifest_files, 2);
 
-        assert!(
-            fixture
-                .run_cleanup(utc_now() - TimeDelta::try_days(7).unwrap())
-                .await
-                .is_err()
-        );
+        // Failed deletes are reported in the stats rather than as an error.
+        // Data files are deleted before manifests, so the data file is gone.
+        let stats = fixture
+            .run_cleanup(utc_now() - TimeDelta::try_days(7).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(stats.old_versions, 0);
+        assert_eq!(stats.data_files_removed, 1);
+        assert_eq!(stats.failed_paths.len(), 1);
+        assert_eq!(stats.failed_paths[0].extension(), Some("manifest"));
 
-        // This test currently relies on us sending in manifest files after
-        // data files.  Also, the delete process is run in parallel.  However,
-        // it seems stable to stably delete the data file even though the manifest delete fails.
-        // My guess is that it is not possible to interrupt a task in flight and so it still
-        // has to finish the buffered tasks even if they are ignored.
         let mid_count = fixture.count_files().await.unwrap();
         assert_eq!(mid_count.num_data_files, 1);
         assert_eq!(mid_count.num_manifest_files, 2);
@@ -2533,6 +2965,83 @@ mod tests {
         assert_eq!(after_count.num_manifest_files, 1);
     }
 
+    #[tokio::test]
+    async fn failed_file_deletes_keep_manifests() {
+        let fixture = MockDatasetFixture::try_new().unwrap();
+        fixture.create_some_data().await.unwrap();
+        fixture.overwrite_some_data().await.unwrap();
+        fixture.overwrite_some_data().await.unwrap();
+        MockClock::set_system_time(TimeDelta::try_days(10).unwrap().to_std().unwrap());
+
+        fixture.mock_store.policy.lock().unwrap().set_before_policy(
+            "block_delete_data",
+            Arc::new(|op, path| -> Result<()> {
+                if op.contains("delete") && path.extension() == Some("lance") {
+                    Err(Error::internal("Delete data blocked".to_string()))
+                } else {
+                    Ok(())
+                }
+            }),
+        );
+
+        let before_count = fixture.count_files().await.unwrap();
+        let policy = CleanupPolicyBuilder::default()
+            .before_timestamp(utc_now() - TimeDelta::try_days(7).unwrap())
+            .delete_concurrency(2)
+            .unwrap()
+            .build();
+        let stats = fixture
+            .run_cleanup_with_policy(policy.clone())
+            .await
+            .unwrap();
+
+        // The old data files are reported and the manifests that reference
+        // them are kept.
+        assert_eq!(stats.data_files_removed, 0);
+        assert_eq!(stats.old_versions, 0);
+        assert_eq!(stats.failed_paths.len(), 2);
+        assert!(
+            stats
+                .failed_paths
+                .iter()
+                .all(|path| path.extension() == Some("lance"))
+        );
+        let mid_count = fixture.count_files().await.unwrap();
+        assert_eq!(mid_count.num_data_files, before_count.num_data_files);
+        assert_eq!(
+            mid_count.num_manifest_files,
+            before_count.num_manifest_files
+        );
+        assert_eq!(
+            stats.bytes_removed,
+            before_count.num_bytes - mid_count.num_bytes
+        );
+
+        fixture
+            .mock_store
+            .policy
+            .lock()
+            .unwrap()
+            .clear_before_policy("block_delete_data");
+
+        let stats = fixture.run_cleanup_with_policy(policy).await.unwrap();
+        assert!(stats.failed_paths.is_empty());
+        assert_eq!(stats.data_files_removed, 2);
+        assert_eq!(stats.old_versions, 2);
+        let after_count = fixture.count_files().await.unwrap();
+        assert_eq!(after_count.num_data_files, 1);
+        assert_eq!(after_count.num_manifest_files, 1);
+    }
+
+    #[test]
+    fn test_delete_concurrency_must_be_positive() {
+        assert!(
+            CleanupPolicyBuilder::default()
+                .delete_concurrency(0)
+                .is_err()
+        );
+    }
+
     #[tokio::test]
     async fn cleanup_and_retain_3_recent_versions() {
         let fixture = MockDatasetFixture::try_new().unwrap();
@@ -2621,6 +3130,182 @@ mod tests {
         assert_eq!(after_count.num_manifest_files, 3);
     }
 
+    // Five versions, one written every day, the last one now
+    async fn create_daily_versions(fixture: &MockDatasetFixture) {
+        fixture.create_some_data().await.unwrap();
+        for day in 1..5 {
+            MockClock::set_system_time(TimeDelta::try_days(day).unwrap().to_std().unwrap());
+            fixture.overwrite_some_data().await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn cleanup_keep_last_n_versions_and_older_than() {
+        let fixture = MockDatasetFixture::try_new().unwrap();
+        create_daily_versions(&fixture).await;
+        let before = utc_now() - TimeDelta::try_hours(36).unwrap();
+
+        // Versions 1 to 3 are older than the cutoff but 2 and 3 are among the last 4
+        let policy = CleanupPolicyBuilder::default()
+            .before_timestamp(before)
+            .keep_last_n_versions(4)
+            .unwrap()
+            .build();
+        let removed = fixture.run_cleanup_with_policy(policy).await.unwrap();
+        assert_eq!(removed.old_versions, 1);
+        assert_eq!(removed.removed_versions, vec![1]);
+
+        // Version 4 is the only one of the last 2 that is old, the cutoff keeps it
+        let policy = CleanupPolicyBuilder::default()
+            .before_timestamp(before)
+            .keep_last_n_versions(2)
+            .unwrap()
+            .build();
+        let removed = fixture.run_cleanup_with_policy(policy).await.unwrap();
+        assert_eq!(removed.removed_versions, vec![2, 3]);
+
+        let after_count = fixture.count_files().await.unwrap();
+        assert_eq!(after_count.num_manifest_files, 2);
+        assert_eq!(after_count.num_data_files, 2);
+    }
+
+    #[tokio::test]
+    async fn cleanup_keep_more_versions_than_exist() {
+        let fixture = MockDatasetFixture::try_new().unwrap();
+        create_daily_versions(&fixture).await;
+
+        let policy = CleanupPolicyBuilder::default()
+            .before_timestamp(utc_now())
+            .keep_last_n_versions(10)
+            .unwrap()
+            .build();
+        let removed = fixture.run_cleanup_with_policy(policy).await.unwrap();
+        assert_eq!(removed.old_versions, 0);
+        assert!(removed.removed_versions.is_empty());
+        assert_eq!(fixture.count_files().await.unwrap().num_manifest_files, 5);
+
+        assert!(matches!(
+            CleanupPolicyBuilder::default().keep_last_n_versions(0),
+            Err(Error::Cleanup { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn cleanup_dry_run_matches_real_run() {
+        let fixture = MockDatasetFixture::try_new().unwrap();
+        create_daily_versions(&fixture).await;
+        let before_count = fixture.count_files().await.unwrap();
+
+        let builder = || {
+            CleanupPolicyBuilder::default()
+                .before_timestamp(utc_now() - TimeDelta::try_hours(36).unwrap())
+                .keep_last_n_versions(3)
+                .unwrap()
+        };
+        let dry_run = fixture
+            .run_cleanup_with_policy(builder().dry_run(true).build())
+            .await
+            .unwrap();
+        assert_eq!(dry_run.removed_versions, vec![1, 2]);
+        assert_gt!(dry_run.bytes_removed, 0);
+
+        // Nothing was deleted
+        let after_dry_run = fixture.count_files().await.unwrap();
+        assert_eq!(after_dry_run.num_bytes, before_count.num_bytes);
+        assert_eq!(
+            after_dry_run.num_manifest_files,
+            before_count.num_manifest_files
+        );
+
+        let removed = fixture
+            .run_cleanup_with_policy(builder().build())
+            .await
+            .unwrap();
+        let after_count = fixture.count_files().await.unwrap();
+        assert_eq!(
+            removed.bytes_removed,
+            before_count.num_bytes - after_count.num_bytes
+        );
+        assert_eq!(dry_run.bytes_removed, removed.bytes_removed);
+        assert_eq!(dry_run.removed_versions, removed.removed_versions);
+        assert_eq!(dry_run.old_versions, removed.old_versions);
+        assert_eq!(dry_run.data_files_removed, removed.data_files_removed);
+        assert_eq!(
+            dry_run.transaction_files_removed,
+            removed.transaction_files_removed
+        );
+    }
+
+    #[tokio::test]
+    async fn cleanup_keeps_leased_versions() {
+        let fixture = MockDatasetFixture::try_new().unwrap();
+        create_daily_versions(&fixture).await;
+        let leased = fixture
+            .open_leased(2, Duration::from_secs(3600))
+            .await
+            .unwrap();
+
+        let removed = fixture.run_cleanup(utc_now()).await.unwrap();
+        assert_eq!(removed.removed_versions, vec![1, 3, 4]);
+        assert_eq!(removed.leased_versions, vec![2]);
+        // The reader can still scan its version
+        let batch = leased.scan().try_into_batch().await.unwrap();
+        assert_eq!(batch.num_rows(), 512);
+
+        // A lease that is no longer renewed, e.g. because the reader died, stops
+        // protecting the version once it expires, and is removed
+        MockClock::set_system_time(
+            (TimeDelta::try_days(4).unwrap() + TimeDelta::try_hours(3).unwrap())
+                .to_std()
+                .unwrap(),
+        );
+        let removed = fixture.run_cleanup(utc_now()).await.unwrap();
+        assert_eq!(removed.removed_versions, vec![2]);
+        assert!(removed.leased_versions.is_empty());
+        assert!(
+            list_leases(&leased.object_store, &leased.base)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn lease_churn_does_not_block_commits() {
+        let fixture = MockDatasetFixture::try_new().unwrap();
+        fixture.create_some_data().await.unwrap();
+
+        let appends = async {
+            for _ in 0..5 {
+                fixture.append_some_data().await.unwrap();
+            }
+        };
+        let churn = async {
+            for _ in 0..20 {
+                let leased = fixture
+                    .open_leased(1, Duration::from_millis(5))
+                    .await
+                    .unwrap();
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                drop(leased);
+            }
+        };
+        tokio::join!(appends, churn);
+
+        let dataset = fixture.open().await.unwrap();
+        assert_eq!(dataset.version().version, 6);
+        assert_eq!(dataset.count_rows(None).await.unwrap(), 6 * 512);
+
+        // Every lease was released when its dataset was dropped
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(
+            list_leases(&dataset.object_store, &dataset.base)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
     #[tokio::test]
     async fn cleanup_preserves_unmanaged_dirs_and_files() {
         // Ensure cleanup does not delete unmanaged directories/files under the dataset root
@@ -3791,21 +4476,20 @@ mod tests {
     fn test_calculate_duration_s3() {
         // Normal case: duration is computed from S3 batch size and configured rate.
         let normal_rate = 100;
-        let expected_duration_ns =
-            1_000_000_000u64.div_ceil(normal_rate * S3_DELETE_STREAM_BATCH_SIZE);
+        let expected_duration_ns = 1_000_000_000u64.div_ceil(normal_rate * 1_000);
         assert_eq!(
-            calculate_duration("s3".to_string(), normal_rate),
+            calculate_duration(1_000, normal_rate),
             Duration::from_nanos(expected_duration_ns)
         );
 
         // Edge case: rate too small should be clamped to 1.
-        let min_rate_duration = calculate_duration("s3".to_string(), 1);
-        assert_eq!(calculate_duration("s3".to_string(), 0), min_rate_duration);
+        let min_rate_duration = calculate_duration(1_000, 1);
+        assert_eq!(calculate_duration(1_000, 0), min_rate_duration);
 
         // Edge case: computed duration_ns too small should be clamped to at least 1ns.
         let very_large_rate = 2_000_000;
         assert_eq!(
-            calculate_duration("s3".to_string(), very_large_rate),
+            calculate_duration(1_000, very_large_rate),
             Duration::from_nanos(1)
         );
     }
@@ -0,0 +1,60 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Which versions of Lance can read a dataset.
+
+use std::fmt;
+
+use lance_table::feature_flags::{ReaderFeature, reader_features};
+
+use super::Dataset;
+use crate::Result;
+
+/// The reader features a dataset uses, see [`Dataset::compatibility_report`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompatibilityReport {
+    /// The features readers must support, ordered by level.
+    pub features: Vec<ReaderFeature>,
+}
+
+impl CompatibilityReport {
+    /// The lowest reader feature level that can read the dataset.
+    pub fn min_reader_feature_level(&self) -> u32 {
+        self.features.last().map_or(0, |feature| feature.level)
+    }
+
+    /// The first version of Lance that can read the dataset, or `None` if any
+    /// version can.
+    pub fn min_version(&self) -> Option<&str> {
+        self.features
+            .last()
+            .map(|feature| feature.min_version.as_str())
+    }
+}
+
+impl fmt::Display for CompatibilityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for feature in &self.features {
+            writeln!(
+                f,
+                "{}: level {}, introduced in {}",
+                feature.name, feature.level, feature.min_version
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Dataset {
+    /// The reader features used by the current version of the dataset and the
+    /// version of Lance that introduced each.
+    ///
+    /// The features are derived from the contents of the manifest, so the
+    /// report also covers versions written before the features were recorded
+    /// in the manifest.
+    pub fn compatibility_report(&self) -> Result<CompatibilityReport> {
+        Ok(CompatibilityReport {
+            features: reader_features(&self.manifest)?,
+        })
+    }
+}
@@ -1,18 +1,60 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-FileCopyrightText: Copyright The Lance Authors
 
-use super::transaction::Transaction;
+use std::sync::Arc;
+
+use super::rowids::load_row_id_sequence;
+use super::transaction::{Operation, Transaction};
 use crate::Dataset;
 use crate::Result;
-use crate::dataset::scanner::DatasetRecordBatchStream;
+use crate::dataset::scanner::{
+    BATCH_SIZE_FALLBACK, DatasetRecordBatchStream, get_default_batch_size,
+};
+use arrow_array::cast::AsArray;
+use arrow_array::types::UInt64Type;
+use arrow_array::{ArrayRef, RecordBatch, StringArray, UInt64Array, new_null_array};
+use arrow_schema::{DataType, Field as ArrowField, Schema as ArrowSchema, SchemaRef};
 use chrono::{DateTime, Utc};
+use datafusion::error::DataFusionError;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
 use futures::stream::{self, StreamExt, TryStreamExt};
 use lance_core::Error;
 use lance_core::ROW_CREATED_AT_VERSION;
 use lance_core::ROW_ID;
 use lance_core::ROW_LAST_UPDATED_AT_VERSION;
 use lance_core::WILDCARD;
+use lance_core::datatypes::Schema;
+use lance_core::error::LanceOptionExt;
 use lance_core::utils::tokio::get_num_compute_intensive_cpus;
+use roaring::RoaringTreemap;
+
+/// The column of [`DatasetDelta::get_changes`] holding the [`ChangeType`] of each row.
+pub const CHANGE_TYPE_COLUMN: &str = "_change_type";
+
+/// The kind of a row-level change reported by [`DatasetDelta::get_changes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeType {
+    /// The row was inserted, with its values at the end version.
+    Insert,
+    /// The row was deleted, with its values at the begin version.
+    Delete,
+    /// The row was updated, with its values at the begin version.
+    UpdatePre,
+    /// The row was updated, with its values at the end version.
+    UpdatePost,
+}
+
+impl ChangeType {
+    /// The value stored in [`CHANGE_TYPE_COLUMN`].
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Insert => "insert",
+            Self::Delete => "delete",
+            Self::UpdatePre => "update_pre",
+            Self::UpdatePost => "update_post",
+        }
+    }
+}
 
 /// Builder for creating a [`DatasetDelta`] to explore changes between dataset versions.
 ///
@@ -448,11 +490,268 @@ impl DatasetDelta {
             inserted_row_filter, updated_rows_filter
         ))
     }
+
+    /// Get the row-level changes between the two versions.
+    ///
+    /// Each output row has a `_change_type` (see [`ChangeType`]), the `_rowid` of the
+    /// row, and the requested `columns`, all columns of the end version if `None`.
+    /// Inserted rows carry their values at the end version and deleted rows their
+    /// values at the begin version. An updated row produces an `update_pre` row
+    /// immediately followed by an `update_post` row. A row both inserted and deleted
+    /// within the range produces nothing.
+    ///
+    /// Changes are computed from the row id sequences and deletion vectors of the two
+    /// versions, and the `_row_last_updated_at_version` of rows present in both, so only
+    /// the changed rows are read. Versions whose transactions only rewrite data, like
+    /// compaction, produce no changes. Columns missing from the begin version, for
+    /// example added in between, are null in `delete` and `update_pre` rows.
+    ///
+    /// Requires stable row ids. Rows are grouped by change type: inserts, then updates,
+    /// then deletes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use lance::{Dataset, Result};
+    /// # use futures::TryStreamExt;
+    /// # async fn example(dataset: &Dataset, previous_version: u64) -> Result<()> {
+    /// let delta = dataset.delta()
+    ///     .compared_against_version(previous_version)
+    ///     .build()?;
+    /// let mut changes = delta.get_changes(Some(&["id", "value"])).await?;
+    /// while let Some(batch) = changes.try_next().await? {
+    ///     // Process batch...
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_changes(&self, columns: Option<&[&str]>) -> Result<DatasetRecordBatchStream> {
+        let (begin_version, end_version) = self.resolve_range().await?;
+        if begin_version > end_version {
+            return Err(Error::invalid_input(format!(
+                "Begin version {begin_version} is after end version {end_version}"
+            )));
+        }
+        // Version 0 is the empty dataset before the first commit.
+        let begin = if begin_version == 0 {
+            None
+        } else {
+            Some(Arc::new(
+                self.base_dataset.checkout_version(begin_version).await?,
+            ))
+        };
+        let end = Arc::new(self.base_dataset.checkout_version(end_version).await?);
+        if !end.manifest.uses_stable_row_ids()
+            || begin
+                .as_ref()
+                .is_some_and(|begin| !begin.manifest.uses_stable_row_ids())
+        {
+            return Err(Error::invalid_input(
+                "Row-level changes require a dataset with stable row ids",
+            ));
+        }
+
+        let projection = match columns {
+            Some(columns) => end.schema().project(columns)?,
+            None => end.schema().clone(),
+        };
+        let schema = changes_schema(&projection, begin.as_deref());
+
+        let (inserted, updated, deleted) = if self.only_rewrites(begin_version, end_version).await {
+            Default::default()
+        } else {
+            let begin_ids = match &begin {
+                Some(begin) => live_row_ids(begin).await?,
+                None => RoaringTreemap::new(),
+            };
+            let end_ids = live_row_ids(&end).await?;
+            let updated = if begin.is_some() {
+                updated_row_ids(&end, begin_version).await? & &begin_ids
+            } else {
+                RoaringTreemap::new()
+            };
+            (&end_ids - &begin_ids, updated, &begin_ids - &end_ids)
+        };
+
+        let batch_size = get_default_batch_size().unwrap_or(BATCH_SIZE_FALLBACK) as u64;
+        let chunks = |change_type: ChangeType, row_ids: RoaringTreemap| {
+            let row_ids = row_ids.into_iter().collect::<Vec<_>>();
+            row_ids
+                .chunks(batch_size as usize)
+                .map(|chunk| (change_type, chunk.to_vec()))
+                .collect::<Vec<_>>()
+        };
+        let mut tasks = chunks(ChangeType::Insert, inserted);
+        tasks.extend(chunks(ChangeType::UpdatePre, updated));
+        tasks.extend(chunks(ChangeType::Delete, deleted));
+
+        let stream_schema = schema.clone();
+        let stream = stream::iter(tasks)
+            .map(move |(change_type, row_ids)| {
+                let begin = begin.clone();
+                let end = end.clone();
+                let projection = projection.clone();
+                let schema = stream_schema.clone();
+                async move {
+                    let read = |dataset: Arc<Dataset>, change_type| {
+                        take_changes(dataset, &row_ids, &projection, &schema, change_type)
+                    };
+                    match change_type {
+                        ChangeType::Insert => read(end, ChangeType::Insert).await,
+                        ChangeType::Delete => read(begin.expect_ok()?, ChangeType::Delete).await,
+                        // Updates are scheduled once and produce both rows.
+                        ChangeType::UpdatePre | ChangeType::UpdatePost => {
+                            let pre = read(begin.expect_ok()?, ChangeType::UpdatePre).await?;
+                            let post = read(end, ChangeType::UpdatePost).await?;
+                            let indices = (0..row_ids.len())
+                                .flat_map(|row| [(0, row), (1, row)])
+                                .collect::<Vec<_>>();
+                            Ok(arrow_select::interleave::interleave_record_batch(
+                                &[&pre, &post],
+                                &indices,
+                            )?)
+                        }
+                    }
+                }
+            })
+            .buffered(get_num_compute_intensive_cpus())
+            .map_err(DataFusionError::from);
+        Ok(DatasetRecordBatchStream::new(Box::pin(
+            RecordBatchStreamAdapter::new(schema, stream),
+        )))
+    }
+
+    /// Whether every version in `(begin_version, end_version]` only rewrote data
+    /// without changing rows, so there are no row-level changes.
+    ///
+    /// Returns false if any transaction can not be read.
+    async fn only_rewrites(&self, begin_version: u64, end_version: u64) -> bool {
+        let delta = Self {
+            begin_version,
+            end_version,
+            base_dataset: self.base_dataset.clone(),
+            begin_timestamp: None,
+            end_timestamp: None,
+        };
+        let Ok(transactions) = delta.list_transactions().await else {
+            return false;
+        };
+        transactions.len() as u64 == end_version - begin_version
+            && transactions.iter().all(|transaction| {
+                matches!(
+                    transaction.operation,
+                    Operation::Rewrite { .. }
+                        | Operation::CreateIndex { .. }
+                        | Operation::ReserveFragments { .. }
+                )
+            })
+    }
+}
+
+/// The schema of [`DatasetDelta::get_changes`]: the change type, the row id and the
+/// projected columns. Columns missing from `begin` are made nullable.
+fn changes_schema(projection: &Schema, begin: Option<&Dataset>) -> SchemaRef {
+    let mut fields = vec![
+        ArrowField::new(CHANGE_TYPE_COLUMN, DataType::Utf8, false),
+        ArrowField::new(ROW_ID, DataType::UInt64, false),
+    ];
+    for field in ArrowSchema::from(projection).fields() {
+        let in_begin = begin.is_none_or(|begin| begin.schema().field(field.name()).is_some());
+        fields.push(
+            field
+                .as_ref()
+                .clone()
+                .with_nullable(field.is_nullable() || !in_begin),
+        );
+    }
+    Arc::new(ArrowSchema::new(fields))
+}
+
+/// The row ids of all rows in `dataset` that are not deleted.
+async fn live_row_ids(dataset: &Dataset) -> Result<RoaringTreemap> {
+    let fragments = dataset.get_fragments();
+    let row_ids = stream::iter(fragments)
+        .map(|fragment| async move {
+            let mut sequence = load_row_id_sequence(dataset, fragment.metadata())
+                .await?
+                .as_ref()
+                .clone();
+            if let Some(deletion_vector) = fragment.get_deletion_vector().await? {
+                sequence.mask(deletion_vector.to_sorted_iter())?;
+            }
+            Ok::<_, Error>(sequence.iter().collect::<RoaringTreemap>())
+        })
+        .buffer_unordered(dataset.object_store.io_parallelism())
+        .try_collect::<Vec<_>>()
+        .await?;
+    Ok(row_ids
+        .into_iter()
+        .fold(RoaringTreemap::new(), |acc, ids| acc | ids))
+}
+
+/// The row ids of rows in `dataset` that existed at, and were updated after,
+/// `begin_version`.
+async fn updated_row_ids(dataset: &Dataset, begin_version: u64) -> Result<RoaringTreemap> {
+    let mut scanner = dataset.scan();
+    scanner.project(&[ROW_ID, ROW_CREATED_AT_VERSION, ROW_LAST_UPDATED_AT_VERSION])?;
+    scanner.filter(&format!(
+        "_row_created_at_version <= {begin_version} AND _row_last_updated_at_version > {begin_version}"
+    ))?;
+    let mut row_ids = RoaringTreemap::new();
+    let mut stream = scanner.try_into_stream().await?;
+    while let Some(batch) = stream.try_next().await? {
+        let ids = batch[ROW_ID].as_primitive::<UInt64Type>();
+        row_ids.extend(ids.values().iter().copied());
+    }
+    Ok(row_ids)
+}
+
+/// Read the projected columns of `row_ids` from `dataset` as a batch of `schema`.
+///
+/// Columns `dataset` does not have are filled with nulls, and columns whose type
+/// changed since are cast to the type in `schema`.
+async fn take_changes(
+    dataset: Arc<Dataset>,
+    row_ids: &[u64],
+    projection: &Schema,
+    schema: &SchemaRef,
+    change_type: ChangeType,
+) -> Result<RecordBatch> {
+    let available = projection
+        .fields
+        .iter()
+        .filter(|field| dataset.schema().field(&field.name).is_some())
+        .map(|field| field.name.as_str())
+        .collect::<Vec<_>>();
+    let values = if available.is_empty() {
+        None
+    } else {
+        let projection = dataset.schema().project(&available)?;
+        Some(dataset.take_rows(row_ids, projection).await?)
+    };
+
+    let mut columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from(vec![change_type.as_str(); row_ids.len()])),
+        Arc::new(UInt64Array::from(row_ids.to_vec())),
+    ];
+    for field in schema.fields().iter().skip(2) {
+        let column = values
+            .as_ref()
+            .and_then(|values| values.column_by_name(field.name()));
+        columns.push(match column {
+            Some(column) if column.data_type() == field.data_type() => column.clone(),
+            Some(column) => arrow_cast::cast(column, field.data_type())?,
+            None => new_null_array(field.data_type(), row_ids.len()),
+        });
+    }
+    Ok(RecordBatch::try_new(schema.clone(), columns)?)
 }
 
 #[cfg(test)]
 mod tests {
 
+    use super::{CHANGE_TYPE_COLUMN, ChangeType};
+    use crate::dataset::optimize::{CompactionOptions, compact_files};
     use crate::dataset::transaction::Operation;
     use crate::dataset::{Dataset, WriteParams};
     use arrow_array::cast::AsArray;
@@ -1601,4 +1900,156 @@ mod tests {
         // Should include transactions at v2 and v3
         assert_eq!(txs.len(), 2);
     }
+
+    /// Collect the change feed as `(change_type, _rowid, key, value)` tuples.
+    async fn collect_changes(ds: &Dataset, from: u64, to: u64) -> Vec<(String, u64, i32, String)> {
+        let batches: Vec<_> = ds
+            .changes(from, to, Some(&["key", "value"]))
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        let mut changes = vec![];
+        for batch in batches {
+            let change_types = batch[CHANGE_TYPE_COLUMN].as_string::<i32>();
+            let row_ids = batch[ROW_ID].as_primitive::<UInt64Type>();
+            let keys = batch["key"].as_primitive::<Int32Type>();
+            let values = batch["value"].as_string::<i32>();
+            for i in 0..batch.num_rows() {
+                changes.push((
+                    change_types.value(i).to_string(),
+                    row_ids.value(i),
+                    keys.value(i),
+                    values.value(i).to_string(),
+                ));
+            }
+        }
+        changes
+    }
+
+    fn change(
+        change_type: ChangeType,
+        row_id: u64,
+        key: i32,
+        value: &str,
+    ) -> (String, u64, i32, String) {
+        (
+            change_type.as_str().to_string(),
+            row_id,
+            key,
+            value.to_string(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_changes_append() {
+        let dir = lance_core::utils::tempfile::TempStrDir::default();
+        write_dataset_temp(&dir, 0, 3, 1, "a", true, false).await;
+        let ds = write_dataset_temp(&dir, 3, 2, 1, "b", true, true).await;
+        assert_eq!(ds.version().version, 2);
+
+        assert_eq!(
+            collect_changes(&ds, 1, 2).await,
+            vec![
+                change(ChangeType::Insert, 3, 3, "b"),
+                change(ChangeType::Insert, 4, 4, "b"),
+            ]
+        );
+        // From the beginning every live row is an insert.
+        assert_eq!(collect_changes(&ds, 0, 2).await.len(), 5);
+        assert!(collect_changes(&ds, 2, 2).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_changes_delete() {
+        let mut ds = create_test_dataset(5, 1, "a", true).await;
+        ds.delete("key < 2").await.unwrap();
+
+        assert_eq!(
+            collect_changes(&ds, 1, 2).await,
+            vec![
+                change(ChangeType::Delete, 0, 0, "a"),
+                change(ChangeType::Delete, 1, 1, "a"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_changes_update() {
+        let ds = create_test_dataset(5, 1, "a", true).await;
+        let mut ds = update_where(ds, "key = 1 OR key = 3", "b").await;
+        ds.delete("key = 4").await.unwrap();
+        let data = lance_datagen::gen_batch()
+            .col("key", array::step_custom::<Int32Type>(5, 1))
+            .col("value", array::fill_utf8("c".to_string()))
+            .into_reader_rows(RowCount::from(1), BatchCount::from(1));
+        ds.append(data, None).await.unwrap();
+        assert_eq!(ds.version().version, 4);
+
+        assert_eq!(
+            collect_changes(&ds, 1, 4).await,
+            vec![
+                change(ChangeType::Insert, 5, 5, "c"),
+                change(ChangeType::UpdatePre, 1, 1, "a"),
+                change(ChangeType::UpdatePost, 1, 1, "b"),
+                change(ChangeType::UpdatePre, 3, 3, "a"),
+                change(ChangeType::UpdatePost, 3, 3, "b"),
+                change(ChangeType::Delete, 4, 4, "a"),
+            ]
+        );
+        // Only the requested columns are returned.
+        let batch = collect_stream(ds.changes(1, 2, Some(&["value"])).await.unwrap()).await;
+        let names = batch
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec![CHANGE_TYPE_COLUMN, ROW_ID, "value"]);
+        assert_eq!(batch.num_rows(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_changes_across_compaction() {
+        let dir = lance_core::utils::tempfile::TempStrDir::default();
+        write_dataset_temp(&dir, 0, 3, 1, "a", true, false).await;
+        let mut ds = write_dataset_temp(&dir, 3, 3, 1, "a", true, true).await;
+        ds.delete("key = 0").await.unwrap();
+        let ds = update_where(ds, "key = 4", "b").await;
+        let before_compaction = ds.version().version;
+
+        let mut ds = ds;
+        let metrics = compact_files(&mut ds, CompactionOptions::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(metrics.fragments_removed, 3);
+        assert!(ds.version().version > before_compaction);
+
+        // Compaction moves rows without changing them.
+        assert!(
+            collect_changes(&ds, before_compaction, ds.version().version)
+                .await
+                .is_empty()
+        );
+        // A range spanning the compaction only reports the real changes.
+        assert_eq!(
+            collect_changes(&ds, 2, ds.version().version).await,
+            vec![
+                change(ChangeType::UpdatePre, 4, 4, "a"),
+                change(ChangeType::UpdatePost, 4, 4, "b"),
+                change(ChangeType::Delete, 0, 0, "a"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_changes_require_stable_row_ids() {
+        let mut ds = create_test_dataset(5, 1, "a", false).await;
+        ds.delete("key = 0").await.unwrap();
+        let Err(err) = ds.changes(1, 2, None).await else {
+            panic!("changes should fail without stable row ids");
+        };
+        assert!(err.to_string().contains("stable row ids"), "{err}");
+    }
 }
@@ -0,0 +1,658 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Export a version of a dataset to Parquet files
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use arrow_array::cast::AsArray;
+use arrow_array::{
+    Array, ArrayRef, FixedSizeListArray, Float32Array, LargeListArray, ListArray, RecordBatch,
+    StructArray, make_array,
+};
+use arrow_schema::{DataType, Field, FieldRef, Fields, Schema, SchemaRef};
+
+use futures::{StreamExt, TryStreamExt};
+use lance_arrow::bfloat16::{BFloat16Array, is_bfloat16_field};
+use lance_arrow::{ARROW_EXT_META_KEY, ARROW_EXT_NAME_KEY};
+use lance_io::object_store::{ObjectStore, ObjectStoreParams};
+use lance_io::stream::RecordBatchStream;
+use object_store::path::Path;
+use parquet::arrow::AsyncArrowWriter;
+use parquet::arrow::async_writer::ParquetObjectWriter;
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use super::fragment::FileFragment;
+use crate::{Dataset, Error, Result};
+
+/// The name of the file, in the export directory, that lists the exported files
+pub const PARQUET_EXPORT_MANIFEST: &str = "_lance_export.json";
+
+/// Options for [`Dataset::export_parquet`]
+#[derive(Debug, Clone, Default)]
+pub struct ParquetExportOptions {
+    /// Parameters used to open the object store the Parquet files are written to
+    pub store_params: ObjectStoreParams,
+    /// Start a new Parquet file once the current file of a fragment reaches this size
+    ///
+    /// By default every fragment is written to a single file.
+    pub target_file_size_bytes: Option<usize>,
+    /// Whether the `_rowid` column is added to the exported files
+    pub with_row_id: bool,
+    /// The number of rows in each batch read from the dataset
+    pub batch_size: Option<usize>,
+    /// The number of fragments that are exported at the same time
+    ///
+    /// Defaults to the I/O parallelism of the destination object store.
+    pub parallelism: Option<usize>,
+    /// The properties of the Parquet writer, e.g. the compression codec
+    pub writer_properties: Option<WriterProperties>,
+    /// Skip the fragments that an earlier, interrupted export of the same version
+    /// recorded in its [`PARQUET_EXPORT_MANIFEST`]
+    pub resume: bool,
+}
+
+/// The Parquet files written by [`Dataset::export_parquet`]
+///
+/// This is stored as JSON in the [`PARQUET_EXPORT_MANIFEST`] file of the export directory
+/// and updated as each fragment completes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParquetExportManifest {
+    /// The version of the dataset that was exported
+    pub dataset_version: u64,
+    /// Whether the files include the `_rowid` column
+    pub with_row_id: bool,
+    /// The fragments that have been exported
+    pub fragments: Vec<ExportedFragment>,
+}
+
+/// The Parquet files that one fragment was exported to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedFragment {
+    pub fragment_id: u64,
+    /// Paths of the Parquet files, relative to the export directory
+    ///
+    /// This is empty if every row of the fragment has been deleted.
+    pub files: Vec<String>,
+    /// The number of rows written, excluding deleted rows
+    pub num_rows: u64,
+}
+
+impl Dataset {
+    /// Export this version of the dataset to Parquet files in `dest_uri`
+    ///
+    /// Each fragment is written to its own file (or files, with
+    /// [`ParquetExportOptions::target_file_size_bytes`]) and fragments are exported in
+    /// parallel.  Deleted rows are skipped.  The schema metadata and the field metadata are
+    /// kept, except for Lance extension types which are written as their storage type.
+    /// Parquet has no bfloat16 type, so bfloat16 values (e.g. vectors stored as
+    /// `FixedSizeList<bfloat16>`) are widened to `Float32`.
+    ///
+    /// The files that have been written are listed in the [`PARQUET_EXPORT_MANIFEST`]
+    /// file, which is updated after every fragment.  If the export fails it can be
+    /// continued with [`ParquetExportOptions::resume`].
+    pub async fn export_parquet(
+        &self,
+        dest_uri: &str,
+        options: &ParquetExportOptions,
+    ) -> Result<ParquetExportManifest> {
+        let (store, dest) = ObjectStore::from_uri_and_params(
+            self.session.store_registry(),
+            dest_uri,
+            &options.store_params,
+        )
+        .await?;
+        let manifest_path = dest.clone().join(PARQUET_EXPORT_MANIFEST);
+        let dataset_version = self.version().version;
+
+        let mut manifest = None;
+        if options.resume && store.exists(&manifest_path).await? {
+            let existing: ParquetExportManifest =
+                serde_json::from_slice(&store.read_one_all(&manifest_path).await?)?;
+            if existing.dataset_version != dataset_version
+                || existing.with_row_id != options.with_row_id
+            {
+                return Err(Error::invalid_input(format!(
+                    "Cannot resume the export to '{}': it was started for version {} (with_row_id={}) \
+                     but version {} (with_row_id={}) is being exported",
+                    dest_uri,
+                    existing.dataset_version,
+                    existing.with_row_id,
+                    dataset_version,
+                    options.with_row_id
+                )));
+            }
+            manifest = Some(existing);
+        }
+        let mut manifest = manifest.unwrap_or(ParquetExportManifest {
+            dataset_version,
+            with_row_id: options.with_row_id,
+            fragments: Vec::new(),
+        });
+
+        let completed = manifest
+            .fragments
+            .iter()
+            .map(|fragment| fragment.fragment_id)
+            .collect::<HashSet<_>>();
+        let fragments = self
+            .get_fragments()
+            .into_iter()
+            .filter(|fragment| {
+                let done = completed.contains(&(fragment.id() as u64));
+                if done {
+                    info!(
+                        "Skipping fragment {}, it has already been exported",
+                        fragment.id()
+                    );
+                }
+                !done
+            })
+            .collect::<Vec<_>>();
+
+        let parallelism = options
+            .parallelism
+            .unwrap_or_else(|| store.io_parallelism())
+            .max(1);
+        // The scan output doesn't carry the schema metadata
+        let metadata = &self.schema().metadata;
+        let store = &store;
+        let dest = &dest;
+        let mut exports = futures::stream::iter(fragments)
+            .map(|fragment| export_fragment(fragment, metadata, store, dest, options))
+            .buffer_unordered(parallelism);
+        while let Some(exported) = exports.try_next().await? {
+            manifest.fragments.push(exported);
+            write_manifest(store, &manifest_path, &manifest).await?;
+        }
+
+        manifest
+            .fragments
+            .sort_by_key(|fragment| fragment.fragment_id);
+        write_manifest(store, &manifest_path, &manifest).await?;
+        Ok(manifest)
+    }
+}
+
+async fn write_manifest(
+    store: &ObjectStore,
+    path: &Path,
+    manifest: &ParquetExportManifest,
+) -> Result<()> {
+    store.put(path, &serde_json::to_vec(manifest)?).await?;
+    Ok(())
+}
+
+async fn export_fragment(
+    fragment: FileFragment,
+    metadata: &HashMap<String, String>,
+    store: &ObjectStore,
+    dest: &Path,
+    options: &ParquetExportOptions,
+) -> Result<ExportedFragment> {
+    let fragment_id = fragment.id() as u64;
+    let mut scanner = fragment.scan();
+    scanner.scan_in_order(true);
+    if let Some(batch_size) = options.batch_size {
+        scanner.batch_size(batch_size);
+    }
+    if options.with_row_id {
+        scanner.with_row_id();
+    }
+    let mut stream = scanner.try_into_stream().await?;
+    let schema = Arc::new(to_parquet_schema(stream.schema().as_ref(), metadata));
+
+    let mut files = Vec::new();
+    let mut num_rows = 0;
+    let mut writer: Option<(String, AsyncArrowWriter<ParquetObjectWriter>)> = None;
+    while let Some(batch) = stream.try_next().await? {
+        if batch.num_rows() == 0 {
+            continue;
+        }
+        let batch = convert_batch(&batch, &schema)?;
+        let (file, file_writer) = match &mut writer {
+            Some(writer) => writer,
+            None => {
+                let file = format!("{}-{}.parquet", fragment_id, files.len());
+                let object_writer =
+                    ParquetObjectWriter::new(store.inner.clone(), dest.clone().join(file.as_str()));
+                let file_writer = AsyncArrowWriter::try_new(
+                    object_writer,
+                    schema.clone(),
+                    options.writer_properties.clone(),
+                )
+                .map_err(|err| parquet_error(&file, err))?;
+                writer.insert((file, file_writer))
+            }
+        };
+        file_writer
+            .write(&batch)
+            .await
+            .map_err(|err| parquet_error(file, err))?;
+        num_rows += batch.num_rows() as u64;
+
+        if let Some(target) = options.target_file_size_bytes
+            && file_writer.bytes_written() + file_writer.in_progress_size() >= target
+            && let Some((file, file_writer)) = writer.take()
+        {
+            file_writer
+                .close()
+                .await
+                .map_err(|err| parquet_error(&file, err))?;
+            files.push(file);
+        }
+    }
+    if let Some((file, file_writer)) = writer {
+        file_writer
+            .close()
+            .await
+            .map_err(|err| parquet_error(&file, err))?;
+        files.push(file);
+    }
+
+    Ok(ExportedFragment {
+        fragment_id,
+        files,
+        num_rows,
+    })
+}
+
+fn parquet_error(file: &str, err: ParquetError) -> Error {
+    Error::io(format!("Failed to write Parquet file '{}': {}", file, err))
+}
+
+/// Map the output schema of a scan to one that can be stored in Parquet
+fn to_parquet_schema(schema: &Schema, metadata: &HashMap<String, String>) -> Schema {
+    let fields = schema
+        .fields()
+        .iter()
+        .map(to_parquet_field)
+        .collect::<Vec<_>>();
+    Schema::new_with_metadata(fields, metadata.clone())
+}
+
+fn to_parquet_field(field: &FieldRef) -> FieldRef {
+    let data_type = if is_bfloat16_field(field) {
+        DataType::Float32
+    } else {
+        match field.data_type() {
+            DataType::List(item) => DataType::List(to_parquet_field(item)),
+            DataType::LargeList(item) => DataType::LargeList(to_parquet_field(item)),
+            DataType::FixedSizeList(item, size) => {
+                DataType::FixedSizeList(to_parquet_field(item), *size)
+            }
+            DataType::Struct(fields) => {
+                DataType::Struct(fields.iter().map(to_parquet_field).collect::<Fields>())
+            }
+            DataType::Map(entries, sorted) => DataType::Map(to_parquet_field(entries), *sorted),
+            data_type => data_type.clone(),
+        }
+    };
+
+    let mut metadata = field.metadata().clone();
+    let is_lance_extension = metadata
+        .get(ARROW_EXT_NAME_KEY)
+        .is_some_and(|name| name.starts_with("lance."));
+    if is_lance_extension {
+        metadata.remove(ARROW_EXT_NAME_KEY);
+        metadata.remove(ARROW_EXT_META_KEY);
+    }
+    Arc::new(Field::new(field.name(), data_type, field.is_nullable()).with_metadata(metadata))
+}
+
+fn convert_batch(batch: &RecordBatch, schema: &SchemaRef) -> Result<RecordBatch> {
+    let columns = batch
+        .columns()
+        .iter()
+        .zip(batch.schema().fields())
+        .zip(schema.fields())
+        .map(|((column, from), to)| convert_column(column, from, to))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(RecordBatch::try_new(schema.clone(), columns)?)
+}
+
+fn convert_column(array: &ArrayRef, from: &Field, to: &Field) -> Result<ArrayRef> {
+    if array.data_type() == to.data_type() {
+        return Ok(array.clone());
+    }
+    if is_bfloat16_field(from) {
+        let values = BFloat16Array::try_from(array.as_fixed_size_binary().clone())?;
+        let values = values
+            .iter()
+            .map(|value| value.map(|value| value.to_f32()))
+            .collect::<Float32Array>();
+        return Ok(Arc::new(values));
+    }
+    let converted: ArrayRef = match (from.data_type(), to.data_type()) {
+        (DataType::FixedSizeList(from_item, _), DataType::FixedSizeList(to_item, size)) => {
+            let list = array.as_fixed_size_list();
+            Arc::new(FixedSizeListArray::try_new(
+                to_item.clone(),
+                *size,
+                convert_column(list.values(), from_item, to_item)?,
+                list.nulls().cloned(),
+            )?)
+        }
+        (DataType::List(from_item), DataType::List(to_item)) => {
+            let list = array.as_list::<i32>();
+            Arc::new(ListArray::try_new(
+                to_item.clone(),
+                list.offsets().clone(),
+                convert_column(list.values(), from_item, to_item)?,
+                list.nulls().cloned(),
+            )?)
+        }
+        (DataType::LargeList(from_item), DataType::LargeList(to_item)) => {
+            let list = array.as_list::<i64>();
+            Arc::new(LargeListArray::try_new(
+                to_item.clone(),
+                list.offsets().clone(),
+                convert_column(list.values(), from_item, to_item)?,
+                list.nulls().cloned(),
+            )?)
+        }
+        (DataType::Struct(from_fields), DataType::Struct(to_fields)) => {
+            let array = array.as_struct();
+            let columns = array
+                .columns()
+                .iter()
+                .zip(from_fields.iter().zip(to_fields.iter()))
+                .map(|(column, (from, to))| convert_column(column, from, to))
+                .collect::<Result<Vec<_>>>()?;
+            Arc::new(StructArray::try_new(
+                to_fields.clone(),
+                columns,
+                array.nulls().cloned(),
+            )?)
+        }
+        // Only the field metadata changed, the data is laid out the same way
+        (_, data_type) => make_array(
+            array
+                .to_data()
+                .into_builder()
+                .data_type(data_type.clone())
+                .build()?,
+        ),
+    };
+    Ok(converted)
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::types::Float32Type;
+    use arrow_array::{Int64Array, RecordBatchIterator, StringArray, UInt64Array};
+    use half::bf16;
+    use lance_arrow::FixedSizeListArrayExt;
+    use lance_arrow::bfloat16::BFLOAT16_EXT_NAME;
+    use lance_core::utils::tempfile::TempStrDir;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    use super::*;
+    use crate::dataset::{WriteMode, WriteParams};
+    use crate::import::{self, ParquetImportOptions};
+
+    fn test_batch(start: i64, num_rows: i64) -> RecordBatch {
+        let metadata = HashMap::from([("owner".to_string(), "export-test".to_string())]);
+        let schema = Arc::new(Schema::new_with_metadata(
+            vec![
+                Field::new("id", DataType::Int64, false),
+                Field::new("name", DataType::Utf8, true).with_metadata(HashMap::from([(
+                    "description".to_string(),
+                    "a name".to_string(),
+                )])),
+                Field::new(
+                    "vector",
+                    DataType::FixedSizeList(
+                        Arc::new(Field::new("item", DataType::Float32, true)),
+                        4,
+                    ),
+                    true,
+                ),
+            ],
+            metadata,
+        ));
+        let ids = Int64Array::from_iter_values(start..start + num_rows);
+        let names = StringArray::from_iter_values((start..start + num_rows).map(|i| i.to_string()));
+        let vectors = FixedSizeListArray::try_new_from_values(
+            Float32Array::from_iter_values((0..num_rows * 4).map(|i| (start * 4 + i) as f32)),
+            4,
+        )
+        .unwrap();
+        RecordBatch::try_new(
+            schema,
+            vec![Arc::new(ids), Arc::new(names), Arc::new(vectors)],
+        )
+        .unwrap()
+    }
+
+    async fn write_dataset(uri: &str, batch: RecordBatch, mode: WriteMode) -> Dataset {
+        let schema = batch.schema();
+        let params = WriteParams {
+            max_rows_per_file: 100,
+            mode,
+            ..Default::default()
+        };
+        Dataset::write(
+            RecordBatchIterator::new(vec![Ok(batch)], schema),
+            uri,
+            Some(params),
+        )
+        .await
+        .unwrap()
+    }
+
+    fn read_parquet(path: &str) -> RecordBatch {
+        let file = std::fs::File::open(path).unwrap();
+        let builder = ParquetRecordBatchReaderBuilder::try_new(file).unwrap();
+        // The batches don't carry the schema metadata, the builder's schema does
+        let schema = builder.schema().clone();
+        let batches = builder
+            .build()
+            .unwrap()
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .unwrap();
+        arrow_select::concat::concat_batches(&schema, &batches).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_export_reimport_round_trip() {
+        let test_dir = TempStrDir::default();
+        let uri = format!("{}/dataset", test_dir.as_str());
+        let dest = format!("{}/export", test_dir.as_str());
+        let original = test_batch(0, 250);
+        let dataset = write_dataset(&uri, original.clone(), WriteMode::Create).await;
+        assert_eq!(dataset.get_fragments().len(), 3);
+
+        let manifest = dataset
+            .export_parquet(&dest, &ParquetExportOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(manifest.dataset_version, 1);
+        assert_eq!(
+            manifest
+                .fragments
+                .iter()
+                .map(|fragment| (fragment.fragment_id, fragment.files.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (0, vec!["0-0.parquet".to_string()]),
+                (1, vec!["1-0.parquet".to_string()]),
+                (2, vec!["2-0.parquet".to_string()]),
+            ]
+        );
+        let stored: ParquetExportManifest = serde_json::from_slice(
+            &std::fs::read(format!("{}/{}", dest, PARQUET_EXPORT_MANIFEST)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(stored, manifest);
+
+        let exported = read_parquet(&format!("{}/0-0.parquet", dest));
+        assert_eq!(
+            exported
+                .schema()
+                .metadata()
+                .get("owner")
+                .map(String::as_str),
+            Some("export-test")
+        );
+        assert_eq!(
+            exported
+                .schema()
+                .field_with_name("name")
+                .unwrap()
+                .metadata()
+                .get("description")
+                .map(String::as_str),
+            Some("a name")
+        );
+
+        let reimported = import::parquet(
+            &dest,
+            &format!("{}/reimported", test_dir.as_str()),
+            &ParquetImportOptions {
+                keep_metadata: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let batch = reimported.scan().try_into_batch().await.unwrap();
+        assert_eq!(batch, original);
+    }
+
+    #[tokio::test]
+    async fn test_export_skips_deleted_rows() {
+        let test_dir = TempStrDir::default();
+        let uri = format!("{}/dataset", test_dir.as_str());
+        let dest = format!("{}/export", test_dir.as_str());
+        let mut dataset = write_dataset(&uri, test_batch(0, 200), WriteMode::Create).await;
+        // Deletes all of fragment 0 and part of fragment 1
+        dataset.delete("id < 150").await.unwrap();
+
+        let options = ParquetExportOptions {
+            with_row_id: true,
+            ..Default::default()
+        };
+        let manifest = dataset.export_parquet(&dest, &options).await.unwrap();
+        assert_eq!(manifest.fragments.len(), 1);
+        assert_eq!(manifest.fragments[0].fragment_id, 1);
+        assert_eq!(manifest.fragments[0].num_rows, 50);
+
+        let exported = read_parquet(&format!("{}/1-0.parquet", dest));
+        let ids = exported["id"].as_primitive::<arrow_array::types::Int64Type>();
+        assert_eq!(ids.values().to_vec(), (150..200).collect::<Vec<_>>());
+        let row_ids = exported["_rowid"].as_primitive::<arrow_array::types::UInt64Type>();
+        assert_eq!(
+            row_ids,
+            &UInt64Array::from_iter_values((50..100).map(|offset| (1 << 32) + offset))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_historical_version_and_resume() {
+        let test_dir = TempStrDir::default();
+        let uri = format!("{}/dataset", test_dir.as_str());
+        let dest = format!("{}/export", test_dir.as_str());
+        write_dataset(&uri, test_batch(0, 100), WriteMode::Create).await;
+        let dataset = write_dataset(&uri, test_batch(100, 100), WriteMode::Append).await;
+        assert_eq!(dataset.version().version, 2);
+
+        let version_1 = dataset.checkout_version(1).await.unwrap();
+        let manifest = version_1
+            .export_parquet(&dest, &ParquetExportOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(manifest.dataset_version, 1);
+        assert_eq!(manifest.fragments.len(), 1);
+        assert_eq!(
+            read_parquet(&format!("{}/0-0.parquet", dest)),
+            test_batch(0, 100)
+        );
+
+        // A different version can't continue the export
+        let options = ParquetExportOptions {
+            resume: true,
+            ..Default::default()
+        };
+        let err = dataset.export_parquet(&dest, &options).await.unwrap_err();
+        assert!(matches!(err, Error::InvalidInput { .. }), "{err}");
+
+        // Resuming an export of the same version only writes the missing fragments
+        let partial = ParquetExportManifest {
+            dataset_version: 2,
+            with_row_id: false,
+            fragments: vec![ExportedFragment {
+                fragment_id: 0,
+                files: vec!["0-0.parquet".to_string()],
+                num_rows: 100,
+            }],
+        };
+        std::fs::write(
+            format!("{}/{}", dest, PARQUET_EXPORT_MANIFEST),
+            serde_json::to_vec(&partial).unwrap(),
+        )
+        .unwrap();
+        std::fs::remove_file(format!("{}/0-0.parquet", dest)).unwrap();
+        let manifest = dataset.export_parquet(&dest, &options).await.unwrap();
+        assert_eq!(manifest.fragments.len(), 2);
+        assert!(!std::path::Path::new(&format!("{}/0-0.parquet", dest)).exists());
+        assert_eq!(
+            read_parquet(&format!("{}/1-0.parquet", dest)),
+            test_batch(100, 100)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_bfloat16_vectors_and_file_size_target() {
+        let test_dir = TempStrDir::default();
+        let uri = format!("{}/dataset", test_dir.as_str());
+        let dest = format!("{}/export", test_dir.as_str());
+
+        let bf16_item =
+            Field::new("item", DataType::FixedSizeBinary(2), true).with_metadata(HashMap::from([
+                (
+                    ARROW_EXT_NAME_KEY.to_string(),
+                    BFLOAT16_EXT_NAME.to_string(),
+                ),
+            ]));
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "vector",
+            DataType::FixedSizeList(Arc::new(bf16_item.clone()), 2),
+            true,
+        )]));
+        let values = BFloat16Array::from_iter_values((0..200).map(|i| bf16::from_f32(i as f32)));
+        let vectors = FixedSizeListArray::try_new(
+            Arc::new(bf16_item),
+            2,
+            Arc::new(values.into_inner()),
+            None,
+        )
+        .unwrap();
+        let batch = RecordBatch::try_new(schema, vec![Arc::new(vectors)]).unwrap();
+        let dataset = write_dataset(&uri, batch, WriteMode::Create).await;
+
+        let options = ParquetExportOptions {
+            // Every batch starts a new file
+            target_file_size_bytes: Some(1),
+            batch_size: Some(25),
+            ..Default::default()
+        };
+        let manifest = dataset.export_parquet(&dest, &options).await.unwrap();
+        assert_eq!(manifest.fragments.len(), 1);
+        assert_eq!(manifest.fragments[0].files.len(), 4);
+        assert_eq!(manifest.fragments[0].num_rows, 100);
+
+        let exported = read_parquet(&format!("{}/0-0.parquet", dest));
+        let expected_type =
+            DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float32, true)), 2);
+        assert_eq!(exported.schema().field(0).data_type(), &expected_type);
+        let vectors = exported.column(0).as_fixed_size_list();
+        let values = vectors.values().as_primitive::<Float32Type>();
+        assert_eq!(values.value(0), 0.0);
+        assert_eq!(values.value(1), 1.0);
+    }
+}
@@ -3,6 +3,7 @@
 
 //! Wraps a Fragment of the dataset.
 
+pub mod layout;
 pub mod session;
 pub mod write;
 
@@ -12,18 +13,18 @@ use std::ops::Range;
 use std::sync::Arc;
 
 use arrow::compute::concat_batches;
-use arrow_array::cast::as_primitive_array;
-use arrow_array::types::UInt64Type;
+use arrow_array::cast::{AsArray, as_primitive_array};
+use arrow_array::types::{Int64Type, UInt64Type};
 use arrow_array::{
     Array, RecordBatch, RecordBatchReader, StructArray, UInt32Array, UInt64Array, new_null_array,
 };
-use arrow_schema::Schema as ArrowSchema;
+use arrow_schema::{DataType, Field as ArrowField, Schema as ArrowSchema};
 use datafusion::logical_expr::Expr;
 use datafusion::scalar::ScalarValue;
 use futures::future::{BoxFuture, try_join_all};
 use futures::{FutureExt, StreamExt, TryFutureExt, TryStreamExt, join, stream};
 use lance_arrow::{RecordBatchExt, SchemaExt};
-use lance_core::datatypes::{OnMissing, OnTypeMismatch, SchemaCompareOptions};
+use lance_core::datatypes::{OnMetadataConflict, OnMissing, OnTypeMismatch, SchemaCompareOptions};
 use lance_core::utils::address::RowAddress;
 use lance_core::utils::deletion::DeletionVector;
 use lance_core::utils::tokio::get_num_compute_intensive_cpus;
@@ -33,18 +34,20 @@ use lance_core::{
     ROW_LAST_UPDATED_AT_VERSION_FIELD,
 };
 use lance_datafusion::utils::StreamingWriteSource;
-use lance_encoding::decoder::DecoderPlugins;
 use lance_file::previous::reader::{
     FileReader as PreviousFileReader, read_batch as previous_read_batch,
 };
 use lance_file::reader::{CachedFileMetadata, FileReaderOptions, ReaderProjection};
 use lance_file::version::LanceFileVersion;
+use lance_file::writer::PAGE_STATS_NUM_ROWS_COLUMN;
 use lance_file::{LanceEncodingsIo, determine_file_version};
 use lance_io::ReadBatchParams;
 use lance_io::scheduler::{FileScheduler, ScanScheduler, SchedulerConfig};
 use lance_io::utils::CachedFileSize;
 use lance_table::format::{DataFile, DeletionFile, Fragment};
-use lance_table::io::deletion::{deletion_file_path, write_deletion_file};
+use lance_table::io::deletion::{
+    DeletionFileOptions, deletion_file_path, write_deletion_file_with_options,
+};
 use lance_table::rowids::RowIdSequence;
 use lance_table::utils::stream::{
     ReadBatchFutStream, ReadBatchTask, ReadBatchTaskStream, RowIdAndDeletesConfig,
@@ -135,6 +138,19 @@ pub trait GenericFileReader: std::fmt::Debug + Send + Sync {
     /// Get storage statistics for this file (ignored by v1 reader)
     fn storage_stats(&self) -> Vec<(u32, u64)>;
 
+    /// Read the page statistics of the given fields.
+    ///
+    /// The batch has a row per page of the file.  The [`PAGE_STATS_NUM_ROWS_COLUMN`]
+    /// column has the number of rows of each page and there is a struct column, named
+    /// after the field id, with the `null_count`, `min_value` and `max_value` of each
+    /// field that has statistics.
+    ///
+    /// Returns None if the file has no statistics for any of the fields.
+    fn read_page_stats<'a>(
+        &'a self,
+        field_ids: &'a [i32],
+    ) -> BoxFuture<'a, Result<Option<RecordBatch>>>;
+
     // Helper functions to fallback to the legacy implementation while we
     // slowly migrate functionality over to the generic reader
 
@@ -304,6 +320,29 @@ impl GenericFileReader for V1Reader {
         Vec::new()
     }
 
+    fn read_page_stats<'a>(
+        &'a self,
+        field_ids: &'a [i32],
+    ) -> BoxFuture<'a, Result<Option<RecordBatch>>> {
+        async move {
+            let Some(stats) = self.reader.read_page_stats(field_ids).await? else {
+                return Ok(None);
+            };
+            // v1 files have a page per batch
+            let num_rows = (0..self.reader.num_batches())
+                .map(|batch_idx| self.reader.num_rows_in_batch(batch_idx as i32) as u64)
+                .collect::<UInt64Array>();
+            let num_rows_field =
+                ArrowField::new(PAGE_STATS_NUM_ROWS_COLUMN, DataType::UInt64, false);
+            Ok(Some(stats.try_with_column_at(
+                0,
+                num_rows_field,
+                Arc::new(num_rows),
+            )?))
+        }
+        .boxed()
+    }
+
     fn clone_box(&self) -> Box<dyn GenericFileReader> {
         Box::new(self.clone())
     }
@@ -508,6 +547,30 @@ mod v2_adapter {
             stats
         }
 
+        fn read_page_stats<'a>(
+            &'a self,
+            field_ids: &'a [i32],
+        ) -> BoxFuture<'a, Result<Option<RecordBatch>>> {
+            async move {
+                let Some(stats) = self.reader.read_page_stats().await? else {
+                    return Ok(None);
+                };
+                let field_columns = field_ids
+                    .iter()
+                    .filter_map(|field_id| stats.schema().index_of(&field_id.to_string()).ok())
+                    .collect::<Vec<_>>();
+                if field_columns.is_empty() {
+                    return Ok(None);
+                }
+                let num_rows_column = stats.schema().index_of(PAGE_STATS_NUM_ROWS_COLUMN)?;
+                let columns = std::iter::once(num_rows_column)
+                    .chain(field_columns)
+                    .collect::<Vec<_>>();
+                Ok(Some(stats.project(&columns)?))
+            }
+            .boxed()
+        }
+
         fn projection(&self) -> &Arc<Schema> {
             &self.projection
         }
@@ -619,6 +682,14 @@ impl GenericFileReader for NullReader {
         Vec::new()
     }
 
+    fn read_page_stats<'a>(
+        &'a self,
+        _field_ids: &'a [i32],
+    ) -> BoxFuture<'a, Result<Option<RecordBatch>>> {
+        // Fields without data files have no statistics
+        async move { Ok(None) }.boxed()
+    }
+
     fn projection(&self) -> &Arc<Schema> {
         &self.schema
     }
@@ -669,6 +740,13 @@ pub struct FragReadConfig {
     pub reader_priority: Option<u32>,
     /// File reader options to use when reading data files.
     pub file_reader_options: Option<FileReaderOptions>,
+    /// Coalesce nearby ranges of a page request into a single read (bounded by
+    /// the object store's coalesce gap and max IOP size) instead of issuing one
+    /// read per value.
+    ///
+    /// This trades some wasted bandwidth for far fewer IOPS and is enabled for
+    /// random access (take) where many scattered rows land on the same page.
+    pub coalesce_page_reads: bool,
 }
 
 impl FragReadConfig {
@@ -713,6 +791,22 @@ impl FragReadConfig {
         self.file_reader_options = Some(value);
         self
     }
+
+    pub fn with_coalesce_page_reads(mut self, value: bool) -> Self {
+        self.coalesce_page_reads = value;
+        self
+    }
+}
+
+/// The range of values of a column in a fragment, see [`FileFragment::column_bounds`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnBounds {
+    /// The smallest non-null value, or null if every value is null.
+    pub min: ScalarValue,
+    /// The largest non-null value, or null if every value is null.
+    pub max: ScalarValue,
+    /// The number of null values, deleted rows included.
+    pub null_count: u64,
 }
 
 impl FileFragment {
@@ -794,12 +888,16 @@ impl FileFragment {
             let file_scheduler = scheduler
                 .open_file(&filepath, &CachedFileSize::unknown())
                 .await?;
+            let mut reader_options = dataset.file_reader_options.clone().unwrap_or_default();
+            reader_options.key_provider = reader_options
+                .key_provider
+                .or_else(|| dataset.key_provider());
             let reader = lance_file::reader::FileReader::try_open(
                 file_scheduler,
                 None,
-                Arc::<DecoderPlugins>::default(),
+                dataset.decoder_plugins(),
                 &dataset.metadata_cache.file_metadata_cache(&filepath),
-                dataset.file_reader_options.clone().unwrap_or_default(),
+                reader_options,
             )
             .await?;
             // If the schemas are not compatible we can't calculate field id offsets
@@ -881,6 +979,52 @@ impl FileFragment {
             .find(|f| f.fields.contains(&(field_id as i32)))
     }
 
+    /// The columns of the dataset that have no data in this fragment.
+    ///
+    /// This happens when columns are added without backfilling them, e.g. with
+    /// [`NewColumnTransform::AllNulls`], and later fragments are written with
+    /// the columns.  Reads return nulls for these columns in this fragment.
+    ///
+    /// Nested fields are returned as paths (e.g. `s.x`).  If a struct column is
+    /// missing its children are not listed separately.
+    pub fn missing_columns(&self) -> Vec<String> {
+        let schema = self.schema();
+        let missing = self.missing_field_ids(schema);
+        schema
+            .fields_pre_order()
+            .filter(|field| missing.contains(&field.id))
+            .filter(|field| {
+                let ancestry = schema.field_ancestry_by_id(field.id).unwrap_or_default();
+                !ancestry[..ancestry.len().saturating_sub(1)]
+                    .iter()
+                    .any(|ancestor| missing.contains(&ancestor.id))
+            })
+            .filter_map(|field| schema.field_path(field.id).ok())
+            .collect()
+    }
+
+    /// The ids of the fields of `schema`, a projection of the dataset schema,
+    /// that none of the data files of this fragment contain.
+    pub(crate) fn missing_field_ids(&self, schema: &Schema) -> HashSet<i32> {
+        let stored = self
+            .metadata
+            .files
+            .iter()
+            .flat_map(|data_file| {
+                data_file
+                    .schema(schema)
+                    .fields_pre_order()
+                    .map(|field| field.id)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<HashSet<_>>();
+        schema
+            .fields_pre_order()
+            .map(|field| field.id)
+            .filter(|id| *id >= 0 && !stored.contains(id))
+            .collect()
+    }
+
     /// Open a FileFragment with a given default projection.
     ///
     /// All read operations (other than `read_projected`) will use the supplied
@@ -974,10 +1118,7 @@ impl FileFragment {
         if data_file.is_legacy_file() {
             let max_field_id = data_file.fields.iter().max().unwrap();
             if !schema_per_file.fields.is_empty() {
-                let path = self
-                    .dataset
-                    .data_file_dir(data_file)?
-                    .join(data_file.path.as_str());
+                let path = self.dataset.data_file_path(data_file)?;
                 let object_store = self.dataset.object_store_for_data_file(data_file).await?;
                 let field_id_offset = Self::get_field_id_offset(data_file);
                 let reader = PreviousFileReader::try_new_with_fragment_id(
@@ -1003,52 +1144,57 @@ impl FileFragment {
         } else if schema_per_file.fields.is_empty() {
             Ok(None)
         } else {
-            let path = self
-                .dataset
-                .data_file_dir(data_file)?
-                .join(data_file.path.as_str());
-            let (store_scheduler, reader_priority) = if let Some(base_id) = data_file.base_id {
-                // TODO: make object stores for non-default bases reuse the same scan scheduler
-                //  currently we always create a new one
-                let object_store = self.dataset.object_store(Some(base_id)).await?;
-                let config = SchedulerConfig::max_bandwidth(&object_store);
-                (
-                    ScanScheduler::new(object_store, config),
-                    read_config.reader_priority.unwrap_or(0),
-                )
-            } else if let Some(scan_scheduler) = read_config.scan_scheduler.as_ref() {
-                (
-                    scan_scheduler.clone(),
-                    read_config.reader_priority.unwrap_or(0),
-                )
-            } else {
-                (
-                    ScanScheduler::new(
-                        self.dataset.object_store.clone(),
-                        SchedulerConfig::max_bandwidth(&self.dataset.object_store),
-                    ),
-                    0,
-                )
-            };
-            let file_scheduler = store_scheduler
+            let path = self.dataset.data_file_path(data_file)?;
+            let (store_scheduler, reader_priority) =
+                if data_file.base_id.is_some() || data_file.has_absolute_uri() {
+                    // TODO: make object stores for non-default bases reuse the same scan scheduler
+                    //  currently we always create a new one
+                    let object_store = self.dataset.object_store_for_data_file(data_file).await?;
+                    let config = SchedulerConfig::max_bandwidth(&object_store);
+                    (
+                        ScanScheduler::new(object_store, config),
+                        read_config.reader_priority.unwrap_or(0),
+                    )
+                } else if let Some(scan_scheduler) = read_config.scan_scheduler.as_ref() {
+                    (
+                        scan_scheduler.clone(),
+                        read_config.reader_priority.unwrap_or(0),
+                    )
+                } else {
+                    (
+                        ScanScheduler::new(
+                            self.dataset.object_store.clone(),
+                            SchedulerConfig::max_bandwidth(&self.dataset.object_store),
+                        ),
+                        0,
+                    )
+                };
+            let mut file_scheduler = store_scheduler
                 .open_file_with_priority(&path, reader_priority as u64, &data_file.file_size_bytes)
                 .await?;
+            if read_config.coalesce_page_reads {
+                file_scheduler = file_scheduler.with_request_coalescing();
+            }
             let file_metadata = self.get_file_metadata(&file_scheduler).await?;
             let path = file_scheduler.reader().path().clone();
             let metadata_cache = self.dataset.metadata_cache.file_metadata_cache(&path);
+            let mut reader_options = read_config
+                .file_reader_options
+                .clone()
+                .or_else(|| self.dataset.file_reader_options.clone())
+                .unwrap_or_default();
+            reader_options.key_provider = reader_options
+                .key_provider
+                .or_else(|| self.dataset.key_provider());
             let reader = Arc::new(
                 lance_file::reader::FileReader::try_open_with_file_metadata(
                     Arc::new(LanceEncodingsIo::new(file_scheduler.clone())),
                     path,
                     None,
-                    Arc::<DecoderPlugins>::default(),
+                    self.dataset.decoder_plugins(),
                     file_metadata,
                     &metadata_cache,
-                    read_config
-                        .file_reader_options
-                        .clone()
-                        .or_else(|| self.dataset.file_reader_options.clone())
-                        .unwrap_or_default(),
+                    reader_options,
                 )
                 .await?,
             );
@@ -1204,6 +1350,52 @@ impl FileFragment {
         Ok(num_physical_rows - num_deleted_rows)
     }
 
+    /// Get the bounds of a top-level column from the page statistics of the fragment.
+    ///
+    /// Deleted rows are included, so the bounds may be wider than the values that are
+    /// still visible. String and binary bounds may be truncated, in which case they are
+    /// still a lower and an upper bound of the values.
+    ///
+    /// Returns `None` if the column has no statistics in this fragment.  Legacy (v1) data
+    /// files always record page statistics, newer ones only if they were written with
+    /// [`WriteParams::collect_stats`].
+    pub async fn column_bounds(&self, column: &str) -> Result<Option<ColumnBounds>> {
+        let projection = self.dataset.schema().project(&[column])?;
+        let field = &projection.fields[0];
+        if field.data_type().is_nested() {
+            return Ok(None);
+        }
+
+        let reader = self.open(&projection, FragReadConfig::default()).await?;
+        // There are no statistics if the column was added after this fragment was written
+        let Some(stats) = reader.read_page_stats(&projection).await?.pop() else {
+            return Ok(None);
+        };
+        let Some(field_stats) = stats
+            .column_by_name(&field.id.to_string())
+            .and_then(|stats| stats.as_struct_opt())
+        else {
+            return Ok(None);
+        };
+        let (Some(null_counts), Some(min_values), Some(max_values)) = (
+            field_stats.column_by_name("null_count"),
+            field_stats.column_by_name("min_value"),
+            field_stats.column_by_name("max_value"),
+        ) else {
+            return Ok(None);
+        };
+
+        let null_count = null_counts
+            .as_primitive_opt::<Int64Type>()
+            .map(|counts| counts.values().iter().sum::<i64>() as u64)
+            .unwrap_or_default();
+        Ok(Some(ColumnBounds {
+            min: fold_page_bound(min_values.as_ref(), std::cmp::Ordering::Less)?,
+            max: fold_page_bound(max_values.as_ref(), std::cmp::Ordering::Greater)?,
+            null_count,
+        }))
+    }
+
     /// Get the number of physical rows in the fragment. This includes deleted rows.
     ///
     /// If there are no deleted rows, this is equal to the number of rows in the
@@ -1259,9 +1451,7 @@ impl FileFragment {
             for field_id in data_file.fields.iter() {
                 if *field_id <= last {
                     return Err(Error::corrupt_file(
-                        self.dataset
-                            .data_file_dir(data_file)?
-                            .join(data_file.path.as_str()),
+                        self.dataset.data_file_path(data_file)?,
                         format!(
                             "Field id {} is not in increasing order in fragment {:#?}",
                             field_id, self
@@ -1271,9 +1461,7 @@ impl FileFragment {
 
                 if !seen_fields.insert(field_id) {
                     return Err(Error::corrupt_file(
-                        self.dataset
-                            .data_file_dir(data_file)?
-                            .join(data_file.path.as_str()),
+                        self.dataset.data_file_path(data_file)?,
                         format!(
                             "Field id {} is duplicated in fragment {:#?}",
                             field_id, self
@@ -1287,9 +1475,7 @@ impl FileFragment {
             != self.metadata.files.iter().all(|f| f.is_legacy_file())
         {
             return Err(Error::corrupt_file(
-                self.dataset
-                    .data_file_dir(&self.metadata.files[0])?
-                    .join(self.metadata.files[0].path.as_str()),
+                self.dataset.data_file_path(&self.metadata.files[0])?,
                 "Fragment contains a mix of v1 and v2 data files".to_string(),
             ));
         }
@@ -1299,13 +1485,13 @@ impl FileFragment {
         }
 
         let get_lengths = self.metadata.files.iter().map(|data_file| async move {
-            let data_file_dir = self.dataset.data_file_dir(data_file)?;
+            let data_file_path = self.dataset.data_file_path(data_file)?;
             let reader = self
                 .open_reader(data_file, None, &FragReadConfig::default())
                 .await?
                 .ok_or_else(|| {
                     Error::corrupt_file(
-                        data_file_dir.clone().join(data_file.path.as_str()),
+                        data_file_path,
                         "did not have any fields in common with the dataset schema",
                     )
                 })?;
@@ -1321,10 +1507,7 @@ impl FileFragment {
         let expected_length = get_lengths.first().unwrap_or(&0);
         for (length, data_file) in get_lengths.iter().zip(self.metadata.files.iter()) {
             if length != expected_length {
-                let path = self
-                    .dataset
-                    .data_file_dir(data_file)?
-                    .join(data_file.path.as_str());
+                let path = self.dataset.data_file_path(data_file)?;
                 return Err(Error::corrupt_file(
                     path,
                     format!(
@@ -1338,9 +1521,7 @@ impl FileFragment {
             && physical_rows != *expected_length
         {
             return Err(Error::corrupt_file(
-                self.dataset
-                    .data_file_dir(&self.metadata.files[0])?
-                    .join(self.metadata.files[0].path.as_str()),
+                self.dataset.data_file_path(&self.metadata.files[0])?,
                 format!(
                     "Fragment metadata has incorrect physical_rows. Actual: {} Metadata: {}",
                     expected_length, physical_rows
@@ -1486,7 +1667,8 @@ impl FileFragment {
                     .with_row_id(with_row_id)
                     .with_row_address(with_row_address)
                     .with_row_created_at_version(with_row_created_at_version)
-                    .with_row_last_updated_at_version(with_row_last_updated_at_version),
+                    .with_row_last_updated_at_version(with_row_last_updated_at_version)
+                    .with_coalesce_page_reads(true),
             )
             .await?;
 
@@ -1588,6 +1770,26 @@ impl FileFragment {
         left_on: &str,
         right_on: &str,
         max_field_id: i32,
+    ) -> Result<(Fragment, Schema)> {
+        self.merge_columns_with(
+            stream,
+            left_on,
+            right_on,
+            max_field_id,
+            OnMetadataConflict::default(),
+        )
+        .await
+    }
+
+    /// [`Self::merge_columns`], resolving the schema metadata keys that both
+    /// the dataset and the stream have according to `on_metadata_conflict`.
+    pub async fn merge_columns_with(
+        &mut self,
+        stream: impl RecordBatchReader + Send + 'static,
+        left_on: &str,
+        right_on: &str,
+        max_field_id: i32,
+        on_metadata_conflict: OnMetadataConflict,
     ) -> Result<(Fragment, Schema)> {
         let stream = Box::new(stream);
         if self.schema().field(left_on).is_none() && left_on != ROW_ID && left_on != ROW_ADDR {
@@ -1621,7 +1823,9 @@ impl FileFragment {
         let joiner = Arc::new(HashJoiner::try_new(stream, right_on).await?);
         // Final schema is union of current schema, plus the RHS schema without
         // the right_on key.
-        let mut new_schema: Schema = self.schema().merge(joiner.out_schema().as_ref())?;
+        let mut new_schema: Schema = self
+            .schema()
+            .merge_with(joiner.out_schema().as_ref(), on_metadata_conflict)?;
         new_schema.set_field_id(Some(max_field_id));
 
         let new_fragment = self
@@ -1877,12 +2081,23 @@ impl FileFragment {
             return Ok(Some(self));
         }
 
-        self.write_deletions(deletion_vector).await
+        self.write_deletions(deletion_vector, &DeletionFileOptions::default())
+            .await
     }
 
     pub async fn extend_deletions(
         self,
         new_deletions: impl IntoIterator<Item = u32>,
+    ) -> Result<Option<Self>> {
+        self.extend_deletions_with_options(new_deletions, &DeletionFileOptions::default())
+            .await
+    }
+
+    /// Like [`Self::extend_deletions`], writing the deletion file with `options`.
+    pub async fn extend_deletions_with_options(
+        self,
+        new_deletions: impl IntoIterator<Item = u32>,
+        options: &DeletionFileOptions,
     ) -> Result<Option<Self>> {
         let mut deletion_vector = self
             .get_deletion_vector()
@@ -1893,10 +2108,14 @@ impl FileFragment {
 
         deletion_vector.extend(new_deletions);
 
-        self.write_deletions(deletion_vector).await
+        self.write_deletions(deletion_vector, options).await
     }
 
-    async fn write_deletions(mut self, deletion_vector: DeletionVector) -> Result<Option<Self>> {
+    async fn write_deletions(
+        mut self,
+        deletion_vector: DeletionVector,
+        options: &DeletionFileOptions,
+    ) -> Result<Option<Self>> {
         let physical_rows = self.physical_rows().await?;
         if deletion_vector.len() == physical_rows
             && deletion_vector.contains_range(0..physical_rows as u32)
@@ -1917,12 +2136,13 @@ impl FileFragment {
             )));
         }
 
-        self.metadata.deletion_file = write_deletion_file(
+        self.metadata.deletion_file = write_deletion_file_with_options(
             &self.dataset.base,
             self.metadata.id,
             self.dataset.version().version,
             &deletion_vector,
             self.dataset.object_store.as_ref(),
+            options,
         )
         .await?;
 
@@ -1967,6 +2187,29 @@ pub(crate) fn resolve_actual_row_ids(row_ids: &[u32], sorted_deleted_ids: &[u32]
     row_ids
 }
 
+/// Combine the per-page min (`keep` is `Less`) or max (`Greater`) values of a column.
+///
+/// Pages without a bound (all nulls) are skipped.
+fn fold_page_bound(values: &dyn Array, keep: std::cmp::Ordering) -> Result<ScalarValue> {
+    let mut bound: Option<ScalarValue> = None;
+    for i in 0..values.len() {
+        if values.is_null(i) {
+            continue;
+        }
+        let value = ScalarValue::try_from_array(values, i)?;
+        if bound
+            .as_ref()
+            .is_none_or(|bound| value.partial_cmp(bound) == Some(keep))
+        {
+            bound = Some(value);
+        }
+    }
+    match bound {
+        Some(bound) => Ok(bound),
+        None => Ok(ScalarValue::try_from(values.data_type())?),
+    }
+}
+
 // Cache key for file metadata
 #[derive(Debug, Clone)]
 struct FileMetadataCacheKey;
@@ -2211,10 +2454,12 @@ impl FragmentReader {
     pub(crate) fn legacy_num_batches(&self) -> usize {
         let legacy_reader = self.readers[0].as_legacy();
         let num_batches = legacy_reader.num_batches();
+        // Fields without data files are read with a null reader, which has no batches
         assert!(
             self.readers
                 .iter()
-                .all(|r| r.as_legacy().num_batches() == num_batches),
+                .filter_map(|r| r.as_legacy_opt())
+                .all(|r| r.num_batches() == num_batches),
             "Data files have varying number of batches, which is not yet supported."
         );
         num_batches
@@ -2239,6 +2484,25 @@ impl FragmentReader {
         }
     }
 
+    /// Read the page statistics of the fields of `projection`, see
+    /// [`GenericFileReader::read_page_stats`].
+    ///
+    /// Returns a batch for each data file with statistics.  Data files may have
+    /// different page boundaries, so the batches are not merged.
+    pub(crate) async fn read_page_stats(&self, projection: &Schema) -> Result<Vec<RecordBatch>> {
+        let mut stats_batches = vec![];
+        for reader in self.readers.iter() {
+            let field_ids = reader.projection().intersection(projection)?.field_ids();
+            if field_ids.is_empty() {
+                continue;
+            }
+            if let Some(stats_batch) = reader.read_page_stats(&field_ids).await? {
+                stats_batches.push(stats_batch);
+            }
+        }
+        Ok(stats_batches)
+    }
+
     /// Read the page statistics of the fragment for the specified fields.
     ///
     /// TODO: This method is relied upon by the v1 pushdown mechanism and will need to stay
@@ -2254,7 +2518,10 @@ impl FragmentReader {
                 Some(projection) => Arc::new(reader.projection().intersection(projection)?),
                 None => reader.projection().clone(),
             };
-            let reader = reader.as_legacy();
+            // Fields without data files have no statistics
+            let Some(reader) = reader.as_legacy_opt() else {
+                continue;
+            };
             if let Some(stats_batch) = reader.read_page_stats(&schema.field_ids()).await? {
                 stats_batches.push(stats_batch);
             }
@@ -2285,13 +2552,20 @@ impl FragmentReader {
         // All batches have the same size in v1, except for the last one.
         let batch_offset = batch_id * first_reader.num_rows_in_batch(0);
         let rows_in_batch = first_reader.num_rows_in_batch(batch_id as i32);
+        let expected_rows = params
+            .clone()
+            .into()
+            .slice(0, rows_in_batch)
+            .unwrap()
+            .to_offsets()?
+            .len();
 
         let batches = if !projection.fields.is_empty() {
             let read_tasks = self.readers.iter().map(|reader| {
                 let projection = reader.projection().intersection(projection);
                 let params = params.clone();
 
-                let reader = reader.as_legacy();
+                let reader = reader.as_legacy_opt();
 
                 async move {
                     // Apply ? inside the task to keep read_tasks a simple iter of futures
@@ -2301,12 +2575,18 @@ impl FragmentReader {
                         // The projection caused one of the data files to become
                         // irrelevant and so we can skip it
                         Result::Ok(None)
-                    } else {
+                    } else if let Some(reader) = reader {
                         Ok(Some(
                             reader
                                 .read_batch(batch_id as i32, params, &projection)
                                 .await?,
                         ))
+                    } else {
+                        // Fields without data files are all null
+                        Ok(Some(NullReader::batch(
+                            Arc::new((&projection).into()),
+                            expected_rows,
+                        )))
                     }
                 }
             });
@@ -2316,13 +2596,6 @@ impl FragmentReader {
             // If we are selecting no columns, we can assume we are just getting
             // the row ids. If this is the case, we need to generate an empty
             // batch with the correct number of rows.
-            let expected_rows = params
-                .clone()
-                .into()
-                .slice(0, rows_in_batch)
-                .unwrap()
-                .to_offsets()?
-                .len();
             vec![RecordBatch::from(StructArray::new_empty_fields(
                 expected_rows,
                 None,
@@ -2755,10 +3028,13 @@ mod tests {
     use arrow_schema::{DataType, Field as ArrowField, Schema as ArrowSchema};
     use lance_core::ROW_ID;
     use lance_core::utils::tempfile::TempStrDir;
-    use lance_datagen::{RowCount, array, gen_batch};
+    use lance_datagen::{Dimension, RowCount, array, gen_batch};
     use lance_file::version::LanceFileVersion;
     use lance_file::writer::FileWriterOptions;
-    use lance_io::{assert_io_eq, assert_io_lt, object_store::ObjectStore};
+    use lance_io::object_store::{
+        COALESCE_GAP_KEY, ObjectStore, ObjectStoreParams, StorageOptionsAccessor,
+    };
+    use lance_io::{assert_io_eq, assert_io_lt};
     use pretty_assertions::assert_eq;
     use rstest::rstest;
     use std::collections::HashMap;
@@ -4121,4 +4397,194 @@ mod tests {
         assert_io_eq!(stats, read_iops, 1);
         assert_io_lt!(stats, read_bytes, 4096);
     }
+
+    #[tokio::test]
+    async fn test_take_coalesces_page_reads() {
+        // 512 bytes per row so that rows 1000 apart are much further apart than a block
+        let batch = gen_batch()
+            .col("id", array::step::<arrow_array::types::Int32Type>())
+            .col(
+                "vec",
+                array::rand_vec::<arrow_array::types::Float32Type>(Dimension::from(128)),
+            )
+            .into_batch_rows(RowCount::from(10_000))
+            .unwrap();
+        let dataset = InsertBuilder::new("memory://test")
+            .execute(vec![batch.clone()])
+            .await
+            .unwrap();
+        let fragment = dataset.get_fragments().pop().unwrap();
+        let projection = dataset.schema().project(&["vec"]).unwrap();
+
+        let offsets = (0..10).map(|i| i * 1000).collect::<Vec<u32>>();
+        let expected = arrow_select::take::take(
+            batch.column_by_name("vec").unwrap(),
+            &arrow_array::UInt32Array::from(offsets.clone()),
+            None,
+        )
+        .unwrap();
+
+        let take_with = |coalesce: bool| {
+            let fragment = fragment.clone();
+            let projection = projection.clone();
+            let offsets = offsets.clone();
+            async move {
+                let reader = fragment
+                    .open(
+                        &projection,
+                        FragReadConfig::default().with_coalesce_page_reads(coalesce),
+                    )
+                    .await
+                    .unwrap();
+                reader.take_as_batch(&offsets, None).await.unwrap()
+            }
+        };
+
+        // Warm up the metadata cache so only data reads are counted below
+        take_with(false).await;
+        dataset.object_store.as_ref().io_stats_incremental();
+
+        let uncoalesced = take_with(false).await;
+        let uncoalesced_stats = dataset.object_store.as_ref().io_stats_incremental();
+        let coalesced = take_with(true).await;
+        let coalesced_stats = dataset.object_store.as_ref().io_stats_incremental();
+
+        assert_eq!(uncoalesced.column(0).as_ref(), expected.as_ref());
+        assert_eq!(coalesced.column(0).as_ref(), expected.as_ref());
+        assert!(
+            coalesced_stats.read_iops < uncoalesced_stats.read_iops,
+            "coalesced take used {} IOPS, uncoalesced used {}",
+            coalesced_stats.read_iops,
+            uncoalesced_stats.read_iops
+        );
+    }
+
+    #[tokio::test]
+    async fn test_take_sparse_rows_reads_no_extra_bytes() {
+        // 512 bytes per row so that rows 1000 apart are much further apart than
+        // the coalesce gap
+        let batch = gen_batch()
+            .col(
+                "vec",
+                array::rand_vec::<arrow_array::types::Float32Type>(Dimension::from(128)),
+            )
+            .into_batch_rows(RowCount::from(10_000))
+            .unwrap();
+        let write_params = WriteParams {
+            store_params: Some(ObjectStoreParams {
+                storage_options_accessor: Some(Arc::new(
+                    StorageOptionsAccessor::with_static_options(HashMap::from([(
+                        COALESCE_GAP_KEY.to_string(),
+                        "4096".to_string(),
+                    )])),
+                )),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let dataset = InsertBuilder::new("memory://test")
+            .with_params(&write_params)
+            .execute(vec![batch])
+            .await
+            .unwrap();
+        let fragment = dataset.get_fragments().pop().unwrap();
+        let projection = dataset.schema().project(&["vec"]).unwrap();
+        let offsets = (0..10).map(|i| i * 1000).collect::<Vec<u32>>();
+
+        let take_with = |coalesce: bool| {
+            let fragment = fragment.clone();
+            let projection = projection.clone();
+            let offsets = offsets.clone();
+            async move {
+                let reader = fragment
+                    .open(
+                        &projection,
+                        FragReadConfig::default().with_coalesce_page_reads(coalesce),
+                    )
+                    .await
+                    .unwrap();
+                reader.take_as_batch(&offsets, None).await.unwrap()
+            }
+        };
+
+        // Warm up the metadata cache so only data reads are counted below
+        take_with(false).await;
+        dataset.object_store.as_ref().io_stats_incremental();
+
+        let uncoalesced = take_with(false).await;
+        let uncoalesced_stats = dataset.object_store.as_ref().io_stats_incremental();
+        let coalesced = take_with(true).await;
+        let coalesced_stats = dataset.object_store.as_ref().io_stats_incremental();
+
+        assert_eq!(uncoalesced, coalesced);
+        assert_eq!(coalesced_stats.read_bytes, uncoalesced_stats.read_bytes);
+        assert_eq!(coalesced_stats.read_iops, uncoalesced_stats.read_iops);
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_column_bounds(
+        #[values(LanceFileVersion::Legacy, LanceFileVersion::Stable)]
+        data_storage_version: LanceFileVersion,
+        #[values(false, true)] collect_stats: bool,
+    ) {
+        let schema = Arc::new(ArrowSchema::new(vec![
+            ArrowField::new("i", DataType::Int32, true),
+            ArrowField::new("s", DataType::Utf8, false),
+        ]));
+        let batches = [
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(Int32Array::from(vec![Some(5), None, Some(3)])),
+                    Arc::new(StringArray::from(vec!["b", "c", "a"])),
+                ],
+            ),
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(Int32Array::from(vec![Some(10), None])),
+                    Arc::new(StringArray::from(vec!["e", "d"])),
+                ],
+            ),
+        ];
+        let write_params = WriteParams {
+            data_storage_version: Some(data_storage_version),
+            collect_stats,
+            ..Default::default()
+        };
+        let dataset = Dataset::write(
+            RecordBatchIterator::new(batches, schema),
+            "memory://",
+            Some(write_params),
+        )
+        .await
+        .unwrap();
+        let fragment = dataset.get_fragment(0).unwrap();
+
+        let i_bounds = fragment.column_bounds("i").await.unwrap();
+        let s_bounds = fragment.column_bounds("s").await.unwrap();
+        // Legacy data files always have statistics
+        if data_storage_version == LanceFileVersion::Legacy || collect_stats {
+            assert_eq!(
+                i_bounds,
+                Some(ColumnBounds {
+                    min: ScalarValue::Int32(Some(3)),
+                    max: ScalarValue::Int32(Some(10)),
+                    null_count: 2,
+                })
+            );
+            assert_eq!(
+                s_bounds,
+                Some(ColumnBounds {
+                    min: ScalarValue::Utf8(Some("a".to_string())),
+                    max: ScalarValue::Utf8(Some("e".to_string())),
+                    null_count: 0,
+                })
+            );
+        } else {
+            assert_eq!(i_bounds, None);
+            assert_eq!(s_bounds, None);
+        }
+    }
 }
@@ -0,0 +1,315 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Describe how the fragments of a dataset are laid out in storage
+//!
+//! [`FileFragment::physical_layout`] and [`Dataset::describe_fragments`] report the
+//! data files of each fragment, which fields each file holds, the file sizes and row
+//! counts, the deletion file and the storage version of each file.  This is what is
+//! needed to understand, for example, why a scan opens more files than expected after
+//! columns were added, without decoding the manifest by hand.
+
+use std::sync::Arc;
+
+use futures::{StreamExt, TryStreamExt, stream};
+use lance_core::Result;
+use lance_file::reader::CachedFileMetadata;
+use lance_file::version::LanceFileVersion;
+use lance_io::scheduler::{ScanScheduler, SchedulerConfig};
+use lance_table::format::{DataFile, DeletionFileType};
+use lance_table::io::deletion::relative_deletion_file_path;
+use serde::{Deserialize, Serialize};
+
+use super::FileFragment;
+use crate::Dataset;
+
+/// The physical layout of a fragment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FragmentLayout {
+    pub id: u64,
+    /// The number of rows in the data files, including deleted rows, if known
+    pub physical_rows: Option<usize>,
+    pub files: Vec<DataFileLayout>,
+    pub deletion_file: Option<DeletionFileLayout>,
+}
+
+/// The layout of one data file of a fragment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DataFileLayout {
+    /// Path of the file, relative to the data directory of its base
+    pub path: String,
+    pub base_id: Option<u32>,
+    /// The storage version the file was written with, e.g. `2.1`
+    pub data_storage_version: String,
+    /// The size of the file in bytes, if known
+    pub size_bytes: Option<u64>,
+    /// The number of rows in the file, only known when the file metadata was read
+    pub num_rows: Option<u64>,
+    /// The fields stored in the file, in the order of the file
+    pub columns: Vec<ColumnLayout>,
+}
+
+/// A field stored in a data file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnLayout {
+    pub field_id: i32,
+    /// The path of the field in the dataset schema, unset if the field was dropped
+    pub field_path: Option<String>,
+    /// The column of the field in the file, unset for legacy files and for fields
+    /// without a column of their own
+    pub column_index: Option<u32>,
+    /// The number of pages of the column, only known when the file metadata was read
+    pub num_pages: Option<usize>,
+}
+
+/// The deletion file of a fragment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeletionFileLayout {
+    /// Path of the file, relative to the root of its base
+    pub path: String,
+    pub base_id: Option<u32>,
+    pub file_type: DeletionFileType,
+    /// The number of deleted rows, if known
+    pub num_deleted_rows: Option<usize>,
+}
+
+impl FileFragment {
+    /// Describe the data files and deletion file of this fragment
+    ///
+    /// Everything except the page counts and row counts of the data files comes from
+    /// the manifest.  If `read_file_metadata` is set, the metadata of each v2 data file
+    /// is read as well, from the metadata cache if the file was opened before, to fill
+    /// those in.
+    pub async fn physical_layout(&self, read_file_metadata: bool) -> Result<FragmentLayout> {
+        let mut files = Vec::with_capacity(self.metadata.files.len());
+        for data_file in &self.metadata.files {
+            let file_metadata = if read_file_metadata && !data_file.is_legacy_file() {
+                Some(self.data_file_metadata(data_file).await?)
+            } else {
+                None
+            };
+            files.push(self.data_file_layout(data_file, file_metadata.as_deref()));
+        }
+
+        let deletion_file =
+            self.metadata
+                .deletion_file
+                .as_ref()
+                .map(|deletion_file| DeletionFileLayout {
+                    path: relative_deletion_file_path(self.metadata.id, deletion_file),
+                    base_id: deletion_file.base_id,
+                    file_type: deletion_file.file_type.clone(),
+                    num_deleted_rows: deletion_file.num_deleted_rows,
+                });
+
+        Ok(FragmentLayout {
+            id: self.metadata.id,
+            physical_rows: self.metadata.physical_rows,
+            files,
+            deletion_file,
+        })
+    }
+
+    fn data_file_layout(
+        &self,
+        data_file: &DataFile,
+        file_metadata: Option<&CachedFileMetadata>,
+    ) -> DataFileLayout {
+        let (major, minor) = (data_file.file_major_version, data_file.file_minor_version);
+        let data_storage_version = LanceFileVersion::try_from_major_minor(major, minor)
+            .map(|version| version.to_string())
+            .unwrap_or_else(|_| format!("{major}.{minor}"));
+        let columns = data_file
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, &field_id)| {
+                let column_index = data_file
+                    .column_indices
+                    .get(i)
+                    .filter(|&&column_index| column_index >= 0)
+                    .map(|&column_index| column_index as u32);
+                let num_pages = file_metadata
+                    .zip(column_index)
+                    .and_then(|(metadata, index)| {
+                        metadata
+                            .column_infos
+                            .get(index as usize)
+                            .map(|info| info.page_infos.len())
+                    });
+                ColumnLayout {
+                    field_id,
+                    field_path: self.schema().field_path(field_id).ok(),
+                    column_index,
+                    num_pages,
+                }
+            })
+            .collect();
+
+        DataFileLayout {
+            path: data_file.path.clone(),
+            base_id: data_file.base_id,
+            data_storage_version,
+            size_bytes: data_file
+                .file_size_bytes
+                .get()
+                .map(u64::from)
+                .or(file_metadata.map(|metadata| metadata.file_size_bytes)),
+            num_rows: file_metadata.map(|metadata| metadata.num_rows),
+            columns,
+        }
+    }
+
+    async fn data_file_metadata(&self, data_file: &DataFile) -> Result<Arc<CachedFileMetadata>> {
+        let path = self.dataset.data_file_path(data_file)?;
+        let object_store = self.dataset.object_store_for_data_file(data_file).await?;
+        let scheduler = ScanScheduler::new(
+            object_store.clone(),
+            SchedulerConfig::max_bandwidth(&object_store),
+        );
+        let file_scheduler = scheduler
+            .open_file(&path, &data_file.file_size_bytes)
+            .await?;
+        self.get_file_metadata(&file_scheduler).await
+    }
+}
+
+impl Dataset {
+    /// Describe the physical layout of every fragment, see
+    /// [`FileFragment::physical_layout`]
+    pub async fn describe_fragments(
+        &self,
+        read_file_metadata: bool,
+    ) -> Result<Vec<FragmentLayout>> {
+        stream::iter(self.get_fragments())
+            .map(|fragment| async move { fragment.physical_layout(read_file_metadata).await })
+            .buffered(self.object_store.io_parallelism())
+            .try_collect()
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use lance_datagen::{BatchCount, RowCount, array};
+
+    use super::*;
+    use crate::dataset::optimize::{CompactionOptions, compact_files};
+    use crate::dataset::{NewColumnTransform, WriteParams};
+
+    async fn create_dataset(uri: &str, rows_per_file: usize, num_files: u32) -> Dataset {
+        let data = lance_datagen::gen_batch()
+            .col("id", array::step::<arrow_array::types::Int32Type>())
+            .into_reader_rows(
+                RowCount::from(rows_per_file as u64),
+                BatchCount::from(num_files),
+            );
+        let write_params = WriteParams {
+            max_rows_per_file: rows_per_file,
+            data_storage_version: Some(LanceFileVersion::V2_1),
+            ..Default::default()
+        };
+        Dataset::write(data, uri, Some(write_params)).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_layout_after_add_columns() {
+        let mut dataset = create_dataset("memory://", 100, 2).await;
+        dataset
+            .add_columns(
+                NewColumnTransform::SqlExpressions(vec![("doubled".into(), "id * 2".into())]),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        let layouts = dataset.describe_fragments(true).await.unwrap();
+        assert_eq!(layouts.len(), 2);
+        for layout in &layouts {
+            assert_eq!(layout.physical_rows, Some(100));
+            assert!(layout.deletion_file.is_none());
+            assert_eq!(layout.files.len(), 2);
+
+            let paths = layout
+                .files
+                .iter()
+                .map(|file| {
+                    assert_eq!(file.data_storage_version, "2.1");
+                    assert!(file.size_bytes.is_some_and(|size| size > 0));
+                    assert_eq!(file.num_rows, Some(100));
+                    assert!(
+                        file.columns
+                            .iter()
+                            .all(|column| column.num_pages.is_some_and(|pages| pages > 0))
+                    );
+                    file.columns
+                        .iter()
+                        .map(|column| column.field_path.clone().unwrap())
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                paths,
+                vec![vec!["id".to_string()], vec!["doubled".to_string()]]
+            );
+
+            let field_ids = layout
+                .files
+                .iter()
+                .flat_map(|file| file.columns.iter().map(|column| column.field_id))
+                .collect::<HashSet<_>>();
+            assert_eq!(field_ids.len(), 2);
+        }
+
+        // Without reading the file metadata only what the manifest knows is set
+        let layout = dataset
+            .get_fragment(0)
+            .unwrap()
+            .physical_layout(false)
+            .await
+            .unwrap();
+        assert!(layout.files.iter().all(|file| file.num_rows.is_none()));
+        assert!(
+            layout
+                .files
+                .iter()
+                .flat_map(|file| &file.columns)
+                .all(|column| column.num_pages.is_none() && column.column_index.is_some())
+        );
+
+        let json = serde_json::to_string(&layouts).unwrap();
+        let parsed: Vec<FragmentLayout> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, layouts);
+    }
+
+    #[tokio::test]
+    async fn test_layout_after_compaction() {
+        let mut dataset = create_dataset("memory://", 50, 4).await;
+        dataset.delete("id < 10").await.unwrap();
+
+        let layouts = dataset.describe_fragments(false).await.unwrap();
+        assert_eq!(layouts.len(), 4);
+        let deletion_file = layouts[0].deletion_file.as_ref().unwrap();
+        assert!(deletion_file.path.starts_with("_deletions/"));
+        assert_eq!(deletion_file.num_deleted_rows, Some(10));
+        assert!(
+            layouts[1..]
+                .iter()
+                .all(|layout| layout.deletion_file.is_none())
+        );
+
+        compact_files(&mut dataset, CompactionOptions::default(), None)
+            .await
+            .unwrap();
+        let layouts = dataset.describe_fragments(true).await.unwrap();
+        assert_eq!(layouts.len(), 1);
+        let layout = &layouts[0];
+        assert_eq!(layout.physical_rows, Some(190));
+        assert!(layout.deletion_file.is_none());
+        assert_eq!(layout.files.len(), 1);
+        assert_eq!(layout.files[0].num_rows, Some(190));
+    }
+}
@@ -144,6 +144,8 @@ impl<'a> FragmentCreateBuilder<'a> {
             schema,
             FileWriterOptions {
                 format_version: params.data_storage_version,
+                key_provider: params.key_provider(),
+                custom_encodings: params.custom_encodings(),
                 ..Default::default()
             },
         )?;
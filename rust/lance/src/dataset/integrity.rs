@@ -0,0 +1,598 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Checksums of the files of a dataset version, to verify copies of the dataset
+//!
+//! [`Dataset::write_integrity_manifest`] checksums every file the current version
+//! needs: the manifest, the data files with their blob sidecar files, the deletion
+//! files and the index files.  The result is stored as JSON under [`INTEGRITY_DIR`].
+//! After the dataset has been copied, e.g. to another region, open the copy and pass
+//! the manifest to [`Dataset::verify_integrity`] to find files that are missing or
+//! differ from the original.
+//!
+//! Paths are recorded relative to the root of the dataset, or of the base they are
+//! stored in, so the manifest applies to any copy with the same layout.  Blobs stored
+//! outside of the dataset and referenced by URI are not covered.
+
+use std::collections::{HashMap, HashSet};
+
+use futures::{StreamExt, TryStreamExt, stream};
+use lance_io::object_store::ObjectStore;
+use lance_table::io::deletion::deletion_file_path;
+use object_store::path::Path;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::index::DatasetIndexExt;
+use crate::{Dataset, Error, Result};
+
+/// The directory, under the dataset root, that integrity manifests are written to
+pub const INTEGRITY_DIR: &str = "_integrity";
+
+/// The checksum recorded for each file of an [`IntegrityManifest`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChecksumAlgorithm {
+    /// The 128-bit XXH3 hash, fast but not cryptographic
+    #[default]
+    Xxh3,
+    Sha256,
+}
+
+/// What a file of an [`IntegrityManifest`] holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IntegrityFileKind {
+    Manifest,
+    Data,
+    Blob,
+    Deletion,
+    Index,
+}
+
+/// The checksum of one file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileChecksum {
+    /// Path of the file, relative to the root of the base it is in
+    pub path: String,
+    /// The base the file is in, unset for the dataset itself
+    pub base_id: Option<u32>,
+    pub kind: IntegrityFileKind,
+    pub size: u64,
+    /// The checksum, in hex
+    pub checksum: String,
+}
+
+/// The files of a dataset version and their checksums
+///
+/// Written by [`Dataset::write_integrity_manifest`] and checked by
+/// [`Dataset::verify_integrity`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntegrityManifest {
+    pub dataset_version: u64,
+    pub algorithm: ChecksumAlgorithm,
+    /// The files, sorted by base and path
+    pub files: Vec<FileChecksum>,
+}
+
+/// How a file differs from its [`FileChecksum`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrityMismatchKind {
+    Missing,
+    SizeMismatch { expected: u64, actual: u64 },
+    ChecksumMismatch { expected: String, actual: String },
+}
+
+/// A file that does not match the [`IntegrityManifest`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntegrityMismatch {
+    pub path: String,
+    pub base_id: Option<u32>,
+    pub kind: IntegrityMismatchKind,
+}
+
+/// The result of [`Dataset::verify_integrity`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntegrityReport {
+    /// The number of files that match the manifest
+    pub num_verified: u64,
+    /// The files that are missing or differ, in the order of the manifest
+    pub mismatches: Vec<IntegrityMismatch>,
+}
+
+impl IntegrityReport {
+    /// True if every file matches the manifest
+    pub fn is_clean(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+impl Dataset {
+    /// Checksum every file of this version and store the result under [`INTEGRITY_DIR`]
+    ///
+    /// Every file is read once, up to [`ObjectStore::io_parallelism`] files at a time.
+    /// See the [module documentation](self).
+    pub async fn write_integrity_manifest(
+        &self,
+        algorithm: ChecksumAlgorithm,
+    ) -> Result<IntegrityManifest> {
+        let files = self.integrity_files().await?;
+        let parallelism = self.object_store.io_parallelism();
+        let mut files = stream::iter(files)
+            .map(|(base_id, path, kind)| async move {
+                let store = self.object_store(base_id).await?;
+                let location = integrity_base_root(self, base_id)?.join(path.as_str());
+                let (size, checksum) = checksum_file(&store, &location, algorithm).await?;
+                Ok::<_, Error>(FileChecksum {
+                    path,
+                    base_id,
+                    kind,
+                    size,
+                    checksum,
+                })
+            })
+            .buffer_unordered(parallelism)
+            .try_collect::<Vec<_>>()
+            .await?;
+        files.sort_by(|a, b| (a.base_id, &a.path).cmp(&(b.base_id, &b.path)));
+
+        let manifest = IntegrityManifest {
+            dataset_version: self.version().version,
+            algorithm,
+            files,
+        };
+        self.object_store
+            .put(
+                &self.integrity_manifest_path(manifest.dataset_version),
+                &serde_json::to_vec(&manifest)?,
+            )
+            .await?;
+        Ok(manifest)
+    }
+
+    /// Read the manifest [`Self::write_integrity_manifest`] wrote for `version`
+    pub async fn read_integrity_manifest(&self, version: u64) -> Result<IntegrityManifest> {
+        let path = self.integrity_manifest_path(version);
+        Ok(serde_json::from_slice(
+            &self.object_store.read_one_all(&path).await?,
+        )?)
+    }
+
+    /// Check the files of this dataset against `manifest`
+    ///
+    /// Up to [`ObjectStore::io_parallelism`] files are read at a time.  Files that are
+    /// missing, or whose size or checksum differs, are listed in the report.  A file
+    /// that can't be read for another reason fails the verification.
+    pub async fn verify_integrity(&self, manifest: &IntegrityManifest) -> Result<IntegrityReport> {
+        let parallelism = self.object_store.io_parallelism();
+        let algorithm = manifest.algorithm;
+        let checks = stream::iter(&manifest.files)
+            .map(|file| async move {
+                let store = self.object_store(file.base_id).await?;
+                let location = integrity_base_root(self, file.base_id)?.join(file.path.as_str());
+                let kind = match store.inner.head(&location).await {
+                    Err(object_store::Error::NotFound { .. }) => {
+                        Some(IntegrityMismatchKind::Missing)
+                    }
+                    Err(err) => return Err(err.into()),
+                    Ok(meta) if meta.size != file.size => {
+                        Some(IntegrityMismatchKind::SizeMismatch {
+                            expected: file.size,
+                            actual: meta.size,
+                        })
+                    }
+                    Ok(_) => {
+                        let (_, checksum) = checksum_file(&store, &location, algorithm).await?;
+                        (checksum != file.checksum).then(|| {
+                            IntegrityMismatchKind::ChecksumMismatch {
+                                expected: file.checksum.clone(),
+                                actual: checksum,
+                            }
+                        })
+                    }
+                };
+                Ok::<_, Error>(kind.map(|kind| IntegrityMismatch {
+                    path: file.path.clone(),
+                    base_id: file.base_id,
+                    kind,
+                }))
+            })
+            .buffered(parallelism);
+
+        let mut report = IntegrityReport::default();
+        let mut checks = std::pin::pin!(checks);
+        while let Some(mismatch) = checks.try_next().await? {
+            match mismatch {
+                Some(mismatch) => report.mismatches.push(mismatch),
+                None => report.num_verified += 1,
+            }
+        }
+        Ok(report)
+    }
+
+    fn integrity_manifest_path(&self, version: u64) -> Path {
+        self.base
+            .clone()
+            .join(INTEGRITY_DIR)
+            .join(format!("{version}.json"))
+    }
+
+    /// The files this version needs, by base and path relative to the base root
+    async fn integrity_files(&self) -> Result<Vec<(Option<u32>, String, IntegrityFileKind)>> {
+        let mut files = Vec::new();
+        let mut add = |base_id: Option<u32>, location: &Path, kind: IntegrityFileKind| {
+            let root = integrity_base_root(self, base_id)?;
+            let path = relative_path(&root, location)?;
+            files.push((base_id, path, kind));
+            Ok::<_, Error>(())
+        };
+
+        add(
+            None,
+            &self.manifest_location.path,
+            IntegrityFileKind::Manifest,
+        )?;
+
+        // Blob sidecar files are not in the manifest, they are found next to their data
+        // file, as data/{data_file_key}/{blob_id}.blob
+        let mut data_dirs = HashMap::<Option<u32>, (Path, HashSet<String>)>::new();
+        for fragment in self.manifest.fragments.iter() {
+            for data_file in &fragment.files {
+                if data_file.has_absolute_uri() {
+                    return Err(Error::not_supported(format!(
+                        "Integrity manifests do not support data files with absolute URIs, such as {}",
+                        data_file.path
+                    )));
+                }
+                let data_dir = self.data_file_dir(data_file)?;
+                add(
+                    data_file.base_id,
+                    &data_dir.clone().join(data_file.path.as_str()),
+                    IntegrityFileKind::Data,
+                )?;
+                if let Some(key) = data_file.path.strip_suffix(".lance") {
+                    data_dirs
+                        .entry(data_file.base_id)
+                        .or_insert_with(|| (data_dir, HashSet::new()))
+                        .1
+                        .insert(key.to_string());
+                }
+            }
+            if let Some(deletion_file) = &fragment.deletion_file {
+                let dataset_dir = self.dataset_dir_for_deletion(deletion_file)?;
+                add(
+                    deletion_file.base_id,
+                    &deletion_file_path(&dataset_dir, fragment.id, deletion_file),
+                    IntegrityFileKind::Deletion,
+                )?;
+            }
+        }
+        for (base_id, (data_dir, keys)) in data_dirs {
+            let store = self.object_store(base_id).await?;
+            let mut listing = store.read_dir_all(&data_dir, None);
+            while let Some(meta) = listing.try_next().await? {
+                if meta.location.extension() != Some("blob") {
+                    continue;
+                }
+                let mut parts = meta.location.prefix_match(&data_dir).into_iter().flatten();
+                if let (Some(key), Some(_), None) = (parts.next(), parts.next(), parts.next())
+                    && keys.contains(key.as_ref())
+                {
+                    add(base_id, &meta.location, IntegrityFileKind::Blob)?;
+                }
+            }
+        }
+
+        for index in self.load_indices().await?.iter() {
+            let store = self.object_store_for_index(index).await?;
+            let index_dir = self.indice_files_dir(index)?.child(index.uuid.to_string());
+            let mut listing = store.read_dir_all(&index_dir, None);
+            while let Some(meta) = listing.try_next().await? {
+                add(index.base_id, &meta.location, IntegrityFileKind::Index)?;
+            }
+        }
+        Ok(files)
+    }
+}
+
+/// The directory the paths of files in `base_id` are relative to
+fn integrity_base_root(dataset: &Dataset, base_id: Option<u32>) -> Result<Path> {
+    match base_id {
+        Some(base_id) => {
+            let base_path = dataset.manifest.base_paths.get(&base_id).ok_or_else(|| {
+                Error::invalid_input(format!("Dataset base path with ID {} not found", base_id))
+            })?;
+            base_path.extract_path(dataset.session.store_registry())
+        }
+        None => Ok(dataset.base.clone()),
+    }
+}
+
+fn relative_path(root: &Path, location: &Path) -> Result<String> {
+    let parts = location
+        .prefix_match(root)
+        .ok_or_else(|| Error::internal(format!("File {location} is not under its base {root}")))?;
+    Ok(Path::from_iter(parts).to_string())
+}
+
+/// The size and checksum, in hex, of the file at `location`
+async fn checksum_file(
+    store: &ObjectStore,
+    location: &Path,
+    algorithm: ChecksumAlgorithm,
+) -> Result<(u64, String)> {
+    enum Hasher {
+        Xxh3(Box<Xxh3>),
+        Sha256(Sha256),
+    }
+
+    let mut hasher = match algorithm {
+        ChecksumAlgorithm::Xxh3 => Hasher::Xxh3(Box::default()),
+        ChecksumAlgorithm::Sha256 => Hasher::Sha256(Sha256::new()),
+    };
+    let mut size = 0;
+    let mut body = store.inner.get(location).await?.into_stream();
+    while let Some(chunk) = body.try_next().await? {
+        size += chunk.len() as u64;
+        match &mut hasher {
+            Hasher::Xxh3(hasher) => hasher.update(&chunk),
+            Hasher::Sha256(hasher) => hasher.update(&chunk),
+        }
+    }
+    let checksum = match hasher {
+        Hasher::Xxh3(hasher) => format!("{:032x}", hasher.digest128()),
+        Hasher::Sha256(hasher) => hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect(),
+    };
+    Ok((size, checksum))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow_array::{Int32Array, RecordBatch, RecordBatchIterator};
+    use arrow_schema::{DataType, Field, Schema as ArrowSchema};
+    use lance_core::utils::tempfile::TempStrDir;
+    use lance_datagen::{BatchCount, RowCount, array};
+    use lance_file::version::LanceFileVersion;
+    use lance_index::IndexType;
+    use lance_index::scalar::ScalarIndexParams;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::blob::{BlobArrayBuilder, blob_field};
+    use crate::dataset::WriteParams;
+
+    async fn test_dataset(uri: &str) -> Dataset {
+        let data = lance_datagen::gen_batch()
+            .col("id", array::step::<arrow_array::types::Int32Type>())
+            .into_reader_rows(RowCount::from(100), BatchCount::from(3));
+        let write_params = WriteParams {
+            max_rows_per_file: 100,
+            ..Default::default()
+        };
+        let mut dataset = Dataset::write(data, uri, Some(write_params)).await.unwrap();
+        dataset.delete("id = 5").await.unwrap();
+        dataset
+            .create_index(
+                &["id"],
+                IndexType::BTree,
+                None,
+                &ScalarIndexParams::default(),
+                false,
+            )
+            .await
+            .unwrap();
+        dataset
+    }
+
+    fn count_kind(manifest: &IntegrityManifest, kind: IntegrityFileKind) -> usize {
+        manifest
+            .files
+            .iter()
+            .filter(|file| file.kind == kind)
+            .count()
+    }
+
+    #[rstest]
+    #[case::xxh3(ChecksumAlgorithm::Xxh3, 32)]
+    #[case::sha256(ChecksumAlgorithm::Sha256, 64)]
+    #[tokio::test]
+    async fn test_untouched_dataset_verifies(
+        #[case] algorithm: ChecksumAlgorithm,
+        #[case] checksum_len: usize,
+    ) {
+        let test_dir = TempStrDir::default();
+        let dataset = test_dataset(&test_dir).await;
+
+        let manifest = dataset.write_integrity_manifest(algorithm).await.unwrap();
+        assert_eq!(manifest.dataset_version, dataset.version().version);
+        assert_eq!(count_kind(&manifest, IntegrityFileKind::Manifest), 1);
+        assert_eq!(count_kind(&manifest, IntegrityFileKind::Data), 3);
+        assert_eq!(count_kind(&manifest, IntegrityFileKind::Deletion), 1);
+        assert!(count_kind(&manifest, IntegrityFileKind::Index) > 0);
+        assert!(
+            manifest
+                .files
+                .iter()
+                .all(|file| file.checksum.len() == checksum_len && file.base_id.is_none())
+        );
+        assert!(
+            manifest
+                .files
+                .iter()
+                .any(|file| file.path.starts_with("data/"))
+        );
+
+        let read = dataset
+            .read_integrity_manifest(manifest.dataset_version)
+            .await
+            .unwrap();
+        assert_eq!(read, manifest);
+
+        let report = dataset.verify_integrity(&manifest).await.unwrap();
+        assert!(report.is_clean(), "{report:?}");
+        assert_eq!(report.num_verified, manifest.files.len() as u64);
+
+        // A fresh handle to the same files, like a copy, verifies too
+        let reopened = Dataset::open(&test_dir).await.unwrap();
+        assert!(
+            reopened
+                .verify_integrity(&manifest)
+                .await
+                .unwrap()
+                .is_clean()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_detects_truncated_and_missing_files() {
+        let test_dir = TempStrDir::default();
+        let dataset = test_dataset(&test_dir).await;
+        let manifest = dataset
+            .write_integrity_manifest(ChecksumAlgorithm::Xxh3)
+            .await
+            .unwrap();
+
+        let mut data_files = manifest
+            .files
+            .iter()
+            .filter(|file| file.kind == IntegrityFileKind::Data);
+        let truncated = data_files.next().unwrap();
+        let missing = data_files.next().unwrap();
+        let truncated_path = dataset.base.clone().join(truncated.path.as_str());
+        let content = dataset
+            .object_store
+            .read_one_all(&truncated_path)
+            .await
+            .unwrap();
+        dataset
+            .object_store
+            .put(&truncated_path, &content[..content.len() - 10])
+            .await
+            .unwrap();
+        dataset
+            .object_store
+            .delete(&dataset.base.clone().join(missing.path.as_str()))
+            .await
+            .unwrap();
+
+        let report = dataset.verify_integrity(&manifest).await.unwrap();
+        assert!(!report.is_clean());
+        assert_eq!(report.num_verified, manifest.files.len() as u64 - 2);
+        let mismatch = |path: &str| {
+            report
+                .mismatches
+                .iter()
+                .find(|mismatch| mismatch.path == path)
+                .unwrap()
+                .kind
+                .clone()
+        };
+        assert_eq!(
+            mismatch(&truncated.path),
+            IntegrityMismatchKind::SizeMismatch {
+                expected: truncated.size,
+                actual: truncated.size - 10,
+            }
+        );
+        assert_eq!(mismatch(&missing.path), IntegrityMismatchKind::Missing);
+    }
+
+    #[tokio::test]
+    async fn test_detects_corrupt_file() {
+        let test_dir = TempStrDir::default();
+        let dataset = test_dataset(&test_dir).await;
+        let manifest = dataset
+            .write_integrity_manifest(ChecksumAlgorithm::Sha256)
+            .await
+            .unwrap();
+
+        let deletion = manifest
+            .files
+            .iter()
+            .find(|file| file.kind == IntegrityFileKind::Deletion)
+            .unwrap();
+        let path = dataset.base.clone().join(deletion.path.as_str());
+        let mut content = dataset
+            .object_store
+            .read_one_all(&path)
+            .await
+            .unwrap()
+            .to_vec();
+        content[0] ^= 0xFF;
+        dataset.object_store.put(&path, &content).await.unwrap();
+
+        let report = dataset.verify_integrity(&manifest).await.unwrap();
+        assert_eq!(report.mismatches.len(), 1);
+        assert_eq!(report.mismatches[0].path, deletion.path);
+        assert!(matches!(
+            report.mismatches[0].kind,
+            IntegrityMismatchKind::ChecksumMismatch { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_blob_sidecar_files() {
+        let test_dir = TempStrDir::default();
+        let mut blobs = BlobArrayBuilder::new(1);
+        blobs.push_bytes(vec![7u8; 100 * 1024]).unwrap();
+        let schema = Arc::new(ArrowSchema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            blob_field("blob", true),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(Int32Array::from(vec![1])), blobs.finish().unwrap()],
+        )
+        .unwrap();
+        let dataset = Dataset::write(
+            RecordBatchIterator::new(vec![Ok(batch)], schema),
+            &test_dir,
+            Some(WriteParams {
+                data_storage_version: Some(LanceFileVersion::V2_2),
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+
+        let manifest = dataset
+            .write_integrity_manifest(ChecksumAlgorithm::Xxh3)
+            .await
+            .unwrap();
+        let blob = manifest
+            .files
+            .iter()
+            .find(|file| file.kind == IntegrityFileKind::Blob)
+            .expect("the sidecar file should be in the manifest");
+        assert!(blob.path.starts_with("data/") && blob.path.ends_with(".blob"));
+        assert!(
+            dataset
+                .verify_integrity(&manifest)
+                .await
+                .unwrap()
+                .is_clean()
+        );
+
+        dataset
+            .object_store
+            .delete(&dataset.base.clone().join(blob.path.as_str()))
+            .await
+            .unwrap();
+        let report = dataset.verify_integrity(&manifest).await.unwrap();
+        assert_eq!(
+            report.mismatches,
+            vec![IntegrityMismatch {
+                path: blob.path.clone(),
+                base_id: None,
+                kind: IntegrityMismatchKind::Missing,
+            }]
+        );
+    }
+}
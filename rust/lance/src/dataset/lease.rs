@@ -0,0 +1,227 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Read leases that protect the version a reader is on from cleanup
+//!
+//! A dataset opened with [`DatasetBuilder::with_read_lease`] writes a small lease
+//! object under `_leases/` with the version it reads and when the lease expires, and
+//! renews it in the background for as long as the dataset is alive.  Cleanup keeps
+//! every version pinned by an unexpired lease, see
+//! [`RemovalStats::leased_versions`](super::cleanup::RemovalStats::leased_versions).
+//!
+//! This is cooperative: readers that don't take a lease are not protected.
+//!
+//! [`DatasetBuilder::with_read_lease`]: super::builder::DatasetBuilder::with_read_lease
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use futures::TryStreamExt;
+use lance_core::error::ErrorClass;
+use lance_core::{Error, Result};
+use lance_io::object_store::ObjectStore;
+use object_store::path::Path;
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use tokio::time::{MissedTickBehavior, interval};
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::Dataset;
+use crate::utils::temporal::utc_now;
+
+pub const LEASES_DIR: &str = "_leases";
+
+/// The contents of a lease object
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeaseContents {
+    /// The version pinned by the lease
+    pub version: u64,
+    /// The branch of the version, unset for the main branch
+    pub branch: Option<String>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A lease held by an open dataset
+///
+/// The lease is renewed once per `ttl`, and each renewal moves the expiry to two
+/// `ttl`s from then, so a single late renewal does not let the lease lapse.  When
+/// the last clone of the dataset is dropped the renewal stops and the lease object is
+/// deleted.
+#[derive(Debug)]
+pub(crate) struct ReadLease {
+    object_store: Arc<ObjectStore>,
+    path: Path,
+    renewal: JoinHandle<()>,
+}
+
+impl ReadLease {
+    /// Write a lease on the version of `dataset` and start renewing it
+    pub(crate) async fn acquire(dataset: &Dataset, ttl: Duration) -> Result<Self> {
+        if ttl.is_zero() {
+            return Err(Error::invalid_input(
+                "The ttl of a read lease must be positive",
+            ));
+        }
+        let expiry = TimeDelta::from_std(ttl * 2).map_err(|_| {
+            Error::invalid_input(format!("The ttl of a read lease is too large: {ttl:?}"))
+        })?;
+        let object_store = dataset.object_store.clone();
+        let path = dataset
+            .base
+            .child(LEASES_DIR)
+            .child(format!("{}.json", Uuid::new_v4()));
+        let version = dataset.manifest.version;
+        let branch = dataset.manifest.branch.clone();
+
+        let write = {
+            let object_store = object_store.clone();
+            let path = path.clone();
+            move || {
+                let contents = LeaseContents {
+                    version,
+                    branch: branch.clone(),
+                    expires_at: utc_now() + expiry,
+                };
+                let object_store = object_store.clone();
+                let path = path.clone();
+                async move {
+                    let json = serde_json::to_vec(&contents)?;
+                    object_store.put(&path, &json).await?;
+                    Ok::<_, Error>(())
+                }
+            }
+        };
+        write().await?;
+
+        let renewal = tokio::spawn({
+            let path = path.clone();
+            async move {
+                let mut ticker = interval(ttl);
+                ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                // The first tick completes immediately
+                ticker.tick().await;
+                loop {
+                    ticker.tick().await;
+                    if let Err(err) = write().await {
+                        warn!("Failed to renew the read lease {}: {}", path, err);
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            object_store,
+            path,
+            renewal,
+        })
+    }
+}
+
+impl Drop for ReadLease {
+    fn drop(&mut self) {
+        self.renewal.abort();
+        // Release the lease now rather than leaving it to expire
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            let object_store = self.object_store.clone();
+            let path = self.path.clone();
+            handle.spawn(async move {
+                if let Err(err) = object_store.delete(&path).await {
+                    debug!("Failed to release the read lease {}: {}", path, err);
+                }
+            });
+        }
+    }
+}
+
+/// List the leases of the dataset at `base`
+///
+/// Lease objects that can't be parsed are skipped.
+pub(crate) async fn list_leases(
+    object_store: &ObjectStore,
+    base: &Path,
+) -> Result<Vec<(Path, LeaseContents)>> {
+    let objects = object_store
+        .list(Some(base.child(LEASES_DIR)))
+        .try_collect::<Vec<_>>()
+        .await?;
+    let mut leases = Vec::with_capacity(objects.len());
+    for object in objects {
+        let bytes = match object_store.read_one_all(&object.location).await {
+            Ok(bytes) => bytes,
+            // Released since it was listed
+            Err(err) if err.class() == ErrorClass::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+        match serde_json::from_slice::<LeaseContents>(&bytes) {
+            Ok(contents) => leases.push((object.location, contents)),
+            Err(err) => warn!("Skipping invalid read lease {}: {}", object.location, err),
+        }
+    }
+    Ok(leases)
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::types::Int32Type;
+    use lance_datagen::{BatchCount, RowCount, array};
+    use mock_instant::thread_local::MockClock;
+
+    use super::*;
+    use crate::dataset::builder::DatasetBuilder;
+
+    #[tokio::test]
+    async fn test_lease_renewed_and_released() {
+        let data = lance_datagen::gen_batch()
+            .col("x", array::step::<Int32Type>())
+            .into_reader_rows(RowCount::from(10), BatchCount::from(1));
+        let dataset = Dataset::write(data, "memory://leases", None).await.unwrap();
+        let object_store = dataset.object_store.clone();
+        let base = dataset.base.clone();
+
+        MockClock::set_system_time(Duration::from_secs(1000));
+        let ttl = Duration::from_millis(50);
+        let leased = DatasetBuilder::from_uri("memory://leases")
+            .with_session(dataset.session.clone())
+            .with_read_lease(ttl)
+            .load()
+            .await
+            .unwrap();
+        let leases = list_leases(&object_store, &base).await.unwrap();
+        assert_eq!(leases.len(), 1);
+        let (path, contents) = &leases[0];
+        assert_eq!(contents.version, 1);
+        assert_eq!(contents.branch, None);
+        assert_eq!(
+            contents.expires_at,
+            DateTime::from_timestamp(1000, 100_000_000).unwrap()
+        );
+
+        // Renewals move the expiry while any clone of the dataset, e.g. one held by a
+        // running scan, is alive
+        let scan = leased.clone();
+        drop(leased);
+        MockClock::set_system_time(Duration::from_secs(2000));
+        tokio::time::sleep(ttl * 3).await;
+        let leases = list_leases(&object_store, &base).await.unwrap();
+        assert_eq!(leases.len(), 1);
+        assert_eq!(&leases[0].0, path);
+        assert_eq!(
+            leases[0].1.expires_at,
+            DateTime::from_timestamp(2000, 100_000_000).unwrap()
+        );
+
+        drop(scan);
+        tokio::time::sleep(ttl).await;
+        assert!(list_leases(&object_store, &base).await.unwrap().is_empty());
+
+        let err = DatasetBuilder::from_uri("memory://leases")
+            .with_session(dataset.session.clone())
+            .with_read_lease(Duration::ZERO)
+            .load()
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidInput { .. }), "{err}");
+    }
+}
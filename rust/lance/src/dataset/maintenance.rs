@@ -0,0 +1,1106 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Background table maintenance.
+//!
+//! Services that keep a dataset open usually want to compact small files and
+//! merge delta indices as the table grows, without writing their own loop. A
+//! [`Scheduler`] checks the dataset on a fixed interval and runs
+//! [`compact_files`] and [`DatasetIndexExt::optimize_indices`] when the
+//! [`MaintenancePolicy`] thresholds are crossed.
+//!
+//! Every check starts from the latest version, so the dataset can be written
+//! concurrently by other handles or processes. Actions that lose a commit race
+//! are retried on top of the new version.
+//!
+//! To run maintenance once, e.g. from a cron job, call [`Dataset::optimize`].
+//! It inspects the dataset the same way and runs compaction, index merging
+//! and cleanup of old versions as needed.
+//!
+//! ```no_run
+//! # use lance::Dataset;
+//! # use lance::dataset::maintenance::{MaintenancePolicy, Scheduler};
+//! # async fn example(dataset: Dataset) -> lance::Result<()> {
+//! let handle = Scheduler::new(dataset, MaintenancePolicy::default()).spawn();
+//! // ... serve requests ...
+//! handle.shutdown().await
+//! # }
+//! ```
+
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::{StreamExt, stream};
+use lance_core::error::ErrorClass;
+use lance_core::utils::backoff::Backoff;
+use lance_core::{Error, Result};
+use lance_index::is_system_index;
+use lance_index::optimize::OptimizeOptions;
+use rand::Rng;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+use super::cleanup::RemovalStats;
+use super::optimize::{CompactionMetrics, CompactionOptions, compact_files};
+use crate::Dataset;
+use crate::index::DatasetIndexExt;
+use crate::utils::temporal::utc_now;
+
+/// When the [`Scheduler`] checks the dataset and what it does about it.
+#[derive(Debug, Clone)]
+pub struct MaintenancePolicy {
+    /// Compact once at least this many fragments have fewer rows than
+    /// `compaction.target_rows_per_fragment`. Default: 16.
+    pub min_small_fragments: usize,
+    /// Merge an index once it consists of at least this many segments, i.e.
+    /// the base index and its deltas. Default: 4.
+    pub max_delta_indices: usize,
+    /// Time between checks. Default: 5 minutes.
+    pub interval: Duration,
+    /// A random delay of up to this much is added to every interval, so that
+    /// schedulers started together do not all commit at the same time.
+    /// Default: 30 seconds.
+    pub jitter: Duration,
+    /// How many actions of one check may run at the same time. Default: 1.
+    pub max_concurrent_jobs: usize,
+    /// How many times an action is retried after a commit conflict. Default: 3.
+    pub max_conflict_retries: u32,
+    /// Options for the compactions the scheduler runs.
+    pub compaction: CompactionOptions,
+    /// Options for the index merges the scheduler runs. `index_names` and
+    /// `num_indices_to_merge` are replaced by the indices that crossed the
+    /// threshold and their segment count.
+    pub optimize: OptimizeOptions,
+}
+
+impl Default for MaintenancePolicy {
+    fn default() -> Self {
+        Self {
+            min_small_fragments: 16,
+            max_delta_indices: 4,
+            interval: Duration::from_secs(5 * 60),
+            jitter: Duration::from_secs(30),
+            max_concurrent_jobs: 1,
+            max_conflict_retries: 3,
+            compaction: CompactionOptions::default(),
+            optimize: OptimizeOptions::default(),
+        }
+    }
+}
+
+/// An action the [`Scheduler`] decided to run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MaintenanceAction {
+    /// Compact small fragments with [`compact_files`].
+    CompactFiles,
+    /// Merge the segments of the given indices.
+    OptimizeIndices {
+        index_names: Vec<String>,
+        num_indices_to_merge: usize,
+    },
+}
+
+/// The state of the dataset seen by one check.
+#[derive(Debug, Clone)]
+pub struct MaintenanceCheck {
+    /// The version that was checked.
+    pub version: u64,
+    /// Fragments with fewer rows than the compaction target.
+    pub small_fragments: usize,
+    /// The number of segments of every user index, by name.
+    pub index_segments: Vec<(String, usize)>,
+    /// What the check decided to run, empty if no threshold was crossed.
+    pub actions: Vec<MaintenanceAction>,
+}
+
+/// The outcome of one [`MaintenanceAction`].
+#[derive(Debug)]
+pub struct ActionReport {
+    pub action: MaintenanceAction,
+    /// More than one if the action was retried after commit conflicts.
+    pub attempts: u32,
+    pub elapsed: Duration,
+    /// The version committed by the action.
+    pub result: Result<u64>,
+    /// Set for successful compactions.
+    pub compaction_metrics: Option<CompactionMetrics>,
+}
+
+/// Hooks to observe a [`Scheduler`], e.g. to export metrics.
+///
+/// Checks and actions are also logged, so implementing this is optional.
+pub trait MaintenanceHook: Send + Sync {
+    /// Called after every check, before its actions run.
+    fn on_check(&self, _check: &MaintenanceCheck) {}
+
+    /// Called when an action finished, successfully or not.
+    fn on_action(&self, _report: &ActionReport) {}
+}
+
+/// The time source of a [`Scheduler`].
+#[async_trait::async_trait]
+pub trait Clock: Send + Sync + Debug {
+    /// Time elapsed since a fixed, arbitrary origin.
+    fn now(&self) -> Duration;
+
+    /// Wait until [`Self::now`] reaches `deadline`.
+    async fn sleep_until(&self, deadline: Duration);
+}
+
+/// A [`Clock`] backed by the tokio timer.
+#[derive(Debug)]
+pub struct SystemClock {
+    origin: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self {
+            origin: Instant::now(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+
+    async fn sleep_until(&self, deadline: Duration) {
+        tokio::time::sleep_until(self.origin + deadline).await;
+    }
+}
+
+/// Runs compaction and index optimization on a dataset in the background.
+pub struct Scheduler {
+    dataset: Dataset,
+    policy: MaintenancePolicy,
+    clock: Arc<dyn Clock>,
+    hook: Option<Arc<dyn MaintenanceHook>>,
+}
+
+impl Scheduler {
+    pub fn new(dataset: Dataset, policy: MaintenancePolicy) -> Self {
+        Self {
+            dataset,
+            policy,
+            clock: Arc::new(SystemClock::default()),
+            hook: None,
+        }
+    }
+
+    pub fn with_hook(mut self, hook: Arc<dyn MaintenanceHook>) -> Self {
+        self.hook = Some(hook);
+        self
+    }
+
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Start checking the dataset on a tokio task, the first check happens
+    /// after one interval.
+    pub fn spawn(self) -> SchedulerHandle {
+        let token = CancellationToken::new();
+        let deadline = self.clock.now() + self.next_interval();
+        let task = tokio::spawn(self.run(token.clone(), deadline));
+        SchedulerHandle {
+            token,
+            task: Some(task),
+        }
+    }
+
+    /// Check the latest version of the dataset once and run the actions the
+    /// policy calls for.
+    pub async fn run_once(&mut self) -> Result<MaintenanceCheck> {
+        self.dataset.checkout_latest().await?;
+        let check = self.check().await?;
+        log::debug!(
+            "Maintenance check of {} at version {}: {} small fragments, actions {:?}",
+            self.dataset.uri(),
+            check.version,
+            check.small_fragments,
+            check.actions
+        );
+        if let Some(hook) = &self.hook {
+            hook.on_check(&check);
+        }
+        stream::iter(check.actions.clone())
+            .map(|action| self.run_action(action))
+            .buffer_unordered(self.policy.max_concurrent_jobs.max(1))
+            .collect::<Vec<_>>()
+            .await;
+        Ok(check)
+    }
+
+    async fn run(mut self, token: CancellationToken, mut deadline: Duration) {
+        loop {
+            tokio::select! {
+                biased;
+                _ = token.cancelled() => return,
+                _ = self.clock.sleep_until(deadline) => {}
+            }
+            // Schedule from the start of the check, so a slow compaction does
+            // not push every later check back.
+            deadline = self.clock.now() + self.next_interval();
+            // A check that already started is allowed to finish, so shutdown
+            // never abandons a compaction halfway through.
+            if let Err(err) = self.run_once().await {
+                log::warn!(
+                    "Maintenance check of {} failed: {}",
+                    self.dataset.uri(),
+                    err
+                );
+            }
+        }
+    }
+
+    fn next_interval(&self) -> Duration {
+        if self.policy.jitter.is_zero() {
+            return self.policy.interval;
+        }
+        self.policy.interval + rand::rng().random_range(Duration::ZERO..=self.policy.jitter)
+    }
+
+    async fn check(&self) -> Result<MaintenanceCheck> {
+        let target_rows = self.policy.compaction.target_rows_per_fragment;
+        let small_fragments = self
+            .dataset
+            .manifest
+            .fragments
+            .iter()
+            .filter(|fragment| fragment.num_rows().is_some_and(|rows| rows < target_rows))
+            .count();
+
+        let index_segments = index_segments(&self.dataset).await?;
+
+        let mut actions = Vec::new();
+        if small_fragments >= self.policy.min_small_fragments {
+            actions.push(MaintenanceAction::CompactFiles);
+        }
+        let to_merge = index_segments
+            .iter()
+            .filter(|(_, segments)| *segments >= self.policy.max_delta_indices.max(2))
+            .collect::<Vec<_>>();
+        if let Some(num_indices_to_merge) = to_merge.iter().map(|(_, segments)| *segments).max() {
+            actions.push(MaintenanceAction::OptimizeIndices {
+                index_names: to_merge.iter().map(|(name, _)| name.clone()).collect(),
+                num_indices_to_merge,
+            });
+        }
+
+        Ok(MaintenanceCheck {
+            version: self.dataset.manifest.version,
+            small_fragments,
+            index_segments,
+            actions,
+        })
+    }
+
+    async fn run_action(&self, action: MaintenanceAction) {
+        let start = self.clock.now();
+        let mut dataset = self.dataset.clone();
+        let mut backoff = Backoff::default();
+        let mut attempts = 0;
+        let (result, compaction_metrics) = loop {
+            attempts += 1;
+            let result = match &action {
+                MaintenanceAction::CompactFiles => {
+                    compact_files(&mut dataset, self.policy.compaction.clone(), None)
+                        .await
+                        .map(Some)
+                }
+                MaintenanceAction::OptimizeIndices {
+                    index_names,
+                    num_indices_to_merge,
+                } => {
+                    let options = self
+                        .policy
+                        .optimize
+                        .clone()
+                        .index_names(index_names.clone())
+                        .num_indices_to_merge(Some(*num_indices_to_merge));
+                    dataset.optimize_indices(&options).await.map(|_| None)
+                }
+            };
+            match result {
+                Ok(metrics) => break (Ok(dataset.manifest.version), metrics),
+                Err(err) if is_conflict(&err) && attempts <= self.policy.max_conflict_retries => {
+                    log::info!(
+                        "Maintenance action {:?} on {} conflicted, retrying: {}",
+                        action,
+                        dataset.uri(),
+                        err
+                    );
+                    let delay = backoff.next_backoff();
+                    self.clock.sleep_until(self.clock.now() + delay).await;
+                    if let Err(err) = dataset.checkout_latest().await {
+                        break (Err(err), None);
+                    }
+                }
+                Err(err) => break (Err(err), None),
+            }
+        };
+
+        let report = ActionReport {
+            action,
+            attempts,
+            elapsed: self.clock.now().saturating_sub(start),
+            result,
+            compaction_metrics,
+        };
+        match &report.result {
+            Ok(version) => log::info!(
+                "Maintenance action {:?} on {} committed version {} in {:?}, compaction metrics: {:?}",
+                report.action,
+                self.dataset.uri(),
+                version,
+                report.elapsed,
+                report.compaction_metrics
+            ),
+            Err(err) => log::warn!(
+                "Maintenance action {:?} on {} failed after {} attempts: {}",
+                report.action,
+                self.dataset.uri(),
+                report.attempts,
+                err
+            ),
+        }
+        if let Some(hook) = &self.hook {
+            hook.on_action(&report);
+        }
+    }
+}
+
+/// The number of segments of every user index, by name.
+async fn index_segments(dataset: &Dataset) -> Result<Vec<(String, usize)>> {
+    let indices = dataset.load_indices().await?;
+    let mut index_segments = Vec::<(String, usize)>::new();
+    for index in indices.iter().filter(|index| !is_system_index(index)) {
+        match index_segments
+            .iter_mut()
+            .find(|(name, _)| name == &index.name)
+        {
+            Some((_, segments)) => *segments += 1,
+            None => index_segments.push((index.name.clone(), 1)),
+        }
+    }
+    Ok(index_segments)
+}
+
+fn is_conflict(err: &Error) -> bool {
+    err.class() == ErrorClass::Conflict || err.is_retryable()
+}
+
+/// Stops a spawned [`Scheduler`], also when dropped.
+pub struct SchedulerHandle {
+    token: CancellationToken,
+    task: Option<JoinHandle<()>>,
+}
+
+impl SchedulerHandle {
+    /// Stop the scheduler and wait for a check that is in progress to finish.
+    pub async fn shutdown(mut self) -> Result<()> {
+        self.token.cancel();
+        if let Some(task) = self.task.take() {
+            task.await
+                .map_err(|err| Error::internal(format!("Maintenance scheduler panicked: {err}")))?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for SchedulerHandle {
+    fn drop(&mut self) {
+        self.token.cancel();
+    }
+}
+
+/// Which parts of [`Dataset::optimize`] run and when they are needed.
+#[derive(Debug, Clone)]
+pub struct DatasetOptimizeOptions {
+    /// Compact small fragments and fragments with many deletions. Default: true.
+    pub compact_files: bool,
+    /// Merge delta indices. Default: true.
+    pub optimize_indices: bool,
+    /// Remove versions older than `cleanup_older_than`. Default: true.
+    pub cleanup_old_versions: bool,
+    /// Compact once at least this many fragments have fewer rows than
+    /// `compaction.target_rows_per_fragment`. Default: 2.
+    pub min_small_fragments: usize,
+    /// Merge an index once it consists of at least this many segments.
+    /// Default: 2.
+    pub max_delta_indices: usize,
+    /// Clean up once at least this many versions are older than
+    /// `cleanup_older_than`. Default: 1.
+    pub min_old_versions: usize,
+    /// Versions younger than this are kept. Default: 7 days.
+    pub cleanup_older_than: Duration,
+    /// No step is started once this much time has passed, a step that already
+    /// started runs to completion. Default: no limit.
+    pub time_budget: Option<Duration>,
+    /// Only report what would run, without writing anything. Default: false.
+    pub dry_run: bool,
+    /// Options of the compaction step. A fragment whose deleted fraction
+    /// exceeds `materialize_deletions_threshold` also triggers compaction.
+    pub compaction: CompactionOptions,
+    /// Options of the index step, `index_names` and `num_indices_to_merge` are
+    /// set from the indices that need merging.
+    pub optimize: OptimizeOptions,
+}
+
+impl Default for DatasetOptimizeOptions {
+    fn default() -> Self {
+        Self {
+            compact_files: true,
+            optimize_indices: true,
+            cleanup_old_versions: true,
+            min_small_fragments: 2,
+            max_delta_indices: 2,
+            min_old_versions: 1,
+            cleanup_older_than: Duration::from_secs(7 * 24 * 60 * 60),
+            time_budget: None,
+            dry_run: false,
+            compaction: CompactionOptions::default(),
+            optimize: OptimizeOptions::default(),
+        }
+    }
+}
+
+/// A step of [`Dataset::optimize`], in the order they run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptimizeStep {
+    CompactFiles,
+    OptimizeIndices,
+    CleanupOldVersions,
+}
+
+/// What happened to one [`OptimizeStep`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepStatus {
+    /// Turned off in the options.
+    Disabled,
+    /// No threshold was crossed.
+    NotNeeded,
+    /// Would have run, but this is a dry run.
+    Planned,
+    /// Would have run, but the time budget was used up.
+    OutOfBudget,
+    /// Ran and committed.
+    Completed,
+}
+
+/// The outcome of one [`OptimizeStep`].
+#[derive(Debug, Clone)]
+pub struct StepReport {
+    pub step: OptimizeStep,
+    pub status: StepStatus,
+    /// The stats the decision was based on.
+    pub reason: String,
+    pub elapsed: Duration,
+}
+
+/// What [`Dataset::optimize`] did and why.
+#[derive(Debug, Clone)]
+pub struct OptimizeReport {
+    /// The version of the dataset after all steps.
+    pub version: u64,
+    /// One entry per [`OptimizeStep`], in the order they ran.
+    pub steps: Vec<StepReport>,
+    /// Set if files were compacted.
+    pub compaction_metrics: Option<CompactionMetrics>,
+    /// Set if old versions were cleaned up.
+    pub removal_stats: Option<RemovalStats>,
+}
+
+impl OptimizeReport {
+    pub fn step(&self, step: OptimizeStep) -> &StepReport {
+        self.steps
+            .iter()
+            .find(|report| report.step == step)
+            .expect("every step is reported")
+    }
+}
+
+/// Implementation of [`Dataset::optimize`].
+///
+/// Compaction runs first, since it materializes deletions and rewrites the
+/// fragments the indices cover, then index merging, then cleanup so that it
+/// also removes the versions the first two replaced. A failing step returns
+/// its error, the steps committed before it are kept.
+pub(crate) async fn optimize(
+    dataset: &mut Dataset,
+    options: DatasetOptimizeOptions,
+) -> Result<OptimizeReport> {
+    let start = Instant::now();
+    // Whether a step runs, or why not.
+    let decide = |enabled: bool, needed: bool| {
+        if !enabled {
+            Some(StepStatus::Disabled)
+        } else if !needed {
+            Some(StepStatus::NotNeeded)
+        } else if options.dry_run {
+            Some(StepStatus::Planned)
+        } else if options
+            .time_budget
+            .is_some_and(|budget| start.elapsed() >= budget)
+        {
+            Some(StepStatus::OutOfBudget)
+        } else {
+            None
+        }
+    };
+    let mut steps = Vec::with_capacity(3);
+    let mut compaction_metrics = None;
+    let mut removal_stats = None;
+
+    let target_rows = options.compaction.target_rows_per_fragment;
+    let deletions_threshold = options.compaction.materialize_deletions_threshold;
+    let mut small_fragments = 0;
+    let mut deleted_fragments = 0;
+    for fragment in dataset.manifest.fragments.iter() {
+        if fragment.num_rows().is_some_and(|rows| rows < target_rows) {
+            small_fragments += 1;
+        }
+        let deleted = fragment
+            .deletion_file
+            .as_ref()
+            .and_then(|file| file.num_deleted_rows)
+            .unwrap_or_default();
+        let physical = fragment.physical_rows.unwrap_or_default().max(1);
+        if deleted as f32 / physical as f32 > deletions_threshold {
+            deleted_fragments += 1;
+        }
+    }
+    let needed = small_fragments >= options.min_small_fragments.max(2)
+        || (options.compaction.materialize_deletions && deleted_fragments > 0);
+    let reason = format!(
+        "{} fragments, {} with fewer than {} rows, {} with more than {:.0}% rows deleted",
+        dataset.manifest.fragments.len(),
+        small_fragments,
+        target_rows,
+        deleted_fragments,
+        deletions_threshold * 100.0
+    );
+    let step_start = Instant::now();
+    let status = match decide(options.compact_files, needed) {
+        Some(status) => status,
+        None => {
+            compaction_metrics =
+                Some(compact_files(dataset, options.compaction.clone(), None).await?);
+            StepStatus::Completed
+        }
+    };
+    steps.push(StepReport {
+        step: OptimizeStep::CompactFiles,
+        status,
+        reason,
+        elapsed: step_start.elapsed(),
+    });
+
+    // Compaction remaps the indices but leaves their segments as they are.
+    let index_segments = index_segments(dataset).await?;
+    let to_merge = index_segments
+        .iter()
+        .filter(|(_, segments)| *segments >= options.max_delta_indices.max(2))
+        .collect::<Vec<_>>();
+    let reason = format!(
+        "index segments {:?}, merging at {}",
+        index_segments,
+        options.max_delta_indices.max(2)
+    );
+    let step_start = Instant::now();
+    let status = match decide(options.optimize_indices, !to_merge.is_empty()) {
+        Some(status) => status,
+        None => {
+            let optimize = options
+                .optimize
+                .clone()
+                .index_names(to_merge.iter().map(|(name, _)| name.clone()).collect())
+                .num_indices_to_merge(to_merge.iter().map(|(_, segments)| *segments).max());
+            dataset.optimize_indices(&optimize).await?;
+            StepStatus::Completed
+        }
+    };
+    steps.push(StepReport {
+        step: OptimizeStep::OptimizeIndices,
+        status,
+        reason,
+        elapsed: step_start.elapsed(),
+    });
+
+    let older_than = chrono::Duration::from_std(options.cleanup_older_than)
+        .map_err(|err| Error::invalid_input(format!("Invalid cleanup_older_than: {err}")))?;
+    let (old_versions, reason) = if options.cleanup_old_versions {
+        let cutoff = utc_now() - older_than;
+        let old_versions = dataset
+            .versions()
+            .await?
+            .iter()
+            .filter(|version| {
+                version.version != dataset.manifest.version && version.timestamp < cutoff
+            })
+            .count();
+        let reason = format!(
+            "{} versions older than {:?}",
+            old_versions, options.cleanup_older_than
+        );
+        (old_versions, reason)
+    } else {
+        (0, String::new())
+    };
+    let step_start = Instant::now();
+    let status = match decide(
+        options.cleanup_old_versions,
+        old_versions >= options.min_old_versions.max(1),
+    ) {
+        Some(status) => status,
+        None => {
+            removal_stats = Some(
+                dataset
+                    .cleanup_old_versions(older_than, Some(false), Some(false))
+                    .await?,
+            );
+            StepStatus::Completed
+        }
+    };
+    steps.push(StepReport {
+        step: OptimizeStep::CleanupOldVersions,
+        status,
+        reason,
+        elapsed: step_start.elapsed(),
+    });
+
+    for step in &steps {
+        log::info!(
+            "Optimize {:?} of {}: {:?} ({}) in {:?}",
+            step.step,
+            dataset.uri(),
+            step.status,
+            step.reason,
+            step.elapsed
+        );
+    }
+    Ok(OptimizeReport {
+        version: dataset.manifest.version,
+        steps,
+        compaction_metrics,
+        removal_stats,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use arrow_array::types::{Float32Type, Int32Type};
+    use lance_core::utils::tempfile::TempStrDir;
+    use lance_datagen::{BatchCount, Dimension, RowCount, array, gen_batch};
+    use lance_index::IndexType;
+    use lance_linalg::distance::MetricType;
+    use tokio::sync::{mpsc, watch};
+
+    use super::*;
+    use crate::dataset::{WriteMode, WriteParams};
+    use crate::index::vector::VectorIndexParams;
+
+    /// A clock that only moves when the test advances it.
+    #[derive(Debug)]
+    struct MockClock {
+        now: watch::Sender<Duration>,
+    }
+
+    impl MockClock {
+        fn new() -> Arc<Self> {
+            Arc::new(Self {
+                now: watch::Sender::new(Duration::ZERO),
+            })
+        }
+
+        fn advance(&self, duration: Duration) {
+            self.now.send_modify(|now| *now += duration);
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Clock for MockClock {
+        fn now(&self) -> Duration {
+            *self.now.borrow()
+        }
+
+        async fn sleep_until(&self, deadline: Duration) {
+            let mut now = self.now.subscribe();
+            now.wait_for(|now| *now >= deadline).await.unwrap();
+        }
+    }
+
+    enum Event {
+        Check(MaintenanceCheck),
+        Action(ActionReport),
+    }
+
+    struct ChannelHook(Mutex<mpsc::UnboundedSender<Event>>);
+
+    impl MaintenanceHook for ChannelHook {
+        fn on_check(&self, check: &MaintenanceCheck) {
+            let _ = self.0.lock().unwrap().send(Event::Check(check.clone()));
+        }
+
+        fn on_action(&self, report: &ActionReport) {
+            let report = ActionReport {
+                action: report.action.clone(),
+                attempts: report.attempts,
+                elapsed: report.elapsed,
+                result: match &report.result {
+                    Ok(version) => Ok(*version),
+                    Err(err) => Err(Error::internal(err.to_string())),
+                },
+                compaction_metrics: report.compaction_metrics.clone(),
+            };
+            let _ = self.0.lock().unwrap().send(Event::Action(report));
+        }
+    }
+
+    const INTERVAL: Duration = Duration::from_secs(60);
+
+    fn spawn(
+        dataset: Dataset,
+        policy: MaintenancePolicy,
+    ) -> (
+        SchedulerHandle,
+        Arc<MockClock>,
+        mpsc::UnboundedReceiver<Event>,
+    ) {
+        let clock = MockClock::new();
+        let (tx, rx) = mpsc::unbounded_channel();
+        let handle = Scheduler::new(
+            dataset,
+            MaintenancePolicy {
+                interval: INTERVAL,
+                jitter: Duration::ZERO,
+                ..policy
+            },
+        )
+        .with_clock(clock.clone())
+        .with_hook(Arc::new(ChannelHook(Mutex::new(tx))))
+        .spawn();
+        (handle, clock, rx)
+    }
+
+    async fn next_check(
+        clock: &MockClock,
+        events: &mut mpsc::UnboundedReceiver<Event>,
+    ) -> MaintenanceCheck {
+        clock.advance(INTERVAL);
+        match events.recv().await.unwrap() {
+            Event::Check(check) => check,
+            Event::Action(report) => panic!("unexpected action {:?}", report.action),
+        }
+    }
+
+    async fn next_action(events: &mut mpsc::UnboundedReceiver<Event>) -> ActionReport {
+        match events.recv().await.unwrap() {
+            Event::Action(report) => report,
+            Event::Check(check) => panic!("unexpected check {check:?}"),
+        }
+    }
+
+    fn int_data(rows: u64) -> impl arrow_array::RecordBatchReader + Send + 'static {
+        gen_batch()
+            .col("i", array::step::<Int32Type>())
+            .into_reader_rows(RowCount::from(rows), BatchCount::from(1))
+    }
+
+    async fn append(uri: &str, data: impl arrow_array::RecordBatchReader + Send + 'static) {
+        let params = WriteParams {
+            mode: WriteMode::Append,
+            ..Default::default()
+        };
+        Dataset::write(data, uri, Some(params)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_compaction_fires_at_threshold() {
+        let test_dir = TempStrDir::default();
+        let uri = test_dir.as_str();
+        let dataset = Dataset::write(int_data(10), uri, None).await.unwrap();
+        append(uri, int_data(10)).await;
+        append(uri, int_data(10)).await;
+
+        let policy = MaintenancePolicy {
+            min_small_fragments: 4,
+            compaction: CompactionOptions {
+                target_rows_per_fragment: 1000,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let (handle, clock, mut events) = spawn(dataset, policy);
+
+        // Nothing happens before the first interval has passed.
+        clock.advance(INTERVAL - Duration::from_secs(1));
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+        assert!(events.try_recv().is_err());
+        clock.advance(Duration::from_secs(1));
+        let check = match events.recv().await.unwrap() {
+            Event::Check(check) => check,
+            Event::Action(_) => panic!("unexpected action"),
+        };
+        assert_eq!(check.small_fragments, 3);
+        assert!(check.actions.is_empty());
+
+        // Another writer pushes the dataset over the threshold.
+        append(uri, int_data(10)).await;
+        let check = next_check(&clock, &mut events).await;
+        assert_eq!(check.version, 4);
+        assert_eq!(check.small_fragments, 4);
+        assert_eq!(check.actions, vec![MaintenanceAction::CompactFiles]);
+        let report = next_action(&mut events).await;
+        assert_eq!(report.action, MaintenanceAction::CompactFiles);
+        assert_eq!(report.attempts, 1);
+        let compacted_version = report.result.unwrap();
+        let metrics = report.compaction_metrics.unwrap();
+        assert_eq!(metrics.fragments_removed, 4);
+        assert_eq!(metrics.fragments_added, 1);
+
+        // Compacted, the dataset is below the threshold again.
+        let check = next_check(&clock, &mut events).await;
+        assert_eq!(check.version, compacted_version);
+        assert_eq!(check.small_fragments, 1);
+        assert!(check.actions.is_empty());
+
+        handle.shutdown().await.unwrap();
+        let dataset = Dataset::open(uri).await.unwrap();
+        assert_eq!(dataset.get_fragments().len(), 1);
+        assert_eq!(dataset.count_rows(None).await.unwrap(), 40);
+    }
+
+    #[tokio::test]
+    async fn test_optimize_fires_at_threshold() {
+        let test_dir = TempStrDir::default();
+        let uri = test_dir.as_str();
+        let vec_data = |rows| {
+            gen_batch()
+                .col("vec", array::rand_vec::<Float32Type>(Dimension::from(8)))
+                .into_reader_rows(RowCount::from(rows), BatchCount::from(1))
+        };
+        let mut dataset = Dataset::write(vec_data(256), uri, None).await.unwrap();
+        dataset
+            .create_index(
+                &["vec"],
+                IndexType::Vector,
+                Some("vec_idx".into()),
+                &VectorIndexParams::ivf_flat(2, MetricType::L2),
+                true,
+            )
+            .await
+            .unwrap();
+        let add_delta = || async {
+            append(uri, vec_data(64)).await;
+            let mut dataset = Dataset::open(uri).await.unwrap();
+            dataset
+                .optimize_indices(&OptimizeOptions::append())
+                .await
+                .unwrap();
+        };
+        add_delta().await;
+
+        let policy = MaintenancePolicy {
+            min_small_fragments: usize::MAX,
+            max_delta_indices: 3,
+            ..Default::default()
+        };
+        let (handle, clock, mut events) = spawn(dataset, policy);
+
+        let check = next_check(&clock, &mut events).await;
+        assert_eq!(check.index_segments, vec![("vec_idx".to_string(), 2)]);
+        assert!(check.actions.is_empty());
+
+        add_delta().await;
+        let check = next_check(&clock, &mut events).await;
+        assert_eq!(check.index_segments, vec![("vec_idx".to_string(), 3)]);
+        let action = MaintenanceAction::OptimizeIndices {
+            index_names: vec!["vec_idx".to_string()],
+            num_indices_to_merge: 3,
+        };
+        assert_eq!(check.actions, vec![action.clone()]);
+        let report = next_action(&mut events).await;
+        assert_eq!(report.action, action);
+        report.result.unwrap();
+
+        let check = next_check(&clock, &mut events).await;
+        assert_eq!(check.index_segments, vec![("vec_idx".to_string(), 1)]);
+        assert!(check.actions.is_empty());
+
+        handle.shutdown().await.unwrap();
+    }
+
+    /// Three fragments covered by an index with two deltas.
+    async fn fragmented_vec_dataset(uri: &str) -> Dataset {
+        let vec_data = |rows| {
+            gen_batch()
+                .col("vec", array::rand_vec::<Float32Type>(Dimension::from(8)))
+                .into_reader_rows(RowCount::from(rows), BatchCount::from(1))
+        };
+        let mut dataset = Dataset::write(vec_data(256), uri, None).await.unwrap();
+        dataset
+            .create_index(
+                &["vec"],
+                IndexType::Vector,
+                Some("vec_idx".into()),
+                &VectorIndexParams::ivf_flat(2, MetricType::L2),
+                true,
+            )
+            .await
+            .unwrap();
+        for _ in 0..2 {
+            append(uri, vec_data(64)).await;
+            dataset.checkout_latest().await.unwrap();
+            dataset
+                .optimize_indices(&OptimizeOptions::append())
+                .await
+                .unwrap();
+        }
+        dataset
+    }
+
+    #[tokio::test]
+    async fn test_dataset_optimize() {
+        let test_dir = TempStrDir::default();
+        let uri = test_dir.as_str();
+        let mut dataset = fragmented_vec_dataset(uri).await;
+        assert_eq!(dataset.get_fragments().len(), 3);
+        assert_eq!(
+            index_segments(&dataset).await.unwrap(),
+            vec![("vec_idx".to_string(), 3)]
+        );
+
+        let report = dataset
+            .optimize(DatasetOptimizeOptions::default())
+            .await
+            .unwrap();
+        let statuses = report
+            .steps
+            .iter()
+            .map(|step| (step.step, step.status))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            vec![
+                (OptimizeStep::CompactFiles, StepStatus::Completed),
+                (OptimizeStep::OptimizeIndices, StepStatus::Completed),
+                (OptimizeStep::CleanupOldVersions, StepStatus::NotNeeded),
+            ]
+        );
+        let metrics = report.compaction_metrics.unwrap();
+        assert_eq!(metrics.fragments_removed, 3);
+        assert_eq!(metrics.fragments_added, 1);
+        assert!(report.removal_stats.is_none());
+
+        let dataset = Dataset::open(uri).await.unwrap();
+        assert_eq!(dataset.manifest.version, report.version);
+        assert_eq!(dataset.get_fragments().len(), 1);
+        assert_eq!(dataset.count_rows(None).await.unwrap(), 384);
+        assert_eq!(
+            index_segments(&dataset).await.unwrap(),
+            vec![("vec_idx".to_string(), 1)]
+        );
+
+        // Nothing left to do.
+        let mut dataset = dataset;
+        let report = dataset
+            .optimize(DatasetOptimizeOptions::default())
+            .await
+            .unwrap();
+        assert!(
+            report
+                .steps
+                .iter()
+                .all(|step| step.status == StepStatus::NotNeeded)
+        );
+        assert_eq!(dataset.manifest.version, report.version);
+    }
+
+    #[tokio::test]
+    async fn test_dataset_optimize_dry_run() {
+        let test_dir = TempStrDir::default();
+        let uri = test_dir.as_str();
+        let mut dataset = fragmented_vec_dataset(uri).await;
+        let version = dataset.manifest.version;
+
+        let options = DatasetOptimizeOptions {
+            dry_run: true,
+            cleanup_older_than: Duration::ZERO,
+            ..Default::default()
+        };
+        let report = dataset.optimize(options).await.unwrap();
+        assert_eq!(report.version, version);
+        assert!(
+            report
+                .steps
+                .iter()
+                .all(|step| step.status == StepStatus::Planned)
+        );
+        let compact = report.step(OptimizeStep::CompactFiles);
+        assert!(compact.reason.starts_with("3 fragments, 3 with fewer than"));
+        assert!(report.compaction_metrics.is_none());
+        assert!(report.removal_stats.is_none());
+
+        let dataset = Dataset::open(uri).await.unwrap();
+        assert_eq!(dataset.manifest.version, version);
+        assert_eq!(dataset.versions().await.unwrap().len() as u64, version);
+        assert_eq!(dataset.get_fragments().len(), 3);
+        assert_eq!(
+            index_segments(&dataset).await.unwrap(),
+            vec![("vec_idx".to_string(), 3)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dataset_optimize_budget() {
+        let test_dir = TempStrDir::default();
+        let uri = test_dir.as_str();
+        let mut dataset = Dataset::write(int_data(10), uri, None).await.unwrap();
+        append(uri, int_data(10)).await;
+        dataset.checkout_latest().await.unwrap();
+
+        let options = DatasetOptimizeOptions {
+            cleanup_old_versions: false,
+            time_budget: Some(Duration::ZERO),
+            ..Default::default()
+        };
+        let report = dataset.optimize(options).await.unwrap();
+        assert_eq!(
+            report.step(OptimizeStep::CompactFiles).status,
+            StepStatus::OutOfBudget
+        );
+        assert_eq!(
+            report.step(OptimizeStep::OptimizeIndices).status,
+            StepStatus::NotNeeded
+        );
+        assert_eq!(
+            report.step(OptimizeStep::CleanupOldVersions).status,
+            StepStatus::Disabled
+        );
+        assert_eq!(report.version, 2);
+        assert_eq!(dataset.get_fragments().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_on_drop() {
+        let dataset = Dataset::write(int_data(10), "memory://", None)
+            .await
+            .unwrap();
+        let (handle, clock, mut events) = spawn(dataset, MaintenancePolicy::default());
+        drop(handle);
+        clock.advance(INTERVAL);
+        // The sender is dropped with the scheduler task.
+        assert!(events.recv().await.is_none());
+    }
+}
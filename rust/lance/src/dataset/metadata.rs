@@ -184,6 +184,8 @@ mod tests {
     use rstest::rstest;
 
     use super::*;
+    use crate::dataset::builder::DatasetBuilder;
+    use crate::dataset::optimize::compact_files;
     use arrow_array::{
         ArrayRef, Int32Array, RecordBatch, RecordBatchIterator, UInt32Array, types::Int32Type,
     };
@@ -438,6 +440,77 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_metadata_update_concurrent_with_data_commits() {
+        let mut dataset = test_dataset_nested().await;
+        let rows = dataset.scan().try_into_batch().await.unwrap();
+        let mut stale = dataset.clone();
+
+        // A concurrent append does not conflict with a metadata update
+        dataset
+            .append(
+                RecordBatchIterator::new(vec![Ok(rows.clone())], rows.schema()),
+                None,
+            )
+            .await
+            .unwrap();
+        stale
+            .update_schema_metadata([("owner", "search-team")])
+            .await
+            .unwrap();
+        stale
+            .update_field_metadata()
+            .update("nested.sub_field", [("unit", "ms")])
+            .unwrap()
+            .await
+            .unwrap();
+        assert_eq!(stale.version().version, 4);
+        assert_eq!(stale.count_rows(None).await.unwrap(), 6);
+        let transaction = stale.read_transaction().await.unwrap().unwrap();
+        assert_eq!(transaction.operation.name(), "UpdateConfig");
+
+        // Compaction keeps the metadata, and new opens see it
+        let mut dataset = stale;
+        dataset.checkout_latest().await.unwrap();
+        compact_files(&mut dataset, Default::default(), None)
+            .await
+            .unwrap();
+        let dataset = DatasetBuilder::from_uri(dataset.uri())
+            .with_session(dataset.session())
+            .load()
+            .await
+            .unwrap();
+        assert_eq!(dataset.get_fragments().len(), 1);
+        assert_eq!(
+            dataset.schema().metadata.get("owner"),
+            Some(&"search-team".to_string())
+        );
+        assert_eq!(
+            dataset
+                .schema()
+                .field("nested.sub_field")
+                .unwrap()
+                .metadata
+                .get("unit"),
+            Some(&"ms".to_string())
+        );
+
+        // A concurrent schema change does conflict
+        let mut dataset = dataset;
+        let mut stale = dataset.clone();
+        dataset.drop_columns(&["name"]).await.unwrap();
+        let err = stale
+            .update_field_metadata()
+            .update("name", [("key", "value")])
+            .unwrap()
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, Error::RetryableCommitConflict { .. }),
+            "{err:?}"
+        );
+    }
+
     #[tokio::test]
     async fn test_update_field_metadata_by_id() {
         let mut dataset = test_dataset_nested().await;
@@ -93,12 +93,14 @@ use super::transaction::{
     Operation, RewriteGroup, RewrittenIndex, Transaction, TransactionBuilder,
 };
 use super::utils::make_rowid_capture_stream;
+use super::write::clustering::{FragmentBoundaries, clustering_config_value, vouch_sorted_by};
 use super::{WriteMode, WriteParams, cleanup_data_fragments, write_fragments_internal};
 use crate::Dataset;
 use crate::Result;
 use crate::dataset::utils::CapturedRowIds;
 use crate::index::DatasetIndexExt;
 use crate::io::commit::{commit_transaction, migrate_fragments};
+use crate::session::commit_listener::notify_commit_listeners;
 use arrow::array::AsArray;
 use arrow::datatypes::{UInt8Type, UInt32Type, UInt64Type};
 use arrow_array::Array;
@@ -111,6 +113,8 @@ use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
 use futures::{StreamExt, TryStreamExt};
 use lance_core::Error;
 use lance_core::datatypes::{BlobHandling, BlobKind};
+use lance_core::utils::encryption::ENCRYPTION_KEY_ID_META_KEY;
+use lance_core::utils::resources::{ResourceTracker, with_resource_tracker};
 use lance_core::utils::tokio::get_num_compute_intensive_cpus;
 use lance_core::utils::tracing::{DATASET_COMPACTING_EVENT, TRACE_DATASET_EVENTS};
 use lance_index::frag_reuse::FragReuseGroup;
@@ -408,12 +412,14 @@ impl CompactionOptions {
 /// Preconditions checked in order:
 /// - Compaction mode is not `Reencode`
 /// - Dataset storage format is non-legacy
+/// - Dataset has no blob or encrypted columns
 /// - Fragment list is non-empty
 /// - All data files share identical Lance file versions
 /// - No fragment has a deletion file
 ///   TODO: Need to support schema evolution case like add column and drop column
 /// - All data files share identical schema mappings (`fields`, `column_indices`)
 /// - Input data files must not contain extra global buffers (beyond schema / file descriptor)
+/// - Input data files must not contain shared (column-level) dictionaries
 async fn can_use_binary_copy(
     dataset: &Dataset,
     options: &CompactionOptions,
@@ -450,6 +456,17 @@ async fn can_use_binary_copy_impl(
         return Ok(false);
     }
 
+    // The nonces of encrypted buffers depend on the page index within the file
+    let has_encrypted_columns = dataset
+        .schema()
+        .fields
+        .iter()
+        .any(|field| field.metadata.contains_key(ENCRYPTION_KEY_ID_META_KEY));
+    if has_encrypted_columns {
+        log::debug!("Binary copy disabled: dataset contains encrypted columns");
+        return Ok(false);
+    }
+
     let storage_ok = dataset
         .manifest
         .data_storage_format
@@ -508,14 +525,8 @@ async fn can_use_binary_copy_impl(
             }
 
             // check file global buffer
-            let object_store = match data_file.base_id {
-                Some(base_id) => dataset.object_store(Some(base_id)).await?,
-                None => dataset.object_store.clone(),
-            };
-            let full_path = dataset
-                .data_file_dir(data_file)?
-                .clone()
-                .join(data_file.path.as_str());
+            let object_store = dataset.object_store_for_data_file(data_file).await?;
+            let full_path = dataset.data_file_path(data_file)?;
             let scan_scheduler = ScanScheduler::new(
                 object_store.clone(),
                 SchedulerConfig::max_bandwidth(&object_store),
@@ -536,6 +547,17 @@ async fn can_use_binary_copy_impl(
                 );
                 return Ok(false);
             }
+            // Pages refer to a shared dictionary by its column buffer index.  Binary copy
+            // concatenates the column buffers of all inputs so those references would no
+            // longer hold for anything but the first file.
+            if file_meta
+                .column_infos
+                .iter()
+                .any(|column_info| column_info.has_shared_dictionary())
+            {
+                log::debug!("Binary copy disabled: data file has shared dictionaries");
+                return Ok(false);
+            }
         }
     }
 
@@ -559,6 +581,16 @@ pub struct CompactionMetrics {
     /// The number of files that have been added, which is always equal to the
     /// number of fragments.
     pub files_added: usize,
+    /// The approximate peak memory, in bytes, held by decoded batches and read
+    /// buffers at any one time.
+    ///
+    /// When metrics of separate tasks are added up this is the largest peak of
+    /// any task, since distributed tasks don't share memory.
+    #[serde(default)]
+    pub peak_memory_bytes: u64,
+    /// The number of bytes written to temporary storage.
+    #[serde(default)]
+    pub temp_bytes: u64,
 }
 
 impl AddAssign for CompactionMetrics {
@@ -567,6 +599,15 @@ impl AddAssign for CompactionMetrics {
         self.fragments_added += rhs.fragments_added;
         self.files_removed += rhs.files_removed;
         self.files_added += rhs.files_added;
+        self.peak_memory_bytes = self.peak_memory_bytes.max(rhs.peak_memory_bytes);
+        self.temp_bytes += rhs.temp_bytes;
+    }
+}
+
+impl CompactionMetrics {
+    fn record_resources(&mut self, tracker: &ResourceTracker) {
+        self.peak_memory_bytes = tracker.peak_memory_bytes();
+        self.temp_bytes = tracker.temp_bytes();
     }
 }
 
@@ -618,12 +659,21 @@ impl CompactionPlanner for DefaultCompactionPlanner {
             fragments.windows(2).all(|w| w[0].id() < w[1].id()),
             "fragments in manifest are not sorted"
         );
+        // Fragments of a clustered dataset are only compacted with the neighbors whose
+        // rows come after theirs, so that the new fragments are sorted as well
+        let boundaries = dataset
+            .clustering()
+            .map(|clustering| FragmentBoundaries::try_new(dataset, &clustering))
+            .transpose()?;
+        let boundaries = boundaries.as_ref();
         let mut fragment_metrics = futures::stream::iter(fragments)
             .map(|fragment| async move {
-                match collect_metrics(&fragment).await {
-                    Ok(metrics) => Ok((fragment.metadata, metrics)),
-                    Err(e) => Err(e),
-                }
+                let metrics = collect_metrics(&fragment).await?;
+                let bounds = match boundaries {
+                    Some(boundaries) => boundaries.bounds(&fragment).await?,
+                    None => None,
+                };
+                Ok::<_, Error>((fragment.metadata, metrics, bounds))
             })
             .buffered(dataset.object_store.as_ref().io_parallelism());
 
@@ -639,10 +689,13 @@ impl CompactionPlanner for DefaultCompactionPlanner {
 
         let mut candidate_bins: Vec<CandidateBin> = Vec::new();
         let mut current_bin: Option<CandidateBin> = None;
+        // The sort key of the last row of the current bin, for a clustered dataset
+        let mut bin_last_key = None;
         let mut i = 0;
 
         while let Some(res) = fragment_metrics.next().await {
-            let (fragment, metrics) = res?;
+            let (fragment, metrics, bounds) = res?;
+            let (first_key, last_key) = bounds.unzip();
 
             let candidacy = if self.options.materialize_deletions
                 && metrics.deletion_percentage() > self.options.materialize_deletions_threshold
@@ -663,6 +716,7 @@ impl CompactionPlanner for DefaultCompactionPlanner {
                 (None, None) => {} // keep searching
                 (Some(candidacy), None) => {
                     // Start a new bin
+                    bin_last_key = last_key;
                     current_bin = Some(CandidateBin {
                         fragments: vec![fragment],
                         pos_range: i..(i + 1),
@@ -674,15 +728,24 @@ impl CompactionPlanner for DefaultCompactionPlanner {
                 (Some(candidacy), Some(bin)) => {
                     // We cannot mix "indexed" and "non-indexed" fragments and so we only consider
                     // the existing bin if it contains the same indices
-                    if bin.indices == indices {
+                    let in_order = match (&bin_last_key, &first_key) {
+                        (Some(bin_last), Some(first)) => bin_last <= first,
+                        _ => true,
+                    };
+                    if bin.indices == indices && in_order {
                         // Add to current bin
                         bin.fragments.push(fragment);
                         bin.pos_range.end += 1;
                         bin.candidacy.push(candidacy);
                         bin.row_counts.push(metrics.num_rows());
+                        if last_key.is_some() {
+                            bin_last_key = last_key;
+                        }
                     } else {
-                        // Index set is different.  Complete previous bin and start new one
+                        // Index set is different, or the rows are out of order.  Complete
+                        // previous bin and start new one
                         candidate_bins.push(current_bin.take().unwrap());
+                        bin_last_key = last_key;
                         current_bin = Some(CandidateBin {
                             fragments: vec![fragment],
                             pos_range: i..(i + 1),
@@ -770,24 +833,27 @@ pub async fn compact_files_with_planner(
 
     let dataset_ref = &dataset.clone();
 
-    let result_stream = futures::stream::iter(compaction_plan.tasks.into_iter())
-        .map(|task| rewrite_files(Cow::Borrowed(dataset_ref), task, &compaction_plan.options))
-        .buffer_unordered(
-            compaction_plan
-                .options
-                .num_threads
-                .unwrap_or_else(get_num_compute_intensive_cpus),
-        );
+    let CompactionPlan { tasks, options, .. } = compaction_plan;
+    let options = &options;
+
+    // The tasks run concurrently, so their peaks are tracked together
+    let tracker = Arc::new(ResourceTracker::default());
+    let mut metrics = with_resource_tracker(tracker.clone(), async move {
+        let result_stream = futures::stream::iter(tasks)
+            .map(|task| rewrite_files(Cow::Borrowed(dataset_ref), task, options))
+            .buffer_unordered(
+                options
+                    .num_threads
+                    .unwrap_or_else(get_num_compute_intensive_cpus),
+            );
 
-    let completed_tasks: Vec<RewriteResult> = result_stream.try_collect().await?;
-    let remap_options = remap_options.unwrap_or(Arc::new(DatasetIndexRemapperOptions::default()));
-    let metrics = commit_compaction(
-        dataset,
-        completed_tasks,
-        remap_options,
-        &compaction_plan.options,
-    )
+        let completed_tasks: Vec<RewriteResult> = result_stream.try_collect().await?;
+        let remap_options =
+            remap_options.unwrap_or(Arc::new(DatasetIndexRemapperOptions::default()));
+        commit_compaction(dataset, completed_tasks, remap_options, options).await
+    })
     .await?;
+    metrics.record_resources(&tracker);
 
     Ok(metrics)
 }
@@ -1443,6 +1509,13 @@ async fn reserve_fragment_ids(
         None,
     )
     .await?;
+    notify_commit_listeners(
+        &dataset.session,
+        &dataset.uri,
+        &transaction.operation,
+        Some(dataset.manifest.as_ref()),
+        &manifest,
+    );
 
     // Need +1 since max_fragment_id is inclusive in this case and ranges are exclusive
     let new_max_exclusive = manifest.max_fragment_id.unwrap_or(0) + 1;
@@ -1462,6 +1535,18 @@ async fn rewrite_files(
     dataset: Cow<'_, Dataset>,
     task: TaskData,
     options: &CompactionOptions,
+) -> Result<RewriteResult> {
+    let tracker = Arc::new(ResourceTracker::default());
+    let mut result =
+        with_resource_tracker(tracker.clone(), rewrite_task_files(dataset, task, options)).await?;
+    result.metrics.record_resources(&tracker);
+    Ok(result)
+}
+
+async fn rewrite_task_files(
+    dataset: Cow<'_, Dataset>,
+    task: TaskData,
+    options: &CompactionOptions,
 ) -> Result<RewriteResult> {
     let mut metrics = CompactionMetrics::default();
 
@@ -1873,6 +1958,30 @@ async fn recalc_versions_for_rewritten_fragments(
     Ok(())
 }
 
+/// The clustering of `dataset`, if the fragments each task compacted are in order
+/// together, so that the fragments it wrote are sorted by the clustering as well
+async fn compacted_clustering(
+    dataset: &Dataset,
+    tasks: &[RewriteResult],
+) -> Result<Option<String>> {
+    let Some(clustering) = dataset.clustering() else {
+        return Ok(None);
+    };
+    let boundaries = FragmentBoundaries::try_new(dataset, &clustering)?;
+    let dataset = Arc::new(dataset.clone());
+    for task in tasks {
+        let fragments = task
+            .original_fragments
+            .iter()
+            .map(|fragment| FileFragment::new(dataset.clone(), fragment.clone()))
+            .collect::<Vec<_>>();
+        if !boundaries.in_order(&fragments).await? {
+            return Ok(None);
+        }
+    }
+    Ok(Some(clustering_config_value(&clustering)?))
+}
+
 /// Commit the results of file compaction.
 ///
 /// It is not required that all tasks are passed to this method. If some failed,
@@ -1911,6 +2020,8 @@ pub async fn commit_compaction(
         .min()
         .unwrap_or(dataset.manifest.version);
 
+    let sorted_by = compacted_clustering(dataset, &completed_tasks).await?;
+
     let mut completed_tasks = completed_tasks;
 
     // Single reserve_fragment_ids for all address-style tasks
@@ -2030,7 +2141,10 @@ pub async fn commit_compaction(
             frag_reuse_index,
         },
     )
-    .transaction_properties(options.transaction_properties.clone())
+    .transaction_properties(vouch_sorted_by(
+        options.transaction_properties.clone(),
+        sorted_by.as_deref(),
+    ))
     .build();
 
     if let Err(e) = dataset
@@ -3583,6 +3697,44 @@ mod tests {
         )
     }
 
+    async fn compaction_resources(rows_per_fragment: u64) -> CompactionMetrics {
+        let mut dataset = lance_datagen::gen_batch()
+            .col(
+                "vec",
+                lance_datagen::array::rand_vec::<Float32Type>(Dimension::from(32)),
+            )
+            .col("i", lance_datagen::array::step::<Int32Type>())
+            .into_ram_dataset(
+                FragmentCount::from(4),
+                FragmentRowCount::from(rows_per_fragment),
+            )
+            .await
+            .unwrap();
+        compact_files(&mut dataset, CompactionOptions::default(), None)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_compaction_resource_metrics() {
+        let small = compaction_resources(200).await;
+        let large = compaction_resources(800).await;
+        assert_eq!(small.fragments_removed, 4);
+        assert!(small.peak_memory_bytes > 0, "{small:?}");
+        assert!(
+            large.peak_memory_bytes > 2 * small.peak_memory_bytes,
+            "{small:?} {large:?}"
+        );
+
+        // Repeated runs report about the same peak
+        let again = compaction_resources(200).await;
+        let (low, high) = (
+            small.peak_memory_bytes.min(again.peak_memory_bytes),
+            small.peak_memory_bytes.max(again.peak_memory_bytes),
+        );
+        assert!(high <= low + low / 4, "{small:?} {again:?}");
+    }
+
     #[tokio::test]
     async fn test_concurrent_cleanup_and_compaction_rebase_cleanup() {
         let mut dataset = lance_datagen::gen_batch()
@@ -9,6 +9,7 @@ use crate::dataset::fragment::write::generate_random_filename;
 use crate::datatypes::Schema;
 use lance_arrow::DataTypeExt;
 use lance_core::Error;
+use lance_core::utils::resources::reserve_memory;
 use lance_encoding::decoder::{ColumnInfo, PageEncoding, PageInfo as DecPageInfo};
 use lance_encoding::version::LanceFileVersion;
 use lance_file::format::pbfile;
@@ -249,12 +250,8 @@ pub async fn rewrite_files_binary_copy(
     // Visit each fragment and all of its data files (a fragment may contain multiple files)
     for frag in fragments.iter() {
         for df in frag.files.iter() {
-            let object_store = if let Some(base_id) = df.base_id {
-                dataset.object_store(Some(base_id)).await?
-            } else {
-                dataset.object_store.clone()
-            };
-            let full_path = dataset.data_file_dir(df)?.clone().join(df.path.as_str());
+            let object_store = dataset.object_store_for_data_file(df).await?;
+            let full_path = dataset.data_file_path(df)?;
             let scan_scheduler = ScanScheduler::new(
                 object_store.clone(),
                 SchedulerConfig::max_bandwidth(&object_store),
@@ -346,6 +343,8 @@ pub async fn rewrite_files_binary_copy(
                         page_index += 1;
                     }
 
+                    // The coalesced page buffers are held until they are copied out
+                    let _read_memory = reserve_memory(batch_bytes as usize);
                     let bytes_vec = if batch_ranges.is_empty() {
                         Vec::new()
                     } else {
@@ -582,6 +581,7 @@ async fn flush_footer(
                     encoding: encoded_col_encoding,
                 })),
             }),
+            encryption: None,
         };
         col_metadatas.push(column);
     }
@@ -0,0 +1,240 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Default query parameters stored with the dataset.
+//!
+//! Every client of a dataset should search it with the same parameters, e.g.
+//! the same nprobes for the vector index. [`QueryDefaults`] are stored in the
+//! schema metadata under [`QUERY_DEFAULTS_PREFIX`] and applied by the
+//! [`Scanner`](super::scanner::Scanner) to every query that does not set the
+//! parameter itself.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use super::Dataset;
+use super::transaction::UpdateMapEntry;
+use crate::Result;
+
+/// The namespace of the schema metadata keys that hold the [`QueryDefaults`].
+pub const QUERY_DEFAULTS_PREFIX: &str = "lance.query.defaults.";
+
+const MINIMUM_NPROBES: &str = "minimum_nprobes";
+const MAXIMUM_NPROBES: &str = "maximum_nprobes";
+const REFINE_FACTOR: &str = "refine_factor";
+const EF: &str = "ef";
+const FTS_WAND_FACTOR: &str = "fts_wand_factor";
+
+const KEYS: [&str; 5] = [
+    MINIMUM_NPROBES,
+    MAXIMUM_NPROBES,
+    REFINE_FACTOR,
+    EF,
+    FTS_WAND_FACTOR,
+];
+
+/// Query parameters applied to the queries of a dataset that don't set them.
+///
+/// See [`Dataset::set_query_defaults`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct QueryDefaults {
+    /// See [`Scanner::minimum_nprobes`](super::scanner::Scanner::minimum_nprobes).
+    pub minimum_nprobes: Option<usize>,
+    /// See [`Scanner::maximum_nprobes`](super::scanner::Scanner::maximum_nprobes).
+    pub maximum_nprobes: Option<usize>,
+    /// See [`Scanner::refine`](super::scanner::Scanner::refine).
+    pub refine_factor: Option<u32>,
+    /// See [`Scanner::ef`](super::scanner::Scanner::ef).
+    pub ef: Option<usize>,
+    /// The wand factor of full text searches, see
+    /// [`FullTextSearchQuery::wand_factor`](lance_index::scalar::FullTextSearchQuery::wand_factor).
+    pub fts_wand_factor: Option<f32>,
+}
+
+impl QueryDefaults {
+    /// Set both the minimum and the maximum nprobes, see
+    /// [`Scanner::nprobes`](super::scanner::Scanner::nprobes).
+    pub fn with_nprobes(mut self, nprobes: usize) -> Self {
+        self.minimum_nprobes = Some(nprobes);
+        self.maximum_nprobes = Some(nprobes);
+        self
+    }
+
+    pub fn with_refine_factor(mut self, refine_factor: u32) -> Self {
+        self.refine_factor = Some(refine_factor);
+        self
+    }
+
+    pub fn with_ef(mut self, ef: usize) -> Self {
+        self.ef = Some(ef);
+        self
+    }
+
+    pub fn with_fts_wand_factor(mut self, wand_factor: f32) -> Self {
+        self.fts_wand_factor = Some(wand_factor);
+        self
+    }
+
+    /// Whether no parameter is set.
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Read the defaults from schema metadata.
+    ///
+    /// Values that can't be parsed are logged and ignored, so a bad value
+    /// written by another client doesn't break queries.
+    pub fn from_metadata(metadata: &HashMap<String, String>) -> Self {
+        fn parse<T: FromStr>(metadata: &HashMap<String, String>, key: &str) -> Option<T> {
+            let key = format!("{QUERY_DEFAULTS_PREFIX}{key}");
+            let value = metadata.get(&key)?;
+            let parsed = value.parse().ok();
+            if parsed.is_none() {
+                log::warn!("Ignoring invalid query default '{key}': '{value}'");
+            }
+            parsed
+        }
+
+        let fts_wand_factor = parse::<f32>(metadata, FTS_WAND_FACTOR).filter(|factor| {
+            let valid = factor.is_finite() && *factor >= 0.0;
+            if !valid {
+                log::warn!(
+                    "Ignoring invalid query default '{QUERY_DEFAULTS_PREFIX}{FTS_WAND_FACTOR}': {factor}"
+                );
+            }
+            valid
+        });
+        Self {
+            minimum_nprobes: parse(metadata, MINIMUM_NPROBES),
+            maximum_nprobes: parse(metadata, MAXIMUM_NPROBES),
+            refine_factor: parse(metadata, REFINE_FACTOR),
+            ef: parse(metadata, EF),
+            fts_wand_factor,
+        }
+    }
+
+    /// The schema metadata updates that store these defaults, removing the
+    /// keys of unset parameters.
+    fn to_metadata_updates(&self) -> Vec<UpdateMapEntry> {
+        let values = [
+            self.minimum_nprobes.map(|v| v.to_string()),
+            self.maximum_nprobes.map(|v| v.to_string()),
+            self.refine_factor.map(|v| v.to_string()),
+            self.ef.map(|v| v.to_string()),
+            self.fts_wand_factor.map(|v| v.to_string()),
+        ];
+        KEYS.iter()
+            .zip(values)
+            .map(|(key, value)| UpdateMapEntry {
+                key: format!("{QUERY_DEFAULTS_PREFIX}{key}"),
+                value,
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for QueryDefaults {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let values = [
+            (MINIMUM_NPROBES, self.minimum_nprobes.map(|v| v.to_string())),
+            (MAXIMUM_NPROBES, self.maximum_nprobes.map(|v| v.to_string())),
+            (REFINE_FACTOR, self.refine_factor.map(|v| v.to_string())),
+            (EF, self.ef.map(|v| v.to_string())),
+            (FTS_WAND_FACTOR, self.fts_wand_factor.map(|v| v.to_string())),
+        ];
+        let mut first = true;
+        for (key, value) in values {
+            if let Some(value) = value {
+                if !first {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{key}={value}")?;
+                first = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Dataset {
+    /// The default query parameters stored with the dataset.
+    pub fn query_defaults(&self) -> QueryDefaults {
+        QueryDefaults::from_metadata(&self.schema().metadata)
+    }
+
+    /// Store the default query parameters of the dataset, replacing the
+    /// previous ones. Pass [`QueryDefaults::default()`] to remove them.
+    ///
+    /// The defaults are applied to every query that doesn't set the
+    /// parameter itself, and show up as `QueryDefaults` in the output of
+    /// [`Scanner::explain_plan`](super::scanner::Scanner::explain_plan).
+    ///
+    /// ```
+    /// # use lance::{Dataset, Result};
+    /// # use lance::dataset::query_defaults::QueryDefaults;
+    /// # async fn test_set_query_defaults(dataset: &mut Dataset) -> Result<()> {
+    /// dataset
+    ///     .set_query_defaults(QueryDefaults::default().with_nprobes(20).with_refine_factor(2))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn set_query_defaults(&mut self, defaults: QueryDefaults) -> Result<()> {
+        self.update_schema_metadata(defaults.to_metadata_updates())
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metadata_round_trip() {
+        let defaults = QueryDefaults::default()
+            .with_nprobes(20)
+            .with_ef(64)
+            .with_fts_wand_factor(0.5);
+        let metadata = defaults
+            .to_metadata_updates()
+            .into_iter()
+            .filter_map(|entry| Some((entry.key, entry.value?)))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(metadata.len(), 4);
+        assert_eq!(
+            metadata.get("lance.query.defaults.minimum_nprobes"),
+            Some(&"20".to_string())
+        );
+        assert_eq!(QueryDefaults::from_metadata(&metadata), defaults);
+        assert_eq!(
+            defaults.to_string(),
+            "minimum_nprobes=20, maximum_nprobes=20, ef=64, fts_wand_factor=0.5"
+        );
+    }
+
+    #[test]
+    fn test_invalid_values_are_ignored() {
+        let metadata = HashMap::from([
+            (
+                "lance.query.defaults.minimum_nprobes".to_string(),
+                "many".to_string(),
+            ),
+            (
+                "lance.query.defaults.refine_factor".to_string(),
+                "-1".to_string(),
+            ),
+            (
+                "lance.query.defaults.fts_wand_factor".to_string(),
+                "NaN".to_string(),
+            ),
+            ("lance.query.defaults.ef".to_string(), "32".to_string()),
+        ]);
+        assert_eq!(
+            QueryDefaults::from_metadata(&metadata),
+            QueryDefaults::default().with_ef(32)
+        );
+        assert!(QueryDefaults::from_metadata(&HashMap::new()).is_empty());
+    }
+}
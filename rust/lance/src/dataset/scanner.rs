@@ -9,10 +9,12 @@ use std::ops::Range;
 use std::pin::Pin;
 use std::sync::{Arc, LazyLock};
 use std::task::{Context, Poll};
+use std::time::Duration;
 
 use crate::index::DatasetIndexExt;
 use arrow::array::AsArray;
-use arrow_array::{Array, Float32Array, Int64Array, RecordBatch};
+use arrow::ffi_stream::FFI_ArrowArrayStream;
+use arrow_array::{Array, ArrayRef, Float32Array, Int64Array, RecordBatch};
 use arrow_schema::{DataType, Field as ArrowField, Schema as ArrowSchema, SchemaRef, SortOptions};
 use arrow_select::concat::concat_batches;
 use async_recursion::async_recursion;
@@ -27,6 +29,7 @@ use datafusion::physical_plan::coalesce_partitions::CoalescePartitionsExec;
 use datafusion::physical_plan::expressions;
 use datafusion::physical_plan::projection::ProjectionExec as DFProjectionExec;
 use datafusion::physical_plan::sorts::sort::SortExec;
+use datafusion::physical_plan::sorts::sort_preserving_merge::SortPreservingMergeExec;
 use datafusion::physical_plan::{
     ExecutionPlan, SendableRecordBatchStream,
     aggregates::{AggregateExec, AggregateMode, PhysicalGroupBy},
@@ -49,6 +52,7 @@ use futures::future::BoxFuture;
 use futures::stream::{Stream, StreamExt};
 use futures::{FutureExt, TryStreamExt};
 use lance_arrow::floats::{FloatType, coerce_float_vector};
+use lance_arrow::sparse::is_sparse_vector_data_type;
 use lance_arrow::{DataTypeExt, SchemaExt as ArrowSchemaExt};
 use lance_core::datatypes::{
     BlobHandling, Field, OnMissing, Projection, escape_field_path_for_project, format_field_path,
@@ -59,7 +63,8 @@ use lance_core::utils::tokio::get_num_compute_intensive_cpus;
 use lance_core::{ROW_ADDR, ROW_ID, ROW_OFFSET};
 use lance_datafusion::aggregate::Aggregate;
 use lance_datafusion::exec::{
-    LanceExecutionOptions, OneShotExec, StrictBatchSizeExec, analyze_plan, execute_plan,
+    ContiguousVectorsExec, LanceExecutionOptions, OneShotExec, StrictBatchSizeExec, analyze_plan,
+    execute_plan,
 };
 use lance_datafusion::expr::safe_coerce_scalar;
 use lance_datafusion::projection::ProjectionPlan;
@@ -72,55 +77,73 @@ use lance_index::scalar::inverted::query::{
     FtsQuery, FtsQueryNode, FtsSearchParams, MatchQuery, PhraseQuery, fill_fts_query_column,
 };
 use lance_index::scalar::inverted::{SCORE_COL, SCORE_FIELD};
-use lance_index::vector::{DEFAULT_QUERY_PARALLELISM, DIST_COL, Query};
+use lance_index::scalar::sparse::SparseQuery;
+use lance_index::vector::utils::has_nan;
+use lance_index::vector::{DEFAULT_QUERY_PARALLELISM, DIST_COL, NanHandling, Query};
 use lance_index::{metrics::NoOpMetricsCollector, scalar::inverted::FTS_SCHEMA};
+use lance_io::ffi::{ffi_runtime, to_ffi_arrow_array_stream};
 use lance_io::stream::RecordBatchStream;
 use lance_linalg::distance::MetricType;
 use lance_select::{IndexExprResult, RowAddrMask, RowAddrTreeMap};
 use lance_table::format::{Fragment, IndexMetadata};
 use roaring::RoaringBitmap;
+use tokio_util::sync::CancellationToken;
 use tracing::{Span, info_span, instrument};
 use uuid::Uuid;
 
 use super::Dataset;
+use crate::dataset::fragment::{ColumnBounds, FileFragment};
+use crate::dataset::query_defaults::QueryDefaults;
 use crate::dataset::row_offsets_to_row_addresses;
 use crate::dataset::utils::SchemaAdapter;
-use crate::index::DatasetIndexInternalExt;
 use crate::index::scalar::inverted::{load_segment_details, load_segments};
 use crate::index::scalar_logical::scalar_index_fragment_bitmap;
 use crate::index::vector::utils::{
     default_distance_type_for, get_vector_dim, get_vector_type, validate_distance_type_for,
 };
-use crate::io::exec::filtered_read::{FilteredReadExec, FilteredReadOptions};
+use crate::index::{DatasetIndexInternalExt, ScalarIndexInfo};
+use crate::io::exec::filtered_read::{
+    FilteredReadExec, FilteredReadOptions, FilteredReadThreadingMode,
+};
 use crate::io::exec::fts::{
     BoostQueryExec, FlatMatchFilterExec, FlatMatchQueryExec, MatchQueryExec, PhraseQueryExec,
 };
 use crate::io::exec::knn::MultivectorScoringExec;
 use crate::io::exec::scalar_index::{MaterializeIndexExec, ScalarIndexExec};
+use crate::io::exec::sparse::{SparseFlatSearchExec, SparseIndexSearchExec, SparseSearch};
 use crate::io::exec::{
     AddRowAddrExec, FilterPlan as ExprFilterPlan, KNNVectorDistanceExec, LancePushdownScanExec,
     LanceScanExec, Planner, PreFilterSource, ScanConfig, TakeExec,
     knn::{
-        KnnBatchParams, QUERY_INDEX_COL, knn_empty_result_schema, new_knn_exec, query_index_field,
+        ANNIvfBatchSearchExec, KnnBatchParams, QUERY_INDEX_COL, knn_empty_result_schema,
+        new_knn_exec, query_index_field,
     },
     project,
 };
 use crate::io::exec::{
-    AddRowOffsetExec, LANCE_RELATIONAL_ALGEBRA_VERSION, LanceFilterExec, LanceScanConfig,
-    get_physical_optimizer,
+    AddRowOffsetExec, GroupLimitExec, LANCE_RELATIONAL_ALGEBRA_VERSION, LanceFilterExec,
+    LanceScanConfig, get_physical_optimizer,
 };
+use crate::utils::cancellation::OperationLimits;
 use crate::{Error, Result};
 use crate::{
     datatypes::Schema,
     io::exec::fts::{BoolSlot, BooleanQueryExec, build_boolean_query_children},
 };
 
+pub use crate::io::exec::NullGroups;
 pub use lance_datafusion::exec::{ExecutionStatsCallback, ExecutionSummaryCounts};
 #[cfg(feature = "substrait")]
 use lance_datafusion::substrait::parse_substrait;
+pub use prepared::{PreparedQuery, PreparedQueryOptions, QueryParams, StaleVersionPolicy};
+
+pub mod prepared;
 
 pub(crate) const BATCH_SIZE_FALLBACK: usize = 8192;
 
+/// How many times `k` candidates a grouped vector search fetches by default
+pub const DEFAULT_GROUP_BY_OVERFETCH_FACTOR: u32 = 4;
+
 /// Parse an environment variable as a specific type, logging a warning on parse failure.
 fn parse_env_var<T: std::str::FromStr>(env_var_name: &str, default_val: &str) -> Option<T>
 where
@@ -160,6 +183,10 @@ pub static DEFAULT_FRAGMENT_READAHEAD: LazyLock<Option<usize>> = LazyLock::new(|
 
 const DEFAULT_XTR_OVERFETCH_VALUE: u32 = 10;
 
+// A scan ordered by the clustering of the dataset merges at most this many fragments,
+// reading more of them at once costs more memory than sorting
+const MAX_MERGED_FRAGMENTS: usize = 256;
+
 pub static DEFAULT_XTR_OVERFETCH: LazyLock<u32> = LazyLock::new(|| {
     parse_env_var(
         "LANCE_XTR_OVERFETCH",
@@ -193,11 +220,20 @@ pub fn get_default_io_buffer_size_override() -> Option<u64> {
     )
 }
 
+/// Limits the results of a vector search per group, see [`Scanner::group_by`]
+#[derive(Debug, Clone)]
+struct VectorGroupBy {
+    column: String,
+    max_per_group: usize,
+    overfetch_factor: u32,
+    null_groups: NullGroups,
+}
+
 /// Defines an ordering for a single column
 ///
 /// Floats are sorted using the IEEE 754 total ordering
 /// Strings are sorted using UTF-8 lexicographic order (i.e. we sort the binary)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ColumnOrdering {
     pub ascending: bool,
     pub nulls_first: bool,
@@ -238,6 +274,108 @@ impl ColumnOrdering {
     }
 }
 
+/// The order in which a scan reads fragments, see [`Scanner::fragment_read_order`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FragmentReadOrder {
+    /// Read fragments in manifest order, or in the order given to [`Scanner::with_fragments`]
+    #[default]
+    Manifest,
+    /// For a scan with an ordering and a limit, read the fragments whose statistics for
+    /// the first ordering column look most promising first, and skip the fragments that
+    /// cannot contribute to the result
+    Statistics,
+}
+
+/// Order `fragments` for a scan that returns the first `k` rows by `ordering`, see
+/// [`FragmentReadOrder::Statistics`].
+///
+/// Together, the fragments whose worst value is best are guaranteed to hold `k` rows
+/// that are no worse than some threshold.  A fragment whose best value is strictly
+/// worse than that threshold cannot contribute and is dropped.  Fragments without
+/// bounds are kept and read first, the rest are read best value first.
+fn order_fragments_by_bounds(
+    fragments: Vec<(Fragment, Option<ColumnBounds>)>,
+    ordering: &ColumnOrdering,
+    k: usize,
+) -> Vec<Fragment> {
+    // (best, worst) value of the rows in a fragment, by the sort order
+    let value_range = |bounds: &ColumnBounds| {
+        if ordering.ascending {
+            (&bounds.min, &bounds.max)
+        } else {
+            (&bounds.max, &bounds.min)
+        }
+    };
+    // Whether `value` sorts strictly after `other`
+    let is_worse = |value: &ScalarValue, other: &ScalarValue| {
+        let cmp = value.partial_cmp(other);
+        if ordering.ascending {
+            cmp == Some(std::cmp::Ordering::Greater)
+        } else {
+            cmp == Some(std::cmp::Ordering::Less)
+        }
+    };
+    let by_value = |a: &ScalarValue, b: &ScalarValue| {
+        let cmp = a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal);
+        if ordering.ascending {
+            cmp
+        } else {
+            cmp.reverse()
+        }
+    };
+
+    let (mut bounded, unbounded): (Vec<_>, Vec<_>) =
+        fragments.into_iter().partition(|(_, bounds)| {
+            bounds.as_ref().is_some_and(|bounds| {
+                // Nulls sorted first beat any value, and a column that is all nulls
+                // has no value bounds
+                !(ordering.nulls_first && bounds.null_count > 0)
+                    && !bounds.min.is_null()
+                    && !bounds.max.is_null()
+            })
+        });
+
+    // Rows at least as good as each fragment's worst value. Nulls sorted last are not.
+    let mut guarantees = bounded
+        .iter()
+        .filter_map(|(fragment, bounds)| {
+            let bounds = bounds.as_ref()?;
+            let num_rows = fragment.num_rows()? as u64;
+            let num_rows = if ordering.nulls_first {
+                num_rows
+            } else {
+                num_rows.saturating_sub(bounds.null_count)
+            };
+            Some((value_range(bounds).1, num_rows))
+        })
+        .collect::<Vec<_>>();
+    guarantees.sort_by(|(a, _), (b, _)| by_value(a, b));
+    let mut guaranteed_rows = 0;
+    let threshold = guarantees.into_iter().find_map(|(worst, num_rows)| {
+        guaranteed_rows += num_rows;
+        (guaranteed_rows >= k as u64).then(|| worst.clone())
+    });
+
+    if let Some(threshold) = &threshold {
+        bounded.retain(|(_, bounds)| {
+            let best = value_range(bounds.as_ref().unwrap()).0;
+            !is_worse(best, threshold)
+        });
+    }
+    bounded.sort_by(|(_, a), (_, b)| {
+        by_value(
+            value_range(a.as_ref().unwrap()).0,
+            value_range(b.as_ref().unwrap()).0,
+        )
+    });
+
+    unbounded
+        .into_iter()
+        .chain(bounded)
+        .map(|(fragment, _)| fragment)
+        .collect()
+}
+
 /// Materialization style for the scanner
 ///
 /// This only affects columns that are not used in a filter
@@ -290,6 +428,8 @@ struct PlannedFilteredScan {
     plan: Arc<dyn ExecutionPlan>,
     limit_pushed_down: bool,
     filter_pushed_down: bool,
+    /// Each partition of the plan is sorted by the ordering of the scan
+    partitions_sorted: bool,
 }
 
 pub struct FilterPlan {
@@ -404,11 +544,14 @@ impl FilterPlan {
 pub struct LanceFilter {
     query_filter: Option<QueryFilter>,
     expr_filter: Option<ExprFilter>,
+    /// Membership filters added by [`Scanner::filter_in`], combined with
+    /// `expr_filter` using AND
+    in_filters: Vec<Expr>,
 }
 
 impl LanceFilter {
     pub fn is_none(&self) -> bool {
-        self.query_filter.is_none() && self.expr_filter.is_none()
+        self.query_filter.is_none() && self.expr_filter.is_none() && self.in_filters.is_empty()
     }
 }
 
@@ -743,6 +886,9 @@ pub struct Scanner {
     /// Optional full text search query
     full_text_query: Option<FullTextSearchQuery>,
 
+    /// Optional sparse vector search
+    sparse_query: Option<SparseSearch>,
+
     /// The batch size controls the maximum size of rows to return for each read.
     batch_size: Option<usize>,
 
@@ -759,6 +905,12 @@ pub struct Scanner {
     /// Number of bytes to allow to queue up in the I/O buffer
     io_buffer_size: Option<u64>,
 
+    /// Memory budget for the scan, defaults to the session's
+    max_scan_memory_bytes: Option<u64>,
+
+    /// Number of batches to decode concurrently, defaults to the session's
+    compute_parallelism: Option<usize>,
+
     limit: Option<i64>,
     offset: Option<i64>,
 
@@ -777,6 +929,12 @@ pub struct Scanner {
     /// True when the query shape represents a batch of single-vector queries
     /// (list-like query on a fixed-size vector column, or multiple concatenated vectors).
     is_batch_nearest: bool,
+    /// What a flat vector search does with rows whose distance is NaN.
+    nan_handling: NanHandling,
+    /// If false, a nearest query whose vector contains NaN is rejected.
+    allow_nan_query: bool,
+    /// If set, the vector search keeps at most this many rows per group.
+    group_by: Option<VectorGroupBy>,
 
     /// If false, do not use any scalar indices for the scan
     ///
@@ -798,6 +956,9 @@ pub struct Scanner {
     /// If set, this scanner serves only these fragments.
     fragments: Option<Vec<Fragment>>,
 
+    /// The order in which fragments are read (default: manifest order)
+    fragment_read_order: FragmentReadOrder,
+
     /// If set, this scanner will only search the specified vector index segments.
     index_segments: Option<Vec<Uuid>>,
 
@@ -815,12 +976,22 @@ pub struct Scanner {
     /// If set, this callback will be called after the scan with summary statistics
     scan_stats_callback: Option<ExecutionStatsCallback>,
 
+    /// If set, the query fails with a timeout error once it has run this long
+    timeout: Option<Duration>,
+
+    /// If set, cancelling this token stops the query
+    cancellation_token: Option<CancellationToken>,
+
     /// Whether the result returned by the scanner must be of the size of the batch_size.
     /// By default, it is false.
     /// Mainly, if the result is returned strictly according to the batch_size,
     /// batching and waiting are required, and the performance will decrease.
     strict_batch_size: bool,
 
+    /// Whether vector columns in the output must be backed by a single contiguous,
+    /// aligned values buffer.  By default, it is false.
+    contiguous_vectors: bool,
+
     /// File reader options to use when reading data files.
     file_reader_options: Option<FileReaderOptions>,
 
@@ -829,6 +1000,13 @@ pub struct Scanner {
     /// Which version of the relational algebra to use when generating the physical plan
     relational_algebra_version: u32,
 
+    /// The indices of the dataset, resolved once by [`Self::prepare`]
+    resolved_indices: Option<Arc<ResolvedIndices>>,
+
+    /// The dataset's [`QueryDefaults`] in effect for this scan, i.e. not
+    /// overridden by the caller.  Shown by [`Self::explain_plan`].
+    query_defaults: QueryDefaults,
+
     // Legacy fields to help migrate some old projection behavior to new behavior
     //
     // There are two behaviors we are moving away from:
@@ -859,6 +1037,22 @@ pub struct Scanner {
     autoproject_scoring_columns: bool,
 }
 
+/// The indices of a dataset version, which a [`PreparedQuery`] resolves once
+/// instead of for every plan
+struct ResolvedIndices {
+    indices: Arc<Vec<IndexMetadata>>,
+    scalar_index_info: Arc<ScalarIndexInfo>,
+}
+
+impl ResolvedIndices {
+    async fn load(dataset: &Dataset) -> Result<Self> {
+        Ok(Self {
+            indices: dataset.load_indices().await?,
+            scalar_index_info: Arc::new(dataset.scalar_index_info().await?),
+        })
+    }
+}
+
 /// Represents a user-requested take operation
 #[derive(Debug, Clone)]
 pub enum TakeOperation {
@@ -1018,6 +1212,8 @@ impl Scanner {
     pub fn new(dataset: Arc<Dataset>) -> Self {
         let projection_plan = ProjectionPlan::full(dataset.clone()).unwrap();
         let file_reader_options = dataset.file_reader_options.clone();
+        let max_scan_memory_bytes = dataset.session.max_scan_memory_bytes();
+        let compute_parallelism = dataset.session.compute_parallelism();
         let mut scanner = Self {
             dataset,
             projection_plan,
@@ -1026,26 +1222,36 @@ impl Scanner {
             materialization_style: MaterializationStyle::Heuristic,
             filter: LanceFilter::default(),
             full_text_query: None,
+            sparse_query: None,
             batch_size: None,
             batch_size_bytes: None,
             batch_readahead: get_num_compute_intensive_cpus(),
             fragment_readahead: None,
             io_buffer_size: None,
+            max_scan_memory_bytes,
+            compute_parallelism,
             limit: None,
             offset: None,
             ordering: None,
             nearest: None,
             nearest_query_count: 1,
             is_batch_nearest: false,
+            nan_handling: NanHandling::default(),
+            allow_nan_query: false,
+            group_by: None,
             use_stats: true,
             ordered: true,
             fragments: None,
+            fragment_read_order: FragmentReadOrder::default(),
             index_segments: None,
             fast_search: false,
             use_scalar_index: true,
             include_deleted_rows: false,
             scan_stats_callback: None,
+            timeout: None,
+            cancellation_token: None,
             strict_batch_size: false,
+            contiguous_vectors: false,
             file_reader_options,
             aggregate: None,
             legacy_with_row_addr: false,
@@ -1053,6 +1259,8 @@ impl Scanner {
             explicit_projection: false,
             autoproject_scoring_columns: true,
             relational_algebra_version: LANCE_RELATIONAL_ALGEBRA_VERSION,
+            resolved_indices: None,
+            query_defaults: QueryDefaults::default(),
         };
         scanner.apply_blob_handling();
         scanner
@@ -1192,6 +1400,26 @@ impl Scanner {
         self
     }
 
+    /// Fail the query with [`lance_core::Error::Timeout`] if it has not finished after
+    /// `timeout`
+    ///
+    /// The time starts when the stream is created and includes planning, index search
+    /// and reading the results.  When it runs out the I/O of the query is cancelled and
+    /// the stream returns the error instead of the remaining batches.
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Stop the query when `token` is cancelled
+    ///
+    /// The query's I/O is cancelled and the stream returns
+    /// [`lance_core::Error::Cancelled`] instead of the remaining batches.
+    pub fn cancellation_token(&mut self, token: CancellationToken) -> &mut Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
     /// Set the materialization style for the scan
     ///
     /// This controls when columns are fetched from storage.  The default should work
@@ -1264,7 +1492,7 @@ impl Scanner {
     ///    .limit(10)
     ///    .into_stream();
     /// ```
-    pub fn full_text_search(&mut self, query: FullTextSearchQuery) -> Result<&mut Self> {
+    pub fn full_text_search(&mut self, mut query: FullTextSearchQuery) -> Result<&mut Self> {
         let fields = query.columns();
         if !fields.is_empty() {
             for field in fields.iter() {
@@ -1274,14 +1502,68 @@ impl Scanner {
             }
         }
 
+        self.query_defaults.fts_wand_factor = None;
+        if query.wand_factor.is_none() {
+            query.wand_factor = self.dataset.query_defaults().fts_wand_factor;
+            self.query_defaults.fts_wand_factor = query.wand_factor;
+        }
+
         self.full_text_query = Some(query);
         Ok(self)
     }
 
+    /// Find the k rows of a sparse vector column with the highest dot product
+    /// with the query, the sparse vector with the given dimensions and values.
+    ///
+    /// Only rows with a positive score are returned, in descending order of
+    /// `_score`.  A sparse vector index on the column is used for the indexed
+    /// fragments, the others are scored in a scan unless [`Self::fast_search`]
+    /// is set.  Set [`Self::use_scalar_index`] to false to scan every fragment.
+    ///
+    /// ```rust,ignore
+    /// let stream = dataset.scan()
+    ///    .nearest_sparse("embedding", &[12, 4096], &[0.8, 0.3], 10)?
+    ///    .try_into_stream()
+    ///    .await?;
+    /// ```
+    pub fn nearest_sparse(
+        &mut self,
+        column: &str,
+        indices: &[i32],
+        values: &[f32],
+        k: usize,
+    ) -> Result<&mut Self> {
+        if k == 0 {
+            return Err(Error::invalid_input("k must be positive".to_string()));
+        }
+        let field = self
+            .dataset
+            .schema()
+            .field(column)
+            .ok_or_else(|| Error::invalid_input(format!("Column {} not found", column)))?;
+        if !is_sparse_vector_data_type(&field.data_type()) {
+            return Err(Error::invalid_input(format!(
+                "Column {} has type {} and is not a sparse vector column",
+                column,
+                field.data_type()
+            )));
+        }
+        self.sparse_query = Some(SparseSearch {
+            column: column.to_string(),
+            query: SparseQuery::try_new(indices, values)?,
+            k,
+        });
+        Ok(self)
+    }
+
     /// Set a filter using a Substrait ExtendedExpression message
     ///
     /// The message must contain exactly one expression and that expression
-    /// must be a scalar expression whose return type is boolean.
+    /// must be a scalar expression whose return type is boolean.  Field references,
+    /// including references to nested struct fields, are bound against the dataset
+    /// schema and the filter is planned like any other filter, so it can use scalar
+    /// indices.  Functions that DataFusion does not support result in a
+    /// [`Error::NotSupported`] that names the function's extension URI.
     pub fn filter_substrait(&mut self, filter: &[u8]) -> Result<&mut Self> {
         self.filter.expr_filter = Some(ExprFilter::Substrait(filter.to_vec()));
         Ok(self)
@@ -1292,6 +1574,79 @@ impl Scanner {
         self
     }
 
+    /// Only return rows whose `column` is one of `keys`
+    ///
+    /// This is meant for key sets that are too large to write as an `IN` list in
+    /// SQL, e.g. hundreds of thousands of ids.  The keys are deduplicated and sorted
+    /// once, here, and the membership test is planned like any other filter: it uses a
+    /// BTree or Bitmap index on the column if there is one and is otherwise evaluated
+    /// against a hash set of the keys during the scan.
+    ///
+    /// The keys are cast to the type of the column if they differ, keys that can't be
+    /// represented in that type are dropped.  Null keys never match.  This can be called
+    /// more than once, a row must then match every key set as well as the filter set
+    /// with [`Self::filter`] or [`Self::filter_expr`], if any.
+    ///
+    /// ```rust,ignore
+    /// let ids = Int64Array::from(vec![5, 10, 1_000_000]);
+    /// let stream = dataset.scan()
+    ///     .filter_in("id", Arc::new(ids)).unwrap()
+    ///     .into_stream();
+    /// ```
+    pub fn filter_in(&mut self, column: &str, keys: ArrayRef) -> Result<&mut Self> {
+        let field = self
+            .dataset
+            .schema()
+            .field(column)
+            .ok_or_else(|| Error::invalid_input(format!("Column {} not found", column)))?;
+        let data_type = field.data_type();
+        if !(data_type.is_primitive()
+            || matches!(
+                data_type,
+                DataType::Boolean
+                    | DataType::Utf8
+                    | DataType::LargeUtf8
+                    | DataType::Utf8View
+                    | DataType::Binary
+                    | DataType::LargeBinary
+                    | DataType::BinaryView
+            ))
+        {
+            return Err(Error::invalid_input(format!(
+                "filter_in is not supported on column {} of type {}",
+                column, data_type
+            )));
+        }
+        let keys = if keys.data_type() == &data_type {
+            keys
+        } else if arrow_cast::can_cast_types(keys.data_type(), &data_type) {
+            arrow_cast::cast(&keys, &data_type)?
+        } else {
+            return Err(Error::invalid_input(format!(
+                "Keys of type {} can't be compared to column {} of type {}",
+                keys.data_type(),
+                column,
+                data_type
+            )));
+        };
+
+        let mut values = (0..keys.len())
+            .filter(|&i| keys.is_valid(i))
+            .map(|i| ScalarValue::try_from_array(&keys, i))
+            .collect::<datafusion::common::Result<Vec<_>>>()?;
+        values.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        values.dedup();
+
+        let in_filter = if values.is_empty() {
+            lit(false)
+        } else {
+            lance_datafusion::logical_expr::field_path_to_expr(column)?
+                .in_list(values.into_iter().map(lit).collect(), false)
+        };
+        self.filter.in_filters.push(in_filter);
+        Ok(self)
+    }
+
     /// Set aggregation.
     ///
     /// The aggregate expression is parsed immediately using the dataset schema.
@@ -1364,6 +1719,24 @@ impl Scanner {
         self
     }
 
+    /// Cap the memory this scan may buffer, overriding the session's
+    /// [`crate::session::Session::with_max_scan_memory_bytes`].
+    ///
+    /// The I/O buffer is limited to this size (or [`Self::io_buffer_size`] if that is smaller)
+    /// and I/O pauses while it is full.  Planning fails if a single batch of the projected
+    /// columns cannot fit within the budget.
+    pub fn max_scan_memory_bytes(&mut self, bytes: u64) -> &mut Self {
+        self.max_scan_memory_bytes = Some(bytes);
+        self
+    }
+
+    /// Cap the number of batches this scan decodes concurrently, overriding the session's
+    /// [`crate::session::Session::with_compute_parallelism`].
+    pub fn compute_parallelism(&mut self, num_threads: usize) -> &mut Self {
+        self.compute_parallelism = Some(num_threads.max(1));
+        self
+    }
+
     /// Set the prefetch size.
     /// Ignored in v2 and newer format
     pub fn batch_readahead(&mut self, nbatches: usize) -> &mut Self {
@@ -1388,7 +1761,10 @@ impl Scanner {
     /// sequence is returned in order but there may be slightly less parallelism.
     ///
     /// If this is false, then batches will be returned as soon as they are
-    /// available, potentially increasing throughput slightly
+    /// available, potentially increasing throughput slightly.  Fragments are
+    /// read concurrently and each one emits its batches as they are decoded,
+    /// so batches from different fragments may be interleaved.  Filters and
+    /// limits still apply, but a limit may return any of the matching rows.
     ///
     /// If an ordering is defined (using [Self::order_by]) then the scan will
     /// always scan in parallel and any value set here will be ignored.
@@ -1397,6 +1773,20 @@ impl Scanner {
         self
     }
 
+    /// Set the order in which fragments are read (default: [`FragmentReadOrder::Manifest`])
+    ///
+    /// With [`FragmentReadOrder::Statistics`], a scan with an ordering (see
+    /// [Self::order_by]), a limit and no filter uses the statistics of the first
+    /// ordering column to read the most promising fragments first and to skip the
+    /// fragments that cannot reach the first `offset + limit` rows.  Fragments without
+    /// statistics for the column are always read.  Legacy (v1) data files always record
+    /// column statistics, newer ones only if they were written with
+    /// [`crate::dataset::WriteParams::collect_stats`].
+    pub fn fragment_read_order(&mut self, order: FragmentReadOrder) -> &mut Self {
+        self.fragment_read_order = order;
+        self
+    }
+
     /// Set whether to use scalar index.
     ///
     /// By default, scalar indices will be used to optimize a query if available.
@@ -1418,6 +1808,22 @@ impl Scanner {
         self
     }
 
+    /// Set whether vector columns must be contiguous in the output.
+    ///
+    /// If this is true then the values of every top-level `FixedSizeList` column with
+    /// fixed-width values (e.g. `FixedSizeList<Float32>`) are a single buffer with no
+    /// offset, holding exactly the values of the batch, and aligned to
+    /// [`lance_arrow::contiguous::VECTOR_BUFFER_ALIGNMENT`] (64 bytes).  This lets the
+    /// buffer be handed to a device without another copy.
+    ///
+    /// Filters, takes and rechunking slice batches after they are decoded, so the
+    /// check runs on the final output and copies the values of any column that does
+    /// not meet this already.
+    pub fn contiguous_vectors(&mut self, contiguous_vectors: bool) -> &mut Self {
+        self.contiguous_vectors = contiguous_vectors;
+        self
+    }
+
     /// Set limit and offset.
     ///
     /// If offset is set, the first offset rows will be skipped. If limit is set,
@@ -1458,6 +1864,12 @@ impl Scanner {
     /// Find k-nearest neighbor within the vector column.
     /// the query can be a Float16Array, Float32Array, Float64Array, UInt8Array,
     /// or a ListArray/FixedSizeListArray of the above types.
+    ///
+    /// A list of query vectors against a fixed size vector column is a batch search.  Each
+    /// query gets its own k results, tagged with a `query_index` column.  The queries share
+    /// the other search parameters and the filter.  When the search uses an index with a
+    /// fixed number of probes (see [Self::nprobes]) the queries are searched together, so
+    /// each partition is loaded once for all the queries that probe it.
     pub fn nearest(&mut self, column: &str, q: &dyn Array, k: usize) -> Result<&mut Self> {
         if !self.prefilter {
             // We can allow fragment scan if the input to nearest is a prefilter.
@@ -1558,16 +1970,21 @@ impl Scanner {
             }
         };
 
+        let defaults = self.dataset.query_defaults();
+        self.query_defaults.minimum_nprobes = defaults.minimum_nprobes;
+        self.query_defaults.maximum_nprobes = defaults.maximum_nprobes;
+        self.query_defaults.ef = defaults.ef;
+        self.query_defaults.refine_factor = defaults.refine_factor;
         self.nearest = Some(Query {
             column: column.to_string(),
             key,
             k,
             lower_bound: None,
             upper_bound: None,
-            minimum_nprobes: 1,
-            maximum_nprobes: None,
-            ef: None,
-            refine_factor: None,
+            minimum_nprobes: defaults.minimum_nprobes.unwrap_or(1),
+            maximum_nprobes: defaults.maximum_nprobes,
+            ef: defaults.ef,
+            refine_factor: defaults.refine_factor,
             metric_type: None,
             use_index: true,
             query_parallelism: DEFAULT_QUERY_PARALLELISM,
@@ -1604,6 +2021,8 @@ impl Scanner {
         if let Some(q) = self.nearest.as_mut() {
             q.minimum_nprobes = n;
             q.maximum_nprobes = Some(n);
+            self.query_defaults.minimum_nprobes = None;
+            self.query_defaults.maximum_nprobes = None;
         } else {
             log::warn!("nprobes is not set because nearest has not been called yet");
         }
@@ -1619,6 +2038,8 @@ impl Scanner {
         if let Some(q) = self.nearest.as_mut() {
             q.minimum_nprobes = n;
             q.maximum_nprobes = Some(n);
+            self.query_defaults.minimum_nprobes = None;
+            self.query_defaults.maximum_nprobes = None;
         } else {
             log::warn!("nprobes is not set because nearest has not been called yet");
         }
@@ -1635,6 +2056,7 @@ impl Scanner {
     pub fn minimum_nprobes(&mut self, n: usize) -> &mut Self {
         if let Some(q) = self.nearest.as_mut() {
             q.minimum_nprobes = n;
+            self.query_defaults.minimum_nprobes = None;
         } else {
             log::warn!("minimum_nprobes is not set because nearest has not been called yet");
         }
@@ -1655,6 +2077,7 @@ impl Scanner {
     pub fn maximum_nprobes(&mut self, n: usize) -> &mut Self {
         if let Some(q) = self.nearest.as_mut() {
             q.maximum_nprobes = Some(n);
+            self.query_defaults.maximum_nprobes = None;
         } else {
             log::warn!("maximum_nprobes is not set because nearest has not been called yet");
         }
@@ -1664,6 +2087,7 @@ impl Scanner {
     pub fn ef(&mut self, ef: usize) -> &mut Self {
         if let Some(q) = self.nearest.as_mut() {
             q.ef = Some(ef);
+            self.query_defaults.ef = None;
         }
         self
     }
@@ -1693,7 +2117,8 @@ impl Scanner {
     ///   re-ranked without fetching additional elements.
     pub fn refine(&mut self, factor: u32) -> &mut Self {
         if let Some(q) = self.nearest.as_mut() {
-            q.refine_factor = Some(factor)
+            q.refine_factor = Some(factor);
+            self.query_defaults.refine_factor = None;
         };
         self
     }
@@ -1758,6 +2183,85 @@ impl Scanner {
         self
     }
 
+    /// Set what a vector search does with rows whose distance to the query is
+    /// NaN, such as rows whose vector contains NaN.
+    ///
+    /// By default these rows are dropped. Rows with a null vector never match.
+    pub fn nan_handling(&mut self, nan_handling: NanHandling) -> &mut Self {
+        self.nan_handling = nan_handling;
+        self
+    }
+
+    /// Allow a nearest query whose vector contains NaN.
+    ///
+    /// The distance from such a query to any row is NaN, so by default the
+    /// query is rejected when the plan is created.
+    pub fn allow_nan_query(&mut self, allow: bool) -> &mut Self {
+        self.allow_nan_query = allow;
+        self
+    }
+
+    /// Keep at most `max_per_group` results of the vector search per distinct value
+    /// of `column`.
+    ///
+    /// This is useful when rows are chunks of larger documents and the search should
+    /// return the best chunks of different documents.  The search fetches more
+    /// candidates than `k` (see [`Self::group_by_overfetch_factor`]), keeps the nearest
+    /// rows of each group and then returns up to `k` rows.  Fewer rows are returned if
+    /// the candidates don't cover enough groups.  Filters, projections, limits and
+    /// orderings apply to the grouped results as usual.
+    pub fn group_by(&mut self, column: &str, max_per_group: usize) -> Result<&mut Self> {
+        if max_per_group == 0 {
+            return Err(Error::invalid_input(
+                "max_per_group must be positive".to_string(),
+            ));
+        }
+        self.dataset
+            .schema()
+            .field(column)
+            .ok_or(Error::invalid_input(format!("Column {} not found", column)))?;
+        let (overfetch_factor, null_groups) = self
+            .group_by
+            .as_ref()
+            .map(|group_by| (group_by.overfetch_factor, group_by.null_groups))
+            .unwrap_or((DEFAULT_GROUP_BY_OVERFETCH_FACTOR, NullGroups::default()));
+        self.group_by = Some(VectorGroupBy {
+            column: column.to_string(),
+            max_per_group,
+            overfetch_factor,
+            null_groups,
+        });
+        Ok(self)
+    }
+
+    /// Set how many times `k` candidates a grouped vector search fetches before the
+    /// per-group limit is applied.
+    ///
+    /// Defaults to [`DEFAULT_GROUP_BY_OVERFETCH_FACTOR`].  Raise it if many of the
+    /// nearest rows share a group and the search returns fewer than `k` rows.
+    pub fn group_by_overfetch_factor(&mut self, factor: u32) -> &mut Self {
+        if let Some(group_by) = self.group_by.as_mut() {
+            group_by.overfetch_factor = factor;
+        } else {
+            log::warn!(
+                "group_by_overfetch_factor is not set because group_by has not been called yet"
+            );
+        }
+        self
+    }
+
+    /// Set how a grouped vector search treats rows whose group value is null.
+    ///
+    /// By default every null is a group of its own.
+    pub fn group_by_nulls(&mut self, null_groups: NullGroups) -> &mut Self {
+        if let Some(group_by) = self.group_by.as_mut() {
+            group_by.null_groups = null_groups;
+        } else {
+            log::warn!("group_by_nulls is not set because group_by has not been called yet");
+        }
+        self
+    }
+
     /// Instruct the scanner to return the `_rowid` meta column from the dataset.
     pub fn with_row_id(&mut self) -> &mut Self {
         self.legacy_with_row_id = true;
@@ -1799,14 +2303,14 @@ impl Scanner {
     }
 
     /// Compute the resolved file reader options, merging the scanner's explicit
-    /// `file_reader_options`, the dataset-level defaults, and the `batch_size_bytes`
-    /// setting.
+    /// `file_reader_options`, the dataset-level defaults, the `batch_size_bytes`
+    /// setting, and the session's decode buffer pool.
     fn resolved_file_reader_options(&self) -> Option<FileReaderOptions> {
         let base = self
             .file_reader_options
             .clone()
             .or_else(|| self.dataset.file_reader_options.clone());
-        match (base, self.batch_size_bytes) {
+        let mut options = match (base, self.batch_size_bytes) {
             (Some(mut opts), Some(bsb)) => {
                 if opts.batch_size_bytes.is_none() {
                     opts.batch_size_bytes = Some(bsb);
@@ -1819,7 +2323,14 @@ impl Scanner {
                 ..Default::default()
             }),
             (None, None) => None,
+        };
+        if let Some(buffer_pool) = self.dataset.session.decode_buffer_pool() {
+            let decoder_config = &mut options.get_or_insert_default().decoder_config;
+            if decoder_config.buffer_pool.is_none() {
+                decoder_config.buffer_pool = Some(buffer_pool.clone());
+            }
         }
+        options
     }
 
     /// Create a physical expression for a column that may be nested
@@ -1889,15 +2400,32 @@ impl Scanner {
     /// will be available for filtering but not otherwise) and so you may want to call this
     /// after setting all other options.
     pub fn get_expr_filter(&self) -> Result<Option<Expr>> {
-        if let Some(filter) = &self.filter.expr_filter {
-            let filter_schema = self.filterable_schema()?;
-            Ok(Some(filter.to_datafusion(
-                self.dataset.schema(),
-                filter_schema.as_ref(),
-            )?))
-        } else {
-            Ok(None)
+        if self.filter.expr_filter.is_none() && self.filter.in_filters.is_empty() {
+            return Ok(None);
         }
+        let filter_schema = self.filterable_schema()?;
+        self.expr_filter_with_schema(filter_schema.as_ref())
+    }
+
+    // The expr filter ANDed with the filters of `filter_in`
+    fn expr_filter_with_schema(&self, filter_schema: &Schema) -> Result<Option<Expr>> {
+        let expr_filter = self
+            .filter
+            .expr_filter
+            .as_ref()
+            .map(|filter| filter.to_datafusion(self.dataset.schema(), filter_schema))
+            .transpose()?;
+        Ok(self
+            .filter
+            .in_filters
+            .iter()
+            .cloned()
+            .fold(expr_filter, |acc, in_filter| {
+                Some(match acc {
+                    Some(acc) => acc.and(in_filter),
+                    None => in_filter,
+                })
+            }))
     }
 
     fn add_extra_columns(&self, schema: Schema) -> Result<Schema> {
@@ -1910,7 +2438,7 @@ impl Scanner {
             }
         };
 
-        if self.full_text_query.is_some() {
+        if self.full_text_query.is_some() || self.sparse_query.is_some() {
             extra_columns.push(ArrowField::new(SCORE_COL, DataType::Float32, true));
         }
 
@@ -1956,7 +2484,7 @@ impl Scanner {
                 let vector_expr = expressions::col(DIST_COL, current_schema)?;
                 output_expr.push((vector_expr, DIST_COL.to_string()));
             }
-            if self.full_text_query.is_some()
+            if (self.full_text_query.is_some() || self.sparse_query.is_some())
                 && output_expr.iter().all(|(_, name)| name != SCORE_COL)
             {
                 if self.explicit_projection {
@@ -2017,21 +2545,60 @@ impl Scanner {
         Ok(output_expr)
     }
 
+    /// Plan this query once to execute it many times, see [`PreparedQuery`].
+    ///
+    /// The filter may contain placeholders, `$1`, `$2` and so on, whose values
+    /// are given to each [`PreparedQuery::execute`] along with the query vector.
+    ///
+    /// ```rust,ignore
+    /// let prepared = dataset.scan()
+    ///     .nearest("vector", &query_vector, 10)?
+    ///     .filter("tenant_id = $1")?
+    ///     .prepare()
+    ///     .await?;
+    /// let stream = prepared
+    ///     .execute(QueryParams {
+    ///         query: Some(Arc::new(other_vector)),
+    ///         values: vec![ScalarValue::Int32(Some(42))],
+    ///     })
+    ///     .await?;
+    /// ```
+    pub async fn prepare(&self) -> Result<PreparedQuery> {
+        self.prepare_with_options(PreparedQueryOptions::default())
+            .await
+    }
+
+    /// [`Self::prepare`] with options, such as what to do once the dataset
+    /// has a newer version
+    pub async fn prepare_with_options(
+        &self,
+        options: PreparedQueryOptions,
+    ) -> Result<PreparedQuery> {
+        PreparedQuery::try_new(self.clone(), options).await
+    }
+
     /// Create a stream from the Scanner.
     #[instrument(skip_all)]
     pub fn try_into_stream(&self) -> BoxFuture<'_, Result<DatasetRecordBatchStream>> {
         // Future intentionally boxed here to avoid large futures on the stack
         async move {
-            let plan = self.create_plan().await?;
+            let Some(limits) = self.operation_limits() else {
+                let plan = self.create_plan().await?;
+                return Ok(DatasetRecordBatchStream::new(execute_plan(
+                    plan,
+                    self.execution_options(),
+                )?));
+            };
 
-            Ok(DatasetRecordBatchStream::new(execute_plan(
+            let plan = limits.run(self.create_plan()).await?;
+            let stream = execute_plan(
                 plan,
                 LanceExecutionOptions {
-                    batch_size: self.batch_size,
-                    execution_stats_callback: self.scan_stats_callback.clone(),
-                    ..Default::default()
+                    cancellation_token: Some(limits.cancellation_token().clone()),
+                    ..self.execution_options()
                 },
-            )?))
+            )?;
+            Ok(DatasetRecordBatchStream::new(limits.limit_stream(stream)))
         }
         .boxed()
     }
@@ -2040,14 +2607,22 @@ impl Scanner {
         &self,
         mut options: LanceExecutionOptions,
     ) -> Result<SendableRecordBatchStream> {
-        let plan = self.create_plan().await?;
-
         // Use the scan stats callback if the user didn't set an execution stats callback
         if options.execution_stats_callback.is_none() {
             options.execution_stats_callback = self.scan_stats_callback.clone();
         }
 
-        execute_plan(plan, options)
+        let Some(limits) = self.operation_limits() else {
+            let plan = self.create_plan().await?;
+            return execute_plan(plan, options);
+        };
+        let plan = limits.run(self.create_plan()).await?;
+        options.cancellation_token = Some(limits.cancellation_token().clone());
+        Ok(limits.limit_stream(execute_plan(plan, options)?))
+    }
+
+    fn operation_limits(&self) -> Option<OperationLimits> {
+        OperationLimits::new(self.timeout, self.cancellation_token.as_ref())
     }
 
     pub(crate) fn execution_options(&self) -> LanceExecutionOptions {
@@ -2058,6 +2633,19 @@ impl Scanner {
         }
     }
 
+    /// Execute the scan and export the results as an Arrow C stream.
+    ///
+    /// The stream is driven on `handle`, or on an internal runtime if `None`, from the
+    /// thread that reads it.  See [`lance_io::ffi::to_ffi_arrow_array_stream`] for how
+    /// errors and releasing the stream are handled.
+    pub async fn try_into_ffi_stream(
+        &self,
+        handle: Option<tokio::runtime::Handle>,
+    ) -> Result<FFI_ArrowArrayStream> {
+        let stream = self.try_into_stream().await?;
+        to_ffi_arrow_array_stream(stream, handle.map_or_else(ffi_runtime, Ok)?)
+    }
+
     pub async fn try_into_batch(&self) -> Result<RecordBatch> {
         let stream = self.try_into_stream().await?;
         let schema = stream.schema();
@@ -2358,19 +2946,74 @@ impl Scanner {
             ));
         }
 
+        if let Some(group_by) = &self.group_by {
+            if self.nearest.is_none() {
+                return Err(Error::invalid_input(
+                    "group_by is only supported for vector search".to_string(),
+                ));
+            }
+            if self.is_batch_nearest {
+                return Err(Error::not_supported(
+                    "group_by is not supported when searching with multiple query vectors"
+                        .to_string(),
+                ));
+            }
+            if group_by.overfetch_factor == 0 {
+                return Err(Error::invalid_input(
+                    "The group_by overfetch factor must be positive".to_string(),
+                ));
+            }
+        }
+
+        if let Some(budget) = self.max_scan_memory_bytes {
+            // Only fixed-width columns are counted, so this is a lower bound on the batch size
+            let row_bytes = self
+                .projection_plan
+                .physical_projection
+                .to_arrow_schema()
+                .fields()
+                .iter()
+                .filter_map(|field| field.data_type().byte_width_opt())
+                .sum::<usize>() as u64;
+            let batch_bytes = self
+                .batch_size_bytes
+                .unwrap_or(row_bytes * self.get_batch_size() as u64);
+            if batch_bytes > budget {
+                return Err(Error::invalid_input(format!(
+                    "A single batch of this scan needs at least {batch_bytes} bytes which exceeds \
+                     max_scan_memory_bytes ({budget}).  Use a smaller batch size or a larger budget."
+                )));
+            }
+        }
+
         Ok(())
     }
 
+    async fn load_indices(&self) -> Result<Arc<Vec<IndexMetadata>>> {
+        match &self.resolved_indices {
+            Some(resolved) => Ok(resolved.indices.clone()),
+            None => self.dataset.load_indices().await,
+        }
+    }
+
+    async fn scalar_index_info(&self) -> Result<Arc<ScalarIndexInfo>> {
+        match &self.resolved_indices {
+            Some(resolved) => Ok(resolved.scalar_index_info.clone()),
+            None => Ok(Arc::new(self.dataset.scalar_index_info().await?)),
+        }
+    }
+
     async fn create_filter_plan(&self, use_scalar_index: bool) -> Result<FilterPlan> {
         let filter_schema = self.filterable_schema()?;
         let planner = Planner::new(Arc::new(filter_schema.as_ref().into()));
 
         // Check expr filter
-        let filter_plan = if let Some(filter) = self.filter.expr_filter.as_ref() {
-            let expr = filter.to_datafusion(self.dataset.schema(), filter_schema.as_ref())?;
-            let index_info = self.dataset.scalar_index_info().await?;
+        let filter_plan = if let Some(expr) =
+            self.expr_filter_with_schema(filter_schema.as_ref())?
+        {
+            let index_info = self.scalar_index_info().await?;
             let filter_plan =
-                planner.create_filter_plan(expr.clone(), &index_info, use_scalar_index)?;
+                planner.create_filter_plan(expr.clone(), index_info.as_ref(), use_scalar_index)?;
 
             // This tests if any of the fragments are missing the physical_rows property (old style)
             // If they are then we cannot use scalar indices
@@ -2391,7 +3034,7 @@ impl Scanner {
                     // We need row counts to use scalar indices.  If we don't have them then
                     // fallback to a non-indexed filter
                     let filter_plan =
-                        planner.create_filter_plan(expr.clone(), &index_info, false)?;
+                        planner.create_filter_plan(expr.clone(), index_info.as_ref(), false)?;
                     FilterPlan::new(self.filter.query_filter.clone(), filter_plan)
                 } else {
                     FilterPlan::new(self.filter.query_filter.clone(), filter_plan)
@@ -2518,11 +3161,19 @@ impl Scanner {
         let mut filter_plan = self.create_filter_plan(use_scalar_index).await?;
 
         let mut use_limit_node = true;
+        let mut partitions_sorted = false;
         // Source: either a (K|A)NN search, full text search, or a (full|indexed) scan
-        let mut plan: Arc<dyn ExecutionPlan> = match (&self.nearest, &self.full_text_query) {
-            (Some(_), None) => self.vector_search_source(&mut filter_plan).await?,
-            (None, Some(query)) => self.fts_search_source(&mut filter_plan, query).await?,
-            (None, None) => {
+        let mut plan: Arc<dyn ExecutionPlan> = match (
+            &self.nearest,
+            &self.full_text_query,
+            &self.sparse_query,
+        ) {
+            (Some(_), None, None) => self.vector_search_source(&mut filter_plan).await?,
+            (None, Some(query), None) => self.fts_search_source(&mut filter_plan, query).await?,
+            (None, None, Some(search)) => {
+                self.sparse_search_source(&mut filter_plan, search).await?
+            }
+            (None, None, None) => {
                 if self.projection_plan.has_output_cols()
                     && self.projection_plan.physical_projection.is_empty()
                 {
@@ -2562,12 +3213,13 @@ impl Scanner {
                     if planned_read.filter_pushed_down {
                         filter_plan.disable_refine();
                     }
+                    partitions_sorted = planned_read.partitions_sorted;
                     planned_read.plan
                 }
             }
             _ => {
                 return Err(Error::invalid_input_source(
-                    "Cannot have both nearest and full text search".into(),
+                    "Cannot combine nearest, full text search and sparse vector search".into(),
                 ));
             }
         };
@@ -2596,11 +3248,30 @@ impl Scanner {
             )?;
         }
 
+        if let Some(group_by) = &self.group_by {
+            pre_filter_projection =
+                pre_filter_projection.union_column(&group_by.column, OnMissing::Error)?;
+        }
+
         plan = self.take(plan, pre_filter_projection)?;
 
         // Filter
         plan = filter_plan.refine_filter(plan, self).await?;
 
+        // Keep the nearest rows of each group, up to k
+        if let (Some(group_by), Some(query)) = (&self.group_by, &self.nearest) {
+            if plan.output_partitioning().partition_count() > 1 {
+                plan = Arc::new(CoalescePartitionsExec::new(plan));
+            }
+            plan = Arc::new(GroupLimitExec::try_new(
+                plan,
+                group_by.column.clone(),
+                group_by.max_per_group,
+                query.k,
+                group_by.null_groups,
+            )?);
+        }
+
         // Aggregate (if set, applies aggregate and returns early)
         if let Some(agg) = &self.aggregate {
             // Take only columns needed by the aggregate, not the full projection.
@@ -2650,11 +3321,13 @@ impl Scanner {
                     })
                 })
                 .collect::<Result<Vec<_>>>()?;
-            plan = Arc::new(SortExec::new(
-                LexOrdering::new(col_exprs)
-                    .ok_or(exec_datafusion_err!("Unexpected empty sort expressions"))?,
-                plan,
-            ));
+            let sort_exprs = LexOrdering::new(col_exprs)
+                .ok_or(exec_datafusion_err!("Unexpected empty sort expressions"))?;
+            plan = if partitions_sorted {
+                Arc::new(SortPreservingMergeExec::new(sort_exprs, plan))
+            } else {
+                Arc::new(SortExec::new(sort_exprs, plan))
+            };
         }
 
         // Limit / offset
@@ -2680,6 +3353,10 @@ impl Scanner {
             plan = Arc::new(StrictBatchSizeExec::new(plan, self.get_batch_size()));
         }
 
+        if self.contiguous_vectors {
+            plan = Arc::new(ContiguousVectorsExec::new(plan));
+        }
+
         let optimizer = get_physical_optimizer();
         let options: ConfigOptions = Default::default();
         for rule in optimizer.rules {
@@ -2798,6 +3475,7 @@ impl Scanner {
             plan,
             limit_pushed_down: false,
             filter_pushed_down,
+            partitions_sorted: false,
         })
     }
 
@@ -2821,9 +3499,15 @@ impl Scanner {
         fragments: Option<Arc<Vec<Fragment>>>,
         scan_range: Option<Range<u64>>,
     ) -> Result<Arc<dyn ExecutionPlan>> {
+        // Version columns require ordered scanning, see `legacy_filtered_read`
+        let ordered = self.ordered
+            || projection.with_row_last_updated_at_version
+            || projection.with_row_created_at_version;
         let mut read_options = FilteredReadOptions::basic_full_read(&self.dataset)
             .with_filter_plan(filter_plan.clone())
-            .with_projection(projection);
+            .with_projection(projection)
+            .with_ordered_output(ordered)
+            .with_page_stats(self.use_stats);
 
         if let Some(fragments) = fragments {
             read_options = read_options.with_fragments(fragments);
@@ -2849,10 +3533,15 @@ impl Scanner {
             read_options = read_options.with_deleted_rows()?;
         }
 
-        if let Some(io_buffer_size_bytes) = self.io_buffer_size {
+        if let Some(io_buffer_size_bytes) = self.io_buffer_size_limit() {
             read_options = read_options.with_io_buffer_size(io_buffer_size_bytes);
         }
 
+        if let Some(num_threads) = self.compute_parallelism {
+            read_options.threading_mode =
+                FilteredReadThreadingMode::OnePartitionMultipleThreads(num_threads);
+        }
+
         if self.fast_search && filter_plan.has_index_query() {
             read_options = read_options.with_only_indexed_fragments();
         }
@@ -2911,6 +3600,7 @@ impl Scanner {
                 filter_pushed_down: true,
                 limit_pushed_down,
                 plan,
+                partitions_sorted: false,
             })
         }
     }
@@ -3006,35 +3696,143 @@ impl Scanner {
             None
         };
 
+        let fragments = match self.fragments_by_statistics(filter_plan).await? {
+            Some(fragments) => Some(fragments),
+            None => self.fragments.clone(),
+        };
+
+        if self.merges_clustered_fragments(filter_plan) {
+            let fragments = fragments.unwrap_or_else(|| self.dataset.fragments().as_ref().clone());
+            if (1..=MAX_MERGED_FRAGMENTS).contains(&fragments.len()) {
+                return self
+                    .clustered_read(filter_plan, projection, fragments)
+                    .await;
+            }
+        }
+
         self.filtered_read(
             filter_plan,
             projection,
             self.include_deleted_rows,
-            self.fragments.clone().map(Arc::new),
+            fragments.map(Arc::new),
             scan_range,
             /*is_prefilter= */ false,
         )
         .await
     }
 
-    async fn fts_search_source(
-        &self,
-        filter_plan: &mut FilterPlan,
-        query: &FullTextSearchQuery,
-    ) -> Result<Arc<dyn ExecutionPlan>> {
-        log::trace!("source is an fts search");
-        if self.include_deleted_rows {
-            return Err(Error::invalid_input_source(
-                "Cannot include deleted rows in an FTS search".into(),
-            ));
-        }
+    /// Whether the scan is ordered by a prefix of the clustering of the dataset, see
+    /// [`Dataset::clustering`], and can merge the fragments, each sorted by it, instead
+    /// of sorting all the rows
+    fn merges_clustered_fragments(&self, filter_plan: &ExprFilterPlan) -> bool {
+        let (Some(ordering), Some(clustering)) = (&self.ordering, self.dataset.clustering()) else {
+            return false;
+        };
+        self.ordered
+            && !self.include_deleted_rows
+            && self.aggregate.is_none()
+            && !filter_plan.has_index_query()
+            && !self.dataset.is_legacy_storage()
+            && clustering.starts_with(ordering)
+    }
 
-        // The source is an FTS search
-        if self.prefilter {
-            let source: Arc<dyn ExecutionPlan> = match &filter_plan.vector_filter() {
-                Some(vector_query) => {
-                    // Perform vector search first then rerank according to BM25 scores
-                    let vector_plan = self
+    /// Read each fragment into a partition of its own, in physical order
+    async fn clustered_read(
+        &self,
+        filter_plan: &ExprFilterPlan,
+        projection: Projection,
+        fragments: Vec<Fragment>,
+    ) -> Result<PlannedFilteredScan> {
+        let mut reads = Vec::with_capacity(fragments.len());
+        for fragment in fragments {
+            reads.push(
+                self.new_filtered_read(
+                    filter_plan,
+                    projection.clone(),
+                    false,
+                    Some(Arc::new(vec![fragment])),
+                    None,
+                )
+                .await?,
+            );
+        }
+        Ok(PlannedFilteredScan {
+            plan: UnionExec::try_new(reads)?,
+            limit_pushed_down: false,
+            filter_pushed_down: true,
+            partitions_sorted: true,
+        })
+    }
+
+    /// The fragments to read, in order, if [`FragmentReadOrder::Statistics`] applies to
+    /// this scan.
+    async fn fragments_by_statistics(
+        &self,
+        filter_plan: &ExprFilterPlan,
+    ) -> Result<Option<Vec<Fragment>>> {
+        if self.fragment_read_order != FragmentReadOrder::Statistics
+            || !filter_plan.is_empty()
+            || self.aggregate.is_some()
+            || self.include_deleted_rows
+        {
+            return Ok(None);
+        }
+        let (Some(ordering), Some(limit)) = (&self.ordering, self.limit) else {
+            return Ok(None);
+        };
+        let Ok(k) = usize::try_from(limit + self.offset.unwrap_or(0)) else {
+            return Ok(None);
+        };
+        let Some(field) = self.dataset.schema().field(&ordering[0].column_name) else {
+            return Ok(None);
+        };
+        // Statistics skip NaN, which sorts after every other float
+        if field.data_type().is_floating() {
+            return Ok(None);
+        }
+
+        let fragments = match &self.fragments {
+            Some(fragments) => fragments.clone(),
+            None => self.dataset.fragments().as_ref().clone(),
+        };
+        let column = ordering[0].column_name.as_str();
+        let bounds = futures::stream::iter(fragments.iter())
+            .map(|fragment| {
+                let fragment = FileFragment::new(self.dataset.clone(), fragment.clone());
+                async move { fragment.column_bounds(column).await }
+            })
+            .buffered(self.dataset.object_store.io_parallelism())
+            .try_collect::<Vec<_>>()
+            .await?;
+        if bounds.iter().all(Option::is_none) {
+            return Ok(None);
+        }
+
+        Ok(Some(order_fragments_by_bounds(
+            fragments.into_iter().zip(bounds).collect(),
+            &ordering[0],
+            k,
+        )))
+    }
+
+    async fn fts_search_source(
+        &self,
+        filter_plan: &mut FilterPlan,
+        query: &FullTextSearchQuery,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        log::trace!("source is an fts search");
+        if self.include_deleted_rows {
+            return Err(Error::invalid_input_source(
+                "Cannot include deleted rows in an FTS search".into(),
+            ));
+        }
+
+        // The source is an FTS search
+        if self.prefilter {
+            let source: Arc<dyn ExecutionPlan> = match &filter_plan.vector_filter() {
+                Some(vector_query) => {
+                    // Perform vector search first then rerank according to BM25 scores
+                    let vector_plan = self
                         .vector_search(&filter_plan.expr_filter_plan, vector_query)
                         .await?;
                     self.fts_rerank(vector_plan, query).await?
@@ -3052,6 +3850,33 @@ impl Scanner {
         }
     }
 
+    async fn sparse_search_source(
+        &self,
+        filter_plan: &mut FilterPlan,
+        search: &SparseSearch,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        log::trace!("source is a sparse vector search");
+        if self.include_deleted_rows {
+            return Err(Error::invalid_input_source(
+                "Cannot include deleted rows in a sparse vector search".into(),
+            ));
+        }
+
+        if self.prefilter {
+            // If we are prefiltering then the search nodes will take care of the filter
+            let source = self
+                .sparse_search(&filter_plan.expr_filter_plan, search)
+                .await?;
+            filter_plan.disable_refine();
+            Ok(source)
+        } else {
+            // If we are postfiltering then we can't use scalar indices for the filter
+            // and will need to run the postfilter in memory
+            filter_plan.make_refine_only();
+            self.sparse_search(&ExprFilterPlan::default(), search).await
+        }
+    }
+
     async fn vector_search_source(
         &self,
         filter_plan: &mut FilterPlan,
@@ -3064,6 +3889,23 @@ impl Scanner {
         let Some(query) = self.nearest.as_ref() else {
             return Err(Error::invalid_input("No nearest query".to_string()));
         };
+        // A grouped search fetches more candidates, the group limit cuts them back to k
+        let grouped_query;
+        let query = match &self.group_by {
+            Some(group_by) => {
+                let mut q = query.clone();
+                q.k = q.k.saturating_mul(group_by.overfetch_factor as usize);
+                grouped_query = q;
+                &grouped_query
+            }
+            None => query,
+        };
+        if !self.allow_nan_query && has_nan(query.key.as_ref()) {
+            return Err(Error::invalid_input(format!(
+                "The query vector for column {} contains NaN, use allow_nan_query to search with it anyway",
+                query.column
+            )));
+        }
 
         if self.prefilter {
             log::trace!("source is a vector search (prefilter)");
@@ -3534,6 +4376,109 @@ impl Scanner {
         Ok(plan)
     }
 
+    async fn sparse_search(
+        &self,
+        filter_plan: &ExprFilterPlan,
+        search: &SparseSearch,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let index = if self.use_scalar_index {
+            self.dataset
+                .load_scalar_index(
+                    IndexCriteria::default()
+                        .for_column(&search.column)
+                        .supports_sparse_search(),
+                )
+                .await?
+        } else {
+            None
+        };
+        let target_fragments = self
+            .fragments
+            .clone()
+            .unwrap_or_else(|| self.dataset.fragments().to_vec());
+
+        let (index_plan, unindexed_fragments) = match index {
+            Some(index) => {
+                let segments = self.dataset.load_indices_by_name(&index.name).await?;
+                let indexed_frags = segments
+                    .iter()
+                    .filter_map(|segment| segment.fragment_bitmap.as_ref())
+                    .fold(RoaringBitmap::new(), |acc, bitmap| acc | bitmap);
+                let prefilter_source = self.prefilter_source(filter_plan, indexed_frags).await?;
+                let index_plan: Arc<dyn ExecutionPlan> = Arc::new(SparseIndexSearchExec::new(
+                    self.dataset.clone(),
+                    search.clone(),
+                    segments,
+                    prefilter_source,
+                ));
+                let unindexed_fragments = self
+                    .retain_target_fragments(self.dataset.unindexed_fragments(&index.name).await?);
+                (Some(index_plan), unindexed_fragments)
+            }
+            None => (None, target_fragments),
+        };
+
+        let flat_plan = if unindexed_fragments.is_empty() || self.fast_search {
+            None
+        } else {
+            Some(self.plan_flat_sparse_search(unindexed_fragments, search, filter_plan)?)
+        };
+
+        let plan = match (index_plan, flat_plan) {
+            (Some(index_plan), None) => return Ok(index_plan),
+            (Some(index_plan), Some(flat_plan)) => UnionExec::try_new(vec![index_plan, flat_plan])?,
+            (None, Some(flat_plan)) => flat_plan,
+            (None, None) => return Ok(Arc::new(EmptyExec::new(FTS_SCHEMA.clone()))),
+        };
+        let plan = Arc::new(RepartitionExec::try_new(
+            plan,
+            Partitioning::RoundRobinBatch(1),
+        )?);
+        let sort_expr = PhysicalSortExpr {
+            expr: expressions::col(SCORE_COL, plan.schema().as_ref())?,
+            options: SortOptions {
+                descending: true,
+                nulls_first: false,
+            },
+        };
+        Ok(Arc::new(
+            SortExec::new([sort_expr].into(), plan).with_fetch(Some(search.k)),
+        ))
+    }
+
+    /// Plan a sparse vector search that scans the given fragments
+    fn plan_flat_sparse_search(
+        &self,
+        fragments: Vec<Fragment>,
+        search: &SparseSearch,
+        filter_plan: &ExprFilterPlan,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let mut columns = vec![search.column.clone()];
+        if let Some(expr) = filter_plan.full_expr.as_ref() {
+            columns.extend(Planner::column_names_in_expr(expr));
+        }
+        let scan_schema = Arc::new(self.dataset.schema().project(&columns)?);
+        let mut scan_node = self.scan_fragments(
+            true,
+            false,
+            false,
+            false,
+            false,
+            scan_schema,
+            Arc::new(fragments),
+            None,
+            false,
+        );
+        if let Some(expr) = filter_plan.full_expr.as_ref() {
+            // If there is a prefilter we need to manually apply it to the new data
+            scan_node = Arc::new(LanceFilterExec::try_new(expr.clone(), scan_node)?);
+        }
+        Ok(Arc::new(SparseFlatSearchExec::new(
+            search.clone(),
+            scan_node,
+        )))
+    }
+
     /// Plan match query on unindexed fragments
     async fn plan_flat_match_query(
         &self,
@@ -3597,7 +4542,7 @@ impl Scanner {
         let column_id = self.dataset.schema().field_id(q.column.as_str())?;
         let use_index = q.use_index;
         let indices = if use_index {
-            self.dataset.load_indices().await?
+            self.load_indices().await?
         } else {
             Arc::new(vec![])
         };
@@ -3735,10 +4680,6 @@ impl Scanner {
         };
 
         if let Some((index_name, index_segments, index_metric)) = index_and_segments {
-            if self.is_batch_nearest {
-                return self.batch_indexed_vector_search(filter_plan, &q).await;
-            }
-
             log::trace!("index found for vector search");
             // Use the index's metric type
             q.metric_type = Some(index_metric);
@@ -3749,6 +4690,31 @@ impl Scanner {
                     "Refine factor cannot be zero".to_string(),
                 ));
             }
+            let k = q.k * q.refine_factor.unwrap_or(1) as usize;
+            match q.ef {
+                Some(ef) if ef < k => {
+                    return Err(Error::invalid_input(format!(
+                        "ef ({}) must be greater than or equal to k ({})",
+                        ef, k
+                    )));
+                }
+                Some(_) => {}
+                // Resolve the HNSW default up front so the plan shows the beam width used.
+                None => {
+                    q.ef = index_segments
+                        .iter()
+                        .filter_map(
+                            crate::index::vector::details::hnsw_construction_ef_from_index_metadata,
+                        )
+                        .max()
+                        .map(|ef| ef.max(k));
+                }
+            }
+            if self.is_batch_nearest {
+                return self
+                    .batch_indexed_vector_search(filter_plan, &q, &index_name, &index_segments)
+                    .await;
+            }
             let ann_node = match vector_type {
                 DataType::FixedSizeList(_, _) => self.ann(&q, &index_segments, filter_plan).await?,
                 DataType::List(_) => self.multivec_ann(&q, &index_segments, filter_plan).await?,
@@ -3818,37 +4784,65 @@ impl Scanner {
         }
     }
 
+    /// Plan a batch of queries against a vector index
+    ///
+    /// With a fixed number of probes, no refine and no unindexed data to scan, the
+    /// queries are searched together so each partition is loaded once for all the
+    /// queries that probe it.  Otherwise each query is planned on its own.
     async fn batch_indexed_vector_search(
         &self,
         filter_plan: &ExprFilterPlan,
         q: &Query,
+        index_name: &str,
+        index_segments: &[IndexMetadata],
     ) -> Result<Arc<dyn ExecutionPlan>> {
-        let query_dim = q.key.len() / self.nearest_query_count;
-        let mut query_plans = Vec::with_capacity(self.nearest_query_count);
+        let search_jointly = q.refine_factor.is_none()
+            && q.maximum_nprobes == Some(q.minimum_nprobes)
+            && (self.fast_search
+                || self
+                    .unindexed_target_fragments(index_name, index_segments)
+                    .await?
+                    .is_empty());
 
-        for query_index in 0..self.nearest_query_count {
-            let mut single_query = q.clone();
-            single_query.key = q.key.slice(query_index * query_dim, query_dim);
+        let unioned: Arc<dyn ExecutionPlan> = if search_jointly {
+            let prefilter_source = self
+                .prefilter_source(filter_plan, self.get_indexed_frags(index_segments))
+                .await?;
+            Arc::new(ANNIvfBatchSearchExec::try_new(
+                self.dataset.clone(),
+                index_segments.to_vec(),
+                q.clone(),
+                self.nearest_query_count,
+                prefilter_source,
+            )?)
+        } else {
+            let query_dim = q.key.len() / self.nearest_query_count;
+            let mut query_plans = Vec::with_capacity(self.nearest_query_count);
 
-            let mut single_scanner = self.clone();
-            single_scanner.nearest_query_count = 1;
-            single_scanner.is_batch_nearest = false;
-            single_scanner.nearest = Some(single_query.clone());
+            for query_index in 0..self.nearest_query_count {
+                let mut single_query = q.clone();
+                single_query.key = q.key.slice(query_index * query_dim, query_dim);
 
-            let single_plan = single_scanner
-                .vector_search(filter_plan, &single_query)
-                .await?;
-            query_plans.push(Self::add_query_index_column(
-                single_plan,
-                query_index as i32,
-            )?);
-        }
+                let mut single_scanner = self.clone();
+                single_scanner.nearest_query_count = 1;
+                single_scanner.is_batch_nearest = false;
+                single_scanner.nearest = Some(single_query.clone());
 
-        let unioned = UnionExec::try_new(query_plans)?;
-        let unioned = Arc::new(RepartitionExec::try_new(
-            unioned,
-            Partitioning::RoundRobinBatch(1),
-        )?) as Arc<dyn ExecutionPlan>;
+                let single_plan = single_scanner
+                    .vector_search(filter_plan, &single_query)
+                    .await?;
+                query_plans.push(Self::add_query_index_column(
+                    single_plan,
+                    query_index as i32,
+                )?);
+            }
+
+            let unioned = UnionExec::try_new(query_plans)?;
+            Arc::new(RepartitionExec::try_new(
+                unioned,
+                Partitioning::RoundRobinBatch(1),
+            )?)
+        };
 
         let query_index_sort = PhysicalSortExpr {
             expr: expressions::col(QUERY_INDEX_COL, unioned.schema().as_ref())?,
@@ -3898,27 +4892,38 @@ impl Scanner {
         Ok(Arc::new(ProjectionExec::try_new(projection_exprs, plan)?))
     }
 
-    /// Combine ANN results with KNN results for data appended after index creation
-    async fn knn_combined(
+    /// The fragments to search that the index segments do not cover
+    async fn unindexed_target_fragments(
         &self,
-        q: &Query,
         index_name: &str,
         indexed_segments: &[IndexMetadata],
-        mut knn_node: Arc<dyn ExecutionPlan>,
-        filter_plan: &ExprFilterPlan,
-    ) -> Result<Arc<dyn ExecutionPlan>> {
-        let fallback_fragments = if let Some(target_fragments) = &self.fragments {
+    ) -> Result<Vec<Fragment>> {
+        if let Some(target_fragments) = &self.fragments {
             let indexed_fragments = self.get_indexed_frags(indexed_segments);
-            target_fragments
+            Ok(target_fragments
                 .iter()
                 .filter(|fragment| !indexed_fragments.contains(fragment.id as u32))
                 .cloned()
-                .collect::<Vec<_>>()
+                .collect())
         } else if self.index_segments.is_some() {
-            Vec::new()
+            Ok(Vec::new())
         } else {
-            self.dataset.unindexed_fragments(index_name).await?
-        };
+            self.dataset.unindexed_fragments(index_name).await
+        }
+    }
+
+    /// Combine ANN results with KNN results for data appended after index creation
+    async fn knn_combined(
+        &self,
+        q: &Query,
+        index_name: &str,
+        indexed_segments: &[IndexMetadata],
+        mut knn_node: Arc<dyn ExecutionPlan>,
+        filter_plan: &ExprFilterPlan,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let fallback_fragments = self
+            .unindexed_target_fragments(index_name, indexed_segments)
+            .await?;
 
         if !fallback_fragments.is_empty() {
             let q = q.clone();
@@ -4179,8 +5184,24 @@ impl Scanner {
         }
     }
 
+    /// The explicit I/O buffer size, capped by the scan memory budget
+    fn io_buffer_size_limit(&self) -> Option<u64> {
+        match (self.io_buffer_size, self.max_scan_memory_bytes) {
+            (Some(size), Some(budget)) => Some(size.min(budget)),
+            (size, budget) => size.or(budget),
+        }
+    }
+
     fn get_io_buffer_size(&self) -> u64 {
-        self.io_buffer_size.unwrap_or(*DEFAULT_IO_BUFFER_SIZE)
+        self.io_buffer_size_limit()
+            .unwrap_or(*DEFAULT_IO_BUFFER_SIZE)
+    }
+
+    fn get_batch_readahead(&self) -> usize {
+        self.compute_parallelism
+            .map_or(self.batch_readahead, |num_threads| {
+                self.batch_readahead.min(num_threads)
+            })
     }
 
     /// Create an Execution plan with a scan node
@@ -4238,7 +5259,7 @@ impl Scanner {
         log::trace!("scan_fragments covered {} fragments", fragments.len());
         let config = LanceScanConfig {
             batch_size: self.get_batch_size(),
-            batch_readahead: self.batch_readahead,
+            batch_readahead: self.get_batch_readahead(),
             fragment_readahead: self.fragment_readahead,
             io_buffer_size: self.get_io_buffer_size(),
             with_row_id,
@@ -4266,7 +5287,7 @@ impl Scanner {
         log::trace!("pushdown_scan");
 
         let config = ScanConfig {
-            batch_readahead: self.batch_readahead,
+            batch_readahead: self.get_batch_readahead(),
             fragment_readahead: self
                 .fragment_readahead
                 .unwrap_or(LEGACY_DEFAULT_FRAGMENT_READAHEAD),
@@ -4453,6 +5474,7 @@ impl Scanner {
                 lower_bound: q.lower_bound,
                 upper_bound: q.upper_bound,
                 distance_type: metric_type,
+                nan_handling: self.nan_handling,
             },
         )?);
 
@@ -4501,7 +5523,8 @@ impl Scanner {
             flat_dist
         };
 
-        // Use DataFusion's [SortExec] for Top-K search
+        // KNNVectorDistanceExec already keeps only the k nearest rows of each
+        // partition, so this [SortExec] merges at most k rows per partition.
         let sort = SortExec::new(
             [
                 PhysicalSortExpr {
@@ -4811,22 +5834,35 @@ impl Scanner {
     #[instrument(level = "info", skip(self))]
     pub async fn analyze_plan(&self) -> Result<String> {
         let plan = self.create_plan().await?;
-        analyze_plan(
+        let analysis = analyze_plan(
             plan,
             LanceExecutionOptions {
                 batch_size: self.batch_size,
                 ..Default::default()
             },
         )
-        .await
+        .await?;
+        Ok(self.with_query_defaults(analysis))
     }
 
+    /// Format the physical plan of the scan.
+    ///
+    /// If the scan uses any of the dataset's [`QueryDefaults`], the plan is
+    /// preceded by a `QueryDefaults: ...` line listing their values.
     #[instrument(level = "info", skip(self))]
     pub async fn explain_plan(&self, verbose: bool) -> Result<String> {
         let plan = self.create_plan().await?;
         let display = DisplayableExecutionPlan::new(plan.as_ref());
 
-        Ok(format!("{}", display.indent(verbose)))
+        Ok(self.with_query_defaults(format!("{}", display.indent(verbose))))
+    }
+
+    fn with_query_defaults(&self, plan: String) -> String {
+        if self.query_defaults.is_empty() {
+            plan
+        } else {
+            format!("QueryDefaults: {}\n{}", self.query_defaults, plan)
+        }
     }
 
     /// Run [`Self::count_rows`]'s underlying plan and return it formatted with
@@ -5198,6 +6234,7 @@ mod test {
 
     use arrow::array::as_primitive_array;
     use arrow::datatypes::{Float64Type, Int32Type, Int64Type};
+    use arrow::ffi_stream::ArrowArrayStreamReader;
     use arrow_array::cast::AsArray;
     use arrow_array::types::{Float32Type, UInt32Type, UInt64Type};
     use arrow_array::{
@@ -5208,11 +6245,17 @@ mod test {
     use arrow_ord::sort::sort_to_indices;
     use arrow_schema::Fields;
     use arrow_select::take;
+    #[cfg(feature = "substrait")]
+    use datafusion::functions::core::expr_fn::get_field;
     use datafusion::logical_expr::{col, lit};
     use half::f16;
     use lance_arrow::{FixedSizeListArrayExt, SchemaExt};
     use lance_core::utils::tempfile::TempStrDir;
     use lance_core::{ROW_CREATED_AT_VERSION, ROW_LAST_UPDATED_AT_VERSION};
+    #[cfg(feature = "substrait")]
+    use lance_datafusion::exec::get_session_context;
+    #[cfg(feature = "substrait")]
+    use lance_datafusion::substrait::encode_substrait;
     use lance_datagen::{
         ArrayGeneratorExt, BatchCount, ByteCount, Dimension, RowCount, array, gen_batch,
     };
@@ -5225,7 +6268,7 @@ mod test {
     use lance_index::vector::sq::builder::SQBuildParams;
     use lance_index::{IndexType, scalar::ScalarIndexParams};
     use lance_io::assert_io_gt;
-    use lance_io::object_store::ObjectStoreParams;
+    use lance_io::object_store::{ObjectStoreParams, WrappingObjectStore};
 
     use lance_linalg::distance::DistanceType;
     use lance_testing::datagen::{BatchGenerator, IncrementingInt32, RandomVector};
@@ -5237,6 +6280,7 @@ mod test {
     use crate::dataset::WriteParams;
     use crate::dataset::optimize::{CompactionOptions, compact_files};
     use crate::dataset::scanner::test_dataset::TestVectorDataset;
+    use crate::dataset::{ProjectionRequest, TakeBuilder};
     use crate::index::vector::{StageParams, VectorIndexParams};
     use crate::utils::test::{
         DatagenExt, FragmentCount, FragmentRowCount, ThrottledStoreWrapper, assert_plan_node_equals,
@@ -5455,44 +6499,145 @@ mod test {
     }
 
     #[tokio::test]
-    async fn test_column_not_exist() {
+    async fn test_strict_batch_size_with_limit_and_nearest() {
         let dataset = lance_datagen::gen_batch()
             .col("x", array::step::<Int32Type>())
+            .col("vec", array::rand_vec::<Float32Type>(Dimension::from(4)))
             .into_ram_dataset(FragmentCount::from(7), FragmentRowCount::from(6))
             .await
             .unwrap();
 
-        let check_err_msg = |r: Result<DatasetRecordBatchStream>| {
-            let Err(err) = r else {
-                panic!(
-                    "Expected an error to be raised saying column y is not found but got no error"
-                )
-            };
-
-            assert!(
-                err.to_string().contains("No field named y"),
-                "Expected error to contain 'No field named y' but got {}",
-                err
-            );
-        };
-
-        let mut scan = dataset.scan();
-        scan.project(&["x", "y"]).unwrap();
-        check_err_msg(scan.try_into_stream().await);
-
         let mut scan = dataset.scan();
-        scan.project(&["y"]).unwrap();
-        check_err_msg(scan.try_into_stream().await);
+        scan.batch_size(4)
+            .strict_batch_size(true)
+            .limit(Some(15), Some(3))
+            .unwrap();
+        let batches = scan
+            .try_into_stream()
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        let batch_sizes = batches.iter().map(|b| b.num_rows()).collect::<Vec<_>>();
+        assert_eq!(batch_sizes, vec![4, 4, 4, 3]);
+        let x = batches
+            .iter()
+            .flat_map(|b| b["x"].as_primitive::<Int32Type>().values().to_vec())
+            .collect::<Vec<_>>();
+        assert_eq!(x, (3..18).collect::<Vec<_>>());
 
-        // This represents a query like `SELECT 1 AS foo` which we could _technically_ satisfy
-        // but it is not supported today
         let mut scan = dataset.scan();
-        scan.project_with_transform(&[("foo", "1")]).unwrap();
-        match scan.try_into_stream().await {
-            Ok(_) => panic!("Expected an error to be raised saying not supported"),
-            Err(e) => {
-                assert!(
-                    e.to_string().contains("Received only dynamic expressions"),
+        scan.batch_size(4)
+            .strict_batch_size(true)
+            .nearest("vec", &Float32Array::from(vec![0.5; 4]), 10)
+            .unwrap();
+        let batches = scan
+            .try_into_stream()
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        let batch_sizes = batches.iter().map(|b| b.num_rows()).collect::<Vec<_>>();
+        assert_eq!(batch_sizes, vec![4, 4, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_contiguous_vectors() {
+        let dataset = lance_datagen::gen_batch()
+            .col("x", array::step::<Int32Type>())
+            .col("vec", array::rand_vec::<Float32Type>(Dimension::from(16)))
+            .into_ram_dataset(FragmentCount::from(3), FragmentRowCount::from(100))
+            .await
+            .unwrap();
+
+        let configure: [fn(&mut Scanner); 3] = [
+            |scan| {
+                scan.filter("x % 3 == 0").unwrap();
+            },
+            |scan| {
+                scan.filter("x % 2 == 1")
+                    .unwrap()
+                    .batch_size(7)
+                    .strict_batch_size(true);
+            },
+            |scan| {
+                scan.limit(Some(50), Some(125)).unwrap();
+            },
+        ];
+        for configure in configure {
+            let mut scan = dataset.scan();
+            configure(&mut scan);
+            let expected = scan.try_into_batch().await.unwrap();
+
+            let mut scan = dataset.scan();
+            configure(&mut scan);
+            scan.contiguous_vectors(true);
+            let batches = scan
+                .try_into_stream()
+                .await
+                .unwrap()
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap();
+            for batch in &batches {
+                let vectors = batch["vec"].as_fixed_size_list();
+                let values = vectors.values().to_data();
+                assert_eq!(values.buffers().len(), 1);
+                assert_eq!(values.offset(), 0);
+                assert_eq!(values.buffers()[0].ptr_offset(), 0);
+                assert_eq!(values.buffers()[0].len(), batch.num_rows() * 16 * 4);
+                assert_eq!(
+                    values.buffers()[0].as_ptr() as usize
+                        % lance_arrow::contiguous::VECTOR_BUFFER_ALIGNMENT,
+                    0
+                );
+            }
+            let actual = concat_batches(&expected.schema(), &batches).unwrap();
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_column_not_exist() {
+        let dataset = lance_datagen::gen_batch()
+            .col("x", array::step::<Int32Type>())
+            .into_ram_dataset(FragmentCount::from(7), FragmentRowCount::from(6))
+            .await
+            .unwrap();
+
+        let check_err_msg = |r: Result<DatasetRecordBatchStream>| {
+            let Err(err) = r else {
+                panic!(
+                    "Expected an error to be raised saying column y is not found but got no error"
+                )
+            };
+
+            assert!(
+                err.to_string().contains("No field named y"),
+                "Expected error to contain 'No field named y' but got {}",
+                err
+            );
+        };
+
+        let mut scan = dataset.scan();
+        scan.project(&["x", "y"]).unwrap();
+        check_err_msg(scan.try_into_stream().await);
+
+        let mut scan = dataset.scan();
+        scan.project(&["y"]).unwrap();
+        check_err_msg(scan.try_into_stream().await);
+
+        // This represents a query like `SELECT 1 AS foo` which we could _technically_ satisfy
+        // but it is not supported today
+        let mut scan = dataset.scan();
+        scan.project_with_transform(&[("foo", "1")]).unwrap();
+        match scan.try_into_stream().await {
+            Ok(_) => panic!("Expected an error to be raised saying not supported"),
+            Err(e) => {
+                assert!(
+                    e.to_string().contains("Received only dynamic expressions"),
                     "Expected error to contain 'Received only dynamic expressions' but got {}",
                     e
                 );
@@ -5843,6 +6988,132 @@ mod test {
         assert!(duration < Duration::from_secs(10));
     }
 
+    async fn dataset_with_store_wrapper(
+        wrapper: Option<Arc<dyn WrappingObjectStore>>,
+        num_fragments: u32,
+    ) -> Dataset {
+        let write_params = WriteParams {
+            store_params: Some(ObjectStoreParams {
+                object_store_wrapper: wrapper,
+                ..Default::default()
+            }),
+            max_rows_per_file: 10,
+            ..Default::default()
+        };
+        gen_batch()
+            .col("i", array::step::<Int32Type>())
+            .col("vec", array::rand_vec::<Float32Type>(Dimension::from(4)))
+            .into_ram_dataset_with_params(
+                FragmentCount::from(num_fragments),
+                FragmentRowCount::from(10),
+                Some(write_params),
+            )
+            .await
+            .unwrap()
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_scan_timeout(#[values(false, true)] vector_search: bool) {
+        let throttled = Arc::new(ThrottledStoreWrapper {
+            config: ThrottleConfig {
+                wait_get_per_call: Duration::from_millis(200),
+                ..Default::default()
+            },
+        });
+        let dataset = dataset_with_store_wrapper(Some(throttled), 100).await;
+
+        let mut scan = dataset.scan();
+        if vector_search {
+            let key = Float32Array::from(vec![0.5; 4]);
+            scan.nearest("vec", &key, 5).unwrap();
+        }
+        scan.timeout(Duration::from_millis(300));
+
+        let start = Instant::now();
+        let err = match scan.try_into_stream().await {
+            Ok(stream) => stream.try_collect::<Vec<_>>().await.unwrap_err(),
+            Err(err) => err,
+        };
+        let duration = start.elapsed();
+        assert!(matches!(err, Error::Timeout { .. }), "{err:?}");
+        // Reading every fragment takes 20s or more
+        assert!(duration < Duration::from_secs(5), "{duration:?}");
+    }
+
+    #[tokio::test]
+    async fn test_scan_cancellation_stops_io() {
+        let throttled = Arc::new(ThrottledStoreWrapper {
+            config: ThrottleConfig {
+                wait_get_per_call: Duration::from_millis(20),
+                ..Default::default()
+            },
+        });
+        let dataset = dataset_with_store_wrapper(Some(throttled), 100).await;
+
+        let token = CancellationToken::new();
+        let mut scan = dataset.scan();
+        scan.batch_size(10).cancellation_token(token.clone());
+        let mut stream = scan.try_into_stream().await.unwrap();
+        stream.next().await.unwrap().unwrap();
+
+        token.cancel();
+        let err = stream.next().await.unwrap().unwrap_err();
+        assert!(matches!(err, Error::Cancelled { .. }), "{err:?}");
+        assert!(stream.next().await.is_none());
+
+        // Reads that were in flight are aborted and no new reads start
+        dataset.object_store.as_ref().io_stats_incremental();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(
+            dataset
+                .object_store
+                .as_ref()
+                .io_stats_incremental()
+                .read_iops,
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scan_limits_do_not_affect_results() {
+        let dataset = dataset_with_store_wrapper(None, 10).await;
+        let expected = dataset.scan().try_into_batch().await.unwrap();
+
+        let token = CancellationToken::new();
+        let mut scan = dataset.scan();
+        scan.timeout(Duration::from_secs(60))
+            .cancellation_token(token.clone());
+        assert_eq!(scan.try_into_batch().await.unwrap(), expected);
+        assert!(!token.is_cancelled());
+
+        let batch = TakeBuilder::try_new_from_ids(
+            Arc::new(dataset.clone()),
+            vec![1, 5, 7],
+            ProjectionRequest::from_columns(["i"], dataset.schema()),
+        )
+        .unwrap()
+        .with_timeout(Duration::from_secs(60))
+        .with_cancellation_token(token.clone())
+        .execute()
+        .await
+        .unwrap();
+        assert_eq!(batch.num_rows(), 3);
+
+        token.cancel();
+        let err = TakeBuilder::try_new_from_ids(
+            Arc::new(dataset.clone()),
+            vec![1, 5, 7],
+            ProjectionRequest::from_columns(["i"], dataset.schema()),
+        )
+        .unwrap()
+        .with_cancellation_token(token)
+        .execute()
+        .await
+        .unwrap_err();
+        assert!(matches!(err, Error::Cancelled { .. }), "{err:?}");
+    }
+
     #[rstest]
     #[tokio::test]
     async fn test_knn_nodes(
@@ -6222,6 +7493,205 @@ mod test {
         .await;
     }
 
+    fn batch_knn_queries(query_count: usize) -> FixedSizeListArray {
+        // Spread the queries over the 80 distinct vectors of the test dataset
+        let query_values = (0..query_count)
+            .flat_map(|query_index| {
+                let start = (query_index * 77 % 80) * 32;
+                (start..start + 32).map(|v| v as f32)
+            })
+            .collect::<Vec<_>>();
+        FixedSizeListArray::try_new_from_values(Float32Array::from(query_values), 32).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_batch_knn_indexed_joint_search() {
+        let mut test_ds = TestVectorDataset::new(LanceFileVersion::Stable, true)
+            .await
+            .unwrap();
+        test_ds.make_vector_index().await.unwrap();
+        let dataset = &test_ds.dataset;
+        let queries = batch_knn_queries(16);
+
+        let batch_scan = || {
+            let mut scan = dataset.scan();
+            scan.nearest("vec", &queries, 5)
+                .unwrap()
+                .nprobes(1)
+                .prefilter(true)
+                .filter("i % 3 != 0")
+                .unwrap()
+                .project(&["i"])
+                .unwrap();
+            scan
+        };
+        let plan = batch_scan().explain_plan(false).await.unwrap();
+        assert!(
+            plan.contains("ANNIvfBatchSearch: name=idx, queries=16, k=5, nprobes=1"),
+            "batch KNN with fixed nprobes should search the queries jointly, got:\n{}",
+            plan
+        );
+        assert!(!plan.contains("ANNSubIndex"), "{plan}");
+
+        let batch = batch_scan().try_into_batch().await.unwrap();
+        assert_query_index_field(&batch);
+        let query_indices = batch[QUERY_INDEX_COL].as_primitive::<Int32Type>();
+        for query_index in 0..queries.len() {
+            let single = dataset
+                .scan()
+                .nearest("vec", queries.value(query_index).as_ref(), 5)
+                .unwrap()
+                .nprobes(1)
+                .prefilter(true)
+                .filter("i % 3 != 0")
+                .unwrap()
+                .project(&["i"])
+                .unwrap()
+                .try_into_batch()
+                .await
+                .unwrap();
+
+            let mask = BooleanArray::from_iter(
+                query_indices
+                    .iter()
+                    .map(|value| value.map(|value| value == query_index as i32)),
+            );
+            let batch_slice = arrow::compute::filter_record_batch(&batch, &mask).unwrap();
+            assert_eq!(
+                batch_slice["i"].as_primitive::<Int32Type>().values(),
+                single["i"].as_primitive::<Int32Type>().values()
+            );
+            assert_eq!(
+                batch_slice[DIST_COL].as_primitive::<Float32Type>().values(),
+                single[DIST_COL].as_primitive::<Float32Type>().values()
+            );
+        }
+
+        // Without a fixed number of probes each query is planned on its own
+        let plan = dataset
+            .scan()
+            .nearest("vec", &queries, 5)
+            .unwrap()
+            .minimum_nprobes(1)
+            .explain_plan(false)
+            .await
+            .unwrap();
+        assert!(!plan.contains("ANNIvfBatchSearch"), "{plan}");
+        assert!(plan.contains("ANNSubIndex"), "{plan}");
+    }
+
+    #[tokio::test]
+    async fn test_batch_knn_indexed_shares_partition_reads() {
+        use crate::dataset::builder::DatasetBuilder;
+
+        let mut test_ds = TestVectorDataset::new(LanceFileVersion::Stable, true)
+            .await
+            .unwrap();
+        test_ds.make_vector_index().await.unwrap();
+        // Nothing is cached, so every partition search reads the partition
+        let dataset = DatasetBuilder::from_uri(test_ds.dataset.uri())
+            .with_index_cache_size_bytes(0)
+            .load()
+            .await
+            .unwrap();
+        let indices_dir = dataset.indices_dir();
+        let index_reads = || {
+            dataset
+                .object_store
+                .as_ref()
+                .io_stats_incremental()
+                .requests
+                .iter()
+                .filter(|request| request.path.prefix_matches(&indices_dir))
+                .count()
+        };
+        let queries = batch_knn_queries(8);
+
+        index_reads();
+        dataset
+            .scan()
+            .nearest("vec", queries.value(0).as_ref(), 5)
+            .unwrap()
+            .nprobes(2)
+            .try_into_batch()
+            .await
+            .unwrap();
+        let single_reads = index_reads();
+
+        let mut scan = dataset.scan();
+        scan.nearest("vec", &queries, 5).unwrap().nprobes(2);
+        let batch = scan.try_into_batch().await.unwrap();
+        assert_eq!(batch.num_rows(), 8 * 5);
+        let batch_reads = index_reads();
+
+        for query_index in 0..queries.len() {
+            dataset
+                .scan()
+                .nearest("vec", queries.value(query_index).as_ref(), 5)
+                .unwrap()
+                .nprobes(2)
+                .try_into_batch()
+                .await
+                .unwrap();
+        }
+        let loop_reads = index_reads();
+
+        // Each partition is read once for the whole batch
+        assert!(
+            batch_reads <= single_reads,
+            "batch reads {batch_reads} > single query reads {single_reads}"
+        );
+        assert!(
+            batch_reads < loop_reads,
+            "batch reads {batch_reads} >= per-query reads {loop_reads}"
+        );
+        let analysis = scan.analyze_plan().await.unwrap();
+        assert!(analysis.contains("parts_loaded=2"), "{analysis}");
+    }
+
+    #[tokio::test]
+    async fn test_batch_knn_indexed_many_queries() {
+        use crate::dataset::builder::DatasetBuilder;
+        use crate::session::Session;
+
+        let mut test_ds = TestVectorDataset::new(LanceFileVersion::Stable, true)
+            .await
+            .unwrap();
+        test_ds.make_vector_index().await.unwrap();
+        let session = Session::default().with_max_scan_memory_bytes(64 * 1024);
+        let dataset = DatasetBuilder::from_uri(test_ds.dataset.uri())
+            .with_session(Arc::new(session))
+            .load()
+            .await
+            .unwrap();
+
+        let queries = batch_knn_queries(1000);
+        let batch = dataset
+            .scan()
+            .nearest("vec", &queries, 10)
+            .unwrap()
+            .nprobes(1)
+            .prefilter(true)
+            .filter("i >= 10")
+            .unwrap()
+            .project(&["i"])
+            .unwrap()
+            .try_into_batch()
+            .await
+            .unwrap();
+
+        assert_eq!(batch.num_rows(), 1000 * 10);
+        let expected = Int32Array::from_iter_values((0..1000).flat_map(|i| [i; 10]));
+        assert_eq!(batch[QUERY_INDEX_COL].as_ref(), &expected as &dyn Array);
+        assert!(
+            batch["i"]
+                .as_primitive::<Int32Type>()
+                .values()
+                .iter()
+                .all(|i| *i >= 10)
+        );
+    }
+
     #[tokio::test]
     async fn test_can_project_distance() {
         let test_ds = TestVectorDataset::new(LanceFileVersion::Stable, true)
@@ -6391,7 +7861,120 @@ mod test {
         assert!(actual_i.is_subset(&close_i));
     }
 
-    #[rstest]
+    /// Prefiltered ANN search returning the sorted `i` of the results, and the plan.
+    async fn prefiltered_ann(
+        dataset: &Dataset,
+        filter: &str,
+        use_scalar_index: bool,
+    ) -> (Vec<i32>, String) {
+        let key: Float32Array = (32..64).map(|v| v as f32).collect();
+        let mut scan = dataset.scan();
+        scan.nearest("vec", &key, 500)
+            .unwrap()
+            .minimum_nprobes(2)
+            .prefilter(true)
+            .use_scalar_index(use_scalar_index)
+            .filter(filter)
+            .unwrap()
+            .project(&["i"])
+            .unwrap();
+        let plan = scan.explain_plan(true).await.unwrap();
+        let batch = scan.try_into_batch().await.unwrap();
+        let mut ids = batch
+            .column_by_name("i")
+            .unwrap()
+            .as_primitive::<Int32Type>()
+            .values()
+            .to_vec();
+        ids.sort();
+        (ids, plan)
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_knn_prefilter_from_scalar_index(
+        #[values(LanceFileVersion::Legacy, LanceFileVersion::Stable)]
+        data_storage_version: LanceFileVersion,
+    ) {
+        let mut test_ds = TestVectorDataset::new(data_storage_version, false)
+            .await
+            .unwrap();
+        test_ds
+            .dataset
+            .create_index(
+                &["s"],
+                IndexType::Bitmap,
+                None,
+                &ScalarIndexParams::default(),
+                true,
+            )
+            .await
+            .unwrap();
+        test_ds.make_vector_index().await.unwrap();
+        test_ds.dataset.delete("i = 105").await.unwrap();
+        let filter = "s IN ('s-5', 's-105', 's-205', 's-305', 's-405')";
+
+        // The bitmap index covers every fragment of the vector index.
+        let (ids, plan) = prefiltered_ann(&test_ds.dataset, filter, true).await;
+        assert_eq!(ids, vec![5, 205, 305]);
+        assert!(plan.contains("prefilter: index-only"), "{plan}");
+        let (scanned_ids, plan) = prefiltered_ann(&test_ds.dataset, filter, false).await;
+        assert_eq!(scanned_ids, ids);
+        assert!(plan.contains("prefilter: scan"), "{plan}");
+
+        // Without reading the data files at all.
+        let data_dir = test_ds.dataset.data_dir();
+        let data_reads = |use_scalar_index: bool| {
+            let dataset = &test_ds.dataset;
+            let data_dir = &data_dir;
+            async move {
+                let key: Float32Array = (32..64).map(|v| v as f32).collect();
+                dataset.object_store.as_ref().io_stats_incremental();
+                dataset
+                    .scan()
+                    .nearest("vec", &key, 500)
+                    .unwrap()
+                    .minimum_nprobes(2)
+                    .prefilter(true)
+                    .use_scalar_index(use_scalar_index)
+                    .filter(filter)
+                    .unwrap()
+                    .project::<&str>(&[])
+                    .unwrap()
+                    .with_row_id()
+                    .try_into_batch()
+                    .await
+                    .unwrap();
+                let stats = dataset.object_store.as_ref().io_stats_incremental();
+                stats
+                    .requests
+                    .iter()
+                    .filter(|request| request.path.prefix_matches(data_dir))
+                    .count()
+            }
+        };
+        assert_eq!(data_reads(true).await, 0);
+        assert!(data_reads(false).await > 0);
+
+        // Rows appended after the bitmap index was built are scanned.
+        test_ds.append_new_data().await.unwrap();
+        test_ds.make_vector_index().await.unwrap();
+        let (ids, plan) = prefiltered_ann(&test_ds.dataset, filter, true).await;
+        assert_eq!(ids, vec![5, 205, 305, 405]);
+        assert!(plan.contains("prefilter: index+scan"), "{plan}");
+        let (scanned_ids, _) = prefiltered_ann(&test_ds.dataset, filter, false).await;
+        assert_eq!(scanned_ids, ids);
+
+        // As is the part of the filter the bitmap index can't answer.
+        let filter = "s IN ('s-5', 's-105', 's-205', 's-305', 's-405') AND i > 200";
+        let (ids, plan) = prefiltered_ann(&test_ds.dataset, filter, true).await;
+        assert_eq!(ids, vec![205, 305, 405]);
+        assert!(plan.contains("prefilter: index+scan"), "{plan}");
+        let (scanned_ids, _) = prefiltered_ann(&test_ds.dataset, filter, false).await;
+        assert_eq!(scanned_ids, ids);
+    }
+
+    #[rstest]
     #[tokio::test]
     async fn test_knn_filter_new_data(
         #[values(LanceFileVersion::Legacy, LanceFileVersion::Stable)]
@@ -6748,6 +8331,88 @@ mod test {
         assert_eq!(expected_row_ids, actual_row_ids);
     }
 
+    #[tokio::test]
+    async fn test_scan_unordered_emits_fragments_as_they_complete() {
+        let dataset = lance_datagen::gen_batch()
+            .col("x", array::step::<Int32Type>())
+            .into_ram_dataset(FragmentCount::from(8), FragmentRowCount::from(1000))
+            .await
+            .unwrap();
+
+        let mut scan = dataset.scan();
+        scan.batch_size(100).scan_in_order(false);
+
+        // Out-of-order emission depends on scheduling, so try a few times.
+        let mut out_of_order = false;
+        for _ in 0..10 {
+            let batches = scan
+                .try_into_stream()
+                .await
+                .unwrap()
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap();
+            let mut x = batches
+                .iter()
+                .flat_map(|b| b["x"].as_primitive::<Int32Type>().values().to_vec())
+                .collect::<Vec<_>>();
+            if !x.is_sorted() {
+                out_of_order = true;
+                x.sort();
+            }
+            assert_eq!(x, (0..8000).collect::<Vec<_>>());
+            if out_of_order {
+                break;
+            }
+        }
+        assert!(out_of_order);
+    }
+
+    #[tokio::test]
+    async fn test_limit_unordered() {
+        let test_ds = TestVectorDataset::new(LanceFileVersion::Stable, false)
+            .await
+            .unwrap();
+        let dataset = &test_ds.dataset;
+
+        let sorted_i = |batch: &RecordBatch| {
+            let mut i = batch["i"].as_primitive::<Int32Type>().values().to_vec();
+            i.sort();
+            i
+        };
+
+        let expected = dataset
+            .scan()
+            .limit(Some(30), Some(19))
+            .unwrap()
+            .try_into_batch()
+            .await
+            .unwrap();
+        let actual = dataset
+            .scan()
+            .scan_in_order(false)
+            .limit(Some(30), Some(19))
+            .unwrap()
+            .try_into_batch()
+            .await
+            .unwrap();
+        assert_eq!(actual.num_rows(), 30);
+        assert_eq!(sorted_i(&actual), sorted_i(&expected));
+
+        let actual = dataset
+            .scan()
+            .scan_in_order(false)
+            .filter("i % 3 = 0")
+            .unwrap()
+            .limit(Some(10), None)
+            .unwrap()
+            .try_into_batch()
+            .await
+            .unwrap();
+        assert_eq!(actual.num_rows(), 10);
+        assert!(sorted_i(&actual).iter().all(|i| i % 3 == 0));
+    }
+
     #[tokio::test]
     async fn test_scan_unordered_with_row_id() {
         // This test doesn't make sense for v2 files, there is no way to get an out-of-order scan
@@ -7608,6 +9273,48 @@ mod test {
         );
     }
 
+    #[tokio::test]
+    async fn test_hnsw_ef() {
+        let vec_params = VectorIndexParams::with_ivf_hnsw_sq_params(
+            MetricType::L2,
+            IvfBuildParams::new(2),
+            HnswBuildParams::default().ef_construction(40),
+            SQBuildParams::default(),
+        );
+        let mut data = gen_batch()
+            .col("vec", array::rand_vec::<Float32Type>(Dimension::from(16)))
+            .into_ram_dataset(FragmentCount::from(2), FragmentRowCount::from(200))
+            .await
+            .unwrap();
+        data.create_index(&["vec"], IndexType::Vector, None, &vec_params, true)
+            .await
+            .unwrap();
+        let q = Float32Array::from(vec![0.5; 16]);
+
+        // Without an explicit ef the index default, max(k, ef_construction), is used.
+        for (k, ef) in [(10, 40), (50, 50)] {
+            let mut scan = data.scan();
+            scan.nearest("vec", &q, k).unwrap();
+            let plan = scan.explain_plan(false).await.unwrap();
+            assert!(
+                plan.contains(&format!("k={k}, deltas=1, metric=L2, ef={ef}")),
+                "{plan}"
+            );
+            assert_eq!(scan.try_into_batch().await.unwrap().num_rows(), k);
+        }
+
+        let mut scan = data.scan();
+        scan.nearest("vec", &q, 10).unwrap().ef(64);
+        let plan = scan.analyze_plan().await.unwrap();
+        assert!(plan.contains("ef=64"), "{plan}");
+
+        let mut scan = data.scan();
+        scan.nearest("vec", &q, 10).unwrap().ef(5);
+        let err = scan.try_into_batch().await.unwrap_err();
+        assert!(matches!(err, Error::InvalidInput { .. }), "{err}");
+        assert!(err.to_string().contains("ef (5)"), "{err}");
+    }
+
     #[rstest]
     #[tokio::test]
     async fn test_count_rows_with_filter(
@@ -8372,6 +10079,103 @@ mod test {
         .unwrap();
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_scan_into_ffi_stream() {
+        // The stream blocks on the runtime, so it is read from outside of it
+        async fn read_ffi_stream(
+            stream: FFI_ArrowArrayStream,
+        ) -> std::result::Result<Vec<RecordBatch>, arrow_schema::ArrowError> {
+            tokio::task::spawn_blocking(move || {
+                ArrowArrayStreamReader::try_new(stream)
+                    .unwrap()
+                    .collect::<std::result::Result<Vec<_>, _>>()
+            })
+            .await
+            .unwrap()
+        }
+
+        let data = gen_batch()
+            .col("i", array::step::<Int32Type>())
+            .col("s", array::cycle_utf8_literals(&["1", "2", "three"]))
+            .into_reader_rows(RowCount::from(30), BatchCount::from(3));
+        let dataset = Dataset::write(data, "memory://test_scan_into_ffi_stream", None)
+            .await
+            .unwrap();
+
+        let mut scanner = dataset.scan();
+        scanner.batch_size(7).filter("i % 2 = 0").unwrap();
+        let expected = scanner.try_into_batch().await.unwrap();
+        for handle in [None, Some(tokio::runtime::Handle::current())] {
+            let stream = scanner.try_into_ffi_stream(handle).await.unwrap();
+            let batches = read_ffi_stream(stream).await.unwrap();
+            assert!(batches.len() > 1);
+            assert_eq!(
+                concat_batches(&expected.schema(), &batches).unwrap(),
+                expected
+            );
+        }
+
+        // Errors raised while scanning come back through the stream
+        let mut scanner = dataset.scan();
+        scanner.filter("CAST(s AS INT) > 0").unwrap();
+        let stream = scanner.try_into_ffi_stream(None).await.unwrap();
+        let err = read_ffi_stream(stream).await.unwrap_err();
+        assert!(err.to_string().contains("'three'"), "{}", err);
+    }
+
+    #[cfg(feature = "substrait")]
+    #[tokio::test]
+    async fn test_filter_substrait() {
+        let nested = StructArray::from(vec![(
+            Arc::new(ArrowField::new("a", DataType::Int32, false)),
+            Arc::new(Int32Array::from_iter_values((0..100).map(|i| i % 10))) as ArrayRef,
+        )]);
+        let schema = Arc::new(ArrowSchema::new(vec![
+            ArrowField::new("id", DataType::Int32, false),
+            ArrowField::new("s", nested.data_type().clone(), false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from_iter_values(0..100)),
+                Arc::new(nested),
+            ],
+        )
+        .unwrap();
+        let reader = RecordBatchIterator::new(vec![Ok(batch)], schema.clone());
+        let mut dataset = Dataset::write(reader, "memory://test_filter_substrait", None)
+            .await
+            .unwrap();
+        dataset
+            .create_index(
+                &["id"],
+                IndexType::BTree,
+                None,
+                &ScalarIndexParams::default(),
+                true,
+            )
+            .await
+            .unwrap();
+
+        let state = get_session_context(&LanceExecutionOptions::default()).state();
+        let filter = col("id")
+            .lt(lit(20))
+            .and(get_field(col("s"), "a").eq(lit(3)));
+        let filter = encode_substrait(filter, schema, &state).unwrap();
+
+        let mut scanner = dataset.scan();
+        scanner.filter_substrait(&filter).unwrap();
+        let plan = scanner.explain_plan(true).await.unwrap();
+        assert!(
+            plan.contains("ScalarIndexQuery: query=[id < 20]"),
+            "{}",
+            plan
+        );
+
+        let batch = scanner.try_into_batch().await.unwrap();
+        assert_eq!(batch["id"].as_primitive::<Int32Type>().values(), &[3, 13]);
+    }
+
     #[tokio::test]
     async fn test_like_prefix_with_btree_index() {
         // Create dataset with string data that has various prefixes
@@ -9253,6 +11057,50 @@ full_filter=name LIKE Utf8(\"test%2\"), refine_filter=name LIKE Utf8(\"test%2\")
         assert_io_lt!(io_stats, read_bytes, index_scan_bytes);
     }
 
+    #[tokio::test]
+    async fn test_late_materialization_scattered_take() {
+        // Late materialized columns are fetched with a coalesced take, make sure the
+        // values still line up with the rows that matched the filter
+        let data = gen_batch()
+            .col(
+                "vector",
+                array::rand_vec::<Float32Type>(Dimension::from(128)),
+            )
+            .col("id", array::step::<Int32Type>())
+            .into_reader_rows(RowCount::from(5000), BatchCount::from(4));
+        let dataset = Dataset::write(
+            data,
+            "memory://test",
+            Some(WriteParams {
+                max_rows_per_file: 8000,
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+
+        let scan = |style: MaterializationStyle| {
+            let dataset = dataset.clone();
+            async move {
+                dataset
+                    .scan()
+                    .filter("id % 97 = 3")
+                    .unwrap()
+                    .materialization_style(style)
+                    .try_into_batch()
+                    .await
+                    .unwrap()
+            }
+        };
+        let early = scan(MaterializationStyle::AllEarly).await;
+        let late = scan(MaterializationStyle::AllLate).await;
+
+        let ids = late["id"].as_primitive::<Int32Type>();
+        assert_eq!(ids.len(), (0..20_000).filter(|i| i % 97 == 3).count());
+        assert!(ids.values().iter().all(|id| id % 97 == 3));
+        assert_eq!(early, late);
+    }
+
     #[rstest]
     #[tokio::test]
     async fn test_project_nested(
@@ -11284,6 +13132,181 @@ full_filter=name LIKE Utf8(\"test%2\"), refine_filter=name LIKE Utf8(\"test%2\")
         assert_eq!(unsorted_values[2], 80);
     }
 
+    #[test]
+    fn test_order_fragments_by_bounds() {
+        let fragment = |id, num_rows| Fragment::new(id).with_physical_rows(num_rows);
+        let bounds = |min: i64, max: i64, null_count| {
+            Some(ColumnBounds {
+                min: ScalarValue::Int64(Some(min)),
+                max: ScalarValue::Int64(Some(max)),
+                null_count,
+            })
+        };
+        let fragments = vec![
+            (fragment(0, 10), bounds(0, 9, 0)),
+            (fragment(1, 10), bounds(50, 59, 0)),
+            (fragment(2, 10), None),
+            (fragment(3, 10), bounds(5, 14, 0)),
+            (fragment(4, 10), bounds(20, 29, 2)),
+        ];
+        let order = |ordering: ColumnOrdering, k| {
+            order_fragments_by_bounds(fragments.clone(), &ordering, k)
+                .into_iter()
+                .map(|fragment| fragment.id)
+                .collect::<Vec<_>>()
+        };
+
+        // Fragments 0 and 3 hold 20 rows <= 14
+        assert_eq!(
+            order(ColumnOrdering::asc_nulls_last("v".into()), 15),
+            vec![2, 0, 3]
+        );
+        // Nulls sorted first can be anywhere in the result
+        assert_eq!(
+            order(ColumnOrdering::asc_nulls_first("v".into()), 15),
+            vec![2, 4, 0, 3]
+        );
+        // Fragment 1 holds 10 rows >= 50
+        assert_eq!(
+            order(ColumnOrdering::desc_nulls_last("v".into()), 5),
+            vec![2, 1]
+        );
+        // Fragments 1 and 4 hold 18 rows >= 20
+        assert_eq!(
+            order(ColumnOrdering::desc_nulls_last("v".into()), 18),
+            vec![2, 1, 4]
+        );
+        // Not enough rows to skip anything
+        assert_eq!(
+            order(ColumnOrdering::asc_nulls_last("v".into()), 100),
+            vec![2, 0, 3, 4, 1]
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_fragment_read_order_skips_fragments(
+        #[values(LanceFileVersion::Legacy, LanceFileVersion::Stable)]
+        data_storage_version: LanceFileVersion,
+    ) {
+        let dataset = gen_batch()
+            .col("ts", array::step::<Int64Type>())
+            .col("payload", array::rand_utf8(ByteCount::from(64), false))
+            .into_ram_dataset_with_params(
+                FragmentCount::from(8),
+                FragmentRowCount::from(1000),
+                Some(WriteParams {
+                    max_rows_per_file: 1000,
+                    data_storage_version: Some(data_storage_version),
+                    collect_stats: true,
+                    ..Default::default()
+                }),
+            )
+            .await
+            .unwrap();
+
+        let latest = |order| {
+            let mut scan = dataset.scan();
+            scan.order_by(Some(vec![ColumnOrdering::desc_nulls_last("ts".into())]))
+                .unwrap()
+                .limit(Some(10), None)
+                .unwrap()
+                .fragment_read_order(order);
+            async move { scan.try_into_batch().await.unwrap() }
+        };
+
+        let _ = dataset.object_store.as_ref().io_stats_incremental(); // reset
+        let expected = latest(FragmentReadOrder::Manifest).await;
+        let full_scan_bytes = dataset
+            .object_store
+            .as_ref()
+            .io_stats_incremental()
+            .read_bytes;
+
+        let batch = latest(FragmentReadOrder::Statistics).await;
+        let io_stats = dataset.object_store.as_ref().io_stats_incremental();
+        assert_eq!(batch, expected);
+        assert_eq!(
+            batch["ts"].as_primitive::<Int64Type>().values().to_vec(),
+            (7990..8000).rev().collect::<Vec<_>>()
+        );
+        // Only the last fragment is read
+        assert!(
+            io_stats.read_bytes < full_scan_bytes / 4,
+            "{} >= {} / 4",
+            io_stats.read_bytes,
+            full_scan_bytes
+        );
+    }
+
+    #[rstest]
+    #[tokio::test]
+    async fn test_fragment_read_order_overlapping_fragments(
+        #[values(LanceFileVersion::Legacy, LanceFileVersion::Stable)]
+        data_storage_version: LanceFileVersion,
+    ) {
+        // Fragments with overlapping ranges, nulls and deletions
+        let schema = Arc::new(ArrowSchema::new(vec![
+            ArrowField::new("id", DataType::Int64, false),
+            ArrowField::new("v", DataType::Int64, true),
+        ]));
+        let batches = (0..6)
+            .map(|fragment| {
+                let ids = (fragment * 100..(fragment + 1) * 100).collect::<Vec<i64>>();
+                let values = ids
+                    .iter()
+                    .map(|id| (fragment != 2 || id % 10 != 0).then_some((id * 7919) % 500))
+                    .collect::<Int64Array>();
+                RecordBatch::try_new(
+                    schema.clone(),
+                    vec![Arc::new(Int64Array::from(ids)), Arc::new(values)],
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let reader = RecordBatchIterator::new(batches.into_iter().map(Ok), schema.clone());
+        let mut dataset = Dataset::write(
+            reader,
+            "memory://",
+            Some(WriteParams {
+                max_rows_per_file: 100,
+                data_storage_version: Some(data_storage_version),
+                collect_stats: true,
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+        dataset.delete("v < 10 OR id % 7 = 0").await.unwrap();
+
+        for ordering in [
+            ColumnOrdering::asc_nulls_first("v".into()),
+            ColumnOrdering::asc_nulls_last("v".into()),
+            ColumnOrdering::desc_nulls_first("v".into()),
+            ColumnOrdering::desc_nulls_last("v".into()),
+        ] {
+            for (limit, offset) in [(1, None), (5, None), (50, Some(20)), (1000, None)] {
+                let scan = |order| {
+                    let mut scan = dataset.scan();
+                    scan.order_by(Some(vec![
+                        ordering.clone(),
+                        ColumnOrdering::asc_nulls_first("id".into()),
+                    ]))
+                    .unwrap()
+                    .limit(Some(limit), offset)
+                    .unwrap()
+                    .fragment_read_order(order);
+                    async move { scan.try_into_batch().await.unwrap() }
+                };
+                assert_eq!(
+                    scan(FragmentReadOrder::Statistics).await,
+                    scan(FragmentReadOrder::Manifest).await,
+                    "{ordering:?} limit {limit} offset {offset:?}"
+                );
+            }
+        }
+    }
+
     #[tokio::test]
     async fn test_scan_with_version_columns() {
         use arrow_array::{Int32Array, RecordBatch, RecordBatchIterator};
@@ -11467,6 +13490,124 @@ full_filter=name LIKE Utf8(\"test%2\"), refine_filter=name LIKE Utf8(\"test%2\")
         assert_eq!(filtered.options().io_buffer_size_bytes, Some(7777));
     }
 
+    #[tokio::test]
+    async fn test_session_scan_limits() {
+        use crate::dataset::builder::DatasetBuilder;
+        use crate::session::Session;
+
+        let test_dir = TempStrDir::default();
+        let data = lance_datagen::gen_batch()
+            .col("x", lance_datagen::array::step::<Int32Type>())
+            .into_reader_rows(RowCount::from(1000), BatchCount::from(10));
+        let write_params = WriteParams {
+            max_rows_per_file: 2000,
+            ..Default::default()
+        };
+        Dataset::write(data, &test_dir, Some(write_params))
+            .await
+            .unwrap();
+
+        let session = Session::default()
+            .with_max_scan_memory_bytes(1024)
+            .with_compute_parallelism(2);
+        let dataset = DatasetBuilder::from_uri(&test_dir)
+            .with_session(Arc::new(session))
+            .load()
+            .await
+            .unwrap();
+
+        // The limits are inherited from the session
+        let mut scanner = dataset.scan();
+        scanner.batch_size(100);
+        let plan = scanner.create_plan().await.unwrap();
+        let filtered = find_filtered_read(plan.as_ref()).unwrap();
+        assert_eq!(filtered.options().io_buffer_size_bytes, Some(1024));
+        assert!(matches!(
+            filtered.options().threading_mode,
+            FilteredReadThreadingMode::OnePartitionMultipleThreads(2)
+        ));
+
+        // A scan under the tiny budget is throttled, not failed
+        let batches = scanner
+            .try_into_stream()
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        let batch = concat_batches(&batches[0].schema(), &batches).unwrap();
+        let expected = Int32Array::from_iter_values(0..10_000);
+        assert_eq!(batch.column_by_name("x").unwrap().as_ref(), &expected);
+
+        // The limits can be overridden per scanner
+        let mut scanner = dataset.scan();
+        scanner
+            .batch_size(100)
+            .max_scan_memory_bytes(4096)
+            .compute_parallelism(1);
+        let plan = scanner.create_plan().await.unwrap();
+        let filtered = find_filtered_read(plan.as_ref()).unwrap();
+        assert_eq!(filtered.options().io_buffer_size_bytes, Some(4096));
+        assert!(matches!(
+            filtered.options().threading_mode,
+            FilteredReadThreadingMode::OnePartitionMultipleThreads(1)
+        ));
+
+        // A single batch which does not fit in the budget is an error
+        let err = dataset
+            .scan()
+            .batch_size(1000)
+            .create_plan()
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("max_scan_memory_bytes"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_session_decode_buffer_pool() {
+        use crate::dataset::builder::DatasetBuilder;
+        use crate::session::Session;
+
+        let test_dir = TempStrDir::default();
+        let mut data = lance_datagen::gen_batch();
+        for i in 0..10 {
+            data = data.col(format!("c{i}"), lance_datagen::array::step::<Int64Type>());
+        }
+        let data = data.into_reader_rows(RowCount::from(8192), BatchCount::from(4));
+        Dataset::write(data, &test_dir, None).await.unwrap();
+
+        let session = Arc::new(Session::default().with_decode_buffer_pool(16 * 1024 * 1024));
+        let dataset = DatasetBuilder::from_uri(&test_dir)
+            .with_session(session.clone())
+            .load()
+            .await
+            .unwrap();
+
+        // Batches are dropped as they are read so later batches reuse their buffers
+        for _ in 0..2 {
+            let mut stream = dataset
+                .scan()
+                .batch_size(1024)
+                .try_into_stream()
+                .await
+                .unwrap();
+            let mut offset = 0;
+            while let Some(batch) = stream.try_next().await.unwrap() {
+                let num_rows = batch.num_rows() as i64;
+                let expected = Int64Array::from_iter_values(offset..offset + num_rows);
+                for column in batch.columns() {
+                    assert_eq!(column.as_ref(), &expected);
+                }
+                offset += num_rows;
+            }
+            assert_eq!(offset, 8192 * 4);
+        }
+
+        let pool = session.decode_buffer_pool().unwrap();
+        assert!(pool.num_reused() > 0);
+        assert!(pool.pooled_bytes() <= pool.max_bytes());
+    }
+
     // The env var key scopes serial_test's lock so this test only blocks others
     // that touch LANCE_DEFAULT_IO_BUFFER_SIZE — unrelated tests still run in
     // parallel.
@@ -11871,4 +14012,371 @@ full_filter=name LIKE Utf8(\"test%2\"), refine_filter=name LIKE Utf8(\"test%2\")
         })
         .await;
     }
+
+    #[tokio::test]
+    async fn test_filter_in_large_probe_set() {
+        let dataset = gen_batch()
+            .col("id", array::step::<Int64Type>())
+            .into_ram_dataset(FragmentCount::from(6), FragmentRowCount::from(50_000))
+            .await
+            .unwrap();
+
+        // 1M distinct keys, about a quarter of them in the dataset, plus some nulls
+        let keys = (0..1_000_000i64)
+            .map(|i| (i % 1000 != 0).then_some((i * 13) % 1_200_000))
+            .collect::<Int64Array>();
+        let probe_set = keys.iter().flatten().collect::<HashSet<_>>();
+
+        let batch = dataset
+            .scan()
+            .project(&["id"])
+            .unwrap()
+            .filter_in("id", Arc::new(keys))
+            .unwrap()
+            .try_into_batch()
+            .await
+            .unwrap();
+        let mut ids = batch["id"].as_primitive::<Int64Type>().values().to_vec();
+        ids.sort_unstable();
+
+        // Semi-join reference
+        let expected = (0..300_000i64)
+            .filter(|id| probe_set.contains(id))
+            .collect::<Vec<_>>();
+        assert!(!expected.is_empty());
+        assert_eq!(ids, expected);
+    }
+
+    #[tokio::test]
+    async fn test_filter_in_uses_index() {
+        use lance_io::assert_io_lt;
+
+        let mut dataset = gen_batch()
+            .col("indexed", array::step::<Int32Type>())
+            .col("not_indexed", array::step::<Int32Type>())
+            .into_ram_dataset(FragmentCount::from(10), FragmentRowCount::from(1000))
+            .await
+            .unwrap();
+        dataset
+            .create_index(
+                &["indexed"],
+                IndexType::BTree,
+                None,
+                &ScalarIndexParams::default(),
+                false,
+            )
+            .await
+            .unwrap();
+        // Int64 keys are cast to the Int32 column, 20000 is not in the dataset
+        let keys = || -> ArrayRef { Arc::new(Int64Array::from(vec![50, 7001, 20000])) };
+
+        let _ = dataset.object_store.as_ref().io_stats_incremental(); // reset
+        let mut scan = dataset.scan();
+        scan.filter_in("not_indexed", keys()).unwrap();
+        assert!(
+            !scan
+                .explain_plan(false)
+                .await
+                .unwrap()
+                .contains("ScalarIndexQuery")
+        );
+        let batch = scan.try_into_batch().await.unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        let full_scan_bytes = dataset
+            .object_store
+            .as_ref()
+            .io_stats_incremental()
+            .read_bytes;
+
+        let mut scan = dataset.scan();
+        scan.filter_in("indexed", keys()).unwrap();
+        assert!(
+            scan.explain_plan(false)
+                .await
+                .unwrap()
+                .contains("ScalarIndexQuery")
+        );
+        let _ = dataset.object_store.as_ref().io_stats_incremental(); // reset
+        let batch = scan.try_into_batch().await.unwrap();
+        let io_stats = dataset.object_store.as_ref().io_stats_incremental();
+        assert_io_lt!(io_stats, read_bytes, full_scan_bytes);
+        let mut values = batch["indexed"]
+            .as_primitive::<Int32Type>()
+            .values()
+            .to_vec();
+        values.sort_unstable();
+        assert_eq!(values, vec![50, 7001]);
+    }
+
+    #[tokio::test]
+    async fn test_filter_in_duplicate_keys() {
+        let dataset = gen_batch()
+            .col("i", array::step::<Int32Type>())
+            .col("s", array::cycle_utf8_literals(&["a", "b", "c", "d"]))
+            .into_ram_dataset(FragmentCount::from(2), FragmentRowCount::from(100))
+            .await
+            .unwrap();
+
+        // Duplicate and null keys match every row at most once
+        let keys = Arc::new(Int32Array::from(vec![
+            Some(3),
+            Some(3),
+            None,
+            Some(150),
+            Some(3),
+            Some(150),
+        ]));
+        let batch = dataset
+            .scan()
+            .filter_in("i", keys)
+            .unwrap()
+            .try_into_batch()
+            .await
+            .unwrap();
+        let mut values = batch["i"].as_primitive::<Int32Type>().values().to_vec();
+        values.sort_unstable();
+        assert_eq!(values, vec![3, 150]);
+
+        // Key sets are combined with each other and with the filter
+        let keys = Arc::new(LargeStringArray::from(vec!["b", "b", "d"]));
+        let batch = dataset
+            .scan()
+            .filter("i < 10")
+            .unwrap()
+            .filter_in("s", keys)
+            .unwrap()
+            .filter_in("i", Arc::new(Int32Array::from(vec![1, 2, 3, 50])))
+            .unwrap()
+            .try_into_batch()
+            .await
+            .unwrap();
+        assert_eq!(
+            batch["i"].as_primitive::<Int32Type>().values().to_vec(),
+            vec![1, 3]
+        );
+
+        // Only null keys match nothing
+        let keys = Arc::new(Int32Array::from(vec![None, None]));
+        let batch = dataset
+            .scan()
+            .filter_in("i", keys)
+            .unwrap()
+            .try_into_batch()
+            .await
+            .unwrap();
+        assert_eq!(batch.num_rows(), 0);
+
+        assert!(
+            dataset
+                .scan()
+                .filter_in("missing", Arc::new(Int32Array::from(vec![1])))
+                .is_err()
+        );
+    }
+
+    // Rows of the grouped vector search tests are chunks of docs, `id % 11 == 0` has no doc
+    fn chunk_doc(id: i32) -> Option<i32> {
+        (id % 11 != 0).then_some(id % 13)
+    }
+
+    // The distance of each chunk to the zero query, unique per chunk
+    fn chunk_distance(id: i32) -> f32 {
+        (((id * 37) % 400) as f32).powi(2)
+    }
+
+    async fn grouped_vector_dataset() -> Dataset {
+        use lance_arrow::fixed_size_list_type;
+
+        let schema = Arc::new(ArrowSchema::new(vec![
+            ArrowField::new("id", DataType::Int32, false),
+            ArrowField::new("doc", DataType::Int32, true),
+            ArrowField::new("vec", fixed_size_list_type(2, DataType::Float32), true),
+        ]));
+        let vectors =
+            Float32Array::from_iter_values((0..400).flat_map(|id| [((id * 37) % 400) as f32, 0.0]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from_iter_values(0..400)),
+                Arc::new(Int32Array::from_iter((0..400).map(chunk_doc))),
+                Arc::new(FixedSizeListArray::try_new_from_values(vectors, 2).unwrap()),
+            ],
+        )
+        .unwrap();
+        let reader = RecordBatchIterator::new(vec![Ok(batch)], schema);
+        Dataset::write(reader, "memory://grouped", None)
+            .await
+            .unwrap()
+    }
+
+    /// The ids a grouped search should return: the `num_candidates` nearest chunks that
+    /// pass `prefilter`, of those the ones that pass `postfilter`, at most
+    /// `max_per_group` per doc and `k` in total
+    fn grouped_search_reference(
+        num_candidates: usize,
+        prefilter: impl Fn(i32) -> bool,
+        postfilter: impl Fn(i32) -> bool,
+        max_per_group: usize,
+        k: usize,
+        null_groups: NullGroups,
+    ) -> Vec<i32> {
+        let mut candidates = (0..400).filter(|id| prefilter(*id)).collect::<Vec<_>>();
+        candidates.sort_by(|a, b| chunk_distance(*a).total_cmp(&chunk_distance(*b)));
+        candidates.truncate(num_candidates);
+
+        let mut group_counts = std::collections::HashMap::<Option<i32>, usize>::new();
+        candidates
+            .into_iter()
+            .filter(|id| postfilter(*id))
+            .filter(|id| {
+                let doc = chunk_doc(*id);
+                if doc.is_none() && null_groups == NullGroups::Distinct {
+                    return true;
+                }
+                let count = group_counts.entry(doc).or_default();
+                *count += 1;
+                *count <= max_per_group
+            })
+            .take(k)
+            .collect()
+    }
+
+    fn result_ids(batch: &RecordBatch) -> Vec<i32> {
+        let distances = batch[DIST_COL].as_primitive::<Float32Type>().values();
+        assert!(distances.windows(2).all(|pair| pair[0] <= pair[1]));
+        batch["id"].as_primitive::<Int32Type>().values().to_vec()
+    }
+
+    #[tokio::test]
+    async fn test_vector_group_by_matches_reference() {
+        let dataset = grouped_vector_dataset().await;
+        let key = Float32Array::from(vec![0.0, 0.0]);
+
+        for (max_per_group, overfetch_factor, null_groups) in [
+            (1, None, NullGroups::Distinct),
+            (2, None, NullGroups::Single),
+            (3, Some(2), NullGroups::Distinct),
+            (1, Some(40), NullGroups::Single),
+        ] {
+            let mut scan = dataset.scan();
+            scan.project(&["id"]).unwrap();
+            scan.nearest("vec", &key, 10).unwrap();
+            scan.group_by("doc", max_per_group).unwrap();
+            if let Some(factor) = overfetch_factor {
+                scan.group_by_overfetch_factor(factor);
+            }
+            scan.group_by_nulls(null_groups);
+            let batch = scan.try_into_batch().await.unwrap();
+
+            // The group column is only read for grouping
+            assert_eq!(
+                batch
+                    .schema()
+                    .fields()
+                    .iter()
+                    .map(|field| field.name().as_str())
+                    .collect::<Vec<_>>(),
+                vec!["id", DIST_COL]
+            );
+            let num_candidates =
+                10 * overfetch_factor.unwrap_or(DEFAULT_GROUP_BY_OVERFETCH_FACTOR) as usize;
+            let expected = grouped_search_reference(
+                num_candidates,
+                |_| true,
+                |_| true,
+                max_per_group,
+                10,
+                null_groups,
+            );
+            assert_eq!(expected.len(), 10);
+            assert_eq!(
+                result_ids(&batch),
+                expected,
+                "max_per_group={max_per_group} overfetch_factor={overfetch_factor:?} null_groups={null_groups:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_vector_group_by_with_refine_and_prefilter() {
+        let mut dataset = grouped_vector_dataset().await;
+        dataset
+            .create_index(
+                &["vec"],
+                IndexType::Vector,
+                None,
+                &VectorIndexParams::ivf_pq(2, 8, 2, MetricType::L2, 2),
+                false,
+            )
+            .await
+            .unwrap();
+        let key = Float32Array::from(vec![0.0, 0.0]);
+
+        for prefilter in [true, false] {
+            let mut scan = dataset.scan();
+            scan.prefilter(prefilter);
+            scan.filter("id >= 100").unwrap();
+            scan.nearest("vec", &key, 10).unwrap();
+            // Search every partition and refine every candidate so the results are exact
+            scan.minimum_nprobes(2);
+            scan.refine(10);
+            scan.group_by("doc", 1).unwrap();
+            scan.group_by_overfetch_factor(40);
+            let plan = scan.explain_plan(false).await.unwrap();
+            assert!(plan.contains("GroupLimit: column=doc, max_per_group=1, limit=10"));
+            let batch = scan.try_into_batch().await.unwrap();
+
+            let expected = grouped_search_reference(
+                400,
+                |id| id >= 100,
+                |_| true,
+                1,
+                10,
+                NullGroups::Distinct,
+            );
+            assert_eq!(result_ids(&batch), expected, "prefilter={prefilter}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_vector_group_by_pruned_groups() {
+        let dataset = grouped_vector_dataset().await;
+        let key = Float32Array::from(vec![0.0, 0.0]);
+
+        // The filter prunes every candidate of all but two docs, so fewer than k rows
+        // are left
+        let mut scan = dataset.scan();
+        scan.filter("doc < 2").unwrap();
+        scan.nearest("vec", &key, 10).unwrap();
+        scan.group_by("doc", 2).unwrap();
+        let batch = scan.try_into_batch().await.unwrap();
+        let expected = grouped_search_reference(
+            40,
+            |_| true,
+            |id| chunk_doc(id).is_some_and(|doc| doc < 2),
+            2,
+            10,
+            NullGroups::Distinct,
+        );
+        assert!(expected.len() < 10);
+        assert_eq!(result_ids(&batch), expected);
+
+        // Every candidate is pruned
+        let mut scan = dataset.scan();
+        scan.filter("id < 0").unwrap();
+        scan.nearest("vec", &key, 10).unwrap();
+        scan.group_by("doc", 2).unwrap();
+        assert_eq!(scan.try_into_batch().await.unwrap().num_rows(), 0);
+
+        // Invalid groupings
+        let mut scan = dataset.scan();
+        assert!(scan.group_by("doc", 0).is_err());
+        assert!(scan.group_by("missing", 1).is_err());
+        scan.group_by("doc", 1).unwrap();
+        let err = scan.try_into_batch().await.unwrap_err();
+        assert!(
+            err.to_string().contains("only supported for vector search"),
+            "{err}"
+        );
+    }
 }
@@ -0,0 +1,606 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Queries that are planned once and executed many times.
+//!
+//! Serving the same search with only the query vector and a few filter values
+//! changing spends a good part of every query on planning: parsing the filter
+//! and resolving the indices of the dataset. [`Scanner::prepare`] does that
+//! once. The filter may contain placeholders, `tenant_id = $1`, that take the
+//! type of the column they are compared with, and each
+//! [`PreparedQuery::execute`] only binds the query vector and the placeholder
+//! values before building the physical plan.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use arrow_array::ArrayRef;
+use arrow_schema::DataType;
+use datafusion::common::tree_node::{Transformed, TreeNode, TreeNodeRecursion};
+use datafusion::logical_expr::Expr;
+use datafusion::physical_plan::ExecutionPlan;
+use datafusion::scalar::ScalarValue;
+use lance_datafusion::expr::safe_coerce_scalar;
+use lance_index::vector::Query;
+
+use super::{DatasetRecordBatchStream, ExprFilter, ResolvedIndices, Scanner};
+use crate::{Error, Result};
+
+/// What a [`PreparedQuery`] does once the dataset has a newer version than
+/// the one it was prepared against
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StaleVersionPolicy {
+    /// Prepare the query again against the latest version
+    #[default]
+    Replan,
+    /// Fail with [`Error::PrerequisiteFailed`]
+    Error,
+    /// Keep querying the version the query was prepared against, without
+    /// looking for newer versions
+    Pin,
+}
+
+/// Options of [`Scanner::prepare_with_options`]
+#[derive(Debug, Clone, Default)]
+pub struct PreparedQueryOptions {
+    pub stale_version_policy: StaleVersionPolicy,
+    /// How long an execution may go without looking for a newer version.
+    ///
+    /// Looking is a request to the object store, so queries that run
+    /// thousands of times per second may want to look less often than every
+    /// time, which is the default.
+    pub version_check_interval: Duration,
+}
+
+/// The parameters of one [`PreparedQuery::execute`]
+#[derive(Debug, Clone, Default)]
+pub struct QueryParams {
+    /// The query vector of the nearest neighbor search, the one the query was
+    /// prepared with if `None`.
+    ///
+    /// It must have the shape of the prepared one, e.g. a batch of as many
+    /// vectors.
+    pub query: Option<ArrayRef>,
+    /// The values of the placeholders in the filter, `$1` first.
+    ///
+    /// Values of another type than the placeholder are cast if that is
+    /// lossless, e.g. an `Int64` to an `Int32` column.
+    pub values: Vec<ScalarValue>,
+}
+
+/// A query planned once by [`Scanner::prepare`] to execute it many times,
+/// see the [module docs](self)
+pub struct PreparedQuery {
+    /// The scanner the query was prepared from, prepared again on a new version
+    source: Scanner,
+    options: PreparedQueryOptions,
+    plan: RwLock<Arc<PreparedPlan>>,
+    last_version_check: Mutex<Instant>,
+}
+
+/// The part of a query that doesn't depend on its parameters
+struct PreparedPlan {
+    /// The scanner with the indices resolved and the filter parsed, still
+    /// with its placeholders
+    scanner: Scanner,
+    /// The type of each placeholder, `$1` first
+    parameter_types: Vec<DataType>,
+}
+
+impl PreparedPlan {
+    async fn try_new(source: &Scanner) -> Result<Self> {
+        let mut scanner = source.clone();
+        scanner.validate_options()?;
+
+        let filter = match &scanner.filter.expr_filter {
+            Some(filter) => {
+                let filter_schema = scanner.filterable_schema()?;
+                Some(filter.to_datafusion(scanner.dataset.schema(), filter_schema.as_ref())?)
+            }
+            None => None,
+        };
+        let parameter_types = match &filter {
+            Some(filter) => parameter_types(filter)?,
+            None => Vec::new(),
+        };
+        if let Some(filter) = filter {
+            scanner.filter.expr_filter = Some(ExprFilter::Datafusion(filter));
+        }
+        scanner.resolved_indices = Some(Arc::new(ResolvedIndices::load(&scanner.dataset).await?));
+
+        Ok(Self {
+            scanner,
+            parameter_types,
+        })
+    }
+
+    /// The scanner of this plan with `params` bound
+    fn bind(&self, params: QueryParams) -> Result<Scanner> {
+        let mut scanner = self.scanner.clone();
+
+        if let Some(query) = params.query {
+            let Some(prepared) = self.scanner.nearest.as_ref() else {
+                return Err(Error::invalid_input(
+                    "A query vector was given for a prepared query without nearest neighbor search",
+                ));
+            };
+            scanner.nearest(&prepared.column, query.as_ref(), prepared.k)?;
+            if scanner.nearest_query_count != self.scanner.nearest_query_count
+                || scanner.is_batch_nearest != self.scanner.is_batch_nearest
+            {
+                return Err(Error::invalid_input(format!(
+                    "The query vector has another shape than the {} prepared queries",
+                    self.scanner.nearest_query_count
+                )));
+            }
+            // Keep the search options of the prepared query, e.g. nprobes
+            if let Some(bound) = scanner.nearest.take() {
+                scanner.nearest = Some(Query {
+                    key: bound.key,
+                    ..prepared.clone()
+                });
+            }
+        }
+
+        if params.values.len() != self.parameter_types.len() {
+            return Err(Error::invalid_input(format!(
+                "The prepared query has {} placeholders but {} values were given",
+                self.parameter_types.len(),
+                params.values.len()
+            )));
+        }
+        if let Some(ExprFilter::Datafusion(filter)) = &self.scanner.filter.expr_filter
+            && !self.parameter_types.is_empty()
+        {
+            let values = params
+                .values
+                .into_iter()
+                .zip(&self.parameter_types)
+                .enumerate()
+                .map(|(i, (value, data_type))| {
+                    let id = format!("${}", i + 1);
+                    let value = bind_value(&id, value, data_type)?;
+                    Ok((id, value))
+                })
+                .collect::<Result<HashMap<_, _>>>()?;
+            let filter = filter
+                .clone()
+                .transform(|expr| {
+                    if let Expr::Placeholder(placeholder) = &expr
+                        && let Some(value) = values.get(&placeholder.id)
+                    {
+                        return Ok(Transformed::yes(Expr::Literal(value.clone(), None)));
+                    }
+                    Ok(Transformed::no(expr))
+                })?
+                .data;
+            scanner.filter.expr_filter = Some(ExprFilter::Datafusion(filter));
+        }
+
+        Ok(scanner)
+    }
+}
+
+impl PreparedQuery {
+    pub(super) async fn try_new(source: Scanner, options: PreparedQueryOptions) -> Result<Self> {
+        let plan = PreparedPlan::try_new(&source).await?;
+        Ok(Self {
+            source,
+            options,
+            plan: RwLock::new(Arc::new(plan)),
+            last_version_check: Mutex::new(Instant::now()),
+        })
+    }
+
+    /// The version of the dataset the query is currently planned against
+    pub fn dataset_version(&self) -> u64 {
+        self.plan.read().unwrap().scanner.dataset.version().version
+    }
+
+    /// The type of each placeholder in the filter, `$1` first
+    pub fn parameter_types(&self) -> Vec<DataType> {
+        self.plan.read().unwrap().parameter_types.clone()
+    }
+
+    /// Execute the query with `params`
+    pub async fn execute(&self, params: QueryParams) -> Result<DatasetRecordBatchStream> {
+        let scanner = self.bind(params).await?;
+        scanner.try_into_stream().await
+    }
+
+    /// The physical plan of the query with `params`
+    pub async fn create_plan(&self, params: QueryParams) -> Result<Arc<dyn ExecutionPlan>> {
+        let scanner = self.bind(params).await?;
+        scanner.create_plan().await
+    }
+
+    async fn bind(&self, params: QueryParams) -> Result<Scanner> {
+        self.current_plan().await?.bind(params)
+    }
+
+    /// The plan for the latest version, subject to the [`StaleVersionPolicy`]
+    async fn current_plan(&self) -> Result<Arc<PreparedPlan>> {
+        let plan = self.plan.read().unwrap().clone();
+        if self.options.stale_version_policy == StaleVersionPolicy::Pin {
+            return Ok(plan);
+        }
+        {
+            let mut last_version_check = self.last_version_check.lock().unwrap();
+            if !self.options.version_check_interval.is_zero()
+                && last_version_check.elapsed() < self.options.version_check_interval
+            {
+                return Ok(plan);
+            }
+            *last_version_check = Instant::now();
+        }
+
+        let version = plan.scanner.dataset.version().version;
+        let latest_version = plan.scanner.dataset.latest_version_id().await?;
+        if latest_version <= version {
+            return Ok(plan);
+        }
+        match self.options.stale_version_policy {
+            StaleVersionPolicy::Error => Err(Error::prerequisite_failed(format!(
+                "The query was prepared against version {version} of the dataset but the latest version is {latest_version}"
+            ))),
+            _ => {
+                let mut dataset = plan.scanner.dataset.as_ref().clone();
+                dataset.checkout_latest().await?;
+                let mut source = self.source.clone();
+                source.dataset = Arc::new(dataset);
+                let replanned = Arc::new(PreparedPlan::try_new(&source).await?);
+                let mut plan = self.plan.write().unwrap();
+                // Another execution may have replanned in the meantime
+                if plan.scanner.dataset.version().version
+                    < replanned.scanner.dataset.version().version
+                {
+                    *plan = replanned;
+                }
+                Ok(plan.clone())
+            }
+        }
+    }
+}
+
+/// The type of each placeholder in `filter`, `$1` first
+fn parameter_types(filter: &Expr) -> Result<Vec<DataType>> {
+    let mut placeholders = Vec::new();
+    filter.apply(|expr| {
+        if let Expr::Placeholder(placeholder) = expr {
+            placeholders.push(placeholder.clone());
+        }
+        Ok(TreeNodeRecursion::Continue)
+    })?;
+
+    let mut parameter_types = vec![None; placeholders.len()];
+    for placeholder in &placeholders {
+        let position = placeholder
+            .id
+            .strip_prefix('$')
+            .and_then(|position| position.parse::<usize>().ok())
+            .filter(|position| *position > 0)
+            .ok_or_else(|| {
+                Error::invalid_input(format!(
+                    "Placeholder {} is not one of $1, $2, ... of a prepared query",
+                    placeholder.id
+                ))
+            })?;
+        let data_type = placeholder
+            .field
+            .as_ref()
+            .map(|field| field.data_type().clone())
+            .ok_or_else(|| {
+                Error::invalid_input(format!(
+                    "Could not infer the type of placeholder {}, compare it with a column",
+                    placeholder.id
+                ))
+            })?;
+        if position > parameter_types.len() {
+            parameter_types.resize(position, None);
+        }
+        match &parameter_types[position - 1] {
+            Some(existing) if existing != &data_type => {
+                return Err(Error::invalid_input(format!(
+                    "Placeholder {} is used as both {} and {}",
+                    placeholder.id, existing, data_type
+                )));
+            }
+            _ => parameter_types[position - 1] = Some(data_type),
+        }
+    }
+    parameter_types
+        .into_iter()
+        .enumerate()
+        .map(|(i, data_type)| {
+            data_type.ok_or_else(|| {
+                Error::invalid_input(format!(
+                    "Placeholder ${} is not used by the prepared query",
+                    i + 1
+                ))
+            })
+        })
+        .collect()
+}
+
+/// `value` as the `data_type` of placeholder `id`
+fn bind_value(id: &str, value: ScalarValue, data_type: &DataType) -> Result<ScalarValue> {
+    if value.data_type() == *data_type {
+        return Ok(value);
+    }
+    if value.is_null() {
+        return Ok(ScalarValue::try_from(data_type)?);
+    }
+    safe_coerce_scalar(&value, data_type).ok_or_else(|| {
+        Error::invalid_input(format!(
+            "Placeholder {id} is a {data_type} and can't be bound to the {} {value}",
+            value.data_type()
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow::compute::concat_batches;
+    use arrow_array::{Float32Array, RecordBatch};
+    use futures::TryStreamExt;
+    use lance_file::version::LanceFileVersion;
+
+    use super::*;
+    use crate::dataset::scanner::test_dataset::TestVectorDataset;
+
+    async fn test_dataset() -> TestVectorDataset {
+        let mut test_ds = TestVectorDataset::new(LanceFileVersion::Stable, false)
+            .await
+            .unwrap();
+        test_ds.make_vector_index().await.unwrap();
+        test_ds.make_scalar_index().await.unwrap();
+        test_ds
+    }
+
+    fn query_vector(offset: f32) -> ArrayRef {
+        Arc::new(Float32Array::from_iter_values(
+            (0..32).map(|v| v as f32 + offset),
+        ))
+    }
+
+    async fn count_from_395(prepared: &PreparedQuery) -> Result<usize> {
+        let stream = prepared
+            .execute(QueryParams {
+                values: vec![ScalarValue::Int32(Some(395))],
+                ..Default::default()
+            })
+            .await?;
+        let batches = stream.try_collect::<Vec<_>>().await?;
+        Ok(batches.iter().map(RecordBatch::num_rows).sum())
+    }
+
+    async fn collect(stream: DatasetRecordBatchStream) -> RecordBatch {
+        let batches = stream.try_collect::<Vec<_>>().await.unwrap();
+        let schema = batches[0].schema();
+        concat_batches(&schema, &batches).unwrap()
+    }
+
+    fn adhoc(test_ds: &TestVectorDataset, offset: f32, filter: &str) -> Scanner {
+        let mut scanner = test_ds.dataset.scan();
+        scanner
+            .nearest("vec", query_vector(offset).as_ref(), 10)
+            .unwrap()
+            .nprobes(2)
+            .prefilter(true)
+            .filter(filter)
+            .unwrap();
+        scanner
+    }
+
+    fn params(offset: f32, values: Vec<ScalarValue>) -> QueryParams {
+        QueryParams {
+            query: Some(query_vector(offset)),
+            values,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_prepared_matches_adhoc() {
+        let test_ds = test_dataset().await;
+        let prepared = adhoc(&test_ds, 0.0, "i >= $1 AND s != $2")
+            .prepare()
+            .await
+            .unwrap();
+        assert_eq!(
+            prepared.parameter_types(),
+            vec![DataType::Int32, DataType::Utf8]
+        );
+
+        for (offset, low, excluded) in [(0.0, 0, "s-1"), (1000.0, 100, "s-150"), (5000.0, 300, "")]
+        {
+            let expected = adhoc(
+                &test_ds,
+                offset,
+                &format!("i >= {low} AND s != '{excluded}'"),
+            )
+            .try_into_batch()
+            .await
+            .unwrap();
+            let actual = collect(
+                prepared
+                    .execute(params(
+                        offset,
+                        vec![
+                            ScalarValue::Int32(Some(low)),
+                            ScalarValue::Utf8(Some(excluded.to_string())),
+                        ],
+                    ))
+                    .await
+                    .unwrap(),
+            )
+            .await;
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_placeholder_validation() {
+        let test_ds = test_dataset().await;
+        let prepared = adhoc(&test_ds, 0.0, "i BETWEEN $1 AND $2")
+            .prepare()
+            .await
+            .unwrap();
+
+        // Lossless casts are fine
+        let batch = collect(
+            prepared
+                .execute(params(
+                    0.0,
+                    vec![ScalarValue::Int64(Some(10)), ScalarValue::Int8(Some(20))],
+                ))
+                .await
+                .unwrap(),
+        )
+        .await;
+        assert!(batch.num_rows() > 0);
+
+        let err = prepared
+            .execute(params(0.0, vec![ScalarValue::Int32(Some(10))]))
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("2 placeholders but 1 values"),
+            "{err}"
+        );
+
+        let err = prepared
+            .execute(params(
+                0.0,
+                vec![
+                    ScalarValue::Int32(Some(10)),
+                    ScalarValue::Utf8(Some("twenty".to_string())),
+                ],
+            ))
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("Placeholder $2 is a Int32"),
+            "{err}"
+        );
+
+        let err = prepared
+            .execute(params(
+                0.0,
+                vec![
+                    ScalarValue::Int64(Some(10)),
+                    ScalarValue::Int64(Some(i64::MAX)),
+                ],
+            ))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Placeholder $2"), "{err}");
+
+        // Query vectors must keep their shape
+        let err = prepared
+            .execute(QueryParams {
+                query: Some(Arc::new(Float32Array::from_iter_values(
+                    (0..16).map(|v| v as f32),
+                ))),
+                values: vec![ScalarValue::Int32(Some(1)), ScalarValue::Int32(Some(2))],
+            })
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("query dim(16)"), "{err}");
+
+        // Placeholders must be positional and typed by a column
+        let mut scanner = test_ds.dataset.scan();
+        scanner.filter("i > $tenant").unwrap();
+        assert!(scanner.prepare().await.is_err());
+        let mut scanner = test_ds.dataset.scan();
+        scanner.filter("i > $2").unwrap();
+        let err = scanner.prepare().await.unwrap_err();
+        assert!(err.to_string().contains("$1 is not used"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_prepared_planning_is_faster() {
+        let test_ds = test_dataset().await;
+        let filter = "i >= 10 AND i < 390 AND s != 's-100' AND s NOT LIKE 's-2%'";
+        let prepared = adhoc(
+            &test_ds,
+            0.0,
+            "i >= $1 AND i < $2 AND s != $3 AND s NOT LIKE $4",
+        )
+        .prepare()
+        .await
+        .unwrap();
+        let values = vec![
+            ScalarValue::Int32(Some(10)),
+            ScalarValue::Int32(Some(390)),
+            ScalarValue::Utf8(Some("s-100".to_string())),
+            ScalarValue::Utf8(Some("s-2%".to_string())),
+        ];
+
+        const ITERATIONS: usize = 50;
+        // Warm up the index cache for both
+        adhoc(&test_ds, 0.0, filter).create_plan().await.unwrap();
+        prepared
+            .create_plan(params(0.0, values.clone()))
+            .await
+            .unwrap();
+
+        let start = Instant::now();
+        for i in 0..ITERATIONS {
+            adhoc(&test_ds, i as f32, filter)
+                .create_plan()
+                .await
+                .unwrap();
+        }
+        let adhoc_time = start.elapsed();
+        let start = Instant::now();
+        for i in 0..ITERATIONS {
+            prepared
+                .create_plan(params(i as f32, values.clone()))
+                .await
+                .unwrap();
+        }
+        let prepared_time = start.elapsed();
+        assert!(
+            prepared_time < adhoc_time,
+            "prepared {prepared_time:?} vs ad-hoc {adhoc_time:?}"
+        );
+    }
+
+    #[rstest::rstest]
+    #[case::replan(StaleVersionPolicy::Replan)]
+    #[case::error(StaleVersionPolicy::Error)]
+    #[case::pin(StaleVersionPolicy::Pin)]
+    #[tokio::test]
+    async fn test_version_change(#[case] policy: StaleVersionPolicy) {
+        let mut test_ds = test_dataset().await;
+        let mut scanner = test_ds.dataset.scan();
+        scanner.filter("i >= $1").unwrap();
+        let prepared = scanner
+            .prepare_with_options(PreparedQueryOptions {
+                stale_version_policy: policy,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let version = prepared.dataset_version();
+        assert_eq!(count_from_395(&prepared).await.unwrap(), 5);
+
+        // Adds rows 400..410
+        test_ds.append_new_data().await.unwrap();
+        match policy {
+            StaleVersionPolicy::Replan => {
+                assert_eq!(count_from_395(&prepared).await.unwrap(), 15);
+                assert!(prepared.dataset_version() > version);
+            }
+            StaleVersionPolicy::Error => {
+                let err = count_from_395(&prepared).await.unwrap_err();
+                assert!(matches!(err, Error::PrerequisiteFailed { .. }), "{err}");
+            }
+            StaleVersionPolicy::Pin => {
+                assert_eq!(count_from_395(&prepared).await.unwrap(), 5);
+                assert_eq!(prepared.dataset_version(), version);
+            }
+        }
+    }
+}
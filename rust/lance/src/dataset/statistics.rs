@@ -8,6 +8,8 @@ use std::{collections::HashMap, future::Future, sync::Arc};
 use futures::{StreamExt, TryStreamExt};
 use lance_core::Result;
 use lance_io::scheduler::{ScanScheduler, SchedulerConfig};
+use lance_table::format::DeletionFileType;
+use lance_table::io::deletion::deletion_file_path;
 
 use super::{Dataset, fragment::FileFragment};
 
@@ -27,6 +29,38 @@ pub struct DataStatistics {
     pub fields: Vec<FieldStatistics>,
 }
 
+/// Deletions recorded for a single fragment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FragmentDeletionStatistics {
+    /// Id of the fragment
+    pub fragment_id: u64,
+    /// Number of rows deleted from the fragment
+    pub num_deleted_rows: usize,
+    /// Encoding of the fragment's deletion file
+    pub file_type: DeletionFileType,
+    /// Size of the deletion file in bytes
+    pub file_size: u64,
+}
+
+/// Statistics about the deletion files of the dataset
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeletionStatistics {
+    /// One entry per fragment with a deletion file, ordered by fragment id
+    pub fragments: Vec<FragmentDeletionStatistics>,
+}
+
+impl DeletionStatistics {
+    /// Total number of deleted rows across all fragments
+    pub fn num_deleted_rows(&self) -> usize {
+        self.fragments.iter().map(|f| f.num_deleted_rows).sum()
+    }
+
+    /// Total size of all deletion files in bytes
+    pub fn total_file_size(&self) -> u64 {
+        self.fragments.iter().map(|f| f.file_size).sum()
+    }
+}
+
 pub trait DatasetStatisticsExt {
     /// Get statistics about the data in the dataset
     fn calculate_data_stats(
@@ -82,3 +116,44 @@ impl DatasetStatisticsExt for Dataset {
         })
     }
 }
+
+impl Dataset {
+    /// Get the deleted row count and deletion file size of each fragment.
+    ///
+    /// Fragments without deletions are omitted.
+    pub async fn deletion_stats(&self) -> Result<DeletionStatistics> {
+        let mut fragments = futures::stream::iter(self.get_fragments())
+            .filter_map(|fragment| async move {
+                fragment
+                    .metadata
+                    .deletion_file
+                    .clone()
+                    .map(|f| (fragment, f))
+            })
+            .map(|(fragment, deletion_file)| async move {
+                let dataset_dir = self.dataset_dir_for_deletion(&deletion_file)?;
+                let object_store = self.object_store_for_deletion(&deletion_file).await?;
+                let path = deletion_file_path(&dataset_dir, fragment.id() as u64, &deletion_file);
+                let file_size = object_store.size(&path).await?;
+                let num_deleted_rows = match deletion_file.num_deleted_rows {
+                    Some(num_deleted_rows) => num_deleted_rows,
+                    None => fragment
+                        .get_deletion_vector()
+                        .await?
+                        .map(|dv| dv.len())
+                        .unwrap_or_default(),
+                };
+                Result::Ok(FragmentDeletionStatistics {
+                    fragment_id: fragment.id() as u64,
+                    num_deleted_rows,
+                    file_type: deletion_file.file_type,
+                    file_size,
+                })
+            })
+            .buffer_unordered(self.object_store.io_parallelism())
+            .try_collect::<Vec<_>>()
+            .await?;
+        fragments.sort_by_key(|f| f.fragment_id);
+        Ok(DeletionStatistics { fragments })
+    }
+}
@@ -1,11 +1,14 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-FileCopyrightText: Copyright The Lance Authors
 
+use std::task::{Context, Poll};
+use std::time::Duration;
 use std::{collections::BTreeMap, collections::HashMap, ops::Range, pin::Pin, sync::Arc};
 
 use crate::dataset::fragment::FragReadConfig;
 use crate::dataset::rowids::get_row_id_index;
 use crate::io::exec::AddRowOffsetExec;
+use crate::utils::cancellation::OperationLimits;
 use crate::{Error, Result};
 use arrow::{compute::concat_batches, datatypes::UInt64Type};
 use arrow_array::cast::AsArray;
@@ -16,7 +19,7 @@ use datafusion::common::Column;
 use datafusion::error::DataFusionError;
 use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
 use datafusion_expr::Expr;
-use futures::{Future, Stream, StreamExt, TryStreamExt};
+use futures::{Future, FutureExt, Stream, StreamExt, TryStreamExt};
 use lance_arrow::RecordBatchExt;
 use lance_arrow::json::convert_lance_json_to_arrow;
 use lance_core::datatypes::Schema;
@@ -24,6 +27,7 @@ use lance_core::utils::address::RowAddress;
 use lance_core::utils::deletion::OffsetMapper;
 use lance_core::{ROW_ADDR, ROW_OFFSET};
 use lance_datafusion::projection::{OutputColumn, ProjectionPlan};
+use tokio_util::sync::CancellationToken;
 
 use super::ProjectionRequest;
 use super::{Dataset, fragment::FileFragment, scanner::DatasetRecordBatchStream};
@@ -126,6 +130,16 @@ pub async fn take(
     take_rows(builder).await
 }
 
+/// The schema of the batch returned by [`take`] for `projection`
+pub fn take_schema(
+    dataset: &Dataset,
+    projection: ProjectionRequest,
+) -> Result<arrow_schema::SchemaRef> {
+    let projection = projection.into_projection_plan(Arc::new(dataset.clone()))?;
+    let empty = RecordBatch::new_empty(Arc::new(projection.output_schema()?));
+    Ok(to_logical_json_batch(empty)?.schema())
+}
+
 /// Take rows by the internal ROW ids.
 #[allow(clippy::needless_question_mark)]
 async fn do_take_rows(
@@ -443,6 +457,104 @@ pub fn take_scan(
     )))
 }
 
+/// A request of [`Dataset::take_stream`]
+#[derive(Debug, Clone)]
+pub struct TakeRequest {
+    /// An id chosen by the caller, the response to the request carries it
+    pub id: u64,
+    /// The rows to take, see [`Dataset::take_rows`]
+    pub row_ids: Vec<u64>,
+    /// The columns to take
+    pub columns: Vec<String>,
+}
+
+/// The response to a [`TakeRequest`]
+#[derive(Debug)]
+pub struct TakeResponse {
+    /// The id of the request
+    pub id: u64,
+    /// The rows, in the order they were requested, or the error the request failed with
+    pub result: Result<RecordBatch>,
+}
+
+/// The most requests that are coalesced into a single take
+const MAX_COALESCED_REQUESTS: usize = 64;
+
+pub(super) fn take_stream(
+    dataset: Arc<Dataset>,
+    requests: impl Stream<Item = TakeRequest> + Send + 'static,
+) -> impl Stream<Item = TakeResponse> + Send + 'static {
+    let parallelism = dataset.object_store.io_parallelism();
+    requests
+        // Whatever requests are waiting when a take can start are coalesced
+        .ready_chunks(MAX_COALESCED_REQUESTS)
+        .flat_map(|requests| {
+            let mut by_columns: HashMap<Vec<String>, Vec<TakeRequest>> = HashMap::new();
+            for request in requests {
+                by_columns
+                    .entry(request.columns.clone())
+                    .or_default()
+                    .push(request);
+            }
+            futures::stream::iter(by_columns.into_values())
+        })
+        .map(move |requests| take_coalesced(dataset.clone(), requests))
+        .buffer_unordered(parallelism)
+        .flat_map(futures::stream::iter)
+}
+
+/// Serve requests for the same columns with a single take
+///
+/// The take reads the rows the requests share, and so the pages they share, once.  If
+/// it fails, or rows were deleted so the batch can't be split between the requests,
+/// each request is taken on its own so that only the failing ones see an error.
+async fn take_coalesced(dataset: Arc<Dataset>, requests: Vec<TakeRequest>) -> Vec<TakeResponse> {
+    if requests.len() > 1 {
+        let row_ids = requests
+            .iter()
+            .flat_map(|request| request.row_ids.iter().copied())
+            .collect::<Vec<_>>();
+        let num_rows = row_ids.len();
+        if let Ok(batch) = take_columns(dataset.clone(), row_ids, &requests[0].columns).await
+            && batch.num_rows() == num_rows
+        {
+            let mut offset = 0;
+            return requests
+                .into_iter()
+                .map(|request| {
+                    let rows = batch.slice(offset, request.row_ids.len());
+                    offset += request.row_ids.len();
+                    TakeResponse {
+                        id: request.id,
+                        result: Ok(rows),
+                    }
+                })
+                .collect();
+        }
+    }
+    futures::future::join_all(requests.into_iter().map(|request| {
+        let dataset = dataset.clone();
+        async move {
+            TakeResponse {
+                id: request.id,
+                result: take_columns(dataset, request.row_ids, &request.columns).await,
+            }
+        }
+    }))
+    .await
+}
+
+async fn take_columns(
+    dataset: Arc<Dataset>,
+    row_ids: Vec<u64>,
+    columns: &[String],
+) -> Result<RecordBatch> {
+    let projection = dataset.schema().project_preserve_system_columns(columns)?;
+    TakeBuilder::try_new_from_ids(dataset, row_ids, projection.into())?
+        .execute()
+        .await
+}
+
 struct RowAddressStats {
     sorted: bool,
     contiguous: bool,
@@ -478,6 +590,8 @@ pub struct TakeBuilder {
     row_addrs: Option<Vec<u64>>,
     projection: Arc<ProjectionPlan>,
     with_row_address: bool,
+    timeout: Option<Duration>,
+    cancellation_token: Option<CancellationToken>,
 }
 
 impl TakeBuilder {
@@ -493,6 +607,8 @@ impl TakeBuilder {
             projection: Arc::new(projection.into_projection_plan(dataset.clone())?),
             dataset,
             with_row_address: false,
+            timeout: None,
+            cancellation_token: None,
         })
     }
 
@@ -508,6 +624,8 @@ impl TakeBuilder {
             projection,
             dataset,
             with_row_address: false,
+            timeout: None,
+            cancellation_token: None,
         })
     }
 
@@ -517,9 +635,47 @@ impl TakeBuilder {
         self
     }
 
+    /// Fail the take with [`lance_core::Error::Timeout`] if it has not finished after
+    /// `timeout`, aborting its in-flight reads
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Fail the take with [`lance_core::Error::Cancelled`] when `token` is cancelled,
+    /// aborting its in-flight reads
+    pub fn with_cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
     /// Execute the take operation and return a single batch
     pub async fn execute(self) -> Result<RecordBatch> {
-        take_rows(self).await
+        match OperationLimits::new(self.timeout, self.cancellation_token.as_ref()) {
+            Some(limits) => limits.run(take_rows(self)).await,
+            None => take_rows(self).await,
+        }
+    }
+
+    /// Read the rows in the background so a later take of them is served from the caches
+    ///
+    /// The take runs on a tokio task, its reads are scheduled like those of [`Self::execute`]
+    /// so no more than the store's IO parallelism are in flight.  What the take reads is
+    /// kept by the caches it passes through: file metadata in the session's metadata cache,
+    /// and the data itself only when the store has a disk cache (the `storage_cache_dir`
+    /// storage option), which evicts within its `storage_cache_size_bytes` budget as usual.
+    ///
+    /// Must be called within a tokio runtime.
+    pub fn prefetch(self) -> PrefetchHandle {
+        let cancellation = self
+            .cancellation_token
+            .as_ref()
+            .map_or_else(CancellationToken::new, CancellationToken::child_token);
+        let take = self.with_cancellation_token(cancellation.clone());
+        PrefetchHandle {
+            task: tokio::spawn(async move { take.execute().await.map(|_| ()) }),
+            cancellation,
+        }
     }
 
     pub fn is_empty(&self) -> bool {
@@ -551,6 +707,33 @@ impl TakeBuilder {
     }
 }
 
+/// A prefetch started by [`TakeBuilder::prefetch`] or [`Dataset::prefetch`]
+///
+/// Awaiting the handle waits for the prefetch to finish.  Dropping it cancels the prefetch
+/// and aborts its in-flight reads, blocks that were already read stay cached.
+#[derive(Debug)]
+pub struct PrefetchHandle {
+    task: tokio::task::JoinHandle<Result<()>>,
+    cancellation: CancellationToken,
+}
+
+impl Future for PrefetchHandle {
+    type Output = Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.task.poll_unpin(cx).map(|res| {
+            res.unwrap_or_else(|err| Err(Error::internal(format!("Prefetch task failed: {err}"))))
+        })
+    }
+}
+
+impl Drop for PrefetchHandle {
+    fn drop(&mut self) {
+        self.cancellation.cancel();
+        self.task.abort();
+    }
+}
+
 fn take_struct_array(array: &StructArray, indices: &UInt64Array) -> Result<StructArray> {
     let nulls = array.nulls().map(|nulls| {
         let is_valid = indices.iter().map(|index| {
@@ -603,6 +786,12 @@ mod test {
     use std::collections::HashMap;
 
     use crate::dataset::{WriteParams, scanner::test_dataset::TestVectorDataset};
+    use crate::utils::test::{DatagenExt, FragmentCount, FragmentRowCount, ThrottledStoreWrapper};
+    use arrow_array::types::{Float32Type, Int32Type};
+    use lance_core::utils::tempfile::TempStdDir;
+    use lance_datagen::{Dimension, array, gen_batch};
+    use lance_io::object_store::{ObjectStoreParams, StorageOptionsAccessor, WrappingObjectStore};
+    use object_store::throttle::ThrottleConfig;
 
     use super::*;
 
@@ -785,6 +974,32 @@ mod test {
         assert_nested_arrow_json_schema(&empty);
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_take_into_ffi_stream() {
+        use arrow::ffi_stream::ArrowArrayStreamReader;
+
+        let data = nested_arrow_json_batch();
+        let batches = RecordBatchIterator::new([Ok(data.clone())], data.schema());
+        let dataset = Dataset::write(batches, "memory://", None).await.unwrap();
+        let projection = Schema::try_from(data.schema().as_ref()).unwrap();
+
+        let expected = dataset.take(&[1, 0], projection.clone()).await.unwrap();
+        let stream = dataset
+            .take_into_ffi_stream(&[1, 0], projection, None)
+            .unwrap();
+        // The stream blocks on the runtime, so it is read from outside of it
+        let batches = tokio::task::spawn_blocking(move || {
+            ArrowArrayStreamReader::try_new(stream)
+                .unwrap()
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .unwrap()
+        })
+        .await
+        .unwrap();
+        assert_eq!(batches, vec![expected]);
+        assert_nested_arrow_json_schema(&batches[0]);
+    }
+
     #[tokio::test]
     async fn test_take_with_deletion() {
         let data = test_batch(0..120);
@@ -1285,6 +1500,39 @@ mod test {
         );
     }
 
+    #[tokio::test]
+    async fn test_take_scattered_rows_many_fragments() {
+        let data = test_batch(0..10_000);
+        let write_params = WriteParams {
+            max_rows_per_file: 500,
+            ..Default::default()
+        };
+        let batches = RecordBatchIterator::new([Ok(data.clone())], data.schema());
+        let dataset = Dataset::write(batches, "memory://", Some(write_params))
+            .await
+            .unwrap();
+        assert_eq!(dataset.get_fragments().len(), 20);
+
+        // Clustered, unsorted and duplicated offsets spanning every fragment
+        let indices = (0..2_000_u64)
+            .map(|i| (i * 7_919) % 10_000)
+            .chain([3, 3, 9_999, 0])
+            .collect::<Vec<_>>();
+        let projection = Schema::try_from(data.schema().as_ref()).unwrap();
+        let values = dataset.take(&indices, projection).await.unwrap();
+
+        let expected = indices.iter().map(|i| *i as i32).collect::<Vec<_>>();
+        assert_eq!(
+            values.column(0).as_ref(),
+            &Int32Array::from_iter_values(expected.iter().copied()) as &dyn Array
+        );
+        assert_eq!(
+            values.column(1).as_ref(),
+            &StringArray::from_iter_values(expected.iter().map(|v| format!("str-{v}")))
+                as &dyn Array
+        );
+    }
+
     #[rstest]
     #[tokio::test]
     async fn test_take_rows(
@@ -1443,4 +1691,254 @@ mod test {
             result
         );
     }
+
+    /// A dataset of one fragment whose rows are read through a disk cache in `cache_dir`
+    async fn prefetch_dataset(
+        cache_dir: &TempStdDir,
+        cache_size_bytes: u64,
+        wrapper: Option<Arc<dyn WrappingObjectStore>>,
+        num_rows: u64,
+    ) -> Dataset {
+        let storage_options = HashMap::from([
+            (
+                "storage_cache_dir".to_string(),
+                cache_dir.to_str().unwrap().to_string(),
+            ),
+            (
+                "storage_cache_size_bytes".to_string(),
+                cache_size_bytes.to_string(),
+            ),
+            (
+                "storage_metadata_cache_size".to_string(),
+                "1000".to_string(),
+            ),
+            (
+                "storage_metadata_cache_ttl_ms".to_string(),
+                "60000".to_string(),
+            ),
+        ]);
+        let write_params = WriteParams {
+            store_params: Some(ObjectStoreParams {
+                object_store_wrapper: wrapper,
+                storage_options_accessor: Some(Arc::new(
+                    StorageOptionsAccessor::with_static_options(storage_options),
+                )),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        gen_batch()
+            .col("i", array::step::<Int32Type>())
+            .col("vec", array::rand_vec::<Float32Type>(Dimension::from(32)))
+            .into_ram_dataset_with_params(
+                FragmentCount::from(1),
+                FragmentRowCount::from(num_rows as u32),
+                Some(write_params),
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_warms_cache() {
+        let cache_dir = TempStdDir::default();
+        let dataset = prefetch_dataset(&cache_dir, 1024 * 1024 * 1024, None, 1000).await;
+        let row_ids = (0..1000).step_by(7).collect::<Vec<u64>>();
+        let projection = ProjectionRequest::from_columns(["i", "vec"], dataset.schema());
+
+        dataset
+            .prefetch(&row_ids, projection.clone())
+            .unwrap()
+            .await
+            .unwrap();
+
+        let store = dataset.object_store.as_ref();
+        store.io_stats_incremental();
+        let batch = dataset.take_rows(&row_ids, projection).await.unwrap();
+        assert_eq!(batch.num_rows(), row_ids.len());
+        let stats = store.io_stats_incremental();
+        assert_eq!(stats.read_iops, 0, "{stats:?}");
+        assert!(stats.disk_cache_hits > 0, "{stats:?}");
+        assert_eq!(stats.disk_cache_misses, 0, "{stats:?}");
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_respects_cache_budget() {
+        const BUDGET: u64 = 1024 * 1024;
+        let cache_dir = TempStdDir::default();
+        // About 2.6MB of vectors, more than the cache may hold
+        let dataset = prefetch_dataset(&cache_dir, BUDGET, None, 20_000).await;
+        let row_ids = (0..20_000).collect::<Vec<u64>>();
+        let projection = ProjectionRequest::from_columns(["vec"], dataset.schema());
+
+        dataset
+            .prefetch(&row_ids, projection.clone())
+            .unwrap()
+            .await
+            .unwrap();
+        // The cache evicts on its next operation once its housekeeping is due
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        dataset.take_rows(&[0], projection).await.unwrap();
+
+        let cached_bytes = dir_size(cache_dir.as_ref());
+        assert!(cached_bytes <= BUDGET, "{cached_bytes}");
+    }
+
+    fn dir_size(dir: &std::path::Path) -> u64 {
+        std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| {
+                let entry = entry.unwrap();
+                if entry.file_type().unwrap().is_dir() {
+                    dir_size(&entry.path())
+                } else {
+                    entry.metadata().unwrap().len()
+                }
+            })
+            .sum()
+    }
+
+    #[tokio::test]
+    async fn test_drop_prefetch_cancels_io() {
+        let cache_dir = TempStdDir::default();
+        let throttled = Arc::new(ThrottledStoreWrapper {
+            config: ThrottleConfig {
+                wait_get_per_call: Duration::from_millis(50),
+                ..Default::default()
+            },
+        });
+        let dataset =
+            prefetch_dataset(&cache_dir, 1024 * 1024 * 1024, Some(throttled), 20_000).await;
+        let row_ids = (0..20_000).collect::<Vec<u64>>();
+
+        let handle = dataset
+            .prefetch(&row_ids, dataset.schema().clone())
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        drop(handle);
+
+        // Reads that were in flight are aborted and no new reads start
+        let store = dataset.object_store.as_ref();
+        store.io_stats_incremental();
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(store.io_stats_incremental().read_iops, 0);
+    }
+
+    async fn take_stream_dataset() -> Dataset {
+        let data = test_batch(0..1000);
+        let write_params = WriteParams {
+            max_rows_per_file: 250,
+            ..Default::default()
+        };
+        let batches = RecordBatchIterator::new([Ok(data.clone())], data.schema());
+        Dataset::write(batches, "memory://", Some(write_params))
+            .await
+            .unwrap()
+    }
+
+    /// The row id of the `i`th row of [`take_stream_dataset`]
+    fn take_stream_row_id(i: u64) -> u64 {
+        u64::from(RowAddress::new_from_parts(
+            (i / 250) as u32,
+            (i % 250) as u32,
+        ))
+    }
+
+    async fn collect_responses(
+        dataset: &Dataset,
+        requests: Vec<TakeRequest>,
+    ) -> HashMap<u64, Result<RecordBatch>> {
+        dataset
+            .take_stream(futures::stream::iter(requests))
+            .map(|response| (response.id, response.result))
+            .collect()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_take_stream_coalesces_requests() {
+        let dataset = take_stream_dataset().await;
+        let row_ids = [910_u64, 5, 260, 511, 6, 777]
+            .into_iter()
+            .map(take_stream_row_id)
+            .collect::<Vec<_>>();
+        let columns = vec!["i".to_string(), "s".to_string()];
+        let expected = dataset
+            .take_rows(&row_ids, dataset.schema().clone())
+            .await
+            .unwrap();
+
+        let store = dataset.object_store.as_ref();
+        store.io_stats_incremental();
+        dataset
+            .take_rows(&row_ids, dataset.schema().clone())
+            .await
+            .unwrap();
+        let single = store.io_stats_incremental();
+        assert!(single.read_iops > 0);
+
+        let requests = (0..8)
+            .map(|id| TakeRequest {
+                id,
+                row_ids: row_ids.clone(),
+                columns: columns.clone(),
+            })
+            .collect();
+        let responses = collect_responses(&dataset, requests).await;
+        let coalesced = store.io_stats_incremental();
+
+        assert_eq!(responses.len(), 8);
+        for result in responses.into_values() {
+            assert_eq!(result.unwrap(), expected);
+        }
+        // The rows are read once for all the requests, not once per request
+        assert!(
+            coalesced.read_iops <= single.read_iops,
+            "{coalesced:?} {single:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_take_stream_out_of_order_with_errors() {
+        let dataset = take_stream_dataset().await;
+        let requests = (0..20_u64)
+            .map(|id| {
+                let row_ids = (0..id % 5 + 1)
+                    .map(|i| take_stream_row_id((id * 97 + i * 331) % 1000))
+                    .collect();
+                let columns = match id % 3 {
+                    0 => vec!["i".to_string()],
+                    1 => vec!["s".to_string(), "i".to_string()],
+                    _ => vec!["i".to_string(), "s".to_string()],
+                };
+                TakeRequest {
+                    id,
+                    row_ids,
+                    columns,
+                }
+            })
+            .chain([TakeRequest {
+                id: 100,
+                row_ids: vec![0],
+                columns: vec!["missing".to_string()],
+            }])
+            .collect::<Vec<_>>();
+
+        let responses = collect_responses(&dataset, requests.clone()).await;
+        let reversed = collect_responses(&dataset, requests.iter().rev().cloned().collect()).await;
+        assert_eq!(responses.len(), requests.len());
+        assert_eq!(reversed.len(), requests.len());
+
+        for request in &requests[..20] {
+            let projection = ProjectionRequest::from_columns(&request.columns, dataset.schema());
+            let expected = dataset
+                .take_rows(&request.row_ids, projection)
+                .await
+                .unwrap();
+            assert_eq!(responses[&request.id].as_ref().unwrap(), &expected);
+            assert_eq!(reversed[&request.id].as_ref().unwrap(), &expected);
+        }
+        assert!(responses[&100].is_err());
+        assert!(reversed[&100].is_err());
+    }
 }
@@ -0,0 +1,230 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+use std::sync::Arc;
+
+use arrow_array::{Int32Array, RecordBatch, RecordBatchIterator};
+use arrow_schema::{DataType, Field as ArrowField, Schema as ArrowSchema};
+use lance_core::utils::tempfile::TempStrDir;
+use lance_file::version::LanceFileVersion;
+use lance_table::feature_flags::{READER_FEATURE_LEVEL, ReaderFeature};
+
+use crate::dataset::builder::DatasetBuilder;
+use crate::dataset::write::{WriteMode, WriteParams};
+use crate::dataset::{ManifestWriteConfig, write_manifest_file};
+use crate::{Dataset, Error};
+
+async fn write(uri: &str, params: WriteParams) -> crate::Result<Dataset> {
+    let schema = Arc::new(ArrowSchema::new(vec![ArrowField::new(
+        "i",
+        DataType::Int32,
+        false,
+    )]));
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![Arc::new(Int32Array::from_iter_values(0..20))],
+    )
+    .unwrap();
+    Dataset::write(
+        RecordBatchIterator::new(vec![Ok(batch)], schema),
+        uri,
+        Some(params),
+    )
+    .await
+}
+
+fn feature_names(dataset: &Dataset) -> Vec<String> {
+    let report = dataset.compatibility_report().unwrap();
+    // The features are recorded in the manifest on commit
+    assert_eq!(report.features, dataset.manifest.reader_features);
+    report.features.into_iter().map(|f| f.name).collect()
+}
+
+#[tokio::test]
+async fn test_compatibility_report() {
+    let test_uri = TempStrDir::default();
+    let mut dataset = write(
+        &test_uri,
+        WriteParams {
+            data_storage_version: Some(LanceFileVersion::V2_0),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(feature_names(&dataset), vec!["data_storage_v2.0"]);
+
+    dataset.delete("i < 10").await.unwrap();
+    assert_eq!(
+        feature_names(&dataset),
+        vec!["data_storage_v2.0", "deletion_files"]
+    );
+    let report = dataset.compatibility_report().unwrap();
+    assert_eq!(report.min_reader_feature_level(), 1);
+    assert_eq!(report.min_version(), Some("0.16.0"));
+    assert_eq!(
+        report.to_string(),
+        "data_storage_v2.0: level 1, introduced in 0.16.0\n\
+         deletion_files: level 1, introduced in 0.16.0\n"
+    );
+
+    let test_uri = TempStrDir::default();
+    let dataset = write(
+        &test_uri,
+        WriteParams {
+            data_storage_version: Some(LanceFileVersion::V2_2),
+            enable_stable_row_ids: true,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        feature_names(&dataset),
+        vec!["stable_row_ids", "data_storage_v2.2"]
+    );
+    let report = dataset.compatibility_report().unwrap();
+    assert_eq!(report.min_reader_feature_level(), 5);
+    assert_eq!(report.min_version(), Some("4.0.0-beta.1"));
+
+    let test_uri = TempStrDir::default();
+    let dataset = write(
+        &test_uri,
+        WriteParams {
+            data_storage_version: Some(LanceFileVersion::Legacy),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    let report = dataset.compatibility_report().unwrap();
+    assert!(report.features.is_empty());
+    assert_eq!(report.min_version(), None);
+}
+
+#[tokio::test]
+async fn test_open_with_lower_reader_feature_level() {
+    let test_uri = TempStrDir::default();
+    write(
+        &test_uri,
+        WriteParams {
+            data_storage_version: Some(LanceFileVersion::V2_2),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    let err = DatasetBuilder::from_uri(&test_uri)
+        .with_reader_feature_level(4)
+        .load()
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::NotSupported { .. }), "{err}");
+    assert!(
+        err.to_string()
+            .contains("Dataset requires feature `data_storage_v2.2` (min version 4.0.0-beta.1)"),
+        "{err}"
+    );
+
+    DatasetBuilder::from_uri(&test_uri)
+        .with_reader_feature_level(5)
+        .load()
+        .await
+        .unwrap();
+    Dataset::open(&test_uri).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_open_with_unknown_reader_feature() {
+    let test_uri = TempStrDir::default();
+    let dataset = write(&test_uri, WriteParams::default()).await.unwrap();
+
+    // Simulate a dataset written by a newer version of Lance
+    let mut manifest = dataset.manifest.as_ref().clone();
+    manifest.reader_features.push(ReaderFeature {
+        name: "teleportation".to_string(),
+        level: READER_FEATURE_LEVEL + 1,
+        min_version: "99.0.0".to_string(),
+    });
+    manifest.version += 1;
+    write_manifest_file(
+        dataset.object_store.as_ref(),
+        dataset.commit_handler.as_ref(),
+        &dataset.base,
+        &mut manifest,
+        None,
+        &ManifestWriteConfig {
+            auto_set_feature_flags: false,
+            ..Default::default()
+        },
+        dataset.manifest_location.naming_scheme,
+        None,
+    )
+    .await
+    .unwrap();
+
+    let err = Dataset::open(&test_uri).await.unwrap_err();
+    assert!(matches!(err, Error::NotSupported { .. }), "{err}");
+    assert!(
+        err.to_string()
+            .contains("Dataset requires feature `teleportation` (min version 99.0.0)"),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn test_max_reader_feature_level() {
+    let test_uri = TempStrDir::default();
+    let err = write(
+        &test_uri,
+        WriteParams {
+            data_storage_version: Some(LanceFileVersion::V2_2),
+            max_reader_feature_level: Some(4),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, Error::NotSupported { .. }), "{err}");
+    assert!(
+        err.to_string()
+            .contains("requires reader feature `data_storage_v2.2` (level 5"),
+        "{err}"
+    );
+    // Nothing was committed
+    assert!(matches!(
+        Dataset::open(&test_uri).await,
+        Err(Error::DatasetNotFound { .. })
+    ));
+
+    let dataset = write(
+        &test_uri,
+        WriteParams {
+            data_storage_version: Some(LanceFileVersion::V2_1),
+            max_reader_feature_level: Some(4),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        dataset
+            .compatibility_report()
+            .unwrap()
+            .min_reader_feature_level(),
+        3
+    );
+
+    // Appends keep the storage version of the dataset, which is within the level
+    write(
+        &test_uri,
+        WriteParams {
+            mode: WriteMode::Append,
+            max_reader_feature_level: Some(3),
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+}
@@ -4,7 +4,7 @@
 use std::sync::Arc;
 use std::vec;
 
-use crate::dataset::WriteDestination;
+use crate::dataset::{SchemaCompatibility, WriteDestination};
 use crate::{Dataset, Error, Result};
 
 use crate::dataset::write::{WriteMode, WriteParams};
@@ -53,6 +53,94 @@ async fn concurrent_create() {
     }
 }
 
+#[tokio::test]
+async fn concurrent_open_or_create() {
+    let schema = Arc::new(ArrowSchema::new(vec![ArrowField::new(
+        "a",
+        DataType::Int32,
+        false,
+    )]));
+    let test_uri = TempStrDir::default();
+
+    let datasets = futures::future::try_join_all((0..8).map(|_| {
+        Dataset::open_or_create(&test_uri, schema.clone(), SchemaCompatibility::Exact, None)
+    }))
+    .await
+    .unwrap();
+
+    for dataset in &datasets {
+        assert_eq!(dataset.version().version, 1);
+        assert_eq!(dataset.count_rows(None).await.unwrap(), 0);
+    }
+    let versions = datasets[0].versions().await.unwrap();
+    assert_eq!(versions.len(), 1);
+
+    // Every handle can be written through
+    let batch =
+        RecordBatch::try_new(schema.clone(), vec![Arc::new(Int32Array::from(vec![1]))]).unwrap();
+    let dataset = Dataset::write(
+        RecordBatchIterator::new(vec![Ok(batch)], schema.clone()),
+        WriteDestination::Dataset(Arc::new(datasets[3].clone())),
+        Some(WriteParams {
+            mode: WriteMode::Append,
+            ..Default::default()
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(dataset.version().version, 2);
+    assert_eq!(dataset.count_rows(None).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn open_or_create_checks_schema() {
+    let test_uri = TempStrDir::default();
+    let schema = Arc::new(ArrowSchema::new(vec![
+        ArrowField::new("a", DataType::Int32, false),
+        ArrowField::new("b", DataType::Utf8, true),
+    ]));
+    Dataset::open_or_create(&test_uri, schema, SchemaCompatibility::Exact, None)
+        .await
+        .unwrap();
+
+    let subset = Arc::new(ArrowSchema::new(vec![ArrowField::new(
+        "b",
+        DataType::Utf8,
+        true,
+    )]));
+    let err = Dataset::open_or_create(&test_uri, subset.clone(), SchemaCompatibility::Exact, None)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, Error::SchemaMismatch { difference, .. } if difference.contains("missing=[a]")),
+        "{err}"
+    );
+    let dataset = Dataset::open_or_create(&test_uri, subset, SchemaCompatibility::Subset, None)
+        .await
+        .unwrap();
+    assert_eq!(dataset.version().version, 1);
+
+    let mismatch = Arc::new(ArrowSchema::new(vec![
+        ArrowField::new("a", DataType::Int64, false),
+        ArrowField::new("b", DataType::Utf8, true),
+    ]));
+    let err = Dataset::open_or_create(
+        &test_uri,
+        mismatch.clone(),
+        SchemaCompatibility::Subset,
+        None,
+    )
+    .await
+    .unwrap_err();
+    assert!(
+        matches!(&err, Error::SchemaMismatch { difference, .. } if difference.contains("`a` should have type int32 but type was int64")),
+        "{err}"
+    );
+    Dataset::open_or_create(&test_uri, mismatch, SchemaCompatibility::Ignore, None)
+        .await
+        .unwrap();
+}
+
 #[tokio::test]
 async fn test_limit_pushdown_in_physical_plan() -> Result<()> {
     use tempfile::tempdir;
@@ -0,0 +1,212 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+use std::sync::Arc;
+
+use arrow_array::{ArrayRef, Int32Array, RecordBatch, RecordBatchIterator, StringArray};
+use arrow_schema::{DataType, Field as ArrowField, Schema as ArrowSchema};
+use futures::TryStreamExt;
+use lance_core::utils::encryption::{
+    ENCRYPTION_KEY_ID_META_KEY, EncryptionKey, KeyProvider, StaticKeyProvider,
+};
+use lance_core::utils::tempfile::TempStrDir;
+use lance_file::version::LanceFileVersion;
+use lance_index::IndexType;
+use lance_index::scalar::ScalarIndexParams;
+use lance_io::object_store::ObjectStoreParams;
+
+use crate::Dataset;
+use crate::dataset::builder::DatasetBuilder;
+use crate::dataset::optimize::{CompactionMode, CompactionOptions, compact_files};
+use crate::dataset::write::{WriteMode, WriteParams};
+use crate::index::DatasetIndexExt;
+use crate::session::Session;
+
+fn key_provider() -> Arc<dyn KeyProvider> {
+    Arc::new(StaticKeyProvider::new().with_key("pii", EncryptionKey::new([42; 32])))
+}
+
+fn ssn(i: i32) -> String {
+    format!("ssn-{:06}", i)
+}
+
+// An `id` column and an `ssn` column encrypted with the key `pii`
+fn batch(ids: std::ops::Range<i32>) -> RecordBatch {
+    let schema = Arc::new(ArrowSchema::new(vec![
+        ArrowField::new("id", DataType::Int32, false),
+        ArrowField::new("ssn", DataType::Utf8, true)
+            .with_metadata([(ENCRYPTION_KEY_ID_META_KEY.to_string(), "pii".to_string())].into()),
+    ]));
+    RecordBatch::try_new(
+        schema,
+        vec![
+            Arc::new(Int32Array::from_iter_values(ids.clone())) as ArrayRef,
+            Arc::new(StringArray::from_iter_values(ids.map(ssn))) as ArrayRef,
+        ],
+    )
+    .unwrap()
+}
+
+async fn write(uri: &str, ids: std::ops::Range<i32>, mode: WriteMode) -> Dataset {
+    let batch = batch(ids);
+    let schema = batch.schema();
+    let params = WriteParams {
+        mode,
+        max_rows_per_file: 250,
+        data_storage_version: Some(LanceFileVersion::V2_1),
+        store_params: Some(ObjectStoreParams {
+            key_provider: Some(key_provider()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    Dataset::write(
+        RecordBatchIterator::new(vec![Ok(batch)], schema),
+        uri,
+        Some(params),
+    )
+    .await
+    .unwrap()
+}
+
+// Asserts that none of the data files contain plain values of the `ssn` column
+fn assert_no_plain_values(uri: &str) {
+    let mut num_files = 0;
+    for entry in std::fs::read_dir(format!("{}/data", uri)).unwrap() {
+        let bytes = std::fs::read(entry.unwrap().path()).unwrap();
+        assert!(
+            !bytes.windows(10).any(|window| window == b"ssn-000123"),
+            "a data file contains a plain value of the encrypted column"
+        );
+        num_files += 1;
+    }
+    assert!(num_files > 0);
+}
+
+async fn scan_error(dataset: &Dataset) -> String {
+    match dataset.scan().try_into_stream().await {
+        Ok(stream) => stream
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap_err()
+            .to_string(),
+        Err(err) => err.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_encrypted_column_round_trip() {
+    let test_dir = TempStrDir::default();
+    let uri = test_dir.as_str();
+    let dataset = write(uri, 0..1000, WriteMode::Create).await;
+    assert_no_plain_values(uri);
+
+    // The key is read from the session when the dataset is opened again
+    let session = Arc::new(Session::default().with_key_provider(key_provider()));
+    let dataset_with_session = DatasetBuilder::from_uri(uri)
+        .with_session(session)
+        .load()
+        .await
+        .unwrap();
+
+    for dataset in [dataset, dataset_with_session] {
+        let batch = dataset.scan().try_into_batch().await.unwrap();
+        assert_eq!(batch.num_rows(), 1000);
+        assert_eq!(
+            batch.column_by_name("ssn").unwrap().as_ref(),
+            &StringArray::from_iter_values((0..1000).map(ssn))
+        );
+
+        let taken = dataset
+            .take(&[3, 400, 999], dataset.schema().clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            taken.column_by_name("ssn").unwrap().as_ref(),
+            &StringArray::from(vec!["ssn-000003", "ssn-000400", "ssn-000999"])
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_encrypted_column_without_key() {
+    let test_dir = TempStrDir::default();
+    let uri = test_dir.as_str();
+    write(uri, 0..1000, WriteMode::Create).await;
+
+    let dataset = Dataset::open(uri).await.unwrap();
+
+    // The other columns can still be read
+    let ids = dataset
+        .scan()
+        .project(&["id"])
+        .unwrap()
+        .try_into_batch()
+        .await
+        .unwrap();
+    assert_eq!(ids.num_rows(), 1000);
+
+    let err = scan_error(&dataset).await;
+    assert!(err.contains("column 'ssn'"), "{err}");
+    assert!(err.contains("key 'pii'"), "{err}");
+
+    let err = dataset
+        .take(&[3], dataset.schema().clone())
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("column 'ssn'"), "{err}");
+
+    // An index would store the values unencrypted
+    let mut dataset = dataset;
+    let err = dataset
+        .create_index(
+            &["ssn"],
+            IndexType::BTree,
+            None,
+            &ScalarIndexParams::default(),
+            false,
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("encrypted"), "{err}");
+}
+
+#[tokio::test]
+async fn test_compaction_keeps_encryption() {
+    let test_dir = TempStrDir::default();
+    let uri = test_dir.as_str();
+    write(uri, 0..500, WriteMode::Create).await;
+    let mut dataset = write(uri, 500..1000, WriteMode::Append).await;
+    assert_eq!(dataset.get_fragments().len(), 4);
+
+    for compaction_mode in [CompactionMode::TryBinaryCopy, CompactionMode::Reencode] {
+        let metrics = compact_files(
+            &mut dataset,
+            CompactionOptions {
+                target_rows_per_fragment: 2000,
+                compaction_mode: Some(compaction_mode),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(metrics.fragments_added, 1);
+        assert_eq!(dataset.get_fragments().len(), 1);
+        assert_no_plain_values(uri);
+
+        let batch = dataset.scan().try_into_batch().await.unwrap();
+        assert_eq!(
+            batch.column_by_name("ssn").unwrap().as_ref(),
+            &StringArray::from_iter_values((0..1000).map(ssn))
+        );
+
+        // Split the dataset up again for the next compaction
+        dataset = write(uri, 0..1000, WriteMode::Overwrite).await;
+    }
+
+    let dataset = Dataset::open(uri).await.unwrap();
+    let err = scan_error(&dataset).await;
+    assert!(err.contains("column 'ssn'"), "{err}");
+}
@@ -5,10 +5,10 @@ use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::vec;
 
-use crate::dataset::ROW_ID;
 use crate::dataset::builder::DatasetBuilder;
 use crate::dataset::tests::dataset_migrations::scan_dataset;
 use crate::dataset::tests::dataset_transactions::{assert_results, execute_sql};
+use crate::dataset::{ColumnAlteration, ROW_ID};
 use crate::index::vector::VectorIndexParams;
 use crate::session::Session;
 use crate::{Dataset, Error, Result};
@@ -19,10 +19,10 @@ use crate::index::DatasetIndexExt;
 use arrow::array::{AsArray, GenericListBuilder, GenericStringBuilder};
 use arrow::datatypes::UInt64Type;
 use arrow_array::RecordBatch;
-use arrow_array::{Array, GenericStringArray, StructArray, UInt64Array};
+use arrow_array::{Array, GenericStringArray, LargeStringArray, StructArray, UInt64Array};
 use arrow_array::{
     ArrayRef, Float32Array, Int32Array, RecordBatchIterator, StringArray,
-    builder::StringDictionaryBuilder,
+    builder::{Float32Builder, LargeListBuilder, StringDictionaryBuilder},
     types::{Float32Type, Int32Type},
 };
 use arrow_schema::{
@@ -3534,3 +3534,100 @@ async fn test_legacy_dataset_uses_v2_0_for_indexes() {
         "Index files should never use legacy format, even for legacy datasets"
     );
 }
+
+#[tokio::test]
+async fn test_index_large_types() {
+    let test_uri = TempStrDir::default();
+    let dim = 8;
+    let nrows = 256;
+
+    let mut embeddings = LargeListBuilder::new(Float32Builder::new());
+    for i in 0..nrows {
+        embeddings
+            .values()
+            .append_slice(&vec![i as f32; dim as usize]);
+        embeddings.append(true);
+    }
+    let schema = Arc::new(ArrowSchema::new(vec![
+        ArrowField::new("id", DataType::Int32, false),
+        ArrowField::new("text", DataType::LargeUtf8, false),
+        ArrowField::new(
+            "emb",
+            DataType::new_large_list(DataType::Float32, true),
+            true,
+        ),
+    ]));
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(Int32Array::from_iter_values(0..nrows)),
+            Arc::new(LargeStringArray::from_iter_values(
+                (0..nrows).map(|i| format!("lance document {i}")),
+            )),
+            Arc::new(embeddings.finish()),
+        ],
+    )
+    .unwrap();
+    let mut dataset = Dataset::write(
+        RecordBatchIterator::new(vec![Ok(batch)], schema.clone()),
+        &test_uri,
+        None,
+    )
+    .await
+    .unwrap();
+
+    // LargeUtf8 columns are indexed like Utf8 columns.
+    dataset
+        .create_index(
+            &["text"],
+            IndexType::Inverted,
+            None,
+            &InvertedIndexParams::default(),
+            true,
+        )
+        .await
+        .unwrap();
+    let results = dataset
+        .scan()
+        .full_text_search(FullTextSearchQuery::new("17".to_owned()))
+        .unwrap()
+        .try_into_batch()
+        .await
+        .unwrap();
+    assert_eq!(results.num_rows(), 1);
+    assert_eq!(results["id"].as_primitive::<Int32Type>().value(0), 17);
+
+    // Variable-length embeddings are normalized to FixedSizeList first.
+    let params = VectorIndexParams::ivf_flat(2, MetricType::L2);
+    let err = dataset
+        .create_index(&["emb"], IndexType::Vector, None, &params, true)
+        .await
+        .unwrap_err();
+    assert_contains!(
+        err.to_string(),
+        "cast the column to a FixedSizeList of the vector dimension"
+    );
+
+    dataset
+        .alter_columns(&[
+            ColumnAlteration::new("emb".into()).cast_to(DataType::FixedSizeList(
+                Arc::new(ArrowField::new("item", DataType::Float32, true)),
+                dim,
+            )),
+        ])
+        .await
+        .unwrap();
+    dataset
+        .create_index(&["emb"], IndexType::Vector, None, &params, true)
+        .await
+        .unwrap();
+    let query = Float32Array::from(vec![42.0; dim as usize]);
+    let results = dataset
+        .scan()
+        .nearest("emb", &query, 1)
+        .unwrap()
+        .try_into_batch()
+        .await
+        .unwrap();
+    assert_eq!(results["id"].as_primitive::<Int32Type>().value(0), 42);
+}
@@ -470,6 +470,115 @@ async fn test_object_store_uses_runtime_base_store_params() {
     assert_eq!(store_b.store_prefix, "az$container@account-b");
 }
 
+#[tokio::test]
+async fn test_read_data_files_by_absolute_uri() {
+    use crate::dataset::cleanup::CleanupPolicy;
+    use crate::dataset::transaction::Operation;
+
+    let data = || {
+        gen_batch()
+            .col("x", array::step::<Int32Type>())
+            .into_reader_rows(RowCount::from(100), BatchCount::from(1))
+    };
+    let uri = "memory://absolute_uris";
+    let dataset = Dataset::write(data(), uri, None).await.unwrap();
+
+    // Write data files to a local directory, then reference them from the
+    // in-memory dataset by their absolute URIs
+    let foreign_dir = TempStdDir::default();
+    let foreign = Dataset::write(data(), foreign_dir.to_str().unwrap(), None)
+        .await
+        .unwrap();
+    let mut foreign_paths = Vec::new();
+    let fragments = foreign
+        .get_fragments()
+        .into_iter()
+        .map(|fragment| {
+            let mut metadata = fragment.metadata().clone();
+            for file in metadata.files.iter_mut() {
+                let local_path = foreign_dir.join("data").join(&file.path);
+                file.path = format!("file://{}", local_path.to_str().unwrap());
+                foreign_paths.push(local_path);
+            }
+            metadata
+        })
+        .collect::<Vec<_>>();
+
+    let append = |fragments: Vec<_>, read_version| {
+        let session = dataset.session();
+        async move {
+            Dataset::commit(
+                uri,
+                Operation::Append { fragments },
+                Some(read_version),
+                None,
+                None,
+                session,
+                false,
+            )
+            .await
+            .unwrap()
+        }
+    };
+    let dataset = append(fragments.clone(), 1).await;
+    assert_ne!(
+        dataset.manifest.reader_feature_flags & feature_flags::FLAG_ABSOLUTE_DATA_FILE_URIS,
+        0
+    );
+
+    let foreign_file = &dataset.get_fragments()[1].metadata().files[0];
+    let foreign_store = dataset
+        .object_store_for_data_file(foreign_file)
+        .await
+        .unwrap();
+    assert!(!Arc::ptr_eq(&foreign_store, &dataset.object_store));
+    let _ = dataset.object_store.io_stats_incremental(); // reset
+    let _ = foreign_store.io_stats_incremental(); // reset
+
+    let batches = dataset
+        .scan()
+        .try_into_stream()
+        .await
+        .unwrap()
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    let batch = concat_batches(&batches[0].schema(), &batches).unwrap();
+    let expected = Int32Array::from_iter_values((0..100).chain(0..100));
+    assert_eq!(batch.column(0).as_ref(), &expected as &dyn Array);
+    assert!(dataset.object_store.io_stats_incremental().read_iops > 0);
+    assert!(foreign_store.io_stats_incremental().read_iops > 0);
+
+    // Cleanup leaves the files alone unless explicitly allowed
+    let overwrite_params = WriteParams {
+        mode: WriteMode::Overwrite,
+        session: Some(dataset.session()),
+        ..Default::default()
+    };
+    let dataset = Dataset::write(data(), uri, Some(overwrite_params.clone()))
+        .await
+        .unwrap();
+    let policy = CleanupPolicy {
+        before_version: Some(dataset.version().version),
+        ..Default::default()
+    };
+    let stats = dataset.cleanup_with_policy(policy.clone()).await.unwrap();
+    assert_eq!(stats.old_versions, 2);
+    assert!(foreign_paths.iter().all(|path| path.exists()));
+
+    let dataset = append(fragments, dataset.version().version).await;
+    let dataset = Dataset::write(data(), uri, Some(overwrite_params))
+        .await
+        .unwrap();
+    let policy = CleanupPolicy {
+        before_version: Some(dataset.version().version),
+        delete_foreign_files: true,
+        ..policy
+    };
+    dataset.cleanup_with_policy(policy).await.unwrap();
+    assert!(foreign_paths.iter().all(|path| !path.exists()));
+}
+
 #[rstest]
 #[lance_test_macros::test(tokio::test)]
 async fn test_create_dataset(
@@ -856,6 +965,7 @@ async fn test_write_manifest(
             use_legacy_format: None,
             storage_format: None,
             disable_transaction_file: false,
+            max_reader_feature_level: None,
         },
         dataset.manifest_location.naming_scheme,
         None,
@@ -2086,3 +2196,81 @@ async fn test_open_dataset_non_not_found_error_is_not_masked() {
         err,
     );
 }
+
+#[tokio::test]
+async fn test_read_only_dataset_never_writes() {
+    use arrow_array::cast::AsArray;
+    use lance_core::utils::testing::{ProxyObjectStore, ProxyObjectStorePolicy};
+    use std::sync::Mutex;
+
+    #[derive(Debug)]
+    struct RecordingStore {
+        policy: Arc<Mutex<ProxyObjectStorePolicy>>,
+    }
+
+    impl WrappingObjectStore for RecordingStore {
+        fn wrap(
+            &self,
+            _storage_prefix: &str,
+            original: Arc<dyn object_store::ObjectStore>,
+        ) -> Arc<dyn object_store::ObjectStore> {
+            Arc::new(ProxyObjectStore::new(original, self.policy.clone()))
+        }
+    }
+
+    let test_uri = TempStrDir::default();
+    let data = gen_batch()
+        .col("i", array::step::<Int32Type>())
+        .col("vec", array::rand_vec::<Float32Type>(8.into()))
+        .into_reader_rows(RowCount::from(100), BatchCount::from(2));
+    Dataset::write(data, &test_uri, None).await.unwrap();
+
+    let writes = Arc::new(Mutex::new(Vec::<String>::new()));
+    let mut policy = ProxyObjectStorePolicy::new();
+    let recorded = writes.clone();
+    policy.set_before_policy(
+        "record_writes",
+        Arc::new(move |method, path| {
+            if matches!(
+                method,
+                "put" | "put_multipart" | "delete" | "copy" | "rename"
+            ) {
+                recorded.lock().unwrap().push(format!("{method} {path}"));
+            }
+            Ok(())
+        }),
+    );
+    let store_params = ObjectStoreParams {
+        object_store_wrapper: Some(Arc::new(RecordingStore {
+            policy: Arc::new(Mutex::new(policy)),
+        })),
+        ..Default::default()
+    };
+
+    let mut dataset = DatasetBuilder::from_uri(&test_uri)
+        .with_store_params(store_params)
+        .read_only(true)
+        .load()
+        .await
+        .unwrap();
+    assert!(dataset.is_read_only());
+
+    let rows = dataset.scan().try_into_batch().await.unwrap();
+    assert_eq!(rows.num_rows(), 200);
+
+    let query = rows["vec"].as_fixed_size_list().value(0);
+    let nearest = dataset
+        .scan()
+        .nearest("vec", &query, 5)
+        .unwrap()
+        .try_into_batch()
+        .await
+        .unwrap();
+    assert_eq!(nearest.num_rows(), 5);
+
+    let err = dataset.delete("i < 10").await.unwrap_err();
+    assert!(matches!(err, Error::ReadOnly { .. }), "{err:?}");
+    assert_eq!(dataset.count_rows(None).await.unwrap(), 200);
+
+    assert!(writes.lock().unwrap().is_empty(), "{:?}", writes);
+}
@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-FileCopyrightText: Copyright The Lance Authors
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::vec;
 
@@ -12,6 +13,7 @@ use crate::dataset::{AutoCleanupParams, MergeInsertBuilder, ProjectionRequest};
 use crate::index::DatasetIndexExt;
 use crate::{Dataset, Error};
 use lance_core::ROW_ADDR;
+use lance_core::datatypes::OnMetadataConflict;
 use lance_index::IndexType;
 use lance_index::optimize::OptimizeOptions;
 use lance_index::scalar::ScalarIndexParams;
@@ -208,6 +210,75 @@ async fn test_large_merge(
     dataset.validate().await.unwrap();
 }
 
+#[tokio::test]
+async fn test_merge_keeps_existing_metadata() {
+    let schema = Arc::new(
+        ArrowSchema::new(vec![ArrowField::new("key", DataType::Int32, false)]).with_metadata(
+            HashMap::from([
+                ("owner".to_string(), "existing".to_string()),
+                ("kept".to_string(), "yes".to_string()),
+            ]),
+        ),
+    );
+    let batch =
+        RecordBatch::try_new(schema.clone(), vec![Arc::new(Int32Array::from(vec![1, 2]))]).unwrap();
+    let mut dataset = Dataset::write(
+        RecordBatchIterator::new(vec![Ok(batch)], schema),
+        "memory://",
+        None,
+    )
+    .await
+    .unwrap();
+
+    let new_data = |column: &str, owner: &str| {
+        let schema = Arc::new(
+            ArrowSchema::new(vec![
+                ArrowField::new("key", DataType::Int32, false),
+                ArrowField::new(column, DataType::Int32, false),
+            ])
+            .with_metadata(HashMap::from([
+                ("owner".to_string(), owner.to_string()),
+                (format!("{column}.added"), "yes".to_string()),
+            ])),
+        );
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![1, 2])),
+                Arc::new(Int32Array::from(vec![10, 20])),
+            ],
+        )
+        .unwrap();
+        RecordBatchIterator::new(vec![Ok(batch)], schema)
+    };
+
+    dataset
+        .merge(new_data("a", "incoming"), "key", "key")
+        .await
+        .unwrap();
+    assert_eq!(
+        dataset.schema().metadata,
+        HashMap::from([
+            ("owner".to_string(), "existing".to_string()),
+            ("kept".to_string(), "yes".to_string()),
+            ("a.added".to_string(), "yes".to_string()),
+        ])
+    );
+
+    dataset
+        .merge_with(
+            new_data("b", "incoming"),
+            "key",
+            "key",
+            OnMetadataConflict::Overwrite,
+        )
+        .await
+        .unwrap();
+    assert_eq!(dataset.schema().metadata["owner"], "incoming");
+    assert_eq!(dataset.schema().metadata["kept"], "yes");
+    assert_eq!(dataset.schema().metadata["b.added"], "yes");
+}
+
 #[rstest]
 #[tokio::test]
 async fn test_merge_on_row_id(
@@ -0,0 +1,233 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+use std::sync::Arc;
+
+use arrow_array::cast::AsArray;
+use arrow_array::types::Int32Type;
+use arrow_array::{
+    Array, FixedSizeListArray, Float32Array, Int32Array, RecordBatch, RecordBatchIterator,
+};
+use arrow_schema::{DataType, Field as ArrowField, Schema as ArrowSchema};
+use lance_arrow::FixedSizeListArrayExt;
+use lance_core::utils::tempfile::TempStrDir;
+use lance_index::IndexType;
+use lance_linalg::distance::MetricType;
+
+use crate::Dataset;
+use crate::dataset::{NewColumnTransform, WriteMode, WriteParams};
+use crate::index::DatasetIndexExt;
+use crate::index::vector::VectorIndexParams;
+
+const DIM: i32 = 8;
+
+fn batch(ids: std::ops::Range<i32>, with_score: bool) -> RecordBatch {
+    let mut fields = vec![
+        ArrowField::new("id", DataType::Int32, false),
+        ArrowField::new(
+            "vec",
+            DataType::FixedSizeList(
+                Arc::new(ArrowField::new("item", DataType::Float32, true)),
+                DIM,
+            ),
+            false,
+        ),
+    ];
+    let values = Float32Array::from_iter_values(
+        ids.clone()
+            .flat_map(|id| std::iter::repeat_n(id as f32, DIM as usize)),
+    );
+    let mut columns: Vec<Arc<dyn Array>> = vec![
+        Arc::new(Int32Array::from_iter_values(ids.clone())),
+        Arc::new(FixedSizeListArray::try_new_from_values(values, DIM).unwrap()),
+    ];
+    if with_score {
+        fields.push(ArrowField::new("score", DataType::Int32, true));
+        columns.push(Arc::new(Int32Array::from_iter_values(ids)));
+    }
+    RecordBatch::try_new(Arc::new(ArrowSchema::new(fields)), columns).unwrap()
+}
+
+async fn write(uri: &str, batch: RecordBatch, mode: WriteMode) -> Dataset {
+    let schema = batch.schema();
+    Dataset::write(
+        RecordBatchIterator::new(vec![Ok(batch)], schema),
+        uri,
+        Some(WriteParams {
+            mode,
+            ..Default::default()
+        }),
+    )
+    .await
+    .unwrap()
+}
+
+/// Rows 0..100 are written before `score` is added as an all-null column and
+/// rows 100..200 are appended with `score = id` afterwards.
+async fn backfilled_dataset(uri: &str) -> Dataset {
+    let mut dataset = write(uri, batch(0..100, false), WriteMode::Create).await;
+    dataset
+        .add_columns(
+            NewColumnTransform::AllNulls(Arc::new(ArrowSchema::new(vec![ArrowField::new(
+                "score",
+                DataType::Int32,
+                true,
+            )]))),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    write(uri, batch(100..200, true), WriteMode::Append).await
+}
+
+async fn filtered_ids(dataset: &Dataset, filter: &str) -> Vec<i32> {
+    let batch = dataset
+        .scan()
+        .project(&["id"])
+        .unwrap()
+        .filter(filter)
+        .unwrap()
+        .try_into_batch()
+        .await
+        .unwrap();
+    let mut ids = batch["id"].as_primitive::<Int32Type>().values().to_vec();
+    ids.sort();
+    ids
+}
+
+#[tokio::test]
+async fn test_fragment_missing_columns() {
+    let test_dir = TempStrDir::default();
+    let dataset = backfilled_dataset(test_dir.as_str()).await;
+
+    let fragments = dataset.get_fragments();
+    assert_eq!(fragments.len(), 2);
+    assert_eq!(fragments[0].missing_columns(), vec!["score".to_string()]);
+    assert!(fragments[1].missing_columns().is_empty());
+
+    // The missing column is read as nulls without touching the data files
+    let batch = dataset
+        .scan()
+        .project(&["id", "score"])
+        .unwrap()
+        .try_into_batch()
+        .await
+        .unwrap();
+    assert_eq!(batch.num_rows(), 200);
+    assert_eq!(batch["score"].null_count(), 100);
+    let scores = fragments[0]
+        .scan()
+        .project(&["score"])
+        .unwrap()
+        .try_into_batch()
+        .await
+        .unwrap();
+    assert_eq!(scores["score"].data_type(), &DataType::Int32);
+    assert_eq!(scores["score"].null_count(), 100);
+}
+
+#[tokio::test]
+async fn test_filter_on_missing_column() {
+    let test_dir = TempStrDir::default();
+    let dataset = backfilled_dataset(test_dir.as_str()).await;
+
+    assert_eq!(
+        filtered_ids(&dataset, "score IS NULL").await,
+        (0..100).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        filtered_ids(&dataset, "score >= 190").await,
+        (190..200).collect::<Vec<_>>()
+    );
+    // Comparisons with null are never true, even when negated
+    assert_eq!(
+        filtered_ids(&dataset, "NOT (score > 110)").await,
+        (100..=110).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        filtered_ids(&dataset, "score > 195 OR id < 3").await,
+        vec![0, 1, 2, 196, 197, 198, 199]
+    );
+    assert_eq!(
+        filtered_ids(&dataset, "score IS NULL AND id >= 98").await,
+        vec![98, 99]
+    );
+
+    assert_eq!(
+        dataset
+            .count_rows(Some("score > 150".to_string()))
+            .await
+            .unwrap(),
+        49
+    );
+    assert_eq!(
+        dataset
+            .count_rows(Some("score IS NULL".to_string()))
+            .await
+            .unwrap(),
+        100
+    );
+    let fragments = dataset.get_fragments();
+    assert_eq!(
+        fragments[0]
+            .count_rows(Some("score > 150".to_string()))
+            .await
+            .unwrap(),
+        0
+    );
+    assert_eq!(
+        fragments[0]
+            .count_rows(Some("score IS NULL".to_string()))
+            .await
+            .unwrap(),
+        100
+    );
+}
+
+#[tokio::test]
+async fn test_vector_search_prefilter_on_missing_column() {
+    let test_dir = TempStrDir::default();
+    let mut dataset = backfilled_dataset(test_dir.as_str()).await;
+    dataset
+        .create_index(
+            &["vec"],
+            IndexType::Vector,
+            None,
+            &VectorIndexParams::ivf_flat(4, MetricType::L2),
+            true,
+        )
+        .await
+        .unwrap();
+
+    let query = Float32Array::from(vec![42.0; DIM as usize]);
+    let results = dataset
+        .scan()
+        .nearest("vec", &query, 5)
+        .unwrap()
+        .nprobes(4)
+        .prefilter(true)
+        .filter("score IS NOT NULL")
+        .unwrap()
+        .try_into_batch()
+        .await
+        .unwrap();
+    let mut ids = results["id"].as_primitive::<Int32Type>().values().to_vec();
+    ids.sort();
+    assert_eq!(ids, (100..105).collect::<Vec<_>>());
+
+    let results = dataset
+        .scan()
+        .nearest("vec", &query, 5)
+        .unwrap()
+        .nprobes(4)
+        .prefilter(true)
+        .filter("score IS NULL")
+        .unwrap()
+        .try_into_batch()
+        .await
+        .unwrap();
+    let mut ids = results["id"].as_primitive::<Int32Type>().values().to_vec();
+    ids.sort();
+    assert_eq!(ids, (40..45).collect::<Vec<_>>());
+}
@@ -0,0 +1,274 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+use std::sync::Arc;
+
+use arrow_array::cast::AsArray;
+use arrow_array::types::Int32Type;
+use arrow_array::{
+    FixedSizeListArray, Float32Array, Int32Array, RecordBatch, RecordBatchIterator, StringArray,
+};
+use arrow_schema::{DataType, Field as ArrowField, Schema as ArrowSchema};
+use lance_arrow::FixedSizeListArrayExt;
+use lance_core::utils::tempfile::TempStrDir;
+use lance_index::IndexType;
+use lance_index::scalar::FullTextSearchQuery;
+use lance_index::scalar::inverted::tokenizer::InvertedIndexParams;
+use lance_linalg::distance::MetricType;
+
+use crate::Dataset;
+use crate::dataset::WriteMode;
+use crate::dataset::query_defaults::{QUERY_DEFAULTS_PREFIX, QueryDefaults};
+use crate::dataset::scanner::Scanner;
+use crate::dataset::write::WriteParams;
+use crate::index::DatasetIndexExt;
+use crate::index::vector::VectorIndexParams;
+
+const DIM: i32 = 8;
+
+fn batch(ids: std::ops::Range<i32>) -> RecordBatch {
+    let schema = Arc::new(ArrowSchema::new(vec![
+        ArrowField::new("id", DataType::Int32, false),
+        ArrowField::new("text", DataType::Utf8, false),
+        ArrowField::new(
+            "vec",
+            DataType::FixedSizeList(
+                Arc::new(ArrowField::new("item", DataType::Float32, true)),
+                DIM,
+            ),
+            false,
+        ),
+    ]));
+    let values = Float32Array::from_iter_values(
+        ids.clone()
+            .flat_map(|id| std::iter::repeat_n(id as f32, DIM as usize)),
+    );
+    RecordBatch::try_new(
+        schema,
+        vec![
+            Arc::new(Int32Array::from_iter_values(ids.clone())),
+            Arc::new(StringArray::from_iter_values(
+                ids.map(|id| format!("word{}", id % 10)),
+            )),
+            Arc::new(FixedSizeListArray::try_new_from_values(values, DIM).unwrap()),
+        ],
+    )
+    .unwrap()
+}
+
+async fn write(uri: &str, ids: std::ops::Range<i32>, mode: WriteMode) -> Dataset {
+    let batch = batch(ids);
+    let schema = batch.schema();
+    Dataset::write(
+        RecordBatchIterator::new(vec![Ok(batch)], schema),
+        uri,
+        Some(WriteParams {
+            mode,
+            ..Default::default()
+        }),
+    )
+    .await
+    .unwrap()
+}
+
+async fn indexed_dataset(uri: &str) -> Dataset {
+    let mut dataset = write(uri, 0..500, WriteMode::Create).await;
+    dataset
+        .create_index(
+            &["vec"],
+            IndexType::Vector,
+            None,
+            &VectorIndexParams::ivf_flat(4, MetricType::L2),
+            true,
+        )
+        .await
+        .unwrap();
+    dataset
+}
+
+async fn explain_nearest(dataset: &Dataset, configure: impl Fn(&mut Scanner)) -> String {
+    let query = Float32Array::from(vec![42.0; DIM as usize]);
+    let mut scanner = dataset.scan();
+    scanner.nearest("vec", &query, 5).unwrap();
+    configure(&mut scanner);
+    scanner.explain_plan(false).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_query_defaults_round_trip() {
+    let test_dir = TempStrDir::default();
+    let uri = test_dir.as_str();
+    let mut dataset = write(uri, 0..100, WriteMode::Create).await;
+    dataset
+        .update_schema_metadata([("owner", "search-team")])
+        .await
+        .unwrap();
+
+    let defaults = QueryDefaults::default()
+        .with_nprobes(3)
+        .with_refine_factor(2)
+        .with_fts_wand_factor(0.5);
+    dataset.set_query_defaults(defaults.clone()).await.unwrap();
+    assert_eq!(dataset.query_defaults(), defaults);
+
+    // The defaults survive later commits and reopening the dataset
+    write(uri, 100..200, WriteMode::Append).await;
+    let mut dataset = Dataset::open(uri).await.unwrap();
+    assert_eq!(dataset.query_defaults(), defaults);
+
+    // Replacing the defaults removes the keys that are no longer set
+    let defaults = QueryDefaults::default().with_ef(64);
+    dataset.set_query_defaults(defaults.clone()).await.unwrap();
+    let mut dataset = Dataset::open(uri).await.unwrap();
+    assert_eq!(dataset.query_defaults(), defaults);
+    dataset
+        .set_query_defaults(QueryDefaults::default())
+        .await
+        .unwrap();
+    assert!(dataset.query_defaults().is_empty());
+    assert!(
+        !dataset
+            .schema()
+            .metadata
+            .keys()
+            .any(|key| key.starts_with(QUERY_DEFAULTS_PREFIX))
+    );
+    assert_eq!(
+        dataset.schema().metadata.get("owner"),
+        Some(&"search-team".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_query_defaults_apply_to_vector_search() {
+    let test_dir = TempStrDir::default();
+    let mut dataset = indexed_dataset(test_dir.as_str()).await;
+
+    // Datasets without defaults are searched as before
+    let plan = explain_nearest(&dataset, |_| {}).await;
+    assert!(!plan.contains("QueryDefaults"), "{plan}");
+    assert!(
+        plan.contains("minimum_nprobes=1, maximum_nprobes=None"),
+        "{plan}"
+    );
+
+    dataset
+        .set_query_defaults(
+            QueryDefaults::default()
+                .with_nprobes(3)
+                .with_refine_factor(2),
+        )
+        .await
+        .unwrap();
+    let plan = explain_nearest(&dataset, |_| {}).await;
+    assert!(
+        plan.starts_with("QueryDefaults: minimum_nprobes=3, maximum_nprobes=3, refine_factor=2\n"),
+        "{plan}"
+    );
+    assert!(
+        plan.contains("minimum_nprobes=3, maximum_nprobes=Some(3)"),
+        "{plan}"
+    );
+
+    // Explicit parameters win over the defaults
+    let plan = explain_nearest(&dataset, |scanner| {
+        scanner.nprobes(2);
+    })
+    .await;
+    assert!(
+        plan.starts_with("QueryDefaults: refine_factor=2\n"),
+        "{plan}"
+    );
+    assert!(
+        plan.contains("minimum_nprobes=2, maximum_nprobes=Some(2)"),
+        "{plan}"
+    );
+    let plan = explain_nearest(&dataset, |scanner| {
+        scanner.nprobes(2).refine(1);
+    })
+    .await;
+    assert!(!plan.contains("QueryDefaults"), "{plan}");
+
+    let results = dataset
+        .scan()
+        .nearest("vec", &Float32Array::from(vec![42.0; DIM as usize]), 1)
+        .unwrap()
+        .try_into_batch()
+        .await
+        .unwrap();
+    assert_eq!(results["id"].as_primitive::<Int32Type>().value(0), 42);
+}
+
+#[tokio::test]
+async fn test_query_defaults_apply_to_full_text_search() {
+    let test_dir = TempStrDir::default();
+    let mut dataset = indexed_dataset(test_dir.as_str()).await;
+    dataset
+        .create_index(
+            &["text"],
+            IndexType::Inverted,
+            None,
+            &InvertedIndexParams::default(),
+            true,
+        )
+        .await
+        .unwrap();
+    dataset
+        .set_query_defaults(QueryDefaults::default().with_fts_wand_factor(0.5))
+        .await
+        .unwrap();
+
+    let plan = dataset
+        .scan()
+        .full_text_search(FullTextSearchQuery::new("word7".to_owned()))
+        .unwrap()
+        .explain_plan(false)
+        .await
+        .unwrap();
+    assert!(
+        plan.starts_with("QueryDefaults: fts_wand_factor=0.5\n"),
+        "{plan}"
+    );
+
+    let plan = dataset
+        .scan()
+        .full_text_search(FullTextSearchQuery::new("word7".to_owned()).wand_factor(Some(1.0)))
+        .unwrap()
+        .explain_plan(false)
+        .await
+        .unwrap();
+    assert!(!plan.contains("QueryDefaults"), "{plan}");
+
+    let results = dataset
+        .scan()
+        .full_text_search(FullTextSearchQuery::new("word7".to_owned()))
+        .unwrap()
+        .try_into_batch()
+        .await
+        .unwrap();
+    assert_eq!(results.num_rows(), 50);
+}
+
+#[tokio::test]
+async fn test_invalid_query_defaults_are_ignored() {
+    let test_dir = TempStrDir::default();
+    let mut dataset = indexed_dataset(test_dir.as_str()).await;
+    dataset
+        .update_schema_metadata([
+            ("lance.query.defaults.minimum_nprobes", "lots"),
+            ("lance.query.defaults.refine_factor", "2"),
+        ])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        dataset.query_defaults(),
+        QueryDefaults::default().with_refine_factor(2)
+    );
+    let plan = explain_nearest(&dataset, |_| {}).await;
+    assert!(
+        plan.starts_with("QueryDefaults: refine_factor=2\n"),
+        "{plan}"
+    );
+    assert!(plan.contains("minimum_nprobes=1"), "{plan}");
+}
@@ -2,14 +2,17 @@
 // SPDX-FileCopyrightText: Copyright The Lance Authors
 
 use crate::Dataset;
-use crate::dataset::{NewColumnTransform, WriteMode, WriteParams};
+use crate::dataset::optimize::{CompactionOptions, compact_files};
+use crate::dataset::{ColumnAlteration, NewColumnTransform, WriteMode, WriteParams};
+use arrow_array::builder::{LargeListBuilder, StringBuilder};
 use arrow_array::{
-    Array, ArrayRef, FixedSizeListArray, Int32Array, ListArray, NullArray, RecordBatch,
-    RecordBatchIterator, StringArray, StructArray,
+    Array, ArrayRef, FixedSizeListArray, Int32Array, LargeBinaryArray, LargeStringArray, ListArray,
+    NullArray, RecordBatch, RecordBatchIterator, StringArray, StructArray,
 };
 use arrow_schema::{
     DataType, Field as ArrowField, Field, Fields as ArrowFields, Fields, Schema as ArrowSchema,
 };
+use lance_core::utils::tempfile::TempStrDir;
 use lance_encoding::version::LanceFileVersion;
 use rstest::rstest;
 use std::collections::HashMap;
@@ -637,3 +640,230 @@ async fn test_scan_with_null_typed_struct_subfield_across_fragments() {
     let result = ds.scan().try_into_batch().await.unwrap();
     assert_eq!(result.num_rows(), 4);
 }
+
+#[tokio::test]
+async fn test_custom_metadata_round_trip() {
+    let ext_metadata = |name: &str| {
+        HashMap::from([
+            ("ARROW:extension:name".to_string(), name.to_string()),
+            ("annotation".to_string(), format!("{name}-note")),
+        ])
+    };
+    let point_fields = ArrowFields::from(vec![
+        ArrowField::new("x", DataType::Int32, true).with_metadata(ext_metadata("my.coord")),
+    ]);
+    let schema = Arc::new(
+        ArrowSchema::new(vec![
+            ArrowField::new("id", DataType::Int32, false).with_metadata(ext_metadata("my.id")),
+            ArrowField::new("point", DataType::Struct(point_fields.clone()), true)
+                .with_metadata(ext_metadata("my.point")),
+            ArrowField::new("label", DataType::Utf8, true),
+        ])
+        .with_metadata(HashMap::from([("owner".to_string(), "team".to_string())])),
+    );
+    let make_batch = |start: i32| {
+        RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from_iter_values(start..start + 10)),
+                Arc::new(StructArray::new(
+                    point_fields.clone(),
+                    vec![Arc::new(Int32Array::from_iter_values(start..start + 10))],
+                    None,
+                )),
+                Arc::new(StringArray::from_iter_values(
+                    (start..start + 10).map(|i| i.to_string()),
+                )),
+            ],
+        )
+        .unwrap()
+    };
+    let assert_metadata = |actual: &ArrowSchema, label_name: &str| {
+        assert_eq!(actual.metadata(), schema.metadata());
+        assert_eq!(
+            actual.field_with_name("id").unwrap().metadata(),
+            &ext_metadata("my.id")
+        );
+        let point = actual.field_with_name("point").unwrap();
+        assert_eq!(point.metadata(), &ext_metadata("my.point"));
+        let DataType::Struct(children) = point.data_type() else {
+            panic!("expected struct, got {}", point.data_type());
+        };
+        assert_eq!(children[0].metadata(), &ext_metadata("my.coord"));
+        assert!(
+            actual
+                .field_with_name(label_name)
+                .unwrap()
+                .metadata()
+                .is_empty()
+        );
+    };
+
+    let test_dir = TempStrDir::default();
+    let mut dataset = Dataset::write(
+        RecordBatchIterator::new(vec![Ok(make_batch(0))], schema.clone()),
+        &test_dir,
+        None,
+    )
+    .await
+    .unwrap();
+    let scanned = dataset.scan().try_into_batch().await.unwrap();
+    assert_metadata(&scanned.schema(), "label");
+
+    dataset
+        .append(
+            RecordBatchIterator::new(vec![Ok(make_batch(10))], schema.clone()),
+            None,
+        )
+        .await
+        .unwrap();
+    let scanned = dataset.scan().try_into_batch().await.unwrap();
+    assert_metadata(&scanned.schema(), "label");
+
+    dataset
+        .add_columns(
+            NewColumnTransform::AllNulls(Arc::new(ArrowSchema::new(vec![
+                ArrowField::new("extra", DataType::Int32, true)
+                    .with_metadata(ext_metadata("my.extra")),
+            ]))),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    dataset
+        .alter_columns(&[ColumnAlteration::new("label".into()).rename("name".into())])
+        .await
+        .unwrap();
+    let scanned = dataset.scan().try_into_batch().await.unwrap();
+    assert_metadata(&scanned.schema(), "name");
+    assert_eq!(
+        scanned
+            .schema()
+            .field_with_name("extra")
+            .unwrap()
+            .metadata(),
+        &ext_metadata("my.extra")
+    );
+
+    dataset.drop_columns(&["extra"]).await.unwrap();
+    let scanned = dataset.scan().try_into_batch().await.unwrap();
+    assert_metadata(&scanned.schema(), "name");
+
+    compact_files(&mut dataset, CompactionOptions::default(), None)
+        .await
+        .unwrap();
+    assert_eq!(dataset.get_fragments().len(), 1);
+
+    // Reopen to check the metadata survives manifest serialization.
+    let dataset = Dataset::open(&test_dir).await.unwrap();
+    let scanned = dataset.scan().try_into_batch().await.unwrap();
+    assert_metadata(&scanned.schema(), "name");
+    assert_eq!(scanned.num_rows(), 20);
+}
+
+#[tokio::test]
+async fn test_large_types_round_trip() {
+    let schema = Arc::new(ArrowSchema::new(vec![
+        ArrowField::new("id", DataType::Int32, false),
+        ArrowField::new("payload", DataType::LargeBinary, true),
+        ArrowField::new("text", DataType::LargeUtf8, true),
+        ArrowField::new("tags", DataType::new_large_list(DataType::Utf8, true), true),
+    ]));
+    let make_batch = |start: i32| {
+        let mut tags = LargeListBuilder::new(StringBuilder::new());
+        for i in start..start + 10 {
+            tags.values()
+                .append_value(if i % 2 == 0 { "even" } else { "odd" });
+            tags.append(true);
+        }
+        RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from_iter_values(start..start + 10)),
+                Arc::new(LargeBinaryArray::from_iter_values(
+                    (start..start + 10).map(|i| i.to_le_bytes()),
+                )),
+                Arc::new(LargeStringArray::from_iter_values(
+                    (start..start + 10).map(|i| format!("doc {i}")),
+                )),
+                Arc::new(tags.finish()),
+            ],
+        )
+        .unwrap()
+    };
+
+    let test_dir = TempStrDir::default();
+    let mut dataset = Dataset::write(
+        RecordBatchIterator::new(vec![Ok(make_batch(0))], schema.clone()),
+        &test_dir,
+        None,
+    )
+    .await
+    .unwrap();
+    dataset
+        .append(
+            RecordBatchIterator::new(vec![Ok(make_batch(10))], schema.clone()),
+            None,
+        )
+        .await
+        .unwrap();
+
+    let filtered = dataset
+        .scan()
+        .filter("text = 'doc 13' OR (array_has(tags, 'even') AND id < 4)")
+        .unwrap()
+        .try_into_batch()
+        .await
+        .unwrap();
+    assert_eq!(
+        filtered["id"]
+            .as_any()
+            .downcast_ref::<Int32Array>()
+            .unwrap(),
+        &Int32Array::from(vec![0, 2, 13])
+    );
+
+    // Backfilled columns keep the 64-bit offsets of their sources.
+    dataset
+        .add_columns(
+            NewColumnTransform::SqlExpressions(vec![
+                ("payload_copy".into(), "payload".into()),
+                ("title".into(), "upper(text)".into()),
+                ("tags_copy".into(), "tags".into()),
+            ]),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    let expected_schema = ArrowSchema::new(
+        schema
+            .fields()
+            .iter()
+            .cloned()
+            .chain([
+                Arc::new(ArrowField::new("payload_copy", DataType::LargeBinary, true)),
+                Arc::new(ArrowField::new("title", DataType::LargeUtf8, true)),
+                Arc::new(ArrowField::new(
+                    "tags_copy",
+                    DataType::new_large_list(DataType::Utf8, true),
+                    true,
+                )),
+            ])
+            .collect::<Vec<_>>(),
+    );
+    assert_eq!(ArrowSchema::from(dataset.schema()), expected_schema);
+    let before = dataset.scan().try_into_batch().await.unwrap();
+    assert_eq!(before.schema().as_ref(), &expected_schema);
+    assert_eq!(before["payload_copy"], before["payload"]);
+    assert_eq!(before["tags_copy"], before["tags"]);
+
+    compact_files(&mut dataset, CompactionOptions::default(), None)
+        .await
+        .unwrap();
+    assert_eq!(dataset.get_fragments().len(), 1);
+    let dataset = Dataset::open(&test_dir).await.unwrap();
+    assert_eq!(ArrowSchema::from(dataset.schema()), expected_schema);
+    assert_eq!(dataset.scan().try_into_batch().await.unwrap(), before);
+}
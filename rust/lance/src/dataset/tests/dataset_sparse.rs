@@ -0,0 +1,256 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+use std::sync::Arc;
+
+use arrow_array::cast::AsArray;
+use arrow_array::types::{Float32Type, Int32Type};
+use arrow_array::{ArrayRef, Int32Array, RecordBatch, RecordBatchIterator};
+use arrow_schema::{DataType, Field as ArrowField, Schema as ArrowSchema};
+use datafusion::common::{assert_contains, assert_not_contains};
+use lance_arrow::sparse::{SparseVectorArray, sparse_vector_field};
+use lance_core::utils::tempfile::TempStrDir;
+use lance_index::IndexType;
+use lance_index::scalar::inverted::SCORE_COL;
+use lance_index::scalar::sparse::SparseQuery;
+use lance_index::scalar::{BuiltinIndexType, ScalarIndexParams};
+use rand::{Rng, SeedableRng};
+
+use crate::Dataset;
+use crate::dataset::write::{WriteMode, WriteParams};
+use crate::index::DatasetIndexExt;
+
+type Vectors = Vec<Option<(Vec<i32>, Vec<f32>)>>;
+
+fn random_vectors(num_rows: usize, seed: u64) -> Vectors {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+    (0..num_rows)
+        .map(|i| match i % 40 {
+            5 => None,
+            9 => Some((vec![], vec![])),
+            _ => {
+                let mut dims = (0..rng.random_range(1..12))
+                    .map(|_| {
+                        let x: f32 = rng.random_range(0.0..1.0);
+                        (x * x * 300.0) as i32
+                    })
+                    .collect::<Vec<_>>();
+                dims.sort_unstable();
+                dims.dedup();
+                let values = dims.iter().map(|_| rng.random_range(0.01..1.0)).collect();
+                Some((dims, values))
+            }
+        })
+        .collect()
+}
+
+fn sparse_batch(first_id: i32, vectors: &Vectors) -> RecordBatch {
+    let schema = Arc::new(ArrowSchema::new(vec![
+        ArrowField::new("id", DataType::Int32, false),
+        sparse_vector_field("embedding", true),
+    ]));
+    let ids = Int32Array::from_iter_values(first_id..first_id + vectors.len() as i32);
+    let embeddings = SparseVectorArray::try_from_iter(vectors.clone()).unwrap();
+    RecordBatch::try_new(
+        schema,
+        vec![
+            Arc::new(ids) as ArrayRef,
+            Arc::new(embeddings.into_inner()) as ArrayRef,
+        ],
+    )
+    .unwrap()
+}
+
+async fn write(uri: &str, first_id: i32, vectors: &Vectors, mode: WriteMode) -> Dataset {
+    let batch = sparse_batch(first_id, vectors);
+    let schema = batch.schema();
+    let params = WriteParams {
+        mode,
+        max_rows_per_file: 250,
+        ..Default::default()
+    };
+    Dataset::write(
+        RecordBatchIterator::new(vec![Ok(batch)], schema),
+        uri,
+        Some(params),
+    )
+    .await
+    .unwrap()
+}
+
+async fn create_sparse_index(dataset: &mut Dataset) {
+    dataset
+        .create_index(
+            &["embedding"],
+            IndexType::SparseVector,
+            None,
+            &ScalarIndexParams::for_builtin(BuiltinIndexType::SparseVector),
+            true,
+        )
+        .await
+        .unwrap();
+}
+
+/// The ids of the top k rows and their scores, `selected` picks rows by id
+fn brute_force(
+    vectors: &Vectors,
+    query: &SparseQuery,
+    k: usize,
+    selected: impl Fn(i32) -> bool,
+) -> Vec<(i32, f32)> {
+    let mut scored = vectors
+        .iter()
+        .enumerate()
+        .filter(|(id, _)| selected(*id as i32))
+        .filter_map(|(id, vector)| {
+            let (indices, values) = vector.as_ref()?;
+            let score = query.dot(indices, values);
+            (score > 0.0).then_some((id as i32, score))
+        })
+        .collect::<Vec<_>>();
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+    scored.truncate(k);
+    scored
+}
+
+async fn search(
+    dataset: &Dataset,
+    query: &SparseQuery,
+    k: usize,
+    filter: Option<&str>,
+    use_index: bool,
+) -> (Vec<(i32, f32)>, String) {
+    let mut scanner = dataset.scan();
+    scanner
+        .project(&["id"])
+        .unwrap()
+        .nearest_sparse("embedding", query.indices(), query.values(), k)
+        .unwrap()
+        .use_scalar_index(use_index)
+        .prefilter(true);
+    if let Some(filter) = filter {
+        scanner.filter(filter).unwrap();
+    }
+    let plan = scanner.explain_plan(true).await.unwrap();
+    let batch = scanner.try_into_batch().await.unwrap();
+    let ids = batch["id"].as_primitive::<Int32Type>().values();
+    let scores = batch[SCORE_COL].as_primitive::<Float32Type>().values();
+    (
+        ids.iter().copied().zip(scores.iter().copied()).collect(),
+        plan,
+    )
+}
+
+fn assert_same_results(actual: &[(i32, f32)], expected: &[(i32, f32)]) {
+    assert_eq!(
+        actual.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+        expected.iter().map(|(id, _)| *id).collect::<Vec<_>>()
+    );
+    for ((_, actual), (_, expected)) in actual.iter().zip(expected) {
+        assert!((actual - expected).abs() < 1e-4, "{actual} != {expected}");
+    }
+}
+
+#[tokio::test]
+async fn test_sparse_search_matches_brute_force() {
+    let test_dir = TempStrDir::default();
+    let mut vectors = random_vectors(1000, 1);
+    let mut dataset = write(&test_dir, 0, &vectors, WriteMode::Create).await;
+
+    let queries = [
+        SparseQuery::try_new(&[0, 1, 2, 3], &[0.4, 1.0, 0.2, 0.7]).unwrap(),
+        SparseQuery::try_new(&[5, 40, 250], &[1.0, -0.5, 2.0]).unwrap(),
+    ];
+    for query in &queries {
+        let expected = brute_force(&vectors, query, 10, |_| true);
+        let (actual, plan) = search(&dataset, query, 10, None, true).await;
+        assert_same_results(&actual, &expected);
+        assert_contains!(&plan, "SparseFlatSearch");
+        assert_not_contains!(&plan, "SparseIndexSearch");
+    }
+
+    create_sparse_index(&mut dataset).await;
+    for query in &queries {
+        let expected = brute_force(&vectors, query, 10, |_| true);
+        let (actual, plan) = search(&dataset, query, 10, None, true).await;
+        assert_same_results(&actual, &expected);
+        assert_contains!(&plan, "SparseIndexSearch");
+        assert_not_contains!(&plan, "SparseFlatSearch");
+
+        let (actual, plan) = search(&dataset, query, 10, None, false).await;
+        assert_same_results(&actual, &expected);
+        assert_not_contains!(&plan, "SparseIndexSearch");
+    }
+
+    // New rows are scored in a scan until the index is updated
+    let new_vectors = random_vectors(300, 2);
+    let mut dataset = write(&test_dir, 1000, &new_vectors, WriteMode::Append).await;
+    vectors.extend(new_vectors);
+    for query in &queries {
+        let expected = brute_force(&vectors, query, 20, |_| true);
+        let (actual, plan) = search(&dataset, query, 20, None, true).await;
+        assert_same_results(&actual, &expected);
+        assert_contains!(&plan, "SparseIndexSearch");
+        assert_contains!(&plan, "SparseFlatSearch");
+    }
+
+    dataset.optimize_indices(&Default::default()).await.unwrap();
+    for query in &queries {
+        let expected = brute_force(&vectors, query, 20, |_| true);
+        let (actual, plan) = search(&dataset, query, 20, None, true).await;
+        assert_same_results(&actual, &expected);
+        assert_not_contains!(&plan, "SparseFlatSearch");
+    }
+}
+
+#[tokio::test]
+async fn test_sparse_search_with_filter_and_deletions() {
+    let test_dir = TempStrDir::default();
+    let vectors = random_vectors(1000, 3);
+    let mut dataset = write(&test_dir, 0, &vectors, WriteMode::Create).await;
+    create_sparse_index(&mut dataset).await;
+    dataset.delete("id < 100 OR id % 7 = 0").await.unwrap();
+
+    let query = SparseQuery::try_new(&[0, 1, 2], &[1.0, 0.5, 0.25]).unwrap();
+    let selected = |id: i32| !(id < 100 || id % 7 == 0) && id % 2 == 0;
+    let expected = brute_force(&vectors, &query, 15, selected);
+    for use_index in [true, false] {
+        let (actual, _) = search(&dataset, &query, 15, Some("id % 2 = 0"), use_index).await;
+        assert_same_results(&actual, &expected);
+    }
+}
+
+#[tokio::test]
+async fn test_sparse_search_empty_vectors() {
+    let test_dir = TempStrDir::default();
+    let vectors: Vectors = vec![
+        Some((vec![], vec![])),
+        None,
+        Some((vec![3, 8], vec![0.5, 0.5])),
+        Some((vec![8], vec![2.0])),
+        Some((vec![], vec![])),
+    ];
+    let mut dataset = write(&test_dir, 0, &vectors, WriteMode::Create).await;
+
+    for indexed in [false, true] {
+        if indexed {
+            create_sparse_index(&mut dataset).await;
+        }
+        // Rows that share no dimension with the query are not matches
+        let query = SparseQuery::try_new(&[8, 100], &[1.0, 1.0]).unwrap();
+        let (actual, _) = search(&dataset, &query, 10, None, true).await;
+        assert_eq!(actual, vec![(3, 2.0), (2, 0.5)]);
+
+        let empty = SparseQuery::try_new(&[], &[]).unwrap();
+        let (actual, _) = search(&dataset, &empty, 10, None, true).await;
+        assert!(actual.is_empty());
+    }
+
+    let mut scanner = dataset.scan();
+    let err = scanner.nearest_sparse("id", &[1], &[1.0], 10).unwrap_err();
+    assert_contains!(err.to_string(), "not a sparse vector column");
+    let err = scanner
+        .nearest_sparse("embedding", &[1, 2], &[1.0], 10)
+        .unwrap_err();
+    assert_contains!(err.to_string(), "2 indices but 1 values");
+}
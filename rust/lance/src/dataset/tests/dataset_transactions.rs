@@ -237,6 +237,63 @@ async fn test_migrate_v2_manifest_paths() {
     );
 }
 
+#[tokio::test]
+async fn test_migrate_mixed_manifest_paths() {
+    let test_uri = TempStrDir::default();
+
+    let data = || {
+        lance_datagen::gen_batch()
+            .col("key", array::step::<Int32Type>())
+            .into_reader_rows(RowCount::from(10), BatchCount::from(1))
+    };
+    Dataset::write(data(), &test_uri, None).await.unwrap();
+    let dataset = Dataset::write(
+        data(),
+        &test_uri,
+        Some(WriteParams {
+            mode: WriteMode::Append,
+            ..Default::default()
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(dataset.version().version, 2);
+
+    // Rename the latest manifest to the V1 scheme, as a copy between stores might.
+    let v2_path = ManifestNamingScheme::V2.manifest_path(&dataset.base, 2);
+    let v1_path = ManifestNamingScheme::V1.manifest_path(&dataset.base, 2);
+    dataset.object_store.copy(&v2_path, &v1_path).await.unwrap();
+    dataset.object_store.delete(&v2_path).await.unwrap();
+
+    let err = Dataset::open(&test_uri).await.unwrap_err();
+    assert!(err.to_string().contains("migrate_manifest_paths"), "{err}");
+
+    // A specific version can still be opened to repair the dataset.
+    let mut dataset = DatasetBuilder::from_uri(&test_uri)
+        .with_version(1)
+        .load()
+        .await
+        .unwrap();
+    // Migrating a second time is a no-op.
+    for target in [ManifestNamingScheme::V2, ManifestNamingScheme::V2] {
+        dataset.migrate_manifest_paths(target).await.unwrap();
+        assert_eq!(dataset.version().version, 2);
+        assert_eq!(dataset.manifest_location().naming_scheme, target);
+    }
+    dataset
+        .migrate_manifest_paths(ManifestNamingScheme::V1)
+        .await
+        .unwrap();
+
+    let dataset = Dataset::open(&test_uri).await.unwrap();
+    assert_eq!(dataset.version().version, 2);
+    assert_eq!(
+        dataset.manifest_location().naming_scheme,
+        ManifestNamingScheme::V1
+    );
+    assert_eq!(dataset.count_rows(None).await.unwrap(), 20);
+}
+
 pub(super) async fn execute_sql(
     sql: &str,
     table: String,
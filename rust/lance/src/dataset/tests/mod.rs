@@ -4,14 +4,19 @@
 #[cfg(feature = "substrait")]
 mod dataset_aggregate;
 mod dataset_common;
+mod dataset_compatibility;
 mod dataset_concurrency_store;
+mod dataset_encryption;
 #[cfg(feature = "geo")]
 mod dataset_geo;
 mod dataset_index;
 mod dataset_io;
 mod dataset_merge_update;
 mod dataset_migrations;
+mod dataset_missing_columns;
+mod dataset_query_defaults;
 mod dataset_scanner;
 mod dataset_schema_evolution;
+mod dataset_sparse;
 mod dataset_transactions;
 mod dataset_versioning;
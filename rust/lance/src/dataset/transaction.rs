@@ -13,6 +13,7 @@
 //! [Transaction Specification](https://lance.org/format/table/transaction/#transaction-types).
 
 use super::ManifestWriteConfig;
+use super::write::clustering::retain_clustering;
 use super::write::merge_insert::inserted_rows::KeyExistenceFilter;
 use crate::dataset::transaction::UpdateMode::{RewriteColumns, RewriteRows};
 use crate::index::mem_wal::update_mem_wal_index_merged_generations;
@@ -1435,6 +1436,18 @@ impl Operation {
         }
     }
 
+    /// Whether this operation updates schema or field metadata without touching data
+    pub(crate) fn modifies_schema_metadata(&self) -> bool {
+        matches!(
+            self,
+            Self::UpdateConfig {
+                schema_metadata_updates,
+                field_metadata_updates,
+                ..
+            } if schema_metadata_updates.is_some() || !field_metadata_updates.is_empty()
+        )
+    }
+
     pub(crate) fn modifies_same_metadata(&self, other: &Self) -> bool {
         match (self, other) {
             (
@@ -2511,6 +2524,8 @@ impl Transaction {
             _ => {}
         }
 
+        retain_clustering(&mut manifest, current_manifest, self)?;
+
         // Handle UpdateBases operation to update manifest base_paths
         if let Operation::UpdateBases { new_bases } = &self.operation {
             // Validate and add new base paths to the manifest
@@ -0,0 +1,524 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! The history of a dataset as a table
+//!
+//! [`Dataset::transaction_log`] returns one row per version with the columns of
+//! [`TRANSACTION_LOG_SCHEMA`]:
+//!
+//! * `version`, `timestamp` and `manifest_size` come from the manifest and are always
+//!   set.
+//! * `operation`, `transaction_uuid`, `read_version` and `properties` come from the
+//!   transaction that created the version.  They are null when the transaction is no
+//!   longer available, e.g. the transaction file of an old dataset was removed.
+//! * `fragments_added`, `fragments_removed`, `fragments_updated`, `rows_added` and
+//!   `rows_deleted` are derived by comparing the manifest with the one of the
+//!   previous version.  They are null when the previous manifest was cleaned up, and
+//!   the row counts are also null when a fragment doesn't record its row count.  Rows
+//!   that are rewritten into new fragments, e.g. by an update, count as both deleted
+//!   and added, except for compaction, which doesn't change any rows.
+
+use std::collections::HashMap;
+use std::ops::RangeBounds;
+use std::sync::{Arc, LazyLock};
+
+use arrow_array::builder::{MapBuilder, StringBuilder};
+use arrow_array::{ArrayRef, RecordBatch, StringArray, TimestampMicrosecondArray, UInt64Array};
+use arrow_schema::{DataType, Field, Fields, Schema, SchemaRef, TimeUnit};
+use futures::stream::{self, BoxStream};
+use futures::{StreamExt, TryStreamExt};
+use lance_core::Result;
+use lance_core::error::ErrorClass;
+use lance_io::object_store::ObjectStore;
+use lance_io::utils::read_message;
+use lance_table::format::{Fragment, Manifest, pb};
+use lance_table::io::commit::ManifestLocation;
+use lance_table::io::manifest::read_manifest;
+use object_store::path::Path;
+use prost::Message;
+
+use super::transaction::{Operation, Transaction};
+use crate::Dataset;
+
+/// The number of versions in each batch of the transaction log
+const BATCH_SIZE: usize = 256;
+
+pub static TRANSACTION_LOG_SCHEMA: LazyLock<SchemaRef> = LazyLock::new(|| {
+    let properties = DataType::Map(
+        Arc::new(Field::new(
+            "entries",
+            DataType::Struct(Fields::from(vec![
+                Field::new("keys", DataType::Utf8, false),
+                Field::new("values", DataType::Utf8, true),
+            ])),
+            false,
+        )),
+        false,
+    );
+    Arc::new(Schema::new(vec![
+        Field::new("version", DataType::UInt64, false),
+        Field::new(
+            "timestamp",
+            DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            false,
+        ),
+        Field::new("operation", DataType::Utf8, true),
+        Field::new("transaction_uuid", DataType::Utf8, true),
+        Field::new("read_version", DataType::UInt64, true),
+        Field::new("fragments_added", DataType::UInt64, true),
+        Field::new("fragments_removed", DataType::UInt64, true),
+        Field::new("fragments_updated", DataType::UInt64, true),
+        Field::new("rows_added", DataType::UInt64, true),
+        Field::new("rows_deleted", DataType::UInt64, true),
+        Field::new("properties", properties, true),
+        Field::new("manifest_size", DataType::UInt64, false),
+    ]))
+});
+
+/// A manifest and, if it is in the requested range, the transaction that created it
+struct VersionRecord {
+    manifest: Manifest,
+    manifest_size: u64,
+    transaction: Option<Transaction>,
+    in_range: bool,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct FragmentChanges {
+    fragments_added: u64,
+    fragments_removed: u64,
+    fragments_updated: u64,
+    rows_added: Option<u64>,
+    rows_deleted: Option<u64>,
+}
+
+fn live_rows<'a>(fragments: impl IntoIterator<Item = &'a Fragment>) -> Option<u64> {
+    fragments
+        .into_iter()
+        .map(|fragment| fragment.num_rows().map(|rows| rows as u64))
+        .sum()
+}
+
+impl FragmentChanges {
+    fn between(previous: &[Fragment], current: &[Fragment], operation: Option<&Operation>) -> Self {
+        // An overwrite starts the fragment ids over, so they can't be matched up
+        if matches!(operation, Some(Operation::Overwrite { .. })) {
+            return Self {
+                fragments_added: current.len() as u64,
+                fragments_removed: previous.len() as u64,
+                fragments_updated: 0,
+                rows_added: live_rows(current),
+                rows_deleted: live_rows(previous),
+            };
+        }
+
+        let previous_by_id = previous
+            .iter()
+            .map(|fragment| (fragment.id, fragment))
+            .collect::<HashMap<_, _>>();
+        let mut changes = Self::default();
+        let mut added = Vec::new();
+        let mut retained = 0;
+        for fragment in current {
+            match previous_by_id.get(&fragment.id) {
+                None => added.push(fragment),
+                Some(previous) => {
+                    retained += 1;
+                    if *previous != fragment {
+                        changes.fragments_updated += 1;
+                    }
+                }
+            }
+        }
+        changes.fragments_added = added.len() as u64;
+        changes.fragments_removed = (previous.len() - retained) as u64;
+
+        if matches!(operation, Some(Operation::Rewrite { .. })) {
+            changes.rows_added = Some(0);
+            changes.rows_deleted = Some(0);
+        } else if let (Some(previous_rows), Some(current_rows), Some(added_rows)) =
+            (live_rows(previous), live_rows(current), live_rows(added))
+        {
+            changes.rows_added = Some(added_rows);
+            changes.rows_deleted = Some(previous_rows.saturating_sub(current_rows - added_rows));
+        }
+        changes
+    }
+}
+
+/// Read the transaction that created the version of `manifest`
+///
+/// Returns `None` if the manifest doesn't have an inline transaction and its
+/// transaction file is missing.
+async fn read_version_transaction(
+    object_store: &ObjectStore,
+    transactions_dir: &Path,
+    location: &ManifestLocation,
+    manifest: &Manifest,
+) -> Result<Option<Transaction>> {
+    if let Some(pos) = manifest.transaction_section {
+        let reader = if let Some(size) = location.size {
+            object_store
+                .open_with_size(&location.path, size as usize)
+                .await?
+        } else {
+            object_store.open(&location.path).await?
+        };
+        let transaction: pb::Transaction = read_message(reader.as_ref(), pos).await?;
+        Transaction::try_from(transaction).map(Some)
+    } else if let Some(file) = &manifest.transaction_file {
+        let path = transactions_dir.child(file.as_str());
+        match object_store.read_one_all(&path).await {
+            Ok(data) => Transaction::try_from(pb::Transaction::decode(data)?).map(Some),
+            Err(err) if err.class() == ErrorClass::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    } else {
+        Ok(None)
+    }
+}
+
+pub(super) async fn transaction_log(
+    dataset: &Dataset,
+    versions: impl RangeBounds<u64>,
+) -> Result<BoxStream<'static, Result<RecordBatch>>> {
+    // The manifest before each version is read too, to tell what the version changed
+    let mut locations = dataset
+        .commit_handler
+        .list_manifest_locations(&dataset.base, &dataset.object_store, false)
+        .try_filter(|location| {
+            futures::future::ready(
+                versions.contains(&location.version) || versions.contains(&(location.version + 1)),
+            )
+        })
+        .map_ok(|location| {
+            let in_range = versions.contains(&location.version);
+            (location, in_range)
+        })
+        .try_collect::<Vec<_>>()
+        .await?;
+    locations.sort_by_key(|(location, _)| location.version);
+
+    let object_store = dataset.object_store.clone();
+    let transactions_dir = dataset.transactions_dir();
+    let io_parallelism = object_store.io_parallelism();
+    let records = stream::iter(locations)
+        .map(move |(location, in_range)| {
+            let object_store = object_store.clone();
+            let transactions_dir = transactions_dir.clone();
+            async move {
+                let manifest_size = match location.size {
+                    Some(size) => size,
+                    None => object_store.size(&location.path).await?,
+                };
+                let manifest =
+                    read_manifest(&object_store, &location.path, Some(manifest_size)).await?;
+                let transaction = if in_range {
+                    read_version_transaction(&object_store, &transactions_dir, &location, &manifest)
+                        .await?
+                } else {
+                    None
+                };
+                Ok(VersionRecord {
+                    manifest,
+                    manifest_size,
+                    transaction,
+                    in_range,
+                })
+            }
+        })
+        .buffered(io_parallelism);
+
+    let mut previous: Option<Arc<Vec<Fragment>>> = None;
+    let mut previous_version = None;
+    let rows = records.try_filter_map(move |record| {
+        let version = record.manifest.version;
+        let current = record.manifest.fragments.clone();
+        let is_first = version == 1
+            || record
+                .transaction
+                .as_ref()
+                .is_some_and(|transaction| transaction.read_version == 0);
+        let changes = match &previous {
+            Some(previous) if previous_version == version.checked_sub(1) => {
+                Some(previous.as_slice())
+            }
+            _ if is_first => Some(&[] as &[Fragment]),
+            _ => None,
+        }
+        .map(|previous| {
+            let operation = record
+                .transaction
+                .as_ref()
+                .map(|transaction| &transaction.operation);
+            FragmentChanges::between(previous, &current, operation)
+        });
+        previous = Some(current);
+        previous_version = Some(version);
+        futures::future::ready(Ok(record.in_range.then_some((record, changes))))
+    });
+
+    Ok(rows
+        .try_chunks(BATCH_SIZE)
+        .map_err(|err| err.1)
+        .and_then(|rows| futures::future::ready(build_batch(rows)))
+        .boxed())
+}
+
+fn build_batch(rows: Vec<(VersionRecord, Option<FragmentChanges>)>) -> Result<RecordBatch> {
+    let mut properties = MapBuilder::new(None, StringBuilder::new(), StringBuilder::new());
+    for (record, _) in &rows {
+        match record
+            .transaction
+            .as_ref()
+            .and_then(|transaction| transaction.transaction_properties.as_ref())
+        {
+            Some(entries) => {
+                let mut entries = entries.iter().collect::<Vec<_>>();
+                entries.sort();
+                for (key, value) in entries {
+                    properties.keys().append_value(key);
+                    properties.values().append_value(value);
+                }
+                properties.append(true)?;
+            }
+            None => properties.append(false)?,
+        }
+    }
+
+    let transactions = rows
+        .iter()
+        .map(|(record, _)| record.transaction.as_ref())
+        .collect::<Vec<_>>();
+    let changes = |value: fn(&FragmentChanges) -> Option<u64>| -> ArrayRef {
+        Arc::new(UInt64Array::from_iter(
+            rows.iter()
+                .map(|(_, changes)| changes.as_ref().and_then(value)),
+        ))
+    };
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(UInt64Array::from_iter_values(
+            rows.iter().map(|(record, _)| record.manifest.version),
+        )),
+        Arc::new(
+            TimestampMicrosecondArray::from_iter_values(
+                rows.iter()
+                    .map(|(record, _)| record.manifest.timestamp().timestamp_micros()),
+            )
+            .with_timezone("UTC"),
+        ),
+        Arc::new(StringArray::from_iter(transactions.iter().map(
+            |transaction| transaction.map(|transaction| transaction.operation.name()),
+        ))),
+        Arc::new(StringArray::from_iter(transactions.iter().map(
+            |transaction| transaction.map(|transaction| transaction.uuid.as_str()),
+        ))),
+        Arc::new(UInt64Array::from_iter(transactions.iter().map(
+            |transaction| transaction.map(|transaction| transaction.read_version),
+        ))),
+        changes(|changes| Some(changes.fragments_added)),
+        changes(|changes| Some(changes.fragments_removed)),
+        changes(|changes| Some(changes.fragments_updated)),
+        changes(|changes| changes.rows_added),
+        changes(|changes| changes.rows_deleted),
+        Arc::new(properties.finish()),
+        Arc::new(UInt64Array::from_iter_values(
+            rows.iter().map(|(record, _)| record.manifest_size),
+        )),
+    ];
+    Ok(RecordBatch::try_new(
+        TRANSACTION_LOG_SCHEMA.clone(),
+        columns,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Int32Type, UInt64Type};
+    use arrow_array::{Array, RecordBatchReader};
+    use lance_datagen::{BatchCount, RowCount, array};
+
+    use super::*;
+    use crate::dataset::cleanup::CleanupPolicy;
+    use crate::dataset::optimize::{CompactionOptions, compact_files};
+    use crate::dataset::{InsertBuilder, WriteMode, WriteParams};
+    use crate::utils::test::copy_test_data_to_tmp;
+
+    fn data(start: i32, num_rows: u64) -> impl RecordBatchReader + Send + 'static {
+        lance_datagen::gen_batch()
+            .col("x", array::step_custom::<Int32Type>(start, 1))
+            .into_reader_rows(RowCount::from(num_rows), BatchCount::from(1))
+    }
+
+    async fn collect_log(dataset: &Dataset, versions: impl RangeBounds<u64>) -> RecordBatch {
+        let batches = dataset
+            .transaction_log(versions)
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        arrow_select::concat::concat_batches(&TRANSACTION_LOG_SCHEMA, &batches).unwrap()
+    }
+
+    fn u64s(log: &RecordBatch, column: &str) -> Vec<Option<u64>> {
+        log[column].as_primitive::<UInt64Type>().iter().collect()
+    }
+
+    fn strings(log: &RecordBatch, column: &str) -> Vec<Option<String>> {
+        log[column]
+            .as_string::<i32>()
+            .iter()
+            .map(|value| value.map(str::to_string))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_transaction_log_contents() {
+        let properties = HashMap::from([
+            ("job_id".to_string(), "ingest-42".to_string()),
+            ("user".to_string(), "etl".to_string()),
+        ]);
+        let mut dataset = Dataset::write(
+            data(0, 100),
+            "memory://log",
+            Some(WriteParams {
+                max_rows_per_file: 50,
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+        dataset
+            .append(
+                data(100, 20),
+                Some(WriteParams::default().with_transaction_properties(properties)),
+            )
+            .await
+            .unwrap();
+        dataset.delete("x < 10 OR x >= 115").await.unwrap();
+        compact_files(&mut dataset, CompactionOptions::default(), None)
+            .await
+            .unwrap();
+        let dataset = InsertBuilder::new(Arc::new(dataset))
+            .with_params(&WriteParams {
+                mode: WriteMode::Overwrite,
+                ..Default::default()
+            })
+            .execute_stream(Box::new(data(0, 7)) as Box<dyn RecordBatchReader + Send>)
+            .await
+            .unwrap();
+
+        let log = collect_log(&dataset, ..).await;
+        assert_eq!(log.schema(), *TRANSACTION_LOG_SCHEMA);
+        assert_eq!(u64s(&log, "version"), [1, 2, 3, 4, 5].map(Some));
+        assert_eq!(
+            strings(&log, "operation"),
+            ["Overwrite", "Append", "Delete", "Rewrite", "Overwrite"]
+                .map(|op| Some(op.to_string()))
+        );
+        assert_eq!(u64s(&log, "read_version"), [0, 1, 2, 3, 4].map(Some));
+        assert_eq!(u64s(&log, "rows_added"), [100, 20, 0, 0, 7].map(Some));
+        assert_eq!(u64s(&log, "rows_deleted"), [0, 0, 15, 0, 105].map(Some));
+        assert_eq!(u64s(&log, "fragments_added"), [2, 1, 0, 1, 1].map(Some));
+        assert_eq!(u64s(&log, "fragments_removed"), [0, 0, 0, 3, 1].map(Some));
+        assert_eq!(u64s(&log, "fragments_updated"), [0, 0, 2, 0, 0].map(Some));
+        assert!(log["transaction_uuid"].null_count() == 0);
+        assert!(
+            u64s(&log, "manifest_size")
+                .iter()
+                .all(|size| size.unwrap() > 0)
+        );
+        let timestamps = log["timestamp"]
+            .as_primitive::<arrow_array::types::TimestampMicrosecondType>()
+            .values();
+        assert!(timestamps.windows(2).all(|pair| pair[0] <= pair[1]));
+
+        // Writer supplied properties round trip through the commit
+        let properties = log["properties"].as_map();
+        assert_eq!(properties.null_count(), 4);
+        assert!(properties.is_valid(1));
+        let entries = properties.value(1);
+        let keys = entries.column(0).as_string::<i32>();
+        let values = entries.column(1).as_string::<i32>();
+        assert_eq!(
+            keys.iter().collect::<Vec<_>>(),
+            [Some("job_id"), Some("user")]
+        );
+        assert_eq!(
+            values.iter().collect::<Vec<_>>(),
+            [Some("ingest-42"), Some("etl")]
+        );
+
+        let range = collect_log(&dataset, 2..4).await;
+        assert_eq!(u64s(&range, "version"), [2, 3].map(Some));
+        // The previous version is read to tell what the first one in range changed
+        assert_eq!(u64s(&range, "rows_added"), [20, 0].map(Some));
+        assert_eq!(collect_log(&dataset, 6..).await.num_rows(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_transaction_log_after_cleanup() {
+        let mut dataset = Dataset::write(data(0, 50), "memory://cleaned", None)
+            .await
+            .unwrap();
+        dataset.append(data(50, 50), None).await.unwrap();
+        dataset.delete("x < 60").await.unwrap();
+        dataset.append(data(100, 10), None).await.unwrap();
+        dataset
+            .cleanup_with_policy(CleanupPolicy {
+                before_version: Some(3),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        // Versions that were cleaned up are gone, and nothing is known about what the
+        // oldest remaining version changed
+        let log = collect_log(&dataset, ..).await;
+        assert_eq!(u64s(&log, "version"), [3, 4].map(Some));
+        assert_eq!(
+            strings(&log, "operation"),
+            ["Delete", "Append"].map(|op| Some(op.to_string()))
+        );
+        assert_eq!(u64s(&log, "rows_deleted"), [None, Some(0)]);
+        assert_eq!(u64s(&log, "rows_added"), [None, Some(10)]);
+        assert_eq!(u64s(&log, "fragments_added"), [None, Some(1)]);
+    }
+
+    #[tokio::test]
+    async fn test_transaction_log_missing_transaction_file() {
+        // Written before transactions were stored in the manifest
+        let test_dir = copy_test_data_to_tmp("v0.27.1/pq_in_schema").unwrap();
+        let transactions_dir = test_dir.std_path().join("_transactions");
+        for entry in std::fs::read_dir(&transactions_dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with("1-")
+            {
+                std::fs::remove_file(path).unwrap();
+            }
+        }
+        let dataset = Dataset::open(&test_dir.path_str()).await.unwrap();
+
+        let log = collect_log(&dataset, ..).await;
+        assert_eq!(u64s(&log, "version"), [1, 2].map(Some));
+        let operations = strings(&log, "operation");
+        assert!(operations[0].is_some());
+        assert_eq!(operations[1], None);
+        assert_eq!(log["transaction_uuid"].null_count(), 1);
+        assert_eq!(log["properties"].null_count(), 2);
+        // The manifest derived columns are still there
+        assert_eq!(log["timestamp"].null_count(), 0);
+        assert!(
+            u64s(&log, "manifest_size")
+                .iter()
+                .all(|size| size.unwrap() > 0)
+        );
+        assert!(u64s(&log, "fragments_added")[1].is_some());
+    }
+}
@@ -151,6 +151,7 @@ impl Updater {
             &schema,
             &self.fragment.dataset().base,
             data_storage_version,
+            self.fragment.dataset().key_provider(),
         )
         .await
     }
@@ -11,14 +11,18 @@ use lance_core::datatypes::{
     NullabilityComparison, OnMissing, OnTypeMismatch, SchemaCompareOptions,
 };
 use lance_core::error::LanceOptionExt;
+use lance_core::utils::encryption::{ENCRYPTION_KEY_ID_META_KEY, KeyProvider};
+use lance_core::utils::resources::reserve_memory;
 use lance_core::utils::tempfile::TempDir;
 use lance_core::utils::tracing::{AUDIT_MODE_CREATE, AUDIT_TYPE_DATA, TRACE_FILE_AUDIT};
 use lance_core::{Error, Result, datatypes::Schema};
 use lance_datafusion::chunker::{break_stream, chunk_stream};
 use lance_datafusion::spill::{SpillReceiver, SpillSender, create_replay_spill};
 use lance_datafusion::utils::StreamingWriteSource;
+use lance_encoding::encodings::physical::custom::CustomEncodingRegistry;
 use lance_file::previous::writer::{
-    FileWriter as PreviousFileWriter, ManifestProvider as PreviousManifestProvider,
+    FileWriter as PreviousFileWriter, FileWriterOptions as PreviousFileWriterOptions,
+    ManifestProvider as PreviousManifestProvider,
 };
 use lance_file::version::LanceFileVersion;
 use lance_file::writer::{self as current_writer, FileWriterOptions};
@@ -42,20 +46,31 @@ use crate::session::Session;
 use super::DATA_DIR;
 use super::fragment::write::generate_random_filename;
 use super::progress::{NoopFragmentWriteProgress, WriteFragmentProgress};
+use super::scanner::ColumnOrdering;
 use super::transaction::Transaction;
 use super::utils::SchemaAdapter;
 
+pub(crate) mod clustering;
 mod commit;
 pub mod delete;
+mod distributed;
 mod insert;
 pub mod merge_insert;
+pub mod multi_statement;
+mod open_or_create;
 mod retry;
+mod unique;
 pub mod update;
 
 pub use super::progress::{WriteProgressFn, WriteStats};
+pub use clustering::{CLUSTERING_CONFIG_KEY, UnsortedAppends};
 pub use commit::{CommitBuilder, DEFAULT_COMMIT_TIMEOUT};
 pub use delete::{DeleteBuilder, DeleteResult, UncommittedDelete};
-pub use insert::InsertBuilder;
+pub use distributed::{DistributedWriteSession, FragmentMetadata, WriterTicket};
+pub use insert::{InsertBuilder, SKIPPED_INDEXES_PROPERTY};
+pub use lance_table::io::deletion::{DeletionFileEncoding, DeletionFileOptions};
+pub use open_or_create::SchemaCompatibility;
+pub use unique::DuplicateKeyPolicy;
 
 /// The destination to write data to.
 #[derive(Debug, Clone)]
@@ -158,6 +173,38 @@ impl TryFrom<&str> for ExternalBlobMode {
     }
 }
 
+/// How an append handles data whose schema differs from the dataset's.
+///
+/// Without one, missing nullable columns and differences in nullability are
+/// allowed and everything else is rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaEvolution {
+    /// Add new top-level nullable columns of the data to the dataset before
+    /// appending. Rows written before have nulls in them.
+    AllowNewColumns,
+    /// Cast columns whose type can be widened without loss to the type in the
+    /// dataset, e.g. int32 to int64 or utf8 to large_utf8.
+    CoerceTypes,
+    /// Reject every difference, including missing columns and nullability.
+    Strict,
+}
+
+impl TryFrom<&str> for SchemaEvolution {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        match value.to_lowercase().as_str() {
+            "allow_new_columns" => Ok(Self::AllowNewColumns),
+            "coerce_types" => Ok(Self::CoerceTypes),
+            "strict" => Ok(Self::Strict),
+            _ => Err(Error::invalid_input(format!(
+                "Invalid schema evolution: {}",
+                value
+            ))),
+        }
+    }
+}
+
 fn validate_external_blob_write_params(params: &WriteParams) -> Result<()> {
     if params.external_blob_mode == ExternalBlobMode::Ingest
         && params.allow_external_blob_outside_bases
@@ -313,6 +360,61 @@ pub struct WriteParams {
     /// When a pack file reaches this size, a new one is started.
     /// If not set, defaults to 1 GiB.
     pub blob_pack_file_size_threshold: Option<usize>,
+
+    /// How appends handle data whose schema differs from the dataset's, see
+    /// [`SchemaEvolution`]. Has no effect when creating or overwriting.
+    pub schema_evolution: Option<SchemaEvolution>,
+
+    /// If set, the rows written must have unique primary keys, with the
+    /// duplicates handled by the policy. Appends require a BTree or Bitmap
+    /// index on every key column. See [`DuplicateKeyPolicy`].
+    pub enforce_unique: Option<DuplicateKeyPolicy>,
+
+    /// If not empty, creating or overwriting sorts the written rows by these
+    /// columns and records the order as the clustering of the dataset, see
+    /// [`Dataset::clustering`]. Appends to a clustered dataset keep the rows of
+    /// each fragment in that order, according to [`Self::unsorted_appends`], and
+    /// must leave this empty or set to the clustering. Appends to a dataset that
+    /// isn't clustered only sort the written rows.
+    pub sort_by: Vec<ColumnOrdering>,
+
+    /// What an append to a clustered dataset does with its rows. Defaults to
+    /// [`UnsortedAppends::Sort`].
+    pub unsorted_appends: UnsortedAppends,
+
+    /// The memory, in bytes, the sort of a write may use before spilling to
+    /// disk. If not set, the `LANCE_MEM_POOL_SIZE` environment variable or a
+    /// default of 100MiB is used.
+    pub sort_memory_limit: Option<u64>,
+
+    /// If true, overwriting an existing dataset rebuilds its indexes on the new
+    /// data, with the parameters they were built with. Otherwise an overwrite
+    /// drops every index.
+    ///
+    /// Only indexes whose columns keep their name and data type are rebuilt.
+    /// The others are dropped, logged, and listed with the reason in the
+    /// [`SKIPPED_INDEXES_PROPERTY`] transaction property of the overwrite. The
+    /// indexes are rebuilt after the overwrite is committed, so if a rebuild
+    /// fails the new data stays. Has no effect when creating or appending.
+    pub preserve_indexes_on_identical_columns: bool,
+
+    /// If set, the write fails instead of committing a version that needs a
+    /// reader feature above this level, e.g. a newer data storage version, so
+    /// readers running older versions of Lance can still read the dataset.
+    /// See [`lance_table::feature_flags::KNOWN_READER_FEATURES`] for the
+    /// features and their levels.
+    pub max_reader_feature_level: Option<u32>,
+
+    /// If true, min, max and null count statistics of the written columns are
+    /// stored in the data files, see
+    /// [`lance_file::writer::FileWriterOptions::collect_stats`]. Data files of
+    /// the legacy format always store them. Default is false.
+    pub collect_stats: bool,
+
+    /// The max number of bytes of string and binary values kept in the min and
+    /// max statistics. Longer values are truncated and the max value is
+    /// incremented so it remains an upper bound. If not set, 64 bytes are kept.
+    pub stats_prefix_length: Option<usize>,
 }
 
 impl Default for WriteParams {
@@ -342,6 +444,15 @@ impl Default for WriteParams {
             allow_external_blob_outside_bases: false,
             external_blob_mode: ExternalBlobMode::Reference,
             blob_pack_file_size_threshold: None,
+            schema_evolution: None,
+            enforce_unique: None,
+            sort_by: Vec::new(),
+            unsorted_appends: UnsortedAppends::default(),
+            sort_memory_limit: None,
+            preserve_indexes_on_identical_columns: false,
+            max_reader_feature_level: None,
+            collect_stats: false,
+            stats_prefix_length: None,
         }
     }
 }
@@ -367,6 +478,26 @@ impl WriteParams {
             .unwrap_or_default()
     }
 
+    /// The key provider of the store params, or else the one of the session
+    pub fn key_provider(&self) -> Option<Arc<dyn KeyProvider>> {
+        self.store_params
+            .as_ref()
+            .and_then(|store_params| store_params.key_provider.clone())
+            .or_else(|| {
+                self.session
+                    .as_ref()
+                    .and_then(|session| session.key_provider().cloned())
+            })
+    }
+
+    /// The custom encodings registered on the session, see
+    /// [`Session::with_custom_encodings`]
+    pub fn custom_encodings(&self) -> Option<CustomEncodingRegistry> {
+        self.session
+            .as_ref()
+            .and_then(|session| session.custom_encodings().cloned())
+    }
+
     /// Set exact runtime object store params for a registered base path.
     ///
     /// These params are used as-is for that base. The write-level default
@@ -532,6 +663,17 @@ pub async fn do_write_fragments(
         source_store_registry,
         source_store_params,
         params.blob_pack_file_size_threshold,
+    )
+    .with_key_provider(
+        params
+            .key_provider()
+            .or_else(|| dataset.and_then(|dataset| dataset.key_provider())),
+    )
+    .with_stats(params.collect_stats, params.stats_prefix_length)
+    .with_custom_encodings(
+        params
+            .custom_encodings()
+            .or_else(|| dataset.and_then(|dataset| dataset.session.custom_encodings().cloned())),
     );
     let mut writer: Option<Box<dyn GenericWriter>> = None;
     let mut num_rows_in_current_file = 0;
@@ -545,6 +687,13 @@ pub async fn do_write_fragments(
     let loop_result: Result<()> = async {
         while let Some(batch_chunk) = buffered_reader.next().await {
             let batch_chunk = batch_chunk?;
+            // The decoded batches are held until the writer has encoded them
+            let _batch_memory = reserve_memory(
+                batch_chunk
+                    .iter()
+                    .map(RecordBatch::get_array_memory_size)
+                    .sum(),
+            );
 
             if writer.is_none() {
                 let (new_writer, new_fragment) = writer_generator.new_writer().await?;
@@ -1125,6 +1274,7 @@ pub async fn open_writer(
     schema: &Schema,
     base_dir: &Path,
     storage_version: LanceFileVersion,
+    key_provider: Option<Arc<dyn KeyProvider>>,
 ) -> Result<Box<dyn GenericWriter>> {
     open_writer_with_options(
         object_store,
@@ -1133,6 +1283,7 @@ pub async fn open_writer(
         storage_version,
         WriterOptions {
             add_data_dir: true,
+            key_provider,
             ..Default::default()
         },
     )
@@ -1149,6 +1300,10 @@ struct WriterOptions {
     source_store_registry: Arc<ObjectStoreRegistry>,
     source_store_params: ObjectStoreParams,
     blob_pack_file_size_threshold: Option<usize>,
+    key_provider: Option<Arc<dyn KeyProvider>>,
+    collect_stats: bool,
+    stats_prefix_length: Option<usize>,
+    custom_encodings: Option<CustomEncodingRegistry>,
 }
 
 async fn open_writer_with_options(
@@ -1167,6 +1322,10 @@ async fn open_writer_with_options(
         source_store_registry,
         source_store_params,
         blob_pack_file_size_threshold,
+        key_provider,
+        collect_stats,
+        stats_prefix_length,
+        custom_encodings,
     } = options;
 
     let data_file_key = generate_random_filename();
@@ -1180,13 +1339,28 @@ async fn open_writer_with_options(
 
     let full_path = data_dir.clone().join(filename.as_str());
 
+    if storage_version == LanceFileVersion::Legacy
+        && let Some(field) = schema
+            .fields
+            .iter()
+            .find(|field| field.metadata.contains_key(ENCRYPTION_KEY_ID_META_KEY))
+    {
+        return Err(Error::not_supported(format!(
+            "Cannot encrypt the field `{}`, the legacy file format does not support encryption",
+            field.name
+        )));
+    }
+
     let writer = if storage_version == LanceFileVersion::Legacy {
         Box::new(V1WriterAdapter {
             writer: PreviousFileWriter::<ManifestDescribing>::try_new(
                 object_store,
                 &full_path,
                 schema.clone(),
-                &Default::default(),
+                &PreviousFileWriterOptions {
+                    stats_prefix_length,
+                    ..Default::default()
+                },
             )
             .await?,
             path: filename,
@@ -1200,6 +1374,10 @@ async fn open_writer_with_options(
             schema.clone(),
             FileWriterOptions {
                 format_version: Some(storage_version),
+                key_provider,
+                collect_stats,
+                stats_prefix_length,
+                custom_encodings,
                 ..Default::default()
             },
         )?;
@@ -1258,6 +1436,10 @@ struct WriterGenerator {
     source_store_registry: Arc<ObjectStoreRegistry>,
     source_store_params: ObjectStoreParams,
     blob_pack_file_size_threshold: Option<usize>,
+    key_provider: Option<Arc<dyn KeyProvider>>,
+    collect_stats: bool,
+    stats_prefix_length: Option<usize>,
+    custom_encodings: Option<CustomEncodingRegistry>,
     /// Counter for round-robin selection
     next_base_index: AtomicUsize,
 }
@@ -1289,10 +1471,30 @@ impl WriterGenerator {
             source_store_registry,
             source_store_params,
             blob_pack_file_size_threshold,
+            key_provider: None,
+            collect_stats: false,
+            stats_prefix_length: None,
+            custom_encodings: None,
             next_base_index: AtomicUsize::new(0),
         }
     }
 
+    fn with_key_provider(mut self, key_provider: Option<Arc<dyn KeyProvider>>) -> Self {
+        self.key_provider = key_provider;
+        self
+    }
+
+    fn with_stats(mut self, collect_stats: bool, stats_prefix_length: Option<usize>) -> Self {
+        self.collect_stats = collect_stats;
+        self.stats_prefix_length = stats_prefix_length;
+        self
+    }
+
+    fn with_custom_encodings(mut self, custom_encodings: Option<CustomEncodingRegistry>) -> Self {
+        self.custom_encodings = custom_encodings;
+        self
+    }
+
     /// Select the next target base using round-robin strategy.
     /// TODO: In the future, we can develop different strategies for selecting target bases
     fn select_target_base(&self) -> Option<&TargetBaseInfo> {
@@ -1323,6 +1525,10 @@ impl WriterGenerator {
                     source_store_registry: self.source_store_registry.clone(),
                     source_store_params: self.source_store_params.clone(),
                     blob_pack_file_size_threshold: self.blob_pack_file_size_threshold,
+                    key_provider: self.key_provider.clone(),
+                    collect_stats: self.collect_stats,
+                    stats_prefix_length: self.stats_prefix_length,
+                    custom_encodings: self.custom_encodings.clone(),
                 },
             )
             .await?
@@ -1341,6 +1547,10 @@ impl WriterGenerator {
                     source_store_registry: self.source_store_registry.clone(),
                     source_store_params: self.source_store_params.clone(),
                     blob_pack_file_size_threshold: self.blob_pack_file_size_threshold,
+                    key_provider: self.key_provider.clone(),
+                    collect_stats: self.collect_stats,
+                    stats_prefix_length: self.stats_prefix_length,
+                    custom_encodings: self.custom_encodings.clone(),
                 },
             )
             .await?
@@ -1489,15 +1699,26 @@ mod tests {
     use super::*;
     use std::collections::HashMap;
 
-    use arrow_array::{Int32Array, RecordBatchIterator, RecordBatchReader, StructArray};
+    use arrow_array::cast::AsArray;
+    use arrow_array::{
+        Int32Array, RecordBatchIterator, RecordBatchReader, StringArray, StructArray,
+    };
     use arrow_schema::{DataType, Field as ArrowField, Fields, Schema as ArrowSchema};
     use datafusion::{error::DataFusionError, physical_plan::stream::RecordBatchStreamAdapter};
     use datafusion_physical_plan::RecordBatchStream;
     use futures::TryStreamExt;
+    use lance_core::cache::LanceCache;
+    use lance_core::utils::tempfile::TempStrDir;
     use lance_datagen::{BatchCount, RowCount, array, gen_batch};
+    use lance_encoding::constants::{CUSTOM_ENCODING_META_KEY, CUSTOM_ENCODING_PARAMS_META_KEY};
+    use lance_encoding::decoder::DecoderPlugins;
+    use lance_encoding::encodings::physical::custom::CustomEncoding;
     use lance_file::previous::reader::FileReader as PreviousFileReader;
+    use lance_file::reader as current_reader;
     use lance_io::object_store::StorageOptionsAccessor;
+    use lance_io::scheduler::{ScanScheduler, SchedulerConfig};
     use lance_io::traits::Reader;
+    use lance_io::utils::CachedFileSize;
     use lance_table::format::BasePath;
 
     #[test]
@@ -1870,6 +2091,132 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_write_page_stats() {
+        let schema = Arc::new(ArrowSchema::new(vec![ArrowField::new(
+            "name",
+            DataType::Utf8,
+            false,
+        )]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(StringArray::from(vec![
+                "user_000001",
+                "user_000002",
+                "user_000003",
+            ]))],
+        )
+        .unwrap();
+        let data_stream = Box::pin(RecordBatchStreamAdapter::new(
+            schema.clone(),
+            futures::stream::iter(std::iter::once(Ok(batch))),
+        ));
+        let write_params = WriteParams {
+            data_storage_version: Some(LanceFileVersion::V2_1),
+            collect_stats: true,
+            stats_prefix_length: Some(6),
+            ..Default::default()
+        };
+
+        let object_store = Arc::new(ObjectStore::memory());
+        let base_dir = Path::from("test");
+        let (fragments, _) = write_fragments_internal(
+            None,
+            object_store.clone(),
+            &base_dir,
+            Schema::try_from(schema.as_ref()).unwrap(),
+            data_stream,
+            write_params,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let path = base_dir
+            .child(DATA_DIR)
+            .child(fragments[0].files[0].path.as_str());
+        let scheduler =
+            ScanScheduler::new(object_store.clone(), SchedulerConfig::default_for_testing());
+        let file_scheduler = scheduler
+            .open_file(&path, &CachedFileSize::unknown())
+            .await
+            .unwrap();
+        let file_reader = current_reader::FileReader::try_open(
+            file_scheduler,
+            None,
+            Arc::<DecoderPlugins>::default(),
+            &LanceCache::no_cache(),
+            current_reader::FileReaderOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        let stats = file_reader.read_page_stats().await.unwrap().unwrap();
+        let name_stats = stats["0"].as_struct();
+        assert_eq!(
+            name_stats["min_value"].as_string::<i32>(),
+            &StringArray::from(vec!["user_0"])
+        );
+        assert_eq!(
+            name_stats["max_value"].as_string::<i32>(),
+            &StringArray::from(vec!["user_1"])
+        );
+    }
+
+    #[derive(Debug)]
+    struct XorEncoding;
+
+    impl CustomEncoding for XorEncoding {
+        fn encode(&self, data: &[u8], parameters: &[u8]) -> Result<Vec<u8>> {
+            let key = parameters.first().copied().unwrap_or(0xFF);
+            Ok(data.iter().map(|b| b ^ key).collect())
+        }
+
+        fn decode(&self, data: &[u8], parameters: &[u8]) -> Result<Vec<u8>> {
+            self.encode(data, parameters)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_custom_encodings_from_session() {
+        let schema = Arc::new(ArrowSchema::new(vec![
+            ArrowField::new("sequence", DataType::Utf8, true).with_metadata(
+                [
+                    (CUSTOM_ENCODING_META_KEY.to_string(), "xor".to_string()),
+                    (CUSTOM_ENCODING_PARAMS_META_KEY.to_string(), "k".to_string()),
+                ]
+                .into(),
+            ),
+        ]));
+        let sequences = StringArray::from_iter_values((0..1000).map(|i| format!("ACGT{i}TTGCA")));
+        let batch = RecordBatch::try_new(schema.clone(), vec![Arc::new(sequences)]).unwrap();
+
+        let session = Arc::new(Session::default().with_custom_encodings(
+            CustomEncodingRegistry::new().with_encoding("xor", Arc::new(XorEncoding)),
+        ));
+        let test_dir = TempStrDir::default();
+        let dataset = Dataset::write(
+            RecordBatchIterator::new([Ok(batch.clone())], schema.clone()),
+            test_dir.as_str(),
+            Some(WriteParams {
+                data_storage_version: Some(LanceFileVersion::V2_3),
+                session: Some(session),
+                ..Default::default()
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(dataset.scan().try_into_batch().await.unwrap(), batch);
+
+        // Without the codec registered the column can't be decoded
+        let dataset = Dataset::open(test_dir.as_str()).await.unwrap();
+        let err = match dataset.scan().try_into_stream().await {
+            Ok(stream) => stream.try_collect::<Vec<_>>().await.unwrap_err(),
+            Err(err) => err,
+        };
+        assert!(err.to_string().contains("xor"), "{}", err);
+    }
+
     #[tokio::test]
     async fn test_file_v1_schema_order() {
         // Create a schema where fields ids are not in order and contain holes.
@@ -3292,8 +3639,6 @@ mod tests {
 
     #[tokio::test]
     async fn test_cleanup_data_files_on_failed_write() {
-        use lance_core::utils::tempfile::TempStrDir;
-
         let test_dir = TempStrDir::default();
         let test_uri = test_dir.as_str();
 
@@ -3349,7 +3694,6 @@ mod tests {
     async fn test_cleanup_data_files_on_failed_write_multi_file() {
         // Verify cleanup when a failure occurs after one file has already been completed
         // (i.e., max_rows_per_file causes a file boundary before the error).
-        use lance_core::utils::tempfile::TempStrDir;
 
         let test_dir = TempStrDir::default();
         let test_uri = test_dir.as_str();
@@ -3410,8 +3754,6 @@ mod tests {
     /// object store), while same-fragment files with `base_id == None` are deleted.
     #[tokio::test]
     async fn test_cleanup_data_fragments_skips_external_base() {
-        use lance_core::utils::tempfile::TempStrDir;
-
         let test_dir = TempStrDir::default();
         let test_uri = test_dir.as_str();
 
@@ -0,0 +1,794 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Sorting written rows and recording their order in the manifest.
+//!
+//! With [`WriteParams::sort_by`] set, creating or overwriting a dataset sorts the
+//! written rows, spilling to disk when they don't fit in
+//! [`WriteParams::sort_memory_limit`], and records the order in the dataset config
+//! under [`CLUSTERING_CONFIG_KEY`].
+//!
+//! The recorded order, the clustering, is a promise about every fragment: the rows
+//! of each fragment are sorted by it. Fragments are not ordered relative to each
+//! other, since appends and compaction add fragments at the end. Scans ordered by a
+//! prefix of the clustering merge the fragments instead of sorting all the rows, and
+//! the fragments written together cover key ranges that don't overlap, which keeps
+//! their statistics tight enough for pruning.
+//!
+//! Appends to a clustered dataset are sorted by the clustering, or only checked with
+//! [`UnsortedAppends::Flag`]. A write that vouches for the order of the fragments it
+//! adds says so in the `lance.clustering.sorted_by` transaction property. A commit
+//! that adds fragments without vouching for them, or that changes the sort columns,
+//! drops the clustering from the manifest.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_row::{OwnedRow, RowConverter, Rows, SortField};
+use arrow_schema::{Schema as ArrowSchema, SortOptions};
+use datafusion::error::{DataFusionError, Result as DataFusionResult};
+use datafusion::execution::SendableRecordBatchStream;
+use datafusion::physical_expr::{LexOrdering, PhysicalSortExpr};
+use datafusion::physical_plan::sorts::sort::SortExec;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion_physical_expr::expressions::Column;
+use futures::StreamExt;
+use lance_core::datatypes::Schema;
+use lance_datafusion::exec::{
+    ExecutionStatsCallback, LanceExecutionOptions, OneShotExec, execute_plan,
+};
+use lance_table::format::Manifest;
+use serde::{Deserialize, Serialize};
+
+use super::WriteParams;
+use crate::dataset::fragment::FileFragment;
+use crate::dataset::scanner::ColumnOrdering;
+use crate::dataset::transaction::{Operation, Transaction};
+use crate::{Dataset, Error, Result};
+
+/// The dataset config key holding the clustering of the dataset, as a JSON list of
+/// `{"column", "ascending", "nulls_first"}` objects
+pub const CLUSTERING_CONFIG_KEY: &str = "lance.clustering.sort_by";
+
+/// The transaction property through which a write vouches that the fragments it
+/// adds are sorted by the clustering it holds, in the format of the config value
+pub(crate) const SORTED_BY_PROPERTY: &str = "lance.clustering.sorted_by";
+
+/// What an append to a clustered dataset does with its rows, see [`WriteParams::sort_by`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnsortedAppends {
+    /// Sort the appended rows by the clustering of the dataset (default).
+    #[default]
+    Sort,
+    /// Write the rows as given. If they turn out not to be sorted, the append
+    /// flags the dataset as no longer clustered by dropping the clustering from
+    /// the manifest.
+    Flag,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SortKey {
+    column: String,
+    ascending: bool,
+    nulls_first: bool,
+}
+
+/// The config value recording `ordering` as the clustering of a dataset
+pub(crate) fn clustering_config_value(ordering: &[ColumnOrdering]) -> Result<String> {
+    let keys = ordering
+        .iter()
+        .map(|column| SortKey {
+            column: column.column_name.clone(),
+            ascending: column.ascending,
+            nulls_first: column.nulls_first,
+        })
+        .collect::<Vec<_>>();
+    Ok(serde_json::to_string(&keys)?)
+}
+
+fn parse_clustering(value: &str) -> Option<Vec<ColumnOrdering>> {
+    let keys: Vec<SortKey> = serde_json::from_str(value).ok()?;
+    let ordering = keys
+        .into_iter()
+        .map(|key| ColumnOrdering {
+            ascending: key.ascending,
+            nulls_first: key.nulls_first,
+            column_name: key.column,
+        })
+        .collect::<Vec<_>>();
+    (!ordering.is_empty()).then_some(ordering)
+}
+
+fn sort_options(column: &ColumnOrdering) -> SortOptions {
+    SortOptions {
+        descending: !column.ascending,
+        nulls_first: column.nulls_first,
+    }
+}
+
+/// Check that every column of `ordering` is a top-level column of `schema`
+pub(crate) fn validate_sort_by(schema: &ArrowSchema, ordering: &[ColumnOrdering]) -> Result<()> {
+    for column in ordering {
+        if schema.index_of(&column.column_name).is_err() {
+            return Err(Error::invalid_input(format!(
+                "sort_by column '{}' is not a top-level column of the written data",
+                column.column_name
+            )));
+        }
+    }
+    Ok(())
+}
+
+impl Dataset {
+    /// The order the rows of every fragment are sorted by, if the dataset was
+    /// written with [`WriteParams::sort_by`] and no later write broke it
+    pub fn clustering(&self) -> Option<Vec<ColumnOrdering>> {
+        self.manifest
+            .config
+            .get(CLUSTERING_CONFIG_KEY)
+            .and_then(|value| parse_clustering(value))
+    }
+}
+
+/// Sort `stream` by `ordering`, spilling to disk once the sort holds more than
+/// `memory_limit` bytes
+pub(crate) fn sort_stream(
+    stream: SendableRecordBatchStream,
+    ordering: &[ColumnOrdering],
+    memory_limit: Option<u64>,
+    execution_stats_callback: Option<ExecutionStatsCallback>,
+) -> Result<SendableRecordBatchStream> {
+    let schema = stream.schema();
+    validate_sort_by(&schema, ordering)?;
+    let sort_exprs = ordering
+        .iter()
+        .map(|column| {
+            Ok(PhysicalSortExpr {
+                expr: Arc::new(Column::new_with_schema(&column.column_name, &schema)?),
+                options: sort_options(column),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let Some(sort_exprs) = LexOrdering::new(sort_exprs) else {
+        return Ok(stream);
+    };
+    let plan = Arc::new(SortExec::new(
+        sort_exprs,
+        Arc::new(OneShotExec::new(stream)),
+    ));
+    execute_plan(
+        plan,
+        LanceExecutionOptions {
+            use_spilling: true,
+            mem_pool_size: memory_limit,
+            execution_stats_callback,
+            ..Default::default()
+        },
+    )
+}
+
+/// The order a write sorted its rows by, or checked them against
+pub(crate) struct SortedWrite {
+    clustering: String,
+    /// Set for an append in [`UnsortedAppends::Flag`] mode, cleared once the rows
+    /// turn out not to be sorted
+    in_order: Option<Arc<AtomicBool>>,
+}
+
+impl SortedWrite {
+    /// The config value of the clustering
+    pub(crate) fn clustering(&self) -> &str {
+        &self.clustering
+    }
+
+    /// The clustering, if the written rows are sorted by it
+    ///
+    /// Only final once the written stream is exhausted.
+    pub(crate) fn sorted_by(&self) -> Option<&str> {
+        let in_order = self
+            .in_order
+            .as_ref()
+            .is_none_or(|in_order| in_order.load(Ordering::Relaxed));
+        in_order.then_some(self.clustering.as_str())
+    }
+}
+
+/// Add the property vouching for `sorted_by` to the properties of a transaction
+pub(crate) fn vouch_sorted_by(
+    properties: Option<Arc<HashMap<String, String>>>,
+    sorted_by: Option<&str>,
+) -> Option<Arc<HashMap<String, String>>> {
+    let Some(sorted_by) = sorted_by else {
+        return properties;
+    };
+    let mut properties = properties.as_deref().cloned().unwrap_or_default();
+    properties.insert(SORTED_BY_PROPERTY.to_string(), sorted_by.to_string());
+    Some(Arc::new(properties))
+}
+
+/// Apply [`WriteParams::sort_by`] to a write, or the clustering of `appending_to` to
+/// an append
+///
+/// Returns the order the written rows are sorted by, which a create or overwrite
+/// records as the clustering and an append vouches for. An append to a dataset
+/// that isn't clustered is sorted but returns nothing, since the fragments of the
+/// dataset are not known to be sorted.
+pub(crate) fn cluster_write(
+    stream: SendableRecordBatchStream,
+    params: &WriteParams,
+    appending_to: Option<&Dataset>,
+) -> Result<(SendableRecordBatchStream, Option<SortedWrite>)> {
+    let Some(clustering) = appending_to.and_then(|dataset| dataset.clustering()) else {
+        if params.sort_by.is_empty() {
+            return Ok((stream, None));
+        }
+        let stream = sort_stream(stream, &params.sort_by, params.sort_memory_limit, None)?;
+        let sorted = match appending_to {
+            Some(_) => None,
+            None => Some(SortedWrite {
+                clustering: clustering_config_value(&params.sort_by)?,
+                in_order: None,
+            }),
+        };
+        return Ok((stream, sorted));
+    };
+
+    let value = clustering_config_value(&clustering)?;
+    if !params.sort_by.is_empty() && clustering_config_value(&params.sort_by)? != value {
+        return Err(Error::invalid_input(format!(
+            "sort_by of an append must be empty or match the clustering of the dataset, {value}"
+        )));
+    }
+    match params.unsorted_appends {
+        UnsortedAppends::Sort => {
+            let stream = sort_stream(stream, &clustering, params.sort_memory_limit, None)?;
+            let sorted = SortedWrite {
+                clustering: value,
+                in_order: None,
+            };
+            Ok((stream, Some(sorted)))
+        }
+        UnsortedAppends::Flag => {
+            let (stream, in_order) = check_sorted(stream, &clustering)?;
+            let sorted = SortedWrite {
+                clustering: value,
+                in_order: Some(in_order),
+            };
+            Ok((stream, Some(sorted)))
+        }
+    }
+}
+
+/// Compares rows by the columns of an ordering
+struct SortKeyConverter {
+    columns: Vec<String>,
+    converter: RowConverter,
+}
+
+impl SortKeyConverter {
+    fn try_new(schema: &ArrowSchema, ordering: &[ColumnOrdering]) -> Result<Self> {
+        validate_sort_by(schema, ordering)?;
+        let fields = ordering
+            .iter()
+            .map(|column| {
+                let data_type = schema.field_with_name(&column.column_name)?.data_type();
+                Ok(SortField::new_with_options(
+                    data_type.clone(),
+                    sort_options(column),
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            columns: ordering.iter().map(|c| c.column_name.clone()).collect(),
+            converter: RowConverter::new(fields)?,
+        })
+    }
+
+    fn convert(&self, batch: &RecordBatch) -> Result<Rows> {
+        let columns =
+            self.columns
+                .iter()
+                .map(|name| {
+                    batch.column_by_name(name).cloned().ok_or_else(|| {
+                        Error::invalid_input(format!("missing sort column '{name}'"))
+                    })
+                })
+                .collect::<Result<Vec<ArrayRef>>>()?;
+        Ok(self.converter.convert_columns(&columns)?)
+    }
+}
+
+/// Pass `stream` through, recording whether its rows are sorted by `ordering`
+///
+/// The returned flag is only final once the stream is exhausted.
+pub(crate) fn check_sorted(
+    stream: SendableRecordBatchStream,
+    ordering: &[ColumnOrdering],
+) -> Result<(SendableRecordBatchStream, Arc<AtomicBool>)> {
+    let schema = stream.schema();
+    let converter = SortKeyConverter::try_new(&schema, ordering)?;
+    let in_order = Arc::new(AtomicBool::new(true));
+    let in_order_ref = in_order.clone();
+    let mut last: Option<OwnedRow> = None;
+    let checked = stream.map(move |batch: DataFusionResult<RecordBatch>| {
+        let batch = batch?;
+        if batch.num_rows() > 0 && in_order_ref.load(Ordering::Relaxed) {
+            let rows = converter
+                .convert(&batch)
+                .map_err(|e| DataFusionError::External(e.into()))?;
+            let sorted = last
+                .as_ref()
+                .map(|row| row.row())
+                .into_iter()
+                .chain(rows.iter())
+                .is_sorted();
+            if !sorted {
+                in_order_ref.store(false, Ordering::Relaxed);
+            }
+            last = Some(rows.row(rows.num_rows() - 1).owned());
+        }
+        Ok(batch)
+    });
+    Ok((
+        Box::pin(RecordBatchStreamAdapter::new(schema, checked)),
+        in_order,
+    ))
+}
+
+/// Reads the first and last rows of fragments to tell whether the rows of two
+/// fragments, each sorted by the clustering, are also in order together
+pub(crate) struct FragmentBoundaries {
+    projection: Schema,
+    converter: SortKeyConverter,
+}
+
+impl FragmentBoundaries {
+    pub(crate) fn try_new(dataset: &Dataset, ordering: &[ColumnOrdering]) -> Result<Self> {
+        let columns = ordering
+            .iter()
+            .map(|column| column.column_name.as_str())
+            .collect::<Vec<_>>();
+        let projection = dataset.schema().project(&columns)?;
+        let converter = SortKeyConverter::try_new(&ArrowSchema::from(&projection), ordering)?;
+        Ok(Self {
+            projection,
+            converter,
+        })
+    }
+
+    /// The sort keys of the first and last physical row of `fragment`, `None` if it
+    /// has no rows
+    ///
+    /// Deleted rows are included, the keys bound the live rows either way.
+    pub(crate) async fn bounds(
+        &self,
+        fragment: &FileFragment,
+    ) -> Result<Option<(OwnedRow, OwnedRow)>> {
+        let num_rows = fragment.physical_rows().await? as u32;
+        if num_rows == 0 {
+            return Ok(None);
+        }
+        let batch = fragment
+            .take_rows(
+                &[0, num_rows - 1],
+                &self.projection,
+                false,
+                false,
+                false,
+                false,
+            )
+            .await?;
+        let rows = self.converter.convert(&batch)?;
+        Ok(Some((
+            rows.row(0).owned(),
+            rows.row(rows.num_rows() - 1).owned(),
+        )))
+    }
+
+    /// Whether the rows of `fragments`, read one fragment after the other, are sorted
+    pub(crate) async fn in_order(&self, fragments: &[FileFragment]) -> Result<bool> {
+        let mut last: Option<OwnedRow> = None;
+        for fragment in fragments {
+            if let Some((first, fragment_last)) = self.bounds(fragment).await? {
+                if last.as_ref().is_some_and(|last| *last > first) {
+                    return Ok(false);
+                }
+                last = Some(fragment_last);
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Keep the clustering of `manifest`, as built by `transaction` on top of
+/// `previous`, only while every fragment is still sorted by it
+pub(crate) fn retain_clustering(
+    manifest: &mut Manifest,
+    previous: Option<&Manifest>,
+    transaction: &Transaction,
+) -> Result<()> {
+    let Some(value) = manifest.config.get(CLUSTERING_CONFIG_KEY).cloned() else {
+        return Ok(());
+    };
+    let previous_value = previous.and_then(|m| m.config.get(CLUSTERING_CONFIG_KEY));
+    let Some(ordering) = parse_clustering(&value) else {
+        return Err(Error::invalid_input(format!(
+            "invalid value for {CLUSTERING_CONFIG_KEY}: {value}"
+        )));
+    };
+
+    let vouched = transaction
+        .transaction_properties
+        .as_ref()
+        .and_then(|properties| properties.get(SORTED_BY_PROPERTY))
+        .is_some_and(|sorted_by| sorted_by == &value);
+    let sort_field_ids = ordering
+        .iter()
+        .filter_map(|column| manifest.schema.field(&column.column_name))
+        .map(|field| field.id as u32)
+        .collect::<Vec<_>>();
+    if sort_field_ids.len() != ordering.len() {
+        // A sort column was dropped
+        manifest.config_mut().remove(CLUSTERING_CONFIG_KEY);
+        return Ok(());
+    }
+
+    let keep = match &transaction.operation {
+        Operation::Overwrite {
+            config_upsert_values,
+            ..
+        } => config_upsert_values
+            .as_ref()
+            .is_some_and(|values| values.contains_key(CLUSTERING_CONFIG_KEY)),
+        Operation::UpdateConfig { .. } => {
+            if previous_value != Some(&value) {
+                return Err(Error::invalid_input(format!(
+                    "{CLUSTERING_CONFIG_KEY} can only be removed, it is set by writing with WriteParams::sort_by"
+                )));
+            }
+            true
+        }
+        Operation::Append { .. } | Operation::Rewrite { .. } => vouched,
+        Operation::Update {
+            new_fragments,
+            fields_modified,
+            ..
+        } => {
+            (new_fragments.is_empty() || vouched)
+                && !fields_modified.iter().any(|id| sort_field_ids.contains(id))
+        }
+        Operation::DataReplacement { replacements } => !replacements.iter().any(|replacement| {
+            replacement
+                .1
+                .fields
+                .iter()
+                .any(|id| sort_field_ids.contains(&(*id as u32)))
+        }),
+        _ => true,
+    };
+    // A cast of a sort column can change the order of its values
+    let overwrite = matches!(transaction.operation, Operation::Overwrite { .. });
+    let same_types = overwrite
+        || previous.is_none_or(|previous| {
+            ordering.iter().all(|column| {
+                let before = previous.schema.field(&column.column_name);
+                let after = manifest.schema.field(&column.column_name);
+                match (before, after) {
+                    (Some(before), Some(after)) => before.data_type() == after.data_type(),
+                    _ => true,
+                }
+            })
+        });
+    if !keep || !same_types {
+        manifest.config_mut().remove(CLUSTERING_CONFIG_KEY);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::Int64Type;
+    use arrow_array::{Int64Array, StringArray};
+    use arrow_schema::{DataType, Field};
+    use futures::{TryStreamExt, stream};
+    use lance_datafusion::exec::ExecutionSummaryCounts;
+
+    use super::*;
+    use crate::dataset::optimize::{CompactionOptions, compact_files};
+    use crate::dataset::{InsertBuilder, UpdateBuilder, WriteMode};
+
+    fn schema() -> Arc<ArrowSchema> {
+        Arc::new(ArrowSchema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("value", DataType::Utf8, true),
+        ]))
+    }
+
+    fn batch(ids: &[i64]) -> RecordBatch {
+        let values = ids.iter().map(|id| format!("v{id}")).collect::<Vec<_>>();
+        RecordBatch::try_new(
+            schema(),
+            vec![
+                Arc::new(Int64Array::from(ids.to_vec())),
+                Arc::new(StringArray::from(values)),
+            ],
+        )
+        .unwrap()
+    }
+
+    /// The numbers in `0..n` in a scrambled order, `step` must be coprime with `n`
+    fn scrambled(n: i64, step: i64) -> Vec<i64> {
+        (0..n).map(|i| (i * step) % n).collect()
+    }
+
+    fn sort_by_id() -> Vec<ColumnOrdering> {
+        vec![ColumnOrdering::asc_nulls_last("id".to_string())]
+    }
+
+    fn create_params(max_rows_per_file: usize) -> WriteParams {
+        WriteParams {
+            sort_by: sort_by_id(),
+            max_rows_per_file,
+            ..Default::default()
+        }
+    }
+
+    fn append_params(unsorted_appends: UnsortedAppends) -> WriteParams {
+        WriteParams {
+            mode: WriteMode::Append,
+            unsorted_appends,
+            ..Default::default()
+        }
+    }
+
+    async fn append(dataset: Dataset, ids: &[i64], params: &WriteParams) -> Dataset {
+        InsertBuilder::new(Arc::new(dataset))
+            .with_params(params)
+            .execute(vec![batch(ids)])
+            .await
+            .unwrap()
+    }
+
+    /// The ids of each fragment, in the order of the rows in the fragment
+    async fn fragment_ids(dataset: &Dataset) -> Vec<Vec<i64>> {
+        let mut ids = Vec::new();
+        for fragment in dataset.get_fragments() {
+            let batch = fragment
+                .scan()
+                .project(&["id"])
+                .unwrap()
+                .try_into_batch()
+                .await
+                .unwrap();
+            ids.push(
+                batch
+                    .column(0)
+                    .as_primitive::<Int64Type>()
+                    .values()
+                    .to_vec(),
+            );
+        }
+        ids
+    }
+
+    #[tokio::test]
+    async fn test_sorted_write_fragment_ranges() {
+        let dataset = InsertBuilder::new("memory://")
+            .with_params(&create_params(100))
+            .execute(vec![batch(&scrambled(1000, 337))])
+            .await
+            .unwrap();
+        assert_eq!(dataset.clustering(), Some(sort_by_id()));
+
+        // The fragments cover consecutive key ranges, with nothing in between
+        let fragments = fragment_ids(&dataset).await;
+        assert_eq!(fragments.len(), 10);
+        for (i, ids) in fragments.iter().enumerate() {
+            let start = i as i64 * 100;
+            assert_eq!(ids, &(start..start + 100).collect::<Vec<_>>());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_order_by_clustering() {
+        let evens = scrambled(100, 37)
+            .into_iter()
+            .map(|i| i * 2)
+            .collect::<Vec<_>>();
+        let odds = evens.iter().map(|i| i + 1).collect::<Vec<_>>();
+        let dataset = InsertBuilder::new("memory://")
+            .with_params(&create_params(30))
+            .execute(vec![batch(&evens)])
+            .await
+            .unwrap();
+        let dataset = append(dataset, &odds, &append_params(UnsortedAppends::Sort)).await;
+        assert_eq!(dataset.clustering(), Some(sort_by_id()));
+        assert!(
+            fragment_ids(&dataset)
+                .await
+                .iter()
+                .all(|ids| ids.is_sorted())
+        );
+
+        let mut scan = dataset.scan();
+        scan.order_by(Some(sort_by_id())).unwrap();
+        let plan = scan.explain_plan(false).await.unwrap();
+        assert!(plan.contains("SortPreservingMergeExec"), "{plan}");
+        assert!(!plan.contains("SortExec:"), "{plan}");
+        let batch = scan.try_into_batch().await.unwrap();
+        assert_eq!(
+            batch["id"].as_primitive::<Int64Type>().values().to_vec(),
+            (0..200).collect::<Vec<_>>()
+        );
+
+        // Other orders still sort
+        let mut scan = dataset.scan();
+        scan.order_by(Some(vec![ColumnOrdering::desc_nulls_last(
+            "id".to_string(),
+        )]))
+        .unwrap();
+        let plan = scan.explain_plan(false).await.unwrap();
+        assert!(plan.contains("SortExec:"), "{plan}");
+        let batch = scan.try_into_batch().await.unwrap();
+        assert_eq!(
+            batch["id"].as_primitive::<Int64Type>().values().to_vec(),
+            (0..200).rev().collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unsorted_appends() {
+        let dataset = InsertBuilder::new("memory://")
+            .with_params(&create_params(1000))
+            .execute(vec![batch(&scrambled(100, 7))])
+            .await
+            .unwrap();
+
+        // Sorted rows keep the clustering
+        let flag = append_params(UnsortedAppends::Flag);
+        let dataset = append(dataset, &[100, 101, 102], &flag).await;
+        assert_eq!(dataset.clustering(), Some(sort_by_id()));
+
+        // Appends can't sort by another order
+        let err = InsertBuilder::new(Arc::new(dataset.clone()))
+            .with_params(&WriteParams {
+                sort_by: vec![ColumnOrdering::desc_nulls_last("id".to_string())],
+                ..append_params(UnsortedAppends::Sort)
+            })
+            .execute(vec![batch(&[1])])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("must be empty or match"), "{err}");
+
+        // Deletes don't move rows
+        let mut dataset = dataset;
+        dataset.delete("id < 10").await.unwrap();
+        assert_eq!(dataset.clustering(), Some(sort_by_id()));
+
+        // The clustering can only be set by writing
+        let err = dataset
+            .update_config([(CLUSTERING_CONFIG_KEY, "[]")])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains(CLUSTERING_CONFIG_KEY), "{err}");
+
+        // Unsorted rows are written as given and flag the dataset as not clustered
+        let dataset = append(dataset, &[300, 200, 250], &flag).await;
+        assert_eq!(dataset.clustering(), None);
+        assert_eq!(
+            fragment_ids(&dataset).await.last().unwrap(),
+            &vec![300, 200, 250]
+        );
+        // Without a clustering, appends don't record one
+        let dataset = append(dataset, &[3, 2, 1], &append_params(UnsortedAppends::Sort)).await;
+        assert_eq!(dataset.clustering(), None);
+
+        // Overwriting without sort_by drops the clustering, updating a sort column too
+        let dataset = InsertBuilder::new("memory://")
+            .with_params(&create_params(1000))
+            .execute(vec![batch(&[2, 1])])
+            .await
+            .unwrap();
+        let overwritten = InsertBuilder::new(Arc::new(dataset.clone()))
+            .with_params(&WriteParams {
+                mode: WriteMode::Overwrite,
+                ..Default::default()
+            })
+            .execute(vec![batch(&[2, 1])])
+            .await
+            .unwrap();
+        assert_eq!(overwritten.clustering(), None);
+        let updated = UpdateBuilder::new(Arc::new(dataset))
+            .update_where("id = 1")
+            .unwrap()
+            .set("id", "10")
+            .unwrap()
+            .build()
+            .unwrap()
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(updated.new_dataset.clustering(), None);
+    }
+
+    #[tokio::test]
+    async fn test_compaction_keeps_clustering() {
+        let dataset = InsertBuilder::new("memory://")
+            .with_params(&create_params(1000))
+            .execute(vec![batch(&scrambled(100, 7))])
+            .await
+            .unwrap();
+        let sort = append_params(UnsortedAppends::Sort);
+        let dataset = append(dataset, &(100..200).rev().collect::<Vec<_>>(), &sort).await;
+        let dataset = append(
+            dataset,
+            &scrambled(100, 3).iter().map(|i| i + 50).collect::<Vec<_>>(),
+            &sort,
+        )
+        .await;
+        let mut dataset = append(dataset, &(300..400).collect::<Vec<_>>(), &sort).await;
+        assert_eq!(dataset.get_fragments().len(), 4);
+
+        // The third fragment overlaps the second one, so it starts a new fragment
+        compact_files(&mut dataset, CompactionOptions::default(), None)
+            .await
+            .unwrap();
+        assert_eq!(dataset.clustering(), Some(sort_by_id()));
+        let fragments = fragment_ids(&dataset).await;
+        assert_eq!(fragments.len(), 2);
+        assert_eq!(fragments[0], (0..200).collect::<Vec<_>>());
+        assert_eq!(fragments[1], (50..150).chain(300..400).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_sort_spills() {
+        const MEMORY_LIMIT: u64 = 20 * 1024 * 1024;
+        const NUM_ROWS: i64 = 1 << 21;
+        // 2M rows of about 24 bytes each, well over the memory limit
+        let ids = scrambled(NUM_ROWS, 7919);
+        let batches = ids.chunks(64 * 1024).map(batch).collect::<Vec<_>>();
+        let source = || -> SendableRecordBatchStream {
+            Box::pin(RecordBatchStreamAdapter::new(
+                schema(),
+                stream::iter(batches.clone().into_iter().map(Ok)),
+            ))
+        };
+
+        let spill_count = Arc::new(AtomicUsize::new(0));
+        let spills = spill_count.clone();
+        let callback: ExecutionStatsCallback = Arc::new(move |counts: &ExecutionSummaryCounts| {
+            let count = counts.all_counts.get("spill_count").copied().unwrap_or(0);
+            spills.store(count, Ordering::Relaxed);
+        });
+        let sorted = sort_stream(source(), &sort_by_id(), Some(MEMORY_LIMIT), Some(callback))
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert!(spill_count.load(Ordering::Relaxed) > 0);
+        let sorted_ids = sorted
+            .iter()
+            .flat_map(|batch| batch["id"].as_primitive::<Int64Type>().values().to_vec())
+            .collect::<Vec<_>>();
+        assert_eq!(sorted_ids, (0..NUM_ROWS).collect::<Vec<_>>());
+
+        // Writes sort under the same limit
+        let dataset = InsertBuilder::new("memory://")
+            .with_params(&WriteParams {
+                sort_memory_limit: Some(MEMORY_LIMIT),
+                ..create_params(1 << 20)
+            })
+            .execute_stream(source())
+            .await
+            .unwrap();
+        let fragments = fragment_ids(&dataset).await;
+        assert_eq!(fragments.len(), 2);
+        assert_eq!(fragments[0], (0..1 << 20).collect::<Vec<_>>());
+        assert_eq!(fragments[1], (1 << 20..NUM_ROWS).collect::<Vec<_>>());
+    }
+}
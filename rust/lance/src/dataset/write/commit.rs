@@ -28,6 +28,7 @@ use crate::{
 use super::{WriteDestination, resolve_commit_handler};
 use crate::dataset::branch_location::BranchLocation;
 use crate::dataset::transaction::validate_operation;
+use crate::session::commit_listener::notify_commit_listeners;
 use lance_core::utils::tracing::{DATASET_COMMITTED_EVENT, TRACE_DATASET_EVENTS};
 use tracing::info;
 
@@ -49,6 +50,7 @@ pub struct CommitBuilder<'a> {
     affected_rows: Option<RowAddrTreeMap>,
     transaction_properties: Option<Arc<HashMap<String, String>>>,
     timeout: Option<Duration>,
+    max_reader_feature_level: Option<u32>,
 }
 
 /// Default timeout applied to [`CommitBuilder::execute`] when none is set.
@@ -70,6 +72,7 @@ impl<'a> CommitBuilder<'a> {
             affected_rows: None,
             transaction_properties: None,
             timeout: Some(DEFAULT_COMMIT_TIMEOUT),
+            max_reader_feature_level: None,
         }
     }
 
@@ -194,6 +197,13 @@ impl<'a> CommitBuilder<'a> {
         self
     }
 
+    /// Refuse to commit a version that readers of a lower reader feature level
+    /// can't read, see [`lance_table::feature_flags::KNOWN_READER_FEATURES`].
+    pub fn with_max_reader_feature_level(mut self, level: u32) -> Self {
+        self.max_reader_feature_level = Some(level);
+        self
+    }
+
     /// provide Configuration key-value pairs associated with this transaction.
     /// This is used to store metadata about the transaction, such as commit messages, engine information, etc.
     /// this properties map will be persisted as a part of the transaction object
@@ -232,6 +242,9 @@ impl<'a> CommitBuilder<'a> {
     }
 
     async fn execute_inner(self, transaction: Transaction) -> Result<Dataset> {
+        if let WriteDestination::Dataset(dataset) = &self.dest {
+            dataset.check_writable()?;
+        }
         let session = self
             .session
             .or_else(|| self.dest.dataset().map(|ds| ds.session.clone()))
@@ -359,6 +372,7 @@ impl<'a> CommitBuilder<'a> {
         let manifest_config = ManifestWriteConfig {
             use_stable_row_ids,
             storage_format: self.storage_format.map(DataStorageFormat::new),
+            max_reader_feature_level: self.max_reader_feature_level,
             ..Default::default()
         };
 
@@ -419,6 +433,15 @@ impl<'a> CommitBuilder<'a> {
             detached=self.detached,
             operation=&transaction.operation.name()
         );
+        if !self.detached {
+            notify_commit_listeners(
+                &session,
+                &dest.uri(),
+                &transaction.operation,
+                dest.dataset().map(|ds| ds.manifest.as_ref()),
+                &manifest,
+            );
+        }
 
         let fragment_bitmap = Arc::new(manifest.fragments.iter().map(|f| f.id as u32).collect());
 
@@ -456,6 +479,9 @@ impl<'a> CommitBuilder<'a> {
                     file_reader_options: None,
                     store_params: self.store_params.clone().map(Box::new),
                     base_store_params: None,
+                    read_lease: None,
+                    #[cfg(feature = "metrics")]
+                    commit_metrics: Default::default(),
                 })
             }
         }
@@ -14,6 +14,7 @@ use futures::{StreamExt, TryStreamExt};
 use lance_core::{Error, ROW_ID, Result};
 use lance_select::RowAddrTreeMap;
 use lance_table::format::Fragment;
+use lance_table::io::deletion::DeletionFileOptions;
 use roaring::RoaringTreemap;
 use std::collections::BTreeMap;
 use std::sync::Arc;
@@ -49,9 +50,10 @@ pub struct UncommittedDelete {
 /// Apply deletions to fragments based on a RoaringTreemap of row IDs.
 ///
 /// Returns the set of modified fragments and removed fragments, if any.
-async fn apply_deletions(
+pub(super) async fn apply_deletions(
     dataset: &Dataset,
     removed_row_addrs: &RoaringTreemap,
+    options: &DeletionFileOptions,
 ) -> Result<(Vec<Fragment>, Vec<u64>)> {
     let bitmaps = Arc::new(removed_row_addrs.bitmaps().collect::<BTreeMap<_, _>>());
 
@@ -70,7 +72,10 @@ async fn apply_deletions(
             async move {
                 let fragment_id = fragment.id();
                 if let Some(bitmap) = bitmaps_ref.get(&(fragment_id as u32)) {
-                    match fragment.extend_deletions(*bitmap).await {
+                    match fragment
+                        .extend_deletions_with_options(*bitmap, options)
+                        .await
+                    {
                         Ok(Some(new_fragment)) => {
                             Ok(FragmentChange::Modified(Box::new(new_fragment.metadata)))
                         }
@@ -123,6 +128,7 @@ pub struct DeleteBuilder {
     filter: ExprFilter,
     conflict_retries: u32,
     retry_timeout: Duration,
+    deletion_file_options: DeletionFileOptions,
 }
 
 impl DeleteBuilder {
@@ -133,6 +139,7 @@ impl DeleteBuilder {
             filter: ExprFilter::Sql(predicate.into()),
             conflict_retries: 10,
             retry_timeout: Duration::from_secs(30),
+            deletion_file_options: DeletionFileOptions::default(),
         }
     }
 
@@ -143,6 +150,7 @@ impl DeleteBuilder {
             filter: ExprFilter::Datafusion(expr),
             conflict_retries: 10,
             retry_timeout: Duration::from_secs(30),
+            deletion_file_options: DeletionFileOptions::default(),
         }
     }
 
@@ -158,11 +166,18 @@ impl DeleteBuilder {
         self
     }
 
+    /// Set how the deletion files of modified fragments are encoded
+    pub fn deletion_file_options(mut self, options: DeletionFileOptions) -> Self {
+        self.deletion_file_options = options;
+        self
+    }
+
     /// Execute the delete operation
     pub async fn execute(self) -> Result<DeleteResult> {
         let job = DeleteJob {
             dataset: self.dataset.clone(),
             filter: self.filter,
+            deletion_file_options: self.deletion_file_options,
         };
 
         let config = RetryConfig {
@@ -203,6 +218,7 @@ impl DeleteBuilder {
         let job = DeleteJob {
             dataset: self.dataset,
             filter: self.filter,
+            deletion_file_options: self.deletion_file_options,
         };
         let data = job.execute_impl().await?;
         let DeleteData {
@@ -229,6 +245,7 @@ impl DeleteBuilder {
 struct DeleteJob {
     dataset: Arc<Dataset>,
     filter: ExprFilter,
+    deletion_file_options: DeletionFileOptions,
 }
 
 /// Data returned by delete operation
@@ -267,6 +284,7 @@ impl RetryExecutor for DeleteJob {
     type Result = DeleteResult;
 
     async fn execute_impl(&self) -> Result<Self::Data> {
+        self.dataset.check_writable()?;
         // Create a single scanner for the entire dataset
         let mut scanner = self.dataset.scan();
         scanner.with_row_id().project(&[ROW_ID])?;
@@ -328,8 +346,12 @@ impl RetryExecutor for DeleteJob {
                     let row_id_index = get_row_id_index(&self.dataset).await?;
                     let removed_row_addrs = removed_row_ids.row_addrs(row_id_index.as_deref());
 
-                    let (fragments, deleted_ids) =
-                        apply_deletions(&self.dataset, &removed_row_addrs).await?;
+                    let (fragments, deleted_ids) = apply_deletions(
+                        &self.dataset,
+                        &removed_row_addrs,
+                        &self.deletion_file_options,
+                    )
+                    .await?;
                     let num_deleted_rows = removed_row_addrs.len();
                     let affected_rows = RowAddrTreeMap::from(removed_row_addrs.as_ref().clone());
                     (
@@ -1069,4 +1091,74 @@ mod tests {
         dataset.checkout_latest().await.unwrap();
         assert_eq!(dataset.count_rows(None).await.unwrap(), 90);
     }
+
+    #[tokio::test]
+    async fn test_delete_deletion_file_options() {
+        use lance_table::format::DeletionFileType;
+        use lance_table::io::deletion::{DeletionFileEncoding, deletion_file_path};
+
+        let schema = Arc::new(ArrowSchema::new(vec![ArrowField::new(
+            "i",
+            DataType::UInt32,
+            false,
+        )]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![Arc::new(UInt32Array::from_iter_values(0..1000u32))],
+        )
+        .unwrap();
+        let dataset = InsertBuilder::new("memory://")
+            .with_params(&WriteParams {
+                max_rows_per_file: 500,
+                ..Default::default()
+            })
+            .execute(vec![batch])
+            .await
+            .unwrap();
+        assert!(dataset.deletion_stats().await.unwrap().fragments.is_empty());
+
+        let with_encoding = |encoding| DeletionFileOptions {
+            encoding,
+            ..Default::default()
+        };
+        // Sparse deletes written as a bitmap, dense deletes as an array.
+        let result = DeleteBuilder::new(Arc::new(dataset), "i < 10")
+            .deletion_file_options(with_encoding(DeletionFileEncoding::Bitmap))
+            .execute()
+            .await
+            .unwrap();
+        let result = DeleteBuilder::new(result.new_dataset, "i >= 500 AND i < 900")
+            .deletion_file_options(with_encoding(DeletionFileEncoding::Array))
+            .execute()
+            .await
+            .unwrap();
+        let dataset = result.new_dataset;
+        assert_eq!(dataset.count_rows(None).await.unwrap(), 590);
+
+        let stats = dataset.deletion_stats().await.unwrap();
+        let summary = stats
+            .fragments
+            .iter()
+            .map(|f| (f.fragment_id, f.num_deleted_rows, f.file_type.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (0, 10, DeletionFileType::Bitmap),
+                (1, 400, DeletionFileType::Array),
+            ]
+        );
+        assert_eq!(stats.num_deleted_rows(), 410);
+
+        let mut total_size = 0;
+        for fragment in dataset.get_fragments() {
+            let deletion_file = fragment.metadata.deletion_file.as_ref().unwrap();
+            let path = deletion_file_path(&dataset.base, fragment.id() as u64, deletion_file);
+            let size = dataset.object_store.size(&path).await.unwrap();
+            let entry = &stats.fragments[fragment.id()];
+            assert_eq!(entry.file_size, size);
+            total_size += size;
+        }
+        assert_eq!(stats.total_file_size(), total_size);
+    }
 }
@@ -0,0 +1,632 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Write a dataset from many independent workers and commit the result once.
+//!
+//! The coordinator starts a [`DistributedWriteSession`] and hands out one
+//! [`WriterTicket`] per worker (e.g. one per partition of a DataFrame).  Tickets
+//! are serializable so they can be shipped to other processes or machines.  Each
+//! worker calls [`DistributedWriteSession::write_fragment`] with its ticket and
+//! its partition of the data and sends the returned [`FragmentMetadata`] back to
+//! the coordinator, which commits all of them as a single transaction with
+//! [`DistributedWriteSession::commit`].
+//!
+//! Nothing written by the workers is visible until the commit succeeds.  If a
+//! worker fails the coordinator can simply drop the session; the files that were
+//! already written are unreferenced and will be removed by cleanup.
+
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use arrow_schema::Schema as ArrowSchema;
+use lance_core::datatypes::Schema;
+use lance_datafusion::utils::StreamingWriteSource;
+use lance_file::version::LanceFileVersion;
+use lance_io::object_store::{ObjectStoreParams, StorageOptionsAccessor};
+use lance_table::format::Fragment;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{CommitBuilder, WriteDestination, WriteMode, WriteParams};
+use crate::arrow::json::ArrowJsonExt;
+use crate::dataset::ReadParams;
+use crate::dataset::builder::DatasetBuilder;
+use crate::dataset::fragment::write::FragmentCreateBuilder;
+use crate::dataset::transaction::{Operation, TransactionBuilder};
+use crate::{Dataset, Error, Result};
+
+/// Everything a worker needs to write its share of a distributed write.
+///
+/// Created by [`DistributedWriteSession::tickets`] and consumed by
+/// [`DistributedWriteSession::write_fragment`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WriterTicket {
+    /// The session this ticket belongs to
+    pub session_id: String,
+    /// The index of the worker, used to order fragments deterministically on commit
+    pub worker_id: u32,
+    pub dataset_uri: String,
+    /// The Arrow schema of the data, as JSON
+    pub schema: String,
+    /// When appending, the version of the dataset whose schema (and field ids) the
+    /// fragments must be written with.
+    pub append_to_version: Option<u64>,
+    pub data_storage_version: String,
+    pub max_rows_per_file: usize,
+    pub max_rows_per_group: usize,
+    pub max_bytes_per_file: usize,
+    /// Storage options needed to reach the dataset, if any.
+    pub storage_options: Option<HashMap<String, String>>,
+}
+
+/// The result of a worker's share of a distributed write.
+///
+/// This should be passed back to [`DistributedWriteSession::commit`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FragmentMetadata {
+    pub session_id: String,
+    pub worker_id: u32,
+    /// The field ids of the schema the fragments were written with
+    pub field_ids: Vec<i32>,
+    pub data_storage_version: String,
+    /// The written fragments.  Ids are assigned when the session is committed.
+    pub fragments: Vec<Fragment>,
+}
+
+/// Coordinates a write of one dataset version by many independent workers.
+///
+/// ```no_run
+/// # use std::sync::Arc;
+/// # use arrow_array::RecordBatchReader;
+/// # use lance::Result;
+/// # use lance::dataset::{DistributedWriteSession, WriteParams};
+/// # async fn example(readers: Vec<Box<dyn RecordBatchReader + Send>>) -> Result<()> {
+/// let schema = readers[0].schema();
+/// let session =
+///     DistributedWriteSession::begin("s3://bucket/table", &schema, WriteParams::default())
+///         .await?;
+/// let mut written = Vec::new();
+/// for (ticket, reader) in session.tickets(readers.len() as u32).iter().zip(readers) {
+///     // Usually this runs on a remote worker
+///     written.push(DistributedWriteSession::write_fragment(ticket, reader).await?);
+/// }
+/// let dataset = session.commit(written).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct DistributedWriteSession {
+    session_id: String,
+    dataset_uri: String,
+    arrow_schema: String,
+    schema: Schema,
+    dataset: Option<Arc<Dataset>>,
+    data_storage_version: LanceFileVersion,
+    params: WriteParams,
+}
+
+impl DistributedWriteSession {
+    /// Start a distributed write of data with the given schema.
+    ///
+    /// `params.mode` decides whether the workers create, append to or overwrite the
+    /// dataset.  As with other writes, appending to or overwriting a dataset that
+    /// does not exist creates it.
+    pub async fn begin(
+        dataset_uri: &str,
+        schema: &ArrowSchema,
+        mut params: WriteParams,
+    ) -> Result<Self> {
+        if schema.fields().is_empty() {
+            return Err(Error::invalid_input("Cannot write with an empty schema."));
+        }
+
+        let dataset = match DatasetBuilder::from_uri(dataset_uri)
+            .with_read_params(ReadParams {
+                store_options: params.store_params.clone(),
+                commit_handler: params.commit_handler.clone(),
+                session: params.session.clone(),
+                ..Default::default()
+            })
+            .load()
+            .await
+        {
+            Ok(dataset) => Some(Arc::new(dataset)),
+            Err(Error::DatasetNotFound { .. } | Error::NotFound { .. }) => None,
+            Err(e) => return Err(e),
+        };
+
+        match (params.mode, &dataset) {
+            (WriteMode::Create, Some(dataset)) => {
+                return Err(Error::dataset_already_exists(dataset.uri.clone()));
+            }
+            (WriteMode::Append | WriteMode::Overwrite, None) => {
+                params.mode = WriteMode::Create;
+            }
+            _ => {}
+        }
+
+        let data_schema = Schema::try_from(schema)?;
+        let (schema, data_storage_version) = match (params.mode, &dataset) {
+            (WriteMode::Append, Some(dataset)) => {
+                data_schema.check_compatible(dataset.schema(), &Default::default())?;
+                (
+                    dataset.schema().clone(),
+                    dataset.manifest.data_storage_format.lance_file_version()?,
+                )
+            }
+            (WriteMode::Overwrite, Some(dataset)) => {
+                let version = params
+                    .data_storage_version
+                    .map(Ok)
+                    .unwrap_or_else(|| dataset.manifest.data_storage_format.lance_file_version())?;
+                (data_schema, version)
+            }
+            _ => (data_schema, params.storage_version_or_default()),
+        };
+
+        Ok(Self {
+            session_id: Uuid::new_v4().to_string(),
+            dataset_uri: dataset_uri.to_string(),
+            arrow_schema: ArrowSchema::from(&schema).to_json()?,
+            schema,
+            dataset,
+            data_storage_version: data_storage_version.resolve(),
+            params,
+        })
+    }
+
+    /// The unique id of this session, shared by all its tickets.
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// The schema the fragments will be written with.
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Create the ticket for a single worker.
+    pub fn ticket(&self, worker_id: u32) -> WriterTicket {
+        WriterTicket {
+            session_id: self.session_id.clone(),
+            worker_id,
+            dataset_uri: self.dataset_uri.clone(),
+            schema: self.arrow_schema.clone(),
+            append_to_version: match (self.params.mode, &self.dataset) {
+                (WriteMode::Append, Some(dataset)) => Some(dataset.manifest.version),
+                _ => None,
+            },
+            data_storage_version: self.data_storage_version.to_string(),
+            max_rows_per_file: self.params.max_rows_per_file,
+            max_rows_per_group: self.params.max_rows_per_group,
+            max_bytes_per_file: self.params.max_bytes_per_file,
+            storage_options: self
+                .params
+                .store_params
+                .as_ref()
+                .and_then(|params| params.storage_options())
+                .cloned(),
+        }
+    }
+
+    /// Create tickets for `num_workers` workers.
+    pub fn tickets(&self, num_workers: u32) -> Vec<WriterTicket> {
+        (0..num_workers).map(|id| self.ticket(id)).collect()
+    }
+
+    /// Write a worker's share of the data.
+    ///
+    /// This only needs the ticket and can run in any process.  The written files
+    /// are not visible until the returned metadata is committed by the coordinator.
+    pub async fn write_fragment(
+        ticket: &WriterTicket,
+        source: impl StreamingWriteSource,
+    ) -> Result<FragmentMetadata> {
+        let data_storage_version = LanceFileVersion::from_str(&ticket.data_storage_version)?;
+
+        let schema = if let Some(version) = ticket.append_to_version {
+            let mut builder = DatasetBuilder::from_uri(&ticket.dataset_uri).with_version(version);
+            if let Some(storage_options) = ticket.storage_options.clone() {
+                builder = builder.with_storage_options(storage_options);
+            }
+            builder.load().await?.schema().clone()
+        } else {
+            Schema::try_from(&ArrowSchema::from_json(&ticket.schema)?)?
+        };
+
+        let params = WriteParams {
+            max_rows_per_file: ticket.max_rows_per_file,
+            max_rows_per_group: ticket.max_rows_per_group,
+            max_bytes_per_file: ticket.max_bytes_per_file,
+            data_storage_version: Some(data_storage_version),
+            store_params: ticket
+                .storage_options
+                .clone()
+                .map(|storage_options| ObjectStoreParams {
+                    storage_options_accessor: Some(Arc::new(
+                        StorageOptionsAccessor::with_static_options(storage_options),
+                    )),
+                    ..Default::default()
+                }),
+            ..Default::default()
+        };
+
+        let fragments = FragmentCreateBuilder::new(&ticket.dataset_uri)
+            .schema(&schema)
+            .write_params(&params)
+            .write_fragments(source)
+            .await?;
+
+        Ok(FragmentMetadata {
+            session_id: ticket.session_id.clone(),
+            worker_id: ticket.worker_id,
+            field_ids: schema.field_ids(),
+            data_storage_version: ticket.data_storage_version.clone(),
+            fragments,
+        })
+    }
+
+    /// Commit the fragments written by the workers as a single new version.
+    ///
+    /// Fragments are ordered by worker id (and by write order within a worker) so the
+    /// assigned fragment ids do not depend on the order in which workers finished.
+    pub async fn commit(&self, written: Vec<FragmentMetadata>) -> Result<Dataset> {
+        let mut written = written;
+        written.sort_by_key(|metadata| metadata.worker_id);
+
+        let mut workers = HashSet::with_capacity(written.len());
+        for metadata in &written {
+            if !workers.insert(metadata.worker_id) {
+                return Err(Error::invalid_input(format!(
+                    "Worker {} reported fragments more than once in distributed write session {}",
+                    metadata.worker_id, self.session_id
+                )));
+            }
+            self.validate(metadata)?;
+        }
+
+        let fragments = written
+            .into_iter()
+            .flat_map(|metadata| metadata.fragments)
+            .map(|mut fragment| {
+                fragment.id = 0;
+                fragment
+            })
+            .collect::<Vec<_>>();
+        if fragments.is_empty() {
+            return Err(Error::invalid_input(format!(
+                "No fragments were written in distributed write session {}",
+                self.session_id
+            )));
+        }
+
+        let operation = match self.params.mode {
+            WriteMode::Append => Operation::Append { fragments },
+            WriteMode::Create | WriteMode::Overwrite => Operation::Overwrite {
+                schema: self.schema.clone(),
+                fragments,
+                config_upsert_values: None,
+                initial_bases: None,
+            },
+        };
+        let read_version = self
+            .dataset
+            .as_ref()
+            .map(|dataset| dataset.manifest.version)
+            .unwrap_or(0);
+        let transaction = TransactionBuilder::new(read_version, operation)
+            .uuid(self.session_id.clone())
+            .transaction_properties(self.params.transaction_properties.clone())
+            .build();
+
+        let dest = match &self.dataset {
+            Some(dataset) => WriteDestination::Dataset(dataset.clone()),
+            None => WriteDestination::Uri(&self.dataset_uri),
+        };
+        let mut commit_builder = CommitBuilder::new(dest)
+            .use_stable_row_ids(self.params.enable_stable_row_ids)
+            .with_storage_format(self.data_storage_version)
+            .enable_v2_manifest_paths(self.params.enable_v2_manifest_paths)
+            .with_skip_auto_cleanup(self.params.skip_auto_cleanup);
+        if let Some(store_params) = self.params.store_params.as_ref() {
+            commit_builder = commit_builder.with_store_params(store_params.clone());
+        }
+        if let Some(commit_handler) = self.params.commit_handler.as_ref() {
+            commit_builder = commit_builder.with_commit_handler(commit_handler.clone());
+        }
+        if let Some(session) = self.params.session.as_ref() {
+            commit_builder = commit_builder.with_session(session.clone());
+        }
+
+        commit_builder.execute(transaction).await
+    }
+
+    fn validate(&self, metadata: &FragmentMetadata) -> Result<()> {
+        if metadata.session_id != self.session_id {
+            return Err(Error::invalid_input(format!(
+                "Fragments from worker {} belong to distributed write session {}, expected {}",
+                metadata.worker_id, metadata.session_id, self.session_id
+            )));
+        }
+
+        let field_ids = self.schema.field_ids();
+        if metadata.field_ids != field_ids {
+            return Err(Error::invalid_input(format!(
+                "Worker {} wrote fragments with field ids {:?} but the session schema has field ids {:?}",
+                metadata.worker_id, metadata.field_ids, field_ids
+            )));
+        }
+
+        let expected_version = self.data_storage_version.resolve();
+        if LanceFileVersion::from_str(&metadata.data_storage_version)?.resolve() != expected_version
+        {
+            return Err(Error::invalid_input(format!(
+                "Worker {} wrote fragments with data storage version {} but the session uses {}",
+                metadata.worker_id, metadata.data_storage_version, expected_version
+            )));
+        }
+
+        for fragment in &metadata.fragments {
+            for data_file in &fragment.files {
+                let file_version = LanceFileVersion::try_from_major_minor(
+                    data_file.file_major_version,
+                    data_file.file_minor_version,
+                )?;
+                if file_version.resolve() != expected_version {
+                    return Err(Error::invalid_input(format!(
+                        "Data file {} from worker {} has version {} but the session uses {}",
+                        data_file.path, metadata.worker_id, file_version, expected_version
+                    )));
+                }
+                if let Some(field_id) = data_file
+                    .fields
+                    .iter()
+                    .find(|field_id| **field_id >= 0 && !field_ids.contains(*field_id))
+                {
+                    return Err(Error::invalid_input(format!(
+                        "Data file {} from worker {} contains field {} which is not in the session schema",
+                        data_file.path, metadata.worker_id, field_id
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::{Int32Array, RecordBatch, RecordBatchIterator, StringArray};
+    use arrow_schema::{ArrowError, DataType, Field as ArrowField};
+    use lance_core::utils::tempfile::TempStrDir;
+
+    use super::*;
+
+    fn schema() -> Arc<ArrowSchema> {
+        Arc::new(ArrowSchema::new(vec![
+            ArrowField::new("i", DataType::Int32, false),
+            ArrowField::new("s", DataType::Utf8, false),
+        ]))
+    }
+
+    fn batch(range: std::ops::Range<i32>) -> RecordBatch {
+        RecordBatch::try_new(
+            schema(),
+            vec![
+                Arc::new(Int32Array::from_iter_values(range.clone())),
+                Arc::new(StringArray::from_iter_values(
+                    range.map(|i| format!("str-{i}")),
+                )),
+            ],
+        )
+        .unwrap()
+    }
+
+    /// Run a worker on its own runtime and thread, passing the ticket and the
+    /// result through JSON as a real distributed worker would.
+    fn run_worker(
+        ticket: String,
+        batches: Vec<std::result::Result<RecordBatch, ArrowError>>,
+    ) -> Result<String> {
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+            runtime.block_on(async move {
+                let ticket: WriterTicket = serde_json::from_str(&ticket).unwrap();
+                let reader = RecordBatchIterator::new(batches, schema());
+                let metadata = DistributedWriteSession::write_fragment(&ticket, reader).await?;
+                Ok(serde_json::to_string(&metadata).unwrap())
+            })
+        })
+        .join()
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_distributed_write() {
+        let test_dir = TempStrDir::default();
+        let uri = test_dir.as_str();
+
+        let session = DistributedWriteSession::begin(uri, &schema(), WriteParams::default())
+            .await
+            .unwrap();
+        let tickets = session.tickets(3);
+
+        // Workers finish in reverse order, ids must still follow worker order
+        let mut written = Vec::new();
+        for (worker, ticket) in tickets.iter().enumerate().rev() {
+            let start = worker as i32 * 100;
+            let result = run_worker(
+                serde_json::to_string(ticket).unwrap(),
+                vec![
+                    Ok(batch(start..start + 50)),
+                    Ok(batch(start + 50..start + 100)),
+                ],
+            )
+            .unwrap();
+            written.push(serde_json::from_str::<FragmentMetadata>(&result).unwrap());
+        }
+
+        let dataset = session.commit(written).await.unwrap();
+        assert_eq!(dataset.manifest.version, 1);
+        assert_eq!(dataset.count_rows(None).await.unwrap(), 300);
+        let fragments = dataset.get_fragments();
+        assert_eq!(
+            fragments.iter().map(|f| f.id()).collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+        let scanned = dataset.scan().try_into_batch().await.unwrap();
+        assert_eq!(
+            scanned.column(0).as_ref(),
+            &Int32Array::from_iter_values(0..300)
+        );
+
+        // Now append to the dataset that was just created
+        let params = WriteParams {
+            mode: WriteMode::Append,
+            ..Default::default()
+        };
+        let session = DistributedWriteSession::begin(uri, &schema(), params)
+            .await
+            .unwrap();
+        let written = session
+            .tickets(2)
+            .iter()
+            .map(|ticket| {
+                let start = 300 + ticket.worker_id as i32 * 10;
+                let result = run_worker(
+                    serde_json::to_string(ticket).unwrap(),
+                    vec![Ok(batch(start..start + 10))],
+                )
+                .unwrap();
+                serde_json::from_str::<FragmentMetadata>(&result).unwrap()
+            })
+            .collect::<Vec<_>>();
+        let dataset = session.commit(written).await.unwrap();
+        assert_eq!(dataset.manifest.version, 2);
+        assert_eq!(dataset.count_rows(None).await.unwrap(), 320);
+        assert_eq!(
+            dataset
+                .get_fragments()
+                .iter()
+                .map(|f| f.id())
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 4]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_distributed_write_worker_failure() {
+        let test_dir = TempStrDir::default();
+        let uri = test_dir.as_str();
+        let data = RecordBatchIterator::new(vec![Ok(batch(0..10))], schema());
+        Dataset::write(data, uri, None).await.unwrap();
+
+        let params = WriteParams {
+            mode: WriteMode::Append,
+            ..Default::default()
+        };
+        let session = DistributedWriteSession::begin(uri, &schema(), params)
+            .await
+            .unwrap();
+        let tickets = session.tickets(3);
+
+        let mut results = Vec::new();
+        for ticket in &tickets {
+            let start = 10 + ticket.worker_id as i32 * 10;
+            let mut batches = vec![Ok(batch(start..start + 10))];
+            if ticket.worker_id == 1 {
+                batches.push(Err(ArrowError::ComputeError("worker crashed".to_string())));
+            }
+            results.push(run_worker(serde_json::to_string(ticket).unwrap(), batches));
+        }
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+        assert!(results[2].is_ok());
+
+        // The coordinator gives up on the session, none of the written data is visible
+        drop(session);
+        let dataset = Dataset::open(uri).await.unwrap();
+        assert_eq!(dataset.manifest.version, 1);
+        assert_eq!(dataset.count_rows(None).await.unwrap(), 10);
+        assert_eq!(dataset.get_fragments().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_distributed_write_validation() {
+        let test_dir = TempStrDir::default();
+        let uri = test_dir.as_str();
+
+        let session = DistributedWriteSession::begin(uri, &schema(), WriteParams::default())
+            .await
+            .unwrap();
+        let other_session = DistributedWriteSession::begin(
+            uri,
+            &schema(),
+            WriteParams {
+                data_storage_version: Some(LanceFileVersion::Legacy),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        let reader = RecordBatchIterator::new(vec![Ok(batch(0..10))], schema());
+        let written = DistributedWriteSession::write_fragment(&session.ticket(0), reader)
+            .await
+            .unwrap();
+
+        // Fragments from another session are rejected
+        let reader = RecordBatchIterator::new(vec![Ok(batch(0..10))], schema());
+        let foreign = DistributedWriteSession::write_fragment(&other_session.ticket(1), reader)
+            .await
+            .unwrap();
+        let err = session
+            .commit(vec![written.clone(), foreign.clone()])
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("distributed write session"),
+            "{err}"
+        );
+
+        // Even if they claim to be part of the session, the storage version must match
+        let disguised = FragmentMetadata {
+            session_id: session.session_id().to_string(),
+            ..foreign
+        };
+        let err = session
+            .commit(vec![written.clone(), disguised])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("data storage version"), "{err}");
+
+        // So must the schema
+        let mut mismatched = written.clone();
+        mismatched.worker_id = 1;
+        mismatched.field_ids = vec![0];
+        let err = session
+            .commit(vec![written.clone(), mismatched])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("field ids"), "{err}");
+
+        // Duplicate reports from the same worker are rejected
+        let err = session
+            .commit(vec![written.clone(), written.clone()])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("more than once"), "{err}");
+
+        // Nothing was committed by the failed attempts
+        assert!(matches!(
+            Dataset::open(uri).await,
+            Err(Error::DatasetNotFound { .. })
+        ));
+        session.commit(vec![written]).await.unwrap();
+    }
+}
@@ -1,38 +1,55 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-FileCopyrightText: Copyright The Lance Authors
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
 use arrow_array::{RecordBatch, RecordBatchIterator};
+use arrow_schema::{DataType, Field as ArrowField, FieldRef, Schema as ArrowSchema};
+use datafusion::error::Result as DataFusionResult;
 use datafusion::execution::SendableRecordBatchStream;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use futures::StreamExt;
 use humantime::format_duration;
-use lance_core::datatypes::{NullabilityComparison, Schema, SchemaCompareOptions};
+use lance_core::datatypes::{
+    Field, NullabilityComparison, Schema, SchemaCompareOptions, SchemaDiff, SchemaDifference,
+};
 use lance_core::utils::tracing::{DATASET_WRITING_EVENT, TRACE_DATASET_EVENTS};
 use lance_core::{ROW_ADDR, ROW_ID, ROW_OFFSET};
 use lance_datafusion::utils::StreamingWriteSource;
 use lance_file::version::LanceFileVersion;
 use lance_io::object_store::ObjectStore;
 use lance_table::feature_flags::can_write_dataset;
-use lance_table::format::Fragment;
+use lance_table::format::{Fragment, IndexMetadata};
 use lance_table::io::commit::CommitHandler;
 use object_store::path::Path;
 
 use crate::Dataset;
+use crate::dataset::NewColumnTransform;
 use crate::dataset::ReadParams;
 use crate::dataset::builder::DatasetBuilder;
-use crate::dataset::transaction::{Operation, Transaction, TransactionBuilder};
+use crate::dataset::transaction::{Operation, Transaction, TransactionBuilder, UpdateMode};
 use crate::dataset::write::{validate_and_resolve_target_bases, write_fragments_internal};
+use crate::index::{DatasetIndexExt, DatasetIndexInternalExt};
 use crate::{Error, Result};
 use tracing::info;
 
+use super::SchemaEvolution;
 use super::WriteDestination;
 use super::WriteMode;
 use super::WriteParams;
+use super::clustering::{CLUSTERING_CONFIG_KEY, SortedWrite, cluster_write, vouch_sorted_by};
 use super::commit::CommitBuilder;
 use super::resolve_commit_handler;
+use super::unique::{InsertedKeys, UniqueKeyEnforcer};
 use crate::dataset::progress::{WriteProgressFn, WriteStats};
 
+/// The transaction property of an overwrite that lists the indexes
+/// [`WriteParams::preserve_indexes_on_identical_columns`] could not rebuild
+///
+/// The value is a JSON object from index name to the reason.
+pub const SKIPPED_INDEXES_PROPERTY: &str = "lance.overwrite.skipped_indexes";
+
 /// Insert or create a new dataset.
 ///
 /// There are different variants of `execute()` methods. Those with the `_stream`
@@ -147,7 +164,17 @@ impl<'a> InsertBuilder<'a> {
             commit_builder = commit_builder.with_session(session.clone());
         }
 
-        commit_builder.execute(transaction).await
+        if let Some(level) = context.params.max_reader_feature_level {
+            commit_builder = commit_builder.with_max_reader_feature_level(level);
+        }
+
+        let mut dataset = commit_builder.execute(transaction).await?;
+        if let Some(overwritten) = context.dest.dataset() {
+            for index_name in &context.preserved_indexes {
+                dataset.initialize_index(overwritten, index_name).await?;
+            }
+        }
+        Ok(dataset)
     }
 
     async fn write_uncommitted_impl(
@@ -190,6 +217,9 @@ impl<'a> InsertBuilder<'a> {
         schema: Schema,
     ) -> Result<(Transaction, WriteContext<'_>)> {
         let mut context = self.resolve_context().await?;
+        if let Some(dataset) = context.dest.dataset() {
+            dataset.check_writable()?;
+        }
 
         info!(
             target: TRACE_DATASET_EVENTS,
@@ -198,12 +228,20 @@ impl<'a> InsertBuilder<'a> {
             mode=?context.params.mode
         );
 
+        let (stream, schema) = Self::evolve_schema(&mut context, stream, schema).await?;
         self.validate_write(&mut context, &schema)?;
 
         let existing_base_paths = context.dest.dataset().map(|ds| &ds.manifest.base_paths);
         let target_base_info =
             validate_and_resolve_target_bases(&mut context.params, existing_base_paths).await?;
 
+        let (stream, inserted_keys) = Self::enforce_unique(&context, stream, &schema).await?;
+        let appending_to = match (&context.params.mode, &context.dest) {
+            (WriteMode::Append, WriteDestination::Dataset(dataset)) => Some(dataset.as_ref()),
+            _ => None,
+        };
+        let (stream, sorted) = cluster_write(stream, &context.params, appending_to)?;
+
         let (written_fragments, written_schema) = write_fragments_internal(
             context.dest.dataset(),
             context.object_store.clone(),
@@ -215,7 +253,14 @@ impl<'a> InsertBuilder<'a> {
         )
         .await?;
 
-        let transaction = Self::build_transaction(written_schema, written_fragments, &context)?;
+        Self::plan_preserved_indexes(&mut context, &written_schema).await?;
+        let transaction = Self::build_transaction(
+            written_schema,
+            written_fragments,
+            inserted_keys.as_ref(),
+            sorted.as_ref(),
+            &context,
+        )?;
 
         Ok((transaction, context))
     }
@@ -223,6 +268,8 @@ impl<'a> InsertBuilder<'a> {
     fn build_transaction(
         schema: Schema,
         fragments: Vec<Fragment>,
+        inserted_keys: Option<&InsertedKeys>,
+        sorted: Option<&SortedWrite>,
         context: &WriteContext<'_>,
     ) -> Result<Transaction> {
         let operation = match context.params.mode {
@@ -243,6 +290,12 @@ impl<'a> InsertBuilder<'a> {
                         format_duration(duration).to_string(),
                     );
                 }
+                if let Some(sorted) = sorted {
+                    upsert_values.insert(
+                        CLUSTERING_CONFIG_KEY.to_string(),
+                        sorted.clustering().to_string(),
+                    );
+                }
                 let config_upsert_values = if upsert_values.is_empty() {
                     None
                 } else {
@@ -259,12 +312,51 @@ impl<'a> InsertBuilder<'a> {
             WriteMode::Overwrite => Operation::Overwrite {
                 schema,
                 fragments,
-                config_upsert_values: None,
+                config_upsert_values: sorted.map(|sorted| {
+                    HashMap::from([(
+                        CLUSTERING_CONFIG_KEY.to_string(),
+                        sorted.clustering().to_string(),
+                    )])
+                }),
                 initial_bases: context.params.initial_bases.clone(),
             },
-            WriteMode::Append => Operation::Append { fragments },
+            WriteMode::Append => match inserted_keys {
+                // Committed like the inserts of a merge insert, so that the
+                // commit checks the keys against the ones of concurrent writes.
+                Some(inserted_keys) => Operation::Update {
+                    removed_fragment_ids: vec![],
+                    updated_fragments: vec![],
+                    fields_for_preserving_frag_bitmap: schema
+                        .fields
+                        .iter()
+                        .map(|f| f.id as u32)
+                        .collect(),
+                    new_fragments: fragments,
+                    fields_modified: vec![],
+                    merged_generations: vec![],
+                    update_mode: Some(UpdateMode::RewriteRows),
+                    inserted_rows_filter: Some(inserted_keys.filter()),
+                    updated_fragment_offsets: None,
+                },
+                None => Operation::Append { fragments },
+            },
         };
 
+        let mut transaction_properties = vouch_sorted_by(
+            context.params.transaction_properties.clone(),
+            sorted.and_then(SortedWrite::sorted_by),
+        );
+        if !context.skipped_indexes.is_empty() {
+            let mut properties = transaction_properties
+                .as_deref()
+                .cloned()
+                .unwrap_or_default();
+            properties.insert(
+                SKIPPED_INDEXES_PROPERTY.to_string(),
+                serde_json::to_string(&context.skipped_indexes)?,
+            );
+            transaction_properties = Some(Arc::new(properties));
+        }
         let transaction = TransactionBuilder::new(
             context
                 .dest
@@ -273,12 +365,212 @@ impl<'a> InsertBuilder<'a> {
                 .unwrap_or(0),
             operation,
         )
-        .transaction_properties(context.params.transaction_properties.clone())
+        .transaction_properties(transaction_properties)
         .build();
 
         Ok(transaction)
     }
 
+    /// Apply [`WriteParams::preserve_indexes_on_identical_columns`] to an overwrite.
+    ///
+    /// Splits the indexes of the overwritten dataset into the ones that are rebuilt
+    /// after the commit and the ones that are dropped, with the reason.
+    async fn plan_preserved_indexes(context: &mut WriteContext<'_>, schema: &Schema) -> Result<()> {
+        let (true, WriteMode::Overwrite, WriteDestination::Dataset(dataset)) = (
+            context.params.preserve_indexes_on_identical_columns,
+            &context.params.mode,
+            &context.dest,
+        ) else {
+            return Ok(());
+        };
+        let dataset = dataset.clone();
+
+        let indices = dataset.load_indices().await?;
+        for index in indices.iter() {
+            if lance_index::is_system_index(index)
+                || context.preserved_indexes.contains(&index.name)
+                || context.skipped_indexes.contains_key(&index.name)
+            {
+                continue;
+            }
+            match Self::index_incompatibility(dataset.schema(), schema, index) {
+                None => context.preserved_indexes.push(index.name.clone()),
+                Some(reason) => {
+                    log::warn!("Overwrite drops index '{}': {}", index.name, reason);
+                    context.skipped_indexes.insert(index.name.clone(), reason);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Why `index` on `old_schema` can't be rebuilt on data of `new_schema`, if it can't
+    fn index_incompatibility(
+        old_schema: &Schema,
+        new_schema: &Schema,
+        index: &IndexMetadata,
+    ) -> Option<String> {
+        if index.index_details.is_none() {
+            return Some("the index parameters are not recorded".to_string());
+        }
+        if index.fields.len() != 1 {
+            return Some("only indexes on a single column are rebuilt".to_string());
+        }
+        for field_id in &index.fields {
+            let (Some(old_field), Ok(path)) = (
+                old_schema.field_by_id(*field_id),
+                old_schema.field_path(*field_id),
+            ) else {
+                return Some(format!("field {field_id} is not in the dataset"));
+            };
+            match new_schema.field(&path) {
+                None => return Some(format!("column '{path}' is not in the new data")),
+                Some(new_field) if new_field.data_type() != old_field.data_type() => {
+                    return Some(format!(
+                        "column '{path}' changes type from {} to {}",
+                        old_field.data_type(),
+                        new_field.data_type()
+                    ));
+                }
+                Some(_) => {}
+            }
+        }
+        None
+    }
+
+    /// Apply [`WriteParams::enforce_unique`].
+    ///
+    /// Appends check the keys against the dataset. Creating or overwriting a
+    /// dataset only checks the written keys against each other.
+    async fn enforce_unique(
+        context: &WriteContext<'_>,
+        stream: SendableRecordBatchStream,
+        schema: &Schema,
+    ) -> Result<(SendableRecordBatchStream, Option<InsertedKeys>)> {
+        let Some(policy) = context.params.enforce_unique.clone() else {
+            return Ok((stream, None));
+        };
+        let enforcer = match (&context.params.mode, &context.dest) {
+            (WriteMode::Append, WriteDestination::Dataset(dataset)) => {
+                UniqueKeyEnforcer::try_new(Some(dataset.clone()), dataset.schema(), policy, true)
+                    .await?
+            }
+            _ => UniqueKeyEnforcer::try_new(None, schema, policy, true).await?,
+        };
+        let (stream, inserted_keys) = enforcer.enforce(stream);
+        Ok((stream, Some(inserted_keys)))
+    }
+
+    /// Apply [`WriteParams::schema_evolution`] to an append.
+    ///
+    /// Every difference the mode does not allow is reported at once. New
+    /// columns are committed to the dataset before the data is written, so
+    /// the append is made on top of the version that has them.
+    async fn evolve_schema(
+        context: &mut WriteContext<'_>,
+        stream: SendableRecordBatchStream,
+        schema: Schema,
+    ) -> Result<(SendableRecordBatchStream, Schema)> {
+        let (Some(evolution), WriteMode::Append, WriteDestination::Dataset(dataset)) = (
+            context.params.schema_evolution,
+            &context.params.mode,
+            &context.dest,
+        ) else {
+            return Ok((stream, schema));
+        };
+        let dataset = dataset.clone();
+
+        let mut rejected = Vec::new();
+        let mut new_columns = Vec::new();
+        let mut coerce = false;
+        for difference in schema.diff(dataset.schema()).differences {
+            match (evolution, &difference) {
+                (SchemaEvolution::Strict, _) => rejected.push(difference),
+                // Allowed by every other mode, as they are without one.
+                (
+                    _,
+                    SchemaDifference::MissingField { nullable: true, .. }
+                    | SchemaDifference::NullabilityMismatch { .. },
+                ) => {}
+                (
+                    SchemaEvolution::AllowNewColumns,
+                    SchemaDifference::NewField {
+                        path,
+                        nullable: true,
+                        ..
+                    },
+                ) if path.len() == 1 => new_columns.push(path[0].clone()),
+                (
+                    SchemaEvolution::CoerceTypes,
+                    SchemaDifference::TypeMismatch {
+                        expected, actual, ..
+                    },
+                ) if is_lossless_cast(actual, expected) => coerce = true,
+                _ => rejected.push(difference),
+            }
+        }
+        if !rejected.is_empty() {
+            return Err(Error::schema_mismatch(format!(
+                "{} (schema evolution {:?})",
+                SchemaDiff {
+                    differences: rejected
+                },
+                evolution
+            )));
+        }
+
+        if !new_columns.is_empty() {
+            let arrow_schema = ArrowSchema::from(&schema);
+            let fields = new_columns
+                .iter()
+                .map(|name| arrow_schema.field_with_name(name).cloned())
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            log::info!(
+                "Adding columns {:?} to {} for an append",
+                new_columns,
+                dataset.uri()
+            );
+            let mut evolved = dataset.as_ref().clone();
+            evolved
+                .add_columns(
+                    NewColumnTransform::AllNulls(Arc::new(ArrowSchema::new(fields))),
+                    None,
+                    None,
+                )
+                .await?;
+            context.dest = WriteDestination::Dataset(Arc::new(evolved));
+        }
+
+        if !coerce {
+            return Ok((stream, schema));
+        }
+        let arrow_schema = ArrowSchema::from(&schema);
+        let target = Arc::new(ArrowSchema::new_with_metadata(
+            arrow_schema
+                .fields()
+                .iter()
+                .map(|field| match dataset.schema().field(field.name()) {
+                    Some(expected) => Arc::new(coerce_field(field, expected)),
+                    None => field.clone(),
+                })
+                .collect::<Vec<_>>(),
+            arrow_schema.metadata().clone(),
+        ));
+        let stream_target = target.clone();
+        let stream = stream.map(move |batch| -> DataFusionResult<RecordBatch> {
+            let batch = batch?;
+            let columns = batch
+                .columns()
+                .iter()
+                .zip(stream_target.fields())
+                .map(|(column, field)| arrow_cast::cast(column, field.data_type()))
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            Ok(RecordBatch::try_new(stream_target.clone(), columns)?)
+        });
+        let stream = Box::pin(RecordBatchStreamAdapter::new(target.clone(), stream));
+        Ok((stream, Schema::try_from(target.as_ref())?))
+    }
+
     fn validate_write(&self, context: &mut WriteContext, data_schema: &Schema) -> Result<()> {
         // Write mode
         match (&context.params.mode, &context.dest) {
@@ -424,11 +716,65 @@ impl<'a> InsertBuilder<'a> {
             base_path,
             commit_handler,
             storage_version,
+            preserved_indexes: Vec::new(),
+            skipped_indexes: BTreeMap::new(),
         })
     }
 }
 
 #[derive(Debug)]
+/// Whether every value of `from` is also a value of `to`.
+fn is_lossless_cast(from: &DataType, to: &DataType) -> bool {
+    use DataType::*;
+    matches!(
+        (from, to),
+        (Int8, Int16 | Int32 | Int64 | Float32 | Float64)
+            | (Int16, Int32 | Int64 | Float32 | Float64)
+            | (Int32, Int64 | Float64)
+            | (
+                UInt8,
+                UInt16 | UInt32 | UInt64 | Int16 | Int32 | Int64 | Float32 | Float64
+            )
+            | (UInt16, UInt32 | UInt64 | Int32 | Int64 | Float32 | Float64)
+            | (UInt32, UInt64 | Int64 | Float64)
+            | (Float16, Float32 | Float64)
+            | (Float32, Float64)
+            | (Utf8, LargeUtf8)
+            | (Binary, LargeBinary)
+    )
+}
+
+/// `field` with the types of its (nested) children widened to the ones in
+/// `expected` where [`is_lossless_cast`] allows it.
+fn coerce_field(field: &ArrowField, expected: &Field) -> ArrowField {
+    let coerce_item = |item: &FieldRef| match expected.children.first() {
+        Some(expected) => Arc::new(coerce_field(item, expected)),
+        None => item.clone(),
+    };
+    let data_type = match field.data_type() {
+        DataType::Struct(children) => DataType::Struct(
+            children
+                .iter()
+                .map(|child| match expected.child(child.name()) {
+                    Some(expected) => Arc::new(coerce_field(child, expected)),
+                    None => child.clone(),
+                })
+                .collect(),
+        ),
+        DataType::List(item) => DataType::List(coerce_item(item)),
+        DataType::LargeList(item) => DataType::LargeList(coerce_item(item)),
+        data_type => {
+            let expected_type = expected.data_type();
+            if is_lossless_cast(data_type, &expected_type) {
+                expected_type
+            } else {
+                data_type.clone()
+            }
+        }
+    };
+    field.clone().with_data_type(data_type)
+}
+
 struct WriteContext<'a> {
     params: WriteParams,
     dest: WriteDestination<'a>,
@@ -436,6 +782,10 @@ struct WriteContext<'a> {
     base_path: Path,
     commit_handler: Arc<dyn CommitHandler>,
     storage_version: LanceFileVersion,
+    /// The indexes of the overwritten dataset to rebuild after the commit
+    preserved_indexes: Vec<String>,
+    /// The indexes of the overwritten dataset that are dropped, with the reason
+    skipped_indexes: BTreeMap<String, String>,
 }
 
 #[cfg(test)]
@@ -708,4 +1058,408 @@ mod test {
         assert_eq!(last.rows_written, 300, "all 300 rows must be reported");
         assert_eq!(last.files_written, 1, "a single file should be written");
     }
+
+    mod schema_evolution {
+        use arrow_array::cast::AsArray;
+        use arrow_array::types::{Float64Type, Int64Type};
+        use arrow_array::{
+            ArrayRef, Float32Array, Float64Array, Int32Array, Int64Array, StringArray,
+        };
+
+        use super::*;
+
+        /// `id` and a nested `meta` struct, with the given types.
+        fn batch(
+            id: ArrayRef,
+            meta: Vec<(&str, ArrayRef)>,
+            extra: Option<(&str, ArrayRef)>,
+        ) -> RecordBatch {
+            let meta = StructArray::from(
+                meta.into_iter()
+                    .map(|(name, array)| {
+                        (
+                            Arc::new(Field::new(name, array.data_type().clone(), true)),
+                            array,
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+            );
+            let mut fields = vec![
+                Field::new("id", id.data_type().clone(), false),
+                Field::new("meta", meta.data_type().clone(), true),
+            ];
+            let mut columns = vec![id, Arc::new(meta) as ArrayRef];
+            if let Some((name, array)) = extra {
+                fields.push(Field::new(name, array.data_type().clone(), true));
+                columns.push(array);
+            }
+            RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).unwrap()
+        }
+
+        fn base_batch() -> RecordBatch {
+            batch(
+                Arc::new(Int64Array::from(vec![1, 2])),
+                vec![
+                    ("score", Arc::new(Float64Array::from(vec![0.5, 1.5]))),
+                    ("label", Arc::new(StringArray::from(vec!["a", "b"]))),
+                ],
+                None,
+            )
+        }
+
+        async fn dataset() -> Arc<Dataset> {
+            let dataset = InsertBuilder::new("memory://")
+                .execute(vec![base_batch()])
+                .await
+                .unwrap();
+            Arc::new(dataset)
+        }
+
+        async fn append(
+            dataset: &Arc<Dataset>,
+            evolution: Option<SchemaEvolution>,
+            data: RecordBatch,
+        ) -> Result<Dataset> {
+            InsertBuilder::new(dataset.clone())
+                .with_params(&WriteParams {
+                    mode: WriteMode::Append,
+                    schema_evolution: evolution,
+                    ..Default::default()
+                })
+                .execute(vec![data])
+                .await
+        }
+
+        fn mismatch(result: Result<Dataset>) -> String {
+            match result {
+                Err(Error::SchemaMismatch { difference, .. }) => difference,
+                Err(err) => panic!("expected a schema mismatch, got {err}"),
+                Ok(_) => panic!("expected a schema mismatch"),
+            }
+        }
+
+        #[test]
+        fn test_parse_schema_evolution() {
+            assert_eq!(
+                SchemaEvolution::try_from("allow_new_columns").unwrap(),
+                SchemaEvolution::AllowNewColumns
+            );
+            assert_eq!(
+                SchemaEvolution::try_from("COERCE_TYPES").unwrap(),
+                SchemaEvolution::CoerceTypes
+            );
+            assert_eq!(
+                SchemaEvolution::try_from("strict").unwrap(),
+                SchemaEvolution::Strict
+            );
+            assert!(SchemaEvolution::try_from("lenient").is_err());
+        }
+
+        #[tokio::test]
+        async fn test_strict() {
+            let dataset = dataset().await;
+            append(&dataset, Some(SchemaEvolution::Strict), base_batch())
+                .await
+                .unwrap();
+
+            // Each difference is reported with its path, including the ones
+            // that are allowed without a mode.
+            let data = batch(
+                Arc::new(Int32Array::from(vec![3])),
+                vec![("score", Arc::new(Float32Array::from(vec![2.5])))],
+                None,
+            );
+            let message = mismatch(append(&dataset, Some(SchemaEvolution::Strict), data).await);
+            assert_eq!(
+                message,
+                "id should have type Int64 but was Int32; \
+                 meta.score should have type Float64 but was Float32; \
+                 missing field meta.label (nullable) (schema evolution Strict)"
+            );
+        }
+
+        #[tokio::test]
+        async fn test_coerce_types() {
+            let dataset = dataset().await;
+            let data = batch(
+                Arc::new(Int32Array::from(vec![3])),
+                vec![
+                    ("score", Arc::new(Float32Array::from(vec![2.5]))),
+                    ("label", Arc::new(StringArray::from(vec!["c"]))),
+                ],
+                None,
+            );
+            let dataset = append(&dataset, Some(SchemaEvolution::CoerceTypes), data)
+                .await
+                .unwrap();
+            assert_eq!(
+                dataset.schema().field("meta.score").unwrap().data_type(),
+                DataType::Float64
+            );
+            let scanned = dataset.scan().try_into_batch().await.unwrap();
+            assert_eq!(
+                scanned["id"].as_primitive::<Int64Type>().values(),
+                &[1, 2, 3]
+            );
+            let meta = scanned["meta"].as_struct();
+            assert_eq!(
+                meta["score"].as_primitive::<Float64Type>().values(),
+                &[0.5, 1.5, 2.5]
+            );
+
+            // Narrowing and new columns are not coerced.
+            let dataset = Arc::new(dataset);
+            let data = batch(
+                Arc::new(Int64Array::from(vec![4])),
+                vec![("score", Arc::new(StringArray::from(vec!["high"])))],
+                Some(("extra", Arc::new(Int32Array::from(vec![1])))),
+            );
+            let message =
+                mismatch(append(&dataset, Some(SchemaEvolution::CoerceTypes), data).await);
+            assert_eq!(
+                message,
+                "meta.score should have type Float64 but was Utf8; \
+                 unexpected field extra (Int32, nullable) (schema evolution CoerceTypes)"
+            );
+        }
+
+        #[tokio::test]
+        async fn test_allow_new_columns() {
+            let dataset = dataset().await;
+            let data = batch(
+                Arc::new(Int64Array::from(vec![3])),
+                vec![("score", Arc::new(Float64Array::from(vec![2.5])))],
+                Some(("extra", Arc::new(StringArray::from(vec!["new"])))),
+            );
+            let dataset = append(&dataset, Some(SchemaEvolution::AllowNewColumns), data)
+                .await
+                .unwrap();
+            assert!(dataset.schema().field("extra").is_some());
+            // The columns were added by their own commit.
+            assert_eq!(dataset.manifest.version, 3);
+            let scanned = dataset.scan().try_into_batch().await.unwrap();
+            let extra = scanned["extra"].as_string::<i32>();
+            assert_eq!(
+                extra.iter().collect::<Vec<_>>(),
+                vec![None, None, Some("new")]
+            );
+            let label = scanned["meta"].as_struct()["label"].as_string::<i32>();
+            assert_eq!(
+                label.iter().collect::<Vec<_>>(),
+                vec![Some("a"), Some("b"), None]
+            );
+
+            // New nested fields are not added, nor are types changed.
+            let dataset = Arc::new(dataset);
+            let data = batch(
+                Arc::new(Int32Array::from(vec![4])),
+                vec![
+                    ("score", Arc::new(Float64Array::from(vec![3.5]))),
+                    ("rank", Arc::new(Int32Array::from(vec![1]))),
+                ],
+                None,
+            );
+            let message =
+                mismatch(append(&dataset, Some(SchemaEvolution::AllowNewColumns), data).await);
+            assert_eq!(
+                message,
+                "id should have type Int64 but was Int32; \
+                 unexpected field meta.rank (Int32, nullable) (schema evolution AllowNewColumns)"
+            );
+            assert_eq!(dataset.manifest.version, 3);
+        }
+
+        #[tokio::test]
+        async fn test_without_schema_evolution() {
+            let dataset = dataset().await;
+            // A missing nullable field is fine, a new column is not.
+            let data = batch(
+                Arc::new(Int64Array::from(vec![3])),
+                vec![("score", Arc::new(Float64Array::from(vec![2.5])))],
+                None,
+            );
+            append(&dataset, None, data).await.unwrap();
+            let data = batch(
+                Arc::new(Int64Array::from(vec![3])),
+                vec![("score", Arc::new(Float64Array::from(vec![2.5])))],
+                Some(("extra", Arc::new(StringArray::from(vec!["new"])))),
+            );
+            assert!(append(&dataset, None, data).await.is_err());
+        }
+    }
+
+    mod preserve_indexes {
+        use std::collections::BTreeSet;
+
+        use arrow_array::types::{Float32Type, Int32Type};
+        use lance_core::utils::tempfile::TempStrDir;
+        use lance_datagen::{BatchCount, RowCount, array};
+        use lance_index::IndexType;
+        use lance_index::scalar::{InvertedIndexParams, ScalarIndexParams};
+        use lance_linalg::distance::MetricType;
+
+        use crate::index::vector::VectorIndexParams;
+
+        use super::*;
+
+        fn data(text_as_int: bool, first_id: i32) -> impl RecordBatchReader + Send + 'static {
+            let text = if text_as_int {
+                array::step::<Int32Type>()
+            } else {
+                array::cycle_utf8_literals(&["hello world", "foo bar", "test data"])
+            };
+            lance_datagen::gen_batch()
+                .col("vector", array::rand_vec::<Float32Type>(8.into()))
+                .col("text", text)
+                .col("id", array::step_custom::<Int32Type>(first_id, 1))
+                .into_reader_rows(RowCount::from(300), BatchCount::from(1))
+        }
+
+        async fn indexed_dataset(uri: &str) -> Dataset {
+            let mut dataset = Dataset::write(data(false, 0), uri, None).await.unwrap();
+            dataset
+                .create_index(
+                    &["vector"],
+                    IndexType::Vector,
+                    Some("vec_idx".to_string()),
+                    &VectorIndexParams::ivf_pq(4, 8, 2, MetricType::L2, 10),
+                    false,
+                )
+                .await
+                .unwrap();
+            dataset
+                .create_index(
+                    &["text"],
+                    IndexType::Inverted,
+                    Some("text_idx".to_string()),
+                    &InvertedIndexParams::default()
+                        .with_position(true)
+                        .stem(false),
+                    false,
+                )
+                .await
+                .unwrap();
+            dataset
+                .create_index(
+                    &["id"],
+                    IndexType::BTree,
+                    Some("id_idx".to_string()),
+                    &ScalarIndexParams::default(),
+                    false,
+                )
+                .await
+                .unwrap();
+            dataset
+        }
+
+        async fn describe(dataset: &Dataset) -> Vec<(String, String, String)> {
+            dataset
+                .describe_indices(None)
+                .await
+                .unwrap()
+                .iter()
+                .map(|index| {
+                    (
+                        index.name().to_string(),
+                        index.index_type().to_string(),
+                        index.details().unwrap(),
+                    )
+                })
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect()
+        }
+
+        fn overwrite_params(preserve_indexes: bool) -> WriteParams {
+            WriteParams {
+                mode: WriteMode::Overwrite,
+                preserve_indexes_on_identical_columns: preserve_indexes,
+                ..Default::default()
+            }
+        }
+
+        #[tokio::test]
+        async fn test_overwrite_rebuilds_indexes() {
+            let test_dir = TempStrDir::default();
+            let dataset = indexed_dataset(test_dir.as_str()).await;
+            let before = describe(&dataset).await;
+            assert_eq!(before.len(), 3);
+
+            let dataset = Dataset::write(
+                data(false, 1000),
+                test_dir.as_str(),
+                Some(overwrite_params(true)),
+            )
+            .await
+            .unwrap();
+            assert_eq!(describe(&dataset).await, before);
+            for name in ["vec_idx", "text_idx", "id_idx"] {
+                assert!(dataset.unindexed_fragments(name).await.unwrap().is_empty());
+            }
+            let plan = dataset
+                .scan()
+                .filter("id = 1125")
+                .unwrap()
+                .explain_plan(false)
+                .await
+                .unwrap();
+            assert!(plan.contains("ScalarIndexQuery"), "{plan}");
+            let batch = dataset
+                .scan()
+                .filter("id = 1125")
+                .unwrap()
+                .try_into_batch()
+                .await
+                .unwrap();
+            assert_eq!(batch.num_rows(), 1);
+
+            // Without the option an overwrite drops every index
+            let dataset = Dataset::write(
+                data(false, 0),
+                test_dir.as_str(),
+                Some(overwrite_params(false)),
+            )
+            .await
+            .unwrap();
+            assert!(dataset.load_indices().await.unwrap().is_empty());
+        }
+
+        #[tokio::test]
+        async fn test_overwrite_skips_changed_columns() {
+            let test_dir = TempStrDir::default();
+            indexed_dataset(test_dir.as_str()).await;
+
+            let dataset = Dataset::write(
+                data(true, 0),
+                test_dir.as_str(),
+                Some(overwrite_params(true)),
+            )
+            .await
+            .unwrap();
+            let names = dataset
+                .load_indices()
+                .await
+                .unwrap()
+                .iter()
+                .map(|index| index.name.clone())
+                .collect::<BTreeSet<_>>();
+            assert_eq!(
+                names,
+                BTreeSet::from(["id_idx".to_string(), "vec_idx".to_string()])
+            );
+
+            // The overwrite is followed by one commit per rebuilt index
+            let overwrite = dataset
+                .read_transaction_by_version(dataset.version().version - 2)
+                .await
+                .unwrap()
+                .unwrap();
+            assert!(matches!(overwrite.operation, Operation::Overwrite { .. }));
+            let properties = overwrite.transaction_properties.unwrap();
+            assert_eq!(
+                properties[SKIPPED_INDEXES_PROPERTY],
+                r#"{"text_idx":"column 'text' changes type from Utf8 to Int32"}"#
+            );
+        }
+    }
 }
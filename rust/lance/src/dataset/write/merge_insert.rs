@@ -43,6 +43,7 @@ use inserted_rows::KeyExistenceFilter;
 
 use super::cleanup_data_fragments;
 use super::retry::{RetryConfig, RetryExecutor, execute_with_retry};
+use super::unique::{DuplicateKeyPolicy, UniqueKeyEnforcer};
 use super::{CommitBuilder, WriteParams, write_fragments_internal};
 use crate::dataset::rowids::get_row_id_index;
 use crate::dataset::transaction::UpdateMode::{RewriteColumns, RewriteRows};
@@ -358,6 +359,8 @@ pub struct MergeInsertJob {
     dataset: Arc<Dataset>,
     // The parameters controlling how to merge the two streams
     params: MergeInsertParams,
+    // How to handle source rows with duplicate primary keys, if enforced
+    enforce_unique: Option<DuplicateKeyPolicy>,
 }
 
 /// Build a merge insert operation.
@@ -407,6 +410,7 @@ pub struct MergeInsertJob {
 pub struct MergeInsertBuilder {
     dataset: Arc<Dataset>,
     params: MergeInsertParams,
+    enforce_unique: Option<DuplicateKeyPolicy>,
 }
 
 impl MergeInsertBuilder {
@@ -469,6 +473,7 @@ impl MergeInsertBuilder {
                 source_dedupe_behavior: SourceDedupeBehavior::Fail,
                 commit_retries: None,
             },
+            enforce_unique: None,
         })
     }
 
@@ -551,6 +556,18 @@ impl MergeInsertBuilder {
         self
     }
 
+    /// Require the inserted rows to have unique primary keys, see
+    /// [`DuplicateKeyPolicy`].
+    ///
+    /// The job must join on the primary key, and every key column needs a
+    /// BTree or Bitmap index. Source rows that repeat a key are handled by the
+    /// policy, rows matching the dataset are merged as usual. The commit fails
+    /// if a concurrent write inserted one of the new keys.
+    pub fn enforce_unique(&mut self, policy: DuplicateKeyPolicy) -> &mut Self {
+        self.enforce_unique = Some(policy);
+        self
+    }
+
     /// Mark MemWAL region generations as merged when this commit succeeds.
     /// This updates the merged_generations in the MemWAL Index atomically with the data commit.
     pub fn mark_generations_as_merged(&mut self, generations: Vec<MergedGeneration>) -> &mut Self {
@@ -579,6 +596,7 @@ impl MergeInsertBuilder {
         Ok(MergeInsertJob {
             dataset: self.dataset.clone(),
             params: self.params.clone(),
+            enforce_unique: self.enforce_unique.clone(),
         })
     }
 }
@@ -989,6 +1007,7 @@ impl MergeInsertJob {
                         &write_schema,
                         &dataset.base,
                         data_storage_version,
+                        dataset.key_provider(),
                     )
                     .await?;
 
@@ -1399,15 +1418,6 @@ impl MergeInsertJob {
             .collect::<Vec<_>>();
         let on_cols_refs = on_cols.iter().map(|s| s.as_str()).collect::<Vec<_>>();
         let source_df = session_ctx.read_one_shot(source)?;
-        // Capture the source field names *before* aliasing / joining so we
-        // can tell which dataset columns are missing from the source and
-        // need to be filled from the target side of the join below.
-        let source_field_names: std::collections::HashSet<String> = source_df
-            .schema()
-            .fields()
-            .iter()
-            .map(|f| f.name().clone())
-            .collect();
         // Inject a sentinel literal column so we can reliably determine, after the join,
         // whether the source side contributed a row.  This is NULL-safe: even when every
         // ON column is NULL the sentinel lets us distinguish a source-only row from a
@@ -1419,7 +1429,7 @@ impl MergeInsertJob {
         let scan_aliased = scan.alias("target")?;
         let join_type = self.create_plan_join_type();
         let dataset_schema: Schema = self.dataset.schema().into();
-        let mut df = scan_aliased
+        let df = scan_aliased
             .join(
                 source_df_aliased,
                 join_type,
@@ -1432,26 +1442,10 @@ impl MergeInsertJob {
                 merge_insert_action(&self.params, Some(&dataset_schema))?,
             )?;
 
-        // Partial-schema upsert: for every dataset column missing from the
-        // source, add a synthetic unqualified column that copies the target
-        // side's value for that column. For matched rows this carries the
-        // existing target value (preserving non-source columns on update);
-        // for unmatched source rows (inserts) the outer join leaves the
-        // target side NULL, so inserts get NULL for missing columns. The
-        // unqualified name matches the dataset field and becomes a normal
-        // data column from the write exec's perspective.
-        //
-        // We iterate the dataset schema in order so that the resulting
-        // physical plan is deterministic and easy to inspect in tests.
-        for field in dataset_schema.fields() {
-            if !source_field_names.contains(field.name()) {
-                df = df.with_column(
-                    field.name(),
-                    logical_expr::col(format!("target.\"{}\"", field.name())),
-                )?;
-            }
-        }
-
+        // Partial-schema upsert: dataset columns missing from the source are
+        // not pulled through the join, so the scan only reads the keys and
+        // the columns the merge conditions need. The write exec takes them
+        // by `_rowaddr` for the rows it rewrites.
         let (session_state, logical_plan) = df.into_parts();
 
         let write_node = logical_plan::MergeInsertWriteNode::new(
@@ -1582,8 +1576,8 @@ impl MergeInsertJob {
         );
 
         // Partial-schema upsert: every source field must exist in the target
-        // and have a compatible data type. Missing target columns will be
-        // filled from the target side of the join in `create_plan`.
+        // and have a compatible data type. Missing target columns are taken
+        // from the updated rows by the write exec.
         let is_subset_schema = !is_full_schema
             && lance_schema.fields.iter().all(|sf| {
                 full_schema
@@ -1661,6 +1655,44 @@ impl MergeInsertJob {
         self,
         source: SendableRecordBatchStream,
     ) -> Result<UncommittedMergeInsert> {
+        let Some(policy) = self.enforce_unique.clone() else {
+            return self.execute_uncommitted_merge(source).await;
+        };
+        let enforcer = UniqueKeyEnforcer::try_new(
+            Some(self.dataset.clone()),
+            self.dataset.schema(),
+            policy,
+            false,
+        )
+        .await?;
+        if enforcer.key_columns() != self.params.on.as_slice() {
+            return Err(Error::invalid_input(format!(
+                "enforce_unique requires the merge insert to join on the primary key {:?}, not {:?}",
+                enforcer.key_columns(),
+                self.params.on
+            )));
+        }
+        let (source, inserted_keys) = enforcer.enforce(source);
+        let mut uncommitted = self.execute_uncommitted_merge(source).await?;
+        // Only a full schema merge on the primary key without an index
+        // tracks the inserted keys itself.
+        if let Operation::Update {
+            inserted_rows_filter,
+            ..
+        } = &mut uncommitted.transaction.operation
+            && inserted_rows_filter.is_none()
+        {
+            *inserted_rows_filter = Some(inserted_keys.filter());
+            uncommitted.inserted_rows_filter = inserted_rows_filter.clone();
+        }
+        Ok(uncommitted)
+    }
+
+    async fn execute_uncommitted_merge(
+        self,
+        source: SendableRecordBatchStream,
+    ) -> Result<UncommittedMergeInsert> {
+        self.dataset.check_writable()?;
         // Check if we can use the fast path
         let can_use_fast_path = self.can_use_create_plan(source.schema().as_ref()).await?;
 
@@ -3950,22 +3982,135 @@ mod tests {
                 "expected HashJoinExec in plan, got: {}",
                 plan
             );
-            // Evidence that the partial-schema fix is active: the target
-            // side of the join reads the `other` column (which is missing
-            // from the source) and an explicit projection carries it
-            // through to the write exec alongside source columns.
+            // The target side of the join only reads the join key. The
+            // `other` column missing from the source is taken by the write
+            // exec for updated rows instead of being scanned for every row.
             assert!(
-                plan.contains("LanceRead") && plan.contains("projection=[other"),
-                "target-side scan should include the filled `other` column: {}",
+                plan.contains("LanceRead") && plan.contains("projection=[key]"),
+                "target-side scan should only read the join key: {}",
                 plan
             );
             assert!(
-                plan.contains("other@0 as other"),
-                "expected post-join projection to carry `other` from the target side: {}",
+                !plan.contains("projection=[other") && !plan.contains("as other"),
+                "the missing `other` column must not be scanned: {}",
                 plan
             );
         }
 
+        /// A dataset with a join key, an updated column and wide payload
+        /// columns a partial-schema source leaves out.
+        async fn setup_wide(enable_stable_row_ids: bool) -> (Arc<Dataset>, RecordBatch) {
+            let mut data = lance_datagen::gen_batch()
+                .with_seed(Seed::from(1))
+                .col("key", array::step::<UInt32Type>())
+                .col("value", array::step::<UInt32Type>());
+            for i in 0..8 {
+                data = data.col(format!("payload{}", i), array::rand_utf8(256.into(), false));
+            }
+            let batch = data.into_batch_rows(RowCount::from(2048)).unwrap();
+            let write_params = WriteParams {
+                max_rows_per_file: 512,
+                enable_stable_row_ids,
+                ..Default::default()
+            };
+            let ds = Dataset::write(
+                RecordBatchIterator::new([Ok(batch.clone())], batch.schema()),
+                "memory://",
+                Some(write_params),
+            )
+            .await
+            .unwrap();
+            (Arc::new(ds), batch)
+        }
+
+        #[rstest]
+        #[tokio::test]
+        async fn test_merge_insert_subcols_late_materialization(
+            #[values(false, true)] enable_stable_row_ids: bool,
+        ) {
+            let (ds, original) = setup_wide(enable_stable_row_ids).await;
+
+            // Update 10 rows spread over the fragments and insert 2.
+            let keys: Vec<u32> = (0..10).map(|i| i * 200 + 7).chain([5000, 5001]).collect();
+            let source_schema = Arc::new(Schema::new(vec![
+                Field::new("key", DataType::UInt32, true),
+                Field::new("value", DataType::UInt32, true),
+            ]));
+            let source = RecordBatch::try_new(
+                source_schema.clone(),
+                vec![
+                    Arc::new(UInt32Array::from(keys.clone())),
+                    Arc::new(UInt32Array::from_iter_values(
+                        keys.iter().map(|k| k + 100_000),
+                    )),
+                ],
+            )
+            .unwrap();
+
+            ds.object_store.as_ref().io_stats_incremental();
+            let (updated_ds, stats) =
+                MergeInsertBuilder::try_new(ds.clone(), vec!["key".to_string()])
+                    .unwrap()
+                    .when_matched(WhenMatched::UpdateAll)
+                    .when_not_matched(WhenNotMatched::InsertAll)
+                    .try_build()
+                    .unwrap()
+                    .execute_reader(Box::new(RecordBatchIterator::new(
+                        [Ok(source)],
+                        source_schema,
+                    )))
+                    .await
+                    .unwrap();
+            let io_stats = ds.object_store.as_ref().io_stats_incremental();
+            assert_eq!(stats.num_updated_rows, 10);
+            assert_eq!(stats.num_inserted_rows, 2);
+
+            // The payload columns take up about 4MiB. Only the keys and the
+            // payload of the 10 updated rows should be read.
+            lance_io::assert_io_lt!(io_stats, read_bytes, 512 * 1024);
+
+            let result = updated_ds
+                .scan()
+                .order_by(Some(vec![ColumnOrdering::asc_nulls_first(
+                    "key".to_string(),
+                )]))
+                .unwrap()
+                .try_into_batch()
+                .await
+                .unwrap();
+            assert_eq!(result.num_rows(), original.num_rows() + 2);
+            assert_eq!(result.schema().fields(), original.schema().fields());
+
+            // Every original row keeps its payload; updated rows have the
+            // new value and inserted rows have null payloads.
+            let expected_values = original["value"]
+                .as_primitive::<UInt32Type>()
+                .iter()
+                .zip(original["key"].as_primitive::<UInt32Type>().values())
+                .map(|(value, key)| {
+                    if keys.contains(key) {
+                        Some(key + 100_000)
+                    } else {
+                        value
+                    }
+                })
+                .chain([Some(105_000), Some(105_001)])
+                .collect::<UInt32Array>();
+            assert_eq!(
+                result["value"].as_primitive::<UInt32Type>(),
+                &expected_values
+            );
+            for i in 0..8 {
+                let name = format!("payload{}", i);
+                let payload = result[name.as_str()].as_string::<i32>();
+                assert_eq!(
+                    &payload.slice(0, original.num_rows()),
+                    original[name.as_str()].as_string::<i32>()
+                );
+                assert_eq!(payload.slice(original.num_rows(), 2).null_count(), 2);
+            }
+        }
+
         /// Partial-schema upserts with `insert_not_matched=InsertAll` must
         /// reject non-nullable missing columns at the API boundary instead
         /// of producing a confusing downstream writer error. The user-
@@ -4,9 +4,12 @@
 use std::collections::HashSet;
 use std::sync::{Arc, Mutex};
 
-use arrow_array::{Array, RecordBatch, UInt8Array, UInt64Array};
+use arrow_array::cast::AsArray;
+use arrow_array::types::UInt64Type;
+use arrow_array::{Array, ArrayRef, RecordBatch, UInt8Array, UInt64Array, new_null_array};
 use arrow_schema::Schema;
 use arrow_select;
+use arrow_select::interleave::interleave;
 use datafusion::common::{DataFusionError, Result as DFResult};
 use datafusion::physical_plan::metrics::{BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet};
 use datafusion::{
@@ -18,8 +21,9 @@ use datafusion::{
     },
 };
 use datafusion_physical_expr::{EquivalenceProperties, Partitioning};
-use futures::{StreamExt, stream};
+use futures::{StreamExt, TryStreamExt, stream};
 use lance_core::{Error, ROW_ADDR, ROW_ID};
+use lance_datafusion::projection::ProjectionPlan;
 use lance_table::format::RowIdMeta;
 use roaring::RoaringTreemap;
 
@@ -35,6 +39,7 @@ use crate::dataset::write::merge_insert::{
 use crate::{
     Dataset,
     dataset::{
+        ProjectionRequest, TakeBuilder,
         transaction::{Operation, Transaction},
         write::{
             WriteParams,
@@ -170,6 +175,94 @@ impl MergeState {
     }
 }
 
+/// Where a column of the write stream comes from.
+#[derive(Debug, Clone, Copy)]
+enum LateColumn {
+    /// A column of the filtered batch.
+    Input(usize),
+    /// A dataset column the input doesn't carry.
+    Taken,
+}
+
+/// Materializes the dataset columns a partial-schema source leaves out.
+///
+/// Scanning them with the join would read them for every target row, even
+/// though only the rows being rewritten need them. Instead, the filtered
+/// batches carry `_rowaddr` and the missing columns are taken by address
+/// for updated rows only. Inserted rows get nulls.
+#[derive(Debug, Clone)]
+struct LateMaterialization {
+    dataset: Arc<Dataset>,
+    projection: Arc<ProjectionPlan>,
+    /// Index of `_rowaddr` in the filtered batch.
+    row_addr_idx: usize,
+    /// One entry per column of `output_schema`.
+    columns: Vec<LateColumn>,
+    output_schema: Arc<Schema>,
+}
+
+impl LateMaterialization {
+    async fn materialize(&self, batch: RecordBatch) -> DFResult<RecordBatch> {
+        let row_addrs = batch.column(self.row_addr_idx).as_primitive::<UInt64Type>();
+        let mut addrs = Vec::with_capacity(batch.num_rows());
+        // (array, row) pairs to interleave: array 0 is the taken rows, array
+        // 1 a single null.
+        let mut indices = Vec::with_capacity(batch.num_rows());
+        for row_addr in row_addrs.iter() {
+            match row_addr {
+                Some(row_addr) => {
+                    indices.push((0, addrs.len()));
+                    addrs.push(row_addr);
+                }
+                None => indices.push((1, 0)),
+            }
+        }
+        let all_taken = addrs.len() == batch.num_rows();
+        let taken = if addrs.is_empty() {
+            None
+        } else {
+            Some(
+                TakeBuilder::try_new_from_addresses(
+                    self.dataset.clone(),
+                    addrs,
+                    self.projection.clone(),
+                )?
+                .execute()
+                .await?,
+            )
+        };
+
+        let columns = self
+            .columns
+            .iter()
+            .zip(self.output_schema.fields())
+            .map(|(column, field)| -> DFResult<ArrayRef> {
+                match (column, &taken) {
+                    (LateColumn::Input(idx), _) => Ok(batch.column(*idx).clone()),
+                    (LateColumn::Taken, None) => {
+                        Ok(new_null_array(field.data_type(), batch.num_rows()))
+                    }
+                    (LateColumn::Taken, Some(taken)) => {
+                        let values = taken.column_by_name(field.name()).ok_or_else(|| {
+                            DataFusionError::Internal(format!(
+                                "Column {:?} missing from the rows taken for merge insert",
+                                field.name()
+                            ))
+                        })?;
+                        if all_taken {
+                            return Ok(values.clone());
+                        }
+                        let nulls = new_null_array(values.data_type(), 1);
+                        Ok(interleave(&[values.as_ref(), nulls.as_ref()], &indices)?)
+                    }
+                }
+            })
+            .collect::<DFResult<Vec<_>>>()?;
+
+        Ok(RecordBatch::try_new(self.output_schema.clone(), columns)?)
+    }
+}
+
 /// Inserts new rows and updates existing rows in the target table.
 ///
 /// This does the actual write.
@@ -305,8 +398,15 @@ impl FullSchemaMergeInsertExec {
         input_stream: SendableRecordBatchStream,
         merge_state: Arc<Mutex<MergeState>>,
     ) -> DFResult<SendableRecordBatchStream> {
-        let (_, rowaddr_idx, rowid_idx, action_idx, data_column_indices, output_schema) =
-            self.prepare_stream_schema(input_stream.schema())?;
+        let (
+            _,
+            rowaddr_idx,
+            rowid_idx,
+            action_idx,
+            data_column_indices,
+            output_schema,
+            late_materialization,
+        ) = self.prepare_stream_schema(input_stream.schema())?;
 
         let output_schema_clone = output_schema.clone();
         let stream = input_stream.map(move |batch_result| -> DFResult<RecordBatch> {
@@ -349,6 +449,18 @@ impl FullSchemaMergeInsertExec {
             )
         });
 
+        if let Some(late_materialization) = late_materialization {
+            let output_schema = late_materialization.output_schema.clone();
+            let stream = stream.and_then(move |batch| {
+                let late_materialization = late_materialization.clone();
+                async move { late_materialization.materialize(batch).await }
+            });
+            return Ok(Box::pin(RecordBatchStreamAdapter::new(
+                output_schema,
+                stream,
+            )));
+        }
+
         Ok(Box::pin(RecordBatchStreamAdapter::new(
             output_schema,
             stream,
@@ -391,6 +503,7 @@ impl FullSchemaMergeInsertExec {
         usize,
         Vec<usize>,
         Arc<Schema>,
+        Option<LateMaterialization>,
     )> {
         // Find column indices
         let (rowaddr_idx, _) = input_schema.column_with_name(ROW_ADDR).ok_or_else(|| {
@@ -414,15 +527,9 @@ impl FullSchemaMergeInsertExec {
                 ))
             })?;
 
-        // Emit data columns in dataset-schema order, keyed by name. This
-        // matters for partial-schema upserts, where `create_plan` adds
-        // synthetic unqualified columns (filled from the target side) at
-        // the end of the logical projection. Without name-based reordering
-        // they would land at the end of the write stream and mismatch the
-        // intended writer schema (which is `dataset.schema()`). Using name
-        // lookup is also a strictly-safer choice for the full-schema path:
-        // it turns an implicit positional assumption into an explicit
-        // name-based invariant.
+        // Emit data columns in dataset-schema order, keyed by name. Using
+        // name lookup turns an implicit positional assumption into an
+        // explicit name-based invariant.
         let mut name_to_idx: std::collections::HashMap<&str, usize> =
             std::collections::HashMap::with_capacity(input_schema.fields().len());
         for (idx, field) in input_schema.fields().iter().enumerate() {
@@ -447,18 +554,19 @@ impl FullSchemaMergeInsertExec {
         let mut data_column_indices: Vec<usize> = Vec::with_capacity(dataset_fields.len());
         let mut output_fields: Vec<Arc<arrow_schema::Field>> =
             Vec::with_capacity(dataset_fields.len());
+        let mut late_columns = Vec::with_capacity(dataset_fields.len());
+        let mut missing_columns = Vec::new();
         for dataset_field in dataset_fields {
-            let idx = *name_to_idx
-                .get(dataset_field.name().as_str())
-                .ok_or_else(|| {
-                    datafusion::error::DataFusionError::Internal(format!(
-                        "Dataset field {:?} missing from merge insert input schema \
-                     — this indicates a logical plan pruning bug",
-                        dataset_field.name()
-                    ))
-                })?;
-            data_column_indices.push(idx);
-            output_fields.push(Arc::new(input_schema.field(idx).clone()));
+            if let Some(&idx) = name_to_idx.get(dataset_field.name().as_str()) {
+                late_columns.push(LateColumn::Input(data_column_indices.len()));
+                data_column_indices.push(idx);
+                output_fields.push(Arc::new(input_schema.field(idx).clone()));
+            } else {
+                // Partial-schema upsert: the source doesn't carry this
+                // column, so it is materialized after filtering.
+                late_columns.push(LateColumn::Taken);
+                missing_columns.push(dataset_field.name().as_str());
+            }
         }
 
         if data_column_indices.is_empty() {
@@ -467,6 +575,35 @@ impl FullSchemaMergeInsertExec {
             ));
         }
 
+        let late_materialization = if missing_columns.is_empty() {
+            None
+        } else {
+            let output_schema = Arc::new(Schema::new(
+                late_columns
+                    .iter()
+                    .zip(dataset_fields.iter())
+                    .map(|(column, dataset_field)| match column {
+                        LateColumn::Input(idx) => output_fields[*idx].clone(),
+                        LateColumn::Taken => dataset_field.clone(),
+                    })
+                    .collect::<Vec<_>>(),
+            ));
+            let projection =
+                ProjectionRequest::from(self.dataset.schema().project(&missing_columns)?)
+                    .into_projection_plan(self.dataset.clone())?;
+            // The filtered batches carry `_rowaddr` last so the missing
+            // columns of updated rows can be taken by address.
+            data_column_indices.push(rowaddr_idx);
+            output_fields.push(Arc::new(input_schema.field(rowaddr_idx).clone()));
+            Some(LateMaterialization {
+                dataset: self.dataset.clone(),
+                projection: Arc::new(projection),
+                row_addr_idx: output_fields.len() - 1,
+                columns: late_columns,
+                output_schema,
+            })
+        };
+
         let output_schema = Arc::new(Schema::new(output_fields));
 
         Ok((
@@ -476,6 +613,7 @@ impl FullSchemaMergeInsertExec {
             action_idx,
             data_column_indices,
             output_schema,
+            late_materialization,
         ))
     }
 
@@ -577,13 +715,23 @@ impl FullSchemaMergeInsertExec {
         input_stream: SendableRecordBatchStream,
         merge_state: Arc<Mutex<MergeState>>,
     ) -> DFResult<(SendableRecordBatchStream, SendableRecordBatchStream)> {
-        let (_, rowaddr_idx, rowid_idx, action_idx, data_column_indices, output_schema) =
-            self.prepare_stream_schema(input_stream.schema())?;
+        let (
+            _,
+            rowaddr_idx,
+            rowid_idx,
+            action_idx,
+            data_column_indices,
+            filtered_schema,
+            late_materialization,
+        ) = self.prepare_stream_schema(input_stream.schema())?;
+        let output_schema = late_materialization
+            .as_ref()
+            .map(|late| late.output_schema.clone())
+            .unwrap_or_else(|| filtered_schema.clone());
 
         let (update_tx, update_rx) = tokio::sync::mpsc::unbounded_channel();
         let (insert_tx, insert_rx) = tokio::sync::mpsc::unbounded_channel();
 
-        let output_schema_clone = output_schema.clone();
         let merge_state_clone = merge_state;
 
         tokio::spawn(async move {
@@ -592,15 +740,22 @@ impl FullSchemaMergeInsertExec {
             while let Some(batch_result) = input_stream.next().await {
                 match batch_result {
                     Ok(batch) => {
-                        match Self::process_and_split_batch(
+                        let split = Self::process_and_split_batch(
                             &batch,
                             rowaddr_idx,
                             rowid_idx,
                             action_idx,
                             &data_column_indices,
-                            output_schema_clone.clone(),
+                            filtered_schema.clone(),
                             merge_state_clone.clone(),
-                        ) {
+                        );
+                        let split = match (split, &late_materialization) {
+                            (Ok((update_batch, insert_batch)), Some(late)) => {
+                                Self::materialize_split(late, update_batch, insert_batch).await
+                            }
+                            (split, _) => split,
+                        };
+                        match split {
                             Ok((update_batch_opt, insert_batch_opt)) => {
                                 if let Some(update_batch) = update_batch_opt
                                     && update_tx.send(Ok(update_batch)).is_err()
@@ -640,6 +795,22 @@ impl FullSchemaMergeInsertExec {
         Ok((update_stream, insert_stream))
     }
 
+    async fn materialize_split(
+        late_materialization: &LateMaterialization,
+        update_batch: Option<RecordBatch>,
+        insert_batch: Option<RecordBatch>,
+    ) -> DFResult<(Option<RecordBatch>, Option<RecordBatch>)> {
+        let update_batch = match update_batch {
+            Some(batch) => Some(late_materialization.materialize(batch).await?),
+            None => None,
+        };
+        let insert_batch = match insert_batch {
+            Some(batch) => Some(late_materialization.materialize(batch).await?),
+            None => None,
+        };
+        Ok((update_batch, insert_batch))
+    }
+
     fn process_and_split_batch(
         batch: &RecordBatch,
         rowaddr_idx: usize,
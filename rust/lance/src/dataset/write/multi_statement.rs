@@ -0,0 +1,888 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use arrow::compute::kernels::zip::zip;
+use arrow::compute::{and, cast, filter_record_batch, not, prep_null_mask_filter};
+use arrow_array::cast::AsArray;
+use arrow_array::types::UInt64Type;
+use arrow_array::{Array, BooleanArray, RecordBatch};
+use arrow_schema::{Field as ArrowField, Schema as ArrowSchema, SchemaRef};
+use datafusion::error::DataFusionError;
+use datafusion::physical_plan::PhysicalExpr;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::prelude::Expr;
+use futures::TryStreamExt;
+use lance_arrow::RecordBatchExt;
+use lance_core::ROW_ADDR;
+use lance_select::RowAddrTreeMap;
+use lance_table::format::Fragment;
+use lance_table::io::deletion::DeletionFileOptions;
+use roaring::RoaringTreemap;
+
+use super::delete::apply_deletions;
+use super::retry::{RetryConfig, RetryExecutor, execute_with_retry};
+use super::{CommitBuilder, WriteParams, cleanup_data_fragments, write_fragments_internal};
+use crate::dataset::NewColumnTransform;
+use crate::dataset::fragment::FileFragment;
+use crate::dataset::schema_evolution::add_columns_to_fragments;
+use crate::dataset::transaction::UpdateMode::RewriteRows;
+use crate::dataset::transaction::{Operation, Transaction};
+use crate::io::exec::Planner;
+use crate::{Dataset, Error, Result};
+
+#[derive(Debug, Clone)]
+enum Statement {
+    Delete(String),
+    Append(Vec<RecordBatch>),
+    Update {
+        predicate: Option<String>,
+        updates: Vec<(String, String)>,
+    },
+    AddColumns(Vec<(String, String)>),
+}
+
+/// Build a transaction that commits several writes as a single new version.
+///
+/// The statements are run in the order they were added against the version
+/// of the dataset the builder was created from, and each one sees the effects
+/// of the statements before it. Nothing is visible to readers until all of
+/// them are committed together. If a concurrent commit conflicts with the
+/// transaction, the whole transaction is run again against the latest
+/// version.
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use arrow_array::RecordBatch;
+/// # use lance::{Dataset, Result};
+/// # async fn example(dataset: Arc<Dataset>, replacements: RecordBatch) -> Result<()> {
+/// let result = dataset
+///     .transaction()
+///     .delete("region_id = 10")
+///     .append(vec![replacements])
+///     .execute()
+///     .await?;
+/// println!("Deleted {} rows", result.num_deleted_rows);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Updated rows are moved to new fragments, like with
+/// [`UpdateBuilder`](super::update::UpdateBuilder), but they are given new row
+/// ids. Columns added with [`Self::add_columns`] are computed from the columns
+/// the dataset had before the transaction, and are not supported on datasets
+/// with stable row ids.
+#[derive(Debug, Clone)]
+pub struct MultiStatementBuilder {
+    dataset: Arc<Dataset>,
+    statements: Vec<Statement>,
+    conflict_retries: u32,
+    retry_timeout: Duration,
+}
+
+impl MultiStatementBuilder {
+    pub fn new(dataset: Arc<Dataset>) -> Self {
+        Self {
+            dataset,
+            statements: Vec::new(),
+            conflict_retries: 10,
+            retry_timeout: Duration::from_secs(30),
+        }
+    }
+
+    /// Delete the rows matching `predicate`.
+    pub fn delete(mut self, predicate: impl Into<String>) -> Self {
+        self.statements.push(Statement::Delete(predicate.into()));
+        self
+    }
+
+    /// Append `batches`, which must have the columns of the dataset, including
+    /// the ones added earlier in the transaction.
+    pub fn append(mut self, batches: Vec<RecordBatch>) -> Self {
+        self.statements.push(Statement::Append(batches));
+        self
+    }
+
+    /// Set columns to the given SQL expressions for the rows matching
+    /// `predicate`, or for all rows if it is `None`.
+    ///
+    /// All expressions are evaluated against the values before the update.
+    pub fn update(mut self, predicate: Option<&str>, updates: &[(&str, &str)]) -> Self {
+        self.statements.push(Statement::Update {
+            predicate: predicate.map(str::to_string),
+            updates: updates
+                .iter()
+                .map(|(column, value)| (column.to_string(), value.to_string()))
+                .collect(),
+        });
+        self
+    }
+
+    /// Add columns defined by SQL expressions over the existing columns.
+    pub fn add_columns(mut self, columns: &[(&str, &str)]) -> Self {
+        self.statements.push(Statement::AddColumns(
+            columns
+                .iter()
+                .map(|(name, expr)| (name.to_string(), expr.to_string()))
+                .collect(),
+        ));
+        self
+    }
+
+    /// Set the number of times to retry on commit conflicts.
+    ///
+    /// Default is 10.
+    pub fn conflict_retries(mut self, retries: u32) -> Self {
+        self.conflict_retries = retries;
+        self
+    }
+
+    /// Set the total timeout for all retries.
+    ///
+    /// Default is 30 seconds.
+    pub fn retry_timeout(mut self, timeout: Duration) -> Self {
+        self.retry_timeout = timeout;
+        self
+    }
+
+    /// Run the statements and commit them as one version.
+    pub async fn execute(self) -> Result<MultiStatementResult> {
+        if self.statements.is_empty() {
+            return Err(Error::invalid_input("No statements in transaction"));
+        }
+        let job = MultiStatementJob {
+            dataset: self.dataset.clone(),
+            statements: Arc::new(self.statements),
+        };
+        let config = RetryConfig {
+            max_retries: self.conflict_retries,
+            retry_timeout: self.retry_timeout,
+        };
+        Box::pin(execute_with_retry(job, self.dataset, config)).await
+    }
+}
+
+/// Result of a [`MultiStatementBuilder`] transaction.
+#[derive(Debug, Clone)]
+pub struct MultiStatementResult {
+    /// The new dataset after the transaction.
+    pub new_dataset: Arc<Dataset>,
+    /// The number of rows removed by delete statements.
+    pub num_deleted_rows: u64,
+    /// The number of rows added by append statements.
+    pub num_inserted_rows: u64,
+    /// The number of rows matched by update statements.
+    pub num_updated_rows: u64,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct RowCounts {
+    deleted: u64,
+    inserted: u64,
+    updated: u64,
+}
+
+#[derive(Debug)]
+struct MultiStatementData {
+    operation: Operation,
+    affected_rows: Option<RowAddrTreeMap>,
+    /// Fragments holding only the data files written by this attempt.
+    written: Vec<Fragment>,
+    counts: RowCounts,
+}
+
+#[derive(Debug, Clone)]
+struct MultiStatementJob {
+    dataset: Arc<Dataset>,
+    statements: Arc<Vec<Statement>>,
+}
+
+impl MultiStatementJob {
+    async fn stage(&self) -> Result<MultiStatementData> {
+        self.dataset.check_writable()?;
+        let mut staged = StagedChanges::new(self.dataset.clone());
+        for statement in self.statements.iter() {
+            match statement {
+                Statement::Delete(predicate) => staged.delete(predicate).await?,
+                Statement::Append(batches) => staged.append(batches)?,
+                Statement::Update { predicate, updates } => {
+                    staged.update(predicate.as_deref(), updates).await?
+                }
+                Statement::AddColumns(columns) => staged.add_columns(columns)?,
+            }
+        }
+        staged.finish().await
+    }
+
+    async fn commit_impl(
+        &self,
+        dataset: Arc<Dataset>,
+        data: MultiStatementData,
+    ) -> Result<MultiStatementResult> {
+        let transaction = Transaction::new(dataset.manifest.version, data.operation, None);
+        let mut builder = CommitBuilder::new(dataset.clone());
+        if let Some(affected_rows) = data.affected_rows {
+            builder = builder.with_affected_rows(affected_rows);
+        }
+        match builder.execute(transaction).await {
+            Ok(new_dataset) => Ok(MultiStatementResult {
+                new_dataset: Arc::new(new_dataset),
+                num_deleted_rows: data.counts.deleted,
+                num_inserted_rows: data.counts.inserted,
+                num_updated_rows: data.counts.updated,
+            }),
+            // Nothing was committed, so the files of this attempt are not
+            // referenced by any version.
+            Err(
+                err @ (Error::RetryableCommitConflict { .. }
+                | Error::IncompatibleTransaction { .. }),
+            ) => {
+                cleanup_data_fragments(&dataset.object_store, &dataset.base, &data.written).await;
+                Err(err)
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+impl RetryExecutor for MultiStatementJob {
+    type Data = MultiStatementData;
+    type Result = MultiStatementResult;
+
+    async fn execute_impl(&self) -> Result<Self::Data> {
+        self.stage().await
+    }
+
+    async fn commit(&self, dataset: Arc<Dataset>, data: Self::Data) -> Result<Self::Result> {
+        self.commit_impl(dataset, data).await
+    }
+
+    fn update_dataset(&mut self, dataset: Arc<Dataset>) {
+        self.dataset = dataset;
+    }
+}
+
+/// The state of a transaction between statements.
+///
+/// Rows of the dataset stay where they are until the transaction is finished,
+/// deletions are tracked by row address. Rows added or changed by the
+/// transaction are kept in memory in the transaction schema, which is the
+/// dataset schema followed by the added columns.
+struct StagedChanges {
+    dataset: Arc<Dataset>,
+    dataset_schema: SchemaRef,
+    schema: SchemaRef,
+    new_columns: Vec<(String, String)>,
+    /// Expressions of `new_columns` over `dataset_schema`.
+    new_column_exprs: Vec<Arc<dyn PhysicalExpr>>,
+    deleted: RoaringTreemap,
+    batches: Vec<RecordBatch>,
+    counts: RowCounts,
+}
+
+impl StagedChanges {
+    fn new(dataset: Arc<Dataset>) -> Self {
+        let dataset_schema: SchemaRef = Arc::new(dataset.schema().into());
+        Self {
+            dataset,
+            schema: dataset_schema.clone(),
+            dataset_schema,
+            new_columns: Vec::new(),
+            new_column_exprs: Vec::new(),
+            deleted: RoaringTreemap::new(),
+            batches: Vec::new(),
+            counts: RowCounts::default(),
+        }
+    }
+
+    fn planner(&self) -> Planner {
+        Planner::new(self.schema.clone())
+    }
+
+    fn parse_filter(&self, predicate: &str) -> Result<Expr> {
+        let planner = self.planner();
+        planner.optimize_expr(planner.parse_filter(predicate)?)
+    }
+
+    fn evaluate_filter(expr: &Arc<dyn PhysicalExpr>, batch: &RecordBatch) -> Result<BooleanArray> {
+        let mask = expr.evaluate(batch)?.into_array(batch.num_rows())?;
+        let mask = mask.as_boolean();
+        // Rows the predicate is null for do not match.
+        Ok(if mask.null_count() > 0 {
+            prep_null_mask_filter(mask)
+        } else {
+            mask.clone()
+        })
+    }
+
+    /// Add the columns added so far to a batch of the dataset schema.
+    fn with_new_columns(&self, batch: RecordBatch) -> Result<RecordBatch> {
+        let mut columns = batch.columns().to_vec();
+        for expr in &self.new_column_exprs {
+            columns.push(expr.evaluate(&batch)?.into_array(batch.num_rows())?);
+        }
+        Ok(RecordBatch::try_new(self.schema.clone(), columns)?)
+    }
+
+    /// Rows of the dataset matching `filter` that are not deleted yet, in the
+    /// transaction schema, with their row addresses.
+    async fn scan_matches(&self, filter: Option<&Expr>) -> Result<(Vec<RecordBatch>, Vec<u64>)> {
+        let mut scanner = self.dataset.scan();
+        scanner.with_row_address();
+        if let Some(filter) = filter
+            && Planner::column_names_in_expr(filter)
+                .iter()
+                .all(|name| self.dataset_schema.field_with_name(name).is_ok())
+        {
+            scanner.filter_expr(filter.clone());
+        }
+        let predicate = filter
+            .map(|filter| self.planner().create_physical_expr(filter))
+            .transpose()?;
+
+        let mut stream = scanner.try_into_stream().await?;
+        let mut matches = Vec::new();
+        let mut row_addrs = Vec::new();
+        while let Some(batch) = stream.try_next().await? {
+            let addrs = batch
+                .column_by_name(ROW_ADDR)
+                .ok_or_else(|| Error::internal("Scan did not return row addresses"))?
+                .clone();
+            let batch = self.with_new_columns(batch.drop_column(ROW_ADDR)?)?;
+            let mut mask = addrs
+                .as_primitive::<UInt64Type>()
+                .values()
+                .iter()
+                .map(|addr| Some(!self.deleted.contains(*addr)))
+                .collect::<BooleanArray>();
+            if let Some(predicate) = &predicate {
+                mask = and(&mask, &Self::evaluate_filter(predicate, &batch)?)?;
+            }
+            matches.push(filter_record_batch(&batch, &mask)?);
+            row_addrs.extend(
+                arrow::compute::filter(&addrs, &mask)?
+                    .as_primitive::<UInt64Type>()
+                    .values(),
+            );
+        }
+        Ok((matches, row_addrs))
+    }
+
+    async fn delete(&mut self, predicate: &str) -> Result<()> {
+        let expr = self.parse_filter(predicate)?;
+        let (_, row_addrs) = self.scan_matches(Some(&expr)).await?;
+        self.counts.deleted += row_addrs.len() as u64;
+        self.deleted.extend(row_addrs);
+
+        let expr = self.planner().create_physical_expr(&expr)?;
+        for batch in &mut self.batches {
+            let keep = not(&Self::evaluate_filter(&expr, batch)?)?;
+            self.counts.deleted += (batch.num_rows() - keep.true_count()) as u64;
+            *batch = filter_record_batch(batch, &keep)?;
+        }
+        Ok(())
+    }
+
+    fn append(&mut self, batches: &[RecordBatch]) -> Result<()> {
+        for batch in batches {
+            let columns = self
+                .schema
+                .fields()
+                .iter()
+                .map(|field| {
+                    let column = batch.column_by_name(field.name()).ok_or_else(|| {
+                        Error::invalid_input(format!(
+                            "Appended data is missing column '{}'",
+                            field.name()
+                        ))
+                    })?;
+                    Ok(cast(column, field.data_type())?)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            if batch.num_columns() != columns.len() {
+                return Err(Error::invalid_input(format!(
+                    "Appended data has columns that are not in the dataset: {}",
+                    batch.schema()
+                )));
+            }
+            self.counts.inserted += batch.num_rows() as u64;
+            self.batches
+                .push(RecordBatch::try_new(self.schema.clone(), columns)?);
+        }
+        Ok(())
+    }
+
+    async fn update(
+        &mut self,
+        predicate: Option<&str>,
+        updates: &[(String, String)],
+    ) -> Result<()> {
+        if updates.is_empty() {
+            return Err(Error::invalid_input("No updates provided"));
+        }
+        let planner = self.planner();
+        let updates = updates
+            .iter()
+            .map(|(column, value)| {
+                let (index, field) = self.schema.column_with_name(column).ok_or_else(|| {
+                    Error::invalid_input(format!(
+                        "Column '{}' does not exist in dataset schema: {:?}",
+                        column, self.schema
+                    ))
+                })?;
+                let expr = planner.optimize_expr(planner.parse_expr(value)?)?;
+                Ok((
+                    index,
+                    field.data_type().clone(),
+                    planner.create_physical_expr(&expr)?,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let apply = |batch: &RecordBatch, mask: Option<&BooleanArray>| -> Result<RecordBatch> {
+            let mut columns = batch.columns().to_vec();
+            for (index, data_type, expr) in &updates {
+                let values = expr.evaluate(batch)?.into_array(batch.num_rows())?;
+                let values = cast(&values, data_type)?;
+                columns[*index] = match mask {
+                    Some(mask) => zip(mask, &values, batch.column(*index))?,
+                    None => values,
+                };
+            }
+            Ok(RecordBatch::try_new(batch.schema(), columns)?)
+        };
+
+        let filter = predicate
+            .map(|predicate| self.parse_filter(predicate))
+            .transpose()?;
+        let (matches, row_addrs) = self.scan_matches(filter.as_ref()).await?;
+        self.counts.updated += row_addrs.len() as u64;
+        self.deleted.extend(row_addrs);
+        let matches = matches
+            .iter()
+            .map(|batch| apply(batch, None))
+            .collect::<Result<Vec<_>>>()?;
+
+        let filter = filter
+            .map(|filter| planner.create_physical_expr(&filter))
+            .transpose()?;
+        for batch in &mut self.batches {
+            let mask = filter
+                .as_ref()
+                .map(|filter| Self::evaluate_filter(filter, batch))
+                .transpose()?;
+            self.counts.updated +=
+                mask.as_ref()
+                    .map_or(batch.num_rows(), |mask| mask.true_count()) as u64;
+            *batch = apply(batch, mask.as_ref())?;
+        }
+        self.batches.extend(matches);
+        Ok(())
+    }
+
+    fn add_columns(&mut self, columns: &[(String, String)]) -> Result<()> {
+        if self.dataset.manifest.uses_stable_row_ids() {
+            return Err(Error::not_supported(
+                "Adding columns in a transaction is not supported on datasets with stable row ids",
+            ));
+        }
+        let planner = Planner::new(self.dataset_schema.clone());
+        let mut fields = self.schema.fields().to_vec();
+        for (name, value) in columns {
+            if fields.iter().any(|field| field.name() == name) {
+                return Err(Error::invalid_input(format!(
+                    "Column '{}' already exists in the dataset",
+                    name
+                )));
+            }
+            let expr = planner.optimize_expr(planner.parse_expr(value)?)?;
+            let expr = planner.create_physical_expr(&expr)?;
+            fields.push(Arc::new(ArrowField::new(
+                name,
+                expr.data_type(&self.dataset_schema)?,
+                expr.nullable(&self.dataset_schema)?,
+            )));
+            self.new_columns.push((name.clone(), value.clone()));
+            self.new_column_exprs.push(expr);
+        }
+        self.schema = Arc::new(ArrowSchema::new_with_metadata(
+            fields,
+            self.schema.metadata().clone(),
+        ));
+
+        let num_dataset_columns = self.dataset_schema.fields().len();
+        let batches = std::mem::take(&mut self.batches);
+        for batch in batches {
+            let mut columns = batch.columns().to_vec();
+            let dataset_columns = batch.project(&(0..num_dataset_columns).collect::<Vec<_>>())?;
+            for expr in &self.new_column_exprs[columns.len() - num_dataset_columns..] {
+                columns.push(
+                    expr.evaluate(&dataset_columns)?
+                        .into_array(batch.num_rows())?,
+                );
+            }
+            self.batches
+                .push(RecordBatch::try_new(self.schema.clone(), columns)?);
+        }
+        Ok(())
+    }
+
+    /// Write the staged rows and build the operation that commits all of the
+    /// changes at once.
+    async fn finish(self) -> Result<MultiStatementData> {
+        let dataset = self.dataset.clone();
+        let (updated_fragments, removed_fragment_ids) =
+            apply_deletions(&dataset, &self.deleted, &DeletionFileOptions::default()).await?;
+
+        if self.new_columns.is_empty() {
+            let new_fragments = self.write_batches(dataset.schema().clone()).await?;
+            let operation = Operation::Update {
+                removed_fragment_ids,
+                updated_fragments,
+                new_fragments: new_fragments.clone(),
+                // Changed rows are moved to new fragments, like in the
+                // "rewrite rows" mode of an update.
+                fields_modified: vec![],
+                merged_generations: Vec::new(),
+                fields_for_preserving_frag_bitmap: vec![],
+                update_mode: Some(RewriteRows),
+                inserted_rows_filter: None,
+                updated_fragment_offsets: None,
+            };
+            return Ok(MultiStatementData {
+                operation,
+                affected_rows: Some(RowAddrTreeMap::from(self.deleted)),
+                written: new_fragments,
+                counts: self.counts,
+            });
+        }
+
+        // Adding columns rewrites every fragment, so the transaction is
+        // committed as a merge of the final fragments.
+        let fragments = dataset
+            .manifest
+            .fragments
+            .iter()
+            .filter(|fragment| !removed_fragment_ids.contains(&fragment.id))
+            .map(|fragment| {
+                let fragment = updated_fragments
+                    .iter()
+                    .find(|updated| updated.id == fragment.id)
+                    .unwrap_or(fragment);
+                FileFragment::new(dataset.clone(), fragment.clone())
+            })
+            .collect::<Vec<_>>();
+        let (mut fragments, schema) = add_columns_to_fragments(
+            &dataset,
+            NewColumnTransform::SqlExpressions(self.new_columns.clone()),
+            None,
+            &fragments,
+            None,
+        )
+        .await?;
+        let mut written = fragments
+            .iter()
+            .map(|fragment| {
+                let mut new_files = fragment.clone();
+                let old_files = &dataset.manifest.fragments[..];
+                new_files.files.retain(|file| {
+                    !old_files
+                        .iter()
+                        .filter(|old| old.id == fragment.id)
+                        .any(|old| old.files.iter().any(|f| f.path == file.path))
+                });
+                new_files
+            })
+            .collect::<Vec<_>>();
+
+        let mut new_fragments = match self.write_batches(schema.clone()).await {
+            Ok(new_fragments) => new_fragments,
+            Err(err) => {
+                cleanup_data_fragments(&dataset.object_store, &dataset.base, &written).await;
+                return Err(err);
+            }
+        };
+        // Merge does not assign fragment ids, but it conflicts with any
+        // concurrent write that adds fragments.
+        let mut next_fragment_id = dataset.manifest.max_fragment_id().map_or(0, |id| id + 1);
+        for fragment in &mut new_fragments {
+            fragment.id = next_fragment_id;
+            next_fragment_id += 1;
+        }
+        written.extend(new_fragments.iter().cloned());
+        fragments.extend(new_fragments);
+
+        Ok(MultiStatementData {
+            operation: Operation::Merge { fragments, schema },
+            affected_rows: None,
+            written,
+            counts: self.counts,
+        })
+    }
+
+    async fn write_batches(&self, schema: lance_core::datatypes::Schema) -> Result<Vec<Fragment>> {
+        if self.batches.iter().all(|batch| batch.num_rows() == 0) {
+            return Ok(Vec::new());
+        }
+        let arrow_schema = Arc::new(ArrowSchema::from(&schema));
+        let batches = self
+            .batches
+            .iter()
+            .map(|batch| {
+                RecordBatch::try_new(arrow_schema.clone(), batch.columns().to_vec())
+                    .map_err(DataFusionError::from)
+            })
+            .collect::<Vec<_>>();
+        let stream = RecordBatchStreamAdapter::new(arrow_schema, futures::stream::iter(batches));
+        let version = self
+            .dataset
+            .manifest()
+            .data_storage_format
+            .lance_file_version()?;
+        // The schema is only checked against the dataset without added columns.
+        let dataset = self.new_columns.is_empty().then_some(self.dataset.as_ref());
+        let (fragments, _) = write_fragments_internal(
+            dataset,
+            self.dataset.object_store.clone(),
+            &self.dataset.base,
+            schema,
+            Box::pin(stream),
+            WriteParams::with_storage_version(version),
+            None,
+        )
+        .await?;
+        Ok(fragments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use arrow_array::{Int64Array, RecordBatchIterator, StringArray};
+    use arrow_schema::{DataType, Field};
+    use lance_core::utils::tempfile::TempStrDir;
+
+    use super::*;
+    use crate::dataset::WriteMode;
+
+    fn batch(ids: std::ops::Range<i64>, name: &str) -> RecordBatch {
+        let schema = Arc::new(ArrowSchema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, true),
+        ]));
+        let num_rows = ids.end - ids.start;
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Int64Array::from_iter_values(ids)),
+                Arc::new(StringArray::from_iter_values(std::iter::repeat_n(
+                    name,
+                    num_rows as usize,
+                ))),
+            ],
+        )
+        .unwrap()
+    }
+
+    async fn make_test_dataset(test_uri: &str) -> Arc<Dataset> {
+        let data = batch(0..30, "foo");
+        let write_params = WriteParams {
+            max_rows_per_file: 10,
+            ..Default::default()
+        };
+        let reader = RecordBatchIterator::new([Ok(data.clone())], data.schema());
+        Arc::new(
+            Dataset::write(reader, test_uri, Some(write_params))
+                .await
+                .unwrap(),
+        )
+    }
+
+    async fn rows(dataset: &Dataset, columns: &[&str]) -> RecordBatch {
+        let mut scanner = dataset.scan();
+        scanner.project(columns).unwrap();
+        scanner
+            .order_by(Some(vec![
+                crate::dataset::scanner::ColumnOrdering::asc_nulls_first("id".to_string()),
+            ]))
+            .unwrap();
+        scanner.try_into_batch().await.unwrap()
+    }
+
+    fn ids(batch: &RecordBatch) -> Vec<i64> {
+        batch["id"]
+            .as_primitive::<arrow_array::types::Int64Type>()
+            .values()
+            .to_vec()
+    }
+
+    #[tokio::test]
+    async fn test_delete_and_append_are_atomic() {
+        let test_dir = TempStrDir::default();
+        let dataset = make_test_dataset(&test_dir).await;
+        let base_version = dataset.version().version;
+
+        // A reader polls the latest version while the transaction runs and
+        // must never see the rows deleted without their replacements.
+        let done = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let reader = {
+            let mut dataset = dataset.as_ref().clone();
+            let done = done.clone();
+            tokio::spawn(async move {
+                let mut versions = HashSet::new();
+                loop {
+                    let finished = done.load(std::sync::atomic::Ordering::SeqCst);
+                    dataset.checkout_latest().await.unwrap();
+                    assert_eq!(dataset.count_rows(None).await.unwrap(), 30);
+                    versions.insert(dataset.version().version);
+                    if finished {
+                        return versions;
+                    }
+                    tokio::task::yield_now().await;
+                }
+            })
+        };
+
+        let result = dataset
+            .transaction()
+            .delete("id < 10")
+            .append(vec![batch(30..40, "bar")])
+            .execute()
+            .await
+            .unwrap();
+        done.store(true, std::sync::atomic::Ordering::SeqCst);
+        let versions = reader.await.unwrap();
+
+        assert_eq!(result.num_deleted_rows, 10);
+        assert_eq!(result.num_inserted_rows, 10);
+        assert_eq!(result.new_dataset.version().version, base_version + 1);
+        assert_eq!(
+            result.new_dataset.latest_version_id().await.unwrap(),
+            base_version + 1
+        );
+        assert!(versions.is_subset(&HashSet::from([base_version, base_version + 1])));
+        let rows = rows(&result.new_dataset, &["id"]).await;
+        assert_eq!(ids(&rows), (10..40).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_statements_see_earlier_statements() {
+        let test_dir = TempStrDir::default();
+        let dataset = make_test_dataset(&test_dir).await;
+
+        let result = dataset
+            .transaction()
+            .append(vec![batch(30..35, "new")])
+            // Matches both existing and appended rows.
+            .update(Some("id >= 28"), &[("name", "name || '!'")])
+            // Deletes an appended row and an updated row.
+            .delete("id = 31 OR name = 'foo!'")
+            .add_columns(&[("double_id", "id * 2")])
+            .update(Some("id = 0"), &[("double_id", "-1")])
+            .execute()
+            .await
+            .unwrap();
+        assert_eq!(result.num_inserted_rows, 5);
+        assert_eq!(result.num_updated_rows, 7 + 1);
+        assert_eq!(result.num_deleted_rows, 3);
+
+        let dataset = result.new_dataset;
+        let rows = rows(&dataset, &["id", "name", "double_id"]).await;
+        let expected_ids = (0..28).chain([30, 32, 33, 34]).collect::<Vec<_>>();
+        assert_eq!(ids(&rows), expected_ids);
+        let names = rows["name"].as_string::<i32>();
+        assert_eq!(names.value(0), "foo");
+        assert_eq!(names.value(28), "new!");
+        let double_ids = rows["double_id"].as_primitive::<arrow_array::types::Int64Type>();
+        assert_eq!(double_ids.value(0), -1);
+        assert_eq!(double_ids.value(1), 2);
+        assert_eq!(double_ids.value(31), 68);
+    }
+
+    #[tokio::test]
+    async fn test_conflict_is_retried() {
+        let test_dir = TempStrDir::default();
+        let dataset = make_test_dataset(&test_dir).await;
+        let base_version = dataset.version().version;
+
+        // Another writer commits after the transaction was started. Adding
+        // columns conflicts with the delete, so the transaction runs again on
+        // top of it.
+        let builder = dataset
+            .transaction()
+            .delete("id = 5")
+            .add_columns(&[("double_id", "id * 2")]);
+        let mut other = dataset.as_ref().clone();
+        other.delete("id = 20").await.unwrap();
+
+        let result = builder.execute().await.unwrap();
+        let dataset = result.new_dataset;
+        assert_eq!(dataset.version().version, base_version + 2);
+        let rows = rows(&dataset, &["id", "double_id"]).await;
+        let expected = (0..30)
+            .filter(|id| *id != 5 && *id != 20)
+            .collect::<Vec<_>>();
+        assert_eq!(ids(&rows), expected);
+        assert_eq!(rows["double_id"].null_count(), 0);
+    }
+
+    async fn data_files(test_uri: &str) -> HashSet<String> {
+        let data_dir = std::path::Path::new(test_uri).join("data");
+        std::fs::read_dir(data_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_failed_transaction_leaves_no_files() {
+        let test_dir = TempStrDir::default();
+        let dataset = make_test_dataset(&test_dir).await;
+        let base_version = dataset.version().version;
+
+        // A statement fails after rows were staged.
+        let err = dataset
+            .transaction()
+            .delete("id < 10")
+            .append(vec![batch(30..40, "bar").project(&[1]).unwrap()])
+            .execute()
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidInput { .. }), "{err}");
+
+        // The transaction is incompatible with a concurrent overwrite after it
+        // wrote its files.
+        let builder = dataset
+            .transaction()
+            .delete("id < 10")
+            .append(vec![batch(30..40, "bar")])
+            .add_columns(&[("double_id", "id * 2")]);
+        let overwrite = batch(100..110, "other");
+        let reader = RecordBatchIterator::new([Ok(overwrite.clone())], overwrite.schema());
+        let params = WriteParams {
+            mode: WriteMode::Overwrite,
+            ..Default::default()
+        };
+        let overwritten = Dataset::write(reader, &test_dir, Some(params))
+            .await
+            .unwrap();
+        let files_before = data_files(&test_dir).await;
+        let err = builder.execute().await.unwrap_err();
+        assert!(
+            matches!(err, Error::IncompatibleTransaction { .. }),
+            "{err}"
+        );
+
+        assert_eq!(data_files(&test_dir).await, files_before);
+        let mut dataset = dataset.as_ref().clone();
+        dataset.checkout_latest().await.unwrap();
+        assert_eq!(dataset.version().version, overwritten.version().version);
+        assert_eq!(dataset.version().version, base_version + 1);
+        assert_eq!(
+            ids(&rows(&dataset, &["id"]).await),
+            (100..110).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Opening a dataset, creating it first if it doesn't exist.
+//!
+//! Datasets are created with a conditional put of the first manifest, so when
+//! several processes create the same dataset at once exactly one of them wins.
+//! [`Dataset::open_or_create`] lets the others open the winner's dataset
+//! instead of failing with [`Error::DatasetAlreadyExists`].
+
+use arrow_array::RecordBatchIterator;
+use arrow_schema::SchemaRef as ArrowSchemaRef;
+use lance_core::datatypes::{Schema, SchemaCompareOptions};
+
+use super::{WriteMode, WriteParams};
+use crate::dataset::ReadParams;
+use crate::dataset::builder::DatasetBuilder;
+use crate::{Dataset, Error, Result};
+
+/// How the schema passed to [`Dataset::open_or_create`] must match the schema
+/// of a dataset that already exists.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SchemaCompatibility {
+    /// The schemas must have the same fields, in the same order, with the
+    /// same types and nullability (default).
+    #[default]
+    Exact,
+    /// Every field of the schema must be in the dataset, in any order. The
+    /// dataset may have more fields.
+    Subset,
+    /// The schema of an existing dataset is not checked.
+    Ignore,
+}
+
+impl SchemaCompatibility {
+    fn compare_options(&self) -> Option<SchemaCompareOptions> {
+        match self {
+            Self::Exact => Some(SchemaCompareOptions::default()),
+            Self::Subset => Some(SchemaCompareOptions {
+                allow_subschema: true,
+                ignore_field_order: true,
+                ..Default::default()
+            }),
+            Self::Ignore => None,
+        }
+    }
+}
+
+impl Dataset {
+    /// Open the dataset at `uri`, or create an empty dataset with `schema` if
+    /// there is none.
+    ///
+    /// This is safe to call from many writers at once: exactly one of them
+    /// creates the dataset and the others open it. The schema of a dataset
+    /// that already exists is checked against `schema` according to
+    /// `compatibility`, failing with [`Error::SchemaMismatch`] that describes
+    /// the differences.
+    ///
+    /// The store, commit handler and session of `params` are used to open the
+    /// dataset as well. `params.mode` is ignored.
+    ///
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use arrow_schema::{DataType, Field, Schema};
+    /// # use lance::{Dataset, Result};
+    /// # use lance::dataset::SchemaCompatibility;
+    /// # async fn test_open_or_create(uri: &str) -> Result<()> {
+    /// let schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, false)]));
+    /// let dataset = Dataset::open_or_create(uri, schema, SchemaCompatibility::Exact, None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn open_or_create(
+        uri: &str,
+        schema: ArrowSchemaRef,
+        compatibility: SchemaCompatibility,
+        params: Option<WriteParams>,
+    ) -> Result<Self> {
+        let mut params = params.unwrap_or_default();
+        params.mode = WriteMode::Create;
+        let open = || {
+            DatasetBuilder::from_uri(uri)
+                .with_read_params(ReadParams {
+                    store_options: params.store_params.clone(),
+                    commit_handler: params.commit_handler.clone(),
+                    session: params.session.clone(),
+                    ..Default::default()
+                })
+                .load()
+        };
+
+        let dataset = match open().await {
+            Ok(dataset) => dataset,
+            Err(Error::DatasetNotFound { .. } | Error::NotFound { .. }) => {
+                let empty = RecordBatchIterator::new(vec![], schema.clone());
+                match Self::write(empty, uri, Some(params.clone())).await {
+                    // We created it, so the schema is the requested one
+                    Ok(dataset) => return Ok(dataset),
+                    // Another writer created it first
+                    Err(Error::DatasetAlreadyExists { .. }) => open().await?,
+                    Err(e) => return Err(e),
+                }
+            }
+            Err(e) => return Err(e),
+        };
+
+        if let Some(options) = compatibility.compare_options() {
+            Schema::try_from(schema.as_ref())?.check_compatible(dataset.schema(), &options)?;
+        }
+        Ok(dataset)
+    }
+}
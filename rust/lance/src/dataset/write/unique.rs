@@ -0,0 +1,588 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Primary key uniqueness for appends and merge inserts.
+//!
+//! The primary key is the schema's unenforced primary key. With
+//! [`WriteParams::enforce_unique`](super::WriteParams::enforce_unique) or
+//! [`MergeInsertBuilder::enforce_unique`](super::merge_insert::MergeInsertBuilder::enforce_unique)
+//! set, the keys of the written rows are checked against the keys written
+//! before them and, through a BTree or Bitmap index on every key column,
+//! against the keys in the dataset. Duplicates are handled according to a
+//! [`DuplicateKeyPolicy`].
+//!
+//! Keys are only checked against the version the write started from. To catch
+//! a concurrent writer inserting the same keys, the transaction carries a
+//! filter of the inserted keys, which the commit checks against the ones of
+//! the transactions committed in the meantime, like merge inserts on the
+//! primary key do. An append that is not enforced can't be checked this way,
+//! so it makes an enforced write committed after it fail with a retryable
+//! conflict.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use arrow::compute::{filter_record_batch, not};
+use arrow_array::{BooleanArray, RecordBatch, UInt32Array};
+use arrow_select::take::take;
+use datafusion::error::DataFusionError;
+use datafusion::execution::SendableRecordBatchStream;
+use datafusion::logical_expr::{Expr, col, lit};
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::scalar::ScalarValue;
+use futures::{StreamExt, stream};
+use lance_index::IndexCriteria;
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::merge_insert::format_key_values_on_columns;
+use super::merge_insert::inserted_rows::{
+    KeyExistenceFilter, KeyExistenceFilterBuilder, KeyValue, extract_key_value_from_batch,
+};
+use crate::dataset::Dataset;
+use crate::index::DatasetIndexExt;
+use crate::{Error, Result};
+
+/// Number of keys looked up in the dataset with one scan.
+const LOOKUP_BATCH_SIZE: usize = 1024;
+
+/// What to do with rows whose primary key is already taken, by a row in the
+/// dataset or by a row written before them.
+#[derive(Debug, Clone, Default)]
+pub enum DuplicateKeyPolicy {
+    /// Fail the write (default).
+    #[default]
+    Reject,
+    /// Leave the rows out and write the others.
+    Drop,
+    /// Leave the rows out and send them to the channel, which gets one batch
+    /// per written batch that had duplicates. The rows are dropped if the
+    /// receiver is gone. A merge insert that is retried sends them again.
+    Route(UnboundedSender<RecordBatch>),
+}
+
+/// Whether the key of a row is new, a duplicate, or the key of a row of the
+/// dataset that a merge insert updates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyState {
+    New,
+    Repeated,
+    Existing,
+}
+
+/// Checks the primary keys of a write, see the [module documentation](self).
+pub(crate) struct UniqueKeyEnforcer {
+    /// The dataset written to, none when creating one.
+    dataset: Option<Arc<Dataset>>,
+    key_columns: Vec<String>,
+    policy: DuplicateKeyPolicy,
+    /// Whether keys in the dataset are duplicates, as for an append, or the
+    /// rows a merge insert matches.
+    existing_are_duplicates: bool,
+    seen: HashSet<KeyValue>,
+    inserted: Arc<Mutex<KeyExistenceFilterBuilder>>,
+}
+
+impl UniqueKeyEnforcer {
+    /// Enforce the primary key of `schema` on a write to `dataset`.
+    ///
+    /// Fails if the schema has no primary key, or if a key column of an
+    /// existing dataset has no index that supports exact equality.
+    pub(crate) async fn try_new(
+        dataset: Option<Arc<Dataset>>,
+        schema: &lance_core::datatypes::Schema,
+        policy: DuplicateKeyPolicy,
+        existing_are_duplicates: bool,
+    ) -> Result<Self> {
+        let key_fields = schema.unenforced_primary_key();
+        if key_fields.is_empty() {
+            return Err(Error::invalid_input(
+                "enforce_unique requires a primary key, set the \
+                 lance-schema:unenforced-primary-key metadata on the key columns",
+            ));
+        }
+        let key_columns = key_fields
+            .iter()
+            .map(|field| field.name.clone())
+            .collect::<Vec<_>>();
+        if let Some(dataset) = &dataset {
+            for column in &key_columns {
+                let index = dataset
+                    .load_scalar_index(
+                        IndexCriteria::default()
+                            .for_column(column)
+                            .supports_exact_equality(),
+                    )
+                    .await?;
+                if index.is_none() {
+                    return Err(Error::invalid_input(format!(
+                        "enforce_unique requires a BTree or Bitmap index on the primary key \
+                         column '{}'",
+                        column
+                    )));
+                }
+            }
+        }
+        let field_ids = key_fields.iter().map(|field| field.id).collect();
+        Ok(Self {
+            dataset,
+            key_columns,
+            policy,
+            existing_are_duplicates,
+            seen: HashSet::new(),
+            inserted: Arc::new(Mutex::new(KeyExistenceFilterBuilder::new(field_ids))),
+        })
+    }
+
+    pub(crate) fn key_columns(&self) -> &[String] {
+        &self.key_columns
+    }
+
+    /// Check the batches of `stream`. Returns the stream of the rows to write
+    /// and the keys it inserted, complete once the stream is done.
+    pub(crate) fn enforce(
+        self,
+        stream: SendableRecordBatchStream,
+    ) -> (SendableRecordBatchStream, InsertedKeys) {
+        let inserted = InsertedKeys(self.inserted.clone());
+        let schema = stream.schema();
+        let checked = stream::unfold((stream, self), |(mut stream, mut enforcer)| async move {
+            let batch = match stream.next().await? {
+                Ok(batch) => enforcer
+                    .check_batch(batch)
+                    .await
+                    .map_err(|err| DataFusionError::External(Box::new(err))),
+                Err(err) => Err(err),
+            };
+            Some((batch, (stream, enforcer)))
+        });
+        (
+            Box::pin(RecordBatchStreamAdapter::new(schema, checked)),
+            inserted,
+        )
+    }
+
+    async fn check_batch(&mut self, batch: RecordBatch) -> Result<RecordBatch> {
+        let mut keys = Vec::with_capacity(batch.num_rows());
+        let mut states = Vec::with_capacity(batch.num_rows());
+        for row in 0..batch.num_rows() {
+            let key =
+                extract_key_value_from_batch(&batch, row, &self.key_columns).ok_or_else(|| {
+                    Error::invalid_input(format!(
+                        "Primary key ({}) is null or of an unsupported type",
+                        format_key_values_on_columns(&batch, row, &self.key_columns)
+                    ))
+                })?;
+            let state = if self.seen.insert(key.clone()) {
+                KeyState::New
+            } else {
+                KeyState::Repeated
+            };
+            keys.push(key);
+            states.push(state);
+        }
+
+        let candidates = (0..batch.num_rows())
+            .filter(|row| states[*row] == KeyState::New)
+            .collect::<Vec<_>>();
+        let existing = self.existing_keys(&batch, &candidates).await?;
+        for row in candidates {
+            if existing.contains(&keys[row]) {
+                states[row] = KeyState::Existing;
+            }
+        }
+
+        let mut inserted = self.inserted.lock().unwrap();
+        let mut keep = Vec::with_capacity(batch.num_rows());
+        for (row, (key, state)) in keys.into_iter().zip(&states).enumerate() {
+            let duplicate = match state {
+                KeyState::New => false,
+                KeyState::Repeated => true,
+                KeyState::Existing => self.existing_are_duplicates,
+            };
+            if duplicate && matches!(self.policy, DuplicateKeyPolicy::Reject) {
+                return Err(Error::invalid_input(format!(
+                    "Duplicate primary key ({}): {}",
+                    format_key_values_on_columns(&batch, row, &self.key_columns),
+                    if *state == KeyState::Repeated {
+                        "the key is written more than once"
+                    } else {
+                        "the key is already in the dataset"
+                    }
+                )));
+            }
+            if *state == KeyState::New {
+                inserted.insert(key)?;
+            }
+            keep.push(!duplicate);
+        }
+        drop(inserted);
+
+        if keep.iter().all(|keep| *keep) {
+            return Ok(batch);
+        }
+        let keep = BooleanArray::from(keep);
+        if let DuplicateKeyPolicy::Route(sender) = &self.policy {
+            let duplicates = filter_record_batch(&batch, &not(&keep)?)?;
+            // The caller may not care about the rest of the duplicates.
+            let _ = sender.send(duplicates);
+        }
+        Ok(filter_record_batch(&batch, &keep)?)
+    }
+
+    /// The keys of the `rows` of `batch` that are in the dataset.
+    async fn existing_keys(
+        &self,
+        batch: &RecordBatch,
+        rows: &[usize],
+    ) -> Result<HashSet<KeyValue>> {
+        let mut existing = HashSet::new();
+        let Some(dataset) = &self.dataset else {
+            return Ok(existing);
+        };
+        for rows in rows.chunks(LOOKUP_BATCH_SIZE) {
+            let indices = UInt32Array::from_iter_values(rows.iter().map(|row| *row as u32));
+            // A conjunction of one IN list per column, which every key column
+            // index can answer. It can match more than the keys of a
+            // composite primary key, so the matches are compared below.
+            let mut filter: Option<Expr> = None;
+            for column in &self.key_columns {
+                let column_values = batch
+                    .column_by_name(column)
+                    .expect("the keys of the rows were extracted");
+                let values = take(column_values, &indices, None)?;
+                let list = (0..values.len())
+                    .map(|i| ScalarValue::try_from_array(&values, i).map(lit))
+                    .collect::<datafusion::error::Result<Vec<_>>>()?;
+                let in_list = col(column.as_str()).in_list(list, false);
+                filter = Some(match filter {
+                    Some(filter) => filter.and(in_list),
+                    None => in_list,
+                });
+            }
+            let mut scanner = dataset.scan();
+            scanner.project(&self.key_columns)?;
+            if let Some(filter) = filter {
+                scanner.filter_expr(filter);
+            }
+            let matches = scanner.try_into_batch().await?;
+            existing.extend(
+                (0..matches.num_rows()).filter_map(|row| {
+                    extract_key_value_from_batch(&matches, row, &self.key_columns)
+                }),
+            );
+        }
+        Ok(existing)
+    }
+}
+
+/// The keys a checked write inserted, see [`UniqueKeyEnforcer::enforce`].
+#[derive(Debug, Clone)]
+pub(crate) struct InsertedKeys(Arc<Mutex<KeyExistenceFilterBuilder>>);
+
+impl InsertedKeys {
+    pub(crate) fn filter(&self) -> KeyExistenceFilter {
+        KeyExistenceFilter::from_bloom_filter(&self.0.lock().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::Int64Type;
+    use arrow_array::{Int64Array, StringArray};
+    use arrow_schema::{DataType, Field, Schema as ArrowSchema};
+    use lance_core::datatypes::LANCE_UNENFORCED_PRIMARY_KEY;
+    use lance_index::IndexType;
+    use lance_index::scalar::ScalarIndexParams;
+
+    use super::*;
+    use crate::dataset::{
+        CommitBuilder, InsertBuilder, MergeInsertBuilder, WhenMatched, WriteMode, WriteParams,
+    };
+
+    fn schema(primary_key: bool) -> Arc<ArrowSchema> {
+        let mut id = Field::new("id", DataType::Int64, false);
+        if primary_key {
+            id = id.with_metadata(HashMap::from([(
+                LANCE_UNENFORCED_PRIMARY_KEY.to_string(),
+                "true".to_string(),
+            )]));
+        }
+        Arc::new(ArrowSchema::new(vec![
+            id,
+            Field::new("value", DataType::Utf8, true),
+        ]))
+    }
+
+    fn batch(ids: &[i64]) -> RecordBatch {
+        let values = ids.iter().map(|id| format!("v{id}")).collect::<Vec<_>>();
+        RecordBatch::try_new(
+            schema(true),
+            vec![
+                Arc::new(Int64Array::from(ids.to_vec())),
+                Arc::new(StringArray::from(values)),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn source(ids: &[i64]) -> SendableRecordBatchStream {
+        Box::pin(RecordBatchStreamAdapter::new(
+            schema(true),
+            stream::iter([Ok(batch(ids))]),
+        ))
+    }
+
+    /// A dataset with `ids` and a BTree index on the primary key.
+    async fn dataset(ids: &[i64]) -> Arc<Dataset> {
+        let mut dataset = InsertBuilder::new("memory://")
+            .execute(vec![batch(ids)])
+            .await
+            .unwrap();
+        dataset
+            .create_index(
+                &["id"],
+                IndexType::BTree,
+                None,
+                &ScalarIndexParams::default(),
+                false,
+            )
+            .await
+            .unwrap();
+        Arc::new(dataset)
+    }
+
+    fn append_params(policy: DuplicateKeyPolicy) -> WriteParams {
+        WriteParams {
+            mode: WriteMode::Append,
+            enforce_unique: Some(policy),
+            ..Default::default()
+        }
+    }
+
+    async fn ids(dataset: &Dataset) -> Vec<i64> {
+        let batch = dataset
+            .scan()
+            .project(&["id"])
+            .unwrap()
+            .try_into_batch()
+            .await
+            .unwrap();
+        let mut ids = batch
+            .column(0)
+            .as_primitive::<Int64Type>()
+            .values()
+            .to_vec();
+        ids.sort();
+        ids
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_within_write() {
+        let dataset = dataset(&[1, 2]).await;
+        let err = InsertBuilder::new(dataset.clone())
+            .with_params(&append_params(DuplicateKeyPolicy::Reject))
+            .execute(vec![batch(&[3, 4, 3])])
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Duplicate primary key (id = 3): the key is written more than once"),
+            "{err}"
+        );
+
+        // Duplicates are found across batches too.
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let appended = InsertBuilder::new(dataset.clone())
+            .with_params(&append_params(DuplicateKeyPolicy::Route(sender)))
+            .execute(vec![batch(&[3, 4]), batch(&[4, 5, 3])])
+            .await
+            .unwrap();
+        assert_eq!(ids(&appended).await, vec![1, 2, 3, 4, 5]);
+        assert_eq!(receiver.recv().await.unwrap(), batch(&[4, 3]));
+        assert!(receiver.try_recv().is_err());
+
+        // Creating a dataset needs no index.
+        let created = InsertBuilder::new("memory://")
+            .with_params(&WriteParams {
+                enforce_unique: Some(DuplicateKeyPolicy::Drop),
+                ..Default::default()
+            })
+            .execute(vec![batch(&[1, 1, 2])])
+            .await
+            .unwrap();
+        assert_eq!(ids(&created).await, vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_of_existing() {
+        let dataset = dataset(&[1, 2]).await;
+        let err = InsertBuilder::new(dataset.clone())
+            .with_params(&append_params(DuplicateKeyPolicy::Reject))
+            .execute(vec![batch(&[3, 2])])
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Duplicate primary key (id = 2): the key is already in the dataset"),
+            "{err}"
+        );
+
+        let appended = InsertBuilder::new(dataset.clone())
+            .with_params(&append_params(DuplicateKeyPolicy::Drop))
+            .execute(vec![batch(&[3, 2])])
+            .await
+            .unwrap();
+        assert_eq!(ids(&appended).await, vec![1, 2, 3]);
+
+        // Rows appended since the index was built are found as well.
+        let appended = InsertBuilder::new(Arc::new(appended))
+            .with_params(&append_params(DuplicateKeyPolicy::Drop))
+            .execute(vec![batch(&[1, 3, 4])])
+            .await
+            .unwrap();
+        assert_eq!(ids(&appended).await, vec![1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_requires_primary_key_and_index() {
+        let unindexed = InsertBuilder::new("memory://")
+            .execute(vec![batch(&[1])])
+            .await
+            .unwrap();
+        let err = InsertBuilder::new(Arc::new(unindexed))
+            .with_params(&append_params(DuplicateKeyPolicy::Reject))
+            .execute(vec![batch(&[2])])
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("requires a BTree or Bitmap index on the primary key column 'id'"),
+            "{err}"
+        );
+
+        let data = RecordBatch::try_new(
+            schema(false),
+            vec![
+                Arc::new(Int64Array::from(vec![1])),
+                Arc::new(StringArray::from(vec!["v1"])),
+            ],
+        )
+        .unwrap();
+        let err = InsertBuilder::new("memory://")
+            .with_params(&WriteParams {
+                enforce_unique: Some(DuplicateKeyPolicy::Reject),
+                ..Default::default()
+            })
+            .execute(vec![data])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("requires a primary key"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_appends() {
+        let dataset = dataset(&[1, 2]).await;
+        let append = async |ids: &[i64]| {
+            InsertBuilder::new(dataset.clone())
+                .with_params(&append_params(DuplicateKeyPolicy::Reject))
+                .execute_uncommitted(vec![batch(ids)])
+                .await
+                .unwrap()
+        };
+        let first = append(&[3, 4]).await;
+        let same_key = append(&[5, 3]).await;
+        let other_keys = append(&[6]).await;
+
+        let committed = CommitBuilder::new(dataset.clone())
+            .execute(first)
+            .await
+            .unwrap();
+        let err = CommitBuilder::new(dataset.clone())
+            .execute(same_key)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, Error::RetryableCommitConflict { .. }),
+            "{err}"
+        );
+        let committed = CommitBuilder::new(dataset.clone())
+            .execute(other_keys)
+            .await
+            .unwrap();
+        assert_eq!(ids(&committed).await, vec![1, 2, 3, 4, 6]);
+
+        // Retried on the latest version, the key is found in the dataset.
+        let err = InsertBuilder::new(Arc::new(committed))
+            .with_params(&append_params(DuplicateKeyPolicy::Reject))
+            .execute(vec![batch(&[5, 3])])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("already in the dataset"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn test_merge_insert() {
+        let dataset = dataset(&[1, 2]).await;
+        let job = |on: &str, policy: DuplicateKeyPolicy| {
+            MergeInsertBuilder::try_new(dataset.clone(), vec![on.to_string()])
+                .unwrap()
+                .when_matched(WhenMatched::UpdateAll)
+                .enforce_unique(policy)
+                .try_build()
+                .unwrap()
+        };
+
+        let err = job("value", DuplicateKeyPolicy::Reject)
+            .execute(source(&[3]))
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("requires the merge insert to join on the primary key"),
+            "{err}"
+        );
+
+        // Matching the dataset is not a duplicate, repeating a source key is.
+        let err = job("id", DuplicateKeyPolicy::Reject)
+            .execute(source(&[2, 3, 3]))
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Duplicate primary key (id = 3): the key is written more than once"),
+            "{err}"
+        );
+        let (merged, stats) = job("id", DuplicateKeyPolicy::Drop)
+            .execute(source(&[2, 3, 3]))
+            .await
+            .unwrap();
+        assert_eq!((stats.num_updated_rows, stats.num_inserted_rows), (1, 1));
+        assert_eq!(ids(&merged).await, vec![1, 2, 3]);
+
+        // The inserted keys are checked at commit.
+        let first = job("id", DuplicateKeyPolicy::Reject)
+            .execute_uncommitted(source(&[1, 4]))
+            .await
+            .unwrap();
+        let second = job("id", DuplicateKeyPolicy::Reject)
+            .execute_uncommitted(source(&[4]))
+            .await
+            .unwrap();
+        assert!(second.inserted_rows_filter.is_some());
+        CommitBuilder::new(dataset.clone())
+            .execute(first.transaction)
+            .await
+            .unwrap();
+        let err = CommitBuilder::new(dataset.clone())
+            .execute(second.transaction)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, Error::RetryableCommitConflict { .. }),
+            "{err}"
+        );
+    }
+}
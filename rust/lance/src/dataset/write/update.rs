@@ -268,6 +268,7 @@ impl UpdateJob {
     }
 
     async fn execute_impl(self) -> Result<UpdateData> {
+        self.dataset.check_writable()?;
         let mut scanner = self.dataset.scan();
         scanner.with_row_id();
 
@@ -0,0 +1,18 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Import data stored in other formats into Lance datasets
+
+#[cfg(feature = "csv")]
+mod csv;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "parquet")]
+mod parquet;
+
+#[cfg(feature = "parquet")]
+pub use self::parquet::{IMPORTED_PARQUET_FILE_KEY, ParquetImportOptions, parquet};
+#[cfg(feature = "csv")]
+pub use csv::{CsvColumnOptions, CsvImportOptions, CsvImportSummary, csv};
+#[cfg(feature = "json")]
+pub use json::{JsonCompression, JsonImportOptions, JsonImportSummary, JsonSchema, json_lines};
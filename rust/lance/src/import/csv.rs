@@ -0,0 +1,848 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Import CSV files
+
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use arrow_array::timezone::Tz;
+use arrow_array::{
+    Array, ArrayRef, BooleanArray, Int64Array, RecordBatch, StringArray, UInt64Array,
+};
+use arrow_cast::CastOptions;
+use arrow_csv::reader::{Decoder, Format, ReaderBuilder};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef, TimeUnit};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone};
+use datafusion::error::DataFusionError;
+use datafusion::physical_plan::SendableRecordBatchStream;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use futures::TryStreamExt;
+use futures::io::{AsyncBufRead, AsyncBufReadExt};
+use lance_io::object_store::{ObjectStore, ObjectStoreParams};
+use lance_io::traits::Writer;
+use object_store::ObjectStoreExt;
+use object_store::path::Path;
+use tokio::io::AsyncWriteExt;
+
+use crate::dataset::{InsertBuilder, WriteParams};
+use crate::{Dataset, Error, Result};
+
+/// Per-column options for [`csv`]
+#[derive(Debug, Clone, Default)]
+pub struct CsvColumnOptions {
+    /// The values read as null in this column, instead of [`CsvImportOptions::null_values`]
+    pub null_values: Option<Vec<String>>,
+    /// The [`chrono`] formats tried in order to parse the values of a timestamp column
+    ///
+    /// Formats may contain only a date, which is read as midnight, and may contain a
+    /// UTC offset.  Values without an offset are in the time zone of the column, or UTC
+    /// if it has none.  If empty, the RFC 3339 like formats understood by Arrow casts
+    /// are accepted.
+    pub timestamp_formats: Vec<String>,
+}
+
+/// Options for [`csv`]
+#[derive(Debug, Clone)]
+pub struct CsvImportOptions {
+    /// How the converted rows are written to the Lance dataset
+    ///
+    /// Rejected rows are never written.  The session's store registry is also used to
+    /// open the CSV and rejects files.
+    pub write_params: WriteParams,
+    /// Parameters used to open the object stores holding the CSV and rejects files
+    pub source_store_params: ObjectStoreParams,
+    /// The schema of the dataset
+    ///
+    /// If the file has a header then its columns are matched to the schema by name and
+    /// columns that are not in the schema are ignored.  Otherwise the columns of the file
+    /// are the fields of the schema, in order.
+    pub schema: SchemaRef,
+    pub has_header: bool,
+    pub delimiter: u8,
+    pub quote: u8,
+    /// The character that escapes quotes inside quoted fields, quotes are doubled if unset
+    pub escape: Option<u8>,
+    /// The values read as null, unless overridden by [`CsvColumnOptions::null_values`]
+    pub null_values: Vec<String>,
+    /// Options for specific columns, by field name
+    pub columns: HashMap<String, CsvColumnOptions>,
+    /// The maximum number of rows decoded into each batch
+    ///
+    /// Only the rows of the current batch are kept in memory.
+    pub batch_size: usize,
+    /// A CSV file that rows that cannot be converted to the schema are written to
+    ///
+    /// Each rejected row is written with its line number in the source file, the reason
+    /// it was rejected and its original values.  The file is only created if a row is
+    /// rejected.  If unset, the first such row fails the import.
+    pub rejects_uri: Option<String>,
+}
+
+impl CsvImportOptions {
+    pub fn new(schema: SchemaRef) -> Self {
+        Self {
+            write_params: WriteParams::default(),
+            source_store_params: ObjectStoreParams::default(),
+            schema,
+            has_header: true,
+            delimiter: b',',
+            quote: b'"',
+            escape: None,
+            null_values: vec![String::new()],
+            columns: HashMap::new(),
+            batch_size: 8 * 1024,
+            rejects_uri: None,
+        }
+    }
+
+    fn format(&self) -> Format {
+        let format = Format::default()
+            .with_delimiter(self.delimiter)
+            .with_quote(self.quote);
+        match self.escape {
+            Some(escape) => format.with_escape(escape),
+            None => format,
+        }
+    }
+}
+
+/// The result of [`csv`]
+#[derive(Debug)]
+pub struct CsvImportSummary {
+    pub dataset: Dataset,
+    /// The number of rows that were written
+    pub num_rows: u64,
+    /// The number of rows written to [`CsvImportOptions::rejects_uri`]
+    pub num_rejected_rows: u64,
+}
+
+/// Import a CSV file into a Lance dataset with an explicit schema
+///
+/// The file is streamed through the object store abstraction and decoded `batch_size`
+/// rows at a time, so memory use does not depend on the size of the file.  Values are
+/// converted to the types of the schema with Arrow casts, or with the configured formats
+/// for timestamp columns.  All rows are committed as a single version of the dataset.
+///
+/// Quoted fields may contain delimiters and line breaks.  Blank lines are skipped.
+pub async fn csv(
+    source_uri: &str,
+    dest_uri: &str,
+    options: &CsvImportOptions,
+) -> Result<CsvImportSummary> {
+    let registry = options
+        .write_params
+        .session
+        .as_ref()
+        .map(|session| session.store_registry())
+        .unwrap_or_default();
+    let (store, path) = ObjectStore::from_uri_and_params(
+        registry.clone(),
+        source_uri,
+        &options.source_store_params,
+    )
+    .await?;
+    let rejects = match &options.rejects_uri {
+        Some(uri) => Some(
+            ObjectStore::from_uri_and_params(registry, uri, &options.source_store_params).await?,
+        ),
+        None => None,
+    };
+
+    let num_rows = Arc::new(AtomicU64::new(0));
+    let num_rejected_rows = Arc::new(AtomicU64::new(0));
+    let reader =
+        CsvReader::open(&store, &path, options, rejects, num_rejected_rows.clone()).await?;
+    let rows = num_rows.clone();
+    let stream = futures::stream::try_unfold(reader, |mut reader| async move {
+        Result::Ok(reader.next_batch().await?.map(|batch| (batch, reader)))
+    })
+    .map_ok(move |batch| {
+        rows.fetch_add(batch.num_rows() as u64, Ordering::Relaxed);
+        batch
+    })
+    .map_err(DataFusionError::from);
+    let stream: SendableRecordBatchStream = Box::pin(RecordBatchStreamAdapter::new(
+        options.schema.clone(),
+        stream,
+    ));
+
+    let dataset = InsertBuilder::new(dest_uri)
+        .with_params(&options.write_params)
+        .execute_stream(stream)
+        .await?;
+    Ok(CsvImportSummary {
+        dataset,
+        num_rows: num_rows.load(Ordering::Relaxed),
+        num_rejected_rows: num_rejected_rows.load(Ordering::Relaxed),
+    })
+}
+
+/// How one field of the schema is read from the columns of the file
+struct ColumnConversion {
+    field: Arc<Field>,
+    /// The index of the column in the file
+    index: usize,
+    null_values: Vec<String>,
+    timestamp_formats: Vec<String>,
+    time_zone: Option<Tz>,
+}
+
+impl ColumnConversion {
+    fn new(field: &Arc<Field>, index: usize, options: &CsvImportOptions) -> Result<Self> {
+        let column = options.columns.get(field.name());
+        let timestamp_formats = column
+            .map(|column| column.timestamp_formats.clone())
+            .unwrap_or_default();
+        let time_zone = match field.data_type() {
+            DataType::Timestamp(_, Some(tz)) => Some(tz.parse::<Tz>()?),
+            DataType::Timestamp(_, None) => None,
+            _ if timestamp_formats.is_empty() => None,
+            data_type => {
+                return Err(Error::invalid_input(format!(
+                    "Timestamp formats were given for column '{}' which has type {}",
+                    field.name(),
+                    data_type
+                )));
+            }
+        };
+        Ok(Self {
+            field: field.clone(),
+            index,
+            null_values: column
+                .and_then(|column| column.null_values.clone())
+                .unwrap_or_else(|| options.null_values.clone()),
+            timestamp_formats,
+            time_zone,
+        })
+    }
+
+    /// Convert the values of the column, returning the converted array and the rows
+    /// that could not be converted
+    fn convert(&self, raw: &StringArray) -> Result<(ArrayRef, BooleanArray)> {
+        // The decoder reads empty values as null
+        let values = raw
+            .iter()
+            .map(|value| {
+                let value = value.unwrap_or_default();
+                (!self.null_values.iter().any(|null| null == value)).then_some(value)
+            })
+            .collect::<StringArray>();
+
+        let array = match self.field.data_type() {
+            DataType::Timestamp(unit, _) if !self.timestamp_formats.is_empty() => {
+                let timestamps = values
+                    .iter()
+                    .map(|value| value.and_then(|value| self.parse_timestamp(value, unit)))
+                    .collect::<Int64Array>();
+                arrow_cast::cast(&timestamps, self.field.data_type())?
+            }
+            data_type => arrow_cast::cast_with_options(
+                &values,
+                data_type,
+                &CastOptions {
+                    safe: true,
+                    ..Default::default()
+                },
+            )?,
+        };
+        let failed = (0..values.len())
+            .map(|row| {
+                Some(
+                    (values.is_valid(row) && array.is_null(row))
+                        || (!self.field.is_nullable() && array.is_null(row)),
+                )
+            })
+            .collect::<BooleanArray>();
+        Ok((array, failed))
+    }
+
+    fn parse_timestamp(&self, value: &str, unit: &TimeUnit) -> Option<i64> {
+        let timestamp = self.timestamp_formats.iter().find_map(|format| {
+            if let Ok(timestamp) = DateTime::parse_from_str(value, format) {
+                return Some(timestamp.to_utc());
+            }
+            let local = NaiveDateTime::parse_from_str(value, format)
+                .or_else(|_| {
+                    NaiveDate::parse_from_str(value, format)
+                        .map(|date| date.and_hms_opt(0, 0, 0).unwrap())
+                })
+                .ok()?;
+            match &self.time_zone {
+                Some(tz) => tz
+                    .from_local_datetime(&local)
+                    .single()
+                    .map(|timestamp| timestamp.to_utc()),
+                None => Some(local.and_utc()),
+            }
+        })?;
+        match unit {
+            TimeUnit::Second => Some(timestamp.timestamp()),
+            TimeUnit::Millisecond => Some(timestamp.timestamp_millis()),
+            TimeUnit::Microsecond => Some(timestamp.timestamp_micros()),
+            TimeUnit::Nanosecond => timestamp.timestamp_nanos_opt(),
+        }
+    }
+
+    fn error(&self, raw: &StringArray, row: usize) -> String {
+        let value = if raw.is_valid(row) {
+            raw.value(row)
+        } else {
+            ""
+        };
+        if self.null_values.iter().any(|null| null == value) {
+            format!("column '{}' cannot be null", self.field.name())
+        } else {
+            format!(
+                "column '{}': cannot convert '{}' to {}",
+                self.field.name(),
+                value,
+                self.field.data_type()
+            )
+        }
+    }
+}
+
+type ByteSource = Pin<Box<dyn AsyncBufRead + Send>>;
+
+fn decode_error(path: &Path, line: u64, err: ArrowError) -> Error {
+    Error::invalid_input(format!(
+        "Failed to read CSV file '{}' after line {}: {}",
+        path, line, err
+    ))
+}
+
+/// Decodes the rows of a CSV file into batches with the target schema
+struct CsvReader {
+    source: ByteSource,
+    path: Path,
+    schema: SchemaRef,
+    decoder: Decoder,
+    /// The names of the columns of the file, used for the rejects file
+    column_names: Vec<String>,
+    conversions: Vec<ColumnConversion>,
+    /// The line the next row starts on
+    next_line: u64,
+    rejects: Option<RejectsWriter>,
+    num_rejected_rows: Arc<AtomicU64>,
+}
+
+impl CsvReader {
+    async fn open(
+        store: &ObjectStore,
+        path: &Path,
+        options: &CsvImportOptions,
+        rejects: Option<(Arc<ObjectStore>, Path)>,
+        num_rejected_rows: Arc<AtomicU64>,
+    ) -> Result<Self> {
+        for name in options.columns.keys() {
+            if options.schema.field_with_name(name).is_err() {
+                return Err(Error::invalid_input(format!(
+                    "Column options were given for '{}' which is not in the schema",
+                    name
+                )));
+            }
+        }
+
+        let mut source: ByteSource = Box::pin(
+            store
+                .inner
+                .get(path)
+                .await?
+                .into_stream()
+                .map_err(std::io::Error::other)
+                .into_async_read(),
+        );
+        let mut next_line = 1;
+        let (column_names, conversions) = if options.has_header {
+            let mut header = Vec::new();
+            source.read_until(b'\n', &mut header).await?;
+            next_line += 1;
+            let (header, _) = options
+                .format()
+                .with_header(true)
+                .infer_schema(Cursor::new(header), Some(0))?;
+            let column_names = header
+                .fields()
+                .iter()
+                .map(|field| field.name().clone())
+                .collect::<Vec<_>>();
+            let conversions = options
+                .schema
+                .fields()
+                .iter()
+                .map(|field| {
+                    let index = column_names
+                        .iter()
+                        .position(|name| name == field.name())
+                        .ok_or_else(|| {
+                            Error::invalid_input(format!(
+                                "Column '{}' of the schema is not in the header of CSV file '{}'",
+                                field.name(),
+                                path
+                            ))
+                        })?;
+                    ColumnConversion::new(field, index, options)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            (column_names, conversions)
+        } else {
+            let column_names = options
+                .schema
+                .fields()
+                .iter()
+                .map(|field| field.name().clone())
+                .collect::<Vec<_>>();
+            let conversions = options
+                .schema
+                .fields()
+                .iter()
+                .enumerate()
+                .map(|(index, field)| ColumnConversion::new(field, index, options))
+                .collect::<Result<Vec<_>>>()?;
+            (column_names, conversions)
+        };
+
+        // Every column is decoded as a string and converted afterwards so that the rows
+        // that fail to convert can be told apart
+        let raw_schema = Arc::new(Schema::new(
+            column_names
+                .iter()
+                .map(|name| Field::new(name, DataType::Utf8, true))
+                .collect::<Vec<_>>(),
+        ));
+        let decoder = ReaderBuilder::new(raw_schema)
+            .with_format(options.format().with_header(false))
+            .with_batch_size(options.batch_size.max(1))
+            .build_decoder();
+
+        Ok(Self {
+            source,
+            path: path.clone(),
+            schema: options.schema.clone(),
+            decoder,
+            column_names,
+            conversions,
+            next_line,
+            rejects: rejects.map(|(store, path)| RejectsWriter {
+                store,
+                path,
+                writer: None,
+            }),
+            num_rejected_rows,
+        })
+    }
+
+    async fn next_batch(&mut self) -> Result<Option<RecordBatch>> {
+        loop {
+            let Some((raw, first_line)) = self.next_raw_batch().await? else {
+                if let Some(rejects) = self.rejects.as_mut() {
+                    rejects.finish().await?;
+                }
+                return Ok(None);
+            };
+            let batch = self.convert(&raw, first_line).await?;
+            if batch.num_rows() > 0 {
+                return Ok(Some(batch));
+            }
+            // Every row of this batch was rejected
+        }
+    }
+
+    /// Decode the next batch of rows as strings, along with the line it starts on
+    async fn next_raw_batch(&mut self) -> Result<Option<(RecordBatch, u64)>> {
+        let mut num_lines = 0;
+        loop {
+            let buf = self.source.fill_buf().await?;
+            let decoded = self
+                .decoder
+                .decode(buf)
+                .map_err(|err| decode_error(&self.path, self.next_line, err))?;
+            num_lines += buf[..decoded].iter().filter(|b| **b == b'\n').count() as u64;
+            self.source.as_mut().consume(decoded);
+            if decoded == 0 || self.decoder.capacity() == 0 {
+                break;
+            }
+        }
+        let batch = self
+            .decoder
+            .flush()
+            .map_err(|err| decode_error(&self.path, self.next_line, err))?;
+        let first_line = self.next_line;
+        self.next_line += num_lines;
+        Ok(batch.map(|batch| (batch, first_line)))
+    }
+
+    async fn convert(&mut self, raw: &RecordBatch, first_line: u64) -> Result<RecordBatch> {
+        let raw_columns = raw
+            .columns()
+            .iter()
+            .map(|column| column.as_any().downcast_ref::<StringArray>().unwrap())
+            .collect::<Vec<_>>();
+
+        let mut columns = Vec::with_capacity(self.conversions.len());
+        let mut errors: Vec<Option<String>> = vec![None; raw.num_rows()];
+        for conversion in &self.conversions {
+            let raw_column = raw_columns[conversion.index];
+            let (array, failed) = conversion.convert(raw_column)?;
+            for row in failed.values().set_indices() {
+                errors[row].get_or_insert_with(|| conversion.error(raw_column, row));
+            }
+            columns.push(array);
+        }
+        if errors.iter().all(Option::is_none) {
+            return Ok(RecordBatch::try_new(self.schema.clone(), columns)?);
+        }
+
+        // Quoted values can span lines
+        let mut lines = Vec::with_capacity(raw.num_rows());
+        let mut line = first_line;
+        for row in 0..raw.num_rows() {
+            lines.push(line);
+            line += 1 + raw_columns
+                .iter()
+                .filter(|column| column.is_valid(row))
+                .map(|column| column.value(row).matches('\n').count() as u64)
+                .sum::<u64>();
+        }
+
+        let rejected = errors
+            .iter()
+            .map(|error| Some(error.is_some()))
+            .collect::<BooleanArray>();
+        let Some(rejects) = self.rejects.as_mut() else {
+            let row = rejected.values().set_indices().next().unwrap();
+            return Err(Error::invalid_input(format!(
+                "Line {} of CSV file '{}' does not match the schema: {}",
+                lines[row],
+                self.path,
+                errors[row].as_ref().unwrap()
+            )));
+        };
+
+        let mut reject_columns: Vec<ArrayRef> = vec![
+            Arc::new(UInt64Array::from_iter_values(
+                rejected.values().set_indices().map(|row| lines[row]),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                errors.iter().flatten().map(String::as_str),
+            )),
+        ];
+        for column in raw.columns() {
+            reject_columns.push(arrow_select::filter::filter(column, &rejected)?);
+        }
+        let mut reject_fields = vec![
+            Field::new("line", DataType::UInt64, false),
+            Field::new("error", DataType::Utf8, false),
+        ];
+        reject_fields.extend(
+            self.column_names
+                .iter()
+                .map(|name| Field::new(name, DataType::Utf8, true)),
+        );
+        let reject_batch =
+            RecordBatch::try_new(Arc::new(Schema::new(reject_fields)), reject_columns)?;
+        self.num_rejected_rows
+            .fetch_add(reject_batch.num_rows() as u64, Ordering::Relaxed);
+        rejects.write(&reject_batch).await?;
+
+        // Rejected rows can hold nulls in non-nullable columns, so they are removed before
+        // the batch is built
+        let accepted = arrow_arith::boolean::not(&rejected)?;
+        let columns = columns
+            .iter()
+            .map(|column| arrow_select::filter::filter(column, &accepted))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(RecordBatch::try_new(self.schema.clone(), columns)?)
+    }
+}
+
+/// Writes rejected rows to a CSV file, which is created on the first write
+struct RejectsWriter {
+    store: Arc<ObjectStore>,
+    path: Path,
+    writer: Option<Box<dyn Writer>>,
+}
+
+impl RejectsWriter {
+    async fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        let header = self.writer.is_none();
+        let writer = match &mut self.writer {
+            Some(writer) => writer,
+            None => self.writer.insert(self.store.create(&self.path).await?),
+        };
+        let mut buf = Vec::new();
+        arrow_csv::WriterBuilder::new()
+            .with_header(header)
+            .build(&mut buf)
+            .write(batch)?;
+        writer.write_all(&buf).await?;
+        Ok(())
+    }
+
+    async fn finish(&mut self) -> Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            Writer::shutdown(&mut writer).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Int32Type, TimestampMicrosecondType, TimestampMillisecondType};
+    use arrow_array::{Float64Array, Int32Array, TimestampMicrosecondArray};
+    use futures::TryStreamExt;
+    use lance_core::utils::tempfile::TempStrDir;
+
+    use super::*;
+
+    async fn read_all(dataset: &Dataset) -> RecordBatch {
+        let batches = dataset
+            .scan()
+            .try_into_stream()
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        arrow_select::concat::concat_batches(&batches[0].schema(), &batches).unwrap()
+    }
+
+    fn timestamp_options(formats: &[&str]) -> CsvColumnOptions {
+        CsvColumnOptions {
+            timestamp_formats: formats.iter().map(|format| format.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timestamp_formats() {
+        let test_dir = TempStrDir::default();
+        let source = format!("{}/events.csv", test_dir.as_str());
+        std::fs::write(
+            &source,
+            "id,local,zoned,iso\n\
+             1,2024-03-01 12:30:00,2024-03-01T12:30:00+0000,2024-03-01T12:30:00Z\n\
+             2,01/03/2024 12:30,2024-03-01 14:30:00,2024-03-01 12:30:00\n\
+             3,2024-03-01,,\n",
+        )
+        .unwrap();
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new(
+                "local",
+                DataType::Timestamp(TimeUnit::Microsecond, None),
+                true,
+            ),
+            Field::new(
+                "zoned",
+                DataType::Timestamp(TimeUnit::Millisecond, Some("+02:00".into())),
+                true,
+            ),
+            Field::new(
+                "iso",
+                DataType::Timestamp(TimeUnit::Microsecond, None),
+                true,
+            ),
+        ]));
+        let mut options = CsvImportOptions::new(schema.clone());
+        options.columns.insert(
+            "local".to_string(),
+            timestamp_options(&["%Y-%m-%d %H:%M:%S", "%d/%m/%Y %H:%M", "%Y-%m-%d"]),
+        );
+        // Values without an offset are in the time zone of the column
+        options.columns.insert(
+            "zoned".to_string(),
+            timestamp_options(&["%Y-%m-%dT%H:%M:%S%z", "%Y-%m-%d %H:%M:%S"]),
+        );
+        let summary = csv(&source, &format!("{}/events", test_dir.as_str()), &options)
+            .await
+            .unwrap();
+        assert_eq!(summary.num_rows, 3);
+        let actual = read_all(&summary.dataset).await;
+        assert_eq!(actual.schema(), schema);
+
+        let noon = 1_709_296_200_000_000;
+        let midnight = 1_709_251_200_000_000;
+        assert_eq!(
+            actual.column(1).as_primitive::<TimestampMicrosecondType>(),
+            &TimestampMicrosecondArray::from(vec![noon, noon, midnight])
+        );
+        let zoned = actual.column(2).as_primitive::<TimestampMillisecondType>();
+        assert_eq!(zoned.value(0), noon / 1000);
+        assert_eq!(zoned.value(1), noon / 1000);
+        assert!(zoned.is_null(2));
+        let iso = actual.column(3).as_primitive::<TimestampMicrosecondType>();
+        assert_eq!(iso.value(0), noon);
+        assert_eq!(iso.value(1), noon);
+        assert!(iso.is_null(2));
+    }
+
+    #[tokio::test]
+    async fn test_quoted_fields_and_null_tokens() {
+        let test_dir = TempStrDir::default();
+        let source = format!("{}/quoted.csv", test_dir.as_str());
+        std::fs::write(
+            &source,
+            "name,ignored,score,note\n\
+             \"Smith, John\",x,1.5,\"said \"\"hi\"\"\"\n\
+             \"multi\nline\",y,NA,\n\
+             plain,z,-,NA\n",
+        )
+        .unwrap();
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("note", DataType::Utf8, true),
+            Field::new("name", DataType::Utf8, false),
+            Field::new("score", DataType::Float64, true),
+        ]));
+        let mut options = CsvImportOptions::new(schema);
+        options.null_values = vec!["NA".to_string()];
+        options.columns.insert(
+            "score".to_string(),
+            CsvColumnOptions {
+                null_values: Some(vec!["NA".to_string(), "-".to_string()]),
+                ..Default::default()
+            },
+        );
+        let summary = csv(&source, &format!("{}/quoted", test_dir.as_str()), &options)
+            .await
+            .unwrap();
+        let actual = read_all(&summary.dataset).await;
+        assert_eq!(
+            actual.column(0).as_string::<i32>(),
+            &StringArray::from(vec![Some("said \"hi\""), Some(""), None])
+        );
+        assert_eq!(
+            actual.column(1).as_string::<i32>(),
+            &StringArray::from(vec!["Smith, John", "multi\nline", "plain"])
+        );
+        assert_eq!(
+            actual.column(2).as_any().downcast_ref::<Float64Array>(),
+            Some(&Float64Array::from(vec![Some(1.5), None, None]))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejects_file() {
+        let test_dir = TempStrDir::default();
+        let source = format!("{}/rejects.csv", test_dir.as_str());
+        std::fs::write(
+            &source,
+            "id,ts,msg\n\
+             1,2024-01-01,ok\n\
+             two,2024-01-02,bad id\n\
+             3,yesterday,bad ts\n\
+             4,2024-01-04,\"spans\ntwo lines\"\n\
+             ,2024-01-05,missing id\n\
+             6,2024-01-06,ok\n",
+        )
+        .unwrap();
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("ts", DataType::Timestamp(TimeUnit::Second, None), true),
+            Field::new("msg", DataType::Utf8, true),
+        ]));
+        let dest = format!("{}/dataset", test_dir.as_str());
+
+        // Without a rejects file the first bad row fails the import
+        let options = CsvImportOptions {
+            batch_size: 2,
+            ..CsvImportOptions::new(schema.clone())
+        };
+        let err = csv(&source, &dest, &options).await.unwrap_err();
+        assert!(matches!(err, Error::InvalidInput { .. }), "{err}");
+        assert!(err.to_string().contains("Line 3 of"), "{err}");
+        assert!(err.to_string().contains("'two' to Int32"), "{err}");
+
+        let rejects_path = format!("{}/rejects/bad.csv", test_dir.as_str());
+        let mut options = CsvImportOptions {
+            batch_size: 2,
+            rejects_uri: Some(rejects_path.clone()),
+            ..CsvImportOptions::new(schema)
+        };
+        options
+            .columns
+            .insert("ts".to_string(), timestamp_options(&["%Y-%m-%d"]));
+        let summary = csv(&source, &dest, &options).await.unwrap();
+        assert_eq!(summary.num_rows, 3);
+        assert_eq!(summary.num_rejected_rows, 3);
+        let actual = read_all(&summary.dataset).await;
+        assert_eq!(
+            actual.column(0).as_primitive::<Int32Type>(),
+            &Int32Array::from(vec![1, 4, 6])
+        );
+
+        let rejects = std::fs::read_to_string(&rejects_path).unwrap();
+        assert_eq!(
+            rejects,
+            "line,error,id,ts,msg\n\
+             3,column 'id': cannot convert 'two' to Int32,two,2024-01-02,bad id\n\
+             4,column 'ts': cannot convert 'yesterday' to Timestamp(s),3,yesterday,bad ts\n\
+             7,column 'id' cannot be null,,2024-01-05,missing id\n"
+        );
+
+        // Nothing is created when no row is rejected
+        let clean = format!("{}/clean.csv", test_dir.as_str());
+        std::fs::write(&clean, "id,ts,msg\n1,2024-01-01,ok\n").unwrap();
+        options.rejects_uri = Some(format!("{}/rejects/clean.csv", test_dir.as_str()));
+        let summary = csv(&clean, &format!("{}/clean", test_dir.as_str()), &options)
+            .await
+            .unwrap();
+        assert_eq!(summary.num_rejected_rows, 0);
+        assert!(
+            !std::path::Path::new(&format!("{}/rejects/clean.csv", test_dir.as_str())).exists()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_headerless_positional_schema() {
+        let test_dir = TempStrDir::default();
+        let source = format!("{}/legacy.txt", test_dir.as_str());
+        std::fs::write(&source, "1;'a;b'\n\n2;'c'\n").unwrap();
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("value", DataType::Utf8, true),
+        ]));
+        let options = CsvImportOptions {
+            has_header: false,
+            delimiter: b';',
+            quote: b'\'',
+            ..CsvImportOptions::new(schema.clone())
+        };
+        let summary = csv(&source, &format!("{}/legacy", test_dir.as_str()), &options)
+            .await
+            .unwrap();
+        let actual = read_all(&summary.dataset).await;
+        assert_eq!(actual.schema(), schema);
+        assert_eq!(
+            actual.column(0).as_primitive::<Int32Type>(),
+            &Int32Array::from(vec![1, 2])
+        );
+        assert_eq!(
+            actual.column(1).as_string::<i32>(),
+            &StringArray::from(vec!["a;b", "c"])
+        );
+
+        // The schema must match the number of columns in the file
+        let short = Arc::new(Schema::new(vec![Field::new("id", DataType::Int32, false)]));
+        let options = CsvImportOptions {
+            has_header: false,
+            delimiter: b';',
+            ..CsvImportOptions::new(short)
+        };
+        let err = csv(&source, &format!("{}/short", test_dir.as_str()), &options)
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("incorrect number of fields"),
+            "{err}"
+        );
+    }
+}
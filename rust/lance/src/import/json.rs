@@ -0,0 +1,651 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Import newline-delimited JSON (JSON Lines) files
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use arrow_array::RecordBatch;
+use arrow_json::reader::{ReaderBuilder, infer_json_schema_from_iterator};
+use arrow_schema::{DataType, FieldRef, Fields, Schema, SchemaRef};
+use async_compression::futures::bufread::{GzipDecoder, ZstdDecoder};
+use datafusion::error::DataFusionError;
+use datafusion::physical_plan::SendableRecordBatchStream;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use futures::TryStreamExt;
+use futures::io::{AsyncBufRead, AsyncBufReadExt, BufReader};
+use lance_io::object_store::{ObjectStore, ObjectStoreParams};
+use object_store::ObjectStoreExt;
+use object_store::path::Path;
+
+use crate::dataset::{InsertBuilder, WriteParams};
+use crate::{Dataset, Error, Result};
+
+/// The compression of a JSON Lines file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JsonCompression {
+    /// Decide from the file extension: `.gz` is gzip, `.zst` is zstd and anything else
+    /// is uncompressed
+    #[default]
+    Infer,
+    Uncompressed,
+    Gzip,
+    Zstd,
+}
+
+impl JsonCompression {
+    fn resolve(self, path: &Path) -> Self {
+        match self {
+            Self::Infer => match path.extension() {
+                Some("gz" | "gzip") => Self::Gzip,
+                Some("zst" | "zstd") => Self::Zstd,
+                _ => Self::Uncompressed,
+            },
+            compression => compression,
+        }
+    }
+}
+
+/// The schema of the records in a JSON Lines file
+#[derive(Debug, Clone)]
+pub enum JsonSchema {
+    /// Decode the records with the given schema
+    ///
+    /// Fields of the records that are not in the schema are ignored.
+    Provided(SchemaRef),
+    /// Infer the schema from the first `num_records` records
+    ///
+    /// Objects become struct columns and arrays become list columns.  Integers and
+    /// floats in the same field are widened to `Float64` and other conflicts become
+    /// strings.  Fields that are null in every sampled record are typed as strings.
+    ///
+    /// Fields are ordered by the record they first appear in, and by name within a
+    /// record.
+    Infer { num_records: usize },
+}
+
+/// Options for [`json_lines`]
+#[derive(Debug, Clone)]
+pub struct JsonImportOptions {
+    /// How the decoded records are written to the Lance dataset
+    ///
+    /// All records are committed as a single version, and the session's store registry
+    /// is also used to open the JSON Lines file.
+    pub write_params: WriteParams,
+    /// Parameters used to open the object store holding the JSON Lines file
+    pub source_store_params: ObjectStoreParams,
+    /// The maximum number of records decoded into each batch
+    ///
+    /// Only the lines of the current batch are kept in memory.
+    pub batch_size: usize,
+    pub compression: JsonCompression,
+    /// Skip lines that are not valid records instead of failing the import
+    ///
+    /// The number of skipped lines is reported by [`JsonImportSummary`].
+    pub skip_bad_lines: bool,
+}
+
+impl Default for JsonImportOptions {
+    fn default() -> Self {
+        Self {
+            write_params: WriteParams::default(),
+            source_store_params: ObjectStoreParams::default(),
+            batch_size: 8 * 1024,
+            compression: JsonCompression::default(),
+            skip_bad_lines: false,
+        }
+    }
+}
+
+/// The result of [`json_lines`]
+#[derive(Debug)]
+pub struct JsonImportSummary {
+    pub dataset: Dataset,
+    /// The number of records that were written
+    pub num_rows: u64,
+    /// The number of lines skipped because of [`JsonImportOptions::skip_bad_lines`]
+    pub num_skipped_lines: u64,
+}
+
+/// Import a JSON Lines file, optionally gzip or zstd compressed, into a Lance dataset
+///
+/// Every non-blank line of the file must hold one JSON object.  The file is streamed
+/// through the object store abstraction and decoded `batch_size` lines at a time, so
+/// memory use does not depend on the size of the file.  All records are committed as
+/// a single version of the dataset.
+///
+/// A line that is not valid JSON, holds something other than one object, or does not
+/// match the schema fails the import with an error naming the line, unless
+/// [`JsonImportOptions::skip_bad_lines`] is set.
+pub async fn json_lines(
+    source_uri: &str,
+    dest_uri: &str,
+    schema: JsonSchema,
+    options: &JsonImportOptions,
+) -> Result<JsonImportSummary> {
+    let registry = options
+        .write_params
+        .session
+        .as_ref()
+        .map(|session| session.store_registry())
+        .unwrap_or_default();
+    let (store, path) =
+        ObjectStore::from_uri_and_params(registry, source_uri, &options.source_store_params)
+            .await?;
+
+    let schema = match schema {
+        JsonSchema::Provided(schema) => schema,
+        JsonSchema::Infer { num_records } => {
+            Arc::new(infer_schema(&store, &path, num_records, options).await?)
+        }
+    };
+
+    let num_rows = Arc::new(AtomicU64::new(0));
+    let num_skipped_lines = Arc::new(AtomicU64::new(0));
+    let reader = JsonLinesReader::open(
+        &store,
+        &path,
+        schema.clone(),
+        options,
+        num_skipped_lines.clone(),
+    )
+    .await?;
+    let rows = num_rows.clone();
+    let stream = futures::stream::try_unfold(reader, |mut reader| async move {
+        Result::Ok(reader.next_batch().await?.map(|batch| (batch, reader)))
+    })
+    .map_ok(move |batch| {
+        rows.fetch_add(batch.num_rows() as u64, Ordering::Relaxed);
+        batch
+    })
+    .map_err(DataFusionError::from);
+    let stream: SendableRecordBatchStream = Box::pin(RecordBatchStreamAdapter::new(schema, stream));
+
+    let dataset = InsertBuilder::new(dest_uri)
+        .with_params(&options.write_params)
+        .execute_stream(stream)
+        .await?;
+    Ok(JsonImportSummary {
+        dataset,
+        num_rows: num_rows.load(Ordering::Relaxed),
+        num_skipped_lines: num_skipped_lines.load(Ordering::Relaxed),
+    })
+}
+
+/// Infer the schema of the first `num_records` records of the file
+async fn infer_schema(
+    store: &ObjectStore,
+    path: &Path,
+    num_records: usize,
+    options: &JsonImportOptions,
+) -> Result<Schema> {
+    let mut reader = open_lines(store, path, options.compression).await?;
+    let mut records = Vec::with_capacity(num_records.min(options.batch_size));
+    let mut line = Vec::new();
+    let mut line_number = 0;
+    while records.len() < num_records {
+        line.clear();
+        if read_line(&mut reader, &mut line, path).await? == 0 {
+            break;
+        }
+        line_number += 1;
+        if is_blank(&line) {
+            continue;
+        }
+        match serde_json::from_slice::<serde_json::Value>(&line) {
+            Ok(record @ serde_json::Value::Object(_)) => records.push(record),
+            _ if options.skip_bad_lines => {}
+            Ok(value) => {
+                return Err(bad_line(
+                    line_number,
+                    path,
+                    format!("expected a JSON object, found {}", value),
+                ));
+            }
+            Err(err) => return Err(bad_line(line_number, path, err)),
+        }
+    }
+    if records.is_empty() {
+        return Err(Error::invalid_input(format!(
+            "Cannot infer a schema, JSON Lines file '{}' has no records",
+            path
+        )));
+    }
+
+    let schema = infer_json_schema_from_iterator(records.iter().map(Ok))?;
+    let fields = schema
+        .fields()
+        .iter()
+        .map(replace_null_type)
+        .collect::<Vec<_>>();
+    Ok(Schema::new(fields))
+}
+
+/// Type fields that only held nulls as strings, so that later values can be decoded
+fn replace_null_type(field: &FieldRef) -> FieldRef {
+    let data_type = match field.data_type() {
+        DataType::Null => DataType::Utf8,
+        DataType::List(item) => DataType::List(replace_null_type(item)),
+        DataType::Struct(fields) => {
+            DataType::Struct(fields.iter().map(replace_null_type).collect::<Fields>())
+        }
+        data_type => data_type.clone(),
+    };
+    Arc::new(field.as_ref().clone().with_data_type(data_type))
+}
+
+type LineSource = Pin<Box<dyn AsyncBufRead + Send>>;
+
+/// Open a buffered reader over the decompressed bytes of the file
+async fn open_lines(
+    store: &ObjectStore,
+    path: &Path,
+    compression: JsonCompression,
+) -> Result<LineSource> {
+    let bytes = store
+        .inner
+        .get(path)
+        .await?
+        .into_stream()
+        .map_err(std::io::Error::other)
+        .into_async_read();
+    Ok(match compression.resolve(path) {
+        JsonCompression::Gzip => {
+            let mut decoder = GzipDecoder::new(bytes);
+            // Concatenated gzip files are common when events are appended over time
+            decoder.multiple_members(true);
+            Box::pin(BufReader::new(decoder))
+        }
+        JsonCompression::Zstd => {
+            let mut decoder = ZstdDecoder::new(bytes);
+            decoder.multiple_members(true);
+            Box::pin(BufReader::new(decoder))
+        }
+        JsonCompression::Infer | JsonCompression::Uncompressed => Box::pin(bytes),
+    })
+}
+
+/// Append the next line, including its line break, to `buf`
+///
+/// Returns the number of bytes read, which is zero at the end of the file.
+async fn read_line(reader: &mut LineSource, buf: &mut Vec<u8>, path: &Path) -> Result<usize> {
+    reader.read_until(b'\n', buf).await.map_err(|err| {
+        Error::io(format!(
+            "Failed to read JSON Lines file '{}': {}",
+            path, err
+        ))
+    })
+}
+
+fn is_blank(line: &[u8]) -> bool {
+    line.iter().all(u8::is_ascii_whitespace)
+}
+
+fn bad_line(line_number: usize, path: &Path, err: impl std::fmt::Display) -> Error {
+    Error::invalid_input(format!(
+        "Line {} of JSON Lines file '{}' is not a valid record: {}",
+        line_number, path, err
+    ))
+}
+
+/// Decodes the lines of a JSON Lines file into batches of records
+struct JsonLinesReader {
+    source: LineSource,
+    path: Path,
+    schema: SchemaRef,
+    batch_size: usize,
+    skip_bad_lines: bool,
+    num_skipped_lines: Arc<AtomicU64>,
+    line_number: usize,
+    /// The lines of the current batch
+    buffer: Vec<u8>,
+    /// The line number and start offset in `buffer` of each line of the current batch
+    lines: Vec<(usize, usize)>,
+}
+
+impl JsonLinesReader {
+    async fn open(
+        store: &ObjectStore,
+        path: &Path,
+        schema: SchemaRef,
+        options: &JsonImportOptions,
+        num_skipped_lines: Arc<AtomicU64>,
+    ) -> Result<Self> {
+        Ok(Self {
+            source: open_lines(store, path, options.compression).await?,
+            path: path.clone(),
+            schema,
+            batch_size: options.batch_size.max(1),
+            skip_bad_lines: options.skip_bad_lines,
+            num_skipped_lines,
+            line_number: 0,
+            buffer: Vec::new(),
+            lines: Vec::new(),
+        })
+    }
+
+    async fn next_batch(&mut self) -> Result<Option<RecordBatch>> {
+        loop {
+            self.buffer.clear();
+            self.lines.clear();
+            while self.lines.len() < self.batch_size {
+                let start = self.buffer.len();
+                if read_line(&mut self.source, &mut self.buffer, &self.path).await? == 0 {
+                    break;
+                }
+                self.line_number += 1;
+                if is_blank(&self.buffer[start..]) {
+                    self.buffer.truncate(start);
+                } else {
+                    self.lines.push((self.line_number, start));
+                }
+            }
+            if self.lines.is_empty() {
+                return Ok(None);
+            }
+
+            // Decoding the whole batch at once is much faster.  Only when that fails are
+            // the lines decoded one at a time to find the bad ones.
+            let batch = match self.decode(&self.buffer, self.lines.len()) {
+                Ok(batch) => Some(batch),
+                Err(_) => self.decode_lines()?,
+            };
+            if batch.is_some() {
+                return Ok(batch);
+            }
+            // Every line of this batch was skipped
+        }
+    }
+
+    /// Decode `buf`, which must hold exactly `num_records` records
+    fn decode(&self, buf: &[u8], num_records: usize) -> Result<RecordBatch> {
+        // A larger batch size lets a line with more than one record be detected
+        let mut decoder = ReaderBuilder::new(self.schema.clone())
+            .with_batch_size(num_records + 1)
+            .build_decoder()?;
+        decoder.decode(buf)?;
+        if decoder.has_partial_record() {
+            return Err(Error::invalid_input("incomplete JSON object"));
+        }
+        let batch = decoder
+            .flush()?
+            .unwrap_or_else(|| RecordBatch::new_empty(self.schema.clone()));
+        if batch.num_rows() != num_records {
+            return Err(Error::invalid_input(format!(
+                "expected {} records, found {}",
+                num_records,
+                batch.num_rows()
+            )));
+        }
+        Ok(batch)
+    }
+
+    fn decode_lines(&self) -> Result<Option<RecordBatch>> {
+        let mut batches = Vec::with_capacity(self.lines.len());
+        for (i, (line_number, start)) in self.lines.iter().enumerate() {
+            let end = self
+                .lines
+                .get(i + 1)
+                .map_or(self.buffer.len(), |(_, next)| *next);
+            match self.decode(&self.buffer[*start..end], 1) {
+                Ok(batch) => batches.push(batch),
+                Err(_) if self.skip_bad_lines => {
+                    self.num_skipped_lines.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(err) => return Err(bad_line(*line_number, &self.path, err)),
+            }
+        }
+        if batches.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(arrow_select::concat::concat_batches(
+            &self.schema,
+            &batches,
+        )?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use arrow_array::{
+        Array, BooleanArray, Float64Array, Int64Array, ListArray, StringArray, StructArray,
+        cast::AsArray, types::Int64Type,
+    };
+    use arrow_schema::Field;
+    use async_compression::futures::bufread::{GzipEncoder, ZstdEncoder};
+    use futures::{AsyncReadExt, TryStreamExt};
+    use lance_core::utils::tempfile::TempStrDir;
+
+    use super::*;
+
+    async fn read_all(dataset: &Dataset) -> RecordBatch {
+        let batches = dataset
+            .scan()
+            .try_into_stream()
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        arrow_select::concat::concat_batches(&batches[0].schema(), &batches).unwrap()
+    }
+
+    async fn compress(data: &[u8], compression: JsonCompression) -> Vec<u8> {
+        let mut compressed = Vec::new();
+        match compression {
+            JsonCompression::Gzip => GzipEncoder::new(data).read_to_end(&mut compressed).await,
+            JsonCompression::Zstd => ZstdEncoder::new(data).read_to_end(&mut compressed).await,
+            _ => unreachable!(),
+        }
+        .unwrap();
+        compressed
+    }
+
+    const EVENTS: &str = r#"{"id": 1, "name": "a", "score": 1, "tags": ["x"], "user": {"age": 30, "admin": true}}
+{"id": 2, "name": null, "score": 2.5, "extra": null}
+
+{"id": 3, "tags": ["y", "z"], "user": {"age": null, "admin": false}}
+"#;
+
+    #[tokio::test]
+    async fn test_infer_nested_schema() {
+        let test_dir = TempStrDir::default();
+        let source = format!("{}/events.jsonl.gz", test_dir.as_str());
+        std::fs::write(
+            &source,
+            compress(EVENTS.as_bytes(), JsonCompression::Gzip).await,
+        )
+        .unwrap();
+
+        let expected = Schema::new(vec![
+            Field::new("id", DataType::Int64, true),
+            Field::new("name", DataType::Utf8, true),
+            Field::new("score", DataType::Float64, true),
+            Field::new(
+                "tags",
+                DataType::List(Arc::new(Field::new("item", DataType::Utf8, true))),
+                true,
+            ),
+            Field::new(
+                "user",
+                DataType::Struct(Fields::from(vec![
+                    Field::new("admin", DataType::Boolean, true),
+                    Field::new("age", DataType::Int64, true),
+                ])),
+                true,
+            ),
+            Field::new("extra", DataType::Utf8, true),
+        ]);
+        let summary = json_lines(
+            &source,
+            &format!("{}/events", test_dir.as_str()),
+            JsonSchema::Infer { num_records: 100 },
+            &Default::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(summary.num_rows, 3);
+        assert_eq!(summary.num_skipped_lines, 0);
+        let actual = read_all(&summary.dataset).await;
+        assert_eq!(actual.schema().as_ref(), &expected);
+
+        assert_eq!(
+            actual.column(0).as_primitive::<Int64Type>(),
+            &Int64Array::from(vec![1, 2, 3])
+        );
+        assert_eq!(
+            actual.column(2).as_any().downcast_ref::<Float64Array>(),
+            Some(&Float64Array::from(vec![Some(1.0), Some(2.5), None]))
+        );
+        let tags = actual
+            .column(3)
+            .as_any()
+            .downcast_ref::<ListArray>()
+            .unwrap();
+        assert!(tags.is_null(1));
+        assert_eq!(
+            tags.value(2).as_string::<i32>(),
+            &StringArray::from(vec!["y", "z"])
+        );
+        let user = actual
+            .column(4)
+            .as_any()
+            .downcast_ref::<StructArray>()
+            .unwrap();
+        assert!(user.is_null(1));
+        assert_eq!(
+            user.column(0).as_boolean(),
+            &BooleanArray::from(vec![Some(true), None, Some(false)])
+        );
+
+        // The schema only depends on the sampled records.  Fields are ordered by the
+        // record they first appear in, and by name within a record.
+        let mut reordered = EVENTS.lines().rev().collect::<Vec<_>>().join("\n");
+        reordered.insert_str(0, "{\"id\": 0}\n");
+        let source = format!("{}/reordered.jsonl", test_dir.as_str());
+        std::fs::write(&source, reordered).unwrap();
+        let store = ObjectStore::local();
+        let path = Path::from_filesystem_path(&source).unwrap();
+        for _ in 0..3 {
+            let schema = infer_schema(&store, &path, 1, &Default::default())
+                .await
+                .unwrap();
+            assert_eq!(
+                schema,
+                Schema::new(vec![Field::new("id", DataType::Int64, true)])
+            );
+        }
+        let schema = infer_schema(&store, &path, 3, &Default::default())
+            .await
+            .unwrap();
+        let names = schema
+            .fields()
+            .iter()
+            .map(|field| field.name().as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["id", "tags", "user", "extra", "name", "score"]);
+    }
+
+    #[tokio::test]
+    async fn test_bad_lines() {
+        let test_dir = TempStrDir::default();
+        let source = format!("{}/bad.jsonl", test_dir.as_str());
+        std::fs::write(
+            &source,
+            "{\"a\": 1}\n{\"a\": 2\n[1, 2]\n{\"a\": \"three\"}\n{\"a\": 4}{\"a\": 5}\n{\"a\": 6}\n",
+        )
+        .unwrap();
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+
+        let err = json_lines(
+            &source,
+            &format!("{}/fail", test_dir.as_str()),
+            JsonSchema::Provided(schema.clone()),
+            &Default::default(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, Error::InvalidInput { .. }), "{err}");
+        assert!(err.to_string().contains("Line 2 of"), "{err}");
+
+        // Inference fails on the same line
+        let err = json_lines(
+            &source,
+            &format!("{}/fail", test_dir.as_str()),
+            JsonSchema::Infer { num_records: 10 },
+            &Default::default(),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("Line 2 of"), "{err}");
+
+        // Batches with only bad lines are skipped as well
+        for batch_size in [1, 2, 100] {
+            let options = JsonImportOptions {
+                batch_size,
+                skip_bad_lines: true,
+                ..Default::default()
+            };
+            let summary = json_lines(
+                &source,
+                &format!("{}/skip-{}", test_dir.as_str(), batch_size),
+                JsonSchema::Provided(schema.clone()),
+                &options,
+            )
+            .await
+            .unwrap();
+            assert_eq!(summary.num_rows, 2);
+            assert_eq!(summary.num_skipped_lines, 4);
+            let actual = read_all(&summary.dataset).await;
+            assert_eq!(
+                actual.column(0).as_primitive::<Int64Type>(),
+                &Int64Array::from(vec![1, 6])
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_streaming_memory_is_bounded() {
+        let test_dir = TempStrDir::default();
+        let source = format!("{}/large.jsonl.zst", test_dir.as_str());
+        let num_rows = 200_000;
+        let mut data = Vec::new();
+        for i in 0..num_rows {
+            writeln!(data, "{{\"id\": {}, \"value\": \"value-{:010}\"}}", i, i).unwrap();
+        }
+        let line_size = data.len() / num_rows;
+        std::fs::write(&source, compress(&data, JsonCompression::Zstd).await).unwrap();
+
+        let store = ObjectStore::local();
+        let path = Path::from_filesystem_path(&source).unwrap();
+        let schema = Arc::new(
+            infer_schema(&store, &path, 10, &Default::default())
+                .await
+                .unwrap(),
+        );
+        let options = JsonImportOptions {
+            batch_size: 1000,
+            ..Default::default()
+        };
+        let mut reader = JsonLinesReader::open(&store, &path, schema, &options, Default::default())
+            .await
+            .unwrap();
+        let mut rows = 0;
+        while let Some(batch) = reader.next_batch().await.unwrap() {
+            assert!(batch.num_rows() <= 1000);
+            rows += batch.num_rows();
+            // Only the lines of one batch are ever buffered
+            assert!(
+                reader.buffer.capacity() <= 2 * 1000 * line_size,
+                "{} bytes buffered",
+                reader.buffer.capacity()
+            );
+        }
+        assert_eq!(rows, num_rows);
+        assert!(data.len() > 20 * reader.buffer.capacity());
+    }
+}
@@ -0,0 +1,712 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Import Parquet files
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use ::parquet::arrow::ParquetRecordBatchStreamBuilder;
+use ::parquet::arrow::async_reader::ParquetObjectReader;
+use ::parquet::errors::ParquetError;
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, FieldRef, Fields, Schema, SchemaRef};
+use datafusion::error::DataFusionError;
+use datafusion::physical_plan::SendableRecordBatchStream;
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use futures::{StreamExt, TryStreamExt};
+use lance_core::datatypes::LogicalType;
+use lance_io::object_store::{ObjectStore, ObjectStoreParams};
+use object_store::path::Path;
+use tracing::info;
+
+use crate::dataset::builder::DatasetBuilder;
+use crate::dataset::{InsertBuilder, ReadParams, WriteMode, WriteParams};
+use crate::{Dataset, Error, Result};
+
+/// Transaction property recording the source file that a commit imported
+///
+/// Used by [`ParquetImportOptions::resume`] to find the files that are already
+/// part of the dataset.
+pub const IMPORTED_PARQUET_FILE_KEY: &str = "lance.import.parquet.file";
+
+/// Options for [`parquet`]
+#[derive(Debug, Clone)]
+pub struct ParquetImportOptions {
+    /// How each Parquet file is written to the Lance dataset
+    ///
+    /// Every source file is committed as its own version, tagged with
+    /// [`IMPORTED_PARQUET_FILE_KEY`] on top of `transaction_properties`.  `mode`
+    /// applies to the first source file that is written, every later file is appended.
+    pub write_params: WriteParams,
+    /// Parameters used to open the object store holding the Parquet files
+    pub source_store_params: ObjectStoreParams,
+    /// The number of rows in each batch read from the Parquet files
+    pub batch_size: usize,
+    /// Whether the field and schema metadata of the Parquet files is carried over
+    pub keep_metadata: bool,
+    /// Skip the source files that an earlier import committed to the destination
+    ///
+    /// Every source file is committed separately, so an interrupted import can be
+    /// resumed by running it again with this option.
+    pub resume: bool,
+}
+
+impl Default for ParquetImportOptions {
+    fn default() -> Self {
+        Self {
+            write_params: WriteParams::default(),
+            source_store_params: ObjectStoreParams::default(),
+            batch_size: 8 * 1024,
+            keep_metadata: false,
+            resume: false,
+        }
+    }
+}
+
+/// Import one Parquet file, or a directory of Parquet files, into a Lance dataset
+///
+/// `source_uri` is either a single `.parquet` file or a directory that is searched
+/// recursively for `.parquet` files, which are imported in path order.  Files are read
+/// through the object store abstraction one row group at a time and every file is
+/// committed as its own version of the dataset.
+///
+/// All files must share the same schema.  Parquet logical types are mapped to the
+/// equivalent Arrow types (decimals, timestamps with time zones, nested lists, structs
+/// and maps).  Decimals stored with fewer than 128 bits are widened to `Decimal128`.
+/// Types that Lance cannot store, such as intervals, return an error naming the column.
+pub async fn parquet(
+    source_uri: &str,
+    dest_uri: &str,
+    options: &ParquetImportOptions,
+) -> Result<Dataset> {
+    let registry = options
+        .write_params
+        .session
+        .as_ref()
+        .map(|session| session.store_registry())
+        .unwrap_or_default();
+    let (store, source_path) =
+        ObjectStore::from_uri_and_params(registry, source_uri, &options.source_store_params)
+            .await?;
+    let source_files = list_parquet_files(&store, &source_path).await?;
+    if source_files.is_empty() {
+        return Err(Error::invalid_input(format!(
+            "No Parquet files found at '{}'",
+            source_uri
+        )));
+    }
+
+    let mut dataset = None;
+    let mut imported = HashSet::new();
+    if options.resume {
+        match open_dataset(dest_uri, &options.write_params).await {
+            Ok(existing) => {
+                imported = imported_files(&existing).await?;
+                dataset = Some(Arc::new(existing));
+            }
+            Err(Error::DatasetNotFound { .. }) => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    let mut expected_schema: Option<(String, SchemaRef)> = None;
+    for (path, size) in source_files {
+        let location = path.to_string();
+        if imported.contains(&location) {
+            info!("Skipping {}, it has already been imported", location);
+            continue;
+        }
+
+        let (schema, stream) = read_parquet_file(&store, path, size, options).await?;
+        match &expected_schema {
+            Some((first, expected)) => {
+                if expected.fields() != schema.fields() {
+                    return Err(Error::invalid_input(format!(
+                        "Parquet file '{}' has a different schema than '{}': {} vs {}",
+                        location, first, schema, expected
+                    )));
+                }
+            }
+            None => expected_schema = Some((location.clone(), schema)),
+        }
+
+        let mut transaction_properties = options
+            .write_params
+            .transaction_properties
+            .as_deref()
+            .cloned()
+            .unwrap_or_default();
+        transaction_properties.insert(IMPORTED_PARQUET_FILE_KEY.to_string(), location);
+        let mut params = options
+            .write_params
+            .clone()
+            .with_transaction_properties(transaction_properties);
+        let written = match dataset.take() {
+            Some(dataset) => {
+                params.mode = WriteMode::Append;
+                InsertBuilder::new(dataset)
+                    .with_params(&params)
+                    .execute_stream(stream)
+                    .await?
+            }
+            None => {
+                InsertBuilder::new(dest_uri)
+                    .with_params(&params)
+                    .execute_stream(stream)
+                    .await?
+            }
+        };
+        dataset = Some(Arc::new(written));
+    }
+
+    match dataset {
+        Some(dataset) => Ok(Arc::unwrap_or_clone(dataset)),
+        // Everything was imported by an earlier run
+        None => open_dataset(dest_uri, &options.write_params).await,
+    }
+}
+
+async fn open_dataset(uri: &str, write_params: &WriteParams) -> Result<Dataset> {
+    DatasetBuilder::from_uri(uri)
+        .with_read_params(ReadParams {
+            store_options: write_params.store_params.clone(),
+            session: write_params.session.clone(),
+            commit_handler: write_params.commit_handler.clone(),
+            ..Default::default()
+        })
+        .load()
+        .await
+}
+
+/// The source files recorded by the import commits of `dataset`
+async fn imported_files(dataset: &Dataset) -> Result<HashSet<String>> {
+    let mut imported = HashSet::new();
+    for version in dataset.versions().await? {
+        if let Some(transaction) = dataset.read_transaction_by_version(version.version).await?
+            && let Some(file) = transaction
+                .transaction_properties
+                .as_ref()
+                .and_then(|properties| properties.get(IMPORTED_PARQUET_FILE_KEY))
+        {
+            imported.insert(file.clone());
+        }
+    }
+    Ok(imported)
+}
+
+/// The paths and sizes of the Parquet files at `path`, in path order
+async fn list_parquet_files(store: &ObjectStore, path: &Path) -> Result<Vec<(Path, u64)>> {
+    if path.extension() == Some("parquet") {
+        return Ok(vec![(path.clone(), store.size(path).await?)]);
+    }
+    let mut files = store
+        .read_dir_all(path, None)
+        .try_filter_map(|meta| {
+            let file = (meta.location.extension() == Some("parquet"))
+                .then_some((meta.location, meta.size));
+            futures::future::ready(Ok(file))
+        })
+        .try_collect::<Vec<_>>()
+        .await?;
+    files.sort();
+    Ok(files)
+}
+
+/// Open a stream over the batches of one Parquet file, converted to the Lance schema
+async fn read_parquet_file(
+    store: &ObjectStore,
+    path: Path,
+    size: u64,
+    options: &ParquetImportOptions,
+) -> Result<(SchemaRef, SendableRecordBatchStream)> {
+    let location = path.to_string();
+    let parquet_error = |err: ParquetError| {
+        Error::io(format!(
+            "Failed to read Parquet file '{}': {}",
+            location, err
+        ))
+    };
+
+    let reader = ParquetObjectReader::new(store.inner.clone(), path).with_file_size(size);
+    let builder = ParquetRecordBatchStreamBuilder::new(reader)
+        .await
+        .map_err(parquet_error)?
+        .with_batch_size(options.batch_size);
+    let schema = Arc::new(to_lance_schema(
+        builder.schema(),
+        options.keep_metadata,
+        &location,
+    )?);
+
+    let target = schema.clone();
+    let stream = builder.build().map_err(parquet_error)?.map(move |batch| {
+        let batch = batch.map_err(|err| DataFusionError::External(Box::new(err)))?;
+        convert_batch(batch, &target).map_err(DataFusionError::from)
+    });
+    let stream = Box::pin(RecordBatchStreamAdapter::new(schema.clone(), stream));
+    Ok((schema, stream))
+}
+
+fn convert_batch(batch: RecordBatch, schema: &SchemaRef) -> Result<RecordBatch> {
+    let columns = batch
+        .columns()
+        .iter()
+        .zip(schema.fields())
+        .map(|(column, field)| {
+            if column.data_type() == field.data_type() {
+                Ok(column.clone())
+            } else {
+                Ok(arrow_cast::cast(column, field.data_type())?)
+            }
+        })
+        .collect::<Result<Vec<ArrayRef>>>()?;
+    Ok(RecordBatch::try_new(schema.clone(), columns)?)
+}
+
+/// Map the Arrow schema of a Parquet file to one that Lance can store
+fn to_lance_schema(schema: &Schema, keep_metadata: bool, file: &str) -> Result<Schema> {
+    let fields = schema
+        .fields()
+        .iter()
+        .map(|field| to_lance_field(field, field.name(), keep_metadata, file))
+        .collect::<Result<Vec<_>>>()?;
+    let metadata = if keep_metadata {
+        schema.metadata().clone()
+    } else {
+        HashMap::new()
+    };
+    Ok(Schema::new_with_metadata(fields, metadata))
+}
+
+fn to_lance_field(field: &Field, path: &str, keep_metadata: bool, file: &str) -> Result<FieldRef> {
+    let child = |child: &FieldRef| {
+        to_lance_field(
+            child,
+            &format!("{}.{}", path, child.name()),
+            keep_metadata,
+            file,
+        )
+    };
+    let data_type = match field.data_type() {
+        DataType::Decimal32(precision, scale) | DataType::Decimal64(precision, scale) => {
+            DataType::Decimal128(*precision, *scale)
+        }
+        DataType::List(item) => DataType::List(child(item)?),
+        DataType::LargeList(item) => DataType::LargeList(child(item)?),
+        DataType::FixedSizeList(item, size) => DataType::FixedSizeList(child(item)?, *size),
+        DataType::Struct(fields) => {
+            DataType::Struct(fields.iter().map(child).collect::<Result<Fields>>()?)
+        }
+        DataType::Map(entries, sorted) => DataType::Map(child(entries)?, *sorted),
+        data_type => data_type.clone(),
+    };
+    if LogicalType::try_from(&data_type).is_err() {
+        return Err(Error::not_supported(format!(
+            "Cannot import column '{}' of Parquet file '{}': Lance does not support the type {}",
+            path,
+            file,
+            field.data_type()
+        )));
+    }
+    let mut lance_field = field.as_ref().clone().with_data_type(data_type);
+    if !keep_metadata {
+        lance_field.set_metadata(HashMap::new());
+    }
+    Ok(Arc::new(lance_field))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ::parquet::arrow::ArrowWriter;
+    use arrow_array::types::{Int32Type, IntervalDayTimeType};
+    use arrow_array::{
+        ArrayRef, BinaryArray, BooleanArray, Date32Array, Decimal32Array, Decimal128Array,
+        Decimal256Array, FixedSizeBinaryArray, Float32Array, Float64Array, Int8Array, Int16Array,
+        Int32Array, Int64Array, IntervalDayTimeArray, LargeBinaryArray, LargeStringArray,
+        ListArray, RecordBatch, StringArray, StructArray, Time64MicrosecondArray,
+        TimestampMicrosecondArray, TimestampMillisecondArray, UInt8Array, UInt16Array, UInt32Array,
+        UInt64Array,
+    };
+    use arrow_buffer::i256;
+    use arrow_schema::{DataType, Field, Fields, Schema, TimeUnit};
+    use futures::TryStreamExt;
+    use lance_core::utils::tempfile::TempStrDir;
+
+    use super::*;
+
+    fn write_parquet(path: &str, batch: &RecordBatch) {
+        let file = std::fs::File::create(path).unwrap();
+        let mut writer = ArrowWriter::try_new(file, batch.schema(), None).unwrap();
+        writer.write(batch).unwrap();
+        writer.close().unwrap();
+    }
+
+    async fn read_all(dataset: &Dataset) -> RecordBatch {
+        let batches = dataset
+            .scan()
+            .try_into_stream()
+            .await
+            .unwrap()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        arrow_select::concat::concat_batches(&batches[0].schema(), &batches).unwrap()
+    }
+
+    fn int_batch(start: i64, num_rows: i64) -> RecordBatch {
+        let schema = Arc::new(Schema::new(vec![Field::new("x", DataType::Int64, false)]));
+        RecordBatch::try_new(
+            schema,
+            vec![Arc::new(Int64Array::from_iter_values(
+                start..start + num_rows,
+            ))],
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_import_round_trip_all_types() {
+        let struct_fields = Fields::from(vec![
+            Field::new("a", DataType::Int32, true),
+            Field::new("b", DataType::Utf8, true),
+        ]);
+        let columns: Vec<(Field, ArrayRef)> = vec![
+            (
+                Field::new("bool", DataType::Boolean, true),
+                Arc::new(BooleanArray::from(vec![Some(true), None, Some(false)])),
+            ),
+            (
+                Field::new("i8", DataType::Int8, true),
+                Arc::new(Int8Array::from(vec![Some(-1), None, Some(i8::MAX)])),
+            ),
+            (
+                Field::new("i16", DataType::Int16, false),
+                Arc::new(Int16Array::from(vec![-2, 0, i16::MAX])),
+            ),
+            (
+                Field::new("i32", DataType::Int32, true)
+                    .with_metadata([("description".to_string(), "an int".to_string())].into()),
+                Arc::new(Int32Array::from(vec![Some(3), Some(4), None])),
+            ),
+            (
+                Field::new("i64", DataType::Int64, false),
+                Arc::new(Int64Array::from(vec![i64::MIN, 0, i64::MAX])),
+            ),
+            (
+                Field::new("u8", DataType::UInt8, false),
+                Arc::new(UInt8Array::from(vec![0, 1, u8::MAX])),
+            ),
+            (
+                Field::new("u16", DataType::UInt16, false),
+                Arc::new(UInt16Array::from(vec![0, 1, u16::MAX])),
+            ),
+            (
+                Field::new("u32", DataType::UInt32, false),
+                Arc::new(UInt32Array::from(vec![0, 1, u32::MAX])),
+            ),
+            (
+                Field::new("u64", DataType::UInt64, false),
+                Arc::new(UInt64Array::from(vec![0, 1, u64::MAX])),
+            ),
+            (
+                Field::new("f32", DataType::Float32, true),
+                Arc::new(Float32Array::from(vec![Some(1.5), None, Some(-0.25)])),
+            ),
+            (
+                Field::new("f64", DataType::Float64, false),
+                Arc::new(Float64Array::from(vec![1e300, 0.0, -2.5])),
+            ),
+            (
+                Field::new("dec128", DataType::Decimal128(10, 2), true),
+                Arc::new(
+                    Decimal128Array::from(vec![Some(12345), None, Some(-99)])
+                        .with_precision_and_scale(10, 2)
+                        .unwrap(),
+                ),
+            ),
+            (
+                Field::new("dec256", DataType::Decimal256(50, 10), false),
+                Arc::new(
+                    Decimal256Array::from(vec![
+                        i256::from_i128(1),
+                        i256::from_i128(i128::MAX),
+                        i256::from_i128(-7),
+                    ])
+                    .with_precision_and_scale(50, 10)
+                    .unwrap(),
+                ),
+            ),
+            (
+                Field::new("str", DataType::Utf8, true),
+                Arc::new(StringArray::from(vec![Some("a"), None, Some("")])),
+            ),
+            (
+                Field::new("large_str", DataType::LargeUtf8, false),
+                Arc::new(LargeStringArray::from(vec!["x", "yy", "zzz"])),
+            ),
+            (
+                Field::new("bin", DataType::Binary, true),
+                Arc::new(BinaryArray::from(vec![
+                    Some(b"\x00\x01".as_ref()),
+                    None,
+                    Some(b"".as_ref()),
+                ])),
+            ),
+            (
+                Field::new("large_bin", DataType::LargeBinary, false),
+                Arc::new(LargeBinaryArray::from(vec![
+                    b"a".as_ref(),
+                    b"bc".as_ref(),
+                    b"def".as_ref(),
+                ])),
+            ),
+            (
+                Field::new("fixed_bin", DataType::FixedSizeBinary(2), false),
+                Arc::new(
+                    FixedSizeBinaryArray::try_from_iter(
+                        vec![b"ab".to_vec(), b"cd".to_vec(), b"ef".to_vec()].into_iter(),
+                    )
+                    .unwrap(),
+                ),
+            ),
+            (
+                Field::new("date", DataType::Date32, true),
+                Arc::new(Date32Array::from(vec![Some(0), Some(19000), None])),
+            ),
+            (
+                Field::new("time", DataType::Time64(TimeUnit::Microsecond), false),
+                Arc::new(Time64MicrosecondArray::from(vec![
+                    0,
+                    1_000_000,
+                    86_399_999_999,
+                ])),
+            ),
+            (
+                Field::new(
+                    "ts_utc",
+                    DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+                    true,
+                ),
+                Arc::new(
+                    TimestampMicrosecondArray::from(vec![Some(0), None, Some(1_700_000_000)])
+                        .with_timezone("UTC"),
+                ),
+            ),
+            (
+                Field::new(
+                    "ts_offset",
+                    DataType::Timestamp(TimeUnit::Millisecond, Some("+08:00".into())),
+                    false,
+                ),
+                Arc::new(TimestampMillisecondArray::from(vec![1, 2, 3]).with_timezone("+08:00")),
+            ),
+            (
+                Field::new(
+                    "list",
+                    DataType::List(Arc::new(Field::new("item", DataType::Int32, true))),
+                    true,
+                ),
+                Arc::new(ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+                    Some(vec![Some(1), None]),
+                    None,
+                    Some(vec![]),
+                ])),
+            ),
+            (
+                Field::new("struct", DataType::Struct(struct_fields.clone()), true),
+                Arc::new(StructArray::new(
+                    struct_fields,
+                    vec![
+                        Arc::new(Int32Array::from(vec![Some(1), None, Some(3)])),
+                        Arc::new(StringArray::from(vec![Some("one"), Some("two"), None])),
+                    ],
+                    None,
+                )),
+            ),
+        ];
+        let (fields, arrays): (Vec<_>, Vec<_>) = columns.into_iter().unzip();
+        let decimal32 = Field::new("dec32", DataType::Decimal32(5, 2), false);
+        let expected =
+            RecordBatch::try_new(Arc::new(Schema::new(fields.clone())), arrays.clone()).unwrap();
+        let source = RecordBatch::try_new(
+            Arc::new(Schema::new([fields, vec![decimal32]].concat())),
+            [
+                arrays,
+                vec![Arc::new(
+                    Decimal32Array::from(vec![1, -2, 999])
+                        .with_precision_and_scale(5, 2)
+                        .unwrap(),
+                )],
+            ]
+            .concat(),
+        )
+        .unwrap();
+
+        let test_dir = TempStrDir::default();
+        let source_uri = format!("{}/data.parquet", test_dir.as_str());
+        write_parquet(&source_uri, &source);
+
+        for keep_metadata in [false, true] {
+            let dest_uri = format!("{}/dataset_{}", test_dir.as_str(), keep_metadata);
+            let options = ParquetImportOptions {
+                keep_metadata,
+                ..Default::default()
+            };
+            let dataset = parquet(&source_uri, &dest_uri, &options).await.unwrap();
+            let actual = read_all(&dataset).await;
+
+            for field in expected.schema().fields() {
+                let column = actual.column_by_name(field.name()).unwrap();
+                assert_eq!(column.data_type(), field.data_type(), "{}", field.name());
+                assert_eq!(
+                    column.as_ref(),
+                    expected.column_by_name(field.name()).unwrap().as_ref(),
+                    "{}",
+                    field.name()
+                );
+            }
+            // Narrow decimals are widened
+            let decimal = actual.column_by_name("dec32").unwrap();
+            assert_eq!(decimal.data_type(), &DataType::Decimal128(5, 2));
+            assert_eq!(
+                decimal.as_ref(),
+                &Decimal128Array::from(vec![1, -2, 999])
+                    .with_precision_and_scale(5, 2)
+                    .unwrap()
+            );
+
+            let metadata = actual
+                .schema()
+                .field_with_name("i32")
+                .unwrap()
+                .metadata()
+                .clone();
+            if keep_metadata {
+                assert_eq!(metadata.get("description").unwrap(), "an int");
+            } else {
+                assert!(metadata.is_empty(), "{:?}", metadata);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_import_directory_and_resume() {
+        let source_dir = TempStrDir::default();
+        for i in 0..3 {
+            write_parquet(
+                &format!("{}/part-{}.parquet", source_dir.as_str(), i),
+                &int_batch(i * 100, 100),
+            );
+        }
+        std::fs::write(format!("{}/_SUCCESS", source_dir.as_str()), b"").unwrap();
+
+        let dest_dir = TempStrDir::default();
+        let options = ParquetImportOptions {
+            write_params: WriteParams {
+                max_rows_per_file: 40,
+                ..Default::default()
+            },
+            resume: true,
+            ..Default::default()
+        };
+        let dataset = parquet(source_dir.as_str(), dest_dir.as_str(), &options)
+            .await
+            .unwrap();
+        // One commit per source file, each split into fragments of at most 40 rows
+        assert_eq!(dataset.version().version, 3);
+        assert_eq!(dataset.get_fragments().len(), 9);
+        let values = read_all(&dataset).await;
+        assert_eq!(
+            values.column(0).as_ref(),
+            &Int64Array::from_iter_values(0..300)
+        );
+
+        // Resuming only imports the files that have not been committed yet
+        write_parquet(
+            &format!("{}/part-3.parquet", source_dir.as_str()),
+            &int_batch(300, 100),
+        );
+        let dataset = parquet(source_dir.as_str(), dest_dir.as_str(), &options)
+            .await
+            .unwrap();
+        assert_eq!(dataset.version().version, 4);
+        assert_eq!(dataset.count_rows(None).await.unwrap(), 400);
+
+        let dataset = parquet(source_dir.as_str(), dest_dir.as_str(), &options)
+            .await
+            .unwrap();
+        assert_eq!(dataset.version().version, 4);
+    }
+
+    #[tokio::test]
+    async fn test_import_errors() {
+        let test_dir = TempStrDir::default();
+
+        // Lance cannot store intervals, even nested ones
+        let interval = Field::new(
+            "span",
+            DataType::Interval(arrow_schema::IntervalUnit::DayTime),
+            true,
+        );
+        let struct_fields = Fields::from(vec![interval]);
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "s",
+            DataType::Struct(struct_fields.clone()),
+            true,
+        )]));
+        let batch = RecordBatch::try_new(
+            schema,
+            vec![Arc::new(StructArray::new(
+                struct_fields,
+                vec![Arc::new(IntervalDayTimeArray::from(vec![
+                    IntervalDayTimeType::make_value(1, 2),
+                ]))],
+                None,
+            ))],
+        )
+        .unwrap();
+        let source_uri = format!("{}/interval.parquet", test_dir.as_str());
+        write_parquet(&source_uri, &batch);
+        let err = parquet(
+            &source_uri,
+            &format!("{}/interval", test_dir.as_str()),
+            &Default::default(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, Error::NotSupported { .. }), "{err}");
+        assert!(err.to_string().contains("column 's.span'"), "{err}");
+
+        // All files of a directory must have the same schema
+        let mixed_dir = format!("{}/mixed", test_dir.as_str());
+        std::fs::create_dir(&mixed_dir).unwrap();
+        write_parquet(&format!("{}/a.parquet", mixed_dir), &int_batch(0, 10));
+        let other = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![Field::new("y", DataType::Utf8, true)])),
+            vec![Arc::new(StringArray::from(vec!["a"]))],
+        )
+        .unwrap();
+        write_parquet(&format!("{}/b.parquet", mixed_dir), &other);
+        let err = parquet(
+            &mixed_dir,
+            &format!("{}/mixed_dataset", test_dir.as_str()),
+            &Default::default(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, Error::InvalidInput { .. }), "{err}");
+        assert!(err.to_string().contains("b.parquet"), "{err}");
+
+        let err = parquet(
+            &format!("{}/empty", test_dir.as_str()),
+            &format!("{}/empty_dataset", test_dir.as_str()),
+            &Default::default(),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("No Parquet files found"), "{err}");
+    }
+}
@@ -4,7 +4,7 @@
 //! Secondary Index
 //!
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::sync::{Arc, OnceLock};
 
 use arrow_schema::DataType;
@@ -17,6 +17,7 @@ use lance_core::datatypes::Field;
 use lance_core::datatypes::Schema as LanceSchema;
 use lance_core::utils::address::RowAddress;
 use lance_core::utils::parse::parse_env_as_bool;
+use lance_core::utils::resources::{ResourceTracker, with_resource_tracker};
 use lance_core::utils::tracing::{
     IO_TYPE_OPEN_FRAG_REUSE, IO_TYPE_OPEN_MEM_WAL, IO_TYPE_OPEN_VECTOR, TRACE_IO_EVENTS,
 };
@@ -66,6 +67,7 @@ use vector::details::{
     derive_vector_index_type, infer_missing_vector_details, vector_details_as_json,
 };
 pub(crate) use vector::details::{vector_index_details, vector_index_details_default};
+use vector::ivf::IvfPartitionSummary;
 use vector::ivf::v2::{IVFIndex, IvfStateEntryBox};
 use vector::utils::get_vector_type;
 
@@ -85,7 +87,7 @@ use crate::dataset::index::LanceIndexStoreExt;
 use crate::dataset::optimize::RemappedIndex;
 use crate::dataset::optimize::remapping::RemapResult;
 use crate::dataset::transaction::{Operation, Transaction, TransactionBuilder};
-pub use crate::index::api::{DatasetIndexExt, IndexSegment, IntoIndexSegment};
+pub use crate::index::api::{DatasetIndexExt, IndexBuildMetrics, IndexSegment, IntoIndexSegment};
 use crate::index::frag_reuse::{load_frag_reuse_index_details, open_frag_reuse_index};
 use crate::index::mem_wal::open_mem_wal_index;
 pub use crate::index::prefilter::{FilterLoader, PreFilter};
@@ -384,10 +386,12 @@ impl CacheKey for MemWalCacheKey<'_> {
     }
 }
 
-// Whether to auto-migrate a dataset when we encounter corruption.
-fn auto_migrate_corruption() -> bool {
+// Whether to auto-migrate a dataset when we encounter corruption. Migrating
+// commits a new version, so read-only datasets are never migrated.
+fn auto_migrate_corruption(dataset: &Dataset) -> bool {
     static LANCE_AUTO_MIGRATION: OnceLock<bool> = OnceLock::new();
     *LANCE_AUTO_MIGRATION.get_or_init(|| parse_env_as_bool("LANCE_AUTO_MIGRATION", true))
+        && !dataset.is_read_only()
 }
 
 /// Derive a friendly (but not necessarily unique) type name from a type URL.
@@ -422,6 +426,7 @@ fn legacy_type_name(index_uri: &str, index_type_hint: Option<&str>) -> String {
         "ZoneMap" => IndexType::ZoneMap.to_string(),
         "BloomFilter" => IndexType::BloomFilter.to_string(),
         "RTree" => IndexType::RTree.to_string(),
+        "SparseVector" => IndexType::SparseVector.to_string(),
         "Inverted" => IndexType::Inverted.to_string(),
         "Json" => IndexType::Scalar.to_string(),
         "Flat" | "Vector" => IndexType::Vector.to_string(),
@@ -913,9 +918,23 @@ impl DatasetIndexExt for Dataset {
     }
 
     async fn drop_index(&mut self, name: &str) -> Result<()> {
-        let indices = self.load_indices_by_name(name).await?;
+        let all_indices = self.load_indices().await?;
+        let indices = all_indices
+            .iter()
+            .filter(|idx| idx.name == name)
+            .cloned()
+            .collect::<Vec<_>>();
         if indices.is_empty() {
-            return Err(Error::index_not_found(format!("name={}", name)));
+            let existing = all_indices
+                .iter()
+                .filter(|idx| !is_system_index(idx))
+                .map(|idx| idx.name.as_str())
+                .collect::<BTreeSet<_>>();
+            return Err(Error::index_not_found(format!(
+                "name={}, existing indices: [{}]",
+                name,
+                existing.into_iter().collect::<Vec<_>>().join(", ")
+            )));
         }
 
         let transaction = Transaction::new(
@@ -1304,81 +1323,10 @@ impl DatasetIndexExt for Dataset {
     }
 
     #[instrument(skip_all)]
-    async fn optimize_indices(&mut self, options: &OptimizeOptions) -> Result<()> {
-        let dataset = Arc::new(self.clone());
-        let indices = self.load_indices().await?;
-
-        let indices_to_optimize = options
-            .index_names
-            .as_ref()
-            .map(|names| names.iter().collect::<HashSet<_>>());
-        let name_to_indices = indices
-            .iter()
-            .filter(|idx| {
-                indices_to_optimize
-                    .as_ref()
-                    .is_none_or(|names| names.contains(&idx.name))
-                    && !is_system_index(idx)
-            })
-            .map(|idx| (idx.name.clone(), idx))
-            .into_group_map();
-
-        let mut new_indices = vec![];
-        let mut removed_indices = vec![];
-        for deltas in name_to_indices.values() {
-            // Scalar indices have no rebalance concept, so skip them entirely
-            // when every fragment is already covered and the caller hasn't
-            // asked for retrain or an explicit delta merge. Vector indices
-            // fall through and use a rebalance-aware no-op check inside
-            // merge_indices_with_unindexed_frags.
-            if !options.retrain
-                && options.num_indices_to_merge.is_none_or(|n| n == 0)
-                && index_group_is_scalar(self, deltas)
-                && index_group_has_no_unindexed(self, deltas)
-            {
-                continue;
-            }
-
-            let Some(res) = merge_indices(dataset.clone(), deltas.as_slice(), options).await?
-            else {
-                continue;
-            };
-
-            let last_idx = deltas.last().expect("Delta indices should not be empty");
-            let new_idx = IndexMetadata {
-                uuid: res.new_uuid,
-                name: last_idx.name.clone(), // Keep the same name
-                fields: last_idx.fields.clone(),
-                dataset_version: self.manifest.version,
-                fragment_bitmap: Some(res.new_fragment_bitmap),
-                index_details: Some(Arc::new(res.new_index_details)),
-                index_version: res.new_index_version,
-                created_at: Some(chrono::Utc::now()),
-                base_id: None, // New merged index file locates in the cloned dataset.
-                files: res.files,
-            };
-            removed_indices.extend(res.removed_indices.iter().map(|&idx| idx.clone()));
-            new_indices.push(new_idx);
-        }
-
-        if new_indices.is_empty() {
-            return Ok(());
-        }
-
-        let transaction = TransactionBuilder::new(
-            self.manifest.version,
-            Operation::CreateIndex {
-                new_indices,
-                removed_indices,
-            },
-        )
-        .transaction_properties(options.transaction_properties.clone())
-        .build();
-
-        self.apply_commit(transaction, &Default::default(), &Default::default())
-            .await?;
-
-        Ok(())
+    async fn optimize_indices(&mut self, options: &OptimizeOptions) -> Result<IndexBuildMetrics> {
+        let tracker = Arc::new(ResourceTracker::default());
+        with_resource_tracker(tracker.clone(), optimize_indices_impl(self, options)).await?;
+        Ok(IndexBuildMetrics::from(tracker.as_ref()))
     }
 
     async fn index_statistics(&self, index_name: &str) -> Result<String> {
@@ -1421,6 +1369,83 @@ impl DatasetIndexExt for Dataset {
     }
 }
 
+async fn optimize_indices_impl(dataset: &mut Dataset, options: &OptimizeOptions) -> Result<()> {
+    let shared = Arc::new(dataset.clone());
+    let indices = dataset.load_indices().await?;
+
+    let indices_to_optimize = options
+        .index_names
+        .as_ref()
+        .map(|names| names.iter().collect::<HashSet<_>>());
+    let name_to_indices = indices
+        .iter()
+        .filter(|idx| {
+            indices_to_optimize
+                .as_ref()
+                .is_none_or(|names| names.contains(&idx.name))
+                && !is_system_index(idx)
+        })
+        .map(|idx| (idx.name.clone(), idx))
+        .into_group_map();
+
+    let mut new_indices = vec![];
+    let mut removed_indices = vec![];
+    for deltas in name_to_indices.values() {
+        // Scalar indices have no rebalance concept, so skip them entirely
+        // when every fragment is already covered and the caller hasn't
+        // asked for retrain or an explicit delta merge. Vector indices
+        // fall through and use a rebalance-aware no-op check inside
+        // merge_indices_with_unindexed_frags.
+        if !options.retrain
+            && options.num_indices_to_merge.is_none_or(|n| n == 0)
+            && index_group_is_scalar(dataset, deltas)
+            && index_group_has_no_unindexed(dataset, deltas)
+        {
+            continue;
+        }
+
+        let Some(res) = merge_indices(shared.clone(), deltas.as_slice(), options).await? else {
+            continue;
+        };
+
+        let last_idx = deltas.last().expect("Delta indices should not be empty");
+        let new_idx = IndexMetadata {
+            uuid: res.new_uuid,
+            name: last_idx.name.clone(), // Keep the same name
+            fields: last_idx.fields.clone(),
+            dataset_version: dataset.manifest.version,
+            fragment_bitmap: Some(res.new_fragment_bitmap),
+            index_details: Some(Arc::new(res.new_index_details)),
+            index_version: res.new_index_version,
+            created_at: Some(chrono::Utc::now()),
+            base_id: None, // New merged index file locates in the cloned dataset.
+            files: res.files,
+        };
+        removed_indices.extend(res.removed_indices.iter().map(|&idx| idx.clone()));
+        new_indices.push(new_idx);
+    }
+
+    if new_indices.is_empty() {
+        return Ok(());
+    }
+
+    let transaction = TransactionBuilder::new(
+        dataset.manifest.version,
+        Operation::CreateIndex {
+            new_indices,
+            removed_indices,
+        },
+    )
+    .transaction_properties(options.transaction_properties.clone())
+    .build();
+
+    dataset
+        .apply_commit(transaction, &Default::default(), &Default::default())
+        .await?;
+
+    Ok(())
+}
+
 fn index_group_is_scalar(dataset: &Dataset, deltas: &[&IndexMetadata]) -> bool {
     let Some(field_id) = deltas.first().and_then(|d| d.fields.first()) else {
         return false;
@@ -1521,6 +1546,7 @@ async fn index_statistics_scalar(
 ) -> Result<String> {
     let field_id = metadatas[0].fields[0];
     let field_path = ds.schema().field_path(field_id)?;
+    let is_vector = !index_group_is_scalar(ds, &metadatas.iter().collect::<Vec<_>>());
 
     let (indices_stats, index_uri, num_indices, updated_at) =
         collect_regular_indices_statistics(ds, metadatas, &field_path).await?;
@@ -1542,7 +1568,13 @@ async fn index_statistics_scalar(
         return migrate_and_recompute_index_statistics(ds, index_name).await;
     };
 
-    let stats = json!({
+    let partition_summary = if is_vector {
+        combined_partition_summary(&indices_stats)
+    } else {
+        None
+    };
+
+    let mut stats = json!({
         "index_type": index_type,
         "name": index_name,
         "num_indices": num_indices,
@@ -1556,10 +1588,40 @@ async fn index_statistics_scalar(
         "num_indexed_rows_per_delta": num_indexed_rows_per_delta,
         "updated_at_timestamp_ms": updated_at,
     });
+    if let Some(partition_summary) = partition_summary {
+        stats["partition_summary"] = serde_json::to_value(partition_summary)?;
+    }
 
     serialize_index_statistics(&stats)
 }
 
+/// Summarize the partitions of all deltas of an IVF index together.
+///
+/// Deltas share the IVF centroids, so partition `i` of every delta covers the
+/// same region and the sizes add up. Returns `None` when a delta does not list
+/// its partitions or the deltas disagree on the number of partitions.
+fn combined_partition_summary(indices_stats: &[serde_json::Value]) -> Option<IvfPartitionSummary> {
+    let mut sizes: Option<Vec<u64>> = None;
+    for stats in indices_stats {
+        let delta_sizes = stats
+            .get("partitions")?
+            .as_array()?
+            .iter()
+            .map(|partition| partition["size"].as_u64())
+            .collect::<Option<Vec<_>>>()?;
+        match &mut sizes {
+            None => sizes = Some(delta_sizes),
+            Some(sizes) if sizes.len() == delta_sizes.len() => {
+                for (size, delta_size) in sizes.iter_mut().zip(delta_sizes) {
+                    *size += delta_size;
+                }
+            }
+            Some(_) => return None,
+        }
+    }
+    sizes.map(|sizes| IvfPartitionSummary::from_sizes(&sizes))
+}
+
 async fn collect_regular_indices_statistics(
     ds: &Dataset,
     metadatas: Vec<IndexMetadata>,
@@ -1617,7 +1679,7 @@ async fn gather_fragment_statistics(
     {
         Ok(rows) => rows,
         Err(Error::Internal { message, .. })
-            if auto_migrate_corruption() && message.contains("trigger a single write") =>
+            if auto_migrate_corruption(ds) && message.contains("trigger a single write") =>
         {
             return Ok(None);
         }
@@ -1626,7 +1688,7 @@ async fn gather_fragment_statistics(
 
     let Some(num_indexed_fragments) = unique_indexed_fragment_count(&indexed_fragments_per_delta)
     else {
-        if auto_migrate_corruption() {
+        if auto_migrate_corruption(ds) {
             return Ok(None);
         }
         return Err(Error::internal(
@@ -2392,7 +2454,7 @@ impl DatasetIndexInternalExt for Dataset {
                 ))
             })?;
 
-        let mut field_names = Vec::new();
+        let mut field_paths = Vec::new();
         for field_id in source_index.fields.iter() {
             let source_field = source_dataset
                 .schema()
@@ -2403,25 +2465,27 @@ impl DatasetIndexInternalExt for Dataset {
                         field_id
                     ))
                 })?;
+            let field_path = source_dataset.schema().field_path(*field_id)?;
 
-            let target_field = self.schema().field(&source_field.name).ok_or_else(|| {
+            let target_field = self.schema().field(&field_path).ok_or_else(|| {
                 Error::index(format!(
                     "Field '{}' required by index '{}' not found in target dataset",
-                    source_field.name, index_name
+                    field_path, index_name
                 ))
             })?;
 
             if source_field.data_type() != target_field.data_type() {
                 return Err(Error::index(format!(
                     "Field '{}' has different types in source ({:?}) and target ({:?}) datasets",
-                    source_field.name,
+                    field_path,
                     source_field.data_type(),
                     target_field.data_type()
                 )));
             }
 
-            field_names.push(source_field.name.as_str());
+            field_paths.push(field_path);
         }
+        let field_names = field_paths.iter().map(String::as_str).collect::<Vec<_>>();
 
         if field_names.is_empty() {
             return Err(Error::index(format!(
@@ -3182,12 +3246,30 @@ mod tests {
 
         assert_eq!(dataset.load_indices().await.unwrap().len(), 2);
 
+        // A reader that opened the dataset before the drop keeps using the index.
+        let concurrent = dataset.clone();
+        let query = arrow_array::Float32Array::from(vec![0.5; 16]);
         dataset.drop_index(&idx_name).await.unwrap();
 
         assert_eq!(dataset.load_indices().await.unwrap().len(), 1);
+        for (ds, uses_index) in [(&concurrent, true), (&dataset, false)] {
+            let mut scanner = ds.scan();
+            scanner.nearest("vec", &query, 5).unwrap();
+            let plan = scanner.explain_plan(false).await.unwrap();
+            assert_eq!(plan.contains("ANNSubIndex"), uses_index, "{plan}");
+            assert_eq!(scanner.try_into_batch().await.unwrap().num_rows(), 5);
+        }
 
-        // Even though we didn't give the scalar index a name it still has an auto-generated one we can use
+        let err = dataset.drop_index("missing").await.unwrap_err();
+        assert!(matches!(err, Error::IndexNotFound { .. }), "{err}");
         let scalar_idx_name = &dataset.load_indices().await.unwrap()[0].name;
+        assert!(
+            err.to_string()
+                .contains(&format!("existing indices: [{scalar_idx_name}]")),
+            "{err}"
+        );
+
+        // Even though we didn't give the scalar index a name it still has an auto-generated one we can use
         dataset.drop_index(scalar_idx_name).await.unwrap();
 
         assert_eq!(dataset.load_indices().await.unwrap().len(), 0);
@@ -3852,6 +3934,157 @@ mod tests {
         assert_fts_ids(&dataset, FullTextSearchQuery::new("iota".to_owned()), &[5]).await;
     }
 
+    #[tokio::test]
+    async fn test_fts_delta_segments_score_like_rebuild() {
+        use lance_index::scalar::inverted::query::PhraseQuery;
+
+        const TEXTS: [&str; 6] = [
+            "the quick brown fox jumps over the lazy dog",
+            "quick brown foxes are quick",
+            "a lazy afternoon with a brown dog",
+            "lance stores vectors and text",
+            "full text search over lance datasets",
+            "the dog jumps over the quick fox",
+        ];
+
+        fn batch(schema: Arc<Schema>, ids: std::ops::Range<i32>) -> RecordBatch {
+            let texts = ids
+                .clone()
+                .map(|id| format!("{} {}", TEXTS[id as usize % TEXTS.len()], id));
+            RecordBatch::try_new(
+                schema,
+                vec![
+                    Arc::new(Int32Array::from_iter_values(ids)),
+                    Arc::new(StringArray::from_iter_values(texts)),
+                ],
+            )
+            .unwrap()
+        }
+
+        async fn search(dataset: &Dataset, query: FullTextSearchQuery) -> Vec<(i32, f32)> {
+            let result = dataset
+                .scan()
+                .project(&["id"])
+                .unwrap()
+                .full_text_search(query)
+                .unwrap()
+                .limit(Some(100), None)
+                .unwrap()
+                .try_into_batch()
+                .await
+                .unwrap();
+            let mut hits = result["id"]
+                .as_primitive::<Int32Type>()
+                .values()
+                .iter()
+                .copied()
+                .zip(
+                    result["_score"]
+                        .as_primitive::<Float32Type>()
+                        .values()
+                        .iter()
+                        .copied(),
+                )
+                .collect::<Vec<_>>();
+            hits.sort_by_key(|(id, _)| *id);
+            hits
+        }
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("text", DataType::Utf8, false),
+        ]));
+        let params = InvertedIndexParams::default().with_position(true);
+
+        // The base index, then two deltas stacked on top of it.
+        let dir = TempStrDir::default();
+        let mut dataset = Dataset::write(
+            RecordBatchIterator::new(vec![Ok(batch(schema.clone(), 0..40))], schema.clone()),
+            &dir,
+            None,
+        )
+        .await
+        .unwrap();
+        dataset
+            .create_index(&["text"], IndexType::Inverted, None, &params, true)
+            .await
+            .unwrap();
+        for ids in [40..60, 60..80] {
+            dataset
+                .append(
+                    RecordBatchIterator::new(vec![Ok(batch(schema.clone(), ids))], schema.clone()),
+                    None,
+                )
+                .await
+                .unwrap();
+            dataset
+                .optimize_indices(&OptimizeOptions::append())
+                .await
+                .unwrap();
+        }
+        let stats: serde_json::Value =
+            serde_json::from_str(&dataset.index_statistics("text_idx").await.unwrap()).unwrap();
+        assert_eq!(stats["num_indices"], 3);
+        assert_eq!(stats["num_unindexed_rows"], 0);
+
+        let rebuild_dir = TempStrDir::default();
+        let mut rebuilt = Dataset::write(
+            RecordBatchIterator::new(vec![Ok(batch(schema.clone(), 0..80))], schema.clone()),
+            &rebuild_dir,
+            None,
+        )
+        .await
+        .unwrap();
+        rebuilt
+            .create_index(&["text"], IndexType::Inverted, None, &params, true)
+            .await
+            .unwrap();
+
+        // Scores use document frequencies merged over all segments, so they
+        // match a single index built over the same documents.
+        let queries = || {
+            [
+                FullTextSearchQuery::new("quick".to_owned()),
+                FullTextSearchQuery::new("lazy dog".to_owned()),
+                FullTextSearchQuery::new("lance".to_owned()),
+                // Matches documents in the base and in both deltas.
+                FullTextSearchQuery::new_query(PhraseQuery::new("quick brown".to_owned()).into()),
+                FullTextSearchQuery::new_query(
+                    PhraseQuery::new("jumps over the".to_owned()).into(),
+                ),
+            ]
+        };
+        for (query, expected) in queries().into_iter().zip(queries()) {
+            let hits = search(&dataset, query).await;
+            let expected = search(&rebuilt, expected).await;
+            assert!(!hits.is_empty());
+            assert!(hits.iter().any(|(id, _)| *id < 40) && hits.iter().any(|(id, _)| *id >= 60));
+            assert_eq!(
+                hits.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+                expected.iter().map(|(id, _)| *id).collect::<Vec<_>>()
+            );
+            for ((id, score), (_, expected_score)) in hits.iter().zip(expected.iter()) {
+                assert!(
+                    (score - expected_score).abs() <= 1e-4 * expected_score.abs().max(1.0),
+                    "score of {} is {}, rebuilt index scores {}",
+                    id,
+                    score,
+                    expected_score
+                );
+            }
+        }
+
+        // Deleted documents are filtered out of the base and the deltas.
+        dataset.delete("id = 0 OR id = 66").await.unwrap();
+        let hits = search(
+            &dataset,
+            FullTextSearchQuery::new_query(PhraseQuery::new("quick brown".to_owned()).into()),
+        )
+        .await;
+        assert!(!hits.is_empty());
+        assert!(hits.iter().all(|(id, _)| *id != 0 && *id != 66));
+    }
+
     #[tokio::test]
     async fn test_create_index_too_small_for_pq() {
         let test_dir = TempStrDir::default();
@@ -4146,6 +4379,115 @@ mod tests {
         assert!(created_at <= after_index);
     }
 
+    #[tokio::test]
+    async fn test_index_statistics_partition_skew() {
+        // Fixed centroids with the rows clustered around the first two, so the
+        // partition sizes are known up front.
+        let centroids = [[10.0f32, 0.0], [-10.0, 0.0], [0.0, 10.0], [0.0, -10.0]];
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "vector",
+            DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float32, true)), 2),
+            false,
+        )]));
+        let batch = |num_rows: usize, centroid: [f32; 2]| {
+            let values = (0..num_rows)
+                .flat_map(|i| {
+                    let offset = (i % 10) as f32 * 0.01;
+                    [centroid[0] + offset, centroid[1] - offset]
+                })
+                .collect::<Float32Array>();
+            let vectors = FixedSizeListArray::try_new_from_values(values, 2).unwrap();
+            RecordBatch::try_new(schema.clone(), vec![Arc::new(vectors)]).unwrap()
+        };
+
+        let reader = RecordBatchIterator::new(
+            vec![Ok(batch(900, centroids[0])), Ok(batch(100, centroids[1]))],
+            schema.clone(),
+        );
+        let mut dataset = Dataset::write(reader, "memory://", None).await.unwrap();
+        let centroids_fsl = FixedSizeListArray::try_new_from_values(
+            centroids.into_iter().flatten().collect::<Float32Array>(),
+            2,
+        )
+        .unwrap();
+        let params = VectorIndexParams::with_ivf_flat_params(
+            DistanceType::L2,
+            IvfBuildParams::try_with_centroids(4, Arc::new(centroids_fsl)).unwrap(),
+        );
+        dataset
+            .create_index(
+                &["vector"],
+                IndexType::Vector,
+                Some("vec_idx".into()),
+                &params,
+                true,
+            )
+            .await
+            .unwrap();
+
+        // Index the appended rows in a delta of their own
+        let reader = RecordBatchIterator::new(vec![Ok(batch(100, centroids[1]))], schema.clone());
+        dataset.append(reader, None).await.unwrap();
+        dataset
+            .optimize_indices(&OptimizeOptions::append())
+            .await
+            .unwrap();
+
+        let stats: serde_json::Value =
+            serde_json::from_str(&dataset.index_statistics("vec_idx").await.unwrap()).unwrap();
+        let summary = |value: &serde_json::Value| {
+            serde_json::from_value::<IvfPartitionSummary>(value.clone()).unwrap()
+        };
+
+        let mut deltas = stats["indices"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|delta| summary(&delta["partition_summary"]))
+            .collect::<Vec<_>>();
+        deltas.sort_by_key(|delta| delta.num_rows);
+        assert_eq!(
+            deltas
+                .iter()
+                .map(|delta| (delta.num_rows, delta.max_size, delta.num_empty_partitions))
+                .collect::<Vec<_>>(),
+            vec![(100, 100, 3), (1000, 900, 2)]
+        );
+        for delta in stats["indices"].as_array().unwrap() {
+            let partitions = delta["partitions"].as_array().unwrap();
+            assert_eq!(partitions.len(), 4);
+            for partition in partitions {
+                assert_eq!(partition["centroid_norm"].as_f64().unwrap(), 10.0);
+            }
+        }
+
+        // Sizes over both deltas are [900, 200, 0, 0]
+        let combined = summary(&stats["partition_summary"]);
+        assert_eq!(combined.num_rows, 1100);
+        assert_eq!(combined.num_partitions, 4);
+        assert_eq!(combined.num_empty_partitions, 2);
+        assert_eq!(combined.empty_fraction, 0.5);
+        assert_eq!((combined.min_size, combined.max_size), (0, 900));
+        assert_eq!(combined.mean_size, 275.0);
+        assert!((combined.stddev_size - 136875f64.sqrt()).abs() < 1e-9);
+        assert_eq!(
+            (combined.p50_size, combined.p90_size, combined.p99_size),
+            (0, 900, 900)
+        );
+        assert!((combined.max_to_mean_ratio - 900.0 / 275.0).abs() < 1e-9);
+        let non_empty_buckets = combined
+            .histogram
+            .iter()
+            .filter(|bucket| bucket.num_partitions > 0)
+            .map(|bucket| (bucket.min_size, bucket.max_size, bucket.num_partitions))
+            .collect::<Vec<_>>();
+        assert_eq!(combined.histogram.len(), 10);
+        assert_eq!(
+            non_empty_buckets,
+            vec![(0, 90, 2), (182, 272, 1), (819, 900, 1)]
+        );
+    }
+
     #[tokio::test]
     async fn test_index_statistics_updated_at() {
         // Test that updated_at appears in index statistics
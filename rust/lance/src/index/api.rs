@@ -5,6 +5,7 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use datafusion::execution::SendableRecordBatchStream;
+use lance_core::utils::resources::ResourceTracker;
 use lance_index::{IndexParams, IndexType, PrewarmOptions, optimize::OptimizeOptions};
 use lance_table::format::IndexMetadata;
 use roaring::RoaringBitmap;
@@ -12,6 +13,30 @@ use uuid::Uuid;
 
 use crate::{Error, Result};
 
+/// The resources used to build an index.
+///
+/// Returned by [`DatasetIndexExt::optimize_indices`] and
+/// [`crate::index::CreateIndexBuilder::execute_with_metrics`].  The figures are
+/// approximate: memory is counted where large buffers are allocated (decoded
+/// batches, shuffle buffers, kmeans training data and FTS posting lists)
+/// rather than measured from the allocator.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IndexBuildMetrics {
+    /// The peak memory, in bytes, held in those buffers at any one time
+    pub peak_memory_bytes: u64,
+    /// The number of bytes written to temporary storage, such as shuffle files
+    pub temp_bytes: u64,
+}
+
+impl From<&ResourceTracker> for IndexBuildMetrics {
+    fn from(tracker: &ResourceTracker) -> Self {
+        Self {
+            peak_memory_bytes: tracker.peak_memory_bytes(),
+            temp_bytes: tracker.temp_bytes(),
+        }
+    }
+}
+
 /// A single physical segment of a logical index.
 ///
 /// Each segment is stored independently and will become one manifest entry when committed.
@@ -148,7 +173,12 @@ pub trait DatasetIndexExt {
 
     /// Drop indices by name.
     ///
-    /// Upon finish, a new dataset version is generated.
+    /// Upon finish, a new dataset version is generated. Older versions still
+    /// reference the index, so its files are kept until those versions are
+    /// removed by [`crate::dataset::cleanup::cleanup_old_versions`].
+    ///
+    /// Returns [`Error::IndexNotFound`] listing the existing index names if no
+    /// index is called `name`.
     async fn drop_index(&mut self, name: &str) -> Result<()>;
 
     /// Prewarm an index by name.
@@ -231,7 +261,9 @@ pub trait DatasetIndexExt {
     ) -> Result<Option<IndexMetadata>>;
 
     /// Optimize indices.
-    async fn optimize_indices(&mut self, options: &OptimizeOptions) -> Result<()>;
+    ///
+    /// Returns the resources used to update the indices.
+    async fn optimize_indices(&mut self, options: &OptimizeOptions) -> Result<IndexBuildMetrics>;
 
     /// Find an index with the given name and return its serialized statistics.
     async fn index_statistics(&self, index_name: &str) -> Result<String>;
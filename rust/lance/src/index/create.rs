@@ -8,7 +8,7 @@ use crate::{
         transaction::{Operation, TransactionBuilder},
     },
     index::{
-        DatasetIndexExt, DatasetIndexInternalExt, IntoIndexSegment,
+        DatasetIndexExt, DatasetIndexInternalExt, IndexBuildMetrics, IntoIndexSegment,
         build_index_metadata_from_segments,
         scalar::{build_bitmap_index_segment, build_scalar_index},
         vector::{
@@ -17,10 +17,16 @@ use crate::{
         },
         vector_index_details, vector_index_details_default,
     },
+    utils::cancellation::OperationLimits,
 };
 use futures::future::BoxFuture;
 use lance_core::datatypes::format_field_path;
-use lance_index::progress::{IndexBuildProgress, NoopIndexBuildProgress};
+use lance_core::utils::encryption::ENCRYPTION_KEY_ID_META_KEY;
+use lance_core::utils::resources::{ResourceTracker, with_resource_tracker};
+use lance_core::utils::tokio::with_compute_parallelism;
+use lance_index::progress::{
+    IndexBuildProgress, NoopIndexBuildProgress, TracingIndexBuildProgress,
+};
 use lance_index::{IndexParams, IndexType, scalar::CreatedIndex};
 use lance_index::{
     metrics::NoOpMetricsCollector,
@@ -28,6 +34,7 @@ use lance_index::{
 };
 use lance_table::format::{IndexMetadata, list_index_files_with_sizes};
 use std::{collections::HashMap, future::IntoFuture, sync::Arc};
+use tokio_util::sync::CancellationToken;
 use tracing::instrument;
 use uuid::Uuid;
 
@@ -58,6 +65,7 @@ pub struct CreateIndexBuilder<'a> {
     progress: Arc<dyn IndexBuildProgress>,
     /// Transaction properties to store with this commit.
     transaction_properties: Option<Arc<HashMap<String, String>>>,
+    cancellation_token: Option<CancellationToken>,
 }
 
 impl<'a> CreateIndexBuilder<'a> {
@@ -80,6 +88,7 @@ impl<'a> CreateIndexBuilder<'a> {
             preprocessed_data: None,
             progress: Arc::new(NoopIndexBuildProgress),
             transaction_properties: None,
+            cancellation_token: None,
         }
     }
 
@@ -116,6 +125,8 @@ impl<'a> CreateIndexBuilder<'a> {
         self
     }
 
+    /// Report the progress of the build to `p`. Stages are also emitted as
+    /// `tracing` events, whether or not a callback is set.
     pub fn progress(mut self, p: Arc<dyn IndexBuildProgress>) -> Self {
         self.progress = p;
         self
@@ -131,8 +142,31 @@ impl<'a> CreateIndexBuilder<'a> {
         self
     }
 
+    /// Stop the build with [`lance_core::Error::Cancelled`] when `token` is cancelled
+    ///
+    /// Nothing is committed once the build is cancelled.  Files written by the build so
+    /// far are left behind and removed by cleanup.
+    pub fn cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
     #[instrument(skip_all)]
     pub async fn execute_uncommitted(&mut self) -> Result<IndexMetadata> {
+        let limits = OperationLimits::new(None, self.cancellation_token.as_ref());
+        let build = async {
+            match self.dataset.session.compute_parallelism() {
+                Some(limit) => with_compute_parallelism(limit, self.build_uncommitted()).await,
+                None => self.build_uncommitted().await,
+            }
+        };
+        match limits {
+            Some(limits) => limits.run(build).await,
+            None => build.await,
+        }
+    }
+
+    async fn build_uncommitted(&mut self) -> Result<IndexMetadata> {
         if self.columns.len() != 1 {
             return Err(Error::index(
                 "Only support building index on 1 column at the moment".to_string(),
@@ -147,6 +181,14 @@ impl<'a> CreateIndexBuilder<'a> {
             )));
         };
         let field = *field_path.last().unwrap();
+        if field_path[0]
+            .metadata
+            .contains_key(ENCRYPTION_KEY_ID_META_KEY)
+        {
+            return Err(Error::not_supported(format!(
+                "CreateIndex: column '{column_input}' is encrypted and an index would store its values unencrypted"
+            )));
+        }
         // Reconstruct the column path with correct case from schema
         // Use quoted format for SQL parsing (special chars are quoted)
         let names: Vec<&str> = field_path.iter().map(|f| f.name.as_str()).collect();
@@ -216,6 +258,9 @@ impl<'a> CreateIndexBuilder<'a> {
             None => Uuid::new_v4(),
         };
         let mut output_index_uuid = index_id;
+        // Stage transitions are also emitted as tracing events.
+        let progress: Arc<dyn IndexBuildProgress> =
+            Arc::new(TracingIndexBuildProgress::new(self.progress.clone()));
         let created_index = match (self.index_type, self.params.index_name()) {
             (
                 IndexType::Bitmap
@@ -225,7 +270,8 @@ impl<'a> CreateIndexBuilder<'a> {
                 | IndexType::ZoneMap
                 | IndexType::BloomFilter
                 | IndexType::LabelList
-                | IndexType::RTree,
+                | IndexType::RTree
+                | IndexType::SparseVector,
                 LANCE_SCALAR_INDEX,
             ) => {
                 assert!(
@@ -280,7 +326,7 @@ impl<'a> CreateIndexBuilder<'a> {
                         column,
                         &index_id.to_string(),
                         fragments,
-                        self.progress.clone(),
+                        progress.clone(),
                     )
                     .await?
                 } else {
@@ -292,7 +338,7 @@ impl<'a> CreateIndexBuilder<'a> {
                         train,
                         self.fragments.clone(),
                         preprocesssed_data,
-                        self.progress.clone(),
+                        progress.clone(),
                     )
                     .await?
                 }
@@ -314,7 +360,7 @@ impl<'a> CreateIndexBuilder<'a> {
                     train,
                     self.fragments.clone(),
                     None,
-                    self.progress.clone(),
+                    progress.clone(),
                 )
                 .await?
             }
@@ -340,7 +386,7 @@ impl<'a> CreateIndexBuilder<'a> {
                     train,
                     self.fragments.clone(),
                     None,
-                    self.progress.clone(),
+                    progress.clone(),
                 )
                 .await?
             }
@@ -378,7 +424,7 @@ impl<'a> CreateIndexBuilder<'a> {
                             vec_params,
                             fri,
                             fragments,
-                            self.progress.clone(),
+                            progress.clone(),
                         ))
                         .await?;
                         output_index_uuid = segment_uuid;
@@ -391,7 +437,7 @@ impl<'a> CreateIndexBuilder<'a> {
                             &index_id.to_string(),
                             vec_params,
                             fri,
-                            self.progress.clone(),
+                            progress.clone(),
                         ))
                         .await?;
                     }
@@ -492,6 +538,14 @@ impl<'a> CreateIndexBuilder<'a> {
         })
     }
 
+    /// Build and commit the index like awaiting the builder does, and also return
+    /// the resources used by the build.
+    pub async fn execute_with_metrics(self) -> Result<(IndexMetadata, IndexBuildMetrics)> {
+        let tracker = Arc::new(ResourceTracker::default());
+        let index = with_resource_tracker(tracker.clone(), self.execute()).await?;
+        Ok((index, IndexBuildMetrics::from(tracker.as_ref())))
+    }
+
     #[instrument(skip_all)]
     async fn execute(mut self) -> Result<IndexMetadata> {
         let new_idx = self.execute_uncommitted().await?;
@@ -1945,6 +1999,67 @@ mod tests {
         );
     }
 
+    async fn build_ivf_pq_with_metrics(num_rows: u64) -> (Dataset, IndexBuildMetrics) {
+        let mut dataset = gen_batch()
+            .col(
+                "vector",
+                lance_datagen::array::rand_vec::<Float32Type>(lance_datagen::Dimension::from(16)),
+            )
+            .into_ram_dataset(FragmentCount::from(2), FragmentRowCount::from(num_rows / 2))
+            .await
+            .unwrap();
+        let params = VectorIndexParams::ivf_pq(4, 8, 4, MetricType::L2, 10);
+        let (_, metrics) = dataset
+            .create_index_builder(&["vector"], IndexType::Vector, &params)
+            .execute_with_metrics()
+            .await
+            .unwrap();
+        (dataset, metrics)
+    }
+
+    fn assert_close(a: u64, b: u64) {
+        assert!(a.max(b) <= a.min(b) + a.min(b) / 4, "{a} vs {b}");
+    }
+
+    #[tokio::test]
+    async fn test_index_build_metrics() {
+        let (mut dataset, small) = build_ivf_pq_with_metrics(400).await;
+        let (_, large) = build_ivf_pq_with_metrics(1600).await;
+        assert!(small.peak_memory_bytes > 0, "{small:?}");
+        assert!(small.temp_bytes > 0, "{small:?}");
+        assert!(
+            large.peak_memory_bytes > small.peak_memory_bytes,
+            "{small:?} {large:?}"
+        );
+        assert!(
+            large.temp_bytes > 2 * small.temp_bytes,
+            "{small:?} {large:?}"
+        );
+
+        // Repeated builds report about the same usage
+        let (_, again) = build_ivf_pq_with_metrics(400).await;
+        assert_close(small.peak_memory_bytes, again.peak_memory_bytes);
+        assert_close(small.temp_bytes, again.temp_bytes);
+
+        // Updating the index with new data shuffles that data
+        let reader = gen_batch()
+            .col(
+                "vector",
+                lance_datagen::array::rand_vec::<Float32Type>(lance_datagen::Dimension::from(16)),
+            )
+            .into_reader_rows(
+                lance_datagen::RowCount::from(200),
+                lance_datagen::BatchCount::from(1),
+            );
+        dataset.append(reader, None).await.unwrap();
+        let metrics = dataset
+            .optimize_indices(&OptimizeOptions::append())
+            .await
+            .unwrap();
+        assert!(metrics.peak_memory_bytes > 0, "{metrics:?}");
+        assert!(metrics.temp_bytes > 0, "{metrics:?}");
+    }
+
     #[tokio::test]
     async fn test_create_index_vector_commits_with_segment_metadata() {
         let tmpdir = TempStrDir::default();
@@ -1992,6 +2107,175 @@ mod tests {
         );
     }
 
+    type Recorded<T> = Arc<std::sync::Mutex<Vec<T>>>;
+
+    /// Records the spans entered during a build and the progress events
+    /// emitted by [`TracingIndexBuildProgress`].
+    #[derive(Default, Clone)]
+    struct BuildTraceRecorder {
+        /// `(span, ancestors from the parent up)` in creation order
+        spans: Recorded<(String, Vec<String>)>,
+        /// `(message, stage, enclosing spans from the innermost out)` in
+        /// emission order
+        events: Recorded<(String, String, Vec<String>)>,
+    }
+
+    #[derive(Default)]
+    struct EventFields {
+        message: String,
+        stage: String,
+    }
+
+    impl tracing::field::Visit for EventFields {
+        fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+            if field.name() == "stage" {
+                self.stage = value.to_string();
+            }
+        }
+
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            if field.name() == "message" {
+                self.message = format!("{value:?}");
+            }
+        }
+    }
+
+    impl<S> tracing_subscriber::Layer<S> for BuildTraceRecorder
+    where
+        S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    {
+        fn on_new_span(
+            &self,
+            _: &tracing::span::Attributes<'_>,
+            id: &tracing::span::Id,
+            ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let span = ctx.span(id).unwrap();
+            let ancestors = span
+                .scope()
+                .skip(1)
+                .map(|ancestor| ancestor.name().to_string())
+                .collect();
+            self.spans
+                .lock()
+                .unwrap()
+                .push((span.name().to_string(), ancestors));
+        }
+
+        fn on_event(
+            &self,
+            event: &tracing::Event<'_>,
+            ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let mut fields = EventFields::default();
+            event.record(&mut fields);
+            if fields.message.starts_with("index build stage") {
+                let spans = ctx
+                    .event_scope(event)
+                    .into_iter()
+                    .flatten()
+                    .map(|span| span.name().to_string())
+                    .collect();
+                self.events
+                    .lock()
+                    .unwrap()
+                    .push((fields.message, fields.stage, spans));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_index_vector_emits_build_trace() {
+        use lance_index::vector::pq::PQBuildParams;
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let recorder = BuildTraceRecorder::default();
+        let _guard =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(recorder.clone()));
+
+        let tmpdir = TempStrDir::default();
+        let reader = gen_batch()
+            .col(
+                "vector",
+                lance_datagen::array::rand_vec::<Float32Type>(lance_datagen::Dimension::from(16)),
+            )
+            .into_reader_rows(
+                lance_datagen::RowCount::from(256),
+                lance_datagen::BatchCount::from(2),
+            );
+        let mut dataset = Dataset::write(reader, tmpdir.as_str(), None).await.unwrap();
+        let params = VectorIndexParams::with_ivf_pq_params(
+            DistanceType::L2,
+            IvfBuildParams::new(2),
+            PQBuildParams::new(4, 8),
+        );
+        dataset
+            .create_index(&["vector"], IndexType::Vector, None, &params, false)
+            .await
+            .unwrap();
+
+        let spans = recorder.spans.lock().unwrap().clone();
+        let ancestors_of = |name: &str| {
+            spans
+                .iter()
+                .find(|(span, _)| span == name)
+                .unwrap_or_else(|| panic!("no {name} span in {spans:?}"))
+                .1
+                .clone()
+        };
+        for phase in [
+            "train_ivf",
+            "train_quantizer",
+            "shuffle",
+            "merge_partitions",
+        ] {
+            assert_eq!(ancestors_of(phase)[0], "build_vector_index");
+        }
+        for (span, phase) in [
+            ("sample_training_data", "train_ivf"),
+            ("kmeans", "train_ivf"),
+            ("fit_quantizer", "train_quantizer"),
+        ] {
+            assert!(
+                ancestors_of(span).iter().any(|ancestor| ancestor == phase),
+                "{span} should run during {phase}: {spans:?}"
+            );
+        }
+
+        // Each stage starts, reports progress where it has any, and completes
+        // inside its own span before the next one starts.
+        let events = recorder.events.lock().unwrap().clone();
+        let expected = [
+            ("started", "train_ivf"),
+            ("progress", "train_ivf"),
+            ("completed", "train_ivf"),
+            ("started", "train_quantizer"),
+            ("completed", "train_quantizer"),
+            ("started", "shuffle"),
+            ("progress", "shuffle"),
+            ("completed", "shuffle"),
+            ("started", "merge_partitions"),
+            ("progress", "merge_partitions"),
+            ("completed", "merge_partitions"),
+        ];
+        let actual = events
+            .iter()
+            .map(|(message, stage, _)| {
+                (
+                    message.trim_start_matches("index build stage "),
+                    stage.as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(actual, expected);
+        for (message, stage, spans) in &events {
+            assert!(
+                spans.contains(stage),
+                "'{message}' for {stage} emitted outside its span: {spans:?}"
+            );
+        }
+    }
+
     #[tokio::test]
     async fn test_create_index_ivf_rq_preserves_index_version_on_segment_commit_path() {
         let tmpdir = TempStrDir::default();
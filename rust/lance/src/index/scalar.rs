@@ -24,6 +24,7 @@ use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
 use futures::TryStreamExt;
 use itertools::Itertools;
 use lance_core::datatypes::Field;
+use lance_core::utils::resources::TrackedResources;
 use lance_core::utils::tracing::{IO_TYPE_OPEN_SCALAR, TRACE_IO_EVENTS};
 use lance_core::{Error, ROW_ADDR, ROW_ID, Result};
 use lance_datafusion::exec::LanceExecutionOptions;
@@ -49,7 +50,7 @@ use lance_index::scalar::{
 use lance_index::{IndexCriteria, IndexType};
 use lance_table::format::{Fragment, IndexMetadata};
 use log::info;
-use tracing::instrument;
+use tracing::{Instrument, info_span, instrument};
 
 // Log an update every TRAINING_UPDATE_FREQ million rows processed
 const TRAINING_UPDATE_FREQ: usize = 1000000;
@@ -245,6 +246,11 @@ impl IndexDetails {
         self.0.type_url.ends_with("InvertedIndexDetails")
     }
 
+    /// Returns true if the index supports sparse vector search
+    pub fn supports_sparse_search(&self) -> bool {
+        self.0.type_url.ends_with("SparseVectorIndexDetails")
+    }
+
     /// Returns the plugin for the index
     pub fn get_plugin(&self) -> Result<&dyn ScalarIndexPlugin> {
         SCALAR_INDEX_PLUGIN_REGISTRY.get_plugin_by_details(self.0.as_ref())
@@ -292,22 +298,28 @@ pub(super) async fn build_scalar_index(
     let training_request =
         plugin.new_training_request(params.params.as_deref().unwrap_or("{}"), &field)?;
 
-    progress.stage_start("load_data", None, "rows").await?;
-    let training_data = match preprocessed_data {
-        Some(preprocessed_data) => preprocessed_data,
-        None => {
-            load_training_data(
-                dataset,
-                column,
-                training_request.criteria(),
-                None,
-                train,
-                fragment_ids.clone(),
-            )
-            .await?
-        }
-    };
-    progress.stage_complete("load_data").await?;
+    let training_data = async {
+        progress.stage_start("load_data", None, "rows").await?;
+        let training_data = match preprocessed_data {
+            Some(preprocessed_data) => preprocessed_data,
+            None => {
+                load_training_data(
+                    dataset,
+                    column,
+                    training_request.criteria(),
+                    None,
+                    train,
+                    fragment_ids.clone(),
+                )
+                .await?
+            }
+        };
+        progress.stage_complete("load_data").await?;
+        Ok::<_, Error>(training_data)
+    }
+    .instrument(info_span!("load_data"))
+    .await?;
+    let training_data = track_batch_memory(training_data);
 
     let created_index = plugin
         .train_index(
@@ -317,11 +329,31 @@ pub(super) async fn build_scalar_index(
             fragment_ids,
             progress,
         )
+        .instrument(info_span!("train_index", index_type = %params.index_type))
         .await?;
 
     Ok(created_index)
 }
 
+/// Count the latest batch of `stream` toward the memory of the enclosing job
+///
+/// The stream may be consumed by a spawned task, so the trackers are captured here.
+fn track_batch_memory(stream: SendableRecordBatchStream) -> SendableRecordBatchStream {
+    let resources = TrackedResources::current();
+    if !resources.is_tracked() {
+        return stream;
+    }
+    let mut batch_memory = None;
+    let schema = stream.schema();
+    Box::pin(RecordBatchStreamAdapter::new(
+        schema,
+        stream.inspect_ok(move |batch| {
+            drop(batch_memory.take());
+            batch_memory = Some(resources.reserve_memory(batch.get_array_memory_size()));
+        }),
+    ))
+}
+
 /// Build a canonical bitmap index segment over a caller-selected fragment set.
 ///
 /// This is intentionally separate from `build_scalar_index(..., fragment_ids=Some(...))`.
@@ -580,6 +612,10 @@ pub fn index_matches_criteria(
             return Ok(false);
         }
 
+        if criteria.must_support_sparse_search && !index_details.supports_sparse_search() {
+            return Ok(false);
+        }
+
         // We should not use FTS / NGram indices for exact equality queries
         // (i.e. merge insert with a join on the indexed column)
         if criteria.must_support_exact_equality {
@@ -722,6 +758,7 @@ mod tests {
         let criteria = IndexCriteria {
             must_support_fts: false,
             must_support_exact_equality: false,
+            must_support_sparse_search: false,
             for_column: None,
             has_name: None,
         };
@@ -748,6 +785,7 @@ mod tests {
         let criteria = IndexCriteria {
             must_support_fts: false,
             must_support_exact_equality: false,
+            must_support_sparse_search: false,
             for_column: None,
             has_name: None,
         };
@@ -769,6 +807,7 @@ mod tests {
         let mut criteria = IndexCriteria {
             must_support_fts: false,
             must_support_exact_equality: false,
+            must_support_sparse_search: false,
             for_column: Some("mycol"),
             has_name: None,
         };
@@ -785,6 +824,7 @@ mod tests {
         let mut criteria = IndexCriteria {
             must_support_fts: false,
             must_support_exact_equality: false,
+            must_support_sparse_search: false,
             for_column: None,
             has_name: Some("btree_index"),
         };
@@ -807,6 +847,7 @@ mod tests {
         let mut criteria = IndexCriteria {
             must_support_fts: false,
             must_support_exact_equality: true,
+            must_support_sparse_search: false,
             for_column: None,
             has_name: None,
         };
@@ -828,6 +869,7 @@ mod tests {
         let mut criteria = IndexCriteria {
             must_support_fts: false,
             must_support_exact_equality: false,
+            must_support_sparse_search: false,
             for_column: None,
             has_name: None,
         };
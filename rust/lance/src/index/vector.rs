@@ -11,6 +11,7 @@ pub mod builder;
 pub(crate) mod details;
 pub mod ivf;
 pub mod pq;
+mod pretrained;
 pub mod utils;
 
 #[cfg(test)]
@@ -22,7 +23,6 @@ use builder::IvfIndexBuilder;
 use datafusion::physical_plan::SendableRecordBatchStream;
 use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
 use futures::stream;
-use lance_core::utils::tempfile::TempStdDir;
 use lance_file::previous::reader::FileReader as PreviousFileReader;
 use lance_index::frag_reuse::FragReuseIndex;
 use lance_index::metrics::NoOpMetricsCollector;
@@ -34,12 +34,11 @@ use lance_index::vector::flat::index::{FlatBinQuantizer, FlatIndex, FlatQuantize
 use lance_index::vector::hnsw::HNSW;
 use lance_index::vector::ivf::builder::recommended_num_partitions;
 use lance_index::vector::ivf::storage::IvfModel;
-use object_store::path::Path;
 
 use lance_arrow::FixedSizeListArrayExt;
 use lance_index::vector::pq::ProductQuantizer;
 use lance_index::vector::quantizer::QuantizationType;
-use lance_index::vector::v3::shuffler::{Shuffler, create_ivf_shuffler};
+use lance_index::vector::v3::shuffler::{Shuffler, create_scratch_ivf_shuffler};
 use lance_index::vector::v3::subindex::SubIndexType;
 use lance_index::vector::{
     VectorIndex,
@@ -545,26 +544,29 @@ async fn prepare_vector_segment_build(
 
     let num_rows = dataset.count_rows(None).await?;
     let index_type = params.index_type();
-    let num_partitions = ivf_params0.num_partitions.unwrap_or_else(|| {
-        recommended_num_partitions(
-            num_rows,
+    // Provided centroids fix the number of partitions.
+    let num_partitions = ivf_params0
+        .num_partitions
+        .or_else(|| {
             ivf_params0
-                .target_partition_size
-                .unwrap_or(index_type.target_partition_size()),
-        )
-    });
+                .centroids
+                .as_ref()
+                .map(|centroids| centroids.len())
+        })
+        .unwrap_or_else(|| {
+            recommended_num_partitions(
+                num_rows,
+                ivf_params0
+                    .target_partition_size
+                    .unwrap_or(index_type.target_partition_size()),
+            )
+        });
     let mut ivf_params = ivf_params0.clone();
     ivf_params.num_partitions = Some(num_partitions);
 
     let format_version = dataset_format_version(dataset);
-    let temp_dir = TempStdDir::default();
-    let temp_dir_path = Path::from_filesystem_path(&temp_dir)?;
-    let shuffler = create_ivf_shuffler(
-        temp_dir_path,
-        num_partitions,
-        format_version,
-        Some(progress),
-    );
+    let shuffler =
+        create_scratch_ivf_shuffler(&ivf_params, num_partitions, format_version, Some(progress))?;
 
     Ok((element_type, index_type, ivf_params, shuffler))
 }
@@ -582,6 +584,8 @@ pub(crate) async fn build_distributed_vector_index(
     fragment_ids: &[u32],
     progress: Arc<dyn IndexBuildProgress>,
 ) -> Result<Uuid> {
+    let params = pretrained::load_pretrained_models(dataset, column, params).await?;
+    let params = params.as_ref();
     let (element_type, index_type, ivf_params, shuffler) = prepare_vector_segment_build(
         dataset,
         column,
@@ -927,6 +931,8 @@ pub(crate) async fn build_vector_index(
     frag_reuse_index: Option<Arc<FragReuseIndex>>,
     progress: Arc<dyn IndexBuildProgress>,
 ) -> Result<()> {
+    let params = pretrained::load_pretrained_models(dataset, column, params).await?;
+    let params = params.as_ref();
     let (element_type, index_type, ivf_params, shuffler) = prepare_vector_segment_build(
         dataset,
         column,
@@ -1234,14 +1240,12 @@ pub(crate) async fn build_vector_index_incremental(
 
     let format_version = dataset_format_version(dataset);
 
-    let temp_dir = TempStdDir::default();
-    let temp_dir_path = Path::from_filesystem_path(&temp_dir)?;
-    let shuffler = create_ivf_shuffler(
-        temp_dir_path,
+    let shuffler = create_scratch_ivf_shuffler(
+        ivf_params,
         ivf_model.num_partitions(),
         format_version,
         Some(progress.clone()),
-    );
+    )?;
 
     let index_dir = dataset.indices_dir().join(uuid);
 
@@ -1838,6 +1842,7 @@ fn derive_ivf_params(ivf_model: &IvfModel) -> IvfBuildParams {
         target_partition_size: None,
         max_iters: 50, // Default
         centroids: ivf_model.centroids.clone().map(Arc::new),
+        centroids_file: None,
         #[allow(deprecated)]
         retrain: false, // Don't retrain since we have centroids
         sample_rate: 256, // Default
@@ -1848,6 +1853,8 @@ fn derive_ivf_params(ivf_model: &IvfModel) -> IvfBuildParams {
         precomputed_shuffle_buffers: None,
         shuffle_partition_batches: 1024 * 10, // Default
         shuffle_partition_concurrency: 2,     // Default
+        shuffle_memory_budget_bytes: None,
+        shuffle_temp_dir: None,
         storage_options: None,
     }
 }
@@ -1861,6 +1868,7 @@ fn derive_pq_params(pq_quantizer: &ProductQuantizer) -> PQBuildParams {
         max_iters: 50,   // Default
         kmeans_redos: 1, // Default
         codebook: Some(Arc::new(pq_quantizer.codebook.clone())),
+        codebook_file: None,
         sample_rate: 256, // Default
     }
 }
@@ -1892,6 +1900,8 @@ fn derive_hnsw_params(source_index: &dyn VectorIndex) -> HnswBuildParams {
         m: 20,
         ef_construction: 100,
         prefetch_distance: None,
+        m_max: None,
+        m_max0: None,
     };
 
     let Ok(stats) = source_index.statistics() else {
@@ -1919,12 +1929,22 @@ fn derive_hnsw_params(source_index: &dyn VectorIndex) -> HnswBuildParams {
             .and_then(|v| v.as_u64())
             .map(|v| v as usize)
             .unwrap_or(100);
+        let m_max = params
+            .get("m_max")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize);
+        let m_max0 = params
+            .get("m_max0")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize);
 
         return HnswBuildParams {
             max_level,
             m,
             ef_construction,
             prefetch_distance: None,
+            m_max,
+            m_max0,
         };
     }
 
@@ -3091,6 +3111,8 @@ mod tests {
             m: 24,
             ef_construction: 120,
             prefetch_distance: None,
+            m_max: None,
+            m_max0: None,
         };
         let pq_params = PQBuildParams {
             num_sub_vectors: 8,
@@ -3350,6 +3372,8 @@ mod tests {
             m: 16,
             ef_construction: 80,
             prefetch_distance: None,
+            m_max: None,
+            m_max0: None,
         };
         let sq_params = SQBuildParams {
             num_bits: 8,
@@ -23,8 +23,9 @@ use futures::{
 use lance_arrow::{FixedSizeListArrayExt, RecordBatchExt};
 use lance_core::ROW_ID;
 use lance_core::datatypes::Schema;
+use lance_core::utils::resources::reserve_memory;
 use lance_core::utils::tempfile::TempStdDir;
-use lance_core::utils::tokio::{get_num_compute_intensive_cpus, spawn_cpu};
+use lance_core::utils::tokio::{compute_parallelism, spawn_cpu};
 use lance_core::{Error, ROW_ID_FIELD, Result};
 use lance_encoding::version::LanceFileVersion;
 use lance_file::writer::{FileWriter, FileWriterOptions};
@@ -72,7 +73,7 @@ use lance_linalg::kernels::normalize_fsl;
 use log::info;
 use object_store::path::Path;
 use prost::Message;
-use tracing::{Level, instrument, span};
+use tracing::{Instrument, Level, info_span, instrument, span};
 
 use crate::Dataset;
 use crate::dataset::ProjectionRequest;
@@ -284,42 +285,63 @@ impl<S: IvfSubIndex + 'static, Q: Quantization + 'static> IvfIndexBuilder<S, Q>
 
     // build the index with the all data in the dataset,
     // return the number of indices merged
+    #[instrument(name = "build_vector_index", skip_all, fields(column = %self.column))]
     pub async fn build(&mut self) -> Result<usize> {
         let progress = self.progress.clone();
 
         // step 1. train IVF & quantizer
         let max_iters = self.ivf_params.as_ref().map(|p| p.max_iters as u64);
-        progress
-            .stage_start("train_ivf", max_iters, "iterations")
-            .await?;
-        self.with_ivf(self.load_or_build_ivf().boxed().await?);
-        progress.stage_complete("train_ivf").await?;
+        async {
+            progress
+                .stage_start("train_ivf", max_iters, "iterations")
+                .await?;
+            self.with_ivf(self.load_or_build_ivf().boxed().await?);
+            progress.stage_complete("train_ivf").await
+        }
+        .instrument(info_span!("train_ivf"))
+        .boxed()
+        .await?;
 
-        progress.stage_start("train_quantizer", None, "").await?;
-        self.with_quantizer(self.load_or_build_quantizer().await?);
-        progress.stage_complete("train_quantizer").await?;
+        async {
+            progress.stage_start("train_quantizer", None, "").await?;
+            self.with_quantizer(self.load_or_build_quantizer().await?);
+            progress.stage_complete("train_quantizer").await
+        }
+        .instrument(info_span!("train_quantizer"))
+        .boxed()
+        .await?;
 
         // step 2. shuffle the dataset
         if self.shuffle_reader.is_none() {
-            let num_rows = self.num_rows_to_shuffle().await?;
-            progress.stage_start("shuffle", num_rows, "rows").await?;
-            let input = self.shuffle_data_input.lock().unwrap().take();
-            if let Some(input) = input {
-                self.shuffle_data(Some(input)).boxed().await?;
-            } else {
-                self.shuffle_dataset().boxed().await?;
+            async {
+                let num_rows = self.num_rows_to_shuffle().await?;
+                progress.stage_start("shuffle", num_rows, "rows").await?;
+                let input = self.shuffle_data_input.lock().unwrap().take();
+                if let Some(input) = input {
+                    self.shuffle_data(Some(input)).boxed().await?;
+                } else {
+                    self.shuffle_dataset().boxed().await?;
+                }
+                progress.stage_complete("shuffle").await
             }
-            progress.stage_complete("shuffle").await?;
+            .instrument(info_span!("shuffle"))
+            .boxed()
+            .await?;
         }
 
         // step 3. build and merge partitions
-        let num_partitions = self.ivf.as_ref().map(|ivf| ivf.num_partitions() as u64);
-        progress
-            .stage_start("merge_partitions", num_partitions, "partitions")
-            .await?;
-        let build_idx_stream = self.build_partitions().boxed().await?;
-        self.merge_partitions(build_idx_stream).await?;
-        progress.stage_complete("merge_partitions").await?;
+        async {
+            let num_partitions = self.ivf.as_ref().map(|ivf| ivf.num_partitions() as u64);
+            progress
+                .stage_start("merge_partitions", num_partitions, "partitions")
+                .await?;
+            let build_idx_stream = self.build_partitions().boxed().await?;
+            self.merge_partitions(build_idx_stream).await?;
+            progress.stage_complete("merge_partitions").await
+        }
+        .instrument(info_span!("merge_partitions"))
+        .boxed()
+        .await?;
 
         Ok(self.merged_num)
     }
@@ -361,7 +383,7 @@ impl<S: IvfSubIndex + 'static, Q: Quantization + 'static> IvfIndexBuilder<S, Q>
 
         self.merge_partitions(
             stream::iter(build_iter)
-                .buffered(get_num_compute_intensive_cpus())
+                .buffered(compute_parallelism())
                 .boxed(),
         )
         .await?;
@@ -442,6 +464,21 @@ impl<S: IvfSubIndex + 'static, Q: Quantization + 'static> IvfIndexBuilder<S, Q>
                 "dataset not set before loading or building quantizer",
             ));
         };
+        // A quantizer given by its params needs only the vector dimension,
+        // so skip sampling training data.
+        if let Some(params) = self.quantizer_params.as_ref()
+            && params.is_pretrained()
+        {
+            let dim = utils::get_vector_dim(dataset.schema(), &self.column)?;
+            let no_data = FixedSizeListArray::new_null(
+                Arc::new(Field::new("item", DataType::Float32, true)),
+                dim as i32,
+                0,
+            );
+            info!("Pre-trained quantizer is provided, skip quantizer training");
+            return Q::build(&no_data, DistanceType::L2, params);
+        }
+
         let sample_size_hint = match &self.quantizer_params {
             Some(params) => params.sample_size(),
             None => 256 * 256, // here it must be retrain, let's just set sample size to the default value
@@ -458,6 +495,10 @@ impl<S: IvfSubIndex + 'static, Q: Quantization + 'static> IvfIndexBuilder<S, Q>
             sample_size_hint,
             self.fragment_filter.as_deref(),
         )
+        .instrument(info_span!(
+            "sample_training_data",
+            sample_size = sample_size_hint
+        ))
         .await?;
         info!(
             "Finished loading training data in {:02} seconds",
@@ -489,6 +530,7 @@ impl<S: IvfSubIndex + 'static, Q: Quantization + 'static> IvfIndexBuilder<S, Q>
         };
 
         info!("Start to train quantizer");
+        let _training_memory = reserve_memory(training_data.get_array_memory_size());
         let start = std::time::Instant::now();
         let quantizer = match &self.quantizer {
             Some(q) => q.clone(),
@@ -497,7 +539,8 @@ impl<S: IvfSubIndex + 'static, Q: Quantization + 'static> IvfIndexBuilder<S, Q>
                     .quantizer_params
                     .as_ref()
                     .ok_or(Error::invalid_input("quantizer build params not set"))?;
-                Q::build(&training_data, DistanceType::L2, quantizer_params)?
+                info_span!("fit_quantizer", num_vectors = training_data.len())
+                    .in_scope(|| Q::build(&training_data, DistanceType::L2, quantizer_params))?
             }
         };
         info!(
@@ -583,7 +626,7 @@ impl<S: IvfSubIndex + 'static, Q: Quantization + 'static> IvfIndexBuilder<S, Q>
                 log::info!("shuffle column {} over dataset", self.column);
                 let mut builder = dataset.scan();
                 builder
-                    .batch_readahead(get_num_compute_intensive_cpus())
+                    .batch_readahead(compute_parallelism())
                     .project(&[self.column.as_str()])?
                     .with_row_id();
 
@@ -740,7 +783,7 @@ impl<S: IvfSubIndex + 'static, Q: Quantization + 'static> IvfIndexBuilder<S, Q>
                     }
                 })
             })
-            .buffered(get_num_compute_intensive_cpus())
+            .buffered(compute_parallelism())
             .map(|x| x.unwrap())
             .peekable(),
         );
@@ -996,7 +1039,7 @@ impl<S: IvfSubIndex + 'static, Q: Quantization + 'static> IvfIndexBuilder<S, Q>
                     }
                 });
         Ok(stream::iter(build_iter)
-            .buffered(get_num_compute_intensive_cpus())
+            .buffered(compute_parallelism())
             .boxed())
     }
 
@@ -1571,7 +1614,7 @@ impl<S: IvfSubIndex + 'static, Q: Quantization + 'static> IvfIndexBuilder<S, Q>
         // Train split centroids in parallel (low memory — only samples).
         let trained_centroids = stream::iter(split_partitions.iter().copied())
             .map(|part_idx| async move { self.train_split_centroids::<T>(part_idx).await })
-            .buffered(get_num_compute_intensive_cpus())
+            .buffered(compute_parallelism())
             .try_collect::<Vec<_>>()
             .await?;
 
@@ -1662,7 +1705,7 @@ impl<S: IvfSubIndex + 'static, Q: Quantization + 'static> IvfIndexBuilder<S, Q>
                     let ivf_transformer = transformer.clone();
                     tokio::spawn(async move { ivf_transformer.transform(&batch?) })
                 })
-                .buffered(get_num_compute_intensive_cpus())
+                .buffered(compute_parallelism())
                 .map(|x| x.unwrap())
                 .peekable(),
         );
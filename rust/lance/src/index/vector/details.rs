@@ -17,7 +17,9 @@ use lance_file::reader::FileReaderOptions;
 use lance_index::pb::VectorIndexDetails;
 use lance_index::pb::VectorMetricType;
 use lance_index::pb::index::Implementation;
-use lance_index::pb::vector_index_details::{Compression, FlatCompression, rabit_quantization};
+use lance_index::pb::vector_index_details::{
+    Compression, ExternalModel, FlatCompression, rabit_quantization,
+};
 use lance_index::{INDEX_FILE_NAME, INDEX_METADATA_SCHEMA_KEY, pb};
 use lance_io::scheduler::{ScanScheduler, SchedulerConfig};
 use lance_io::traits::Reader;
@@ -52,6 +54,24 @@ struct VectorDetailsJson {
     compression: Option<CompressionDetailsJson>,
     #[serde(skip_serializing_if = "HashMap::is_empty")]
     runtime_hints: HashMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    external_centroids: Option<ExternalModelJson>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    external_codebook: Option<ExternalModelJson>,
+}
+
+#[derive(Serialize)]
+struct ExternalModelJson {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uri: Option<String>,
+}
+
+impl From<ExternalModel> for ExternalModelJson {
+    fn from(model: ExternalModel) -> Self {
+        Self {
+            uri: (!model.uri.is_empty()).then_some(model.uri),
+        }
+    }
 }
 
 #[derive(Serialize)]
@@ -60,6 +80,10 @@ struct HnswDetailsJson {
     construction_ef: u32,
     #[serde(skip_serializing_if = "is_zero")]
     max_level: u32,
+    #[serde(skip_serializing_if = "is_zero")]
+    max_neighbors: u32,
+    #[serde(skip_serializing_if = "is_zero")]
+    max_neighbors_level0: u32,
 }
 
 fn is_zero(v: &u32) -> bool {
@@ -95,6 +119,8 @@ pub fn vector_index_details(params: &VectorIndexParams) -> prost_types::Any {
     let mut hnsw_index_config = None;
     let mut compression = None;
     let mut runtime_hints: HashMap<String, String> = params.runtime_hints.clone();
+    let mut external_centroids = None;
+    let mut external_codebook = None;
 
     for stage in &params.stages {
         match stage {
@@ -102,6 +128,11 @@ pub fn vector_index_details(params: &VectorIndexParams) -> prost_types::Any {
                 if let Some(tps) = ivf.target_partition_size {
                     target_partition_size = tps as u64;
                 }
+                if (ivf.centroids.is_some() || ivf.centroids_file.is_some()) && !ivf.retrain {
+                    external_centroids = Some(ExternalModel {
+                        uri: ivf.centroids_file.clone().unwrap_or_default(),
+                    });
+                }
                 runtime_hints.insert("lance.ivf.max_iters".to_string(), ivf.max_iters.to_string());
                 runtime_hints.insert(
                     "lance.ivf.sample_rate".to_string(),
@@ -126,6 +157,11 @@ pub fn vector_index_details(params: &VectorIndexParams) -> prost_types::Any {
             }
             StageParams::PQ(pq) => {
                 compression = Some(Compression::Pq(pq.into()));
+                if pq.codebook.is_some() || pq.codebook_file.is_some() {
+                    external_codebook = Some(ExternalModel {
+                        uri: pq.codebook_file.clone().unwrap_or_default(),
+                    });
+                }
                 runtime_hints.insert("lance.pq.max_iters".to_string(), pq.max_iters.to_string());
                 runtime_hints.insert(
                     "lance.pq.sample_rate".to_string(),
@@ -162,6 +198,8 @@ pub fn vector_index_details(params: &VectorIndexParams) -> prost_types::Any {
         hnsw_index_config,
         compression,
         runtime_hints,
+        external_centroids,
+        external_codebook,
     };
     prost_types::Any::from_msg(&details).unwrap()
 }
@@ -258,6 +296,8 @@ pub fn vector_params_from_details(details: &prost_types::Any) -> Option<VectorIn
         m: h.max_connections as usize,
         ef_construction: h.construction_ef as usize,
         max_level: h.max_level as u16,
+        m_max: (h.max_neighbors > 0).then_some(h.max_neighbors as usize),
+        m_max0: (h.max_neighbors_level0 > 0).then_some(h.max_neighbors_level0 as usize),
         ..Default::default()
     });
 
@@ -345,6 +385,22 @@ pub fn metric_type_from_index_metadata(index: &IndexMetadata) -> Option<Distance
     Some(DistanceType::from(metric_enum))
 }
 
+/// Extract the HNSW `ef_construction` from index metadata without opening the index file.
+///
+/// Returns `None` for indices without an HNSW sub-index, and for legacy indices
+/// without details.
+pub fn hnsw_construction_ef_from_index_metadata(index: &IndexMetadata) -> Option<usize> {
+    let index_details = index.index_details.as_ref()?;
+    if index_details.value.is_empty() {
+        return None;
+    }
+
+    let details = index_details.to_msg::<VectorIndexDetails>().ok()?;
+    details
+        .hnsw_index_config
+        .map(|hnsw| hnsw.construction_ef as usize)
+}
+
 /// Returns true if the proto value represents a "truly empty" VectorIndexDetails
 /// (i.e., a legacy index that was created before we populated this field).
 fn is_empty_vector_details(details: &prost_types::Any) -> bool {
@@ -455,6 +511,8 @@ pub fn vector_details_as_json(details: &prost_types::Any) -> Result<String> {
         max_connections: h.max_connections,
         construction_ef: h.construction_ef,
         max_level: h.max_level,
+        max_neighbors: h.max_neighbors,
+        max_neighbors_level0: h.max_neighbors_level0,
     });
 
     let compression = d.compression.and_then(|c| match c {
@@ -488,6 +546,8 @@ pub fn vector_details_as_json(details: &prost_types::Any) -> Result<String> {
         hnsw,
         compression,
         runtime_hints: d.runtime_hints,
+        external_centroids: d.external_centroids.map(Into::into),
+        external_codebook: d.external_codebook.map(Into::into),
     };
 
     serde_json::to_string(&json).map_err(|e| Error::index(format!("Failed to serialize: {}", e)))
@@ -550,6 +610,8 @@ fn convert_legacy_proto_to_details(proto: &pb::Index) -> Result<prost_types::Any
         hnsw_index_config: None,
         compression,
         runtime_hints: Default::default(),
+        external_centroids: None,
+        external_codebook: None,
     };
     Ok(prost_types::Any::from_msg(&details).unwrap())
 }
@@ -618,11 +680,7 @@ async fn convert_v3_metadata_to_details(
             .and_then(|entries| entries.into_iter().next())
             .map(|s| serde_json::from_str::<HnswMetadata>(&s))
             .transpose()?
-            .map(|hnsw| HnswParameters {
-                max_connections: hnsw.params.m as u32,
-                construction_ef: hnsw.params.ef_construction as u32,
-                max_level: hnsw.params.max_level as u32,
-            })
+            .map(|hnsw| HnswParameters::from(&hnsw.params))
     } else {
         None
     };
@@ -689,6 +747,8 @@ async fn convert_v3_metadata_to_details(
         hnsw_index_config,
         compression,
         runtime_hints: Default::default(),
+        external_centroids: None,
+        external_codebook: None,
     };
     Ok(prost_types::Any::from_msg(&details).unwrap())
 }
@@ -738,6 +798,8 @@ mod tests {
             hnsw_index_config: hnsw,
             compression,
             runtime_hints: Default::default(),
+            external_centroids: None,
+            external_codebook: None,
         };
         prost_types::Any::from_msg(&details).unwrap()
     }
@@ -778,6 +840,7 @@ mod tests {
             max_connections: 20,
             construction_ef: 150,
             max_level: 7,
+            ..Default::default()
         });
         assert_eq!(
             derive_vector_index_type(&make_details(VectorMetricType::L2, hnsw, None)),
@@ -837,6 +900,7 @@ mod tests {
                 max_connections: 30,
                 construction_ef: 200,
                 max_level: 8,
+                ..Default::default()
             }),
             Some(Compression::Sq(ScalarQuantization { num_bits: 4 })),
         );
@@ -887,6 +951,8 @@ mod tests {
                 hnsw_index_config: None,
                 compression: None,
                 runtime_hints: Default::default(),
+                external_centroids: None,
+                external_codebook: None,
             };
             prost_types::Any::from_msg(&d).unwrap()
         };
@@ -1147,6 +1213,8 @@ mod tests {
             ef_construction: 150,
             max_level: 6,
             prefetch_distance: Some(4),
+            m_max: None,
+            m_max0: None,
         };
         let mut params = VectorIndexParams::with_ivf_hnsw_sq_params(
             DistanceType::L2,
@@ -1209,6 +1277,8 @@ mod tests {
             ef_construction: 100,
             max_level: 5,
             prefetch_distance: None,
+            m_max: None,
+            m_max0: None,
         };
         let params = VectorIndexParams::ivf_hnsw(DistanceType::L2, IvfBuildParams::default(), hnsw);
 
@@ -1293,6 +1363,8 @@ mod tests {
             ef_construction: 200,
             max_level: 5,
             prefetch_distance: Some(2),
+            m_max: Some(16),
+            m_max0: Some(48),
         };
         let pq = PQBuildParams {
             num_sub_vectors: 8,
@@ -1401,6 +1473,8 @@ mod tests {
                 assert_eq!(hnsw.m, 30);
                 assert_eq!(hnsw.ef_construction, 200);
                 assert_eq!(hnsw.max_level, 5);
+                assert_eq!(hnsw.m_max, Some(16));
+                assert_eq!(hnsw.m_max0, Some(48));
             }
             Combo::IvfHnswPq => {
                 let StageParams::Hnsw(hnsw) = &restored.stages[1] else {
@@ -46,6 +46,7 @@ use lance_core::{
     cache::{LanceCache, UnsizedCacheKey, WeakLanceCache},
     traits::DatasetTakeRows,
     utils::parse::parse_env_as_bool,
+    utils::resources::reserve_memory,
     utils::tracing::{IO_TYPE_LOAD_VECTOR_PART, TRACE_IO_EVENTS},
 };
 use lance_file::{
@@ -104,7 +105,7 @@ use prost::Message;
 use rand::rngs::SmallRng;
 use rand::{Rng, SeedableRng};
 use roaring::RoaringBitmap;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::{
     any::Any,
@@ -113,7 +114,7 @@ use std::{
     sync::Arc,
 };
 use tokio::sync::mpsc;
-use tracing::instrument;
+use tracing::{Instrument, info_span, instrument};
 use uuid::Uuid;
 
 pub mod builder;
@@ -950,6 +951,7 @@ async fn optimize_ivf_hnsw_indices<Q: Quantization>(
 #[derive(Serialize)]
 pub struct IvfIndexPartitionStatistics {
     size: u32,
+    centroid_norm: f32,
 }
 
 #[derive(Serialize)]
@@ -960,13 +962,156 @@ pub struct IvfIndexStatistics {
     metric_type: String,
     num_partitions: usize,
     sub_index: serde_json::Value,
-    partitions: Vec<IvfIndexPartitionStatistics>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    partitions: Option<Vec<IvfIndexPartitionStatistics>>,
+    partition_summary: IvfPartitionSummary,
     #[serde(skip_serializing_if = "Option::is_none")]
     centroids: Option<Vec<Vec<f32>>>,
     loss: Option<f64>,
     index_file_version: IndexFileVersion,
 }
 
+/// The number of buckets in [`IvfPartitionSummary::histogram`].
+const PARTITION_HISTOGRAM_BUCKETS: u64 = 10;
+
+/// How the rows of an IVF index are spread over its partitions.
+///
+/// Searches probe a fixed number of partitions, so when a few partitions hold
+/// most of the rows, queries landing elsewhere see little of the data and
+/// recall drops. A `max_to_mean_ratio` far above 1 or a large `empty_fraction`
+/// usually means the index should be retrained.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IvfPartitionSummary {
+    /// Total number of rows over all partitions
+    pub num_rows: u64,
+    pub num_partitions: usize,
+    /// Number of partitions without any rows
+    pub num_empty_partitions: usize,
+    /// `num_empty_partitions / num_partitions`
+    pub empty_fraction: f64,
+    pub min_size: u64,
+    pub max_size: u64,
+    pub mean_size: f64,
+    /// Population standard deviation of the partition sizes
+    pub stddev_size: f64,
+    pub p50_size: u64,
+    pub p90_size: u64,
+    pub p99_size: u64,
+    /// `max_size / mean_size`, 1 when the partitions are perfectly balanced
+    pub max_to_mean_ratio: f64,
+    /// Number of partitions per size range, in up to ten equal-width buckets
+    /// from `min_size` to `max_size`
+    pub histogram: Vec<IvfPartitionSizeBucket>,
+}
+
+/// A bucket of [`IvfPartitionSummary::histogram`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IvfPartitionSizeBucket {
+    /// Smallest partition size in the bucket, inclusive
+    pub min_size: u64,
+    /// Largest partition size in the bucket, inclusive
+    pub max_size: u64,
+    pub num_partitions: usize,
+}
+
+impl IvfPartitionSummary {
+    pub(crate) fn from_sizes(sizes: &[u64]) -> Self {
+        let mut sorted = sizes.to_vec();
+        sorted.sort_unstable();
+        let num_partitions = sorted.len();
+        let num_rows = sorted.iter().sum::<u64>();
+        let min_size = sorted.first().copied().unwrap_or_default();
+        let max_size = sorted.last().copied().unwrap_or_default();
+        let num_empty_partitions = sorted.iter().take_while(|size| **size == 0).count();
+
+        let (mean_size, stddev_size) = if num_partitions == 0 {
+            (0.0, 0.0)
+        } else {
+            let mean = num_rows as f64 / num_partitions as f64;
+            let variance = sorted
+                .iter()
+                .map(|size| (*size as f64 - mean).powi(2))
+                .sum::<f64>()
+                / num_partitions as f64;
+            (mean, variance.sqrt())
+        };
+        // Nearest-rank percentile
+        let percentile = |p: f64| {
+            let rank = (p * num_partitions as f64).ceil() as usize;
+            sorted
+                .get(rank.saturating_sub(1))
+                .copied()
+                .unwrap_or_default()
+        };
+
+        let mut histogram = Vec::new();
+        if num_partitions > 0 {
+            let width = (max_size - min_size + 1).div_ceil(PARTITION_HISTOGRAM_BUCKETS);
+            let num_buckets = (max_size - min_size + 1).div_ceil(width);
+            histogram = (0..num_buckets)
+                .map(|bucket| IvfPartitionSizeBucket {
+                    min_size: min_size + bucket * width,
+                    max_size: (min_size + (bucket + 1) * width - 1).min(max_size),
+                    num_partitions: 0,
+                })
+                .collect::<Vec<_>>();
+            for size in &sorted {
+                histogram[((size - min_size) / width) as usize].num_partitions += 1;
+            }
+        }
+
+        Self {
+            num_rows,
+            num_partitions,
+            num_empty_partitions,
+            empty_fraction: if num_partitions == 0 {
+                0.0
+            } else {
+                num_empty_partitions as f64 / num_partitions as f64
+            },
+            min_size,
+            max_size,
+            mean_size,
+            stddev_size,
+            p50_size: percentile(0.5),
+            p90_size: percentile(0.9),
+            p99_size: percentile(0.99),
+            max_to_mean_ratio: if num_rows == 0 {
+                0.0
+            } else {
+                max_size as f64 / mean_size
+            },
+            histogram,
+        }
+    }
+}
+
+/// Environment variable controlling whether vector index statistics list every
+/// partition with its size and centroid norm. Defaults to true; set it to a
+/// falsy value (e.g. `0`, `false`) to only report the
+/// [`IvfPartitionSummary`], which stays small for indexes with many partitions.
+pub const LANCE_INCLUDE_VECTOR_PARTITIONS_ENV: &str = "LANCE_INCLUDE_VECTOR_PARTITIONS";
+
+/// List the partitions for inclusion in index stats, honoring
+/// `LANCE_INCLUDE_VECTOR_PARTITIONS`.
+pub(crate) fn maybe_partitions_for_stats(
+    sizes: &[u64],
+    centroids: &FixedSizeListArray,
+) -> Result<Option<Vec<IvfIndexPartitionStatistics>>> {
+    if !parse_env_as_bool(LANCE_INCLUDE_VECTOR_PARTITIONS_ENV, true) {
+        return Ok(None);
+    }
+    let partitions = sizes
+        .iter()
+        .zip(centroids_to_vectors(centroids)?)
+        .map(|(size, centroid)| IvfIndexPartitionStatistics {
+            size: *size as u32,
+            centroid_norm: centroid.iter().map(|v| v * v).sum::<f32>().sqrt(),
+        })
+        .collect();
+    Ok(Some(partitions))
+}
+
 /// Environment variable controlling whether vector index statistics include
 /// the centroid vectors. When unset, centroids are still included for
 /// backward compatibility, but a one-time warning is logged. Set to a truthy
@@ -1091,13 +1236,12 @@ impl Index for IVFIndex {
     }
 
     fn statistics(&self) -> Result<serde_json::Value> {
-        let partitions_statistics = (0..self.ivf.num_partitions())
-            .map(|part_id| IvfIndexPartitionStatistics {
-                size: self.ivf.partition_size(part_id) as u32,
-            })
+        let partition_sizes = (0..self.ivf.num_partitions())
+            .map(|part_id| self.ivf.partition_size(part_id) as u64)
             .collect::<Vec<_>>();
-
-        let centroid_vecs = maybe_centroids_for_stats(self.ivf.centroids.as_ref().unwrap())?;
+        let centroids = self.ivf.centroids.as_ref().unwrap();
+        let partitions_statistics = maybe_partitions_for_stats(&partition_sizes, centroids)?;
+        let centroid_vecs = maybe_centroids_for_stats(centroids)?;
 
         Ok(serde_json::to_value(IvfIndexStatistics {
             index_type: self.index_type().to_string(),
@@ -1107,6 +1251,7 @@ impl Index for IVFIndex {
             num_partitions: self.ivf.num_partitions(),
             sub_index: self.sub_index.statistics()?,
             partitions: partitions_statistics,
+            partition_summary: IvfPartitionSummary::from_sizes(&partition_sizes),
             centroids: centroid_vecs,
             loss: self.ivf.loss(),
             index_file_version: IndexFileVersion::Legacy,
@@ -1474,8 +1619,12 @@ pub async fn build_ivf_model(
         "Loading training data for IVF. Sample size: {}",
         sample_size_hint
     );
-    let training_data =
-        maybe_sample_training_data(dataset, column, sample_size_hint, fragment_ids).await?;
+    let training_data = maybe_sample_training_data(dataset, column, sample_size_hint, fragment_ids)
+        .instrument(info_span!(
+            "sample_training_data",
+            sample_size = sample_size_hint
+        ))
+        .await?;
     info!(
         "Finished loading training data in {:02} seconds",
         start.elapsed().as_secs_f32()
@@ -1500,6 +1649,8 @@ pub async fn build_ivf_model(
     let training_data = filter_finite_training_data(training_data)?;
 
     info!("Start to train IVF model");
+    // The training data is the bulk of the kmeans working set
+    let _training_memory = reserve_memory(training_data.get_array_memory_size());
     let start = std::time::Instant::now();
     let ivf = train_ivf_model(centroids, &training_data, mt, params, progress).await?;
     info!(
@@ -2431,13 +2582,16 @@ where
     let (progress_tx, mut progress_rx) = mpsc::unbounded_channel::<u64>();
     let progress_worker = {
         let progress = progress.clone();
-        tokio::spawn(async move {
-            while let Some(iter) = progress_rx.recv().await {
-                if let Err(e) = progress.stage_progress("train_ivf", iter).await {
-                    warn!("Progress callback error during train_ivf: {e}");
+        tokio::spawn(
+            async move {
+                while let Some(iter) = progress_rx.recv().await {
+                    if let Err(e) = progress.stage_progress("train_ivf", iter).await {
+                        warn!("Progress callback error during train_ivf: {e}");
+                    }
                 }
             }
-        })
+            .in_current_span(),
+        )
     };
 
     let on_progress: Arc<dyn Fn(u32, u32) + Send + Sync> = {
@@ -3766,13 +3920,16 @@ async fn train_streaming_coreset_ivf_model(
     let (progress_tx, mut progress_rx) = mpsc::unbounded_channel::<u64>();
     let progress_worker = {
         let progress = progress.clone();
-        tokio::spawn(async move {
-            while let Some(iter) = progress_rx.recv().await {
-                if let Err(e) = progress.stage_progress("train_ivf", iter).await {
-                    warn!("Progress callback error during train_ivf: {e}");
+        tokio::spawn(
+            async move {
+                while let Some(iter) = progress_rx.recv().await {
+                    if let Err(e) = progress.stage_progress("train_ivf", iter).await {
+                        warn!("Progress callback error during train_ivf: {e}");
+                    }
                 }
             }
-        })
+            .in_current_span(),
+        )
     };
 
     let on_progress: Arc<dyn Fn(u32, u32) + Send + Sync> = {
@@ -3974,13 +4131,16 @@ async fn train_streaming_ivf_model(
     let (progress_tx, mut progress_rx) = mpsc::unbounded_channel::<u64>();
     let progress_worker = {
         let progress = progress.clone();
-        tokio::spawn(async move {
-            while let Some(iter) = progress_rx.recv().await {
-                if let Err(e) = progress.stage_progress("train_ivf", iter).await {
-                    warn!("Progress callback error during train_ivf: {e}");
+        tokio::spawn(
+            async move {
+                while let Some(iter) = progress_rx.recv().await {
+                    if let Err(e) = progress.stage_progress("train_ivf", iter).await {
+                        warn!("Progress callback error during train_ivf: {e}");
+                    }
                 }
             }
-        })
+            .in_current_span(),
+        )
     };
 
     let on_progress: Arc<dyn Fn(u32, u32) + Send + Sync> = {
@@ -4161,6 +4321,7 @@ mod tests {
     use lance_index::VECTOR_INDEX_VERSION;
     use lance_index::metrics::NoOpMetricsCollector;
     use lance_index::vector::sq::builder::SQBuildParams;
+    use lance_index::vector::{DIST_COL, NanHandling};
     use lance_linalg::distance::l2_distance_batch;
     use lance_testing::datagen::{
         generate_random_array, generate_random_array_with_range, generate_random_array_with_seed,
@@ -4270,7 +4431,8 @@ mod tests {
             metric_type: "l2".to_string(),
             num_partitions: 0,
             sub_index: serde_json::Value::Null,
-            partitions: vec![],
+            partitions: None,
+            partition_summary: IvfPartitionSummary::from_sizes(&[]),
             centroids: None,
             loss: None,
             index_file_version: IndexFileVersion::V3,
@@ -4286,6 +4448,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_partition_summary_balanced_and_empty() {
+        let empty = IvfPartitionSummary::from_sizes(&[]);
+        assert_eq!((empty.num_partitions, empty.num_rows), (0, 0));
+        assert_eq!(empty.empty_fraction, 0.0);
+        assert_eq!(empty.max_to_mean_ratio, 0.0);
+        assert!(empty.histogram.is_empty());
+
+        let balanced = IvfPartitionSummary::from_sizes(&[5, 5, 5]);
+        assert_eq!(balanced.num_rows, 15);
+        assert_eq!(balanced.stddev_size, 0.0);
+        assert_eq!(balanced.max_to_mean_ratio, 1.0);
+        assert_eq!(
+            balanced.histogram,
+            vec![IvfPartitionSizeBucket {
+                min_size: 5,
+                max_size: 5,
+                num_partitions: 3,
+            }]
+        );
+    }
+
     /// This goal of this function is to generate data that behaves in a very deterministic way so that
     /// we can evaluate the correctness of an IVF_PQ implementation.  Currently it is restricted to the
     /// L2 distance metric.
@@ -5052,6 +5236,138 @@ mod tests {
         check_index(&dataset, num_non_null, dims).await;
     }
 
+    #[tokio::test]
+    async fn test_null_and_nan_vectors() {
+        // Rows 0..100 are normal, 100..120 are null and 120..140 contain a NaN.
+        let dims = 8;
+        let mut values = Vec::new();
+        for i in 0..140 {
+            let mut vector = vec![i as f32; dims];
+            if i >= 120 {
+                vector[0] = f32::NAN;
+            }
+            values.extend(vector);
+        }
+        let vectors = FixedSizeListArray::try_new(
+            Arc::new(Field::new("item", DataType::Float32, true)),
+            dims as i32,
+            Arc::new(Float32Array::from(values)),
+            Some(NullBuffer::from_iter(
+                (0..140).map(|i| !(100..120).contains(&i)),
+            )),
+        )
+        .unwrap();
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "vec",
+            vectors.data_type().clone(),
+            true,
+        )]));
+        let data = RecordBatch::try_new(schema.clone(), vec![Arc::new(vectors)]).unwrap();
+        let mut dataset = InsertBuilder::new("memory://")
+            .execute(vec![data])
+            .await
+            .unwrap();
+
+        // Row ids and distances of a search with `k = 140`.
+        async fn search(
+            dataset: &Dataset,
+            nan_handling: NanHandling,
+            upper_bound: Option<f32>,
+        ) -> (Vec<u64>, Vec<f32>) {
+            let query = Float32Array::from(vec![0.0; 8]);
+            let results = dataset
+                .scan()
+                .nearest("vec", &query, 140)
+                .unwrap()
+                .distance_range(None, upper_bound)
+                .nprobes(2)
+                .nan_handling(nan_handling)
+                .with_row_id()
+                .try_into_batch()
+                .await
+                .unwrap();
+            let row_ids = results[ROW_ID].as_primitive::<UInt64Type>().values();
+            let distances = results[DIST_COL].as_primitive::<Float32Type>().values();
+            (row_ids.to_vec(), distances.to_vec())
+        }
+        let normal_rows = (0..100).collect::<Vec<u64>>();
+
+        // Flat search
+        let (row_ids, distances) = search(&dataset, NanHandling::Drop, None).await;
+        assert_eq!(row_ids, normal_rows);
+        assert!(distances.iter().all(|d| !d.is_nan()));
+
+        let (row_ids, distances) = search(&dataset, NanHandling::SortLast, None).await;
+        assert_eq!(row_ids[..100], normal_rows);
+        assert_eq!(row_ids[100..], (120..140).collect::<Vec<u64>>());
+        assert!(distances[100..].iter().all(|d| d.is_nan()));
+
+        let (row_ids, _) = search(&dataset, NanHandling::SortLast, Some(f32::MAX)).await;
+        assert_eq!(row_ids, normal_rows);
+
+        // Query vectors containing NaN are rejected unless allowed, and then
+        // every distance is NaN.
+        let query = Float32Array::from(vec![f32::NAN; 8]);
+        let err = dataset
+            .scan()
+            .nearest("vec", &query, 10)
+            .unwrap()
+            .try_into_batch()
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("contains NaN"), "{}", err);
+        let results = dataset
+            .scan()
+            .nearest("vec", &query, 10)
+            .unwrap()
+            .allow_nan_query(true)
+            .try_into_batch()
+            .await
+            .unwrap();
+        assert_eq!(results.num_rows(), 0);
+
+        // Null and NaN vectors are left out of the index, so they never appear
+        // in indexed results.
+        dataset
+            .create_index(
+                &["vec"],
+                IndexType::Vector,
+                None,
+                &VectorIndexParams::ivf_flat(2, MetricType::L2),
+                false,
+            )
+            .await
+            .unwrap();
+        for nan_handling in [NanHandling::Drop, NanHandling::SortLast] {
+            let (row_ids, _) = search(&dataset, nan_handling, None).await;
+            assert_eq!(row_ids, normal_rows);
+        }
+
+        // Unindexed rows are searched flat and merged with the indexed ones.
+        let mut vector = vec![1.0; 8];
+        vector[3] = f32::NAN;
+        let vectors =
+            FixedSizeListArray::try_new_from_values(Float32Array::from(vector), 8).unwrap();
+        let data = RecordBatch::try_new(schema, vec![Arc::new(vectors)]).unwrap();
+        let dataset = InsertBuilder::new(Arc::new(dataset))
+            .with_params(&WriteParams {
+                mode: WriteMode::Append,
+                ..Default::default()
+            })
+            .execute(vec![data])
+            .await
+            .unwrap();
+        let (row_ids, _) = search(&dataset, NanHandling::Drop, None).await;
+        assert_eq!(row_ids, normal_rows);
+        let (row_ids, distances) = search(&dataset, NanHandling::SortLast, None).await;
+        assert_eq!(row_ids[..100], normal_rows);
+        assert_eq!(
+            row_ids[100..],
+            [u64::from(RowAddress::new_from_parts(1, 0))]
+        );
+        assert!(distances[100].is_nan());
+    }
+
     #[tokio::test]
     async fn test_create_ivf_pq_cosine() {
         let test_dir = TempStrDir::default();
@@ -74,7 +74,9 @@ use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
 use tracing::{info, instrument};
 
-use super::{IvfIndexPartitionStatistics, IvfIndexStatistics, maybe_centroids_for_stats};
+use super::{
+    IvfIndexStatistics, IvfPartitionSummary, maybe_centroids_for_stats, maybe_partitions_for_stats,
+};
 
 /// Serializable state of an IVF index, sufficient to reconstruct the index
 /// without re-reading global buffers from object storage.
@@ -1097,13 +1099,12 @@ impl<S: IvfSubIndex + 'static, Q: Quantization + 'static> Index for IVFIndex<S,
     }
 
     fn statistics(&self) -> Result<serde_json::Value> {
-        let partitions_statistics = (0..self.ivf.num_partitions())
-            .map(|part_id| IvfIndexPartitionStatistics {
-                size: self.storage.partition_size(part_id) as u32,
-            })
+        let partition_sizes = (0..self.ivf.num_partitions())
+            .map(|part_id| self.storage.partition_size(part_id) as u64)
             .collect::<Vec<_>>();
-
-        let centroid_vecs = maybe_centroids_for_stats(self.ivf.centroids.as_ref().unwrap())?;
+        let centroids = self.ivf.centroids.as_ref().unwrap();
+        let partitions_statistics = maybe_partitions_for_stats(&partition_sizes, centroids)?;
+        let centroid_vecs = maybe_centroids_for_stats(centroids)?;
 
         let (sub_index_type, quantization_type) = self.sub_index_type();
         let index_type = index_type_string(sub_index_type, quantization_type);
@@ -1155,6 +1156,7 @@ impl<S: IvfSubIndex + 'static, Q: Quantization + 'static> Index for IVFIndex<S,
             num_partitions: self.ivf.num_partitions(),
             sub_index: serde_json::Value::Object(sub_index_stats),
             partitions: partitions_statistics,
+            partition_summary: IvfPartitionSummary::from_sizes(&partition_sizes),
             centroids: centroid_vecs,
             loss: self.ivf.loss(),
             index_file_version: IndexFileVersion::V3,
@@ -1250,6 +1252,67 @@ impl<S: IvfSubIndex + 'static, Q: Quantization + 'static> VectorIndex for IVFInd
         Ok(batch)
     }
 
+    #[instrument(level = "debug", skip(self, queries, pre_filter, metrics))]
+    async fn search_in_partition_batch(
+        &self,
+        partition_id: usize,
+        queries: &[Query],
+        pre_filter: Arc<dyn PreFilter>,
+        metrics: &dyn MetricsCollector,
+    ) -> Result<Vec<RecordBatch>> {
+        // Load the partition once and search it with every query
+        let part_entry = self.load_partition(partition_id, true, metrics).await?;
+        pre_filter.wait_for_ready().await?;
+
+        let residual_centroid = if self.use_residual_scratch {
+            Some(self.ivf.centroid(partition_id).ok_or_else(|| {
+                Error::index(format!("partition centroid {partition_id} does not exist"))
+            })?)
+        } else {
+            None
+        };
+        let queries = queries
+            .iter()
+            .map(|query| self.preprocess_query(partition_id, query))
+            .collect::<Result<Vec<_>>>()?;
+        let scratch_pool = self.scratch_pool.clone();
+        let (batches, local_metrics) = spawn_cpu(move || {
+            let local_metrics = LocalMetricsCollector::default();
+            let part = part_entry
+                .as_any()
+                .downcast_ref::<PartitionEntry<S, Q>>()
+                .ok_or(Error::internal(
+                    "failed to downcast partition entry".to_string(),
+                ))?;
+            let residual = residual_centroid.as_deref().map(QueryResidual::Centroid);
+            let batches = scratch_pool.with_scratch(|scratch| {
+                queries
+                    .into_iter()
+                    .map(|query| {
+                        let param = (&query).into();
+                        let k = query.k * query.refine_factor.unwrap_or(1) as usize;
+                        part.index.search_with_scratch(
+                            query.key,
+                            k,
+                            param,
+                            &part.storage,
+                            pre_filter.clone(),
+                            &local_metrics,
+                            residual,
+                            scratch,
+                        )
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })?;
+            Result::Ok((batches, local_metrics))
+        })
+        .await?;
+
+        local_metrics.dump_into(metrics);
+
+        Ok(batches)
+    }
+
     async fn prepare_partition_search(
         &self,
         partition_id: usize,
@@ -1693,7 +1756,7 @@ mod tests {
         index::vector::IndexFileVersion,
     };
     use lance_core::cache::LanceCache;
-    use lance_core::utils::tempfile::TempStrDir;
+    use lance_core::utils::tempfile::{TempStdDir, TempStrDir};
     use lance_core::{ROW_ID, Result};
     use lance_encoding::decoder::DecoderPlugins;
     use lance_file::reader::{FileReader, FileReaderOptions};
@@ -2889,6 +2952,176 @@ mod tests {
         );
     }
 
+    async fn write_model(uri: &str, column: &str, model: FixedSizeListArray) {
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            column,
+            model.data_type().clone(),
+            false,
+        )]));
+        let batch = RecordBatch::try_new(schema.clone(), vec![Arc::new(model)]).unwrap();
+        let batches = RecordBatchIterator::new(vec![Ok(batch)], schema);
+        Dataset::write(batches, uri, None).await.unwrap();
+    }
+
+    async fn recall_at_k(
+        dataset: &Dataset,
+        queries: &FixedSizeListArray,
+        k: usize,
+        nprobes: usize,
+    ) -> f32 {
+        let mut hits = 0;
+        for query in queries.iter().flatten() {
+            let gt = ground_truth(dataset, "vector", query.as_ref(), k, DistanceType::L2).await;
+            let results = dataset
+                .scan()
+                .nearest("vector", query.as_ref(), k)
+                .unwrap()
+                .minimum_nprobes(nprobes)
+                .with_row_id()
+                .try_into_batch()
+                .await
+                .unwrap();
+            hits += results[ROW_ID]
+                .as_primitive::<UInt64Type>()
+                .values()
+                .iter()
+                .filter(|row_id| gt.contains(row_id))
+                .count();
+        }
+        hits as f32 / (queries.len() * k) as f32
+    }
+
+    #[tokio::test]
+    async fn test_build_from_external_ivf_pq() {
+        const INDEX_NAME: &str = "vector_idx";
+        const NLIST: usize = 4;
+        const NUM_SUB_VECTORS: usize = 4;
+        const NUM_BITS: usize = 8;
+
+        let test_dir = TempStrDir::default();
+        let uri = format!("{}/ds", test_dir.as_str());
+        let (mut dataset, vectors) = generate_test_dataset::<Float32Type>(&uri, 0.0..1.0).await;
+        let queries = vectors.slice(0, 10);
+
+        // Train the models with a regular build, and export them as if they
+        // were trained elsewhere.
+        let params =
+            VectorIndexParams::ivf_pq(NLIST, NUM_BITS as u8, NUM_SUB_VECTORS, DistanceType::L2, 50);
+        dataset
+            .create_index(
+                &["vector"],
+                IndexType::Vector,
+                Some(INDEX_NAME.into()),
+                &params,
+                true,
+            )
+            .await
+            .unwrap();
+        let trained_recall = recall_at_k(&dataset, &queries, 10, NLIST).await;
+
+        let index_meta = dataset.load_indices().await.unwrap()[0].clone();
+        let index = dataset
+            .open_vector_index(
+                "vector",
+                &index_meta.uuid.to_string(),
+                &NoOpMetricsCollector,
+            )
+            .await
+            .unwrap();
+        let centroids = index.ivf_model().centroids.clone().unwrap();
+        let pq: lance_index::vector::pq::ProductQuantizer = index.quantizer().try_into().unwrap();
+        let centroids_uri = format!("{}/centroids", test_dir.as_str());
+        let codebook_uri = format!("{}/codebook", test_dir.as_str());
+        write_model(&centroids_uri, "centroids", centroids).await;
+        write_model(&codebook_uri, "codebook", pq.codebook.clone()).await;
+
+        let ivf_params = IvfBuildParams::with_centroids_file(NLIST, centroids_uri.clone());
+        let pq_params =
+            PQBuildParams::with_codebook_file(NUM_SUB_VECTORS, NUM_BITS, codebook_uri.clone());
+        let params = VectorIndexParams::with_ivf_pq_params(DistanceType::L2, ivf_params, pq_params);
+        let progress = Arc::new(RecordingProgress::default());
+        let index_meta = dataset
+            .create_index_builder(&["vector"], IndexType::Vector, &params)
+            .name(INDEX_NAME.to_string())
+            .replace(true)
+            .progress(progress.clone())
+            .await
+            .unwrap();
+        assert!(
+            !progress
+                .recorded_events()
+                .iter()
+                .any(|(kind, stage, _)| kind == "progress" && stage == "train_ivf"),
+            "no IVF training expected: {:?}",
+            progress.recorded_events()
+        );
+
+        let details = index_meta
+            .index_details
+            .as_ref()
+            .unwrap()
+            .to_msg::<lance_index::pb::VectorIndexDetails>()
+            .unwrap();
+        assert_eq!(details.external_centroids.unwrap().uri, centroids_uri);
+        assert_eq!(details.external_codebook.unwrap().uri, codebook_uri);
+
+        // The same models give the same codes, so recall matches.
+        let external_recall = recall_at_k(&dataset, &queries, 10, NLIST).await;
+        assert_ge!(
+            external_recall,
+            trained_recall - 0.05,
+            "trained: {trained_recall}, external: {external_recall}"
+        );
+    }
+
+    #[rstest]
+    #[case::centroids(16, DIM)]
+    #[case::codebook(DIM, 16)]
+    #[tokio::test]
+    async fn test_build_from_external_dimension_mismatch(
+        #[case] centroids_dim: usize,
+        #[case] codebook_dim: usize,
+    ) {
+        let test_dir = TempStrDir::default();
+        let uri = format!("{}/ds", test_dir.as_str());
+        let (dataset, _) = generate_test_dataset::<Float32Type>(&uri, 0.0..1.0).await;
+
+        let centroids = FixedSizeListArray::try_new_from_values(
+            generate_random_array(4 * centroids_dim),
+            centroids_dim as i32,
+        )
+        .unwrap();
+        let codebook: ArrayRef = Arc::new(generate_random_array(256 * codebook_dim));
+        let params = VectorIndexParams::with_ivf_pq_params(
+            DistanceType::L2,
+            IvfBuildParams::try_with_centroids(4, Arc::new(centroids)).unwrap(),
+            PQBuildParams::with_codebook(4, 8, codebook),
+        );
+
+        let progress = Arc::new(RecordingProgress::default());
+        let err = crate::index::vector::build_vector_index(
+            &dataset,
+            "vector",
+            "vector_idx",
+            &uuid::Uuid::new_v4().to_string(),
+            &params,
+            None,
+            progress.clone(),
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            matches!(err, crate::Error::InvalidInput { .. }),
+            "unexpected error: {err}"
+        );
+        // Rejected before sampling, training or shuffling started.
+        assert!(
+            progress.recorded_events().is_empty(),
+            "{:?}",
+            progress.recorded_events()
+        );
+    }
+
     #[rstest]
     #[case::ivf_flat(IndexType::IvfFlat)]
     #[case::ivf_pq(IndexType::IvfPq)]
@@ -3843,6 +4076,68 @@ mod tests {
         test_delete_all_rows(params).await;
     }
 
+    #[tokio::test]
+    async fn test_build_with_shuffle_memory_budget() {
+        let test_dir = TempStrDir::default();
+        let (mut dataset, vectors) =
+            generate_test_dataset::<Float32Type>(test_dir.as_str(), 0.0..1.0).await;
+        let nlist = 4;
+        // Fixed centroids so both builds assign every vector to the same partition.
+        let centroids = Arc::new(vectors.slice(0, nlist));
+        let search = |dataset: Dataset| {
+            let query = vectors.value(0);
+            async move {
+                let result = dataset
+                    .scan()
+                    .nearest("vector", query.as_primitive::<Float32Type>(), 100)
+                    .unwrap()
+                    .nprobes(nlist)
+                    .with_row_id()
+                    .try_into_batch()
+                    .await
+                    .unwrap();
+                result[ROW_ID]
+                    .as_primitive::<UInt64Type>()
+                    .values()
+                    .iter()
+                    .copied()
+                    .collect::<HashSet<_>>()
+            }
+        };
+
+        let ivf_params = IvfBuildParams::try_with_centroids(nlist, centroids).unwrap();
+        let params = VectorIndexParams::with_ivf_flat_params(DistanceType::L2, ivf_params.clone());
+        dataset
+            .create_index(&["vector"], IndexType::Vector, None, &params, true)
+            .await
+            .unwrap();
+        let in_memory = search(dataset.clone()).await;
+
+        // A budget far below the size of the data spills every few rows.
+        let shuffle_dir = TempStdDir::default();
+        let spilling_params = VectorIndexParams::with_ivf_flat_params(
+            DistanceType::L2,
+            IvfBuildParams {
+                shuffle_memory_budget_bytes: Some(1024),
+                shuffle_temp_dir: Some(shuffle_dir.to_path_buf()),
+                ..ivf_params
+            },
+        );
+        dataset
+            .create_index(&["vector"], IndexType::Vector, None, &spilling_params, true)
+            .await
+            .unwrap();
+        let spilled = search(dataset.clone()).await;
+
+        let gt = ground_truth(&dataset, "vector", &vectors.value(0), 100, DistanceType::L2).await;
+        let recall = |results: &HashSet<u64>| results.intersection(&gt).count();
+        assert_eq!(recall(&spilled), recall(&in_memory));
+        assert_eq!(spilled, in_memory);
+
+        // The scratch files of the shuffle are removed once the build is done.
+        assert_eq!(std::fs::read_dir(&*shuffle_dir).unwrap().count(), 0);
+    }
+
     #[rstest]
     #[case(4, DistanceType::L2, 0.9)]
     #[case(4, DistanceType::Cosine, 0.9)]
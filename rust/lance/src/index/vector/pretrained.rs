@@ -0,0 +1,147 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Vector index builds from IVF centroids and PQ codebooks trained elsewhere.
+//!
+//! Centroids and codebooks can be passed in memory, or as Lance datasets with
+//! [`IvfBuildParams::centroids_file`] and [`PQBuildParams::codebook_file`].
+//! They are loaded and checked against the vector column before the build
+//! samples or shuffles any data.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow_array::cast::AsArray;
+use arrow_array::{Array, FixedSizeListArray};
+use arrow_schema::DataType;
+use lance_index::vector::ivf::IvfBuildParams;
+use lance_index::vector::pq::PQBuildParams;
+
+use super::utils::{get_vector_dim, get_vector_type};
+use super::{StageParams, VectorIndexParams};
+use crate::dataset::Dataset;
+use crate::dataset::builder::DatasetBuilder;
+use crate::{Error, Result};
+
+fn has_pretrained_models(params: &VectorIndexParams) -> bool {
+    params.stages.iter().any(|stage| match stage {
+        StageParams::Ivf(ivf) => ivf.centroids.is_some() || ivf.centroids_file.is_some(),
+        StageParams::PQ(pq) => pq.codebook.is_some() || pq.codebook_file.is_some(),
+        _ => false,
+    })
+}
+
+/// Load the centroids and codebook files of `params` and check every
+/// provided model fits `column`.
+///
+/// Returns `params` unchanged when no model was provided.
+pub(super) async fn load_pretrained_models<'a>(
+    dataset: &Dataset,
+    column: &str,
+    params: &'a VectorIndexParams,
+) -> Result<Cow<'a, VectorIndexParams>> {
+    if !has_pretrained_models(params) {
+        return Ok(Cow::Borrowed(params));
+    }
+    let dim = get_vector_dim(dataset.schema(), column)?;
+    let (_, element_type) = get_vector_type(dataset.schema(), column)?;
+
+    let mut params = params.clone();
+    let storage_options = params.stages.iter().find_map(|stage| match stage {
+        StageParams::Ivf(ivf) => ivf.storage_options.clone(),
+        _ => None,
+    });
+    for stage in &mut params.stages {
+        match stage {
+            StageParams::Ivf(ivf) => {
+                load_centroids(ivf, storage_options.as_ref()).await?;
+                ivf.validate_centroids(dim, params.metric_type)?;
+            }
+            StageParams::PQ(pq) => {
+                load_codebook(pq, storage_options.as_ref()).await?;
+                pq.validate_codebook(dim, params.metric_type)?;
+                if let Some(codebook) = &pq.codebook
+                    && codebook.data_type() != &element_type
+                {
+                    return Err(Error::invalid_input(format!(
+                        "PQ codebook is {} but column {column} is {element_type}",
+                        codebook.data_type()
+                    )));
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(Cow::Owned(params))
+}
+
+async fn load_centroids(
+    ivf: &mut IvfBuildParams,
+    storage_options: Option<&HashMap<String, String>>,
+) -> Result<()> {
+    let Some(uri) = &ivf.centroids_file else {
+        return Ok(());
+    };
+    if ivf.centroids.is_some() {
+        return Err(Error::invalid_input(
+            "IVF centroids and centroids_file are mutually exclusive",
+        ));
+    }
+    ivf.centroids = Some(Arc::new(load_model(uri, storage_options).await?));
+    Ok(())
+}
+
+/// Load the codebook file of `pq`, and flatten a codebook given as a
+/// FixedSizeList to the values the quantizer expects.
+async fn load_codebook(
+    pq: &mut PQBuildParams,
+    storage_options: Option<&HashMap<String, String>>,
+) -> Result<()> {
+    if let Some(uri) = &pq.codebook_file {
+        if pq.codebook.is_some() {
+            return Err(Error::invalid_input(
+                "PQ codebook and codebook_file are mutually exclusive",
+            ));
+        }
+        pq.codebook = Some(Arc::new(load_model(uri, storage_options).await?));
+    }
+    if let Some(values) = pq
+        .codebook
+        .as_ref()
+        .and_then(|codebook| codebook.as_fixed_size_list_opt())
+        .map(|fsl| fsl.values().clone())
+    {
+        pq.codebook = Some(values);
+    }
+    Ok(())
+}
+
+/// Read the single FixedSizeList column of the Lance dataset at `uri`.
+async fn load_model(
+    uri: &str,
+    storage_options: Option<&HashMap<String, String>>,
+) -> Result<FixedSizeListArray> {
+    let mut builder = DatasetBuilder::from_uri(uri);
+    if let Some(storage_options) = storage_options {
+        builder = builder.with_storage_options(storage_options.clone());
+    }
+    let dataset = builder.load().await?;
+    let columns = dataset
+        .schema()
+        .fields
+        .iter()
+        .filter(|field| matches!(field.data_type(), DataType::FixedSizeList(..)))
+        .map(|field| field.name.as_str())
+        .collect::<Vec<_>>();
+    let [column] = columns.as_slice() else {
+        return Err(Error::invalid_input(format!(
+            "{uri} must have exactly one FixedSizeList column, found {}",
+            columns.len()
+        )));
+    };
+    let mut scanner = dataset.scan();
+    scanner.project(&[*column])?;
+    let batch = scanner.try_into_batch().await?;
+    Ok(batch.column(0).as_fixed_size_list().clone())
+}
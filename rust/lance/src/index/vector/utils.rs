@@ -159,16 +159,42 @@ pub fn infer_vector_dim(data_type: &arrow::datatypes::DataType) -> Result<usize>
 fn infer_vector_dim_impl(data_type: &arrow::datatypes::DataType, in_list: bool) -> Result<usize> {
     match (data_type, in_list) {
         (arrow::datatypes::DataType::FixedSizeList(_, dim), _) => Ok(*dim as usize),
-        (arrow::datatypes::DataType::List(inner), false) => {
+        (arrow::datatypes::DataType::List(inner), false)
+            if matches!(
+                inner.data_type(),
+                arrow::datatypes::DataType::FixedSizeList(..)
+            ) =>
+        {
             infer_vector_dim_impl(inner.data_type(), true)
         }
-        _ => Err(Error::invalid_input(format!(
-            "Data type is not a vector (FixedSizeListArray or List<FixedSizeListArray>), but {:?}",
-            data_type
-        ))),
+        _ => Err(not_a_vector(data_type)),
     }
 }
 
+/// The error for a column that can't be indexed as vectors.
+///
+/// Variable-length lists, such as `LargeList<Float32>` embeddings, are not
+/// indexed directly; the hint points at the cast that normalizes them.
+fn not_a_vector(data_type: &arrow::datatypes::DataType) -> Error {
+    use arrow::datatypes::DataType;
+    let hint = match data_type {
+        DataType::LargeList(inner) if matches!(inner.data_type(), DataType::FixedSizeList(..)) => {
+            ", cast the multivector column to List<FixedSizeList> with alter_columns"
+        }
+        DataType::List(inner) | DataType::LargeList(inner)
+            if inner.data_type().is_floating()
+                || matches!(inner.data_type(), DataType::UInt8 | DataType::Int8) =>
+        {
+            ", cast the column to a FixedSizeList of the vector dimension with alter_columns"
+        }
+        _ => "",
+    };
+    Error::invalid_input(format!(
+        "Data type is not a vector (FixedSizeListArray or List<FixedSizeListArray>), but {:?}{}",
+        data_type, hint
+    ))
+}
+
 /// Checks whether the given column is with a valid vector type
 /// returns the vector type (FixedSizeList for vectors, or List for multivectors),
 /// and element type (Float16/Float32/Float64 or UInt8 for binary vectors).
@@ -251,13 +277,15 @@ fn infer_vector_element_type_impl(
                 ))),
             }
         }
-        (arrow::datatypes::DataType::List(inner), false) => {
+        (arrow::datatypes::DataType::List(inner), false)
+            if matches!(
+                inner.data_type(),
+                arrow::datatypes::DataType::FixedSizeList(..)
+            ) =>
+        {
             infer_vector_element_type_impl(inner.data_type(), true)
         }
-        _ => Err(Error::invalid_input(format!(
-            "Data type is not a vector (FixedSizeListArray or List<FixedSizeListArray>), but {:?}",
-            data_type
-        ))),
+        _ => Err(not_a_vector(data_type)),
     }
 }
 
@@ -1067,6 +1095,34 @@ mod tests {
         assert_eq!(ranges, expected.collect::<Vec<_>>());
     }
 
+    #[test]
+    fn test_infer_vector_type_of_large_list() {
+        let fsl = DataType::new_fixed_size_list(DataType::Float32, 8, true);
+        assert_eq!(infer_vector_dim(&fsl).unwrap(), 8);
+        assert_eq!(
+            infer_vector_element_type(&DataType::new_list(fsl.clone(), true)).unwrap(),
+            DataType::Float32
+        );
+
+        for data_type in [
+            DataType::new_list(DataType::Float32, true),
+            DataType::new_large_list(DataType::Float32, true),
+        ] {
+            let err = infer_vector_dim(&data_type).unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains("cast the column to a FixedSizeList of the vector dimension"),
+                "{err}"
+            );
+        }
+        let err = infer_vector_element_type(&DataType::new_large_list(fsl, true)).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("cast the multivector column to List<FixedSizeList>"),
+            "{err}"
+        );
+    }
+
     #[tokio::test]
     async fn test_maybe_sample_training_data_multivector_infers_vectors_per_row() {
         let nrows: usize = 2000;
@@ -26,6 +26,8 @@ use std::time::Instant;
 
 use conflict_resolver::TransactionRebase;
 use lance_core::utils::backoff::{Backoff, SlotBackoff};
+#[cfg(feature = "metrics")]
+use lance_core::utils::metrics::{self, Counter};
 use lance_file::version::LanceFileVersion;
 use lance_index::metrics::NoOpMetricsCollector;
 use lance_io::utils::CachedFileSize;
@@ -910,6 +912,42 @@ async fn load_and_sort_new_transactions(
     Ok((new_ds, txns))
 }
 
+/// Commit instruments for one dataset, labeled with [`metrics::dataset_label`].
+#[cfg(feature = "metrics")]
+#[derive(Debug)]
+pub(crate) struct CommitMetrics {
+    commits: Counter,
+    conflicts: Counter,
+}
+
+#[cfg(feature = "metrics")]
+impl CommitMetrics {
+    /// The instruments of `dataset`, registered on its first commit and
+    /// shared with the versions committed from it.
+    fn of(dataset: &Dataset) -> &Self {
+        dataset
+            .commit_metrics
+            .get_or_init(|| Self::new(dataset.uri()))
+    }
+
+    fn new(uri: &str) -> Self {
+        let dataset = metrics::dataset_label(uri);
+        let labels = [("dataset", dataset.as_str())];
+        Self {
+            commits: metrics::counter(
+                "lance_commits_total",
+                "Transactions committed to existing datasets.",
+                &labels,
+            ),
+            conflicts: metrics::counter(
+                "lance_commit_conflicts_total",
+                "Commit attempts that lost the race for the next version.",
+                &labels,
+            ),
+        }
+    }
+}
+
 /// Attempt to commit a transaction, with retries and conflict resolution.
 #[allow(clippy::too_many_arguments)]
 pub(crate) async fn commit_transaction(
@@ -922,11 +960,14 @@ pub(crate) async fn commit_transaction(
     manifest_naming_scheme: ManifestNamingScheme,
     affected_rows: Option<&RowAddrTreeMap>,
 ) -> Result<(Manifest, ManifestLocation)> {
+    dataset.check_writable()?;
     // Note: object_store has been configured with WriteParams, but dataset.object_store.as_ref()
     // has not necessarily. So for anything involving writing, use `object_store`.
     let read_version = transaction.read_version;
     let mut target_version = read_version + 1;
     let original_dataset = dataset.clone();
+    #[cfg(feature = "metrics")]
+    let metrics = CommitMetrics::of(dataset);
 
     // read_version sometimes defaults to zero for overwrite.
     // If num_retries is zero, we are in "strict overwrite" mode.
@@ -1091,9 +1132,13 @@ pub(crate) async fn commit_transaction(
                         _ => {}
                     };
                 }
+                #[cfg(feature = "metrics")]
+                metrics.commits.inc();
                 return Ok((manifest, manifest_location));
             }
             Err(CommitError::CommitConflict) => {
+                #[cfg(feature = "metrics")]
+                metrics.conflicts.inc();
                 let next_attempt_i = backoff.attempt() + 1;
 
                 if backoff.attempt() == 0 {
@@ -1148,6 +1193,8 @@ mod tests {
     use futures::future::join_all;
     use lance_arrow::FixedSizeListArrayExt;
     use lance_core::datatypes::{Field, Schema};
+    #[cfg(feature = "metrics")]
+    use lance_core::utils::metrics::{counter_value, dataset_label};
     use lance_core::utils::tempfile::TempStrDir;
     use lance_datagen::{BatchCount, RowCount, array, gen_batch};
     use lance_index::IndexType;
@@ -1292,6 +1339,44 @@ mod tests {
         test_commit_handler(handler, false).await;
     }
 
+    #[cfg(feature = "metrics")]
+    #[tokio::test]
+    async fn test_commit_and_scan_metrics() {
+        let data = || {
+            gen_batch()
+                .col("i", array::step::<Int32Type>())
+                .into_reader_rows(RowCount::from(100), BatchCount::from(1))
+        };
+        let mut dataset = Dataset::write(data(), "memory://test_commit_metrics", None)
+            .await
+            .unwrap();
+        let label = dataset_label(dataset.uri());
+        let labels = [("dataset", label.as_str())];
+        let commits = || counter_value("lance_commits_total", &labels).unwrap_or(0);
+        let conflicts = || counter_value("lance_commit_conflicts_total", &labels).unwrap_or(0);
+        // Other tests share the scheme and cache labels, so those only grow.
+        let read_bytes =
+            || counter_value("lance_io_read_bytes_total", &[("scheme", "memory")]).unwrap_or(0);
+        let cache_lookups = || {
+            let labels = [("cache", "metadata")];
+            counter_value("lance_cache_hits_total", &labels).unwrap_or(0)
+                + counter_value("lance_cache_misses_total", &labels).unwrap_or(0)
+        };
+
+        // Creating the dataset does not go through commit_transaction.
+        assert_eq!(commits(), 0);
+        dataset.append(data(), None).await.unwrap();
+        dataset.append(data(), None).await.unwrap();
+        assert_eq!(commits(), 2);
+        assert_eq!(conflicts(), 0);
+
+        let (bytes_before, lookups_before) = (read_bytes(), cache_lookups());
+        let batch = dataset.scan().try_into_batch().await.unwrap();
+        assert_eq!(batch.num_rows(), 300);
+        assert!(read_bytes() > bytes_before);
+        assert!(cache_lookups() > lookups_before);
+    }
+
     #[tokio::test]
     async fn test_roundtrip_transaction_file() {
         let object_store = ObjectStore::memory();
@@ -986,6 +986,13 @@ impl<'a> TransactionRebase<'a> {
         other_version: u64,
     ) -> Result<()> {
         match &other_transaction.operation {
+            // The merged schema was built from the old schema, so it would undo
+            // concurrent changes to schema or field metadata
+            Operation::UpdateConfig { .. }
+                if other_transaction.operation.modifies_schema_metadata() =>
+            {
+                Err(self.retryable_conflict_err(other_transaction, other_version))
+            }
             Operation::CreateIndex { .. }
             | Operation::ReserveFragments { .. }
             | Operation::Clone { .. }
@@ -1066,6 +1073,11 @@ impl<'a> TransactionRebase<'a> {
         other_version: u64,
     ) -> Result<()> {
         match &other_transaction.operation {
+            Operation::UpdateConfig { .. }
+                if other_transaction.operation.modifies_schema_metadata() =>
+            {
+                Err(self.retryable_conflict_err(other_transaction, other_version))
+            }
             // Project is compatible with anything that doesn't change the schema
             Operation::Append { .. }
             | Operation::Update { .. }
@@ -1094,18 +1106,13 @@ impl<'a> TransactionRebase<'a> {
         other_transaction: &Transaction,
         other_version: u64,
     ) -> Result<()> {
-        if let Operation::UpdateConfig {
-            schema_metadata_updates,
-            field_metadata_updates,
-            ..
-        } = &self.transaction.operation
-        {
+        if let Operation::UpdateConfig { .. } = &self.transaction.operation {
+            let modifies_schema_metadata = self.transaction.operation.modifies_schema_metadata();
             match &other_transaction.operation {
                 Operation::Overwrite { .. } => {
                     // Updates to schema metadata or field metadata conflict with any kind
                     // of overwrite.
-                    if schema_metadata_updates.is_some()
-                        || !field_metadata_updates.is_empty()
+                    if modifies_schema_metadata
                         || self
                             .transaction
                             .operation
@@ -1131,6 +1138,11 @@ impl<'a> TransactionRebase<'a> {
                         Ok(())
                     }
                 }
+                // Metadata of fields may be updated while they are added or
+                // dropped, so retry against the new schema
+                Operation::Merge { .. } | Operation::Project { .. } if modifies_schema_metadata => {
+                    Err(self.retryable_conflict_err(other_transaction, other_version))
+                }
                 Operation::Append { .. }
                 | Operation::Clone { .. }
                 | Operation::Delete { .. }
@@ -2433,7 +2445,8 @@ mod tests {
                     fragments: vec![fragment0.clone(), fragment2.clone()],
                     schema: lance_core::datatypes::Schema::default(),
                 },
-                // Merge conflicts with everything except CreateIndex and ReserveFragments.
+                // Merge conflicts with everything except CreateIndex, ReserveFragments
+                // and config updates that leave the schema alone.
                 [
                     Retryable,     // append
                     Compatible,    // create index
@@ -2443,7 +2456,7 @@ mod tests {
                     Retryable,     // rewrite
                     Compatible,    // reserve
                     Retryable,     // update
-                    Compatible,    // update config
+                    Retryable,     // update config
                 ],
             ),
             (
@@ -2577,7 +2590,7 @@ mod tests {
             ),
             (
                 // Changing schema metadata conflicts with another update changing schema
-                // metadata or with an overwrite
+                // metadata, with a schema change or with an overwrite
                 create_update_config_for_test(
                     None,
                     None,
@@ -2591,7 +2604,7 @@ mod tests {
                     Compatible,    // append
                     Compatible,    // create index
                     Compatible,    // delete
-                    Compatible,    // merge
+                    Retryable,     // merge
                     NotCompatible, // overwrite
                     Compatible,    // rewrite
                     Compatible,    // reserve
@@ -2601,7 +2614,7 @@ mod tests {
             ),
             (
                 // Changing field metadata conflicts with another update changing same field
-                // metadata or overwrite
+                // metadata, with a schema change or with an overwrite
                 create_update_config_for_test(
                     None,
                     None,
@@ -2618,7 +2631,7 @@ mod tests {
                     Compatible,    // append
                     Compatible,    // create index
                     Compatible,    // delete
-                    Compatible,    // merge
+                    Retryable,     // merge
                     NotCompatible, // overwrite
                     Compatible,    // rewrite
                     Compatible,    // reserve
@@ -14,6 +14,7 @@ pub mod filtered_read;
 #[cfg(feature = "substrait")]
 pub mod filtered_read_proto;
 pub mod fts;
+mod group_limit;
 pub(crate) mod knn;
 mod optimizer;
 mod projection;
@@ -21,6 +22,7 @@ mod pushdown_scan;
 mod rowids;
 pub mod scalar_index;
 mod scan;
+pub mod sparse;
 #[cfg(feature = "substrait")]
 pub mod table_identifier;
 mod take;
@@ -29,7 +31,10 @@ pub mod testing;
 pub mod utils;
 
 pub use filter::LanceFilterExec;
-pub use knn::{ANNIvfPartitionExec, ANNIvfSubIndexExec, KNNVectorDistanceExec};
+pub use group_limit::{GroupLimitExec, NullGroups};
+pub use knn::{
+    ANNIvfBatchSearchExec, ANNIvfPartitionExec, ANNIvfSubIndexExec, KNNVectorDistanceExec,
+};
 pub use lance_datafusion::planner::Planner;
 pub use lance_index::scalar::expression::FilterPlan;
 pub use optimizer::get_physical_optimizer;
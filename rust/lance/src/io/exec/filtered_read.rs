@@ -11,6 +11,7 @@ use arrow_array::RecordBatch;
 use arrow_schema::SchemaRef;
 use datafusion::common::runtime::SpawnedTask;
 use datafusion::common::stats::Precision;
+use datafusion::common::tree_node::{Transformed, TreeNode};
 use datafusion::error::{DataFusionError, Result as DataFusionResult};
 use datafusion::execution::{SendableRecordBatchStream, TaskContext};
 use datafusion::physical_plan::metrics::ExecutionPlanMetricsSet;
@@ -19,6 +20,7 @@ use datafusion::physical_plan::{
     DisplayAs, DisplayFormatType, ExecutionPlan, PlanProperties,
     execution_plan::{Boundedness, EmissionType},
 };
+use datafusion::scalar::ScalarValue;
 use datafusion_expr::Expr;
 use datafusion_physical_expr::{EquivalenceProperties, Partitioning, PhysicalExpr};
 use datafusion_physical_plan::Statistics;
@@ -32,6 +34,7 @@ use lance_core::utils::deletion::DeletionVector;
 use lance_core::utils::futures::FinallyStreamExt;
 use lance_core::utils::tokio::get_num_compute_intensive_cpus;
 use lance_core::{Error, Result, datatypes::Projection};
+use lance_datafusion::exec::task_cancellation_token;
 use lance_datafusion::planner::Planner;
 use lance_datafusion::utils::{
     ExecutionPlanMetricsSetExt, FRAGMENTS_SCANNED_METRIC, RANGES_SCANNED_METRIC,
@@ -48,6 +51,7 @@ use lance_table::rowids::RowIdSequence;
 use lance_table::utils::stream::ReadBatchFut;
 use roaring::RoaringBitmap;
 use tokio::sync::{Mutex as AsyncMutex, OnceCell};
+use tokio_util::sync::CancellationToken;
 use tracing::{Instrument, instrument};
 
 use crate::Dataset;
@@ -58,6 +62,7 @@ use crate::dataset::scanner::{
     get_default_io_buffer_size_override,
 };
 
+use super::pushdown_scan::page_stats_ranges;
 use super::utils::IoMetrics;
 
 #[derive(Debug)]
@@ -133,6 +138,9 @@ struct LoadedFragment {
     //
     // This count does not include deleted rows
     num_logical_rows: u64,
+    // The ranges of physical rows that the page statistics can't rule out for
+    // the filter, or None if they were not checked
+    stats_ranges: Option<Vec<Range<u64>>>,
 }
 
 /// Given a sorted iterator of deleted row offsets, return a sorted iterator of valid row ranges
@@ -328,6 +336,8 @@ struct FilteredReadStream {
     threading_mode: FilteredReadThreadingMode,
     /// Range to apply to the result stream if not already pushed down in planning phase
     scan_range_after_filter: Option<Range<u64>>,
+    /// Whether batches are returned in fragment order
+    ordered_output: bool,
 }
 
 impl std::fmt::Debug for FilteredReadStream {
@@ -344,6 +354,7 @@ impl FilteredReadStream {
         options: FilteredReadOptions,
         metrics: &ExecutionPlanMetricsSet,
         plan: FilteredReadInternalPlan,
+        cancellation: Option<CancellationToken>,
     ) -> DataFusionResult<Self> {
         let global_metrics = Arc::new(FilteredReadGlobalMetrics::new(metrics));
 
@@ -376,6 +387,7 @@ impl FilteredReadStream {
                     dataset.clone(),
                     frag.clone(),
                     options.with_deleted_rows,
+                    None,
                 ))
             })
             .collect::<Vec<_>>();
@@ -398,7 +410,8 @@ impl FilteredReadStream {
         } else {
             SchedulerConfig::max_bandwidth(obj_store.as_ref())
         };
-        let scan_scheduler = ScanScheduler::new(obj_store, scheduler_config);
+        let scan_scheduler =
+            ScanScheduler::new_with_cancellation(obj_store, scheduler_config, cancellation);
 
         // Get scan_range_after_filter from the plan
         let scan_range_after_filter = plan.scan_range_after_filter.clone();
@@ -426,8 +439,19 @@ impl FilteredReadStream {
                     .map(|thread_result| thread_result.unwrap())
                 }
             })
-            .buffered(fragment_readahead);
-        let task_stream = fragment_streams.try_flatten().boxed();
+            .boxed();
+        let task_stream = if options.ordered_output {
+            fragment_streams
+                .buffered(fragment_readahead)
+                .try_flatten()
+                .boxed()
+        } else {
+            fragment_streams
+                .buffer_unordered(fragment_readahead)
+                .map_ok(|stream| stream.boxed())
+                .try_flatten_unordered(fragment_readahead)
+                .boxed()
+        };
 
         Ok(Self {
             output_schema,
@@ -437,6 +461,7 @@ impl FilteredReadStream {
             active_partitions_counter: Arc::new(AtomicUsize::new(0)),
             threading_mode,
             scan_range_after_filter,
+            ordered_output: options.ordered_output,
         })
     }
 
@@ -444,6 +469,7 @@ impl FilteredReadStream {
         dataset: Arc<Dataset>,
         frag: Fragment,
         include_deleted_rows: bool,
+        stats_filter: Option<Expr>,
     ) -> Result<LoadedFragment> {
         let file_fragment = FileFragment::new(dataset.clone(), frag.clone());
         let deletion_vector = if include_deleted_rows {
@@ -464,12 +490,17 @@ impl FilteredReadStream {
             let addrs_as_ids = Arc::new(RowIdSequence::from(row_ids_start..row_ids_end));
             (addrs_as_ids, num_logical_rows)
         };
+        let stats_ranges = match stats_filter {
+            Some(filter) => page_stats_ranges(&file_fragment, &filter).await?,
+            None => None,
+        };
         Ok(LoadedFragment {
             row_id_sequence,
             fragment: Arc::new(file_fragment),
             num_physical_rows,
             num_logical_rows,
             deletion_vector,
+            stats_ranges,
         })
     }
 
@@ -532,6 +563,7 @@ impl FilteredReadStream {
             num_logical_rows,
             num_physical_rows,
             deletion_vector,
+            stats_ranges,
         } in fragments.iter()
         {
             if let Some(range_before_filter) = &options.scan_range_before_filter
@@ -558,6 +590,17 @@ impl FilteredReadStream {
                 }
             }
 
+            if let Some(stats_ranges) = stats_ranges {
+                to_read = Self::intersect_ranges(&to_read, stats_ranges);
+                if to_read.is_empty() {
+                    log::trace!(
+                        "Skipping fragment {} because its page statistics rule out the filter",
+                        fragment.id()
+                    );
+                    continue;
+                }
+            }
+
             // Apply index and apply scan range after filter if applicable
             Self::apply_index_to_fragment(
                 evaluated_index,
@@ -580,6 +623,7 @@ impl FilteredReadStream {
 
         // Build filters for each fragment
         let mut filters = HashMap::new();
+        let mut pruned_fragments = Vec::new();
         for fragment in fragments.iter() {
             let fragment_id = fragment.fragment.id() as u32;
             if let Some(to_read) = fragments_to_read.get(&fragment_id) {
@@ -607,7 +651,24 @@ impl FilteredReadStream {
                     };
 
                     if let Some(f) = filter {
-                        filters.insert(fragment_id, Arc::new(f));
+                        let f = Self::filter_for_fragment(&fragment.fragment, f);
+                        match &f {
+                            Expr::Literal(ScalarValue::Boolean(Some(true)), _) => {}
+                            Expr::Literal(
+                                ScalarValue::Boolean(Some(false) | None) | ScalarValue::Null,
+                                _,
+                            ) => {
+                                log::trace!(
+                                    "Skipping fragment {} because the filter can never match its rows",
+                                    fragment_id
+                                );
+                                pruned_fragments.push(fragment_id);
+                                continue;
+                            }
+                            _ => {
+                                filters.insert(fragment_id, Arc::new(f));
+                            }
+                        }
                     }
 
                     log::trace!(
@@ -626,6 +687,10 @@ impl FilteredReadStream {
             }
         }
 
+        for fragment_id in pruned_fragments {
+            fragments_to_read.remove(&fragment_id);
+        }
+
         // If scan_range_after_filter was pushed down, don't include it in the plan
         let scan_range_after_filter = if scan_planned_with_limit_pushed_down {
             None
@@ -640,6 +705,53 @@ impl FilteredReadStream {
         }
     }
 
+    /// Specialize the filter for a fragment that is missing some of the columns
+    /// it references, e.g. a column added with all nulls that was never backfilled.
+    ///
+    /// Those columns are null for every row of the fragment, so the filter is
+    /// simplified as if they were null literals.  This often reduces the filter
+    /// to a constant, which lets the fragment be skipped (or read unfiltered).
+    fn filter_for_fragment(fragment: &FileFragment, filter: Expr) -> Expr {
+        let Ok(schema) = fragment
+            .schema()
+            .project(&Planner::column_names_in_expr(&filter))
+        else {
+            return filter;
+        };
+        let missing_fields = fragment.missing_field_ids(&schema);
+        if missing_fields.is_empty() {
+            return filter;
+        }
+
+        let specialized = filter.clone().transform_up(|expr| {
+            if let Expr::Column(column) = &expr
+                && let Some(field) = schema.fields.iter().find(|f| f.name == column.name)
+                && missing_fields.contains(&field.id)
+            {
+                let null = ScalarValue::try_from(&field.data_type())?;
+                return Ok(Transformed::yes(Expr::Literal(null, None)));
+            }
+            Ok(Transformed::no(expr))
+        });
+        let specialized = match specialized {
+            Ok(specialized) if specialized.transformed => specialized.data,
+            Ok(_) => return filter,
+            Err(err) => {
+                log::debug!("Failed to specialize filter for missing columns: {}", err);
+                return filter;
+            }
+        };
+
+        let planner = Planner::new(Arc::new((&schema).into()));
+        match planner.optimize_expr(specialized) {
+            Ok(expr) => expr,
+            Err(err) => {
+                log::debug!("Failed to simplify filter for missing columns: {}", err);
+                filter
+            }
+        }
+    }
+
     fn plan_to_scoped_fragments(
         plan: &FilteredReadInternalPlan,
         fragments: &[LoadedFragment],
@@ -979,16 +1091,18 @@ impl FilteredReadStream {
                     }
                 });
                 let partition_metrics_clone = partition_metrics.clone();
-                let base_batch_stream =
-                    futures_stream
-                        .try_buffered(num_threads)
-                        .try_filter_map(move |batch| {
-                            std::future::ready(Ok(if batch.num_rows() == 0 {
-                                None
-                            } else {
-                                Some(batch)
-                            }))
-                        });
+                let futures_stream = if self.ordered_output {
+                    futures_stream.try_buffered(num_threads).boxed()
+                } else {
+                    futures_stream.try_buffer_unordered(num_threads).boxed()
+                };
+                let base_batch_stream = futures_stream.try_filter_map(move |batch| {
+                    std::future::ready(Ok(if batch.num_rows() == 0 {
+                        None
+                    } else {
+                        Some(batch)
+                    }))
+                });
 
                 let batch_stream = if let Some(ref range) = self.scan_range_after_filter {
                     Self::apply_hard_range(base_batch_stream, range.clone()).boxed()
@@ -1295,6 +1409,12 @@ pub struct FilteredReadOptions {
     pub io_buffer_size_bytes: Option<u64>,
     /// If true, skip fragments that are not covered by the scalar index result.
     pub only_indexed_fragments: bool,
+    /// If false, fragments return their batches as soon as they are decoded
+    /// instead of in fragment order.
+    pub ordered_output: bool,
+    /// If true, skip the pages whose statistics show that none of their rows
+    /// match the filter.
+    pub use_page_stats: bool,
 }
 
 impl FilteredReadOptions {
@@ -1324,6 +1444,8 @@ impl FilteredReadOptions {
             full_filter: None,
             io_buffer_size_bytes: None,
             only_indexed_fragments: false,
+            ordered_output: true,
+            use_page_stats: false,
             threading_mode: FilteredReadThreadingMode::OnePartitionMultipleThreads(
                 get_num_compute_intensive_cpus(),
             ),
@@ -1482,6 +1604,23 @@ impl FilteredReadOptions {
         self.only_indexed_fragments = true;
         self
     }
+
+    /// Set whether batches are returned in fragment order (default: true)
+    ///
+    /// See [`crate::dataset::scanner::Scanner::scan_in_order`] for more details.
+    pub fn with_ordered_output(mut self, ordered_output: bool) -> Self {
+        self.ordered_output = ordered_output;
+        self
+    }
+
+    /// Set whether to skip pages by their statistics (default: false)
+    ///
+    /// Only data files written with [`crate::dataset::WriteParams::collect_stats`]
+    /// have statistics for the v2 format.
+    pub fn with_page_stats(mut self, use_page_stats: bool) -> Self {
+        self.use_page_stats = use_page_stats;
+        self
+    }
 }
 
 /// A plan node that reads a dataset, applying an optional filter and projection.
@@ -1688,6 +1827,10 @@ impl FilteredReadExec {
                     .unwrap_or_else(|| dataset.fragments().clone());
 
                 let with_deleted_rows = options.with_deleted_rows;
+                let stats_filter = options
+                    .full_filter
+                    .clone()
+                    .filter(|_| options.use_page_stats);
                 let frag_futs = fragments
                     .iter()
                     .map(|frag| {
@@ -1695,6 +1838,7 @@ impl FilteredReadExec {
                             dataset.clone(),
                             frag.clone(),
                             with_deleted_rows,
+                            stats_filter.clone(),
                         ))
                     })
                     .collect::<Vec<_>>();
@@ -1763,10 +1907,15 @@ impl FilteredReadExec {
                 )
                 .await
                 .map_err(|e| DataFusionError::External(e.into()))?;
-                let new_running_stream =
-                    FilteredReadStream::try_new(dataset, options, &metrics, plan.clone())
-                        .await
-                        .map_err(|e| DataFusionError::External(e.into()))?;
+                let new_running_stream = FilteredReadStream::try_new(
+                    dataset,
+                    options,
+                    &metrics,
+                    plan.clone(),
+                    task_cancellation_token(&context),
+                )
+                .await
+                .map_err(|e| DataFusionError::External(e.into()))?;
                 let first_stream = new_running_stream.get_stream(&metrics, partition);
                 *running_stream = Some(new_running_stream);
                 first_stream
@@ -2132,8 +2281,10 @@ mod tests {
         datatypes::{Float32Type, UInt32Type, UInt64Type},
     };
     use arrow_array::{
-        Array, ArrayRef, Int32Array, RecordBatch, RecordBatchIterator, UInt32Array, cast::AsArray,
+        Array, ArrayRef, Int32Array, RecordBatch, RecordBatchIterator, StringArray, UInt32Array,
+        cast::AsArray,
     };
+    use arrow_schema::{DataType, Field};
     use itertools::Itertools;
     use lance_core::datatypes::OnMissing;
     use lance_core::utils::tempfile::TempStrDir;
@@ -3854,4 +4005,97 @@ mod tests {
             assert_eq!(result1.column(i).as_ref(), result3.column(i).as_ref());
         }
     }
+
+    #[tokio::test]
+    async fn test_page_stats_pruning() {
+        // Four pages of sorted strings, one per prefix, in a file of the default version
+        let schema = Arc::new(arrow_schema::Schema::new(vec![Field::new(
+            "s",
+            DataType::Utf8,
+            false,
+        )]));
+        let batches = ["apple", "banana", "cherry", "date"].map(|prefix| {
+            let strings =
+                StringArray::from_iter_values((0..100).map(|i| format!("{prefix}{i:03}")));
+            RecordBatch::try_new(schema.clone(), vec![Arc::new(strings)])
+        });
+        let params = WriteParams {
+            collect_stats: true,
+            ..Default::default()
+        };
+        let dataset = Arc::new(
+            Dataset::write(
+                RecordBatchIterator::new(batches, schema),
+                "memory://test",
+                Some(params),
+            )
+            .await
+            .unwrap(),
+        );
+        assert!(!dataset.is_legacy_storage());
+
+        let planner = Planner::new(Arc::new(arrow_schema::Schema::from(dataset.schema())));
+        let index_info = dataset.scalar_index_info().await.unwrap();
+        let ctx = Arc::new(TaskContext::default());
+        for (filter, planned, num_rows) in [
+            ("s LIKE 'ban%'", vec![100..200], 100),
+            ("starts_with(s, 'cherry')", vec![200..300], 100),
+            ("s >= 'c'", vec![200..400], 200),
+            (
+                "s < 'apple050' OR s >= 'date099'",
+                vec![0..100, 300..400],
+                51,
+            ),
+            ("s > 'e'", vec![], 0),
+            // Wildcards after the prefix can't be turned into a range
+            ("s LIKE '_anana%'", vec![0..400], 100),
+        ] {
+            let filter_plan = planner
+                .create_filter_plan(planner.parse_filter(filter).unwrap(), &index_info, false)
+                .unwrap();
+            for use_page_stats in [false, true] {
+                let options = FilteredReadOptions::basic_full_read(&dataset)
+                    .with_filter_plan(filter_plan.clone())
+                    .with_page_stats(use_page_stats);
+                let exec = FilteredReadExec::try_new(dataset.clone(), options, None).unwrap();
+
+                let plan = exec.get_or_create_plan(ctx.clone()).await.unwrap();
+                let planned_ranges = plan
+                    .rows
+                    .get_fragment_bitmap(0)
+                    .map(bitmap_to_ranges)
+                    .unwrap_or_default();
+                if use_page_stats {
+                    assert_eq!(planned_ranges, planned, "{filter}");
+                } else {
+                    assert_eq!(planned_ranges, vec![0..400], "{filter}");
+                }
+
+                let batches = exec
+                    .execute(0, ctx.clone())
+                    .unwrap()
+                    .try_collect::<Vec<_>>()
+                    .await
+                    .unwrap();
+                let total_rows = batches.iter().map(|b| b.num_rows()).sum::<usize>();
+                assert_eq!(total_rows, num_rows, "{filter}");
+            }
+        }
+
+        // The scanner only reads the matching page
+        for (use_stats, rows_scanned) in [(false, 400), (true, 100)] {
+            let analysis = dataset
+                .scan()
+                .filter("s LIKE 'ban%'")
+                .unwrap()
+                .use_stats(use_stats)
+                .analyze_plan()
+                .await
+                .unwrap();
+            assert!(
+                analysis.contains(&format!("rows_scanned={rows_scanned},")),
+                "{analysis}"
+            );
+        }
+    }
 }
@@ -0,0 +1,296 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use arrow_array::{RecordBatch, UInt32Array};
+use arrow_ord::sort::{SortColumn, lexsort_to_indices};
+use arrow_row::{RowConverter, SortField};
+use arrow_schema::{SchemaRef, SortOptions};
+use arrow_select::concat::concat_batches;
+use datafusion::error::{DataFusionError, Result as DataFusionResult};
+use datafusion::execution::TaskContext;
+use datafusion::physical_plan::execution_plan::{Boundedness, CardinalityEffect, EmissionType};
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::{
+    DisplayAs, DisplayFormatType, ExecutionPlan, Partitioning, PlanProperties,
+    SendableRecordBatchStream,
+};
+use datafusion_physical_expr::{Distribution, EquivalenceProperties};
+use futures::{StreamExt, TryStreamExt, stream};
+use lance_core::ROW_ID;
+use lance_index::vector::DIST_COL;
+
+use crate::Result;
+
+/// How a grouped vector search treats rows whose group value is null
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NullGroups {
+    /// Every null is a group of its own, so rows with a null group value are never
+    /// pruned by the per-group limit
+    #[default]
+    Distinct,
+    /// All nulls are one group
+    Single,
+}
+
+/// Keeps the `max_per_group` nearest rows of each group of vector search results
+///
+/// The input is the (over-fetched) output of a vector search.  Rows are ranked by
+/// distance, ties broken by row id, and the first `limit` rows that fit within their
+/// group's quota are emitted in that order as a single batch.  This needs all input
+/// rows, which is bounded by the number of candidates the search fetched.
+#[derive(Debug)]
+pub struct GroupLimitExec {
+    input: Arc<dyn ExecutionPlan>,
+    column: String,
+    max_per_group: usize,
+    limit: usize,
+    null_groups: NullGroups,
+    properties: Arc<PlanProperties>,
+}
+
+impl GroupLimitExec {
+    pub fn try_new(
+        input: Arc<dyn ExecutionPlan>,
+        column: impl Into<String>,
+        max_per_group: usize,
+        limit: usize,
+        null_groups: NullGroups,
+    ) -> Result<Self> {
+        let column = column.into();
+        let schema = input.schema();
+        for required in [column.as_str(), DIST_COL] {
+            if schema.column_with_name(required).is_none() {
+                return Err(crate::Error::internal(format!(
+                    "GroupLimitExec input must have a {} column",
+                    required
+                )));
+            }
+        }
+        let properties = Arc::new(PlanProperties::new(
+            EquivalenceProperties::new(schema),
+            Partitioning::UnknownPartitioning(1),
+            EmissionType::Final,
+            Boundedness::Bounded,
+        ));
+        Ok(Self {
+            input,
+            column,
+            max_per_group,
+            limit,
+            null_groups,
+            properties,
+        })
+    }
+
+    fn group_limit(
+        batch: &RecordBatch,
+        column: &str,
+        max_per_group: usize,
+        limit: usize,
+        null_groups: NullGroups,
+    ) -> DataFusionResult<RecordBatch> {
+        if batch.num_rows() == 0 {
+            return Ok(batch.clone());
+        }
+        let mut sort_columns = vec![SortColumn {
+            values: batch[DIST_COL].clone(),
+            options: Some(SortOptions {
+                descending: false,
+                nulls_first: false,
+            }),
+        }];
+        if let Some(row_ids) = batch.column_by_name(ROW_ID) {
+            sort_columns.push(SortColumn {
+                values: row_ids.clone(),
+                options: None,
+            });
+        }
+        let order = lexsort_to_indices(&sort_columns, None)?;
+
+        let groups = batch[column].clone();
+        let converter = RowConverter::new(vec![SortField::new(groups.data_type().clone())])?;
+        let group_rows = converter.convert_columns(&[groups.clone()])?;
+
+        let mut group_counts: HashMap<&[u8], usize> = HashMap::new();
+        let mut keep = Vec::with_capacity(limit.min(order.len()));
+        for idx in order.values().iter().map(|idx| *idx as usize) {
+            if keep.len() == limit {
+                break;
+            }
+            if groups.is_null(idx) && null_groups == NullGroups::Distinct {
+                keep.push(idx as u32);
+                continue;
+            }
+            let count = group_counts.entry(group_rows.row(idx).data()).or_default();
+            if *count < max_per_group {
+                *count += 1;
+                keep.push(idx as u32);
+            }
+        }
+
+        let indices = UInt32Array::from(keep);
+        Ok(arrow_select::take::take_record_batch(batch, &indices)?)
+    }
+}
+
+impl DisplayAs for GroupLimitExec {
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default | DisplayFormatType::Verbose => {
+                write!(
+                    f,
+                    "GroupLimit: column={}, max_per_group={}, limit={}",
+                    self.column, self.max_per_group, self.limit
+                )
+            }
+            DisplayFormatType::TreeRender => {
+                write!(
+                    f,
+                    "GroupLimit\ncolumn={}\nmax_per_group={}\nlimit={}",
+                    self.column, self.max_per_group, self.limit
+                )
+            }
+        }
+    }
+}
+
+impl ExecutionPlan for GroupLimitExec {
+    fn name(&self) -> &str {
+        "GroupLimitExec"
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![&self.input]
+    }
+
+    fn required_input_distribution(&self) -> Vec<Distribution> {
+        // All candidates must be ranked together
+        vec![Distribution::SinglePartition]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        mut children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        if children.len() != 1 {
+            return Err(DataFusionError::Internal(
+                "GroupLimitExec expects exactly one child".to_string(),
+            ));
+        }
+        Ok(Arc::new(Self::try_new(
+            children.remove(0),
+            self.column.clone(),
+            self.max_per_group,
+            self.limit,
+            self.null_groups,
+        )?))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<TaskContext>,
+    ) -> DataFusionResult<SendableRecordBatchStream> {
+        let input = self.input.execute(partition, context)?;
+        let schema = self.schema();
+        let column = self.column.clone();
+        let max_per_group = self.max_per_group;
+        let limit = self.limit;
+        let null_groups = self.null_groups;
+        let stream = stream::once({
+            let schema = schema.clone();
+            async move {
+                let batches = input.try_collect::<Vec<_>>().await?;
+                let batch = concat_batches(&schema, &batches)?;
+                Self::group_limit(&batch, &column, max_per_group, limit, null_groups)
+            }
+        });
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            schema,
+            stream.boxed(),
+        )))
+    }
+
+    fn properties(&self) -> &Arc<PlanProperties> {
+        &self.properties
+    }
+
+    fn cardinality_effect(&self) -> CardinalityEffect {
+        CardinalityEffect::LowerEqual
+    }
+
+    fn supports_limit_pushdown(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::{Float32Array, Int32Array, UInt64Array};
+    use arrow_schema::{DataType, Field, Schema};
+
+    use super::*;
+
+    fn candidates(groups: Vec<Option<i32>>) -> RecordBatch {
+        let num_rows = groups.len();
+        let schema = Arc::new(Schema::new(vec![
+            Field::new(DIST_COL, DataType::Float32, true),
+            Field::new(ROW_ID, DataType::UInt64, true),
+            Field::new("doc", DataType::Int32, true),
+        ]));
+        // Distances are the reverse of the row ids so the output order differs from the
+        // input order
+        RecordBatch::try_new(
+            schema,
+            vec![
+                Arc::new(Float32Array::from_iter_values(
+                    (0..num_rows).rev().map(|i| i as f32),
+                )),
+                Arc::new(UInt64Array::from_iter_values(0..num_rows as u64)),
+                Arc::new(Int32Array::from(groups)),
+            ],
+        )
+        .unwrap()
+    }
+
+    fn kept_row_ids(batch: &RecordBatch) -> Vec<u64> {
+        batch[ROW_ID]
+            .as_any()
+            .downcast_ref::<UInt64Array>()
+            .unwrap()
+            .values()
+            .to_vec()
+    }
+
+    #[test]
+    fn test_group_limit_nulls() {
+        let batch = candidates(vec![Some(1), None, Some(1), None, Some(2), Some(1), None]);
+
+        let distinct =
+            GroupLimitExec::group_limit(&batch, "doc", 1, 10, NullGroups::Distinct).unwrap();
+        assert_eq!(kept_row_ids(&distinct), vec![6, 5, 4, 3, 1]);
+
+        let single = GroupLimitExec::group_limit(&batch, "doc", 1, 10, NullGroups::Single).unwrap();
+        assert_eq!(kept_row_ids(&single), vec![6, 5, 4]);
+
+        let limited = GroupLimitExec::group_limit(&batch, "doc", 2, 3, NullGroups::Single).unwrap();
+        assert_eq!(kept_row_ids(&limited), vec![6, 5, 4]);
+
+        let empty =
+            GroupLimitExec::group_limit(&batch.slice(0, 0), "doc", 2, 3, NullGroups::Single)
+                .unwrap();
+        assert_eq!(empty.num_rows(), 0);
+    }
+}
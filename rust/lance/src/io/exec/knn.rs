@@ -51,8 +51,8 @@ use lance_index::metrics::MetricsCollector;
 use lance_index::prefilter::PreFilter;
 use lance_index::vector::DIST_Q_C_COLUMN;
 use lance_index::vector::{
-    DIST_COL, INDEX_UUID_COLUMN, PART_ID_COLUMN, PartitionSearchControl, Query, VectorIndex,
-    flat::compute_distance,
+    DIST_COL, INDEX_UUID_COLUMN, NanHandling, PART_ID_COLUMN, PartitionSearchControl, Query,
+    VectorIndex, flat::compute_distance,
 };
 use lance_linalg::distance::DistanceType;
 use lance_linalg::kernels::normalize_arrow;
@@ -156,6 +156,7 @@ pub struct KNNVectorDistanceExec {
     pub upper_bound: Option<f32>,
     pub column: String,
     pub distance_type: DistanceType,
+    pub nan_handling: NanHandling,
 
     input_schema: SchemaRef,
     output_schema: SchemaRef,
@@ -167,10 +168,13 @@ pub struct KNNVectorDistanceExec {
 pub struct KnnBatchParams {
     pub is_batch: bool,
     pub query_count: usize,
+    /// Number of nearest rows to keep per query. Without `is_batch`, zero
+    /// keeps every row and leaves the top-k selection to the parent plan.
     pub k: usize,
     pub lower_bound: Option<f32>,
     pub upper_bound: Option<f32>,
     pub distance_type: DistanceType,
+    pub nan_handling: NanHandling,
 }
 
 struct BatchKnnConfig {
@@ -183,6 +187,7 @@ struct BatchKnnConfig {
     lower_bound: Option<f32>,
     upper_bound: Option<f32>,
     distance_type: DistanceType,
+    nan_handling: NanHandling,
 }
 
 impl DisplayAs for KNNVectorDistanceExec {
@@ -235,6 +240,7 @@ impl KNNVectorDistanceExec {
                 lower_bound: None,
                 upper_bound: None,
                 distance_type,
+                nan_handling: NanHandling::Drop,
             },
         )
     }
@@ -252,6 +258,7 @@ impl KNNVectorDistanceExec {
             lower_bound,
             upper_bound,
             distance_type,
+            nan_handling,
         } = params;
         if query_count == 0 {
             return Err(Error::invalid_input(
@@ -310,13 +317,17 @@ impl KNNVectorDistanceExec {
                 Boundedness::Bounded,
             ))
         } else {
-            Arc::new(
-                input
-                    .properties()
-                    .as_ref()
-                    .clone()
-                    .with_eq_properties(EquivalenceProperties::new(output_schema.clone())),
-            )
+            let properties = input
+                .properties()
+                .as_ref()
+                .clone()
+                .with_eq_properties(EquivalenceProperties::new(output_schema.clone()));
+            // The streaming top-k emits nothing until its input is exhausted.
+            if k > 0 {
+                Arc::new(properties.with_emission_type(EmissionType::Final))
+            } else {
+                Arc::new(properties)
+            }
         };
 
         Ok(Self {
@@ -329,6 +340,7 @@ impl KNNVectorDistanceExec {
             upper_bound,
             column: column.to_string(),
             distance_type,
+            nan_handling,
             input_schema,
             output_schema,
             properties,
@@ -350,6 +362,7 @@ impl KNNVectorDistanceExec {
             lower_bound,
             upper_bound,
             distance_type,
+            nan_handling,
         } = config;
         let query_dim = query.len() / query_count;
         let mut heaps = (0..query_count)
@@ -384,17 +397,13 @@ impl KNNVectorDistanceExec {
                     if !distances.is_valid(row_index) {
                         continue;
                     }
-                    let distance = distance_values[row_index];
-                    if distance.is_nan() {
-                        continue;
-                    }
                     // Single-query flat KNN applies distance_range as a plan filter.
                     // Batch mode filters before insertion so top-k stays per query.
-                    if lower_bound.is_some_and(|lower_bound| distance < lower_bound)
-                        || upper_bound.is_some_and(|upper_bound| distance >= upper_bound)
-                    {
+                    let Some(distance) =
+                        nan_handling.rank(distance_values[row_index], lower_bound, upper_bound)
+                    else {
                         continue;
-                    }
+                    };
                     let query_index = query_index as i32;
                     let row_id = row_ids.value(row_index);
                     let row_index = row_index as u32;
@@ -500,6 +509,7 @@ impl ExecutionPlan for KNNVectorDistanceExec {
                 lower_bound: self.lower_bound,
                 upper_bound: self.upper_bound,
                 distance_type: self.distance_type,
+                nan_handling: self.nan_handling,
             },
         )?))
     }
@@ -523,6 +533,7 @@ impl ExecutionPlan for KNNVectorDistanceExec {
                     lower_bound: self.lower_bound,
                     upper_bound: self.upper_bound,
                     distance_type: self.distance_type,
+                    nan_handling: self.nan_handling,
                 },
             ));
             let schema = self.schema();
@@ -537,6 +548,7 @@ impl ExecutionPlan for KNNVectorDistanceExec {
         let key = self.query.clone();
         let column = self.column.clone();
         let dt = self.distance_type;
+        let nan_handling = self.nan_handling;
         let schema = self.schema();
 
         // Empty batches don't have a vector column to score; filter them out
@@ -552,6 +564,7 @@ impl ExecutionPlan for KNNVectorDistanceExec {
         let elapsed_compute = BaselineMetrics::new(&self.metrics, partition)
             .elapsed_compute()
             .clone();
+        let top_k_compute = elapsed_compute.clone();
 
         let stream = InstrumentedChildInputStream::new(
             filtered_input,
@@ -573,17 +586,52 @@ impl ExecutionPlan for KNNVectorDistanceExec {
                     let distances = batch[DIST_COL].as_primitive::<Float32Type>();
                     let distance_values = distances.values();
                     let mask = BooleanArray::from_iter((0..distances.len()).map(|row_index| {
-                        Some(distances.is_valid(row_index) && !distance_values[row_index].is_nan())
+                        Some(
+                            distances.is_valid(row_index)
+                                && nan_handling
+                                    .rank(distance_values[row_index], None, None)
+                                    .is_some(),
+                        )
                     }));
-                    arrow::compute::filter_record_batch(&batch, &mask)
-                        .map_err(|e| DataFusionError::ArrowError(Box::new(e), None))
+                    let batch = arrow::compute::filter_record_batch(&batch, &mask)
+                        .map_err(|e| DataFusionError::ArrowError(Box::new(e), None))?;
+                    if nan_handling == NanHandling::SortLast {
+                        sort_nan_last(batch)
+                    } else {
+                        Ok(batch)
+                    }
                 }
             },
             get_num_compute_intensive_cpus(),
             partition,
             &self.metrics,
         );
-        Ok(Box::pin(stream) as SendableRecordBatchStream)
+        if self.k == 0 {
+            return Ok(Box::pin(stream) as SendableRecordBatchStream);
+        }
+
+        // Distances are computed in parallel above; only the rows that
+        // can make the top k of this partition are kept from each batch.
+        let top_k = FlatTopK::new(
+            self.k,
+            self.lower_bound,
+            self.upper_bound,
+            self.nan_handling,
+        );
+        let schema = self.schema();
+        let result = stream
+            .try_fold(top_k, move |mut top_k, batch| {
+                let _timer = top_k_compute.timer();
+                future::ready(top_k.push(&batch).map(|_| top_k))
+            })
+            .and_then({
+                let schema = schema.clone();
+                move |top_k| future::ready(top_k.finish(schema))
+            });
+        Ok(
+            Box::pin(RecordBatchStreamAdapter::new(schema, stream::once(result)))
+                as SendableRecordBatchStream,
+        )
     }
 
     fn partition_statistics(&self, partition: Option<usize>) -> DataFusionResult<Statistics> {
@@ -681,6 +729,172 @@ impl Ord for BatchKnnCandidate {
     }
 }
 
+/// Top-k of a single-query flat search, built one batch at a time.
+///
+/// Only the rows of a batch that can still make the top k are copied out of
+/// it, and the copies are compacted once they hold more than `2 * k` rows, so
+/// memory stays proportional to `k` no matter how many rows are scanned.
+/// Rows are ordered by distance, then by row id, like the sort that used to
+/// select them.
+struct FlatTopK {
+    k: usize,
+    lower_bound: Option<f32>,
+    upper_bound: Option<f32>,
+    nan_handling: NanHandling,
+    /// Max-heap of the best rows so far, the worst one on top.
+    heap: BinaryHeap<BatchKnnCandidate>,
+    /// Rows held by the batches the heap points into.
+    retained_rows: usize,
+}
+
+impl FlatTopK {
+    fn new(
+        k: usize,
+        lower_bound: Option<f32>,
+        upper_bound: Option<f32>,
+        nan_handling: NanHandling,
+    ) -> Self {
+        Self {
+            k,
+            lower_bound,
+            upper_bound,
+            nan_handling,
+            heap: BinaryHeap::with_capacity(k),
+            retained_rows: 0,
+        }
+    }
+
+    /// Offer every row of `batch`, which must have `_rowid` and `_distance`.
+    fn push(&mut self, batch: &RecordBatch) -> DataFusionResult<()> {
+        let row_ids = batch
+            .column_by_name(ROW_ID)
+            .ok_or_else(|| {
+                DataFusionError::Internal(
+                    "KNNVectorDistanceExec top-k requires _rowid in input".to_string(),
+                )
+            })?
+            .as_primitive::<UInt64Type>();
+        let distances = batch[DIST_COL].as_primitive::<Float32Type>();
+
+        let worst = if self.heap.len() < self.k {
+            None
+        } else {
+            self.heap.peek().map(|worst| (worst.distance, worst.row_id))
+        };
+        let mut rows = distances
+            .iter()
+            .zip(row_ids.values().iter())
+            .enumerate()
+            .filter_map(|(row_index, (distance, &row_id))| {
+                let distance =
+                    self.nan_handling
+                        .rank(distance?, self.lower_bound, self.upper_bound)?;
+                let beats_worst = worst.is_none_or(|(worst_distance, worst_row_id)| {
+                    distance
+                        .total_cmp(&worst_distance)
+                        .then(row_id.cmp(&worst_row_id))
+                        .is_lt()
+                });
+                beats_worst.then_some((distance, row_id, row_index as u32))
+            })
+            .collect::<Vec<_>>();
+        if rows.is_empty() {
+            return Ok(());
+        }
+        if rows.len() > self.k {
+            rows.select_nth_unstable_by(self.k - 1, |left, right| {
+                left.0.total_cmp(&right.0).then(left.1.cmp(&right.1))
+            });
+            rows.truncate(self.k);
+        }
+
+        let indices = UInt32Array::from_iter_values(rows.iter().map(|row| row.2));
+        let kept = arrow_select::take::take_record_batch(batch, &indices)
+            .map_err(|e| DataFusionError::ArrowError(Box::new(e), None))?;
+        self.retained_rows += kept.num_rows();
+        for (row_index, (distance, row_id, _)) in rows.into_iter().enumerate() {
+            let candidate = BatchKnnCandidate {
+                query_index: 0,
+                distance,
+                row_id,
+                batch: kept.clone(),
+                row_index: row_index as u32,
+            };
+            if self.heap.len() < self.k {
+                self.heap.push(candidate);
+            } else if self
+                .heap
+                .peek()
+                .is_some_and(|worst| candidate.cmp(worst).is_lt())
+            {
+                self.heap.pop();
+                self.heap.push(candidate);
+            }
+        }
+
+        if self.retained_rows > 2 * self.k {
+            self.compact()?;
+        }
+        Ok(())
+    }
+
+    /// Copy the rows still in the heap into one batch, dropping the others.
+    fn compact(&mut self) -> DataFusionResult<()> {
+        let candidates = std::mem::take(&mut self.heap).into_vec();
+        let batch = Self::take_rows(&candidates)?;
+        self.retained_rows = batch.num_rows();
+        self.heap = candidates
+            .into_iter()
+            .enumerate()
+            .map(|(row_index, candidate)| BatchKnnCandidate {
+                batch: batch.clone(),
+                row_index: row_index as u32,
+                ..candidate
+            })
+            .collect();
+        Ok(())
+    }
+
+    fn take_rows(candidates: &[BatchKnnCandidate]) -> DataFusionResult<RecordBatch> {
+        let batches = candidates
+            .iter()
+            .map(|candidate| &candidate.batch)
+            .collect::<Vec<_>>();
+        let indices = candidates
+            .iter()
+            .enumerate()
+            .map(|(i, candidate)| (i, candidate.row_index as usize))
+            .collect::<Vec<_>>();
+        arrow_select::interleave::interleave_record_batch(&batches, &indices)
+            .map_err(|e| DataFusionError::ArrowError(Box::new(e), None))
+    }
+
+    /// The top k rows, nearest first.
+    fn finish(self, schema: SchemaRef) -> DataFusionResult<RecordBatch> {
+        if self.heap.is_empty() {
+            return Ok(RecordBatch::new_empty(schema));
+        }
+        Self::take_rows(&self.heap.into_sorted_vec())
+    }
+}
+
+/// Replace the NaN distances of `batch` with positive NaN, which sorts after
+/// every other distance.
+fn sort_nan_last(batch: RecordBatch) -> DataFusionResult<RecordBatch> {
+    let distances = batch[DIST_COL]
+        .as_primitive::<Float32Type>()
+        .unary::<_, Float32Type>(|distance| {
+            if distance.is_nan() {
+                f32::NAN
+            } else {
+                distance
+            }
+        });
+    batch
+        .replace_column_by_name(DIST_COL, Arc::new(distances))
+        .map_err(|e| DataFusionError::ArrowError(Box::new(e), None))
+}
+
 pub static KNN_INDEX_SCHEMA: LazyLock<SchemaRef> = LazyLock::new(|| knn_empty_result_schema(false));
 
 /// Schema for empty vector-search results (e.g. `fast_search` with no index).
@@ -1063,7 +1277,14 @@ impl DisplayAs for ANNIvfSubIndexExec {
                     self.query.k * self.query.refine_factor.unwrap_or(1) as usize,
                     self.indices.len(),
                     metric_str
-                )
+                )?;
+                if let Some(ef) = self.query.ef {
+                    write!(f, ", ef={}", ef)?;
+                }
+                if let Some(mode) = self.prefilter_source.mode() {
+                    write!(f, ", prefilter: {}", mode)?;
+                }
+                Ok(())
             }
             DisplayFormatType::TreeRender => {
                 write!(
@@ -1073,7 +1294,14 @@ impl DisplayAs for ANNIvfSubIndexExec {
                     self.query.k * self.query.refine_factor.unwrap_or(1) as usize,
                     self.indices.len(),
                     metric_str
-                )
+                )?;
+                if let Some(ef) = self.query.ef {
+                    write!(f, "\nef={}", ef)?;
+                }
+                if let Some(mode) = self.prefilter_source.mode() {
+                    write!(f, "\nprefilter={}", mode)?;
+                }
+                Ok(())
             }
         }
     }
@@ -1653,6 +1881,395 @@ impl ExecutionPlan for ANNIvfSubIndexExec {
     }
 }
 
+/// [ExecutionPlan] to run a batch of vector queries against an IVF index.
+///
+/// `query.key` holds `query_count` query vectors back to back.  Every query probes
+/// its `minimum_nprobes` closest partitions.  The probes of all queries are grouped
+/// by partition so each partition is loaded once and searched with every query that
+/// needs it, `get_num_compute_intensive_cpus()` partitions at a time.
+///
+/// Only a fixed number of probes is supported, there is no late search and no
+/// refine.  The output is the top `k` rows of each query, ordered by query index,
+/// distance and row id:
+///
+/// ```text
+/// {
+///    "query_index": Int32,
+///    "_distance": Float32,
+///    "_rowid": UInt64,
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ANNIvfBatchSearchExec {
+    dataset: Arc<Dataset>,
+
+    indices: Vec<IndexMetadata>,
+
+    /// The vector query, with the query vectors concatenated in `key`.
+    query: Query,
+
+    query_count: usize,
+
+    /// Prefiltering input, shared by all queries
+    prefilter_source: PreFilterSource,
+
+    properties: Arc<PlanProperties>,
+
+    metrics: ExecutionPlanMetricsSet,
+}
+
+impl ANNIvfBatchSearchExec {
+    pub fn try_new(
+        dataset: Arc<Dataset>,
+        indices: Vec<IndexMetadata>,
+        query: Query,
+        query_count: usize,
+        prefilter_source: PreFilterSource,
+    ) -> Result<Self> {
+        if indices.is_empty() {
+            return Err(Error::execution(
+                "ANNIvfBatchSearchExec node: no index found for query".to_string(),
+            ));
+        }
+        if query.maximum_nprobes != Some(query.minimum_nprobes) || query.refine_factor.is_some() {
+            return Err(Error::invalid_input(
+                "ANNIvfBatchSearchExec node requires a fixed number of probes and no refine"
+                    .to_string(),
+            ));
+        }
+        if query_count == 0 || query.key.len() % query_count != 0 {
+            return Err(Error::invalid_input(format!(
+                "query of {} values cannot be split into {} query vectors",
+                query.key.len(),
+                query_count
+            )));
+        }
+        let properties = Arc::new(PlanProperties::new(
+            EquivalenceProperties::new(knn_empty_result_schema(true)),
+            Partitioning::RoundRobinBatch(1),
+            EmissionType::Final,
+            Boundedness::Bounded,
+        ));
+        Ok(Self {
+            dataset,
+            indices,
+            query,
+            query_count,
+            prefilter_source,
+            properties,
+            metrics: ExecutionPlanMetricsSet::new(),
+        })
+    }
+
+    /// Search one index delta with every query, offering the results to `top_k`.
+    async fn search_delta(
+        index: Arc<dyn VectorIndex>,
+        queries: &[Query],
+        pre_filter: Arc<DatasetPreFilter>,
+        metrics: Arc<AnnIndexMetrics>,
+        top_k: &mut [FlatTopK],
+    ) -> DataFusionResult<()> {
+        let nprobes = queries[0].minimum_nprobes;
+        let ranked = stream::iter(queries.iter().cloned())
+            .map(|query| find_partitions_on_cpu(index.clone(), query))
+            .buffered(get_num_compute_intensive_cpus())
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        // Invert the per-query probes into the queries each partition is searched with
+        let mut probes = HashMap::<u32, Vec<(usize, f32)>>::new();
+        for (query_index, (partitions, dist_q_c)) in ranked.iter().enumerate() {
+            let nprobes = nprobes.min(partitions.len());
+            for (part_id, dist_q_c) in partitions
+                .values()
+                .iter()
+                .zip(dist_q_c.values().iter())
+                .take(nprobes)
+            {
+                probes
+                    .entry(*part_id)
+                    .or_default()
+                    .push((query_index, *dist_q_c));
+            }
+        }
+        metrics.partitions_searched.add(probes.len());
+
+        let mut results = stream::iter(probes.into_iter().sorted_by_key(|(part_id, _)| *part_id))
+            .map(|(part_id, probes)| {
+                let index = index.clone();
+                let pre_filter = pre_filter.clone();
+                let metrics = metrics.clone();
+                let part_queries = probes
+                    .iter()
+                    .map(|(query_index, dist_q_c)| {
+                        let mut query = queries[*query_index].clone();
+                        query.dist_q_c = *dist_q_c;
+                        query
+                    })
+                    .collect::<Vec<_>>();
+                async move {
+                    let batches = index
+                        .search_in_partition_batch(
+                            part_id as usize,
+                            &part_queries,
+                            pre_filter,
+                            &metrics.index_metrics,
+                        )
+                        .await
+                        .map_err(|e| {
+                            DataFusionError::Execution(format!("Failed to calculate KNN: {}", e))
+                        })?;
+                    DataFusionResult::Ok(
+                        probes
+                            .into_iter()
+                            .map(|(query_index, _)| query_index)
+                            .zip(batches)
+                            .collect::<Vec<_>>(),
+                    )
+                }
+            })
+            .buffer_unordered(get_num_compute_intensive_cpus());
+        while let Some(batches) = results.try_next().await? {
+            for (query_index, batch) in batches {
+                top_k[query_index].push(&batch)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl DisplayAs for ANNIvfBatchSearchExec {
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let metric_str = self
+            .query
+            .metric_type
+            .map(|m| format!("{:?}", m))
+            .unwrap_or_else(|| "default".to_string());
+        match t {
+            DisplayFormatType::Default | DisplayFormatType::Verbose => {
+                write!(
+                    f,
+                    "ANNIvfBatchSearch: name={}, queries={}, k={}, nprobes={}, deltas={}, metric={}",
+                    self.indices[0].name,
+                    self.query_count,
+                    self.query.k,
+                    self.query.minimum_nprobes,
+                    self.indices.len(),
+                    metric_str
+                )?;
+                if let Some(ef) = self.query.ef {
+                    write!(f, ", ef={}", ef)?;
+                }
+                if let Some(mode) = self.prefilter_source.mode() {
+                    write!(f, ", prefilter: {}", mode)?;
+                }
+                Ok(())
+            }
+            DisplayFormatType::TreeRender => {
+                write!(
+                    f,
+                    "ANNIvfBatchSearch\nname={}\nqueries={}\nk={}\nnprobes={}\ndeltas={}\nmetric={}",
+                    self.indices[0].name,
+                    self.query_count,
+                    self.query.k,
+                    self.query.minimum_nprobes,
+                    self.indices.len(),
+                    metric_str
+                )?;
+                if let Some(ef) = self.query.ef {
+                    write!(f, "\nef={}", ef)?;
+                }
+                if let Some(mode) = self.prefilter_source.mode() {
+                    write!(f, "\nprefilter={}", mode)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl ExecutionPlan for ANNIvfBatchSearchExec {
+    fn name(&self) -> &str {
+        "ANNIvfBatchSearchExec"
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.properties.eq_properties.schema().clone()
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        match &self.prefilter_source {
+            PreFilterSource::None => vec![],
+            PreFilterSource::FilteredRowIds(src) => vec![src],
+            PreFilterSource::ScalarIndexQuery(src) => vec![src],
+        }
+    }
+
+    fn required_input_distribution(&self) -> Vec<Distribution> {
+        // Prefilter inputs must be a single partition
+        self.children()
+            .iter()
+            .map(|_| Distribution::SinglePartition)
+            .collect()
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        mut children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        let prefilter_source = match (&self.prefilter_source, children.pop()) {
+            (PreFilterSource::None, None) => PreFilterSource::None,
+            (PreFilterSource::FilteredRowIds(_), Some(src)) if children.is_empty() => {
+                PreFilterSource::FilteredRowIds(src)
+            }
+            (PreFilterSource::ScalarIndexQuery(_), Some(src)) if children.is_empty() => {
+                PreFilterSource::ScalarIndexQuery(src)
+            }
+            _ => {
+                return Err(DataFusionError::Internal(
+                    "ANNIvfBatchSearchExec node must have exactly one child per prefilter"
+                        .to_string(),
+                ));
+            }
+        };
+        Ok(Arc::new(Self {
+            dataset: self.dataset.clone(),
+            indices: self.indices.clone(),
+            query: self.query.clone(),
+            query_count: self.query_count,
+            prefilter_source,
+            properties: self.properties.clone(),
+            metrics: ExecutionPlanMetricsSet::new(),
+        }))
+    }
+
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<datafusion::execution::TaskContext>,
+    ) -> DataFusionResult<SendableRecordBatchStream> {
+        let prefilter_loader = match &self.prefilter_source {
+            PreFilterSource::FilteredRowIds(src_node) => {
+                let stream = src_node.execute(partition, context)?;
+                Some(Box::new(FilteredRowIdsToPrefilter(stream)) as Box<dyn FilterLoader>)
+            }
+            PreFilterSource::ScalarIndexQuery(src_node) => {
+                let stream = src_node.execute(partition, context)?;
+                Some(Box::new(SelectionVectorToPrefilter(stream)) as Box<dyn FilterLoader>)
+            }
+            PreFilterSource::None => None,
+        };
+        let pre_filter = Arc::new(DatasetPreFilter::new(
+            self.dataset.clone(),
+            &self.indices,
+            prefilter_loader,
+        ));
+
+        let schema = self.schema();
+        let ds = self.dataset.clone();
+        let indices = self.indices.clone();
+        let query = self.query.clone();
+        let query_count = self.query_count;
+        let metrics = Arc::new(AnnIndexMetrics::new(&self.metrics, partition));
+        let timer = Instant::now();
+
+        let stream = stream::once({
+            let schema = schema.clone();
+            async move {
+                let dim = query.key.len() / query_count;
+                let mut top_k = (0..query_count)
+                    .map(|_| FlatTopK::new(query.k, None, None, NanHandling::SortLast))
+                    .collect::<Vec<_>>();
+                for index_meta in &indices {
+                    let index = ds
+                        .open_vector_index(
+                            &query.column,
+                            &index_meta.uuid.to_string(),
+                            &metrics.index_metrics,
+                        )
+                        .await?;
+                    let queries = (0..query_count)
+                        .map(|query_index| {
+                            let mut single = query.clone();
+                            single.key = query.key.slice(query_index * dim, dim);
+                            normalize_query_for_index(index.as_ref(), single)
+                        })
+                        .collect::<DataFusionResult<Vec<_>>>()?;
+                    Self::search_delta(
+                        index,
+                        &queries,
+                        pre_filter.clone(),
+                        metrics.clone(),
+                        &mut top_k,
+                    )
+                    .await?;
+                }
+
+                let mut query_indices = Int32Builder::new();
+                let mut distances = Vec::with_capacity(query_count);
+                let mut row_ids = Vec::with_capacity(query_count);
+                for (query_index, top_k) in top_k.into_iter().enumerate() {
+                    let batch = top_k.finish(KNN_INDEX_SCHEMA.clone())?;
+                    query_indices.append_value_n(query_index as i32, batch.num_rows());
+                    distances.push(batch[DIST_COL].clone());
+                    row_ids.push(batch[ROW_ID].clone());
+                }
+                let concat = |arrays: Vec<ArrayRef>| {
+                    arrow_select::concat::concat(
+                        &arrays
+                            .iter()
+                            .map(|array| array.as_ref())
+                            .collect::<Vec<_>>(),
+                    )
+                };
+                let batch = RecordBatch::try_new(
+                    schema,
+                    vec![
+                        Arc::new(query_indices.finish()),
+                        concat(distances)?,
+                        concat(row_ids)?,
+                    ],
+                )?;
+
+                metrics.baseline_metrics.record_output(batch.num_rows());
+                metrics
+                    .baseline_metrics
+                    .elapsed_compute()
+                    .add_duration(timer.elapsed());
+                metrics.baseline_metrics.done();
+                DataFusionResult::Ok(batch)
+            }
+        });
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            schema,
+            stream.boxed(),
+        )))
+    }
+
+    fn partition_statistics(&self, _partition: Option<usize>) -> DataFusionResult<Statistics> {
+        Ok(Statistics {
+            num_rows: Precision::Inexact(self.query.k * self.query_count),
+            ..Statistics::new_unknown(self.schema().as_ref())
+        })
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+
+    fn properties(&self) -> &Arc<PlanProperties> {
+        &self.properties
+    }
+
+    fn supports_limit_pushdown(&self) -> bool {
+        false
+    }
+}
+
 fn adjust_probes(query: &mut Query, pruned_nprobes: usize) {
     query.minimum_nprobes = query.minimum_nprobes.max(pruned_nprobes);
     if let Some(maximum) = query.maximum_nprobes
@@ -1877,9 +2494,12 @@ mod tests {
     use arrow_array::{
         ArrayRef, FixedSizeListArray, Float32Array, Int32Array, RecordBatchIterator, StringArray,
     };
-    use arrow_schema::{Field as ArrowField, Schema as ArrowSchema};
+    use arrow_schema::{Field as ArrowField, Schema as ArrowSchema, SortOptions};
     use async_trait::async_trait;
     use datafusion::error::Result as DataFusionResult;
+    use datafusion::physical_expr::PhysicalSortExpr;
+    use datafusion::physical_plan::expressions;
+    use datafusion::physical_plan::sorts::sort::SortExec;
     use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
     use deepsize::DeepSizeOf;
     use lance_core::utils::tempfile::TempStrDir;
@@ -2585,6 +3205,60 @@ mod tests {
         assert_eq!(expected, results[0]);
     }
 
+    async fn nearest_row_ids(
+        dataset: &Dataset,
+        query: &dyn Array,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> Vec<u64> {
+        let batch = dataset
+            .scan()
+            .nearest("vector", query, 20)
+            .unwrap()
+            .with_row_id()
+            .limit(limit, offset)
+            .unwrap()
+            .try_into_batch()
+            .await
+            .unwrap();
+        batch[ROW_ID].as_primitive::<UInt64Type>().values().to_vec()
+    }
+
+    #[tokio::test]
+    async fn test_flat_search_limit_offset() {
+        let test_dir = TempStrDir::default();
+        let data = lance_datagen::gen_batch()
+            .col(
+                "vector",
+                array::rand_vec::<Float32Type>(lance_datagen::Dimension::from(8)),
+            )
+            .into_reader_rows(RowCount::from(100), BatchCount::from(10));
+        let write_params = WriteParams {
+            max_rows_per_file: 300,
+            ..Default::default()
+        };
+        let dataset = Dataset::write(data, test_dir.as_str(), Some(write_params))
+            .await
+            .unwrap();
+        let query = Float32Array::from(vec![0.5; 8]);
+
+        let all = nearest_row_ids(&dataset, &query, None, None).await;
+        assert_eq!(all.len(), 20);
+        for (limit, offset, expected) in [
+            (Some(5), None, &all[..5]),
+            (Some(5), Some(3), &all[3..8]),
+            (None, Some(15), &all[15..]),
+            (Some(10), Some(18), &all[18..]),
+            (Some(5), Some(25), &all[20..]),
+        ] {
+            assert_eq!(
+                nearest_row_ids(&dataset, &query, limit, offset).await,
+                expected,
+                "limit={limit:?} offset={offset:?}"
+            );
+        }
+    }
+
     #[test]
     fn test_create_knn_flat() {
         let dim: usize = 128;
@@ -2629,6 +3303,186 @@ mod tests {
         );
     }
 
+    /// Batches of 4-d vectors with only 7 distinct values, so most distances
+    /// tie. Later batches hold smaller row ids.
+    fn tied_vector_batches(num_batches: usize, rows_per_batch: usize) -> Vec<RecordBatch> {
+        let schema = Arc::new(ArrowSchema::new(vec![
+            ArrowField::new(ROW_ID, DataType::UInt64, false),
+            ArrowField::new(
+                "vector",
+                DataType::FixedSizeList(
+                    Arc::new(ArrowField::new("item", DataType::Float32, true)),
+                    4,
+                ),
+                true,
+            ),
+        ]));
+        (0..num_batches)
+            .map(|batch_index| {
+                let row_ids = (0..rows_per_batch)
+                    .map(|row| ((num_batches - batch_index) * 1000 + row) as u64);
+                let values = (0..rows_per_batch)
+                    .flat_map(|row| std::iter::repeat_n(((row * 3 + batch_index) % 7) as f32, 4));
+                RecordBatch::try_new(
+                    schema.clone(),
+                    vec![
+                        Arc::new(UInt64Array::from_iter_values(row_ids)),
+                        Arc::new(
+                            FixedSizeListArray::try_new_from_values(
+                                Float32Array::from_iter_values(values),
+                                4,
+                            )
+                            .unwrap(),
+                        ),
+                    ],
+                )
+                .unwrap()
+            })
+            .collect()
+    }
+
+    /// Flat search sorted like `Scanner::flat_knn` does, keeping `node_k` rows
+    /// in [`KNNVectorDistanceExec`] (zero keeps all of them).
+    async fn sorted_flat_search(
+        batches: Vec<RecordBatch>,
+        node_k: usize,
+        lower_bound: Option<f32>,
+        upper_bound: Option<f32>,
+    ) -> RecordBatch {
+        let input: Arc<dyn ExecutionPlan> = Arc::new(TestingExec::new(batches));
+        let knn: Arc<dyn ExecutionPlan> = Arc::new(
+            KNNVectorDistanceExec::try_new_batch(
+                input,
+                "vector",
+                Arc::new(Float32Array::from(vec![2.5; 4])),
+                KnnBatchParams {
+                    is_batch: false,
+                    query_count: 1,
+                    k: node_k,
+                    lower_bound,
+                    upper_bound,
+                    distance_type: DistanceType::L2,
+                    nan_handling: NanHandling::Drop,
+                },
+            )
+            .unwrap(),
+        );
+        let sort_options = SortOptions {
+            descending: false,
+            nulls_first: false,
+        };
+        let schema = knn.schema();
+        let sort = SortExec::new(
+            [
+                PhysicalSortExpr {
+                    expr: expressions::col(DIST_COL, &schema).unwrap(),
+                    options: sort_options,
+                },
+                PhysicalSortExpr {
+                    expr: expressions::col(ROW_ID, &schema).unwrap(),
+                    options: sort_options,
+                },
+            ]
+            .into(),
+            knn,
+        );
+        let results = datafusion::physical_plan::collect(
+            Arc::new(sort),
+            Arc::new(datafusion::execution::TaskContext::default()),
+        )
+        .await
+        .unwrap();
+        concat_batches(&schema, &results).unwrap()
+    }
+
+    #[rstest]
+    #[case::one(1, None, None)]
+    #[case::ties(35, None, None)]
+    #[case::more_than_rows(1000, None, None)]
+    #[case::range(35, Some(4.0), Some(40.0))]
+    #[tokio::test]
+    async fn test_flat_top_k_matches_sort(
+        #[case] k: usize,
+        #[case] lower_bound: Option<f32>,
+        #[case] upper_bound: Option<f32>,
+    ) {
+        let batches = tied_vector_batches(12, 50);
+
+        // The old plan scored every row and sorted them all; the distance
+        // range was applied by a filter before the sort.
+        let all = sorted_flat_search(batches.clone(), 0, None, None).await;
+        let distances = all[DIST_COL].as_primitive::<Float32Type>();
+        let in_range = BooleanArray::from_iter(distances.values().iter().map(|distance| {
+            Some(
+                lower_bound.is_none_or(|lower| *distance >= lower)
+                    && upper_bound.is_none_or(|upper| *distance < upper),
+            )
+        }));
+        let expected = arrow::compute::filter_record_batch(&all, &in_range).unwrap();
+        let expected = expected.slice(0, k.min(expected.num_rows()));
+
+        let actual = sorted_flat_search(batches, k, lower_bound, upper_bound).await;
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn test_flat_top_k_memory_bounded() {
+        let k = 100;
+        let query: ArrayRef = Arc::new(Float32Array::from(vec![0.5; 16]));
+        let batches = lance_datagen::gen_batch()
+            .col(ROW_ID, array::step::<UInt64Type>())
+            .col(
+                "vector",
+                array::rand_vec::<Float32Type>(lance_datagen::Dimension::from(16)),
+            )
+            .into_reader_rows(RowCount::from(4096), BatchCount::from(256));
+
+        let mut top_k = FlatTopK::new(k, None, None, NanHandling::Drop);
+        let mut all = Vec::new();
+        for batch in batches {
+            let batch = compute_distance(query.clone(), DistanceType::L2, "vector", batch.unwrap())
+                .await
+                .unwrap();
+            let distances = batch[DIST_COL].as_primitive::<Float32Type>();
+            let row_ids = batch[ROW_ID].as_primitive::<UInt64Type>();
+            all.extend(
+                distances
+                    .values()
+                    .iter()
+                    .copied()
+                    .zip(row_ids.values().iter().copied()),
+            );
+
+            top_k.push(&batch).unwrap();
+            // At most 2k compacted rows plus the k of the latest batch.
+            assert!(top_k.retained_rows <= 3 * k, "{}", top_k.retained_rows);
+        }
+        assert_eq!(all.len(), 4096 * 256);
+
+        let schema = Arc::new(ArrowSchema::new(vec![ArrowField::new(
+            DIST_COL,
+            DataType::Float32,
+            true,
+        )]));
+        let result = top_k.finish(schema).unwrap();
+        let actual = result[DIST_COL]
+            .as_primitive::<Float32Type>()
+            .values()
+            .iter()
+            .copied()
+            .zip(
+                result[ROW_ID]
+                    .as_primitive::<UInt64Type>()
+                    .values()
+                    .iter()
+                    .copied(),
+            )
+            .collect::<Vec<_>>();
+        all.sort_by(|left, right| left.0.total_cmp(&right.0).then(left.1.cmp(&right.1)));
+        all.truncate(k);
+        assert_eq!(actual, all);
+    }
+
     #[tokio::test]
     async fn test_multivector_score() {
         let query = Query {
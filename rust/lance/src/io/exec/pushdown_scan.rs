@@ -1,7 +1,8 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-FileCopyrightText: Copyright The Lance Authors
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::ops::Range;
 use std::{any::Any, sync::Arc};
 
 use arrow_array::cast::AsArray;
@@ -9,9 +10,11 @@ use arrow_array::types::{Int64Type, UInt64Type};
 use arrow_array::{Array, BooleanArray, Int64Array, PrimitiveArray, RecordBatch, UInt32Array};
 use arrow_schema::{DataType, Schema as ArrowSchema, SchemaRef};
 use arrow_select::filter::filter_record_batch;
+use datafusion::common::tree_node::{Transformed, TransformedResult, TreeNode};
 use datafusion::error::{DataFusionError, Result};
-use datafusion::logical_expr::col;
+use datafusion::logical_expr::expr::{Like, ScalarFunction};
 use datafusion::logical_expr::interval_arithmetic::{Interval, NullableInterval};
+use datafusion::logical_expr::{col, lit};
 use datafusion::optimizer::simplify_expressions::{ExprSimplifier, SimplifyContext};
 use datafusion::physical_plan::execution_plan::{Boundedness, EmissionType};
 use datafusion::physical_plan::metrics::{ExecutionPlanMetricsSet, MetricsSet};
@@ -30,6 +33,7 @@ use lance_arrow::{RecordBatchExt, SchemaExt};
 use lance_core::utils::tokio::get_num_compute_intensive_cpus;
 use lance_core::{ROW_ADDR, ROW_ADDR_FIELD, ROW_ID_FIELD};
 use lance_file::reader::FileReaderOptions;
+use lance_file::writer::PAGE_STATS_NUM_ROWS_COLUMN;
 use lance_io::ReadBatchParams;
 use lance_table::format::Fragment;
 
@@ -285,6 +289,8 @@ struct FragmentScanner {
     config: ScanConfig,
     reader: FragmentReader,
     stats: Option<RecordBatch>,
+    /// Fields of the predicate that have no data in this fragment and so are all null
+    missing_fields: HashSet<i32>,
 }
 
 impl FragmentScanner {
@@ -313,6 +319,7 @@ impl FragmentScanner {
         let stats = reader
             .legacy_read_page_stats(Some(&predicate_projection))
             .await?;
+        let missing_fields = fragment.missing_field_ids(&predicate_projection);
 
         Ok(Self {
             fragment,
@@ -322,6 +329,7 @@ impl FragmentScanner {
             config,
             reader,
             stats,
+            missing_fields,
         })
     }
 
@@ -339,7 +347,7 @@ impl FragmentScanner {
             .filter(|(_, predicate)| {
                 futures::future::ready(!matches!(
                     predicate,
-                    Expr::Literal(ScalarValue::Boolean(Some(false)), _)
+                    Expr::Literal(ScalarValue::Boolean(Some(false) | None), _)
                 ))
             })
             .map(move |(batch_id, predicate)| {
@@ -645,13 +653,7 @@ impl FragmentScanner {
                         (null_count, batch_size) if null_count == batch_size as i64 => NullableInterval::Null { datatype: field.data_type() },
                         _ => NullableInterval::MaybeNull { values }
                     };
-                    let column_path = predicate_projection.field_ancestry_by_id(field.id).unwrap();
-                    let mut parts_iter = column_path.into_iter().map(|part| part.name.as_str());
-                    let mut expr = col(parts_iter.next().unwrap());
-                    for part in parts_iter {
-                        expr = expr.field(part);
-                    }
-                    guarantees.push((expr, interval));
+                    guarantees.push((column_expr(predicate_projection, field.id), interval));
                 }
                 guarantees
             })
@@ -659,8 +661,11 @@ impl FragmentScanner {
 
     fn simplified_predicates(&self) -> Result<Vec<Expr>> {
         let num_batches = self.reader.legacy_num_batches();
+        let missing_field_guarantees =
+            null_field_guarantees(&self.predicate_projection, &self.missing_fields);
 
-        if let Some(stats) = &self.stats {
+        let batch_guarantees: Vec<Vec<(Expr, NullableInterval)>> = if let Some(stats) = &self.stats
+        {
             let batch_sizes: Vec<usize> = (0..num_batches as u32)
                 .map(|batch_id| {
                     self.reader
@@ -669,39 +674,268 @@ impl FragmentScanner {
                         as usize
                 })
                 .collect();
-            let schema =
-                Arc::new(ArrowSchema::from(self.predicate_projection.as_ref()).try_into()?);
-            let context = SimplifyContext::default().with_schema(schema);
-            let mut simplifier = ExprSimplifier::new(context);
-
-            let mut predicates = Vec::with_capacity(num_batches);
-            for guarantees in
-                Self::extract_guarantees(&self.predicate_projection, &batch_sizes, stats)
+            Self::extract_guarantees(&self.predicate_projection, &batch_sizes, stats)
+                .map(|mut guarantees| {
+                    guarantees.extend(missing_field_guarantees.iter().cloned());
+                    guarantees
+                })
+                .collect()
+        } else if !missing_field_guarantees.is_empty() {
+            vec![missing_field_guarantees; num_batches]
+        } else {
+            return Ok(vec![self.predicate.clone(); num_batches]);
+        };
+
+        simplify_with_guarantees(
+            &self.predicate_projection,
+            &self.predicate,
+            batch_guarantees,
+        )
+    }
+}
+
+/// Fields without data in the fragment are null in every batch
+fn null_field_guarantees(
+    predicate_projection: &Schema,
+    missing_fields: &HashSet<i32>,
+) -> Vec<(Expr, NullableInterval)> {
+    predicate_projection
+        .fields_pre_order()
+        .filter(|field| missing_fields.contains(&field.id))
+        .map(|field| {
+            let interval = NullableInterval::Null {
+                datatype: field.data_type(),
+            };
+            (column_expr(predicate_projection, field.id), interval)
+        })
+        .collect()
+}
+
+/// Simplify the predicate with each set of guarantees
+fn simplify_with_guarantees(
+    predicate_projection: &Schema,
+    predicate: &Expr,
+    all_guarantees: Vec<Vec<(Expr, NullableInterval)>>,
+) -> Result<Vec<Expr>> {
+    let schema = Arc::new(ArrowSchema::from(predicate_projection).try_into()?);
+    let context = SimplifyContext::default().with_schema(schema);
+    let mut simplifier = ExprSimplifier::new(context);
+    let bounded_predicate = with_prefix_bounds(predicate.clone())?;
+
+    let mut predicates = Vec::with_capacity(all_guarantees.len());
+    for guarantees in all_guarantees {
+        simplifier = simplifier.with_guarantees(guarantees);
+        let simplified_expr = match simplifier.simplify(bounded_predicate.clone()) {
+            Ok(expr) => expr,
+            Err(err) => {
+                // TODO: this logs on each iteration, but maybe should should
+                // only log once per call of this func?
+                log::debug!("Failed to simplify predicate: {}", err);
+                predicate.clone()
+            }
+        };
+
+        predicates.push(simplified_expr);
+    }
+    Ok(predicates)
+}
+
+/// The ranges of rows of a fragment that the page statistics can't rule out for
+/// `predicate`.
+///
+/// This is how v2 scans skip pages, the legacy pushdown scan prunes whole batches
+/// instead.  Returns `None` if the fragment has no statistics for the predicate.
+pub(crate) async fn page_stats_ranges(
+    fragment: &FileFragment,
+    predicate: &Expr,
+) -> Result<Option<Vec<Range<u64>>>> {
+    let columns = Planner::column_names_in_expr(predicate);
+    // Predicates on columns that are not stored, e.g. the row id, have no statistics
+    let Ok(predicate_projection) = fragment.dataset().schema().project(&columns) else {
+        return Ok(None);
+    };
+    let reader = fragment
+        .open(&predicate_projection, FragReadConfig::default())
+        .await?;
+    let stats = reader.read_page_stats(&predicate_projection).await?;
+    if stats.is_empty() {
+        return Ok(None);
+    }
+
+    // Split the fragment at the page boundaries of every data file, so that each
+    // segment is covered by a single page of each file.
+    let num_rows = fragment.physical_rows().await? as u64;
+    let mut boundaries = BTreeSet::from([num_rows]);
+    let mut file_pages = Vec::with_capacity(stats.len());
+    for stats in &stats {
+        let page_sizes = stats
+            .column_by_name(PAGE_STATS_NUM_ROWS_COLUMN)
+            .and_then(|num_rows| num_rows.as_primitive_opt::<UInt64Type>())
+            .ok_or_else(|| {
+                Error::internal("Invalid statistics: missing the number of rows of each page")
+            })?
+            .values()
+            .iter()
+            .map(|num_rows| *num_rows as usize)
+            .collect::<Vec<_>>();
+        let page_ends = page_sizes
+            .iter()
+            .scan(0, |offset, num_rows| {
+                *offset += *num_rows as u64;
+                Some(*offset)
+            })
+            .collect::<Vec<_>>();
+        boundaries.extend(page_ends.iter().copied().filter(|end| *end < num_rows));
+        let page_guarantees =
+            FragmentScanner::extract_guarantees(&predicate_projection, &page_sizes, stats)
+                .collect::<Vec<_>>();
+        file_pages.push((page_ends, page_guarantees));
+    }
+
+    let missing_field_guarantees = null_field_guarantees(
+        &predicate_projection,
+        &fragment.missing_field_ids(&predicate_projection),
+    );
+    let mut segments = Vec::with_capacity(boundaries.len());
+    let mut segment_guarantees = Vec::with_capacity(boundaries.len());
+    let mut page_indices = vec![0; file_pages.len()];
+    let mut start = 0;
+    for end in boundaries {
+        let mut guarantees = missing_field_guarantees.clone();
+        for ((page_ends, page_guarantees), page_idx) in file_pages.iter().zip(&mut page_indices) {
+            while page_ends
+                .get(*page_idx)
+                .is_some_and(|page_end| *page_end < end)
             {
-                simplifier = simplifier.with_guarantees(guarantees);
-                let simplified_expr = match simplifier.simplify(self.predicate.clone()) {
-                    Ok(expr) => expr,
-                    Err(err) => {
-                        // TODO: this logs on each iteration, but maybe should should
-                        // only log once per call of this func?
-                        log::debug!("Failed to simplify predicate: {}", err);
-                        self.predicate.clone()
-                    }
-                };
+                *page_idx += 1;
+            }
+            if let Some(page_guarantees) = page_guarantees.get(*page_idx) {
+                guarantees.extend(page_guarantees.iter().cloned());
+            }
+        }
+        segments.push(start..end);
+        segment_guarantees.push(guarantees);
+        start = end;
+    }
+
+    let predicates =
+        simplify_with_guarantees(&predicate_projection, predicate, segment_guarantees)?;
+    let mut ranges: Vec<Range<u64>> = Vec::new();
+    for (segment, predicate) in segments.into_iter().zip(predicates) {
+        if segment.is_empty()
+            || matches!(
+                predicate,
+                Expr::Literal(
+                    ScalarValue::Boolean(Some(false) | None) | ScalarValue::Null,
+                    _
+                )
+            )
+        {
+            continue;
+        }
+        match ranges.last_mut() {
+            Some(last) if last.end == segment.start => last.end = segment.end,
+            _ => ranges.push(segment),
+        }
+    }
+    Ok(Some(ranges))
+}
+
+/// The expression for the column with the given field id, e.g. `s.x`
+fn column_expr(schema: &Schema, field_id: i32) -> Expr {
+    let column_path = schema.field_ancestry_by_id(field_id).unwrap();
+    let mut parts_iter = column_path.into_iter().map(|part| part.name.as_str());
+    let mut expr = col(parts_iter.next().unwrap());
+    for part in parts_iter {
+        expr = expr.field(part);
+    }
+    expr
+}
 
-                predicates.push(simplified_expr);
+/// Add the range implied by prefix predicates (`col LIKE 'prefix%'` and
+/// `starts_with(col, 'prefix')`) as extra conjuncts, so that batches can be
+/// pruned by the min / max statistics of the column.
+fn with_prefix_bounds(predicate: Expr) -> Result<Expr> {
+    predicate
+        .transform_up(|expr| {
+            let Some((column, literal, prefix)) = prefix_predicate(&expr) else {
+                return Ok(Transformed::no(expr));
+            };
+            let lower = column
+                .clone()
+                .gt_eq(lit(string_scalar_like(literal, prefix)));
+            let upper = prefix_upper_bound(prefix)
+                .map(|upper| column.clone().lt(lit(string_scalar_like(literal, &upper))));
+            let mut bounded = expr.clone().and(lower);
+            if let Some(upper) = upper {
+                bounded = bounded.and(upper);
             }
-            Ok(predicates)
-        } else {
-            Ok(vec![self.predicate.clone(); num_batches])
+            Ok(Transformed::yes(bounded))
+        })
+        .data()
+}
+
+/// If `expr` only matches strings starting with a literal prefix, return the
+/// column, the string literal in the predicate and the prefix.
+fn prefix_predicate(expr: &Expr) -> Option<(&Expr, &ScalarValue, &str)> {
+    let (column, literal, prefix) = match expr {
+        Expr::Like(Like {
+            negated: false,
+            expr,
+            pattern,
+            escape_char: None,
+            case_insensitive: false,
+        }) => {
+            let Expr::Literal(literal, _) = pattern.as_ref() else {
+                return None;
+            };
+            let prefix = literal.try_as_str()??.strip_suffix('%')?;
+            // Any other wildcard (or an escaped one) makes the prefix inexact.
+            if prefix.contains(['%', '_', '\\']) {
+                return None;
+            }
+            (expr.as_ref(), literal, prefix)
+        }
+        Expr::ScalarFunction(ScalarFunction { func, args }) if func.name() == "starts_with" => {
+            let [expr, Expr::Literal(literal, _)] = args.as_slice() else {
+                return None;
+            };
+            (expr, literal, literal.try_as_str()??)
         }
+        _ => return None,
+    };
+    (matches!(column, Expr::Column(_)) && !prefix.is_empty()).then_some((column, literal, prefix))
+}
+
+/// Build a string scalar holding `value` with the same type as `literal`.
+fn string_scalar_like(literal: &ScalarValue, value: &str) -> ScalarValue {
+    let value = Some(value.to_string());
+    match literal {
+        ScalarValue::LargeUtf8(_) => ScalarValue::LargeUtf8(value),
+        ScalarValue::Utf8View(_) => ScalarValue::Utf8View(value),
+        _ => ScalarValue::Utf8(value),
     }
 }
 
+/// The smallest string that is greater than every string starting with `prefix`.
+///
+/// Returns `None` if there is no such string.
+fn prefix_upper_bound(prefix: &str) -> Option<String> {
+    let mut chars = prefix.chars().collect::<Vec<_>>();
+    while let Some(last) = chars.pop() {
+        // char::from_u32 skips over the surrogate range, which has no chars.
+        if let Some(next) = (last as u32 + 1..=char::MAX as u32).find_map(char::from_u32) {
+            chars.push(next);
+            return Some(chars.into_iter().collect());
+        }
+    }
+    None
+}
+
 #[cfg(test)]
 mod test {
     use arrow_array::{
-        ArrayRef, DictionaryArray, FixedSizeListArray, Float32Array, Int32Array,
+        ArrayRef, BinaryArray, DictionaryArray, FixedSizeListArray, Float32Array, Int32Array,
         RecordBatchIterator, StringArray, StructArray, TimestampMicrosecondArray, UInt64Array,
         types::{Float32Type, Int32Type},
     };
@@ -709,6 +943,7 @@ mod test {
     use arrow_schema::{Field, TimeUnit};
     use arrow_select::concat::concat_batches;
     use datafusion::prelude::{Column, SessionContext, lit};
+    use datafusion_functions::string::expr_fn::starts_with;
     use lance_arrow::{FixedSizeListArrayExt, SchemaExt};
     use lance_core::utils::tempfile::TempStrDir;
     use lance_file::version::LanceFileVersion;
@@ -1547,4 +1782,194 @@ mod test {
             assert_eq!(floats, &expected);
         }
     }
+
+    async fn write_legacy_dataset(data: RecordBatch, max_rows_per_group: usize) -> Arc<Dataset> {
+        let schema = data.schema();
+        let reader = RecordBatchIterator::new(vec![Ok(data)], schema);
+        let params = WriteParams {
+            max_rows_per_group,
+            data_storage_version: Some(LanceFileVersion::Legacy),
+            ..Default::default()
+        };
+        Arc::new(
+            Dataset::write(reader, "memory://test", Some(params))
+                .await
+                .unwrap(),
+        )
+    }
+
+    /// Returns the number of batches in the first fragment that statistics
+    /// rule out for `predicate`.
+    async fn num_pruned_batches(dataset: &Arc<Dataset>, predicate: Expr) -> usize {
+        let columns: Vec<_> = predicate
+            .column_refs()
+            .into_iter()
+            .map(|col| col.name.as_str())
+            .collect();
+        let predicate_projection = Arc::new(dataset.schema().project(&columns).unwrap());
+        let scanner = FragmentScanner::open(
+            dataset.fragments()[0].clone(),
+            dataset.clone(),
+            Arc::new(dataset.schema().clone()),
+            predicate_projection,
+            predicate.clone(),
+            ScanConfig::default(),
+        )
+        .await
+        .unwrap();
+        scanner
+            .simplified_predicates()
+            .unwrap()
+            .iter()
+            .filter(|predicate| {
+                matches!(
+                    predicate,
+                    Expr::Literal(ScalarValue::Boolean(Some(false)), _)
+                )
+            })
+            .count()
+    }
+
+    #[tokio::test]
+    async fn test_prefix_pruning() {
+        // Four batches of sorted strings, one per prefix.
+        let strings = ["apple", "banana", "cherry", "date"]
+            .iter()
+            .flat_map(|prefix| (0..100).map(move |i| Some(format!("{prefix}{i:03}"))))
+            .collect::<StringArray>();
+        let schema = Arc::new(ArrowSchema::new(vec![Field::new(
+            "s",
+            DataType::Utf8,
+            false,
+        )]));
+        let data = RecordBatch::try_new(schema, vec![Arc::new(strings)]).unwrap();
+        let dataset = write_legacy_dataset(data, 100).await;
+
+        let ctx = SessionContext::new();
+        for (predicate, pruned, num_rows) in [
+            (col("s").like(lit("ban%")), 3, 100),
+            (col("s").like(lit("banana05%")), 3, 10),
+            (starts_with(col("s"), lit("cherry")), 3, 100),
+            (col("s").gt_eq(lit("c")), 2, 200),
+            (col("s").lt(lit("apple050")), 3, 50),
+            // Wildcards after the prefix can't be turned into a range.
+            (col("s").like(lit("b%5")), 0, 10),
+            (col("s").like(lit("_anana%")), 0, 100),
+            (col("s").not_like(lit("ban%")), 0, 300),
+        ] {
+            assert_eq!(
+                num_pruned_batches(&dataset, predicate.clone()).await,
+                pruned,
+                "{predicate}"
+            );
+            let result = pushdown_scan(
+                &ctx,
+                dataset.clone(),
+                vec![0],
+                predicate.clone(),
+                ScanConfig::default(),
+            )
+            .await
+            .unwrap();
+            assert_eq!(result.num_rows(), num_rows, "{predicate}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pruning_long_common_prefix() {
+        // Statistics truncate these values to a shared 64 byte prefix, so batches
+        // can't be told apart but must still return the right rows.
+        let common = "x".repeat(100);
+        let strings = StringArray::from_iter_values((0..400).map(|i| format!("{common}{i:03}")));
+        let schema = Arc::new(ArrowSchema::new(vec![Field::new(
+            "s",
+            DataType::Utf8,
+            false,
+        )]));
+        let data = RecordBatch::try_new(schema, vec![Arc::new(strings)]).unwrap();
+        let dataset = write_legacy_dataset(data, 100).await;
+
+        let ctx = SessionContext::new();
+        for (predicate, pruned, num_rows) in [
+            (col("s").gt_eq(lit(format!("{common}250"))), 0, 150),
+            (col("s").like(lit(format!("{common}1%"))), 0, 100),
+            (col("s").eq(lit(format!("{common}399"))), 0, 1),
+            (col("s").gt(lit(format!("{}y", "x".repeat(63)))), 4, 0),
+            (col("s").lt(lit("x".repeat(64))), 4, 0),
+        ] {
+            assert_eq!(
+                num_pruned_batches(&dataset, predicate.clone()).await,
+                pruned,
+                "{predicate}"
+            );
+            let result = pushdown_scan(
+                &ctx,
+                dataset.clone(),
+                vec![0],
+                predicate.clone(),
+                ScanConfig::default(),
+            )
+            .await
+            .unwrap();
+            assert_eq!(result.num_rows(), num_rows, "{predicate}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pruning_binary_bounds() {
+        // Long non-UTF8 values whose truncated max has to carry over 0xFF bytes.
+        let values = (0..200_u8)
+            .map(|i| {
+                let mut value = vec![i / 100];
+                value.extend(std::iter::repeat_n(0xFF, 80));
+                value.push(i);
+                value
+            })
+            .collect::<Vec<_>>();
+        let binary = BinaryArray::from_iter_values(values.iter());
+        let schema = Arc::new(ArrowSchema::new(vec![Field::new(
+            "b",
+            DataType::Binary,
+            false,
+        )]));
+        let data = RecordBatch::try_new(schema, vec![Arc::new(binary)]).unwrap();
+        let dataset = write_legacy_dataset(data, 100).await;
+
+        let ctx = SessionContext::new();
+        for (predicate, pruned, num_rows) in [
+            // The first batch has a max bound of [0x01, 0x00, ...], so only
+            // the second batch can be ruled out.
+            (
+                col("b").lt(lit(ScalarValue::Binary(Some(vec![0x01])))),
+                1,
+                100,
+            ),
+            (
+                col("b").gt_eq(lit(ScalarValue::Binary(Some(vec![0x01])))),
+                0,
+                100,
+            ),
+            (
+                col("b").eq(lit(ScalarValue::Binary(Some(values[150].clone())))),
+                1,
+                1,
+            ),
+        ] {
+            assert_eq!(
+                num_pruned_batches(&dataset, predicate.clone()).await,
+                pruned,
+                "{predicate}"
+            );
+            let result = pushdown_scan(
+                &ctx,
+                dataset.clone(),
+                vec![0],
+                predicate.clone(),
+                ScanConfig::default(),
+            )
+            .await
+            .unwrap();
+            assert_eq!(result.num_rows(), num_rows, "{predicate}");
+        }
+    }
 }
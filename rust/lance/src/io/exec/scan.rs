@@ -27,10 +27,12 @@ use lance_arrow::SchemaExt;
 use lance_core::utils::tokio::get_num_compute_intensive_cpus;
 use lance_core::utils::tracing::StreamTracingExt;
 use lance_core::{Error, ROW_ADDR_FIELD, ROW_ID_FIELD};
+use lance_datafusion::exec::task_cancellation_token;
 use lance_file::reader::FileReaderOptions;
 use lance_io::scheduler::{ScanScheduler, SchedulerConfig};
 use lance_table::format::Fragment;
 use log::debug;
+use tokio_util::sync::CancellationToken;
 use tracing::Instrument;
 
 use crate::dataset::Dataset;
@@ -156,6 +158,7 @@ impl LanceStream {
     ///  - ***with_row_address***: load row address from the datasets.
     ///  - ***with_make_deletions_null***: make deletions null.
     ///  - ***scan_in_order***: whether to scan the fragments in the provided order.
+    ///  - ***cancellation***: cancels the I/O of the scan (v2 files only).
     #[allow(clippy::too_many_arguments)]
     pub fn try_new(
         dataset: Arc<Dataset>,
@@ -165,6 +168,7 @@ impl LanceStream {
         config: LanceScanConfig,
         metrics: &ExecutionPlanMetricsSet,
         partition: usize,
+        cancellation: Option<CancellationToken>,
     ) -> Result<Self> {
         let is_v2_scan = fragments
             .iter()
@@ -173,7 +177,14 @@ impl LanceStream {
             .unwrap_or(false);
         if is_v2_scan {
             Self::try_new_v2(
-                dataset, fragments, offsets, projection, config, metrics, partition,
+                dataset,
+                fragments,
+                offsets,
+                projection,
+                config,
+                metrics,
+                partition,
+                cancellation,
             )
         } else {
             Self::try_new_v1(dataset, fragments, projection, config, metrics, partition)
@@ -189,6 +200,7 @@ impl LanceStream {
         config: LanceScanConfig,
         metrics: &ExecutionPlanMetricsSet,
         partition: usize,
+        cancellation: Option<CancellationToken>,
     ) -> Result<Self> {
         let scan_metrics = ScanMetrics::new(metrics, partition);
         let timer = scan_metrics.baseline_metrics.elapsed_compute().timer();
@@ -268,9 +280,10 @@ impl LanceStream {
             file_fragments = filtered_fragments;
         }
 
-        let scan_scheduler = ScanScheduler::new(
+        let scan_scheduler = ScanScheduler::new_with_cancellation(
             dataset.object_store.clone(),
             SchedulerConfig::new(config.io_buffer_size),
+            cancellation,
         );
 
         let scan_scheduler_clone = scan_scheduler.clone();
@@ -690,7 +703,7 @@ impl ExecutionPlan for LanceScanExec {
     fn execute(
         &self,
         partition: usize,
-        _context: Arc<datafusion::execution::context::TaskContext>,
+        context: Arc<datafusion::execution::context::TaskContext>,
     ) -> Result<SendableRecordBatchStream> {
         let dataset = self.dataset.clone();
         let fragments = self.fragments.clone();
@@ -698,10 +711,18 @@ impl ExecutionPlan for LanceScanExec {
         let projection = self.projection.clone();
         let config = self.config.clone();
         let metrics = self.metrics.clone();
+        let cancellation = task_cancellation_token(&context);
 
         let lance_fut_stream = stream::once(async move {
             LanceStream::try_new(
-                dataset, fragments, range, projection, config, &metrics, partition,
+                dataset,
+                fragments,
+                range,
+                projection,
+                config,
+                &metrics,
+                partition,
+                cancellation,
             )
         });
         let lance_stream = lance_fut_stream.try_flatten();
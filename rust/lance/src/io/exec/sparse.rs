@@ -0,0 +1,397 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Execution nodes for sparse vector search, see [`crate::dataset::scanner::Scanner::nearest_sparse`]
+
+use std::sync::Arc;
+
+use arrow_array::{Float32Array, RecordBatch, UInt64Array};
+use datafusion::error::{DataFusionError, Result as DataFusionResult};
+use datafusion::execution::SendableRecordBatchStream;
+use datafusion::physical_plan::execution_plan::{Boundedness, EmissionType};
+use datafusion::physical_plan::metrics::{BaselineMetrics, ExecutionPlanMetricsSet, MetricsSet};
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::{DisplayAs, DisplayFormatType, ExecutionPlan, PlanProperties};
+use datafusion_physical_expr::{Distribution, EquivalenceProperties, Partitioning};
+use futures::{StreamExt, TryStreamExt, stream};
+use lance_arrow::sparse::SparseVectorArray;
+use lance_core::{Error, ROW_ID, Result};
+use lance_index::prefilter::PreFilter;
+use lance_index::scalar::inverted::FTS_SCHEMA;
+use lance_index::scalar::sparse::{SparseQuery, SparseTopK, SparseVectorIndex};
+use lance_table::format::IndexMetadata;
+use tracing::instrument;
+
+use super::PreFilterSource;
+use super::utils::{IndexMetrics, build_prefilter};
+use crate::Dataset;
+use crate::index::DatasetIndexInternalExt;
+
+/// A top-k dot-product search over a sparse vector column
+#[derive(Debug, Clone)]
+pub struct SparseSearch {
+    pub column: String,
+    pub query: SparseQuery,
+    pub k: usize,
+}
+
+impl std::fmt::Display for SparseSearch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "column={}, k={}, dims={}",
+            self.column,
+            self.k,
+            self.query.indices().len()
+        )
+    }
+}
+
+fn scores_batch(row_ids: Vec<u64>, scores: Vec<f32>) -> DataFusionResult<RecordBatch> {
+    Ok(RecordBatch::try_new(
+        FTS_SCHEMA.clone(),
+        vec![
+            Arc::new(UInt64Array::from(row_ids)),
+            Arc::new(Float32Array::from(scores)),
+        ],
+    )?)
+}
+
+/// Searches the segments of a sparse vector index
+///
+/// Outputs the `_rowid` and `_score` of the top k rows of all segments.
+#[derive(Debug)]
+pub struct SparseIndexSearchExec {
+    dataset: Arc<Dataset>,
+    search: SparseSearch,
+    segments: Vec<IndexMetadata>,
+    prefilter_source: PreFilterSource,
+
+    properties: Arc<PlanProperties>,
+    metrics: ExecutionPlanMetricsSet,
+}
+
+impl DisplayAs for SparseIndexSearchExec {
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default | DisplayFormatType::Verbose => {
+                write!(f, "SparseIndexSearch: {}", self.search)
+            }
+            DisplayFormatType::TreeRender => {
+                write!(
+                    f,
+                    "SparseIndexSearch\ncolumn={}\nk={}",
+                    self.search.column, self.search.k
+                )
+            }
+        }
+    }
+}
+
+impl SparseIndexSearchExec {
+    pub fn new(
+        dataset: Arc<Dataset>,
+        search: SparseSearch,
+        segments: Vec<IndexMetadata>,
+        prefilter_source: PreFilterSource,
+    ) -> Self {
+        let properties = Arc::new(PlanProperties::new(
+            EquivalenceProperties::new(FTS_SCHEMA.clone()),
+            Partitioning::RoundRobinBatch(1),
+            EmissionType::Final,
+            Boundedness::Bounded,
+        ));
+        Self {
+            dataset,
+            search,
+            segments,
+            prefilter_source,
+            properties,
+            metrics: ExecutionPlanMetricsSet::new(),
+        }
+    }
+
+    pub fn search(&self) -> &SparseSearch {
+        &self.search
+    }
+}
+
+async fn open_sparse_segment(
+    dataset: &Dataset,
+    column: &str,
+    segment: &IndexMetadata,
+    metrics: &IndexMetrics,
+) -> Result<Arc<dyn lance_index::scalar::ScalarIndex>> {
+    let uuid = segment.uuid.to_string();
+    let index = dataset.open_scalar_index(column, &uuid, metrics).await?;
+    if index.as_any().downcast_ref::<SparseVectorIndex>().is_none() {
+        return Err(Error::invalid_input(format!(
+            "Index {} on column {} is not a sparse vector index",
+            segment.name, column
+        )));
+    }
+    Ok(index)
+}
+
+impl ExecutionPlan for SparseIndexSearchExec {
+    fn name(&self) -> &str {
+        "SparseIndexSearchExec"
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        match &self.prefilter_source {
+            PreFilterSource::None => vec![],
+            PreFilterSource::FilteredRowIds(src) => vec![src],
+            PreFilterSource::ScalarIndexQuery(src) => vec![src],
+        }
+    }
+
+    fn required_input_distribution(&self) -> Vec<Distribution> {
+        // Prefilter inputs must be a single partition
+        self.children()
+            .iter()
+            .map(|_| Distribution::SinglePartition)
+            .collect()
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        mut children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        let prefilter_source = match (&self.prefilter_source, children.pop()) {
+            (PreFilterSource::None, None) => PreFilterSource::None,
+            (PreFilterSource::FilteredRowIds(_), Some(src)) if children.is_empty() => {
+                PreFilterSource::FilteredRowIds(src)
+            }
+            (PreFilterSource::ScalarIndexQuery(_), Some(src)) if children.is_empty() => {
+                PreFilterSource::ScalarIndexQuery(src)
+            }
+            _ => {
+                return Err(DataFusionError::Internal(
+                    "Unexpected children for SparseIndexSearchExec".to_string(),
+                ));
+            }
+        };
+        Ok(Arc::new(Self {
+            dataset: self.dataset.clone(),
+            search: self.search.clone(),
+            segments: self.segments.clone(),
+            prefilter_source,
+            properties: self.properties.clone(),
+            metrics: ExecutionPlanMetricsSet::new(),
+        }))
+    }
+
+    #[instrument(name = "sparse_index_search_exec", level = "debug", skip_all)]
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<datafusion::execution::TaskContext>,
+    ) -> DataFusionResult<SendableRecordBatchStream> {
+        let ds = self.dataset.clone();
+        let search = self.search.clone();
+        let segments = self.segments.clone();
+        let index_metrics = IndexMetrics::new(&self.metrics, partition);
+        let baseline_metrics = BaselineMetrics::new(&self.metrics, partition);
+        let pre_filter = build_prefilter(
+            context,
+            partition,
+            &self.prefilter_source,
+            ds.clone(),
+            &segments,
+        )?;
+
+        let stream = stream::once(async move {
+            let indices =
+                futures::future::try_join_all(segments.iter().map(|segment| {
+                    open_sparse_segment(&ds, &search.column, segment, &index_metrics)
+                }))
+                .await?;
+            pre_filter.wait_for_ready().await?;
+
+            let _timer = baseline_metrics.elapsed_compute().timer();
+            let mut top_k = SparseTopK::new(search.k);
+            for index in &indices {
+                let index = index
+                    .as_any()
+                    .downcast_ref::<SparseVectorIndex>()
+                    .expect("checked when opened");
+                let (row_ids, scores) =
+                    index.search(&search.query, search.k, pre_filter.as_ref(), &index_metrics)?;
+                for (row_id, score) in row_ids.into_iter().zip(scores) {
+                    top_k.push(row_id, score);
+                }
+            }
+            let (row_ids, scores) = top_k.into_sorted();
+            baseline_metrics.record_output(row_ids.len());
+            scores_batch(row_ids, scores)
+        });
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.schema(),
+            stream.boxed(),
+        )))
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+
+    fn properties(&self) -> &Arc<PlanProperties> {
+        &self.properties
+    }
+
+    fn supports_limit_pushdown(&self) -> bool {
+        false
+    }
+}
+
+/// Scores the sparse vectors of its input against the query
+///
+/// The input must have the sparse vector column and `_rowid`.  Outputs the
+/// `_rowid` and `_score` of every row with a positive score, the caller keeps
+/// the top k.
+#[derive(Debug)]
+pub struct SparseFlatSearchExec {
+    search: SparseSearch,
+    input: Arc<dyn ExecutionPlan>,
+
+    properties: Arc<PlanProperties>,
+    metrics: ExecutionPlanMetricsSet,
+}
+
+impl DisplayAs for SparseFlatSearchExec {
+    fn fmt_as(&self, t: DisplayFormatType, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match t {
+            DisplayFormatType::Default | DisplayFormatType::Verbose => {
+                write!(f, "SparseFlatSearch: {}", self.search)
+            }
+            DisplayFormatType::TreeRender => {
+                write!(
+                    f,
+                    "SparseFlatSearch\ncolumn={}\nk={}",
+                    self.search.column, self.search.k
+                )
+            }
+        }
+    }
+}
+
+impl SparseFlatSearchExec {
+    pub fn new(search: SparseSearch, input: Arc<dyn ExecutionPlan>) -> Self {
+        let properties = Arc::new(PlanProperties::new(
+            EquivalenceProperties::new(FTS_SCHEMA.clone()),
+            Partitioning::UnknownPartitioning(input.output_partitioning().partition_count()),
+            EmissionType::Incremental,
+            Boundedness::Bounded,
+        ));
+        Self {
+            search,
+            input,
+            properties,
+            metrics: ExecutionPlanMetricsSet::new(),
+        }
+    }
+
+    pub fn search(&self) -> &SparseSearch {
+        &self.search
+    }
+
+    fn score_batch(search: &SparseSearch, batch: &RecordBatch) -> Result<RecordBatch> {
+        let vectors = batch.column_by_name(&search.column).ok_or_else(|| {
+            Error::internal(format!(
+                "Sparse vector column {} missing from search input",
+                search.column
+            ))
+        })?;
+        let vectors = SparseVectorArray::try_from(vectors.as_ref())?;
+        let row_ids = batch
+            .column_by_name(ROW_ID)
+            .ok_or_else(|| Error::internal("Row ids missing from sparse vector search input"))?
+            .as_any()
+            .downcast_ref::<UInt64Array>()
+            .ok_or_else(|| Error::internal("Row ids of sparse vector search input are not u64"))?;
+
+        let (row_ids, scores): (Vec<u64>, Vec<f32>) = row_ids
+            .values()
+            .iter()
+            .zip(vectors.iter())
+            .filter_map(|(row_id, vector)| {
+                let (indices, values) = vector?;
+                let score = search.query.dot(indices, values);
+                (score > 0.0).then_some((*row_id, score))
+            })
+            .unzip();
+        Ok(scores_batch(row_ids, scores)?)
+    }
+}
+
+impl ExecutionPlan for SparseFlatSearchExec {
+    fn name(&self) -> &str {
+        "SparseFlatSearchExec"
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn children(&self) -> Vec<&Arc<dyn ExecutionPlan>> {
+        vec![&self.input]
+    }
+
+    fn with_new_children(
+        self: Arc<Self>,
+        mut children: Vec<Arc<dyn ExecutionPlan>>,
+    ) -> DataFusionResult<Arc<dyn ExecutionPlan>> {
+        if children.len() != 1 {
+            return Err(DataFusionError::Internal(
+                "Unexpected number of children".to_string(),
+            ));
+        }
+        Ok(Arc::new(Self::new(
+            self.search.clone(),
+            children.pop().unwrap(),
+        )))
+    }
+
+    #[instrument(name = "sparse_flat_search_exec", level = "debug", skip_all)]
+    fn execute(
+        &self,
+        partition: usize,
+        context: Arc<datafusion::execution::TaskContext>,
+    ) -> DataFusionResult<SendableRecordBatchStream> {
+        let search = self.search.clone();
+        let baseline_metrics = BaselineMetrics::new(&self.metrics, partition);
+        let stream = self
+            .input
+            .execute(partition, context)?
+            .map(move |batch| {
+                let _timer = baseline_metrics.elapsed_compute().timer();
+                let batch = Self::score_batch(&search, &batch?)?;
+                baseline_metrics.record_output(batch.num_rows());
+                Ok::<_, DataFusionError>(batch)
+            })
+            .try_filter(|batch| futures::future::ready(batch.num_rows() > 0));
+
+        Ok(Box::pin(RecordBatchStreamAdapter::new(
+            self.schema(),
+            stream.boxed(),
+        )))
+    }
+
+    fn metrics(&self) -> Option<MetricsSet> {
+        Some(self.metrics.clone_inner())
+    }
+
+    fn properties(&self) -> &Arc<PlanProperties> {
+        &self.properties
+    }
+
+    fn supports_limit_pushdown(&self) -> bool {
+        false
+    }
+}
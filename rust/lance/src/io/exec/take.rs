@@ -29,6 +29,7 @@ use lance_core::error::{DataFusionResult, LanceOptionExt};
 use lance_core::utils::address::RowAddress;
 use lance_core::utils::tokio::get_num_compute_intensive_cpus;
 use lance_core::{ROW_ADDR, ROW_ID};
+use lance_datafusion::exec::task_cancellation_token;
 use lance_io::scheduler::{ScanScheduler, SchedulerConfig};
 use tracing::error;
 
@@ -133,7 +134,9 @@ impl TakeStream {
             fragment
                 .open(
                     &self.fields_to_take,
-                    FragReadConfig::default().with_scan_scheduler(self.scan_scheduler.clone()),
+                    FragReadConfig::default()
+                        .with_scan_scheduler(self.scan_scheduler.clone())
+                        .with_coalesce_page_reads(true),
                 )
                 .await?,
         );
@@ -608,6 +611,7 @@ impl ExecutionPlan for TakeExec {
         partition: usize,
         context: Arc<datafusion::execution::context::TaskContext>,
     ) -> Result<SendableRecordBatchStream> {
+        let cancellation = task_cancellation_token(&context);
         let input_stream = self.input.execute(partition, context)?;
         let dataset = self.dataset.clone();
         let schema_to_take = self.schema_to_take.clone();
@@ -621,7 +625,8 @@ impl ExecutionPlan for TakeExec {
             let obj_store = dataset.object_store.clone();
             let scheduler_config = SchedulerConfig::max_bandwidth(&obj_store);
             // unwrap is safe since SchedulerConfig::max_bandwidth is always valid
-            let scan_scheduler = ScanScheduler::new(obj_store, scheduler_config);
+            let scan_scheduler =
+                ScanScheduler::new_with_cancellation(obj_store, scheduler_config, cancellation);
 
             let take_stream = Arc::new(TakeStream::new(
                 dataset,
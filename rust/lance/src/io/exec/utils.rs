@@ -32,6 +32,7 @@ use lance_index::prefilter::FilterLoader;
 use lance_select::{RowAddrMask, RowAddrTreeMap, result::IndexExprResult};
 use std::future::Future;
 
+use super::scalar_index::{MaterializeIndexExec, ScalarIndexExec};
 use crate::Dataset;
 use crate::index::prefilter::DatasetPreFilter;
 
@@ -45,6 +46,27 @@ pub enum PreFilterSource {
     None,
 }
 
+impl PreFilterSource {
+    /// How the allowed rows are found, for display in plans.
+    ///
+    /// `index-only` answers the filter from scalar indices alone. `index+scan` uses the
+    /// indices where they apply and reads rows for the fragments or the parts of the filter
+    /// they don't cover. `scan` reads the filter columns for every row.
+    pub fn mode(&self) -> Option<&'static str> {
+        fn uses_index(plan: &Arc<dyn ExecutionPlan>) -> bool {
+            plan.as_any().is::<ScalarIndexExec>()
+                || plan.as_any().is::<MaterializeIndexExec>()
+                || plan.children().into_iter().any(uses_index)
+        }
+        match self {
+            Self::FilteredRowIds(plan) if uses_index(plan) => Some("index+scan"),
+            Self::FilteredRowIds(_) => Some("scan"),
+            Self::ScalarIndexQuery(_) => Some("index-only"),
+            Self::None => None,
+        }
+    }
+}
+
 pub(crate) fn build_prefilter(
     context: Arc<datafusion::execution::TaskContext>,
     partition: usize,
@@ -79,6 +79,8 @@ pub mod arrow;
 pub mod blob;
 pub mod datafusion;
 pub mod dataset;
+#[cfg(any(feature = "parquet", feature = "json", feature = "csv"))]
+pub mod import;
 pub mod index;
 pub mod io;
 pub mod session;
@@ -4,19 +4,27 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use arrow_array::RecordBatchIterator;
+use arrow_schema::SchemaRef;
 use deepsize::DeepSizeOf;
 use lance_core::cache::{CacheBackend, LanceCache};
+use lance_core::utils::encryption::KeyProvider;
 use lance_core::{Error, Result};
+use lance_encoding::buffer_pool::BufferPool;
+use lance_encoding::encodings::physical::custom::CustomEncodingRegistry;
 use lance_index::IndexType;
 use lance_io::object_store::ObjectStoreRegistry;
 
-use crate::dataset::{DEFAULT_INDEX_CACHE_SIZE, DEFAULT_METADATA_CACHE_SIZE};
+use crate::Dataset;
+use crate::dataset::{DEFAULT_INDEX_CACHE_SIZE, DEFAULT_METADATA_CACHE_SIZE, WriteParams};
 use crate::session::caches::GlobalMetadataCache;
 use crate::session::index_caches::GlobalIndexCache;
 
+use self::commit_listener::CommitListener;
 use self::index_extension::IndexExtension;
 
 pub(crate) mod caches;
+pub mod commit_listener;
 pub mod index_caches;
 pub(crate) mod index_extension;
 
@@ -53,6 +61,24 @@ pub struct Session {
     pub(crate) index_extensions: HashMap<(IndexType, String), Arc<dyn IndexExtension>>,
 
     store_registry: Arc<ObjectStoreRegistry>,
+
+    /// See [`Session::with_max_scan_memory_bytes`]
+    max_scan_memory_bytes: Option<u64>,
+
+    /// See [`Session::with_compute_parallelism`]
+    compute_parallelism: Option<usize>,
+
+    /// See [`Session::with_decode_buffer_pool`]
+    decode_buffer_pool: Option<Arc<BufferPool>>,
+
+    /// See [`Session::with_commit_listener`]
+    pub(crate) commit_listeners: Vec<Arc<dyn CommitListener>>,
+
+    /// See [`Session::with_key_provider`]
+    key_provider: Option<Arc<dyn KeyProvider>>,
+
+    /// See [`Session::with_custom_encodings`]
+    custom_encodings: Option<CustomEncodingRegistry>,
 }
 
 impl DeepSizeOf for Session {
@@ -83,6 +109,12 @@ impl std::fmt::Debug for Session {
                 "index_extensions",
                 &self.index_extensions.keys().collect::<Vec<_>>(),
             )
+            .field("max_scan_memory_bytes", &self.max_scan_memory_bytes)
+            .field("compute_parallelism", &self.compute_parallelism)
+            .field("decode_buffer_pool", &self.decode_buffer_pool)
+            .field("commit_listeners", &self.commit_listeners)
+            .field("key_provider", &self.key_provider)
+            .field("custom_encodings", &self.custom_encodings)
             .finish()
     }
 }
@@ -103,10 +135,20 @@ impl Session {
         store_registry: Arc<ObjectStoreRegistry>,
     ) -> Self {
         Self {
-            index_cache: GlobalIndexCache(LanceCache::with_capacity(index_cache_size)),
-            metadata_cache: GlobalMetadataCache(LanceCache::with_capacity(metadata_cache_size)),
+            index_cache: GlobalIndexCache(
+                LanceCache::with_capacity(index_cache_size).with_metrics("index"),
+            ),
+            metadata_cache: GlobalMetadataCache(
+                LanceCache::with_capacity(metadata_cache_size).with_metrics("metadata"),
+            ),
             index_extensions: HashMap::new(),
             store_registry,
+            max_scan_memory_bytes: None,
+            compute_parallelism: None,
+            decode_buffer_pool: None,
+            commit_listeners: Vec::new(),
+            key_provider: None,
+            custom_encodings: None,
         }
     }
 
@@ -120,13 +162,117 @@ impl Session {
         store_registry: Arc<ObjectStoreRegistry>,
     ) -> Self {
         Self {
-            index_cache: GlobalIndexCache(LanceCache::with_backend(index_cache_backend)),
-            metadata_cache: GlobalMetadataCache(LanceCache::with_capacity(metadata_cache_size)),
+            index_cache: GlobalIndexCache(
+                LanceCache::with_backend(index_cache_backend).with_metrics("index"),
+            ),
+            metadata_cache: GlobalMetadataCache(
+                LanceCache::with_capacity(metadata_cache_size).with_metrics("metadata"),
+            ),
             index_extensions: HashMap::new(),
             store_registry,
+            max_scan_memory_bytes: None,
+            compute_parallelism: None,
+            decode_buffer_pool: None,
+            commit_listeners: Vec::new(),
+            key_provider: None,
+            custom_encodings: None,
         }
     }
 
+    /// Cap the memory a single scan may buffer.
+    ///
+    /// This bounds the bytes of read-ahead I/O that a scan queues up before the data is
+    /// decoded.  When the budget is full the scan pauses I/O until the buffered data is
+    /// consumed.  A scan whose batches alone would not fit in the budget fails to plan.
+    ///
+    /// Applies to every scan of datasets opened with this session and can be overridden
+    /// with [`crate::dataset::scanner::Scanner::max_scan_memory_bytes`].
+    pub fn with_max_scan_memory_bytes(mut self, bytes: u64) -> Self {
+        self.max_scan_memory_bytes = Some(bytes);
+        self
+    }
+
+    /// Cap the number of CPU threads a single scan or index build may keep busy.
+    ///
+    /// This limits the number of batches decoded concurrently by a scan, and the number of
+    /// threads used for kmeans training and shuffling when building an index.
+    ///
+    /// Applies to every dataset opened with this session.  Scans can override it with
+    /// [`crate::dataset::scanner::Scanner::compute_parallelism`].
+    pub fn with_compute_parallelism(mut self, num_threads: usize) -> Self {
+        self.compute_parallelism = Some(num_threads.max(1));
+        self
+    }
+
+    /// Reuse the buffers of dropped batches when decoding, keeping at most `max_bytes` of
+    /// idle buffers around.
+    ///
+    /// This saves allocating (and zeroing) fresh buffers for every decoded batch, which
+    /// matters most for scans of many columns.  The pool is shared by every scan of
+    /// datasets opened with this session.  See [`lance_encoding::buffer_pool`].
+    pub fn with_decode_buffer_pool(mut self, max_bytes: u64) -> Self {
+        self.decode_buffer_pool = Some(Arc::new(BufferPool::new(max_bytes)));
+        self
+    }
+
+    /// Notify `listener` of every version committed by datasets using this session.
+    ///
+    /// Listeners run in the background after the commit succeeds, see
+    /// [`commit_listener::CommitListener`].
+    pub fn with_commit_listener(mut self, listener: Arc<dyn CommitListener>) -> Self {
+        self.commit_listeners.push(listener);
+        self
+    }
+
+    /// Look up the keys of encrypted columns with `key_provider`.
+    ///
+    /// Columns are encrypted by setting the id of their key under
+    /// [`lance_core::utils::encryption::ENCRYPTION_KEY_ID_META_KEY`] in the field
+    /// metadata.  The keys are never stored in the dataset, so datasets opened with
+    /// this session can only read encrypted columns whose key the provider has.  A
+    /// key provider in [`lance_io::object_store::ObjectStoreParams`] takes precedence.
+    pub fn with_key_provider(mut self, key_provider: Arc<dyn KeyProvider>) -> Self {
+        self.key_provider = Some(key_provider);
+        self
+    }
+
+    /// Make the custom codecs of `custom_encodings` available to datasets using this
+    /// session.
+    ///
+    /// Fields select a codec by setting its id under
+    /// [`lance_encoding::constants::CUSTOM_ENCODING_META_KEY`] in the field metadata.
+    /// Writing such a field requires data storage version 2.3 or later, and reading it
+    /// requires a session with the same codec registered.
+    pub fn with_custom_encodings(mut self, custom_encodings: CustomEncodingRegistry) -> Self {
+        self.custom_encodings = Some(custom_encodings);
+        self
+    }
+
+    /// The scan memory budget set by [`Self::with_max_scan_memory_bytes`], if any
+    pub fn max_scan_memory_bytes(&self) -> Option<u64> {
+        self.max_scan_memory_bytes
+    }
+
+    /// The CPU thread limit set by [`Self::with_compute_parallelism`], if any
+    pub fn compute_parallelism(&self) -> Option<usize> {
+        self.compute_parallelism
+    }
+
+    /// The pool set by [`Self::with_decode_buffer_pool`], if any
+    pub fn decode_buffer_pool(&self) -> Option<&Arc<BufferPool>> {
+        self.decode_buffer_pool.as_ref()
+    }
+
+    /// The key provider set by [`Self::with_key_provider`], if any
+    pub fn key_provider(&self) -> Option<&Arc<dyn KeyProvider>> {
+        self.key_provider.as_ref()
+    }
+
+    /// The codecs set by [`Self::with_custom_encodings`], if any
+    pub fn custom_encodings(&self) -> Option<&CustomEncodingRegistry> {
+        self.custom_encodings.as_ref()
+    }
+
     /// Register a new index extension.
     ///
     /// A name can only be registered once per type of index extension.
@@ -195,6 +341,27 @@ impl Session {
         self.store_registry.clone()
     }
 
+    /// Create an empty dataset with `schema` under a unique `memory://` URI.
+    ///
+    /// The `memory://` store is shared by everything using the object store
+    /// registry of this session, so the dataset can be reopened, indexed,
+    /// compacted and cleaned up through other handles opened with this
+    /// session, while other sessions do not see it. Its data is freed along
+    /// with the registry.
+    pub async fn temp_dataset(self: &Arc<Self>, schema: SchemaRef) -> Result<Dataset> {
+        let uri = format!("memory://temp/{}", uuid::Uuid::new_v4());
+        let params = WriteParams {
+            session: Some(self.clone()),
+            ..Default::default()
+        };
+        Dataset::write(
+            RecordBatchIterator::new(vec![], schema),
+            uri.as_str(),
+            Some(params),
+        )
+        .await
+    }
+
     /// Get a reference to the raw metadata cache (for use in index reconstruction).
     pub fn file_metadata_cache(&self) -> &LanceCache {
         &self.metadata_cache.0
@@ -224,7 +391,15 @@ impl Default for Session {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::dataset::builder::DatasetBuilder;
+    use crate::dataset::cleanup::CleanupPolicyBuilder;
+    use crate::dataset::optimize::{CompactionOptions, compact_files};
+    use crate::index::DatasetIndexExt;
+    use arrow_array::{Int32Array, RecordBatch};
+    use arrow_schema::{DataType, Field, Schema as ArrowSchema};
+    use chrono::Utc;
     use lance_core::cache::UnsizedCacheKey;
+    use lance_index::scalar::ScalarIndexParams;
     use lance_index::vector::VectorIndex;
     use std::borrow::Cow;
 
@@ -251,4 +426,88 @@ mod tests {
                 .is_none()
         );
     }
+
+    #[tokio::test]
+    async fn test_temp_dataset_lifecycle() {
+        let session = Arc::new(Session::default());
+        let schema = Arc::new(ArrowSchema::new(vec![Field::new(
+            "x",
+            DataType::Int32,
+            false,
+        )]));
+        let mut dataset = session.temp_dataset(schema.clone()).await.unwrap();
+        assert!(dataset.uri().starts_with("memory://"));
+        let other_temp = session.temp_dataset(schema.clone()).await.unwrap();
+        assert_ne!(other_temp.uri(), dataset.uri());
+
+        for start in [0, 100] {
+            let batch = RecordBatch::try_new(
+                schema.clone(),
+                vec![Arc::new(Int32Array::from_iter_values(start..start + 100))],
+            )
+            .unwrap();
+            dataset
+                .append(RecordBatchIterator::new([Ok(batch)], schema.clone()), None)
+                .await
+                .unwrap();
+        }
+        dataset
+            .create_index(
+                &["x"],
+                IndexType::BTree,
+                None,
+                &ScalarIndexParams::default(),
+                true,
+            )
+            .await
+            .unwrap();
+
+        // A second handle from the same session sees the first one's writes
+        // and maintains the dataset.
+        let mut other = DatasetBuilder::from_uri(dataset.uri())
+            .with_session(session.clone())
+            .load()
+            .await
+            .unwrap();
+        assert_eq!(other.count_rows(None).await.unwrap(), 200);
+        assert_eq!(other.get_fragments().len(), 2);
+        compact_files(&mut other, CompactionOptions::default(), None)
+            .await
+            .unwrap();
+        let version = other.version().version;
+        other.tags().create("compacted", version).await.unwrap();
+        let stats = other
+            .cleanup_with_policy(
+                CleanupPolicyBuilder::default()
+                    .before_timestamp(Utc::now())
+                    .delete_unverified(true)
+                    .build(),
+            )
+            .await
+            .unwrap();
+        assert!(stats.old_versions > 0);
+        assert!(stats.data_files_removed > 0);
+
+        // Back on the first handle.
+        dataset.checkout_latest().await.unwrap();
+        assert_eq!(dataset.version().version, version);
+        assert_eq!(dataset.get_fragments().len(), 1);
+        assert_eq!(dataset.load_indices().await.unwrap().len(), 1);
+        assert_eq!(
+            dataset
+                .count_rows(Some("x >= 150".to_string()))
+                .await
+                .unwrap(),
+            50
+        );
+        let tagged = dataset.checkout_version("compacted").await.unwrap();
+        assert_eq!(tagged.version().version, version);
+
+        // Other sessions have their own memory store.
+        let isolated = DatasetBuilder::from_uri(dataset.uri())
+            .with_session(Arc::new(Session::default()))
+            .load()
+            .await;
+        assert!(isolated.is_err());
+    }
 }
@@ -0,0 +1,369 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Notifications for new dataset versions.
+//!
+//! A [`CommitListener`] registered on a [`Session`] (or through
+//! [`crate::dataset::builder::DatasetBuilder::with_commit_listener`]) is told about every
+//! version committed by datasets using that session.  This can be used to invalidate
+//! downstream caches or to notify other services without polling for new versions.
+
+use std::collections::HashMap;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+
+use futures::FutureExt;
+use lance_table::format::{Fragment, Manifest};
+use tracing::warn;
+
+use crate::dataset::transaction::Operation;
+use crate::session::Session;
+
+/// Receives a [`CommitEvent`] after each successful commit.
+///
+/// Listeners are invoked on a background task once the new version is visible.  They
+/// cannot delay or fail the commit, and a listener that panics does not affect other
+/// listeners.  Detached commits are not reported.
+#[async_trait::async_trait]
+pub trait CommitListener: Send + Sync + std::fmt::Debug {
+    async fn on_commit(&self, event: &CommitEvent);
+}
+
+/// A newly committed version of a dataset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommitEvent {
+    /// The URI of the dataset
+    pub uri: String,
+    /// The version that was committed
+    pub version: u64,
+    /// The name of the committed operation, e.g. "Append" or "Delete"
+    pub operation: String,
+    /// What the commit changed
+    pub summary: CommitSummary,
+}
+
+/// Rows and fragments changed by a commit.
+///
+/// Rewrites such as compaction move rows between fragments without adding or deleting
+/// any, so they only report fragment changes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommitSummary {
+    pub rows_added: u64,
+    pub rows_deleted: u64,
+    pub fragments_added: u64,
+    pub fragments_removed: u64,
+    /// Fragments that were kept but modified, e.g. given new deletions or columns
+    pub fragments_updated: u64,
+}
+
+fn live_rows(fragment: &Fragment) -> u64 {
+    fragment.num_rows().unwrap_or_default() as u64
+}
+
+impl CommitSummary {
+    /// Summarize `operation`, which was committed on top of `previous` (`None` for a
+    /// new dataset) and produced `manifest`.
+    pub(crate) fn new(
+        operation: &Operation,
+        previous: Option<&Manifest>,
+        manifest: &Manifest,
+    ) -> Self {
+        let previous_rows: HashMap<u64, u64> = previous
+            .map(|manifest| {
+                manifest
+                    .fragments
+                    .iter()
+                    .map(|frag| (frag.id, live_rows(frag)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let rows_before = |id: u64| previous_rows.get(&id).copied().unwrap_or_default();
+        let rows_deleted = |updated: &[Fragment], removed: &[u64]| {
+            updated
+                .iter()
+                .map(|frag| rows_before(frag.id).saturating_sub(live_rows(frag)))
+                .chain(removed.iter().map(|id| rows_before(*id)))
+                .sum()
+        };
+
+        match operation {
+            Operation::Append { fragments } => Self {
+                rows_added: fragments.iter().map(live_rows).sum(),
+                fragments_added: fragments.len() as u64,
+                ..Default::default()
+            },
+            Operation::Delete {
+                updated_fragments,
+                deleted_fragment_ids,
+                ..
+            } => Self {
+                rows_deleted: rows_deleted(updated_fragments, deleted_fragment_ids),
+                fragments_removed: deleted_fragment_ids.len() as u64,
+                fragments_updated: updated_fragments.len() as u64,
+                ..Default::default()
+            },
+            Operation::Update {
+                removed_fragment_ids,
+                updated_fragments,
+                new_fragments,
+                ..
+            } => Self {
+                rows_added: new_fragments.iter().map(live_rows).sum(),
+                rows_deleted: rows_deleted(updated_fragments, removed_fragment_ids),
+                fragments_added: new_fragments.len() as u64,
+                fragments_removed: removed_fragment_ids.len() as u64,
+                fragments_updated: updated_fragments.len() as u64,
+            },
+            Operation::Overwrite { fragments, .. } => Self {
+                rows_added: fragments.iter().map(live_rows).sum(),
+                rows_deleted: previous_rows.values().sum(),
+                fragments_added: fragments.len() as u64,
+                fragments_removed: previous_rows.len() as u64,
+                fragments_updated: 0,
+            },
+            Operation::Rewrite { groups, .. } => Self {
+                fragments_added: groups.iter().map(|g| g.new_fragments.len() as u64).sum(),
+                fragments_removed: groups.iter().map(|g| g.old_fragments.len() as u64).sum(),
+                ..Default::default()
+            },
+            Operation::Merge { fragments, .. } => Self {
+                fragments_updated: fragments.len() as u64,
+                ..Default::default()
+            },
+            Operation::Restore { .. } => {
+                let rows_before: u64 = previous_rows.values().sum();
+                let rows_after: u64 = manifest.fragments.iter().map(live_rows).sum();
+                Self {
+                    rows_added: rows_after.saturating_sub(rows_before),
+                    rows_deleted: rows_before.saturating_sub(rows_after),
+                    fragments_added: manifest
+                        .fragments
+                        .iter()
+                        .filter(|frag| !previous_rows.contains_key(&frag.id))
+                        .count() as u64,
+                    fragments_removed: previous_rows
+                        .keys()
+                        .filter(|id| !manifest.fragments.iter().any(|frag| frag.id == **id))
+                        .count() as u64,
+                    fragments_updated: 0,
+                }
+            }
+            _ => Self::default(),
+        }
+    }
+}
+
+/// Tell the listeners of `session` that `manifest` was committed.
+///
+/// Each listener runs on its own task so a slow or panicking listener cannot hold up
+/// the commit or the other listeners.
+pub(crate) fn notify_commit_listeners(
+    session: &Session,
+    uri: &str,
+    operation: &Operation,
+    previous: Option<&Manifest>,
+    manifest: &Manifest,
+) {
+    if session.commit_listeners.is_empty() {
+        return;
+    }
+    let event = Arc::new(CommitEvent {
+        uri: uri.to_string(),
+        version: manifest.version,
+        operation: operation.name().to_string(),
+        summary: CommitSummary::new(operation, previous, manifest),
+    });
+    for listener in session.commit_listeners.iter().cloned() {
+        let event = event.clone();
+        tokio::spawn(async move {
+            if AssertUnwindSafe(listener.on_commit(&event))
+                .catch_unwind()
+                .await
+                .is_err()
+            {
+                warn!(
+                    "Commit listener {:?} panicked handling version {} of {}",
+                    listener, event.version, event.uri
+                );
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use arrow_array::RecordBatchReader;
+    use arrow_array::types::Int32Type;
+    use lance_datagen::{BatchCount, RowCount, array};
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::Dataset;
+    use crate::dataset::optimize::compact_files;
+    use crate::dataset::{InsertBuilder, WriteMode, WriteParams};
+
+    #[derive(Debug)]
+    struct RecordingListener(mpsc::UnboundedSender<CommitEvent>);
+
+    #[async_trait::async_trait]
+    impl CommitListener for RecordingListener {
+        async fn on_commit(&self, event: &CommitEvent) {
+            self.0.send(event.clone()).unwrap();
+        }
+    }
+
+    #[derive(Debug)]
+    struct PanickingListener;
+
+    #[async_trait::async_trait]
+    impl CommitListener for PanickingListener {
+        async fn on_commit(&self, _event: &CommitEvent) {
+            panic!("listener failure");
+        }
+    }
+
+    fn recording_listener() -> (
+        Arc<dyn CommitListener>,
+        mpsc::UnboundedReceiver<CommitEvent>,
+    ) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (Arc::new(RecordingListener(tx)), rx)
+    }
+
+    async fn next_event(rx: &mut mpsc::UnboundedReceiver<CommitEvent>) -> CommitEvent {
+        tokio::time::timeout(Duration::from_secs(10), rx.recv())
+            .await
+            .expect("listener was not invoked")
+            .unwrap()
+    }
+
+    fn rows(num_rows: u64) -> Box<dyn RecordBatchReader + Send> {
+        Box::new(
+            lance_datagen::gen_batch()
+                .col("i", array::step::<Int32Type>())
+                .into_reader_rows(RowCount::from(num_rows), BatchCount::from(1)),
+        )
+    }
+
+    async fn write(uri: &str, session: Arc<Session>, num_rows: u64, mode: WriteMode) -> Dataset {
+        InsertBuilder::new(uri)
+            .with_params(&WriteParams {
+                session: Some(session),
+                mode,
+                max_rows_per_file: 100,
+                ..Default::default()
+            })
+            .execute_stream(rows(num_rows))
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_listener_summaries() {
+        let (listener, mut rx) = recording_listener();
+        let session = Arc::new(Session::default().with_commit_listener(listener));
+
+        write(
+            "memory://summaries",
+            session.clone(),
+            200,
+            WriteMode::Create,
+        )
+        .await;
+        let event = next_event(&mut rx).await;
+        assert_eq!(event.version, 1);
+        assert_eq!(event.operation, "Overwrite");
+        assert_eq!(
+            event.summary,
+            CommitSummary {
+                rows_added: 200,
+                fragments_added: 2,
+                ..Default::default()
+            }
+        );
+
+        let mut dataset = write(
+            "memory://summaries",
+            session.clone(),
+            100,
+            WriteMode::Append,
+        )
+        .await;
+        let event = next_event(&mut rx).await;
+        assert_eq!(event.version, 2);
+        assert_eq!(event.operation, "Append");
+        assert_eq!(
+            event.summary,
+            CommitSummary {
+                rows_added: 100,
+                fragments_added: 1,
+                ..Default::default()
+            }
+        );
+
+        // Both the first and the appended fragment hold rows with i < 50
+        dataset.delete("i < 50").await.unwrap();
+        let event = next_event(&mut rx).await;
+        assert_eq!(event.version, 3);
+        assert_eq!(event.operation, "Delete");
+        assert_eq!(
+            event.summary,
+            CommitSummary {
+                rows_deleted: 100,
+                fragments_updated: 2,
+                ..Default::default()
+            }
+        );
+
+        compact_files(&mut dataset, Default::default(), None)
+            .await
+            .unwrap();
+        // Compaction may first commit a version reserving fragment ids
+        let mut event = next_event(&mut rx).await;
+        if event.operation == "ReserveFragments" {
+            assert_eq!(event.summary, CommitSummary::default());
+            event = next_event(&mut rx).await;
+        }
+        assert_eq!(event.version, dataset.version().version);
+        assert_eq!(event.operation, "Rewrite");
+        assert_eq!(
+            event.summary,
+            CommitSummary {
+                fragments_added: 1,
+                fragments_removed: 3,
+                ..Default::default()
+            }
+        );
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_panicking_listener() {
+        let (first, mut first_rx) = recording_listener();
+        let (second, mut second_rx) = recording_listener();
+        let session = Arc::new(
+            Session::default()
+                .with_commit_listener(first)
+                .with_commit_listener(Arc::new(PanickingListener))
+                .with_commit_listener(second),
+        );
+
+        write(
+            "memory://panicking",
+            session.clone(),
+            100,
+            WriteMode::Create,
+        )
+        .await;
+        let dataset = write("memory://panicking", session, 100, WriteMode::Append).await;
+        assert_eq!(dataset.version().version, 2);
+        assert_eq!(dataset.count_rows(None).await.unwrap(), 200);
+
+        for rx in [&mut first_rx, &mut second_rx] {
+            assert_eq!(next_event(rx).await.version, 1);
+            assert_eq!(next_event(rx).await.version, 2);
+        }
+    }
+}
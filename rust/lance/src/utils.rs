@@ -3,6 +3,7 @@
 
 //! Various utilities
 
+pub(crate) mod cancellation;
 pub(crate) mod future;
 pub(crate) mod temporal;
 #[cfg(test)]
@@ -0,0 +1,138 @@
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-FileCopyrightText: Copyright The Lance Authors
+
+//! Timeouts and cancellation of a single operation, e.g. a query or a take
+
+use std::future::Future;
+use std::time::Duration;
+
+use datafusion::physical_plan::SendableRecordBatchStream;
+use datafusion_physical_plan::stream::RecordBatchStreamAdapter;
+use futures::StreamExt;
+use lance_core::{Error, Result};
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+/// The timeout and cancellation token of an operation
+///
+/// The operation gets its own token, a child of the caller's, and passes it to its I/O
+/// schedulers.  Cancelling the caller's token fails the operation with
+/// [`Error::Cancelled`].  Running past the timeout cancels the operation's token, so
+/// its I/O stops, and fails it with [`Error::Timeout`].  The caller's token is never
+/// cancelled by a timeout.
+#[derive(Debug, Clone)]
+pub(crate) struct OperationLimits {
+    cancellation: CancellationToken,
+    timeout: Option<(Duration, Instant)>,
+}
+
+impl OperationLimits {
+    /// Limits starting now, or `None` if there is neither a timeout nor a token
+    pub(crate) fn new(
+        timeout: Option<Duration>,
+        cancellation: Option<&CancellationToken>,
+    ) -> Option<Self> {
+        if timeout.is_none() && cancellation.is_none() {
+            return None;
+        }
+        Some(Self {
+            cancellation: cancellation
+                .map_or_else(CancellationToken::new, CancellationToken::child_token),
+            timeout: timeout.map(|timeout| (timeout, Instant::now() + timeout)),
+        })
+    }
+
+    /// The token to cancel the I/O of the operation with
+    pub(crate) fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancellation
+    }
+
+    /// Resolves with the error that ends the operation
+    async fn exceeded(&self) -> Error {
+        let deadline = async {
+            match self.timeout {
+                Some((_, deadline)) => tokio::time::sleep_until(deadline).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::select! {
+            biased;
+            _ = deadline => {
+                self.cancellation.cancel();
+                let (timeout, _) = self.timeout.unwrap();
+                Error::timeout(format!("Operation did not finish within {timeout:?}"))
+            }
+            _ = self.cancellation.cancelled() => Error::cancelled("Operation was cancelled"),
+        }
+    }
+
+    /// Run `fut`, failing it if the limits are exceeded first
+    ///
+    /// Dropping `fut` aborts the reads it has in flight.
+    pub(crate) async fn run<T>(&self, fut: impl Future<Output = Result<T>>) -> Result<T> {
+        tokio::select! {
+            biased;
+            err = self.exceeded() => Err(err),
+            res = fut => res,
+        }
+    }
+
+    /// Fail `stream` once the limits are exceeded instead of ending it early
+    ///
+    /// The stream yields the error and then ends.
+    pub(crate) fn limit_stream(
+        self,
+        stream: SendableRecordBatchStream,
+    ) -> SendableRecordBatchStream {
+        let schema = stream.schema();
+        let stream = futures::stream::unfold(Some(stream), move |stream| {
+            let limits = self.clone();
+            async move {
+                let mut stream = stream?;
+                tokio::select! {
+                    biased;
+                    err = limits.exceeded() => Some((Err(err.into()), None)),
+                    batch = stream.next() => batch.map(|batch| (batch, Some(stream))),
+                }
+            }
+        })
+        .fuse();
+        Box::pin(RecordBatchStreamAdapter::new(schema, stream))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_operation_limits() {
+        assert!(OperationLimits::new(None, None).is_none());
+
+        let limits = OperationLimits::new(Some(Duration::from_millis(10)), None).unwrap();
+        let err = limits
+            .run(std::future::pending::<Result<()>>())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Timeout { .. }), "{err:?}");
+        assert!(limits.cancellation_token().is_cancelled());
+
+        // A timeout does not cancel the caller's token
+        let caller = CancellationToken::new();
+        let limits = OperationLimits::new(Some(Duration::from_millis(10)), Some(&caller)).unwrap();
+        assert!(
+            limits
+                .run(std::future::pending::<Result<()>>())
+                .await
+                .is_err()
+        );
+        assert!(!caller.is_cancelled());
+
+        let limits = OperationLimits::new(None, Some(&caller)).unwrap();
+        assert_eq!(limits.run(async { Ok(7) }).await.unwrap(), 7);
+        caller.cancel();
+        assert!(limits.cancellation_token().is_cancelled());
+        let err = limits.run(async { Ok(7) }).await.unwrap_err();
+        assert!(matches!(err, Error::Cancelled { .. }), "{err:?}");
+    }
+}
@@ -6,7 +6,7 @@ use std::sync::Arc;
 use arrow_array::{RecordBatch, UInt32Array, cast::AsArray};
 use arrow_select::concat::concat_batches;
 use datafusion::datasource::MemTable;
-use datafusion::prelude::SessionContext;
+use datafusion::prelude::{SessionConfig, SessionContext};
 use lance::Dataset;
 use lance::dataset::scanner::ColumnOrdering;
 use lance_datafusion::udf::register_functions;
@@ -74,6 +74,20 @@ async fn test_take(original: &RecordBatch, ds: &Dataset) {
 /// Querying with filter should give same result as filtering original
 /// record batch in DataFusion.
 async fn test_filter(original: &RecordBatch, ds: &Dataset, predicate: &str) {
+    check_filter(create_datafusion_context(), original, ds, predicate).await;
+}
+
+/// Like [`test_filter`], but DataFusion parses number literals as decimals so
+/// the expected result compares decimal columns exactly.
+async fn test_decimal_filter(original: &RecordBatch, ds: &Dataset, predicate: &str) {
+    let config =
+        SessionConfig::new().set_bool("datafusion.sql_parser.parse_float_as_decimal", true);
+    let ctx = SessionContext::new_with_config(config);
+    register_functions(&ctx);
+    check_filter(ctx, original, ds, predicate).await;
+}
+
+async fn check_filter(ctx: SessionContext, original: &RecordBatch, ds: &Dataset, predicate: &str) {
     // Scan with filter and order
     let mut scanner = ds.scan();
     scanner
@@ -85,7 +99,6 @@ async fn test_filter(original: &RecordBatch, ds: &Dataset, predicate: &str) {
         .unwrap();
     let scanned = scanner.try_into_batch().await.unwrap();
 
-    let ctx = create_datafusion_context();
     let table = MemTable::try_new(original.schema(), vec![vec![original.clone()]]).unwrap();
     ctx.register_table("t", Arc::new(table)).unwrap();
 
@@ -94,7 +107,7 @@ async fn test_filter(original: &RecordBatch, ds: &Dataset, predicate: &str) {
     let expected_batches = df.collect().await.unwrap();
     let expected = concat_batches(&original.schema(), &expected_batches).unwrap();
 
-    assert_eq!(&expected, &scanned);
+    assert_eq!(&expected, &scanned, "{predicate}");
 }
 
 // Rebuild a batch using only columns present in the schema (drops _score from FTS results).
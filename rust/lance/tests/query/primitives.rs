@@ -6,7 +6,7 @@ use std::sync::Arc;
 use arrow::datatypes::*;
 use arrow_array::{
     ArrayRef, BinaryArray, BinaryViewArray, Float32Array, Float64Array, Int32Array,
-    LargeBinaryArray, LargeStringArray, RecordBatch, StringArray, StringViewArray,
+    LargeBinaryArray, LargeStringArray, RecordBatch, StringArray, StringViewArray, cast::AsArray,
 };
 use arrow_schema::DataType;
 use lance::Dataset;
@@ -17,7 +17,7 @@ use lance::index::DatasetIndexExt;
 use lance_datagen::{ArrayGeneratorExt, RowCount, array, gen_batch};
 use lance_index::IndexType;
 
-use super::{test_filter, test_scan, test_take};
+use super::{test_decimal_filter, test_filter, test_scan, test_take};
 use crate::utils::DatasetTestCases;
 
 #[tokio::test]
@@ -443,6 +443,70 @@ async fn test_query_decimal(#[case] data_type: DataType) {
         .await
 }
 
+#[tokio::test]
+#[rstest::rstest]
+#[case::decimal128(DataType::Decimal128(38, 10))]
+#[case::decimal256(DataType::Decimal256(76, 20))]
+async fn test_query_decimal_full_precision(#[case] data_type: DataType) {
+    let (DataType::Decimal128(precision, scale) | DataType::Decimal256(precision, scale)) =
+        data_type
+    else {
+        unreachable!()
+    };
+    // The largest value of the type, followed by values around the scale boundary
+    let max = format!(
+        "{}.{}",
+        "9".repeat((precision as i8 - scale) as usize),
+        "9".repeat(scale as usize)
+    );
+    let values = [
+        Some(max.clone()),
+        Some(format!("-{max}")),
+        Some("0".to_string()),
+        Some("0.5".to_string()),
+        Some("0.4999999999".to_string()),
+        Some("0.5000000001".to_string()),
+        Some("-0.5".to_string()),
+        Some("12345.6789".to_string()),
+        Some("-12345.6789".to_string()),
+        None,
+    ];
+    let values = StringArray::from_iter(values.into_iter().cycle().take(60));
+    let values = arrow::compute::cast(&values, &data_type).unwrap();
+    assert_eq!(
+        arrow::compute::cast(&values, &DataType::Utf8)
+            .unwrap()
+            .as_string::<i32>()
+            .value(0),
+        max
+    );
+    let batch = RecordBatch::try_from_iter(vec![
+        (
+            "id",
+            Arc::new(Int32Array::from_iter_values(0..60)) as ArrayRef,
+        ),
+        ("value", values),
+    ])
+    .unwrap();
+
+    DatasetTestCases::from_data(batch)
+        .with_index_types(
+            "value",
+            [None, Some(IndexType::Bitmap), Some(IndexType::BTree)],
+        )
+        .run(|ds: Dataset, original: RecordBatch| async move {
+            test_scan(&original, &ds).await;
+            test_take(&original, &ds).await;
+            test_decimal_filter(&original, &ds, "value = 0.5").await;
+            test_decimal_filter(&original, &ds, "value = -12345.6789").await;
+            test_decimal_filter(&original, &ds, "value in (0.5, 12345.6789)").await;
+            test_decimal_filter(&original, &ds, "value > 0.4999999999").await;
+            test_decimal_filter(&original, &ds, "value < 0.5000000001").await;
+            test_decimal_filter(&original, &ds, "value between -0.5 and 0.5").await;
+        })
+        .await
+}
+
 /// Regression test: filtered scan panics after compaction with SRID when a
 /// RangeWithBitmap segment appears after a Range segment in a fragment's
 /// RowIdSequence. The bitmap iterator was advanced using a global offset